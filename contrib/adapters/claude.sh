@@ -73,8 +73,9 @@ SOCK_ARGS=()
 if [ -n "$SOCK" ]; then
     SOCK_ARGS=(--socket "$SOCK")
 fi
-KJ_RESPONSE=$(echo "$KJ_INPUT" | "$KJ_MCP" hook "${SOCK_ARGS[@]}" 2>/dev/null) || true
-KJ_EXIT=${PIPESTATUS[1]:-0}
+# pipefail makes the pipeline's status the hook client's own exit code.
+KJ_EXIT=0
+KJ_RESPONSE=$(echo "$KJ_INPUT" | "$KJ_MCP" hook "${SOCK_ARGS[@]}" 2>/dev/null) || KJ_EXIT=$?
 
 # If kaijutsu denied the action, relay to Claude
 if [ "$KJ_EXIT" -eq 2 ] 2>/dev/null; then
@@ -83,6 +84,20 @@ if [ "$KJ_EXIT" -eq 2 ] 2>/dev/null; then
     exit 2
 fi
 
+# Kernel hook policy said "ask": defer to Claude's own permission prompt,
+# carrying the reason (and any drift context) along.
+DECISION=$(echo "$KJ_RESPONSE" | jq -r '.block // empty' 2>/dev/null || true)
+if [ "$DECISION" = "ask" ] && [ "$EVENT_NAME" = "PreToolUse" ]; then
+    echo "$KJ_RESPONSE" | jq '{
+        hookSpecificOutput: ({
+            hookEventName: "PreToolUse",
+            permissionDecision: "ask",
+            permissionDecisionReason: (.reason // "kaijutsu hook policy")
+        } + (if .context then {additionalContext: .context} else {} end))
+    }'
+    exit 0
+fi
+
 # Inject drift context if present in the response
 if [ -n "$KJ_RESPONSE" ]; then
     CONTEXT=$(echo "$KJ_RESPONSE" | jq -r '.context // empty' 2>/dev/null || true)
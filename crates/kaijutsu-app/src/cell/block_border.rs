@@ -12,6 +12,7 @@ use crate::connection::RpcConnectionState;
 use crate::text::TextMetrics;
 use crate::ui::drift::DriftState;
 use crate::ui::theme::Theme;
+use crate::view::block_locks::BlockLocks;
 use crate::view::document::DocumentCache;
 use crate::view::{
     BlockCell, BlockCellContainer, BlockKind, BlockSnapshot, CellEditor, DriftKind, EditorEntities,
@@ -144,10 +145,12 @@ pub fn determine_block_border_style(
     conn_state: Res<RpcConnectionState>,
     drift_state: Res<DriftState>,
     doc_cache: Res<DocumentCache>,
+    locks: Res<BlockLocks>,
     mut last_gen: Local<u64>,
 ) {
     // Border styles only change when blocks change (add/remove/line count/status)
-    if layout_gen.0 == *last_gen {
+    // or a block is locked or released.
+    if layout_gen.0 == *last_gen && !locks.is_changed() {
         return;
     }
     *last_gen = layout_gen.0;
@@ -183,6 +186,8 @@ pub fn determine_block_border_style(
         .filter_map(|b| b.tool_call_id)
         .collect();
 
+    let me = conn_state.identity.as_ref().map(|i| i.principal_id);
+
     // Build context for labels
     let ctx = BorderContext {
         username: conn_state
@@ -218,13 +223,22 @@ pub fn determine_block_border_style(
         }
 
         let has_result_below = has_result.contains(&block.id);
-        let new_style = compute_border_style(
+        let mut new_style = compute_border_style(
             block,
             &theme,
             &ctx,
             has_result_below,
             text_metrics.cell_font_size,
         );
+        // Someone else's lock: tint the border and name the holder.
+        if let Some(lock) = locks.get(&block.id).filter(|l| Some(l.holder) != me) {
+            new_style = Some(locked_border_style(
+                new_style,
+                lock,
+                &theme,
+                text_metrics.cell_font_size,
+            ));
+        }
 
         match (&new_style, existing_style) {
             (Some(style), Some(existing)) if style == existing => {
@@ -276,14 +290,20 @@ fn compute_border_style(
                 ),
             };
 
-            // Top label: "COMMAND @username" for shell, "TOOL CALL model" for others
+            // Top label: "COMMAND @username" for shell (plus a cwd/env chip
+            // when the block records how it ran), "TOOL CALL model" for others
             let top_label = match block.tool_kind {
                 Some(ToolKind::Shell) => {
-                    if ctx.username.is_empty() {
+                    let mut label = if ctx.username.is_empty() {
                         "COMMAND".to_string()
                     } else {
                         format!("COMMAND @{}", ctx.username)
+                    };
+                    if let Some(command) = &block.shell_command {
+                        label.push_str(" · ");
+                        label.push_str(&command.chip());
                     }
+                    label
                 }
                 _ => {
                     if ctx.model.is_empty() {
@@ -499,3 +519,33 @@ fn compute_border_style(
     result
 }
 
+/// `style` marked as held by another principal's lock: a full border in the
+/// theme's warning color, labelled `✎ amy editing` underneath. Blocks that
+/// draw no border of their own get one.
+fn locked_border_style(
+    style: Option<BlockBorderStyle>,
+    lock: &kaijutsu_types::BlockLock,
+    theme: &Theme,
+    font_size: f32,
+) -> BlockBorderStyle {
+    let base = theme.block_border_padding * font_size;
+    let mut style = style.unwrap_or_else(|| BlockBorderStyle {
+        kind: BorderKind::Full,
+        color: theme.warning,
+        thickness: theme.block_border_thickness,
+        corner_radius: theme.block_border_corner_radius,
+        padding: BorderPadding {
+            top: base * 0.75,
+            bottom: base,
+            left: base,
+            right: base,
+        },
+        animation: BorderAnimation::None,
+        top_label: None,
+        bottom_label: None,
+    });
+    style.kind = BorderKind::Full;
+    style.color = theme.warning.with_alpha(0.7);
+    style.bottom_label = Some(format!("✎ {} editing", lock.holder_label()));
+    style
+}
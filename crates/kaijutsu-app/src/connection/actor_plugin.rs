@@ -62,6 +62,9 @@ pub struct RpcConnectionState {
     /// Survives across Reconnecting events so the dock can surface the
     /// underlying cause (e.g. SSH agent missing) instead of just spinning.
     pub last_error: Option<String>,
+    /// The actor's latest status, backoff metadata included — what the
+    /// `:connection` panel renders. `None` until the first status arrives.
+    pub status: Option<kaijutsu_client::ConnectionStatus>,
 }
 
 /// Channel for async tasks to send results back to Bevy systems.
@@ -133,6 +136,31 @@ pub enum RpcResultMessage {
     DriftQueueReceived {
         staged: Vec<kaijutsu_client::StagedDriftInfo>,
     },
+    /// Inbox unread count + newest item (from `ui::inbox` polling).
+    InboxReceived { page: kaijutsu_client::InboxPage },
+    /// The principal's preferences (from `ui::prefs`, on connect).
+    PreferencesReceived { prefs: kaijutsu_types::Preferences },
+    /// Consent log entries past `after_seq`, with verification (from
+    /// `ui::consent` polling).
+    ConsentLogReceived {
+        after_seq: u64,
+        page: kaijutsu_client::ConsentLogPage,
+    },
+    /// Tool calls waiting on this principal's approval (from `ui::consent`
+    /// prompt polling).
+    ConsentPromptsReceived {
+        prompts: Vec<kaijutsu_types::ConsentPrompt>,
+    },
+    /// The active context's event timeline (from `ui::timeline_strip`).
+    ContextTimelineReceived {
+        timeline: kaijutsu_types::ContextTimeline,
+    },
+    /// Prompt completions (from `ui::completion`). `request` matches
+    /// `CompletionState`'s request counter so stale replies are dropped.
+    CompletionsReceived {
+        request: u64,
+        completions: Vec<kaijutsu_client::Completion>,
+    },
     /// Semantic clusters received (time-well band-2 poll). Drained into
     /// `TimeWellState.clusters` to drive the haystack's cluster-grouped angle.
     ClustersReceived {
@@ -604,6 +632,22 @@ fn poll_bootstrap_results(
                                 .detach();
                         }
 
+                        // 1d. Subscribe to per-context activity metrics — the
+                        // sustained floor under the time well's card glow
+                        // (time_well::activity). Same best-effort terms as 1c.
+                        {
+                            let h2 = h.clone();
+                            bevy::tasks::IoTaskPool::get()
+                                .spawn(async move {
+                                    if let Err(e) = h2.subscribe_activity(0).await {
+                                        log::warn!(
+                                            "Context activity subscribe failed (cards glow on chatter only): {e}"
+                                        );
+                                    }
+                                })
+                                .detach();
+                        }
+
                         // 2. If we joined a specific context, fetch its state.
                         // Invariant: SpawnActor with context_id=Some is only issued
                         // after the kernel is attached (see sync.rs / create_dialog.rs),
@@ -830,6 +874,7 @@ fn update_connection_state(
     time: Res<Time>,
 ) {
     for ConnectionStatusMessage(status) in status_events.read() {
+        state.status = Some(status.clone());
         match status {
             kaijutsu_client::ConnectionStatus::Idle => {
                 state.connected = false;
@@ -847,7 +892,7 @@ fn update_connection_state(
                 state.context_id = *context_id;
                 state.last_error = None;
             }
-            kaijutsu_client::ConnectionStatus::Connecting { attempt } => {
+            kaijutsu_client::ConnectionStatus::Connecting { attempt, .. } => {
                 state.connected = false;
                 state.reconnect_attempt = *attempt;
                 // Intentionally leave last_error in place — the cause from
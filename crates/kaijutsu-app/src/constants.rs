@@ -43,7 +43,6 @@ impl ZLayer {
     #[allow(dead_code)]
     pub const DROPDOWN: i32 = 200;
     /// Toast notifications
-    #[allow(dead_code)]
     pub const TOAST: i32 = 250;
 }
 
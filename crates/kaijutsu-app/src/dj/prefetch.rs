@@ -162,8 +162,9 @@ const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
 /// the connection so it redials; a per-object failure (NotFound /
 /// HashMismatch) leaves the healthy transport in place. Logs the happy path
 /// too (`docs/issues.md` "Audio sink follow-ups") — hit/miss and timing, so a
-/// live debugging session has something to read instead of silence.
-async fn resolve_with_lazy_connect(
+/// live debugging session has something to read instead of silence. Also
+/// backs inline image blocks (`view::block_image`), over their own slot.
+pub(crate) async fn resolve_with_lazy_connect(
     slot: &AsyncMutex<Option<Arc<CasResolver<SftpClient>>>>,
     hash: &ContentHash,
     config: SshConfig,
@@ -128,6 +128,16 @@ pub enum Action {
     // ========================================================================
     /// x (in Navigation) — toggle block excluded from conversation
     ToggleBlockExcluded,
+    /// Shift+K (in Navigation) — move the focused block before its previous sibling
+    MoveBlockUp,
+    /// Shift+J (in Navigation) — move the focused block after its next sibling
+    MoveBlockDown,
+    /// y (in Navigation) — copy the focused block's permalink
+    /// (`kaijutsu://kernel/context/block-key`) to the clipboard
+    YankPermalink,
+    /// t (in Navigation) — toggle the translated view of text blocks
+    /// (into the seat's `ui.language`)
+    ToggleTranslation,
 
     /// q (in Navigation) or platform quit
     Quit,
@@ -135,6 +145,9 @@ pub enum Action {
     Screenshot,
     /// F1 — toggle debug overlay
     DebugToggle,
+    /// Alt+N — dismiss the newest toast; with none left, close the
+    /// `:notifications` panel
+    DismissToast,
 
     // ========================================================================
     // Scene navigation (RoomNav / WellZoomed / PatchBayZoomed / StationZoomed)
@@ -201,6 +214,9 @@ pub enum Action {
     PromptContextRename,
     /// `Ctrl+A '` — same pattern, `kj context switch ` (switch-by-prompt).
     PromptContextSwitch,
+    /// `Ctrl+A e` — toggle inline edit-diff highlights (fading tints over
+    /// text another writer changed in a settled block).
+    ToggleEditDiff,
 
     // ========================================================================
     // Context Interrupt (Ctrl+C in TextInput/Navigation)
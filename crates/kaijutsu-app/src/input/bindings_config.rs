@@ -366,6 +366,10 @@ fn action_to_str(a: &Action) -> String {
         Action::SummonChat => "SummonChat".into(),
         Action::ToggleSurface => "ToggleSurface".into(),
         Action::ToggleBlockExcluded => "ToggleBlockExcluded".into(),
+        Action::MoveBlockUp => "MoveBlockUp".into(),
+        Action::MoveBlockDown => "MoveBlockDown".into(),
+        Action::YankPermalink => "YankPermalink".into(),
+        Action::ToggleTranslation => "ToggleTranslation".into(),
         Action::PopLevel => "PopLevel".into(),
         Action::Activate => "Activate".into(),
         Action::FocusNextBlock => "FocusNextBlock".into(),
@@ -405,6 +409,7 @@ fn action_to_str(a: &Action) -> String {
         Action::Quit => "Quit".into(),
         Action::Screenshot => "Screenshot".into(),
         Action::DebugToggle => "DebugToggle".into(),
+        Action::DismissToast => "DismissToast".into(),
         Action::InterruptContext { immediate } => {
             format!("InterruptContext:{immediate}")
         }
@@ -426,6 +431,7 @@ fn action_to_str(a: &Action) -> String {
         Action::CloseAndDemoteContext => "CloseAndDemoteContext".into(),
         Action::GoToWell => "GoToWell".into(),
         Action::DetachToConversation => "DetachToConversation".into(),
+        Action::ToggleEditDiff => "ToggleEditDiff".into(),
         Action::SendLiteralPrefix => "SendLiteralPrefix".into(),
         Action::PromptContextRename => "PromptContextRename".into(),
         Action::PromptContextSwitch => "PromptContextSwitch".into(),
@@ -446,6 +452,10 @@ fn parse_action(s: &str) -> Result<Action, String> {
         "SummonChat" => Ok(Action::SummonChat),
         "ToggleSurface" => Ok(Action::ToggleSurface),
         "ToggleBlockExcluded" => Ok(Action::ToggleBlockExcluded),
+        "MoveBlockUp" => Ok(Action::MoveBlockUp),
+        "MoveBlockDown" => Ok(Action::MoveBlockDown),
+        "YankPermalink" => Ok(Action::YankPermalink),
+        "ToggleTranslation" => Ok(Action::ToggleTranslation),
         "PopLevel" => Ok(Action::PopLevel),
         // Pre-rename alias (bindings.toml written before 2026-07-16).
         "Unfocus" => Ok(Action::PopLevel),
@@ -486,6 +496,7 @@ fn parse_action(s: &str) -> Result<Action, String> {
         "Quit" => Ok(Action::Quit),
         "Screenshot" => Ok(Action::Screenshot),
         "DebugToggle" => Ok(Action::DebugToggle),
+        "DismissToast" => Ok(Action::DismissToast),
         "StepNext" => Ok(Action::StepNext),
         "StepPrev" => Ok(Action::StepPrev),
         "LevelUp" => Ok(Action::LevelUp),
@@ -501,6 +512,7 @@ fn parse_action(s: &str) -> Result<Action, String> {
         "CloseAndDemoteContext" => Ok(Action::CloseAndDemoteContext),
         "GoToWell" => Ok(Action::GoToWell),
         "DetachToConversation" => Ok(Action::DetachToConversation),
+        "ToggleEditDiff" => Ok(Action::ToggleEditDiff),
         "SendLiteralPrefix" => Ok(Action::SendLiteralPrefix),
         "PromptContextRename" => Ok(Action::PromptContextRename),
         "PromptContextSwitch" => Ok(Action::PromptContextSwitch),
@@ -683,6 +695,10 @@ mod tests {
         "SummonChat",
         "ToggleSurface",
         "ToggleBlockExcluded",
+        "MoveBlockUp",
+        "MoveBlockDown",
+        "YankPermalink",
+        "ToggleTranslation",
         "PopLevel",
         "Activate",
         "FocusNextBlock",
@@ -721,6 +737,7 @@ mod tests {
         "Quit",
         "Screenshot",
         "DebugToggle",
+        "DismissToast",
         "InterruptContext",
         "StepNext",
         "StepPrev",
@@ -740,6 +757,7 @@ mod tests {
         "CloseAndDemoteContext",
         "GoToWell",
         "DetachToConversation",
+        "ToggleEditDiff",
         "SendLiteralPrefix",
         "PromptContextRename",
         "PromptContextSwitch",
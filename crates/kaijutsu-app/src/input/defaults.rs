@@ -30,6 +30,13 @@ pub fn default_bindings() -> Vec<Binding> {
         Action::Screenshot,
         "Save screenshot",
     ));
+    b.push(Binding::key_mod(
+        KeyCode::KeyN,
+        Modifiers::ALT,
+        InputContext::Global,
+        Action::DismissToast,
+        "Dismiss notification",
+    ));
 
     // Tiling: Alt+hjkl pane focus
     b.push(Binding::key_mod(
@@ -150,6 +157,32 @@ pub fn default_bindings() -> Vec<Binding> {
         Action::ToggleBlockExcluded,
         "Toggle block excluded",
     ));
+    b.push(Binding::key_mod(
+        KeyCode::KeyK,
+        Modifiers::SHIFT,
+        InputContext::Navigation,
+        Action::MoveBlockUp,
+        "Move block up",
+    ));
+    b.push(Binding::key_mod(
+        KeyCode::KeyJ,
+        Modifiers::SHIFT,
+        InputContext::Navigation,
+        Action::MoveBlockDown,
+        "Move block down",
+    ));
+    b.push(Binding::key(
+        KeyCode::KeyY,
+        InputContext::Navigation,
+        Action::YankPermalink,
+        "Yank block permalink",
+    ));
+    b.push(Binding::key(
+        KeyCode::KeyT,
+        InputContext::Navigation,
+        Action::ToggleTranslation,
+        "Toggle translated view",
+    ));
     b.push(Binding::key(
         KeyCode::Tab,
         InputContext::Navigation,
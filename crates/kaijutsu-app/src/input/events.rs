@@ -32,6 +32,57 @@ impl ActionFired {
     }
 }
 
+/// Request to reorder a block among its siblings (`reorderBlock` RPC).
+///
+/// The seam for drag-to-reorder: keyboard moves (Shift+J/K) write it today,
+/// and a pointer-drag system only has to resolve the drop target to a
+/// sibling and write the same message. `after_id: None` lands the block
+/// ahead of its first sibling.
+#[derive(Message, Clone, Debug)]
+pub struct BlockReorderRequested {
+    pub block_id: kaijutsu_crdt::BlockId,
+    pub after_id: Option<kaijutsu_crdt::BlockId>,
+}
+
+/// Request to split the focused conversation pane and show a context in the
+/// new pane — `:vsplit <context>` / `:split <context>` submitted at the shell
+/// surface (see [`crate::ui::tiling::parse_split_command`]).
+#[derive(Message, Clone, Debug)]
+pub struct SplitPaneRequested {
+    pub direction: crate::ui::tiling::SplitDirection,
+    /// Context id, id prefix, or label; empty takes the next MRU context.
+    pub context: String,
+}
+
+/// Request to focus a block in the main conversation and scroll it into
+/// view — `:goto <target>` submitted at the shell surface (see
+/// [`super::systems::parse_goto_command`]).
+#[derive(Message, Clone, Debug)]
+pub struct GotoBlockRequested {
+    /// Block label, full key, or unambiguous id abbreviation.
+    pub target: String,
+}
+
+/// Request to open a block permalink: connect-wait, switch to (joining) its
+/// context, and go to the block — `:goto <permalink>` or the
+/// `open_permalink` peer action (see [`super::permalink`]).
+#[derive(Message, Clone, Debug)]
+pub struct PermalinkOpenRequested {
+    pub link: kaijutsu_types::Permalink,
+}
+
+/// Request to toggle the notification history panel — `:notifications`
+/// submitted at the shell surface (see
+/// [`crate::ui::toast::parse_notifications_command`]).
+#[derive(Message, Clone, Debug)]
+pub struct NotificationsPanelRequested;
+
+/// A connection command submitted at the shell surface — `:connection`,
+/// `:retry` or `:connect <host>[:<port>]` (see
+/// [`crate::ui::connection_panel::parse_connection_command`]).
+#[derive(Message, Clone, Debug)]
+pub struct ConnectionCommandRequested(pub crate::ui::connection_panel::ConnectionCommand);
+
 /// Raw text that should be inserted into the focused text field.
 ///
 /// Emitted by the dispatcher when input occurs in TextInput context
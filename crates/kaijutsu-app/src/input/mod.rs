@@ -39,6 +39,7 @@ pub mod interrupt;
 pub mod events;
 pub mod focus;
 pub mod map;
+pub mod permalink;
 pub mod bindings_config;
 pub mod prefix;
 pub mod scroll_config;
@@ -100,7 +101,13 @@ impl Plugin for InputPlugin {
         app.add_message::<events::ActionFired>()
             .add_message::<events::TextInputReceived>()
             .add_message::<events::GrabbedKey>()
-            .add_message::<events::LiteralPrefix>();
+            .add_message::<events::LiteralPrefix>()
+            .add_message::<events::BlockReorderRequested>()
+            .add_message::<events::SplitPaneRequested>()
+            .add_message::<events::GotoBlockRequested>()
+            .add_message::<events::PermalinkOpenRequested>()
+            .add_message::<events::NotificationsPanelRequested>()
+            .add_message::<events::ConnectionCommandRequested>();
 
         // System clipboard (graceful fallback if unavailable)
         match arboard::Clipboard::new() {
@@ -119,6 +126,7 @@ impl Plugin for InputPlugin {
             .init_resource::<context::ActiveInputContexts>()
             .init_resource::<context::KeyboardGrab>()
             .init_resource::<prefix::PrefixState>()
+            .init_resource::<permalink::PendingPermalink>()
             .init_resource::<events::AnalogInput>()
             .init_resource::<interrupt::InterruptState>()
             .init_resource::<scroll_config::ScrollConfig>()
@@ -199,10 +207,14 @@ impl Plugin for InputPlugin {
                 systems::handle_screenshot,
                 // Tiling pane management (global)
                 systems::handle_tiling,
+                systems::handle_split_requests,
                 // Navigation context
                 systems::handle_navigate_blocks.run_if(focus::in_conversation),
                 systems::handle_collapse_toggle.run_if(focus::in_conversation),
                 systems::handle_toggle_block_excluded.run_if(focus::in_conversation),
+                systems::handle_move_block.run_if(focus::in_conversation),
+                systems::handle_block_reorder_requests,
+                systems::handle_goto_requests,
                 // Scrolling (multi-context)
                 systems::handle_scroll.run_if(focus::scroll_context_active),
                 // Text input context
@@ -211,6 +223,20 @@ impl Plugin for InputPlugin {
                 .in_set(InputPhase::Handle),
         );
 
+        // Block permalinks: yank the focused one; walk an opened one to its
+        // block (hands off to handle_goto_requests the frame it lands).
+        app.add_systems(
+            Update,
+            (
+                permalink::handle_yank_permalink.run_if(focus::in_conversation),
+                permalink::handle_permalink_open_requests,
+                permalink::drive_pending_permalink
+                    .after(permalink::handle_permalink_open_requests)
+                    .before(systems::handle_goto_requests),
+            )
+                .in_set(InputPhase::Handle),
+        );
+
         // Cleanup phase: defensive logic
         app.add_systems(
             Update,
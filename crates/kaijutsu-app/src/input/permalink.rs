@@ -0,0 +1,177 @@
+//! Block permalinks (`kaijutsu://kernel/context/block-key`, see
+//! [`kaijutsu_types::Permalink`]).
+//!
+//! `y` in Navigation yanks the focused block's permalink to the clipboard.
+//! Opening one — the `kaijutsu <link>` argument, `:goto <link>`, or the
+//! `open_permalink` peer action — parks it in [`PendingPermalink`] and walks
+//! it to the block over as many frames as it takes: wait for the connection,
+//! check the kernel, switch to the context (joining it on a cache miss, via
+//! the ordinary [`ContextSwitchRequested`] path), wait for the block to land
+//! in the conversation, then hand off to `:goto` to focus and scroll.
+//!
+//! [`ContextSwitchRequested`]: crate::cell::ContextSwitchRequested
+
+use bevy::prelude::*;
+use kaijutsu_types::Permalink;
+
+use super::action::Action;
+use super::events::{ActionFired, GotoBlockRequested, PermalinkOpenRequested};
+use crate::cell::{EditorEntities, FocusTarget, MainCell};
+use crate::ui::toast::{ToastSeverity, Toasts};
+
+/// Seconds an opened permalink waits for its block to show up before it is
+/// given up on (the block may be deleted, or the context unreachable).
+const OPEN_TIMEOUT_SECS: f64 = 30.0;
+
+/// A permalink being opened.
+#[derive(Resource, Default)]
+pub struct PendingPermalink(Option<PendingOpen>);
+
+struct PendingOpen {
+    link: Permalink,
+    /// Whether the context switch has been requested.
+    switched: bool,
+    /// `Time::elapsed_secs_f64()` once connected; `None` before.
+    started: Option<f64>,
+}
+
+impl PendingPermalink {
+    /// Start opening `link` (replacing any link still in flight).
+    pub fn open(link: Permalink) -> Self {
+        Self(Some(PendingOpen {
+            link,
+            switched: false,
+            started: None,
+        }))
+    }
+}
+
+/// Handle `YankPermalink` — copy the focused block's permalink.
+pub fn handle_yank_permalink(
+    mut actions: MessageReader<ActionFired>,
+    focus: Res<FocusTarget>,
+    conn_state: Res<crate::connection::RpcConnectionState>,
+    mut clipboard: Option<ResMut<super::SystemClipboard>>,
+    mut toasts: ResMut<Toasts>,
+    time: Res<Time>,
+) {
+    for ActionFired { action, .. } in actions.read() {
+        if !matches!(action, Action::YankPermalink) {
+            continue;
+        }
+        let (Some(block_id), Some(kernel_id)) = (focus.block_id, conn_state.kernel_id) else {
+            continue;
+        };
+        let link = Permalink::new(kernel_id, block_id).to_string();
+        let Some(ref mut clip) = clipboard else {
+            warn!("Yank permalink: no clipboard; {link}");
+            continue;
+        };
+        match clip.0.set_text(link.clone()) {
+            Ok(()) => {
+                info!("Yanked permalink {link}");
+                toasts.push(
+                    ToastSeverity::Info,
+                    "Permalink copied",
+                    link,
+                    time.elapsed_secs_f64(),
+                );
+            }
+            Err(e) => warn!("Yank permalink failed: {e}"),
+        }
+    }
+}
+
+/// Handle [`PermalinkOpenRequested`]: start opening the link.
+pub fn handle_permalink_open_requests(
+    mut requests: MessageReader<PermalinkOpenRequested>,
+    mut pending: ResMut<PendingPermalink>,
+) {
+    if let Some(req) = requests.read().last() {
+        *pending = PendingPermalink::open(req.link);
+    }
+}
+
+/// Advance the pending permalink one step: connected → right kernel →
+/// context active → block present → goto.
+pub fn drive_pending_permalink(
+    mut pending: ResMut<PendingPermalink>,
+    conn_state: Res<crate::connection::RpcConnectionState>,
+    doc_cache: Res<crate::cell::DocumentCache>,
+    entities: Res<EditorEntities>,
+    geometries: Query<&crate::view::geometry::ConversationGeometry, With<MainCell>>,
+    mut switch_writer: MessageWriter<crate::cell::ContextSwitchRequested>,
+    mut goto_writer: MessageWriter<GotoBlockRequested>,
+    mut toasts: ResMut<Toasts>,
+    time: Res<Time>,
+) {
+    let Some(open) = pending.0.as_mut() else {
+        return;
+    };
+    let Some(kernel_id) = conn_state.kernel_id else {
+        return;
+    };
+    let now = time.elapsed_secs_f64();
+    let link = open.link;
+
+    if link.kernel_id != kernel_id {
+        warn!(
+            "Permalink {link} is for kernel {}, connected to {}",
+            link.kernel_id.short(),
+            kernel_id.short()
+        );
+        toasts.push(
+            ToastSeverity::Warning,
+            "Permalink is for another kernel",
+            format!(
+                "link: {}, connected: {}",
+                link.kernel_id.short(),
+                kernel_id.short()
+            ),
+            now,
+        );
+        pending.0 = None;
+        return;
+    }
+    let started = *open.started.get_or_insert(now);
+
+    let context_id = link.context_id();
+    if doc_cache.active_id() != Some(context_id) {
+        if !open.switched {
+            switch_writer.write(crate::cell::ContextSwitchRequested { context_id });
+            open.switched = true;
+        }
+    } else {
+        use crate::view::geometry::RowKey;
+        let present = entities
+            .main_cell
+            .and_then(|main| geometries.get(main).ok())
+            .is_some_and(|geom| {
+                geom.rows()
+                    .iter()
+                    .any(|row| matches!(row.key, RowKey::Block(id) if id == link.block_id))
+            });
+        if present {
+            goto_writer.write(GotoBlockRequested {
+                target: link.block_id.to_key(),
+            });
+            pending.0 = None;
+            return;
+        }
+    }
+
+    if now - started > OPEN_TIMEOUT_SECS {
+        warn!("Permalink {link}: block not found after {OPEN_TIMEOUT_SECS}s");
+        toasts.push(
+            ToastSeverity::Warning,
+            "Permalink not found",
+            format!(
+                "block {} did not appear in context {}",
+                link.block_id.to_key(),
+                context_id.short()
+            ),
+            now,
+        );
+        pending.0 = None;
+    }
+}
@@ -105,6 +105,8 @@ pub fn resolve_chord(key: KeyCode, ctrl: bool, shift: bool) -> Option<Action> {
         KeyCode::KeyP => Some(Action::ActiveSeatStep(-1)),
         // d — detach to the conversation view from any scene/editor.
         KeyCode::KeyD => Some(Action::DetachToConversation),
+        // e — toggle inline edit-diff highlights on settled blocks.
+        KeyCode::KeyE => Some(Action::ToggleEditDiff),
         // Esc cancels the pending prefix quietly.
         KeyCode::Escape => None,
         _ => None,
@@ -165,6 +167,11 @@ mod tests {
         );
     }
 
+    #[test]
+    fn edit_diff_toggle_chord() {
+        assert_eq!(resolve_chord(KeyCode::KeyE, false, false), Some(Action::ToggleEditDiff));
+    }
+
     #[test]
     fn unbound_is_none() {
         assert_eq!(resolve_chord(KeyCode::KeyX, false, false), None);
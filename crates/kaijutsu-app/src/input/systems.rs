@@ -520,6 +520,96 @@ pub fn handle_navigate_blocks(
     debug!("Block focus: {:?} (index {})", new_id, new_idx);
 }
 
+/// The REPL session a focused block belongs to: the session block itself,
+/// or the session an output block hangs off.
+fn repl_session_for(block: &kaijutsu_crdt::BlockSnapshot) -> Option<kaijutsu_crdt::BlockId> {
+    (block.kind == kaijutsu_crdt::BlockKind::Repl).then(|| block.parent_id.unwrap_or(block.id))
+}
+
+/// Parse `:goto <target>` typed at the shell surface. The target is a block
+/// label or block id reference; anything else goes to kaish as usual.
+pub fn parse_goto_command(text: &str) -> Option<String> {
+    let rest = text.trim().strip_prefix(':')?;
+    let mut words = rest.split_whitespace();
+    if !matches!(words.next()?, "goto" | "go") {
+        return None;
+    }
+    let target = words.next()?.to_string();
+    if words.next().is_some() {
+        return None;
+    }
+    Some(target)
+}
+
+/// Resolve a `:goto` target against the conversation's blocks: a label
+/// first, then a full key or id abbreviation (see
+/// [`kaijutsu_types::resolve_block_ref`]).
+fn resolve_goto_target(
+    blocks: &[kaijutsu_crdt::BlockSnapshot],
+    target: &str,
+) -> Result<kaijutsu_crdt::BlockId, kaijutsu_types::PrefixError> {
+    let labels = blocks
+        .iter()
+        .filter_map(|b| b.label.as_deref().map(|label| (label, b.id)));
+    kaijutsu_types::resolve_block_ref(blocks.iter().map(|b| b.id), labels, target)
+}
+
+/// Handle [`GotoBlockRequested`]: focus the named block in the main
+/// conversation and scroll it into view, like block navigation does.
+///
+/// [`GotoBlockRequested`]: super::events::GotoBlockRequested
+pub fn handle_goto_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<super::events::GotoBlockRequested>,
+    entities: Res<EditorEntities>,
+    cells: Query<&CellEditor>,
+    geometries: Query<&crate::view::geometry::ConversationGeometry, With<MainCell>>,
+    containers: Query<&BlockCellContainer>,
+    mut focus: ResMut<FocusTarget>,
+    mut scroll_state: ResMut<ConversationScrollState>,
+    focused_markers: Query<Entity, With<FocusedBlockCell>>,
+) {
+    for req in requests.read() {
+        let Some(main_ent) = entities.main_cell else {
+            continue;
+        };
+        let Ok(editor) = cells.get(main_ent) else {
+            continue;
+        };
+        let block_id = match resolve_goto_target(&editor.blocks(), &req.target) {
+            Ok(id) => id,
+            Err(e) => {
+                warn!("Goto: cannot resolve '{}': {e}", req.target);
+                continue;
+            }
+        };
+        use crate::view::geometry::RowKey;
+        let Some((row_y, row_h)) = geometries.get(main_ent).ok().and_then(|geom| {
+            geom.rows().iter().find_map(|row| match row.key {
+                RowKey::Block(id) if id == block_id => Some((row.y_offset, row.height)),
+                _ => None,
+            })
+        }) else {
+            warn!("Goto: '{}' is not in this conversation", req.target);
+            continue;
+        };
+
+        focus.focus_block(block_id);
+        for entity in focused_markers.iter() {
+            commands.entity(entity).remove::<FocusedBlockCell>();
+        }
+        if let Some(entity) = containers
+            .get(main_ent)
+            .ok()
+            .and_then(|c| c.get_entity(&block_id))
+        {
+            commands.entity(entity).insert(FocusedBlockCell);
+        }
+        scroll_to_rect_visible(&mut scroll_state, row_y, row_h);
+        debug!("Goto: '{}' → {:?}", req.target, block_id);
+    }
+}
+
 /// Apply the `FocusedBlockCell` marker once the focused block's entity
 /// exists. Focus nav can land on a block that is outside the entity band
 /// (despawned); the nav scrolls toward it, the band spawns it a frame or
@@ -726,6 +816,92 @@ pub fn handle_toggle_block_excluded(
     }
 }
 
+/// Handle MoveBlockUp / MoveBlockDown — Shift+K / Shift+J in Navigation.
+///
+/// Resolves the focused block's sibling neighbour and writes a
+/// [`BlockReorderRequested`](super::events::BlockReorderRequested); the
+/// reorder itself happens in [`handle_block_reorder_requests`].
+pub fn handle_move_block(
+    mut actions: MessageReader<ActionFired>,
+    focus: Res<FocusTarget>,
+    cells: Query<&CellEditor>,
+    entities: Res<EditorEntities>,
+    mut reorders: MessageWriter<super::events::BlockReorderRequested>,
+) {
+    for ActionFired { action, .. } in actions.read() {
+        let up = match action {
+            Action::MoveBlockUp => true,
+            Action::MoveBlockDown => false,
+            _ => continue,
+        };
+
+        let Some(block_id) = focus.block_id else {
+            continue;
+        };
+        let Some(main_ent) = entities.main_cell else {
+            continue;
+        };
+        let Ok(editor) = cells.get(main_ent) else {
+            continue;
+        };
+        let Some(block) = editor.block_snapshot(&block_id) else {
+            continue;
+        };
+
+        let siblings: Vec<_> = editor
+            .blocks()
+            .into_iter()
+            .filter(|b| b.parent_id == block.parent_id)
+            .map(|b| b.id)
+            .collect();
+        let Some(idx) = siblings.iter().position(|id| *id == block_id) else {
+            continue;
+        };
+
+        let after_id = if up {
+            if idx == 0 {
+                continue;
+            }
+            idx.checked_sub(2).map(|i| siblings[i])
+        } else {
+            match siblings.get(idx + 1) {
+                Some(next) => Some(*next),
+                None => continue,
+            }
+        };
+        reorders.write(super::events::BlockReorderRequested { block_id, after_id });
+    }
+}
+
+/// Fire the `reorderBlock` RPC for each [`BlockReorderRequested`].
+///
+/// The local document catches up through the server's `onBlockMoved`
+/// broadcast — no optimistic local move, so a rejected reorder (non-sibling
+/// drop target) leaves the view untouched.
+///
+/// [`BlockReorderRequested`]: super::events::BlockReorderRequested
+pub fn handle_block_reorder_requests(
+    mut reorders: MessageReader<super::events::BlockReorderRequested>,
+    actor: Option<Res<crate::connection::RpcActor>>,
+    doc_cache: Res<crate::cell::DocumentCache>,
+) {
+    for req in reorders.read() {
+        let (Some(actor), Some(ctx_id)) = (&actor, doc_cache.active_id()) else {
+            continue;
+        };
+        let handle = actor.handle.clone();
+        let (bid, after) = (req.block_id, req.after_id);
+        bevy::tasks::IoTaskPool::get()
+            .spawn(async move {
+                match handle.reorder_block(ctx_id, &bid, after.as_ref()).await {
+                    Ok(_) => log::info!("reorder_block: block={:?} after={:?}", bid, after),
+                    Err(e) => log::warn!("reorder_block failed: {e}"),
+                }
+            })
+            .detach();
+    }
+}
+
 // ============================================================================
 // TILING PANE MANAGEMENT
 // ============================================================================
@@ -793,6 +969,53 @@ pub fn handle_tiling(mut actions: MessageReader<ActionFired>, mut tree: ResMut<T
     }
 }
 
+/// Handle [`SplitPaneRequested`]: resolve the named context and open it in
+/// a new pane beside the focused one, focused. Resolution accepts a full id,
+/// a label, or an id/label prefix — the drift context list first, then
+/// cached documents the list doesn't know yet.
+///
+/// [`SplitPaneRequested`]: super::events::SplitPaneRequested
+pub fn handle_split_requests(
+    mut requests: MessageReader<super::events::SplitPaneRequested>,
+    mut tree: ResMut<TilingTree>,
+    drift: Res<crate::ui::drift::DriftState>,
+    doc_cache: Res<crate::cell::DocumentCache>,
+) {
+    for req in requests.read() {
+        let document_id = if req.context.is_empty() {
+            String::new()
+        } else {
+            let cached = doc_cache
+                .iter()
+                .filter(|(id, _)| !drift.contexts.iter().any(|c| c.id == *id))
+                .map(|(id, entry)| (id, entry.context_name.as_str()));
+            let items = drift
+                .contexts
+                .iter()
+                .map(|c| (c.id, c.label.as_str()))
+                .chain(cached)
+                .map(|(id, label)| (id, (!label.is_empty()).then_some(label)));
+            match kaijutsu_types::ContextId::parse(&req.context)
+                .or_else(|_| kaijutsu_types::resolve_context_prefix(items, &req.context))
+            {
+                Ok(id) => id.to_string(),
+                Err(e) => {
+                    warn!("Tiling: cannot split to '{}': {e}", req.context);
+                    continue;
+                }
+            }
+        };
+        let target = tree.focused;
+        match tree.split_with_document(target, req.direction, &document_id) {
+            Some(new_pane) => info!(
+                "Tiling: split {:?} → new {} showing '{}'",
+                req.direction, new_pane, req.context
+            ),
+            None => warn!("Tiling: focused {} is not a conversation pane", target),
+        }
+    }
+}
+
 // ============================================================================
 // TEXT INPUT (COMPOSE + INLINE BLOCK EDITING)
 // ============================================================================
@@ -825,6 +1048,12 @@ pub fn handle_compose_input(
     mut focus: ResMut<FocusArea>,
     surface: Res<super::focus::ActiveSurface>,
     mut scroll_state: ResMut<ConversationScrollState>,
+    mut split_writer: MessageWriter<super::events::SplitPaneRequested>,
+    mut goto_writer: MessageWriter<super::events::GotoBlockRequested>,
+    mut permalink_writer: MessageWriter<super::events::PermalinkOpenRequested>,
+    mut notifications_writer: MessageWriter<super::events::NotificationsPanelRequested>,
+    mut connection_writer: MessageWriter<super::events::ConnectionCommandRequested>,
+    focus_target: Res<FocusTarget>,
 ) {
     let mut overlay = if surface.is_shell() {
         match shell_overlay.single_mut() {
@@ -868,6 +1097,58 @@ pub fn handle_compose_input(
     for ActionFired { action, .. } in actions.read() {
         match action {
             Action::Submit => {
+                // `:vsplit <context>` / `:split <context>` are pane commands,
+                // handled here rather than sent to kaish.
+                if is_shell
+                    && let Some((direction, context)) =
+                        crate::ui::tiling::parse_split_command(&overlay.text)
+                {
+                    split_writer.write(super::events::SplitPaneRequested { direction, context });
+                    overlay.text.clear();
+                    overlay.cursor = 0;
+                    overlay.selection_anchor = None;
+                    *focus = FocusArea::Conversation;
+                    continue;
+                }
+                // `:goto <block>` jumps to a block by label or id; a
+                // permalink may lead into another context.
+                if is_shell && let Some(target) = parse_goto_command(&overlay.text) {
+                    match target.parse::<kaijutsu_types::Permalink>() {
+                        Ok(link) => {
+                            permalink_writer.write(super::events::PermalinkOpenRequested { link });
+                        }
+                        Err(_) => {
+                            goto_writer.write(super::events::GotoBlockRequested { target });
+                        }
+                    }
+                    overlay.text.clear();
+                    overlay.cursor = 0;
+                    overlay.selection_anchor = None;
+                    *focus = FocusArea::Conversation;
+                    continue;
+                }
+                // `:notifications` toggles the toast history panel.
+                if is_shell && crate::ui::toast::parse_notifications_command(&overlay.text) {
+                    notifications_writer.write(super::events::NotificationsPanelRequested);
+                    overlay.text.clear();
+                    overlay.cursor = 0;
+                    overlay.selection_anchor = None;
+                    *focus = FocusArea::Conversation;
+                    continue;
+                }
+                // `:connection`, `:retry`, `:connect <host>` drive the
+                // connection panel and the reconnect FSM.
+                if is_shell
+                    && let Some(command) =
+                        crate::ui::connection_panel::parse_connection_command(&overlay.text)
+                {
+                    connection_writer.write(super::events::ConnectionCommandRequested(command));
+                    overlay.text.clear();
+                    overlay.cursor = 0;
+                    overlay.selection_anchor = None;
+                    *focus = FocusArea::Conversation;
+                    continue;
+                }
                 if !overlay.is_empty()
                     && let (Some(actor), Some(ctx)) = (&actor, ctx_id)
                 {
@@ -878,7 +1159,40 @@ pub fn handle_compose_input(
                     // even if the user had scrolled away from the bottom.
                     scroll_state.start_following();
 
-                    if is_shell {
+                    let repl = focus_target
+                        .block_id
+                        .and_then(|id| doc_cache.get(ctx)?.synced.get_block(&id))
+                        .and_then(|block| repl_session_for(&block))
+                        .filter(|id| {
+                            doc_cache
+                                .get(ctx)
+                                .and_then(|cached| cached.synced.get_block(id))
+                                .is_some_and(|s| s.status == kaijutsu_crdt::Status::Running)
+                        });
+                    if is_shell && let Some(session) = repl {
+                        // Focused REPL cell: the shell line is its input.
+                        let params = serde_json::json!({
+                            "block_id": session.to_key(),
+                            "input": overlay.text,
+                            "interactive": true,
+                        })
+                        .to_string();
+                        bevy::tasks::IoTaskPool::get()
+                            .spawn(async move {
+                                match handle.execute_tool("repl_send", &params).await {
+                                    Ok(result) if !result.success => {
+                                        log::warn!("repl_send failed: {}", result.output)
+                                    }
+                                    Ok(_) => {}
+                                    Err(e) => log::error!("repl_send failed: {e}"),
+                                }
+                            })
+                            .detach();
+
+                        overlay.text.clear();
+                        overlay.cursor = 0;
+                        overlay.selection_anchor = None;
+                    } else if is_shell {
                         // Shell: call shell_execute directly with local text
                         let code = overlay.text.clone();
                         bevy::tasks::IoTaskPool::get()
@@ -1101,3 +1415,51 @@ pub fn cleanup_stale_focused_markers(
         debug!("Cleaned up stale FocusedBlockCell on {:?}", entity);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_crdt::{BlockId, BlockKind, BlockSnapshotBuilder, ContextId, PrincipalId};
+
+    #[test]
+    fn parse_goto_commands() {
+        assert_eq!(
+            parse_goto_command(":goto design-decision-3").as_deref(),
+            Some("design-decision-3")
+        );
+        assert_eq!(parse_goto_command("  :go #12  ").as_deref(), Some("#12"));
+        assert_eq!(parse_goto_command(":goto"), None);
+        assert_eq!(parse_goto_command(":goto a b"), None);
+        assert_eq!(parse_goto_command("goto intro"), None);
+    }
+
+    #[test]
+    fn goto_prefers_labels_over_id_forms() {
+        let (ctx, agent) = (ContextId::new(), PrincipalId::new());
+        let block = |seq, label: Option<&str>| {
+            let b = BlockSnapshotBuilder::new(BlockId::new(ctx, agent, seq), BlockKind::Text);
+            match label {
+                Some(l) => b.label(l).build(),
+                None => b.build(),
+            }
+        };
+        let blocks = [block(1, Some("intro")), block(2, None)];
+        assert_eq!(resolve_goto_target(&blocks, "intro").unwrap(), blocks[0].id);
+        assert_eq!(resolve_goto_target(&blocks, "#2").unwrap(), blocks[1].id);
+        assert!(resolve_goto_target(&blocks, "outro").is_err());
+    }
+
+    #[test]
+    fn repl_output_routes_to_its_session() {
+        let (ctx, agent) = (ContextId::new(), PrincipalId::new());
+        let session =
+            BlockSnapshotBuilder::new(BlockId::new(ctx, agent, 1), BlockKind::Repl).build();
+        let output = BlockSnapshotBuilder::new(BlockId::new(ctx, agent, 2), BlockKind::Repl)
+            .parent_id(session.id)
+            .build();
+        let text = BlockSnapshotBuilder::new(BlockId::new(ctx, agent, 3), BlockKind::Text).build();
+        assert_eq!(repl_session_for(&session), Some(session.id));
+        assert_eq!(repl_session_for(&output), Some(session.id));
+        assert_eq!(repl_session_for(&text), None);
+    }
+}
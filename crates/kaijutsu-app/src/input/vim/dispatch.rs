@@ -241,6 +241,7 @@ pub fn vim_dispatch_compose(
     surface: Res<crate::input::focus::ActiveSurface>,
     actor: Option<Res<crate::connection::RpcActor>>,
     doc_cache: Res<crate::cell::DocumentCache>,
+    mut completion: ResMut<crate::ui::completion::CompletionState>,
     mut action_writer: MessageWriter<ActionFired>,
     mut text_writer: MessageWriter<TextInputReceived>,
 ) {
@@ -319,6 +320,39 @@ pub fn vim_dispatch_compose(
             continue;
         }
 
+        // An open completion popup owns Tab, the arrows, Ctrl+N/P and
+        // Escape (ui::completion) — ahead of vim so Escape closes the list
+        // instead of leaving Insert.
+        if !surface.is_shell() && completion.is_open() {
+            match event.key_code {
+                KeyCode::Tab => {
+                    completion.accept(&mut overlay);
+                    continue;
+                }
+                KeyCode::ArrowDown => {
+                    completion.select_next();
+                    continue;
+                }
+                KeyCode::KeyN if ctrl => {
+                    completion.select_next();
+                    continue;
+                }
+                KeyCode::ArrowUp => {
+                    completion.select_prev();
+                    continue;
+                }
+                KeyCode::KeyP if ctrl => {
+                    completion.select_prev();
+                    continue;
+                }
+                KeyCode::Escape => {
+                    completion.dismiss();
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
         let Some(tkey) = bevy_to_terminal_key(event, &keys) else {
             continue;
         };
@@ -38,6 +38,10 @@ struct Cli {
     #[arg(long)]
     insecure: bool,
 
+    /// Don't ask the server for zstd compression of large RPC payloads
+    #[arg(long)]
+    no_compress: bool,
+
     /// Share a local directory into `/r/<client-id>/<name>` (repeatable).
     /// Format: `[name=]path[:rw]` — the name defaults to the path's
     /// basename; `:rw` labels the share read-write in the manifest (write
@@ -45,6 +49,11 @@ struct Cli {
     /// today regardless of this flag). See `docs/slash-r.md`.
     #[arg(long = "share", action = clap::ArgAction::Append, value_parser = kaijutsu_client::parse_share_arg)]
     shares: Vec<kaijutsu_client::ShareArg>,
+
+    /// Block permalink to open once connected
+    /// (`kaijutsu://kernel/context/block-key`): joins its context and
+    /// scrolls to the block.
+    link: Option<kaijutsu_types::Permalink>,
 }
 
 mod audio_sched;
@@ -84,6 +93,7 @@ fn main() {
         host: cli.host,
         port: cli.port,
         insecure: cli.insecure,
+        compress: !cli.no_compress,
         ..SshConfig::default()
     };
 
@@ -187,6 +197,11 @@ fn main() {
         .add_plugins(text::KjTextPlugin)
         // Focus-based input dispatch (Phase 1: emits alongside old handlers)
         .add_plugins(input::InputPlugin)
+        .insert_resource(
+            cli.link
+                .map(input::permalink::PendingPermalink::open)
+                .unwrap_or_default(),
+        )
         // Cell editing
         .add_plugins(cell::CellPlugin)
         // Offscreen vello rasterizer (kaijutsu-owned)
@@ -195,6 +210,12 @@ fn main() {
         .add_plugins(view::ui_rtt::UiRttPlugin)
         // Per-block Vello texture rendering
         .add_plugins(view::block_render::BlockRenderPlugin)
+        // Inline image blocks: lazy CAS fetch + decode, click to zoom
+        .add_plugins(view::block_image::BlockImagePlugin)
+        // Other principals' block edit locks, shown on the block borders
+        .add_plugins(view::block_locks::BlockLocksPlugin)
+        // Translated view of text blocks in the seat's language
+        .add_plugins(view::translate::BlockTranslatePlugin)
         // Peer transport (drift navigation: kernel → app invocations)
         .add_plugins(peers::PeersPlugin)
         // Shader effects
@@ -234,6 +255,22 @@ fn main() {
         .add_plugins(ui::dock::DockPlugin)
         // Drift state - context list + staged queue polling
         .add_plugins(ui::drift::DriftPlugin)
+        // Per-principal inbox — unread count behind the North dock badge
+        .add_plugins(ui::inbox::InboxPlugin)
+        // Per-principal preferences, loaded from the kernel on connect
+        .add_plugins(ui::prefs::PreferencesPlugin)
+        // Prompt completion popup under the compose overlay
+        .add_plugins(ui::completion::CompletionPlugin)
+        // Consent audit log — the North dock's consent badge
+        .add_plugins(ui::consent::ConsentLogPlugin)
+        .add_plugins(ui::consent::ConsentPromptPlugin)
+        // Context event timeline strip above the conversation
+        .add_plugins(ui::timeline_strip::TimelineStripPlugin)
+        // Toasts for drift, consent, errors, and reconnects (+ `:notifications`)
+        .add_plugins(ui::toast::ToastPlugin)
+        // Reconnect state, retry now, change server (`:connection`, `:retry`,
+        // `:connect`)
+        .add_plugins(ui::connection_panel::ConnectionPanelPlugin)
         // Room level + patch bay station + time well (docs/scenes/): dive into
         // a zoomed station via `RoomState::zoomed`, Ctrl+W to jump straight
         // into the well. RoomPlugin MUST be added before any zoomable
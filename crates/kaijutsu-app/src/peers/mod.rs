@@ -4,7 +4,7 @@
 //! dispatches them via Bevy systems. The app registers itself as a peer
 //! (`nick = "kaijutsu-app"`) on connect; other agents can then call into the
 //! app — most notably `switch_context` and `active_context` for drift
-//! navigation.
+//! navigation, and `open_permalink` to jump to a block by permalink.
 
 mod plugin;
 mod systems;
@@ -3,6 +3,7 @@
 use super::plugin::PeerInvocationChannel;
 use bevy::prelude::*;
 
+use crate::input::events::PermalinkOpenRequested;
 use crate::ui::drift::DriftState;
 use crate::view::components::ContextSwitchRequested;
 use crate::view::document::DocumentCache;
@@ -18,6 +19,7 @@ pub fn poll_peer_invocations(
     drift: Res<DriftState>,
     mut switch_writer: MessageWriter<ContextSwitchRequested>,
     mut editor_writer: MessageWriter<EditorOpenRequested>,
+    mut permalink_writer: MessageWriter<PermalinkOpenRequested>,
 ) {
     let rx = match channel.rx.lock() {
         Ok(guard) => guard,
@@ -34,6 +36,7 @@ pub fn poll_peer_invocations(
             &drift,
             &mut switch_writer,
             &mut editor_writer,
+            &mut permalink_writer,
         );
         let _ = invocation.reply.send(result);
     }
@@ -46,6 +49,7 @@ fn dispatch_peer_action(
     drift: &DriftState,
     switch_writer: &mut MessageWriter<ContextSwitchRequested>,
     editor_writer: &mut MessageWriter<EditorOpenRequested>,
+    permalink_writer: &mut MessageWriter<PermalinkOpenRequested>,
 ) -> Result<Vec<u8>, String> {
     match action {
         "switch_context" => {
@@ -123,6 +127,29 @@ fn dispatch_peer_action(
             .map_err(|e| format!("serialize: {e}"))
         }
 
+        "open_permalink" => {
+            #[derive(serde::Deserialize)]
+            struct Params {
+                url: String,
+            }
+            let p: Params =
+                serde_json::from_slice(params).map_err(|e| format!("invalid params: {e}"))?;
+            let link: kaijutsu_types::Permalink = p
+                .url
+                .parse()
+                .map_err(|e| format!("invalid permalink: {e}"))?;
+
+            // Opening takes frames (join, sync, scroll): this acknowledges
+            // the request, not the landing.
+            permalink_writer.write(PermalinkOpenRequested { link });
+
+            serde_json::to_vec(&serde_json::json!({
+                "context_id": link.context_id().to_string(),
+                "block_id": link.block_id.to_key(),
+            }))
+            .map_err(|e| format!("serialize: {e}"))
+        }
+
         "active_context" => {
             let active = doc_cache.active_id();
             let mru: Vec<_> = doc_cache
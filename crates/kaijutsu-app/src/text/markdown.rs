@@ -14,6 +14,8 @@
 use bevy::prelude::Color;
 use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
 
+use super::syntax::{SyntaxClass, SyntaxPalette};
+
 /// Theme colors for markdown rendering using Bevy Color.
 #[derive(Clone, Debug)]
 pub struct MarkdownColors {
@@ -25,6 +27,8 @@ pub struct MarkdownColors {
     pub strong: Option<Color>,
     /// Fenced code block color.
     pub code_block: Color,
+    /// Token colors for syntax-highlighted spans (see [`super::syntax`]).
+    pub syntax: SyntaxPalette,
 }
 
 /// Mirrors the `md_heading_color`/`md_code_fg`/`md_code_block_fg`/`md_strong_color`
@@ -39,6 +43,7 @@ impl Default for MarkdownColors {
             code: Color::srgb_u8(0x9E, 0xCE, 0x6A),    // Green
             strong: None,                              // Inherit
             code_block: Color::srgb_u8(0x7A, 0xA2, 0xF7), // Blue
+            syntax: SyntaxPalette::from(&crate::ui::theme::Theme::default().syntax),
         }
     }
 }
@@ -52,10 +57,12 @@ pub struct RichSpan {
     pub code: bool,
     pub heading_level: Option<u8>,
     pub code_block: bool,
+    /// Token class when the span came from the syntax highlighter.
+    pub syntax: Option<SyntaxClass>,
 }
 
 impl RichSpan {
-    fn new(text: impl Into<String>) -> Self {
+    pub(crate) fn new(text: impl Into<String>) -> Self {
         Self {
             text: text.into(),
             bold: false,
@@ -63,6 +70,7 @@ impl RichSpan {
             code: false,
             heading_level: None,
             code_block: false,
+            syntax: None,
         }
     }
 }
@@ -13,6 +13,7 @@ mod resources;
 pub mod rich;
 pub mod shaping;
 pub mod sparkline;
+pub mod syntax;
 
 pub use components::{KjTextEffects, bevy_color_to_brush};
 pub use plugin::KjTextPlugin;
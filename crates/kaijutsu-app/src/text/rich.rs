@@ -47,7 +47,8 @@ pub struct RichContent {
 
 /// The actual content variant being rendered.
 pub enum RichContentKind {
-    /// Markdown with per-span brush coloring.
+    /// Markdown with per-span brush coloring. Also carries syntax-highlighted
+    /// code (`detect_code_content`), whose spans are styled the same way.
     Markdown {
         spans: Vec<RichSpan>,
         plain_text: String,
@@ -97,6 +98,7 @@ pub enum RichContentKind {
 /// - Headings → `md_heading_color`
 /// - Code/code blocks → `md_code_fg` / `md_code_block_fg`
 /// - Bold → `md_strong_color` or base_color
+/// - Syntax tokens → `theme.syntax` by class
 /// - Plain text → `base_color`
 pub fn build_span_brushes(
     spans: &[RichSpan],
@@ -110,7 +112,9 @@ pub fn build_span_brushes(
         let start = byte_offset;
         let end = start + span.text.len();
 
-        let color = if span.heading_level.is_some() {
+        let color = if let Some(class) = span.syntax {
+            md_colors.syntax.color(class)
+        } else if span.heading_level.is_some() {
             md_colors.heading
         } else if span.code_block {
             md_colors.code_block
@@ -283,6 +287,20 @@ pub fn detect_output_content(output: &OutputData, _version: u64) -> Option<RichC
     })
 }
 
+/// Syntax-highlighted content for a block with a declared source language.
+///
+/// Returns `None` when the language has no highlighter, so the block falls
+/// through to the other detectors.
+pub fn detect_code_content(text: &str, language: &str) -> Option<RichContent> {
+    let spans = super::syntax::highlight(text, language)?;
+    Some(RichContent {
+        kind: RichContentKind::Markdown {
+            spans,
+            plain_text: text.to_string(),
+        },
+    })
+}
+
 /// Maximum SVG source size we'll attempt to parse (100KB).
 const SVG_MAX_BYTES: usize = 100 * 1024;
 
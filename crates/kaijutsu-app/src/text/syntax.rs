@@ -0,0 +1,376 @@
+//! Lexical syntax highlighting for blocks with a declared source language.
+//!
+//! A block's `language` (set at creation or detected by the kernel on tool
+//! output) picks a [`LangSpec`]; the text is split into [`RichSpan`]s tagged
+//! with a [`SyntaxClass`], which `build_span_brushes` colors from the theme's
+//! `syntax` palette. This is a tokenizer, not a parser: keywords, strings,
+//! numbers, comments and operators — enough to make code readable, cheap
+//! enough to rerun on every streamed edit.
+
+use bevy::prelude::Color;
+
+use super::markdown::RichSpan;
+use crate::ui::theme::SyntaxColors;
+
+/// Token class of a highlighted span.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyntaxClass {
+    Keyword,
+    String,
+    Number,
+    Comment,
+    Operator,
+    /// Diff `+` line.
+    Inserted,
+    /// Diff `-` line.
+    Deleted,
+    /// Diff headers and hunk markers.
+    Meta,
+}
+
+/// Colors per [`SyntaxClass`], taken from the theme's `syntax` palette.
+#[derive(Clone, Debug)]
+pub struct SyntaxPalette {
+    pub keyword: Color,
+    pub string: Color,
+    pub number: Color,
+    pub comment: Color,
+    pub operator: Color,
+    pub inserted: Color,
+    pub deleted: Color,
+    pub meta: Color,
+}
+
+impl From<&SyntaxColors> for SyntaxPalette {
+    fn from(colors: &SyntaxColors) -> Self {
+        Self {
+            keyword: colors.keyword,
+            string: colors.string,
+            number: colors.number,
+            comment: colors.comment,
+            operator: colors.operator,
+            inserted: colors.string,
+            deleted: colors.error,
+            meta: colors.command,
+        }
+    }
+}
+
+impl SyntaxPalette {
+    pub fn color(&self, class: SyntaxClass) -> Color {
+        match class {
+            SyntaxClass::Keyword => self.keyword,
+            SyntaxClass::String => self.string,
+            SyntaxClass::Number => self.number,
+            SyntaxClass::Comment => self.comment,
+            SyntaxClass::Operator => self.operator,
+            SyntaxClass::Inserted => self.inserted,
+            SyntaxClass::Deleted => self.deleted,
+            SyntaxClass::Meta => self.meta,
+        }
+    }
+}
+
+/// Lexical rules for one language.
+struct LangSpec {
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    /// Whitespace-separated keyword list.
+    keywords: &'static str,
+}
+
+const C_LIKE_QUOTES: &[char] = &['"', '\''];
+
+const RUST: LangSpec = LangSpec {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    // `'` is also a lifetime sigil, so only `"` opens a string.
+    quotes: &['"'],
+    keywords: "as async await break const continue crate dyn else enum extern false fn for if \
+        impl in let loop match mod move mut pub ref return self Self static struct super \
+        trait true type unsafe use where while",
+};
+
+const PYTHON: LangSpec = LangSpec {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: C_LIKE_QUOTES,
+    keywords: "and as assert async await break class continue def del elif else except False \
+        finally for from global if import in is lambda None nonlocal not or pass raise \
+        return True try while with yield",
+};
+
+const JAVASCRIPT: LangSpec = LangSpec {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    keywords: "async await break case catch class const continue default delete do else enum \
+        export extends false finally for function if implements import in instanceof \
+        interface let new null of return static super switch this throw true try type \
+        typeof undefined var void while yield",
+};
+
+const GO: LangSpec = LangSpec {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    keywords: "break case chan const continue default defer else false fallthrough for func go \
+        goto if import interface map nil package range return select struct switch true \
+        type var",
+};
+
+const C_FAMILY: LangSpec = LangSpec {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: C_LIKE_QUOTES,
+    keywords: "auto bool break case char class const continue default delete do double else \
+        enum extern false final float for fun if import int interface long namespace new \
+        null nullptr override package private protected public return short signed \
+        sizeof static struct switch template this throw true try typedef union unsigned \
+        val var virtual void volatile while",
+};
+
+const SHELL: LangSpec = LangSpec {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: C_LIKE_QUOTES,
+    keywords: "case do done elif else esac export fi for function if in local return set then \
+        until while",
+};
+
+const LUA: LangSpec = LangSpec {
+    line_comments: &["--"],
+    block_comment: None,
+    quotes: C_LIKE_QUOTES,
+    keywords: "and break do else elseif end false for function if in local nil not or repeat \
+        return then true until while",
+};
+
+const RUBY: LangSpec = LangSpec {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: C_LIKE_QUOTES,
+    keywords: "begin class def do else elsif end ensure false if module nil require rescue \
+        return self true unless until when while yield",
+};
+
+const SQL: LangSpec = LangSpec {
+    line_comments: &["--"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['\''],
+    keywords: "and as by create delete from group insert into join key left limit not null on \
+        or order primary select set table update values where AND AS BY CREATE DELETE \
+        FROM GROUP INSERT INTO JOIN KEY LEFT LIMIT NOT NULL ON OR ORDER PRIMARY SELECT \
+        SET TABLE UPDATE VALUES WHERE",
+};
+
+const WGSL: LangSpec = LangSpec {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    keywords: "array bool break const continue else f32 false fn for i32 if let loop mat4x4 \
+        override return struct switch true u32 var vec2 vec3 vec4 while",
+};
+
+const DATA: LangSpec = LangSpec {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: C_LIKE_QUOTES,
+    keywords: "true false null yes no",
+};
+
+const JSON: LangSpec = LangSpec {
+    line_comments: &[],
+    block_comment: None,
+    quotes: &['"'],
+    keywords: "true false null",
+};
+
+const CSS: LangSpec = LangSpec {
+    line_comments: &[],
+    block_comment: Some(("/*", "*/")),
+    quotes: C_LIKE_QUOTES,
+    keywords: "important inherit initial none auto",
+};
+
+fn lang_spec(language: &str) -> Option<&'static LangSpec> {
+    Some(match language {
+        "rust" => &RUST,
+        "python" => &PYTHON,
+        "javascript" | "typescript" | "javascriptreact" | "typescriptreact" => &JAVASCRIPT,
+        "go" => &GO,
+        "c" | "cpp" | "java" | "kotlin" | "capnp" => &C_FAMILY,
+        "bash" | "zsh" | "kaish" | "make" | "dockerfile" => &SHELL,
+        "lua" => &LUA,
+        "ruby" => &RUBY,
+        "sql" => &SQL,
+        "wgsl" => &WGSL,
+        "toml" | "yaml" => &DATA,
+        "json" => &JSON,
+        "css" => &CSS,
+        _ => return None,
+    })
+}
+
+/// Split `text` into highlighted spans for `language`. The spans' text
+/// concatenates back to `text` exactly. `None` for a language without a
+/// highlighter.
+pub fn highlight(text: &str, language: &str) -> Option<Vec<RichSpan>> {
+    if language == "diff" {
+        return Some(highlight_diff(text));
+    }
+    let spec = lang_spec(language)?;
+    let mut out = SpanSink::default();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let (len, class) = next_token(rest, c, spec);
+        out.push(&rest[..len], class);
+        rest = &rest[len..];
+    }
+    Some(out.spans)
+}
+
+/// Byte length and class of the token at the start of `rest`.
+fn next_token(rest: &str, c: char, spec: &LangSpec) -> (usize, Option<SyntaxClass>) {
+    if spec.line_comments.iter().any(|p| rest.starts_with(p)) {
+        return (
+            rest.find('\n').unwrap_or(rest.len()),
+            Some(SyntaxClass::Comment),
+        );
+    }
+    if let Some((open, close)) = spec.block_comment
+        && rest.starts_with(open)
+    {
+        let len = rest[open.len()..]
+            .find(close)
+            .map_or(rest.len(), |i| open.len() + i + close.len());
+        return (len, Some(SyntaxClass::Comment));
+    }
+    if spec.quotes.contains(&c) {
+        return (string_len(rest, c), Some(SyntaxClass::String));
+    }
+    if c.is_ascii_digit() {
+        let len = rest
+            .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '.'))
+            .unwrap_or(rest.len());
+        return (len, Some(SyntaxClass::Number));
+    }
+    if c.is_alphabetic() || c == '_' {
+        let len = rest
+            .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+            .unwrap_or(rest.len());
+        let word = &rest[..len];
+        let class = spec
+            .keywords
+            .split_ascii_whitespace()
+            .any(|k| k == word)
+            .then_some(SyntaxClass::Keyword);
+        return (len, class);
+    }
+    let class = "=<>!&|+-*/%^~?"
+        .contains(c)
+        .then_some(SyntaxClass::Operator);
+    (c.len_utf8(), class)
+}
+
+/// Length of a string literal opened by `quote` at the start of `rest`,
+/// honoring backslash escapes. An unterminated string runs to the end of its
+/// line (backtick strings, which may span lines, to the end of the text).
+fn string_len(rest: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, ch) in rest.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == quote {
+            return i + ch.len_utf8();
+        } else if ch == '\n' && quote != '`' {
+            return i;
+        }
+    }
+    rest.len()
+}
+
+/// Color unified-diff lines by their leading marker.
+fn highlight_diff(text: &str) -> Vec<RichSpan> {
+    let mut out = SpanSink::default();
+    for line in text.split_inclusive('\n') {
+        let class = if line.starts_with("+++")
+            || line.starts_with("---")
+            || line.starts_with("@@")
+            || line.starts_with("diff ")
+            || line.starts_with("index ")
+        {
+            Some(SyntaxClass::Meta)
+        } else if line.starts_with('+') {
+            Some(SyntaxClass::Inserted)
+        } else if line.starts_with('-') {
+            Some(SyntaxClass::Deleted)
+        } else {
+            None
+        };
+        out.push(line, class);
+    }
+    out.spans
+}
+
+/// Collects spans, merging runs of the same class so a line of plain
+/// identifiers becomes one span rather than one per token.
+#[derive(Default)]
+struct SpanSink {
+    spans: Vec<RichSpan>,
+}
+
+impl SpanSink {
+    fn push(&mut self, text: &str, class: Option<SyntaxClass>) {
+        if let Some(last) = self.spans.last_mut()
+            && last.syntax == class
+        {
+            last.text.push_str(text);
+            return;
+        }
+        let mut span = RichSpan::new(text);
+        span.syntax = class;
+        self.spans.push(span);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classes(spans: &[RichSpan]) -> Vec<(&str, Option<SyntaxClass>)> {
+        spans.iter().map(|s| (s.text.as_str(), s.syntax)).collect()
+    }
+
+    #[test]
+    fn rust_tokens_are_classified_and_text_is_preserved() {
+        let src = "fn main() { let x = \"hi\"; // done\n}";
+        let spans = highlight(src, "rust").unwrap();
+        let joined: String = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(joined, src);
+        let got = classes(&spans);
+        assert_eq!(got[0], ("fn", Some(SyntaxClass::Keyword)));
+        assert!(got.contains(&("let", Some(SyntaxClass::Keyword))));
+        assert!(got.contains(&("\"hi\"", Some(SyntaxClass::String))));
+        assert!(got.contains(&("// done", Some(SyntaxClass::Comment))));
+        assert!(got.contains(&("=", Some(SyntaxClass::Operator))));
+    }
+
+    #[test]
+    fn diff_lines_and_unknown_languages() {
+        let spans = highlight("@@ -1 +1 @@\n-old\n+new\n ctx\n", "diff").unwrap();
+        assert_eq!(
+            classes(&spans),
+            vec![
+                ("@@ -1 +1 @@\n", Some(SyntaxClass::Meta)),
+                ("-old\n", Some(SyntaxClass::Deleted)),
+                ("+new\n", Some(SyntaxClass::Inserted)),
+                (" ctx\n", None),
+            ]
+        );
+        assert!(highlight("main = putStrLn", "haskell").is_none());
+    }
+}
@@ -0,0 +1,304 @@
+//! Prompt completion — the popup under the compose overlay.
+//!
+//! While composing, the token under the cursor (`@mention`, `/command`,
+//! `#block`, or a VFS path; see `kaijutsu_types::completion`) is sent to the
+//! kernel's `complete` RPC once typing pauses for [`COMPLETION_DEBOUNCE`]
+//! seconds. Candidates show in a list attached to the bottom of the overlay.
+//!
+//! Keys (handled in `vim_dispatch_compose` while the list is open): Tab
+//! accepts, Up/Down or Ctrl+P/Ctrl+N move the selection, Escape closes the
+//! list until the text changes.
+
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use kaijutsu_client::Completion;
+
+use crate::cell::{InputOverlay, InputOverlayMarker};
+use crate::connection::{RpcActor, RpcResultChannel, RpcResultMessage};
+use crate::input::FocusArea;
+use crate::ui::theme::Theme;
+
+/// Quiet period after the last edit before asking the kernel (seconds).
+const COMPLETION_DEBOUNCE: f64 = 0.15;
+
+/// Rows the popup shows at once; the window follows the selection.
+const VISIBLE_ROWS: usize = 8;
+
+/// Completion candidates for the compose overlay.
+#[derive(Resource, Default)]
+pub struct CompletionState {
+    pub items: Vec<Completion>,
+    pub selected: usize,
+    /// Byte range of the token the items replace, in `basis`.
+    range: Range<usize>,
+    /// Text the items (or the in-flight request) were computed for.
+    basis: String,
+    /// The overlay `(text, cursor)` last seen, and when it changed.
+    seen: (String, usize),
+    changed_at: f64,
+    /// Whether `seen` has been sent (or found to have no token).
+    requested: bool,
+    /// Counter matched against `CompletionsReceived::request`.
+    request: u64,
+    /// Escape closed the list; stay closed until the text changes.
+    dismissed: bool,
+}
+
+impl CompletionState {
+    /// Whether the popup is showing and owns Tab/arrows/Escape.
+    pub fn is_open(&self) -> bool {
+        !self.items.is_empty() && !self.dismissed
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+        }
+    }
+
+    pub fn dismiss(&mut self) {
+        self.dismissed = true;
+    }
+
+    /// Replace the token in `overlay` with the selected item. Returns false
+    /// (and changes nothing) if the list is closed or the text has moved on
+    /// since the items were computed.
+    pub fn accept(&mut self, overlay: &mut InputOverlay) -> bool {
+        if !self.is_open() || overlay.text != self.basis {
+            return false;
+        }
+        let Some(item) = self.items.get(self.selected) else {
+            return false;
+        };
+        overlay.clear_selection();
+        overlay.text.replace_range(self.range.clone(), &item.text);
+        overlay.cursor = self.range.start + item.text.len();
+        self.items.clear();
+        true
+    }
+}
+
+/// The list node under the overlay's text surface.
+#[derive(Component)]
+struct CompletionPopup;
+
+/// One row of the popup, by index into the visible window.
+#[derive(Component)]
+struct CompletionRow(usize);
+
+/// Plugin for prompt completion.
+pub struct CompletionPlugin;
+
+impl Plugin for CompletionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CompletionState>().add_systems(
+            Update,
+            (
+                request_completions,
+                receive_completions,
+                spawn_completion_popup,
+                sync_completion_popup,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Track overlay edits and send the token under the cursor once they pause.
+fn request_completions(
+    time: Res<Time>,
+    focus: Res<FocusArea>,
+    actor: Option<Res<RpcActor>>,
+    overlay: Query<&InputOverlay, With<InputOverlayMarker>>,
+    mut state: ResMut<CompletionState>,
+    result_channel: Res<RpcResultChannel>,
+) {
+    let Ok(overlay) = overlay.single() else { return };
+    if !matches!(*focus, FocusArea::Compose) {
+        if !state.items.is_empty() {
+            state.items.clear();
+        }
+        return;
+    }
+
+    let now = time.elapsed_secs_f64();
+    if state.seen.0 != overlay.text || state.seen.1 != overlay.cursor {
+        if state.seen.0 != overlay.text {
+            state.dismissed = false;
+        }
+        state.seen = (overlay.text.clone(), overlay.cursor);
+        state.changed_at = now;
+        state.requested = false;
+        // Drop the list as soon as the cursor leaves its token.
+        let range = kaijutsu_types::token_at(&overlay.text, overlay.cursor).map(|(r, _)| r);
+        if range.is_none_or(|r| r.start != state.range.start) {
+            state.items.clear();
+        }
+    }
+    if state.requested || state.dismissed || now - state.changed_at < COMPLETION_DEBOUNCE {
+        return;
+    }
+    state.requested = true;
+    let Some((range, _)) = kaijutsu_types::token_at(&overlay.text, overlay.cursor) else {
+        state.items.clear();
+        return;
+    };
+    let Some(actor) = actor else { return };
+
+    state.request += 1;
+    state.basis = overlay.text.clone();
+    state.range = range;
+    let request = state.request;
+    let text = overlay.text.clone();
+    let cursor = overlay.cursor as u32;
+    let handle = actor.handle.clone();
+    let tx = result_channel.sender();
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            match handle.complete(&text, cursor).await {
+                Ok(completions) => {
+                    let _ = tx.send(RpcResultMessage::CompletionsReceived {
+                        request,
+                        completions,
+                    });
+                }
+                Err(e) => log::debug!("completion: complete failed: {e}"),
+            }
+        })
+        .detach();
+}
+
+/// Drain `CompletionsReceived` for the latest request into the state.
+fn receive_completions(
+    mut state: ResMut<CompletionState>,
+    mut events: MessageReader<RpcResultMessage>,
+) {
+    for event in events.read() {
+        let RpcResultMessage::CompletionsReceived {
+            request,
+            completions,
+        } = event
+        else {
+            continue;
+        };
+        if *request != state.request {
+            continue;
+        }
+        // A lone candidate that is already what's typed isn't worth a popup.
+        let typed = &state.basis[state.range.clone()];
+        if let [only] = completions.as_slice()
+            && only.text == typed
+        {
+            state.items.clear();
+            continue;
+        }
+        state.items = completions.clone();
+        state.selected = 0;
+    }
+}
+
+/// Attach the popup and its rows to the overlay once it exists.
+fn spawn_completion_popup(
+    mut commands: Commands,
+    overlay: Query<Entity, With<InputOverlayMarker>>,
+    existing: Query<(), With<CompletionPopup>>,
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+) {
+    if !existing.is_empty() {
+        return;
+    }
+    let Ok(overlay) = overlay.single() else { return };
+
+    // Like the unfocused-pane summary, the popup uses Bevy's native text
+    // rather than MSDF: short, transient labels under the real text surface.
+    let font = asset_server.load("fonts/CascadiaCodeNF.ttf");
+    let popup = commands
+        .spawn((
+            CompletionPopup,
+            Node {
+                display: Display::None,
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                border: UiRect::top(Val::Px(1.0)),
+                ..default()
+            },
+            BorderColor::all(theme.compose_palette_border.with_alpha(0.4)),
+        ))
+        .with_children(|popup| {
+            for i in 0..VISIBLE_ROWS {
+                popup.spawn((
+                    CompletionRow(i),
+                    Text::new(""),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(theme.fg_dim),
+                    BackgroundColor(Color::NONE),
+                    Node {
+                        display: Display::None,
+                        padding: UiRect::axes(Val::Px(4.0), Val::Px(1.0)),
+                        ..default()
+                    },
+                ));
+            }
+        })
+        .id();
+    commands.entity(overlay).add_child(popup);
+}
+
+/// Mirror `CompletionState` into the popup rows.
+fn sync_completion_popup(
+    state: Res<CompletionState>,
+    theme: Res<Theme>,
+    mut popup: Query<&mut Node, (With<CompletionPopup>, Without<CompletionRow>)>,
+    mut rows: Query<(
+        &CompletionRow,
+        &mut Text,
+        &mut TextColor,
+        &mut BackgroundColor,
+        &mut Node,
+    )>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    let Ok(mut popup) = popup.single_mut() else { return };
+    let open = state.is_open();
+    popup.display = if open { Display::Flex } else { Display::None };
+    if !open {
+        return;
+    }
+
+    let first = state
+        .selected
+        .saturating_sub(VISIBLE_ROWS - 1)
+        .min(state.items.len().saturating_sub(VISIBLE_ROWS));
+    for (row, mut text, mut color, mut bg, mut node) in rows.iter_mut() {
+        let index = first + row.0;
+        let Some(item) = state.items.get(index) else {
+            node.display = Display::None;
+            continue;
+        };
+        node.display = Display::Flex;
+        text.0.clone_from(&item.display_text);
+        let selected = index == state.selected;
+        *color = TextColor(if selected { theme.fg } else { theme.fg_dim });
+        *bg = BackgroundColor(if selected {
+            theme.selection_bg
+        } else {
+            Color::NONE
+        });
+    }
+}
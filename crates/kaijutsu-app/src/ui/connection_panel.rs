@@ -0,0 +1,372 @@
+//! Connection panel — the reconnect state machine, in view.
+//!
+//! The dock only says "Reconnecting (3)…". `:connection` opens a panel with
+//! what the actor is actually doing: which server, the FSM state, the last
+//! error, and — in backoff — a countdown to the next attempt. Two shell
+//! commands act on it:
+//!
+//! - `:retry` skips the backoff ([`ActorHandle::retry_now`]). From
+//!   `Terminal` it respawns the actor instead, so the bootstrap (whoami,
+//!   theme, context restore) runs again against the fresh connection.
+//! - `:connect <host>[:<port>]` points the app at another server: a new
+//!   actor generation on the changed [`SshConfig`], as a context switch
+//!   would spawn.
+//!
+//! [`ActorHandle::retry_now`]: kaijutsu_client::ActorHandle::retry_now
+//! [`SshConfig`]: kaijutsu_client::SshConfig
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use kaijutsu_client::ConnectionStatus;
+
+use crate::connection::{BootstrapChannel, BootstrapCommand, RpcActor, RpcConnectionState};
+use crate::input::events::ConnectionCommandRequested;
+use crate::ui::theme::Theme;
+use crate::ui::toast::{ToastSeverity, Toasts};
+
+/// A connection command typed at the shell surface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionCommand {
+    /// `:connection` / `:conn` — toggle the panel.
+    TogglePanel,
+    /// `:retry` — dial now instead of waiting out the backoff.
+    Retry,
+    /// `:connect <host>[:<port>]` — switch servers. No port keeps the
+    /// current one.
+    Connect { host: String, port: Option<u16> },
+}
+
+/// Parse `:connection`, `:retry` or `:connect <host>[:<port>]`. A
+/// `:connect` without a host or with a bad port is not a command.
+pub fn parse_connection_command(text: &str) -> Option<ConnectionCommand> {
+    let rest = text.trim().strip_prefix(':')?;
+    match rest {
+        "connection" | "conn" => return Some(ConnectionCommand::TogglePanel),
+        "retry" => return Some(ConnectionCommand::Retry),
+        _ => {}
+    }
+    let target = rest.strip_prefix("connect ")?.trim();
+    if target.is_empty() || target.contains(char::is_whitespace) {
+        return None;
+    }
+    let (host, port) = match target.rsplit_once(':') {
+        Some((host, port)) => (host, Some(port.parse().ok()?)),
+        None => (target, None),
+    };
+    if host.is_empty() {
+        return None;
+    }
+    Some(ConnectionCommand::Connect {
+        host: host.to_string(),
+        port,
+    })
+}
+
+/// Whether the panel is showing.
+#[derive(Resource, Default)]
+pub struct ConnectionPanelState {
+    pub panel_open: bool,
+}
+
+/// The panel itself.
+#[derive(Component)]
+struct ConnectionPanel;
+
+/// The panel's body text.
+#[derive(Component)]
+struct ConnectionPanelBody;
+
+/// Plugin for the `:connection` panel and the `:retry` / `:connect`
+/// commands.
+pub struct ConnectionPanelPlugin;
+
+impl Plugin for ConnectionPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConnectionPanelState>().add_systems(
+            Update,
+            (
+                handle_connection_commands,
+                spawn_connection_panel,
+                sync_connection_panel,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Whole seconds until `until_ms`, rounded up so the countdown reads "1s"
+/// until the attempt fires.
+fn secs_until(until_ms: u64, now_ms: u64) -> u64 {
+    until_ms.saturating_sub(now_ms).div_ceil(1000)
+}
+
+/// The state line and the error worth showing for `status`, as of `now_ms`.
+fn describe(status: Option<&ConnectionStatus>, now_ms: u64) -> (String, Option<String>) {
+    let Some(status) = status else {
+        return ("starting".to_string(), None);
+    };
+    match status {
+        ConnectionStatus::Idle => ("idle".to_string(), None),
+        ConnectionStatus::Connecting {
+            attempt,
+            last_error,
+        } => (
+            format!("connecting (attempt {attempt})"),
+            last_error.clone(),
+        ),
+        ConnectionStatus::Connected { kernel_id, .. } => {
+            (format!("connected to kernel {}", kernel_id.short()), None)
+        }
+        ConnectionStatus::Closing { cause } => ("closing".to_string(), Some(cause.clone())),
+        ConnectionStatus::Cooldown {
+            next_attempt,
+            until_ms,
+            backoff_ms,
+            last_error,
+        } => (
+            format!(
+                "waiting — attempt {next_attempt} in {}s (backoff {}s)",
+                secs_until(*until_ms, now_ms),
+                backoff_ms.div_ceil(1000)
+            ),
+            Some(last_error.clone()),
+        ),
+        ConnectionStatus::Terminal { reason } => (
+            "gave up — :retry to try again".to_string(),
+            Some(reason.clone()),
+        ),
+    }
+}
+
+/// Spawn a fresh actor generation on the current `ssh_config`, as a
+/// context switch does.
+fn respawn_actor(bootstrap: &BootstrapChannel, conn_state: &RpcConnectionState) {
+    let _ = bootstrap.tx.send(BootstrapCommand::SpawnActor {
+        config: conn_state.ssh_config.clone(),
+        kernel_id: None,
+        context_id: None,
+        instance: uuid::Uuid::new_v4().to_string(),
+    });
+}
+
+fn handle_connection_commands(
+    mut requests: MessageReader<ConnectionCommandRequested>,
+    mut panel: ResMut<ConnectionPanelState>,
+    mut conn_state: ResMut<RpcConnectionState>,
+    mut toasts: ResMut<Toasts>,
+    actor: Option<Res<RpcActor>>,
+    bootstrap: Res<BootstrapChannel>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs_f64();
+    for ConnectionCommandRequested(command) in requests.read() {
+        match command {
+            ConnectionCommand::TogglePanel => panel.panel_open = !panel.panel_open,
+            ConnectionCommand::Retry => {
+                if matches!(conn_state.status, Some(ConnectionStatus::Terminal { .. })) {
+                    respawn_actor(&bootstrap, &conn_state);
+                    toasts.push(ToastSeverity::Info, "Retrying", "", now);
+                    continue;
+                }
+                let Some(actor) = &actor else {
+                    continue;
+                };
+                let handle = actor.handle.clone();
+                bevy::tasks::IoTaskPool::get()
+                    .spawn(async move {
+                        match handle.retry_now().await {
+                            Ok(true) => log::info!("retry_now: dialing"),
+                            Ok(false) => log::info!("retry_now: nothing to skip"),
+                            Err(e) => log::warn!("retry_now failed: {e}"),
+                        }
+                    })
+                    .detach();
+            }
+            ConnectionCommand::Connect { host, port } => {
+                conn_state.ssh_config.host = host.clone();
+                if let Some(port) = port {
+                    conn_state.ssh_config.port = *port;
+                }
+                conn_state.kernel_id = None;
+                conn_state.context_id = None;
+                respawn_actor(&bootstrap, &conn_state);
+                toasts.push(
+                    ToastSeverity::Info,
+                    "Changing server",
+                    format!(
+                        "{}:{}",
+                        conn_state.ssh_config.host, conn_state.ssh_config.port
+                    ),
+                    now,
+                );
+                panel.panel_open = true;
+            }
+        }
+    }
+}
+
+/// Spawn the (hidden) panel once.
+fn spawn_connection_panel(
+    mut commands: Commands,
+    existing: Query<(), With<ConnectionPanel>>,
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+) {
+    if !existing.is_empty() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/CascadiaCodeNF.ttf");
+    let text_font = TextFont {
+        font,
+        font_size: 13.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            ConnectionPanel,
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                top: Val::Px(48.0),
+                left: Val::Px(16.0),
+                width: Val::Px(480.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(theme.panel_bg),
+            BorderColor::all(theme.border),
+            GlobalZIndex(crate::constants::ZLayer::TOAST),
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new("Connection  (:connection closes)"),
+                text_font.clone(),
+                TextColor(theme.fg_dim),
+            ));
+            panel.spawn((
+                ConnectionPanelBody,
+                Text::new(""),
+                text_font.clone(),
+                TextColor(theme.fg),
+            ));
+            panel.spawn((
+                Text::new(":retry  retry now    :connect <host>[:<port>]  change server"),
+                text_font,
+                TextColor(theme.fg_dim),
+            ));
+        });
+}
+
+/// Mirror the connection state into the panel. Runs every frame while open
+/// so the countdown ticks; the text is only written when it changes.
+fn sync_connection_panel(
+    panel_state: Res<ConnectionPanelState>,
+    conn_state: Res<RpcConnectionState>,
+    theme: Res<Theme>,
+    mut panel: Query<&mut Node, With<ConnectionPanel>>,
+    mut body: Query<(&mut Text, &mut TextColor), With<ConnectionPanelBody>>,
+) {
+    let Ok(mut node) = panel.single_mut() else {
+        return;
+    };
+    let display = if panel_state.panel_open {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    if node.display != display {
+        node.display = display;
+    }
+    if !panel_state.panel_open {
+        return;
+    }
+    let Ok((mut text, mut color)) = body.single_mut() else {
+        return;
+    };
+
+    let config = &conn_state.ssh_config;
+    let (state, error) = describe(conn_state.status.as_ref(), now_ms());
+    let mut lines = format!(
+        "server  {}@{}:{}\nstate   {state}",
+        config.username, config.host, config.port
+    );
+    if let Some(error) = &error {
+        lines.push_str(&format!("\nerror   {error}"));
+    }
+    if text.0 != lines {
+        text.0 = lines;
+    }
+
+    let tint = match conn_state.status {
+        Some(ConnectionStatus::Connected { .. }) => theme.success,
+        Some(ConnectionStatus::Terminal { .. }) => theme.error,
+        Some(ConnectionStatus::Cooldown { .. }) => theme.warning,
+        _ => theme.fg,
+    };
+    if color.0 != tint {
+        color.0 = tint;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_connection_commands() {
+        assert_eq!(
+            parse_connection_command(" :connection "),
+            Some(ConnectionCommand::TogglePanel)
+        );
+        assert_eq!(
+            parse_connection_command(":retry"),
+            Some(ConnectionCommand::Retry)
+        );
+        assert_eq!(
+            parse_connection_command(":connect kj.example.org:2222"),
+            Some(ConnectionCommand::Connect {
+                host: "kj.example.org".into(),
+                port: Some(2222),
+            })
+        );
+        assert_eq!(
+            parse_connection_command(":connect localhost"),
+            Some(ConnectionCommand::Connect {
+                host: "localhost".into(),
+                port: None,
+            })
+        );
+        for not_one in [":connect", ":connect host:port", ":connect :22", "retry"] {
+            assert_eq!(parse_connection_command(not_one), None, "{not_one:?}");
+        }
+    }
+
+    #[test]
+    fn cooldown_counts_down_to_the_next_attempt() {
+        let status = ConnectionStatus::Cooldown {
+            next_attempt: 3,
+            until_ms: 10_500,
+            backoff_ms: 4_000,
+            last_error: "connection refused".into(),
+        };
+        let (state, error) = describe(Some(&status), 8_000);
+        assert_eq!(state, "waiting — attempt 3 in 3s (backoff 4s)");
+        assert_eq!(error.as_deref(), Some("connection refused"));
+
+        let (state, _) = describe(Some(&status), 11_000);
+        assert!(state.contains("in 0s"), "{state}");
+    }
+}
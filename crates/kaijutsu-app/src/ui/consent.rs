@@ -0,0 +1,554 @@
+//! Consent audit log — the North dock's consent panel.
+//!
+//! Polls `listConsentLog` (on connect, then every [`CONSENT_POLL_INTERVAL`]
+//! seconds) for entries past the last one seen, with `verify` set so the
+//! panel also shows whether the kernel's hash chain and signatures still
+//! check out. The newest [`RECENT_ENTRIES`] decisions are kept for display.
+//!
+//! Consent prompts — tool calls the kernel holds for this principal's
+//! approval (`consent.toml`) — open a modal over everything else, oldest
+//! first. It shows the tool, the context and a preview of the call (the
+//! command, or a diff for an edit), and takes the focus into `Dialog`:
+//! Tab / j / k pick between approve, approve for session and deny, Enter
+//! answers, Escape denies. Open prompts are re-read on connect, whenever a
+//! `ConsentRequest` inbox item arrives, and every
+//! [`CONSENT_PROMPT_POLL_INTERVAL`] seconds so expired ones drop away.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use kaijutsu_types::{
+    ConsentDecision, ConsentEntry, ConsentPrompt, ConsentVerdict, ConsentVerification, InboxKind,
+};
+
+use crate::connection::{
+    RpcActor, RpcConnectionState, RpcResultChannel, RpcResultMessage, ServerEventMessage,
+};
+use crate::input::action::Action;
+use crate::input::context::InputContext;
+use crate::input::events::ActionFired;
+use crate::input::focus::FocusArea;
+use crate::ui::theme::Theme;
+use crate::ui::toast::{ToastSeverity, Toasts};
+
+/// How often to re-read the log (seconds).
+const CONSENT_POLL_INTERVAL: f64 = 30.0;
+
+/// Decisions kept for the panel.
+const RECENT_ENTRIES: usize = 50;
+
+/// The kernel's consent log, as far as the app has read it.
+#[derive(Resource, Default)]
+pub struct ConsentLogState {
+    /// Entries in the whole log.
+    pub total: u64,
+    /// Newest decisions, oldest first.
+    pub recent: VecDeque<ConsentEntry>,
+    /// Result of the last verification, once one has come back.
+    pub verification: Option<ConsentVerification>,
+    /// Last poll timestamp (from `Time::elapsed_secs_f64()`); `None` forces
+    /// an immediate poll (startup, reconnect).
+    last_poll: Option<f64>,
+}
+
+impl ConsentLogState {
+    /// Seq of the newest entry read, the cursor for the next poll.
+    fn last_seq(&self) -> u64 {
+        self.recent.back().map_or(0, |e| e.seq)
+    }
+
+    /// Denials among the recent decisions.
+    pub fn recent_denials(&self) -> usize {
+        self.recent
+            .iter()
+            .filter(|e| e.verdict == ConsentVerdict::Denied)
+            .count()
+    }
+}
+
+/// Plugin for consent log polling.
+pub struct ConsentLogPlugin;
+
+impl Plugin for ConsentLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsentLogState>()
+            .add_systems(Update, (poll_consent_log, update_consent_log).chain());
+    }
+}
+
+/// Fetch new entries on connect and every `CONSENT_POLL_INTERVAL` seconds.
+fn poll_consent_log(
+    actor: Option<Res<RpcActor>>,
+    conn_state: Res<RpcConnectionState>,
+    mut state: ResMut<ConsentLogState>,
+    time: Res<Time>,
+    result_channel: Res<RpcResultChannel>,
+) {
+    let Some(actor) = actor else { return };
+    if conn_state.is_changed() {
+        // A reconnect may be to another kernel: start the cursor over.
+        state.last_poll = None;
+        state.recent.clear();
+    }
+    if !conn_state.connected {
+        return;
+    }
+
+    let elapsed = time.elapsed_secs_f64();
+    if state
+        .last_poll
+        .is_some_and(|last| elapsed - last < CONSENT_POLL_INTERVAL)
+    {
+        return;
+    }
+    state.last_poll = Some(elapsed);
+
+    let after_seq = state.last_seq();
+    let handle = actor.handle.clone();
+    let tx = result_channel.sender();
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            match handle.list_consent_log(after_seq, 0, true).await {
+                Ok(page) => {
+                    let _ = tx.send(RpcResultMessage::ConsentLogReceived { after_seq, page });
+                }
+                Err(e) => log::debug!("consent poll: list_consent_log failed: {e}"),
+            }
+        })
+        .detach();
+}
+
+/// Drain `ConsentLogReceived` into `ConsentLogState`.
+fn update_consent_log(
+    mut state: ResMut<ConsentLogState>,
+    mut events: MessageReader<RpcResultMessage>,
+) {
+    for event in events.read() {
+        let RpcResultMessage::ConsentLogReceived { after_seq, page } = event else {
+            continue;
+        };
+        // A page for a cursor we've since moved past (or reset) is stale.
+        if *after_seq != state.last_seq() {
+            continue;
+        }
+        if let Some(v) = &page.verification
+            && let Some((seq, reason)) = &v.broken_at
+        {
+            log::warn!("consent log fails verification at #{seq}: {reason}");
+        }
+        state.total = page.total;
+        state.verification = page.verification.clone();
+        state.recent.extend(page.entries.iter().cloned());
+        while state.recent.len() > RECENT_ENTRIES {
+            state.recent.pop_front();
+        }
+        // More behind this page: catch up on the next frame, not in 30s.
+        if state.last_seq() < page.total {
+            state.last_poll = None;
+        }
+    }
+}
+
+/// How often to re-read the open prompts (seconds).
+const CONSENT_PROMPT_POLL_INTERVAL: f64 = 10.0;
+
+/// Preview lines the modal shows; the rest is summarised as a count.
+const PREVIEW_ROWS: usize = 24;
+
+/// The answers, in the order Tab walks them.
+const CHOICES: [ConsentDecision; 3] = [
+    ConsentDecision::Approve,
+    ConsentDecision::ApproveForSession,
+    ConsentDecision::Deny,
+];
+
+/// Tool calls waiting on this principal, and the modal's state.
+#[derive(Resource, Default)]
+pub struct ConsentPromptState {
+    /// Open prompts, oldest first; the modal shows the first.
+    pub prompts: Vec<ConsentPrompt>,
+    /// Index into [`CHOICES`] of the highlighted answer.
+    choice: usize,
+    /// Focus to go back to when the last prompt is answered; `Some` while
+    /// the modal holds the focus.
+    prior_focus: Option<FocusArea>,
+    /// Last poll timestamp; `None` forces an immediate poll.
+    last_poll: Option<f64>,
+}
+
+/// The modal.
+#[derive(Component)]
+struct ConsentPromptPanel;
+
+/// Tool, context and time left.
+#[derive(Component)]
+struct ConsentPromptHeader;
+
+/// One preview line, by row.
+#[derive(Component)]
+struct ConsentPromptRow(usize);
+
+/// The answers, highlighted one bracketed.
+#[derive(Component)]
+struct ConsentPromptChoices;
+
+/// Plugin for the consent prompt modal.
+pub struct ConsentPromptPlugin;
+
+impl Plugin for ConsentPromptPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsentPromptState>().add_systems(
+            Update,
+            (
+                poll_consent_prompts,
+                update_consent_prompts,
+                handle_consent_prompt_actions,
+                hold_consent_focus,
+                spawn_consent_prompt_panel,
+                sync_consent_prompt_panel,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn choice_label(decision: ConsentDecision) -> &'static str {
+    match decision {
+        ConsentDecision::Approve => "approve",
+        ConsentDecision::ApproveForSession => "approve for session",
+        ConsentDecision::Deny => "deny",
+    }
+}
+
+/// The answer line, with the highlighted answer bracketed.
+fn choices_line(choice: usize) -> String {
+    CHOICES
+        .iter()
+        .enumerate()
+        .map(|(i, decision)| {
+            if i == choice {
+                format!("[ {} ]", choice_label(*decision))
+            } else {
+                format!("  {}  ", choice_label(*decision))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Color for one preview line: diff headers, additions and removals stand
+/// out; anything else (a command) is plain.
+fn preview_line_color(line: &str, theme: &Theme) -> Color {
+    if line.starts_with("---") || line.starts_with("+++") {
+        theme.accent
+    } else if line.starts_with('+') {
+        theme.success
+    } else if line.starts_with('-') {
+        theme.error
+    } else {
+        theme.fg
+    }
+}
+
+/// Re-read open prompts on connect, on each `ConsentRequest` push, and every
+/// `CONSENT_PROMPT_POLL_INTERVAL` seconds.
+fn poll_consent_prompts(
+    actor: Option<Res<RpcActor>>,
+    conn_state: Res<RpcConnectionState>,
+    mut state: ResMut<ConsentPromptState>,
+    mut pushes: MessageReader<ServerEventMessage>,
+    time: Res<Time>,
+    result_channel: Res<RpcResultChannel>,
+) {
+    for ServerEventMessage(event) in pushes.read() {
+        if let kaijutsu_client::ServerEvent::InboxItem { item } = event
+            && item.kind == InboxKind::ConsentRequest
+        {
+            state.last_poll = None;
+        }
+    }
+    let Some(actor) = actor else { return };
+    if conn_state.is_changed() {
+        state.last_poll = None;
+    }
+    if !conn_state.connected {
+        return;
+    }
+
+    let elapsed = time.elapsed_secs_f64();
+    if state
+        .last_poll
+        .is_some_and(|last| elapsed - last < CONSENT_PROMPT_POLL_INTERVAL)
+    {
+        return;
+    }
+    state.last_poll = Some(elapsed);
+
+    let handle = actor.handle.clone();
+    let tx = result_channel.sender();
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            match handle.list_consent_prompts().await {
+                Ok(prompts) => {
+                    let _ = tx.send(RpcResultMessage::ConsentPromptsReceived { prompts });
+                }
+                Err(e) => log::debug!("consent prompts: list_consent_prompts failed: {e}"),
+            }
+        })
+        .detach();
+}
+
+/// Drain `ConsentPromptsReceived` into `ConsentPromptState`.
+fn update_consent_prompts(
+    mut state: ResMut<ConsentPromptState>,
+    mut events: MessageReader<RpcResultMessage>,
+) {
+    for event in events.read() {
+        let RpcResultMessage::ConsentPromptsReceived { prompts } = event else {
+            continue;
+        };
+        let shown = state.prompts.first().map(|p| p.id);
+        state.prompts = prompts.clone();
+        if state.prompts.first().map(|p| p.id) != shown {
+            state.choice = 0;
+        }
+    }
+}
+
+/// Walk the answers, and send the chosen one (Enter) or a denial (Escape)
+/// for the prompt on show. Only actions fired under the `Dialog` context
+/// count, so the modal answers from the conversation screen.
+fn handle_consent_prompt_actions(
+    mut actions: MessageReader<ActionFired>,
+    mut state: ResMut<ConsentPromptState>,
+    actor: Option<Res<RpcActor>>,
+    mut toasts: ResMut<Toasts>,
+    time: Res<Time>,
+) {
+    for ActionFired { action, context } in actions.read() {
+        if *context != InputContext::Dialog || state.prompts.is_empty() {
+            continue;
+        }
+        let decision = match action {
+            Action::CycleFocusForward | Action::FocusNextBlock => {
+                state.choice = (state.choice + 1) % CHOICES.len();
+                continue;
+            }
+            Action::CycleFocusBackward | Action::FocusPrevBlock => {
+                state.choice = (state.choice + CHOICES.len() - 1) % CHOICES.len();
+                continue;
+            }
+            Action::Activate => CHOICES[state.choice],
+            Action::PopLevel => ConsentDecision::Deny,
+            _ => continue,
+        };
+        let prompt = state.prompts.remove(0);
+        state.choice = 0;
+        let Some(actor) = &actor else {
+            toasts.push(
+                ToastSeverity::Error,
+                "Consent not sent",
+                "not connected",
+                time.elapsed_secs_f64(),
+            );
+            continue;
+        };
+        let handle = actor.handle.clone();
+        bevy::tasks::IoTaskPool::get()
+            .spawn(async move {
+                if let Err(e) = handle.consent_respond(prompt.id, decision).await {
+                    log::warn!("consent prompt #{}: {decision} failed: {e}", prompt.id);
+                }
+            })
+            .detach();
+    }
+}
+
+/// Take the focus into `Dialog` while a prompt is open, and give it back
+/// once none are.
+fn hold_consent_focus(mut state: ResMut<ConsentPromptState>, mut focus: ResMut<FocusArea>) {
+    match (state.prompts.is_empty(), state.prior_focus.is_some()) {
+        (false, false) => {
+            state.prior_focus = Some(focus.clone());
+            *focus = FocusArea::Dialog;
+        }
+        (true, true) => {
+            if let Some(prior) = state.prior_focus.take() {
+                *focus = prior;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Spawn the (hidden) modal once.
+fn spawn_consent_prompt_panel(
+    mut commands: Commands,
+    existing: Query<(), With<ConsentPromptPanel>>,
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+) {
+    if !existing.is_empty() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/CascadiaCodeNF.ttf");
+    let text_font = TextFont {
+        font,
+        font_size: 13.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            ConsentPromptPanel,
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                top: Val::Percent(15.0),
+                left: Val::Percent(20.0),
+                width: Val::Percent(60.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(2.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(theme.panel_bg),
+            BorderColor::all(theme.warning),
+            GlobalZIndex(crate::constants::ZLayer::TOAST),
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                ConsentPromptHeader,
+                Text::new(""),
+                text_font.clone(),
+                TextColor(theme.warning),
+            ));
+            for row in 0..=PREVIEW_ROWS {
+                panel.spawn((
+                    ConsentPromptRow(row),
+                    Text::new(""),
+                    text_font.clone(),
+                    TextColor(theme.fg),
+                ));
+            }
+            panel.spawn((
+                ConsentPromptChoices,
+                Text::new(""),
+                text_font.clone(),
+                TextColor(theme.fg),
+            ));
+            panel.spawn((
+                Text::new("Tab/j/k: choose    Enter: answer    Esc: deny"),
+                text_font,
+                TextColor(theme.fg_dim),
+            ));
+        });
+}
+
+/// Mirror the oldest open prompt into the modal. Runs every frame while
+/// open so the time left ticks; text is only written when it changes.
+#[allow(clippy::type_complexity)]
+fn sync_consent_prompt_panel(
+    state: Res<ConsentPromptState>,
+    theme: Res<Theme>,
+    mut panel: Query<&mut Node, With<ConsentPromptPanel>>,
+    mut header: Query<&mut Text, (With<ConsentPromptHeader>, Without<ConsentPromptRow>)>,
+    mut rows: Query<
+        (&ConsentPromptRow, &mut Text, &mut TextColor, &mut Node),
+        Without<ConsentPromptPanel>,
+    >,
+    mut choices: Query<
+        &mut Text,
+        (
+            With<ConsentPromptChoices>,
+            Without<ConsentPromptHeader>,
+            Without<ConsentPromptRow>,
+        ),
+    >,
+) {
+    let Ok(mut node) = panel.single_mut() else {
+        return;
+    };
+    let prompt = state.prompts.first();
+    let display = if prompt.is_some() {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    if node.display != display {
+        node.display = display;
+    }
+    let Some(prompt) = prompt else {
+        return;
+    };
+
+    let secs_left = prompt
+        .expires_at
+        .saturating_sub(kaijutsu_types::now_millis())
+        .div_ceil(1000);
+    let mut title = format!(
+        "Approve `{}` in @{}?  ({secs_left}s left)",
+        prompt.tool,
+        prompt.context_id.short()
+    );
+    if state.prompts.len() > 1 {
+        title.push_str(&format!("  +{} more", state.prompts.len() - 1));
+    }
+    if let Ok(mut text) = header.single_mut()
+        && text.0 != title
+    {
+        text.0 = title;
+    }
+
+    let lines: Vec<&str> = prompt.preview.lines().collect();
+    for (row, mut text, mut color, mut row_node) in rows.iter_mut() {
+        let (line, tint) = match lines.get(row.0) {
+            Some(line) if row.0 < PREVIEW_ROWS => {
+                (line.to_string(), preview_line_color(line, &theme))
+            }
+            _ if row.0 == PREVIEW_ROWS && lines.len() > PREVIEW_ROWS => (
+                format!("… {} more lines", lines.len() - PREVIEW_ROWS),
+                theme.fg_dim,
+            ),
+            _ => (String::new(), theme.fg),
+        };
+        let row_display = if line.is_empty() && row.0 >= lines.len() {
+            Display::None
+        } else {
+            Display::Flex
+        };
+        if row_node.display != row_display {
+            row_node.display = row_display;
+        }
+        if text.0 != line {
+            text.0 = line;
+        }
+        if color.0 != tint {
+            color.0 = tint;
+        }
+    }
+
+    let answers = choices_line(state.choice);
+    if let Ok(mut text) = choices.single_mut()
+        && text.0 != answers
+    {
+        text.0 = answers;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices_bracket_the_highlighted_answer() {
+        assert_eq!(
+            choices_line(0),
+            "[ approve ]    approve for session      deny  "
+        );
+        assert!(choices_line(2).ends_with("[ deny ]"));
+    }
+}
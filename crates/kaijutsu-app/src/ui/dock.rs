@@ -90,6 +90,11 @@ pub struct DockState {
     // North dock
     pub title: DockText,
     pub event_pulse: DockText,
+    /// Unread inbox badge; empty when there is nothing unread.
+    pub inbox: DockText,
+    /// Consent log badge: decisions recorded, denials, and a broken-chain
+    /// warning; empty until the log has an entry.
+    pub consent: DockText,
     pub connection: DockText,
 
     // North dock sparklines
@@ -118,6 +123,16 @@ impl Default for DockState {
                 color: Color::WHITE,
                 font_size: 13.0,
             },
+            inbox: DockText {
+                text: String::new(),
+                color: Color::WHITE,
+                font_size: 16.0,
+            },
+            consent: DockText {
+                text: String::new(),
+                color: Color::WHITE,
+                font_size: 13.0,
+            },
             connection: DockText {
                 text: "Connecting...".into(),
                 color: Color::WHITE,
@@ -404,7 +419,7 @@ pub fn render_north_dock(
         &title_brush,
     );
 
-    // Right group: sparklines + pulse + gap + connection (right-aligned)
+    // Right group: sparklines + pulse + gap + consent + inbox + connection (right-aligned)
     let gap = 12.0_f64;
     let conn_brush = bevy_color_to_brush(dock_state.connection.color);
     let conn_w = measure_text(
@@ -413,6 +428,18 @@ pub fn render_north_dock(
         font,
     );
 
+    let inbox_brush = bevy_color_to_brush(dock_state.inbox.color);
+    let inbox_w = measure_text(&dock_state.inbox.text, dock_state.inbox.font_size, font);
+    let inbox_span = if inbox_w > 0.0 { inbox_w + gap } else { 0.0 };
+
+    let consent_brush = bevy_color_to_brush(dock_state.consent.color);
+    let consent_w = measure_text(
+        &dock_state.consent.text,
+        dock_state.consent.font_size,
+        font,
+    );
+    let consent_span = if consent_w > 0.0 { consent_w + gap } else { 0.0 };
+
     let pulse_brush = bevy_color_to_brush(dock_state.event_pulse.color);
     let pulse_w = measure_text(
         &dock_state.event_pulse.text,
@@ -426,7 +453,7 @@ pub fn render_north_dock(
     let spark_gap = 8.0_f64;
     let sparks_total = spark_w + spark_gap + spark_w + gap;
 
-    let right_total = sparks_total + pulse_w + gap + conn_w;
+    let right_total = sparks_total + pulse_w + gap + consent_span + inbox_span + conn_w;
     let right_x = (width - pad_h - right_total).max(pad_h);
 
     // Draw sparklines
@@ -468,8 +495,28 @@ pub fn render_north_dock(
 
     draw_dock_text(
         &mut scene,
-        &dock_state.connection.text,
+        &dock_state.consent.text,
         text_right_x + pulse_w + gap,
+        pad_v + 4.0,
+        dock_state.consent.font_size,
+        font,
+        &consent_brush,
+    );
+
+    draw_dock_text(
+        &mut scene,
+        &dock_state.inbox.text,
+        text_right_x + pulse_w + gap + consent_span,
+        pad_v,
+        dock_state.inbox.font_size,
+        font,
+        &inbox_brush,
+    );
+
+    draw_dock_text(
+        &mut scene,
+        &dock_state.connection.text,
+        text_right_x + pulse_w + gap + consent_span + inbox_span,
         pad_v,
         dock_state.connection.font_size,
         font,
@@ -914,6 +961,48 @@ mod tests {
     }
 }
 
+/// Update the inbox badge when the unread count changes.
+pub fn update_inbox(
+    inbox: Res<crate::ui::inbox::InboxState>,
+    theme: Res<Theme>,
+    mut dock: ResMut<DockState>,
+) {
+    if !inbox.is_changed() && !theme.is_changed() {
+        return;
+    }
+    dock.inbox.text = match inbox.unacked {
+        0 => String::new(),
+        n => format!("\u{2709} {n}"),
+    };
+    dock.inbox.color = theme.warning;
+}
+
+/// Update the consent badge when the log or its verification changes.
+pub fn update_consent(
+    consent: Res<crate::ui::consent::ConsentLogState>,
+    theme: Res<Theme>,
+    mut dock: ResMut<DockState>,
+) {
+    if !consent.is_changed() && !theme.is_changed() {
+        return;
+    }
+    if let Some((seq, _)) = consent
+        .verification
+        .as_ref()
+        .and_then(|v| v.broken_at.as_ref())
+    {
+        dock.consent.text = format!("\u{2696} broken at #{seq}");
+        dock.consent.color = theme.error;
+        return;
+    }
+    dock.consent.text = match (consent.total, consent.recent_denials()) {
+        (0, _) => String::new(),
+        (n, 0) => format!("\u{2696} {n}"),
+        (n, d) => format!("\u{2696} {n} \u{2717}{d}"),
+    };
+    dock.consent.color = theme.fg_dim;
+}
+
 /// Update contexts widget when DriftState or DocumentCache changes.
 pub fn update_contexts(
     drift_state: Res<DriftState>,
@@ -1236,6 +1325,8 @@ impl Plugin for DockPlugin {
                 (
                     update_mode,
                     update_connection,
+                    update_inbox,
+                    update_consent,
                     update_contexts,
                     update_hints,
                     update_event_pulse,
@@ -78,6 +78,7 @@ impl Plugin for DriftPlugin {
                 poll_drift_state,
                 update_drift_state,
                 detect_drift_arrival,
+                apply_model_changes,
                 dismiss_stale_notifications,
             )
                 .chain(),
@@ -198,6 +199,34 @@ fn detect_drift_arrival(
     }
 }
 
+/// Relabel a context as soon as any seat switches its model
+/// (`ServerEvent::ContextModelChanged`), instead of waiting for the next poll.
+fn apply_model_changes(
+    mut drift_state: ResMut<DriftState>,
+    mut events: MessageReader<ServerEventMessage>,
+) {
+    for ServerEventMessage(event) in events.read() {
+        if let kaijutsu_client::ServerEvent::ContextModelChanged {
+            context_id,
+            provider,
+            model,
+            ..
+        } = event
+            && let Some(ctx) = drift_state
+                .contexts
+                .iter_mut()
+                .find(|c| c.id == *context_id)
+        {
+            ctx.provider = provider.clone();
+            ctx.model = model.clone();
+            log::info!(
+                "DriftState: {} model → {provider}/{model}",
+                context_id.short()
+            );
+        }
+    }
+}
+
 /// Auto-dismiss stale notifications after NOTIFICATION_DURATION.
 fn dismiss_stale_notifications(mut drift_state: ResMut<DriftState>, time: Res<Time>) {
     if let Some(ref notif) = drift_state.notification
@@ -0,0 +1,101 @@
+//! Inbox state — the unread-notification count behind the North dock badge.
+//!
+//! Seeded by polling `listInbox` (on connect, then every
+//! [`INBOX_POLL_INTERVAL`] seconds to pick up acks made elsewhere) and bumped
+//! live by `ServerEvent::InboxItem` pushes in between.
+
+use bevy::prelude::*;
+
+use kaijutsu_types::InboxItem;
+
+use crate::connection::{
+    RpcActor, RpcConnectionState, RpcResultChannel, RpcResultMessage, ServerEventMessage,
+};
+
+/// How often to re-read the unread count (seconds).
+const INBOX_POLL_INTERVAL: f64 = 30.0;
+
+/// Unread notifications for the connected principal.
+#[derive(Resource, Default)]
+pub struct InboxState {
+    pub unacked: u64,
+    /// The newest item seen, for a hover/summary line.
+    pub latest: Option<InboxItem>,
+    /// Last poll timestamp (from `Time::elapsed_secs_f64()`); `None` forces
+    /// an immediate poll (startup, reconnect).
+    last_poll: Option<f64>,
+}
+
+/// Plugin for inbox polling and live updates.
+pub struct InboxPlugin;
+
+impl Plugin for InboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InboxState>().add_systems(
+            Update,
+            (poll_inbox, update_inbox_state, observe_inbox_items).chain(),
+        );
+    }
+}
+
+/// Fetch the unread count on connect and every `INBOX_POLL_INTERVAL` seconds.
+fn poll_inbox(
+    actor: Option<Res<RpcActor>>,
+    conn_state: Res<RpcConnectionState>,
+    mut inbox: ResMut<InboxState>,
+    time: Res<Time>,
+    result_channel: Res<RpcResultChannel>,
+) {
+    let Some(actor) = actor else { return };
+    if conn_state.is_changed() {
+        inbox.last_poll = None;
+    }
+    if !conn_state.connected {
+        return;
+    }
+
+    let elapsed = time.elapsed_secs_f64();
+    if inbox
+        .last_poll
+        .is_some_and(|last| elapsed - last < INBOX_POLL_INTERVAL)
+    {
+        return;
+    }
+    inbox.last_poll = Some(elapsed);
+
+    let handle = actor.handle.clone();
+    let tx = result_channel.sender();
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            match handle.list_inbox(false, 1).await {
+                Ok(page) => {
+                    let _ = tx.send(RpcResultMessage::InboxReceived { page });
+                }
+                Err(e) => log::debug!("inbox poll: list_inbox failed: {e}"),
+            }
+        })
+        .detach();
+}
+
+/// Drain `InboxReceived` into `InboxState`.
+fn update_inbox_state(mut inbox: ResMut<InboxState>, mut events: MessageReader<RpcResultMessage>) {
+    for event in events.read() {
+        if let RpcResultMessage::InboxReceived { page } = event {
+            inbox.unacked = page.unacked;
+            if let Some(item) = page.items.first() {
+                inbox.latest = Some(item.clone());
+            }
+        }
+    }
+}
+
+/// Count pushed items as they arrive.
+fn observe_inbox_items(mut inbox: ResMut<InboxState>, mut events: MessageReader<ServerEventMessage>) {
+    for ServerEventMessage(event) in events.read() {
+        if let kaijutsu_client::ServerEvent::InboxItem { item } = event {
+            log::info!("inbox: {} — {}", item.kind, item.summary);
+            inbox.unacked += 1;
+            inbox.latest = Some(item.clone());
+        }
+    }
+}
@@ -1,6 +1,11 @@
+pub mod completion;
+pub mod connection_panel;
+pub mod consent;
 pub mod debug;
 pub mod dock;
 pub mod drift;
+pub mod inbox;
+pub mod prefs;
 pub mod screen;
 pub mod state;
 pub mod theme;
@@ -8,3 +13,5 @@ pub mod theme_loader;
 pub mod tiling;
 pub mod tiling_reconciler;
 pub mod timeline;
+pub mod timeline_strip;
+pub mod toast;
@@ -0,0 +1,83 @@
+//! Per-principal preferences, as stored by the kernel.
+//!
+//! Fetched with `getPreferences` each time the connection comes up, so a
+//! change made from another device (or `preference_set` over MCP) is picked
+//! up on the next reconnect. Client-owned settings live under `ui.`; see
+//! `kaijutsu_types::prefs` for the keys the kernel itself understands.
+
+use bevy::prelude::*;
+
+use kaijutsu_types::Preferences;
+
+use crate::connection::{RpcActor, RpcConnectionState, RpcResultChannel, RpcResultMessage};
+
+/// The connected principal's preferences.
+#[derive(Resource, Default)]
+pub struct PreferencesState {
+    pub prefs: Preferences,
+    /// Whether `prefs` reflects the current connection.
+    pub loaded: bool,
+}
+
+impl PreferencesState {
+    /// A client setting by name, without the `ui.` prefix.
+    pub fn ui(&self, name: &str) -> Option<&str> {
+        self.prefs
+            .get(&format!("{}{name}", kaijutsu_types::prefs::PREF_UI_PREFIX))
+            .map(String::as_str)
+    }
+}
+
+/// Plugin for loading preferences on connect.
+pub struct PreferencesPlugin;
+
+impl Plugin for PreferencesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PreferencesState>()
+            .add_systems(Update, (fetch_preferences, update_preferences).chain());
+    }
+}
+
+/// Request the preferences whenever the connection (re)establishes.
+fn fetch_preferences(
+    actor: Option<Res<RpcActor>>,
+    conn_state: Res<RpcConnectionState>,
+    mut prefs: ResMut<PreferencesState>,
+    result_channel: Res<RpcResultChannel>,
+) {
+    let Some(actor) = actor else { return };
+    if !conn_state.is_changed() {
+        return;
+    }
+    prefs.loaded = false;
+    if !conn_state.connected {
+        return;
+    }
+
+    let handle = actor.handle.clone();
+    let tx = result_channel.sender();
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            match handle.get_preferences().await {
+                Ok(prefs) => {
+                    let _ = tx.send(RpcResultMessage::PreferencesReceived { prefs });
+                }
+                Err(e) => log::debug!("prefs: get_preferences failed: {e}"),
+            }
+        })
+        .detach();
+}
+
+/// Drain `PreferencesReceived` into `PreferencesState`.
+fn update_preferences(
+    mut state: ResMut<PreferencesState>,
+    mut events: MessageReader<RpcResultMessage>,
+) {
+    for event in events.read() {
+        if let RpcResultMessage::PreferencesReceived { prefs } = event {
+            log::info!("prefs: loaded {} preference(s)", prefs.len());
+            state.prefs = prefs.clone();
+            state.loaded = true;
+        }
+    }
+}
@@ -469,6 +469,23 @@ impl TilingTree {
         Some(new_conv_id)
     }
 
+    /// Split `target` and show `document_id` in the new pane, focusing it
+    /// (`:vsplit <context>`). An empty `document_id` leaves the new pane for
+    /// MRU assignment, like a plain split.
+    pub fn split_with_document(
+        &mut self,
+        target: PaneId,
+        direction: SplitDirection,
+        document_id: &str,
+    ) -> Option<PaneId> {
+        let new_pane = self.split(target, direction)?;
+        if !document_id.is_empty() {
+            self.set_conversation_document(new_pane, document_id);
+        }
+        self.focus(new_pane);
+        Some(new_pane)
+    }
+
     /// Close a conversation pane.
     ///
     /// If the parent becomes a single-child split, it collapses.
@@ -848,6 +865,26 @@ impl TilingTree {
 // FREE FUNCTIONS — Tree manipulation helpers
 // ============================================================================
 
+/// Parse a vim-style split command typed at the shell surface:
+/// `:vsplit [context]` / `:vs` (side by side) or `:split [context]` / `:sp`
+/// (stacked). Returns the direction and the context argument (empty when
+/// omitted). Anything else — including text without the leading `:` — is
+/// not a split command and goes to kaish as usual.
+pub fn parse_split_command(text: &str) -> Option<(SplitDirection, String)> {
+    let rest = text.trim().strip_prefix(':')?;
+    let mut words = rest.split_whitespace();
+    let direction = match words.next()? {
+        "vsplit" | "vs" => SplitDirection::Row,
+        "split" | "sp" => SplitDirection::Column,
+        _ => return None,
+    };
+    let context = words.next().unwrap_or_default().to_string();
+    if words.next().is_some() {
+        return None;
+    }
+    Some((direction, context))
+}
+
 /// Normalize ratios so content children (ratio > 0) sum to 1.0.
 ///
 /// Children with 0.0 ratios (auto-sized like compose blocks and docks)
@@ -979,6 +1016,39 @@ mod tests {
 
     // ── Split tests ─────────────────────────────────────────────────
 
+    #[test]
+    fn split_with_document_assigns_and_focuses() {
+        let mut tree = TilingTree::default_layout();
+        let conv1 = tree.first_conversation_pane().unwrap();
+        tree.set_conversation_document(conv1, "parent");
+
+        let conv2 = tree
+            .split_with_document(conv1, SplitDirection::Row, "fork")
+            .unwrap();
+        assert_eq!(tree.focused, conv2);
+        assert_eq!(tree.focused_conversation_document(), Some("fork"));
+        assert_eq!(tree.previous_focused, Some(conv1));
+    }
+
+    #[test]
+    fn parse_split_commands() {
+        assert_eq!(
+            parse_split_command(":vsplit fork-a"),
+            Some((SplitDirection::Row, "fork-a".to_string()))
+        );
+        assert_eq!(
+            parse_split_command("  :sp 0193ab  "),
+            Some((SplitDirection::Column, "0193ab".to_string()))
+        );
+        assert_eq!(
+            parse_split_command(":vs"),
+            Some((SplitDirection::Row, String::new()))
+        );
+        assert_eq!(parse_split_command("vsplit fork-a"), None);
+        assert_eq!(parse_split_command(":vsplit a b"), None);
+        assert_eq!(parse_split_command(":q"), None);
+    }
+
     #[test]
     fn split_row_creates_two_conversations() {
         let mut tree = TilingTree::default_layout();
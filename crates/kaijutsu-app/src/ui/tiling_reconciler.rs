@@ -43,7 +43,7 @@ use crate::cell::ConversationContainer;
 #[derive(Component)]
 pub struct TilingRoot;
 
-/// Marker for the summary text shown in unfocused panes.
+/// Marker for the live preview text shown in unfocused panes.
 ///
 /// Despawned when the pane gains focus (block rendering takes over).
 #[derive(Component)]
@@ -414,6 +414,14 @@ pub fn handle_pane_focus_change(
             break;
         }
     }
+    // A pane created and focused this frame (`:vsplit <context>`) has no
+    // entity yet; its document is already in the tree. It starts following.
+    if incoming_doc_id.is_empty()
+        && let Some(doc_id) = tree.focused_conversation_document()
+    {
+        incoming_doc_id = doc_id.to_string();
+        scroll_state.start_following();
+    }
 
     // ── Fire context switch if document differs (multi-pane only) ────
     if !single_pane && !incoming_doc_id.is_empty() {
@@ -446,97 +454,91 @@ pub fn handle_pane_focus_change(
 }
 
 // ============================================================================
-// UNFOCUSED PANE SUMMARY
+// UNFOCUSED PANE PREVIEW
 // ============================================================================
 
-/// System that shows a summary text in unfocused panes and cleans it up
-/// when the pane gains focus.
+/// Blocks shown in an unfocused pane's preview (the document's tail).
+const PREVIEW_BLOCKS: usize = 40;
+
+/// Characters of each block's content shown in the preview.
+const PREVIEW_BLOCK_CHARS: usize = 600;
+
+/// System that shows a live, read-only preview of the document in each
+/// unfocused pane and cleans it up when the pane gains focus.
 ///
-/// Unfocused panes show:
+/// Block rendering follows the focused pane only, so the other panes of a
+/// split (`:vsplit <context>`) show their document's tail as plain text:
 /// ```text
-/// @context_name
-/// N blocks · model_name
+/// @context_name · N blocks
+///
+/// user ▸ …
+/// model ▸ …
 /// ```
+/// The preview is rebuilt whenever the document cache changes, so a fork
+/// and its parent can be watched side by side. Each pane keeps its own
+/// scroll position for when it is focused again (`PaneSavedState`).
 ///
-/// Focused panes have their summary despawned (block rendering takes over).
+/// Focused panes have their preview despawned (block rendering takes over).
 pub fn sync_unfocused_pane_summaries(
     mut commands: Commands,
     tree: Res<TilingTree>,
     theme: Res<Theme>,
     asset_server: Res<AssetServer>,
     doc_cache: Res<crate::cell::DocumentCache>,
-    conv_containers: Query<
-        (Entity, &PaneMarker, &PaneSavedState, Option<&Children>),
+    mut conv_containers: Query<
+        (
+            Entity,
+            &PaneMarker,
+            &PaneSavedState,
+            Option<&Children>,
+            &mut ScrollPosition,
+        ),
         With<ConversationContainer>,
     >,
-    summaries: Query<Entity, With<UnfocusedPaneSummary>>,
+    mut summaries: Query<(Entity, &mut Text), With<UnfocusedPaneSummary>>,
 ) {
     // Only relevant with multiple panes
     if tree.root.conversation_panes().len() < 2 {
         // Clean up any stale summaries from before close
-        for entity in summaries.iter() {
+        for (entity, _) in summaries.iter() {
             commands.entity(entity).despawn();
         }
         return;
     }
 
-    if !tree.is_changed() {
+    if !tree.is_changed() && !doc_cache.is_changed() {
         return;
     }
 
-    for (entity, marker, saved, children) in conv_containers.iter() {
-        let is_focused = marker.pane_id == tree.focused;
+    for (entity, marker, saved, children, mut scroll) in conv_containers.iter_mut() {
+        let existing = children.and_then(|c| c.iter().find(|child| summaries.contains(*child)));
 
-        if is_focused {
-            // Despawn any summary text children on the focused pane
-            if let Some(children) = children {
-                for child in children.iter() {
-                    if summaries.contains(child) {
-                        commands.entity(child).despawn();
-                    }
-                }
+        if marker.pane_id == tree.focused {
+            // Despawn the preview on the focused pane
+            if let Some(child) = existing {
+                commands.entity(child).despawn();
             }
-        } else {
-            // Check if this pane already has a summary
-            let has_summary = children
-                .map(|c| c.iter().any(|child| summaries.contains(child)))
-                .unwrap_or(false);
-
-            if !has_summary {
-                // Build summary text
-                let doc_id = &saved.document_id;
-                let (context_label, block_count) = if doc_id.is_empty() {
-                    ("No context".to_string(), 0)
-                } else if let Ok(ctx_id) = kaijutsu_types::ContextId::parse(doc_id) {
-                    if let Some(cached) = doc_cache.get(ctx_id) {
-                        let name = if cached.context_name.is_empty() {
-                            short_id(doc_id)
-                        } else {
-                            cached.context_name.clone()
-                        };
-                        let count = cached.synced.block_count();
-                        (name, count)
-                    } else {
-                        (short_id(doc_id), 0)
-                    }
-                } else {
-                    (short_id(doc_id), 0)
-                };
-
-                let summary_text = if block_count > 0 {
-                    format!("@{}\n{} blocks", context_label, block_count)
-                } else {
-                    format!("@{}", context_label)
-                };
+            continue;
+        }
 
-                // The unfocused-pane summary is the one surface rendered with
+        let preview = pane_preview_text(&saved.document_id, &doc_cache);
+        match existing {
+            Some(child) => {
+                if let Ok((_, mut text)) = summaries.get_mut(child)
+                    && text.0 != preview
+                {
+                    text.0 = preview;
+                }
+            }
+            None => {
+                // The unfocused-pane preview is the one surface rendered with
                 // Bevy's native text pipeline rather than the MSDF/Vello path —
-                // a deliberate pragmatic choice for a dimmed, secondary label.
+                // a deliberate pragmatic choice for a dimmed, secondary view.
                 // Our bundled mono font keeps it visually consistent.
                 let summary_entity = commands
                     .spawn((
                         UnfocusedPaneSummary,
-                        Text::new(summary_text),
+                        Text::new(preview),
                         TextFont {
                             font: asset_server.load("fonts/CascadiaCodeNF.ttf"),
                             font_size: 14.0,
@@ -554,7 +556,42 @@ pub fn sync_unfocused_pane_summaries(
                 commands.entity(entity).add_child(summary_entity);
             }
         }
+        // Keep the tail in view; layout clamps this to the content height.
+        scroll.y = f32::MAX;
+    }
+}
+
+/// The preview text for a pane showing `doc_id`: a header line, then the
+/// last [`PREVIEW_BLOCKS`] blocks, each clipped to [`PREVIEW_BLOCK_CHARS`].
+fn pane_preview_text(doc_id: &str, doc_cache: &crate::cell::DocumentCache) -> String {
+    if doc_id.is_empty() {
+        return "No context".to_string();
+    }
+    let Some(cached) = kaijutsu_types::ContextId::parse(doc_id)
+        .ok()
+        .and_then(|id| doc_cache.get(id))
+    else {
+        return format!("@{}", short_id(doc_id));
+    };
+
+    let name = if cached.context_name.is_empty() {
+        short_id(doc_id)
+    } else {
+        cached.context_name.clone()
+    };
+    let blocks = cached.synced.blocks();
+    let mut out = format!("@{} · {} blocks", name, blocks.len());
+    for block in blocks.iter().skip(blocks.len().saturating_sub(PREVIEW_BLOCKS)) {
+        let content = block.content.trim();
+        let clipped: String = if block.collapsed {
+            content.lines().next().unwrap_or_default().to_string()
+        } else {
+            content.chars().take(PREVIEW_BLOCK_CHARS).collect()
+        };
+        let ellipsis = if clipped.len() < content.len() { "…" } else { "" };
+        out.push_str(&format!("\n\n{} ▸ {}{}", block.role, clipped, ellipsis));
     }
+    out
 }
 
 /// Shorten a document_id to a readable label. A UUID document id shows its
@@ -0,0 +1,354 @@
+//! Timeline strip — the active context's events over its life, above the
+//! conversation.
+//!
+//! A thin bar chart from `getContextTimeline`: one bar per bucket, as tall
+//! as the bucket's blocks, tool runs, drifts and errors, tinted red where
+//! something failed and accent where a drift arrived. Buckets with only
+//! edits (a model streaming) show a low tick. Hovering a bar puts its
+//! counts and age in the label at the right; otherwise the label sums the
+//! whole span. Re-read every [`TIMELINE_POLL_INTERVAL`] seconds and when
+//! the active context changes. Unrelated to `ui::timeline`, the keyboard
+//! scrubber through block history.
+
+use bevy::prelude::*;
+
+use kaijutsu_types::{ContextId, ContextTimeline, TimelineBucket};
+
+use crate::connection::{RpcActor, RpcConnectionState, RpcResultChannel, RpcResultMessage};
+use crate::ui::screen::Screen;
+use crate::ui::state::ContentArea;
+use crate::ui::theme::Theme;
+
+/// How often to re-read the timeline (seconds).
+const TIMELINE_POLL_INTERVAL: f64 = 15.0;
+
+/// Bars in the strip, one per bucket.
+const STRIP_BUCKETS: u32 = 80;
+
+/// Height of the strip.
+const STRIP_HEIGHT_PX: f32 = 22.0;
+
+/// The active context's timeline, as far as the app has read it.
+#[derive(Resource, Default)]
+pub struct TimelineStripState {
+    pub timeline: Option<ContextTimeline>,
+    /// Context the last poll asked about.
+    context: Option<ContextId>,
+    /// Last poll timestamp; `None` forces an immediate poll.
+    last_poll: Option<f64>,
+}
+
+/// The strip.
+#[derive(Component)]
+struct TimelineStrip;
+
+/// One bar, by bucket index.
+#[derive(Component)]
+struct TimelineBar(usize);
+
+/// Summary (or hovered bucket) text.
+#[derive(Component)]
+struct TimelineStripLabel;
+
+/// Plugin for the timeline strip.
+pub struct TimelineStripPlugin;
+
+impl Plugin for TimelineStripPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimelineStripState>().add_systems(
+            Update,
+            (
+                poll_context_timeline,
+                update_context_timeline,
+                spawn_timeline_strip,
+                sync_timeline_strip,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// `ms` as a compact age: `45s`, `12m`, `3h`, `2d`.
+fn compact_age(ms: u64) -> String {
+    let secs = ms / 1000;
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3_600 => format!("{}m", secs / 60),
+        3_600..86_400 => format!("{}h", secs / 3_600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+/// Counts worth naming in a label; zeros are left out.
+fn describe_counts(bucket: &TimelineBucket) -> String {
+    let parts: Vec<String> = [
+        (bucket.blocks, "blocks"),
+        (bucket.tool_runs, "tools"),
+        (bucket.drifts, "drifts"),
+        (bucket.errors, "errors"),
+        (bucket.edits, "edits"),
+    ]
+    .into_iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, what)| format!("{n} {what}"))
+    .collect();
+    if parts.is_empty() {
+        "quiet".to_string()
+    } else {
+        parts.join(" · ")
+    }
+}
+
+/// Every bucket added together.
+fn totals(timeline: &ContextTimeline) -> TimelineBucket {
+    timeline
+        .buckets
+        .iter()
+        .fold(TimelineBucket::default(), |sum, b| TimelineBucket {
+            blocks: sum.blocks + b.blocks,
+            edits: sum.edits + b.edits,
+            tool_runs: sum.tool_runs + b.tool_runs,
+            drifts: sum.drifts + b.drifts,
+            errors: sum.errors + b.errors,
+        })
+}
+
+/// Fetch the active context's timeline when it changes and every
+/// `TIMELINE_POLL_INTERVAL` seconds.
+fn poll_context_timeline(
+    actor: Option<Res<RpcActor>>,
+    conn_state: Res<RpcConnectionState>,
+    doc_cache: Res<crate::cell::DocumentCache>,
+    mut state: ResMut<TimelineStripState>,
+    time: Res<Time>,
+    result_channel: Res<RpcResultChannel>,
+) {
+    let Some(actor) = actor else { return };
+    let active = doc_cache.active_id();
+    if conn_state.is_changed() || active != state.context {
+        state.context = active;
+        state.last_poll = None;
+        if state.timeline.as_ref().map(|t| t.context_id) != active {
+            state.timeline = None;
+        }
+    }
+    let Some(context_id) = active else { return };
+    if !conn_state.connected {
+        return;
+    }
+
+    let elapsed = time.elapsed_secs_f64();
+    if state
+        .last_poll
+        .is_some_and(|last| elapsed - last < TIMELINE_POLL_INTERVAL)
+    {
+        return;
+    }
+    state.last_poll = Some(elapsed);
+
+    let handle = actor.handle.clone();
+    let tx = result_channel.sender();
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            match handle
+                .get_context_timeline(context_id, 0, STRIP_BUCKETS)
+                .await
+            {
+                Ok(timeline) => {
+                    let _ = tx.send(RpcResultMessage::ContextTimelineReceived { timeline });
+                }
+                Err(e) => log::debug!("timeline poll: get_context_timeline failed: {e}"),
+            }
+        })
+        .detach();
+}
+
+/// Drain `ContextTimelineReceived` into `TimelineStripState`.
+fn update_context_timeline(
+    mut state: ResMut<TimelineStripState>,
+    mut events: MessageReader<RpcResultMessage>,
+) {
+    for event in events.read() {
+        let RpcResultMessage::ContextTimelineReceived { timeline } = event else {
+            continue;
+        };
+        // A reply for a context we've since switched away from is stale.
+        if Some(timeline.context_id) == state.context {
+            state.timeline = Some(timeline.clone());
+        }
+    }
+}
+
+/// Spawn the (hidden) strip once, as the first child of the content area.
+fn spawn_timeline_strip(
+    mut commands: Commands,
+    existing: Query<(), With<TimelineStrip>>,
+    content_area: Query<Entity, With<ContentArea>>,
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+) {
+    if !existing.is_empty() {
+        return;
+    }
+    let Ok(area) = content_area.single() else {
+        return;
+    };
+
+    let font = asset_server.load("fonts/CascadiaCodeNF.ttf");
+    let strip = commands
+        .spawn((
+            TimelineStrip,
+            Node {
+                display: Display::None,
+                width: Val::Percent(100.0),
+                height: Val::Px(STRIP_HEIGHT_PX),
+                flex_shrink: 0.0,
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                padding: UiRect::horizontal(Val::Px(8.0)),
+                border: UiRect::bottom(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(theme.panel_bg),
+            BorderColor::all(theme.border),
+        ))
+        .with_children(|strip| {
+            strip
+                .spawn(Node {
+                    flex_grow: 1.0,
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::FlexEnd,
+                    column_gap: Val::Px(1.0),
+                    padding: UiRect::vertical(Val::Px(3.0)),
+                    ..default()
+                })
+                .with_children(|bars| {
+                    for index in 0..STRIP_BUCKETS as usize {
+                        bars.spawn((
+                            TimelineBar(index),
+                            Node {
+                                flex_grow: 1.0,
+                                height: Val::Percent(0.0),
+                                ..default()
+                            },
+                            BackgroundColor(theme.fg_dim),
+                            Interaction::default(),
+                        ));
+                    }
+                });
+            strip.spawn((
+                TimelineStripLabel,
+                Text::new(""),
+                TextFont {
+                    font,
+                    font_size: 11.0,
+                    ..default()
+                },
+                TextColor(theme.fg_dim),
+            ));
+        })
+        .id();
+    commands.entity(area).insert_children(0, &[strip]);
+}
+
+/// Mirror the timeline into the bars, and the hovered bar (or the whole
+/// span) into the label. Shown only on the conversation screen.
+fn sync_timeline_strip(
+    state: Res<TimelineStripState>,
+    screen: Res<State<Screen>>,
+    theme: Res<Theme>,
+    mut strip: Query<&mut Node, (With<TimelineStrip>, Without<TimelineBar>)>,
+    mut bars: Query<(&TimelineBar, &Interaction, &mut Node, &mut BackgroundColor)>,
+    mut label: Query<&mut Text, With<TimelineStripLabel>>,
+) {
+    let Ok(mut node) = strip.single_mut() else {
+        return;
+    };
+    let timeline = state
+        .timeline
+        .as_ref()
+        .filter(|_| *screen.get() == Screen::Conversation);
+    let display = if timeline.is_some() {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    if node.display != display {
+        node.display = display;
+    }
+    let Some(timeline) = timeline else {
+        return;
+    };
+
+    let peak = timeline.peak().max(1) as f32;
+    let mut hovered = None;
+    for (bar, interaction, mut bar_node, mut color) in bars.iter_mut() {
+        let bucket = timeline.buckets.get(bar.0).copied().unwrap_or_default();
+        if *interaction != Interaction::None {
+            hovered = Some(bar.0);
+        }
+        let height = match bucket.events() {
+            0 if bucket.edits > 0 => 12.0,
+            0 => 0.0,
+            n => (n as f32 / peak * 100.0).max(20.0),
+        };
+        let tint = if bucket.errors > 0 {
+            theme.error
+        } else if bucket.drifts > 0 {
+            theme.accent
+        } else if bucket.events() == 0 {
+            theme.border
+        } else {
+            theme.fg_dim
+        };
+        if bar_node.height != Val::Percent(height) {
+            bar_node.height = Val::Percent(height);
+        }
+        if color.0 != tint {
+            color.0 = tint;
+        }
+    }
+
+    let now = kaijutsu_types::now_millis();
+    let summary = match hovered.and_then(|i| timeline.buckets.get(i).map(|b| (i, b))) {
+        Some((i, bucket)) => format!(
+            "{} ago  {}",
+            compact_age(now.saturating_sub(timeline.bucket_start(i))),
+            describe_counts(bucket)
+        ),
+        None => format!(
+            "{}  {}",
+            compact_age(now.saturating_sub(timeline.start)),
+            describe_counts(&totals(timeline))
+        ),
+    };
+    if let Ok(mut text) = label.single_mut()
+        && text.0 != summary
+    {
+        text.0 = summary;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_are_compact() {
+        assert_eq!(compact_age(59_999), "59s");
+        assert_eq!(compact_age(90_000), "1m");
+        assert_eq!(compact_age(7_200_000), "2h");
+        assert_eq!(compact_age(3 * 86_400_000), "3d");
+
+        let bucket = TimelineBucket {
+            blocks: 4,
+            tool_runs: 2,
+            errors: 1,
+            ..Default::default()
+        };
+        assert_eq!(describe_counts(&bucket), "4 blocks · 2 tools · 1 errors");
+        assert_eq!(describe_counts(&TimelineBucket::default()), "quiet");
+    }
+}
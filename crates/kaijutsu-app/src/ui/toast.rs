@@ -0,0 +1,518 @@
+//! Toasts — transient notices for things that happen away from the focused block.
+//!
+//! Drift arrivals, consent requests, agent errors (Error blocks and the
+//! context-free [`GlobalErrorQueue`]), and connection drops/reconnects each
+//! push a [`Toast`]. The newest [`MAX_VISIBLE`] stack in the top-right corner,
+//! edged in their severity's theme color, and expire on a severity-based
+//! timer; `Alt+N` dismisses the newest early. Every toast is also kept in a
+//! history of the last [`HISTORY_LEN`], shown by `:notifications` at the shell
+//! surface.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use kaijutsu_crdt::BlockKind;
+use kaijutsu_types::{ErrorSeverity, InboxKind};
+
+use crate::connection::{RpcConnectionState, ServerEventMessage};
+use crate::input::action::Action;
+use crate::input::events::{ActionFired, NotificationsPanelRequested};
+use crate::ui::theme::Theme;
+use crate::view::components::GlobalErrorQueue;
+
+/// Toasts on screen at once; older ones drop to history only.
+const MAX_VISIBLE: usize = 4;
+
+/// Toasts kept for the `:notifications` panel.
+const HISTORY_LEN: usize = 50;
+
+/// Rows the `:notifications` panel shows, newest first.
+const PANEL_ROWS: usize = 20;
+
+/// Characters of block content shown in a toast body.
+const PREVIEW_CHARS: usize = 80;
+
+/// How loud a toast is: picks its edge color and how long it stays up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastSeverity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastSeverity {
+    /// Seconds on screen unless dismissed. Errors linger longest.
+    fn lifetime(self) -> f64 {
+        match self {
+            Self::Info | Self::Success => 5.0,
+            Self::Warning => 8.0,
+            Self::Error => 12.0,
+        }
+    }
+
+    fn color(self, theme: &Theme) -> Color {
+        match self {
+            Self::Info => theme.accent,
+            Self::Success => theme.success,
+            Self::Warning => theme.warning,
+            Self::Error => theme.error,
+        }
+    }
+}
+
+/// One notice.
+#[derive(Clone, Debug)]
+pub struct Toast {
+    pub severity: ToastSeverity,
+    pub title: String,
+    pub body: String,
+    /// From `Time::elapsed_secs_f64()`.
+    pub created_at: f64,
+}
+
+/// Toasts on screen plus the history behind `:notifications`.
+#[derive(Resource, Default)]
+pub struct Toasts {
+    /// On screen, oldest first.
+    active: VecDeque<Toast>,
+    /// Everything pushed, oldest first.
+    history: VecDeque<Toast>,
+    /// Whether the `:notifications` panel is showing.
+    pub panel_open: bool,
+}
+
+impl Toasts {
+    pub fn push(
+        &mut self,
+        severity: ToastSeverity,
+        title: impl Into<String>,
+        body: impl Into<String>,
+        now: f64,
+    ) {
+        let toast = Toast {
+            severity,
+            title: title.into(),
+            body: body.into(),
+            created_at: now,
+        };
+        self.history.push_back(toast.clone());
+        while self.history.len() > HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.active.push_back(toast);
+        while self.active.len() > MAX_VISIBLE {
+            self.active.pop_front();
+        }
+    }
+
+    /// Take the newest toast off screen (it stays in history).
+    pub fn dismiss_newest(&mut self) -> bool {
+        self.active.pop_back().is_some()
+    }
+
+    /// Drop toasts past their lifetime. Returns whether any went.
+    fn expire(&mut self, now: f64) -> bool {
+        let before = self.active.len();
+        self.active
+            .retain(|t| now - t.created_at < t.severity.lifetime());
+        self.active.len() != before
+    }
+
+    /// On-screen toasts, newest first.
+    pub fn active(&self) -> impl Iterator<Item = &Toast> {
+        self.active.iter().rev()
+    }
+
+    /// History, newest first.
+    pub fn history(&self) -> impl Iterator<Item = &Toast> {
+        self.history.iter().rev()
+    }
+}
+
+/// Parse `:notifications` (or `:notifs`) typed at the shell surface.
+pub fn parse_notifications_command(text: &str) -> bool {
+    matches!(
+        text.trim().strip_prefix(':'),
+        Some("notifications" | "notifs")
+    )
+}
+
+/// The top-right column of toasts.
+#[derive(Component)]
+struct ToastStack;
+
+/// One toast slot in the stack, by index into [`Toasts::active`].
+#[derive(Component)]
+struct ToastRow(usize);
+
+/// The `:notifications` history panel.
+#[derive(Component)]
+struct NotificationPanel;
+
+/// One history line in the panel, by index into [`Toasts::history`].
+#[derive(Component)]
+struct PanelRow(usize);
+
+/// Plugin for toasts and the notification history.
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Toasts>().add_systems(
+            Update,
+            (
+                toast_server_events,
+                toast_connection_changes,
+                toast_global_errors,
+                handle_toast_input,
+                expire_toasts,
+                spawn_toast_ui,
+                sync_toast_ui,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    match line.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Drift arrivals, Error blocks, consent requests, and reconnects.
+fn toast_server_events(
+    mut toasts: ResMut<Toasts>,
+    mut events: MessageReader<ServerEventMessage>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs_f64();
+    for ServerEventMessage(event) in events.read() {
+        match event {
+            kaijutsu_client::ServerEvent::BlockInserted {
+                context_id, block, ..
+            } => match block.kind {
+                BlockKind::Drift => {
+                    let source = block
+                        .source_context
+                        .map(|c| c.short())
+                        .unwrap_or_else(|| "?".to_string());
+                    toasts.push(
+                        ToastSeverity::Info,
+                        format!("Drift from @{source}"),
+                        preview(&block.content),
+                        now,
+                    );
+                }
+                BlockKind::Error => {
+                    let severity = match block.error.as_ref().map(|e| e.severity) {
+                        Some(ErrorSeverity::Warning) => ToastSeverity::Warning,
+                        _ => ToastSeverity::Error,
+                    };
+                    toasts.push(
+                        severity,
+                        format!("Error in @{}", context_id.short()),
+                        preview(&block.content),
+                        now,
+                    );
+                }
+                _ => {}
+            },
+            kaijutsu_client::ServerEvent::InboxItem { item }
+                if item.kind == InboxKind::ConsentRequest =>
+            {
+                toasts.push(
+                    ToastSeverity::Warning,
+                    "Consent requested",
+                    preview(&item.summary),
+                    now,
+                );
+            }
+            kaijutsu_client::ServerEvent::Reconnected => {
+                toasts.push(
+                    ToastSeverity::Success,
+                    "Reconnected",
+                    "resyncing the open context",
+                    now,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A live connection going down. The way back up arrives as
+/// `ServerEvent::Reconnected`.
+fn toast_connection_changes(
+    mut toasts: ResMut<Toasts>,
+    conn_state: Res<RpcConnectionState>,
+    mut was_connected: Local<bool>,
+    time: Res<Time>,
+) {
+    if !conn_state.is_changed() {
+        return;
+    }
+    if *was_connected && !conn_state.connected {
+        let cause = conn_state.last_error.as_deref().unwrap_or("reconnecting…");
+        toasts.push(
+            ToastSeverity::Warning,
+            "Connection lost",
+            preview(cause),
+            time.elapsed_secs_f64(),
+        );
+    }
+    *was_connected = conn_state.connected;
+}
+
+/// Context-free RPC failures queued in [`GlobalErrorQueue`].
+fn toast_global_errors(
+    mut toasts: ResMut<Toasts>,
+    queue: Res<GlobalErrorQueue>,
+    mut last_seen: Local<f64>,
+) {
+    if !queue.is_changed() {
+        return;
+    }
+    for entry in queue.entries.iter().filter(|e| e.created_at > *last_seen) {
+        toasts.push(
+            ToastSeverity::Error,
+            entry.operation.clone(),
+            preview(&entry.message),
+            entry.created_at,
+        );
+    }
+    if let Some(newest) = queue.entries.back() {
+        *last_seen = last_seen.max(newest.created_at);
+    }
+}
+
+/// `Alt+N` dismisses the newest toast, then closes the panel once none are
+/// left; `:notifications` toggles the panel.
+fn handle_toast_input(
+    mut toasts: ResMut<Toasts>,
+    mut actions: MessageReader<ActionFired>,
+    mut panel_requests: MessageReader<NotificationsPanelRequested>,
+) {
+    for ActionFired { action, .. } in actions.read() {
+        if matches!(action, Action::DismissToast) && !toasts.dismiss_newest() {
+            toasts.panel_open = false;
+        }
+    }
+    for _ in panel_requests.read() {
+        toasts.panel_open = !toasts.panel_open;
+    }
+}
+
+fn expire_toasts(mut toasts: ResMut<Toasts>, time: Res<Time>) {
+    // Checked through a plain borrow so an idle frame doesn't mark the
+    // resource changed and re-sync the UI.
+    let now = time.elapsed_secs_f64();
+    if toasts
+        .active
+        .iter()
+        .any(|t| now - t.created_at >= t.severity.lifetime())
+    {
+        toasts.expire(now);
+    }
+}
+
+/// Spawn the toast stack and the (hidden) history panel once.
+fn spawn_toast_ui(
+    mut commands: Commands,
+    existing: Query<(), With<ToastStack>>,
+    theme: Res<Theme>,
+    asset_server: Res<AssetServer>,
+) {
+    if !existing.is_empty() {
+        return;
+    }
+
+    // Bevy's native text, like the completion popup: short transient labels.
+    let font = asset_server.load("fonts/CascadiaCodeNF.ttf");
+    let text_font = TextFont {
+        font: font.clone(),
+        font_size: 13.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            ToastStack,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(48.0),
+                right: Val::Px(16.0),
+                width: Val::Px(360.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            GlobalZIndex(crate::constants::ZLayer::TOAST),
+        ))
+        .with_children(|stack| {
+            for i in 0..MAX_VISIBLE {
+                stack.spawn((
+                    ToastRow(i),
+                    Text::new(""),
+                    text_font.clone(),
+                    TextColor(theme.fg),
+                    BackgroundColor(theme.panel_bg),
+                    BorderColor::all(theme.accent),
+                    Node {
+                        display: Display::None,
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                        border: UiRect::left(Val::Px(3.0)),
+                        ..default()
+                    },
+                ));
+            }
+        });
+
+    commands
+        .spawn((
+            NotificationPanel,
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                top: Val::Px(48.0),
+                right: Val::Px(392.0),
+                width: Val::Px(480.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(10.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(theme.panel_bg),
+            BorderColor::all(theme.border),
+            GlobalZIndex(crate::constants::ZLayer::TOAST),
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new("Notifications  (Alt+N closes)"),
+                text_font.clone(),
+                TextColor(theme.fg_dim),
+            ));
+            for i in 0..PANEL_ROWS {
+                panel.spawn((
+                    PanelRow(i),
+                    Text::new(""),
+                    text_font.clone(),
+                    TextColor(theme.fg),
+                    Node {
+                        display: Display::None,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+/// Mirror [`Toasts`] into the stack rows and the panel.
+fn sync_toast_ui(
+    toasts: Res<Toasts>,
+    theme: Res<Theme>,
+    time: Res<Time>,
+    mut rows: Query<(&ToastRow, &mut Text, &mut BorderColor, &mut Node), Without<PanelRow>>,
+    mut panel: Query<
+        &mut Node,
+        (
+            With<NotificationPanel>,
+            Without<ToastRow>,
+            Without<PanelRow>,
+        ),
+    >,
+    mut panel_rows: Query<(&PanelRow, &mut Text, &mut TextColor, &mut Node), Without<ToastRow>>,
+) {
+    if !toasts.is_changed() {
+        return;
+    }
+
+    let active: Vec<&Toast> = toasts.active().collect();
+    for (row, mut text, mut border, mut node) in rows.iter_mut() {
+        let Some(toast) = active.get(row.0) else {
+            node.display = Display::None;
+            continue;
+        };
+        node.display = Display::Flex;
+        text.0 = if toast.body.is_empty() {
+            toast.title.clone()
+        } else {
+            format!("{}\n{}", toast.title, toast.body)
+        };
+        *border = BorderColor::all(toast.severity.color(&theme));
+    }
+
+    let Ok(mut panel) = panel.single_mut() else {
+        return;
+    };
+    panel.display = if toasts.panel_open {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    if !toasts.panel_open {
+        return;
+    }
+    let now = time.elapsed_secs_f64();
+    let history: Vec<&Toast> = toasts.history().take(PANEL_ROWS).collect();
+    for (row, mut text, mut color, mut node) in panel_rows.iter_mut() {
+        let Some(toast) = history.get(row.0) else {
+            node.display = Display::None;
+            continue;
+        };
+        node.display = Display::Flex;
+        let age = (now - toast.created_at).max(0.0) as u64;
+        text.0 = format!("{:>4}s ago  {}  {}", age, toast.title, toast.body);
+        *color = TextColor(toast.severity.color(&theme));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_keeps_the_newest_and_history_keeps_all() {
+        let mut toasts = Toasts::default();
+        for i in 0..MAX_VISIBLE + 2 {
+            toasts.push(ToastSeverity::Info, format!("t{i}"), "", i as f64);
+        }
+        let titles: Vec<_> = toasts.active().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles.len(), MAX_VISIBLE);
+        assert_eq!(titles[0], format!("t{}", MAX_VISIBLE + 1));
+        assert_eq!(toasts.history().count(), MAX_VISIBLE + 2);
+
+        assert!(toasts.dismiss_newest());
+        assert_eq!(
+            toasts.active().next().unwrap().title,
+            format!("t{MAX_VISIBLE}")
+        );
+        assert_eq!(
+            toasts.history().count(),
+            MAX_VISIBLE + 2,
+            "dismissal keeps history"
+        );
+    }
+
+    #[test]
+    fn errors_outlive_info() {
+        let mut toasts = Toasts::default();
+        toasts.push(ToastSeverity::Info, "info", "", 0.0);
+        toasts.push(ToastSeverity::Error, "error", "", 0.0);
+        assert!(toasts.expire(ToastSeverity::Info.lifetime()));
+        let left: Vec<_> = toasts.active().map(|t| t.title.as_str()).collect();
+        assert_eq!(left, ["error"]);
+    }
+
+    #[test]
+    fn parse_notifications_commands() {
+        assert!(parse_notifications_command(":notifications"));
+        assert!(parse_notifications_command("  :notifs "));
+        assert!(!parse_notifications_command("notifications"));
+        assert!(!parse_notifications_command(":notifications all"));
+    }
+}
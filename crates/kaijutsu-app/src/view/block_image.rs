@@ -0,0 +1,342 @@
+//! Inline images in conversation cells.
+//!
+//! An image block's content is a CAS hash (`ContentType::Image`). The first
+//! time a visible cell needs one, [`BlockImages::request`] hands the hash to a
+//! background fetch — the same `/v/cas/<hash>` SFTP resolve and XDG cache the
+//! DJ's prefetch uses (`dj::prefetch`) — which decodes it off the main thread,
+//! downscaled to at most [`IMAGE_MAX_DIM`] a side. Virtualized-out cells are
+//! never built, so never fetched. Until the decode lands the cell draws a
+//! placeholder; then it is rebuilt with the image scaled to the cell's width,
+//! capped at [`IMAGE_MAX_HEIGHT`] and never enlarged.
+//!
+//! Clicking an image cell opens it full-window; the next click closes it.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::ui::UiGlobalTransform;
+use crossbeam_channel::{Receiver, Sender};
+use kaijutsu_cas::ContentHash;
+use kaijutsu_client::{CasResolver, SftpClient, SshConfig};
+use tokio::sync::Mutex as AsyncMutex;
+use vello::peniko::{Blob, ImageAlphaType, ImageBrush, ImageData, ImageFormat};
+
+use crate::cell::BlockCell;
+use crate::dj::prefetch::resolve_with_lazy_connect;
+use crate::text::rich::{RichContent, RichContentKind};
+use crate::view::block_render::BlockScene;
+
+/// Longest side of a decoded image; larger ones are downscaled on decode.
+pub const IMAGE_MAX_DIM: u32 = 2048;
+
+/// Tallest an image draws inline, in logical px.
+pub const IMAGE_MAX_HEIGHT: f32 = 480.0;
+
+/// Decoded images kept; the oldest is dropped past this and refetched (from
+/// the local CAS cache) if its cell is rebuilt.
+const IMAGE_CACHE_LEN: usize = 64;
+
+/// One decoded image, ready to draw.
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA8, unpremultiplied; shared with `brush`.
+    rgba: Arc<Vec<u8>>,
+    pub brush: ImageBrush,
+}
+
+impl DecodedImage {
+    fn new(width: u32, height: u32, rgba: Vec<u8>) -> Self {
+        let rgba = Arc::new(rgba);
+        let brush = ImageBrush::new(ImageData {
+            data: Blob::new(rgba.clone()),
+            format: ImageFormat::Rgba8,
+            alpha_type: ImageAlphaType::Alpha,
+            width,
+            height,
+        });
+        Self {
+            width,
+            height,
+            rgba,
+            brush,
+        }
+    }
+
+    /// A Bevy texture of the image, for the zoom view.
+    fn to_bevy_image(&self) -> Image {
+        Image::new(
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.rgba.to_vec(),
+            TextureFormat::Rgba8UnormSrgb,
+            default(),
+        )
+    }
+}
+
+/// Where one hash stands.
+pub enum ImageSlot {
+    Loading,
+    Ready(Arc<DecodedImage>),
+    Failed(String),
+}
+
+type ImageOutcome = (String, Result<DecodedImage, String>);
+
+/// The image fetch runtime and its resolver — one SFTP connection, dialed on
+/// the first image and reused.
+struct ImageFetch {
+    rt: tokio::runtime::Runtime,
+    resolver: Arc<AsyncMutex<Option<Arc<CasResolver<SftpClient>>>>>,
+}
+
+/// Decoded images by CAS hash, plus the fetches in flight.
+#[derive(Resource)]
+pub struct BlockImages {
+    slots: HashMap<String, ImageSlot>,
+    /// Ready hashes, oldest first, for eviction.
+    ready: VecDeque<String>,
+    fetch: Option<ImageFetch>,
+    tx: Sender<ImageOutcome>,
+    rx: Receiver<ImageOutcome>,
+}
+
+impl Default for BlockImages {
+    fn default() -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        Self {
+            slots: HashMap::new(),
+            ready: VecDeque::new(),
+            fetch: None,
+            tx,
+            rx,
+        }
+    }
+}
+
+impl BlockImages {
+    pub fn get(&self, hash: &str) -> Option<&ImageSlot> {
+        self.slots.get(hash)
+    }
+
+    /// Fetch and decode `hash` in the background unless it is already
+    /// loading, loaded, or failed. Dials over `config` on first use.
+    pub fn request(&mut self, hash: &str, config: &SshConfig) {
+        if self.slots.contains_key(hash) {
+            return;
+        }
+        let content_hash = match hash.parse::<ContentHash>() {
+            Ok(h) => h,
+            Err(e) => {
+                self.slots
+                    .insert(hash.to_string(), ImageSlot::Failed(e.to_string()));
+                return;
+            }
+        };
+        self.slots.insert(hash.to_string(), ImageSlot::Loading);
+        let fetch = self.fetch.get_or_insert_with(|| ImageFetch {
+            rt: tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("kaijutsu-image-fetch")
+                .enable_all()
+                .build()
+                .expect("image-fetch tokio runtime"),
+            resolver: Arc::new(AsyncMutex::new(None)),
+        });
+        let slot = fetch.resolver.clone();
+        let tx = self.tx.clone();
+        let config = config.clone();
+        let hash = hash.to_string();
+        fetch.rt.spawn(async move {
+            let fetched = resolve_with_lazy_connect(&slot, &content_hash, config).await;
+            let result = match fetched {
+                Ok(bytes) => tokio::task::spawn_blocking(move || decode(&bytes))
+                    .await
+                    .unwrap_or_else(|e| Err(format!("decode task: {e}"))),
+                Err(e) => Err(e.to_string()),
+            };
+            // A closed receiver just means the app is shutting down.
+            let _ = tx.send((hash, result));
+        });
+    }
+
+    /// Record finished fetches; returns the hashes that changed.
+    fn drain(&mut self) -> Vec<String> {
+        let mut landed = Vec::new();
+        while let Ok((hash, result)) = self.rx.try_recv() {
+            let slot = match result {
+                Ok(image) => {
+                    self.ready.push_back(hash.clone());
+                    ImageSlot::Ready(Arc::new(image))
+                }
+                Err(e) => {
+                    warn!("image {hash} failed to load: {e}");
+                    ImageSlot::Failed(e)
+                }
+            };
+            self.slots.insert(hash.clone(), slot);
+            landed.push(hash);
+        }
+        while self.ready.len() > IMAGE_CACHE_LEN {
+            if let Some(old) = self.ready.pop_front() {
+                self.slots.remove(&old);
+            }
+        }
+        landed
+    }
+}
+
+/// Decode image bytes to RGBA8, downscaling past [`IMAGE_MAX_DIM`].
+fn decode(bytes: &[u8]) -> Result<DecodedImage, String> {
+    let image = image::load_from_memory(bytes).map_err(|e| e.to_string())?;
+    let image = if image.width() > IMAGE_MAX_DIM || image.height() > IMAGE_MAX_DIM {
+        image.thumbnail(IMAGE_MAX_DIM, IMAGE_MAX_DIM)
+    } else {
+        image
+    };
+    let rgba = image.into_rgba8();
+    let (width, height) = rgba.dimensions();
+    Ok(DecodedImage::new(width, height, rgba.into_raw()))
+}
+
+/// The drawn size of a `width`×`height` image in a `max_w`×`max_h` box:
+/// aspect kept, shrunk to fit, never enlarged.
+pub fn fit(width: u32, height: u32, max_w: f32, max_h: f32) -> Vec2 {
+    if width == 0 || height == 0 || max_w <= 0.0 {
+        return Vec2::ZERO;
+    }
+    let size = Vec2::new(width as f32, height as f32);
+    let scale = (max_w / size.x).min(max_h / size.y).min(1.0);
+    size * scale
+}
+
+/// The full-window view of a clicked image.
+#[derive(Component)]
+struct ImageZoom;
+
+pub struct BlockImagePlugin;
+
+impl Plugin for BlockImagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlockImages>()
+            .add_systems(Update, (apply_loaded_images, toggle_image_zoom));
+    }
+}
+
+/// Store finished decodes and force a rebuild of the cells showing them.
+fn apply_loaded_images(
+    mut images: ResMut<BlockImages>,
+    mut cells: Query<(&RichContent, &mut BlockScene), With<BlockCell>>,
+) {
+    if images.rx.is_empty() {
+        return;
+    }
+    let landed = images.drain();
+    for (rich, mut block_scene) in cells.iter_mut() {
+        if let RichContentKind::Image { hash } = &rich.kind
+            && landed.contains(hash)
+        {
+            block_scene.content_version = block_scene.content_version.wrapping_add(1);
+        }
+    }
+}
+
+/// A click on a loaded image cell opens the zoom view; any click while it
+/// is open closes it.
+fn toggle_image_zoom(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cells: Query<(&RichContent, &ComputedNode, &UiGlobalTransform, &Node), With<BlockCell>>,
+    zoom: Query<Entity, With<ImageZoom>>,
+    block_images: Res<BlockImages>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    if let Ok(open) = zoom.single() {
+        commands.entity(open).despawn();
+        return;
+    }
+    let Some(cursor) = windows
+        .single()
+        .ok()
+        .and_then(|w| w.physical_cursor_position())
+    else {
+        return;
+    };
+    let Some(image) = cells.iter().find_map(|(rich, computed, transform, node)| {
+        let RichContentKind::Image { hash } = &rich.kind else {
+            return None;
+        };
+        if node.display == Display::None || !computed.contains_point(*transform, cursor) {
+            return None;
+        }
+        match block_images.get(hash) {
+            Some(ImageSlot::Ready(image)) => Some(image.clone()),
+            _ => None,
+        }
+    }) else {
+        return;
+    };
+
+    let handle = images.add(image.to_bevy_image());
+    commands
+        .spawn((
+            ImageZoom,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            GlobalZIndex(crate::constants::ZLayer::MODAL),
+        ))
+        .with_children(|zoom| {
+            zoom.spawn((
+                ImageNode::new(handle),
+                Node {
+                    max_width: Val::Percent(95.0),
+                    max_height: Val::Percent(95.0),
+                    ..default()
+                },
+            ));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_shrinks_to_the_box_and_never_enlarges() {
+        assert_eq!(fit(800, 600, 400.0, 480.0), Vec2::new(400.0, 300.0));
+        assert_eq!(fit(400, 1600, 800.0, 480.0), Vec2::new(120.0, 480.0));
+        assert_eq!(fit(64, 32, 800.0, 480.0), Vec2::new(64.0, 32.0));
+        assert_eq!(fit(64, 32, 0.0, 480.0), Vec2::ZERO);
+    }
+
+    #[test]
+    fn decode_downscales_oversized_images() {
+        let wide = image::RgbaImage::new(IMAGE_MAX_DIM * 2, 10);
+        let mut png = std::io::Cursor::new(Vec::new());
+        wide.write_to(&mut png, image::ImageFormat::Png).unwrap();
+
+        let decoded = decode(png.get_ref()).unwrap();
+        assert_eq!(decoded.width, IMAGE_MAX_DIM);
+        assert_eq!(decoded.height, 5);
+        assert_eq!(decoded.rgba.len(), (IMAGE_MAX_DIM * 5 * 4) as usize);
+        assert!(decode(b"not an image").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Block locks — who else is editing which block.
+//!
+//! A lock is advisory: the kernel records that a principal is editing a
+//! block (the vi editor takes one while a session is open, `kj block lock`
+//! takes one by hand) and tells other writers about it. This view mirrors a
+//! context's live locks — the full list when the context is joined, then
+//! `BlockLockChanged` pushes — so a block someone else holds gets a tinted
+//! border labelled `✎ amy editing`. Expiry is not announced; a lock is
+//! dropped here once its `expires_at` passes on the local clock.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use crossbeam_channel::{Receiver, Sender};
+use kaijutsu_client::ServerEvent;
+use kaijutsu_crdt::{BlockId, ContextId};
+use kaijutsu_types::BlockLock;
+
+use crate::connection::{RpcActor, RpcResultMessage, ServerEventMessage};
+
+/// How often expired locks are swept.
+const EXPIRY_SWEEP_SECS: f32 = 1.0;
+
+/// A joined context's lock list, fetched after the join.
+struct Fetched {
+    context_id: ContextId,
+    result: Result<Vec<BlockLock>, String>,
+}
+
+/// Live locks by block, across joined contexts.
+#[derive(Resource)]
+pub struct BlockLocks {
+    locks: HashMap<BlockId, BlockLock>,
+    tx: Sender<Fetched>,
+    rx: Receiver<Fetched>,
+}
+
+impl Default for BlockLocks {
+    fn default() -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        Self {
+            locks: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+}
+
+impl BlockLocks {
+    /// The live lock on `block_id`, if any.
+    pub fn get(&self, block_id: &BlockId) -> Option<&BlockLock> {
+        self.locks.get(block_id)
+    }
+
+    /// Replace one context's locks with a freshly fetched list.
+    fn replace_context(&mut self, context_id: ContextId, locks: Vec<BlockLock>) {
+        self.locks.retain(|id, _| id.context_id != context_id);
+        self.locks
+            .extend(locks.into_iter().map(|lock| (lock.block_id, lock)));
+    }
+
+    /// Drop locks expired at `now_millis`; whether any were.
+    fn expire(&mut self, now_millis: u64) -> bool {
+        let before = self.locks.len();
+        self.locks.retain(|_, lock| !lock.is_expired(now_millis));
+        self.locks.len() != before
+    }
+}
+
+pub struct BlockLocksPlugin;
+
+impl Plugin for BlockLocksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlockLocks>().add_systems(
+            Update,
+            (
+                fetch_locks_on_join,
+                apply_fetched_locks,
+                apply_lock_changes,
+                expire_locks,
+            ),
+        );
+    }
+}
+
+/// List a context's locks each time it is joined — pushes only cover changes
+/// from then on.
+fn fetch_locks_on_join(
+    actor: Option<Res<RpcActor>>,
+    mut events: MessageReader<RpcResultMessage>,
+    locks: Res<BlockLocks>,
+) {
+    let joined: Vec<ContextId> = events
+        .read()
+        .filter_map(|e| match e {
+            RpcResultMessage::ContextJoined { membership, .. } => Some(membership.context_id),
+            _ => None,
+        })
+        .collect();
+    let Some(actor) = actor else { return };
+    for context_id in joined {
+        let handle = actor.handle.clone();
+        let tx = locks.tx.clone();
+        bevy::tasks::IoTaskPool::get()
+            .spawn(async move {
+                let result = handle
+                    .list_block_locks(context_id)
+                    .await
+                    .map(|(locks, _policy)| locks)
+                    .map_err(|e| e.to_string());
+                let _ = tx.send(Fetched { context_id, result });
+            })
+            .detach();
+    }
+}
+
+fn apply_fetched_locks(mut locks: ResMut<BlockLocks>) {
+    if locks.rx.is_empty() {
+        return;
+    }
+    while let Ok(Fetched { context_id, result }) = locks.rx.try_recv() {
+        match result {
+            Ok(fetched) => locks.replace_context(context_id, fetched),
+            Err(e) => warn!("block locks: listing {} failed: {e}", context_id.short()),
+        }
+    }
+}
+
+fn apply_lock_changes(
+    mut locks: ResMut<BlockLocks>,
+    mut events: MessageReader<ServerEventMessage>,
+) {
+    for ServerEventMessage(event) in events.read() {
+        if let ServerEvent::BlockLockChanged { block_id, lock, .. } = event {
+            match lock {
+                Some(lock) => {
+                    locks.locks.insert(*block_id, lock.clone());
+                }
+                None => {
+                    locks.locks.remove(block_id);
+                }
+            }
+        }
+    }
+}
+
+/// Drop locks past their `expires_at`. Only touches the resource (and so
+/// re-styles borders) when one actually expired.
+fn expire_locks(mut locks: ResMut<BlockLocks>, time: Res<Time>, mut since: Local<f32>) {
+    *since += time.delta_secs();
+    if *since < EXPIRY_SWEEP_SECS || locks.locks.is_empty() {
+        return;
+    }
+    *since = 0.0;
+    let now = kaijutsu_types::now_millis();
+    if locks.bypass_change_detection().expire(now) {
+        locks.set_changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_crdt::PrincipalId;
+
+    fn lock(context_id: ContextId, seq: u64, expires_at: u64) -> BlockLock {
+        BlockLock {
+            block_id: BlockId::new(context_id, PrincipalId::new(), seq),
+            holder: PrincipalId::new(),
+            holder_name: "amy".into(),
+            note: String::new(),
+            acquired_at: 0,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn fetch_replaces_one_context_and_expiry_drops_stale_locks() {
+        let (a, b) = (ContextId::new(), ContextId::new());
+        let mut locks = BlockLocks::default();
+        locks.replace_context(a, vec![lock(a, 1, 1_000), lock(a, 2, 5_000)]);
+        locks.replace_context(b, vec![lock(b, 1, 5_000)]);
+        assert_eq!(locks.locks.len(), 3);
+
+        let kept = lock(a, 3, 9_000);
+        locks.replace_context(a, vec![kept.clone()]);
+        assert_eq!(locks.locks.len(), 2, "a's old locks are replaced");
+        assert_eq!(locks.get(&kept.block_id), Some(&kept));
+
+        assert!(!locks.expire(4_000));
+        assert!(locks.expire(5_000), "b's lock expired");
+        assert_eq!(locks.locks.len(), 1);
+    }
+}
@@ -22,6 +22,7 @@ use vello::peniko::Fill;
 
 use crate::cell::block_border::{BlockBorderStyle, BlockExcludedState, BorderAnimation, BorderLabelMetrics};
 use crate::cell::{BlockCell, RoleGroupBorder};
+use crate::connection::RpcConnectionState;
 use crate::shaders::BlockFxMaterial;
 use crate::text::msdf::{
     BlockRenderMethod, FontDataMap, MsdfBlockGlyphs,
@@ -36,6 +37,8 @@ use crate::text::components::rainbow_brush;
 use crate::text::markdown::MarkdownColors;
 use crate::text::sparkline::{SparklineColors, build_sparkline_paths, render_sparkline_scene};
 use crate::ui::theme::Theme;
+use crate::view::block_image::{BlockImages, IMAGE_MAX_HEIGHT, ImageSlot, fit};
+use crate::view::edit_diff::{EditDiffSettings, EditHighlight, toggle_edit_diff};
 use crate::view::fieldset;
 use crate::view::ui_rtt::{UiVectorScene, UiRttTexture};
 
@@ -94,6 +97,9 @@ impl Plugin for BlockRenderPlugin {
         // Initialize MSDF atlas in main world (needs Assets<Image>)
         app.add_systems(Startup, init_msdf_atlas);
 
+        app.init_resource::<EditDiffSettings>()
+            .add_systems(Update, toggle_edit_diff);
+
         // Main world: build scenes and resize textures in PostUpdate
         // after Taffy layout so ComputedNode.size() is available.
         app.add_systems(
@@ -261,6 +267,7 @@ pub fn build_block_scenes(
             &mut MsdfBlockGlyphs,
             &mut BlockRenderMethod,
             Option<&BlockExcludedState>,
+            (Option<&mut EditHighlight>, &BlockCell),
         ),
         With<BlockCell>,
     >,
@@ -269,16 +276,23 @@ pub fn build_block_scenes(
     theme: Res<Theme>,
     text_metrics: Res<TextMetrics>,
     time: Res<Time>,
+    edit_diff: Res<EditDiffSettings>,
     mut atlas: Option<ResMut<crate::text::msdf::MsdfAtlas>>,
     mut font_data_map: ResMut<FontDataMap>,
+    mut block_images: ResMut<BlockImages>,
+    conn_state: Res<RpcConnectionState>,
 ) {
     let font = fonts.get(&font_handles.mono);
+    let now = time.elapsed_secs();
+    let insert_tint = theme.success.to_srgba().to_u8_array();
+    let remove_tint = theme.error.to_srgba().to_u8_array();
 
     let md_colors = MarkdownColors {
         heading: theme.md_heading_color,
         code: theme.md_code_fg,
         strong: theme.md_strong_color,
         code_block: theme.md_code_block_fg,
+        syntax: crate::text::syntax::SyntaxPalette::from(&theme.syntax),
     };
 
     let sparkline_colors = SparklineColors {
@@ -291,7 +305,7 @@ pub fn build_block_scenes(
 
     for (
         entity, mut block_scene, mut ui_scene, mut rtt, computed, mut node, rich, border, vis, effects,
-        mut msdf_glyphs, mut render_method, excluded_state,
+        mut msdf_glyphs, mut render_method, excluded_state, (mut highlight, cell),
     ) in block_cells.iter_mut()
     {
         // Skip hidden blocks
@@ -323,8 +337,11 @@ pub fn build_block_scenes(
         let is_rainbow = effects.is_some_and(|e| e.rainbow);
         let has_animation = border.is_some_and(|b| b.animation != BorderAnimation::None);
         let never_built = block_scene.last_built_version == 0;
+        // Fading edit highlights recolor glyphs every frame until they expire.
+        let edit_fading = highlight.as_ref().is_some_and(|h| h.is_active());
         let needs_rebuild = version_changed
             || width_changed
+            || edit_fading
             || (never_built && (is_rainbow || has_animation));
 
         if !needs_rebuild {
@@ -379,6 +396,18 @@ pub fn build_block_scenes(
 
         let text_offset = (pad_left as f64, pad_top as f64);
 
+        // Settled blocks only — a streaming block's appends aren't news.
+        let track_edits = edit_diff.enabled && cell.last_status != kaijutsu_crdt::Status::Running;
+        let mut tint_edits = |glyphs: &mut Vec<crate::text::msdf::PositionedGlyph>,
+                              shown: &str,
+                              layout: &parley::Layout<peniko::Brush>| {
+            if let Some(h) = highlight.as_deref_mut() {
+                h.observe(shown, now, track_edits);
+                h.prune(now);
+                h.tint(glyphs, layout, text_offset, now, insert_tint, remove_tint);
+            }
+        };
+
         match rich.map(|r| &r.kind) {
             Some(RichContentKind::Markdown { spans, plain_text }) => {
                 let layout = font.layout(
@@ -405,9 +434,10 @@ pub fn build_block_scenes(
                             }
                         }
                     }
-                    let glyphs = collect_msdf_glyphs(
+                    let mut glyphs = collect_msdf_glyphs(
                         &layout, &span_brushes, &fallback_brush, text_offset, atlas,
                     );
+                    tint_edits(&mut glyphs, plain_text, &layout);
                     msdf_glyphs.glyphs = glyphs;
                     msdf_glyphs.version = block_scene.scene_version.wrapping_add(1);
                     msdf_glyphs.rainbow = is_rainbow;
@@ -522,9 +552,10 @@ pub fn build_block_scenes(
                             }
                         }
                     }
-                    let glyphs = collect_msdf_glyphs(
+                    let mut glyphs = collect_msdf_glyphs(
                         &parley_layout, &span_brushes, &fallback_brush, text_offset, atlas,
                     );
+                    tint_edits(&mut glyphs, plain_text, &parley_layout);
                     msdf_glyphs.glyphs = glyphs;
                     msdf_glyphs.version = block_scene.scene_version.wrapping_add(1);
                     msdf_glyphs.rainbow = is_rainbow;
@@ -533,38 +564,58 @@ pub fn build_block_scenes(
             }
             Some(RichContentKind::Image { hash }) => {
                 *render_method = BlockRenderMethod::Vello;
-                // Placeholder: dark rectangle with hash label.
-                // Full CAS→decode→ImageNode pipeline requires RpcCommand::CasRead
-                // and async image loading, which lands in a follow-up.
-                let placeholder_h = 120.0_f32;
-                content_height = placeholder_h;
-
-                let rect = vello::kurbo::Rect::new(
-                    pad_left as f64,
-                    pad_top as f64,
-                    (pad_left + content_width) as f64,
-                    (pad_top + placeholder_h) as f64,
-                );
-                scene.fill(
-                    Fill::NonZero,
-                    Affine::IDENTITY,
-                    vello::peniko::Color::new([0.2, 0.2, 0.25, 1.0]),
-                    None,
-                    &rect,
-                );
+                let ready = match block_images.get(hash) {
+                    Some(ImageSlot::Ready(image)) => Some(image.clone()),
+                    _ => None,
+                };
+                if let Some(image) = ready {
+                    let size = fit(image.width, image.height, content_width, IMAGE_MAX_HEIGHT);
+                    content_height = size.y;
+                    if size.x > 0.0 {
+                        let scale = (size.x / image.width as f32) as f64;
+                        scene.draw_image(
+                            &image.brush,
+                            Affine::translate(text_offset) * Affine::scale(scale),
+                        );
+                    }
+                } else {
+                    // Placeholder until the fetch lands (block_image rebuilds
+                    // the cell then): dark rectangle with a status label.
+                    block_images.request(hash, &conn_state.ssh_config);
+                    let short = &hash[..8.min(hash.len())];
+                    let label = match block_images.get(hash) {
+                        Some(ImageSlot::Failed(e)) => format!("[image {short}: {e}]"),
+                        _ => format!("[image {short}: loading…]"),
+                    };
+                    let placeholder_h = 120.0_f32;
+                    content_height = placeholder_h;
+
+                    let rect = vello::kurbo::Rect::new(
+                        pad_left as f64,
+                        pad_top as f64,
+                        (pad_left + content_width) as f64,
+                        (pad_top + placeholder_h) as f64,
+                    );
+                    scene.fill(
+                        Fill::NonZero,
+                        Affine::IDENTITY,
+                        vello::peniko::Color::new([0.2, 0.2, 0.25, 1.0]),
+                        None,
+                        &rect,
+                    );
 
-                let label = format!("[image: {}]", &hash[..8.min(hash.len())]);
-                let label_layout =
-                    font.layout(&label, &style, VelloTextAlign::Left, max_advance);
-                let label_y = pad_top + placeholder_h / 2.0 - label_layout.height() / 2.0;
-                let label_brush = bevy_color_to_brush(theme.block_tool_result);
-                crate::text::rich::render_layout_with_brushes(
-                    &mut scene,
-                    &label_layout,
-                    &[],
-                    &label_brush,
-                    (pad_left as f64, label_y as f64),
-                );
+                    let label_layout =
+                        font.layout(&label, &style, VelloTextAlign::Left, max_advance);
+                    let label_y = pad_top + placeholder_h / 2.0 - label_layout.height() / 2.0;
+                    let label_brush = bevy_color_to_brush(theme.block_tool_result);
+                    crate::text::rich::render_layout_with_brushes(
+                        &mut scene,
+                        &label_layout,
+                        &[],
+                        &label_brush,
+                        (pad_left as f64, label_y as f64),
+                    );
+                }
             }
             None => {
                 // Plain text block
@@ -584,9 +635,10 @@ pub fn build_block_scenes(
                             }
                         }
                     }
-                    let glyphs = collect_msdf_glyphs(
+                    let mut glyphs = collect_msdf_glyphs(
                         &layout, &[], &text_brush, text_offset, atlas,
                     );
+                    tint_edits(&mut glyphs, &block_scene.text, &layout);
                     msdf_glyphs.glyphs = glyphs;
                     msdf_glyphs.version = block_scene.scene_version.wrapping_add(1);
                     msdf_glyphs.rainbow = is_rainbow;
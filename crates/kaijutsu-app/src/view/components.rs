@@ -801,7 +801,7 @@ impl ExpandedErrorParents {
 /// failure, kernel attach failure). These can't be CRDT-synced because
 /// there's no context to sync them into.
 ///
-/// The dock HUD renders these as toasts that auto-dismiss.
+/// Each new entry is also shown as an error toast (`ui::toast`).
 #[derive(Resource, Default)]
 pub struct GlobalErrorQueue {
     pub entries: std::collections::VecDeque<GlobalError>,
@@ -809,9 +809,7 @@ pub struct GlobalErrorQueue {
 
 /// A single transient error entry for dock HUD display.
 pub struct GlobalError {
-    #[allow(dead_code)] // Dock HUD toast fields; renderer reads `created_at` only so far.
     pub operation: String,
-    #[allow(dead_code)] // Dock HUD toast fields; renderer reads `created_at` only so far.
     pub message: String,
     pub created_at: f64,
 }
@@ -0,0 +1,289 @@
+//! Inline edit-diff highlights — fading tints over text another writer changed.
+//!
+//! When a `BlockTextOps` event rewrites a settled block, the cell's text is
+//! replaced in place with no other cue. [`EditHighlight`] remembers the last
+//! string each block cell displayed; on the next build it diffs old against
+//! new, records the changed range as an [`EditMark`], and recolors the MSDF
+//! glyphs under it: inserted text in `theme.success`, the seam left by a
+//! deletion in `theme.error`. The tint lerps back to the base color over
+//! [`EDIT_FADE_SECS`].
+//!
+//! Ranges come from diffing the displayed text (`kaijutsu_types::diff::edits`,
+//! word-granular) rather than the raw op list, which keeps them in display
+//! coordinates after markdown/output formatting and gives each separate
+//! change its own mark. Streaming (`Running`) blocks are excluded: their appends are
+//! the expected shape and would just flash the tail.
+//!
+//! Toggle with `Ctrl+A e` ([`Action::ToggleEditDiff`]).
+
+use std::ops::Range;
+
+use bevy::prelude::*;
+use peniko::Brush;
+
+use crate::input::action::Action;
+use crate::input::events::ActionFired;
+use crate::text::msdf::PositionedGlyph;
+
+/// How long a highlight takes to fade back to the base color.
+pub const EDIT_FADE_SECS: f32 = 1.5;
+
+/// Whether settled-block edits get highlighted at all.
+#[derive(Resource, Debug, Clone)]
+pub struct EditDiffSettings {
+    pub enabled: bool,
+}
+
+impl Default for EditDiffSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditMarkKind {
+    Inserted,
+    /// Zero-width in the new text — drawn over the char at the seam.
+    Removed,
+}
+
+/// One highlighted range, in byte offsets of the currently displayed text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditMark {
+    pub range: Range<usize>,
+    pub kind: EditMarkKind,
+    /// `Time::elapsed_secs` when the edit was observed.
+    pub born: f32,
+}
+
+/// Per-cell edit tracking (lives next to `BlockScene` on every block cell).
+#[derive(Component, Debug, Default)]
+pub struct EditHighlight {
+    /// What the cell displayed at the last build; `None` until first build.
+    shown: Option<String>,
+    pub marks: Vec<EditMark>,
+}
+
+impl EditHighlight {
+    /// Record the text about to be displayed. With `track` set, a change
+    /// against the previous text adds a mark and shifts older marks past it;
+    /// without it the baseline is just replaced (streaming, toggle off).
+    pub fn observe(&mut self, text: &str, now: f32, track: bool) {
+        let Some(old) = self.shown.as_deref() else {
+            self.shown = Some(text.to_string());
+            return;
+        };
+        if old == text {
+            return;
+        }
+        if !track {
+            self.marks.clear();
+            self.shown = Some(text.to_string());
+            return;
+        }
+
+        let edits = kaijutsu_types::diff::edits(old, text);
+        self.marks.retain_mut(|mark| {
+            // Shift by every edit wholly before the mark; drop it if any
+            // edit overlaps — the fresh mark supersedes it.
+            let mut shift = 0isize;
+            for edit in &edits {
+                if edit.old.start >= mark.range.end {
+                    break;
+                }
+                if edit.old.end > mark.range.start {
+                    return false;
+                }
+                shift += edit.new.len() as isize - edit.old.len() as isize;
+            }
+            mark.range.start = mark.range.start.saturating_add_signed(shift);
+            mark.range.end = mark.range.end.saturating_add_signed(shift);
+            true
+        });
+
+        for edit in edits {
+            if !edit.new.is_empty() {
+                self.marks.push(EditMark {
+                    range: edit.new,
+                    kind: EditMarkKind::Inserted,
+                    born: now,
+                });
+            } else if let Some(seam) = seam_char(text, edit.new.start) {
+                self.marks.push(EditMark {
+                    range: seam,
+                    kind: EditMarkKind::Removed,
+                    born: now,
+                });
+            }
+        }
+        self.shown = Some(text.to_string());
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.marks.is_empty()
+    }
+
+    /// Drop marks that have fully faded.
+    pub fn prune(&mut self, now: f32) {
+        self.marks.retain(|m| now - m.born < EDIT_FADE_SECS);
+    }
+
+    /// Recolor glyphs under live marks. `offset` is the same text offset
+    /// handed to `collect_msdf_glyphs`; glyphs are matched by pen position
+    /// against the cursor geometry of each mark's endpoints.
+    pub fn tint(
+        &self,
+        glyphs: &mut [PositionedGlyph],
+        layout: &parley::Layout<Brush>,
+        offset: (f64, f64),
+        now: f32,
+        inserted: [u8; 4],
+        removed: [u8; 4],
+    ) {
+        for mark in &self.marks {
+            let strength = 1.0 - ((now - mark.born) / EDIT_FADE_SECS).clamp(0.0, 1.0);
+            if strength <= 0.0 {
+                continue;
+            }
+            let target = match mark.kind {
+                EditMarkKind::Inserted => inserted,
+                EditMarkKind::Removed => removed,
+            };
+            let start = cursor_box(layout, mark.range.start);
+            let end = cursor_box(layout, mark.range.end);
+            for glyph in glyphs.iter_mut() {
+                let x = (glyph.x as f64) - offset.0;
+                let y = (glyph.y as f64) - offset.1;
+                if covers(&start, &end, x, y) {
+                    glyph.color = lerp_rgba(glyph.color, target, strength);
+                }
+            }
+        }
+    }
+}
+
+/// The char at `at`, or the one before it at end of text.
+fn seam_char(text: &str, at: usize) -> Option<Range<usize>> {
+    if let Some(c) = text[at..].chars().next() {
+        return Some(at..at + c.len_utf8());
+    }
+    let c = text[..at].chars().next_back()?;
+    Some(at - c.len_utf8()..at)
+}
+
+/// Caret position at a byte offset: pen x plus the line's vertical extent.
+struct Caret {
+    x: f64,
+    y0: f64,
+    y1: f64,
+}
+
+fn cursor_box(layout: &parley::Layout<Brush>, byte: usize) -> Caret {
+    let geom =
+        parley::editing::Cursor::from_byte_index(layout, byte, parley::layout::Affinity::Downstream)
+            .geometry(layout, 0.0);
+    Caret {
+        x: geom.x0,
+        y0: geom.y0,
+        y1: geom.y1,
+    }
+}
+
+/// Is the glyph whose pen sits at (`x`, baseline `y`) between the two cursor
+/// positions? Lines strictly between `start` and `end` are covered whole.
+fn covers(start: &Caret, end: &Caret, x: f64, y: f64) -> bool {
+    // Half a pixel of slack absorbs float drift between glyph pen and caret.
+    const EPS: f64 = 0.5;
+    if y < start.y0 || y > end.y1 {
+        return false;
+    }
+    let on_start_line = y <= start.y1;
+    let on_end_line = y >= end.y0;
+    (!on_start_line || x >= start.x - EPS) && (!on_end_line || x < end.x - EPS)
+}
+
+fn lerp_rgba(from: [u8; 4], to: [u8; 4], t: f32) -> [u8; 4] {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    [
+        mix(from[0], to[0]),
+        mix(from[1], to[1]),
+        mix(from[2], to[2]),
+        from[3],
+    ]
+}
+
+/// `Ctrl+A e` — flip [`EditDiffSettings::enabled`].
+pub fn toggle_edit_diff(
+    mut actions: MessageReader<ActionFired>,
+    mut settings: ResMut<EditDiffSettings>,
+) {
+    for ActionFired { action, .. } in actions.read() {
+        if matches!(action, Action::ToggleEditDiff) {
+            settings.enabled = !settings.enabled;
+            info!("edit-diff highlights {}", if settings.enabled { "on" } else { "off" });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separate_edits_get_separate_marks() {
+        let mut h = EditHighlight::default();
+        h.observe("alpha beta gamma", 0.0, true);
+        h.observe("alpha BETA gamma DELTA", 0.0, true);
+        let ranges: Vec<_> = h.marks.iter().map(|m| m.range.clone()).collect();
+        assert_eq!(ranges, vec![6..10, 16..22]);
+    }
+
+    #[test]
+    fn first_observe_is_baseline_only() {
+        let mut h = EditHighlight::default();
+        h.observe("hello", 0.0, true);
+        assert!(!h.is_active());
+    }
+
+    #[test]
+    fn edits_mark_and_shift_older_marks() {
+        let mut h = EditHighlight::default();
+        h.observe("one three", 0.0, true);
+        h.observe("one three four", 0.1, true);
+        assert_eq!(h.marks[0].range, 9..14);
+
+        // Insert before the first mark: it shifts right.
+        h.observe("one two three four", 0.2, true);
+        assert_eq!(h.marks.len(), 2);
+        assert_eq!(h.marks[0].range, 13..18);
+        assert_eq!(h.marks[1].range, 4..8);
+        assert_eq!(h.marks[1].kind, EditMarkKind::Inserted);
+    }
+
+    #[test]
+    fn deletion_marks_the_seam() {
+        let mut h = EditHighlight::default();
+        h.observe("keep drop keep", 0.0, true);
+        h.observe("keep keep", 0.0, true);
+        assert_eq!(h.marks[0].kind, EditMarkKind::Removed);
+        assert_eq!(h.marks[0].range, 5..6);
+
+        h.observe("keep", 0.0, true);
+        assert_eq!(h.marks.last().unwrap().range, 3..4);
+    }
+
+    #[test]
+    fn untracked_changes_clear_and_marks_fade() {
+        let mut h = EditHighlight::default();
+        h.observe("a", 0.0, true);
+        h.observe("ab", 0.0, true);
+        h.prune(EDIT_FADE_SECS - 0.1);
+        assert!(h.is_active());
+        h.prune(EDIT_FADE_SECS);
+        assert!(!h.is_active());
+
+        h.observe("abc", 2.0, true);
+        h.observe("abcd", 2.1, false);
+        assert!(!h.is_active());
+    }
+}
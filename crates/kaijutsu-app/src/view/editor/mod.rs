@@ -406,7 +406,7 @@ mod tests {
         // Over the wire the message is wrapped by capnp/CallError; the
         // substring still identifies the verdict.
         assert!(is_session_lost_error(
-            "RPC error: Cap'n Proto error: Failed: remote exception: \
+            "not found: Cap'n Proto error: Failed: remote exception: \
              Failed: editor: no such session 3"
         ));
     }
@@ -5,7 +5,7 @@
 
 use crate::ui::theme::Theme;
 use kaijutsu_crdt::{BlockKind, BlockSnapshot, DriftKind, Role, Status};
-use kaijutsu_types::{ContextId, OutputData, OutputEntryType, OutputNode};
+use kaijutsu_types::{ContextId, OutputData, OutputEntryType, OutputNode, ReplLanguage, repl_echo};
 
 /// Map a block to its semantic text color based on BlockKind and Role.
 ///
@@ -51,6 +51,15 @@ pub fn block_color(block: &BlockSnapshot, theme: &Theme) -> bevy::prelude::Color
         BlockKind::Notification => theme.block_notification,
         BlockKind::Resource => theme.block_resource,
         BlockKind::Trace => theme.fg_dim,
+        BlockKind::Repl => {
+            if block.parent_id.is_none() {
+                theme.block_tool_call
+            } else if block.status == Status::Error {
+                theme.block_tool_error
+            } else {
+                theme.block_tool_result
+            }
+        }
         BlockKind::Drift => match block.drift_kind {
             Some(DriftKind::Push) => theme.block_drift_push,
             Some(DriftKind::Pull) | Some(DriftKind::Distill) => theme.block_drift_pull,
@@ -709,6 +718,25 @@ fn format_block_inner(block: &BlockSnapshot, local_ctx: Option<ContextId>) -> St
             }
         }
         BlockKind::Trace => block.content.clone(),
+        BlockKind::Repl => {
+            // Output blocks already carry their echoed input.
+            if block.parent_id.is_some() {
+                return block.content.clone();
+            }
+            // Session block: header, then the input log behind the prompt.
+            let language = block.language.as_deref().and_then(ReplLanguage::parse);
+            let name = language.map_or("repl", |l| l.as_str());
+            let state = match block.status {
+                Status::Running => "live",
+                Status::Error => "exited (error)",
+                _ => "exited",
+            };
+            let log = match language {
+                Some(lang) => repl_echo(lang, &block.content),
+                None => block.content.clone(),
+            };
+            format!("{name} REPL [{state}]\n{log}")
+        }
     }
 }
 
@@ -416,6 +416,7 @@ pub fn spawn_block_cells(
                     crate::view::ui_rtt::UiRttTexture::default(),
                     crate::text::msdf::MsdfBlockGlyphs::default(),
                     crate::text::msdf::BlockRenderMethod::default(),
+                    crate::view::edit_diff::EditHighlight::default(),
                     ImageNode::default(),
                     MaterialNode(material_handle),
                     Node {
@@ -9,11 +9,14 @@
 //! - `lifecycle` — spawn/despawn block cell entities (TopLeft anchor, no UiTransform)
 //! - `render` — buffer sync (text → UiVelloText), layout readback
 
+pub mod block_image;
+pub mod block_locks;
 pub mod block_render;
 pub mod brp_methods;
 pub mod components;
 pub mod cursor;
 pub mod document;
+pub mod edit_diff;
 pub mod editor;
 pub mod fieldset;
 pub mod format;
@@ -32,6 +35,7 @@ pub mod submit;
 pub mod sync;
 pub mod time_well;
 pub mod tracker;
+pub mod translate;
 pub mod vello_rasterizer;
 pub mod ui_rtt;
 
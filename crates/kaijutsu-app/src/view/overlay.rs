@@ -90,6 +90,8 @@ pub fn spawn_input_overlay(
         right: Val::Percent(20.0),
         min_height: Val::Px(48.0),
         max_width: Val::Px(800.0),
+        // Text surface on top, completion popup (ui::completion) below.
+        flex_direction: FlexDirection::Column,
         overflow: Overflow::clip(),
         border_radius: BorderRadius::all(Val::Px(style.corner_radius)),
         ..default()
@@ -38,6 +38,7 @@ pub fn sync_block_cell_buffers(
     theme: Res<Theme>,
     svg_fontdb: Res<crate::text::SvgFontDb>,
     doc_cache: Res<crate::cell::DocumentCache>,
+    mut translations: ResMut<crate::view::translate::BlockTranslations>,
     mut layout_gen: ResMut<LayoutGeneration>,
 ) {
     let Some(main_ent) = entities.main_cell else {
@@ -91,6 +92,8 @@ pub fn sync_block_cell_buffers(
 
         let local_ctx = doc_cache.active_id();
         let text = format_single_block(block, local_ctx);
+        // The translated view swaps in a block's translation once it lands.
+        let text = translations.display(block, &text).unwrap_or(text);
 
         // Debounce large blocks — only while still streaming. Once a block
         // reaches Done/Error, always render the final text so trim_end takes
@@ -140,8 +143,25 @@ pub fn sync_block_cell_buffers(
             && block.output.is_some()
             && !block.is_error;
 
+        // Text/ToolResult blocks with a declared source language get syntax
+        // highlighting ahead of the markdown heuristics — a `cat main.rs`
+        // is code even when it happens to contain `# ` lines.
+        let is_code_candidate = matches!(
+            block.kind,
+            kaijutsu_crdt::BlockKind::Text | kaijutsu_crdt::BlockKind::ToolResult
+        ) && block.content_type == kaijutsu_crdt::ContentType::Plain
+            && !block.is_error;
+
         let mut actually_rich = false;
-        if is_rich_candidate
+        if is_code_candidate
+            && let Some(ref language) = block.language
+            && let Some(rich) = crate::text::rich::detect_code_content(&text, language)
+        {
+            commands.entity(entity).insert(rich);
+            actually_rich = true;
+        }
+        if !actually_rich
+            && is_rich_candidate
             && let Some(rich) = crate::text::rich::detect_rich_content_typed(
                 &text,
                 doc_version,
@@ -11,7 +11,10 @@
 //! - a bounded set of live **ripples**, each fired at the *angle* of the context
 //!   that produced the event — so a busy conversation throws a wavefront out from
 //!   its direction on the ring (the "localize to context angle" behavior), and
-//! - a per-context decaying activity level (for a future per-card reaction).
+//! - a per-context activity level for the per-card glow: the kernel's own
+//!   sliding-window metrics (`ServerEvent::ContextActivity` — ops/minute,
+//!   running streams, errors) set the sustained floor, and the decaying event
+//!   chatter flickers on top of it.
 //!
 //! The energy/ripple math is pure and unit-tested; only the angle (which comes
 //! from a card's world position) and the event→weight mapping touch Bevy/client
@@ -21,7 +24,7 @@ use std::collections::HashMap;
 
 use bevy::prelude::Resource;
 use kaijutsu_client::ServerEvent;
-use kaijutsu_types::{ContextId, Status};
+use kaijutsu_types::{ContextActivity, ContextId, Status};
 
 /// Max simultaneous ripples the shader renders (must match the array length in
 /// `WellRingsMaterial`/`well_rings.wgsl`). A busy system keeps the freshest.
@@ -43,6 +46,16 @@ const CONTEXT_DECAY: f32 = 2.2;
 /// Below this a per-context entry is dropped (keeps the map from growing).
 const CONTEXT_EPSILON: f32 = 1e-2;
 
+/// Kernel-metric contributions to a context's sustained level: per running
+/// stream (counted up to [`METRIC_STREAMS_CAP`]), per [`METRIC_OPS_PER_UNIT`]
+/// ops/minute (up to [`METRIC_OPS_CAP`]), and a flat lift while errors sit in
+/// the window.
+const METRIC_PER_STREAM: f32 = 0.75;
+const METRIC_STREAMS_CAP: u32 = 2;
+const METRIC_OPS_PER_UNIT: f32 = 200.0;
+const METRIC_OPS_CAP: f32 = 1.5;
+const METRIC_ERROR_LIFT: f32 = 0.5;
+
 /// Seconds a ripple lives (from spawn to fully expanded + faded). The shader
 /// maps `age / RIPPLE_LIFETIME` to the wavefront radius, so this is also the
 /// time a ping takes to travel from the core to the rim.
@@ -68,6 +81,9 @@ pub struct RingActivity {
     pub energy: f32,
     /// Decaying per-context activity, keyed by context.
     pub per_context: HashMap<ContextId, f32>,
+    /// The kernel's latest sliding-window reading for each non-idle context
+    /// (replaced wholesale on every push — absent means idle).
+    pub metrics: HashMap<ContextId, ContextActivity>,
     /// Live ripples (≤ [`MAX_RIPPLES`]).
     pub ripples: Vec<Ripple>,
 }
@@ -122,14 +138,34 @@ impl RingActivity {
         self.ripples.retain(|r| r.age < RIPPLE_LIFETIME);
     }
 
-    /// Current activity level for one context (0.0 if quiet/unknown). Drives
-    /// the per-card chatter glow: `live::sync_card_live_uniforms` normalizes
-    /// this by [`CONTEXT_MAX`] into the card material's `dim.y` lane.
+    /// Replace the kernel metrics with a fresh `subscribeActivity` reading.
+    /// Each reading is complete, so contexts missing from it go idle.
+    pub fn apply_metrics(&mut self, contexts: &[ContextActivity]) {
+        self.metrics = contexts.iter().map(|a| (a.context_id, *a)).collect();
+    }
+
+    /// Current activity level for one context (0.0 if quiet/unknown): the
+    /// higher of its metric level ([`metric_level`]) and its decaying event
+    /// chatter. Drives the per-card glow: `live::sync_card_live_uniforms`
+    /// normalizes this by [`CONTEXT_MAX`] into the card material's `dim.y`
+    /// lane.
     pub fn context_energy(&self, ctx: &ContextId) -> f32 {
-        self.per_context.get(ctx).copied().unwrap_or(0.0)
+        let chatter = self.per_context.get(ctx).copied().unwrap_or(0.0);
+        let sustained = self.metrics.get(ctx).map_or(0.0, metric_level);
+        chatter.max(sustained)
     }
 }
 
+/// A context's sustained level (0..[`CONTEXT_MAX`]) from the kernel's
+/// metrics. A live stream keeps its card lit between token bursts, and errors
+/// in the window hold a lift until they age out.
+pub fn metric_level(m: &ContextActivity) -> f32 {
+    let streams = m.active_streams.min(METRIC_STREAMS_CAP) as f32 * METRIC_PER_STREAM;
+    let ops = (m.ops_per_minute as f32 / METRIC_OPS_PER_UNIT).min(METRIC_OPS_CAP);
+    let errors = if m.errors > 0 { METRIC_ERROR_LIFT } else { 0.0 };
+    (streams + ops + errors).min(CONTEXT_MAX)
+}
+
 /// Map a kernel event to `(context, weight)`, or `None` for events that aren't
 /// "activity" (collapse/exclude/delete/sync/context-switch/resource churn).
 /// Token streaming ([`ServerEvent::BlockTextOps`]) is the loudest live signal —
@@ -262,6 +298,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn kernel_metrics_hold_a_floor_under_decaying_chatter() {
+        let mut a = RingActivity::default();
+        a.apply_metrics(&[ContextActivity {
+            context_id: ctx(1),
+            ops_per_minute: 0,
+            active_streams: 1,
+            errors: 0,
+        }]);
+        a.record(ctx(1), 0.0, 1.0);
+        for _ in 0..50 {
+            a.tick(0.5);
+        }
+        assert_eq!(
+            a.context_energy(&ctx(1)),
+            METRIC_PER_STREAM,
+            "a running stream keeps the card lit after the chatter decays"
+        );
+
+        a.apply_metrics(&[]);
+        assert_eq!(
+            a.context_energy(&ctx(1)),
+            0.0,
+            "absent from a reading = idle"
+        );
+    }
+
     #[test]
     fn status_running_and_error_are_activity_streaming_is_loudest() {
         let bid = kaijutsu_crdt::BlockId::new(ctx(1), kaijutsu_types::PrincipalId::nil(), 0);
@@ -275,6 +338,7 @@ mod tests {
             block_id: bid,
             ops: vec![],
             seq_num: 0,
+            generation: 0,
         };
         let (_, run_w) = event_signal(&running).expect("status is activity");
         let (_, tops_w) = event_signal(&textops).expect("text ops are activity");
@@ -1584,13 +1584,18 @@ pub fn apply_horizon_label_lod(
 /// the global energy and fires a **ripple at the producing context's ring
 /// angle** (`atan2(card.y, card.x)`), so a busy conversation throws a wavefront
 /// out from its direction on the deck. Events for contexts not currently shown
-/// still raise the global energy (ripple fired at angle 0).
+/// still raise the global energy (ripple fired at angle 0). The kernel's
+/// per-context metric readings land here too, replacing the last one.
 pub fn accumulate_ring_activity(
     mut events: MessageReader<crate::connection::ServerEventMessage>,
     mut activity: ResMut<super::activity::RingActivity>,
     cards: Query<(&Card, &CardTarget)>,
 ) {
     for crate::connection::ServerEventMessage(ev) in events.read() {
+        if let kaijutsu_client::ServerEvent::ContextActivity { contexts, .. } = ev {
+            activity.apply_metrics(contexts);
+            continue;
+        }
         if let Some((ctx, weight)) = super::activity::event_signal(ev) {
             let angle = cards
                 .iter()
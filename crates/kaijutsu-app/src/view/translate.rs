@@ -0,0 +1,320 @@
+//! Translated view — read the conversation in the seat's language.
+//!
+//! The language is the `ui.language` preference. Each time a context is
+//! joined it is sent as the seat's preferred language (`setSeatLanguage`),
+//! which normalizes it; `t` in Navigation then toggles the translated view.
+//! While it is on, a finished text block's cell shows `translateBlock`'s
+//! rendering instead of the block's own text. The kernel caches renderings
+//! per block and language until the block is edited, so scrolling back is
+//! cheap. A block is fetched the first time its cell renders; until the
+//! translation lands, or if it fails, the original shows. Nothing is
+//! written back.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use bevy::prelude::*;
+use crossbeam_channel::{Receiver, Sender};
+use kaijutsu_crdt::{BlockId, BlockKind, BlockSnapshot, Role, Status};
+
+use crate::cell::BlockCell;
+use crate::connection::{RpcActor, RpcResultMessage};
+use crate::input::action::Action;
+use crate::input::events::ActionFired;
+use crate::ui::prefs::PreferencesState;
+use crate::ui::toast::{ToastSeverity, Toasts};
+
+/// Client preference naming the translation language (`ui.language`).
+pub const LANGUAGE_PREF: &str = "language";
+
+enum SlotState {
+    Pending,
+    Ready(String),
+    Failed,
+}
+
+struct Slot {
+    /// Hash of the text the translation was asked for.
+    source: u64,
+    state: SlotState,
+}
+
+enum Landed {
+    Language(Result<Option<String>, String>),
+    Block {
+        block_id: BlockId,
+        source: u64,
+        result: Result<String, String>,
+    },
+}
+
+/// Translations by block, plus the view toggle.
+#[derive(Resource)]
+pub struct BlockTranslations {
+    pub enabled: bool,
+    /// The seat's language as the server normalized it.
+    pub language: Option<String>,
+    slots: HashMap<BlockId, Slot>,
+    /// Blocks to fetch on the next frame.
+    wanted: Vec<(BlockId, u64)>,
+    tx: Sender<Landed>,
+    rx: Receiver<Landed>,
+}
+
+impl Default for BlockTranslations {
+    fn default() -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        Self {
+            enabled: false,
+            language: None,
+            slots: HashMap::new(),
+            wanted: Vec::new(),
+            tx,
+            rx,
+        }
+    }
+}
+
+impl BlockTranslations {
+    /// What to show for `block`, formatted as `text`: its translation if
+    /// the view is on and one has landed for this exact text. A miss queues
+    /// a fetch and shows the original meanwhile.
+    pub fn display(&mut self, block: &BlockSnapshot, text: &str) -> Option<String> {
+        if !self.enabled || self.language.is_none() || !translatable(block) {
+            return None;
+        }
+        let source = source_hash(text);
+        match self.slots.get(&block.id) {
+            Some(slot) if slot.source == source => match &slot.state {
+                SlotState::Ready(translated) => Some(translated.clone()),
+                SlotState::Pending | SlotState::Failed => None,
+            },
+            _ => {
+                self.slots.insert(
+                    block.id,
+                    Slot {
+                        source,
+                        state: SlotState::Pending,
+                    },
+                );
+                self.wanted.push((block.id, source));
+                None
+            }
+        }
+    }
+}
+
+/// Finished prose: user and model text, not while it is still streaming.
+fn translatable(block: &BlockSnapshot) -> bool {
+    block.kind == BlockKind::Text
+        && matches!(block.role, Role::User | Role::Model)
+        && block.status == Status::Done
+        && !block.content.trim().is_empty()
+}
+
+fn source_hash(text: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub struct BlockTranslatePlugin;
+
+impl Plugin for BlockTranslatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlockTranslations>().add_systems(
+            Update,
+            (
+                send_seat_language,
+                toggle_translation,
+                request_translations,
+                apply_translations,
+            ),
+        );
+    }
+}
+
+/// Send `ui.language` as the seat's language whenever a context is joined —
+/// the seat is claimed by the join, and a new one starts without it.
+fn send_seat_language(
+    actor: Option<Res<RpcActor>>,
+    mut events: MessageReader<RpcResultMessage>,
+    prefs: Res<PreferencesState>,
+    translations: Res<BlockTranslations>,
+) {
+    let joined = events
+        .read()
+        .any(|e| matches!(e, RpcResultMessage::ContextJoined { .. }));
+    let Some(actor) = actor else { return };
+    if !joined {
+        return;
+    }
+    let Some(language) = prefs.ui(LANGUAGE_PREF).map(str::to_string) else {
+        return;
+    };
+    let handle = actor.handle.clone();
+    let tx = translations.tx.clone();
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            let result = handle
+                .set_seat_language(Some(language))
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(Landed::Language(result));
+        })
+        .detach();
+}
+
+/// `ToggleTranslation` — flip the view and re-render every cell.
+fn toggle_translation(
+    mut actions: MessageReader<ActionFired>,
+    mut translations: ResMut<BlockTranslations>,
+    mut cells: Query<&mut BlockCell>,
+    mut toasts: ResMut<Toasts>,
+    time: Res<Time>,
+) {
+    for ActionFired { action, .. } in actions.read() {
+        if !matches!(action, Action::ToggleTranslation) {
+            continue;
+        }
+        let Some(language) = translations.language.clone() else {
+            toasts.push(
+                ToastSeverity::Warning,
+                "No translation language",
+                format!("set the ui.{LANGUAGE_PREF} preference (e.g. `ja`) and reconnect"),
+                time.elapsed_secs_f64(),
+            );
+            continue;
+        };
+        translations.enabled = !translations.enabled;
+        let title = if translations.enabled {
+            format!("Translated view ({language})")
+        } else {
+            "Original text".to_string()
+        };
+        toasts.push(ToastSeverity::Info, title, "", time.elapsed_secs_f64());
+        for mut cell in cells.iter_mut() {
+            cell.last_render_version = None;
+        }
+    }
+}
+
+/// Fetch the blocks `display` missed on.
+fn request_translations(actor: Option<Res<RpcActor>>, mut translations: ResMut<BlockTranslations>) {
+    let Some(actor) = actor else { return };
+    if translations.wanted.is_empty() {
+        return;
+    }
+    for (block_id, source) in std::mem::take(&mut translations.wanted) {
+        let handle = actor.handle.clone();
+        let tx = translations.tx.clone();
+        bevy::tasks::IoTaskPool::get()
+            .spawn(async move {
+                let result = handle
+                    .translate_block(block_id, None)
+                    .await
+                    .map(|t| t.text)
+                    .map_err(|e| e.to_string());
+                let _ = tx.send(Landed::Block {
+                    block_id,
+                    source,
+                    result,
+                });
+            })
+            .detach();
+    }
+}
+
+/// Record landed translations and re-render the cells showing them.
+fn apply_translations(
+    mut translations: ResMut<BlockTranslations>,
+    mut cells: Query<&mut BlockCell>,
+) {
+    if translations.rx.is_empty() {
+        return;
+    }
+    let mut landed = Vec::new();
+    let mut rerender_all = false;
+    while let Ok(event) = translations.rx.try_recv() {
+        match event {
+            Landed::Language(Ok(language)) => {
+                if language != translations.language {
+                    info!("translate: seat language {language:?}");
+                    translations.language = language;
+                    translations.slots.clear();
+                    rerender_all = translations.enabled;
+                }
+            }
+            Landed::Language(Err(e)) => warn!("translate: setting the seat language failed: {e}"),
+            Landed::Block {
+                block_id,
+                source,
+                result,
+            } => {
+                let Some(slot) = translations.slots.get_mut(&block_id) else {
+                    continue;
+                };
+                // The block changed (or the language did) since it was asked for.
+                if slot.source != source {
+                    continue;
+                }
+                slot.state = match result {
+                    Ok(text) => SlotState::Ready(text),
+                    Err(e) => {
+                        warn!("translate: block {} failed: {e}", block_id.to_key());
+                        SlotState::Failed
+                    }
+                };
+                landed.push(block_id);
+            }
+        }
+    }
+    for mut cell in cells.iter_mut() {
+        if rerender_all || landed.contains(&cell.block_id) {
+            cell.last_render_version = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_crdt::{BlockSnapshotBuilder, ContextId, PrincipalId};
+
+    fn text_block(status: Status, content: &str) -> BlockSnapshot {
+        BlockSnapshotBuilder::new(
+            BlockId::new(ContextId::new(), PrincipalId::new(), 1),
+            BlockKind::Text,
+        )
+        .role(Role::Model)
+        .status(status)
+        .content(content)
+        .build()
+    }
+
+    #[test]
+    fn display_queues_once_and_refetches_on_edit() {
+        let mut translations = BlockTranslations {
+            enabled: true,
+            language: Some("ja".into()),
+            ..default()
+        };
+        let block = text_block(Status::Done, "hello");
+        assert_eq!(translations.display(&block, "hello"), None);
+        assert_eq!(translations.display(&block, "hello"), None);
+        assert_eq!(translations.wanted.len(), 1, "one fetch per text");
+
+        translations.slots.get_mut(&block.id).unwrap().state =
+            SlotState::Ready("こんにちは".into());
+        assert_eq!(
+            translations.display(&block, "hello").as_deref(),
+            Some("こんにちは")
+        );
+        assert_eq!(translations.display(&block, "hello, team"), None);
+        assert_eq!(translations.wanted.len(), 2, "edited text is fetched again");
+
+        let streaming = text_block(Status::Running, "partial");
+        assert_eq!(translations.display(&streaming, "partial"), None);
+        assert_eq!(translations.wanted.len(), 2, "streaming blocks wait");
+    }
+}
@@ -0,0 +1,307 @@
+//! `kj` — kaijutsu from a plain shell.
+//!
+//! A small client for scripts, CI steps, and anyone not running the app or
+//! an MCP host. It speaks to kaijutsu-server over SSH (ssh-agent auth, like
+//! the app) and either calls kernel RPCs directly or runs the kernel's own
+//! `kj` commands in a context.
+//!
+//!   kj docs list [--kind code]
+//!   kj block read <block>
+//!   kj drift push <dst> "found the bug in parse.rs"
+//!   kj shell -- 'ls /mnt/project | wc -l'
+//!   kj export [<context>] [--format json] [-o transcript.md]
+//!   kj gc [--prune]
+//!
+//! `--json` switches every command to machine-readable output on stdout.
+//! Shell-backed commands exit with the remote command's exit code.
+
+mod render;
+mod session;
+
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+use session::{Session, quote};
+
+/// Script against a kaijutsu server.
+#[derive(Parser, Debug)]
+#[command(name = "kj", version)]
+#[command(about = "Script against a kaijutsu server: documents, blocks, drift, shell, export, gc")]
+struct Cli {
+    #[command(flatten)]
+    conn: ConnArgs,
+
+    /// Print JSON on stdout instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args, Debug)]
+struct ConnArgs {
+    /// SSH host of kaijutsu-server
+    #[arg(long, global = true, env = "KJ_HOST", default_value = "localhost")]
+    host: String,
+
+    /// SSH port of kaijutsu-server
+    #[arg(long, global = true, env = "KJ_PORT", default_value_t = 2222)]
+    port: u16,
+
+    /// Context that shell-backed commands run in: a label, id, or unique
+    /// id prefix
+    #[arg(
+        long,
+        short = 'c',
+        global = true,
+        env = "KJ_CONTEXT",
+        default_value = "default"
+    )]
+    context: String,
+
+    /// Seconds to wait for a shell-backed command to finish
+    #[arg(long, global = true, default_value_t = 300)]
+    timeout: u64,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// CRDT documents.
+    Docs {
+        #[command(subcommand)]
+        command: DocsCommand,
+    },
+    /// Blocks.
+    Block {
+        #[command(subcommand)]
+        command: BlockCommand,
+    },
+    /// Cross-context drift.
+    Drift {
+        #[command(subcommand)]
+        command: DriftCommand,
+    },
+    /// Run a kaish command line in the context and print its output.
+    Shell {
+        /// The command line (joined with spaces)
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// Write a context's conversation as Markdown (or JSON blocks).
+    Export {
+        /// Context to export (defaults to --context)
+        context: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Markdown)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+    /// Find state the server can no longer reach: orphan documents, ghost
+    /// contexts, sessions and client views naming deleted contexts. Needs
+    /// the admin authority in --context.
+    Gc {
+        /// Remove what the scan finds instead of only reporting it
+        #[arg(long)]
+        prune: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DocsCommand {
+    /// List every document, including non-conversation kinds.
+    #[command(alias = "ls")]
+    List {
+        /// Filter by kind: conversation|code|text|config
+        #[arg(long)]
+        kind: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum BlockCommand {
+    /// Print a block's content (a label, key, or unique abbreviation).
+    Read { block: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum DriftCommand {
+    /// Stage content from the context for `dst`.
+    Push {
+        /// Destination context reference
+        dst: String,
+        /// LLM-distill the context instead of sending literal content
+        #[arg(long, short = 's')]
+        summarize: bool,
+        /// Content to stage (joined with spaces); `-` reads stdin
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        content: Vec<String>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    // Logs go to stderr; stdout belongs to the caller's pipeline.
+    let filter = EnvFilter::from_default_env().add_directive(tracing::Level::WARN.into());
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .init();
+
+    let cli = Cli::parse();
+    // Cap'n Proto RPC requires LocalSet for !Send types
+    let local_set = tokio::task::LocalSet::new();
+    match local_set.run_until(run(cli)).await {
+        // Out-of-range codes (negative, > 255) still read as failure.
+        Ok(code) => ExitCode::from(u8::try_from(code).unwrap_or(1)),
+        Err(e) => {
+            eprintln!("kj: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run one command; the result is the process exit code.
+async fn run(cli: Cli) -> Result<i32> {
+    let session = Session::connect(&cli.conn.host, cli.conn.port).await?;
+    let timeout = Duration::from_secs(cli.conn.timeout);
+    match cli.command {
+        Command::Docs {
+            command: DocsCommand::List { kind },
+        } => {
+            let mut line = "kj doc list".to_string();
+            if let Some(kind) = kind {
+                line.push_str(&format!(" --kind {}", quote(&kind)));
+            }
+            if cli.json {
+                // `kj doc list --json` prints the JSON object itself.
+                line.push_str(" --json");
+            }
+            run_kj(&session, &cli.conn.context, &line, timeout, false).await
+        }
+        Command::Block {
+            command: BlockCommand::Read { block },
+        } => {
+            let snapshot = session.read_block(&block).await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&snapshot)?);
+            } else {
+                print!("{}", snapshot.content);
+                if !snapshot.content.ends_with('\n') {
+                    println!();
+                }
+            }
+            Ok(0)
+        }
+        Command::Drift {
+            command:
+                DriftCommand::Push {
+                    dst,
+                    summarize,
+                    content,
+                },
+        } => {
+            let mut line = format!("kj drift push {}", quote(&dst));
+            if summarize {
+                line.push_str(" --summarize");
+            }
+            let content = if content == ["-"] {
+                std::io::read_to_string(std::io::stdin()).context("reading stdin")?
+            } else {
+                content.join(" ")
+            };
+            if !content.is_empty() {
+                line.push(' ');
+                line.push_str(&quote(&content));
+            }
+            run_kj(&session, &cli.conn.context, &line, timeout, cli.json).await
+        }
+        Command::Shell { command } => {
+            run_kj(
+                &session,
+                &cli.conn.context,
+                &command.join(" "),
+                timeout,
+                cli.json,
+            )
+            .await
+        }
+        Command::Export {
+            context,
+            format,
+            output,
+        } => {
+            let query = context.as_deref().unwrap_or(&cli.conn.context);
+            let context_id = session.resolve_context(query).await?;
+            let blocks = session.blocks(context_id).await?;
+            let text = if format == ExportFormat::Json || cli.json {
+                serde_json::to_string_pretty(&blocks)? + "\n"
+            } else {
+                render::transcript(context_id, query, &blocks)
+            };
+            match output {
+                Some(path) => std::fs::write(&path, text)
+                    .with_context(|| format!("writing {}", path.display()))?,
+                None => print!("{text}"),
+            }
+            Ok(0)
+        }
+        Command::Gc { prune } => {
+            let context_id = session.resolve_context(&cli.conn.context).await?;
+            let report = session.scan_garbage(context_id, prune).await?;
+            if cli.json {
+                println!("{}", render::garbage_json(&report));
+            } else {
+                print!("{}", render::garbage(&report));
+            }
+            Ok(0)
+        }
+    }
+}
+
+/// Run a command line in the context. Text mode streams stdout as it
+/// arrives and echoes stderr; JSON mode prints the result envelope once
+/// the command finishes.
+async fn run_kj(
+    session: &Session,
+    context: &str,
+    line: &str,
+    timeout: Duration,
+    json: bool,
+) -> Result<i32> {
+    let context_id = session.resolve_context(context).await?;
+    let result = session
+        .shell(context_id, line, timeout, |chunk| {
+            if !json {
+                let mut stdout = std::io::stdout().lock();
+                let _ = stdout.write_all(chunk.as_bytes());
+                let _ = stdout.flush();
+            }
+        })
+        .await?;
+    if json {
+        println!("{}", render::shell_json(&result));
+    } else {
+        if !result.content.is_empty() && !result.content.ends_with('\n') {
+            println!();
+        }
+        if let Some(stderr) = result.stderr.as_deref().filter(|s| !s.is_empty()) {
+            eprint!("{stderr}");
+        }
+    }
+    Ok(render::exit_status(&result))
+}
@@ -0,0 +1,231 @@
+//! Output shapes: the Markdown transcript for `kj export`, the JSON
+//! envelope for shell results (the same shape the MCP `shell` tool returns),
+//! and the `kj gc` report.
+
+use kaijutsu_client::GarbageReport;
+use kaijutsu_types::language::markdown_fence;
+use kaijutsu_types::reflow::reflows_language;
+use kaijutsu_types::{BlockKind, BlockSnapshot, ContextId};
+
+/// A context's blocks as a Markdown transcript. Thinking, notification, and
+/// ephemeral blocks are left out; tool calls and results are fenced.
+pub fn transcript(context_id: ContextId, label: &str, blocks: &[BlockSnapshot]) -> String {
+    let title = if label.is_empty() { "context" } else { label };
+    let mut out = format!(
+        "# {title}\n\n_kaijutsu context `{}`_\n\n",
+        context_id.to_hex()
+    );
+    for block in blocks {
+        if block.ephemeral {
+            continue;
+        }
+        match block.kind {
+            BlockKind::Thinking | BlockKind::Notification => continue,
+            BlockKind::ToolCall => {
+                let name = block.tool_name.as_deref().unwrap_or("tool");
+                let input = if block.content.is_empty() {
+                    block.tool_input.as_deref().unwrap_or_default()
+                } else {
+                    block.content.as_str()
+                };
+                out.push_str(&format!("**{} → `{name}`**\n\n", block.role.as_str()));
+                out.push_str(&markdown_fence(input, Some("json")));
+            }
+            BlockKind::ToolResult => {
+                let head = match block.exit_code {
+                    Some(code) if code != 0 => format!("result (exit {code})"),
+                    _ if block.is_error => "result (error)".to_string(),
+                    _ => "result".to_string(),
+                };
+                out.push_str(&format!("**{head}**\n\n"));
+                out.push_str(&markdown_fence(&block.content, block.language.as_deref()));
+            }
+            BlockKind::Drift => {
+                let from = block
+                    .source_context
+                    .map(|ctx| format!(" from `{}`", ctx.short()))
+                    .unwrap_or_default();
+                out.push_str(&format!("**drift{from}**\n\n"));
+                out.push_str(block.content.trim_end());
+                out.push('\n');
+            }
+            _ => {
+                out.push_str(&format!("**{}**\n\n", block.role.as_str()));
+                if reflows_language(block.language.as_deref()) {
+                    out.push_str(block.content.trim_end());
+                    out.push('\n');
+                } else {
+                    out.push_str(&markdown_fence(&block.content, block.language.as_deref()));
+                }
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A finished shell result as `{stdout, stderr, exit_code, status, block_id,
+/// content_type, data}`. `exit_code` is `null` when the server never set one;
+/// `data` is the `kj` structured payload when the command produced one.
+pub fn shell_json(result: &BlockSnapshot) -> serde_json::Value {
+    serde_json::json!({
+        "stdout": result.content,
+        "stderr": result.stderr.clone().unwrap_or_default(),
+        "exit_code": result.exit_code,
+        "status": result.status.as_str(),
+        "block_id": result.id.to_key(),
+        "content_type": result.content_type.as_mime(),
+        "data": result.output.as_ref().map(|o| o.to_json()),
+    })
+}
+
+/// The process exit status for a shell result: its own exit code, else 1
+/// for an error status and 0 otherwise.
+pub fn exit_status(result: &BlockSnapshot) -> i32 {
+    match result.exit_code {
+        Some(code) => code,
+        None if result.is_error || result.status == kaijutsu_types::Status::Error => 1,
+        None => 0,
+    }
+}
+
+/// A garbage report as one line per finding, then a summary line.
+pub fn garbage(report: &GarbageReport) -> String {
+    let mut out = String::new();
+    for id in &report.orphan_documents {
+        out.push_str(&format!("orphan document  {}\n", id.to_hex()));
+    }
+    for id in &report.ghost_contexts {
+        out.push_str(&format!("ghost context    {}\n", id.to_hex()));
+    }
+    for (session, ctx) in &report.stale_sessions {
+        out.push_str(&format!(
+            "stale session    {} -> {}\n",
+            session.short(),
+            ctx.to_hex()
+        ));
+    }
+    for (client_id, ctx) in &report.stale_client_views {
+        out.push_str(&format!(
+            "stale view       {client_id} -> {}\n",
+            ctx.to_hex()
+        ));
+    }
+    let found = report.orphan_documents.len()
+        + report.ghost_contexts.len()
+        + report.stale_sessions.len()
+        + report.stale_client_views.len();
+    let verb = match (found, report.pruned) {
+        (0, _) => "nothing to collect".to_string(),
+        (n, true) => format!("{n} pruned"),
+        (n, false) => format!("{n} found (kj gc --prune to remove)"),
+    };
+    out.push_str(&verb);
+    out.push('\n');
+    out
+}
+
+/// A garbage report as `{orphan_documents, ghost_contexts, stale_sessions,
+/// stale_client_views, pruned}`; ids are hex.
+pub fn garbage_json(report: &GarbageReport) -> serde_json::Value {
+    let hex = |ids: &[ContextId]| ids.iter().map(|id| id.to_hex()).collect::<Vec<_>>();
+    serde_json::json!({
+        "orphan_documents": hex(&report.orphan_documents),
+        "ghost_contexts": hex(&report.ghost_contexts),
+        "stale_sessions": report
+            .stale_sessions
+            .iter()
+            .map(|(session, ctx)| serde_json::json!({
+                "session_id": session.to_hex(),
+                "context_id": ctx.to_hex(),
+            }))
+            .collect::<Vec<_>>(),
+        "stale_client_views": report
+            .stale_client_views
+            .iter()
+            .map(|(client_id, ctx)| serde_json::json!({
+                "client_id": client_id,
+                "context_id": ctx.to_hex(),
+            }))
+            .collect::<Vec<_>>(),
+        "pruned": report.pruned,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_types::{BlockId, BlockSnapshotBuilder, PrincipalId, Role, Status};
+
+    fn block(
+        ctx: ContextId,
+        seq: u64,
+        kind: BlockKind,
+        role: Role,
+        content: &str,
+    ) -> BlockSnapshot {
+        BlockSnapshotBuilder::new(BlockId::new(ctx, PrincipalId::new(), seq), kind)
+            .role(role)
+            .content(content)
+            .build()
+    }
+
+    #[test]
+    fn transcript_skips_thinking_and_fences_tools() {
+        let ctx = ContextId::new();
+        let blocks = vec![
+            block(ctx, 0, BlockKind::Text, Role::User, "list the files"),
+            block(ctx, 1, BlockKind::Thinking, Role::Model, "hmm"),
+            block(ctx, 2, BlockKind::ToolResult, Role::Tool, "a.rs\nb.rs"),
+            block(ctx, 3, BlockKind::Text, Role::Model, "Two files."),
+        ];
+        let md = transcript(ctx, "demo", &blocks);
+        assert!(md.starts_with("# demo\n"));
+        assert!(md.contains("list the files"));
+        assert!(!md.contains("hmm"));
+        assert!(md.contains("```\na.rs\nb.rs\n```"));
+        assert!(md.contains("Two files."));
+    }
+
+    #[test]
+    fn shell_json_and_exit_status() {
+        let ctx = ContextId::new();
+        let mut result = block(ctx, 0, BlockKind::ToolResult, Role::Tool, "out");
+        result.status = Status::Done;
+        assert_eq!(exit_status(&result), 0);
+        let json = shell_json(&result);
+        assert_eq!(json["stdout"], "out");
+        assert!(json["exit_code"].is_null());
+
+        result.exit_code = Some(3);
+        assert_eq!(exit_status(&result), 3);
+        assert_eq!(shell_json(&result)["exit_code"], 3);
+
+        result.exit_code = None;
+        result.status = Status::Error;
+        assert_eq!(exit_status(&result), 1);
+    }
+
+    #[test]
+    fn garbage_report_lists_findings_and_summarizes() {
+        assert_eq!(garbage(&GarbageReport::default()), "nothing to collect\n");
+
+        let ghost = ContextId::new();
+        let mut report = GarbageReport {
+            ghost_contexts: vec![ghost],
+            stale_client_views: vec![("laptop".to_string(), ghost)],
+            ..GarbageReport::default()
+        };
+        let text = garbage(&report);
+        assert!(text.contains(&format!("ghost context    {}", ghost.to_hex())));
+        assert!(text.contains("stale view       laptop ->"));
+        assert!(text.ends_with("2 found (kj gc --prune to remove)\n"));
+
+        report.pruned = true;
+        assert!(garbage(&report).ends_with("2 pruned\n"));
+        let json = garbage_json(&report);
+        assert_eq!(json["ghost_contexts"][0], ghost.to_hex());
+        assert_eq!(json["stale_client_views"][0]["client_id"], "laptop");
+        assert_eq!(json["pruned"], true);
+    }
+}
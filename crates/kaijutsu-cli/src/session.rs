@@ -0,0 +1,160 @@
+//! One SSH session to kaijutsu-server, bound to its kernel.
+
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, anyhow};
+use kaijutsu_client::{GarbageReport, KernelHandle, RpcClient, SshConfig, connect_ssh};
+use kaijutsu_types::{
+    BlockFilter, BlockId, BlockKind, BlockQuery, BlockSnapshot, ContextId, resolve_context_prefix,
+};
+use tokio::sync::mpsc;
+
+/// How often to look for a command's result block before it exists.
+const RESULT_POLL: Duration = Duration::from_millis(100);
+
+pub struct Session {
+    // Held for the SSH session's lifetime; every call goes through `kernel`.
+    _client: RpcClient,
+    kernel: KernelHandle,
+}
+
+impl Session {
+    pub async fn connect(host: &str, port: u16) -> Result<Self> {
+        let config = SshConfig {
+            host: host.to_string(),
+            port,
+            username: whoami::username(),
+            ..SshConfig::default()
+        };
+        let client = connect_ssh(config)
+            .await
+            .with_context(|| format!("connecting to {host}:{port}"))?;
+        let (kernel, _) = client.bind_kernel().await.context("binding kernel")?;
+        Ok(Self {
+            _client: client,
+            kernel,
+        })
+    }
+
+    /// A context by label, full id, or unique hex prefix.
+    pub async fn resolve_context(&self, query: &str) -> Result<ContextId> {
+        let contexts = self.kernel.list_contexts().await?;
+        let entries = contexts.iter().map(|c| {
+            let label = (!c.label.is_empty()).then_some(c.label.as_str());
+            (c.id, label)
+        });
+        resolve_context_prefix(entries, query).map_err(|e| anyhow!("context '{query}': {e}"))
+    }
+
+    /// A block by label, key, or unique abbreviation, with its snapshot.
+    pub async fn read_block(&self, query: &str) -> Result<BlockSnapshot> {
+        let id = self.kernel.resolve_block(query).await?;
+        self.block(&id)
+            .await?
+            .ok_or_else(|| anyhow!("block {} is not in its context", id.to_key()))
+    }
+
+    async fn block(&self, id: &BlockId) -> Result<Option<BlockSnapshot>> {
+        let blocks = self
+            .kernel
+            .get_blocks(id.context_id, &BlockQuery::ByIds(vec![*id]))
+            .await?;
+        Ok(blocks.into_iter().next())
+    }
+
+    /// Every block of a context, in document order.
+    pub async fn blocks(&self, context_id: ContextId) -> Result<Vec<BlockSnapshot>> {
+        Ok(self.kernel.get_blocks(context_id, &BlockQuery::All).await?)
+    }
+
+    /// Scan the server for unreachable state, removing it with `prune`.
+    /// Joins `context_id` first: the scan is authorized by the caller's own
+    /// context's admin authority.
+    pub async fn scan_garbage(&self, context_id: ContextId, prune: bool) -> Result<GarbageReport> {
+        self.kernel
+            .join_context(context_id, "kaijutsu-cli")
+            .await
+            .with_context(|| format!("joining {}", context_id.short()))?;
+        Ok(self.kernel.scan_garbage(context_id, prune).await?)
+    }
+
+    /// Run a kaish command line in `context_id` and wait for its result
+    /// block. Output is handed to `on_chunk` as it streams; the returned
+    /// snapshot is read after the block goes terminal, so its content,
+    /// exit code, and stderr are final.
+    pub async fn shell(
+        &self,
+        context_id: ContextId,
+        code: &str,
+        timeout: Duration,
+        mut on_chunk: impl FnMut(&str),
+    ) -> Result<BlockSnapshot> {
+        let cmd = self.kernel.shell_execute(code, context_id, false).await?;
+        tokio::time::timeout(timeout, async {
+            let result = self.result_block(context_id, cmd).await?;
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let tail = self.kernel.tail_block(&result, 0, tx);
+            tokio::pin!(tail);
+            loop {
+                tokio::select! {
+                    Some(chunk) = rx.recv() => on_chunk(&chunk.text),
+                    end = &mut tail => {
+                        end?;
+                        break;
+                    }
+                }
+            }
+            while let Ok(chunk) = rx.try_recv() {
+                on_chunk(&chunk.text);
+            }
+            self.block(&result)
+                .await?
+                .ok_or_else(|| anyhow!("result block {} was deleted", result.to_key()))
+        })
+        .await
+        .map_err(|_| anyhow!("no result after {}s", timeout.as_secs()))?
+    }
+
+    /// The shell result block under command block `cmd`.
+    async fn result_block(&self, context_id: ContextId, cmd: BlockId) -> Result<BlockId> {
+        let query = BlockQuery::ByFilter(BlockFilter {
+            kinds: vec![BlockKind::ToolResult],
+            parent_id: Some(cmd),
+            max_depth: 1,
+            ..BlockFilter::default()
+        });
+        loop {
+            let blocks = self.kernel.get_blocks(context_id, &query).await?;
+            if let Some(block) = blocks.into_iter().find(|b| b.is_shell()) {
+                return Ok(block.id);
+            }
+            tokio::time::sleep(RESULT_POLL).await;
+        }
+    }
+}
+
+/// Quote one argument for a kaish command line.
+pub fn quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@+=,".contains(c))
+    {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_leaves_plain_words_and_wraps_the_rest() {
+        assert_eq!(quote("ctx-1"), "ctx-1");
+        assert_eq!(quote("hello world"), "'hello world'");
+        assert_eq!(quote("it's"), "'it'\\''s'");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("$HOME"), "'$HOME'");
+    }
+}
@@ -10,7 +10,7 @@
 //!        ▼
 //! ┌──────────────────────────┐
 //! │ Connecting { attempt }   │ ◄──┐
-//! │ (handshake task running) │    │ timer expired
+//! │ (handshake task running) │    │ timer expired, or retry_now
 //! └──┬───────┬───────┬───────┘    │
 //!    │       │       │            │
 //!    │ Ok    │ trans │ perm       │
@@ -57,11 +57,22 @@
 //! 7. The `instance` UUID is set once at actor construction and reused for
 //!    every `join_context` and every `subscribe_*` call. The server uses
 //!    `(principal, instance)` to dedupe subscriptions across reconnects.
+//!
+//! 8. Seats from `ActorHandle::open_seat` ride the same connection, each a
+//!    `bindSeat` binding with its own session, instance
+//!    (`{instance}/seat-{n}`) and event broadcast. They outlive the
+//!    connection like `peer_registration` does: every `enter_connected`
+//!    re-binds them, resuming each seat's session by its token.
 
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
-use kaijutsu_crdt::{ContextId, KernelId};
-use kaijutsu_types::{BlockFilter, BlockId, BlockQuery, BlockSnapshot};
+use kaijutsu_crdt::{ContextId, Frontier, KernelId};
+use kaijutsu_types::{
+    BlockFilter, BlockId, BlockLock, BlockQuery, BlockSnapshot, ConsentDecision, ConsentPrompt,
+    ContextCloseFilter, ContextListQuery, ContextTimeline, DocBookmark, KernelListQuery,
+    LockPolicy, Preferences,
+};
 use tokio::sync::{broadcast, mpsc, oneshot, watch};
 use tokio::task::JoinHandle;
 use tracing::Instrument;
@@ -72,15 +83,17 @@ use crate::constants::{
     SUBSCRIBE_TIMEOUT,
 };
 use crate::rpc::{
-    Completion, ContextCluster, ContextInfo, EditorState, HistoryEntry, Identity, InputState,
-    KernelInfo, LlmConfigInfo, McpResource, McpToolResult, ShellValue, SimilarContext,
-    StagedDriftInfo, SubmitResult, SyncState, ToolResult, ToolSchema, VersionSnapshot,
+    BackupReport, BlockSearchHit, BlockSearchQuery, BlockTailChunk, BlockTailEnd, BlockTranslation, Completion, ConsentLogPage, ContextCluster, ContextInfo, ContextMcpServerInfo,
+    ContextPreview, EditorState, GarbageReport, HistoryEntry, Identity, InboxPage, InputState, KernelInfo, LlmConfigInfo,
+    McpResource, McpServerSpec, McpToolResult, PeekedDocument, ShellValue, SimilarContext, StagedDriftInfo,
+    SeatInfo, SubmitResult, SyncState, ToolResult, ToolSchema, VersionSnapshot,
 };
+use crate::seat::SeatHandle;
 use crate::subscriptions::{
-    BlockEventsForwarder, ConnectionStatus, EditorEventsForwarder, ResourceEventsForwarder,
-    ServerEvent, VfsActivityEventsForwarder,
+    ActivityEventsForwarder, BlockEventsForwarder, ConnectionStatus, EditorEventsForwarder,
+    ResourceEventsForwarder, ServerEvent, VfsActivityEventsForwarder,
 };
-use crate::{ConnectError, KernelHandle, RpcClient, SshConfig, connect_ssh};
+use crate::{ConnectError, KernelHandle, RpcClient, RpcError, SshConfig, connect_ssh};
 
 // ────────────────────────────────────────────────────────────────────────────
 // Capacities
@@ -105,7 +118,11 @@ const STATUS_BROADCAST_CAPACITY: usize = 16;
 ///
 /// Variants distinguish *why* a call didn't complete so callers can react
 /// appropriately: a poller can quietly skip on `NotReady`, but a user-facing
-/// command should surface `PermanentlyFailed` loudly.
+/// command should surface `PermanentlyFailed` loudly. Kernel-side failures
+/// are sorted into `NotFound` / `PermissionDenied` / `Conflict` /
+/// `RateLimited` / `ServerError` by [`CallError::from_rpc_message`];
+/// [`CallError::code`] and [`CallError::is_retryable`] give callers a stable
+/// way to branch without matching message text.
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum CallError {
     /// The actor's FSM is in a state that can't serve this call right now.
@@ -120,23 +137,113 @@ pub enum CallError {
     #[error("permanently failed: {0}")]
     PermanentlyFailed(String),
 
+    /// The pipe broke while the call was in flight. The actor is already
+    /// moving to reconnect; the call may succeed once it's `Connected` again.
+    #[error("disconnected: {0}")]
+    Disconnected(String),
+
+    /// The kernel has no such context, block, peer, or server.
+    #[error("not found: {0}")]
+    NotFound(String),
+
+    /// The kernel refused the call: the context's binding, consent mode, or
+    /// authority set doesn't allow it.
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+
+    /// The call raced another writer (label taken, stale generation).
+    /// Re-read and decide again rather than retrying blindly.
+    #[error("conflict: {0}")]
+    Conflict(String),
+
+    /// The kernel is shedding load (capnp `Overloaded`). Back off and retry.
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+
     /// RPC was attempted, the pipe was alive, and the kernel returned an
-    /// error. Connection is still healthy; retry the call (with different
-    /// args, presumably) if the caller wants to.
-    #[error("RPC error: {0}")]
-    Rpc(String),
+    /// error that fits no narrower variant. Connection is still healthy;
+    /// retry the call (with different args, presumably) if the caller wants to.
+    #[error("server error: {0}")]
+    ServerError(String),
 
     /// Per-call deadline (`RPC_CALL_TIMEOUT` or per-call override) exceeded.
     /// Connection is NOT torn down — the handler hung, not the pipe.
     #[error("call timed out after {0:?}")]
     Timeout(Duration),
 
+    /// The pushed ops were rejected — locally before anything was sent (they
+    /// fail `sync::validate_push_ops`), or by the server's shape check
+    /// (`ops rejected: …`). Retrying the same arguments will fail the same
+    /// way; the caller's document needs a resync.
+    #[error("invalid ops: {0}")]
+    InvalidOps(String),
+
     /// The actor task is no longer running. Either an unrecoverable bug or
     /// shutdown in progress. Callers should stop sending commands.
     #[error("actor shut down")]
     Shutdown,
 }
 
+impl CallError {
+    /// Classify the text of a failed call. Kernel errors arrive as capnp
+    /// exceptions formatted `"<Kind>: <reason>"`; the server raises nearly
+    /// everything as `Failed`, so the kind settles `Disconnected` and
+    /// `RateLimited` and the reason's wording settles the rest.
+    pub fn from_rpc_message(msg: impl Into<String>) -> Self {
+        let msg = msg.into();
+        let lower = msg.to_ascii_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+        if is_disconnect_error(&msg) {
+            Self::Disconnected(msg)
+        } else if msg.starts_with("ops rejected") {
+            Self::InvalidOps(msg)
+        } else if msg.contains("Overloaded") || mentions(&["rate limit", "too many requests"]) {
+            Self::RateLimited(msg)
+        } else if mentions(&["not found", "no such", "unknown context"]) {
+            Self::NotFound(msg)
+        } else if mentions(&[
+            "denied",
+            "lacks the",
+            "not permitted",
+            "unauthorized",
+            "forbidden",
+        ]) {
+            Self::PermissionDenied(msg)
+        } else if mentions(&["conflict", "already exists", "stale"]) {
+            Self::Conflict(msg)
+        } else {
+            Self::ServerError(msg)
+        }
+    }
+
+    /// Stable snake_case code for this error, for tool output and logs.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotReady(_) => "not_ready",
+            Self::PermanentlyFailed(_) => "permanently_failed",
+            Self::Disconnected(_) => "disconnected",
+            Self::NotFound(_) => "not_found",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::Conflict(_) => "conflict",
+            Self::RateLimited(_) => "rate_limited",
+            Self::ServerError(_) => "server_error",
+            Self::Timeout(_) => "timeout",
+            Self::InvalidOps(_) => "invalid_ops",
+            Self::Shutdown => "shutdown",
+        }
+    }
+
+    /// Whether the same call, unchanged, may succeed if tried again later.
+    /// True for connection trouble and load shedding; false when the kernel
+    /// answered and said no.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::NotReady(_) | Self::Disconnected(_) | Self::Timeout(_) | Self::RateLimited(_)
+        )
+    }
+}
+
 /// Why the actor declined to serve a call. Returned inside `CallError::NotReady`.
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum NotReadyReason {
@@ -175,6 +282,9 @@ enum ActorState {
     Connecting {
         attempt: u32,
         started_at: Instant,
+        /// Why the previous attempt failed, carried in from `Cooldown` (or
+        /// `Terminal`, on a manual retry). `None` on a first dial.
+        last_error: Option<String>,
     },
     Connected {
         since: Instant,
@@ -189,6 +299,8 @@ enum ActorState {
     Cooldown {
         next_attempt: u32,
         until: Instant,
+        /// The whole wait, `until` minus when the cooldown began.
+        backoff: Duration,
         last_error: String,
     },
     Terminal {
@@ -256,6 +368,8 @@ struct BuiltConnection {
     kernel: KernelHandle,
     kernel_id: KernelId,
     joined_context: Option<ContextId>,
+    /// The seat the handshake's join landed in (resumed or fresh).
+    seat: Option<SeatInfo>,
 }
 
 /// Wraps the live connection while the actor is in `Connected`.
@@ -267,14 +381,41 @@ struct ConnectionState {
     kernel: KernelHandle,
 }
 
+/// A seat opened on the connection (see `ActorHandle::open_seat`).
+struct SeatRecord {
+    context_id: ContextId,
+    /// `{actor instance}/seat-{n}` — the seat's subscribe dedupe key.
+    instance: String,
+    /// Resumes the seat's session when it is re-bound.
+    token: Option<String>,
+    event_tx: broadcast::Sender<ServerEvent>,
+    /// The seat's `bindSeat` capability; `None` while (re-)binding.
+    kernel: Option<KernelHandle>,
+}
+
+/// `OpenSeat`'s reply: the seat number and its event broadcast.
+type OpenSeatReply = oneshot::Sender<Result<(u64, broadcast::Sender<ServerEvent>), CallError>>;
+
 /// Internal messages spawned child tasks send back to the actor loop.
 ///
 /// Used so a long-running RPC (e.g., `join_context` against a slow kernel)
 /// doesn't block the actor's main loop and the loop can still react to
 /// close signals like a ping failure in the meantime.
 enum InternalMsg {
-    /// A `join_context` call returned successfully — update cached context.
-    JoinedContext(ContextId),
+    /// A `join_context` call returned successfully — update cached context
+    /// and, if the server issued one, the seat token.
+    JoinedContext(ContextId, Option<String>),
+    /// A seat finished binding on the connection of `epoch`. `rebound` is
+    /// set when this re-binds a seat after a reconnect.
+    SeatBound {
+        seat: u64,
+        epoch: u64,
+        kernel: KernelHandle,
+        token: Option<String>,
+        rebound: bool,
+    },
+    /// A seat's opener is gone (bind failed or the caller stopped waiting).
+    SeatDropped(u64),
 }
 
 // ────────────────────────────────────────────────────────────────────────────
@@ -304,6 +445,10 @@ enum RpcCommand {
     ListContexts {
         reply: oneshot::Sender<Result<Vec<ContextInfo>, CallError>>,
     },
+    ListContextsQuery {
+        query: ContextListQuery,
+        reply: oneshot::Sender<Result<crate::rpc::ContextPage, CallError>>,
+    },
     ListTracks {
         reply: oneshot::Sender<Result<Vec<crate::rpc::TrackInfo>, CallError>>,
     },
@@ -314,6 +459,17 @@ enum RpcCommand {
         max_entries: u32,
         reply: oneshot::Sender<Result<crate::rpc::SnapshotResult, CallError>>,
     },
+    VfsCreate {
+        path: String,
+        mode: u32,
+        reply: oneshot::Sender<Result<(), CallError>>,
+    },
+    VfsWrite {
+        path: String,
+        offset: u64,
+        data: Vec<u8>,
+        reply: oneshot::Sender<Result<u32, CallError>>,
+    },
     /// Start (or no-op if already started) the VFS activity digest push
     /// subscription for this connection. Handled entirely inline by
     /// `RpcActor::dispatch` (needs `self.event_tx` to build the forwarder,
@@ -323,6 +479,34 @@ enum RpcCommand {
         interval_ms: u32,
         reply: oneshot::Sender<Result<(), CallError>>,
     },
+    /// Start (or no-op if already started) the per-context activity push
+    /// subscription. Handled inline by `RpcActor::dispatch`, like
+    /// `SubscribeVfsActivity`.
+    SubscribeActivity {
+        interval_ms: u32,
+        reply: oneshot::Sender<Result<(), CallError>>,
+    },
+    /// Read-only look at a context without joining it. Handled inline by
+    /// `RpcActor::dispatch`: a streaming peek needs `self.event_tx` for its
+    /// forwarder and `self.peeks` to hold it open.
+    PeekDocument {
+        context_id: ContextId,
+        ttl_secs: u32,
+        stream: bool,
+        reply: oneshot::Sender<Result<PeekedDocument, CallError>>,
+    },
+    /// End a streaming peek early. Handled inline, like `PeekDocument`.
+    EndPeek {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<(), CallError>>,
+    },
+    /// Stream one block's events on a channel of their own. Handled inline,
+    /// like `PeekDocument`: `self.block_subs` ends it on disconnect.
+    SubscribeBlock {
+        block_id: BlockId,
+        reply:
+            oneshot::Sender<Result<(BlockSnapshot, broadcast::Receiver<ServerEvent>), CallError>>,
+    },
     Conclude {
         context_id: ContextId,
         reply: oneshot::Sender<Result<(), CallError>>,
@@ -349,11 +533,90 @@ enum RpcCommand {
         context_id: ContextId,
         reply: oneshot::Sender<Result<(), CallError>>,
     },
+    CloseContexts {
+        filter: ContextCloseFilter,
+        dry_run: bool,
+        reply: oneshot::Sender<Result<Vec<ContextId>, CallError>>,
+    },
+    ReopenContext {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<(), CallError>>,
+    },
+    GetContextStats {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<kaijutsu_types::ContextStats, CallError>>,
+    },
+    GetBudgetStatus {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<kaijutsu_types::BudgetStatus, CallError>>,
+    },
+    SetExecutionBudget {
+        context_id: ContextId,
+        budget: Option<kaijutsu_types::ExecutionBudget>,
+        reply: oneshot::Sender<Result<kaijutsu_types::BudgetStatus, CallError>>,
+    },
+    SetToolHalt {
+        context_id: ContextId,
+        halted: bool,
+        reason: String,
+        reply: oneshot::Sender<Result<kaijutsu_types::BudgetStatus, CallError>>,
+    },
+    GetLlmParams {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<kaijutsu_types::LlmParams, CallError>>,
+    },
+    SetLlmParams {
+        context_id: ContextId,
+        params: Option<kaijutsu_types::LlmParams>,
+        merge: bool,
+        reply: oneshot::Sender<Result<kaijutsu_types::LlmParams, CallError>>,
+    },
+    ContextPreview {
+        context_id: ContextId,
+        strategy: Option<String>,
+        reply: oneshot::Sender<Result<ContextPreview, CallError>>,
+    },
+    ContextRecover {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<kaijutsu_types::ContextRecovery, CallError>>,
+    },
+    DigestSubscribe {
+        subscriber: ContextId,
+        source: Option<ContextId>,
+        every_secs: Option<u32>,
+        on_milestone: bool,
+        focus: Option<String>,
+        remove: bool,
+        reply: oneshot::Sender<Result<Vec<kaijutsu_types::DigestSubscription>, CallError>>,
+    },
+    BroadcastPrompt {
+        content: String,
+        targets: Vec<kaijutsu_types::BroadcastTarget>,
+        label: Option<String>,
+        reply: oneshot::Sender<Result<kaijutsu_types::BroadcastStatus, CallError>>,
+    },
+    GetBroadcast {
+        id: u64,
+        reply: oneshot::Sender<Result<kaijutsu_types::BroadcastStatus, CallError>>,
+    },
+    GetSandboxProfile {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<Option<kaijutsu_types::SandboxProfile>, CallError>>,
+    },
+    SetSandboxProfile {
+        context_id: ContextId,
+        profile: Option<kaijutsu_types::SandboxProfile>,
+        reply: oneshot::Sender<Result<Option<kaijutsu_types::SandboxProfile>, CallError>>,
+    },
     SearchSimilar {
         query: String,
         k: u32,
         reply: oneshot::Sender<Result<Vec<SimilarContext>, CallError>>,
     },
+    SearchBlocks {
+        query: BlockSearchQuery,
+        reply: oneshot::Sender<Result<Vec<BlockSearchHit>, CallError>>,
+    },
     GetNeighbors {
         context_id: ContextId,
         k: u32,
@@ -368,6 +631,19 @@ enum RpcCommand {
         context_type: String,
         reply: oneshot::Sender<Result<ContextId, CallError>>,
     },
+    ReserveContextName {
+        label: String,
+        reply: oneshot::Sender<Result<kaijutsu_types::ContextNameReservation, CallError>>,
+    },
+    CommitContextName {
+        token: kaijutsu_types::ReservationId,
+        context_type: String,
+        reply: oneshot::Sender<Result<ContextId, CallError>>,
+    },
+    ReleaseContextName {
+        token: kaijutsu_types::ReservationId,
+        reply: oneshot::Sender<Result<bool, CallError>>,
+    },
 
     // ── CRDT Sync ────────────────────────────────────────────────────────
     PushOps {
@@ -388,6 +664,12 @@ enum RpcCommand {
         context_id: ContextId,
         reply: oneshot::Sender<Result<(u64, u64), CallError>>,
     },
+    MoveBlock {
+        context_id: ContextId,
+        block_id: BlockId,
+        after: Option<BlockId>,
+        reply: oneshot::Sender<Result<u64, CallError>>,
+    },
 
     // ── Shell / Execution ────────────────────────────────────────────────
     Execute {
@@ -400,12 +682,136 @@ enum RpcCommand {
         user_initiated: bool,
         reply: oneshot::Sender<Result<BlockId, CallError>>,
     },
+    ShellRerun {
+        block_id: BlockId,
+        user_initiated: bool,
+        reply: oneshot::Sender<Result<BlockId, CallError>>,
+    },
     SetBlockExcluded {
         context_id: ContextId,
         block_id: BlockId,
         excluded: bool,
         reply: oneshot::Sender<Result<u64, CallError>>,
     },
+    ReorderBlock {
+        context_id: ContextId,
+        block_id: BlockId,
+        after: Option<BlockId>,
+        reply: oneshot::Sender<Result<u64, CallError>>,
+    },
+    SetBlockLabel {
+        context_id: ContextId,
+        block_id: BlockId,
+        label: Option<String>,
+        reply: oneshot::Sender<Result<u64, CallError>>,
+    },
+    ResolveBlock {
+        query: String,
+        reply: oneshot::Sender<Result<BlockId, CallError>>,
+    },
+    BackupNow {
+        reply: oneshot::Sender<Result<BackupReport, CallError>>,
+    },
+    ScanGarbage {
+        context_id: ContextId,
+        prune: bool,
+        reply: oneshot::Sender<Result<GarbageReport, CallError>>,
+    },
+    SetSeatLanguage {
+        language: Option<String>,
+        reply: oneshot::Sender<Result<Option<String>, CallError>>,
+    },
+    TranslateBlock {
+        block_id: BlockId,
+        language: Option<String>,
+        reply: oneshot::Sender<Result<BlockTranslation, CallError>>,
+    },
+    LockBlock {
+        block_id: BlockId,
+        ttl_secs: Option<u64>,
+        note: String,
+        reply: oneshot::Sender<Result<BlockLock, CallError>>,
+    },
+    UnlockBlock {
+        block_id: BlockId,
+        reply: oneshot::Sender<Result<bool, CallError>>,
+    },
+    ListBlockLocks {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<(Vec<BlockLock>, LockPolicy), CallError>>,
+    },
+    SetLockPolicy {
+        context_id: ContextId,
+        policy: LockPolicy,
+        reply: oneshot::Sender<Result<(), CallError>>,
+    },
+    CreateBookmark {
+        context_id: ContextId,
+        name: String,
+        reply: oneshot::Sender<Result<DocBookmark, CallError>>,
+    },
+    ListBookmarks {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<Vec<DocBookmark>, CallError>>,
+    },
+    ListConsentPrompts {
+        reply: oneshot::Sender<Result<Vec<ConsentPrompt>, CallError>>,
+    },
+    ConsentRespond {
+        prompt_id: u64,
+        decision: ConsentDecision,
+        reply: oneshot::Sender<Result<ConsentPrompt, CallError>>,
+    },
+    GetContextTimeline {
+        context_id: ContextId,
+        window_secs: u64,
+        buckets: u32,
+        reply: oneshot::Sender<Result<ContextTimeline, CallError>>,
+    },
+    RegisterMcpServer {
+        context_id: ContextId,
+        spec: McpServerSpec,
+        reply: oneshot::Sender<Result<ContextMcpServerInfo, CallError>>,
+    },
+    UnregisterMcpServer {
+        context_id: ContextId,
+        instance: String,
+        reply: oneshot::Sender<Result<bool, CallError>>,
+    },
+    ListContextMcpServers {
+        context_id: ContextId,
+        reply: oneshot::Sender<Result<Vec<ContextMcpServerInfo>, CallError>>,
+    },
+    ListInbox {
+        include_acked: bool,
+        limit: u32,
+        reply: oneshot::Sender<Result<InboxPage, CallError>>,
+    },
+    AckInbox {
+        ids: Vec<u64>,
+        reply: oneshot::Sender<Result<(u32, u64), CallError>>,
+    },
+    GetPreferences {
+        reply: oneshot::Sender<Result<Preferences, CallError>>,
+    },
+    SetPreference {
+        key: String,
+        value: Option<String>,
+        reply: oneshot::Sender<Result<Preferences, CallError>>,
+    },
+    ListConsentLog {
+        after_seq: u64,
+        limit: u32,
+        verify: bool,
+        reply: oneshot::Sender<Result<ConsentLogPage, CallError>>,
+    },
+    TailBlock {
+        block_id: BlockId,
+        from_offset: u64,
+        timeout: Duration,
+        tx: mpsc::UnboundedSender<BlockTailChunk>,
+        reply: oneshot::Sender<Result<BlockTailEnd, CallError>>,
+    },
     Interrupt {
         exec_id: u64,
         reply: oneshot::Sender<Result<(), CallError>>,
@@ -492,6 +898,22 @@ enum RpcCommand {
         keys: String,
         reply: oneshot::Sender<Result<EditorState, CallError>>,
     },
+    EditorOpen {
+        path: String,
+        reply: oneshot::Sender<Result<EditorState, CallError>>,
+    },
+    GetEditorState {
+        session_id: u64,
+        reply: oneshot::Sender<Result<EditorState, CallError>>,
+    },
+    EditorSave {
+        session_id: u64,
+        reply: oneshot::Sender<Result<EditorState, CallError>>,
+    },
+    EditorQuit {
+        session_id: u64,
+        reply: oneshot::Sender<Result<(), CallError>>,
+    },
 
     // ── Tool Execution ───────────────────────────────────────────────────
     ExecuteTool {
@@ -519,6 +941,7 @@ enum RpcCommand {
         content: String,
         model: Option<String>,
         context_id: ContextId,
+        params: Option<kaijutsu_types::LlmParams>,
         reply: oneshot::Sender<Result<String, CallError>>,
     },
     ConfigureLlm {
@@ -527,6 +950,11 @@ enum RpcCommand {
         model: String,
         reply: oneshot::Sender<Result<bool, CallError>>,
     },
+    SwitchContextModel {
+        context_id: ContextId,
+        spec: String,
+        reply: oneshot::Sender<Result<(String, String, bool), CallError>>,
+    },
     GetLlmConfig {
         reply: oneshot::Sender<Result<LlmConfigInfo, CallError>>,
     },
@@ -578,6 +1006,10 @@ enum RpcCommand {
     ListKernels {
         reply: oneshot::Sender<Result<Vec<KernelInfo>, CallError>>,
     },
+    ListKernelsQuery {
+        query: KernelListQuery,
+        reply: oneshot::Sender<Result<crate::rpc::KernelPage, CallError>>,
+    },
 
     // ── Join Context (inline — updates actor state) ─────────────────────
     JoinContext {
@@ -605,6 +1037,25 @@ enum RpcCommand {
         params: Vec<u8>,
         reply: oneshot::Sender<Result<Vec<u8>, CallError>>,
     },
+
+    // ── Connection control (inline — answered in any state) ─────────────
+    /// Skip the reconnect backoff. `true` when an attempt was started.
+    RetryNow {
+        reply: oneshot::Sender<Result<bool, CallError>>,
+    },
+
+    // ── Seats (inline — the actor holds each seat's binding) ────────────
+    OpenSeat {
+        context_id: ContextId,
+        reply: OpenSeatReply,
+    },
+    CloseSeat {
+        seat: u64,
+        reply: oneshot::Sender<Result<(), CallError>>,
+    },
+    /// Any other command, run on a seat's binding instead of the
+    /// connection's own.
+    Seat { seat: u64, command: Box<RpcCommand> },
 }
 
 // ── Client-side peer types ──────────────────────────────────────────────────
@@ -639,26 +1090,79 @@ impl RpcCommand {
             Self::DriftCancel { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::GetContextId { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ListContexts { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ListContextsQuery { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ListTracks { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::VfsSnapshot { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::VfsCreate { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::VfsWrite { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::SubscribeVfsActivity { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SubscribeActivity { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::PeekDocument { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::EndPeek { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SubscribeBlock { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::Conclude { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::RenameContext { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::PromoteContext { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::DemoteContext { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::SetContextPaused { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ArchiveContext { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::CloseContexts { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ReopenContext { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::GetContextStats { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::GetBudgetStatus { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SetExecutionBudget { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SetToolHalt { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::GetLlmParams { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SetLlmParams { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ContextPreview { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ContextRecover { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::DigestSubscribe { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::BroadcastPrompt { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::GetBroadcast { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::GetSandboxProfile { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SetSandboxProfile { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::SearchSimilar { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SearchBlocks { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::GetNeighbors { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::GetClusters { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::CreateContext { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ReserveContextName { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::CommitContextName { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ReleaseContextName { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::PushOps { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::GetBlocks { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::GetContextSync { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::CompactContext { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::MoveBlock { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::Execute { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ShellExecute { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ShellRerun { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::SetBlockExcluded { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ReorderBlock { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SetBlockLabel { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ResolveBlock { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::BackupNow { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ScanGarbage { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SetSeatLanguage { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::TranslateBlock { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::LockBlock { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::UnlockBlock { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ListBlockLocks { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SetLockPolicy { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::CreateBookmark { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ListBookmarks { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ListConsentPrompts { reply } => { let _ = reply.send(Err(err)); }
+            Self::ConsentRespond { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::GetContextTimeline { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::RegisterMcpServer { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::UnregisterMcpServer { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ListContextMcpServers { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ListInbox { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::AckInbox { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::GetPreferences { reply } => { let _ = reply.send(Err(err)); }
+            Self::SetPreference { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ListConsentLog { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::TailBlock { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::Interrupt { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::Complete { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::GetCommandHistory { reply, .. } => { let _ = reply.send(Err(err)); }
@@ -675,12 +1179,17 @@ impl RpcCommand {
             Self::CommitCapture { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ReportClockEstimate { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::EditorKeys { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::EditorOpen { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::GetEditorState { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::EditorSave { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::EditorQuit { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ExecuteTool { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::GetToolSchemas { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::CallMcpTool { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ListMcpResources { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::Prompt { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ConfigureLlm { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::SwitchContextModel { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::GetLlmConfig { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::GetConfig { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::SetDefaultProvider { reply, .. } => { let _ = reply.send(Err(err)); }
@@ -692,10 +1201,15 @@ impl RpcCommand {
             Self::ListPresets { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::Whoami { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ListKernels { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::ListKernelsQuery { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::JoinContext { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::ResubscribeBlocks { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::AttachPeer { reply, .. } => { let _ = reply.send(Err(err)); }
             Self::InvokePeer { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::OpenSeat { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::CloseSeat { reply, .. } => { let _ = reply.send(Err(err)); }
+            Self::RetryNow { reply } => { let _ = reply.send(Err(err)); }
+            Self::Seat { command, .. } => command.reply_err(err),
         }
     }
 }
@@ -732,6 +1246,9 @@ pub struct ActorHandle {
     /// so a caller can read "are we connected?" without racing the one-shot
     /// broadcast. See [`Self::current_status`] / [`Self::watch_status`].
     status_watch_rx: watch::Receiver<ConnectionStatus>,
+    /// Set on a seat's handle: commands run on that seat's binding, and
+    /// `event_tx` is the seat's own broadcast.
+    seat: Option<u64>,
 }
 
 impl ActorHandle {
@@ -741,8 +1258,15 @@ impl ActorHandle {
         build: impl FnOnce(oneshot::Sender<Result<T, CallError>>) -> RpcCommand,
     ) -> Result<T, CallError> {
         let (reply, rx) = oneshot::channel();
+        let command = match self.seat {
+            Some(seat) => RpcCommand::Seat {
+                seat,
+                command: Box::new(build(reply)),
+            },
+            None => build(reply),
+        };
         let cmd = ChannelCmd {
-            command: build(reply),
+            command,
             span: tracing::Span::current(),
         };
         self.tx.send(cmd).await.map_err(|_| CallError::Shutdown)?;
@@ -810,6 +1334,15 @@ impl ActorHandle {
         self.send(|reply| RpcCommand::ListContexts { reply }).await
     }
 
+    /// Filtered, paginated `list_contexts`.
+    #[tracing::instrument(skip(self, query))]
+    pub async fn list_contexts_query(
+        &self,
+        query: ContextListQuery,
+    ) -> Result<crate::rpc::ContextPage, CallError> {
+        self.send(|reply| RpcCommand::ListContextsQuery { query, reply }).await
+    }
+
     /// List every track's live state (docs/tracks.md). Empty when no tracks
     /// exist or the kernel runs without a beat scheduler.
     #[tracing::instrument(skip(self))]
@@ -831,6 +1364,22 @@ impl ActorHandle {
         self.send(|reply| RpcCommand::VfsSnapshot { path, depth, max_entries, reply }).await
     }
 
+    /// Create an empty file at `path` with permission bits `mode`.
+    #[tracing::instrument(skip(self))]
+    pub async fn vfs_create(&self, path: &str, mode: u32) -> Result<(), CallError> {
+        let path = path.to_string();
+        self.send(|reply| RpcCommand::VfsCreate { path, mode, reply }).await
+    }
+
+    /// Write `data` into the file at `path` starting at `offset`. Returns the
+    /// byte count the backend reports written.
+    #[tracing::instrument(skip(self, data))]
+    pub async fn vfs_write(&self, path: &str, offset: u64, data: &[u8]) -> Result<u32, CallError> {
+        let path = path.to_string();
+        let data = data.to_vec();
+        self.send(|reply| RpcCommand::VfsWrite { path, offset, data, reply }).await
+    }
+
     /// Start the VFS activity digest push subscription (Lane K, FSN slice-1,
     /// `docs/scenes/vfs.md`). Events surface on [`Self::subscribe_events`] as
     /// [`ServerEvent::VfsActivity`] — same shared stream as blocks/editor,
@@ -846,6 +1395,64 @@ impl ActorHandle {
             .await
     }
 
+    /// Start the per-context activity push subscription. Readings surface on
+    /// [`Self::subscribe_events`] as [`ServerEvent::ContextActivity`], and
+    /// the health watchdog's findings as [`ServerEvent::ContextUnhealthy`].
+    /// Idempotent and re-issued on reconnect, like
+    /// [`Self::subscribe_vfs_activity`].
+    #[tracing::instrument(skip(self))]
+    pub async fn subscribe_activity(&self, interval_ms: u32) -> Result<(), CallError> {
+        self.send(|reply| RpcCommand::SubscribeActivity { interval_ms, reply })
+            .await
+    }
+
+    /// Look at a context without joining it: its blocks and sync state, no
+    /// seat taken and the joined context unchanged. With `stream`, the
+    /// context's block events also arrive on [`Self::subscribe_events`]
+    /// (tagged with their `context_id`, like any other) until
+    /// `expires_in_secs` passes or [`Self::end_peek`] is called; peeking the
+    /// same context again replaces the earlier stream. `ttl_secs` 0 takes the
+    /// server's default. Streams are not re-opened after a reconnect.
+    #[tracing::instrument(skip(self))]
+    pub async fn peek_document(
+        &self,
+        context_id: ContextId,
+        ttl_secs: u32,
+        stream: bool,
+    ) -> Result<PeekedDocument, CallError> {
+        self.send(|reply| RpcCommand::PeekDocument {
+            context_id,
+            ttl_secs,
+            stream,
+            reply,
+        })
+        .await
+    }
+
+    /// Stop a streaming [`Self::peek_document`] before it expires. A no-op
+    /// if none is open for `context_id`.
+    #[tracing::instrument(skip(self))]
+    pub async fn end_peek(&self, context_id: ContextId) -> Result<(), CallError> {
+        self.send(|reply| RpcCommand::EndPeek { context_id, reply })
+            .await
+    }
+
+    /// Follow one block: its snapshot now, then each of its events (text
+    /// ops, status, metadata, output, …) on the returned receiver — not on
+    /// [`Self::subscribe_events`]. Much cheaper kernel-side than a document
+    /// subscription. `BlockDeleted` is the last event; the receiver reports
+    /// `Closed` after a disconnect, and dropping every receiver ends the
+    /// stream kernel-side. `seq_num`s are per-context and skip; a
+    /// `SyncReset` means re-read the block.
+    #[tracing::instrument(skip(self))]
+    pub async fn subscribe_block(
+        &self,
+        block_id: BlockId,
+    ) -> Result<(BlockSnapshot, broadcast::Receiver<ServerEvent>), CallError> {
+        self.send(|reply| RpcCommand::SubscribeBlock { block_id, reply })
+            .await
+    }
+
     /// Conclude a context — the explicit "done" act (sets `concluded`/stamps
     /// `concludedAt` server-side). Idempotent.
     #[tracing::instrument(skip(self))]
@@ -896,180 +1503,759 @@ impl ActorHandle {
         self.send(|reply| RpcCommand::ArchiveContext { context_id, reply }).await
     }
 
-    /// Semantic search: contexts similar to a free-text query (top `k`).
-    #[tracing::instrument(skip(self, query))]
-    pub async fn search_similar(
+    /// Close (archive) every open context `filter` selects in one call —
+    /// sweeping stale experiment forks. Returns the selected ids; with
+    /// `dry_run` nothing is closed. Closed contexts stay restorable with
+    /// [`Self::reopen_context`].
+    #[tracing::instrument(skip(self))]
+    pub async fn close_contexts(
         &self,
-        query: &str,
-        k: u32,
-    ) -> Result<Vec<SimilarContext>, CallError> {
-        let query = query.to_string();
-        self.send(|reply| RpcCommand::SearchSimilar { query, k, reply }).await
+        filter: ContextCloseFilter,
+        dry_run: bool,
+    ) -> Result<Vec<ContextId>, CallError> {
+        self.send(|reply| RpcCommand::CloseContexts { filter, dry_run, reply }).await
     }
 
-    /// Contexts semantically similar to a given context (top `k` neighbors).
+    /// Reopen a closed context into automatic placement (no ring-0 seat).
     #[tracing::instrument(skip(self))]
-    pub async fn get_neighbors(
+    pub async fn reopen_context(&self, context_id: ContextId) -> Result<(), CallError> {
+        self.send(|reply| RpcCommand::ReopenContext { context_id, reply }).await
+    }
+
+    /// Aggregated statistics for one context (block tallies, model, consent,
+    /// drift in/out, pending approvals), computed server-side per call.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_context_stats(
         &self,
         context_id: ContextId,
-        k: u32,
-    ) -> Result<Vec<SimilarContext>, CallError> {
-        self.send(|reply| RpcCommand::GetNeighbors { context_id, k, reply }).await
+    ) -> Result<kaijutsu_types::ContextStats, CallError> {
+        self.send(|reply| RpcCommand::GetContextStats { context_id, reply }).await
     }
 
-    /// Semantic clusters of contexts (only clusters with ≥ `min_cluster_size`
-    /// members; each kernel-labeled).
+    /// A context's execution budget, its spending in the current window, and
+    /// the kernel-wide tool halt.
     #[tracing::instrument(skip(self))]
-    pub async fn get_clusters(
+    pub async fn get_budget_status(
         &self,
-        min_cluster_size: u32,
-    ) -> Result<Vec<ContextCluster>, CallError> {
-        self.send(|reply| RpcCommand::GetClusters { min_cluster_size, reply }).await
+        context_id: ContextId,
+    ) -> Result<kaijutsu_types::BudgetStatus, CallError> {
+        self.send(|reply| RpcCommand::GetBudgetStatus { context_id, reply }).await
+    }
+
+    /// Set a context's execution budget, or clear it with `None`.
+    #[tracing::instrument(skip(self))]
+    pub async fn set_execution_budget(
+        &self,
+        context_id: ContextId,
+        budget: Option<kaijutsu_types::ExecutionBudget>,
+    ) -> Result<kaijutsu_types::BudgetStatus, CallError> {
+        self.send(|reply| RpcCommand::SetExecutionBudget { context_id, budget, reply }).await
+    }
+
+    /// Engage or release the kernel-wide tool halt (kill switch).
+    #[tracing::instrument(skip(self))]
+    pub async fn set_tool_halt(
+        &self,
+        context_id: ContextId,
+        halted: bool,
+        reason: String,
+    ) -> Result<kaijutsu_types::BudgetStatus, CallError> {
+        self.send(|reply| RpcCommand::SetToolHalt { context_id, halted, reason, reply }).await
+    }
+
+    /// A context's stored generation parameters (all unset when none).
+    #[tracing::instrument(skip(self))]
+    pub async fn get_llm_params(
+        &self,
+        context_id: ContextId,
+    ) -> Result<kaijutsu_types::LlmParams, CallError> {
+        self.send(|reply| RpcCommand::GetLlmParams { context_id, reply }).await
+    }
+
+    /// Replace a context's generation parameters, or clear them with `None`.
+    /// With `merge`, the fields `params` sets are laid over what is stored.
+    #[tracing::instrument(skip(self))]
+    pub async fn set_llm_params(
+        &self,
+        context_id: ContextId,
+        params: Option<kaijutsu_types::LlmParams>,
+        merge: bool,
+    ) -> Result<kaijutsu_types::LlmParams, CallError> {
+        self.send(|reply| RpcCommand::SetLlmParams { context_id, params, merge, reply }).await
+    }
+
+    /// What a turn in `context_id` would send the model, assembled but not
+    /// sent. `strategy` overrides the context's assembly for this preview.
+    #[tracing::instrument(skip(self))]
+    pub async fn context_preview(
+        &self,
+        context_id: ContextId,
+        strategy: Option<String>,
+    ) -> Result<ContextPreview, CallError> {
+        self.send(|reply| RpcCommand::ContextPreview { context_id, strategy, reply }).await
+    }
+
+    /// Recover a wedged context: hard-interrupt its LLM stream and mark the
+    /// blocks left `Running` as `Error`. The remedy for a
+    /// [`ServerEvent::ContextUnhealthy`].
+    #[tracing::instrument(skip(self))]
+    pub async fn context_recover(
+        &self,
+        context_id: ContextId,
+    ) -> Result<kaijutsu_types::ContextRecovery, CallError> {
+        self.send(|reply| RpcCommand::ContextRecover { context_id, reply }).await
+    }
+
+    /// Manage `subscriber`'s digest subscriptions: subscribe to `source`,
+    /// unsubscribe with `remove`, or (no source) just list.
+    #[tracing::instrument(skip(self, focus))]
+    pub async fn digest_subscribe(
+        &self,
+        subscriber: ContextId,
+        source: Option<ContextId>,
+        every_secs: Option<u32>,
+        on_milestone: bool,
+        focus: Option<String>,
+        remove: bool,
+    ) -> Result<Vec<kaijutsu_types::DigestSubscription>, CallError> {
+        self.send(|reply| RpcCommand::DigestSubscribe {
+            subscriber,
+            source,
+            every_secs,
+            on_milestone,
+            focus,
+            remove,
+            reply,
+        })
+        .await
+    }
+
+    /// Send one prompt to several contexts at once; returns with every
+    /// target pending.
+    #[tracing::instrument(skip(self, content))]
+    pub async fn broadcast_prompt(
+        &self,
+        content: &str,
+        targets: Vec<kaijutsu_types::BroadcastTarget>,
+        label: Option<String>,
+    ) -> Result<kaijutsu_types::BroadcastStatus, CallError> {
+        let content = content.to_string();
+        self.send(|reply| RpcCommand::BroadcastPrompt { content, targets, label, reply }).await
+    }
+
+    /// Progress of a broadcast.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_broadcast(
+        &self,
+        id: u64,
+    ) -> Result<kaijutsu_types::BroadcastStatus, CallError> {
+        self.send(|reply| RpcCommand::GetBroadcast { id, reply }).await
+    }
+
+    /// A context's sandbox profile for host commands, or `None` when it has
+    /// none.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_sandbox_profile(
+        &self,
+        context_id: ContextId,
+    ) -> Result<Option<kaijutsu_types::SandboxProfile>, CallError> {
+        self.send(|reply| RpcCommand::GetSandboxProfile { context_id, reply }).await
+    }
+
+    /// Set a context's sandbox profile, or clear it with `None`. Returns the
+    /// stored profile.
+    #[tracing::instrument(skip(self, profile))]
+    pub async fn set_sandbox_profile(
+        &self,
+        context_id: ContextId,
+        profile: Option<kaijutsu_types::SandboxProfile>,
+    ) -> Result<Option<kaijutsu_types::SandboxProfile>, CallError> {
+        self.send(|reply| RpcCommand::SetSandboxProfile { context_id, profile, reply }).await
+    }
+
+    /// Semantic search: contexts similar to a free-text query (top `k`).
+    #[tracing::instrument(skip(self, query))]
+    pub async fn search_similar(
+        &self,
+        query: &str,
+        k: u32,
+    ) -> Result<Vec<SimilarContext>, CallError> {
+        let query = query.to_string();
+        self.send(|reply| RpcCommand::SearchSimilar { query, k, reply }).await
+    }
+
+    /// Ranked full-text block search (the server's search index).
+    #[tracing::instrument(skip(self, query))]
+    pub async fn search_blocks(
+        &self,
+        query: BlockSearchQuery,
+    ) -> Result<Vec<BlockSearchHit>, CallError> {
+        self.send(|reply| RpcCommand::SearchBlocks { query, reply })
+            .await
+    }
+
+    /// Contexts semantically similar to a given context (top `k` neighbors).
+    #[tracing::instrument(skip(self))]
+    pub async fn get_neighbors(
+        &self,
+        context_id: ContextId,
+        k: u32,
+    ) -> Result<Vec<SimilarContext>, CallError> {
+        self.send(|reply| RpcCommand::GetNeighbors { context_id, k, reply }).await
+    }
+
+    /// Semantic clusters of contexts (only clusters with ≥ `min_cluster_size`
+    /// members; each kernel-labeled).
+    #[tracing::instrument(skip(self))]
+    pub async fn get_clusters(
+        &self,
+        min_cluster_size: u32,
+    ) -> Result<Vec<ContextCluster>, CallError> {
+        self.send(|reply| RpcCommand::GetClusters { min_cluster_size, reply }).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn create_context(&self, label: &str) -> Result<ContextId, CallError> {
+        self.create_context_typed(label, "").await
+    }
+
+    /// Create a context with an explicit `context_type` (mode bundle).
+    ///
+    /// The type selects which `/etc/rc/<context_type>/create/*` scripts run
+    /// server-side. Empty `context_type` is treated as `"default"`.
+    #[tracing::instrument(skip(self))]
+    pub async fn create_context_typed(
+        &self,
+        label: &str,
+        context_type: &str,
+    ) -> Result<ContextId, CallError> {
+        self.send(|reply| RpcCommand::CreateContext {
+            label: label.into(),
+            context_type: context_type.into(),
+            reply,
+        })
+        .await
+    }
+
+    /// Hold a context name; a taken one comes back without a token, with
+    /// suggestions.
+    #[tracing::instrument(skip(self))]
+    pub async fn reserve_context_name(
+        &self,
+        label: &str,
+    ) -> Result<kaijutsu_types::ContextNameReservation, CallError> {
+        self.send(|reply| RpcCommand::ReserveContextName {
+            label: label.into(),
+            reply,
+        })
+        .await
+    }
+
+    /// Create a context under a reserved name.
+    #[tracing::instrument(skip(self))]
+    pub async fn commit_context_name(
+        &self,
+        token: kaijutsu_types::ReservationId,
+        context_type: &str,
+    ) -> Result<ContextId, CallError> {
+        self.send(|reply| RpcCommand::CommitContextName {
+            token,
+            context_type: context_type.into(),
+            reply,
+        })
+        .await
+    }
+
+    /// Give back a reserved name.
+    #[tracing::instrument(skip(self))]
+    pub async fn release_context_name(
+        &self,
+        token: kaijutsu_types::ReservationId,
+    ) -> Result<bool, CallError> {
+        self.send(|reply| RpcCommand::ReleaseContextName { token, reply }).await
+    }
+
+    // ── CRDT Sync ────────────────────────────────────────────────────────
+
+    /// Push serialized `SyncPayload` ops. The payload is validated locally
+    /// first (see `sync::validate_push_ops`) so a corrupt push fails here with
+    /// [`CallError::InvalidOps`] instead of being rejected by the server.
+    /// `known` is the frontier the caller has already pushed; with it, delta
+    /// ops for a block the server has never seen are caught too.
+    #[tracing::instrument(skip(self, ops, known))]
+    pub async fn push_ops(
+        &self,
+        context_id: ContextId,
+        ops: &[u8],
+        known: Option<&HashMap<BlockId, Frontier>>,
+    ) -> Result<u64, CallError> {
+        crate::sync::validate_push_ops(context_id, ops, known)
+            .map_err(|e| CallError::InvalidOps(e.to_string()))?;
+        self.send(|reply| RpcCommand::PushOps {
+            context_id,
+            ops: ops.to_vec(),
+            reply,
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self, query))]
+    pub async fn get_blocks_query(
+        &self,
+        context_id: ContextId,
+        query: BlockQuery,
+    ) -> Result<Vec<BlockSnapshot>, CallError> {
+        self.send(|reply| RpcCommand::GetBlocks {
+            context_id,
+            query,
+            reply,
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_block(
+        &self,
+        context_id: ContextId,
+        block_id: BlockId,
+    ) -> Result<Option<BlockSnapshot>, CallError> {
+        let mut blocks = self
+            .get_blocks_query(context_id, BlockQuery::ByIds(vec![block_id]))
+            .await?;
+        Ok(blocks.pop())
+    }
+
+    /// Move a block to land after `after`, or to the start of the document
+    /// with `None`. Returns the resulting context version.
+    #[tracing::instrument(skip(self))]
+    pub async fn move_block(
+        &self,
+        context_id: ContextId,
+        block_id: BlockId,
+        after: Option<BlockId>,
+    ) -> Result<u64, CallError> {
+        self.send(|reply| RpcCommand::MoveBlock {
+            context_id,
+            block_id,
+            after,
+            reply,
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self, block_ids))]
+    pub async fn get_blocks(
+        &self,
+        context_id: ContextId,
+        block_ids: Vec<BlockId>,
+    ) -> Result<Vec<BlockSnapshot>, CallError> {
+        self.get_blocks_query(context_id, BlockQuery::ByIds(block_ids))
+            .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_all_blocks(
+        &self,
+        context_id: ContextId,
+    ) -> Result<Vec<BlockSnapshot>, CallError> {
+        self.get_blocks_query(context_id, BlockQuery::All).await
+    }
+
+    #[tracing::instrument(skip(self, filter))]
+    pub async fn query_blocks(
+        &self,
+        context_id: ContextId,
+        filter: BlockFilter,
+    ) -> Result<Vec<BlockSnapshot>, CallError> {
+        self.get_blocks_query(context_id, BlockQuery::ByFilter(filter))
+            .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_context_sync(&self, context_id: ContextId) -> Result<SyncState, CallError> {
+        self.send(|reply| RpcCommand::GetContextSync { context_id, reply })
+            .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn compact_context(&self, context_id: ContextId) -> Result<(u64, u64), CallError> {
+        self.send(|reply| RpcCommand::CompactContext { context_id, reply })
+            .await
+    }
+
+    // ── Shell / Execution ────────────────────────────────────────────────
+
+    #[tracing::instrument(skip(self, code))]
+    pub async fn execute(&self, code: &str) -> Result<u64, CallError> {
+        self.send(|reply| RpcCommand::Execute {
+            code: code.into(),
+            reply,
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self, code))]
+    pub async fn shell_execute(
+        &self,
+        code: &str,
+        context_id: ContextId,
+        user_initiated: bool,
+    ) -> Result<BlockId, CallError> {
+        self.send(|reply| RpcCommand::ShellExecute {
+            code: code.into(),
+            context_id,
+            user_initiated,
+            reply,
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn shell_rerun(
+        &self,
+        block_id: &BlockId,
+        user_initiated: bool,
+    ) -> Result<BlockId, CallError> {
+        self.send(|reply| RpcCommand::ShellRerun {
+            block_id: *block_id,
+            user_initiated,
+            reply,
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn set_block_excluded(
+        &self,
+        context_id: ContextId,
+        block_id: &BlockId,
+        excluded: bool,
+    ) -> Result<u64, CallError> {
+        let bid = *block_id;
+        self.send(|reply| RpcCommand::SetBlockExcluded {
+            context_id,
+            block_id: bid,
+            excluded,
+            reply,
+        })
+        .await
+    }
+
+    /// Reorder a block among its siblings. `after` must share the block's
+    /// parent; `None` lands it ahead of the first sibling.
+    #[tracing::instrument(skip(self))]
+    pub async fn reorder_block(
+        &self,
+        context_id: ContextId,
+        block_id: &BlockId,
+        after: Option<&BlockId>,
+    ) -> Result<u64, CallError> {
+        let bid = *block_id;
+        let after = after.copied();
+        self.send(|reply| RpcCommand::ReorderBlock {
+            context_id,
+            block_id: bid,
+            after,
+            reply,
+        })
+        .await
+    }
+
+    /// Set or clear a block's label (see [`KernelHandle::set_block_label`]).
+    #[tracing::instrument(skip(self))]
+    pub async fn set_block_label(
+        &self,
+        context_id: ContextId,
+        block_id: &BlockId,
+        label: Option<&str>,
+    ) -> Result<u64, CallError> {
+        let bid = *block_id;
+        let label = label.map(str::to_owned);
+        self.send(|reply| RpcCommand::SetBlockLabel {
+            context_id,
+            block_id: bid,
+            label,
+            reply,
+        })
+        .await
+    }
+
+    /// Resolve a label or block-id abbreviation to a block id (see
+    /// [`KernelHandle::resolve_block`]).
+    #[tracing::instrument(skip(self))]
+    pub async fn resolve_block(&self, query: &str) -> Result<BlockId, CallError> {
+        let query = query.to_owned();
+        self.send(|reply| RpcCommand::ResolveBlock { query, reply }).await
+    }
+
+    /// Back up the server's databases now (see [`KernelHandle::backup_now`]).
+    #[tracing::instrument(skip(self))]
+    pub async fn backup_now(&self) -> Result<BackupReport, CallError> {
+        self.send(|reply| RpcCommand::BackupNow { reply }).await
+    }
+
+    /// Scan the server for unreachable state, pruning it with `prune` (see
+    /// [`KernelHandle::scan_garbage`]).
+    #[tracing::instrument(skip(self))]
+    pub async fn scan_garbage(
+        &self,
+        context_id: ContextId,
+        prune: bool,
+    ) -> Result<GarbageReport, CallError> {
+        self.send(|reply| RpcCommand::ScanGarbage {
+            context_id,
+            prune,
+            reply,
+        })
+        .await
+    }
+
+    /// Set or clear this seat's preferred reading language (see
+    /// [`KernelHandle::set_seat_language`]).
+    #[tracing::instrument(skip(self))]
+    pub async fn set_seat_language(
+        &self,
+        language: Option<String>,
+    ) -> Result<Option<String>, CallError> {
+        self.send(|reply| RpcCommand::SetSeatLanguage { language, reply })
+            .await
+    }
+
+    /// A block's text translated, by default into the seat's preferred
+    /// language (see [`KernelHandle::translate_block`]).
+    #[tracing::instrument(skip(self))]
+    pub async fn translate_block(
+        &self,
+        block_id: BlockId,
+        language: Option<String>,
+    ) -> Result<BlockTranslation, CallError> {
+        self.send(|reply| RpcCommand::TranslateBlock {
+            block_id,
+            language,
+            reply,
+        })
+        .await
+    }
+
+    /// Mark a block as being edited by this connection's principal (see
+    /// [`KernelHandle::lock_block`]).
+    #[tracing::instrument(skip(self))]
+    pub async fn lock_block(
+        &self,
+        block_id: BlockId,
+        ttl_secs: Option<u64>,
+        note: String,
+    ) -> Result<BlockLock, CallError> {
+        self.send(|reply| RpcCommand::LockBlock {
+            block_id,
+            ttl_secs,
+            note,
+            reply,
+        })
+        .await
+    }
+
+    /// Release this principal's lock on a block.
+    #[tracing::instrument(skip(self))]
+    pub async fn unlock_block(&self, block_id: BlockId) -> Result<bool, CallError> {
+        self.send(|reply| RpcCommand::UnlockBlock { block_id, reply })
+            .await
+    }
+
+    /// A context's live block locks and its lock policy.
+    #[tracing::instrument(skip(self))]
+    pub async fn list_block_locks(
+        &self,
+        context_id: ContextId,
+    ) -> Result<(Vec<BlockLock>, LockPolicy), CallError> {
+        self.send(|reply| RpcCommand::ListBlockLocks { context_id, reply })
+            .await
+    }
+
+    /// Set a context's lock policy.
+    #[tracing::instrument(skip(self))]
+    pub async fn set_lock_policy(
+        &self,
+        context_id: ContextId,
+        policy: LockPolicy,
+    ) -> Result<(), CallError> {
+        self.send(|reply| RpcCommand::SetLockPolicy {
+            context_id,
+            policy,
+            reply,
+        })
+        .await
+    }
+
+    /// Bookmark a document's current state (see
+    /// [`KernelHandle::create_bookmark`]).
+    #[tracing::instrument(skip(self))]
+    pub async fn create_bookmark(
+        &self,
+        context_id: ContextId,
+        name: String,
+    ) -> Result<DocBookmark, CallError> {
+        self.send(|reply| RpcCommand::CreateBookmark {
+            context_id,
+            name,
+            reply,
+        })
+        .await
+    }
+
+    /// A document's bookmarks, oldest first.
+    #[tracing::instrument(skip(self))]
+    pub async fn list_bookmarks(
+        &self,
+        context_id: ContextId,
+    ) -> Result<Vec<DocBookmark>, CallError> {
+        self.send(|reply| RpcCommand::ListBookmarks { context_id, reply })
+            .await
     }
 
+    /// Tool calls waiting on this principal's approval, oldest first.
     #[tracing::instrument(skip(self))]
-    pub async fn create_context(&self, label: &str) -> Result<ContextId, CallError> {
-        self.create_context_typed(label, "").await
+    pub async fn list_consent_prompts(&self) -> Result<Vec<ConsentPrompt>, CallError> {
+        self.send(|reply| RpcCommand::ListConsentPrompts { reply })
+            .await
     }
 
-    /// Create a context with an explicit `context_type` (mode bundle).
-    ///
-    /// The type selects which `/etc/rc/<context_type>/create/*` scripts run
-    /// server-side. Empty `context_type` is treated as `"default"`.
+    /// Answer a consent prompt (see [`KernelHandle::consent_respond`]).
     #[tracing::instrument(skip(self))]
-    pub async fn create_context_typed(
+    pub async fn consent_respond(
         &self,
-        label: &str,
-        context_type: &str,
-    ) -> Result<ContextId, CallError> {
-        self.send(|reply| RpcCommand::CreateContext {
-            label: label.into(),
-            context_type: context_type.into(),
+        prompt_id: u64,
+        decision: ConsentDecision,
+    ) -> Result<ConsentPrompt, CallError> {
+        self.send(|reply| RpcCommand::ConsentRespond {
+            prompt_id,
+            decision,
             reply,
         })
         .await
     }
 
-    // ── CRDT Sync ────────────────────────────────────────────────────────
-
-    #[tracing::instrument(skip(self, ops))]
-    pub async fn push_ops(&self, context_id: ContextId, ops: &[u8]) -> Result<u64, CallError> {
-        self.send(|reply| RpcCommand::PushOps {
+    /// A context's events bucketed over time (see
+    /// [`KernelHandle::get_context_timeline`]).
+    #[tracing::instrument(skip(self))]
+    pub async fn get_context_timeline(
+        &self,
+        context_id: ContextId,
+        window_secs: u64,
+        buckets: u32,
+    ) -> Result<ContextTimeline, CallError> {
+        self.send(|reply| RpcCommand::GetContextTimeline {
             context_id,
-            ops: ops.to_vec(),
+            window_secs,
+            buckets,
             reply,
         })
         .await
     }
 
-    #[tracing::instrument(skip(self, query))]
-    pub async fn get_blocks_query(
+    /// Attach a downstream MCP server to one context (see
+    /// [`KernelHandle::register_mcp_server`]).
+    #[tracing::instrument(skip(self, spec))]
+    pub async fn register_mcp_server(
         &self,
         context_id: ContextId,
-        query: BlockQuery,
-    ) -> Result<Vec<BlockSnapshot>, CallError> {
-        self.send(|reply| RpcCommand::GetBlocks {
+        spec: McpServerSpec,
+    ) -> Result<ContextMcpServerInfo, CallError> {
+        self.send(|reply| RpcCommand::RegisterMcpServer {
             context_id,
-            query,
+            spec,
             reply,
         })
         .await
     }
 
     #[tracing::instrument(skip(self))]
-    pub async fn get_block(
+    pub async fn unregister_mcp_server(
         &self,
         context_id: ContextId,
-        block_id: BlockId,
-    ) -> Result<Option<BlockSnapshot>, CallError> {
-        let mut blocks = self
-            .get_blocks_query(context_id, BlockQuery::ByIds(vec![block_id]))
-            .await?;
-        Ok(blocks.pop())
+        instance: &str,
+    ) -> Result<bool, CallError> {
+        let instance = instance.to_string();
+        self.send(|reply| RpcCommand::UnregisterMcpServer {
+            context_id,
+            instance,
+            reply,
+        })
+        .await
     }
 
-    #[tracing::instrument(skip(self, block_ids))]
-    pub async fn get_blocks(
+    #[tracing::instrument(skip(self))]
+    pub async fn list_context_mcp_servers(
         &self,
         context_id: ContextId,
-        block_ids: Vec<BlockId>,
-    ) -> Result<Vec<BlockSnapshot>, CallError> {
-        self.get_blocks_query(context_id, BlockQuery::ByIds(block_ids))
+    ) -> Result<Vec<ContextMcpServerInfo>, CallError> {
+        self.send(|reply| RpcCommand::ListContextMcpServers { context_id, reply })
             .await
     }
 
     #[tracing::instrument(skip(self))]
-    pub async fn get_all_blocks(
-        &self,
-        context_id: ContextId,
-    ) -> Result<Vec<BlockSnapshot>, CallError> {
-        self.get_blocks_query(context_id, BlockQuery::All).await
-    }
-
-    #[tracing::instrument(skip(self, filter))]
-    pub async fn query_blocks(
+    pub async fn list_inbox(
         &self,
-        context_id: ContextId,
-        filter: BlockFilter,
-    ) -> Result<Vec<BlockSnapshot>, CallError> {
-        self.get_blocks_query(context_id, BlockQuery::ByFilter(filter))
+        include_acked: bool,
+        limit: u32,
+    ) -> Result<InboxPage, CallError> {
+        self.send(|reply| RpcCommand::ListInbox { include_acked, limit, reply })
             .await
     }
 
     #[tracing::instrument(skip(self))]
-    pub async fn get_context_sync(&self, context_id: ContextId) -> Result<SyncState, CallError> {
-        self.send(|reply| RpcCommand::GetContextSync { context_id, reply })
+    pub async fn ack_inbox(&self, ids: Vec<u64>) -> Result<(u32, u64), CallError> {
+        self.send(|reply| RpcCommand::AckInbox { ids, reply })
             .await
     }
 
     #[tracing::instrument(skip(self))]
-    pub async fn compact_context(&self, context_id: ContextId) -> Result<(u64, u64), CallError> {
-        self.send(|reply| RpcCommand::CompactContext { context_id, reply })
-            .await
+    pub async fn get_preferences(&self) -> Result<Preferences, CallError> {
+        self.send(|reply| RpcCommand::GetPreferences { reply }).await
     }
 
-    // ── Shell / Execution ────────────────────────────────────────────────
-
-    #[tracing::instrument(skip(self, code))]
-    pub async fn execute(&self, code: &str) -> Result<u64, CallError> {
-        self.send(|reply| RpcCommand::Execute {
-            code: code.into(),
-            reply,
-        })
-        .await
+    /// Set a preference, or clear it with `None`; returns the full set.
+    #[tracing::instrument(skip(self))]
+    pub async fn set_preference(
+        &self,
+        key: String,
+        value: Option<String>,
+    ) -> Result<Preferences, CallError> {
+        self.send(|reply| RpcCommand::SetPreference { key, value, reply })
+            .await
     }
 
-    #[tracing::instrument(skip(self, code))]
-    pub async fn shell_execute(
+    /// Page through the kernel's consent audit log (optionally verifying it).
+    #[tracing::instrument(skip(self))]
+    pub async fn list_consent_log(
         &self,
-        code: &str,
-        context_id: ContextId,
-        user_initiated: bool,
-    ) -> Result<BlockId, CallError> {
-        self.send(|reply| RpcCommand::ShellExecute {
-            code: code.into(),
-            context_id,
-            user_initiated,
+        after_seq: u64,
+        limit: u32,
+        verify: bool,
+    ) -> Result<ConsentLogPage, CallError> {
+        self.send(|reply| RpcCommand::ListConsentLog {
+            after_seq,
+            limit,
+            verify,
             reply,
         })
         .await
     }
 
-    #[tracing::instrument(skip(self))]
-    pub async fn set_block_excluded(
+    /// Follow a block's text until it reaches a terminal status or `timeout`
+    /// passes, sending each append to `tx`. The usual per-call deadline does
+    /// not apply — a tail is meant to be long-lived; on `Timeout` everything
+    /// appended so far has already been delivered.
+    #[tracing::instrument(skip(self, tx))]
+    pub async fn tail_block(
         &self,
-        context_id: ContextId,
-        block_id: &BlockId,
-        excluded: bool,
-    ) -> Result<u64, CallError> {
-        let bid = *block_id;
-        self.send(|reply| RpcCommand::SetBlockExcluded {
-            context_id,
-            block_id: bid,
-            excluded,
-            reply,
-        })
-        .await
+        block_id: BlockId,
+        from_offset: u64,
+        timeout: Duration,
+        tx: mpsc::UnboundedSender<BlockTailChunk>,
+    ) -> Result<BlockTailEnd, CallError> {
+        self.send(|reply| RpcCommand::TailBlock { block_id, from_offset, timeout, tx, reply })
+            .await
     }
 
     #[tracing::instrument(skip(self))]
@@ -1274,6 +2460,32 @@ impl ActorHandle {
         .await
     }
 
+    /// Open an editor session on `path`. The returned state carries the new
+    /// session id the other editor calls take.
+    #[tracing::instrument(skip(self))]
+    pub async fn editor_open(&self, path: &str) -> Result<EditorState, CallError> {
+        let path = path.to_string();
+        self.send(|reply| RpcCommand::EditorOpen { path, reply }).await
+    }
+
+    /// Current state of an open editor session.
+    #[tracing::instrument(skip(self))]
+    pub async fn editor_state(&self, session_id: u64) -> Result<EditorState, CallError> {
+        self.send(|reply| RpcCommand::GetEditorState { session_id, reply }).await
+    }
+
+    /// `ZZ` — checkpoint the session's buffer as saved.
+    #[tracing::instrument(skip(self))]
+    pub async fn editor_save(&self, session_id: u64) -> Result<EditorState, CallError> {
+        self.send(|reply| RpcCommand::EditorSave { session_id, reply }).await
+    }
+
+    /// `ZQ` — roll the block back to the session's checkpoint and close it.
+    #[tracing::instrument(skip(self))]
+    pub async fn editor_quit(&self, session_id: u64) -> Result<(), CallError> {
+        self.send(|reply| RpcCommand::EditorQuit { session_id, reply }).await
+    }
+
     // ── Tool Execution ───────────────────────────────────────────────────
 
     #[tracing::instrument(skip(self, params))]
@@ -1325,11 +2537,25 @@ impl ActorHandle {
         content: &str,
         model: Option<&str>,
         context_id: ContextId,
+    ) -> Result<String, CallError> {
+        self.prompt_with_params(content, model, context_id, None).await
+    }
+
+    /// [`Self::prompt`] with per-request generation parameters overriding
+    /// the context's for this turn.
+    #[tracing::instrument(skip(self, content))]
+    pub async fn prompt_with_params(
+        &self,
+        content: &str,
+        model: Option<&str>,
+        context_id: ContextId,
+        params: Option<kaijutsu_types::LlmParams>,
     ) -> Result<String, CallError> {
         self.send(|reply| RpcCommand::Prompt {
             content: content.into(),
             model: model.map(String::from),
             context_id,
+            params,
             reply,
         })
         .await
@@ -1351,6 +2577,23 @@ impl ActorHandle {
         .await
     }
 
+    /// Switch a context's model by alias or `provider/model`, recording the
+    /// switch in the conversation. Returns the resolved
+    /// `(provider, model, changed)`.
+    #[tracing::instrument(skip(self))]
+    pub async fn switch_context_model(
+        &self,
+        context_id: ContextId,
+        spec: &str,
+    ) -> Result<(String, String, bool), CallError> {
+        self.send(|reply| RpcCommand::SwitchContextModel {
+            context_id,
+            spec: spec.into(),
+            reply,
+        })
+        .await
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_llm_config(&self) -> Result<LlmConfigInfo, CallError> {
         self.send(|reply| RpcCommand::GetLlmConfig { reply }).await
@@ -1468,6 +2711,60 @@ impl ActorHandle {
             .await
     }
 
+    // ── Seats ────────────────────────────────────────────────────────────
+
+    /// Open another seat on this connection, joined to `context_id`.
+    ///
+    /// The seat is a session of its own — own join, block subscription and
+    /// resumable seat token — so one process can sit in several contexts
+    /// over a single SSH connection. It follows the actor through
+    /// reconnects. Dropping the last clone of the [`SeatHandle`] releases
+    /// it. See [`crate::seat`].
+    #[tracing::instrument(skip(self))]
+    pub async fn open_seat(&self, context_id: ContextId) -> Result<SeatHandle, CallError> {
+        let (seat, event_tx) = self
+            .send(|reply| RpcCommand::OpenSeat { context_id, reply })
+            .await?;
+        let handle = ActorHandle {
+            tx: self.tx.clone(),
+            event_tx,
+            status_tx: self.status_tx.clone(),
+            status_watch_rx: self.status_watch_rx.clone(),
+            seat: Some(seat),
+        };
+        SeatHandle::start(handle, seat, context_id).await
+    }
+
+    /// Release seat `seat` without waiting — `SeatHandle`'s drop path.
+    pub(crate) fn release_seat(&self, seat: u64) {
+        let (reply, _) = oneshot::channel();
+        let cmd = ChannelCmd {
+            command: RpcCommand::CloseSeat { seat, reply },
+            span: tracing::Span::current(),
+        };
+        if self.tx.try_send(cmd).is_err() {
+            log::warn!("seat {seat} release not delivered; it ends with the connection");
+        }
+    }
+
+    // ── Connection control ───────────────────────────────────────────────
+
+    /// Dial now instead of waiting out the reconnect backoff. From
+    /// `Terminal` this starts over at attempt 1 — the manual way back once
+    /// the cause (a missing SSH agent, a rejected key) is fixed. `false`
+    /// when the actor was already connected or connecting. Always acts on
+    /// the connection itself, even from a seat's handle.
+    #[tracing::instrument(skip(self))]
+    pub async fn retry_now(&self) -> Result<bool, CallError> {
+        let (reply, rx) = oneshot::channel();
+        let cmd = ChannelCmd {
+            command: RpcCommand::RetryNow { reply },
+            span: tracing::Span::current(),
+        };
+        self.tx.send(cmd).await.map_err(|_| CallError::Shutdown)?;
+        rx.await.map_err(|_| CallError::Shutdown)?
+    }
+
     // ── World-level ──────────────────────────────────────────────────────
 
     #[tracing::instrument(skip(self))]
@@ -1480,6 +2777,15 @@ impl ActorHandle {
         self.send(|reply| RpcCommand::ListKernels { reply }).await
     }
 
+    /// Filtered, paginated `list_kernels`.
+    #[tracing::instrument(skip(self, query))]
+    pub async fn list_kernels_query(
+        &self,
+        query: KernelListQuery,
+    ) -> Result<crate::rpc::KernelPage, CallError> {
+        self.send(|reply| RpcCommand::ListKernelsQuery { query, reply }).await
+    }
+
     // ── Peers ────────────────────────────────────────────────────────────
 
     #[tracing::instrument(skip(self, config, invocation_tx))]
@@ -1529,7 +2835,13 @@ impl ActorHandle {
 #[async_trait::async_trait]
 pub trait DocSyncBackend: Send + Sync {
     async fn get_context_sync(&self, context_id: ContextId) -> Result<SyncState, CallError>;
-    async fn push_ops(&self, context_id: ContextId, ops: &[u8]) -> Result<u64, CallError>;
+    /// `known`: the frontier already pushed, as for [`ActorHandle::push_ops`].
+    async fn push_ops(
+        &self,
+        context_id: ContextId,
+        ops: &[u8],
+        known: Option<&HashMap<BlockId, Frontier>>,
+    ) -> Result<u64, CallError>;
 }
 
 #[async_trait::async_trait]
@@ -1538,8 +2850,13 @@ impl DocSyncBackend for ActorHandle {
         ActorHandle::get_context_sync(self, context_id).await
     }
 
-    async fn push_ops(&self, context_id: ContextId, ops: &[u8]) -> Result<u64, CallError> {
-        ActorHandle::push_ops(self, context_id, ops).await
+    async fn push_ops(
+        &self,
+        context_id: ContextId,
+        ops: &[u8],
+        known: Option<&HashMap<BlockId, Frontier>>,
+    ) -> Result<u64, CallError> {
+        ActorHandle::push_ops(self, context_id, ops, known).await
     }
 }
 
@@ -1576,7 +2893,7 @@ where
                 // discover the actor is already Closing and just log.
                 let _ = close_tx.try_send(CloseCause::RpcError(msg.clone()));
             }
-            Err(CallError::Rpc(msg))
+            Err(CallError::from_rpc_message(msg))
         }
         Err(_) => Err(CallError::Timeout(RPC_CALL_TIMEOUT)),
     }
@@ -1620,6 +2937,10 @@ struct RpcActor {
     scope_blocks_to_context: bool,
     /// Context returned by the most recent `join_context`.
     joined_context_id: Option<ContextId>,
+    /// Seat token from the most recent join. The reconnect handshake hands
+    /// it back so the server resumes the same session (filters, activity
+    /// cursor) instead of seating us fresh.
+    seat_token: Option<String>,
     /// Peer registration the actor re-establishes on every reconnect. Set by
     /// the `AttachPeer` command and persisted, mirroring `context_id` — the
     /// kernel's `PeerRegistry` resets on restart, so without this the app
@@ -1635,6 +2956,23 @@ struct RpcActor {
     /// so a second `SubscribeVfsActivity` call on a live connection is a
     /// no-op rather than stacking a duplicate bridge task server-side.
     vfs_activity_interval_ms: Option<u32>,
+    /// Requested tick interval for the context activity subscription —
+    /// persisted and used as a duplicate guard exactly like
+    /// `vfs_activity_interval_ms`.
+    activity_interval_ms: Option<u32>,
+    /// Streaming peeks, by context. Each sender's task holds the server's
+    /// peek capability; dropping the sender (end, replace, or disconnect)
+    /// drops it and the server stops the stream.
+    peeks: HashMap<ContextId, oneshot::Sender<()>>,
+    /// Live `subscribe_block` streams, held the same way as `peeks`.
+    block_subs: Vec<oneshot::Sender<()>>,
+    /// Seats opened through `OpenSeat`, by number. Persisted across
+    /// reconnects like `peer_registration`; `enter_connected` re-binds them.
+    seats: HashMap<u64, SeatRecord>,
+    next_seat: u64,
+    /// Bumped by every `enter_connected`, so a seat bind that lands after its
+    /// connection dropped is recognised as stale.
+    connection_epoch: u64,
 
     /// Owned during `Connected`. Replaced atomically on successful handshake.
     connection: Option<ConnectionState>,
@@ -1685,8 +3023,15 @@ impl RpcActor {
             context_id,
             scope_blocks_to_context,
             joined_context_id: None,
+            seat_token: None,
             peer_registration: None,
             vfs_activity_interval_ms: None,
+            activity_interval_ms: None,
+            peeks: HashMap::new(),
+            block_subs: Vec::new(),
+            seats: HashMap::new(),
+            next_seat: 0,
+            connection_epoch: 0,
             connection: None,
             ping_task: None,
             connecting_task: None,
@@ -1705,9 +3050,14 @@ impl RpcActor {
     fn broadcast_state(&self) {
         let status = match &self.state {
             ActorState::Idle => ConnectionStatus::Idle,
-            ActorState::Connecting { attempt, .. } => {
-                ConnectionStatus::Connecting { attempt: *attempt }
-            }
+            ActorState::Connecting {
+                attempt,
+                last_error,
+                ..
+            } => ConnectionStatus::Connecting {
+                attempt: *attempt,
+                last_error: last_error.clone(),
+            },
             ActorState::Connected { since } => ConnectionStatus::Connected {
                 kernel_id: self.bound_kernel_id.expect("bound_kernel_id set on Connected"),
                 context_id: self.joined_context_id,
@@ -1719,6 +3069,7 @@ impl RpcActor {
             ActorState::Cooldown {
                 next_attempt,
                 until,
+                backoff,
                 last_error,
             } => {
                 let until_ms = system_now_ms().saturating_add(
@@ -1727,6 +3078,7 @@ impl RpcActor {
                 ConnectionStatus::Cooldown {
                     next_attempt: *next_attempt,
                     until_ms,
+                    backoff_ms: backoff.as_millis() as u64,
                     last_error: last_error.clone(),
                 }
             }
@@ -1746,9 +3098,15 @@ impl RpcActor {
             "Actor connecting to {}:{} (attempt {}, instance={})",
             self.config.host, self.config.port, attempt, self.instance
         );
+        let last_error = match &self.state {
+            ActorState::Cooldown { last_error, .. } => Some(last_error.clone()),
+            ActorState::Terminal { reason } => Some(reason.clone()),
+            _ => None,
+        };
         self.state = ActorState::Connecting {
             attempt,
             started_at: Instant::now(),
+            last_error,
         };
         let task = spawn_handshake(
             self.config.clone(),
@@ -1758,6 +3116,8 @@ impl RpcActor {
             self.event_tx.clone(),
             self.peer_registration.clone(),
             self.vfs_activity_interval_ms,
+            self.activity_interval_ms,
+            self.seat_token.clone(),
         );
         self.connecting_task = Some(task);
         self.broadcast_state();
@@ -1780,14 +3140,32 @@ impl RpcActor {
 
         self.bound_kernel_id = Some(built.kernel_id);
         self.joined_context_id = built.joined_context;
+        let pending_consent = match built.seat {
+            Some(seat) => {
+                if seat.resumed {
+                    log::info!("Resumed seat (session {})", seat.session_id.short());
+                }
+                self.seat_token = Some(seat.token);
+                seat.pending_consent
+            }
+            None => Vec::new(),
+        };
         self.connection = Some(ConnectionState {
             client: built.client,
             kernel: built.kernel.clone(),
         });
+        self.connection_epoch += 1;
         self.state = ActorState::Connected {
             since: Instant::now(),
         };
 
+        // Seats opened on an earlier connection come back on this one,
+        // resuming their sessions.
+        let seats: Vec<u64> = self.seats.keys().copied().collect();
+        for seat in seats {
+            self.bind_seat(seat, None, tracing::Span::current());
+        }
+
         // Spawn the liveness pinger. It runs until aborted on Closing.
         let close_tx = self.close_tx.clone();
         let expected_kernel_id = built.kernel_id;
@@ -1806,6 +3184,11 @@ impl RpcActor {
         // (e.g. a headless client) is fine.
         if is_reconnect {
             let _ = self.event_tx.send(ServerEvent::Reconnected);
+            // Consent prompts raised during the outage never reached us as
+            // events; the join re-delivered the unanswered ones.
+            for item in pending_consent {
+                let _ = self.event_tx.send(ServerEvent::InboxItem { item });
+            }
 
             // Eagerly re-fetch the joined context's full CRDT state and deliver
             // it, so renderers converge on what the stream missed during the
@@ -1858,6 +3241,11 @@ impl RpcActor {
         // Drop the live connection (this aborts the RpcSystem via
         // RpcSystemGuard and closes the SSH channels).
         self.connection = None;
+        self.peeks.clear();
+        self.block_subs.clear();
+        for record in self.seats.values_mut() {
+            record.kernel = None;
+        }
         // Abort the ping task; if it was about to fire a duplicate close,
         // that signal is now redundant.
         if let Some(task) = self.ping_task.take() {
@@ -1902,6 +3290,7 @@ impl RpcActor {
         self.state = ActorState::Cooldown {
             next_attempt,
             until,
+            backoff,
             last_error: cause.to_error_string(),
         };
         self.broadcast_state();
@@ -1933,6 +3322,7 @@ impl RpcActor {
                 self.state = ActorState::Cooldown {
                     next_attempt,
                     until,
+                    backoff,
                     last_error: msg,
                 };
                 self.broadcast_state();
@@ -1946,7 +3336,18 @@ impl RpcActor {
     }
 
     /// Reject a command with the current state's `NotReady` reason.
-    fn reject_not_ready(&self, cmd: RpcCommand) {
+    fn reject_not_ready(&mut self, cmd: RpcCommand) {
+        // Releasing a seat needs no connection: forget it so the next
+        // handshake doesn't re-bind it.
+        if let RpcCommand::CloseSeat { seat, reply } = cmd {
+            self.seats.remove(&seat);
+            let _ = reply.send(Ok(()));
+            return;
+        }
+        if let RpcCommand::RetryNow { reply } = cmd {
+            self.retry_now(reply);
+            return;
+        }
         let reason = match &self.state {
             ActorState::Idle => NotReadyReason::Idle,
             ActorState::Connecting { attempt, .. } => NotReadyReason::Connecting {
@@ -1964,7 +3365,7 @@ impl RpcActor {
             _ => {
                 // Caller should not have reached reject_not_ready in
                 // Connected/Terminal; if they did, surface as Rpc error.
-                cmd.reply_err(CallError::Rpc(format!(
+                cmd.reply_err(CallError::ServerError(format!(
                     "internal: reject from state {}",
                     self.state.name()
                 )));
@@ -1974,15 +3375,37 @@ impl RpcActor {
         cmd.reply_err(CallError::NotReady(reason));
     }
 
-    /// Reject a command with the Terminal reason.
-    fn reject_terminal(&self, cmd: RpcCommand) {
+    /// Reject a command with the Terminal reason. A `RetryNow` is the one
+    /// way out: it starts over at attempt 1.
+    fn reject_terminal(&mut self, cmd: RpcCommand) {
+        if let RpcCommand::RetryNow { reply } = cmd {
+            self.retry_now(reply);
+            return;
+        }
         if let ActorState::Terminal { reason } = &self.state {
             cmd.reply_err(CallError::PermanentlyFailed(reason.clone()));
         } else {
-            cmd.reply_err(CallError::Rpc("internal: reject_terminal off-state".into()));
+            cmd.reply_err(CallError::ServerError("internal: reject_terminal off-state".into()));
         }
     }
 
+    /// Answer `RetryNow`: dial at once from `Cooldown` (keeping its attempt
+    /// count) or from `Terminal` (attempt 1); anywhere else there is nothing
+    /// to skip.
+    fn retry_now(&mut self, reply: oneshot::Sender<Result<bool, CallError>>) {
+        let attempt = match &self.state {
+            ActorState::Cooldown { next_attempt, .. } => *next_attempt,
+            ActorState::Terminal { .. } => 1,
+            _ => {
+                let _ = reply.send(Ok(false));
+                return;
+            }
+        };
+        log::info!("Actor retrying now from {}", self.state.name());
+        self.start_connecting(attempt);
+        let _ = reply.send(Ok(true));
+    }
+
     /// Dispatch a command in `Connected`.
     ///
     /// Every command — including `JoinContext` — is spawned as a child task
@@ -2008,15 +3431,19 @@ impl RpcActor {
                 let internal_tx = self.internal_tx.clone();
                 tokio::task::spawn_local(
                     async move {
-                        let result =
-                            run_rpc_call(kernel.join_context(context_id, &instance), &close_tx)
-                                .await;
-                        if result.is_ok() {
+                        let result = run_rpc_call(
+                            kernel.join_context_resuming(context_id, &instance, None),
+                            &close_tx,
+                        )
+                        .await;
+                        let result = result.map(|(joined, seat)| {
                             // Best-effort: if the actor is shutting down,
                             // the channel is closed and the state update
                             // doesn't matter anyway.
-                            let _ = internal_tx.send(InternalMsg::JoinedContext(context_id));
-                        }
+                            let token = seat.map(|s| s.token);
+                            let _ = internal_tx.send(InternalMsg::JoinedContext(context_id, token));
+                            joined
+                        });
                         let _ = reply.send(result);
                     }
                     .instrument(span),
@@ -2058,6 +3485,112 @@ impl RpcActor {
                     .instrument(span),
                 );
             }
+            RpcCommand::SubscribeActivity { interval_ms, reply } => {
+                if self.activity_interval_ms.is_some() {
+                    let _ = reply.send(Ok(()));
+                    return;
+                }
+                self.activity_interval_ms = Some(interval_ms);
+                let kernel = conn.kernel.clone();
+                let event_tx = self.event_tx.clone();
+                tokio::task::spawn_local(
+                    async move {
+                        let forwarder = ActivityEventsForwarder { event_tx };
+                        let client: crate::kaijutsu_capnp::activity_events::Client =
+                            capnp_rpc::new_client(forwarder);
+                        let result = run_rpc_call(
+                            kernel.subscribe_activity(client, interval_ms),
+                            &close_tx,
+                        )
+                        .await;
+                        let _ = reply.send(result);
+                    }
+                    .instrument(span),
+                );
+            }
+            RpcCommand::PeekDocument {
+                context_id,
+                ttl_secs,
+                stream,
+                reply,
+            } => {
+                let kernel = conn.kernel.clone();
+                let callback: Option<crate::kaijutsu_capnp::block_events::Client> =
+                    stream.then(|| {
+                        capnp_rpc::new_client(BlockEventsForwarder {
+                            event_tx: self.event_tx.clone(),
+                        })
+                    });
+                let (stop_tx, stop_rx) = oneshot::channel::<()>();
+                if stream {
+                    // Replacing an entry drops the old sender, ending that peek.
+                    self.peeks.insert(context_id, stop_tx);
+                }
+                tokio::task::spawn_local(
+                    async move {
+                        let result = run_rpc_call(
+                            kernel.peek_document(context_id, callback, ttl_secs),
+                            &close_tx,
+                        )
+                        .await;
+                        match result {
+                            Ok((document, peek)) => {
+                                let expires = Duration::from_secs(document.expires_in_secs as u64);
+                                let _ = reply.send(Ok(document));
+                                if !expires.is_zero() {
+                                    tokio::select! {
+                                        _ = stop_rx => {}
+                                        _ = tokio::time::sleep(expires) => {}
+                                    }
+                                }
+                                drop(peek);
+                            }
+                            Err(e) => {
+                                let _ = reply.send(Err(e));
+                            }
+                        }
+                    }
+                    .instrument(span),
+                );
+            }
+            RpcCommand::EndPeek { context_id, reply } => {
+                self.peeks.remove(&context_id);
+                let _ = reply.send(Ok(()));
+            }
+            RpcCommand::SubscribeBlock { block_id, reply } => {
+                let kernel = conn.kernel.clone();
+                let (event_tx, events) = broadcast::channel(256);
+                let callback: crate::kaijutsu_capnp::block_events::Client =
+                    capnp_rpc::new_client(BlockEventsForwarder {
+                        event_tx: event_tx.clone(),
+                    });
+                let (stop_tx, stop_rx) = oneshot::channel::<()>();
+                self.block_subs.retain(|stop| !stop.is_closed());
+                self.block_subs.push(stop_tx);
+                tokio::task::spawn_local(
+                    async move {
+                        let result =
+                            run_rpc_call(kernel.subscribe_block(&block_id, callback), &close_tx)
+                                .await;
+                        match result {
+                            Ok((block, sub)) => {
+                                let _ = reply.send(Ok((block, events)));
+                                // Hold the kernel's stream open until the
+                                // caller stops listening or we disconnect.
+                                tokio::select! {
+                                    _ = stop_rx => {}
+                                    _ = event_tx.closed() => {}
+                                }
+                                drop(sub);
+                            }
+                            Err(e) => {
+                                let _ = reply.send(Err(e));
+                            }
+                        }
+                    }
+                    .instrument(span),
+                );
+            }
             RpcCommand::AttachPeer {
                 config,
                 invocation_tx,
@@ -2085,6 +3618,69 @@ impl RpcActor {
                     .instrument(span),
                 );
             }
+            RpcCommand::OpenSeat { context_id, reply } => {
+                self.next_seat += 1;
+                let seat = self.next_seat;
+                let (event_tx, _) = broadcast::channel(EVENT_BROADCAST_CAPACITY);
+                self.seats.insert(
+                    seat,
+                    SeatRecord {
+                        context_id,
+                        instance: format!("{}/seat-{seat}", self.instance),
+                        token: None,
+                        event_tx,
+                        kernel: None,
+                    },
+                );
+                self.bind_seat(seat, Some(reply), span);
+            }
+            RpcCommand::CloseSeat { seat, reply } => {
+                // Dropping the binding releases the capability; the server
+                // ends the seat's session and its subscription with it.
+                self.seats.remove(&seat);
+                let _ = reply.send(Ok(()));
+            }
+            RpcCommand::RetryNow { reply } => {
+                let _ = reply.send(Ok(false));
+            }
+            RpcCommand::Seat { seat, command } => {
+                let Some(record) = self.seats.get(&seat) else {
+                    command.reply_err(CallError::NotFound(format!("seat {seat}")));
+                    return;
+                };
+                let Some(kernel) = record.kernel.clone() else {
+                    command.reply_err(CallError::Disconnected(format!(
+                        "seat {seat} is re-binding"
+                    )));
+                    return;
+                };
+                match *command {
+                    // These keep actor-side state for the connection's own
+                    // binding; a seat has its join and subscription already.
+                    cmd @ (RpcCommand::JoinContext { .. }
+                    | RpcCommand::ResubscribeBlocks { .. }
+                    | RpcCommand::SubscribeVfsActivity { .. }
+                    | RpcCommand::SubscribeActivity { .. }
+                    | RpcCommand::PeekDocument { .. }
+                    | RpcCommand::EndPeek { .. }
+                    | RpcCommand::SubscribeBlock { .. }
+                    | RpcCommand::OpenSeat { .. }
+                    | RpcCommand::CloseSeat { .. }
+                    | RpcCommand::RetryNow { .. }
+                    | RpcCommand::Seat { .. }) => {
+                        cmd.reply_err(CallError::ServerError(
+                            "not available on a seat handle".into(),
+                        ));
+                    }
+                    other => {
+                        let client = conn.client.clone();
+                        tokio::task::spawn_local(
+                            dispatch_kernel_command(other, client, kernel, close_tx)
+                                .instrument(span),
+                        );
+                    }
+                }
+            }
             other => {
                 let client = conn.client.clone();
                 let kernel = conn.kernel.clone();
@@ -2095,12 +3691,66 @@ impl RpcActor {
         }
     }
 
+    /// Bind `seat` on the live connection: `bindSeat`, join its context
+    /// (resuming its session when it has a token) and subscribe its block
+    /// events. The outcome comes back as `SeatBound`/`SeatDropped`. `reply`
+    /// is `OpenSeat`'s; a re-bind after reconnect passes `None` and only
+    /// logs a failure — the seat stays unbound until the next connection.
+    fn bind_seat(&self, seat: u64, reply: Option<OpenSeatReply>, span: tracing::Span) {
+        let (Some(conn), Some(record)) = (self.connection.as_ref(), self.seats.get(&seat)) else {
+            return;
+        };
+        let client = conn.client.clone();
+        let context_id = record.context_id;
+        let instance = record.instance.clone();
+        let token = record.token.clone();
+        let event_tx = record.event_tx.clone();
+        let epoch = self.connection_epoch;
+        let internal_tx = self.internal_tx.clone();
+        let close_tx = self.close_tx.clone();
+        tokio::task::spawn_local(
+            async move {
+                let result = run_rpc_call(
+                    establish_seat(&client, context_id, &instance, token.as_deref(), &event_tx),
+                    &close_tx,
+                )
+                .await;
+                let rebound = reply.is_none();
+                match (result, reply) {
+                    (Ok((kernel, token)), reply) => {
+                        let _ = internal_tx.send(InternalMsg::SeatBound {
+                            seat,
+                            epoch,
+                            kernel,
+                            token,
+                            rebound,
+                        });
+                        if let Some(reply) = reply
+                            && reply.send(Ok((seat, event_tx))).is_err()
+                        {
+                            let _ = internal_tx.send(InternalMsg::SeatDropped(seat));
+                        }
+                    }
+                    (Err(e), Some(reply)) => {
+                        let _ = internal_tx.send(InternalMsg::SeatDropped(seat));
+                        let _ = reply.send(Err(e));
+                    }
+                    (Err(e), None) => log::warn!("seat {seat} re-bind failed: {e}"),
+                }
+            }
+            .instrument(span),
+        );
+    }
+
     /// Apply an internal state-update message from a spawned child task.
     fn apply_internal(&mut self, msg: InternalMsg) {
         match msg {
-            InternalMsg::JoinedContext(ctx) => {
+            InternalMsg::JoinedContext(ctx, seat_token) => {
                 self.context_id = Some(ctx);
                 self.joined_context_id = Some(ctx);
+                if seat_token.is_some() {
+                    self.seat_token = seat_token;
+                }
                 // For single-context clients, re-scope block events to this
                 // context now that we know it. The initial subscription (made
                 // at connect, before any context existed) is kernel-wide;
@@ -2114,6 +3764,32 @@ impl RpcActor {
                 }
                 self.broadcast_state();
             }
+            InternalMsg::SeatBound {
+                seat,
+                epoch,
+                kernel,
+                token,
+                rebound,
+            } => {
+                // A bind from a dropped connection is dead on arrival; the
+                // current connection's re-bind supersedes it.
+                if epoch != self.connection_epoch {
+                    return;
+                }
+                let Some(record) = self.seats.get_mut(&seat) else {
+                    return;
+                };
+                record.kernel = Some(kernel);
+                if token.is_some() {
+                    record.token = token;
+                }
+                if rebound {
+                    let _ = record.event_tx.send(ServerEvent::Reconnected);
+                }
+            }
+            InternalMsg::SeatDropped(seat) => {
+                self.seats.remove(&seat);
+            }
         }
     }
 
@@ -2214,7 +3890,11 @@ impl RpcActor {
                     }
                 }
 
-                ActorState::Connecting { started_at, attempt } => {
+                ActorState::Connecting {
+                    started_at,
+                    attempt,
+                    ..
+                } => {
                     let started_at = *started_at;
                     let attempt = *attempt;
                     let total_deadline =
@@ -2275,6 +3955,7 @@ impl RpcActor {
                             self.state = ActorState::Cooldown {
                                 next_attempt,
                                 until,
+                                backoff,
                                 last_error: format!(
                                     "connect exceeded total budget ({:?})",
                                     CONNECT_TOTAL_BUDGET
@@ -2320,7 +4001,8 @@ impl RpcActor {
                 }
 
                 ActorState::Terminal { .. } => {
-                    // Absorbing state. Reject all incoming commands.
+                    // Absorbing state: reject all incoming commands, save a
+                    // manual `RetryNow`, which starts over at attempt 1.
                     tokio::select! {
                         cmd = self.rx.recv() => {
                             let Some(envelope) = cmd else {
@@ -2346,6 +4028,7 @@ impl RpcActor {
 /// Spawn the connect-handshake task. Returns a JoinHandle the actor can
 /// select on. The task runs each step with its own per-phase deadline so
 /// the failure mode names the slow phase.
+#[allow(clippy::too_many_arguments)]
 fn spawn_handshake(
     config: SshConfig,
     context_id: Option<ContextId>,
@@ -2354,6 +4037,8 @@ fn spawn_handshake(
     event_tx: broadcast::Sender<ServerEvent>,
     peer_registration: Option<(PeerConfig, std::sync::mpsc::Sender<PeerInvocation>)>,
     vfs_activity_interval_ms: Option<u32>,
+    activity_interval_ms: Option<u32>,
+    seat_token: Option<String>,
 ) -> JoinHandle<ConnectOutcome> {
     tokio::task::spawn_local(async move {
         connect_handshake(
@@ -2364,6 +4049,8 @@ fn spawn_handshake(
             event_tx,
             peer_registration,
             vfs_activity_interval_ms,
+            activity_interval_ms,
+            seat_token,
         )
         .await
     })
@@ -2397,6 +4084,27 @@ fn block_events_client_and_filter(
     (block_client, filter)
 }
 
+/// `bindSeat`, join and a context-scoped block subscription for one seat.
+/// Returns the seat's binding and the token to resume its session with.
+async fn establish_seat(
+    client: &RpcClient,
+    context_id: ContextId,
+    instance: &str,
+    token: Option<&str>,
+    event_tx: &broadcast::Sender<ServerEvent>,
+) -> Result<(KernelHandle, Option<String>), RpcError> {
+    let (kernel, _) = client.bind_seat().await?;
+    let (_, seat) = kernel
+        .join_context_resuming(context_id, instance, token)
+        .await?;
+    let (block_client, filter) = block_events_client_and_filter(event_tx, Some(context_id));
+    kernel
+        .subscribe_blocks_filtered(block_client, &filter, instance)
+        .await?;
+    Ok((kernel, seat.map(|s| s.token)))
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn connect_handshake(
     config: SshConfig,
     context_id: Option<ContextId>,
@@ -2405,6 +4113,8 @@ async fn connect_handshake(
     event_tx: broadcast::Sender<ServerEvent>,
     peer_registration: Option<(PeerConfig, std::sync::mpsc::Sender<PeerInvocation>)>,
     vfs_activity_interval_ms: Option<u32>,
+    activity_interval_ms: Option<u32>,
+    seat_token: Option<String>,
 ) -> ConnectOutcome {
     // 1. SSH dial + auth + channel open (with per-phase deadline).
     let client = match tokio::time::timeout(SSH_DIAL_TIMEOUT, connect_ssh(config)).await {
@@ -2447,15 +4157,16 @@ async fn connect_handshake(
         }
     };
 
-    // 3. join_context if a context was specified. Optional.
-    let joined_context = if let Some(ctx) = context_id {
+    // 3. join_context if a context was specified. Optional. A remembered
+    //    seat token resumes the previous session if it's still in grace.
+    let (joined_context, seat) = if let Some(ctx) = context_id {
         match tokio::time::timeout(
             RPC_JOIN_CONTEXT_TIMEOUT,
-            kernel.join_context(ctx, &instance),
+            kernel.join_context_resuming(ctx, &instance, seat_token.as_deref()),
         )
         .await
         {
-            Ok(Ok(c)) => Some(c),
+            Ok(Ok((c, seat))) => (Some(c), seat),
             Ok(Err(e)) => {
                 // join_context returns an application error when the context
                 // does not exist (e.g., kernel restart with a fresh db, or
@@ -2477,7 +4188,7 @@ async fn connect_handshake(
             }
         }
     } else {
-        None
+        (None, None)
     };
 
     // 3.5. Re-attach as a peer if a registration is remembered, so the kernel's
@@ -2521,6 +4232,25 @@ async fn connect_handshake(
         }
     }
 
+    // 3.7. Re-subscribe to context activity metrics, best-effort for the
+    //      same reason as 3.6 — the readings only drive rendering.
+    if let Some(interval_ms) = activity_interval_ms {
+        let activity_client: crate::kaijutsu_capnp::activity_events::Client =
+            capnp_rpc::new_client(ActivityEventsForwarder {
+                event_tx: event_tx.clone(),
+            });
+        match tokio::time::timeout(
+            RPC_CALL_TIMEOUT,
+            kernel.subscribe_activity(activity_client, interval_ms),
+        )
+        .await
+        {
+            Ok(Ok(())) => log::info!("Re-subscribed context activity on connect"),
+            Ok(Err(e)) => log::warn!("context activity re-subscribe failed (non-fatal): {e}"),
+            Err(_) => log::warn!("context activity re-subscribe timed out (non-fatal)"),
+        }
+    }
+
     // 4. Subscribe to block + resource events in parallel under a single
     //    deadline. If either fails, the whole handshake fails — we don't
     //    want to enter Connected without subscriptions.
@@ -2586,6 +4316,7 @@ async fn connect_handshake(
         kernel: kernel.clone(),
         kernel_id,
         joined_context,
+        seat,
     })
 }
 
@@ -2666,12 +4397,21 @@ async fn dispatch_kernel_command(
         RpcCommand::ListContexts { reply } => {
             dispatch!(kernel, reply, close_tx, k, k.list_contexts());
         }
+        RpcCommand::ListContextsQuery { query, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.list_contexts_query(&query));
+        }
         RpcCommand::ListTracks { reply } => {
             dispatch!(kernel, reply, close_tx, k, k.list_tracks());
         }
         RpcCommand::VfsSnapshot { path, depth, max_entries, reply } => {
             dispatch!(kernel, reply, close_tx, k, k.vfs_snapshot(&path, depth, max_entries));
         }
+        RpcCommand::VfsCreate { path, mode, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.vfs_create(&path, mode));
+        }
+        RpcCommand::VfsWrite { path, offset, data, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.vfs_write(&path, offset, &data));
+        }
         RpcCommand::Conclude { context_id, reply } => {
             dispatch!(kernel, reply, close_tx, k, k.conclude(context_id));
         }
@@ -2690,9 +4430,70 @@ async fn dispatch_kernel_command(
         RpcCommand::ArchiveContext { context_id, reply } => {
             dispatch!(kernel, reply, close_tx, k, k.archive_context(context_id));
         }
+        RpcCommand::CloseContexts { filter, dry_run, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.close_contexts(&filter, dry_run));
+        }
+        RpcCommand::ReopenContext { context_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.reopen_context(context_id));
+        }
+        RpcCommand::GetContextStats { context_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.get_context_stats(context_id));
+        }
+        RpcCommand::GetBudgetStatus { context_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.get_budget_status(context_id));
+        }
+        RpcCommand::SetExecutionBudget { context_id, budget, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.set_execution_budget(context_id, budget.as_ref()));
+        }
+        RpcCommand::SetToolHalt { context_id, halted, reason, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.set_tool_halt(context_id, halted, &reason));
+        }
+        RpcCommand::GetLlmParams { context_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.get_llm_params(context_id));
+        }
+        RpcCommand::SetLlmParams { context_id, params, merge, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.set_llm_params(context_id, params.as_ref(), merge));
+        }
+        RpcCommand::ContextPreview { context_id, strategy, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.context_preview(context_id, strategy.as_deref()));
+        }
+        RpcCommand::ContextRecover { context_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.context_recover(context_id));
+        }
+        RpcCommand::DigestSubscribe {
+            subscriber, source, every_secs, on_milestone, focus, remove, reply,
+        } => {
+            dispatch!(
+                kernel, reply, close_tx, k,
+                k.digest_subscribe(
+                    subscriber, source, every_secs, on_milestone, focus.as_deref(), remove,
+                )
+            );
+        }
+        RpcCommand::BroadcastPrompt { content, targets, label, reply } => {
+            dispatch!(
+                kernel, reply, close_tx, k,
+                k.broadcast_prompt(&content, &targets, label.as_deref())
+            );
+        }
+        RpcCommand::GetBroadcast { id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.get_broadcast(id));
+        }
+        RpcCommand::GetSandboxProfile { context_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.get_sandbox_profile(context_id));
+        }
+        RpcCommand::SetSandboxProfile { context_id, profile, reply } => {
+            dispatch!(
+                kernel, reply, close_tx, k,
+                k.set_sandbox_profile(context_id, profile.as_ref())
+            );
+        }
         RpcCommand::SearchSimilar { query, k: topk, reply } => {
             dispatch!(kernel, reply, close_tx, k, k.search_similar(&query, topk));
         }
+        RpcCommand::SearchBlocks { query, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.search_blocks(&query));
+        }
         RpcCommand::GetNeighbors { context_id, k: topk, reply } => {
             dispatch!(kernel, reply, close_tx, k, k.get_neighbors(context_id, topk));
         }
@@ -2712,6 +4513,15 @@ async fn dispatch_kernel_command(
                 k.create_context_typed(&label, &context_type)
             );
         }
+        RpcCommand::ReserveContextName { label, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.reserve_context_name(&label));
+        }
+        RpcCommand::CommitContextName { token, context_type, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.commit_context_name(token, &context_type));
+        }
+        RpcCommand::ReleaseContextName { token, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.release_context_name(token));
+        }
 
         // ── CRDT Sync ──
         RpcCommand::PushOps {
@@ -2734,6 +4544,12 @@ async fn dispatch_kernel_command(
         RpcCommand::CompactContext { context_id, reply } => {
             dispatch!(kernel, reply, close_tx, k, k.compact_context(context_id));
         }
+        RpcCommand::MoveBlock { context_id, block_id, after, reply } => {
+            dispatch!(
+                kernel, reply, close_tx, k,
+                k.move_block(context_id, &block_id, after.as_ref())
+            );
+        }
 
         // ── Shell / Execution ──
         RpcCommand::Execute { code, reply } => {
@@ -2750,6 +4566,16 @@ async fn dispatch_kernel_command(
                 k.shell_execute(&code, context_id, user_initiated)
             );
         }
+        RpcCommand::ShellRerun {
+            block_id,
+            user_initiated,
+            reply,
+        } => {
+            dispatch!(
+                kernel, reply, close_tx, k,
+                k.shell_rerun(&block_id, user_initiated)
+            );
+        }
         RpcCommand::SetBlockExcluded {
             context_id,
             block_id,
@@ -2761,6 +4587,201 @@ async fn dispatch_kernel_command(
                 k.set_block_excluded(context_id, &block_id, excluded)
             );
         }
+        RpcCommand::ReorderBlock {
+            context_id,
+            block_id,
+            after,
+            reply,
+        } => {
+            dispatch!(
+                kernel, reply, close_tx, k,
+                k.reorder_block(context_id, &block_id, after.as_ref())
+            );
+        }
+        RpcCommand::SetBlockLabel {
+            context_id,
+            block_id,
+            label,
+            reply,
+        } => {
+            dispatch!(
+                kernel, reply, close_tx, k,
+                k.set_block_label(context_id, &block_id, label.as_deref())
+            );
+        }
+        RpcCommand::ResolveBlock { query, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.resolve_block(&query));
+        }
+        RpcCommand::BackupNow { reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.backup_now());
+        }
+        RpcCommand::ScanGarbage {
+            context_id,
+            prune,
+            reply,
+        } => {
+            dispatch!(
+                kernel,
+                reply,
+                close_tx,
+                k,
+                k.scan_garbage(context_id, prune)
+            );
+        }
+        RpcCommand::SetSeatLanguage { language, reply } => {
+            dispatch!(
+                kernel,
+                reply,
+                close_tx,
+                k,
+                k.set_seat_language(language.as_deref())
+            );
+        }
+        RpcCommand::TranslateBlock {
+            block_id,
+            language,
+            reply,
+        } => {
+            dispatch!(
+                kernel,
+                reply,
+                close_tx,
+                k,
+                k.translate_block(&block_id, language.as_deref())
+            );
+        }
+        RpcCommand::LockBlock {
+            block_id,
+            ttl_secs,
+            note,
+            reply,
+        } => {
+            dispatch!(
+                kernel,
+                reply,
+                close_tx,
+                k,
+                k.lock_block(&block_id, ttl_secs, &note)
+            );
+        }
+        RpcCommand::UnlockBlock { block_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.unlock_block(&block_id));
+        }
+        RpcCommand::ListBlockLocks { context_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.list_block_locks(context_id));
+        }
+        RpcCommand::SetLockPolicy {
+            context_id,
+            policy,
+            reply,
+        } => {
+            dispatch!(
+                kernel,
+                reply,
+                close_tx,
+                k,
+                k.set_lock_policy(context_id, policy)
+            );
+        }
+        RpcCommand::CreateBookmark {
+            context_id,
+            name,
+            reply,
+        } => {
+            dispatch!(
+                kernel,
+                reply,
+                close_tx,
+                k,
+                k.create_bookmark(context_id, &name)
+            );
+        }
+        RpcCommand::ListBookmarks { context_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.list_bookmarks(context_id));
+        }
+        RpcCommand::ListConsentPrompts { reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.list_consent_prompts());
+        }
+        RpcCommand::ConsentRespond {
+            prompt_id,
+            decision,
+            reply,
+        } => {
+            dispatch!(
+                kernel,
+                reply,
+                close_tx,
+                k,
+                k.consent_respond(prompt_id, decision)
+            );
+        }
+        RpcCommand::GetContextTimeline {
+            context_id,
+            window_secs,
+            buckets,
+            reply,
+        } => {
+            dispatch!(
+                kernel,
+                reply,
+                close_tx,
+                k,
+                k.get_context_timeline(context_id, window_secs, buckets)
+            );
+        }
+        RpcCommand::RegisterMcpServer {
+            context_id,
+            spec,
+            reply,
+        } => {
+            dispatch!(kernel, reply, close_tx, k, k.register_mcp_server(context_id, &spec));
+        }
+        RpcCommand::UnregisterMcpServer {
+            context_id,
+            instance,
+            reply,
+        } => {
+            dispatch!(kernel, reply, close_tx, k, k.unregister_mcp_server(context_id, &instance));
+        }
+        RpcCommand::ListContextMcpServers { context_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.list_context_mcp_servers(context_id));
+        }
+        RpcCommand::ListInbox { include_acked, limit, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.list_inbox(include_acked, limit));
+        }
+        RpcCommand::AckInbox { ids, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.ack_inbox(&ids));
+        }
+        RpcCommand::GetPreferences { reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.get_preferences());
+        }
+        RpcCommand::SetPreference { key, value, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.set_preference(&key, value.as_deref()));
+        }
+        RpcCommand::ListConsentLog { after_seq, limit, verify, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.list_consent_log(after_seq, limit, verify));
+        }
+        RpcCommand::TailBlock { block_id, from_offset, timeout, tx, reply } => {
+            // Own deadline instead of RPC_CALL_TIMEOUT; hitting it drops the
+            // call, which cancels the kernel-side tail.
+            let result = match tokio::time::timeout(
+                timeout,
+                kernel.tail_block(&block_id, from_offset, tx),
+            )
+            .await
+            {
+                Ok(Ok(end)) => Ok(end),
+                Ok(Err(e)) => {
+                    let msg = e.to_string();
+                    if is_disconnect_error(&msg) {
+                        let _ = close_tx.try_send(CloseCause::RpcError(msg.clone()));
+                    }
+                    Err(CallError::from_rpc_message(msg))
+                }
+                Err(_) => Err(CallError::Timeout(timeout)),
+            };
+            let _ = reply.send(result);
+        }
         RpcCommand::Interrupt { exec_id, reply } => {
             dispatch!(kernel, reply, close_tx, k, k.interrupt(exec_id));
         }
@@ -2847,6 +4868,18 @@ async fn dispatch_kernel_command(
         RpcCommand::EditorKeys { session_id, keys, reply } => {
             dispatch!(kernel, reply, close_tx, k, k.editor_keys(session_id, &keys));
         }
+        RpcCommand::EditorOpen { path, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.editor_open(&path));
+        }
+        RpcCommand::GetEditorState { session_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.editor_state(session_id));
+        }
+        RpcCommand::EditorSave { session_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.editor_save(session_id));
+        }
+        RpcCommand::EditorQuit { session_id, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.editor_quit(session_id));
+        }
 
         // ── Tool Execution ──
         RpcCommand::ExecuteTool {
@@ -2873,11 +4906,11 @@ async fn dispatch_kernel_command(
 
         // ── LLM ──
         RpcCommand::Prompt {
-            content, model, context_id, reply,
+            content, model, context_id, params, reply,
         } => {
             dispatch!(
                 kernel, reply, close_tx, k,
-                k.prompt(&content, model.as_deref(), context_id)
+                k.prompt_with_params(&content, model.as_deref(), context_id, params.as_ref())
             );
         }
         RpcCommand::ConfigureLlm {
@@ -2888,6 +4921,9 @@ async fn dispatch_kernel_command(
                 k.set_context_model(context_id, &provider, &model)
             );
         }
+        RpcCommand::SwitchContextModel { context_id, spec, reply } => {
+            dispatch!(kernel, reply, close_tx, k, k.switch_context_model(context_id, &spec));
+        }
         RpcCommand::GetLlmConfig { reply } => {
             dispatch!(kernel, reply, close_tx, k, k.get_llm_config());
         }
@@ -2949,26 +4985,72 @@ async fn dispatch_kernel_command(
             let result = run_rpc_call(client.list_kernels(), &close_tx).await;
             let _ = reply.send(result);
         }
+        RpcCommand::ListKernelsQuery { query, reply } => {
+            let result = run_rpc_call(client.list_kernels_query(&query), &close_tx).await;
+            let _ = reply.send(result);
+        }
         // ── JoinContext handled inline by RpcActor::dispatch ──
         RpcCommand::JoinContext { reply, .. } => {
-            let _ = reply.send(Err(CallError::Rpc(
+            let _ = reply.send(Err(CallError::ServerError(
                 "join_context leaked into kernel dispatch (bug)".into(),
             )));
         }
 
         // ── ResubscribeBlocks handled inline by RpcActor::dispatch ──
         RpcCommand::ResubscribeBlocks { reply } => {
-            let _ = reply.send(Err(CallError::Rpc(
+            let _ = reply.send(Err(CallError::ServerError(
                 "resubscribe_blocks leaked into kernel dispatch (bug)".into(),
             )));
         }
 
         // ── SubscribeVfsActivity handled inline by RpcActor::dispatch (needs event_tx) ──
         RpcCommand::SubscribeVfsActivity { reply, .. } => {
-            let _ = reply.send(Err(CallError::Rpc(
+            let _ = reply.send(Err(CallError::ServerError(
                 "subscribe_vfs_activity leaked into kernel dispatch (bug)".into(),
             )));
         }
+        RpcCommand::SubscribeActivity { reply, .. } => {
+            let _ = reply.send(Err(CallError::ServerError(
+                "subscribe_activity leaked into kernel dispatch (bug)".into(),
+            )));
+        }
+        RpcCommand::PeekDocument { reply, .. } => {
+            let _ = reply.send(Err(CallError::ServerError(
+                "peek_document leaked into kernel dispatch (bug)".into(),
+            )));
+        }
+        RpcCommand::EndPeek { reply, .. } => {
+            let _ = reply.send(Err(CallError::ServerError(
+                "end_peek leaked into kernel dispatch (bug)".into(),
+            )));
+        }
+        RpcCommand::SubscribeBlock { reply, .. } => {
+            let _ = reply.send(Err(CallError::ServerError(
+                "subscribe_block leaked into kernel dispatch (bug)".into(),
+            )));
+        }
+
+        // ── Seats handled inline by RpcActor::dispatch ──
+        RpcCommand::OpenSeat { reply, .. } => {
+            let _ = reply.send(Err(CallError::ServerError(
+                "open_seat leaked into kernel dispatch (bug)".into(),
+            )));
+        }
+        RpcCommand::CloseSeat { reply, .. } => {
+            let _ = reply.send(Err(CallError::ServerError(
+                "close_seat leaked into kernel dispatch (bug)".into(),
+            )));
+        }
+        cmd @ RpcCommand::Seat { .. } => {
+            cmd.reply_err(CallError::ServerError(
+                "seat command leaked into kernel dispatch (bug)".into(),
+            ));
+        }
+        RpcCommand::RetryNow { reply } => {
+            let _ = reply.send(Err(CallError::ServerError(
+                "retry_now leaked into kernel dispatch (bug)".into(),
+            )));
+        }
 
         // ── Peers ──
         RpcCommand::AttachPeer {
@@ -2988,7 +5070,7 @@ async fn dispatch_kernel_command(
                     if is_disconnect_error(&msg) {
                         let _ = close_tx.try_send(CloseCause::RpcError(msg.clone()));
                     }
-                    Err(CallError::Rpc(msg))
+                    Err(CallError::from_rpc_message(msg))
                 }
                 Err(_) => Err(CallError::Timeout(RPC_CALL_TIMEOUT)),
             };
@@ -3075,6 +5157,7 @@ pub fn spawn_actor(
         event_tx,
         status_tx,
         status_watch_rx,
+        seat: None,
     }
 }
 
@@ -3126,6 +5209,25 @@ mod tests {
         assert!(s.contains("3"), "got: {s}");
     }
 
+    #[test]
+    fn rpc_messages_map_onto_the_taxonomy() {
+        let cases = [
+            ("Disconnected: Peer disconnected", "disconnected", true),
+            ("Overloaded: queue full", "rate_limited", true),
+            ("Failed: target context 1a2b not found", "not_found", false),
+            ("Failed: shell denied: no exec", "permission_denied", false),
+            ("Failed: label conflict: taken", "conflict", false),
+            ("Failed: failed to merge ops: bad", "server_error", false),
+        ];
+        for (msg, code, retryable) in cases {
+            let e = CallError::from_rpc_message(msg);
+            assert_eq!(e.code(), code, "{msg}");
+            assert_eq!(e.is_retryable(), retryable, "{msg}");
+        }
+        assert!(CallError::Timeout(RPC_CALL_TIMEOUT).is_retryable());
+        assert!(!CallError::Shutdown.is_retryable());
+    }
+
     /// Build a bare `RpcActor` for state-machine unit tests. No network I/O:
     /// `RpcActor::new` only wires in-memory channels, so the state transition
     /// methods (`start_closing`/`finish_closing`/...) are exercisable without
@@ -3162,6 +5264,7 @@ mod tests {
         actor.state = ActorState::Connecting {
             attempt: 3,
             started_at: Instant::now(),
+            last_error: None,
         };
         actor.start_closing(CloseCause::RpcError("disconnected".into()));
         assert!(
@@ -3192,6 +5295,7 @@ mod tests {
         actor.state = ActorState::Cooldown {
             next_attempt: 5,
             until: Instant::now(),
+            backoff: Duration::ZERO,
             last_error: "prior failure".into(),
         };
         actor.start_closing(CloseCause::PingFailed("timeout".into()));
@@ -3236,9 +5340,28 @@ mod tests {
         actor.state = ActorState::Connecting {
             attempt: 7,
             started_at: Instant::now(),
+            last_error: None,
         };
         actor.start_closing(CloseCause::Shutdown);
         actor.finish_closing();
         assert!(matches!(actor.state, ActorState::Terminal { .. }));
     }
+    /// `RetryNow` only cuts a wait short; mid-handshake there is nothing to
+    /// skip and the attempt in flight is left alone.
+    #[test]
+    fn retry_now_while_connecting_is_a_no_op() {
+        let mut actor = test_actor();
+        actor.state = ActorState::Connecting {
+            attempt: 2,
+            started_at: Instant::now(),
+            last_error: Some("connection refused".into()),
+        };
+        let (reply, mut rx) = oneshot::channel();
+        actor.reject_not_ready(RpcCommand::RetryNow { reply });
+        assert!(matches!(rx.try_recv(), Ok(Ok(false))));
+        assert!(matches!(
+            actor.state,
+            ActorState::Connecting { attempt: 2, .. }
+        ));
+    }
 }
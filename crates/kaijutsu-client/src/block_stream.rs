@@ -0,0 +1,449 @@
+//! Byte-stream adapters over a block's text.
+//!
+//! [`BlockWriter`] is an [`AsyncWrite`] that appends to a block: written
+//! bytes are buffered, decoded as UTF-8, and pushed as CRDT appends
+//! (`pushOps`) a batch at a time, so `tokio::io::copy` from a child's stdout
+//! lands in a block the way a streaming model response does. [`BlockReader`]
+//! is an [`AsyncRead`] over `tailBlock`: it yields the block's text from a
+//! char offset, then each append as it lands, and reaches EOF when the block
+//! reaches a terminal status.
+//!
+//! Both ride an [`ActorHandle`] (the writer accepts any [`DocSyncBackend`]),
+//! so they are `Send` and work from any tokio task.
+//!
+//! ```ignore
+//! let mut child = Command::new("cargo").arg("build").stdout(Stdio::piped()).spawn()?;
+//! let mut block = BlockWriter::open(actor.clone(), block_id, principal_id).await?;
+//! tokio::io::copy(child.stdout.as_mut().unwrap(), &mut block).await?;
+//! block.shutdown().await?;
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use kaijutsu_crdt::{BlockId, PrincipalId};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::mpsc;
+
+use crate::actor::{ActorHandle, CallError, DocSyncBackend};
+use crate::rpc::{BlockTailChunk, BlockTailEnd};
+use crate::synced_document::SyncedDocument;
+
+/// Bytes buffered before [`BlockWriter`] pushes without being flushed.
+pub const DEFAULT_BATCH_BYTES: usize = 4096;
+
+type Frontier = kaijutsu_crdt::Frontier;
+
+/// The writer's document and push cursor. Moved into the push future while
+/// one is in flight and handed back when it finishes.
+struct WriterCore<B> {
+    backend: B,
+    doc: SyncedDocument,
+    block_id: BlockId,
+    /// What the kernel has acknowledged; a failed push leaves it put so the
+    /// next push resends the same ops (merging is idempotent).
+    pushed: HashMap<BlockId, Frontier>,
+}
+
+impl<B: DocSyncBackend> WriterCore<B> {
+    async fn push(&mut self) -> io::Result<()> {
+        let ops = self.doc.doc().ops_since(&self.pushed);
+        let frontier = self.doc.doc().frontier();
+        let bytes = kaijutsu_types::codec::encode(&ops)
+            .map_err(|e| io::Error::other(format!("encode block ops: {e}")))?;
+        self.backend
+            .push_ops(self.block_id.context_id, &bytes, Some(&self.pushed))
+            .await
+            .map_err(call_error_to_io)?;
+        self.pushed = frontier;
+        Ok(())
+    }
+}
+
+enum WriteState<B> {
+    Idle(Box<WriterCore<B>>),
+    Pushing(BoxFuture<'static, (Box<WriterCore<B>>, io::Result<()>)>),
+    /// Only while swapping between the two.
+    Swapping,
+}
+
+/// Appends everything written to one block. See the [module docs](self).
+///
+/// Writes are buffered: a batch is pushed once [`DEFAULT_BATCH_BYTES`] (or
+/// [`Self::with_batch_bytes`]) accumulate, and [`AsyncWriteExt::flush`]
+/// pushes whatever is left. A multi-byte character split across writes is
+/// held back until it completes; invalid UTF-8 becomes U+FFFD. Shut the
+/// writer down (or flush it) before dropping it — a dropped writer's
+/// unpushed tail is lost.
+///
+/// [`AsyncWriteExt::flush`]: tokio::io::AsyncWriteExt::flush
+pub struct BlockWriter<B = ActorHandle> {
+    block_id: BlockId,
+    state: WriteState<B>,
+    pending: Vec<u8>,
+    batch_bytes: usize,
+}
+
+impl<B: DocSyncBackend + 'static> BlockWriter<B> {
+    /// Fetch the block's context and start appending to `block_id` as
+    /// `principal_id`. Fails if the block does not exist.
+    pub async fn open(
+        backend: B,
+        block_id: BlockId,
+        principal_id: PrincipalId,
+    ) -> Result<Self, CallError> {
+        let state = backend.get_context_sync(block_id.context_id).await?;
+        let doc = SyncedDocument::from_sync_state(&state, principal_id)
+            .map_err(|e| CallError::InvalidOps(e.to_string()))?;
+        if doc.get_block(&block_id).is_none() {
+            return Err(CallError::NotFound(format!("block {block_id} not found")));
+        }
+        let pushed = doc.doc().frontier();
+        Ok(Self {
+            block_id,
+            state: WriteState::Idle(Box::new(WriterCore {
+                backend,
+                doc,
+                block_id,
+                pushed,
+            })),
+            pending: Vec::new(),
+            batch_bytes: DEFAULT_BATCH_BYTES,
+        })
+    }
+
+    /// Push once this many bytes are buffered instead of
+    /// [`DEFAULT_BATCH_BYTES`].
+    pub fn with_batch_bytes(mut self, batch_bytes: usize) -> Self {
+        self.batch_bytes = batch_bytes.max(1);
+        self
+    }
+
+    /// The block being written.
+    pub fn block_id(&self) -> BlockId {
+        self.block_id
+    }
+
+    /// Push buffered text until less than `threshold` bytes remain. With
+    /// `eof`, a trailing partial character is pushed too (as U+FFFD).
+    fn poll_push(
+        &mut self,
+        cx: &mut Context<'_>,
+        threshold: usize,
+        eof: bool,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            match std::mem::replace(&mut self.state, WriteState::Swapping) {
+                WriteState::Pushing(mut fut) => match fut.as_mut().poll(cx) {
+                    Poll::Ready((core, result)) => {
+                        self.state = WriteState::Idle(core);
+                        result?;
+                    }
+                    Poll::Pending => {
+                        self.state = WriteState::Pushing(fut);
+                        return Poll::Pending;
+                    }
+                },
+                WriteState::Idle(mut core) => {
+                    let text = if self.pending.len() >= threshold {
+                        take_text(&mut self.pending, eof)
+                    } else {
+                        String::new()
+                    };
+                    if text.is_empty() {
+                        self.state = WriteState::Idle(core);
+                        return Poll::Ready(Ok(()));
+                    }
+                    let block_id = self.block_id;
+                    if let Err(e) = core.doc.doc_mut().append_text(&block_id, &text) {
+                        self.state = WriteState::Idle(core);
+                        return Poll::Ready(Err(io::Error::other(format!(
+                            "append to block {block_id}: {e}"
+                        ))));
+                    }
+                    self.state = WriteState::Pushing(Box::pin(async move {
+                        let result = core.push().await;
+                        (core, result)
+                    }));
+                }
+                WriteState::Swapping => unreachable!("BlockWriter state left mid-swap"),
+            }
+        }
+    }
+}
+
+impl<B: DocSyncBackend + 'static> AsyncWrite for BlockWriter<B> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let threshold = this.batch_bytes;
+        ready!(this.poll_push(cx, threshold, false))?;
+        this.pending.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_push(cx, 1, false)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_push(cx, 1, true)
+    }
+}
+
+/// Take the decodable prefix of `pending`, leaving an incomplete trailing
+/// character behind unless `eof`.
+fn take_text(pending: &mut Vec<u8>, eof: bool) -> String {
+    let split = match std::str::from_utf8(pending) {
+        Err(e) if e.error_len().is_none() && !eof => e.valid_up_to(),
+        _ => pending.len(),
+    };
+    let rest = pending.split_off(split);
+    let text = String::from_utf8_lossy(pending).into_owned();
+    *pending = rest;
+    text
+}
+
+/// Follows one block's text. See the [module docs](self).
+pub struct BlockReader {
+    rx: mpsc::UnboundedReceiver<BlockTailChunk>,
+    /// The `tailBlock` call; `None` once it has returned.
+    tail: Option<BoxFuture<'static, Result<BlockTailEnd, CallError>>>,
+    end: Option<BlockTailEnd>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl BlockReader {
+    /// Follow `block_id` from char `from_offset` (0 for the whole block).
+    /// Reads fail with [`io::ErrorKind::TimedOut`] if the block is still
+    /// running after `timeout`; everything appended until then has been
+    /// read.
+    pub fn follow(
+        actor: ActorHandle,
+        block_id: BlockId,
+        from_offset: u64,
+        timeout: Duration,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let tail =
+            Box::pin(async move { actor.tail_block(block_id, from_offset, timeout, tx).await });
+        Self::new(rx, tail)
+    }
+
+    fn new(
+        rx: mpsc::UnboundedReceiver<BlockTailChunk>,
+        tail: BoxFuture<'static, Result<BlockTailEnd, CallError>>,
+    ) -> Self {
+        Self {
+            rx,
+            tail: Some(tail),
+            end: None,
+            chunk: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// How the follow ended — the final length and status — once the reader
+    /// has reached EOF.
+    pub fn end(&self) -> Option<BlockTailEnd> {
+        self.end
+    }
+}
+
+impl AsyncRead for BlockReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.pos < this.chunk.len() {
+                let n = buf.remaining().min(this.chunk.len() - this.pos);
+                buf.put_slice(&this.chunk[this.pos..this.pos + n]);
+                this.pos += n;
+                return Poll::Ready(Ok(()));
+            }
+
+            // The kernel waits for each append to be delivered before it
+            // returns, so once the tail has ended every chunk is queued.
+            let Some(tail) = this.tail.as_mut() else {
+                match this.rx.try_recv() {
+                    Ok(chunk) => {
+                        this.chunk = chunk.text.into_bytes();
+                        this.pos = 0;
+                        continue;
+                    }
+                    Err(_) => return Poll::Ready(Ok(())),
+                }
+            };
+
+            if let Poll::Ready(Some(chunk)) = this.rx.poll_recv(cx) {
+                this.chunk = chunk.text.into_bytes();
+                this.pos = 0;
+                continue;
+            }
+
+            match tail.as_mut().poll(cx) {
+                Poll::Ready(Ok(end)) => {
+                    this.end = Some(end);
+                    this.tail = None;
+                }
+                Poll::Ready(Err(e)) => {
+                    this.tail = None;
+                    return Poll::Ready(Err(call_error_to_io(e)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+fn call_error_to_io(e: CallError) -> io::Error {
+    let kind = match &e {
+        CallError::Timeout(_) => io::ErrorKind::TimedOut,
+        CallError::NotFound(_) => io::ErrorKind::NotFound,
+        CallError::PermissionDenied(_) => io::ErrorKind::PermissionDenied,
+        CallError::Disconnected(_) | CallError::Shutdown => io::ErrorKind::BrokenPipe,
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(kind, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use kaijutsu_crdt::block_store::{BlockStore as CrdtBlockStore, SyncPayload};
+    use kaijutsu_crdt::{BlockKind, ContentType, ContextId, Role, Status};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::rpc::SyncState;
+
+    /// Serves a snapshot of, and merges pushes into, one server-side store.
+    #[derive(Clone)]
+    struct FakeBackend {
+        store: Arc<Mutex<CrdtBlockStore>>,
+        pushes: Arc<Mutex<usize>>,
+    }
+
+    #[async_trait::async_trait]
+    impl DocSyncBackend for FakeBackend {
+        async fn get_context_sync(&self, context_id: ContextId) -> Result<SyncState, CallError> {
+            let ops = kaijutsu_types::codec::encode(&self.store.lock().unwrap().snapshot())
+                .expect("encode snapshot");
+            Ok(SyncState {
+                context_id,
+                ops,
+                version: 1,
+                generation: 0,
+                seq_num: 0,
+            })
+        }
+
+        async fn push_ops(
+            &self,
+            _context_id: ContextId,
+            ops: &[u8],
+            _known: Option<&HashMap<BlockId, Frontier>>,
+        ) -> Result<u64, CallError> {
+            let payload: SyncPayload = kaijutsu_types::codec::decode(ops).expect("decode push");
+            self.store
+                .lock()
+                .unwrap()
+                .merge_ops(payload)
+                .expect("merge push");
+            *self.pushes.lock().unwrap() += 1;
+            Ok(1)
+        }
+    }
+
+    fn backend_with_block() -> (FakeBackend, BlockId) {
+        let mut store = CrdtBlockStore::new(ContextId::new(), PrincipalId::system());
+        let block_id = store
+            .insert_block(
+                None,
+                None,
+                Role::Tool,
+                BlockKind::ToolResult,
+                "",
+                Status::Running,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let backend = FakeBackend {
+            store: Arc::new(Mutex::new(store)),
+            pushes: Arc::new(Mutex::new(0)),
+        };
+        (backend, block_id)
+    }
+
+    fn content(backend: &FakeBackend, block_id: &BlockId) -> String {
+        let store = backend.store.lock().unwrap();
+        store.get_block_snapshot(block_id).unwrap().content
+    }
+
+    #[tokio::test]
+    async fn copy_into_a_block_batches_and_keeps_split_characters_whole() {
+        let (backend, block_id) = backend_with_block();
+        let mut writer = BlockWriter::open(backend.clone(), block_id, PrincipalId::new())
+            .await
+            .unwrap()
+            .with_batch_bytes(8);
+
+        // "é" is two bytes; the first write ends between them, so a flush
+        // pushes "caf" and holds the lead byte back.
+        let bytes = "caf\u{e9} au lait\n".as_bytes();
+        writer.write_all(&bytes[..4]).await.unwrap();
+        assert_eq!(*backend.pushes.lock().unwrap(), 0, "below the batch size");
+        writer.flush().await.unwrap();
+        assert_eq!(content(&backend, &block_id), "caf");
+
+        let mut rest = &bytes[4..];
+        tokio::io::copy(&mut rest, &mut writer).await.unwrap();
+        writer.shutdown().await.unwrap();
+        assert_eq!(content(&backend, &block_id), "caf\u{e9} au lait\n");
+    }
+
+    #[tokio::test]
+    async fn open_fails_for_an_unknown_block() {
+        let (backend, block_id) = backend_with_block();
+        let missing = BlockId::new(block_id.context_id, PrincipalId::new(), 99);
+        let err = BlockWriter::open(backend, missing, PrincipalId::new()).await;
+        assert!(matches!(err, Err(CallError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn reader_yields_appends_then_eof() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        for (offset, text) in [(0, "hello "), (6, "world")] {
+            tx.send(BlockTailChunk {
+                offset,
+                text: text.to_string(),
+            })
+            .unwrap();
+        }
+        let end = BlockTailEnd {
+            offset: 11,
+            status: Some(Status::Done),
+        };
+        let mut reader = BlockReader::new(rx, Box::pin(async move { Ok(end) }));
+
+        let mut out = String::new();
+        reader.read_to_string(&mut out).await.unwrap();
+        assert_eq!(out, "hello world");
+        assert_eq!(reader.end(), Some(end));
+        drop(tx);
+    }
+}
@@ -0,0 +1,142 @@
+//! `KaijutsuClient` — a `Send + Sync` client that needs no `LocalSet`.
+//!
+//! Cap'n Proto's RPC types are `!Send`, so [`RpcClient`](crate::RpcClient)
+//! and [`spawn_actor`] only work inside a `tokio::task::LocalSet`. That is
+//! awkward for hosts built on a multi-threaded runtime (or none at all).
+//! [`KaijutsuClient::spawn`] takes care of it: it starts a dedicated thread
+//! running a current-thread runtime + `LocalSet` (the same shape as the app's
+//! bootstrap thread), spawns the RPC actor there, and hands back its
+//! [`ActorHandle`].
+//!
+//! Every `ActorHandle` method is reachable through `Deref` — the whole kernel
+//! RPC surface, typed, returning [`CallError`](crate::CallError), with
+//! reconnects handled by the actor. No capnp type crosses this boundary, and
+//! the futures are `Send`, so they can be awaited from any runtime thread.
+//!
+//! ```no_run
+//! # async fn demo() -> Result<(), Box<dyn std::error::Error>> {
+//! use kaijutsu_client::{KaijutsuClient, SshConfig};
+//!
+//! let client = KaijutsuClient::spawn(SshConfig::default(), None, "my-tool".into(), true)?;
+//! let info = tokio::spawn({
+//!     let client = client.clone();
+//!     async move { client.get_info().await }
+//! })
+//! .await??;
+//! println!("connected to {}", info.name);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The thread lives as long as the actor: once the last clone of the client
+//! (and of any handle taken from it) is dropped, the actor shuts down and the
+//! thread exits.
+
+use std::ops::Deref;
+
+use kaijutsu_crdt::ContextId;
+
+use crate::actor::{ActorHandle, spawn_actor};
+use crate::ssh::SshConfig;
+
+/// Send + Sync client owning the thread its RPC actor runs on.
+#[derive(Clone)]
+pub struct KaijutsuClient {
+    handle: ActorHandle,
+}
+
+impl KaijutsuClient {
+    /// Start the RPC thread and its actor. Arguments are those of
+    /// [`spawn_actor`]; the actor starts dialing immediately, and calls made
+    /// before it is connected fail with `CallError::NotReady` (watch
+    /// [`ActorHandle::watch_status`] to wait for `Connected`).
+    ///
+    /// Callable from any context — inside or outside a tokio runtime. Fails
+    /// only if the thread or its runtime can't be created.
+    pub fn spawn(
+        config: SshConfig,
+        context_id: Option<ContextId>,
+        instance: String,
+        scope_blocks_to_context: bool,
+    ) -> std::io::Result<Self> {
+        let (ready_tx, ready_rx) = std::sync::mpsc::sync_channel(1);
+        std::thread::Builder::new()
+            .name("kaijutsu-rpc".into())
+            .spawn(move || {
+                let rt = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(rt) => rt,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let local = tokio::task::LocalSet::new();
+                local.block_on(&rt, async move {
+                    let handle = spawn_actor(config, context_id, instance, scope_blocks_to_context);
+                    // The status watch's sender is owned by the actor, so
+                    // `changed()` errors exactly when the actor has exited.
+                    // A receiver doesn't keep the actor alive; a handle would.
+                    let mut status = handle.watch_status();
+                    if ready_tx.send(Ok(handle)).is_err() {
+                        return;
+                    }
+                    while status.changed().await.is_ok() {}
+                    log::debug!("kaijutsu-rpc thread exiting: actor stopped");
+                });
+            })?;
+        let handle = ready_rx.recv().map_err(|_| {
+            std::io::Error::other("kaijutsu-rpc thread exited before starting the actor")
+        })??;
+        Ok(Self { handle })
+    }
+
+    /// The underlying actor handle, e.g. to store where an `ActorHandle` is
+    /// expected. Keeps the RPC thread alive like the client itself does.
+    pub fn handle(&self) -> ActorHandle {
+        self.handle.clone()
+    }
+}
+
+impl Deref for KaijutsuClient {
+    type Target = ActorHandle;
+
+    fn deref(&self) -> &ActorHandle {
+        &self.handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CallError;
+
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn calls_work_from_a_multi_threaded_runtime_without_a_local_set() {
+        // Nothing listens on port 1: the actor never connects, so calls come
+        // back NotReady (or Disconnected) rather than hanging.
+        let config = SshConfig {
+            host: "127.0.0.1".into(),
+            port: 1,
+            ..SshConfig::default()
+        };
+        let client = KaijutsuClient::spawn(config, None, "facade-test".into(), true).unwrap();
+        assert_send_sync(&client);
+
+        let remote = client.clone();
+        let result = tokio::spawn(async move { remote.get_info().await })
+            .await
+            .unwrap();
+        assert!(
+            matches!(
+                result,
+                Err(CallError::NotReady(_) | CallError::Disconnected(_))
+            ),
+            "{result:?}"
+        );
+    }
+}
@@ -242,6 +242,18 @@ impl DocumentStore {
         }
     }
 
+    /// Manually resolve a context's sync quarantine: drop the payloads that
+    /// failed to apply and mark the doc stale so a full re-fetch follows, as
+    /// for a `NeedsResync` effect. Returns how many payloads were dropped, or
+    /// `None` if the context isn't cached.
+    pub fn resolve_quarantine(&mut self, context_id: ContextId) -> Option<usize> {
+        let entry = self.documents.get_mut(&context_id)?;
+        let dropped = entry.synced.resolve_quarantine();
+        entry.synced_at_generation = 0;
+        self.generation = self.generation.wrapping_add(1);
+        Some(dropped)
+    }
+
     /// Move a context_id to the front of the MRU list.
     fn touch_mru(&mut self, context_id: ContextId) {
         self.mru.retain(|&id| id != context_id);
@@ -320,6 +332,8 @@ mod tests {
         SyncState {
             context_id,
             version: n as u64,
+            generation: 0,
+            seq_num: 0,
             ops: kaijutsu_types::codec::encode(&store.snapshot()).expect("encode snapshot"),
         }
     }
@@ -376,6 +390,32 @@ mod tests {
         assert_eq!(store.stale_active(), None);
     }
 
+    #[test]
+    fn resolve_quarantine_makes_the_doc_stale() {
+        let mut store = DocumentStore::default();
+        let c = ctx();
+        store
+            .apply_sync(c, &sync_state(c, 1), PrincipalId::new(), || "ctx".into())
+            .unwrap();
+        store.set_active(c);
+
+        // A corrupt text-ops payload is held, not dropped.
+        let block_id = store.get(c).unwrap().synced.blocks()[0].id;
+        store.apply_server_event(&ServerEvent::BlockTextOps {
+            context_id: c,
+            block_id,
+            ops: b"garbage".to_vec(),
+            seq_num: 0,
+            generation: 0,
+        });
+        assert_eq!(store.get(c).unwrap().synced.quarantine().len(), 1);
+
+        assert_eq!(store.resolve_quarantine(c), Some(1));
+        assert!(store.get(c).unwrap().synced.quarantine().is_empty());
+        assert_eq!(store.stale_active(), Some(c), "resolving wants a re-fetch");
+        assert_eq!(store.resolve_quarantine(ctx()), None);
+    }
+
     #[test]
     fn no_active_context_is_never_stale() {
         let mut store = DocumentStore::default();
@@ -394,6 +434,8 @@ mod tests {
                 &SyncState {
                     context_id: c,
                     version: 1,
+                    generation: 0,
+                    seq_num: 0,
                     ops: kaijutsu_types::codec::encode(&server.snapshot()).unwrap(),
                 },
                 PrincipalId::new(),
@@ -2,11 +2,18 @@
 //!
 //! Provides typed Cap'n Proto RPC client for connecting to kaijutsu servers.
 //! Can connect via SSH (to remote servers) or Unix socket (for testing).
+//!
+//! [`RpcClient`] and [`spawn_actor`] must run inside a `tokio::task::LocalSet`
+//! (Cap'n Proto's types are `!Send`). Hosts that would rather not manage one
+//! use [`KaijutsuClient`], which owns its own RPC thread and is `Send + Sync`.
 
 pub mod actor;
+pub mod block_stream;
+pub mod client;
 pub mod constants;
 pub mod document_store;
 pub mod rpc;
+pub mod seat;
 pub mod sftp;
 pub mod share_server;
 pub mod ssh;
@@ -25,23 +32,30 @@ pub use actor::{
     PeerInvocation, spawn_actor,
 };
 pub use rpc::{
-    Completion, CompletionKind, ConsentMode, ContextCluster, ContextInfo, ContextMembership,
-    EditorState, HistoryEntry, Identity, InputState, KernelConfig, KernelHandle, KernelInfo,
-    LlmConfigInfo, LlmProviderInfo, McpResource, McpToolResult, MountSpec, PresetInfo,
-    RpcClient, RpcError, ShellValue, SimilarContext, SnapshotNode, SnapshotResult, StagedDriftInfo,
-    SubmitResult, SyncState, ToolResult, ToolSchema, TrackInfo, VersionSnapshot, VfsActivityEntry,
-    VfsFileType,
+    BackupReport, BlockSearchHit, BlockSearchQuery, BlockTailChunk, BlockTailEnd, BlockTranslation, Completion, CompletionKind, ConsentLogPage, ConsentMode, ContextCluster, ContextInfo, ContextMcpServerInfo, ContextPreview,
+    ContextMembership, ContextPage, EditorState, GarbageReport, HistoryEntry, Identity, InputState, KernelConfig,
+    InboxPage, KernelHandle, KernelInfo, KernelPage, LlmConfigInfo, LlmProviderHealth, LlmProviderInfo, McpResource,
+    McpServerSpec, McpToolInfo, McpToolResult, MountSpec, PeekedDocument, PresetInfo, RpcClient, RpcError,
+    SeatInfo, ShellValue, SimilarContext, SnapshotNode, SnapshotResult, StagedDriftInfo, SubmitResult,
+    SyncState, ToolResult, ToolSchema, TrackInfo, VersionSnapshot, VfsActivityEntry, VfsFileType,
 };
+pub use block_stream::{BlockReader, BlockWriter};
+pub use client::KaijutsuClient;
 pub use document_store::{DocumentEntry, DocumentStore};
+pub use seat::SeatHandle;
 pub use sftp::{CasFetch, CasResolver, ResolveSource, SftpClient, SftpError, default_cache_dir};
 pub use share_server::{
     ShareArg, ShareHandler, ShareServerConfig, parse_share_arg, validate_unique_names,
 };
-pub use ssh::{KeySource, SshClient, SshConfig, SshError};
+pub use ssh::{KeySource, SshClient, SshConfig, SshError, agent_key_fingerprints};
 pub use subscriptions::{
-    ConnectionStatus, OutputEvent, ServerEvent, editor_events_channel, vfs_activity_events_channel,
+    ConnectionStatus, OutputEvent, ServerEvent, activity_events_channel, editor_events_channel,
+    vfs_activity_events_channel,
+};
+pub use sync::{
+    BlockChange, PushValidationError, QuarantineReason, QuarantinedPayload, SkipReason,
+    SyncChanges, SyncError, SyncManager, SyncResult, validate_push_ops, validate_sync_payload,
 };
-pub use sync::{SkipReason, SyncError, SyncManager, SyncResult};
 pub use synced_document::{SyncEffect, SyncedDocument};
 pub use synced_input::SyncedInput;
 
@@ -51,9 +65,9 @@ pub use synced_input::SyncedInput;
 /// Must be called within a `tokio::task::LocalSet` context.
 pub async fn connect_ssh(config: SshConfig) -> Result<RpcClient, ConnectError> {
     let mut ssh = SshClient::new(config);
-    let rpc_channel = ssh.connect().await?;
+    let (rpc_channel, compression) = ssh.connect_rpc().await?;
     let rpc_stream = rpc_channel.into_stream();
-    let mut client = RpcClient::new(rpc_stream).await?;
+    let mut client = RpcClient::with_compression(rpc_stream, compression).await?;
     // Retain the SSH session handle for clean disconnect and keepalive.
     // Without this, the Handle<ClientHandler> is dropped and no
     // SSH_MSG_DISCONNECT can be sent for graceful shutdown.
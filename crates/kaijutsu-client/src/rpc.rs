@@ -6,10 +6,12 @@ use capnp_rpc::{RpcSystem, rpc_twoparty_capnp, twoparty};
 use futures::AsyncReadExt;
 use kaijutsu_crdt::{ContextId, KernelId};
 use kaijutsu_types::{
-    BlockFilter, BlockId, BlockKind, BlockQuery, BlockSnapshot, BlockSnapshotBuilder, ContentType,
-    DriftKind, ErrorCategory, ErrorPayload, ErrorSeverity, ErrorSpan, PrincipalId, Role, Status,
-    Tick, ToolKind, TrackId,
+    AgentProvenance, BlockFilter, BlockId, BlockKind, BlockMention, BlockQuery, BlockSnapshot,
+    BlockSnapshotBuilder, ContentType, ContextCloseFilter, ContextListQuery, DriftKind,
+    ErrorCategory, ErrorPayload, ErrorSeverity, ErrorSpan, KernelListQuery, MentionTarget,
+    PrincipalId, Role, ShellCommand, Status, Tick, ToolKind, TrackId,
 };
+use kaijutsu_types::rpc_compress::{CompressedStream, RpcCompression};
 use russh::ChannelStream;
 use russh::client::Msg;
 use tokio_util::compat::TokioAsyncReadCompatExt;
@@ -50,6 +52,7 @@ pub struct RpcClient {
     /// Without this, no SSH_MSG_DISCONNECT can be sent on shutdown.
     #[allow(dead_code)]
     ssh_session: Option<std::rc::Rc<std::cell::RefCell<crate::ssh::SshClient>>>,
+    trace: TraceScope,
 }
 
 impl RpcClient {
@@ -57,8 +60,22 @@ impl RpcClient {
     ///
     /// MUST be called within a `tokio::task::LocalSet::run_until()` context.
     pub async fn new(channel_stream: ChannelStream<Msg>) -> Result<Self, RpcError> {
+        Self::with_compression(channel_stream, RpcCompression::None).await
+    }
+
+    /// Initialize RPC over an SSH channel stream with the framing negotiated
+    /// by [`SshClient::connect_rpc`](crate::SshClient::connect_rpc).
+    ///
+    /// MUST be called within a `tokio::task::LocalSet::run_until()` context.
+    pub async fn with_compression(
+        channel_stream: ChannelStream<Msg>,
+        compression: RpcCompression,
+    ) -> Result<Self, RpcError> {
         let compat_stream = TokioAsyncReadCompatExt::compat(channel_stream);
-        Self::from_stream(compat_stream).await
+        match compression {
+            RpcCompression::None => Self::from_stream(compat_stream).await,
+            RpcCompression::Zstd => Self::from_stream(CompressedStream::new(compat_stream)).await,
+        }
     }
 
     /// Initialize RPC from any AsyncRead+AsyncWrite stream
@@ -89,6 +106,7 @@ impl RpcClient {
             world,
             _rpc_guard: rpc_guard,
             ssh_session: None,
+            trace: TraceScope::default(),
         })
     }
 
@@ -97,14 +115,15 @@ impl RpcClient {
         self.ssh_session = Some(std::rc::Rc::new(std::cell::RefCell::new(ssh)));
     }
 
-    /// Get current identity from the server
+    /// Get current identity from the server. The principal also becomes
+    /// the identity baggage of every binding on this connection.
     #[tracing::instrument(skip(self), name = "rpc_client.whoami")]
     pub async fn whoami(&self) -> Result<Identity, RpcError> {
         let request = self.world.whoami_request();
         let response = request.send().promise.await?;
         let identity = response.get()?.get_identity()?;
 
-        Ok(Identity {
+        let identity = Identity {
             username: identity.get_username()?.to_string()?,
             display_name: identity.get_display_name()?.to_string()?,
             // The server always stamps principalId now; an empty/invalid one is
@@ -112,7 +131,9 @@ impl RpcClient {
             principal_id: PrincipalId::try_from_slice(identity.get_principal_id()?).ok_or_else(
                 || RpcError::ServerError("whoami: missing/invalid principalId".to_string()),
             )?,
-        })
+        };
+        self.trace.principal.set(Some(identity.principal_id));
+        Ok(identity)
     }
 
     /// List available kernels
@@ -129,6 +150,35 @@ impl RpcClient {
         Ok(result)
     }
 
+    /// List kernels matching `query`, one page at a time.
+    #[tracing::instrument(skip(self, query), name = "rpc_client.list_kernels_query")]
+    pub async fn list_kernels_query(
+        &self,
+        query: &KernelListQuery,
+    ) -> Result<KernelPage, RpcError> {
+        let mut request = self.world.list_kernels_request();
+        {
+            let mut q = request.get().init_query();
+            if let Some(prefix) = &query.name_prefix {
+                q.set_name_prefix(prefix);
+            }
+            q.set_offset(query.offset);
+            q.set_limit(query.limit.unwrap_or(0));
+        }
+        let response = request.send().promise.await?;
+        let reader = response.get()?;
+        let kernels = reader.get_kernels()?;
+
+        let mut result = Vec::with_capacity(kernels.len() as usize);
+        for kernel in kernels.iter() {
+            result.push(parse_kernel_info(&kernel)?);
+        }
+        Ok(KernelPage {
+            kernels: result,
+            total: reader.get_total(),
+        })
+    }
+
     /// Bind to the server's kernel — handshake getter that returns the
     /// shared kernel capability and its server-assigned ID. Despite the
     /// historical name `attachKernel`, no per-client state is attached;
@@ -137,18 +187,61 @@ impl RpcClient {
     #[tracing::instrument(skip(self), name = "rpc_client.bind_kernel")]
     pub async fn bind_kernel(&self) -> Result<(KernelHandle, KernelId), RpcError> {
         let mut request = self.world.bind_kernel_request();
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let reader = response.get()?;
+        let kernel = reader.get_kernel()?;
+        let kernel_id = parse_kernel_id(reader.get_kernel_id()?)?;
+
+        Ok((
+            KernelHandle {
+                kernel,
+                trace: self.trace.bound(kernel_id),
+            },
+            kernel_id,
+        ))
+    }
+
+    /// Bind the kernel under a session of its own (see `World.bindSeat`).
+    /// The handle joins and subscribes independently of every other binding
+    /// on this connection; dropping it releases the session.
+    #[tracing::instrument(skip(self), name = "rpc_client.bind_seat")]
+    pub async fn bind_seat(&self) -> Result<(KernelHandle, KernelId), RpcError> {
+        let mut request = self.world.bind_seat_request();
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let reader = response.get()?;
         let kernel = reader.get_kernel()?;
         let kernel_id = parse_kernel_id(reader.get_kernel_id()?)?;
 
-        Ok((KernelHandle { kernel }, kernel_id))
+        Ok((
+            KernelHandle {
+                kernel,
+                trace: self.trace.bound(kernel_id),
+            },
+            kernel_id,
+        ))
+    }
+
+    /// Read a context share by token: the share and its frozen blocks. Needs
+    /// no kernel binding, so it is what an observer connection (SSH user
+    /// `observer`) is for.
+    #[tracing::instrument(skip(self, token), name = "rpc_client.open_share")]
+    pub async fn open_share(
+        &self,
+        token: &str,
+    ) -> Result<(kaijutsu_types::ContextShare, Vec<BlockSnapshot>), RpcError> {
+        let mut request = self.world.open_share_request();
+        request.get().set_token(token);
+        let response = request.send().promise.await?;
+        let reader = response.get()?;
+        let share = parse_context_share(&reader.get_share()?)?;
+        let blocks = reader
+            .get_blocks()?
+            .iter()
+            .map(|b| parse_block_snapshot(&b))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((share, blocks))
     }
 }
 
@@ -175,6 +268,22 @@ pub struct KernelInfo {
     pub contexts: Vec<ContextInfo>,
 }
 
+/// One page of a filtered `listKernels`.
+#[derive(Debug, Clone)]
+pub struct KernelPage {
+    pub kernels: Vec<KernelInfo>,
+    /// Matches before pagination.
+    pub total: u32,
+}
+
+/// One page of a filtered `listContexts`.
+#[derive(Debug, Clone)]
+pub struct ContextPage {
+    pub contexts: Vec<ContextInfo>,
+    /// Matches before pagination.
+    pub total: u32,
+}
+
 // ============================================================================
 // Context Membership
 // ============================================================================
@@ -275,6 +384,36 @@ pub struct SimilarContext {
     pub label: String,
 }
 
+/// Filters for `search_blocks`. `None` / 0 fields don't filter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockSearchQuery {
+    /// Bare words (all required), `"quoted phrases"`, `prefix*`, `OR`, `NOT`.
+    pub text: String,
+    pub context_id: Option<ContextId>,
+    pub kind: Option<BlockKind>,
+    pub role: Option<Role>,
+    pub author: Option<PrincipalId>,
+    /// Created at or after (Unix millis).
+    pub since: Option<u64>,
+    /// Created before (Unix millis).
+    pub until: Option<u64>,
+    /// 0 = the server's default.
+    pub limit: u32,
+}
+
+/// A ranked block match from `search_blocks`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockSearchHit {
+    pub block_id: BlockId,
+    pub kind: BlockKind,
+    pub role: Role,
+    pub created_at: u64,
+    /// BM25 relevance, higher is better.
+    pub score: f64,
+    /// The matching passage, terms bracketed `[like this]`.
+    pub snippet: String,
+}
+
 /// A semantic cluster of contexts (`get_clusters`).
 #[derive(Debug, Clone, PartialEq)]
 pub struct ContextCluster {
@@ -294,6 +433,62 @@ pub struct PresetInfo {
     pub model: String,
 }
 
+/// One completed server backup run (`backupNow`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupReport {
+    /// Export target, e.g. `/srv/backups` or `s3://bucket/kaijutsu`.
+    pub location: String,
+    /// Run directory under the target (its UTC stamp).
+    pub run: String,
+    /// Keys written, relative to the target.
+    pub files: Vec<String>,
+    pub bytes: u64,
+    /// Older runs removed by retention.
+    pub pruned: Vec<String>,
+}
+
+/// Unreachable state found by one server garbage scan (`scanGarbage`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GarbageReport {
+    /// Conversation documents no context owns.
+    pub orphan_documents: Vec<ContextId>,
+    /// Contexts the server still routes to with no context row behind them.
+    pub ghost_contexts: Vec<ContextId>,
+    /// `(session, context)`: sessions whose current context was deleted.
+    pub stale_sessions: Vec<(kaijutsu_types::SessionId, ContextId)>,
+    /// `(client_id, context)`: last-viewed contexts that were deleted.
+    pub stale_client_views: Vec<(String, ContextId)>,
+    /// Everything listed was removed.
+    pub pruned: bool,
+}
+
+/// A block's text in another language (`translateBlock`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockTranslation {
+    pub text: String,
+    /// The normalized target tag.
+    pub language: String,
+    /// Served from the server's translation cache.
+    pub cached: bool,
+}
+
+/// A context's next prompt as the model would receive it (`contextPreview`).
+#[derive(Debug, Clone)]
+pub struct ContextPreview {
+    pub provider: String,
+    pub model: String,
+    /// Effective assembly spec, e.g. `checkpoint+window:16`.
+    pub strategy: String,
+    pub system_prompt: String,
+    /// Tool definitions: `[{name, description, input_schema}]`.
+    pub tools: serde_json::Value,
+    /// Wire messages, oldest first.
+    pub messages: serde_json::Value,
+    pub params: kaijutsu_types::LlmParams,
+    pub total_blocks: u64,
+    pub kept_blocks: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct KernelConfig {
     pub name: String,
@@ -319,6 +514,7 @@ pub struct MountSpec {
 #[derive(Clone)]
 pub struct KernelHandle {
     kernel: crate::kaijutsu_capnp::kernel::Client,
+    trace: TraceScope,
 }
 
 impl KernelHandle {
@@ -326,12 +522,7 @@ impl KernelHandle {
     #[tracing::instrument(skip(self), name = "rpc_client.get_info")]
     pub async fn get_info(&self) -> Result<KernelInfo, RpcError> {
         let mut request = self.kernel.get_info_request();
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let info = response.get()?.get_info()?;
         parse_kernel_info(&info)
@@ -347,12 +538,7 @@ impl KernelHandle {
     #[tracing::instrument(skip(self), name = "rpc_client.ping")]
     pub async fn ping(&self) -> Result<(KernelId, u64), RpcError> {
         let mut request = self.kernel.ping_request();
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let reader = response.get()?;
         let kernel_id = parse_kernel_id(reader.get_kernel_id()?)?;
@@ -368,12 +554,7 @@ impl KernelHandle {
     #[tracing::instrument(skip(self), name = "rpc_client.list_contexts")]
     pub async fn list_contexts(&self) -> Result<Vec<ContextInfo>, RpcError> {
         let mut request = self.kernel.list_contexts_request();
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let contexts = response.get()?.get_contexts()?;
 
@@ -384,17 +565,46 @@ impl KernelHandle {
         Ok(result)
     }
 
+    /// List contexts matching `query`, one page at a time.
+    #[tracing::instrument(skip(self, query), name = "rpc_client.list_contexts_query")]
+    pub async fn list_contexts_query(
+        &self,
+        query: &ContextListQuery,
+    ) -> Result<ContextPage, RpcError> {
+        let mut request = self.kernel.list_contexts_request();
+        self.trace.inject(request.get().init_trace());
+        {
+            let mut q = request.get().init_query();
+            if let Some(prefix) = &query.label_prefix {
+                q.set_label_prefix(prefix);
+            }
+            q.set_active_since(query.active_since.unwrap_or(0));
+            if let Some(owner) = query.owner {
+                q.set_owner(owner.as_bytes());
+            }
+            q.set_offset(query.offset);
+            q.set_limit(query.limit.unwrap_or(0));
+        }
+        let response = request.send().promise.await?;
+        let reader = response.get()?;
+        let contexts = reader.get_contexts()?;
+
+        let mut result = Vec::with_capacity(contexts.len() as usize);
+        for ctx in contexts.iter() {
+            result.push(parse_context_info(&ctx)?);
+        }
+        Ok(ContextPage {
+            contexts: result,
+            total: reader.get_total(),
+        })
+    }
+
     /// List every track's live state (docs/tracks.md). Empty when no tracks
     /// exist or the kernel runs without a beat scheduler.
     #[tracing::instrument(skip(self), name = "rpc_client.list_tracks")]
     pub async fn list_tracks(&self) -> Result<Vec<TrackInfo>, RpcError> {
         let mut request = self.kernel.list_tracks_request();
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let tracks = response.get()?.get_tracks()?;
 
@@ -418,12 +628,7 @@ impl KernelHandle {
         let mut request = self.kernel.search_similar_request();
         request.get().set_query(query);
         request.get().set_k(k);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let results = response.get()?.get_results()?;
         let mut out = Vec::with_capacity(results.len() as usize);
@@ -433,6 +638,53 @@ impl KernelHandle {
         Ok(out)
     }
 
+    /// Ranked full-text search over blocks in every context (or one, via
+    /// `query.context_id`). Best match first; errors when the server has no
+    /// search index.
+    #[tracing::instrument(skip(self, query), name = "rpc_client.search_blocks")]
+    pub async fn search_blocks(
+        &self,
+        query: &BlockSearchQuery,
+    ) -> Result<Vec<BlockSearchHit>, RpcError> {
+        let mut request = self.kernel.search_blocks_request();
+        {
+            let mut q = request.get().init_query();
+            q.set_text(&query.text);
+            if let Some(ctx) = query.context_id {
+                q.set_context_id(ctx.as_bytes());
+            }
+            if let Some(kind) = query.kind {
+                q.set_has_kind(true);
+                q.set_kind(block_kind_to_capnp(kind));
+            }
+            if let Some(role) = query.role {
+                q.set_has_role(true);
+                q.set_role(role_to_capnp(role));
+            }
+            if let Some(author) = query.author {
+                q.set_author(author.as_bytes());
+            }
+            q.set_since(query.since.unwrap_or(0));
+            q.set_until(query.until.unwrap_or(0));
+            q.set_limit(query.limit);
+        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let hits = response.get()?.get_hits()?;
+        let mut out = Vec::with_capacity(hits.len() as usize);
+        for h in hits.iter() {
+            out.push(BlockSearchHit {
+                block_id: parse_block_id(&h.get_block_id()?)?,
+                kind: block_kind_from_capnp(h.get_kind()?),
+                role: role_from_capnp(h.get_role()?),
+                created_at: h.get_created_at(),
+                score: h.get_score(),
+                snippet: h.get_snippet()?.to_string()?,
+            });
+        }
+        Ok(out)
+    }
+
     /// Find contexts semantically similar to a given context.
     ///
     /// Returns up to `k` neighbors ranked by cosine similarity. Empty when the
@@ -446,12 +698,7 @@ impl KernelHandle {
         let mut request = self.kernel.get_neighbors_request();
         request.get().set_context_id(context_id.as_bytes());
         request.get().set_k(k);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let results = response.get()?.get_results()?;
         let mut out = Vec::with_capacity(results.len() as usize);
@@ -473,170 +720,1043 @@ impl KernelHandle {
     ) -> Result<Vec<ContextCluster>, RpcError> {
         let mut request = self.kernel.get_clusters_request();
         request.get().set_min_cluster_size(min_cluster_size);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let clusters = response.get()?.get_clusters()?;
+        let mut out = Vec::with_capacity(clusters.len() as usize);
+        for c in clusters.iter() {
+            out.push(parse_context_cluster(&c)?);
+        }
+        Ok(out)
+    }
+
+    /// Create a new context with an optional label.
+    ///
+    /// Returns the server-assigned ContextId. The context is born with the
+    /// `"default"` context_type — use [`create_context_typed`] to pick a mode
+    /// (e.g. `"coder"`, `"mcp"`) whose rc lifecycle then runs on creation.
+    #[tracing::instrument(skip(self), name = "rpc_client.create_context")]
+    pub async fn create_context(&self, label: &str) -> Result<ContextId, RpcError> {
+        self.create_context_typed(label, "").await
+    }
+
+    /// Create a new context with a label and an explicit `context_type`.
+    ///
+    /// The type selects which `/etc/rc/<context_type>/create/*` lifecycle
+    /// scripts run server-side. An empty `context_type` is treated as
+    /// `"default"` by the server.
+    #[tracing::instrument(skip(self), name = "rpc_client.create_context_typed")]
+    pub async fn create_context_typed(
+        &self,
+        label: &str,
+        context_type: &str,
+    ) -> Result<ContextId, RpcError> {
+        let mut request = self.kernel.create_context_request();
+        request.get().set_label(label);
+        request.get().set_context_type(context_type);
+        let response = request.send().promise.await?;
+        parse_context_id(response.get()?.get_id()?)
+    }
+
+    /// Hold a context name for this principal. Names are unique per kernel
+    /// (case- and separator-insensitively); a taken one comes back without a
+    /// token, carrying suggestions. Commit with
+    /// [`commit_context_name`](Self::commit_context_name) or give it back
+    /// with [`release_context_name`](Self::release_context_name).
+    #[tracing::instrument(skip(self), name = "rpc_client.reserve_context_name")]
+    pub async fn reserve_context_name(
+        &self,
+        label: &str,
+    ) -> Result<kaijutsu_types::ContextNameReservation, RpcError> {
+        let mut request = self.kernel.reserve_context_name_request();
+        request.get().set_label(label);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let r = response.get()?.get_reservation()?;
+        let token = r.get_token()?;
+        let token = if token.is_empty() {
+            None
+        } else {
+            Some(
+                kaijutsu_types::ReservationId::try_from_slice(token)
+                    .ok_or_else(|| RpcError::ServerError("invalid reservation token".into()))?,
+            )
+        };
+        Ok(kaijutsu_types::ContextNameReservation {
+            token,
+            label: r.get_label()?.to_string()?,
+            expires_at: r.get_expires_at(),
+            suggestions: r
+                .get_suggestions()?
+                .iter()
+                .map(|s| Ok(s?.to_string()?))
+                .collect::<Result<_, RpcError>>()?,
+        })
+    }
+
+    /// Create a context (as [`create_context_typed`](Self::create_context_typed))
+    /// under a name reserved with [`reserve_context_name`](Self::reserve_context_name).
+    #[tracing::instrument(skip(self), name = "rpc_client.commit_context_name")]
+    pub async fn commit_context_name(
+        &self,
+        token: kaijutsu_types::ReservationId,
+        context_type: &str,
+    ) -> Result<ContextId, RpcError> {
+        let mut request = self.kernel.commit_context_name_request();
+        request.get().set_token(token.as_bytes());
+        request.get().set_context_type(context_type);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_context_id(response.get()?.get_id()?)
+    }
+
+    /// Give back a reserved name. Returns whether it was still held.
+    #[tracing::instrument(skip(self), name = "rpc_client.release_context_name")]
+    pub async fn release_context_name(
+        &self,
+        token: kaijutsu_types::ReservationId,
+    ) -> Result<bool, RpcError> {
+        let mut request = self.kernel.release_context_name_request();
+        request.get().set_token(token.as_bytes());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        Ok(response.get()?.get_released())
+    }
+
+    /// Join a context by ID.
+    ///
+    /// Returns the document_id for the joined context. The `instance` param
+    /// identifies which client connected (for logging/debugging).
+    #[tracing::instrument(skip(self), name = "rpc_client.join_context")]
+    pub async fn join_context(
+        &self,
+        context_id: ContextId,
+        instance: &str,
+    ) -> Result<ContextId, RpcError> {
+        Ok(self
+            .join_context_resuming(context_id, instance, None)
+            .await?
+            .0)
+    }
+
+    /// Join a context, taking back the seat `resume_token` names if it's
+    /// still within its grace period (a fresh seat otherwise). Returns the
+    /// seat to resume next time; `None` from servers without seats.
+    #[tracing::instrument(skip(self, resume_token), name = "rpc_client.join_context_resuming")]
+    pub async fn join_context_resuming(
+        &self,
+        context_id: ContextId,
+        instance: &str,
+        resume_token: Option<&str>,
+    ) -> Result<(ContextId, Option<SeatInfo>), RpcError> {
+        let mut request = self.kernel.join_context_request();
+        request.get().set_context_id(context_id.as_bytes());
+        request.get().set_instance(instance);
+        if let Some(token) = resume_token {
+            request.get().set_resume_token(token);
+        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let results = response.get()?;
+        let joined = parse_context_id(results.get_context_id()?)?;
+        self.trace.joined(joined);
+        let seat = if results.has_seat() {
+            Some(parse_seat(&results.get_seat()?)?)
+        } else {
+            None
+        };
+        Ok((joined, seat))
+    }
+
+    // kaish execution methods
+
+    /// Execute code in the kernel
+    #[tracing::instrument(skip(self, code), name = "rpc_client.execute")]
+    pub async fn execute(&self, code: &str) -> Result<u64, RpcError> {
+        let mut request = self.kernel.execute_request();
+        request.get().set_code(code);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        Ok(response.get()?.get_exec_id())
+    }
+
+    /// Execute shell command with block output (kaish REPL mode)
+    ///
+    /// Creates ShellCommand and ShellOutput blocks in the specified cell.
+    /// Output is streamed via block events.
+    /// Returns the BlockId of the command block.
+    #[tracing::instrument(skip(self, code), name = "rpc_client.shell_execute")]
+    pub async fn shell_execute(
+        &self,
+        code: &str,
+        context_id: ContextId,
+        user_initiated: bool,
+    ) -> Result<BlockId, RpcError> {
+        let mut request = self.kernel.shell_execute_request();
+        request.get().set_code(code);
+        request.get().set_context_id(context_id.as_bytes());
+        request.get().set_user_initiated(user_initiated);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let block_id = response.get()?.get_command_block_id()?;
+        parse_block_id(&block_id)
+    }
+
+    /// Re-run a shell command block in its context, with the cwd and env
+    /// overrides it originally ran with. Returns the BlockId of the new
+    /// command block.
+    #[tracing::instrument(skip(self), name = "rpc_client.shell_rerun")]
+    pub async fn shell_rerun(
+        &self,
+        block_id: &BlockId,
+        user_initiated: bool,
+    ) -> Result<BlockId, RpcError> {
+        let mut request = self.kernel.shell_rerun_request();
+        set_block_id_builder(&mut request.get().init_block_id(), block_id);
+        request.get().set_user_initiated(user_initiated);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let block_id = response.get()?.get_command_block_id()?;
+        parse_block_id(&block_id)
+    }
+
+    /// Toggle block exclusion from conversation hydration.
+    ///
+    /// Excluded blocks are displayed but omitted from LLM context.
+    #[tracing::instrument(skip(self), name = "rpc_client.set_block_excluded")]
+    pub async fn set_block_excluded(
+        &self,
+        context_id: ContextId,
+        block_id: &BlockId,
+        excluded: bool,
+    ) -> Result<u64, RpcError> {
+        let mut request = self.kernel.set_block_excluded_request();
+        request.get().set_context_id(context_id.as_bytes());
+        set_block_id_builder(&mut request.get().init_block_id(), block_id);
+        request.get().set_excluded(excluded);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        Ok(response.get()?.get_ack_version())
+    }
+
+    /// Move a block to a new position. `after` is the block id to land
+    /// after; `None` parks the block at the document beginning. Returns
+    /// the resulting context version (ack).
+    #[tracing::instrument(skip(self), name = "rpc_client.move_block")]
+    pub async fn move_block(
+        &self,
+        context_id: ContextId,
+        block_id: &BlockId,
+        after: Option<&BlockId>,
+    ) -> Result<u64, RpcError> {
+        let mut request = self.kernel.move_block_request();
+        request.get().set_context_id(context_id.as_bytes());
+        set_block_id_builder(&mut request.get().init_block_id(), block_id);
+        match after {
+            Some(a) => {
+                request.get().set_has_after(true);
+                set_block_id_builder(&mut request.get().init_after(), a);
+            }
+            None => {
+                request.get().set_has_after(false);
+            }
+        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        Ok(response.get()?.get_ack_version())
+    }
+
+    /// Reorder a block among its siblings without reparenting. `after` must
+    /// share the block's parent; `None` lands it ahead of the first sibling.
+    /// Returns the resulting context version (ack).
+    #[tracing::instrument(skip(self), name = "rpc_client.reorder_block")]
+    pub async fn reorder_block(
+        &self,
+        context_id: ContextId,
+        block_id: &BlockId,
+        after: Option<&BlockId>,
+    ) -> Result<u64, RpcError> {
+        let mut request = self.kernel.reorder_block_request();
+        request.get().set_context_id(context_id.as_bytes());
+        set_block_id_builder(&mut request.get().init_block_id(), block_id);
+        match after {
+            Some(a) => {
+                request.get().set_has_after(true);
+                set_block_id_builder(&mut request.get().init_after(), a);
+            }
+            None => {
+                request.get().set_has_after(false);
+            }
+        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        Ok(response.get()?.get_ack_version())
+    }
+
+    /// Set a block's label (unique within the context); `None` clears it.
+    /// Returns the resulting context version (ack).
+    #[tracing::instrument(skip(self), name = "rpc_client.set_block_label")]
+    pub async fn set_block_label(
+        &self,
+        context_id: ContextId,
+        block_id: &BlockId,
+        label: Option<&str>,
+    ) -> Result<u64, RpcError> {
+        let mut request = self.kernel.set_block_label_request();
+        request.get().set_context_id(context_id.as_bytes());
+        set_block_id_builder(&mut request.get().init_block_id(), block_id);
+        request.get().set_label(label.unwrap_or(""));
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        Ok(response.get()?.get_ack_version())
+    }
+
+    /// Resolve a block reference — a label, a full key, or an unambiguous
+    /// abbreviation — across the kernel's resident documents.
+    #[tracing::instrument(skip(self), name = "rpc_client.resolve_block")]
+    pub async fn resolve_block(&self, query: &str) -> Result<BlockId, RpcError> {
+        let mut request = self.kernel.resolve_block_request();
+        request.get().set_query(query);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_block_id(&response.get()?.get_block_id()?)
+    }
+
+    /// Back up the server's kernel and auth databases now, outside the
+    /// schedule. Needs the Admin authority in the joined context.
+    #[tracing::instrument(skip(self), name = "rpc_client.backup_now")]
+    pub async fn backup_now(&self) -> Result<BackupReport, RpcError> {
+        let mut request = self.kernel.backup_now_request();
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let report = response.get()?.get_report()?;
+        let texts = |list: capnp::text_list::Reader<'_>| -> Result<Vec<String>, RpcError> {
+            list.iter().map(|t| Ok(t?.to_string()?)).collect()
+        };
+        Ok(BackupReport {
+            location: report.get_location()?.to_string()?,
+            run: report.get_run()?.to_string()?,
+            files: texts(report.get_files()?)?,
+            bytes: report.get_bytes(),
+            pruned: texts(report.get_pruned()?)?,
+        })
+    }
+
+    /// Scan the server for orphan documents, ghost contexts, and sessions or
+    /// client views naming deleted contexts; `prune` also removes them.
+    /// `context_id` must be the joined context, with the Admin authority.
+    #[tracing::instrument(skip(self), name = "rpc_client.scan_garbage")]
+    pub async fn scan_garbage(
+        &self,
+        context_id: ContextId,
+        prune: bool,
+    ) -> Result<GarbageReport, RpcError> {
+        let mut request = self.kernel.scan_garbage_request();
+        request.get().set_context_id(context_id.as_bytes());
+        request.get().set_prune(prune);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let report = response.get()?.get_report()?;
+        let ids = |list: capnp::data_list::Reader<'_>| -> Result<Vec<ContextId>, RpcError> {
+            list.iter().map(|id| parse_context_id(id?)).collect()
+        };
+        let stale_sessions = report
+            .get_stale_sessions()?
+            .iter()
+            .map(|entry| {
+                let session = kaijutsu_types::SessionId::try_from_slice(entry.get_session_id()?)
+                    .ok_or_else(|| RpcError::ServerError("invalid session id".to_string()))?;
+                Ok((session, parse_context_id(entry.get_context_id()?)?))
+            })
+            .collect::<Result<_, RpcError>>()?;
+        let stale_client_views = report
+            .get_stale_client_views()?
+            .iter()
+            .map(|entry| {
+                Ok((
+                    entry.get_client_id()?.to_string()?,
+                    parse_context_id(entry.get_context_id()?)?,
+                ))
+            })
+            .collect::<Result<_, RpcError>>()?;
+        Ok(GarbageReport {
+            orphan_documents: ids(report.get_orphan_documents()?)?,
+            ghost_contexts: ids(report.get_ghost_contexts()?)?,
+            stale_sessions,
+            stale_client_views,
+            pruned: report.get_pruned(),
+        })
+    }
+
+    /// Set (or, with `None`, clear) the language this seat prefers to read
+    /// in. Returns the normalized tag.
+    #[tracing::instrument(skip(self), name = "rpc_client.set_seat_language")]
+    pub async fn set_seat_language(
+        &self,
+        language: Option<&str>,
+    ) -> Result<Option<String>, RpcError> {
+        let mut request = self.kernel.set_seat_language_request();
+        request.get().set_language(language.unwrap_or_default());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let language = response.get()?.get_language()?.to_string()?;
+        Ok(Some(language).filter(|l| !l.is_empty()))
+    }
+
+    /// A block's text translated into `language`, or into the seat's
+    /// preferred language when `None`. The block itself is not changed.
+    #[tracing::instrument(skip(self), name = "rpc_client.translate_block")]
+    pub async fn translate_block(
+        &self,
+        block_id: &BlockId,
+        language: Option<&str>,
+    ) -> Result<BlockTranslation, RpcError> {
+        let mut request = self.kernel.translate_block_request();
+        set_block_id_builder(&mut request.get().init_block_id(), block_id);
+        request.get().set_language(language.unwrap_or_default());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let r = response.get()?;
+        Ok(BlockTranslation {
+            text: r.get_text()?.to_string()?,
+            language: r.get_language()?.to_string()?,
+            cached: r.get_cached(),
+        })
+    }
+
+    /// Mark a block as being edited by this connection's principal for
+    /// `ttl_secs` (`None`: the server default), or renew its lock. Fails
+    /// while another principal holds the block.
+    #[tracing::instrument(skip(self), name = "rpc_client.lock_block")]
+    pub async fn lock_block(
+        &self,
+        block_id: &BlockId,
+        ttl_secs: Option<u64>,
+        note: &str,
+    ) -> Result<kaijutsu_types::BlockLock, RpcError> {
+        let mut request = self.kernel.lock_block_request();
+        set_block_id_builder(&mut request.get().init_block_id(), block_id);
+        request.get().set_ttl_secs(ttl_secs.unwrap_or(0));
+        request.get().set_note(note);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_block_lock(&response.get()?.get_lock()?)
+    }
+
+    /// Release this principal's lock on a block; `false` when it held none.
+    #[tracing::instrument(skip(self), name = "rpc_client.unlock_block")]
+    pub async fn unlock_block(&self, block_id: &BlockId) -> Result<bool, RpcError> {
+        let mut request = self.kernel.unlock_block_request();
+        set_block_id_builder(&mut request.get().init_block_id(), block_id);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        Ok(response.get()?.get_released())
+    }
+
+    /// A context's live block locks and its lock policy.
+    #[tracing::instrument(skip(self), name = "rpc_client.list_block_locks")]
+    pub async fn list_block_locks(
+        &self,
+        context_id: ContextId,
+    ) -> Result<(Vec<kaijutsu_types::BlockLock>, kaijutsu_types::LockPolicy), RpcError> {
+        let mut request = self.kernel.list_block_locks_request();
+        request.get().set_context_id(context_id.as_bytes());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let r = response.get()?;
+        let locks = r
+            .get_locks()?
+            .iter()
+            .map(|lock| parse_block_lock(&lock))
+            .collect::<Result<_, _>>()?;
+        let policy = r
+            .get_policy()?
+            .to_str()?
+            .parse()
+            .map_err(RpcError::ServerError)?;
+        Ok((locks, policy))
+    }
+
+    /// Set a context's lock policy.
+    #[tracing::instrument(skip(self), name = "rpc_client.set_lock_policy")]
+    pub async fn set_lock_policy(
+        &self,
+        context_id: ContextId,
+        policy: kaijutsu_types::LockPolicy,
+    ) -> Result<(), RpcError> {
+        let mut request = self.kernel.set_lock_policy_request();
+        request.get().set_context_id(context_id.as_bytes());
+        request.get().set_policy(policy.as_str());
+        self.trace.inject(request.get().init_trace());
+        request.send().promise.await?;
+        Ok(())
+    }
+
+    /// Bookmark a document's current state under `name`. Fails when the
+    /// document already has a bookmark by that name.
+    #[tracing::instrument(skip(self), name = "rpc_client.create_bookmark")]
+    pub async fn create_bookmark(
+        &self,
+        context_id: ContextId,
+        name: &str,
+    ) -> Result<kaijutsu_types::DocBookmark, RpcError> {
+        let mut request = self.kernel.create_bookmark_request();
+        request.get().set_context_id(context_id.as_bytes());
+        request.get().set_name(name);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_doc_bookmark(&response.get()?.get_bookmark()?)
+    }
+
+    /// A document's bookmarks, oldest first.
+    #[tracing::instrument(skip(self), name = "rpc_client.list_bookmarks")]
+    pub async fn list_bookmarks(
+        &self,
+        context_id: ContextId,
+    ) -> Result<Vec<kaijutsu_types::DocBookmark>, RpcError> {
+        let mut request = self.kernel.list_bookmarks_request();
+        request.get().set_context_id(context_id.as_bytes());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        response
+            .get()?
+            .get_bookmarks()?
+            .iter()
+            .map(|b| parse_doc_bookmark(&b))
+            .collect()
+    }
+
+    /// Tool calls waiting on this connection's principal to approve them,
+    /// oldest first.
+    #[tracing::instrument(skip(self), name = "rpc_client.list_consent_prompts")]
+    pub async fn list_consent_prompts(
+        &self,
+    ) -> Result<Vec<kaijutsu_types::ConsentPrompt>, RpcError> {
+        let mut request = self.kernel.list_consent_prompts_request();
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        response
+            .get()?
+            .get_prompts()?
+            .iter()
+            .map(|p| parse_consent_prompt(&p))
+            .collect()
+    }
+
+    /// Answer a consent prompt. Returns the prompt answered.
+    #[tracing::instrument(skip(self), name = "rpc_client.consent_respond")]
+    pub async fn consent_respond(
+        &self,
+        prompt_id: u64,
+        decision: kaijutsu_types::ConsentDecision,
+    ) -> Result<kaijutsu_types::ConsentPrompt, RpcError> {
+        let mut request = self.kernel.consent_respond_request();
+        request.get().set_prompt_id(prompt_id);
+        request.get().set_decision(decision.as_str());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_consent_prompt(&response.get()?.get_prompt()?)
+    }
+
+    /// A context's events in `buckets` buckets (0 = the server default) over
+    /// the last `window_secs` seconds, or its whole life when 0.
+    #[tracing::instrument(skip(self), name = "rpc_client.get_context_timeline")]
+    pub async fn get_context_timeline(
+        &self,
+        context_id: ContextId,
+        window_secs: u64,
+        buckets: u32,
+    ) -> Result<kaijutsu_types::ContextTimeline, RpcError> {
+        let mut request = self.kernel.get_context_timeline_request();
+        request.get().set_context_id(context_id.as_bytes());
+        request.get().set_window_secs(window_secs);
+        request.get().set_buckets(buckets);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_context_timeline(&response.get()?.get_timeline()?)
+    }
+
+    /// A context's stored generation parameters; all unset when it has none.
+    #[tracing::instrument(skip(self), name = "rpc_client.get_llm_params")]
+    pub async fn get_llm_params(
+        &self,
+        context_id: ContextId,
+    ) -> Result<kaijutsu_types::LlmParams, RpcError> {
+        let mut request = self.kernel.get_llm_params_request();
+        request.get().set_context_id(context_id.as_bytes());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_llm_params(response.get()?.get_params()?)
+    }
+
+    /// Replace a context's generation parameters, or clear them with
+    /// `None`. With `merge`, the fields `params` sets are laid over what is
+    /// stored server-side instead. Returns what is now stored.
+    #[tracing::instrument(skip(self), name = "rpc_client.set_llm_params")]
+    pub async fn set_llm_params(
+        &self,
+        context_id: ContextId,
+        params: Option<&kaijutsu_types::LlmParams>,
+        merge: bool,
+    ) -> Result<kaijutsu_types::LlmParams, RpcError> {
+        let mut request = self.kernel.set_llm_params_request();
+        request.get().set_context_id(context_id.as_bytes());
+        request.get().set_merge(merge);
+        match params {
+            Some(params) => build_llm_params(request.get().init_params(), params),
+            None => request.get().set_clear(true),
+        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_llm_params(response.get()?.get_params()?)
+    }
+
+    /// Assemble what a turn in `context_id` would send the model without
+    /// sending it. `strategy` tries another assembly spec for this preview
+    /// only; `None` uses the context's.
+    #[tracing::instrument(skip(self), name = "rpc_client.context_preview")]
+    pub async fn context_preview(
+        &self,
+        context_id: ContextId,
+        strategy: Option<&str>,
+    ) -> Result<ContextPreview, RpcError> {
+        let mut request = self.kernel.context_preview_request();
+        request.get().set_context_id(context_id.as_bytes());
+        request.get().set_strategy(strategy.unwrap_or(""));
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let preview = response.get()?.get_preview()?;
+        let json = |text: &str| {
+            serde_json::from_str(text)
+                .map_err(|e| RpcError::ServerError(format!("contextPreview: bad JSON: {e}")))
+        };
+        Ok(ContextPreview {
+            provider: preview.get_provider()?.to_string()?,
+            model: preview.get_model()?.to_string()?,
+            strategy: preview.get_strategy()?.to_string()?,
+            system_prompt: preview.get_system_prompt()?.to_string()?,
+            tools: json(preview.get_tools_json()?.to_str()?)?,
+            messages: json(preview.get_messages_json()?.to_str()?)?,
+            params: parse_llm_params(preview.get_params()?)?,
+            total_blocks: preview.get_total_blocks(),
+            kept_blocks: preview.get_kept_blocks(),
+        })
+    }
+
+    /// Recover a wedged context: hard-interrupt its LLM stream and mark
+    /// the blocks left `Running` as `Error`.
+    #[tracing::instrument(skip(self), name = "rpc_client.context_recover")]
+    pub async fn context_recover(
+        &self,
+        context_id: ContextId,
+    ) -> Result<kaijutsu_types::ContextRecovery, RpcError> {
+        let mut request = self.kernel.context_recover_request();
+        request.get().set_context_id(context_id.as_bytes());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let r = response.get()?;
+        let marked = r
+            .get_marked()?
+            .iter()
+            .map(|id| parse_block_id(&id))
+            .collect::<Result<_, _>>()?;
+        Ok(kaijutsu_types::ContextRecovery {
+            interrupted: r.get_interrupted(),
+            marked,
+        })
+    }
+
+    /// Manage `subscriber`'s digest subscriptions. With a `source`,
+    /// subscribe to it (or unsubscribe with `remove`); without one, only
+    /// list. Returns the subscriber's subscriptions afterwards.
+    #[tracing::instrument(skip(self, focus), name = "rpc_client.digest_subscribe")]
+    pub async fn digest_subscribe(
+        &self,
+        subscriber: ContextId,
+        source: Option<ContextId>,
+        every_secs: Option<u32>,
+        on_milestone: bool,
+        focus: Option<&str>,
+        remove: bool,
+    ) -> Result<Vec<kaijutsu_types::DigestSubscription>, RpcError> {
+        let mut request = self.kernel.digest_subscribe_request();
+        {
+            let mut params = request.get();
+            params.set_subscriber(subscriber.as_bytes());
+            params.set_source(source.as_ref().map_or(&[][..], |s| s.as_bytes()));
+            params.set_every_secs(every_secs.unwrap_or(0));
+            params.set_on_milestone(on_milestone);
+            params.set_focus(focus.unwrap_or(""));
+            params.set_remove(remove);
+            self.trace.inject(params.init_trace());
+        }
+        let response = request.send().promise.await?;
+        response
+            .get()?
+            .get_subscriptions()?
+            .iter()
+            .map(|s| parse_digest_subscription(&s))
+            .collect()
+    }
+
+    /// Send one prompt to several contexts at once, each on its target's
+    /// model. Returns immediately with every target pending; poll
+    /// [`get_broadcast`](Self::get_broadcast) for progress.
+    #[tracing::instrument(skip(self, content), name = "rpc_client.broadcast_prompt")]
+    pub async fn broadcast_prompt(
+        &self,
+        content: &str,
+        targets: &[kaijutsu_types::BroadcastTarget],
+        label: Option<&str>,
+    ) -> Result<kaijutsu_types::BroadcastStatus, RpcError> {
+        let mut request = self.kernel.broadcast_prompt_request();
+        {
+            let mut params = request.get();
+            params.set_content(content);
+            params.set_label(label.unwrap_or(""));
+            let mut list = params.reborrow().init_targets(targets.len() as u32);
+            for (i, target) in targets.iter().enumerate() {
+                let mut t = list.reborrow().get(i as u32);
+                t.set_context_id(target.context_id.as_bytes());
+                t.set_model(target.model.as_deref().unwrap_or(""));
+            }
+        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_broadcast_status(response.get()?.get_status()?)
+    }
+
+    /// Progress of a broadcast started with
+    /// [`broadcast_prompt`](Self::broadcast_prompt).
+    #[tracing::instrument(skip(self), name = "rpc_client.get_broadcast")]
+    pub async fn get_broadcast(
+        &self,
+        id: u64,
+    ) -> Result<kaijutsu_types::BroadcastStatus, RpcError> {
+        let mut request = self.kernel.get_broadcast_request();
+        request.get().set_id(id);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_broadcast_status(response.get()?.get_status()?)
+    }
+
+    /// Attach a downstream MCP server to `context_id` only.
+    ///
+    /// Returns the registered server with the tools it advertised on connect.
+    #[tracing::instrument(skip(self, spec), name = "rpc_client.register_mcp_server")]
+    pub async fn register_mcp_server(
+        &self,
+        context_id: ContextId,
+        spec: &McpServerSpec,
+    ) -> Result<ContextMcpServerInfo, RpcError> {
+        let mut request = self.kernel.register_mcp_server_request();
+        request.get().set_context_id(context_id.as_bytes());
+        {
+            let mut s = request.get().init_spec();
+            s.set_instance(&spec.instance);
+            s.set_transport(&spec.transport);
+            s.set_command(&spec.command);
+            let mut args = s.reborrow().init_args(spec.args.len() as u32);
+            for (i, arg) in spec.args.iter().enumerate() {
+                args.set(i as u32, arg);
+            }
+            let mut env = s.reborrow().init_env(spec.env.len() as u32);
+            for (i, (k, v)) in spec.env.iter().enumerate() {
+                env.set(i as u32, format!("{k}={v}"));
+            }
+            s.set_cwd(spec.cwd.as_deref().unwrap_or(""));
+            s.set_url(spec.url.as_deref().unwrap_or(""));
+            s.set_fork_mode(&spec.fork_mode);
+        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_context_mcp_server(&response.get()?.get_server()?)
+    }
+
+    /// Detach a context-scoped MCP server. Returns `true` if that was its
+    /// last context and the server process was stopped.
+    #[tracing::instrument(skip(self), name = "rpc_client.unregister_mcp_server")]
+    pub async fn unregister_mcp_server(
+        &self,
+        context_id: ContextId,
+        instance: &str,
+    ) -> Result<bool, RpcError> {
+        let mut request = self.kernel.unregister_mcp_server_request();
+        request.get().set_context_id(context_id.as_bytes());
+        request.get().set_instance(instance);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        Ok(response.get()?.get_stopped())
+    }
+
+    /// List the MCP servers scoped to `context_id`, health-checking each.
+    #[tracing::instrument(skip(self), name = "rpc_client.list_context_mcp_servers")]
+    pub async fn list_context_mcp_servers(
+        &self,
+        context_id: ContextId,
+    ) -> Result<Vec<ContextMcpServerInfo>, RpcError> {
+        let mut request = self.kernel.list_context_mcp_servers_request();
+        request.get().set_context_id(context_id.as_bytes());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let servers = response.get()?.get_servers()?;
+        let mut result = Vec::with_capacity(servers.len() as usize);
+        for s in servers.iter() {
+            result.push(parse_context_mcp_server(&s)?);
+        }
+        Ok(result)
+    }
+
+    /// The caller's notification inbox, newest first. `limit = 0` takes the
+    /// server default; `unacked` is always the full unread count.
+    #[tracing::instrument(skip(self), name = "rpc_client.list_inbox")]
+    pub async fn list_inbox(&self, include_acked: bool, limit: u32) -> Result<InboxPage, RpcError> {
+        let mut request = self.kernel.list_inbox_request();
+        request.get().set_include_acked(include_acked);
+        request.get().set_limit(limit);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let r = response.get()?;
+        let items = r
+            .get_items()?
+            .iter()
+            .map(|item| parse_inbox_item(&item))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(InboxPage {
+            items,
+            unacked: r.get_unacked(),
+        })
+    }
+
+    /// Acknowledge inbox items by id (empty = all unread). Returns
+    /// `(acked, still_unacked)`.
+    #[tracing::instrument(skip(self), name = "rpc_client.ack_inbox")]
+    pub async fn ack_inbox(&self, ids: &[u64]) -> Result<(u32, u64), RpcError> {
+        let mut request = self.kernel.ack_inbox_request();
         {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
+            let mut list = request.get().init_ids(ids.len() as u32);
+            for (i, id) in ids.iter().enumerate() {
+                list.set(i as u32, *id);
+            }
         }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
-        let clusters = response.get()?.get_clusters()?;
-        let mut out = Vec::with_capacity(clusters.len() as usize);
-        for c in clusters.iter() {
-            out.push(parse_context_cluster(&c)?);
-        }
-        Ok(out)
+        let r = response.get()?;
+        Ok((r.get_acked(), r.get_unacked()))
     }
 
-    /// Create a new context with an optional label.
-    ///
-    /// Returns the server-assigned ContextId. The context is born with the
-    /// `"default"` context_type — use [`create_context_typed`] to pick a mode
-    /// (e.g. `"coder"`, `"mcp"`) whose rc lifecycle then runs on creation.
-    #[tracing::instrument(skip(self), name = "rpc_client.create_context")]
-    pub async fn create_context(&self, label: &str) -> Result<ContextId, RpcError> {
-        self.create_context_typed(label, "").await
+    /// The caller's preferences (see `kaijutsu_types::prefs`).
+    #[tracing::instrument(skip(self), name = "rpc_client.get_preferences")]
+    pub async fn get_preferences(&self) -> Result<kaijutsu_types::Preferences, RpcError> {
+        let mut request = self.kernel.get_preferences_request();
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_preferences(response.get()?.get_prefs()?)
     }
 
-    /// Create a new context with a label and an explicit `context_type`.
-    ///
-    /// The type selects which `/etc/rc/<context_type>/create/*` lifecycle
-    /// scripts run server-side. An empty `context_type` is treated as
-    /// `"default"` by the server.
-    #[tracing::instrument(skip(self), name = "rpc_client.create_context_typed")]
-    pub async fn create_context_typed(
+    /// Set one of the caller's preferences, or clear it with `None`. Returns
+    /// the full set afterwards.
+    #[tracing::instrument(skip(self), name = "rpc_client.set_preference")]
+    pub async fn set_preference(
         &self,
-        label: &str,
-        context_type: &str,
-    ) -> Result<ContextId, RpcError> {
-        let mut request = self.kernel.create_context_request();
-        request.get().set_label(label);
-        request.get().set_context_type(context_type);
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<kaijutsu_types::Preferences, RpcError> {
+        let mut request = self.kernel.set_preference_request();
+        request.get().set_key(key);
+        request.get().set_value(value.unwrap_or_default());
+        request.get().set_clear(value.is_none());
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
-        parse_context_id(response.get()?.get_id()?)
+        parse_preferences(response.get()?.get_prefs()?)
     }
 
-    /// Join a context by ID.
-    ///
-    /// Returns the document_id for the joined context. The `instance` param
-    /// identifies which client connected (for logging/debugging).
-    #[tracing::instrument(skip(self), name = "rpc_client.join_context")]
-    pub async fn join_context(
+    /// Page through the kernel's consent audit log, oldest first, starting
+    /// after `after_seq`. `limit = 0` takes the server default. With
+    /// `verify`, the page also carries a check of the whole log.
+    #[tracing::instrument(skip(self), name = "rpc_client.list_consent_log")]
+    pub async fn list_consent_log(
+        &self,
+        after_seq: u64,
+        limit: u32,
+        verify: bool,
+    ) -> Result<ConsentLogPage, RpcError> {
+        let mut request = self.kernel.list_consent_log_request();
+        request.get().set_after_seq(after_seq);
+        request.get().set_limit(limit);
+        request.get().set_verify(verify);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let r = response.get()?;
+        let entries = r
+            .get_entries()?
+            .iter()
+            .map(|entry| parse_consent_entry(&entry))
+            .collect::<Result<Vec<_>, _>>()?;
+        let verification = verify.then(|| -> Result<_, RpcError> {
+            Ok(kaijutsu_types::ConsentVerification {
+                checked: r.get_checked(),
+                broken_at: match r.get_broken_at_seq() {
+                    0 => None,
+                    seq => Some((seq, r.get_broken_reason()?.to_string()?)),
+                },
+            })
+        });
+        Ok(ConsentLogPage {
+            entries,
+            total: r.get_total(),
+            verification: verification.transpose()?,
+        })
+    }
+
+    /// A context's sandbox profile for host commands, or `None` when it has
+    /// none.
+    #[tracing::instrument(skip(self), name = "rpc_client.get_sandbox_profile")]
+    pub async fn get_sandbox_profile(
         &self,
         context_id: ContextId,
-        instance: &str,
-    ) -> Result<ContextId, RpcError> {
-        let mut request = self.kernel.join_context_request();
+    ) -> Result<Option<kaijutsu_types::SandboxProfile>, RpcError> {
+        let mut request = self.kernel.get_sandbox_profile_request();
         request.get().set_context_id(context_id.as_bytes());
-        request.get().set_instance(instance);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let r = response.get()?;
+        if !r.get_set() {
+            return Ok(None);
+        }
+        parse_sandbox_profile(r.get_profile()?).map(Some)
+    }
+
+    /// Set a context's sandbox profile, or clear it with `None`. Returns the
+    /// stored profile.
+    #[tracing::instrument(skip(self), name = "rpc_client.set_sandbox_profile")]
+    pub async fn set_sandbox_profile(
+        &self,
+        context_id: ContextId,
+        profile: Option<&kaijutsu_types::SandboxProfile>,
+    ) -> Result<Option<kaijutsu_types::SandboxProfile>, RpcError> {
+        let mut request = self.kernel.set_sandbox_profile_request();
+        request.get().set_context_id(context_id.as_bytes());
+        match profile {
+            Some(profile) => build_sandbox_profile(request.get().init_profile(), profile),
+            None => request.get().set_clear(true),
         }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
-        parse_context_id(response.get()?.get_context_id()?)
+        let r = response.get()?;
+        if !r.get_set() {
+            return Ok(None);
+        }
+        parse_sandbox_profile(r.get_profile()?).map(Some)
     }
 
-    // kaish execution methods
+    /// A context's execution budget, its spending in the current window, and
+    /// the kernel-wide tool halt.
+    #[tracing::instrument(skip(self), name = "rpc_client.get_budget_status")]
+    pub async fn get_budget_status(
+        &self,
+        context_id: ContextId,
+    ) -> Result<kaijutsu_types::BudgetStatus, RpcError> {
+        let mut request = self.kernel.get_budget_status_request();
+        request.get().set_context_id(context_id.as_bytes());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_budget_status(response.get()?.get_status()?)
+    }
 
-    /// Execute code in the kernel
-    #[tracing::instrument(skip(self, code), name = "rpc_client.execute")]
-    pub async fn execute(&self, code: &str) -> Result<u64, RpcError> {
-        let mut request = self.kernel.execute_request();
-        request.get().set_code(code);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
+    /// Set a context's execution budget, or clear it with `None`. Returns the
+    /// resulting status. Needs the Admin authority in the joined context.
+    #[tracing::instrument(skip(self), name = "rpc_client.set_execution_budget")]
+    pub async fn set_execution_budget(
+        &self,
+        context_id: ContextId,
+        budget: Option<&kaijutsu_types::ExecutionBudget>,
+    ) -> Result<kaijutsu_types::BudgetStatus, RpcError> {
+        let mut request = self.kernel.set_execution_budget_request();
+        request.get().set_context_id(context_id.as_bytes());
+        match budget {
+            Some(budget) => {
+                let mut b = request.get().init_budget();
+                b.set_max_tool_calls_per_hour(budget.max_tool_calls_per_hour.unwrap_or(0));
+                b.set_max_shell_secs_per_hour(budget.max_shell_secs_per_hour.unwrap_or(0));
+            }
+            None => request.get().set_clear(true),
         }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
-        Ok(response.get()?.get_exec_id())
+        parse_budget_status(response.get()?.get_status()?)
     }
 
-    /// Execute shell command with block output (kaish REPL mode)
-    ///
-    /// Creates ShellCommand and ShellOutput blocks in the specified cell.
-    /// Output is streamed via block events.
-    /// Returns the BlockId of the command block.
-    #[tracing::instrument(skip(self, code), name = "rpc_client.shell_execute")]
-    pub async fn shell_execute(
+    /// Engage (`halted`) or release the kernel-wide tool halt. Releasing
+    /// needs the Admin authority in the joined context.
+    #[tracing::instrument(skip(self), name = "rpc_client.set_tool_halt")]
+    pub async fn set_tool_halt(
         &self,
-        code: &str,
         context_id: ContextId,
-        user_initiated: bool,
-    ) -> Result<BlockId, RpcError> {
-        let mut request = self.kernel.shell_execute_request();
-        request.get().set_code(code);
+        halted: bool,
+        reason: &str,
+    ) -> Result<kaijutsu_types::BudgetStatus, RpcError> {
+        let mut request = self.kernel.set_tool_halt_request();
         request.get().set_context_id(context_id.as_bytes());
-        request.get().set_user_initiated(user_initiated);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        request.get().set_halted(halted);
+        request.get().set_reason(reason);
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
-        let block_id = response.get()?.get_command_block_id()?;
-        parse_block_id(&block_id)
+        parse_budget_status(response.get()?.get_status()?)
     }
 
-    /// Toggle block exclusion from conversation hydration.
+    /// Follow a block's text from char `from_offset` until it reaches a
+    /// terminal status, sending each append to `tx` as it lands.
     ///
-    /// Excluded blocks are displayed but omitted from LLM context.
-    #[tracing::instrument(skip(self), name = "rpc_client.set_block_excluded")]
-    pub async fn set_block_excluded(
+    /// Cancel by dropping the future; the kernel stops with it.
+    #[tracing::instrument(skip(self, tx), name = "rpc_client.tail_block")]
+    pub async fn tail_block(
         &self,
-        context_id: ContextId,
         block_id: &BlockId,
-        excluded: bool,
-    ) -> Result<u64, RpcError> {
-        let mut request = self.kernel.set_block_excluded_request();
-        request.get().set_context_id(context_id.as_bytes());
+        from_offset: u64,
+        tx: tokio::sync::mpsc::UnboundedSender<BlockTailChunk>,
+    ) -> Result<BlockTailEnd, RpcError> {
+        let callback: crate::kaijutsu_capnp::block_tail_events::Client =
+            capnp_rpc::new_client(crate::subscriptions::BlockTailForwarder { tx });
+        let mut request = self.kernel.tail_block_request();
         set_block_id_builder(&mut request.get().init_block_id(), block_id);
-        request.get().set_excluded(excluded);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        request.get().set_from_offset(from_offset);
+        request.get().set_callback(callback);
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
-        Ok(response.get()?.get_ack_version())
+        let r = response.get()?;
+        let status = if r.get_deleted() {
+            None
+        } else {
+            Some(match r.get_status()? {
+                crate::kaijutsu_capnp::Status::Pending => Status::Pending,
+                crate::kaijutsu_capnp::Status::Running => Status::Running,
+                crate::kaijutsu_capnp::Status::Done => Status::Done,
+                crate::kaijutsu_capnp::Status::Error => Status::Error,
+            })
+        };
+        Ok(BlockTailEnd {
+            offset: r.get_offset(),
+            status,
+        })
     }
 
-    /// Move a block to a new position. `after` is the block id to land
-    /// after; `None` parks the block at the document beginning. Returns
-    /// the resulting context version (ack).
-    #[tracing::instrument(skip(self), name = "rpc_client.move_block")]
-    pub async fn move_block(
+    /// Stream one block's events to `callback`, starting with the snapshot
+    /// taken once the kernel had subscribed. The stream runs until the
+    /// returned capability is dropped or the block is deleted.
+    #[tracing::instrument(skip(self, callback), name = "rpc_client.subscribe_block")]
+    pub async fn subscribe_block(
         &self,
-        context_id: ContextId,
         block_id: &BlockId,
-        after: Option<&BlockId>,
-    ) -> Result<u64, RpcError> {
-        let mut request = self.kernel.move_block_request();
-        request.get().set_context_id(context_id.as_bytes());
+        callback: crate::kaijutsu_capnp::block_events::Client,
+    ) -> Result<(BlockSnapshot, crate::kaijutsu_capnp::peek::Client), RpcError> {
+        let mut request = self.kernel.subscribe_block_request();
         set_block_id_builder(&mut request.get().init_block_id(), block_id);
-        match after {
-            Some(a) => {
-                request.get().set_has_after(true);
-                set_block_id_builder(&mut request.get().init_after(), a);
-            }
-            None => {
-                request.get().set_has_after(false);
-            }
-        }
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        request.get().set_callback(callback);
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
-        Ok(response.get()?.get_ack_version())
+        let r = response.get()?;
+        Ok((parse_block_snapshot(&r.get_block()?)?, r.get_sub()?))
     }
 
     /// Subscribe to output events from `execute()` RPCs.
@@ -664,28 +1784,20 @@ impl KernelHandle {
     pub async fn interrupt(&self, exec_id: u64) -> Result<(), RpcError> {
         let mut request = self.kernel.interrupt_request();
         request.get().set_exec_id(exec_id);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         request.send().promise.await?;
         Ok(())
     }
 
-    /// Get completions
+    /// Completions for the token ending at byte `cursor` of `partial`, in
+    /// the joined context. Each result's `text` replaces the whole token
+    /// (see [`kaijutsu_types::token_at`]).
     #[tracing::instrument(skip(self, partial), name = "rpc_client.complete")]
     pub async fn complete(&self, partial: &str, cursor: u32) -> Result<Vec<Completion>, RpcError> {
         let mut request = self.kernel.complete_request();
         request.get().set_partial(partial);
         request.get().set_cursor(cursor);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let completions = response.get()?.get_completions()?;
 
@@ -705,12 +1817,7 @@ impl KernelHandle {
     pub async fn get_command_history(&self, limit: u32) -> Result<Vec<HistoryEntry>, RpcError> {
         let mut request = self.kernel.get_command_history_request();
         request.get().set_limit(limit);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let entries = response.get()?.get_entries()?;
 
@@ -744,19 +1851,25 @@ impl KernelHandle {
     ///
     /// Returns the acknowledged version so the client knows ops were accepted.
     /// The ops should be serialized using serde_json from SerializedOpsOwned.
+    /// A push that breaks the document's shape is refused whole with
+    /// [`RpcError::OpsRejected`].
     #[tracing::instrument(skip(self, ops), name = "rpc_client.push_ops")]
     pub async fn push_ops(&self, context_id: ContextId, ops: &[u8]) -> Result<u64, RpcError> {
         let mut request = self.kernel.push_ops_request();
         request.get().set_context_id(context_id.as_bytes());
         request.get().set_ops(ops);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
-        Ok(response.get()?.get_ack_version())
+        let r = response.get()?;
+        let violations = r
+            .get_violations()?
+            .iter()
+            .map(|v| parse_push_violation(&v))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !violations.is_empty() {
+            return Err(RpcError::OpsRejected(violations));
+        }
+        Ok(r.get_ack_version())
     }
 
     /// Get document state (blocks and CRDT oplog)
@@ -765,12 +1878,7 @@ impl KernelHandle {
     pub async fn compact_context(&self, context_id: ContextId) -> Result<(u64, u64), RpcError> {
         let mut request = self.kernel.compact_context_request();
         request.get().set_context_id(context_id.as_bytes());
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let r = response.get()?;
         Ok((r.get_new_size(), r.get_generation()))
@@ -790,12 +1898,7 @@ impl KernelHandle {
         let mut request = self.kernel.get_blocks_request();
         request.get().set_context_id(context_id.as_bytes());
         set_block_query_builder(request.get().init_query(), query);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let blocks_reader = response.get()?.get_blocks()?;
         let mut blocks = Vec::with_capacity(blocks_reader.len() as usize);
@@ -810,12 +1913,7 @@ impl KernelHandle {
     pub async fn get_context_sync(&self, context_id: ContextId) -> Result<SyncState, RpcError> {
         let mut request = self.kernel.get_context_sync_request();
         request.get().set_context_id(context_id.as_bytes());
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let r = response.get()?;
         let context_id = parse_context_id(r.get_context_id()?)?;
@@ -825,9 +1923,52 @@ impl KernelHandle {
             context_id,
             ops,
             version,
+            generation: r.get_generation(),
+            seq_num: r.get_seq_num(),
         })
     }
 
+    /// Read-only look at a context this connection hasn't joined: blocks and
+    /// sync state, no seat taken. With a `callback`, the context's document
+    /// events stream to it until the returned peek capability is dropped or
+    /// `expires_in_secs` passes (`ttl_secs` 0 = server default).
+    #[tracing::instrument(skip(self, callback), name = "rpc_client.peek_document")]
+    pub async fn peek_document(
+        &self,
+        context_id: ContextId,
+        callback: Option<crate::kaijutsu_capnp::block_events::Client>,
+        ttl_secs: u32,
+    ) -> Result<(PeekedDocument, crate::kaijutsu_capnp::peek::Client), RpcError> {
+        let mut request = self.kernel.peek_document_request();
+        {
+            let mut params = request.get();
+            params.set_context_id(context_id.as_bytes());
+            if let Some(callback) = callback {
+                params.set_callback(callback);
+            }
+            params.set_ttl_secs(ttl_secs);
+        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let r = response.get()?;
+        let state = r.get_state()?;
+        let blocks_reader = state.get_blocks()?;
+        let mut blocks = Vec::with_capacity(blocks_reader.len() as usize);
+        for block in blocks_reader.iter() {
+            blocks.push(parse_block_snapshot(&block)?);
+        }
+        let document = PeekedDocument {
+            context_id: parse_context_id(state.get_context_id()?)?,
+            blocks,
+            ops: state.get_ops().map(|d| d.to_vec()).unwrap_or_default(),
+            version: state.get_version(),
+            generation: r.get_generation(),
+            seq_num: r.get_seq_num(),
+            expires_in_secs: r.get_expires_in_secs(),
+        };
+        Ok((document, r.get_peek()?))
+    }
+
     // =========================================================================
     // LLM operations
     // =========================================================================
@@ -842,6 +1983,19 @@ impl KernelHandle {
         content: &str,
         model: Option<&str>,
         context_id: ContextId,
+    ) -> Result<String, RpcError> {
+        self.prompt_with_params(content, model, context_id, None).await
+    }
+
+    /// [`Self::prompt`] with per-request generation parameters; set fields
+    /// override the context's for this turn only.
+    #[tracing::instrument(skip(self, content), name = "rpc_client.prompt_with_params")]
+    pub async fn prompt_with_params(
+        &self,
+        content: &str,
+        model: Option<&str>,
+        context_id: ContextId,
+        params: Option<&kaijutsu_types::LlmParams>,
     ) -> Result<String, RpcError> {
         let mut request = self.kernel.prompt_request();
         {
@@ -851,13 +2005,11 @@ impl KernelHandle {
                 req.set_model(m);
             }
             req.set_context_id(context_id.as_bytes());
+            if let Some(params) = params {
+                build_llm_params(req.init_params(), params);
+            }
         }
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         Ok(response.get()?.get_prompt_id()?.to_string()?)
     }
@@ -914,12 +2066,7 @@ impl KernelHandle {
     pub async fn editor_open(&self, path: &str) -> Result<EditorState, RpcError> {
         let mut request = self.kernel.editor_open_request();
         request.get().set_path(path);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         parse_editor_state(response.get()?.get_state()?)
     }
@@ -930,12 +2077,7 @@ impl KernelHandle {
         let mut request = self.kernel.editor_keys_request();
         request.get().set_session_id(session_id);
         request.get().set_keys(keys);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         parse_editor_state(response.get()?.get_state()?)
     }
@@ -945,12 +2087,7 @@ impl KernelHandle {
     pub async fn editor_state(&self, session_id: u64) -> Result<EditorState, RpcError> {
         let mut request = self.kernel.editor_state_request();
         request.get().set_session_id(session_id);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         parse_editor_state(response.get()?.get_state()?)
     }
@@ -960,12 +2097,7 @@ impl KernelHandle {
     pub async fn editor_save(&self, session_id: u64) -> Result<EditorState, RpcError> {
         let mut request = self.kernel.editor_save_request();
         request.get().set_session_id(session_id);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         parse_editor_state(response.get()?.get_state()?)
     }
@@ -975,12 +2107,7 @@ impl KernelHandle {
     pub async fn editor_quit(&self, session_id: u64) -> Result<(), RpcError> {
         let mut request = self.kernel.editor_quit_request();
         request.get().set_session_id(session_id);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         request.send().promise.await?;
         Ok(())
     }
@@ -1015,12 +2142,7 @@ impl KernelHandle {
             call.set_tool(tool);
             call.set_params(params);
         }
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let result = response.get()?.get_result()?;
 
@@ -1035,12 +2157,7 @@ impl KernelHandle {
     #[tracing::instrument(skip(self), name = "rpc_client.get_tool_schemas")]
     pub async fn get_tool_schemas(&self) -> Result<Vec<ToolSchema>, RpcError> {
         let mut request = self.kernel.get_tool_schemas_request();
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let schemas = response.get()?.get_schemas()?;
 
@@ -1076,12 +2193,7 @@ impl KernelHandle {
                 })?,
             );
         }
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let result = response.get()?.get_result()?;
 
@@ -1103,12 +2215,7 @@ impl KernelHandle {
     pub async fn list_mcp_resources(&self, server: &str) -> Result<Vec<McpResource>, RpcError> {
         let mut request = self.kernel.list_mcp_resources_request();
         request.get().set_server(server);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let resources = response.get()?.get_resources()?;
 
@@ -1196,12 +2303,7 @@ impl KernelHandle {
             source.set_seq(block_id.seq);
             params.set_target_context_id(target_context.as_bytes());
         }
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let new_block = response.get()?.get_new_block_id()?;
         parse_block_id(&new_block)
@@ -1222,12 +2324,7 @@ impl KernelHandle {
             params.set_context_id(context_id.as_bytes());
             params.set_limit(limit);
         }
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let snapshots = response.get()?.get_snapshots()?;
 
@@ -1256,12 +2353,7 @@ impl KernelHandle {
     #[tracing::instrument(skip(self), name = "rpc_client.get_context_id")]
     pub async fn get_context_id(&self) -> Result<(ContextId, String), RpcError> {
         let mut request = self.kernel.get_context_id_request();
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let reader = response.get()?;
         let id = parse_context_id(reader.get_id()?)?;
@@ -1284,12 +2376,7 @@ impl KernelHandle {
             params.set_model(model);
             params.set_context_id(context_id.as_bytes());
         }
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let reader = response.get()?;
         if reader.get_success() {
@@ -1300,6 +2387,33 @@ impl KernelHandle {
         }
     }
 
+    /// Switch a context's model mid-conversation (`setContextModel`).
+    ///
+    /// `spec` is an alias or `provider/model`, resolved server-side against
+    /// the LLM registry. Returns the resolved `(provider, model, changed)`;
+    /// `changed` is false when the context was already on that model.
+    #[tracing::instrument(skip(self), name = "rpc_client.switch_context_model")]
+    pub async fn switch_context_model(
+        &self,
+        context_id: ContextId,
+        spec: &str,
+    ) -> Result<(String, String, bool), RpcError> {
+        let mut request = self.kernel.set_context_model_request();
+        {
+            let mut params = request.get();
+            params.set_context_id(context_id.as_bytes());
+            params.set_model(spec);
+        }
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let reader = response.get()?;
+        Ok((
+            reader.get_provider()?.to_string()?,
+            reader.get_model()?.to_string()?,
+            reader.get_changed(),
+        ))
+    }
+
     /// View the drift staging queue.
     #[tracing::instrument(skip(self), name = "rpc_client.drift_queue")]
     pub async fn drift_queue(&self) -> Result<Vec<StagedDriftInfo>, RpcError> {
@@ -1358,12 +2472,7 @@ impl KernelHandle {
     pub async fn conclude(&self, context_id: ContextId) -> Result<(), RpcError> {
         let mut request = self.kernel.conclude_request();
         request.get().set_context_id(context_id.as_bytes());
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let reader = response.get()?;
         if reader.get_success() {
@@ -1385,12 +2494,7 @@ impl KernelHandle {
     pub async fn promote_context(&self, context_id: ContextId) -> Result<(), RpcError> {
         let mut request = self.kernel.promote_context_request();
         request.get().set_context_id(context_id.as_bytes());
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let reader = response.get()?;
         if reader.get_success() {
@@ -1412,12 +2516,7 @@ impl KernelHandle {
     pub async fn demote_context(&self, context_id: ContextId) -> Result<(), RpcError> {
         let mut request = self.kernel.demote_context_request();
         request.get().set_context_id(context_id.as_bytes());
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let reader = response.get()?;
         if reader.get_success() {
@@ -1443,12 +2542,7 @@ impl KernelHandle {
         let mut request = self.kernel.set_context_paused_request();
         request.get().set_context_id(context_id.as_bytes());
         request.get().set_paused(paused);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let reader = response.get()?;
         if reader.get_success() {
@@ -1471,12 +2565,56 @@ impl KernelHandle {
     pub async fn archive_context(&self, context_id: ContextId) -> Result<(), RpcError> {
         let mut request = self.kernel.archive_context_request();
         request.get().set_context_id(context_id.as_bytes());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let reader = response.get()?;
+        if reader.get_success() {
+            Ok(())
+        } else {
+            let msg = reader
+                .get_error()?
+                .to_str()
+                .unwrap_or("archive_context failed");
+            Err(RpcError::ServerError(msg.to_string()))
+        }
+    }
+
+    /// Close (archive) every open context `filter` selects, in one write.
+    /// Returns the selected ids; with `dry_run` nothing is closed. An empty
+    /// filter selects nothing.
+    #[tracing::instrument(skip(self), name = "rpc_client.close_contexts")]
+    pub async fn close_contexts(
+        &self,
+        filter: &ContextCloseFilter,
+        dry_run: bool,
+    ) -> Result<Vec<ContextId>, RpcError> {
+        let mut request = self.kernel.close_contexts_request();
         {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
+            let mut f = request.get().init_filter();
+            if let Some(prefix) = &filter.label_prefix {
+                f.set_label_prefix(prefix);
+            }
+            if let Some(parent) = filter.forked_from {
+                f.set_forked_from(parent.as_bytes());
+            }
+            f.set_idle_before(filter.idle_before.unwrap_or(0));
+            f.set_concluded_only(filter.concluded_only);
+            f.set_include_promoted(filter.include_promoted);
         }
+        request.get().set_dry_run(dry_run);
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        let closed = response.get()?.get_closed()?;
+        closed.iter().map(|id| parse_context_id(id?)).collect()
+    }
+
+    /// Reopen a closed (archived) context into automatic placement — takes
+    /// no ring-0 seat, unlike promoting it. Idempotent on an open context.
+    #[tracing::instrument(skip(self), name = "rpc_client.reopen_context")]
+    pub async fn reopen_context(&self, context_id: ContextId) -> Result<(), RpcError> {
+        let mut request = self.kernel.reopen_context_request();
+        request.get().set_context_id(context_id.as_bytes());
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let reader = response.get()?;
         if reader.get_success() {
@@ -1485,11 +2623,26 @@ impl KernelHandle {
             let msg = reader
                 .get_error()?
                 .to_str()
-                .unwrap_or("archive_context failed");
+                .unwrap_or("reopen_context failed");
             Err(RpcError::ServerError(msg.to_string()))
         }
     }
 
+    /// Aggregated statistics for one context — block counts by kind/role,
+    /// bytes, per-principal activity, model, consent mode, drift in/out and
+    /// pending approvals — computed server-side on each call.
+    #[tracing::instrument(skip(self), name = "rpc_client.get_context_stats")]
+    pub async fn get_context_stats(
+        &self,
+        context_id: ContextId,
+    ) -> Result<kaijutsu_types::ContextStats, RpcError> {
+        let mut request = self.kernel.get_context_stats_request();
+        request.get().set_context_id(context_id.as_bytes());
+        self.trace.inject(request.get().init_trace());
+        let response = request.send().promise.await?;
+        parse_context_stats(&response.get()?.get_stats()?)
+    }
+
     // ========================================================================
     // LLM Configuration
     // ========================================================================
@@ -1498,12 +2651,7 @@ impl KernelHandle {
     #[tracing::instrument(skip(self), name = "rpc_client.get_llm_config")]
     pub async fn get_llm_config(&self) -> Result<LlmConfigInfo, RpcError> {
         let mut request = self.kernel.get_llm_config_request();
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let config = response.get()?.get_config()?;
 
@@ -1518,11 +2666,24 @@ impl KernelHandle {
             } else {
                 Vec::new()
             };
+            let health = if p.has_health() {
+                let h = p.get_health()?;
+                Some(LlmProviderHealth {
+                    status: h.get_status()?.to_string()?,
+                    model: h.get_model()?.to_string()?,
+                    latency_ms: h.get_latency_ms(),
+                    detail: h.get_detail()?.to_string()?,
+                    checked_at: h.get_checked_at(),
+                })
+            } else {
+                None
+            };
             providers.push(LlmProviderInfo {
                 name: p.get_name()?.to_string()?,
                 default_model: p.get_default_model()?.to_string()?,
                 available: p.get_available(),
                 models,
+                health,
             });
         }
 
@@ -1604,12 +2765,7 @@ impl KernelHandle {
             params.set_context_id(context_id.as_bytes());
             params.set_immediate(immediate);
         }
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         Ok(response.get()?.get_success())
     }
@@ -1617,12 +2773,7 @@ impl KernelHandle {
     /// List all presets for this kernel.
     pub async fn list_presets(&self) -> Result<Vec<PresetInfo>, RpcError> {
         let mut request = self.kernel.list_presets_request();
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let presets = response.get()?.get_presets()?;
 
@@ -1803,12 +2954,7 @@ impl KernelHandle {
         request.get().set_pos(pos);
         request.get().set_insert(insert);
         request.get().set_delete(delete);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         Ok(response.get()?.get_ack_version())
     }
@@ -1820,12 +2966,7 @@ impl KernelHandle {
     pub async fn get_input_state(&self, context_id: ContextId) -> Result<InputState, RpcError> {
         let mut request = self.kernel.get_input_state_request();
         request.get().set_context_id(context_id.as_bytes());
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let result = response.get()?;
         Ok(InputState {
@@ -1844,12 +2985,7 @@ impl KernelHandle {
         let mut request = self.kernel.push_input_ops_request();
         request.get().set_context_id(context_id.as_bytes());
         request.get().set_ops(ops);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         Ok(response.get()?.get_ack_version())
     }
@@ -1870,12 +3006,7 @@ impl KernelHandle {
         } else {
             crate::kaijutsu_capnp::InputMode::Chat
         });
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let result = response.get()?;
         let block_id = parse_block_id(&result.get_command_block_id()?)?;
@@ -1901,12 +3032,7 @@ impl KernelHandle {
         request.get().set_mime(mime);
         request.get().set_payload(payload);
         request.get().set_cas_hash("");
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         let response = request.send().promise.await?;
         let result = response.get()?;
         parse_block_id(&result.get_block_id()?)
@@ -1931,12 +3057,7 @@ impl KernelHandle {
         request.get().set_tempo_bps(tempo_bps);
         request.get().set_epoch_ns(epoch_ns);
         request.get().set_source(source);
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         request.send().promise.await?;
         Ok(())
     }
@@ -1950,12 +3071,7 @@ impl KernelHandle {
     pub async fn clear_input(&self, context_id: ContextId) -> Result<(), RpcError> {
         let mut request = self.kernel.clear_input_request();
         request.get().set_context_id(context_id.as_bytes());
-        {
-            let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
-            let mut trace = request.get().init_trace();
-            trace.set_traceparent(&traceparent);
-            trace.set_tracestate(&tracestate);
-        }
+        self.trace.inject(request.get().init_trace());
         request.send().promise.await?;
         Ok(())
     }
@@ -2055,6 +3171,27 @@ impl KernelHandle {
         request.send().promise.await?;
         Ok(())
     }
+
+    /// Subscribe to per-context activity metrics (ops/minute, running
+    /// streams, errors over the kernel's sliding window). `interval_ms`
+    /// behaves as in [`Self::subscribe_vfs_activity`]. The server streams
+    /// `onActivity` callbacks to `callback` until the connection drops — see
+    /// [`crate::subscriptions::activity_events_channel`].
+    #[tracing::instrument(skip(self, callback), name = "rpc_client.subscribe_activity")]
+    pub async fn subscribe_activity(
+        &self,
+        callback: crate::kaijutsu_capnp::activity_events::Client,
+        interval_ms: u32,
+    ) -> Result<(), RpcError> {
+        let mut request = self.kernel.subscribe_activity_request();
+        {
+            let mut p = request.get();
+            p.set_callback(callback);
+            p.set_interval_ms(interval_ms);
+        }
+        request.send().promise.await?;
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -2145,6 +3282,41 @@ pub struct VfsActivityEntry {
     pub generation: u64,
 }
 
+/// Parse a capnp `ContextActivity` reader. Shared by the push forwarder
+/// (`subscriptions.rs`).
+pub(crate) fn parse_context_activity(
+    r: crate::kaijutsu_capnp::context_activity::Reader<'_>,
+) -> Result<kaijutsu_types::ContextActivity, RpcError> {
+    Ok(kaijutsu_types::ContextActivity {
+        context_id: parse_context_id(r.get_context_id()?)?,
+        ops_per_minute: r.get_ops_per_minute(),
+        active_streams: r.get_active_streams(),
+        errors: r.get_errors(),
+    })
+}
+
+/// Parse a capnp `HealthIssue` reader. Shared by the push forwarder
+/// (`subscriptions.rs`).
+pub(crate) fn parse_health_issue(
+    r: crate::kaijutsu_capnp::health_issue::Reader<'_>,
+) -> Result<kaijutsu_types::HealthIssue, RpcError> {
+    use crate::kaijutsu_capnp::health_issue::Which;
+    Ok(match r.which()? {
+        Which::StuckRunning(g) => kaijutsu_types::HealthIssue::StuckRunning {
+            block_id: parse_block_id(&g.get_block_id()?)?,
+            running_secs: g.get_seconds(),
+        },
+        Which::SilentStream(g) => kaijutsu_types::HealthIssue::SilentStream {
+            block_id: parse_block_id(&g.get_block_id()?)?,
+            silent_secs: g.get_seconds(),
+        },
+        Which::ToolFailures(g) => kaijutsu_types::HealthIssue::ToolFailures {
+            count: g.get_count(),
+            last_block: parse_block_id(&g.get_last_block()?)?,
+        },
+    })
+}
+
 /// Parse a capnp `VfsActivityEntry` reader into the owned client struct.
 /// Shared by the push forwarder (`subscriptions.rs`).
 pub(crate) fn parse_vfs_activity_entry(
@@ -2201,6 +3373,49 @@ fn write_shell_value(
 // Helper Functions
 // ============================================================================
 
+/// The identity baggage a handle's requests carry (see
+/// `kaijutsu_telemetry::baggage`). The principal is the connection's, learnt
+/// from `whoami` and shared by every binding on it; the kernel and context
+/// are the binding's own, so a seat reports the context it joined.
+#[derive(Clone, Default)]
+struct TraceScope {
+    principal: std::rc::Rc<std::cell::Cell<Option<PrincipalId>>>,
+    binding: std::rc::Rc<std::cell::Cell<kaijutsu_telemetry::TraceIdentity>>,
+}
+
+impl TraceScope {
+    /// A scope for a new binding to `kernel_id` on this connection.
+    fn bound(&self, kernel_id: KernelId) -> Self {
+        Self {
+            principal: self.principal.clone(),
+            binding: std::rc::Rc::new(std::cell::Cell::new(
+                kaijutsu_telemetry::TraceIdentity::default().with_kernel(kernel_id),
+            )),
+        }
+    }
+
+    /// Stamp the context a join on this binding landed in.
+    fn joined(&self, context_id: ContextId) {
+        self.binding
+            .set(self.binding.get().with_context(context_id));
+    }
+
+    /// Fill a request's `TraceContext`: W3C `traceparent`/`tracestate` from
+    /// the current span plus identity `baggage` — scoped baggage first, then
+    /// this handle's identity. The identity is also stamped on the current
+    /// (`rpc_client.*`) span so client-side traces filter the same way.
+    fn inject(&self, mut trace: crate::kaijutsu_capnp::trace_context::Builder<'_>) {
+        let (traceparent, tracestate) = kaijutsu_telemetry::inject_trace_context();
+        let mut own = self.binding.get();
+        own.principal_id = self.principal.get();
+        let identity = kaijutsu_telemetry::TraceIdentity::current().or(&own);
+        identity.record(&tracing::Span::current());
+        trace.set_traceparent(&traceparent);
+        trace.set_tracestate(&tracestate);
+        trace.set_baggage(&identity.to_baggage_header());
+    }
+}
+
 // ============================================================================
 // Block Query Builder Helpers
 // ============================================================================
@@ -2246,6 +3461,7 @@ fn set_block_filter_builder(
                     BlockKind::Notification => crate::kaijutsu_capnp::BlockKind::Notification,
                     BlockKind::Resource => crate::kaijutsu_capnp::BlockKind::Resource,
                     BlockKind::Trace => crate::kaijutsu_capnp::BlockKind::Trace,
+                    BlockKind::Repl => crate::kaijutsu_capnp::BlockKind::Repl,
                 },
             );
         }
@@ -2359,6 +3575,15 @@ fn set_block_event_filter_builder(
                     kaijutsu_types::BlockFlowKind::BeatSync => {
                         crate::kaijutsu_capnp::BlockFlowKind::BeatSync
                     }
+                    kaijutsu_types::BlockFlowKind::InboxPosted => {
+                        crate::kaijutsu_capnp::BlockFlowKind::InboxPosted
+                    }
+                    kaijutsu_types::BlockFlowKind::ModelChanged => {
+                        crate::kaijutsu_capnp::BlockFlowKind::ModelChanged
+                    }
+                    kaijutsu_types::BlockFlowKind::LockChanged => {
+                        crate::kaijutsu_capnp::BlockFlowKind::LockChanged
+                    }
                 },
             );
         }
@@ -2383,6 +3608,7 @@ fn set_block_event_filter_builder(
                     BlockKind::Notification => crate::kaijutsu_capnp::BlockKind::Notification,
                     BlockKind::Resource => crate::kaijutsu_capnp::BlockKind::Resource,
                     BlockKind::Trace => crate::kaijutsu_capnp::BlockKind::Trace,
+                    BlockKind::Repl => crate::kaijutsu_capnp::BlockKind::Repl,
                 },
             );
         }
@@ -2464,6 +3690,18 @@ pub(crate) fn parse_block_metadata(
     } else {
         None
     };
+    let language = reader
+        .get_language()
+        .ok()
+        .and_then(|t| t.to_str().ok())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_owned());
+    let label = reader
+        .get_label()
+        .ok()
+        .and_then(|t| t.to_str().ok())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_owned());
     kaijutsu_types::BlockMetadata {
         exit_code: reader.get_has_exit_code().then(|| reader.get_exit_code()),
         is_error: reader.get_is_error(),
@@ -2471,9 +3709,24 @@ pub(crate) fn parse_block_metadata(
         ephemeral: reader.get_ephemeral(),
         tool_use_id,
         stderr,
+        language,
+        label,
+        reflow: parse_reflow(
+            reader.get_reflow_width(),
+            reader.get_reflow_preserve_newlines(),
+        ),
     }
 }
 
+/// Wire reflow fields → typed setting. Width 0 means unset; an out-of-range
+/// width from a newer peer is kept as sent rather than dropped.
+fn parse_reflow(width: u16, preserve_newlines: bool) -> Option<kaijutsu_types::Reflow> {
+    (width > 0).then_some(kaijutsu_types::Reflow {
+        width,
+        preserve_newlines,
+    })
+}
+
 pub(crate) fn parse_output_data(
     reader: crate::kaijutsu_capnp::output_data::Reader<'_>,
 ) -> Result<kaijutsu_types::OutputData, capnp::Error> {
@@ -2487,78 +3740,590 @@ pub(crate) fn parse_output_data(
     } else {
         None
     };
-    let root_reader = reader.get_root()?;
-    let mut root = Vec::with_capacity(root_reader.len() as usize);
-    for i in 0..root_reader.len() {
-        root.push(parse_output_node(root_reader.get(i))?);
-    }
-    // `OutputData` is `#[non_exhaustive]` upstream — build it through the
-    // constructors rather than a struct literal. `nodes` sets `rich_json:
-    // None`; headers are attached only when present.
-    let mut data = kaijutsu_types::OutputData::nodes(root);
-    if let Some(headers) = headers {
-        data = data.with_headers(headers);
-    }
-    // richJson is "empty string = none" on the wire (see kaijutsu.capnp).
-    // The server is the sole encoder, so malformed JSON here means
-    // corruption or version-skew, not a routine condition — log loud, but
-    // still leave rich_json unset rather than failing the whole snapshot
-    // parse over a display-hint field.
-    let rich_json_text = reader.get_rich_json()?.to_str()?;
-    if !rich_json_text.is_empty() {
-        match serde_json::from_str::<serde_json::Value>(rich_json_text) {
-            Ok(v) => data = data.with_rich_json(v),
-            Err(e) => {
-                log::error!("parse_output_data: rich_json did not parse as JSON: {e}")
-            }
-        }
-    }
-    Ok(data)
+    let root_reader = reader.get_root()?;
+    let mut root = Vec::with_capacity(root_reader.len() as usize);
+    for i in 0..root_reader.len() {
+        root.push(parse_output_node(root_reader.get(i))?);
+    }
+    // `OutputData` is `#[non_exhaustive]` upstream — build it through the
+    // constructors rather than a struct literal. `nodes` sets `rich_json:
+    // None`; headers are attached only when present.
+    let mut data = kaijutsu_types::OutputData::nodes(root);
+    if let Some(headers) = headers {
+        data = data.with_headers(headers);
+    }
+    // richJson is "empty string = none" on the wire (see kaijutsu.capnp).
+    // The server is the sole encoder, so malformed JSON here means
+    // corruption or version-skew, not a routine condition — log loud, but
+    // still leave rich_json unset rather than failing the whole snapshot
+    // parse over a display-hint field.
+    let rich_json_text = reader.get_rich_json()?.to_str()?;
+    if !rich_json_text.is_empty() {
+        match serde_json::from_str::<serde_json::Value>(rich_json_text) {
+            Ok(v) => data = data.with_rich_json(v),
+            Err(e) => {
+                log::error!("parse_output_data: rich_json did not parse as JSON: {e}")
+            }
+        }
+    }
+    Ok(data)
+}
+
+fn parse_context_id(data: &[u8]) -> Result<ContextId, RpcError> {
+    ContextId::try_from_slice(data).ok_or_else(|| {
+        RpcError::ServerError(format!(
+            "invalid context ID: expected 16 bytes, got {}",
+            data.len()
+        ))
+    })
+}
+
+/// Parse 16-byte Data into KernelId.
+fn parse_kernel_id(data: &[u8]) -> Result<KernelId, RpcError> {
+    KernelId::try_from_slice(data).ok_or_else(|| {
+        RpcError::ServerError(format!(
+            "invalid kernel ID: expected 16 bytes, got {}",
+            data.len()
+        ))
+    })
+}
+
+/// Convert a BlockKind to its wire enum.
+fn block_kind_to_capnp(kind: BlockKind) -> crate::kaijutsu_capnp::BlockKind {
+    match kind {
+        BlockKind::Text => crate::kaijutsu_capnp::BlockKind::Text,
+        BlockKind::Thinking => crate::kaijutsu_capnp::BlockKind::Thinking,
+        BlockKind::ToolCall => crate::kaijutsu_capnp::BlockKind::ToolCall,
+        BlockKind::ToolResult => crate::kaijutsu_capnp::BlockKind::ToolResult,
+        BlockKind::Drift => crate::kaijutsu_capnp::BlockKind::Drift,
+        BlockKind::File => crate::kaijutsu_capnp::BlockKind::File,
+        BlockKind::Error => crate::kaijutsu_capnp::BlockKind::Error,
+        BlockKind::Notification => crate::kaijutsu_capnp::BlockKind::Notification,
+        BlockKind::Resource => crate::kaijutsu_capnp::BlockKind::Resource,
+        BlockKind::Trace => crate::kaijutsu_capnp::BlockKind::Trace,
+        BlockKind::Repl => crate::kaijutsu_capnp::BlockKind::Repl,
+    }
+}
+
+/// Convert a wire BlockKind to a BlockKind.
+fn block_kind_from_capnp(kind: crate::kaijutsu_capnp::BlockKind) -> BlockKind {
+    match kind {
+        crate::kaijutsu_capnp::BlockKind::Text => BlockKind::Text,
+        crate::kaijutsu_capnp::BlockKind::Thinking => BlockKind::Thinking,
+        crate::kaijutsu_capnp::BlockKind::ToolCall => BlockKind::ToolCall,
+        crate::kaijutsu_capnp::BlockKind::ToolResult => BlockKind::ToolResult,
+        crate::kaijutsu_capnp::BlockKind::Drift => BlockKind::Drift,
+        crate::kaijutsu_capnp::BlockKind::File => BlockKind::File,
+        crate::kaijutsu_capnp::BlockKind::Error => BlockKind::Error,
+        crate::kaijutsu_capnp::BlockKind::Notification => BlockKind::Notification,
+        crate::kaijutsu_capnp::BlockKind::Resource => BlockKind::Resource,
+        crate::kaijutsu_capnp::BlockKind::Trace => BlockKind::Trace,
+        crate::kaijutsu_capnp::BlockKind::Repl => BlockKind::Repl,
+    }
+}
+
+/// Convert a Role to its wire enum.
+fn role_to_capnp(role: Role) -> crate::kaijutsu_capnp::Role {
+    match role {
+        Role::User => crate::kaijutsu_capnp::Role::User,
+        Role::Model => crate::kaijutsu_capnp::Role::Model,
+        Role::System => crate::kaijutsu_capnp::Role::System,
+        Role::Tool => crate::kaijutsu_capnp::Role::Tool,
+        Role::Asset => crate::kaijutsu_capnp::Role::Asset,
+    }
+}
+
+/// Convert a wire Role to a Role.
+fn role_from_capnp(role: crate::kaijutsu_capnp::Role) -> Role {
+    match role {
+        crate::kaijutsu_capnp::Role::User => Role::User,
+        crate::kaijutsu_capnp::Role::Model => Role::Model,
+        crate::kaijutsu_capnp::Role::System => Role::System,
+        crate::kaijutsu_capnp::Role::Tool => Role::Tool,
+        crate::kaijutsu_capnp::Role::Asset => Role::Asset,
+    }
+}
+
+/// Parse a `SimilarContext` from the wire (search/neighbor results).
+fn parse_similar_context(
+    reader: &crate::kaijutsu_capnp::similar_context::Reader<'_>,
+) -> Result<SimilarContext, RpcError> {
+    Ok(SimilarContext {
+        context_id: parse_context_id(reader.get_context_id()?)?,
+        score: reader.get_score(),
+        label: reader.get_label()?.to_string()?,
+    })
+}
+
+/// Parse a `ContextCluster` from the wire (`get_clusters`).
+fn parse_context_cluster(
+    reader: &crate::kaijutsu_capnp::context_cluster::Reader<'_>,
+) -> Result<ContextCluster, RpcError> {
+    let ids = reader.get_context_ids()?;
+    let mut context_ids = Vec::with_capacity(ids.len() as usize);
+    for id in ids.iter() {
+        context_ids.push(parse_context_id(id?)?);
+    }
+    Ok(ContextCluster {
+        cluster_id: reader.get_cluster_id(),
+        context_ids,
+        label: reader.get_label()?.to_string()?,
+    })
+}
+
+/// Parse `ContextStats` from the wire (`get_context_stats`). Empty Text
+/// fields come back as `None`.
+fn parse_context_stats(
+    reader: &crate::kaijutsu_capnp::context_stats::Reader<'_>,
+) -> Result<kaijutsu_types::ContextStats, RpcError> {
+    let opt_text = |t: String| if t.is_empty() { None } else { Some(t) };
+    let id_bytes = reader.get_context_id()?;
+    let context_id = if id_bytes.is_empty() {
+        None
+    } else {
+        Some(parse_context_id(id_bytes)?)
+    };
+
+    let mut by_kind = std::collections::BTreeMap::new();
+    for entry in reader.get_by_kind()?.iter() {
+        by_kind.insert(entry.get_name()?.to_string()?, entry.get_count());
+    }
+    let mut by_role = std::collections::BTreeMap::new();
+    for entry in reader.get_by_role()?.iter() {
+        by_role.insert(entry.get_name()?.to_string()?, entry.get_count());
+    }
+    let mut principals = Vec::new();
+    for entry in reader.get_principals()?.iter() {
+        let principal_id = PrincipalId::try_from_slice(entry.get_principal_id()?)
+            .ok_or_else(|| RpcError::ServerError("invalid principal ID in stats".into()))?;
+        principals.push(kaijutsu_types::PrincipalActivity {
+            principal_id,
+            block_count: entry.get_block_count(),
+            last_activity_at: entry.get_last_activity_at(),
+        });
+    }
+
+    Ok(kaijutsu_types::ContextStats {
+        context_id,
+        label: opt_text(reader.get_label()?.to_string()?),
+        provider: opt_text(reader.get_provider()?.to_string()?),
+        model: opt_text(reader.get_model()?.to_string()?),
+        consent_mode: opt_text(reader.get_consent_mode()?.to_string()?),
+        context_state: opt_text(reader.get_context_state()?.to_string()?),
+        block_count: reader.get_block_count(),
+        total_bytes: reader.get_total_bytes(),
+        by_kind,
+        by_role,
+        principals,
+        drift_in: reader.get_drift_in(),
+        drift_out: reader.get_drift_out(),
+        pending_approvals: reader.get_pending_approvals(),
+        last_activity_at: reader.get_last_activity_at(),
+    })
+}
+
+/// Parse a `ContextMcpServer` from the wire (register/list MCP servers).
+fn parse_context_mcp_server(
+    reader: &crate::kaijutsu_capnp::context_mcp_server::Reader<'_>,
+) -> Result<ContextMcpServerInfo, RpcError> {
+    let mut tools = Vec::new();
+    for t in reader.get_tools()?.iter() {
+        tools.push(McpToolInfo {
+            name: t.get_name()?.to_string()?,
+            description: t.get_description()?.to_string()?,
+            input_schema: t.get_input_schema()?.to_string()?,
+        });
+    }
+    Ok(ContextMcpServerInfo {
+        instance: reader.get_instance()?.to_string()?,
+        fork_mode: reader.get_fork_mode()?.to_string()?,
+        health: reader.get_health()?.to_string()?,
+        health_reason: reader.get_health_reason()?.to_string()?,
+        tools,
+    })
+}
+
+/// Collect a wire `List(Preference)` into a map.
+fn parse_preferences(
+    list: capnp::struct_list::Reader<'_, crate::kaijutsu_capnp::preference::Owned>,
+) -> Result<kaijutsu_types::Preferences, RpcError> {
+    list.iter()
+        .map(|p| -> Result<_, RpcError> {
+            Ok((p.get_key()?.to_string()?, p.get_value()?.to_string()?))
+        })
+        .collect()
+}
+
+fn build_sandbox_profile(
+    mut builder: crate::kaijutsu_capnp::sandbox_profile::Builder<'_>,
+    profile: &kaijutsu_types::SandboxProfile,
+) {
+    let mut binaries = builder.reborrow().init_binaries(profile.binaries.len() as u32);
+    for (i, binary) in profile.binaries.iter().enumerate() {
+        binaries.set(i as u32, binary.as_str());
+    }
+    builder.set_fs_from_mounts(profile.fs_from_mounts);
+    builder.set_network(profile.network);
+    let limits = &profile.limits;
+    builder.set_cpu_secs(limits.cpu_secs.unwrap_or(0));
+    builder.set_memory_bytes(limits.memory_bytes.unwrap_or(0));
+    builder.set_file_size_bytes(limits.file_size_bytes.unwrap_or(0));
+    builder.set_open_files(limits.open_files.unwrap_or(0));
+    builder.set_processes(limits.processes.unwrap_or(0));
+}
+
+fn parse_sandbox_profile(
+    reader: crate::kaijutsu_capnp::sandbox_profile::Reader<'_>,
+) -> Result<kaijutsu_types::SandboxProfile, RpcError> {
+    let limit = |v: u64| (v != 0).then_some(v);
+    Ok(kaijutsu_types::SandboxProfile {
+        binaries: reader
+            .get_binaries()?
+            .iter()
+            .map(|b| -> Result<_, RpcError> { Ok(b?.to_string()?) })
+            .collect::<Result<_, _>>()?,
+        fs_from_mounts: reader.get_fs_from_mounts(),
+        network: reader.get_network(),
+        limits: kaijutsu_types::SandboxLimits {
+            cpu_secs: limit(reader.get_cpu_secs()),
+            memory_bytes: limit(reader.get_memory_bytes()),
+            file_size_bytes: limit(reader.get_file_size_bytes()),
+            open_files: limit(reader.get_open_files()),
+            processes: limit(reader.get_processes()),
+        },
+    })
+}
+
+fn build_llm_params(
+    mut builder: crate::kaijutsu_capnp::llm_params::Builder<'_>,
+    params: &kaijutsu_types::LlmParams,
+) {
+    builder.set_has_temperature(params.temperature.is_some());
+    builder.set_temperature(params.temperature.unwrap_or(0.0));
+    builder.set_max_tokens(params.max_tokens.unwrap_or(0));
+    builder.set_has_top_p(params.top_p.is_some());
+    builder.set_top_p(params.top_p.unwrap_or(0.0));
+    if let Some(choice) = &params.tool_choice {
+        builder.set_tool_choice(&choice.to_string());
+    }
+}
+
+fn parse_llm_params(
+    reader: crate::kaijutsu_capnp::llm_params::Reader<'_>,
+) -> Result<kaijutsu_types::LlmParams, RpcError> {
+    let tool_choice = reader.get_tool_choice()?.to_str()?;
+    let max_tokens = reader.get_max_tokens();
+    Ok(kaijutsu_types::LlmParams {
+        temperature: reader
+            .get_has_temperature()
+            .then(|| reader.get_temperature()),
+        max_tokens: (max_tokens != 0).then_some(max_tokens),
+        top_p: reader.get_has_top_p().then(|| reader.get_top_p()),
+        tool_choice: if tool_choice.is_empty() {
+            None
+        } else {
+            Some(tool_choice.parse().map_err(RpcError::ServerError)?)
+        },
+    })
+}
+
+fn parse_broadcast_status(
+    reader: crate::kaijutsu_capnp::broadcast_status::Reader<'_>,
+) -> Result<kaijutsu_types::BroadcastStatus, RpcError> {
+    let context_id = |bytes: &[u8]| {
+        ContextId::try_from_slice(bytes)
+            .ok_or_else(|| RpcError::ServerError("invalid context ID in broadcast".into()))
+    };
+    let mut targets = Vec::new();
+    for t in reader.get_targets()?.iter() {
+        let model = t.get_model()?.to_str()?;
+        let error = t.get_error()?.to_str()?;
+        targets.push(kaijutsu_types::BroadcastTargetStatus {
+            context_id: context_id(t.get_context_id()?)?,
+            model: (!model.is_empty()).then(|| model.to_string()),
+            state: t
+                .get_state()?
+                .to_str()?
+                .parse()
+                .map_err(|_| RpcError::ServerError("unknown broadcast state".into()))?,
+            response_block: if t.get_has_response() {
+                Some(parse_block_id(&t.get_response_block()?)?)
+            } else {
+                None
+            },
+            error: (!error.is_empty()).then(|| error.to_string()),
+        });
+    }
+    Ok(kaijutsu_types::BroadcastStatus {
+        id: reader.get_id(),
+        comparison_context_id: context_id(reader.get_comparison_context_id()?)?,
+        prompt: reader.get_prompt()?.to_string()?,
+        targets,
+        started_at: reader.get_started_at(),
+    })
+}
+
+fn parse_digest_subscription(
+    reader: &crate::kaijutsu_capnp::digest_subscription::Reader<'_>,
+) -> Result<kaijutsu_types::DigestSubscription, RpcError> {
+    let focus = reader.get_focus()?.to_string()?;
+    let created_by = PrincipalId::try_from_slice(reader.get_created_by()?)
+        .ok_or_else(|| RpcError::ServerError("invalid principal ID in digest".into()))?;
+    Ok(kaijutsu_types::DigestSubscription {
+        subscriber: parse_context_id(reader.get_subscriber()?)?,
+        source: parse_context_id(reader.get_source()?)?,
+        every_secs: Some(reader.get_every_secs()).filter(|&s| s > 0),
+        on_milestone: reader.get_on_milestone(),
+        focus: (!focus.is_empty()).then_some(focus),
+        created_by,
+        created_at: reader.get_created_at(),
+        last_digest_at: Some(reader.get_last_digest_at()).filter(|&t| t > 0),
+        // Not on the wire: the kernel's change-detection baseline.
+        last_block: None,
+    })
+}
+
+fn parse_budget_status(
+    reader: crate::kaijutsu_capnp::budget_status::Reader<'_>,
+) -> Result<kaijutsu_types::BudgetStatus, RpcError> {
+    let budget = if reader.get_has_budget() {
+        let b = reader.get_budget()?;
+        let calls = b.get_max_tool_calls_per_hour();
+        let shell_secs = b.get_max_shell_secs_per_hour();
+        Some(kaijutsu_types::ExecutionBudget {
+            max_tool_calls_per_hour: (calls != 0).then_some(calls),
+            max_shell_secs_per_hour: (shell_secs != 0).then_some(shell_secs),
+        })
+    } else {
+        None
+    };
+    let halt = if reader.get_halted() {
+        let by = PrincipalId::try_from_slice(reader.get_halted_by()?)
+            .ok_or_else(|| RpcError::ServerError("invalid principal ID in halt".into()))?;
+        Some(kaijutsu_types::ToolHalt {
+            reason: reader.get_halt_reason()?.to_string()?,
+            by,
+            at: reader.get_halted_at(),
+        })
+    } else {
+        None
+    };
+    Ok(kaijutsu_types::BudgetStatus {
+        context_id: parse_context_id(reader.get_context_id()?)?,
+        budget,
+        tool_calls: reader.get_tool_calls(),
+        shell_secs: reader.get_shell_secs(),
+        halt,
+    })
+}
+
+/// Parse a wire `ConfigApplyReport` (pushed by `onConfigApplied`).
+pub(crate) fn parse_config_apply_report(
+    reader: &crate::kaijutsu_capnp::config_apply_report::Reader<'_>,
+) -> Result<kaijutsu_types::ConfigApplyReport, RpcError> {
+    Ok(kaijutsu_types::ConfigApplyReport {
+        path: reader.get_path()?.to_string()?,
+        applied: parse_config_changes(reader.get_applied()?)?,
+        rejected: parse_config_changes(reader.get_rejected()?)?,
+    })
+}
+
+fn parse_config_changes(
+    list: capnp::struct_list::Reader<'_, crate::kaijutsu_capnp::config_change::Owned>,
+) -> Result<Vec<kaijutsu_types::ConfigChange>, RpcError> {
+    list.iter()
+        .map(|c| -> Result<_, RpcError> {
+            Ok(kaijutsu_types::ConfigChange::new(
+                c.get_item()?.to_string()?,
+                c.get_detail()?.to_string()?,
+            ))
+        })
+        .collect()
+}
+
+/// Parse a wire `PushViolation`.
+fn parse_push_violation(
+    reader: &crate::kaijutsu_capnp::push_violation::Reader<'_>,
+) -> Result<kaijutsu_types::PushViolation, RpcError> {
+    Ok(kaijutsu_types::PushViolation {
+        block_id: parse_block_id(&reader.get_block_id()?)?,
+        rule: reader
+            .get_rule()?
+            .to_str()?
+            .parse()
+            .map_err(RpcError::ServerError)?,
+        detail: reader.get_detail()?.to_string()?,
+    })
+}
+
+/// Parse a wire `ContextShare`. Only live shares go on the wire, so it is
+/// never revoked.
+fn parse_context_share(
+    reader: &crate::kaijutsu_capnp::context_share::Reader<'_>,
+) -> Result<kaijutsu_types::ContextShare, RpcError> {
+    Ok(kaijutsu_types::ContextShare {
+        token: reader.get_token()?.to_string()?,
+        context_id: ContextId::try_from_slice(reader.get_context_id()?).ok_or_else(|| {
+            RpcError::ServerError("context share: invalid context id".to_string())
+        })?,
+        label: reader.get_label()?.to_string()?,
+        version: reader.get_version(),
+        block_count: reader.get_block_count(),
+        created_by: PrincipalId::try_from_slice(reader.get_created_by()?)
+            .ok_or_else(|| RpcError::ServerError("context share: invalid creator".to_string()))?,
+        created_at: reader.get_created_at(),
+        expires_at: reader.get_expires_at(),
+        revoked_at: None,
+    })
+}
+
+/// Parse a wire `DocBookmark`.
+fn parse_doc_bookmark(
+    reader: &crate::kaijutsu_capnp::doc_bookmark::Reader<'_>,
+) -> Result<kaijutsu_types::DocBookmark, RpcError> {
+    Ok(kaijutsu_types::DocBookmark {
+        context_id: ContextId::try_from_slice(reader.get_context_id()?)
+            .ok_or_else(|| RpcError::ServerError("bookmark: invalid context id".to_string()))?,
+        name: reader.get_name()?.to_string()?,
+        version: reader.get_version(),
+        block_count: reader.get_block_count(),
+        created_by: PrincipalId::try_from_slice(reader.get_created_by()?)
+            .ok_or_else(|| RpcError::ServerError("bookmark: invalid creator".to_string()))?,
+        created_at: reader.get_created_at(),
+    })
+}
+
+/// Parse a wire `BlockLock`.
+pub(crate) fn parse_block_lock(
+    reader: &crate::kaijutsu_capnp::block_lock::Reader<'_>,
+) -> Result<kaijutsu_types::BlockLock, RpcError> {
+    Ok(kaijutsu_types::BlockLock {
+        block_id: parse_block_id(&reader.get_block_id()?)?,
+        holder: PrincipalId::try_from_slice(reader.get_holder()?)
+            .ok_or_else(|| RpcError::ServerError("block lock: invalid holder".to_string()))?,
+        holder_name: reader.get_holder_name()?.to_string()?,
+        note: reader.get_note()?.to_string()?,
+        acquired_at: reader.get_acquired_at(),
+        expires_at: reader.get_expires_at(),
+    })
+}
+
+/// Parse a wire `InboxItem`; empty optional ids and `ackedAt = 0` read as `None`.
+pub(crate) fn parse_inbox_item(
+    reader: &crate::kaijutsu_capnp::inbox_item::Reader<'_>,
+) -> Result<kaijutsu_types::InboxItem, RpcError> {
+    let kind_str = reader.get_kind()?.to_str()?;
+    let kind = kind_str
+        .parse::<kaijutsu_types::InboxKind>()
+        .map_err(|_| RpcError::ServerError(format!("unknown inbox kind '{kind_str}'")))?;
+    let recipient = PrincipalId::try_from_slice(reader.get_recipient()?)
+        .ok_or_else(|| RpcError::ServerError("inbox item: invalid recipient".to_string()))?;
+    let context_id = match reader.get_context_id()? {
+        [] => None,
+        bytes => Some(parse_context_id(bytes)?),
+    };
+    let sender = match reader.get_sender()? {
+        [] => None,
+        bytes => Some(
+            PrincipalId::try_from_slice(bytes)
+                .ok_or_else(|| RpcError::ServerError("inbox item: invalid sender".to_string()))?,
+        ),
+    };
+    let acked_at = match reader.get_acked_at() {
+        0 => None,
+        t => Some(t),
+    };
+    Ok(kaijutsu_types::InboxItem {
+        id: reader.get_id(),
+        recipient,
+        kind,
+        context_id,
+        sender,
+        summary: reader.get_summary()?.to_string()?,
+        created_at: reader.get_created_at(),
+        acked_at,
+    })
 }
 
-fn parse_context_id(data: &[u8]) -> Result<ContextId, RpcError> {
-    ContextId::try_from_slice(data).ok_or_else(|| {
-        RpcError::ServerError(format!(
-            "invalid context ID: expected 16 bytes, got {}",
-            data.len()
-        ))
+fn parse_seat(reader: &crate::kaijutsu_capnp::seat::Reader<'_>) -> Result<SeatInfo, RpcError> {
+    let session_id = kaijutsu_types::SessionId::try_from_slice(reader.get_session_id()?)
+        .ok_or_else(|| RpcError::ServerError("seat: invalid session id".to_string()))?;
+    let pending_consent = reader
+        .get_pending_consent()?
+        .iter()
+        .map(|item| parse_inbox_item(&item))
+        .collect::<Result<_, _>>()?;
+    Ok(SeatInfo {
+        token: reader.get_token()?.to_string()?,
+        session_id,
+        resumed: reader.get_resumed(),
+        grace: std::time::Duration::from_secs(reader.get_grace_secs() as u64),
+        pending_consent,
     })
 }
 
-/// Parse 16-byte Data into KernelId.
-fn parse_kernel_id(data: &[u8]) -> Result<KernelId, RpcError> {
-    KernelId::try_from_slice(data).ok_or_else(|| {
-        RpcError::ServerError(format!(
-            "invalid kernel ID: expected 16 bytes, got {}",
-            data.len()
-        ))
+fn parse_consent_entry(
+    reader: &crate::kaijutsu_capnp::consent_entry::Reader<'_>,
+) -> Result<kaijutsu_types::ConsentEntry, RpcError> {
+    let bad = |what: &str| RpcError::ServerError(format!("consent entry: invalid {what}"));
+    let principal_id = PrincipalId::try_from_slice(reader.get_principal_id()?)
+        .ok_or_else(|| bad("principal"))?;
+    let context_id = match reader.get_context_id()? {
+        [] => None,
+        bytes => Some(parse_context_id(bytes)?),
+    };
+    let verdict = reader.get_verdict()?.to_str()?.parse().map_err(|_| bad("verdict"))?;
+    let mode = reader.get_mode()?.to_str()?.parse().map_err(|_| bad("mode"))?;
+    Ok(kaijutsu_types::ConsentEntry {
+        seq: reader.get_seq(),
+        at: reader.get_at(),
+        principal_id,
+        context_id,
+        action: reader.get_action()?.to_string()?,
+        params_hash: reader.get_params_hash()?.to_string()?,
+        verdict,
+        basis: reader.get_basis()?.to_string()?,
+        mode,
+        prev_hash: reader.get_prev_hash()?.to_string()?,
+        hash: reader.get_hash()?.to_string()?,
+        signature: reader.get_signature()?.to_string()?,
     })
 }
 
-/// Parse a `SimilarContext` from the wire (search/neighbor results).
-fn parse_similar_context(
-    reader: &crate::kaijutsu_capnp::similar_context::Reader<'_>,
-) -> Result<SimilarContext, RpcError> {
-    Ok(SimilarContext {
+fn parse_consent_prompt(
+    reader: &crate::kaijutsu_capnp::consent_prompt::Reader<'_>,
+) -> Result<kaijutsu_types::ConsentPrompt, RpcError> {
+    Ok(kaijutsu_types::ConsentPrompt {
+        id: reader.get_id(),
         context_id: parse_context_id(reader.get_context_id()?)?,
-        score: reader.get_score(),
-        label: reader.get_label()?.to_string()?,
+        principal_id: PrincipalId::try_from_slice(reader.get_principal_id()?).ok_or_else(|| {
+            RpcError::ServerError("consent prompt: invalid principal".to_string())
+        })?,
+        tool: reader.get_tool()?.to_string()?,
+        preview: reader.get_preview()?.to_string()?,
+        requested_at: reader.get_requested_at(),
+        expires_at: reader.get_expires_at(),
     })
 }
 
-/// Parse a `ContextCluster` from the wire (`get_clusters`).
-fn parse_context_cluster(
-    reader: &crate::kaijutsu_capnp::context_cluster::Reader<'_>,
-) -> Result<ContextCluster, RpcError> {
-    let ids = reader.get_context_ids()?;
-    let mut context_ids = Vec::with_capacity(ids.len() as usize);
-    for id in ids.iter() {
-        context_ids.push(parse_context_id(id?)?);
-    }
-    Ok(ContextCluster {
-        cluster_id: reader.get_cluster_id(),
-        context_ids,
-        label: reader.get_label()?.to_string()?,
+fn parse_context_timeline(
+    reader: &crate::kaijutsu_capnp::context_timeline::Reader<'_>,
+) -> Result<kaijutsu_types::ContextTimeline, RpcError> {
+    Ok(kaijutsu_types::ContextTimeline {
+        context_id: parse_context_id(reader.get_context_id()?)?,
+        start: reader.get_start(),
+        bucket_ms: reader.get_bucket_ms(),
+        buckets: reader
+            .get_buckets()?
+            .iter()
+            .map(|b| kaijutsu_types::TimelineBucket {
+                blocks: b.get_blocks(),
+                edits: b.get_edits(),
+                tool_runs: b.get_tool_runs(),
+                drifts: b.get_drifts(),
+                errors: b.get_errors(),
+            })
+            .collect(),
     })
 }
 
@@ -2761,6 +4526,7 @@ pub(crate) fn parse_block_snapshot(
         crate::kaijutsu_capnp::BlockKind::Notification => BlockKind::Notification,
         crate::kaijutsu_capnp::BlockKind::Resource => BlockKind::Resource,
         crate::kaijutsu_capnp::BlockKind::Trace => BlockKind::Trace,
+        crate::kaijutsu_capnp::BlockKind::Repl => BlockKind::Repl,
     };
 
     let mut builder = BlockSnapshotBuilder::new(id, kind);
@@ -2834,6 +4600,27 @@ pub(crate) fn parse_block_snapshot(
         builder = builder.signature(s);
     }
 
+    if let Ok(s) = reader.get_language()
+        && let Ok(s) = s.to_str()
+        && !s.is_empty()
+    {
+        builder = builder.language(s);
+    }
+
+    if let Ok(s) = reader.get_label()
+        && let Ok(s) = s.to_str()
+        && !s.is_empty()
+    {
+        builder = builder.label(s);
+    }
+
+    if let Some(reflow) = parse_reflow(
+        reader.get_reflow_width(),
+        reader.get_reflow_preserve_newlines(),
+    ) {
+        builder = builder.reflow(reflow);
+    }
+
     // Structured output data. A kj block's OutputData is exactly root-empty +
     // headers-none + rich_json-some — without the rich_json arm here that
     // shape was silently dropped (see parse_block_snapshot_attaches_rich_json_only_output).
@@ -2939,6 +4726,68 @@ pub(crate) fn parse_block_snapshot(
         }
     }
 
+    // Resolved @-mentions (user blocks). A mention with neither id is
+    // malformed — drop it rather than guess a target.
+    if reader.has_mentions() {
+        let mut mentions = Vec::new();
+        for m in reader.get_mentions()?.iter() {
+            let name = m.get_name()?.to_str()?.to_string();
+            if let Some(id) = PrincipalId::try_from_slice(m.get_principal_id()?) {
+                mentions.push(BlockMention::principal(name, id));
+            } else if let Some(id) = ContextId::try_from_slice(m.get_context_id()?) {
+                mentions.push(BlockMention::context(name, id));
+            } else {
+                log::warn!("parse_block_snapshot: mention @{name} has no target");
+            }
+        }
+        builder = builder.mentions(mentions);
+    }
+
+    // How a shell command block ran (shell ToolCall blocks).
+    if reader.has_shell_command() {
+        let sc = reader.get_shell_command()?;
+        let argv = sc
+            .get_argv()?
+            .iter()
+            .map(|arg| Ok(arg?.to_str()?.to_string()))
+            .collect::<Result<Vec<_>, RpcError>>()?;
+        let mut env = std::collections::BTreeMap::new();
+        for entry in sc.get_env()?.iter() {
+            env.insert(
+                entry.get_key()?.to_str()?.to_string(),
+                entry.get_value()?.to_str()?.to_string(),
+            );
+        }
+        let rerun_of = if sc.has_rerun_of() {
+            Some(parse_block_id(&sc.get_rerun_of()?)?)
+        } else {
+            None
+        };
+        builder = builder.shell_command(ShellCommand {
+            argv,
+            cwd: sc.get_cwd()?.to_str()?.to_string(),
+            env,
+            requested_by: PrincipalId::try_from_slice(sc.get_requested_by()?)
+                .unwrap_or_else(PrincipalId::nil),
+            rerun_of,
+        });
+    }
+
+    // Which agent session authored the block (MCP-mirrored blocks).
+    if reader.has_provenance() {
+        let p = reader.get_provenance()?;
+        let text = |t: capnp::text::Reader<'_>| -> Result<Option<String>, RpcError> {
+            let s = t.to_str()?;
+            Ok((!s.is_empty()).then(|| s.to_string()))
+        };
+        builder = builder.provenance(AgentProvenance {
+            agent: p.get_agent()?.to_str()?.to_string(),
+            version: text(p.get_version()?)?,
+            session_id: text(p.get_session_id()?)?,
+            project_dir: text(p.get_project_dir()?)?,
+        });
+    }
+
     // Error payload (for Error blocks)
     if reader.get_has_error_payload()
         && let Ok(ep) = reader.get_error_payload()
@@ -2986,6 +4835,7 @@ pub(crate) fn parse_block_snapshot(
                 crate::kaijutsu_capnp::BlockKind::Notification => BlockKind::Notification,
                 crate::kaijutsu_capnp::BlockKind::Resource => BlockKind::Resource,
                 crate::kaijutsu_capnp::BlockKind::Trace => BlockKind::Trace,
+                crate::kaijutsu_capnp::BlockKind::Repl => BlockKind::Repl,
             })
         } else {
             None
@@ -3024,6 +4874,12 @@ pub(crate) fn parse_block_snapshot(
             crate::kaijutsu_capnp::NotificationKind::Coalesced => {
                 kaijutsu_types::NotificationKind::Coalesced
             }
+            crate::kaijutsu_capnp::NotificationKind::ModelChanged => {
+                kaijutsu_types::NotificationKind::ModelChanged
+            }
+            crate::kaijutsu_capnp::NotificationKind::IssueCreated => {
+                kaijutsu_types::NotificationKind::IssueCreated
+            }
         };
         let level = if np.get_has_level() {
             np.get_level().ok().map(|l| match l {
@@ -3135,6 +4991,8 @@ pub enum CompletionKind {
     Path,
     Variable,
     Keyword,
+    Mention,
+    Block,
 }
 
 impl CompletionKind {
@@ -3144,6 +5002,8 @@ impl CompletionKind {
             crate::kaijutsu_capnp::CompletionKind::Path => CompletionKind::Path,
             crate::kaijutsu_capnp::CompletionKind::Variable => CompletionKind::Variable,
             crate::kaijutsu_capnp::CompletionKind::Keyword => CompletionKind::Keyword,
+            crate::kaijutsu_capnp::CompletionKind::Mention => CompletionKind::Mention,
+            crate::kaijutsu_capnp::CompletionKind::Block => CompletionKind::Block,
         }
     }
 }
@@ -3200,6 +5060,22 @@ pub struct LlmProviderInfo {
     pub available: bool,
     /// All available model IDs for this provider (from aliases + default).
     pub models: Vec<String>,
+    /// Last health check; `None` until the first completes.
+    pub health: Option<LlmProviderHealth>,
+}
+
+/// Last health check of one LLM provider.
+#[derive(Debug, Clone)]
+pub struct LlmProviderHealth {
+    /// `ok`, `auth_failed`, `model_unavailable`, `unreachable` or `degraded`.
+    pub status: String,
+    /// Model probed; empty if the provider has none configured.
+    pub model: String,
+    pub latency_ms: u64,
+    /// Provider error when `status` isn't `ok`.
+    pub detail: String,
+    /// Unix millis.
+    pub checked_at: u64,
 }
 
 /// Current LLM configuration for a kernel
@@ -3236,6 +5112,25 @@ pub struct SyncState {
     pub context_id: ContextId,
     pub ops: Vec<u8>,
     pub version: u64,
+    /// The context's sync generation at the time of the snapshot.
+    pub generation: u64,
+    /// Resume point: `BlockTextOps` events of `generation` with a lower
+    /// `seq_num` are already in `ops`.
+    pub seq_num: u64,
+}
+
+/// A context seen through `peek_document`.
+#[derive(Debug, Clone)]
+pub struct PeekedDocument {
+    pub context_id: ContextId,
+    pub blocks: Vec<BlockSnapshot>,
+    pub ops: Vec<u8>,
+    pub version: u64,
+    /// Resume point, as for [`SyncState`].
+    pub generation: u64,
+    pub seq_num: u64,
+    /// How long the event stream stays open; 0 when none was asked for.
+    pub expires_in_secs: u32,
 }
 
 /// Result from submitting the input document (submitInput @78).
@@ -3314,6 +5209,91 @@ pub struct ToolSchema {
     pub input_schema: String,
 }
 
+/// A downstream MCP server to attach to one context (registerMcpServer @102).
+///
+/// `stdio` servers are spawned on the kernel host and need the context's
+/// `exec` authority; `http` servers need `operator`.
+#[derive(Debug, Clone, Default)]
+pub struct McpServerSpec {
+    /// Broker instance id — unique kernel-wide.
+    pub instance: String,
+    /// "stdio" (default when empty) or "http".
+    pub transport: String,
+    pub command: String,
+    pub args: Vec<String>,
+    /// `(KEY, VALUE)` pairs for the child's environment.
+    pub env: Vec<(String, String)>,
+    pub cwd: Option<String>,
+    pub url: Option<String>,
+    /// "inherit" (default when empty) or "exclude".
+    pub fork_mode: String,
+}
+
+/// A tool exposed by a downstream MCP server.
+#[derive(Debug, Clone)]
+pub struct McpToolInfo {
+    pub name: String,
+    pub description: String,
+    pub input_schema: String,
+}
+
+/// Text appended to a followed block, starting at char `offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockTailChunk {
+    pub offset: u64,
+    pub text: String,
+}
+
+/// How a `tail_block` follow ended: the final length in chars, and the
+/// block's terminal status (`None` if it was deleted).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTailEnd {
+    pub offset: u64,
+    pub status: Option<Status>,
+}
+
+/// One `listInbox` answer: the requested items plus the total unread count.
+#[derive(Debug, Clone)]
+pub struct InboxPage {
+    pub items: Vec<kaijutsu_types::InboxItem>,
+    pub unacked: u64,
+}
+
+/// The seat a `joinContext` landed in. Hand `token` to the next join after
+/// a reconnect (within `grace`) to resume the same session.
+#[derive(Debug, Clone)]
+pub struct SeatInfo {
+    pub token: String,
+    pub session_id: kaijutsu_types::SessionId,
+    /// Whether this join resumed a previous seat.
+    pub resumed: bool,
+    pub grace: std::time::Duration,
+    /// The principal's unacked consent requests, re-delivered on join.
+    pub pending_consent: Vec<kaijutsu_types::InboxItem>,
+}
+
+/// One `listConsentLog` answer.
+#[derive(Debug, Clone)]
+pub struct ConsentLogPage {
+    pub entries: Vec<kaijutsu_types::ConsentEntry>,
+    /// Entries in the whole log.
+    pub total: u64,
+    /// Set when the page was requested with `verify`.
+    pub verification: Option<kaijutsu_types::ConsentVerification>,
+}
+
+/// A context-scoped MCP server and its last health probe.
+#[derive(Debug, Clone)]
+pub struct ContextMcpServerInfo {
+    pub instance: String,
+    pub fork_mode: String,
+    /// "ready" | "degraded" | "down".
+    pub health: String,
+    /// Empty when ready.
+    pub health_reason: String,
+    pub tools: Vec<McpToolInfo>,
+}
+
 /// Result from an MCP tool call
 #[derive(Debug, Clone)]
 pub struct McpToolResult {
@@ -3352,6 +5332,9 @@ pub enum RpcError {
     CapabilityLost,
     #[error("Server error: {0}")]
     ServerError(String),
+    /// `pushOps` refused the push; nothing was merged.
+    #[error("ops rejected: {}", kaijutsu_types::describe_violations(.0))]
+    OpsRejected(Vec<kaijutsu_types::PushViolation>),
     #[error("{0}")]
     Other(String),
 }
@@ -3476,6 +5459,7 @@ mod tests {
             BlockKind::Notification => crate::kaijutsu_capnp::BlockKind::Notification,
             BlockKind::Resource => crate::kaijutsu_capnp::BlockKind::Resource,
             BlockKind::Trace => crate::kaijutsu_capnp::BlockKind::Trace,
+            BlockKind::Repl => crate::kaijutsu_capnp::BlockKind::Repl,
         });
 
         // Set role
@@ -3534,6 +5518,12 @@ mod tests {
                 kaijutsu_types::NotificationKind::Coalesced => {
                     crate::kaijutsu_capnp::NotificationKind::Coalesced
                 }
+                kaijutsu_types::NotificationKind::ModelChanged => {
+                    crate::kaijutsu_capnp::NotificationKind::ModelChanged
+                }
+                kaijutsu_types::NotificationKind::IssueCreated => {
+                    crate::kaijutsu_capnp::NotificationKind::IssueCreated
+                }
             });
             if let Some(level) = payload.level {
                 np.set_has_level(true);
@@ -3608,6 +5598,62 @@ mod tests {
             builder.set_signature(signature);
         }
 
+        if let Some(ref language) = snap.language {
+            builder.set_language(language);
+        }
+
+        if let Some(ref label) = snap.label {
+            builder.set_label(label);
+        }
+
+        if let Some(reflow) = snap.reflow {
+            builder.set_reflow_width(reflow.width);
+            builder.set_reflow_preserve_newlines(reflow.preserve_newlines);
+        }
+
+        if !snap.mentions.is_empty() {
+            let mut list = builder.reborrow().init_mentions(snap.mentions.len() as u32);
+            for (i, mention) in snap.mentions.iter().enumerate() {
+                let mut m = list.reborrow().get(i as u32);
+                m.set_name(&mention.name);
+                match mention.target {
+                    MentionTarget::Principal(id) => m.set_principal_id(id.as_bytes()),
+                    MentionTarget::Context(id) => m.set_context_id(id.as_bytes()),
+                }
+            }
+        }
+
+        if let Some(ref command) = snap.shell_command {
+            let mut sc = builder.reborrow().init_shell_command();
+            {
+                let mut argv = sc.reborrow().init_argv(command.argv.len() as u32);
+                for (i, arg) in command.argv.iter().enumerate() {
+                    argv.set(i as u32, arg);
+                }
+            }
+            sc.set_cwd(&command.cwd);
+            {
+                let mut env = sc.reborrow().init_env(command.env.len() as u32);
+                for (i, (key, value)) in command.env.iter().enumerate() {
+                    let mut e = env.reborrow().get(i as u32);
+                    e.set_key(key);
+                    e.set_value(value);
+                }
+            }
+            sc.set_requested_by(command.requested_by.as_bytes());
+            if let Some(ref of) = command.rerun_of {
+                set_block_id_builder(&mut sc.init_rerun_of(), of);
+            }
+        }
+
+        if let Some(ref provenance) = snap.provenance {
+            let mut p = builder.reborrow().init_provenance();
+            p.set_agent(&provenance.agent);
+            p.set_version(provenance.version.as_deref().unwrap_or(""));
+            p.set_session_id(provenance.session_id.as_deref().unwrap_or(""));
+            p.set_project_dir(provenance.project_dir.as_deref().unwrap_or(""));
+        }
+
         // Parse back
         let reader = message
             .get_root_as_reader::<crate::kaijutsu_capnp::block_snapshot::Reader>()
@@ -3706,6 +5752,142 @@ mod tests {
         assert_eq!(roundtrip_snapshot(&plain).track, None);
     }
 
+    /// Unset fields stay unset across the wire — a zero temperature is a
+    /// value, not "unset" (hasTemperature carries the difference).
+    #[test]
+    fn llm_params_capnp_roundtrip() {
+        let roundtrip = |params: &kaijutsu_types::LlmParams| {
+            let mut message = MessageBuilder::new_default();
+            build_llm_params(
+                message.init_root::<crate::kaijutsu_capnp::llm_params::Builder>(),
+                params,
+            );
+            let reader = message
+                .get_root_as_reader::<crate::kaijutsu_capnp::llm_params::Reader>()
+                .unwrap();
+            parse_llm_params(reader).unwrap()
+        };
+        let full = kaijutsu_types::LlmParams {
+            temperature: Some(0.0),
+            max_tokens: Some(2048),
+            top_p: Some(0.9),
+            tool_choice: Some(kaijutsu_types::ToolChoice::Tool("shell".into())),
+        };
+        assert_eq!(roundtrip(&full), full);
+        let empty = kaijutsu_types::LlmParams::default();
+        assert_eq!(roundtrip(&empty), empty);
+    }
+
+    #[test]
+    fn broadcast_status_parses_targets_and_unset_fields() {
+        let answer = BlockId::new(ContextId::new(), PrincipalId::new(), 7);
+        let (running, done) = (ContextId::new(), ContextId::new());
+        let mut message = MessageBuilder::new_default();
+        {
+            let mut status =
+                message.init_root::<crate::kaijutsu_capnp::broadcast_status::Builder>();
+            status.set_id(3);
+            status.set_comparison_context_id(ContextId::new().as_bytes());
+            status.set_prompt("which is faster?");
+            let mut targets = status.init_targets(2);
+            let mut t = targets.reborrow().get(0);
+            t.set_context_id(running.as_bytes());
+            t.set_state("running");
+            let mut t = targets.reborrow().get(1);
+            t.set_context_id(done.as_bytes());
+            t.set_model("claude-haiku-4-5");
+            t.set_state("done");
+            t.set_has_response(true);
+            set_block_id_builder(&mut t.init_response_block(), &answer);
+        }
+        let reader = message
+            .get_root_as_reader::<crate::kaijutsu_capnp::broadcast_status::Reader>()
+            .unwrap();
+        let status = parse_broadcast_status(reader).unwrap();
+        assert_eq!(status.id, 3);
+        assert_eq!(status.targets[0].context_id, running);
+        assert_eq!(status.targets[0].model, None);
+        assert_eq!(status.targets[0].response_block, None);
+        assert_eq!(status.targets[1].model.as_deref(), Some("claude-haiku-4-5"));
+        assert_eq!(
+            status.targets[1].state,
+            kaijutsu_types::BroadcastState::Done
+        );
+        assert_eq!(status.targets[1].response_block, Some(answer));
+        assert_eq!(status.targets[1].error, None);
+    }
+
+    #[test]
+    fn mentions_capnp_roundtrip() {
+        let id = BlockId {
+            context_id: ContextId::new(),
+            principal_id: PrincipalId::new(),
+            seq: 1,
+        };
+        let mentions = vec![
+            BlockMention::principal("amy", PrincipalId::new()),
+            BlockMention::context("review", ContextId::new()),
+        ];
+        let snap = BlockSnapshotBuilder::new(id, BlockKind::Text)
+            .content("@amy @review")
+            .mentions(mentions.clone())
+            .build();
+        assert_eq!(roundtrip_snapshot(&snap).mentions, mentions);
+
+        let plain = BlockSnapshotBuilder::new(id, BlockKind::Text).build();
+        assert!(roundtrip_snapshot(&plain).mentions.is_empty());
+    }
+
+    #[test]
+    fn shell_command_capnp_roundtrip() {
+        let id = BlockId {
+            context_id: ContextId::new(),
+            principal_id: PrincipalId::new(),
+            seq: 3,
+        };
+        let command = ShellCommand {
+            argv: vec!["cargo".into(), "test".into()],
+            cwd: "/mnt/project".into(),
+            env: [("RUST_LOG".to_string(), "debug".to_string())].into(),
+            requested_by: PrincipalId::new(),
+            rerun_of: Some(BlockId {
+                context_id: id.context_id,
+                principal_id: PrincipalId::new(),
+                seq: 1,
+            }),
+        };
+        let snap = BlockSnapshotBuilder::new(id, BlockKind::ToolCall)
+            .tool_kind(ToolKind::Shell)
+            .shell_command(command.clone())
+            .build();
+        assert_eq!(roundtrip_snapshot(&snap).shell_command, Some(command));
+
+        let plain = BlockSnapshotBuilder::new(id, BlockKind::ToolCall).build();
+        assert_eq!(roundtrip_snapshot(&plain).shell_command, None);
+    }
+
+    #[test]
+    fn provenance_capnp_roundtrip() {
+        let id = BlockId {
+            context_id: ContextId::new(),
+            principal_id: PrincipalId::new(),
+            seq: 4,
+        };
+        let provenance = AgentProvenance {
+            agent: "claude-code".into(),
+            version: Some("2.1.3".into()),
+            session_id: Some("4f1c2a9b-7e0d-4c55-9a61-0b8e2f3d1c77".into()),
+            project_dir: None,
+        };
+        let snap = BlockSnapshotBuilder::new(id, BlockKind::Text)
+            .provenance(provenance.clone())
+            .build();
+        assert_eq!(roundtrip_snapshot(&snap).provenance, Some(provenance));
+
+        let plain = BlockSnapshotBuilder::new(id, BlockKind::Text).build();
+        assert_eq!(roundtrip_snapshot(&plain).provenance, None);
+    }
+
     #[test]
     fn test_parse_block_snapshot_signature_roundtrip() {
         let id = BlockId {
@@ -3728,6 +5910,64 @@ mod tests {
         assert_eq!(roundtrip_snapshot(&plain).signature, None);
     }
 
+    #[test]
+    fn test_parse_block_snapshot_language_roundtrip() {
+        let id = BlockId {
+            context_id: ContextId::new(),
+            principal_id: PrincipalId::new(),
+            seq: 1,
+        };
+        let snap = BlockSnapshotBuilder::new(id, BlockKind::Text)
+            .content("fn main() {}")
+            .language("rust")
+            .build();
+        assert_eq!(roundtrip_snapshot(&snap).language.as_deref(), Some("rust"));
+
+        // Unset travels as "" and parses back to None.
+        let plain = BlockSnapshotBuilder::new(id, BlockKind::Text).build();
+        assert_eq!(roundtrip_snapshot(&plain).language, None);
+    }
+
+    #[test]
+    fn test_parse_block_snapshot_label_roundtrip() {
+        let id = BlockId {
+            context_id: ContextId::new(),
+            principal_id: PrincipalId::new(),
+            seq: 1,
+        };
+        let snap = BlockSnapshotBuilder::new(id, BlockKind::Text)
+            .content("We chose CRDTs.")
+            .label("design-decision-3")
+            .build();
+        assert_eq!(
+            roundtrip_snapshot(&snap).label.as_deref(),
+            Some("design-decision-3")
+        );
+
+        let plain = BlockSnapshotBuilder::new(id, BlockKind::Text).build();
+        assert_eq!(roundtrip_snapshot(&plain).label, None);
+    }
+
+    #[test]
+    fn test_parse_block_snapshot_reflow_roundtrip() {
+        let id = BlockId {
+            context_id: ContextId::new(),
+            principal_id: PrincipalId::new(),
+            seq: 1,
+        };
+        let reflow = kaijutsu_types::Reflow::new(72, true).unwrap();
+        let snap = BlockSnapshotBuilder::new(id, BlockKind::Text)
+            .content("one long line\nand a hard break")
+            .reflow(reflow)
+            .build();
+        let back = roundtrip_snapshot(&snap);
+        assert_eq!(back.reflow, Some(reflow));
+        assert_eq!(back.content, snap.content, "soft and hard breaks survive");
+
+        let plain = BlockSnapshotBuilder::new(id, BlockKind::Text).build();
+        assert_eq!(roundtrip_snapshot(&plain).reflow, None);
+    }
+
     #[test]
     fn test_parse_block_snapshot_file_path_roundtrip() {
         let ctx = ContextId::new();
@@ -3897,6 +6137,8 @@ mod tests {
             kaijutsu_types::NotificationKind::Log,
             kaijutsu_types::NotificationKind::PromptsChanged,
             kaijutsu_types::NotificationKind::Coalesced,
+            kaijutsu_types::NotificationKind::ModelChanged,
+            kaijutsu_types::NotificationKind::IssueCreated,
         ];
         for kind in kinds {
             let payload = kaijutsu_types::NotificationPayload {
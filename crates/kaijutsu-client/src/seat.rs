@@ -0,0 +1,166 @@
+//! Several seats over one connection.
+//!
+//! An [`ActorHandle`] holds one seat: the context it joined, its block
+//! subscription, its resumable session. [`ActorHandle::open_seat`] opens more
+//! on the same SSH connection — each a `bindSeat` binding with a session of
+//! its own — so a process can sit in several contexts without dialing once
+//! per context. A [`SeatHandle`] carries the seat's own event stream and a
+//! [`SyncedDocument`] kept current from it.
+//!
+//! Every seat shares the connection's kernel: the server binds one kernel per
+//! process, so seats differ by context, not by kernel.
+//!
+//! ```ignore
+//! let planner = actor.open_seat(planner_ctx).await?;
+//! let worker = actor.open_seat(worker_ctx).await?;
+//! let mut events = worker.subscribe_events();
+//! planner.actor().shell_execute("cargo test", planner.context_id(), false).await?;
+//! println!("{} blocks", worker.with_document(|doc| doc.block_count()));
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use kaijutsu_crdt::{BlockId, ContextId};
+use kaijutsu_types::BlockSnapshot;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::actor::{ActorHandle, CallError};
+use crate::subscriptions::ServerEvent;
+use crate::synced_document::{SyncEffect, SyncedDocument};
+
+/// One seat opened by [`ActorHandle::open_seat`]. Cheap to clone; the seat
+/// is released when the last clone drops.
+#[derive(Clone)]
+pub struct SeatHandle {
+    inner: Arc<SeatInner>,
+}
+
+struct SeatInner {
+    seat: u64,
+    context_id: ContextId,
+    /// Runs every call on the seat's binding; its events are the seat's.
+    actor: ActorHandle,
+    document: Arc<Mutex<SyncedDocument>>,
+    cache_task: JoinHandle<()>,
+}
+
+impl Drop for SeatInner {
+    fn drop(&mut self) {
+        self.cache_task.abort();
+        self.actor.release_seat(self.seat);
+    }
+}
+
+impl SeatHandle {
+    /// Seed the seat's document and start keeping it current. Releases the
+    /// seat if the seed fails.
+    pub(crate) async fn start(
+        actor: ActorHandle,
+        seat: u64,
+        context_id: ContextId,
+    ) -> Result<Self, CallError> {
+        // Subscribe before fetching so nothing lands between the snapshot
+        // and the stream.
+        let events = actor.subscribe_events();
+        let seeded = async {
+            let principal_id = actor.whoami().await?.principal_id;
+            let state = actor.get_context_sync(context_id).await?;
+            SyncedDocument::from_sync_state(&state, principal_id)
+                .map_err(|e| CallError::ServerError(format!("seat {seat} sync: {e}")))
+        }
+        .await;
+        let document = match seeded {
+            Ok(document) => Arc::new(Mutex::new(document)),
+            Err(e) => {
+                actor.release_seat(seat);
+                return Err(e);
+            }
+        };
+        let cache_task = tokio::spawn(keep_current(
+            actor.clone(),
+            context_id,
+            document.clone(),
+            events,
+        ));
+        Ok(Self {
+            inner: Arc::new(SeatInner {
+                seat,
+                context_id,
+                actor,
+                document,
+                cache_task,
+            }),
+        })
+    }
+
+    /// The context this seat joined.
+    pub fn context_id(&self) -> ContextId {
+        self.inner.context_id
+    }
+
+    /// The seat's RPC surface: calls run under the seat's session, not the
+    /// connection's. `join_context`, the `subscribe_*` calls and peeks are
+    /// refused — they belong to the connection's own seat.
+    pub fn actor(&self) -> &ActorHandle {
+        &self.inner.actor
+    }
+
+    /// This seat's block events (plus `Reconnected` once it is re-bound
+    /// after a reconnect). Other seats' events don't appear here.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ServerEvent> {
+        self.inner.actor.subscribe_events()
+    }
+
+    /// Every block in the seat's cached document.
+    pub fn blocks(&self) -> Vec<BlockSnapshot> {
+        self.with_document(|doc| doc.blocks())
+    }
+
+    /// One block from the seat's cached document.
+    pub fn get_block(&self, id: &BlockId) -> Option<BlockSnapshot> {
+        self.with_document(|doc| doc.get_block(id))
+    }
+
+    /// Read the seat's cached document under its lock.
+    pub fn with_document<R>(&self, f: impl FnOnce(&SyncedDocument) -> R) -> R {
+        let document = self.inner.document.lock().unwrap();
+        f(&document)
+    }
+}
+
+/// Apply the seat's events to its document, refetching the full state when
+/// the stream can't be trusted: a re-bind after reconnect, a lagged
+/// receiver, or an event the document can't place.
+async fn keep_current(
+    actor: ActorHandle,
+    context_id: ContextId,
+    document: Arc<Mutex<SyncedDocument>>,
+    mut events: broadcast::Receiver<ServerEvent>,
+) {
+    loop {
+        let resync = match events.recv().await {
+            Ok(ServerEvent::Reconnected) => true,
+            Ok(event) => matches!(
+                document.lock().unwrap().apply_event(&event),
+                SyncEffect::NeedsResync
+            ),
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                log::debug!("seat document for {context_id} lagged {n} events; resyncing");
+                true
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        if !resync {
+            continue;
+        }
+        match actor.get_context_sync(context_id).await {
+            Ok(state) => {
+                if let Err(e) = document.lock().unwrap().apply_sync_state(&state) {
+                    log::warn!("seat resync for {context_id} rejected: {e}");
+                }
+            }
+            Err(e) => log::warn!("seat resync for {context_id} failed: {e}"),
+        }
+    }
+}
@@ -8,6 +8,9 @@
 //! The connection opens a single session channel and binds it to the
 //! `kaijutsu-rpc` subsystem (`SSH_RPC_SUBSYSTEM`); Cap'n Proto then carries
 //! both request/response and server-pushed subscription streams over it.
+//! With [`SshConfig::compress`] the client asks for the zstd-framed variant
+//! first and falls back to plain if the server refuses it
+//! ([`SshClient::connect_rpc`]).
 
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -15,9 +18,10 @@ use std::sync::Arc;
 use russh::client::{self, Config, Handle};
 use russh::keys::agent::client::AgentClient;
 use russh::keys::{Algorithm, HashAlg, PrivateKey, PrivateKeyWithHashAlg, PublicKey};
-use russh::{Channel, Disconnect};
+use russh::{Channel, ChannelMsg, Disconnect};
 
 use kaijutsu_types::SSH_RPC_SUBSYSTEM;
+use kaijutsu_types::rpc_compress::RpcCompression;
 
 use crate::constants::{
     DEFAULT_SSH_HOST, DEFAULT_SSH_PORT, SSH_INACTIVITY_TIMEOUT, SSH_KEEPALIVE_INTERVAL,
@@ -77,6 +81,10 @@ pub struct SshConfig {
     /// Skip known_hosts verification (accept any server key with a warning).
     /// Intended for `--insecure` CLI flag; default is false (TOFU enabled).
     pub insecure: bool,
+    /// Ask for zstd framing on the RPC channel (large payloads only; see
+    /// `kaijutsu_types::rpc_compress`). Default true; servers without it
+    /// fall back to plain.
+    pub compress: bool,
 }
 
 impl Default for SshConfig {
@@ -87,6 +95,7 @@ impl Default for SshConfig {
             username: whoami::username(),
             key_source: KeySource::Agent,
             insecure: false,
+            compress: true,
         }
     }
 }
@@ -211,6 +220,81 @@ impl SshClient {
         &mut self,
         subsystem: &str,
     ) -> Result<Channel<client::Msg>, SshError> {
+        let session = self.open_session().await?;
+        let channel = Self::request_subsystem(&session, subsystem, false).await?;
+        self.session = Some(session);
+        Ok(channel)
+    }
+
+    /// Connect and bind a channel to the RPC subsystem, negotiating
+    /// compression: with [`SshConfig::compress`] the zstd subsystem is
+    /// requested first and its ack awaited; a refusal (a server that predates
+    /// it) falls back to plain `SSH_RPC_SUBSYSTEM` on a fresh channel of the
+    /// same session. Returns the framing to wrap the stream in.
+    pub async fn connect_rpc(
+        &mut self,
+    ) -> Result<(Channel<client::Msg>, RpcCompression), SshError> {
+        let session = self.open_session().await?;
+        let mut negotiated = None;
+        if self.config.compress {
+            let subsystem = RpcCompression::Zstd.subsystem();
+            match Self::request_subsystem(&session, subsystem, true).await {
+                Ok(channel) => negotiated = Some((channel, RpcCompression::Zstd)),
+                Err(e) => log::info!("{subsystem} refused ({e}), falling back to plain RPC"),
+            }
+        }
+        let negotiated = match negotiated {
+            Some(n) => n,
+            None => (
+                Self::request_subsystem(&session, SSH_RPC_SUBSYSTEM, false).await?,
+                RpcCompression::None,
+            ),
+        };
+        self.session = Some(session);
+        Ok(negotiated)
+    }
+
+    /// Open a session channel on `session` and request `subsystem` on it.
+    /// With `await_ack`, wait for the server's success/failure reply and turn
+    /// a refusal into `ChannelFailed` — needed when the caller has a fallback;
+    /// otherwise a refusal surfaces as the channel closing.
+    async fn request_subsystem(
+        session: &Handle<ClientHandler>,
+        subsystem: &str,
+        await_ack: bool,
+    ) -> Result<Channel<client::Msg>, SshError> {
+        let mut channel = session
+            .channel_open_session()
+            .await
+            .map_err(|e| SshError::ChannelFailed(format!("channel open: {}", e)))?;
+
+        channel
+            .request_subsystem(true, subsystem)
+            .await
+            .map_err(|e| SshError::ChannelFailed(format!("subsystem {}: {}", subsystem, e)))?;
+
+        if await_ack {
+            loop {
+                match channel.wait().await {
+                    Some(ChannelMsg::Success) => break,
+                    Some(ChannelMsg::Failure) | Some(ChannelMsg::Close) | None => {
+                        return Err(SshError::ChannelFailed(format!(
+                            "subsystem {subsystem} refused"
+                        )));
+                    }
+                    // Window adjustments and the like; the server sends no
+                    // data before the ack.
+                    Some(_) => {}
+                }
+            }
+        }
+
+        log::info!("Opened channel and requested {} subsystem", subsystem);
+        Ok(channel)
+    }
+
+    /// Connect and authenticate, returning the session handle.
+    async fn open_session(&self) -> Result<Handle<ClientHandler>, SshError> {
         let config = Config {
             inactivity_timeout: Some(SSH_INACTIVITY_TIMEOUT),
             keepalive_interval: Some(SSH_KEEPALIVE_INTERVAL),
@@ -249,20 +333,7 @@ impl SshClient {
             }
         }
 
-        let channel = session
-            .channel_open_session()
-            .await
-            .map_err(|e| SshError::ChannelFailed(format!("channel open: {}", e)))?;
-
-        channel
-            .request_subsystem(true, subsystem)
-            .await
-            .map_err(|e| SshError::ChannelFailed(format!("subsystem {}: {}", subsystem, e)))?;
-
-        log::info!("Opened channel and requested {} subsystem", subsystem);
-        self.session = Some(session);
-
-        Ok(channel)
+        Ok(session)
     }
 
     /// Authenticate using SSH agent
@@ -390,6 +461,27 @@ impl SshClient {
     }
 }
 
+/// SHA-256 fingerprints of the keys the SSH agent (`$SSH_AUTH_SOCK`) offers —
+/// what [`KeySource::Agent`] would try, without connecting to a server. For
+/// diagnostics (`kaijutsu-mcp doctor`); an empty list is
+/// [`SshError::NoKeysAvailable`].
+pub async fn agent_key_fingerprints() -> Result<Vec<String>, SshError> {
+    let mut agent = AgentClient::connect_env()
+        .await
+        .map_err(|e| SshError::AgentFailed(e.to_string()))?;
+    let keys = agent
+        .request_identities()
+        .await
+        .map_err(|e| SshError::AgentFailed(e.to_string()))?;
+    if keys.is_empty() {
+        return Err(SshError::NoKeysAvailable);
+    }
+    Ok(keys
+        .iter()
+        .map(|k| k.public_key().fingerprint(HashAlg::Sha256).to_string())
+        .collect())
+}
+
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum SshError {
     #[error("Connection failed: {0}")]
@@ -12,15 +12,17 @@ use std::rc::Rc;
 
 use capnp::capability::Promise;
 use kaijutsu_crdt::{ContextId, KernelId};
-use kaijutsu_types::{BlockId, BlockSnapshot};
+use kaijutsu_types::{BlockId, BlockSnapshot, PrincipalId};
 use tokio::sync::broadcast;
 
 use crate::kaijutsu_capnp::{
-    block_events, editor_events, kernel_output, resource_events, vfs_activity_events,
+    activity_events, block_events, block_tail_events, editor_events, kernel_output,
+    resource_events, vfs_activity_events,
 };
 use crate::rpc::{
-    EditorState, SyncState, VfsActivityEntry, parse_block_id, parse_block_snapshot,
-    parse_editor_state, parse_vfs_activity_entry,
+    BlockTailChunk, EditorState, SyncState, VfsActivityEntry, parse_block_id, parse_block_lock,
+    parse_block_snapshot, parse_config_apply_report, parse_context_activity, parse_editor_state,
+    parse_health_issue, parse_inbox_item, parse_vfs_activity_entry,
 };
 
 // ============================================================================
@@ -48,6 +50,9 @@ pub enum ServerEvent {
         /// by tracking the last-seen seq per context and triggering an
         /// `ops_since` re-fetch when the next seq is non-consecutive.
         seq_num: u64,
+        /// The context's sync generation `seq_num` counts within; changes
+        /// when the kernel reloads the context (seqs restart at 0).
+        generation: u64,
     },
     /// A block's execution status changed (Pending → Running → Done/Error).
     BlockStatusChanged {
@@ -154,6 +159,33 @@ pub enum ServerEvent {
         context_id: ContextId,
         beat_ref: kaijutsu_audio::BeatRef,
     },
+    /// A notification landed in this connection's principal's inbox
+    /// (mention, consent request, drift arrival, task assignment). The server
+    /// only forwards items addressed to the authenticated principal.
+    InboxItem { item: kaijutsu_types::InboxItem },
+    /// A context's model was switched (`setContextModel`) — `by` made the
+    /// switch. Seats relabel the context; the conversation itself carries a
+    /// `ModelChanged` notification block.
+    ContextModelChanged {
+        context_id: ContextId,
+        provider: String,
+        model: String,
+        by: PrincipalId,
+    },
+    /// A block was locked for editing or its lock renewed (`lock` is
+    /// `Some`), or released (`None`). Expiry is not announced: drop a lock at
+    /// its `expires_at`.
+    BlockLockChanged {
+        context_id: ContextId,
+        block_id: BlockId,
+        lock: Option<kaijutsu_types::BlockLock>,
+    },
+    /// A rewritten config file was re-applied live on the server (`kj config
+    /// set/edit/reset`): what took effect and what was refused. Kernel-wide,
+    /// delivered to every connection.
+    ConfigApplied {
+        report: kaijutsu_types::ConfigApplyReport,
+    },
     /// A VFS activity digest tick (Lane K, FSN slice-1, `docs/scenes/vfs.md`).
     /// `entries` are the directories whose activity total has changed since
     /// the server-side cursor's last delivered digest — ABSOLUTE totals, not
@@ -163,6 +195,22 @@ pub enum ServerEvent {
         entries: Vec<VfsActivityEntry>,
         global_total: u64,
     },
+    /// Per-context activity over the kernel's sliding window
+    /// (`window_secs`). The complete set of non-idle contexts — a context
+    /// missing from `contexts` is idle. Kernel-wide, like `VfsActivity`.
+    ContextActivity {
+        contexts: Vec<kaijutsu_types::ContextActivity>,
+        window_secs: u32,
+    },
+    /// The kernel's health watchdog has a new finding for a context: a
+    /// block stuck `Running`, a stalled model stream, or a run of failed
+    /// tool results. `issues` is everything currently wrong with it.
+    /// Arrives on the `subscribe_activity` channel; recover with
+    /// `context_recover`.
+    ContextUnhealthy {
+        context_id: ContextId,
+        issues: Vec<kaijutsu_types::HealthIssue>,
+    },
 }
 
 /// Connection lifecycle status broadcast by the reconnect FSM.
@@ -179,7 +227,11 @@ pub enum ConnectionStatus {
     /// Initial state. No command has triggered the first connect yet.
     Idle,
     /// Handshake in progress.
-    Connecting { attempt: u32 },
+    Connecting {
+        attempt: u32,
+        /// Why the previous attempt failed; `None` on a first dial.
+        last_error: Option<String>,
+    },
     /// Connection live; subscriptions registered; liveness ping running.
     Connected {
         kernel_id: KernelId,
@@ -198,10 +250,14 @@ pub enum ConnectionStatus {
         next_attempt: u32,
         /// Unix-epoch milliseconds at which the next attempt fires.
         until_ms: u64,
+        /// Length of this wait. It grows with each failed attempt, so
+        /// `until_ms` minus `backoff_ms` is when the last attempt gave up.
+        backoff_ms: u64,
         /// Human-readable description of the last failure.
         last_error: String,
     },
-    /// Permanent failure. Absorbing state — no more attempts.
+    /// Permanent failure. No more attempts unless one is asked for with
+    /// [`ActorHandle::retry_now()`](crate::ActorHandle::retry_now).
     Terminal { reason: String },
 }
 
@@ -388,6 +444,102 @@ impl vfs_activity_events::Server for VfsActivityEventsForwarder {
     }
 }
 
+// ============================================================================
+// Context Activity Events Forwarder
+// ============================================================================
+
+/// Implements the Cap'n Proto `ActivityEvents::Server` trait, forwarding each
+/// reading into the shared `broadcast::Sender<ServerEvent>`.
+pub(crate) struct ActivityEventsForwarder {
+    pub event_tx: broadcast::Sender<ServerEvent>,
+}
+
+/// Build an `ActivityEvents` callback client plus the receiver its pushes
+/// land on — the [`vfs_activity_events_channel`] counterpart for
+/// [`KernelHandle::subscribe_activity`](crate::rpc::KernelHandle::subscribe_activity).
+pub fn activity_events_channel(
+    capacity: usize,
+) -> (
+    crate::kaijutsu_capnp::activity_events::Client,
+    broadcast::Receiver<ServerEvent>,
+) {
+    let (tx, rx) = broadcast::channel(capacity);
+    let client: crate::kaijutsu_capnp::activity_events::Client =
+        capnp_rpc::new_client(ActivityEventsForwarder { event_tx: tx });
+    (client, rx)
+}
+
+#[allow(refining_impl_trait)]
+impl activity_events::Server for ActivityEventsForwarder {
+    fn on_activity(
+        self: Rc<Self>,
+        params: activity_events::OnActivityParams,
+        _results: activity_events::OnActivityResults,
+    ) -> Promise<(), capnp::Error> {
+        let params = match params.get() {
+            Ok(p) => p,
+            Err(e) => return Promise::err(e),
+        };
+        let list = match params.get_contexts() {
+            Ok(l) => l,
+            Err(e) => return Promise::err(e),
+        };
+        let mut contexts = Vec::with_capacity(list.len() as usize);
+        for entry in list.iter() {
+            match parse_context_activity(entry) {
+                Ok(a) => contexts.push(a),
+                Err(e) => return Promise::err(rpc_to_capnp(e)),
+            }
+        }
+        let window_secs = params.get_window_secs();
+        if self
+            .event_tx
+            .send(ServerEvent::ContextActivity {
+                contexts,
+                window_secs,
+            })
+            .is_err()
+        {
+            tracing::warn!("Event channel closed, dropping ContextActivity event");
+        }
+        Promise::ok(())
+    }
+
+    fn on_unhealthy(
+        self: Rc<Self>,
+        params: activity_events::OnUnhealthyParams,
+        _results: activity_events::OnUnhealthyResults,
+    ) -> Promise<(), capnp::Error> {
+        let params = match params.get() {
+            Ok(p) => p,
+            Err(e) => return Promise::err(e),
+        };
+        let context_id = match params.get_context_id().map(parse_context_id_data) {
+            Ok(Ok(id)) => id,
+            Ok(Err(e)) | Err(e) => return Promise::err(e),
+        };
+        let list = match params.get_issues() {
+            Ok(l) => l,
+            Err(e) => return Promise::err(e),
+        };
+        let mut issues = Vec::with_capacity(list.len() as usize);
+        for entry in list.iter() {
+            match parse_health_issue(entry) {
+                Ok(issue) => issues.push(issue),
+                Err(e) => return Promise::err(rpc_to_capnp(e)),
+            }
+        }
+        if self
+            .event_tx
+            .send(ServerEvent::ContextUnhealthy { context_id, issues })
+            .is_err()
+        {
+            tracing::warn!("Event channel closed, dropping ContextUnhealthy event");
+        }
+        Promise::ok(())
+    }
+}
+
 #[allow(refining_impl_trait)]
 impl block_events::Server for BlockEventsForwarder {
     fn on_block_inserted(
@@ -747,11 +899,13 @@ impl block_events::Server for BlockEventsForwarder {
         };
 
         let seq_num = params.get_seq_num();
+        let generation = params.get_generation();
         let event = ServerEvent::BlockTextOps {
             context_id,
             block_id,
             ops,
             seq_num,
+            generation,
         };
         if self.event_tx.send(event).is_err() {
             tracing::warn!("Event channel closed, dropping BlockTextOps event");
@@ -953,6 +1107,194 @@ impl block_events::Server for BlockEventsForwarder {
         }
         Promise::ok(())
     }
+
+    fn on_inbox_item(
+        self: Rc<Self>,
+        params: block_events::OnInboxItemParams,
+        _results: block_events::OnInboxItemResults,
+    ) -> Promise<(), capnp::Error> {
+        let item = match params.get().and_then(|p| p.get_item()) {
+            Ok(r) => match parse_inbox_item(&r) {
+                Ok(item) => item,
+                Err(e) => return Promise::err(rpc_to_capnp(e)),
+            },
+            Err(e) => return Promise::err(e),
+        };
+
+        if self.event_tx.send(ServerEvent::InboxItem { item }).is_err() {
+            tracing::warn!("Event channel closed, dropping InboxItem event");
+        }
+        Promise::ok(())
+    }
+
+    fn on_context_model_changed(
+        self: Rc<Self>,
+        params: block_events::OnContextModelChangedParams,
+        _results: block_events::OnContextModelChangedResults,
+    ) -> Promise<(), capnp::Error> {
+        let params = match params.get() {
+            Ok(p) => p,
+            Err(e) => return Promise::err(e),
+        };
+
+        let context_id = match params.get_context_id() {
+            Ok(s) => match parse_context_id_data(s) {
+                Ok(id) => id,
+                Err(e) => return Promise::err(e),
+            },
+            Err(e) => return Promise::err(e),
+        };
+        let by = match params.get_by() {
+            Ok(s) => match PrincipalId::try_from_slice(s) {
+                Some(id) => id,
+                None => return Promise::err(capnp::Error::failed("invalid principal ID".into())),
+            },
+            Err(e) => return Promise::err(e),
+        };
+        let provider = match params.get_provider().and_then(read_text) {
+            Ok(s) => s,
+            Err(e) => return Promise::err(e),
+        };
+        let model = match params.get_model().and_then(read_text) {
+            Ok(s) => s,
+            Err(e) => return Promise::err(e),
+        };
+
+        let event = ServerEvent::ContextModelChanged {
+            context_id,
+            provider,
+            model,
+            by,
+        };
+        if self.event_tx.send(event).is_err() {
+            tracing::warn!("Event channel closed, dropping ContextModelChanged event");
+        }
+        Promise::ok(())
+    }
+
+    fn on_block_lock_changed(
+        self: Rc<Self>,
+        params: block_events::OnBlockLockChangedParams,
+        _results: block_events::OnBlockLockChangedResults,
+    ) -> Promise<(), capnp::Error> {
+        let params = match params.get() {
+            Ok(p) => p,
+            Err(e) => return Promise::err(e),
+        };
+
+        let context_id = match params.get_context_id() {
+            Ok(s) => match parse_context_id_data(s) {
+                Ok(id) => id,
+                Err(e) => return Promise::err(e),
+            },
+            Err(e) => return Promise::err(e),
+        };
+        let block_id = match params.get_block_id() {
+            Ok(b) => match parse_block_id(&b) {
+                Ok(id) => id,
+                Err(e) => return Promise::err(rpc_to_capnp(e)),
+            },
+            Err(e) => return Promise::err(e),
+        };
+        let lock = if params.get_locked() {
+            match params.get_lock() {
+                Ok(r) => match parse_block_lock(&r) {
+                    Ok(lock) => Some(lock),
+                    Err(e) => return Promise::err(rpc_to_capnp(e)),
+                },
+                Err(e) => return Promise::err(e),
+            }
+        } else {
+            None
+        };
+
+        let event = ServerEvent::BlockLockChanged {
+            context_id,
+            block_id,
+            lock,
+        };
+        if self.event_tx.send(event).is_err() {
+            tracing::warn!("Event channel closed, dropping BlockLockChanged event");
+        }
+        Promise::ok(())
+    }
+
+    fn on_block_text_ops_batch(
+        self: Rc<Self>,
+        params: block_events::OnBlockTextOpsBatchParams,
+        _results: block_events::OnBlockTextOpsBatchResults,
+    ) -> Promise<(), capnp::Error> {
+        let params = match params.get() {
+            Ok(p) => p,
+            Err(e) => return Promise::err(e),
+        };
+
+        let context_id = match params.get_context_id() {
+            Ok(s) => match parse_context_id_data(s) {
+                Ok(id) => id,
+                Err(e) => return Promise::err(e),
+            },
+            Err(e) => return Promise::err(e),
+        };
+        let entries = match params.get_entries() {
+            Ok(l) => l,
+            Err(e) => return Promise::err(e),
+        };
+
+        // Unpack into the per-op events a run of onBlockTextOps would have
+        // produced; consumers never see the batch.
+        let mut events = Vec::with_capacity(entries.len() as usize);
+        for entry in entries.iter() {
+            let block_id = match entry.get_block_id() {
+                Ok(b) => match parse_block_id(&b) {
+                    Ok(id) => id,
+                    Err(e) => return Promise::err(rpc_to_capnp(e)),
+                },
+                Err(e) => return Promise::err(e),
+            };
+            let ops = match entry.get_ops() {
+                Ok(data) => data.to_vec(),
+                Err(e) => return Promise::err(e),
+            };
+            events.push(ServerEvent::BlockTextOps {
+                context_id,
+                block_id,
+                ops,
+                seq_num: entry.get_seq_num(),
+                generation: entry.get_generation(),
+            });
+        }
+        for event in events {
+            if self.event_tx.send(event).is_err() {
+                tracing::warn!("Event channel closed, dropping BlockTextOps event");
+                break;
+            }
+        }
+        Promise::ok(())
+    }
+
+    fn on_config_applied(
+        self: Rc<Self>,
+        params: block_events::OnConfigAppliedParams,
+        _results: block_events::OnConfigAppliedResults,
+    ) -> Promise<(), capnp::Error> {
+        let report = match params.get().and_then(|p| p.get_report()) {
+            Ok(r) => match parse_config_apply_report(&r) {
+                Ok(report) => report,
+                Err(e) => return Promise::err(rpc_to_capnp(e)),
+            },
+            Err(e) => return Promise::err(e),
+        };
+
+        if self
+            .event_tx
+            .send(ServerEvent::ConfigApplied { report })
+            .is_err()
+        {
+            tracing::warn!("Event channel closed, dropping ConfigApplied event");
+        }
+        Promise::ok(())
+    }
 }
 
 /// Parse a Cap'n Proto `RenderCue` reader into the typed
@@ -1051,6 +1393,44 @@ impl resource_events::Server for ResourceEventsForwarder {
     }
 }
 
+// ============================================================================
+// Block Tail Forwarder
+// ============================================================================
+
+/// Implements the Cap'n Proto `BlockTailEvents::Server` trait for one
+/// `tailBlock` call, forwarding each append to the caller's channel.
+pub(crate) struct BlockTailForwarder {
+    pub tx: tokio::sync::mpsc::UnboundedSender<BlockTailChunk>,
+}
+
+#[allow(refining_impl_trait)]
+impl block_tail_events::Server for BlockTailForwarder {
+    fn on_append(
+        self: Rc<Self>,
+        params: block_tail_events::OnAppendParams,
+        _results: block_tail_events::OnAppendResults,
+    ) -> Promise<(), capnp::Error> {
+        let params = match params.get() {
+            Ok(p) => p,
+            Err(e) => return Promise::err(e),
+        };
+        let text = match params.get_text().map(|t| t.to_str().map(str::to_owned)) {
+            Ok(Ok(text)) => text,
+            Ok(Err(e)) => return Promise::err(capnp::Error::failed(e.to_string())),
+            Err(e) => return Promise::err(e),
+        };
+        // A dropped receiver means the caller stopped following; fail the
+        // push so the kernel ends the tail.
+        match self.tx.send(BlockTailChunk {
+            offset: params.get_offset(),
+            text,
+        }) {
+            Ok(()) => Promise::ok(()),
+            Err(_) => Promise::err(capnp::Error::disconnected("tail receiver dropped".into())),
+        }
+    }
+}
+
 // ============================================================================
 // Kernel Output Events
 // ============================================================================
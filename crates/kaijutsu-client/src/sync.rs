@@ -18,8 +18,26 @@
 //! - `frontier = None` or `context_id` changed -> full sync (from_snapshot)
 //! - `frontier = Some(_)` and matching context_id -> incremental merge (merge_ops)
 //! - On merge failure -> reset frontier, next event triggers full sync
+//!
+//! # Quarantine
+//!
+//! A payload that fails to apply is never just dropped: it goes into the
+//! manager's quarantine with the error that stopped it. Payloads that failed
+//! a causal merge (usually ops that outran the ops they depend on) are
+//! retried after every successful sync; corrupt ones are kept for inspection
+//! only. [`SyncManager::quarantine`] reports what is held, and
+//! [`SyncManager::resolve_quarantine`] drops it all and forces a full resync.
+//!
+//! # Change Coalescing
+//!
+//! Every applied change is also recorded in a [`SyncChanges`] set — per-block
+//! dirty text ranges plus insert/delete/metadata/move flags — that accumulates
+//! until the consumer calls [`SyncManager::drain_changes`]. A renderer drains
+//! once per frame and touches each changed block once, however many ops
+//! landed in between.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
 
 use kaijutsu_crdt::block_store::{BlockStore as CrdtBlockStore, StoreSnapshot, SyncPayload};
 use kaijutsu_crdt::{ContextId, Frontier};
@@ -27,11 +45,11 @@ use kaijutsu_types::{BlockId, BlockSnapshot, OutputData, Status};
 use thiserror::Error;
 use tracing::{error, info, trace, warn};
 
-/// Maximum number of pending ops to buffer before dropping oldest entries.
-/// Sized for text streaming bursts during network reordering — each text
-/// chunk is one entry, so 200 covers ~200 characters of streaming output
-/// arriving before their BlockInserted event.
-const MAX_PENDING_OPS: usize = 200;
+/// Maximum number of quarantined payloads before the quarantine gives up and
+/// forces a full resync. Sized for text streaming bursts during network
+/// reordering — each text chunk is one entry, so 200 covers ~200 characters
+/// of streaming output arriving before their BlockInserted event.
+const MAX_QUARANTINED: usize = 200;
 
 /// Result of a sync operation.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -50,13 +68,125 @@ pub enum SkipReason {
     /// The payload bytes were empty.
     EmptyOplog,
     /// Document ID doesn't match our tracked document.
-    DocumentIdMismatch { expected: String, got: String },
+    DocumentIdMismatch { expected: ContextId, got: ContextId },
     /// Block already exists in document (idempotent insert).
     BlockAlreadyExists,
     /// Protocol violation (e.g., BlockInserted with no ops).
     ProtocolViolation(String),
 }
 
+/// What happened to one block since the last [`SyncManager::drain_changes`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockChange {
+    /// The block arrived (its whole text is new; `dirty` covers it).
+    pub inserted: bool,
+    /// The block was tombstoned. Other fields describe changes before that.
+    pub deleted: bool,
+    /// Char range of the block's *current* text that differs from what it
+    /// was at the last drain. Successive edits are mapped through each other
+    /// and unioned, so the range stays valid for the text as it is now.
+    /// `None` when the text didn't change.
+    pub dirty: Option<Range<usize>>,
+    /// Header or scalar metadata changed (status, output, exit code, label,
+    /// collapsed, excluded, …).
+    pub metadata: bool,
+    /// The block changed position in document order.
+    pub moved: bool,
+}
+
+/// Changes accumulated between [`SyncManager::drain_changes`] calls.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncChanges {
+    /// The document was rebuilt from a snapshot; every block may differ, so
+    /// `blocks` only lists changes made after the rebuild.
+    pub full_sync: bool,
+    /// Per-block changes, keyed in `BlockId` order.
+    pub blocks: BTreeMap<BlockId, BlockChange>,
+}
+
+impl SyncChanges {
+    /// Nothing changed since the last drain.
+    pub fn is_empty(&self) -> bool {
+        !self.full_sync && self.blocks.is_empty()
+    }
+
+    /// Record a text edit that replaced `removed` chars at `start` with
+    /// `inserted` chars, coalescing with any earlier dirty range.
+    fn record_text(&mut self, block_id: BlockId, start: usize, removed: usize, inserted: usize) {
+        let change = self.blocks.entry(block_id).or_default();
+        let edited = start..start + inserted;
+        change.dirty = Some(match change.dirty.take() {
+            None => edited,
+            Some(prev) => {
+                // Map the earlier range through this edit, then union.
+                let shift = |pos: usize| {
+                    if pos <= start {
+                        pos
+                    } else if pos >= start + removed {
+                        pos - removed + inserted
+                    } else {
+                        start + inserted
+                    }
+                };
+                let (s, e) = (shift(prev.start), shift(prev.end));
+                s.min(edited.start)..e.max(edited.end)
+            }
+        });
+    }
+
+    fn record_inserted(&mut self, block_id: BlockId, len: usize) {
+        let change = self.blocks.entry(block_id).or_default();
+        change.inserted = true;
+        change.deleted = false;
+        change.dirty = Some(0..len);
+    }
+
+    fn record_deleted(&mut self, block_id: BlockId) {
+        self.blocks.entry(block_id).or_default().deleted = true;
+    }
+
+    fn record_metadata(&mut self, block_id: BlockId) {
+        self.blocks.entry(block_id).or_default().metadata = true;
+    }
+
+    fn record_moved(&mut self, block_id: BlockId) {
+        self.blocks.entry(block_id).or_default().moved = true;
+    }
+
+    fn record_full_sync(&mut self) {
+        self.full_sync = true;
+        self.blocks.clear();
+    }
+}
+
+/// The single edit turning `before` into `after`: `(start, removed,
+/// inserted)` in chars, from their common prefix and suffix. `None` when the
+/// texts are equal.
+fn text_edit(before: &str, after: &str) -> Option<(usize, usize, usize)> {
+    if before == after {
+        return None;
+    }
+    let prefix = before
+        .chars()
+        .zip(after.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (before_len, after_len) = (before.chars().count(), after.chars().count());
+    let max_suffix = before_len.min(after_len) - prefix;
+    let suffix = before
+        .chars()
+        .rev()
+        .zip(after.chars().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    Some((
+        prefix,
+        before_len - prefix - suffix,
+        after_len - prefix - suffix,
+    ))
+}
+
 /// Error during sync operation.
 #[derive(Error, Debug)]
 pub enum SyncError {
@@ -71,6 +201,54 @@ pub enum SyncError {
     Merge(String),
 }
 
+/// Why a payload is in quarantine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuarantineReason {
+    /// The bytes don't decode. Retrying can't help; only a full resync
+    /// recovers whatever they carried.
+    Corrupt,
+    /// The payload decoded but the CRDT merge failed — usually ops that
+    /// arrived before the ops they depend on. Retried after each successful
+    /// sync.
+    Causal,
+}
+
+impl QuarantineReason {
+    fn of(error: &SyncError) -> Self {
+        match error {
+            SyncError::Deserialize(_) => Self::Corrupt,
+            SyncError::FromOplog(_) | SyncError::Merge(_) => Self::Causal,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Corrupt => "corrupt",
+            Self::Causal => "causal",
+        }
+    }
+}
+
+/// An incoming payload that failed to apply, held instead of dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuarantinedPayload {
+    /// The block the payload arrived for, when the event named one.
+    pub block_id: Option<BlockId>,
+    pub reason: QuarantineReason,
+    /// The error from the most recent attempt.
+    pub error: String,
+    /// Failed attempts so far, counting the one that quarantined it.
+    pub attempts: u32,
+    ops: Vec<u8>,
+}
+
+impl QuarantinedPayload {
+    /// Size of the held payload in bytes.
+    pub fn size(&self) -> usize {
+        self.ops.len()
+    }
+}
+
 /// Manages CRDT sync state for a single document.
 ///
 /// This struct encapsulates all the frontier-tracking and sync decision logic,
@@ -103,10 +281,13 @@ pub struct SyncManager {
     context_id: Option<ContextId>,
     /// Version counter for change detection (bumped on every successful sync).
     version: u64,
-    /// Ops that failed to merge (both incremental and full sync failed).
-    /// These are retried after the next successful sync event.
-    /// Capped at MAX_PENDING_OPS to prevent unbounded growth.
-    pending_ops: Vec<(Option<BlockId>, Vec<u8>)>,
+    /// Payloads that failed to apply, oldest first. Causal failures are
+    /// retried after the next successful sync event; corrupt ones wait for
+    /// [`resolve_quarantine`](Self::resolve_quarantine). Capped at
+    /// MAX_QUARANTINED to prevent unbounded growth.
+    quarantine: Vec<QuarantinedPayload>,
+    /// Changes applied since the last [`drain_changes`](Self::drain_changes).
+    changes: SyncChanges,
 }
 
 #[allow(dead_code)]
@@ -117,7 +298,8 @@ impl SyncManager {
             frontier: None,
             context_id: None,
             version: 0,
-            pending_ops: Vec::new(),
+            quarantine: Vec::new(),
+            changes: SyncChanges::default(),
         }
     }
 
@@ -130,7 +312,8 @@ impl SyncManager {
             frontier,
             context_id,
             version: 0,
-            pending_ops: Vec::new(),
+            quarantine: Vec::new(),
+            changes: SyncChanges::default(),
         }
     }
 
@@ -158,6 +341,18 @@ impl SyncManager {
         self.version
     }
 
+    /// Take the changes applied since the last drain, leaving the set empty.
+    /// Call once per consumer tick (a Bevy frame, a notifier flush) to update
+    /// views per block rather than per op.
+    pub fn drain_changes(&mut self) -> SyncChanges {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Whether any change is waiting for [`drain_changes`](Self::drain_changes).
+    pub fn has_changes(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
     /// Reset sync state, forcing full sync on next event.
     ///
     /// Call this when merge failures occur or when you want to
@@ -165,12 +360,31 @@ impl SyncManager {
     pub fn reset(&mut self) {
         self.frontier = None;
         // Keep context_id - if it changes we'll detect that too
-        // Keep pending_ops - they should be retried after next successful sync
+        // Keep the quarantine - causal entries are retried after the next
+        // successful sync
     }
 
-    /// Number of ops currently buffered for replay.
-    pub fn pending_ops_count(&self) -> usize {
-        self.pending_ops.len()
+    /// Payloads currently held in quarantine, oldest first.
+    pub fn quarantine(&self) -> &[QuarantinedPayload] {
+        &self.quarantine
+    }
+
+    /// Number of payloads currently held in quarantine.
+    pub fn quarantined_count(&self) -> usize {
+        self.quarantine.len()
+    }
+
+    /// Give up on the quarantined payloads: drop them and force a full
+    /// resync, which brings in everything they carried from the server's
+    /// snapshot. Returns how many were dropped. The caller should follow
+    /// this with `get_context_sync` and `apply_initial_state`.
+    pub fn resolve_quarantine(&mut self) -> usize {
+        let dropped = self.quarantine.len();
+        if dropped > 0 {
+            info!("Resolving quarantine: dropping {dropped} payloads for a full resync");
+        }
+        self.reset_frontier();
+        dropped
     }
 
     /// Reset frontier to force a full re-sync on the next event.
@@ -180,85 +394,92 @@ impl SyncManager {
     /// and `apply_initial_state` with the new snapshot.
     pub fn reset_frontier(&mut self) {
         self.frontier = None;
-        self.pending_ops.clear();
+        self.quarantine.clear();
     }
 
-    /// Buffer failed ops for later replay.
+    /// Quarantine a payload that failed to apply with `error`.
     ///
-    /// Called when both incremental merge and full sync fail. The ops are
-    /// retained so they can be replayed after the next successful sync
-    /// (e.g., when the BlockInserted event finally arrives).
-    fn buffer_failed_ops(&mut self, block_id: Option<&BlockId>, ops: &[u8]) {
-        if self.pending_ops.len() >= MAX_PENDING_OPS {
+    /// Causal failures are retried after the next successful sync (e.g.,
+    /// when the BlockInserted event finally arrives); corrupt payloads are
+    /// only held so the failure stays visible until resolved.
+    fn quarantine_failed_ops(&mut self, block_id: Option<&BlockId>, ops: &[u8], error: &SyncError) {
+        if self.quarantine.len() >= MAX_QUARANTINED {
             warn!(
-                "Pending ops buffer full ({}/{}), triggering full resync instead of dropping ops",
-                self.pending_ops.len(),
-                MAX_PENDING_OPS
+                "Quarantine full ({}/{}), triggering full resync instead of holding more",
+                self.quarantine.len(),
+                MAX_QUARANTINED
             );
-            self.pending_ops.clear();
+            self.quarantine.clear();
             self.reset();
             return;
         }
+        let reason = QuarantineReason::of(error);
         info!(
-            "Buffering failed ops for block {:?} ({} bytes, {} pending total)",
+            "Quarantining {} ops for block {:?} ({} bytes, {} held total): {}",
+            reason.as_str(),
             block_id,
             ops.len(),
-            self.pending_ops.len() + 1
+            self.quarantine.len() + 1,
+            error
         );
-        self.pending_ops.push((block_id.cloned(), ops.to_vec()));
+        self.quarantine.push(QuarantinedPayload {
+            block_id: block_id.cloned(),
+            reason,
+            error: error.to_string(),
+            attempts: 1,
+            ops: ops.to_vec(),
+        });
     }
 
-    /// Replay buffered pending ops after a successful sync.
+    /// Retry quarantined causal payloads after a successful sync.
     ///
-    /// Ops that succeed are consumed; ops that still fail go back into the buffer.
-    fn replay_pending_ops(&mut self, doc: &mut CrdtBlockStore) {
-        if self.pending_ops.is_empty() {
+    /// Payloads that merge are released; ones that still fail stay in
+    /// quarantine with their latest error. Corrupt payloads are not retried —
+    /// each attempt would reset the frontier and force a full sync on every
+    /// subsequent event.
+    fn retry_quarantine(&mut self, doc: &mut CrdtBlockStore) {
+        if !self
+            .quarantine
+            .iter()
+            .any(|q| q.reason == QuarantineReason::Causal)
+        {
             return;
         }
 
-        let ops_to_replay: Vec<_> = self.pending_ops.drain(..).collect();
-        let count = ops_to_replay.len();
-        info!("Replaying {} buffered pending ops", count);
+        let held = std::mem::take(&mut self.quarantine);
+        info!("Retrying {} quarantined payloads", held.len());
 
-        let mut still_pending = Vec::new();
-        for (block_id, ops) in ops_to_replay {
-            match self.do_incremental_merge(doc, &ops, block_id.as_ref()) {
+        for mut entry in held {
+            if entry.reason == QuarantineReason::Corrupt {
+                self.quarantine.push(entry);
+                continue;
+            }
+            match self.do_incremental_merge(doc, &entry.ops, entry.block_id.as_ref()) {
                 Ok(_) => {
                     trace!(
-                        "Replayed buffered ops for block {:?} successfully",
-                        block_id
-                    );
-                }
-                Err(SyncError::Merge(ref msg)) => {
-                    // CRDT DataMissing — might succeed later when more ops arrive
-                    warn!(
-                        "Replay merge still failing for block {:?}: {}",
-                        block_id, msg
-                    );
-                    still_pending.push((block_id, ops));
-                }
-                Err(SyncError::Deserialize(ref msg)) => {
-                    // Corrupt data won't improve on retry — drop to avoid a
-                    // "death spiral" where each replay resets the frontier and
-                    // forces a full sync on every subsequent event.
-                    error!(
-                        "Dropping corrupt buffered ops for block {:?}: {}",
-                        block_id, msg
+                        "Released quarantined ops for block {:?} after {} attempts",
+                        entry.block_id, entry.attempts
                     );
                 }
                 Err(e) => {
-                    error!(
-                        "Dropping buffered ops for block {:?} due to unrecoverable error: {}",
-                        block_id, e
+                    warn!(
+                        "Quarantined ops for block {:?} still failing: {}",
+                        entry.block_id, e
                     );
+                    entry.reason = QuarantineReason::of(&e);
+                    entry.error = e.to_string();
+                    entry.attempts += 1;
+                    self.quarantine.push(entry);
                 }
             }
         }
 
-        if !still_pending.is_empty() {
-            info!("{} ops still pending after replay", still_pending.len());
+        if !self.quarantine.is_empty() {
+            info!(
+                "{} payloads still quarantined after retry",
+                self.quarantine.len()
+            );
         }
-        self.pending_ops = still_pending;
     }
 
     /// Apply initial state from server (BlockCellInitialState event).
@@ -305,6 +526,7 @@ impl SyncManager {
         self.frontier = Some(new_store.frontier());
         self.context_id = Some(context_id);
         self.version = self.version.wrapping_add(1);
+        self.changes.record_full_sync();
 
         // Replace the document
         *doc = new_store;
@@ -314,8 +536,8 @@ impl SyncManager {
             context_id, block_count,
         );
 
-        // Replay any buffered ops now that we have a valid document
-        self.replay_pending_ops(doc);
+        // Retry quarantined ops now that we have a valid document
+        self.retry_quarantine(doc);
 
         Ok(SyncResult::FullSync { block_count })
     }
@@ -343,8 +565,8 @@ impl SyncManager {
             );
             return Ok(SyncResult::Skipped {
                 reason: SkipReason::DocumentIdMismatch {
-                    expected: doc.context_id().to_string(),
-                    got: context_id.to_string(),
+                    expected: doc.context_id(),
+                    got: context_id,
                 },
             });
         }
@@ -382,28 +604,34 @@ impl SyncManager {
         let result = if self.needs_full_sync(context_id) {
             match self.do_incremental_merge(doc, ops, Some(&block.id)) {
                 Ok(result) => Ok(result),
-                Err(e) => {
+                Err(merge_err) => {
                     warn!(
                         "Recovery: incremental merge failed for {:?}, falling back to full sync: {}",
-                        block.id, e
+                        block.id, merge_err
                     );
                     match self.do_full_sync(doc, context_id, ops, Some(&block.id)) {
                         Ok(result) => Ok(result),
                         Err(e) => {
-                            // Both paths failed — buffer for later replay
-                            self.buffer_failed_ops(Some(&block.id), ops);
+                            // Both paths failed — quarantine, classified by
+                            // the merge error (the payload is a SyncPayload,
+                            // so the full-sync error says nothing new)
+                            self.quarantine_failed_ops(Some(&block.id), ops, &merge_err);
                             Err(e)
                         }
                     }
                 }
             }
         } else {
-            self.do_incremental_merge(doc, ops, Some(&block.id))
+            let result = self.do_incremental_merge(doc, ops, Some(&block.id));
+            if let Err(e) = &result {
+                self.quarantine_failed_ops(Some(&block.id), ops, e);
+            }
+            result
         };
 
-        // On any successful sync, replay buffered pending ops
+        // On any successful sync, retry quarantined ops
         if result.is_ok() {
-            self.replay_pending_ops(doc);
+            self.retry_quarantine(doc);
         }
 
         result
@@ -416,7 +644,7 @@ impl SyncManager {
     /// On CRDT merge failure (DataMissing), does NOT reset frontier -- this is
     /// likely a race condition where text ops arrived before the corresponding
     /// BlockInserted event. The frontier is still valid; the BlockInserted will
-    /// bring the missing ops.
+    /// bring the missing ops. Either way the payload is quarantined.
     ///
     /// Note: This method does NOT fall back to full sync even when `needs_full_sync()`
     /// is true. Text ops are incremental by nature - if we're out of sync, recovery
@@ -431,8 +659,8 @@ impl SyncManager {
         if context_id != doc.context_id() {
             return Ok(SyncResult::Skipped {
                 reason: SkipReason::DocumentIdMismatch {
-                    expected: doc.context_id().to_string(),
-                    got: context_id.to_string(),
+                    expected: doc.context_id(),
+                    got: context_id,
                 },
             });
         }
@@ -456,13 +684,12 @@ impl SyncManager {
 
         let result = self.do_incremental_merge(doc, ops, None);
 
-        // On success, replay any buffered pending ops
-        if result.is_ok() {
-            self.replay_pending_ops(doc);
-        } else if let Err(SyncError::Merge(_)) = &result {
-            // CRDT merge failure (likely DataMissing) — buffer for replay.
-            // Don't buffer deserialization failures (corrupt data won't improve).
-            self.buffer_failed_ops(None, ops);
+        match &result {
+            // On success, retry any quarantined ops
+            Ok(_) => self.retry_quarantine(doc),
+            // CRDT merge failure (likely DataMissing) is retried later;
+            // corrupt data is held so the divergence stays visible.
+            Err(e) => self.quarantine_failed_ops(None, ops, e),
         }
 
         result
@@ -511,6 +738,7 @@ impl SyncManager {
         self.frontier = Some(new_store.frontier());
         self.context_id = Some(context_id);
         self.version = self.version.wrapping_add(1);
+        self.changes.record_full_sync();
 
         // Replace the document
         *doc = new_store;
@@ -542,12 +770,56 @@ impl SyncManager {
             }
         };
 
+        // Capture what the payload touches so the merge can be recorded as
+        // changes: text before the merge for known blocks, and which blocks
+        // are new.
+        let text_before: Vec<(BlockId, String)> = payload
+            .block_ops
+            .iter()
+            .filter_map(|(id, _)| doc.get_block_snapshot(id).map(|b| (*id, b.content)))
+            .collect();
+        let inserted: Vec<BlockId> = payload
+            .new_blocks
+            .iter()
+            .map(|b| b.id)
+            .filter(|id| doc.get_block_snapshot(id).is_none())
+            .collect();
+        let headers_before: Vec<(BlockId, Option<kaijutsu_types::BlockHeader>)> = payload
+            .updated_headers
+            .iter()
+            .map(|h| (h.id, doc.get_block_header(&h.id)))
+            .collect();
+        let deleted = payload.deleted_blocks.clone();
+
         // Merge the payload
         match doc.merge_ops(payload) {
             Ok(()) => {
                 // Update frontier after merge
                 self.frontier = Some(doc.frontier());
                 self.version = self.version.wrapping_add(1);
+                for (id, before) in text_before {
+                    if let Some(after) = doc.get_block_snapshot(&id)
+                        && let Some((start, removed, inserted)) = text_edit(&before, &after.content)
+                    {
+                        self.changes.record_text(id, start, removed, inserted);
+                    }
+                }
+                for id in inserted {
+                    if let Some(block) = doc.get_block_snapshot(&id) {
+                        self.changes
+                            .record_inserted(id, block.content.chars().count());
+                    }
+                }
+                // Known blocks always ship their header; only a differing
+                // one is a metadata change.
+                for (id, before) in headers_before {
+                    if doc.get_block_header(&id) != before {
+                        self.changes.record_metadata(id);
+                    }
+                }
+                for id in deleted {
+                    self.changes.record_deleted(id);
+                }
                 trace!("Incremental merge for block {:?} succeeded", block_id,);
                 Ok(SyncResult::IncrementalMerge)
             }
@@ -578,6 +850,7 @@ impl SyncManager {
         doc.set_status(block_id, status)
             .map_err(|e| SyncError::Merge(e.to_string()))?;
         self.version = self.version.wrapping_add(1);
+        self.changes.record_metadata(*block_id);
         Ok(())
     }
 
@@ -591,11 +864,12 @@ impl SyncManager {
         doc.set_output(block_id, output)
             .map_err(|e| SyncError::Merge(e.to_string()))?;
         self.version = self.version.wrapping_add(1);
+        self.changes.record_metadata(*block_id);
         Ok(())
     }
 
-    /// Apply scalar metadata (exit_code, stderr, content_type, ephemeral,
-    /// tool_use_id) directly to a block. Frontier-independent — these fields
+    /// Apply scalar metadata (exit_code, stderr, content_type, language,
+    /// label, reflow, ephemeral, tool_use_id) directly to a block. Frontier-independent — these fields
     /// are not DTE-tracked, so this is safe to apply regardless of sync state
     /// and survives a reconnect that would otherwise gate text ops.
     pub fn apply_metadata_change(
@@ -610,11 +884,18 @@ impl SyncManager {
             .map_err(|e| SyncError::Merge(e.to_string()))?;
         doc.set_content_type(block_id, metadata.content_type)
             .map_err(|e| SyncError::Merge(e.to_string()))?;
+        doc.set_language(block_id, metadata.language.clone())
+            .map_err(|e| SyncError::Merge(e.to_string()))?;
+        doc.set_label(block_id, metadata.label.clone())
+            .map_err(|e| SyncError::Merge(e.to_string()))?;
+        doc.set_reflow(block_id, metadata.reflow)
+            .map_err(|e| SyncError::Merge(e.to_string()))?;
         doc.set_ephemeral(block_id, metadata.ephemeral)
             .map_err(|e| SyncError::Merge(e.to_string()))?;
         doc.set_tool_use_id(block_id, metadata.tool_use_id.clone())
             .map_err(|e| SyncError::Merge(e.to_string()))?;
         self.version = self.version.wrapping_add(1);
+        self.changes.record_metadata(*block_id);
         Ok(())
     }
 
@@ -627,6 +908,7 @@ impl SyncManager {
         doc.delete_block(block_id)
             .map_err(|e| SyncError::Merge(e.to_string()))?;
         self.version = self.version.wrapping_add(1);
+        self.changes.record_deleted(*block_id);
         Ok(())
     }
 
@@ -640,6 +922,7 @@ impl SyncManager {
         doc.set_collapsed(block_id, collapsed)
             .map_err(|e| SyncError::Merge(e.to_string()))?;
         self.version = self.version.wrapping_add(1);
+        self.changes.record_metadata(*block_id);
         Ok(())
     }
 
@@ -653,6 +936,7 @@ impl SyncManager {
         doc.set_excluded(block_id, excluded)
             .map_err(|e| SyncError::Merge(e.to_string()))?;
         self.version = self.version.wrapping_add(1);
+        self.changes.record_metadata(*block_id);
         Ok(())
     }
 
@@ -666,10 +950,118 @@ impl SyncManager {
         doc.move_block(block_id, after_id)
             .map_err(|e| SyncError::Merge(e.to_string()))?;
         self.version = self.version.wrapping_add(1);
+        self.changes.record_moved(*block_id);
         Ok(())
     }
 }
 
+// ============================================================================
+// PUSH VALIDATION
+// ============================================================================
+
+/// Why an outgoing `SyncPayload` was rejected before it left the client.
+///
+/// A payload the server can't merge doesn't just fail one call — the ops stay
+/// unpushed and every retry re-sends them, wedging the client out of sync.
+/// Catching the obvious cases locally turns that into one actionable error.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PushValidationError {
+    /// The bytes don't decode as a CBOR `SyncPayload`.
+    #[error("ops don't decode as a SyncPayload: {0}")]
+    Decode(String),
+    /// A block in the payload belongs to a different context.
+    #[error("block {block} belongs to context {found}, not the push target {expected}")]
+    ForeignBlock {
+        block: BlockId,
+        expected: ContextId,
+        found: ContextId,
+    },
+    /// The same block appears twice in `new_blocks`.
+    #[error("block {0} is listed twice in new_blocks")]
+    DuplicateNewBlock(BlockId),
+    /// A block is both created and deleted by the same payload.
+    #[error("block {0} is both a new block and a deletion")]
+    NewAndDeleted(BlockId),
+    /// Incremental DTE ops for a block the server has never been sent —
+    /// merging them against a fresh document fails with `DataMissing`.
+    #[error("delta ops for block {0}, which is neither new nor known to the server")]
+    UnknownBlock(BlockId),
+}
+
+/// Decode and sanity-check serialized push ops for `context_id`.
+///
+/// See [`validate_sync_payload`] for the invariants; `known` is forwarded.
+pub fn validate_push_ops(
+    context_id: ContextId,
+    ops: &[u8],
+    known: Option<&HashMap<BlockId, Frontier>>,
+) -> Result<SyncPayload, PushValidationError> {
+    let payload: SyncPayload = kaijutsu_types::codec::decode(ops)
+        .map_err(|e| PushValidationError::Decode(e.to_string()))?;
+    validate_sync_payload(context_id, &payload, known)?;
+    Ok(payload)
+}
+
+/// Check a `SyncPayload` against the invariants the server's `merge_ops`
+/// relies on, before it is sent:
+///
+/// - every block (and every parent / tool-call link on a new block) lives in
+///   `context_id`;
+/// - `new_blocks` has no duplicates and nothing in it is also deleted;
+/// - with `known` (the frontier already pushed to the server), every
+///   `block_ops` entry targets a block that is either new in this payload or
+///   already known.
+pub fn validate_sync_payload(
+    context_id: ContextId,
+    payload: &SyncPayload,
+    known: Option<&HashMap<BlockId, Frontier>>,
+) -> Result<(), PushValidationError> {
+    let check = |block: &BlockId| {
+        if block.context_id == context_id {
+            Ok(())
+        } else {
+            Err(PushValidationError::ForeignBlock {
+                block: *block,
+                expected: context_id,
+                found: block.context_id,
+            })
+        }
+    };
+
+    let mut new_ids = std::collections::HashSet::with_capacity(payload.new_blocks.len());
+    for snap in &payload.new_blocks {
+        check(&snap.id)?;
+        if let Some(parent) = &snap.parent_id {
+            check(parent)?;
+        }
+        if let Some(tool_call) = &snap.tool_call_id {
+            check(tool_call)?;
+        }
+        if !new_ids.insert(snap.id) {
+            return Err(PushValidationError::DuplicateNewBlock(snap.id));
+        }
+    }
+    for header in &payload.updated_headers {
+        check(&header.id)?;
+    }
+    for id in &payload.deleted_blocks {
+        check(id)?;
+        if new_ids.contains(id) {
+            return Err(PushValidationError::NewAndDeleted(*id));
+        }
+    }
+    for (id, _) in &payload.block_ops {
+        check(id)?;
+        if let Some(known) = known
+            && !new_ids.contains(id)
+            && !known.contains_key(id)
+        {
+            return Err(PushValidationError::UnknownBlock(*id));
+        }
+    }
+    Ok(())
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -715,6 +1107,10 @@ mod tests {
     }
 
     /// Helper: serialize a StoreSnapshot to CBOR bytes.
+    fn merge_error() -> SyncError {
+        SyncError::Merge("DataMissing".into())
+    }
+
     fn snapshot_bytes(store: &CrdtBlockStore) -> Vec<u8> {
         kaijutsu_types::codec::encode(&store.snapshot()).expect("serialize snapshot")
     }
@@ -1237,7 +1633,7 @@ mod tests {
     // =========================================================================
 
     #[test]
-    fn test_pending_ops_buffered_on_double_failure() {
+    fn test_failed_ops_quarantined_on_double_failure() {
         let ctx = test_context_id();
         let mut server = create_server_store(ctx);
 
@@ -1277,14 +1673,18 @@ mod tests {
         let result = sync.apply_block_inserted(&mut client, ctx, &new_block, &ops_bytes);
         // With per-block DTE, the incremental merge may actually succeed since
         // new blocks arrive as full snapshots in SyncPayload.new_blocks.
-        // If it succeeds, the pending buffer stays empty. If it fails, it gets buffered.
+        // If it succeeds, the quarantine stays empty. If it fails, it's held there.
         if result.is_err() {
-            assert_eq!(sync.pending_ops_count(), 1, "Expected 1 pending op");
+            assert_eq!(
+                sync.quarantined_count(),
+                1,
+                "Expected 1 quarantined payload"
+            );
         }
     }
 
     #[test]
-    fn test_pending_ops_replayed_after_successful_sync() {
+    fn test_quarantine_retried_after_successful_sync() {
         let ctx = test_context_id();
         let mut server = create_server_store(ctx);
         let mut client = create_client_store(ctx);
@@ -1302,7 +1702,7 @@ mod tests {
 
         let mut sync = SyncManager::new();
 
-        // Manually buffer some ops to simulate a prior failure
+        // Manually quarantine some ops to simulate a prior failure
         let server_frontier_before = server.frontier();
         let new_block_id = server
             .insert_block(
@@ -1319,8 +1719,8 @@ mod tests {
             .get_block_snapshot(&new_block_id)
             .expect("block exists");
         let ops_bytes = sync_payload_bytes(&server, &server_frontier_before);
-        sync.pending_ops.push((Some(new_block_id), ops_bytes));
-        assert_eq!(sync.pending_ops_count(), 1);
+        sync.quarantine_failed_ops(Some(&new_block_id), &ops_bytes, &merge_error());
+        assert_eq!(sync.quarantined_count(), 1);
 
         let full_snap = snapshot_bytes(&server);
         let result = sync
@@ -1328,17 +1728,17 @@ mod tests {
             .expect("full sync should succeed");
 
         assert!(matches!(result, SyncResult::FullSync { .. }));
-        // After full sync + replay, pending ops should be drained
+        // After full sync + retry, the quarantine should be drained
         assert_eq!(
-            sync.pending_ops_count(),
+            sync.quarantined_count(),
             0,
-            "Pending ops should be drained after replay"
+            "Quarantine should be drained after retry"
         );
         assert!(client.full_text().contains("Buffered content"));
     }
 
     #[test]
-    fn test_pending_ops_text_before_block_inserted() {
+    fn test_quarantine_text_before_block_inserted() {
         let ctx = test_context_id();
         let mut server = create_server_store(ctx);
         let initial_snap = snapshot_bytes(&server);
@@ -1364,7 +1764,11 @@ mod tests {
         // With per-block DTE, text ops for an unknown block will fail in merge_ops
         // because the block doesn't exist in the client store yet.
         if result.is_err() {
-            assert_eq!(sync.pending_ops_count(), 1, "Text ops should be buffered");
+            assert_eq!(
+                sync.quarantined_count(),
+                1,
+                "Text ops should be quarantined"
+            );
         }
 
         let block = server.get_block_snapshot(&block_id).expect("block exists");
@@ -1378,32 +1782,32 @@ mod tests {
     }
 
     #[test]
-    fn test_pending_ops_cap_drops_oldest() {
+    fn test_quarantine_cap_drops_oldest() {
         let mut sync = SyncManager::new();
 
-        for i in 0..(MAX_PENDING_OPS + 10) {
+        for i in 0..(MAX_QUARANTINED + 10) {
             let fake_ops = format!("fake-ops-{}", i).into_bytes();
-            sync.buffer_failed_ops(None, &fake_ops);
+            sync.quarantine_failed_ops(None, &fake_ops, &merge_error());
         }
 
         assert!(
-            sync.pending_ops_count() <= MAX_PENDING_OPS,
-            "Expected <= {} pending ops, got {}",
-            MAX_PENDING_OPS,
-            sync.pending_ops_count()
+            sync.quarantined_count() <= MAX_QUARANTINED,
+            "Expected <= {} quarantined payloads, got {}",
+            MAX_QUARANTINED,
+            sync.quarantined_count()
         );
 
-        let last_ops = &sync.pending_ops.last().unwrap().1;
+        let last_ops = &sync.quarantine().last().unwrap().ops;
         let last_str = String::from_utf8_lossy(last_ops);
         assert!(
-            last_str.contains(&format!("{}", MAX_PENDING_OPS + 9)),
+            last_str.contains(&format!("{}", MAX_QUARANTINED + 9)),
             "Expected newest entry, got: {}",
             last_str
         );
     }
 
     #[test]
-    fn test_pending_ops_replay_partial_failure() {
+    fn test_quarantine_retry_partial_failure() {
         let ctx = test_context_id();
         let mut server = create_server_store(ctx);
         let initial_snap = snapshot_bytes(&server);
@@ -1432,60 +1836,256 @@ mod tests {
         server.append_text(&block_id, " extra").expect("append");
         let valid_text_ops = sync_payload_bytes(&server, &frontier_before);
 
-        sync.pending_ops.push((None, valid_block_ops));
-        sync.pending_ops.push((None, b"not-valid-json".to_vec()));
-        sync.pending_ops.push((None, valid_text_ops));
-        assert_eq!(sync.pending_ops_count(), 3);
+        sync.quarantine_failed_ops(None, &valid_block_ops, &merge_error());
+        sync.quarantine_failed_ops(
+            None,
+            b"not-valid-json",
+            &SyncError::Deserialize("bad cbor".into()),
+        );
+        sync.quarantine_failed_ops(None, &valid_text_ops, &merge_error());
+        assert_eq!(sync.quarantined_count(), 3);
+
+        sync.retry_quarantine(&mut client);
+
+        // The causal payloads merge; the corrupt one is held, not retried.
+        assert_eq!(sync.quarantined_count(), 1);
+        let held = &sync.quarantine()[0];
+        assert_eq!(held.reason, QuarantineReason::Corrupt);
+        assert_eq!(held.attempts, 1);
+        assert_eq!(held.size(), b"not-valid-json".len());
+        assert!(client.full_text().contains("Valid block extra"));
+    }
 
-        sync.replay_pending_ops(&mut client);
+    #[test]
+    fn test_corrupt_text_ops_quarantined_until_resolved() {
+        let ctx = test_context_id();
+        let server = create_server_store(ctx);
+        let mut client = create_client_store(ctx);
+        let mut sync = SyncManager::new();
+        sync.apply_initial_state(&mut client, ctx, &snapshot_bytes(&server))
+            .expect("initial sync");
 
-        assert!(
-            sync.pending_ops_count() < 3,
-            "Expected replay to consume/drop some ops, got {}",
-            sync.pending_ops_count()
-        );
+        let result = sync.apply_text_ops(&mut client, ctx, b"garbage");
+        assert!(matches!(result, Err(SyncError::Deserialize(_))));
+        assert_eq!(sync.quarantined_count(), 1);
+        assert_eq!(sync.quarantine()[0].reason, QuarantineReason::Corrupt);
+        assert!(sync.quarantine()[0].error.contains("deserialize"));
+
+        // A full resync retries causal payloads only; the corrupt one stays.
+        sync.apply_initial_state(&mut client, ctx, &snapshot_bytes(&server))
+            .expect("resync");
+        assert_eq!(sync.quarantined_count(), 1);
+
+        assert_eq!(sync.resolve_quarantine(), 1);
+        assert_eq!(sync.quarantined_count(), 0);
+        assert!(sync.needs_full_sync(ctx), "resolving forces a full resync");
     }
 
     #[test]
     fn test_sync_buffer_overflow_triggers_reset() {
         let mut sync = SyncManager::new();
 
-        for i in 0..MAX_PENDING_OPS {
+        for i in 0..MAX_QUARANTINED {
             let fake_ops = format!("ops-{}", i).into_bytes();
-            sync.buffer_failed_ops(None, &fake_ops);
+            sync.quarantine_failed_ops(None, &fake_ops, &merge_error());
         }
 
-        assert_eq!(sync.pending_ops_count(), MAX_PENDING_OPS);
+        assert_eq!(sync.quarantined_count(), MAX_QUARANTINED);
 
         let overflow_ops = b"overflow-ops";
-        sync.buffer_failed_ops(None, overflow_ops);
+        sync.quarantine_failed_ops(None, overflow_ops, &merge_error());
 
         assert!(
             sync.frontier().is_none(),
             "Frontier should be None after overflow"
         );
         assert_eq!(
-            sync.pending_ops_count(),
+            sync.quarantined_count(),
             0,
-            "Pending ops should be cleared after overflow"
+            "Quarantine should be cleared after overflow"
         );
     }
 
     #[test]
-    fn test_buffer_failed_ops_at_cap() {
+    fn test_quarantine_failed_ops_at_cap() {
         let ctx = test_context_id();
         let mut sync = SyncManager::with_state(Some(ctx), Some(HashMap::new()));
 
-        for i in 0..MAX_PENDING_OPS {
-            sync.buffer_failed_ops(None, &format!("ops-{}", i).into_bytes());
+        for i in 0..MAX_QUARANTINED {
+            sync.quarantine_failed_ops(None, &format!("ops-{}", i).into_bytes(), &merge_error());
         }
 
-        let initial_count = sync.pending_ops_count();
-        assert_eq!(initial_count, MAX_PENDING_OPS);
+        let initial_count = sync.quarantined_count();
+        assert_eq!(initial_count, MAX_QUARANTINED);
 
-        sync.buffer_failed_ops(None, b"trigger-overflow");
+        sync.quarantine_failed_ops(None, b"trigger-overflow", &merge_error());
 
-        assert_eq!(sync.pending_ops_count(), 0, "Buffer should be cleared");
+        assert_eq!(sync.quarantined_count(), 0, "Quarantine should be cleared");
         assert!(sync.frontier().is_none(), "Frontier should be reset");
     }
+
+    // =========================================================================
+    // Change Coalescing Tests
+    // =========================================================================
+
+    /// Ship everything the server did since `frontier` as one text-ops event.
+    fn push_text(
+        sync: &mut SyncManager,
+        client: &mut CrdtBlockStore,
+        server: &CrdtBlockStore,
+        frontier: &mut HashMap<BlockId, Frontier>,
+    ) {
+        let ops = sync_payload_bytes(server, frontier);
+        sync.apply_text_ops(client, server.context_id(), &ops)
+            .expect("text ops merge");
+        *frontier = server.frontier();
+    }
+
+    #[test]
+    fn test_drain_changes_coalesces_interleaved_inserts_and_edits() {
+        let ctx = test_context_id();
+        let mut server = create_server_store(ctx);
+        let mut client = create_client_store(ctx);
+        let mut sync = SyncManager::new();
+        sync.apply_initial_state(&mut client, ctx, &snapshot_bytes(&server))
+            .expect("initial sync");
+        let changes = sync.drain_changes();
+        assert!(changes.full_sync && changes.blocks.is_empty());
+        assert!(!sync.has_changes());
+
+        // Two inserts interleaved with edits to both blocks.
+        let mut frontier = server.frontier();
+        let insert = |server: &mut CrdtBlockStore, text: &str| {
+            server
+                .insert_block(None, None, Role::Model, BlockKind::Text, text, Status::Done, ContentType::Plain)
+                .expect("insert block")
+        };
+        let a = insert(&mut server, "Hello");
+        let block_a = server.get_block_snapshot(&a).unwrap();
+        let ops = sync_payload_bytes(&server, &frontier);
+        sync.apply_block_inserted(&mut client, ctx, &block_a, &ops)
+            .expect("insert a");
+        frontier = server.frontier();
+        server.append_text(&a, " world").unwrap();
+        push_text(&mut sync, &mut client, &server, &mut frontier);
+        let b = insert(&mut server, "abc");
+        let block_b = server.get_block_snapshot(&b).unwrap();
+        let ops = sync_payload_bytes(&server, &frontier);
+        sync.apply_block_inserted(&mut client, ctx, &block_b, &ops)
+            .expect("insert b");
+        frontier = server.frontier();
+        server.edit_text(&b, 0, "x", 0).unwrap();
+        push_text(&mut sync, &mut client, &server, &mut frontier);
+        server.edit_text(&a, 0, "J", 1).unwrap();
+        push_text(&mut sync, &mut client, &server, &mut frontier);
+
+        // One entry per block; an inserted block is dirty end to end.
+        let changes = sync.drain_changes();
+        assert!(!changes.full_sync);
+        assert_eq!(changes.blocks.len(), 2);
+        let (ca, cb) = (&changes.blocks[&a], &changes.blocks[&b]);
+        assert!(ca.inserted && cb.inserted);
+        assert_eq!(ca.dirty, Some(0.."Jello world".len()));
+        assert_eq!(cb.dirty, Some(0.."xabc".len()));
+        assert!(!sync.has_changes());
+
+        // Edits after the drain: the earlier range is mapped through the
+        // later one, so it stays valid for the final text.
+        server.edit_text(&a, 6, "there", 5).unwrap(); // "Jello there" → 6..11
+        push_text(&mut sync, &mut client, &server, &mut frontier);
+        server.append_text(&a, "!").unwrap(); // → 6..12
+        push_text(&mut sync, &mut client, &server, &mut frontier);
+        server.edit_text(&a, 0, "> ", 0).unwrap(); // shifts to 8..14, plus 0..2
+        push_text(&mut sync, &mut client, &server, &mut frontier);
+        server.set_status(&b, Status::Error).unwrap();
+        push_text(&mut sync, &mut client, &server, &mut frontier);
+
+        let changes = sync.drain_changes();
+        assert_eq!(
+            client.get_block_snapshot(&a).unwrap().content,
+            "> Jello there!"
+        );
+        let (ca, cb) = (&changes.blocks[&a], &changes.blocks[&b]);
+        assert_eq!(ca.dirty, Some(0..14));
+        assert!(!ca.inserted && !ca.metadata, "text edits aren't metadata");
+        assert_eq!(cb.dirty, None);
+        assert!(cb.metadata, "header change is recorded");
+    }
+
+    #[test]
+    fn test_record_text_maps_earlier_range_through_later_edit() {
+        let id = BlockId::new(test_context_id(), server_agent(), 1);
+        let mut changes = SyncChanges::default();
+        changes.record_text(id, 6, 5, 6); // 6..12
+        changes.record_text(id, 8, 2, 0); // delete inside: 6..10
+        assert_eq!(changes.blocks[&id].dirty, Some(6..10));
+        changes.record_text(id, 20, 0, 3); // disjoint, after: 6..23
+        assert_eq!(changes.blocks[&id].dirty, Some(6..23));
+
+        assert_eq!(text_edit("abc", "abc"), None);
+        assert_eq!(text_edit("aXc", "aYYc"), Some((1, 1, 2)));
+        assert_eq!(text_edit("aa", "aaa"), Some((2, 0, 1)));
+    }
+
+    // =========================================================================
+    // Push Validation Tests
+    // =========================================================================
+
+    #[test]
+    fn test_validate_push_ops_accepts_real_payload() {
+        let ctx = test_context_id();
+        let server = create_server_store(ctx);
+        let bytes = sync_payload_bytes(&server, &HashMap::new());
+
+        let payload = validate_push_ops(ctx, &bytes, Some(&HashMap::new())).expect("valid push");
+        assert_eq!(payload.new_blocks.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_push_ops_rejects_garbage_and_foreign_context() {
+        let ctx = test_context_id();
+        assert!(matches!(
+            validate_push_ops(ctx, b"not cbor", None),
+            Err(PushValidationError::Decode(_))
+        ));
+
+        let other = create_server_store(test_context_id());
+        let bytes = sync_payload_bytes(&other, &HashMap::new());
+        assert!(matches!(
+            validate_push_ops(ctx, &bytes, None),
+            Err(PushValidationError::ForeignBlock { expected, .. }) if expected == ctx
+        ));
+    }
+
+    #[test]
+    fn test_validate_sync_payload_structural_invariants() {
+        let ctx = test_context_id();
+        let server = create_server_store(ctx);
+        let mut payload = server.ops_since(&HashMap::new());
+        let id = payload.new_blocks[0].id;
+
+        // Delta ops without the snapshot, for a block the server never saw.
+        let mut orphaned = payload.clone();
+        orphaned.new_blocks.clear();
+        assert_eq!(
+            validate_sync_payload(ctx, &orphaned, Some(&HashMap::new())),
+            Err(PushValidationError::UnknownBlock(id))
+        );
+        // Without a frontier the check is skipped.
+        assert!(validate_sync_payload(ctx, &orphaned, None).is_ok());
+
+        payload.deleted_blocks.push(id);
+        assert_eq!(
+            validate_sync_payload(ctx, &payload, None),
+            Err(PushValidationError::NewAndDeleted(id))
+        );
+
+        payload.deleted_blocks.clear();
+        let dup = payload.new_blocks[0].clone();
+        payload.new_blocks.push(dup);
+        assert_eq!(
+            validate_sync_payload(ctx, &payload, None),
+            Err(PushValidationError::DuplicateNewBlock(id))
+        );
+    }
 }
@@ -4,16 +4,16 @@
 //! CRDT internals (Frontier, SyncPayload, StoreSnapshot) behind a clean API.
 //! Both the Bevy app and MCP server consume this instead of duplicating sync logic.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use kaijutsu_crdt::ContextId;
 use kaijutsu_crdt::block_store::BlockStore as CrdtBlockStore;
 use kaijutsu_types::{BlockId, BlockSnapshot, PrincipalId};
-use tracing::{debug, info, warn};
+use tracing::{debug, info, trace, warn};
 
 use crate::rpc::SyncState;
 use crate::subscriptions::ServerEvent;
-use crate::sync::{SyncError, SyncManager};
+use crate::sync::{QuarantinedPayload, SyncChanges, SyncError, SyncManager};
 
 /// Result of applying an event to a [`SyncedDocument`].
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,6 +28,17 @@ pub enum SyncEffect {
     NeedsResync,
 }
 
+/// Where a document stands in the server's `BlockTextOps` stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TextSeqCursor {
+    generation: u64,
+    /// Lowest seq not yet merged.
+    next_seq: u64,
+    /// Seqs above `next_seq` already merged, for events that arrive out of
+    /// order: a seq is skipped only when it is an exact repeat.
+    ahead: BTreeSet<u64>,
+}
+
 /// A CRDT document with integrated sync state.
 ///
 /// Wraps [`CrdtBlockStore`] + [`SyncManager`] so consumers don't need to know
@@ -37,6 +48,10 @@ pub enum SyncEffect {
 /// Handles out-of-order event delivery: if `BlockTextOps`/`BlockStatusChanged`/etc.
 /// arrive before `BlockInserted` for the same block (due to cross-topic FlowBus
 /// ordering), they are buffered and replayed when the insert arrives.
+///
+/// Skips `BlockTextOps` it has already merged: each carries a
+/// `(generation, seq_num)` pair, and a resync records the server's resume
+/// point, so events replayed after a reconnect are dropped before decoding.
 pub struct SyncedDocument {
     doc: CrdtBlockStore,
     sync: SyncManager,
@@ -44,6 +59,8 @@ pub struct SyncedDocument {
     /// Events that arrived before their block's `BlockInserted`.
     /// Keyed by block ID, drained on insert. Bounded to prevent unbounded growth.
     pending_events: HashMap<BlockId, Vec<ServerEvent>>,
+    /// Text-op resume point; `None` until the first sync state or text op.
+    text_seq: Option<TextSeqCursor>,
 }
 
 impl SyncedDocument {
@@ -54,6 +71,10 @@ impl SyncedDocument {
     /// Max events buffered per block — prevents runaway accumulation.
     const MAX_EVENTS_PER_BLOCK: usize = 128;
 
+    /// Max merged seqs held above a gap. Past this the gap is taken as lost
+    /// (a later resync fills it) and the cursor moves up to the held seqs.
+    const MAX_TEXT_SEQS_AHEAD: usize = 1024;
+
     /// Create a new, empty synced document.
     pub fn new(context_id: ContextId, principal_id: PrincipalId) -> Self {
         Self {
@@ -61,6 +82,7 @@ impl SyncedDocument {
             sync: SyncManager::new(),
             context_id,
             pending_events: HashMap::new(),
+            text_seq: None,
         }
     }
 
@@ -70,10 +92,64 @@ impl SyncedDocument {
         if !state.ops.is_empty() {
             sd.sync
                 .apply_initial_state(&mut sd.doc, state.context_id, &state.ops)?;
+            sd.resume_text_seq(state);
         }
         Ok(sd)
     }
 
+    /// Record a `BlockTextOps` seq. `false` if the event is already merged:
+    /// same generation, and below the cursor or seen before. A seq past a
+    /// gap is held until the gap fills, so one delivered late still merges.
+    /// A new generation (the kernel reloaded the context and restarted its
+    /// seqs) replaces the cursor.
+    fn advance_text_seq(&mut self, generation: u64, seq_num: u64) -> bool {
+        match &mut self.text_seq {
+            Some(cursor) if cursor.generation == generation => {
+                if seq_num < cursor.next_seq || !cursor.ahead.insert(seq_num) {
+                    return false;
+                }
+                if cursor.ahead.len() > Self::MAX_TEXT_SEQS_AHEAD
+                    && let Some(lowest) = cursor.ahead.first().copied()
+                {
+                    cursor.next_seq = lowest;
+                }
+                while cursor.ahead.remove(&cursor.next_seq) {
+                    cursor.next_seq += 1;
+                }
+            }
+            slot => {
+                *slot = Some(TextSeqCursor {
+                    generation,
+                    next_seq: seq_num + 1,
+                    ahead: BTreeSet::new(),
+                });
+            }
+        }
+        true
+    }
+
+    /// Move the cursor to a sync state's resume point. Never moves it back
+    /// within a generation: text ops applied while the fetch was in flight
+    /// stay merged.
+    fn resume_text_seq(&mut self, state: &SyncState) {
+        let mut cursor = match self.text_seq.take() {
+            Some(cursor) if cursor.generation == state.generation => cursor,
+            _ => TextSeqCursor {
+                generation: state.generation,
+                next_seq: state.seq_num,
+                ahead: BTreeSet::new(),
+            },
+        };
+        if state.seq_num > cursor.next_seq {
+            cursor.next_seq = state.seq_num;
+            cursor.ahead = cursor.ahead.split_off(&state.seq_num);
+        }
+        while cursor.ahead.remove(&cursor.next_seq) {
+            cursor.next_seq += 1;
+        }
+        self.text_seq = Some(cursor);
+    }
+
     /// Extract the block ID targeted by an event, if it's a per-block event
     /// (not `BlockInserted`, `SyncReset`, or resource/input events).
     fn event_block_id(event: &ServerEvent) -> Option<BlockId> {
@@ -107,7 +183,10 @@ impl SyncedDocument {
             | ServerEvent::InputCleared { context_id, .. }
             | ServerEvent::ContextSwitched { context_id, .. }
             | ServerEvent::RenderCue { context_id, .. }
-            | ServerEvent::BeatSync { context_id, .. } => Some(*context_id),
+            | ServerEvent::BeatSync { context_id, .. }
+            | ServerEvent::ContextModelChanged { context_id, .. }
+            | ServerEvent::BlockLockChanged { context_id, .. }
+            | ServerEvent::ContextUnhealthy { context_id, .. } => Some(*context_id),
             // Editor events are session-scoped, not context-scoped — the
             // editor renders off its own subscription, not the doc cache.
             // A post-reconnect resync delivery names its target context inline.
@@ -118,6 +197,9 @@ impl SyncedDocument {
             | ServerEvent::EditorStateChanged { .. }
             | ServerEvent::EditorClosed { .. }
             | ServerEvent::VfsActivity { .. }
+            | ServerEvent::ContextActivity { .. }
+            | ServerEvent::InboxItem { .. }
+            | ServerEvent::ConfigApplied { .. }
             | ServerEvent::Reconnected => None,
         }
     }
@@ -194,6 +276,13 @@ impl SyncedDocument {
         self.sync.version()
     }
 
+    /// Take the per-block changes applied since the last drain (see
+    /// [`SyncManager::drain_changes`]). Local authoring through
+    /// [`doc_mut`](Self::doc_mut) isn't tracked — the author already knows.
+    pub fn drain_changes(&mut self) -> SyncChanges {
+        self.sync.drain_changes()
+    }
+
     /// Whether we're in a synced state (not waiting for full resync).
     pub fn is_synced(&self) -> bool {
         !self.sync.needs_full_sync(self.context_id)
@@ -212,7 +301,8 @@ impl SyncedDocument {
     ///
     /// Handles all CRDT-relevant event variants internally:
     /// - `BlockInserted` → SyncManager insert (full or incremental) + replay buffered
-    /// - `BlockTextOps` → SyncManager text merge (buffered if block unknown)
+    /// - `BlockTextOps` → SyncManager text merge (skipped if already merged,
+    ///   buffered if block unknown)
     /// - `BlockStatusChanged` → direct doc mutation (buffered if block unknown)
     /// - `BlockDeleted` → direct doc mutation
     /// - `BlockCollapsedChanged` → direct doc mutation (buffered if block unknown)
@@ -221,6 +311,19 @@ impl SyncedDocument {
     /// - `SyncReset` → returns `NeedsResync`, clears pending buffer
     /// - Resource events → `Ignored`
     pub fn apply_event(&mut self, event: &ServerEvent) -> SyncEffect {
+        if let ServerEvent::BlockTextOps {
+            context_id,
+            seq_num,
+            generation,
+            ..
+        } = event
+            && *context_id == self.context_id
+            && !self.advance_text_seq(*generation, *seq_num)
+        {
+            trace!(seq_num, generation, "SyncedDocument: skipping already-merged text ops");
+            return SyncEffect::Ignored;
+        }
+
         // For per-block events (not BlockInserted), check if the block exists.
         // If not, buffer the event — it arrived before its BlockInserted due to
         // cross-topic FlowBus ordering. Only buffer events for our context.
@@ -454,7 +557,18 @@ impl SyncedDocument {
             | ServerEvent::RenderCue { .. }
             | ServerEvent::BeatSync { .. }
             // VFS activity is decorative world-rendering heat, not doc state.
-            | ServerEvent::VfsActivity { .. } => SyncEffect::Ignored,
+            | ServerEvent::VfsActivity { .. }
+            // Activity metrics and health findings drive rendering and
+            // alerts, not doc state.
+            | ServerEvent::ContextActivity { .. }
+            | ServerEvent::ContextUnhealthy { .. }
+            // Inbox items are per-principal, not doc state.
+            | ServerEvent::InboxItem { .. }
+            // Config re-applies are kernel-wide, not doc state.
+            | ServerEvent::ConfigApplied { .. }
+            // The switch's notification block arrives as its own insert.
+            | ServerEvent::ContextModelChanged { .. }
+            | ServerEvent::BlockLockChanged { .. } => SyncEffect::Ignored,
         }
     }
 
@@ -484,6 +598,7 @@ impl SyncedDocument {
         let result = self
             .sync
             .apply_initial_state(&mut self.doc, state.context_id, &state.ops)?;
+        self.resume_text_seq(state);
         match result {
             crate::sync::SyncResult::FullSync { block_count } => {
                 self.context_id = state.context_id;
@@ -515,6 +630,17 @@ impl SyncedDocument {
         self.sync.reset_frontier();
     }
 
+    /// Incoming payloads that failed to apply and are held in quarantine.
+    pub fn quarantine(&self) -> &[QuarantinedPayload] {
+        self.sync.quarantine()
+    }
+
+    /// Drop the quarantined payloads and force a full resync; returns how
+    /// many were dropped. Follow with `get_context_sync` + `apply_sync_state`.
+    pub fn resolve_quarantine(&mut self) -> usize {
+        self.sync.resolve_quarantine()
+    }
+
     // =========================================================================
     // Escape hatches — for consumers that need internals
     // =========================================================================
@@ -598,6 +724,10 @@ mod tests {
             context_id: ctx,
 
             version: 1,
+
+            generation: 0,
+
+            seq_num: 0,
             ops: snap,
         };
 
@@ -617,6 +747,10 @@ mod tests {
             context_id: ctx,
 
             version: 1,
+
+            generation: 0,
+
+            seq_num: 0,
             ops: initial_snap,
         };
         let mut sd = SyncedDocument::from_sync_state(&state, test_principal_id()).unwrap();
@@ -657,6 +791,8 @@ mod tests {
         let state = SyncState {
             context_id: ctx,
             version: 1,
+            generation: 0,
+            seq_num: 0,
             ops: snapshot_bytes(&server),
         };
         let mut sd = SyncedDocument::from_sync_state(&state, test_principal_id()).unwrap();
@@ -700,6 +836,10 @@ mod tests {
             context_id: ctx,
 
             version: 1,
+
+            generation: 0,
+
+            seq_num: 0,
             ops: snap,
         };
         let mut sd = SyncedDocument::from_sync_state(&state, test_principal_id()).unwrap();
@@ -725,6 +865,8 @@ mod tests {
         let state = SyncState {
             context_id: ctx,
             version: 1,
+            generation: 0,
+            seq_num: 0,
             ops: snapshot_bytes(&server),
         };
         let mut sd = SyncedDocument::from_sync_state(&state, test_principal_id()).unwrap();
@@ -771,6 +913,8 @@ mod tests {
         let recovered = SyncState {
             context_id: ctx,
             version: 2,
+            generation: 0,
+            seq_num: 0,
             ops: snapshot_bytes(&server),
         };
         let effect = sd.apply_sync_state(&recovered).unwrap();
@@ -795,6 +939,8 @@ mod tests {
         let state = SyncState {
             context_id: ctx,
             version: 1,
+            generation: 0,
+            seq_num: 0,
             ops: snapshot_bytes(&server),
         };
         let mut sd = SyncedDocument::from_sync_state(&state, test_principal_id()).unwrap();
@@ -826,6 +972,8 @@ mod tests {
         let fresh = SyncState {
             context_id: ctx,
             version: 2,
+            generation: 0,
+            seq_num: 0,
             ops: snapshot_bytes(&server),
         };
         let effect = sd.apply_sync_state(&fresh).unwrap();
@@ -851,6 +999,10 @@ mod tests {
             context_id: ctx,
 
             version: 1,
+
+            generation: 0,
+
+            seq_num: 0,
             ops: initial_snap,
         };
         let mut sd = SyncedDocument::from_sync_state(&state, test_principal_id()).unwrap();
@@ -874,6 +1026,10 @@ mod tests {
             context_id: ctx,
 
             version: 2,
+
+            generation: 0,
+
+            seq_num: 0,
             ops: updated_snap,
         };
         let effect = sd.apply_sync_state(&updated_state).unwrap();
@@ -895,6 +1051,10 @@ mod tests {
             context_id: ctx,
 
             version: 1,
+
+            generation: 0,
+
+            seq_num: 0,
             ops: snap,
         };
 
@@ -974,6 +1134,10 @@ mod tests {
             context_id: ctx,
 
             version: 1,
+
+            generation: 0,
+
+            seq_num: 0,
             ops: snap,
         };
         let mut sd = SyncedDocument::from_sync_state(&state, client_agent).unwrap();
@@ -1080,6 +1244,8 @@ mod tests {
         let state = SyncState {
             context_id: ctx,
             version: 1,
+            generation: 0,
+            seq_num: 0,
             ops: initial_frontier,
         };
         let mut sd = SyncedDocument::from_sync_state(&state, test_principal_id()).unwrap();
@@ -1164,6 +1330,8 @@ mod tests {
             &SyncState {
                 context_id: ctx,
                 version: 1,
+                generation: 0,
+                seq_num: 0,
                 ops: initial,
             },
             test_principal_id(),
@@ -1236,6 +1404,10 @@ mod tests {
             context_id: ctx,
 
             version: 1,
+
+            generation: 0,
+
+            seq_num: 0,
             ops: snap,
         };
         let mut sd = SyncedDocument::from_sync_state(&state, client_agent).unwrap();
@@ -1247,6 +1419,7 @@ mod tests {
             block_id: BlockId::new(ctx, PrincipalId::new(), 999),
             ops: vec![0xFF, 0xFE, 0xFD],
             seq_num: 0,
+            generation: 0,
         };
         let effect = sd.apply_event(&corrupt_event);
         // Should still return Updated (error is logged but doesn't crash)
@@ -1279,4 +1452,79 @@ mod tests {
         assert!(matches!(effect, SyncEffect::Updated { block_count: 2 }));
         assert!(sd.version() > v1, "version should have advanced");
     }
+
+    /// Text ops replayed after a reconnect (same generation, below the
+    /// resume point) are dropped; a new generation starts a fresh cursor.
+    #[test]
+    fn test_replayed_text_ops_skipped() {
+        let ctx = test_context_id();
+        let server = create_server_store(ctx);
+        let state = SyncState {
+            context_id: ctx,
+            version: 1,
+            generation: 3,
+            seq_num: 5,
+            ops: snapshot_bytes(&server),
+        };
+        let mut sd = SyncedDocument::from_sync_state(&state, test_principal_id()).unwrap();
+
+        // Unknown block, so anything not skipped lands in the pending buffer.
+        let block_id = BlockId::new(ctx, PrincipalId::new(), 999);
+        let text_ops = |generation, seq_num| ServerEvent::BlockTextOps {
+            context_id: ctx,
+            block_id,
+            ops: vec![0xFF],
+            seq_num,
+            generation,
+        };
+
+        assert!(matches!(sd.apply_event(&text_ops(3, 4)), SyncEffect::Ignored));
+        assert!(sd.pending_events.is_empty());
+
+        assert!(!matches!(sd.apply_event(&text_ops(3, 5)), SyncEffect::Ignored));
+        assert!(matches!(sd.apply_event(&text_ops(3, 5)), SyncEffect::Ignored));
+        assert_eq!(sd.pending_events[&block_id].len(), 1);
+
+        // Kernel reloaded the context: seqs restart under a new generation.
+        assert!(!matches!(sd.apply_event(&text_ops(4, 0)), SyncEffect::Ignored));
+        assert_eq!(sd.pending_events[&block_id].len(), 2);
+    }
+
+    /// Two writers' text ops can reach a client out of seq order. The late,
+    /// lower seq still merges; only exact repeats are skipped.
+    #[test]
+    fn test_out_of_order_text_ops_not_dropped() {
+        let ctx = test_context_id();
+        let server = create_server_store(ctx);
+        let state = SyncState {
+            context_id: ctx,
+            version: 1,
+            generation: 0,
+            seq_num: 0,
+            ops: snapshot_bytes(&server),
+        };
+        let mut sd = SyncedDocument::from_sync_state(&state, test_principal_id()).unwrap();
+
+        // Unknown block, so anything not skipped lands in the pending buffer.
+        let block_id = BlockId::new(ctx, PrincipalId::new(), 999);
+        let text_ops = |seq_num| ServerEvent::BlockTextOps {
+            context_id: ctx,
+            block_id,
+            ops: vec![0xFF],
+            seq_num,
+            generation: 0,
+        };
+
+        assert!(!matches!(sd.apply_event(&text_ops(1)), SyncEffect::Ignored));
+        assert!(!matches!(sd.apply_event(&text_ops(0)), SyncEffect::Ignored));
+        assert_eq!(sd.pending_events[&block_id].len(), 2, "seq 0 merged late");
+
+        assert!(matches!(sd.apply_event(&text_ops(0)), SyncEffect::Ignored));
+        assert!(matches!(sd.apply_event(&text_ops(1)), SyncEffect::Ignored));
+        assert!(!matches!(sd.apply_event(&text_ops(3)), SyncEffect::Ignored));
+        assert!(!matches!(sd.apply_event(&text_ops(2)), SyncEffect::Ignored));
+        assert!(matches!(sd.apply_event(&text_ops(3)), SyncEffect::Ignored));
+        assert_eq!(sd.pending_events[&block_id].len(), 4);
+        assert_eq!(sd.text_seq.as_ref().map(|c| c.next_seq), Some(4));
+    }
 }
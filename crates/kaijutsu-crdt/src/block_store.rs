@@ -195,6 +195,14 @@ impl BlockStore {
             .map(|b| b.snapshot())
     }
 
+    /// Get a live block's header — its metadata without the text.
+    pub fn get_block_header(&self, id: &BlockId) -> Option<BlockHeader> {
+        self.blocks
+            .get(id)
+            .filter(|b| !b.is_deleted())
+            .map(|b| *b.header())
+    }
+
     /// Get block IDs in document order (sorted by order_key, BlockId tiebreak).
     pub fn block_ids_ordered(&self) -> Vec<BlockId> {
         let mut ordered: Vec<_> = self
@@ -207,6 +215,27 @@ impl BlockStore {
         ordered.into_iter().map(|(_, id)| id).collect()
     }
 
+    /// Label → block for live labeled blocks (see
+    /// [`kaijutsu_types::BlockSnapshot::label`]). Labels are unique per
+    /// document, but concurrent replicas can each claim one; on conflict the
+    /// block earliest in document order holds the label, so every replica
+    /// agrees without a merge step.
+    pub fn label_index(&self) -> BTreeMap<String, BlockId> {
+        let mut index = BTreeMap::new();
+        for id in self.block_ids_ordered() {
+            if let Some(label) = self.blocks.get(&id).and_then(|b| b.label()) {
+                index.entry(label.to_string()).or_insert(id);
+            }
+        }
+        index
+    }
+
+    /// The live block holding `label`, resolved as in
+    /// [`label_index`](Self::label_index).
+    pub fn block_by_label(&self, label: &str) -> Option<BlockId> {
+        self.label_index().remove(label)
+    }
+
     /// The maximum `Tick` over live blocks, or `None` if none carry one. A direct
     /// O(N) scan — no ordering sort, no snapshot allocation — for the re-arm
     /// playhead seed (the ordered/allocating `blocks_ordered` path is gratuitous
@@ -227,6 +256,15 @@ impl BlockStore {
             .collect()
     }
 
+    /// Live block statuses in document order — `blocks_ordered` without
+    /// snapshotting every block's text, for callers that only reduce status.
+    pub fn statuses_ordered(&self) -> Vec<Status> {
+        self.block_ids_ordered()
+            .iter()
+            .filter_map(|id| self.blocks.get(id).map(|b| b.header().status))
+            .collect()
+    }
+
     /// Get full text content (concatenation of all blocks).
     pub fn full_text(&self) -> String {
         self.blocks_ordered()
@@ -1072,6 +1110,53 @@ impl BlockStore {
         Ok(())
     }
 
+    /// Set the source language of a block's text (see
+    /// [`kaijutsu_types::BlockSnapshot::language`]). No LWW clock; the value
+    /// is replicated via `MetadataChanged` / snapshot, like `stderr`.
+    pub fn set_language(&mut self, id: &BlockId, language: Option<String>) -> Result<()> {
+        let block = self
+            .blocks
+            .get_mut(id)
+            .filter(|b| !b.is_deleted())
+            .ok_or(CrdtError::BlockNotFound(*id))?;
+        block.set_language(language);
+        self.version += 1;
+        Ok(())
+    }
+
+    /// Set a block's human-readable label (see
+    /// [`kaijutsu_types::BlockSnapshot::label`]). Raw write: no validation
+    /// or uniqueness check, so replica applies never fail — the kernel
+    /// validates before calling this. Replicated like `language`.
+    pub fn set_label(&mut self, id: &BlockId, label: Option<String>) -> Result<()> {
+        let block = self
+            .blocks
+            .get_mut(id)
+            .filter(|b| !b.is_deleted())
+            .ok_or(CrdtError::BlockNotFound(*id))?;
+        block.set_label(label);
+        self.version += 1;
+        Ok(())
+    }
+
+    /// Set a block's preferred word wrap (see
+    /// [`kaijutsu_types::BlockSnapshot::reflow`]). Metadata only — the text
+    /// is untouched. Replicated like `language`.
+    pub fn set_reflow(
+        &mut self,
+        id: &BlockId,
+        reflow: Option<kaijutsu_types::Reflow>,
+    ) -> Result<()> {
+        let block = self
+            .blocks
+            .get_mut(id)
+            .filter(|b| !b.is_deleted())
+            .ok_or(CrdtError::BlockNotFound(*id))?;
+        block.set_reflow(reflow);
+        self.version += 1;
+        Ok(())
+    }
+
     /// Set the LLM-assigned tool invocation ID on a block.
     pub fn set_tool_use_id(&mut self, id: &BlockId, tool_use_id: Option<String>) -> Result<()> {
         let block = self
@@ -1084,6 +1169,62 @@ impl BlockStore {
         Ok(())
     }
 
+    /// Record resolved `@`-mentions on a block (user blocks). Write-once —
+    /// call it before the block's creation ops are taken so the mentions
+    /// ride the `new_blocks` snapshot; there is no LWW clock to carry a later
+    /// change. See [`kaijutsu_types::BlockSnapshot::mentions`].
+    pub fn set_mentions(
+        &mut self,
+        id: &BlockId,
+        mentions: Vec<kaijutsu_types::BlockMention>,
+    ) -> Result<()> {
+        let block = self
+            .blocks
+            .get_mut(id)
+            .filter(|b| !b.is_deleted())
+            .ok_or(CrdtError::BlockNotFound(*id))?;
+        block.set_mentions(mentions);
+        self.version += 1;
+        Ok(())
+    }
+
+    /// Record how a shell command block ran. Write-once, like
+    /// [`set_mentions`](Self::set_mentions) — call it before the block's
+    /// creation ops are taken. See [`kaijutsu_types::BlockSnapshot::shell_command`].
+    pub fn set_shell_command(
+        &mut self,
+        id: &BlockId,
+        command: kaijutsu_types::ShellCommand,
+    ) -> Result<()> {
+        let block = self
+            .blocks
+            .get_mut(id)
+            .filter(|b| !b.is_deleted())
+            .ok_or(CrdtError::BlockNotFound(*id))?;
+        block.set_shell_command(command);
+        self.version += 1;
+        Ok(())
+    }
+
+    /// Record the agent session that authored a block. Write-once like
+    /// [`set_shell_command`](Self::set_shell_command) — call it before the
+    /// block's creation ops are taken. See
+    /// [`kaijutsu_types::BlockSnapshot::provenance`].
+    pub fn set_provenance(
+        &mut self,
+        id: &BlockId,
+        provenance: kaijutsu_types::AgentProvenance,
+    ) -> Result<()> {
+        let block = self
+            .blocks
+            .get_mut(id)
+            .filter(|b| !b.is_deleted())
+            .ok_or(CrdtError::BlockNotFound(*id))?;
+        block.set_provenance(provenance);
+        self.version += 1;
+        Ok(())
+    }
+
     /// Set the reasoning-continuity token on a block (Thinking blocks).
     /// Write-once at `ThinkingEnd`; replicated via snapshot. See
     /// [`kaijutsu_types::BlockSnapshot::signature`].
@@ -1181,6 +1322,67 @@ impl BlockStore {
         Ok(())
     }
 
+    /// Reorder a block among its siblings without reparenting.
+    ///
+    /// `after` must share the block's `parent_id`; `None` places the block
+    /// ahead of its first sibling. Like `move_block`, only the order_key
+    /// changes, so concurrent reorders converge by key comparison.
+    ///
+    /// Returns the document-order anchor the block was moved after — the
+    /// value peers need to replay the move via `move_block`.
+    pub fn reorder_block(
+        &mut self,
+        id: &BlockId,
+        after: Option<&BlockId>,
+    ) -> Result<Option<BlockId>> {
+        let parent = match self.blocks.get(id) {
+            Some(b) if !b.is_deleted() => b.header().parent_id,
+            _ => return Err(CrdtError::BlockNotFound(*id)),
+        };
+
+        let ordered: Vec<BlockId> = self.block_ids_ordered();
+        // The block's current predecessor — the anchor for a no-op reorder.
+        let in_place = ordered
+            .iter()
+            .position(|bid| bid == id)
+            .and_then(|idx| idx.checked_sub(1))
+            .map(|i| ordered[i]);
+        let anchor = match after {
+            Some(after_id) if after_id != id => {
+                match self.blocks.get(after_id) {
+                    Some(b) if !b.is_deleted() => {
+                        if b.header().parent_id != parent {
+                            return Err(CrdtError::NotSibling {
+                                block: *id,
+                                after: *after_id,
+                            });
+                        }
+                    }
+                    _ => return Err(CrdtError::InvalidReference(*after_id)),
+                }
+                Some(*after_id)
+            }
+            // Reordering after itself: stay put.
+            Some(_) => in_place,
+            None => {
+                // Front of the sibling group: land right after whatever precedes
+                // the first sibling in document order (the parent, for children).
+                let others: Vec<BlockId> = ordered.into_iter().filter(|bid| bid != id).collect();
+                let first = others
+                    .iter()
+                    .position(|bid| self.blocks[bid].header().parent_id == parent);
+                match first {
+                    Some(idx) => idx.checked_sub(1).map(|i| others[i]),
+                    // No other siblings — keep the block where it sits.
+                    None => in_place,
+                }
+            }
+        };
+
+        self.move_block(id, anchor.as_ref())?;
+        Ok(anchor)
+    }
+
     // =========================================================================
     // Sync Operations
     // =========================================================================
@@ -1238,6 +1440,29 @@ impl BlockStore {
         }
     }
 
+    /// Operations for one known block since `frontier`: its DTE delta plus
+    /// its current header. The per-block counterpart of
+    /// [`ops_since`](Self::ops_since) for a local text edit — the cost is one
+    /// block, not the whole store, so a streaming append stays O(1) in the
+    /// document's size. Empty for an unknown or deleted block.
+    pub fn block_ops_since(&self, id: &BlockId, frontier: &Frontier) -> SyncPayload {
+        let mut block_ops = Vec::new();
+        let mut updated_headers = Vec::new();
+        if let Some(block) = self.blocks.get(id).filter(|b| !b.is_deleted()) {
+            let ops = block.ops_since(frontier);
+            if !ops.is_empty() {
+                block_ops.push((*id, ops));
+            }
+            updated_headers.push(*block.header());
+        }
+        SyncPayload {
+            block_ops,
+            new_blocks: Vec::new(),
+            updated_headers,
+            deleted_blocks: Vec::new(),
+        }
+    }
+
     /// Merge a sync payload from a remote peer.
     pub fn merge_ops(&mut self, payload: SyncPayload) -> Result<()> {
         // Track max remote Lamport timestamp for clock advancement
@@ -1355,6 +1580,11 @@ impl BlockStore {
             .collect()
     }
 
+    /// Frontier of a single block's DTE document.
+    pub fn block_frontier(&self, id: &BlockId) -> Option<Frontier> {
+        self.blocks.get(id).map(|block| block.frontier())
+    }
+
     // =========================================================================
     // Fork
     // =========================================================================
@@ -1538,6 +1768,66 @@ impl BlockStore {
         forked
     }
 
+    /// Copy the store's final content into a fresh, compact store — the
+    /// history-rewriting clone (`kj context clone --squash`).
+    ///
+    /// Unlike [`fork`](Self::fork), nothing about how the document was written
+    /// survives: deleted, compacted, and Thinking blocks are dropped, every
+    /// kept block is re-minted under `new_principal_id` with dense seqs, and
+    /// ticks/order keys are renumbered in document order as if the blocks had
+    /// been appended in one pass. A parent that was dropped is replaced by its
+    /// nearest kept ancestor; a dropped tool call clears `tool_call_id`.
+    pub fn squash(&self, new_context_id: ContextId, new_principal_id: PrincipalId) -> Self {
+        let mut squashed = Self::new(new_context_id, new_principal_id);
+
+        let kept: Vec<&BlockContent> = self
+            .block_ids_ordered()
+            .iter()
+            .filter_map(|id| self.blocks.get(id))
+            .filter(|b| {
+                let header = b.header();
+                !header.compacted && header.kind != BlockKind::Thinking
+            })
+            .collect();
+
+        // Ids are minted up front so a parent that sorts after its child
+        // (a moved block) still remaps.
+        let mut id_map: HashMap<BlockId, BlockId> = HashMap::with_capacity(kept.len());
+        for block in &kept {
+            let new_id = squashed.new_block_id();
+            id_map.insert(block.header().id, new_id);
+        }
+
+        for block in kept {
+            let snap = block.snapshot();
+            let mut parent = snap.parent_id;
+            while let Some(pid) = parent
+                && !id_map.contains_key(&pid)
+            {
+                parent = self.blocks.get(&pid).and_then(|b| b.header().parent_id);
+            }
+
+            let tick = squashed.next_tick;
+            squashed.next_tick += 1;
+            let order_key = squashed.order_key_for_tick(tick);
+
+            let mut remapped = snap;
+            remapped.id = id_map[&remapped.id];
+            remapped.parent_id = parent.and_then(|pid| id_map.get(&pid).copied());
+            remapped.tool_call_id = remapped
+                .tool_call_id
+                .and_then(|tcid| id_map.get(&tcid).copied());
+            remapped.tick = Some(Tick::new(tick));
+            remapped.order_key = Some(order_key.clone());
+
+            let content = BlockContent::from_snapshot(&remapped, new_principal_id, order_key);
+            squashed.blocks.insert(remapped.id, content);
+        }
+
+        squashed.version = 1;
+        squashed
+    }
+
     // =========================================================================
     // Snapshot / Restore
     // =========================================================================
@@ -2098,6 +2388,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_set_language_rides_new_block_snapshot() {
+        let ctx = ContextId::new();
+        let mut store1 = BlockStore::new(ctx, PrincipalId::new());
+        let mut store2 = BlockStore::new(ctx, PrincipalId::new());
+        let id = store1
+            .insert_block(
+                None,
+                None,
+                Role::Model,
+                BlockKind::Text,
+                "fn main() {}",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        assert_eq!(store1.get_block_snapshot(&id).unwrap().language, None);
+        store1.set_language(&id, Some("rust".to_string())).unwrap();
+
+        store2.merge_ops(store1.ops_since(&HashMap::new())).unwrap();
+        assert_eq!(
+            store2.get_block_snapshot(&id).unwrap().language.as_deref(),
+            Some("rust")
+        );
+    }
+
+    #[test]
+    fn test_label_index_first_in_document_order_wins() {
+        let mut store = test_store();
+        let insert = |store: &mut BlockStore, after: Option<&BlockId>, text: &str| {
+            store
+                .insert_block(
+                    None,
+                    after,
+                    Role::User,
+                    BlockKind::Text,
+                    text,
+                    Status::Done,
+                    ContentType::Plain,
+                )
+                .unwrap()
+        };
+        let first = insert(&mut store, None, "first");
+        let second = insert(&mut store, Some(&first), "second");
+        store.set_label(&second, Some("intro".to_string())).unwrap();
+        assert_eq!(store.block_by_label("intro"), Some(second));
+
+        // A concurrent claim on an earlier block takes the label.
+        store.set_label(&first, Some("intro".to_string())).unwrap();
+        assert_eq!(store.block_by_label("intro"), Some(first));
+        assert_eq!(store.label_index().len(), 1);
+
+        store.set_label(&first, None).unwrap();
+        assert_eq!(store.block_by_label("intro"), Some(second));
+        assert_eq!(store.block_by_label("missing"), None);
+    }
+
     #[test]
     fn test_drift_block() {
         let mut store = test_store();
@@ -2224,6 +2571,67 @@ mod tests {
         assert_eq!(ordered[2].id, b);
     }
 
+    #[test]
+    fn test_reorder_block_among_siblings() {
+        let mut store = test_store();
+        let parent = store
+            .insert_block(
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                "Question",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let a = store
+            .insert_block(
+                Some(&parent),
+                Some(&parent),
+                Role::Model,
+                BlockKind::Text,
+                "A",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let b = store
+            .insert_block(
+                Some(&parent),
+                Some(&a),
+                Role::Model,
+                BlockKind::Text,
+                "B",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let c = store
+            .insert_block(
+                Some(&parent),
+                Some(&b),
+                Role::Model,
+                BlockKind::Text,
+                "C",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+
+        // Front of the sibling group stays behind the parent.
+        assert_eq!(store.reorder_block(&c, None).unwrap(), Some(parent));
+        assert_eq!(store.block_ids_ordered(), vec![parent, c, a, b]);
+
+        store.reorder_block(&c, Some(&b)).unwrap();
+        assert_eq!(store.block_ids_ordered(), vec![parent, a, b, c]);
+
+        // The parent is not a sibling — reorder never reparents.
+        let err = store.reorder_block(&a, Some(&parent)).unwrap_err();
+        assert!(matches!(err, CrdtError::NotSibling { .. }));
+        assert_eq!(store.get_block_snapshot(&a).unwrap().parent_id, Some(parent));
+    }
+
     #[test]
     fn test_dag_operations() {
         let mut store = test_store();
@@ -2387,6 +2795,75 @@ mod tests {
         assert_eq!(blocks[0].id.principal_id, original_agent);
     }
 
+    #[test]
+    fn test_squash_rewrites_history() {
+        let mut original = test_store();
+
+        let user_msg = original
+            .insert_block(
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                "Hello",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let thinking = original
+            .insert_block(
+                Some(&user_msg),
+                Some(&user_msg),
+                Role::Model,
+                BlockKind::Thinking,
+                "private reasoning",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let scratch = original
+            .insert_block(
+                Some(&user_msg),
+                Some(&thinking),
+                Role::Model,
+                BlockKind::Text,
+                "draft",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let answer = original
+            .insert_block(
+                Some(&thinking),
+                Some(&scratch),
+                Role::Model,
+                BlockKind::Text,
+                "Hi",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        original.edit_text(&answer, 2, " there", 0).unwrap();
+        original.delete_block(&scratch).unwrap();
+
+        let ctx = ContextId::new();
+        let agent = PrincipalId::new();
+        let squashed = original.squash(ctx, agent);
+
+        let blocks = squashed.blocks_ordered();
+        let contents: Vec<_> = blocks.iter().map(|b| b.content.as_str()).collect();
+        assert_eq!(contents, ["Hello", "Hi there"]);
+
+        // One authoring pass: dense seqs and ticks under the new principal.
+        for (i, block) in blocks.iter().enumerate() {
+            assert_eq!(block.id, BlockId::new(ctx, agent, i as u64));
+            assert_eq!(block.tick, Some(Tick::new(i as i64)));
+        }
+        // The answer's Thinking parent was dropped; it hangs off the user turn.
+        assert_eq!(blocks[1].parent_id, Some(blocks[0].id));
+        assert_eq!(squashed.version(), 1);
+    }
+
     #[test]
     fn test_insert_from_snapshot() {
         let mut store = test_store();
@@ -3187,6 +3664,60 @@ mod tests {
         assert_eq!(snap.content, "Hello");
     }
 
+    #[test]
+    fn test_block_ops_since_carries_only_that_block() {
+        let ctx = ContextId::new();
+        let mut store1 = BlockStore::new(ctx, PrincipalId::new());
+        let mut store2 = BlockStore::new(ctx, PrincipalId::new());
+
+        let a = store1
+            .insert_block(
+                None,
+                None,
+                Role::Model,
+                BlockKind::Text,
+                "a",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let b = store1
+            .insert_block(
+                None,
+                Some(&a),
+                Role::Model,
+                BlockKind::Text,
+                "b",
+                Status::Running,
+                ContentType::Plain,
+            )
+            .unwrap();
+        store2.merge_ops(store1.ops_since(&HashMap::new())).unwrap();
+
+        let frontier = store1.block_frontier(&b).unwrap();
+        store1.append_text(&b, "ee").unwrap();
+        let payload = store1.block_ops_since(&b, &frontier);
+        assert_eq!(payload.block_ops.len(), 1);
+        assert_eq!(payload.block_ops[0].0, b);
+        assert_eq!(payload.updated_headers.len(), 1);
+        assert!(payload.new_blocks.is_empty() && payload.deleted_blocks.is_empty());
+
+        store2.merge_ops(payload).unwrap();
+        assert_eq!(store2.get_block_snapshot(&b).unwrap().content, "bee");
+        assert_eq!(store2.get_block_snapshot(&a).unwrap().content, "a");
+        assert_eq!(
+            store2.statuses_ordered(),
+            vec![Status::Done, Status::Running]
+        );
+
+        let unknown = BlockId::new(ctx, PrincipalId::new(), 0);
+        assert!(
+            store1
+                .block_ops_since(&unknown, &Frontier::root())
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_snapshot_cbor_roundtrip() {
         let mut store = test_store();
@@ -205,6 +205,15 @@ pub struct BlockContent {
     /// no track.
     track: Option<kaijutsu_types::TrackId>,
 
+    /// Resolved `@`-mentions (user blocks). Write-once at creation.
+    mentions: Vec<kaijutsu_types::BlockMention>,
+
+    /// How a shell command block ran. Write-once at creation.
+    shell_command: Option<kaijutsu_types::ShellCommand>,
+
+    /// Hosting agent session that authored the block. Write-once at creation.
+    provenance: Option<kaijutsu_types::AgentProvenance>,
+
     /// Non-Copy snapshot fields that don't belong on BlockHeader.
     /// These are write-once metadata set at creation time.
     tool_name: Option<String>,
@@ -215,6 +224,15 @@ pub struct BlockContent {
     /// Standard error stream, persisted separately from `content` (stdout).
     /// Set once at tool completion via [`set_stderr`](Self::set_stderr).
     stderr: Option<String>,
+    /// Source language for highlighting/fencing. Set via
+    /// [`set_language`](Self::set_language); no LWW clock.
+    language: Option<String>,
+    /// Human-readable anchor, unique per document. Set via
+    /// [`set_label`](Self::set_label); no LWW clock.
+    label: Option<String>,
+    /// Preferred word wrap for the block's prose. Set via
+    /// [`set_reflow`](Self::set_reflow); no LWW clock.
+    reflow: Option<kaijutsu_types::Reflow>,
     /// Reasoning-continuity token for Thinking blocks. Set once at
     /// `ThinkingEnd` via [`set_signature`](Self::set_signature). See
     /// [`kaijutsu_types::BlockSnapshot::signature`].
@@ -265,12 +283,18 @@ impl BlockContent {
             order_key,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             tool_name: None,
             tool_input: None,
             tool_call_id: None,
             tool_use_id: None,
             output: None,
             stderr: None,
+            language: None,
+            label: None,
+            reflow: None,
             signature: None,
             source_context: None,
             source_model: None,
@@ -322,12 +346,18 @@ impl BlockContent {
         // Lane identity rides through restore with the block (write-once); the
         // author stays `BlockId.principal_id`, never derived from track.
         block.track = snap.track.clone();
+        block.mentions = snap.mentions.clone();
+        block.shell_command = snap.shell_command.clone();
+        block.provenance = snap.provenance.clone();
         block.tool_name = snap.tool_name.clone();
         block.tool_input = snap.tool_input.clone();
         block.tool_call_id = snap.tool_call_id;
         block.tool_use_id = snap.tool_use_id.clone();
         block.output = snap.output.clone();
         block.stderr = snap.stderr.clone();
+        block.language = snap.language.clone();
+        block.label = snap.label.clone();
+        block.reflow = snap.reflow;
         block.signature = snap.signature.clone();
         block.source_context = snap.source_context;
         block.source_model = snap.source_model.clone();
@@ -368,12 +398,18 @@ impl BlockContent {
             order_key,
             tick: snap.tick,
             track: snap.track.clone(),
+            mentions: snap.mentions.clone(),
+            shell_command: snap.shell_command.clone(),
+            provenance: snap.provenance.clone(),
             tool_name: snap.tool_name.clone(),
             tool_input: snap.tool_input.clone(),
             tool_call_id: snap.tool_call_id,
             tool_use_id: snap.tool_use_id.clone(),
             output: snap.output.clone(),
             stderr: snap.stderr.clone(),
+            language: snap.language.clone(),
+            label: snap.label.clone(),
+            reflow: snap.reflow,
             signature: snap.signature.clone(),
             source_context: snap.source_context,
             source_model: snap.source_model.clone(),
@@ -525,6 +561,30 @@ impl BlockContent {
         self.stderr = stderr;
     }
 
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language;
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    pub fn reflow(&self) -> Option<kaijutsu_types::Reflow> {
+        self.reflow
+    }
+
+    pub fn set_reflow(&mut self, reflow: Option<kaijutsu_types::Reflow>) {
+        self.reflow = reflow;
+    }
+
     pub fn signature(&self) -> Option<&str> {
         self.signature.as_deref()
     }
@@ -534,6 +594,33 @@ impl BlockContent {
         self.signature = signature;
     }
 
+    pub fn mentions(&self) -> &[kaijutsu_types::BlockMention] {
+        &self.mentions
+    }
+
+    /// Set the resolved `@`-mentions (write-once at creation).
+    pub fn set_mentions(&mut self, mentions: Vec<kaijutsu_types::BlockMention>) {
+        self.mentions = mentions;
+    }
+
+    pub fn shell_command(&self) -> Option<&kaijutsu_types::ShellCommand> {
+        self.shell_command.as_ref()
+    }
+
+    /// Set the shell command metadata (write-once at creation).
+    pub fn set_shell_command(&mut self, command: kaijutsu_types::ShellCommand) {
+        self.shell_command = Some(command);
+    }
+
+    pub fn provenance(&self) -> Option<&kaijutsu_types::AgentProvenance> {
+        self.provenance.as_ref()
+    }
+
+    /// Set the authoring agent session (write-once at creation).
+    pub fn set_provenance(&mut self, provenance: kaijutsu_types::AgentProvenance) {
+        self.provenance = Some(provenance);
+    }
+
     pub fn source_context(&self) -> Option<crate::ContextId> {
         self.source_context
     }
@@ -679,9 +766,15 @@ impl BlockContent {
             notification: self.notification.clone(),
             resource: self.resource.clone(),
             content_type: self.header.content_type,
+            language: self.language.clone(),
+            label: self.label.clone(),
+            reflow: self.reflow,
             order_key: Some(self.order_key.clone()),
             tick: self.tick,
             track: self.track.clone(),
+            mentions: self.mentions.clone(),
+            shell_command: self.shell_command.clone(),
+            provenance: self.provenance.clone(),
             updated_at: self.header.updated_at,
             status_at: self.header.status_at,
             collapsed_at: self.header.collapsed_at,
@@ -945,6 +1038,23 @@ mod snapshot_threading_tests {
             .content("hello world")
             .tick(Tick::new(10))
             .track(TrackId::new("bass").unwrap())
+            .mentions(vec![kaijutsu_types::BlockMention::context(
+                "review",
+                ContextId::new(),
+            )])
+            .shell_command(kaijutsu_types::ShellCommand {
+                argv: vec!["ls".to_string()],
+                cwd: "/mnt/project".to_string(),
+                env: [("RUST_LOG".to_string(), "debug".to_string())].into(),
+                requested_by: PrincipalId::nil(),
+                rerun_of: None,
+            })
+            .provenance(kaijutsu_types::AgentProvenance {
+                agent: "claude-code".to_string(),
+                version: Some("2.1.3".to_string()),
+                session_id: Some("4f1c2a9b".to_string()),
+                project_dir: Some("/mnt/project".to_string()),
+            })
             .content_type(ContentType::Markdown)
             .tool_name("shell")
             .tool_input("ls")
@@ -1,11 +1,72 @@
 //! Computed DAG index from CRDT data.
 //!
 //! The ConversationDAG provides efficient tree traversal operations
-//! computed from the flat block list in BlockDocument.
+//! computed from the flat block list in BlockDocument, plus a small query
+//! engine ([`DagQuery`]) for extracting conversation slices: ancestors,
+//! descendants, the path between two blocks, or a depth-bounded subtree,
+//! each optionally filtered by kind/role/status.
 
 use std::collections::{HashMap, HashSet};
 
-use crate::{BlockDocument, BlockId, BlockSnapshot, BlockStore, MAX_DAG_DEPTH};
+use crate::{
+    BlockDocument, BlockFilter, BlockId, BlockSnapshot, BlockStore, CrdtError, MAX_DAG_DEPTH,
+    Result,
+};
+
+/// Which part of the DAG a [`DagQuery`] walks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DagSelection {
+    /// Every block, depth-first from the roots.
+    All,
+    /// Parent of the block up to its root (nearest first).
+    Ancestors(BlockId),
+    /// Everything below the block, depth-first, excluding the block itself.
+    Descendants(BlockId),
+    /// From `from` up to the common ancestor and back down to `to`, both
+    /// endpoints included.
+    Path { from: BlockId, to: BlockId },
+    /// The block and everything below it, depth-first, at most `max_depth`
+    /// levels down (`None` = unbounded).
+    Subtree {
+        root: BlockId,
+        max_depth: Option<usize>,
+    },
+}
+
+/// A traversal plus a per-block filter.
+///
+/// The filter's `kinds`/`roles`/`statuses`/`exclude_compacted` prune the
+/// walked blocks and `limit` caps the result; its `parent_id`/`max_depth`
+/// are ignored — use [`DagSelection::Subtree`] instead.
+#[derive(Debug, Clone)]
+pub struct DagQuery {
+    pub selection: DagSelection,
+    pub filter: BlockFilter,
+}
+
+impl DagQuery {
+    /// Query a selection with no filtering.
+    pub fn new(selection: DagSelection) -> Self {
+        Self {
+            selection,
+            filter: BlockFilter::default(),
+        }
+    }
+
+    /// Attach a per-block filter.
+    pub fn with_filter(mut self, filter: BlockFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+/// One block returned by [`ConversationDAG::query`].
+#[derive(Debug, Clone, Copy)]
+pub struct DagMatch<'a> {
+    /// Absolute depth in the DAG (0 for roots).
+    pub depth: usize,
+    pub block: &'a BlockSnapshot,
+}
 
 /// Computed DAG index from CRDT data.
 ///
@@ -161,6 +222,148 @@ impl ConversationDAG {
         result
     }
 
+    /// Get all blocks below `id`, depth-first, excluding `id` itself.
+    pub fn descendants(&self, id: &BlockId) -> Vec<&BlockSnapshot> {
+        self.subtree_to_depth(id, None)
+            .into_iter()
+            .skip(1)
+            .map(|(_, block)| block)
+            .collect()
+    }
+
+    /// Get a subtree depth-first, stopping `max_depth` levels below `root`
+    /// (`None` = unbounded). Returns (relative depth, block) pairs, with
+    /// `root` at depth 0.
+    ///
+    /// Unlike [`subtree`](Self::subtree) this bounds *depth*, not node count:
+    /// it never descends more than `MAX_DAG_DEPTH` levels, and a visited set
+    /// guards against cycles — so large, shallow conversations come back whole.
+    pub fn subtree_to_depth(
+        &self,
+        root: &BlockId,
+        max_depth: Option<usize>,
+    ) -> Vec<(usize, &BlockSnapshot)> {
+        let mut result = Vec::new();
+        let mut stack = vec![(0, *root)];
+        let mut visited = HashSet::new();
+
+        while let Some((depth, id)) = stack.pop() {
+            if !visited.insert(id) {
+                continue; // cycle detected — skip
+            }
+            if depth > MAX_DAG_DEPTH {
+                tracing::warn!("subtree_to_depth hit MAX_DAG_DEPTH ({MAX_DAG_DEPTH}), pruning");
+                continue;
+            }
+            if let Some(block) = self.blocks.get(&id) {
+                result.push((depth, block));
+                if max_depth.is_some_and(|max| depth >= max) {
+                    continue;
+                }
+                for child in self.get_children(&id).iter().rev() {
+                    stack.push((depth + 1, *child));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Get the path between two blocks: up from `from` to their nearest
+    /// common ancestor, then down to `to`. Both endpoints are included;
+    /// `None` if either block is missing or they share no root.
+    pub fn path(&self, from: &BlockId, to: &BlockId) -> Option<Vec<&BlockSnapshot>> {
+        let up: Vec<&BlockSnapshot> = std::iter::once(self.blocks.get(from)?)
+            .chain(self.ancestors(from))
+            .collect();
+        let down: Vec<&BlockSnapshot> = std::iter::once(self.blocks.get(to)?)
+            .chain(self.ancestors(to))
+            .collect();
+
+        let down_index: HashMap<BlockId, usize> =
+            down.iter().enumerate().map(|(i, b)| (b.id, i)).collect();
+        let (up_at, down_at) = up
+            .iter()
+            .enumerate()
+            .find_map(|(i, b)| down_index.get(&b.id).map(|&j| (i, j)))?;
+
+        let mut path: Vec<&BlockSnapshot> = up[..=up_at].to_vec();
+        path.extend(down[..down_at].iter().rev());
+        Some(path)
+    }
+
+    /// Run a [`DagQuery`].
+    ///
+    /// Errors with [`CrdtError::BlockNotFound`] when an anchor block isn't in
+    /// the DAG, and [`CrdtError::NoPath`] when a path's endpoints share no root.
+    pub fn query(&self, query: &DagQuery) -> Result<Vec<DagMatch<'_>>> {
+        let require = |id: &BlockId| self.blocks.get(id).ok_or(CrdtError::BlockNotFound(*id));
+
+        let walked: Vec<DagMatch<'_>> = match &query.selection {
+            DagSelection::All => self
+                .iter_dfs()
+                .map(|(depth, block)| DagMatch { depth, block })
+                .collect(),
+            DagSelection::Ancestors(id) => {
+                let base = self.depth(&require(id)?.id);
+                self.ancestors(id)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, block)| DagMatch {
+                        depth: base.saturating_sub(i + 1),
+                        block,
+                    })
+                    .collect()
+            }
+            DagSelection::Descendants(id) => {
+                let base = self.depth(&require(id)?.id);
+                self.subtree_to_depth(id, None)
+                    .into_iter()
+                    .skip(1)
+                    .map(|(rel, block)| DagMatch {
+                        depth: base + rel,
+                        block,
+                    })
+                    .collect()
+            }
+            DagSelection::Subtree { root, max_depth } => {
+                let base = self.depth(&require(root)?.id);
+                self.subtree_to_depth(root, *max_depth)
+                    .into_iter()
+                    .map(|(rel, block)| DagMatch {
+                        depth: base + rel,
+                        block,
+                    })
+                    .collect()
+            }
+            DagSelection::Path { from, to } => {
+                require(from)?;
+                require(to)?;
+                self.path(from, to)
+                    .ok_or(CrdtError::NoPath {
+                        from: *from,
+                        to: *to,
+                    })?
+                    .into_iter()
+                    .map(|block| DagMatch {
+                        depth: self.depth(&block.id),
+                        block,
+                    })
+                    .collect()
+            }
+        };
+
+        let limit = match query.filter.limit {
+            0 => usize::MAX,
+            n => n as usize,
+        };
+        Ok(walked
+            .into_iter()
+            .filter(|m| query.filter.matches(m.block))
+            .take(limit)
+            .collect())
+    }
+
     /// Check if the DAG is empty.
     pub fn is_empty(&self) -> bool {
         self.blocks.is_empty()
@@ -445,10 +648,16 @@ mod tests {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             content_type_at: 0,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -487,10 +696,16 @@ mod tests {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             content_type_at: 0,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -537,6 +752,168 @@ mod tests {
         assert!(bfs.len() <= MAX_DAG_DEPTH + 1, "BFS should be bounded");
     }
 
+    /// Root → {question → {thinking, answer}, aside}. Ids come back as
+    /// `[root, question, thinking, answer, aside]`.
+    fn query_doc() -> (BlockDocument, [BlockId; 5]) {
+        let mut doc = test_doc();
+        let root = doc
+            .insert_block(
+                None,
+                None,
+                Role::System,
+                BlockKind::Text,
+                "Root",
+                Status::Done,
+            )
+            .unwrap();
+        let question = doc
+            .insert_block(
+                Some(&root),
+                Some(&root),
+                Role::User,
+                BlockKind::Text,
+                "Question",
+                Status::Done,
+            )
+            .unwrap();
+        let thinking = doc
+            .insert_block(
+                Some(&question),
+                Some(&question),
+                Role::Model,
+                BlockKind::Thinking,
+                "Hmm",
+                Status::Done,
+            )
+            .unwrap();
+        let answer = doc
+            .insert_block(
+                Some(&question),
+                Some(&thinking),
+                Role::Model,
+                BlockKind::Text,
+                "Answer",
+                Status::Done,
+            )
+            .unwrap();
+        let aside = doc
+            .insert_block(
+                Some(&root),
+                Some(&answer),
+                Role::User,
+                BlockKind::Text,
+                "Aside",
+                Status::Done,
+            )
+            .unwrap();
+        (doc, [root, question, thinking, answer, aside])
+    }
+
+    #[test]
+    fn test_query_path_and_ancestors() {
+        let (doc, [root, question, thinking, answer, aside]) = query_doc();
+        let dag = ConversationDAG::from_document(&doc);
+
+        let ids = |q: DagSelection| -> Vec<BlockId> {
+            dag.query(&DagQuery::new(q))
+                .unwrap()
+                .iter()
+                .map(|m| m.block.id)
+                .collect()
+        };
+
+        assert_eq!(ids(DagSelection::Ancestors(answer)), vec![question, root]);
+        assert_eq!(
+            ids(DagSelection::Path {
+                from: thinking,
+                to: aside
+            }),
+            vec![thinking, question, root, aside]
+        );
+        assert_eq!(
+            ids(DagSelection::Path {
+                from: answer,
+                to: answer
+            }),
+            vec![answer]
+        );
+
+        let path = dag
+            .query(&DagQuery::new(DagSelection::Path {
+                from: root,
+                to: answer,
+            }))
+            .unwrap();
+        let depths: Vec<usize> = path.iter().map(|m| m.depth).collect();
+        assert_eq!(depths, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_query_subtree_depth_and_filter() {
+        let (doc, [root, question, thinking, answer, aside]) = query_doc();
+        let dag = ConversationDAG::from_document(&doc);
+
+        let shallow = dag
+            .query(&DagQuery::new(DagSelection::Subtree {
+                root,
+                max_depth: Some(1),
+            }))
+            .unwrap();
+        let ids: Vec<_> = shallow.iter().map(|m| m.block.id).collect();
+        assert_eq!(ids, vec![root, question, aside]);
+
+        let below = dag
+            .query(&DagQuery::new(DagSelection::Descendants(question)))
+            .unwrap();
+        let ids: Vec<_> = below.iter().map(|m| m.block.id).collect();
+        assert_eq!(ids, vec![thinking, answer]);
+
+        let model_text = dag
+            .query(
+                &DagQuery::new(DagSelection::All).with_filter(BlockFilter {
+                    kinds: vec![BlockKind::Text],
+                    roles: vec![Role::Model],
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+        assert_eq!(model_text.len(), 1);
+        assert_eq!(model_text[0].block.id, answer);
+        assert_eq!(model_text[0].depth, 2);
+    }
+
+    #[test]
+    fn test_query_errors() {
+        let (doc, [root, ..]) = query_doc();
+        let dag = ConversationDAG::from_document(&doc);
+
+        let missing = BlockId::new(ContextId::new(), PrincipalId::new(), 99);
+        assert!(matches!(
+            dag.query(&DagQuery::new(DagSelection::Ancestors(missing))),
+            Err(CrdtError::BlockNotFound(id)) if id == missing
+        ));
+
+        let mut doc = doc;
+        let other_root = doc
+            .insert_block(
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                "Other",
+                Status::Done,
+            )
+            .unwrap();
+        let dag = ConversationDAG::from_document(&doc);
+        assert!(matches!(
+            dag.query(&DagQuery::new(DagSelection::Path {
+                from: root,
+                to: other_root
+            })),
+            Err(CrdtError::NoPath { .. })
+        ));
+    }
+
     #[test]
     fn test_subtree() {
         let mut doc = test_doc();
@@ -508,10 +508,16 @@ impl BlockDocument {
             notification: None, // Legacy document predates notification blocks
             resource: None,     // Legacy document predates resource blocks
             content_type: ContentType::Plain, // Legacy document predates content_type
+            language: None,
+            label: None,
+            reflow: None,
             content_type_at: 0,               // Legacy document predates content_type
             order_key: None,                  // Legacy document uses DTE-backed ordering
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,      // Legacy document predates Lamport propagation
             status_at: 0,
             collapsed_at: 0,
@@ -25,6 +25,16 @@ pub enum CrdtError {
     #[error("reference block not found: {0:?}")]
     InvalidReference(BlockId),
 
+    /// Reorder reference is not a sibling of the block being moved.
+    ///
+    /// `reorder_block` only shuffles siblings; reparenting is not a reorder.
+    #[error("block {after:?} is not a sibling of {block:?}")]
+    NotSibling { block: BlockId, after: BlockId },
+
+    /// No parent/child path joins two blocks (they sit under different roots).
+    #[error("no path from {from:?} to {to:?}")]
+    NoPath { from: BlockId, to: BlockId },
+
     /// Duplicate block ID.
     #[error("block already exists: {0:?}")]
     DuplicateBlock(BlockId),
@@ -45,7 +45,7 @@ pub use selection::{
     IntervalSet, RangeError, SelectionError, parse_range, resolve_keep_set, window_base,
 };
 pub use content::BlockContent;
-pub use dag::ConversationDAG;
+pub use dag::{ConversationDAG, DagMatch, DagQuery, DagSelection};
 
 // Legacy (still used by downstream crates)
 pub use document::{BlockDocument, DocumentSnapshot};
@@ -97,9 +97,15 @@ mod tests {
             tool_use_id: None,
             output: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -0,0 +1,484 @@
+//! Abandoned blocks — automatic `Error` for blocks nothing will finish.
+//!
+//! A block left `Pending` or `Running` keeps the app's spinner turning and
+//! keeps an agent polling `block_status` forever. Two rules, read from the
+//! CRDT-owned `/etc/config/abandon.toml` (see
+//! `assets/defaults/abandon.toml`), end that:
+//!
+//! - a block `Pending` longer than `pending_secs` is **abandoned**;
+//! - a block `Running` whose author has lost its last seat — disconnected
+//!   and not back within the resume grace — is **author lost**.
+//!
+//! `[kinds.<kind>]` tables override either rule per block kind. Either way
+//! the block goes to `Error` through the block store, so every subscriber
+//! sees an ordinary `StatusChanged`, and an Error child block
+//! (`block.abandoned` / `block.author_lost`) says why.
+//!
+//! [`AbandonTracker`] follows the block FlowBus for blocks that are
+//! pending or running; the reaper ([`spawn_reaper`]) sweeps it on a timer.
+//! Seats are server state, so the server hands the reaper a function that
+//! names the principals holding one. Blocks already waiting when the reaper
+//! started are not tracked.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use kaijutsu_crdt::{BlockId, BlockKind, Status};
+use kaijutsu_types::{ErrorCategory, ErrorPayload, ErrorSeverity, PrincipalId};
+use serde::Deserialize;
+
+use crate::block_store::{BlockStoreResult, SharedBlockStore};
+use crate::flows::BlockFlow;
+use crate::kernel::Kernel;
+
+/// Config file name under `/etc/config`.
+pub const ABANDON_CONFIG_FILE: &str = "abandon.toml";
+
+/// How often the reaper sweeps (and re-reads the config).
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// One rule set; in `[kinds.<kind>]`, unset fields fall back to the
+/// top-level ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AbandonRule {
+    /// Seconds a block may stay `Pending`; 0 never abandons it.
+    #[serde(default)]
+    pub pending_secs: Option<u64>,
+    /// Fail a `Running` block when its author loses its last seat.
+    #[serde(default)]
+    pub author_lost: Option<bool>,
+}
+
+/// Parsed `abandon.toml`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AbandonConfig {
+    #[serde(default = "default_pending_secs")]
+    pub pending_secs: u64,
+    #[serde(default = "default_author_lost")]
+    pub author_lost: bool,
+    /// Per-kind overrides, keyed by block kind (`tool_call`, `text`, …).
+    #[serde(default)]
+    pub kinds: HashMap<String, AbandonRule>,
+}
+
+fn default_pending_secs() -> u64 {
+    3600
+}
+
+fn default_author_lost() -> bool {
+    true
+}
+
+impl Default for AbandonConfig {
+    fn default() -> Self {
+        Self {
+            pending_secs: default_pending_secs(),
+            author_lost: default_author_lost(),
+            kinds: HashMap::new(),
+        }
+    }
+}
+
+impl AbandonConfig {
+    /// Parse and validate. Also the `kj config set` write-time check.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| format!("invalid TOML: {e}"))?;
+        if let Some(name) = config
+            .kinds
+            .keys()
+            .find(|name| BlockKind::from_str(name).is_none())
+        {
+            return Err(format!("unknown block kind '{name}' in [kinds]"));
+        }
+        Ok(config)
+    }
+
+    fn override_for(&self, kind: Option<BlockKind>) -> Option<&AbandonRule> {
+        let kind = kind?;
+        self.kinds
+            .iter()
+            .find(|(name, _)| BlockKind::from_str(name) == Some(kind))
+            .map(|(_, rule)| rule)
+    }
+
+    /// How long a block of `kind` may stay `Pending`; `None` is forever.
+    /// A kind that first showed up mid-flight (`None`) gets the defaults.
+    pub fn pending_limit(&self, kind: Option<BlockKind>) -> Option<Duration> {
+        let secs = self
+            .override_for(kind)
+            .and_then(|rule| rule.pending_secs)
+            .unwrap_or(self.pending_secs);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+
+    /// Whether a running block of `kind` fails with its author's seat.
+    pub fn fails_on_author_lost(&self, kind: Option<BlockKind>) -> bool {
+        self.override_for(kind)
+            .and_then(|rule| rule.author_lost)
+            .unwrap_or(self.author_lost)
+    }
+}
+
+/// Read `abandon.toml` from the config mount. Missing or broken means the
+/// defaults, with a warning for broken.
+pub async fn load_config(kernel: &Kernel) -> AbandonConfig {
+    use crate::vfs::VfsOps;
+
+    let path = kaijutsu_types::paths::config_path(ABANDON_CONFIG_FILE);
+    let Ok(bytes) = kernel.vfs().read_all(std::path::Path::new(&path)).await else {
+        return AbandonConfig::default();
+    };
+    AbandonConfig::parse(&String::from_utf8_lossy(&bytes)).unwrap_or_else(|e| {
+        tracing::warn!("{path}: {e}; using the default abandon rules");
+        AbandonConfig::default()
+    })
+}
+
+/// Why a block was failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbandonReason {
+    /// `Pending` for `waited`, past its kind's limit.
+    Pending { waited: Duration },
+    /// `Running`, and its author has no seat left.
+    AuthorLost { author: PrincipalId },
+}
+
+impl AbandonReason {
+    /// The Error block's code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Pending { .. } => "block.abandoned",
+            Self::AuthorLost { .. } => "block.author_lost",
+        }
+    }
+
+    /// The Error block's summary.
+    pub fn summary(&self) -> String {
+        match self {
+            Self::Pending { waited } => {
+                format!("abandoned: pending for {}s", waited.as_secs())
+            }
+            Self::AuthorLost { author } => {
+                format!("author lost: {author} disconnected while this was running")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Waiting {
+    /// `None` when first seen by a status change rather than an insert.
+    kind: Option<BlockKind>,
+    status: Status,
+    since: Instant,
+}
+
+/// Blocks currently `Pending` or `Running`, and since when.
+#[derive(Debug, Default)]
+pub struct AbandonTracker {
+    blocks: HashMap<BlockId, Waiting>,
+}
+
+impl AbandonTracker {
+    /// Fold one block event, observed at `at`.
+    pub fn record(&mut self, flow: &BlockFlow, at: Instant) {
+        match flow {
+            BlockFlow::Inserted { block, .. } => {
+                if matches!(block.status, Status::Pending | Status::Running) {
+                    self.blocks.insert(
+                        block.id,
+                        Waiting {
+                            kind: Some(block.kind),
+                            status: block.status,
+                            since: at,
+                        },
+                    );
+                }
+            }
+            BlockFlow::StatusChanged {
+                block_id, status, ..
+            } => match status {
+                Status::Pending | Status::Running => {
+                    let waiting = self.blocks.entry(*block_id).or_insert(Waiting {
+                        kind: None,
+                        status: *status,
+                        since: at,
+                    });
+                    if waiting.status != *status {
+                        waiting.status = *status;
+                        waiting.since = at;
+                    }
+                }
+                Status::Done | Status::Error => {
+                    self.blocks.remove(block_id);
+                }
+            },
+            BlockFlow::Deleted { block_id, .. } => {
+                self.blocks.remove(block_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Blocks to fail as of `now` under `config`. `departed` holds the
+    /// principals that lost their last seat since the previous sweep.
+    /// Ordered by block id.
+    pub fn due(
+        &self,
+        config: &AbandonConfig,
+        now: Instant,
+        departed: &HashSet<PrincipalId>,
+    ) -> Vec<(BlockId, Option<BlockKind>, AbandonReason)> {
+        let mut out: Vec<_> = self
+            .blocks
+            .iter()
+            .filter_map(|(id, waiting)| {
+                let reason = match waiting.status {
+                    Status::Pending => {
+                        let waited = now.saturating_duration_since(waiting.since);
+                        let limit = config.pending_limit(waiting.kind)?;
+                        (waited >= limit).then_some(AbandonReason::Pending { waited })?
+                    }
+                    Status::Running
+                        if departed.contains(&id.principal_id)
+                            && config.fails_on_author_lost(waiting.kind) =>
+                    {
+                        AbandonReason::AuthorLost {
+                            author: id.principal_id,
+                        }
+                    }
+                    _ => return None,
+                };
+                Some((*id, waiting.kind, reason))
+            })
+            .collect();
+        out.sort_by_key(|(id, ..)| *id);
+        out
+    }
+
+    /// Stop watching a block.
+    pub fn forget(&mut self, block_id: &BlockId) {
+        self.blocks.remove(block_id);
+    }
+
+    /// Blocks being watched.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+/// Fail `block_id` for `reason`: set it to `Error` and attach an Error
+/// block saying why. A block that finished since the sweep looked is left
+/// alone; returns whether it was failed.
+pub fn abandon(
+    blocks: &SharedBlockStore,
+    block_id: &BlockId,
+    kind: Option<BlockKind>,
+    reason: &AbandonReason,
+) -> BlockStoreResult<bool> {
+    let context_id = block_id.context_id;
+    let still_waiting = blocks
+        .get_block_snapshot(context_id, block_id)?
+        .is_some_and(|b| matches!(b.status, Status::Pending | Status::Running));
+    if !still_waiting {
+        return Ok(false);
+    }
+    blocks.set_status(context_id, block_id, Status::Error)?;
+    let summary = reason.summary();
+    let payload = ErrorPayload {
+        category: ErrorCategory::Kernel,
+        severity: ErrorSeverity::Error,
+        code: Some(reason.code().to_string()),
+        detail: Some(summary.clone()),
+        span: None,
+        source_kind: kind,
+    };
+    blocks.insert_error_block_as(context_id, block_id, &payload, summary, None)?;
+    Ok(true)
+}
+
+/// Watch the block flows and fail abandoned blocks every
+/// [`SWEEP_INTERVAL`]. `seated` names the principals holding a seat (live
+/// or parked for resume) at the given time. Call once per kernel, from a
+/// runtime that lives as long as the kernel.
+pub fn spawn_reaper(
+    kernel: Arc<Kernel>,
+    blocks: SharedBlockStore,
+    seated: impl Fn(Instant) -> HashSet<PrincipalId> + Send + 'static,
+) -> tokio::task::JoinHandle<()> {
+    let mut sub = kernel.block_flows().subscribe("block.*");
+    tokio::spawn(async move {
+        let mut tracker = AbandonTracker::default();
+        let mut seated_before = HashSet::new();
+        let mut sweep = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            tokio::select! {
+                msg = sub.recv() => match msg {
+                    Some(msg) => tracker.record(&msg.payload, msg.timestamp),
+                    None => break,
+                },
+                _ = sweep.tick() => {
+                    let config = load_config(&kernel).await;
+                    let now = Instant::now();
+                    let seated_now = seated(now);
+                    let departed = seated_before.difference(&seated_now).copied().collect();
+                    seated_before = seated_now;
+                    for (block_id, kind, reason) in tracker.due(&config, now, &departed) {
+                        tracker.forget(&block_id);
+                        match abandon(&blocks, &block_id, kind, &reason) {
+                            Ok(true) => tracing::info!(
+                                block = %block_id.to_key(),
+                                code = reason.code(),
+                                "failed an abandoned block"
+                            ),
+                            Ok(false) => {}
+                            Err(e) => tracing::warn!(
+                                "abandon {}: {e}",
+                                block_id.to_key()
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::OpSource;
+    use kaijutsu_crdt::BlockSnapshotBuilder;
+    use kaijutsu_types::ContextId;
+
+    fn inserted(id: BlockId, kind: BlockKind, status: Status) -> BlockFlow {
+        BlockFlow::Inserted {
+            context_id: id.context_id,
+            block: Arc::new(BlockSnapshotBuilder::new(id, kind).status(status).build()),
+            after_id: None,
+            ops: Arc::from(Vec::new()),
+            source: OpSource::Local,
+        }
+    }
+
+    fn status(id: BlockId, status: Status) -> BlockFlow {
+        BlockFlow::StatusChanged {
+            context_id: id.context_id,
+            block_id: id,
+            status,
+            source: OpSource::Local,
+        }
+    }
+
+    #[test]
+    fn config_overrides_per_kind() {
+        let config = AbandonConfig::parse(
+            "pending_secs = 60\n\
+             [kinds.tool_call]\npending_secs = 0\nauthor_lost = false\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.pending_limit(Some(BlockKind::Text)),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(config.pending_limit(None), Some(Duration::from_secs(60)));
+        assert_eq!(config.pending_limit(Some(BlockKind::ToolCall)), None);
+        assert!(config.fails_on_author_lost(Some(BlockKind::Text)));
+        assert!(!config.fails_on_author_lost(Some(BlockKind::ToolCall)));
+
+        assert!(AbandonConfig::parse("[kinds.nope]\npending_secs = 1\n").is_err());
+        assert!(AbandonConfig::parse("pending = 1\n").is_err());
+        assert_eq!(
+            AbandonConfig::parse(crate::config_seed::DEFAULT_ABANDON).unwrap(),
+            AbandonConfig::default()
+        );
+    }
+
+    #[test]
+    fn stale_pending_and_orphaned_running_come_due() {
+        let config = AbandonConfig::parse("pending_secs = 60\n").unwrap();
+        let mut tracker = AbandonTracker::default();
+        let t0 = Instant::now();
+        let ctx = ContextId::new();
+        let (amy, bob) = (PrincipalId::new(), PrincipalId::new());
+        let pending = BlockId::new(ctx, amy, 0);
+        let amy_running = BlockId::new(ctx, amy, 1);
+        let bob_running = BlockId::new(ctx, bob, 0);
+        let finished = BlockId::new(ctx, bob, 1);
+
+        tracker.record(&inserted(pending, BlockKind::ToolCall, Status::Pending), t0);
+        tracker.record(&inserted(amy_running, BlockKind::Text, Status::Running), t0);
+        tracker.record(&inserted(bob_running, BlockKind::Text, Status::Running), t0);
+        tracker.record(&inserted(finished, BlockKind::Text, Status::Pending), t0);
+        tracker.record(&status(finished, Status::Done), t0);
+        assert_eq!(tracker.len(), 3);
+
+        let none = HashSet::new();
+        assert!(
+            tracker
+                .due(&config, t0 + Duration::from_secs(30), &none)
+                .is_empty()
+        );
+
+        let departed = HashSet::from([bob]);
+        let due = tracker.due(&config, t0 + Duration::from_secs(90), &departed);
+        let codes: Vec<_> = due.iter().map(|(id, _, r)| (*id, r.code())).collect();
+        let mut expected = vec![
+            (pending, "block.abandoned"),
+            (bob_running, "block.author_lost"),
+        ];
+        expected.sort_by_key(|(id, _)| *id);
+        assert_eq!(codes, expected);
+    }
+
+    #[test]
+    fn abandon_fails_the_block_and_says_why() {
+        use crate::block_store::{DocumentKind, shared_block_store};
+        use kaijutsu_types::{ContentType, Role};
+
+        let blocks = shared_block_store(PrincipalId::new());
+        let ctx = ContextId::new();
+        blocks
+            .create_document(ctx, DocumentKind::Conversation, None)
+            .unwrap();
+        let id = blocks
+            .insert_block(
+                ctx,
+                None,
+                None,
+                Role::Tool,
+                BlockKind::ToolCall,
+                "{}",
+                Status::Pending,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let reason = AbandonReason::Pending {
+            waited: Duration::from_secs(3600),
+        };
+
+        assert!(abandon(&blocks, &id, Some(BlockKind::ToolCall), &reason).unwrap());
+        let snapshots = blocks.block_snapshots(ctx).unwrap();
+        assert_eq!(snapshots[0].status, Status::Error);
+        let error = snapshots
+            .iter()
+            .find(|b| b.kind == BlockKind::Error)
+            .expect("an Error block explains the failure");
+        assert_eq!(
+            error.error.as_ref().and_then(|e| e.code.as_deref()),
+            Some("block.abandoned")
+        );
+
+        assert!(
+            !abandon(&blocks, &id, Some(BlockKind::ToolCall), &reason).unwrap(),
+            "an already-failed block is left alone"
+        );
+    }
+}
@@ -0,0 +1,412 @@
+//! Opt-in usage analytics — tool-call counts and latencies, never content.
+//!
+//! Off unless the CRDT-owned `/etc/config/analytics.toml` says
+//! `enabled = true` (see `assets/defaults/analytics.toml` for the format).
+//! While on, the broker records each tool call it runs into
+//! [`UsageAnalytics`]: the `instance/tool` name, how long it took and whether
+//! it failed. Arguments, results, contexts and principals are never looked
+//! at, so they can't leak.
+//!
+//! Aggregation is local. Every `flush_secs` the flusher ([`spawn_flusher`])
+//! closes the window and, when an `endpoint` is configured, POSTs it as a
+//! [`UsageReport`] for an operator dashboard. With no endpoint, nothing
+//! leaves the kernel. Turning analytics off discards the open window.
+//!
+//! `epsilon` is the differential-privacy knob. When set, every count in a
+//! report gets Laplace noise of scale `1/epsilon` before it is sent, and the
+//! exact per-call latencies (mean, max) are left out. Smaller is more
+//! private and noisier; unset sends exact figures.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use kaijutsu_types::KernelId;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::kernel::Kernel;
+
+/// Config file name under `/etc/config`.
+pub const ANALYTICS_CONFIG_FILE: &str = "analytics.toml";
+
+/// Upper bounds of the latency buckets, in milliseconds. A last, open bucket
+/// holds everything slower.
+pub const LATENCY_BUCKETS_MS: [u64; 4] = [10, 100, 1_000, 10_000];
+
+/// How often the flusher re-reads the config, so an edit made outside
+/// `kj config` still takes effect.
+const CONFIG_POLL: Duration = Duration::from_secs(60);
+
+/// Shortest allowed `flush_secs`.
+const MIN_FLUSH_SECS: u64 = 60;
+
+/// Per-request timeout for a report POST.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Parsed `analytics.toml`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnalyticsConfig {
+    /// The off switch. Nothing is recorded while false.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Where reports are POSTed; local-only when `None`.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Window length between reports.
+    #[serde(default = "default_flush_secs")]
+    pub flush_secs: u64,
+    /// Differential-privacy budget per count; exact figures when `None`.
+    #[serde(default)]
+    pub epsilon: Option<f64>,
+}
+
+fn default_flush_secs() -> u64 {
+    3600
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: None,
+            flush_secs: default_flush_secs(),
+            epsilon: None,
+        }
+    }
+}
+
+impl AnalyticsConfig {
+    /// Parse and validate. Also the `kj config set` write-time check.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| format!("invalid TOML: {e}"))?;
+        if let Some(url) = &config.endpoint
+            && !(url.starts_with("http://") || url.starts_with("https://"))
+        {
+            return Err(format!(
+                "analytics endpoint '{url}' must start with http:// or https://"
+            ));
+        }
+        if config.flush_secs < MIN_FLUSH_SECS {
+            return Err(format!("flush_secs must be at least {MIN_FLUSH_SECS}"));
+        }
+        if let Some(epsilon) = config.epsilon
+            && !(epsilon.is_finite() && epsilon > 0.0)
+        {
+            return Err(format!("epsilon must be a positive number, got {epsilon}"));
+        }
+        Ok(config)
+    }
+}
+
+/// One tool's figures inside a window.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ToolUsage {
+    pub calls: u64,
+    pub errors: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    /// Call counts per [`LATENCY_BUCKETS_MS`] bucket, plus the open one.
+    pub latency_buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+/// The open aggregation window.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UsageWindow {
+    /// Unix millis the window opened.
+    pub started_at: u64,
+    /// Keyed by `instance/tool`.
+    pub tools: BTreeMap<String, ToolUsage>,
+}
+
+impl UsageWindow {
+    fn new() -> Self {
+        Self {
+            started_at: kaijutsu_types::now_millis(),
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+/// The kernel's usage aggregator. One per kernel, owned by the broker
+/// ([`Broker::analytics`](crate::mcp::Broker::analytics)).
+pub struct UsageAnalytics {
+    enabled: AtomicBool,
+    window: Mutex<UsageWindow>,
+}
+
+impl Default for UsageAnalytics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageAnalytics {
+    /// Off until the config turns it on.
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            window: Mutex::new(UsageWindow::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Flip the switch. Turning it off discards whatever the open window
+    /// holds. Returns whether the state changed.
+    pub fn set_enabled(&self, enabled: bool) -> bool {
+        let was = self.enabled.swap(enabled, Ordering::Relaxed);
+        if was && !enabled {
+            *self.window.lock() = UsageWindow::new();
+        }
+        was != enabled
+    }
+
+    /// Count one tool call. A no-op while disabled.
+    pub fn record(&self, instance: &str, tool: &str, elapsed: Duration, ok: bool) {
+        if !self.is_enabled() {
+            return;
+        }
+        let ms = elapsed.as_millis().min(u64::MAX as u128) as u64;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        let mut window = self.window.lock();
+        let usage = window
+            .tools
+            .entry(format!("{instance}/{tool}"))
+            .or_default();
+        usage.calls += 1;
+        if !ok {
+            usage.errors += 1;
+        }
+        usage.total_ms = usage.total_ms.saturating_add(ms);
+        usage.max_ms = usage.max_ms.max(ms);
+        usage.latency_buckets[bucket] += 1;
+    }
+
+    /// A copy of the open window.
+    pub fn pending(&self) -> UsageWindow {
+        self.window.lock().clone()
+    }
+
+    /// Close the open window and start a fresh one.
+    pub fn take(&self) -> UsageWindow {
+        std::mem::replace(&mut *self.window.lock(), UsageWindow::new())
+    }
+}
+
+/// What a flush sends.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UsageReport {
+    pub kernel_id: String,
+    pub window_start: u64,
+    pub window_end: u64,
+    /// The noise level applied, if any.
+    pub epsilon: Option<f64>,
+    pub latency_buckets_ms: Vec<u64>,
+    pub tools: Vec<ToolReport>,
+}
+
+/// One tool's line in a [`UsageReport`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ToolReport {
+    pub tool: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub latency_buckets: Vec<u64>,
+    /// Exact figures only; omitted under `epsilon`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ms: Option<u64>,
+}
+
+impl UsageReport {
+    /// Build the report for a closed window, applying `epsilon` noise.
+    pub fn build(
+        window: &UsageWindow,
+        kernel_id: KernelId,
+        window_end: u64,
+        epsilon: Option<f64>,
+    ) -> Self {
+        let noisy = |n: u64| match epsilon {
+            Some(eps) => ((n as f64) + laplace(1.0 / eps)).round().max(0.0) as u64,
+            None => n,
+        };
+        let tools = window
+            .tools
+            .iter()
+            .map(|(tool, usage)| ToolReport {
+                tool: tool.clone(),
+                calls: noisy(usage.calls),
+                errors: noisy(usage.errors),
+                latency_buckets: usage.latency_buckets.iter().map(|n| noisy(*n)).collect(),
+                mean_ms: epsilon
+                    .is_none()
+                    .then(|| usage.total_ms / usage.calls.max(1)),
+                max_ms: epsilon.is_none().then_some(usage.max_ms),
+            })
+            .collect();
+        Self {
+            kernel_id: kernel_id.to_hex(),
+            window_start: window.started_at,
+            window_end,
+            epsilon,
+            latency_buckets_ms: LATENCY_BUCKETS_MS.to_vec(),
+            tools,
+        }
+    }
+}
+
+/// A draw from Laplace(0, `scale`).
+fn laplace(scale: f64) -> f64 {
+    let u: f64 = rand::random::<f64>() - 0.5;
+    -scale * u.signum() * (1.0 - 2.0 * u.abs()).max(f64::MIN_POSITIVE).ln()
+}
+
+/// The current `analytics.toml`. Absent (a kernel seeded before analytics
+/// existed) is the default, off; unparseable is logged and also off.
+pub async fn load_config(kernel: &Kernel) -> AnalyticsConfig {
+    use crate::vfs::VfsOps;
+
+    let path = kaijutsu_types::paths::config_path(ANALYTICS_CONFIG_FILE);
+    let Ok(bytes) = kernel.vfs().read_all(std::path::Path::new(&path)).await else {
+        return AnalyticsConfig::default();
+    };
+    AnalyticsConfig::parse(&String::from_utf8_lossy(&bytes)).unwrap_or_else(|e| {
+        tracing::warn!("{path}: {e}; analytics stays off until it is fixed");
+        AnalyticsConfig::default()
+    })
+}
+
+/// Keep the switch in step with the config and close a window every
+/// `flush_secs`, POSTing it when an endpoint is set. Call once, from a
+/// runtime that lives as long as the kernel.
+pub fn spawn_flusher(kernel: Arc<Kernel>) -> tokio::task::JoinHandle<()> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    tokio::spawn(async move {
+        let analytics = kernel.broker().analytics().clone();
+        let mut last_flush = Instant::now();
+        loop {
+            let config = load_config(&kernel).await;
+            if analytics.set_enabled(config.enabled) {
+                tracing::info!(enabled = config.enabled, "usage analytics switched");
+                last_flush = Instant::now();
+            }
+            if config.enabled && last_flush.elapsed() >= Duration::from_secs(config.flush_secs) {
+                last_flush = Instant::now();
+                let window = analytics.take();
+                if let Some(endpoint) = &config.endpoint
+                    && !window.tools.is_empty()
+                {
+                    let report = UsageReport::build(
+                        &window,
+                        kernel.id(),
+                        kaijutsu_types::now_millis(),
+                        config.epsilon,
+                    );
+                    send(&client, endpoint, &report).await;
+                }
+            }
+            tokio::time::sleep(CONFIG_POLL).await;
+        }
+    })
+}
+
+/// One attempt; a lost report is a gap on a dashboard, not worth a retry
+/// queue.
+async fn send(client: &reqwest::Client, endpoint: &str, report: &UsageReport) {
+    match client.post(endpoint).json(report).send().await {
+        Ok(resp) if resp.status().is_success() => {}
+        Ok(resp) => tracing::warn!("analytics: {endpoint} answered {}", resp.status()),
+        Err(e) => tracing::warn!("analytics: report to {endpoint} failed: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_nothing_until_enabled_and_forgets_on_disable() {
+        let analytics = UsageAnalytics::new();
+        analytics.record(
+            "builtin.block",
+            "block_read",
+            Duration::from_millis(5),
+            true,
+        );
+        assert!(analytics.pending().tools.is_empty());
+
+        assert!(analytics.set_enabled(true));
+        analytics.record(
+            "builtin.block",
+            "block_read",
+            Duration::from_millis(5),
+            true,
+        );
+        analytics.record(
+            "builtin.block",
+            "block_read",
+            Duration::from_millis(250),
+            false,
+        );
+        let window = analytics.pending();
+        let usage = &window.tools["builtin.block/block_read"];
+        assert_eq!((usage.calls, usage.errors, usage.max_ms), (2, 1, 250));
+        assert_eq!(usage.latency_buckets, [1, 0, 1, 0, 0]);
+
+        assert!(analytics.set_enabled(false));
+        assert!(analytics.pending().tools.is_empty());
+    }
+
+    #[test]
+    fn epsilon_hides_exact_latencies() {
+        let analytics = UsageAnalytics::new();
+        analytics.set_enabled(true);
+        analytics.record("builtin.shell", "shell", Duration::from_millis(40), true);
+        let window = analytics.take();
+        assert!(analytics.pending().tools.is_empty());
+
+        let exact = UsageReport::build(&window, KernelId::new(), 1, None);
+        assert_eq!(exact.tools[0].calls, 1);
+        assert_eq!(exact.tools[0].mean_ms, Some(40));
+
+        let noisy = UsageReport::build(&window, KernelId::new(), 1, Some(0.5));
+        assert_eq!(
+            (noisy.tools[0].mean_ms, noisy.tools[0].max_ms),
+            (None, None)
+        );
+        let json = serde_json::to_value(&noisy).unwrap();
+        assert!(json["tools"][0].get("max_ms").is_none());
+    }
+
+    #[test]
+    fn parses_and_rejects() {
+        let config = AnalyticsConfig::parse(
+            "enabled = true\nendpoint = \"https://metrics.example.com/k\"\nepsilon = 1.0",
+        )
+        .unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.flush_secs, 3600);
+        assert!(AnalyticsConfig::parse("endpoint = \"ftp://x\"").is_err());
+        assert!(AnalyticsConfig::parse("epsilon = 0.0").is_err());
+        assert!(AnalyticsConfig::parse("flush_secs = 5").is_err());
+        assert!(AnalyticsConfig::parse("enabeld = true").is_err());
+    }
+
+    #[test]
+    fn seeded_default_is_off() {
+        let config = AnalyticsConfig::parse(crate::config_seed::DEFAULT_ANALYTICS).unwrap();
+        assert_eq!(config, AnalyticsConfig::default());
+    }
+}
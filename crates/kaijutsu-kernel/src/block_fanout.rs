@@ -0,0 +1,197 @@
+//! Per-block event fan-out — subscribe to one block instead of the bus.
+//!
+//! Every `block.*` subscriber on the FlowBus sees every event of every
+//! context and filters for itself. That is the right shape for a client
+//! mirroring whole documents, and the wrong one for a watcher of a single
+//! block (`tailBlock`, `subscribeBlock`, the app's focused cell): each of
+//! them wakes for every token streamed anywhere in the kernel.
+//!
+//! [`BlockFanout`] sits beside the bus in [`BlockStore`](crate::block_store::BlockStore)
+//! and receives each event as it is emitted. Events carrying a block id go
+//! to that block's subscribers only — one map lookup, and no clone at all
+//! for blocks nobody watches. A `SyncReset` goes to every watched block of
+//! its context, since the reset invalidates their text too. A `Deleted`
+//! event is the last one a subscription sees: the block's channel closes
+//! behind it.
+//!
+//! Delivery is lossy like the bus: a subscriber more than
+//! [`BLOCK_FANOUT_CAPACITY`] events behind gets [`BlockSubEvent::Lagged`]
+//! and should re-read the block.
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use tokio::sync::broadcast;
+
+use kaijutsu_crdt::BlockId;
+
+use crate::flows::BlockFlow;
+
+/// Events buffered per watched block before the slowest subscriber lags.
+pub const BLOCK_FANOUT_CAPACITY: usize = 256;
+
+/// Routes block events to per-block subscribers. See the [module docs](self).
+#[derive(Default)]
+pub struct BlockFanout {
+    blocks: Arc<DashMap<BlockId, broadcast::Sender<BlockFlow>>>,
+}
+
+impl BlockFanout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to events for `block_id`, starting with the next one
+    /// published. The block need not exist yet.
+    pub fn subscribe(&self, block_id: BlockId) -> BlockSubscription {
+        let rx = self
+            .blocks
+            .entry(block_id)
+            .or_insert_with(|| broadcast::channel(BLOCK_FANOUT_CAPACITY).0)
+            .subscribe();
+        BlockSubscription {
+            block_id,
+            rx,
+            blocks: self.blocks.clone(),
+        }
+    }
+
+    /// Deliver `flow` to whoever watches the block it concerns.
+    pub fn publish(&self, flow: &BlockFlow) {
+        if self.blocks.is_empty() {
+            return;
+        }
+        match flow {
+            BlockFlow::SyncReset { context_id, .. } => {
+                for entry in self.blocks.iter() {
+                    if entry.key().context_id == *context_id {
+                        let _ = entry.value().send(flow.clone());
+                    }
+                }
+            }
+            BlockFlow::Deleted { block_id, .. } => {
+                // Dropping the sender ends every subscription once it has
+                // drained this last event.
+                if let Some((_, tx)) = self.blocks.remove(block_id) {
+                    let _ = tx.send(flow.clone());
+                }
+            }
+            _ => {
+                if let Some(block_id) = flow.block_id()
+                    && let Some(tx) = self.blocks.get(block_id)
+                {
+                    let _ = tx.send(flow.clone());
+                }
+            }
+        }
+    }
+
+    /// Number of blocks with at least one live subscription.
+    pub fn watched_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+}
+
+/// What a [`BlockSubscription`] yields.
+#[derive(Debug, Clone)]
+pub enum BlockSubEvent {
+    /// An event for the block (or a `SyncReset` of its context).
+    Flow(BlockFlow),
+    /// This many events were dropped because the subscriber fell behind.
+    /// Re-read the block to catch up.
+    Lagged(u64),
+}
+
+/// A live subscription to one block's events. Dropping it unregisters the
+/// block once no other subscription watches it.
+pub struct BlockSubscription {
+    block_id: BlockId,
+    rx: broadcast::Receiver<BlockFlow>,
+    blocks: Arc<DashMap<BlockId, broadcast::Sender<BlockFlow>>>,
+}
+
+impl BlockSubscription {
+    pub fn block_id(&self) -> BlockId {
+        self.block_id
+    }
+
+    /// The next event, or `None` once the block has been deleted and its
+    /// last event delivered.
+    pub async fn recv(&mut self) -> Option<BlockSubEvent> {
+        match self.rx.recv().await {
+            Ok(flow) => Some(BlockSubEvent::Flow(flow)),
+            Err(broadcast::error::RecvError::Lagged(n)) => Some(BlockSubEvent::Lagged(n)),
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    }
+}
+
+impl Drop for BlockSubscription {
+    fn drop(&mut self) {
+        // `self.rx` is still alive here, so the last subscription sees a
+        // count of one.
+        self.blocks
+            .remove_if(&self.block_id, |_, tx| tx.receiver_count() <= 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::OpSource;
+    use kaijutsu_crdt::Status;
+    use kaijutsu_types::{ContextId, PrincipalId};
+
+    fn status(block_id: BlockId) -> BlockFlow {
+        BlockFlow::StatusChanged {
+            context_id: block_id.context_id,
+            block_id,
+            status: Status::Done,
+            source: OpSource::Local,
+        }
+    }
+
+    #[tokio::test]
+    async fn routes_by_block_and_closes_on_delete() {
+        let fanout = BlockFanout::new();
+        let ctx = ContextId::new();
+        let watched = BlockId::new(ctx, PrincipalId::system(), 1);
+        let other = BlockId::new(ctx, PrincipalId::system(), 2);
+        let mut sub = fanout.subscribe(watched);
+
+        fanout.publish(&status(other));
+        fanout.publish(&status(watched));
+        fanout.publish(&BlockFlow::SyncReset {
+            context_id: ContextId::new(),
+            generation: 1,
+        });
+        fanout.publish(&BlockFlow::SyncReset {
+            context_id: ctx,
+            generation: 2,
+        });
+        fanout.publish(&BlockFlow::Deleted {
+            context_id: ctx,
+            block_id: watched,
+            source: OpSource::Local,
+        });
+
+        let mut seen = Vec::new();
+        while let Some(BlockSubEvent::Flow(flow)) = sub.recv().await {
+            seen.push(flow.subject());
+        }
+        assert_eq!(seen, ["block.status", "block.sync_reset", "block.deleted"]);
+        assert_eq!(fanout.watched_blocks(), 0);
+    }
+
+    #[test]
+    fn dropping_the_last_subscription_unregisters_the_block() {
+        let fanout = BlockFanout::new();
+        let block = BlockId::new(ContextId::new(), PrincipalId::system(), 1);
+        let first = fanout.subscribe(block);
+        let second = fanout.subscribe(block);
+        drop(first);
+        assert_eq!(fanout.watched_blocks(), 1);
+        drop(second);
+        assert_eq!(fanout.watched_blocks(), 0);
+    }
+}
@@ -0,0 +1,192 @@
+//! Advisory block locks — who is editing which block, right now.
+//!
+//! [`BlockLocks`] is the in-memory table behind the [`BlockStore`] lock
+//! methods ([`lock_block`](crate::block_store::BlockStore::lock_block),
+//! [`check_lock`](crate::block_store::BlockStore::check_lock), …). A lock is
+//! one principal's claim on one block until its TTL runs out; taking it
+//! again renews it. Expiry is lazy: an expired lock is simply not there to
+//! any reader, and it is dropped the next time the table is touched —
+//! clients hide one on their own clock from `expires_at`, so nothing has to
+//! announce it.
+//!
+//! Locks live only as long as the kernel: they describe presence, and a
+//! restart ends every session that held one.
+//!
+//! [`BlockStore`]: crate::block_store::BlockStore
+
+use std::collections::HashMap;
+
+use kaijutsu_types::{
+    BlockId, BlockLock, ContextId, DEFAULT_LOCK_TTL_SECS, LockPolicy, MAX_LOCK_TTL_SECS,
+    PrincipalId,
+};
+use parking_lot::Mutex;
+
+/// Live locks by block, plus each context's policy once known.
+#[derive(Debug, Default)]
+pub struct BlockLocks {
+    locks: Mutex<HashMap<BlockId, BlockLock>>,
+    policies: Mutex<HashMap<ContextId, LockPolicy>>,
+}
+
+impl BlockLocks {
+    /// Lock `block_id` for `holder` for `ttl_secs` (default
+    /// [`DEFAULT_LOCK_TTL_SECS`], capped at [`MAX_LOCK_TTL_SECS`]), or renew
+    /// the holder's own lock. Another principal's live lock is returned as
+    /// the error.
+    pub fn acquire(
+        &self,
+        block_id: BlockId,
+        holder: PrincipalId,
+        holder_name: &str,
+        ttl_secs: Option<u64>,
+        note: &str,
+        now_millis: u64,
+    ) -> Result<BlockLock, BlockLock> {
+        let ttl = ttl_secs
+            .filter(|&t| t > 0)
+            .unwrap_or(DEFAULT_LOCK_TTL_SECS)
+            .min(MAX_LOCK_TTL_SECS);
+        let mut locks = self.locks.lock();
+        let acquired_at = match locks.get(&block_id) {
+            Some(held) if held.is_expired(now_millis) => now_millis,
+            Some(held) if held.holder != holder => return Err(held.clone()),
+            Some(held) => held.acquired_at,
+            None => now_millis,
+        };
+        let lock = BlockLock {
+            block_id,
+            holder,
+            holder_name: holder_name.to_string(),
+            note: note.to_string(),
+            acquired_at,
+            expires_at: now_millis + ttl * 1000,
+        };
+        locks.insert(block_id, lock.clone());
+        Ok(lock)
+    }
+
+    /// Release `block_id` if `by` holds it. `Ok(None)` when nothing live was
+    /// held; another principal's live lock is returned as the error.
+    pub fn release(
+        &self,
+        block_id: &BlockId,
+        by: PrincipalId,
+        now_millis: u64,
+    ) -> Result<Option<BlockLock>, BlockLock> {
+        let mut locks = self.locks.lock();
+        match locks.get(block_id) {
+            None => Ok(None),
+            Some(held) if held.is_expired(now_millis) => {
+                locks.remove(block_id);
+                Ok(None)
+            }
+            Some(held) if held.holder != by => Err(held.clone()),
+            Some(_) => Ok(locks.remove(block_id)),
+        }
+    }
+
+    /// The live lock on `block_id`, if any.
+    pub fn get(&self, block_id: &BlockId, now_millis: u64) -> Option<BlockLock> {
+        let mut locks = self.locks.lock();
+        match locks.get(block_id) {
+            Some(held) if held.is_expired(now_millis) => {
+                locks.remove(block_id);
+                None
+            }
+            held => held.cloned(),
+        }
+    }
+
+    /// Live locks in `context_id`, oldest first. Drops expired locks from
+    /// every context on the way.
+    pub fn list(&self, context_id: ContextId, now_millis: u64) -> Vec<BlockLock> {
+        let mut locks = self.locks.lock();
+        locks.retain(|_, lock| !lock.is_expired(now_millis));
+        let mut live: Vec<BlockLock> = locks
+            .values()
+            .filter(|lock| lock.block_id.context_id == context_id)
+            .cloned()
+            .collect();
+        live.sort_by_key(|lock| lock.acquired_at);
+        live
+    }
+
+    /// Drop the lock on a block that no longer exists.
+    pub fn forget(&self, block_id: &BlockId) -> Option<BlockLock> {
+        self.locks.lock().remove(block_id)
+    }
+
+    pub(crate) fn cached_policy(&self, context_id: ContextId) -> Option<LockPolicy> {
+        self.policies.lock().get(&context_id).copied()
+    }
+
+    pub(crate) fn cache_policy(&self, context_id: ContextId, policy: LockPolicy) {
+        self.policies.lock().insert(context_id, policy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block() -> BlockId {
+        BlockId::new(ContextId::new(), PrincipalId::new(), 1)
+    }
+
+    #[test]
+    fn a_lock_excludes_others_until_it_expires() {
+        let locks = BlockLocks::default();
+        let (id, amy, bob) = (block(), PrincipalId::new(), PrincipalId::new());
+
+        let lock = locks
+            .acquire(id, amy, "amy", Some(10), "fixing typos", 1_000)
+            .unwrap();
+        assert_eq!(lock.expires_at, 11_000);
+        assert_eq!(lock.holder_label(), "amy");
+
+        let held = locks.acquire(id, bob, "bob", None, "", 2_000).unwrap_err();
+        assert_eq!(held.holder, amy);
+        assert_eq!(locks.release(&id, bob, 2_000).unwrap_err().holder, amy);
+
+        // Renewing keeps the original acquisition time.
+        let renewed = locks.acquire(id, amy, "amy", Some(10), "", 5_000).unwrap();
+        assert_eq!((renewed.acquired_at, renewed.expires_at), (1_000, 15_000));
+
+        assert!(locks.get(&id, 15_000).is_none(), "expired");
+        let taken = locks.acquire(id, bob, "bob", None, "", 15_000).unwrap();
+        assert_eq!(taken.holder, bob);
+        assert_eq!(taken.acquired_at, 15_000);
+    }
+
+    #[test]
+    fn ttl_defaults_and_caps() {
+        let locks = BlockLocks::default();
+        let holder = PrincipalId::new();
+        let lock = locks.acquire(block(), holder, "", None, "", 0).unwrap();
+        assert_eq!(lock.expires_at, DEFAULT_LOCK_TTL_SECS * 1000);
+        let lock = locks
+            .acquire(block(), holder, "", Some(u64::from(u32::MAX)), "", 0)
+            .unwrap();
+        assert_eq!(lock.expires_at, MAX_LOCK_TTL_SECS * 1000);
+    }
+
+    #[test]
+    fn list_is_per_context_and_skips_expired() {
+        let locks = BlockLocks::default();
+        let holder = PrincipalId::new();
+        let (a, b) = (block(), block());
+        locks.acquire(a, holder, "", Some(5), "", 0).unwrap();
+        locks.acquire(b, holder, "", Some(60), "", 0).unwrap();
+
+        assert_eq!(locks.list(a.context_id, 1_000).len(), 1);
+        assert!(locks.list(a.context_id, 5_000).is_empty());
+        assert_eq!(locks.list(b.context_id, 5_000)[0].block_id, b);
+
+        assert_eq!(
+            locks.release(&b, holder, 6_000).unwrap().unwrap().block_id,
+            b
+        );
+        assert_eq!(locks.release(&b, holder, 6_000).unwrap(), None);
+    }
+}
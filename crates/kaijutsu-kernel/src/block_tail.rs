@@ -0,0 +1,181 @@
+//! Follow a block's text as it grows — `tail -f` for one block.
+//!
+//! A [`BlockTail`] subscribes to the block's events (via the store's
+//! [per-block fan-out](crate::block_fanout)) *before* taking its first look
+//! at the block, so nothing appended in between is missed. Each call to
+//! [`BlockTail::next`] yields the text past the last reported offset, waiting
+//! for an event whenever there is none, and ends once the block reaches a
+//! terminal status (or is deleted) with everything drained.
+//!
+//! Offsets count chars, not bytes. Tail semantics are append-only: an edit
+//! behind the cursor is not re-sent, and if the text shrinks below the cursor
+//! the cursor follows it down.
+
+use kaijutsu_crdt::{BlockId, Status};
+use kaijutsu_types::ContextId;
+
+use crate::block_fanout::BlockSubscription;
+use crate::block_store::{BlockStoreResult, SharedBlockStore};
+
+/// One step of a tail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TailEvent {
+    /// `text` was appended starting at char `offset`.
+    Append { offset: u64, text: String },
+    /// The block finished. `offset` is the final length in chars; `status` is
+    /// `None` when the block was deleted mid-tail.
+    End { offset: u64, status: Option<Status> },
+}
+
+/// A live follow of one block.
+pub struct BlockTail {
+    store: SharedBlockStore,
+    block_id: BlockId,
+    offset: usize,
+    sub: BlockSubscription,
+    ended: bool,
+}
+
+impl BlockTail {
+    /// Start following `block_id` from char `from_offset` (0 for the whole
+    /// text). Fails if the block doesn't exist.
+    pub fn new(
+        store: SharedBlockStore,
+        block_id: BlockId,
+        from_offset: u64,
+    ) -> BlockStoreResult<Self> {
+        let context_id: ContextId = block_id.context_id;
+        let sub = store.subscribe_block(block_id);
+        if store.get_block_snapshot(context_id, &block_id)?.is_none() {
+            return Err(kaijutsu_crdt::CrdtError::BlockNotFound(block_id).into());
+        }
+        Ok(Self {
+            store,
+            block_id,
+            offset: from_offset as usize,
+            sub,
+            ended: false,
+        })
+    }
+
+    pub fn block_id(&self) -> BlockId {
+        self.block_id
+    }
+
+    /// The next append, or the end. `None` after `End` has been returned.
+    pub async fn next(&mut self) -> Option<TailEvent> {
+        if self.ended {
+            return None;
+        }
+        loop {
+            let snap = self
+                .store
+                .get_block_snapshot(self.block_id.context_id, &self.block_id)
+                .ok()
+                .flatten();
+            let Some(snap) = snap else {
+                return Some(self.end(None));
+            };
+
+            let len = snap.content.chars().count();
+            if len > self.offset {
+                let start = snap
+                    .content
+                    .char_indices()
+                    .nth(self.offset)
+                    .map(|(i, _)| i)
+                    .unwrap_or(snap.content.len());
+                let event = TailEvent::Append {
+                    offset: self.offset as u64,
+                    text: snap.content[start..].to_string(),
+                };
+                self.offset = len;
+                return Some(event);
+            }
+            self.offset = len;
+            if snap.status.is_terminal() {
+                return Some(self.end(Some(snap.status)));
+            }
+
+            // Nothing new yet — wait for something to happen to this block.
+            // A lag needs no special handling: the snapshot is re-read anyway.
+            if self.sub.recv().await.is_none() {
+                return Some(self.end(Some(snap.status)));
+            }
+        }
+    }
+
+    fn end(&mut self, status: Option<Status>) -> TailEvent {
+        self.ended = true;
+        TailEvent::End {
+            offset: self.offset as u64,
+            status,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::BlockStore;
+    use crate::flows::shared_block_flow_bus;
+    use kaijutsu_crdt::{BlockKind, ContentType, Role};
+    use kaijutsu_types::{DocKind, PrincipalId};
+    use std::sync::Arc;
+
+    fn store() -> SharedBlockStore {
+        Arc::new(BlockStore::with_flows(
+            PrincipalId::system(),
+            shared_block_flow_bus(256),
+        ))
+    }
+
+    #[tokio::test]
+    async fn follows_appends_until_done() {
+        let store = store();
+        let ctx = ContextId::new();
+        store.create_document(ctx, DocKind::Conversation, None).unwrap();
+        let id = store
+            .insert_block(
+                ctx,
+                None,
+                None,
+                Role::Model,
+                BlockKind::Text,
+                "héllo",
+                Status::Running,
+                ContentType::Plain,
+            )
+            .unwrap();
+
+        let mut tail = BlockTail::new(store.clone(), id, 1).unwrap();
+        assert_eq!(
+            tail.next().await,
+            Some(TailEvent::Append { offset: 1, text: "éllo".into() })
+        );
+
+        let writer = store.clone();
+        tokio::spawn(async move {
+            writer.append_text(ctx, &id, " world").unwrap();
+            writer.set_status(ctx, &id, Status::Done).unwrap();
+        });
+        assert_eq!(
+            tail.next().await,
+            Some(TailEvent::Append { offset: 5, text: " world".into() })
+        );
+        assert_eq!(
+            tail.next().await,
+            Some(TailEvent::End { offset: 11, status: Some(Status::Done) })
+        );
+        assert_eq!(tail.next().await, None);
+    }
+
+    #[test]
+    fn unknown_block_is_an_error() {
+        let store = store();
+        let ctx = ContextId::new();
+        store.create_document(ctx, DocKind::Conversation, None).unwrap();
+        let missing = BlockId::new(ctx, PrincipalId::system(), 99);
+        assert!(BlockTail::new(store, missing, 0).is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! Line indexes — where each line of a block starts, computed once.
+//!
+//! The free functions in [`super::translate`] walk the content from the top
+//! on every call. That is fine for a chat turn and quadratic for a 50k-line
+//! log block under a multi-op edit or a search that prints context around
+//! every match. A [`LineIndex`] records every line start (in bytes and in
+//! chars) in one pass; after that a line lookup is an array read.
+//!
+//! [`LineIndexCache`] keeps the index of large blocks between calls. An
+//! entry is tagged with the block's text frontier and only served while the
+//! frontier is unchanged, so a read never sees a stale index; the
+//! [`BlockStore`](crate::block_store::BlockStore) also hands it every
+//! [`BlockFlow`] it emits, and a `TextOps` or `Deleted` drops the block's
+//! entry right away rather than waiting for eviction.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use diamond_types_extended::Frontier;
+use kaijutsu_types::{BlockId, ContextId};
+use parking_lot::Mutex;
+
+use super::error::{EditError, Result};
+use crate::flows::BlockFlow;
+
+/// Blocks smaller than this are indexed fresh on each call; walking them is
+/// cheaper than keeping an entry.
+pub const LINE_INDEX_MIN_BYTES: usize = 16 * 1024;
+
+/// Indexes kept; the oldest is dropped past this.
+pub const LINE_INDEX_CACHE_LEN: usize = 256;
+
+/// Line starts of one text. Line semantics match [`super::translate`]:
+/// lines are 0-indexed, and one past the last line addresses the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// Byte offset of line 0 and of every position after a `\n`.
+    byte_starts: Vec<usize>,
+    /// The same positions in chars.
+    char_starts: Vec<usize>,
+    len: usize,
+    char_len: usize,
+    /// `content.lines().count()`.
+    line_count: u32,
+}
+
+impl LineIndex {
+    pub fn new(content: &str) -> Self {
+        let mut byte_starts = vec![0];
+        let mut char_starts = vec![0];
+        let mut chars = 0;
+        for (byte, ch) in content.char_indices() {
+            chars += 1;
+            if ch == '\n' {
+                byte_starts.push(byte + 1);
+                char_starts.push(chars);
+            }
+        }
+        let newlines = byte_starts.len() - 1;
+        let line_count = if content.is_empty() || content.ends_with('\n') {
+            newlines
+        } else {
+            newlines + 1
+        };
+        Self {
+            byte_starts,
+            char_starts,
+            len: content.len(),
+            char_len: chars,
+            line_count: line_count as u32,
+        }
+    }
+
+    /// Byte length of the indexed text.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Lines as [`super::translate::line_count`] counts them.
+    pub fn line_count(&self) -> u32 {
+        self.line_count
+    }
+
+    /// Pieces of `content.split('\n')`: one more than the newlines.
+    pub fn segment_count(&self) -> usize {
+        self.byte_starts.len()
+    }
+
+    /// The `i`th piece of `content.split('\n')`, without its `\n`.
+    pub fn segment<'a>(&self, content: &'a str, i: usize) -> &'a str {
+        let start = self.byte_starts[i];
+        let end = self
+            .byte_starts
+            .get(i + 1)
+            .map_or(self.len, |next| next - 1);
+        &content[start..end]
+    }
+
+    /// Slot in the start tables for `line`, or where it ends the text.
+    fn slot(&self, line: u32) -> Result<Option<usize>> {
+        let line_idx = line as usize;
+        if line_idx < self.byte_starts.len() {
+            Ok(Some(line_idx))
+        } else if line <= self.line_count {
+            Ok(None)
+        } else {
+            Err(EditError::line_out_of_range(line, self.line_count))
+        }
+    }
+
+    /// [`super::translate::line_to_byte_offset`], from the index.
+    pub fn byte_offset(&self, line: u32) -> Result<usize> {
+        Ok(self.slot(line)?.map_or(self.len, |i| self.byte_starts[i]))
+    }
+
+    /// [`super::translate::line_to_char_offset`], from the index.
+    pub fn char_offset(&self, line: u32) -> Result<usize> {
+        Ok(self
+            .slot(line)?
+            .map_or(self.char_len, |i| self.char_starts[i]))
+    }
+
+    /// [`super::translate::line_range_to_byte_range`], from the index.
+    pub fn byte_range(&self, start_line: u32, end_line: u32) -> Result<(usize, usize)> {
+        check_range(start_line, end_line)?;
+        Ok((self.byte_offset(start_line)?, self.byte_offset(end_line)?))
+    }
+
+    /// [`super::translate::line_range_to_char_range`], from the index.
+    pub fn char_range(&self, start_line: u32, end_line: u32) -> Result<(usize, usize)> {
+        check_range(start_line, end_line)?;
+        Ok((self.char_offset(start_line)?, self.char_offset(end_line)?))
+    }
+
+    /// `content.lines().skip(start).take(end - start)` without walking the
+    /// lines before `start`.
+    pub fn lines<'a>(&self, content: &'a str, start: u32, end: u32) -> std::str::Lines<'a> {
+        let from = self.byte_offset(start).unwrap_or(self.len);
+        let to = self.byte_offset(end).unwrap_or(self.len).max(from);
+        content[from..to].lines()
+    }
+
+    /// [`super::translate::extract_lines_with_numbers`], from the index.
+    pub fn numbered(&self, content: &str, start: u32, end: u32) -> String {
+        let width = self.line_count.to_string().len().max(4);
+        self.lines(content, start, end)
+            .enumerate()
+            .map(|(i, line)| format!("{:>width$}→ {}", start as usize + i + 1, line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// [`super::translate::validate_expected_text`], from the index.
+    pub fn validate_expected(
+        &self,
+        content: &str,
+        start_line: u32,
+        end_line: u32,
+        expected: &str,
+    ) -> Result<()> {
+        if start_line > self.line_count {
+            return Err(EditError::line_out_of_range(start_line, self.line_count));
+        }
+        let actual = self
+            .lines(content, start_line, end_line)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let expected = expected.trim_end_matches('\n');
+        let actual = actual.trim_end_matches('\n');
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(EditError::content_mismatch(
+                expected, actual, start_line, end_line,
+            ))
+        }
+    }
+}
+
+fn check_range(start_line: u32, end_line: u32) -> Result<()> {
+    if end_line < start_line {
+        return Err(EditError::InvalidParams(format!(
+            "end_line ({}) must be >= start_line ({})",
+            end_line, start_line
+        )));
+    }
+    Ok(())
+}
+
+struct CachedIndex {
+    frontier: Frontier,
+    index: Arc<LineIndex>,
+}
+
+#[derive(Default)]
+struct Entries {
+    by_block: HashMap<BlockId, CachedIndex>,
+    /// Blocks oldest first, for eviction.
+    order: VecDeque<BlockId>,
+}
+
+/// Line indexes of large blocks, each valid for one text frontier. Owned by
+/// the block store; see
+/// [`BlockStore::line_index`](crate::block_store::BlockStore::line_index).
+#[derive(Default)]
+pub struct LineIndexCache {
+    entries: Mutex<Entries>,
+}
+
+impl LineIndexCache {
+    /// The index of `content`, which is `block_id`'s text at `frontier`.
+    /// Served from the cache when the frontier matches; built (and kept, for
+    /// a block of at least [`LINE_INDEX_MIN_BYTES`]) otherwise.
+    pub fn get(&self, block_id: BlockId, frontier: &Frontier, content: &str) -> Arc<LineIndex> {
+        if content.len() < LINE_INDEX_MIN_BYTES {
+            return Arc::new(LineIndex::new(content));
+        }
+        if let Some(cached) = self.entries.lock().by_block.get(&block_id)
+            && cached.frontier == *frontier
+            && cached.index.len() == content.len()
+        {
+            return cached.index.clone();
+        }
+
+        let index = Arc::new(LineIndex::new(content));
+        let mut entries = self.entries.lock();
+        let cached = CachedIndex {
+            frontier: frontier.clone(),
+            index: index.clone(),
+        };
+        if entries.by_block.insert(block_id, cached).is_none() {
+            entries.order.push_back(block_id);
+        }
+        while entries.order.len() > LINE_INDEX_CACHE_LEN {
+            if let Some(old) = entries.order.pop_front() {
+                entries.by_block.remove(&old);
+            }
+        }
+        index
+    }
+
+    /// Drop the entry of a block whose text just changed or went away.
+    pub fn observe(&self, flow: &BlockFlow) {
+        let block_id = match flow {
+            BlockFlow::TextOps { block_id, .. } | BlockFlow::Deleted { block_id, .. } => block_id,
+            _ => return,
+        };
+        let mut entries = self.entries.lock();
+        if entries.by_block.remove(block_id).is_some() {
+            entries.order.retain(|id| id != block_id);
+        }
+    }
+
+    /// Drop every entry of a document.
+    pub fn forget_context(&self, context_id: ContextId) {
+        let mut entries = self.entries.lock();
+        entries.by_block.retain(|id, _| id.context_id != context_id);
+        entries.order.retain(|id| id.context_id != context_id);
+    }
+
+    /// Blocks with a cached index.
+    pub fn len(&self) -> usize {
+        self.entries.lock().by_block.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_tools::translate::{
+        extract_lines_with_numbers, line_count, line_range_to_byte_range, line_range_to_char_range,
+        line_to_byte_offset, line_to_char_offset, validate_expected_text,
+    };
+
+    const SAMPLES: &[&str] = &[
+        "",
+        "\n",
+        "hello",
+        "hello\n",
+        "hello\nworld",
+        "héllo\nwörld\n",
+        "改善 → done\nDELETE ME\nkeep",
+        "a\r\nb\r\n\nc",
+    ];
+
+    #[test]
+    fn offsets_match_the_walking_helpers() {
+        for content in SAMPLES {
+            let index = LineIndex::new(content);
+            assert_eq!(index.line_count(), line_count(content), "{content:?}");
+            for line in 0..=index.line_count() + 2 {
+                assert_eq!(
+                    index.byte_offset(line),
+                    line_to_byte_offset(content, line),
+                    "{content:?} line {line}"
+                );
+                assert_eq!(
+                    index.char_offset(line),
+                    line_to_char_offset(content, line),
+                    "{content:?} line {line}"
+                );
+                for end in line..=index.line_count() + 1 {
+                    assert_eq!(
+                        index.byte_range(line, end),
+                        line_range_to_byte_range(content, line, end)
+                    );
+                    assert_eq!(
+                        index.char_range(line, end),
+                        line_range_to_char_range(content, line, end)
+                    );
+                    assert_eq!(
+                        index.numbered(content, line, end),
+                        extract_lines_with_numbers(content, line, end),
+                        "{content:?} {line}..{end}"
+                    );
+                }
+            }
+            assert_eq!(
+                index.segment_count(),
+                content.split('\n').count(),
+                "{content:?}"
+            );
+            for (i, piece) in content.split('\n').enumerate() {
+                assert_eq!(index.segment(content, i), piece);
+            }
+        }
+    }
+
+    #[test]
+    fn expected_text_checks_match() {
+        let content = "one\ntwo\nthree\nfour\n";
+        let index = LineIndex::new(content);
+        for (start, end, expected) in [(1, 3, "two\nthree"), (1, 2, "wrong"), (9, 10, "")] {
+            assert_eq!(
+                index.validate_expected(content, start, end, expected),
+                validate_expected_text(content, start, end, expected)
+            );
+        }
+    }
+
+    #[test]
+    fn cache_serves_one_frontier_and_drops_on_edit() {
+        let cache = LineIndexCache::default();
+        let block = BlockId::new(ContextId::new(), kaijutsu_types::PrincipalId::new(), 1);
+        let content = "a log line\n".repeat(LINE_INDEX_MIN_BYTES / 8);
+        let frontier = Frontier::root();
+
+        let first = cache.get(block, &frontier, &content);
+        let second = cache.get(block, &frontier, &content);
+        assert!(
+            Arc::ptr_eq(&first, &second),
+            "same frontier reuses the index"
+        );
+        assert_eq!(cache.len(), 1);
+
+        cache.observe(&BlockFlow::Deleted {
+            context_id: block.context_id,
+            block_id: block,
+            source: crate::flows::OpSource::Local,
+        });
+        assert!(cache.is_empty());
+
+        // Small blocks aren't kept.
+        cache.get(block, &frontier, "short\n");
+        assert!(cache.is_empty());
+    }
+}
@@ -15,6 +15,9 @@
 //! | `block_search` | Search within a block using regex |
 //! | `block_list` | List blocks with filters |
 //! | `block_status` | Set block status |
+//! | `block_reflow` | Rewrap prose to a width in one edit |
+//! | `block_lock` / `block_unlock` | Advisory "I'm editing this" locks |
+//! | `block_locks` | Who is editing what, and the lock policy |
 //!
 //! # Architecture
 //!
@@ -33,7 +36,8 @@
 //!                      ▼
 //! ┌─────────────────────────────────────────┐
 //! │         Translation Layer               │
-//! │   (line ↔ byte offsets, CAS)            │
+//! │   (line ↔ byte offsets, CAS,            │
+//! │    cached per-block line indexes)       │
 //! └────────────────────┬────────────────────┘
 //!                      │ CRDT operations
 //!                      ▼
@@ -43,11 +47,14 @@
 //! ```
 
 pub mod error;
+pub mod line_index;
 pub mod translate;
 
 // Re-export error types
 pub use error::{EditError, Result};
 
+pub use line_index::{LineIndex, LineIndexCache};
+
 // Re-export translation utilities. The `*_char_*` twins are the ones to feed
 // `edit_text`/`edit_text_as` (the CRDT text layer is char-indexed); the byte
 // variants are for byte-oriented consumers (string slicing, replace_range).
@@ -0,0 +1,340 @@
+//! All-or-nothing block edits — one tool call, one outcome.
+//!
+//! A [`BlockTransaction`] applies writes to one document through the
+//! [`BlockStore`] as usual (journaled, emitted, visible to readers as they
+//! land) and remembers how to undo each. [`commit`](BlockTransaction::commit)
+//! keeps them; [`rollback`](BlockTransaction::rollback), or dropping the
+//! transaction uncommitted — a `?` mid-way — undoes them newest first. The
+//! CRDT has no history erasure, so an undo is an inverse forward write: the
+//! deleted text put back, the inserted text deleted, the old label restored,
+//! an inserted block deleted.
+//!
+//! **Detach, don't retract** (as the editor's `ZQ`): if anyone else wrote a
+//! touched block's text after the transaction did, the inverses no longer
+//! line up and would clobber their work, so the rollback refuses and leaves
+//! every block as it is.
+
+use kaijutsu_crdt::{BlockId, BlockKind, ContentType, Role, Status};
+use kaijutsu_types::{ContextId, PrincipalId};
+
+use crate::block_store::{BlockStore, BlockStoreError, BlockStoreResult};
+
+/// How to undo one applied write.
+enum Undo {
+    /// Chars `pos..pos + inserted` replaced `deleted`.
+    Text {
+        block_id: BlockId,
+        pos: usize,
+        inserted: usize,
+        deleted: String,
+    },
+    Label {
+        block_id: BlockId,
+        previous: Option<String>,
+    },
+    Language {
+        block_id: BlockId,
+        previous: Option<String>,
+    },
+    Inserted {
+        block_id: BlockId,
+    },
+}
+
+/// Writes to one document, undone together unless committed. Start one with
+/// [`BlockStore::transaction`].
+pub struct BlockTransaction<'a> {
+    store: &'a BlockStore,
+    context_id: ContextId,
+    principal_id: Option<PrincipalId>,
+    undo: Vec<Undo>,
+    /// Each text-edited block's content as this transaction left it.
+    written: Vec<(BlockId, String)>,
+    done: bool,
+}
+
+impl<'a> BlockTransaction<'a> {
+    pub(crate) fn new(
+        store: &'a BlockStore,
+        context_id: ContextId,
+        principal_id: Option<PrincipalId>,
+    ) -> Self {
+        Self {
+            store,
+            context_id,
+            principal_id,
+            undo: Vec::new(),
+            written: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Number of writes applied so far.
+    pub fn len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty()
+    }
+
+    fn content(&self, block_id: &BlockId) -> BlockStoreResult<String> {
+        self.store
+            .get_block_snapshot(self.context_id, block_id)?
+            .map(|s| s.content)
+            .ok_or(BlockStoreError::Crdt(
+                kaijutsu_crdt::CrdtError::BlockNotFound(*block_id),
+            ))
+    }
+
+    fn note_written(&mut self, block_id: &BlockId) -> BlockStoreResult<()> {
+        let content = self.content(block_id)?;
+        match self.written.iter_mut().find(|(id, _)| id == block_id) {
+            Some((_, last)) => *last = content,
+            None => self.written.push((*block_id, content)),
+        }
+        Ok(())
+    }
+
+    /// [`BlockStore::edit_text_as`] — `pos` and `delete` count chars.
+    pub fn edit_text(
+        &mut self,
+        block_id: &BlockId,
+        pos: usize,
+        insert: &str,
+        delete: usize,
+    ) -> BlockStoreResult<()> {
+        let deleted: String = self
+            .content(block_id)?
+            .chars()
+            .skip(pos)
+            .take(delete)
+            .collect();
+        self.store.edit_text_as(
+            self.context_id,
+            block_id,
+            pos,
+            insert,
+            delete,
+            self.principal_id,
+        )?;
+        self.undo.push(Undo::Text {
+            block_id: *block_id,
+            pos,
+            inserted: insert.chars().count(),
+            deleted,
+        });
+        self.note_written(block_id)
+    }
+
+    /// [`BlockStore::set_label`].
+    pub fn set_label(&mut self, block_id: &BlockId, label: Option<String>) -> BlockStoreResult<()> {
+        let previous = self
+            .store
+            .get_block_snapshot(self.context_id, block_id)?
+            .and_then(|s| s.label);
+        self.store.set_label(self.context_id, block_id, label)?;
+        self.undo.push(Undo::Label {
+            block_id: *block_id,
+            previous,
+        });
+        Ok(())
+    }
+
+    /// [`BlockStore::set_language`].
+    pub fn set_language(
+        &mut self,
+        block_id: &BlockId,
+        language: Option<String>,
+    ) -> BlockStoreResult<()> {
+        let previous = self
+            .store
+            .get_block_snapshot(self.context_id, block_id)?
+            .and_then(|s| s.language);
+        self.store
+            .set_language(self.context_id, block_id, language)?;
+        self.undo.push(Undo::Language {
+            block_id: *block_id,
+            previous,
+        });
+        Ok(())
+    }
+
+    /// [`BlockStore::insert_block_as`], at the end of `parent_id`'s children
+    /// (or the document).
+    pub fn insert_block(
+        &mut self,
+        parent_id: Option<&BlockId>,
+        role: Role,
+        kind: BlockKind,
+        content: impl Into<String>,
+        status: Status,
+        content_type: ContentType,
+    ) -> BlockStoreResult<BlockId> {
+        let block_id = self.store.insert_block_as(
+            self.context_id,
+            parent_id,
+            None,
+            role,
+            kind,
+            content,
+            status,
+            content_type,
+            self.principal_id,
+        )?;
+        self.undo.push(Undo::Inserted { block_id });
+        Ok(block_id)
+    }
+
+    /// Keep every write.
+    pub fn commit(mut self) {
+        self.done = true;
+    }
+
+    /// Undo every write, newest first. Refuses — changing nothing — when
+    /// another writer touched a block's text since this transaction did.
+    pub fn rollback(mut self) -> BlockStoreResult<()> {
+        self.done = true;
+        self.undo_all()
+    }
+
+    fn undo_all(&mut self) -> BlockStoreResult<()> {
+        for (block_id, expected) in &self.written {
+            // A block inserted here and deleted since has nothing to guard.
+            let Some(snapshot) = self.store.get_block_snapshot(self.context_id, block_id)? else {
+                continue;
+            };
+            if snapshot.content != *expected {
+                return Err(BlockStoreError::Validation(format!(
+                    "block {block_id} was edited by another writer during the transaction; \
+                     not rolled back"
+                )));
+            }
+        }
+        let (store, context_id, principal_id) = (self.store, self.context_id, self.principal_id);
+        while let Some(undo) = self.undo.pop() {
+            match undo {
+                Undo::Text {
+                    block_id,
+                    pos,
+                    inserted,
+                    deleted,
+                } => store.edit_text_as(
+                    context_id,
+                    &block_id,
+                    pos,
+                    &deleted,
+                    inserted,
+                    principal_id,
+                )?,
+                Undo::Label { block_id, previous } => {
+                    store.set_label(context_id, &block_id, previous)?
+                }
+                Undo::Language { block_id, previous } => {
+                    store.set_language(context_id, &block_id, previous)?
+                }
+                Undo::Inserted { block_id } => store.delete_block(context_id, &block_id)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for BlockTransaction<'_> {
+    fn drop(&mut self) {
+        if self.done || self.undo.is_empty() {
+            return;
+        }
+        if let Err(e) = self.undo_all() {
+            tracing::error!(
+                context = %self.context_id,
+                "block transaction rollback failed: {e}"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_types::DocKind;
+
+    fn store_with_block(content: &str) -> (BlockStore, ContextId, BlockId) {
+        let store = BlockStore::new(PrincipalId::new());
+        let ctx = ContextId::new();
+        store
+            .create_document(ctx, DocKind::Conversation, None)
+            .unwrap();
+        let block_id = store
+            .insert_block(
+                ctx,
+                None,
+                None,
+                Role::Model,
+                BlockKind::Text,
+                content,
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        (store, ctx, block_id)
+    }
+
+    fn snapshot(store: &BlockStore, ctx: ContextId, id: &BlockId) -> kaijutsu_crdt::BlockSnapshot {
+        store.get_block_snapshot(ctx, id).unwrap().unwrap()
+    }
+
+    #[test]
+    fn dropped_transaction_undoes_every_write() {
+        let (store, ctx, id) = store_with_block("one\ntwo\nthree\n");
+        let blocks_before = store.block_snapshots(ctx).unwrap().len();
+        {
+            let mut tx = store.transaction(ctx, None);
+            tx.set_label(&id, Some("notes".into())).unwrap();
+            tx.edit_text(&id, 4, "2", 3).unwrap();
+            tx.edit_text(&id, 0, "zero\n", 0).unwrap();
+            tx.set_language(&id, Some("markdown".into())).unwrap();
+            tx.insert_block(
+                None,
+                Role::Model,
+                BlockKind::Text,
+                "extra",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+            assert_eq!(snapshot(&store, ctx, &id).content, "zero\none\n2\nthree\n");
+            // A failing step: the transaction drops uncommitted.
+            assert!(tx.edit_text(&id, 999, "x", 0).is_err());
+        }
+        let after = snapshot(&store, ctx, &id);
+        assert_eq!(after.content, "one\ntwo\nthree\n");
+        assert_eq!(after.label, None);
+        assert_eq!(after.language, None);
+        assert_eq!(
+            store.block_snapshots(ctx).unwrap().len(),
+            blocks_before,
+            "the inserted block is gone"
+        );
+    }
+
+    #[test]
+    fn committed_transaction_keeps_its_writes() {
+        let (store, ctx, id) = store_with_block("abc");
+        let mut tx = store.transaction(ctx, None);
+        tx.edit_text(&id, 3, "def", 0).unwrap();
+        assert_eq!(tx.len(), 1);
+        tx.commit();
+        assert_eq!(snapshot(&store, ctx, &id).content, "abcdef");
+    }
+
+    #[test]
+    fn rollback_refuses_after_a_foreign_write() {
+        let (store, ctx, id) = store_with_block("abc");
+        let mut tx = store.transaction(ctx, None);
+        tx.edit_text(&id, 3, "def", 0).unwrap();
+        store.append_text(ctx, &id, "!").unwrap();
+        assert!(matches!(tx.rollback(), Err(BlockStoreError::Validation(_))));
+        assert_eq!(snapshot(&store, ctx, &id).content, "abcdef!");
+    }
+}
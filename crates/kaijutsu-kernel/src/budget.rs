@@ -0,0 +1,316 @@
+//! Per-context execution budgets and the kernel-wide tool halt.
+//!
+//! [`BudgetTracker`] counts what each context spends — broker tool calls and
+//! shell wall time — over a sliding [`BUDGET_WINDOW_SECS`] window and refuses
+//! work once a context's [`ExecutionBudget`] is spent. It also holds the
+//! kill switch: while a [`ToolHalt`] is engaged, every tool call and shell
+//! command in the kernel is refused.
+//!
+//! One tracker per kernel, owned by the broker ([`Broker::budgets`]). The
+//! broker admits every `call_tool` through [`BudgetTracker::admit_tool_call`];
+//! the shell paths (the `builtin.shell` server and the RPC `shell_execute`)
+//! bracket each command with [`BudgetTracker::admit_shell`] and
+//! [`BudgetTracker::record_shell`].
+//!
+//! Budgets persist in the kernel DB (`context_budgets`) and are cached here on
+//! first use; usage and the halt are in-memory and reset with the kernel.
+//!
+//! [`Broker::budgets`]: crate::mcp::Broker::budgets
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use kaijutsu_types::{
+    BUDGET_WINDOW_SECS, BudgetStatus, ContextId, ExecutionBudget, PrincipalId, ToolHalt,
+};
+use parking_lot::Mutex;
+
+use crate::block_store::DbHandle;
+use crate::kernel_db::KernelDbResult;
+use crate::mcp::PolicyError;
+
+const WINDOW: Duration = Duration::from_secs(BUDGET_WINDOW_SECS);
+
+/// One context's spending inside the window, oldest first.
+#[derive(Debug, Default)]
+struct Usage {
+    tool_calls: VecDeque<Instant>,
+    /// `(finished, wall time)` per shell command.
+    shell: VecDeque<(Instant, Duration)>,
+}
+
+impl Usage {
+    fn prune(&mut self, now: Instant) {
+        let expired = |at: Instant| now.saturating_duration_since(at) >= WINDOW;
+        while self.tool_calls.front().is_some_and(|at| expired(*at)) {
+            self.tool_calls.pop_front();
+        }
+        while self.shell.front().is_some_and(|(at, _)| expired(*at)) {
+            self.shell.pop_front();
+        }
+    }
+
+    fn shell_time(&self) -> Duration {
+        self.shell.iter().map(|(_, d)| *d).sum()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tool_calls.is_empty() && self.shell.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Cached budgets; `None` caches "no budget".
+    budgets: HashMap<ContextId, Option<ExecutionBudget>>,
+    usage: HashMap<ContextId, Usage>,
+    halt: Option<ToolHalt>,
+}
+
+/// Budget accounting and the kill switch for one kernel.
+#[derive(Debug, Default)]
+pub struct BudgetTracker {
+    db: Mutex<Option<DbHandle>>,
+    state: Mutex<State>,
+}
+
+impl BudgetTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wire the kernel DB that budgets are loaded from and saved to. Without
+    /// one, budgets live only in memory (bare-broker tests).
+    pub fn set_db(&self, db: DbHandle) {
+        *self.db.lock() = Some(db);
+    }
+
+    /// The context's budget, loading it from the DB on first use. A DB error
+    /// is logged and treated as "no budget" without caching, so the next
+    /// call retries.
+    pub fn budget(&self, context_id: ContextId) -> Option<ExecutionBudget> {
+        if let Some(cached) = self.state.lock().budgets.get(&context_id) {
+            return *cached;
+        }
+        let Some(db) = self.db.lock().clone() else {
+            return None;
+        };
+        let loaded = db.lock().get_context_budget(context_id);
+        match loaded {
+            Ok(budget) => {
+                self.state.lock().budgets.insert(context_id, budget);
+                budget
+            }
+            Err(e) => {
+                tracing::warn!(context = %context_id, error = %e, "failed to load execution budget");
+                None
+            }
+        }
+    }
+
+    /// Set or clear (`None`) the context's budget, persisting it first.
+    /// Usage already counted in the window still applies.
+    pub fn set_budget(
+        &self,
+        context_id: ContextId,
+        budget: Option<ExecutionBudget>,
+    ) -> KernelDbResult<()> {
+        let budget = budget.filter(|b| !b.is_empty());
+        if let Some(db) = self.db.lock().clone() {
+            db.lock().set_context_budget(context_id, budget.as_ref())?;
+        } else if let Some(b) = &budget {
+            b.validate()
+                .map_err(crate::kernel_db::KernelDbError::Validation)?;
+        }
+        self.state.lock().budgets.insert(context_id, budget);
+        Ok(())
+    }
+
+    /// Admit one broker tool call, counting it against the budget. Refused
+    /// while halted or once the window's calls are spent.
+    pub fn admit_tool_call(&self, context_id: ContextId, now: Instant) -> Result<(), PolicyError> {
+        let budget = self.budget(context_id);
+        let mut state = self.state.lock();
+        if let Some(halt) = &state.halt {
+            return Err(PolicyError::Halted {
+                reason: halt.reason.clone(),
+            });
+        }
+        let usage = state.usage.entry(context_id).or_default();
+        usage.prune(now);
+        if let Some(max) = budget.and_then(|b| b.max_tool_calls_per_hour)
+            && usage.tool_calls.len() >= max as usize
+        {
+            return Err(PolicyError::BudgetExhausted {
+                context: context_id,
+                limit: format!("{max} tool calls per hour"),
+            });
+        }
+        usage.tool_calls.push_back(now);
+        Ok(())
+    }
+
+    /// Admit a shell command. Refused while halted or once the window's
+    /// shell time is spent; a command admitted with time left runs to
+    /// completion and is charged afterwards via [`Self::record_shell`].
+    pub fn admit_shell(&self, context_id: ContextId, now: Instant) -> Result<(), PolicyError> {
+        self.check_halt()?;
+        let Some(max) = self
+            .budget(context_id)
+            .and_then(|b| b.max_shell_secs_per_hour)
+        else {
+            return Ok(());
+        };
+        let mut state = self.state.lock();
+        let Some(usage) = state.usage.get_mut(&context_id) else {
+            return Ok(());
+        };
+        usage.prune(now);
+        if usage.shell_time() >= Duration::from_secs(max) {
+            return Err(PolicyError::BudgetExhausted {
+                context: context_id,
+                limit: format!("{max}s of shell time per hour"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Charge a finished shell command's wall time to the context.
+    pub fn record_shell(&self, context_id: ContextId, finished: Instant, elapsed: Duration) {
+        let mut state = self.state.lock();
+        let usage = state.usage.entry(context_id).or_default();
+        usage.prune(finished);
+        usage.shell.push_back((finished, elapsed));
+    }
+
+    /// Refuse with [`PolicyError::Halted`] while the halt is engaged.
+    pub fn check_halt(&self) -> Result<(), PolicyError> {
+        match &self.state.lock().halt {
+            Some(halt) => Err(PolicyError::Halted {
+                reason: halt.reason.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The context's budget and its spending in the current window.
+    pub fn status(&self, context_id: ContextId, now: Instant) -> BudgetStatus {
+        let budget = self.budget(context_id);
+        let mut state = self.state.lock();
+        let (tool_calls, shell_secs) = match state.usage.get_mut(&context_id) {
+            Some(usage) => {
+                usage.prune(now);
+                (usage.tool_calls.len() as u32, usage.shell_time().as_secs())
+            }
+            None => (0, 0),
+        };
+        if state.usage.get(&context_id).is_some_and(Usage::is_empty) {
+            state.usage.remove(&context_id);
+        }
+        BudgetStatus {
+            context_id,
+            budget,
+            tool_calls,
+            shell_secs,
+            halt: state.halt.clone(),
+        }
+    }
+
+    /// Engage the kill switch. Re-engaging replaces the reason and author.
+    pub fn halt(&self, reason: impl Into<String>, by: PrincipalId) -> ToolHalt {
+        let halt = ToolHalt {
+            reason: reason.into(),
+            by,
+            at: kaijutsu_types::now_millis(),
+        };
+        tracing::warn!(reason = %halt.reason, by = %by, "tool execution halted");
+        self.state.lock().halt = Some(halt.clone());
+        halt
+    }
+
+    /// Release the kill switch, returning the halt that was engaged.
+    pub fn resume(&self) -> Option<ToolHalt> {
+        let released = self.state.lock().halt.take();
+        if released.is_some() {
+            tracing::info!("tool execution resumed");
+        }
+        released
+    }
+
+    /// The engaged halt, if any.
+    pub fn halted(&self) -> Option<ToolHalt> {
+        self.state.lock().halt.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_calls_are_refused_past_the_budget_until_the_window_slides() {
+        let tracker = BudgetTracker::new();
+        let ctx = ContextId::new();
+        let budget = ExecutionBudget {
+            max_tool_calls_per_hour: Some(2),
+            ..Default::default()
+        };
+        tracker.set_budget(ctx, Some(budget)).unwrap();
+
+        let t0 = Instant::now();
+        tracker.admit_tool_call(ctx, t0).unwrap();
+        tracker.admit_tool_call(ctx, t0).unwrap();
+        let refused = tracker.admit_tool_call(ctx, t0);
+        assert!(matches!(refused, Err(PolicyError::BudgetExhausted { .. })));
+        assert_eq!(tracker.status(ctx, t0).tool_calls, 2);
+
+        // An hour later the first calls fall out of the window.
+        let later = t0 + WINDOW;
+        tracker.admit_tool_call(ctx, later).unwrap();
+        assert_eq!(tracker.status(ctx, later).tool_calls_remaining(), Some(1));
+
+        // Other contexts are unaffected.
+        tracker.admit_tool_call(ContextId::new(), t0).unwrap();
+    }
+
+    #[test]
+    fn shell_time_is_charged_after_the_command() {
+        let tracker = BudgetTracker::new();
+        let ctx = ContextId::new();
+        let budget = ExecutionBudget {
+            max_shell_secs_per_hour: Some(60),
+            ..Default::default()
+        };
+        tracker.set_budget(ctx, Some(budget)).unwrap();
+
+        let t0 = Instant::now();
+        tracker.admit_shell(ctx, t0).unwrap();
+        tracker.record_shell(ctx, t0, Duration::from_secs(45));
+        tracker.admit_shell(ctx, t0).unwrap();
+        tracker.record_shell(ctx, t0, Duration::from_secs(30));
+        assert!(tracker.admit_shell(ctx, t0).is_err());
+        assert_eq!(tracker.status(ctx, t0).shell_secs, 75);
+    }
+
+    #[test]
+    fn halt_refuses_everything_until_resumed() {
+        let tracker = BudgetTracker::new();
+        let ctx = ContextId::new();
+        let t0 = Instant::now();
+
+        tracker.halt("runaway loop", PrincipalId::system());
+        assert!(matches!(
+            tracker.admit_tool_call(ctx, t0),
+            Err(PolicyError::Halted { .. })
+        ));
+        assert!(tracker.admit_shell(ctx, t0).is_err());
+        assert_eq!(
+            tracker.status(ctx, t0).halt.map(|h| h.reason),
+            Some("runaway loop".to_string())
+        );
+
+        assert!(tracker.resume().is_some());
+        tracker.admit_tool_call(ctx, t0).unwrap();
+        tracker.admit_shell(ctx, t0).unwrap();
+    }
+}
@@ -0,0 +1,293 @@
+//! Completions for the app's prompt editor.
+//!
+//! [`Completer::complete`] finds the token under the cursor with
+//! [`kaijutsu_types::token_at`] and offers whole replacements for it:
+//!
+//! - `@name` — seat usernames (`KernelDb::seat_usernames_with_prefix`), then
+//!   context labels from the drift router, i.e. the names [`crate::mention`]
+//!   resolves.
+//! - `/verb` at the start of the prompt — `kj` subcommands, with their
+//!   one-line help.
+//! - `#ref` — blocks of the current context, newest first, matched by seq
+//!   or by text in their first line; completes to the full block key.
+//! - anything with a `/` — VFS entries, relative paths resolved against the
+//!   context's cwd. Directories complete with a trailing `/` so the next Tab
+//!   descends.
+
+use std::path::{Path, PathBuf};
+
+use kaijutsu_types::{BlockSnapshot, CompletionToken, ContextId, token_at};
+use parking_lot::Mutex;
+
+use crate::block_store::SharedBlockStore;
+use crate::drift::SharedDriftRouter;
+use crate::kernel_db::KernelDb;
+use crate::vfs::{FileType, MountTable, VfsOps};
+
+/// Most completions returned for one request.
+pub const MAX_COMPLETIONS: usize = 20;
+
+/// Longest first-line excerpt shown for a block completion, in chars.
+const BLOCK_EXCERPT_CHARS: usize = 60;
+
+/// What a completion completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Command,
+    Path,
+    Mention,
+    Block,
+}
+
+/// One candidate. `text` replaces the whole token under the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub text: String,
+    /// What the popup shows — usually `text` plus a hint.
+    pub display: String,
+    pub kind: CompletionKind,
+}
+
+/// The kernel state completions are drawn from.
+pub struct Completer<'a> {
+    pub db: &'a Mutex<KernelDb>,
+    pub drift: &'a SharedDriftRouter,
+    pub store: &'a SharedBlockStore,
+    pub vfs: &'a MountTable,
+}
+
+impl Completer<'_> {
+    /// Completions for the token ending at byte `cursor` of `text`, typed in
+    /// `context_id` (whose shell sits in `cwd`, if it has one).
+    pub async fn complete(
+        &self,
+        context_id: ContextId,
+        cwd: Option<&Path>,
+        text: &str,
+        cursor: usize,
+    ) -> Vec<Completion> {
+        let Some((_, token)) = token_at(text, cursor) else {
+            return Vec::new();
+        };
+        let mut out = match token {
+            CompletionToken::Mention(prefix) => self.mentions(prefix),
+            CompletionToken::Command(prefix) => commands(prefix),
+            CompletionToken::Block(prefix) => self.blocks(context_id, prefix),
+            CompletionToken::Path(token) => self.paths(cwd, token).await,
+        };
+        out.truncate(MAX_COMPLETIONS);
+        out
+    }
+
+    fn mentions(&self, prefix: &str) -> Vec<Completion> {
+        let mut names = self
+            .db
+            .lock()
+            .seat_usernames_with_prefix(prefix, MAX_COMPLETIONS)
+            .unwrap_or_else(|e| {
+                tracing::warn!("completion: seat lookup for @{prefix} failed: {e}");
+                Vec::new()
+            });
+        let seats = names.len();
+        for ctx in self.drift.read().list_contexts() {
+            if let Some(label) = ctx.label.as_deref()
+                && label.starts_with(prefix)
+                && !names.iter().any(|n| n == label)
+            {
+                names.push(label.to_string());
+            }
+        }
+        names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| Completion {
+                text: format!("@{name}"),
+                display: format!("@{name}  {}", if i < seats { "user" } else { "context" }),
+                kind: CompletionKind::Mention,
+            })
+            .collect()
+    }
+
+    fn blocks(&self, context_id: ContextId, prefix: &str) -> Vec<Completion> {
+        let Ok(blocks) = self.store.block_snapshots(context_id) else {
+            return Vec::new();
+        };
+        let needle = prefix.to_lowercase();
+        let by_seq = !prefix.is_empty() && prefix.bytes().all(|b| b.is_ascii_digit());
+        blocks
+            .iter()
+            .rev()
+            .filter(|b| {
+                if by_seq {
+                    b.id.seq.to_string().starts_with(prefix)
+                } else {
+                    first_line(b).to_lowercase().contains(&needle)
+                }
+            })
+            .take(MAX_COMPLETIONS)
+            .map(|b| Completion {
+                text: format!("#{}", b.id.to_key()),
+                display: format!("#{} {}: {}", b.id.seq, b.role, excerpt(first_line(b))),
+                kind: CompletionKind::Block,
+            })
+            .collect()
+    }
+
+    async fn paths(&self, cwd: Option<&Path>, token: &str) -> Vec<Completion> {
+        // Split "src/ma" into the directory as typed ("src/") and the name
+        // prefix ("ma"); the typed directory is kept verbatim in the result.
+        let split = token.rfind('/').map_or(0, |i| i + 1);
+        let (typed_dir, name_prefix) = token.split_at(split);
+        let dir: PathBuf = if typed_dir.starts_with('/') {
+            PathBuf::from(typed_dir)
+        } else {
+            cwd.unwrap_or(Path::new("/")).join(typed_dir)
+        };
+
+        let entries = match self.vfs.readdir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::debug!("completion: readdir {} failed: {e}", dir.display());
+                return Vec::new();
+            }
+        };
+        let mut out: Vec<Completion> = entries
+            .into_iter()
+            .filter(|e| e.name.starts_with(name_prefix))
+            .filter(|e| name_prefix.starts_with('.') || !e.name.starts_with('.'))
+            .map(|e| {
+                let slash = if e.kind == FileType::Directory { "/" } else { "" };
+                Completion {
+                    text: format!("{typed_dir}{}{slash}", e.name),
+                    display: format!("{}{slash}", e.name),
+                    kind: CompletionKind::Path,
+                }
+            })
+            .collect();
+        out.sort_by(|a, b| a.text.cmp(&b.text));
+        out
+    }
+}
+
+/// `kj` subcommands starting with `prefix`, as `/verb`.
+fn commands(prefix: &str) -> Vec<Completion> {
+    crate::kj::kj_command()
+        .get_subcommands()
+        .filter(|c| !c.is_hide_set() && c.get_name().starts_with(prefix))
+        .map(|c| {
+            let about = c.get_about().map(|a| a.to_string()).unwrap_or_default();
+            Completion {
+                text: format!("/{}", c.get_name()),
+                display: format!("/{}  {}", c.get_name(), about),
+                kind: CompletionKind::Command,
+            }
+        })
+        .collect()
+}
+
+fn first_line(block: &BlockSnapshot) -> &str {
+    block.content.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim()
+}
+
+fn excerpt(line: &str) -> String {
+    match line.char_indices().nth(BLOCK_EXCERPT_CHARS) {
+        Some((i, _)) => format!("{}…", &line[..i]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::BlockStore;
+    use crate::drift::shared_drift_router;
+    use crate::vfs::backends::MemoryBackend;
+    use kaijutsu_crdt::{BlockKind, ContentType, Role, Status};
+    use kaijutsu_types::{DocKind, PrincipalId};
+    use std::sync::Arc;
+
+    struct Fixture {
+        db: Mutex<KernelDb>,
+        drift: SharedDriftRouter,
+        store: SharedBlockStore,
+        vfs: MountTable,
+        ctx: ContextId,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let store: SharedBlockStore = Arc::new(BlockStore::new(PrincipalId::system()));
+            let ctx = ContextId::new();
+            store.create_document(ctx, DocKind::Conversation, None).unwrap();
+            for text in ["first prompt", "second: fix the parser"] {
+                store
+                    .insert_block(
+                        ctx,
+                        None,
+                        None,
+                        Role::User,
+                        BlockKind::Text,
+                        text,
+                        Status::Done,
+                        ContentType::Plain,
+                    )
+                    .unwrap();
+            }
+            let mem = MemoryBackend::new();
+            mem.mkdir(Path::new("src"), 0o755).await.unwrap();
+            mem.create(Path::new("src/main.rs"), 0o644).await.unwrap();
+            mem.create(Path::new("src/.hidden"), 0o644).await.unwrap();
+            let vfs = MountTable::new();
+            vfs.mount("/w", mem).await;
+            Self {
+                db: Mutex::new(KernelDb::in_memory().unwrap()),
+                drift: shared_drift_router(),
+                store,
+                vfs,
+                ctx,
+            }
+        }
+
+        async fn complete(&self, cwd: Option<&str>, text: &str) -> Vec<String> {
+            let completer = Completer {
+                db: &self.db,
+                drift: &self.drift,
+                store: &self.store,
+                vfs: &self.vfs,
+            };
+            completer
+                .complete(self.ctx, cwd.map(Path::new), text, text.len())
+                .await
+                .into_iter()
+                .map(|c| c.text)
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn mentions_and_commands() {
+        let f = Fixture::new().await;
+        f.db.lock().record_seat(PrincipalId::new(), "amy").unwrap();
+        assert_eq!(f.complete(None, "hi @a").await, vec!["@amy"]);
+        assert!(f.complete(None, "/conte").await.contains(&"/context".to_string()));
+        assert!(f.complete(None, "say /conte").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn blocks_newest_first_by_text_or_seq() {
+        let f = Fixture::new().await;
+        let found = f.complete(None, "see #parser").await;
+        assert_eq!(found.len(), 1);
+        let all = f.complete(None, "#").await;
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0], found[0], "newest block first");
+    }
+
+    #[tokio::test]
+    async fn paths_resolve_against_cwd_and_hide_dotfiles() {
+        let f = Fixture::new().await;
+        assert_eq!(f.complete(Some("/w"), "cat src/").await, vec!["src/main.rs"]);
+        assert_eq!(f.complete(None, "cat /w/s").await, vec!["/w/src/"]);
+        assert_eq!(f.complete(Some("/w"), "src/.h").await, vec!["src/.hidden"]);
+    }
+}
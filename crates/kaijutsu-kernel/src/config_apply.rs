@@ -0,0 +1,314 @@
+//! Live re-apply of rewritten config files.
+//!
+//! `kj config set/edit/reset` calls [`Kernel::apply_config_file`] after a
+//! successful write. For `models.toml` the new file is parsed and built into
+//! a fresh `LlmRegistry`, diffed against the running one, and swapped in —
+//! no restart. What can't be applied is reported, not silently dropped:
+//!
+//! - a file that fails to parse, or whose `default_provider` doesn't
+//!   register, is rejected whole and the running registry is kept;
+//! - an enabled provider that fails to initialize (missing key, say) is
+//!   rejected on its own; if it was running, the running instance is kept.
+//!
+//! `mcp.toml` is accepted for storage but nothing reads it yet (external MCP
+//! servers are registered at startup), so a write is reported as rejected.
+//! `analytics.toml` flips the usage-analytics switch at once; its other
+//! fields are read by the flusher at each window.
+//!
+//! Each report is published as [`ConfigFlow::Applied`].
+
+use std::collections::BTreeSet;
+
+use kaijutsu_types::{ConfigApplyReport, ConfigChange};
+
+use crate::flows::ConfigFlow;
+use crate::kernel::Kernel;
+use crate::llm::{
+    LlmConfig, LlmRegistry, Provider, initialize_llm_registry, load_models_config_toml,
+};
+
+/// Config files with live-apply handling, by name under `/etc/config`.
+const MODELS_FILE: &str = "models.toml";
+const MCP_FILE: &str = "mcp.toml";
+const ANALYTICS_FILE: &str = crate::analytics::ANALYTICS_CONFIG_FILE;
+
+impl Kernel {
+    /// Re-apply the config file at `canonical` (an `/etc/config` path) after
+    /// a write, publish the report on the config flow bus, and return it.
+    /// `None` for files that have no live-apply step — they are either read
+    /// on every use (`system.md`, `webhooks.toml`, `hooks.toml`) or owned by a
+    /// client.
+    pub async fn apply_config_file(&self, canonical: &str) -> Option<ConfigApplyReport> {
+        use crate::vfs::VfsOps;
+
+        let report = if canonical == kaijutsu_types::paths::config_path(MODELS_FILE) {
+            let raw = match self.vfs().read_all(std::path::Path::new(canonical)).await {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(e) => {
+                    let mut report = ConfigApplyReport::new(canonical);
+                    report
+                        .rejected
+                        .push(ConfigChange::new(MODELS_FILE, format!("read failed: {e}")));
+                    return Some(self.publish_config_report(report));
+                }
+            };
+            self.apply_models_config(canonical, &raw).await
+        } else if canonical == kaijutsu_types::paths::config_path(MCP_FILE) {
+            let mut report = ConfigApplyReport::new(canonical);
+            report.rejected.push(ConfigChange::new(
+                MCP_FILE,
+                "stored, not applied: external MCP servers are registered at startup",
+            ));
+            report
+        } else if canonical == kaijutsu_types::paths::config_path(ANALYTICS_FILE) {
+            let mut report = ConfigApplyReport::new(canonical);
+            let config = crate::analytics::load_config(self).await;
+            if self.broker().analytics().set_enabled(config.enabled) {
+                let state = if config.enabled { "on" } else { "off" };
+                report.applied.push(ConfigChange::new("analytics", state));
+            }
+            report
+        } else {
+            return None;
+        };
+        Some(self.publish_config_report(report))
+    }
+
+    /// Parse `raw` as `models.toml`, swap the rebuilt registry in, and report
+    /// the difference.
+    async fn apply_models_config(&self, canonical: &str, raw: &str) -> ConfigApplyReport {
+        let mut report = ConfigApplyReport::new(canonical);
+        let config = match load_models_config_toml(raw) {
+            Ok(c) => c.llm,
+            Err(e) => {
+                report
+                    .rejected
+                    .push(ConfigChange::new(MODELS_FILE, format!("not applied: {e}")));
+                return report;
+            }
+        };
+        let mut next = match initialize_llm_registry(&config) {
+            Ok(r) => r,
+            Err(e) => {
+                report
+                    .rejected
+                    .push(ConfigChange::new(MODELS_FILE, format!("not applied: {e}")));
+                return report;
+            }
+        };
+
+        let mut running = self.llm().write().await;
+        diff_llm_registry(&running, &config, &mut next, &mut report);
+        *running = next;
+        drop(running);
+        self.recheck_llm_health();
+
+        tracing::info!(
+            path = canonical,
+            applied = report.applied.len(),
+            rejected = report.rejected.len(),
+            "re-applied LLM config"
+        );
+        report
+    }
+
+    fn publish_config_report(&self, report: ConfigApplyReport) -> ConfigApplyReport {
+        self.config_flows().publish(ConfigFlow::Applied {
+            report: report.clone(),
+        });
+        report
+    }
+}
+
+/// Compare the running registry with `next` (freshly built from `config`),
+/// recording each difference in `report`. Enabled providers that failed to
+/// initialize are rejected; when the running registry has one by that name,
+/// it is carried into `next` with its old config so the swap doesn't drop it.
+fn diff_llm_registry(
+    running: &LlmRegistry,
+    config: &LlmConfig,
+    next: &mut LlmRegistry,
+    report: &mut ConfigApplyReport,
+) {
+    let mut configs = config.providers.clone();
+    for pc in config.providers.iter().filter(|pc| pc.enabled) {
+        let name = pc.provider_type.as_str();
+        if next.get(name).is_some() {
+            continue;
+        }
+        let reason = Provider::from_config(pc)
+            .err()
+            .map_or_else(|| "failed to initialize".to_string(), |e| e.to_string());
+        match running.get(name) {
+            Some(provider) => {
+                next.register(name, provider);
+                if let Some(slot) = configs.iter_mut().find(|c| c.provider_type == name)
+                    && let Some(old) = running.provider_config(name)
+                {
+                    *slot = old.clone();
+                }
+                report.rejected.push(ConfigChange::new(
+                    format!("provider {name}"),
+                    format!("{reason}; keeping the running provider"),
+                ));
+            }
+            None => report
+                .rejected
+                .push(ConfigChange::new(format!("provider {name}"), reason)),
+        }
+    }
+    next.set_provider_configs(configs);
+
+    let old_names: BTreeSet<&str> = running.list().into_iter().collect();
+    let new_names: BTreeSet<&str> = next.list().into_iter().collect();
+    for name in new_names.difference(&old_names) {
+        report
+            .applied
+            .push(ConfigChange::new(format!("provider {name}"), "added"));
+    }
+    for name in old_names.difference(&new_names) {
+        report
+            .applied
+            .push(ConfigChange::new(format!("provider {name}"), "removed"));
+    }
+    for name in old_names.intersection(&new_names) {
+        if running.provider_config(name) != next.provider_config(name) {
+            report
+                .applied
+                .push(ConfigChange::new(format!("provider {name}"), "updated"));
+        }
+    }
+
+    push_if_changed(
+        report,
+        "default_provider",
+        running.default_provider_name(),
+        next.default_provider_name(),
+    );
+    push_if_changed(
+        report,
+        "default_model",
+        running.default_model(),
+        next.default_model(),
+    );
+
+    let aliases: BTreeSet<&String> = running
+        .model_aliases()
+        .keys()
+        .chain(next.model_aliases().keys())
+        .collect();
+    for alias in aliases {
+        let old = running.model_aliases().get(alias);
+        let new = next.model_aliases().get(alias);
+        if old != new {
+            push_if_changed(
+                report,
+                &format!("alias {alias}"),
+                old.map(|a| format!("{}/{}", a.provider, a.model)).as_deref(),
+                new.map(|a| format!("{}/{}", a.provider, a.model)).as_deref(),
+            );
+        }
+    }
+}
+
+fn push_if_changed(
+    report: &mut ConfigApplyReport,
+    item: &str,
+    old: Option<&str>,
+    new: Option<&str>,
+) {
+    let detail = match (old, new) {
+        (Some(a), Some(b)) if a != b => format!("\"{a}\" → \"{b}\""),
+        (None, Some(b)) => format!("set to \"{b}\""),
+        (Some(_), None) => "unset".to_string(),
+        _ => return,
+    };
+    report.applied.push(ConfigChange::new(item, detail));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(toml: &str) -> (LlmConfig, LlmRegistry) {
+        let config = load_models_config_toml(toml).unwrap().llm;
+        let registry = initialize_llm_registry(&config).unwrap();
+        (config, registry)
+    }
+
+    #[test]
+    fn diff_reports_provider_default_and_alias_changes() {
+        let (_, running) = registry(
+            r#"
+default_provider = "ollama"
+[providers.ollama]
+base_url = "http://localhost:11434/v1"
+default_model = "qwen3"
+[model_aliases.fast]
+provider = "ollama"
+model = "qwen3"
+"#,
+        );
+        let (config, mut next) = registry(
+            r#"
+default_provider = "local"
+[providers.ollama]
+base_url = "http://gpu:11434/v1"
+default_model = "qwen3"
+[providers.local]
+default_model = "llama"
+[model_aliases.fast]
+provider = "local"
+model = "llama"
+"#,
+        );
+        let mut report = ConfigApplyReport::new("/etc/config/models.toml");
+        diff_llm_registry(&running, &config, &mut next, &mut report);
+
+        let applied: Vec<String> = report.applied.iter().map(|c| c.to_string()).collect();
+        assert_eq!(applied, vec![
+            "provider local: added",
+            "provider ollama: updated",
+            "default_provider: \"ollama\" → \"local\"",
+            "default_model: \"qwen3\" → \"llama\"",
+            "alias fast: \"ollama/qwen3\" → \"local/llama\"",
+        ]);
+        assert!(report.rejected.is_empty());
+    }
+
+    #[test]
+    fn failed_provider_is_rejected_and_running_one_kept() {
+        let (config, mut next) = registry(
+            r#"
+default_provider = "ollama"
+[providers.ollama]
+[providers.deepseek]
+api_key_env = "KAIJUTSU_TEST_UNSET_DEEPSEEK_KEY"
+"#,
+        );
+        // Running: the same file, but deepseek came up (its key was present).
+        let (_, mut running) = registry(
+            r#"
+default_provider = "ollama"
+[providers.ollama]
+"#,
+        );
+        running.register(
+            "deepseek",
+            std::sync::Arc::new(
+                Provider::from_config(
+                    &crate::llm::ProviderConfig::new("deepseek").with_api_key("sk-test"),
+                )
+                .unwrap(),
+            ),
+        );
+        running.set_provider_configs(config.providers.clone());
+        let mut report = ConfigApplyReport::new("/etc/config/models.toml");
+        diff_llm_registry(&running, &config, &mut next, &mut report);
+
+        assert!(next.get("deepseek").is_some());
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].item, "provider deepseek");
+        assert!(report.applied.is_empty(), "{:?}", report.applied);
+    }
+}
@@ -1,9 +1,10 @@
 //! Embedded default config-file bodies + the config seed manifest.
 //!
-//! The config TOMLs (`theme.toml`, `models.toml`, `mcp.toml`) and the system
-//! prompt (`system.md`) are **CRDT-owned**, exactly like `/etc/rc`: a fresh
-//! kernel seeds them from these compiled-in defaults into a [`ConfigCrdtFs`]
-//! mounted at [`CONFIG_VFS_ROOT`], and the CRDT is the sole owner thereafter
+//! The config TOMLs (`theme.toml`, `models.toml`, `mcp.toml`, `webhooks.toml`,
+//! `hooks.toml`, `analytics.toml`, `issues.toml`, `abandon.toml`, `consent.toml`) and the system prompt (`system.md`) are **CRDT-owned**,
+//! exactly like `/etc/rc`: a fresh kernel seeds them from these compiled-in
+//! defaults into a [`ConfigCrdtFs`] mounted at [`CONFIG_VFS_ROOT`], and the
+//! CRDT is the sole owner thereafter
 //! (no host file, no write-through). See `docs/config-crdt-ownership.md`.
 //!
 //! These consts used to live on `ConfigCrdtBackend`; that disk-coupled backend
@@ -24,6 +25,24 @@ pub const DEFAULT_LLM_CONFIG: &str = DEFAULT_MODELS_CONFIG;
 /// Embedded default MCP server configuration (TOML).
 pub const DEFAULT_MCP_CONFIG: &str = include_str!("../../../assets/defaults/mcp.toml");
 
+/// Embedded default outbound-webhook configuration (TOML; no hooks).
+pub const DEFAULT_WEBHOOKS: &str = include_str!("../../../assets/defaults/webhooks.toml");
+
+/// Embedded default hook policy for local agents' tool calls (TOML; no rules).
+pub const DEFAULT_HOOKS: &str = include_str!("../../../assets/defaults/hooks.toml");
+
+/// Embedded default usage-analytics configuration (TOML; off).
+pub const DEFAULT_ANALYTICS: &str = include_str!("../../../assets/defaults/analytics.toml");
+
+/// Embedded default issue-tracker configuration (TOML; no trackers).
+pub const DEFAULT_ISSUES: &str = include_str!("../../../assets/defaults/issues.toml");
+
+/// Embedded default abandoned-block rules (TOML).
+pub const DEFAULT_ABANDON: &str = include_str!("../../../assets/defaults/abandon.toml");
+
+/// Embedded default consent prompts (TOML; asks for nothing).
+pub const DEFAULT_CONSENT: &str = include_str!("../../../assets/defaults/consent.toml");
+
 /// Embedded default system prompt.
 pub const DEFAULT_SYSTEM_PROMPT: &str = include_str!("../../../assets/defaults/system.md");
 
@@ -63,6 +82,12 @@ pub fn config_seed_files() -> Vec<(String, &'static str)> {
         (config_path("theme.toml"), DEFAULT_THEME),
         (config_path("models.toml"), DEFAULT_MODELS_CONFIG),
         (config_path("mcp.toml"), DEFAULT_MCP_CONFIG),
+        (config_path("webhooks.toml"), DEFAULT_WEBHOOKS),
+        (config_path("hooks.toml"), DEFAULT_HOOKS),
+        (config_path("analytics.toml"), DEFAULT_ANALYTICS),
+        (config_path("issues.toml"), DEFAULT_ISSUES),
+        (config_path("abandon.toml"), DEFAULT_ABANDON),
+        (config_path("consent.toml"), DEFAULT_CONSENT),
         (config_path("system.md"), DEFAULT_SYSTEM_PROMPT),
     ]
 }
@@ -106,14 +131,20 @@ mod tests {
     use super::*;
 
     #[test]
-    fn seed_manifest_covers_the_four_config_files() {
+    fn seed_manifest_covers_the_ten_config_files() {
         let files = config_seed_files();
         let names: Vec<&str> = files.iter().map(|(p, _)| p.as_str()).collect();
         assert!(names.contains(&"/etc/config/theme.toml"));
         assert!(names.contains(&"/etc/config/models.toml"));
         assert!(names.contains(&"/etc/config/mcp.toml"));
+        assert!(names.contains(&"/etc/config/webhooks.toml"));
+        assert!(names.contains(&"/etc/config/hooks.toml"));
+        assert!(names.contains(&"/etc/config/analytics.toml"));
+        assert!(names.contains(&"/etc/config/issues.toml"));
+        assert!(names.contains(&"/etc/config/abandon.toml"));
+        assert!(names.contains(&"/etc/config/consent.toml"));
         assert!(names.contains(&"/etc/config/system.md"));
-        assert_eq!(files.len(), 4, "exactly the four known config files");
+        assert_eq!(files.len(), 10, "exactly the ten known config files");
     }
 
     #[test]
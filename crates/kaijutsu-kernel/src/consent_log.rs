@@ -0,0 +1,263 @@
+//! Consent audit log: hashing, signing and verification.
+//!
+//! Rows live in `KernelDb` (`consent_log`, appended by
+//! [`KernelDb::append_consent`]); this module owns the cryptography so the
+//! database and any exported copy are checked the same way.
+//!
+//! Decisions recorded today:
+//!
+//! - the MCP broker's tool-call gate (`Broker::call_tool`): `approved` by the
+//!   context's `binding`, `denied` by `capability` or by a PreCall
+//!   `hook:<id>`;
+//! - a destructive `kj` command confirmed with its latch nonce (`latch`).
+//!
+//! The signing key is 32 random bytes in a file of its own next to the
+//! server's host key ([`load_or_create_key`]), never in the kernel database:
+//! whoever can rewrite the database can't re-sign it. Verification needs that
+//! file — the chain alone still exposes edits, gaps and reordering. A signed
+//! head (last seq and hash, rewritten on every append) exposes rows cut off
+//! the end.
+
+use std::io::Write;
+use std::path::Path;
+
+use hmac::{Hmac, Mac};
+use kaijutsu_types::{
+    ConsentEntry, ConsentMode, ConsentVerdict, ConsentVerification, ContextId, PrincipalId,
+};
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+
+use crate::kernel_db::KernelDb;
+
+/// A decision about to be appended; the log assigns seq, time and hashes.
+#[derive(Debug, Clone)]
+pub struct ConsentDraft {
+    pub principal_id: PrincipalId,
+    pub context_id: Option<ContextId>,
+    pub action: String,
+    pub params_hash: String,
+    pub verdict: ConsentVerdict,
+    pub basis: String,
+    pub mode: ConsentMode,
+}
+
+/// SHA-256 (hex) of a call's parameters.
+pub fn params_hash(params: &[u8]) -> String {
+    hex::encode(Sha256::digest(params))
+}
+
+/// The chained hash of `entry` (over [`ConsentEntry::canonical`]).
+pub fn entry_hash(entry: &ConsentEntry) -> String {
+    hex::encode(Sha256::digest(entry.canonical().as_bytes()))
+}
+
+/// HMAC-SHA256 (hex) of an entry hash under the log key.
+pub fn sign(key: &[u8], hash: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(hash.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// HMAC-SHA256 (hex) of the log's head: its last `seq` and that entry's hash.
+pub fn sign_head(key: &[u8], seq: u64, hash: &str) -> String {
+    sign(key, &format!("head {seq} {hash}"))
+}
+
+/// Check that a log ending at `last` ends where the signed `head`
+/// (`seq`, `hash`, `signature`) says. `None` when it does; otherwise the
+/// first missing or mismatched seq and why.
+pub fn check_head(
+    last: Option<&ConsentEntry>,
+    head: Option<&(u64, String, String)>,
+    key: &[u8],
+) -> Option<(u64, String)> {
+    let end = last.map_or(0, |e| e.seq);
+    match (last, head) {
+        (None, None) => None,
+        (Some(_), None) => Some((end, "the log has no signed head".to_string())),
+        (_, Some((seq, hash, signature))) => {
+            if sign_head(key, *seq, hash) != *signature {
+                Some((*seq, "bad head signature".to_string()))
+            } else if *seq > end {
+                Some((
+                    end + 1,
+                    format!("log ends at {end} but its head is at {seq}"),
+                ))
+            } else if *seq < end || last.is_none_or(|e| e.hash != *hash) {
+                Some((*seq, "head does not match the last entry".to_string()))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Read the 32-byte log key from `path`, or create it (mode 0600) with fresh
+/// random bytes. Called once at startup; the key is then handed to
+/// [`KernelDb::set_consent_key`].
+pub fn load_or_create_key(path: &Path) -> std::io::Result<[u8; 32]> {
+    match std::fs::read(path) {
+        Ok(bytes) => bytes.try_into().map_err(|bytes: Vec<u8>| {
+            std::io::Error::other(format!(
+                "consent log key {} is {} bytes, expected 32",
+                path.display(),
+                bytes.len()
+            ))
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let key: [u8; 32] = rand::random();
+            let mut options = std::fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            options.open(path)?.write_all(&key)?;
+            tracing::info!("generated consent log key at {}", path.display());
+            Ok(key)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Check `entries` (in log order, starting at `prev_hash` of the first) for
+/// sequence gaps, broken links, altered fields and bad signatures. Stops at
+/// the first failure.
+pub fn verify(entries: &[ConsentEntry], key: &[u8]) -> ConsentVerification {
+    let mut result = ConsentVerification::default();
+    let mut prev: Option<&ConsentEntry> = None;
+    for entry in entries {
+        let problem = if let Some(p) = prev
+            && entry.seq != p.seq + 1
+        {
+            Some(format!("sequence jumps from {} to {}", p.seq, entry.seq))
+        } else if let Some(p) = prev
+            && entry.prev_hash != p.hash
+        {
+            Some("prev_hash does not match the previous entry".to_string())
+        } else if entry.seq == 1 && !entry.prev_hash.is_empty() {
+            Some("first entry has a prev_hash".to_string())
+        } else if entry_hash(entry) != entry.hash {
+            Some("hash does not match the entry's fields".to_string())
+        } else if sign(key, &entry.hash) != entry.signature {
+            Some("bad signature".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            result.broken_at = Some((entry.seq, problem));
+            return result;
+        }
+        result.checked += 1;
+        prev = Some(entry);
+    }
+    result
+}
+
+/// Append a decision, logging (never propagating) a failure — the audit
+/// write must not change the outcome of the call it describes.
+pub fn record(db: &Mutex<KernelDb>, draft: ConsentDraft) {
+    if let Err(e) = db.lock().append_consent(&draft) {
+        tracing::warn!(
+            action = %draft.action,
+            verdict = %draft.verdict,
+            "consent log append failed: {e}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(action: &str, verdict: ConsentVerdict) -> ConsentDraft {
+        ConsentDraft {
+            principal_id: PrincipalId::new(),
+            context_id: Some(ContextId::new()),
+            action: action.into(),
+            params_hash: params_hash(b"{}"),
+            verdict,
+            basis: "binding".into(),
+            mode: ConsentMode::Autonomous,
+        }
+    }
+
+    #[test]
+    fn appended_log_verifies_and_tampering_is_caught() {
+        let mut db = KernelDb::in_memory().unwrap();
+        db.append_consent(&draft("mcp builtin.file/read", ConsentVerdict::Approved))
+            .unwrap();
+        db.append_consent(&draft("mcp builtin.file/write", ConsentVerdict::Denied))
+            .unwrap();
+        db.append_consent(&draft("kj context remove", ConsentVerdict::Approved))
+            .unwrap();
+        assert_eq!(db.verify_consent_log().unwrap(), ConsentVerification {
+            checked: 3,
+            broken_at: None
+        });
+
+        let key = db.consent_key().unwrap();
+        let mut entries = db.list_consent(0, 10).unwrap();
+        assert_eq!(entries[1].prev_hash, entries[0].hash);
+
+        entries[1].verdict = ConsentVerdict::Approved;
+        assert_eq!(verify(&entries, &key).broken_at.map(|(seq, _)| seq), Some(2));
+
+        let mut gapped = db.list_consent(0, 10).unwrap();
+        gapped.remove(1);
+        assert_eq!(verify(&gapped, &key).broken_at.map(|(seq, _)| seq), Some(3));
+        assert!(!verify(&db.list_consent(0, 10).unwrap(), b"other key").is_valid());
+    }
+
+    #[test]
+    fn truncated_tail_breaks_the_head() {
+        let mut db = KernelDb::in_memory().unwrap();
+        assert!(db.verify_consent_log().unwrap().is_valid(), "empty log");
+        for action in ["a", "b", "c"] {
+            db.append_consent(&draft(action, ConsentVerdict::Approved))
+                .unwrap();
+        }
+        let key = db.consent_key().unwrap();
+        let mut entries = db.list_consent(0, 10).unwrap();
+        let head = (
+            3,
+            entries[2].hash.clone(),
+            sign_head(&key, 3, &entries[2].hash),
+        );
+        assert_eq!(check_head(entries.last(), Some(&head), &key), None);
+
+        entries.pop();
+        assert_eq!(
+            check_head(entries.last(), Some(&head), &key).map(|(seq, _)| seq),
+            Some(3)
+        );
+        let forged = (
+            2,
+            entries[1].hash.clone(),
+            sign_head(b"other key", 2, &entries[1].hash),
+        );
+        assert!(check_head(entries.last(), Some(&forged), &key).is_some());
+        assert!(check_head(entries.last(), None, &key).is_some());
+    }
+
+    #[test]
+    fn key_file_is_created_once_and_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("consent_key");
+        let key = load_or_create_key(&path).unwrap();
+        assert_eq!(load_or_create_key(&path).unwrap(), key);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::write(&path, b"short").unwrap();
+        assert!(load_or_create_key(&path).is_err());
+    }
+}
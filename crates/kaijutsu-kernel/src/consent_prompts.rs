@@ -0,0 +1,507 @@
+//! Consent prompts — a human approves a model's tool call before it runs.
+//!
+//! In collaborative mode, a call to a tool listed in the CRDT-owned
+//! `/etc/config/consent.toml` (see `assets/defaults/consent.toml`) is held
+//! by [`ask`] until the principal driving the turn answers it. The prompt
+//! ([`ConsentPrompt`]) carries a preview of what the call would do — the
+//! command, or a diff for an edit — and is announced as a `ConsentRequest`
+//! inbox item; clients list open prompts (`listConsentPrompts`) and answer
+//! with `consentRespond`. An answer, or the timeout refusing the call, goes
+//! to the consent log under the principal who was asked, with basis
+//! `prompt` / `prompt:timeout`.
+//!
+//! `approve_for_session` also stops the same tool in the same context from
+//! asking the same principal again, for turns in the session that answered,
+//! until the server restarts. Each call it lets through is still logged,
+//! with basis `prompt:session`. One [`ConsentPrompts`] per
+//! kernel ([`crate::Kernel::consent_prompts`]); like `abandon.toml` the
+//! config is re-read on every call.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use kaijutsu_types::{
+    ConsentDecision, ConsentMode, ConsentPrompt, ConsentVerdict, ContextId, InboxKind, PrincipalId,
+    SessionId,
+};
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+use crate::block_store::SharedBlockStore;
+use crate::kernel::Kernel;
+
+/// Config file name under `/etc/config`.
+pub const CONSENT_CONFIG_FILE: &str = "consent.toml";
+
+/// Longest preview kept on a prompt; longer is cut at a char boundary.
+const MAX_PREVIEW_BYTES: usize = 16 * 1024;
+
+/// Parsed `consent.toml`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConsentConfig {
+    /// Tool-name globs that need approval.
+    #[serde(default)]
+    pub ask: Vec<String>,
+    /// Seconds to wait for an answer before refusing the call.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(skip)]
+    compiled: Vec<Regex>,
+}
+
+fn default_timeout_secs() -> u64 {
+    600
+}
+
+impl Default for ConsentConfig {
+    fn default() -> Self {
+        Self {
+            ask: Vec::new(),
+            timeout_secs: default_timeout_secs(),
+            compiled: Vec::new(),
+        }
+    }
+}
+
+impl ConsentConfig {
+    /// Parse and validate. Also the `kj config set` write-time check.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut config: Self = toml::from_str(content).map_err(|e| format!("invalid TOML: {e}"))?;
+        if config.timeout_secs == 0 {
+            return Err("timeout_secs must be at least 1".to_string());
+        }
+        config.compiled = config
+            .ask
+            .iter()
+            .map(|glob| {
+                crate::hook_policy::glob_regex(glob)
+                    .map_err(|e| format!("invalid tool glob '{glob}': {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(config)
+    }
+
+    /// Whether a call to `tool` needs approval.
+    pub fn asks_for(&self, tool: &str) -> bool {
+        self.compiled.iter().any(|re| re.is_match(tool))
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
+/// Read `consent.toml` from the config mount. Missing or broken means the
+/// defaults (ask for nothing), with a warning for broken.
+pub async fn load_config(kernel: &Kernel) -> ConsentConfig {
+    use crate::vfs::VfsOps;
+
+    let path = kaijutsu_types::paths::config_path(CONSENT_CONFIG_FILE);
+    let Ok(bytes) = kernel.vfs().read_all(std::path::Path::new(&path)).await else {
+        return ConsentConfig::default();
+    };
+    ConsentConfig::parse(&String::from_utf8_lossy(&bytes)).unwrap_or_else(|e| {
+        tracing::warn!("{path}: {e}; asking for no tool calls");
+        ConsentConfig::default()
+    })
+}
+
+/// What a call would do, for a human deciding on it: the command of a shell
+/// call, a unified diff for an edit (`old_string` → `new_string`) or a whole
+/// file write (`content`), else the arguments as JSON.
+pub fn preview(arguments: &Value) -> String {
+    let text = |key: &str| arguments.get(key).and_then(Value::as_str);
+    let body = if let Some(command) = text("command") {
+        match text("stdin") {
+            Some(stdin) => format!("{command}\n--- stdin ---\n{stdin}"),
+            None => command.to_string(),
+        }
+    } else if let (Some(path), Some(new)) = (text("path"), text("new_string")) {
+        let target = match text("anchor") {
+            Some(anchor) => format!("{path} @ {anchor}"),
+            None => path.to_string(),
+        };
+        format!(
+            "--- {target}\n+++ {target}\n{}",
+            unified(text("old_string").unwrap_or_default(), new)
+        )
+    } else if let (Some(path), Some(content)) = (text("path"), text("content")) {
+        format!("+++ {path}\n{}", unified("", content))
+    } else {
+        serde_json::to_string_pretty(arguments).unwrap_or_default()
+    };
+    truncate(body)
+}
+
+fn unified(old: &str, new: &str) -> String {
+    kaijutsu_types::diff::LineDiff::new(old, new)
+        .lines
+        .iter()
+        .map(|line| format!("{}{}\n", line.tag.marker(), line.text))
+        .collect()
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() <= MAX_PREVIEW_BYTES {
+        return text;
+    }
+    let mut cut = MAX_PREVIEW_BYTES;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    text.truncate(cut);
+    text.push_str("\n… (truncated)");
+    text
+}
+
+struct Pending {
+    prompt: ConsentPrompt,
+    reply: oneshot::Sender<ConsentDecision>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: u64,
+    pending: HashMap<u64, Pending>,
+    /// Approvals for the rest of a session: who approved which tool in
+    /// which context, from which session.
+    session: HashSet<SessionApproval>,
+}
+
+/// One `approve_for_session` answer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct SessionApproval {
+    context_id: ContextId,
+    tool: String,
+    principal_id: PrincipalId,
+    session_id: SessionId,
+}
+
+/// The kernel's open consent prompts and session approvals.
+#[derive(Default)]
+pub struct ConsentPrompts {
+    inner: Mutex<Inner>,
+}
+
+pub type SharedConsentPrompts = Arc<ConsentPrompts>;
+
+impl ConsentPrompts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `principal_id` approved `tool` in `context_id` for the rest
+    /// of `session_id`.
+    pub fn approved_for_session(
+        &self,
+        context_id: ContextId,
+        tool: &str,
+        principal_id: PrincipalId,
+        session_id: SessionId,
+    ) -> bool {
+        self.inner.lock().session.contains(&SessionApproval {
+            context_id,
+            tool: tool.to_string(),
+            principal_id,
+            session_id,
+        })
+    }
+
+    /// Open a prompt; the receiver gets the answer.
+    fn open(
+        &self,
+        context_id: ContextId,
+        principal_id: PrincipalId,
+        tool: &str,
+        preview: String,
+        timeout: Duration,
+    ) -> (ConsentPrompt, oneshot::Receiver<ConsentDecision>) {
+        let (reply, answer) = oneshot::channel();
+        let now = kaijutsu_types::now_millis();
+        let mut inner = self.inner.lock();
+        inner.next_id += 1;
+        let prompt = ConsentPrompt {
+            id: inner.next_id,
+            context_id,
+            principal_id,
+            tool: tool.to_string(),
+            preview,
+            requested_at: now,
+            expires_at: now + timeout.as_millis() as u64,
+        };
+        inner.pending.insert(
+            prompt.id,
+            Pending {
+                prompt: prompt.clone(),
+                reply,
+            },
+        );
+        (prompt, answer)
+    }
+
+    /// Drop a prompt nobody answered (timed out, or the turn was cancelled).
+    fn withdraw(&self, id: u64) {
+        self.inner.lock().pending.remove(&id);
+    }
+
+    /// Open prompts addressed to `principal_id`, oldest first.
+    pub fn pending_for(&self, principal_id: PrincipalId) -> Vec<ConsentPrompt> {
+        let mut prompts: Vec<ConsentPrompt> = self
+            .inner
+            .lock()
+            .pending
+            .values()
+            .filter(|p| p.prompt.principal_id == principal_id)
+            .map(|p| p.prompt.clone())
+            .collect();
+        prompts.sort_by_key(|p| p.id);
+        prompts
+    }
+
+    /// Answer prompt `id`. Only the principal it is addressed to may; an
+    /// `approve_for_session` holds for `session_id`, the session answering.
+    pub fn respond(
+        &self,
+        id: u64,
+        decision: ConsentDecision,
+        by: PrincipalId,
+        session_id: SessionId,
+    ) -> Result<ConsentPrompt, String> {
+        let mut inner = self.inner.lock();
+        match inner.pending.get(&id) {
+            None => return Err(format!("no open consent prompt #{id}")),
+            Some(p) if p.prompt.principal_id != by => {
+                return Err(format!("consent prompt #{id} is not addressed to you"));
+            }
+            Some(_) => {}
+        }
+        let Some(pending) = inner.pending.remove(&id) else {
+            unreachable!("checked above under the same lock");
+        };
+        if decision == ConsentDecision::ApproveForSession {
+            inner.session.insert(SessionApproval {
+                context_id: pending.prompt.context_id,
+                tool: pending.prompt.tool.clone(),
+                principal_id: by,
+                session_id,
+            });
+        }
+        pending
+            .reply
+            .send(decision)
+            .map_err(|_| format!("consent prompt #{id} is no longer waiting"))?;
+        Ok(pending.prompt)
+    }
+}
+
+/// Hold a model's call to `tool` for approval when the kernel is in
+/// collaborative mode and `consent.toml` asks for it. `Ok` means run the
+/// call; `Err` is the refusal to hand back to the model.
+///
+/// The prompt goes to `principal_id` — the principal driving the turn — or,
+/// for a turn the kernel drives itself, to the context's creator. With
+/// nobody to ask, the call is refused. `session_id` is the turn's session,
+/// which an earlier `approve_for_session` must match.
+#[allow(clippy::too_many_arguments)]
+pub async fn ask(
+    kernel: &Kernel,
+    documents: &SharedBlockStore,
+    context_id: ContextId,
+    principal_id: PrincipalId,
+    session_id: SessionId,
+    tool: &str,
+    arguments: &Value,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    if kernel.consent_mode().await != ConsentMode::Collaborative {
+        return Ok(());
+    }
+    let config = load_config(kernel).await;
+    if !config.asks_for(tool) {
+        return Ok(());
+    }
+    let prompts = kernel.consent_prompts();
+
+    let db = documents.db();
+    let recipient = if principal_id != PrincipalId::system() {
+        Some(principal_id)
+    } else {
+        db.and_then(|db| db.lock().get_context(context_id).ok().flatten())
+            .map(|row| row.created_by)
+            .filter(|owner| *owner != PrincipalId::system())
+    };
+    let Some(recipient) = recipient else {
+        return Err(format!(
+            "`{tool}` needs approval in collaborative mode and no one is at this turn to give it"
+        ));
+    };
+    let record = |verdict: ConsentVerdict, basis: &str| {
+        if let Some(db) = db {
+            let arguments = serde_json::to_vec(arguments).unwrap_or_default();
+            crate::consent_log::record(
+                db,
+                crate::consent_log::ConsentDraft {
+                    principal_id: recipient,
+                    context_id: Some(context_id),
+                    action: format!("tool {tool}"),
+                    params_hash: crate::consent_log::params_hash(&arguments),
+                    verdict,
+                    basis: basis.to_string(),
+                    mode: ConsentMode::Collaborative,
+                },
+            );
+        }
+    };
+    if prompts.approved_for_session(context_id, tool, recipient, session_id) {
+        record(ConsentVerdict::Approved, "prompt:session");
+        return Ok(());
+    }
+
+    let (prompt, answer) = prompts.open(
+        context_id,
+        recipient,
+        tool,
+        preview(arguments),
+        config.timeout(),
+    );
+    let inbox_id = db.and_then(|db| {
+        let summary = format!(
+            "approve `{tool}`: {}",
+            prompt.preview.lines().next().unwrap_or_default()
+        );
+        crate::inbox::post(
+            db,
+            Some(kernel.block_flows()),
+            recipient,
+            InboxKind::ConsentRequest,
+            Some(context_id),
+            None,
+            &summary,
+        )
+        .inspect_err(|e| tracing::warn!("consent prompt #{}: inbox post failed: {e}", prompt.id))
+        .ok()
+        .map(|item| item.id)
+    });
+
+    let decision = tokio::select! {
+        answer = answer => answer.ok(),
+        _ = tokio::time::sleep(config.timeout()) => None,
+        _ = cancel.cancelled() => None,
+    };
+    prompts.withdraw(prompt.id);
+    if let (Some(db), Some(id)) = (db, inbox_id)
+        && let Err(e) = db.lock().ack_inbox(recipient, &[id])
+    {
+        tracing::warn!("consent prompt #{}: inbox ack failed: {e}", prompt.id);
+    }
+    if cancel.is_cancelled() {
+        return Err(format!("interrupted while `{tool}` waited for approval"));
+    }
+
+    match decision {
+        Some(decision) => record(decision.verdict(), "prompt"),
+        None => record(ConsentVerdict::Denied, "prompt:timeout"),
+    }
+    match decision {
+        Some(ConsentDecision::Approve | ConsentDecision::ApproveForSession) => Ok(()),
+        Some(ConsentDecision::Deny) => Err(format!(
+            "the user declined this `{tool}` call; it was not run"
+        )),
+        None => Err(format!(
+            "no answer to the `{tool}` approval within {}s; it was not run",
+            config.timeout_secs
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn config_globs_pick_the_tools_that_ask() {
+        let config = ConsentConfig::parse("ask = [\"shell\", \"mcp_*\"]").unwrap();
+        assert!(config.asks_for("shell"));
+        assert!(config.asks_for("mcp_github_merge"));
+        assert!(!config.asks_for("read"));
+        assert!(!ConsentConfig::default().asks_for("shell"));
+        assert!(ConsentConfig::parse("timeout_secs = 0").is_err());
+        assert!(ConsentConfig::parse("ask = [\"shell\"]\nasks = []").is_err());
+    }
+
+    #[test]
+    fn previews_show_commands_and_diffs() {
+        assert_eq!(
+            preview(&json!({ "command": "rm -rf build" })),
+            "rm -rf build"
+        );
+        let edit = preview(&json!({
+            "path": "src/lib.rs",
+            "old_string": "fn a() {}\n",
+            "new_string": "fn b() {}\n",
+        }));
+        assert_eq!(
+            edit,
+            "--- src/lib.rs\n+++ src/lib.rs\n-fn a() {}\n+fn b() {}\n"
+        );
+        let write = preview(&json!({ "path": "notes.md", "content": "one\ntwo" }));
+        assert_eq!(write, "+++ notes.md\n+one\n+two\n");
+        let long = preview(&json!({ "command": "x".repeat(MAX_PREVIEW_BYTES + 1) }));
+        assert!(
+            long.ends_with("(truncated)"),
+            "{}",
+            &long[long.len() - 20..]
+        );
+    }
+
+    #[test]
+    fn only_the_asked_principal_answers_and_session_approval_sticks() {
+        let prompts = ConsentPrompts::new();
+        let ctx = ContextId::new();
+        let (alice, bob) = (PrincipalId::new(), PrincipalId::new());
+        let (session, other_session) = (SessionId::new(), SessionId::new());
+        let (prompt, mut answer) =
+            prompts.open(ctx, alice, "shell", "ls".into(), Duration::from_secs(60));
+        assert_eq!(prompts.pending_for(alice), vec![prompt.clone()]);
+        assert!(prompts.pending_for(bob).is_empty());
+
+        let err = prompts
+            .respond(prompt.id, ConsentDecision::Approve, bob, session)
+            .unwrap_err();
+        assert!(err.contains("not addressed to you"), "{err}");
+        assert!(!prompts.approved_for_session(ctx, "shell", alice, session));
+
+        prompts
+            .respond(
+                prompt.id,
+                ConsentDecision::ApproveForSession,
+                alice,
+                session,
+            )
+            .unwrap();
+        assert_eq!(answer.try_recv(), Ok(ConsentDecision::ApproveForSession));
+        assert!(prompts.approved_for_session(ctx, "shell", alice, session));
+        assert!(!prompts.approved_for_session(ContextId::new(), "shell", alice, session));
+        assert!(
+            !prompts.approved_for_session(ctx, "shell", bob, session),
+            "alice's approval doesn't cover bob"
+        );
+        assert!(
+            !prompts.approved_for_session(ctx, "shell", alice, other_session),
+            "nor alice in another session"
+        );
+        assert!(prompts.pending_for(alice).is_empty());
+        assert!(
+            prompts
+                .respond(prompt.id, ConsentDecision::Deny, alice, session)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Per-context activity metrics over a sliding window.
+//!
+//! [`ContextActivityTracker`] folds the block FlowBus into live numbers for
+//! each context: block events per minute, blocks currently `Running` (model
+//! streams, shell executions), and errors. The server's `subscribeActivity`
+//! bridge samples it on a timer and pushes the non-idle contexts to clients,
+//! where they drive the time well's per-card glow.
+//!
+//! One tracker per kernel, fed by one watcher ([`spawn_aggregator`]) — not
+//! one per connection, so a client that subscribes late still sees the
+//! streams that started before it connected.
+//!
+//! **Cost model**: events land in one-second buckets, so a context costs at
+//! most [`ACTIVITY_WINDOW_SECS`] buckets no matter how fast it streams. A
+//! context with no events in the window and nothing running is dropped at
+//! the next [`ContextActivityTracker::snapshot`].
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Instant;
+
+use kaijutsu_crdt::{BlockId, Status};
+use kaijutsu_types::{ACTIVITY_WINDOW_SECS, ContextActivity, ContextId};
+use parking_lot::Mutex;
+
+use crate::flows::{BlockFlow, SharedBlockFlowBus};
+
+/// Shared handle to the kernel's tracker.
+pub type SharedContextActivity = Arc<Mutex<ContextActivityTracker>>;
+
+/// One second of one context's traffic.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    second: u64,
+    ops: u32,
+    errors: u32,
+}
+
+#[derive(Debug, Default)]
+struct ContextWindow {
+    /// Oldest first; at most one bucket per second.
+    buckets: VecDeque<Bucket>,
+    running: HashSet<BlockId>,
+}
+
+impl ContextWindow {
+    fn bump(&mut self, second: u64, ops: u32, errors: u32) {
+        match self.buckets.back_mut() {
+            Some(b) if b.second == second => {
+                b.ops += ops;
+                b.errors += errors;
+            }
+            _ => self.buckets.push_back(Bucket {
+                second,
+                ops,
+                errors,
+            }),
+        }
+    }
+
+    fn prune(&mut self, now_second: u64) {
+        let horizon = now_second.saturating_sub(ACTIVITY_WINDOW_SECS as u64);
+        while self.buckets.front().is_some_and(|b| b.second <= horizon) {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+/// Sliding-window activity for every context the kernel has seen recently.
+#[derive(Debug)]
+pub struct ContextActivityTracker {
+    origin: Instant,
+    contexts: HashMap<ContextId, ContextWindow>,
+}
+
+impl Default for ContextActivityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContextActivityTracker {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            contexts: HashMap::new(),
+        }
+    }
+
+    fn second(&self, at: Instant) -> u64 {
+        at.saturating_duration_since(self.origin).as_secs()
+    }
+
+    /// Fold one block event, observed at `at`, into its context's window.
+    /// Collapse/exclude toggles, sync resets and kernel directives aren't
+    /// activity and are ignored.
+    pub fn record(&mut self, flow: &BlockFlow, at: Instant) {
+        let second = self.second(at);
+        match flow {
+            BlockFlow::Inserted {
+                context_id, block, ..
+            } => {
+                let window = self.contexts.entry(*context_id).or_default();
+                if block.status == Status::Running {
+                    window.running.insert(block.id);
+                }
+                window.bump(second, 1, u32::from(block.status == Status::Error));
+            }
+            BlockFlow::StatusChanged {
+                context_id,
+                block_id,
+                status,
+                ..
+            } => {
+                let window = self.contexts.entry(*context_id).or_default();
+                if *status == Status::Running {
+                    window.running.insert(*block_id);
+                } else {
+                    window.running.remove(block_id);
+                }
+                window.bump(second, 1, u32::from(*status == Status::Error));
+            }
+            BlockFlow::Deleted {
+                context_id,
+                block_id,
+                ..
+            } => {
+                let window = self.contexts.entry(*context_id).or_default();
+                window.running.remove(block_id);
+                window.bump(second, 1, 0);
+            }
+            BlockFlow::TextOps { context_id, .. }
+            | BlockFlow::OutputChanged { context_id, .. }
+            | BlockFlow::MetadataChanged { context_id, .. }
+            | BlockFlow::Moved { context_id, .. } => {
+                self.contexts
+                    .entry(*context_id)
+                    .or_default()
+                    .bump(second, 1, 0);
+            }
+            BlockFlow::CollapsedChanged { .. }
+            | BlockFlow::ExcludedChanged { .. }
+            | BlockFlow::SyncReset { .. }
+            | BlockFlow::ContextSwitched { .. }
+            | BlockFlow::RenderCue { .. }
+            | BlockFlow::BeatSync { .. }
+            | BlockFlow::InboxPosted { .. }
+            | BlockFlow::ModelChanged { .. }
+            | BlockFlow::LockChanged { .. } => {}
+        }
+    }
+
+    /// Current activity of every non-idle context as of `now`, busiest
+    /// first. Ages out buckets older than the window and forgets contexts
+    /// that have gone idle.
+    pub fn snapshot(&mut self, now: Instant) -> Vec<ContextActivity> {
+        let now_second = self.second(now);
+        let mut out = Vec::new();
+        self.contexts.retain(|context_id, window| {
+            window.prune(now_second);
+            let (ops, errors) = window
+                .buckets
+                .iter()
+                .fold((0u32, 0u32), |(o, e), b| (o + b.ops, e + b.errors));
+            let activity = ContextActivity {
+                context_id: *context_id,
+                ops_per_minute: ops.saturating_mul(60) / ACTIVITY_WINDOW_SECS,
+                active_streams: window.running.len() as u32,
+                errors,
+            };
+            if activity.is_idle() {
+                return false;
+            }
+            out.push(activity);
+            true
+        });
+        out.sort_by(|a, b| {
+            b.ops_per_minute
+                .cmp(&a.ops_per_minute)
+                .then_with(|| a.context_id.cmp(&b.context_id))
+        });
+        out
+    }
+}
+
+/// Feed `tracker` from the block FlowBus for as long as the bus lives. Call
+/// once per kernel, from a runtime that lives as long as the kernel.
+pub fn spawn_aggregator(
+    tracker: SharedContextActivity,
+    bus: &SharedBlockFlowBus,
+) -> tokio::task::JoinHandle<()> {
+    let mut sub = bus.subscribe("block.*");
+    tokio::spawn(async move {
+        while let Some(msg) = sub.recv().await {
+            tracker.lock().record(&msg.payload, msg.timestamp);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::OpSource;
+    use kaijutsu_types::PrincipalId;
+    use std::time::Duration;
+
+    fn status(ctx: ContextId, block_id: BlockId, status: Status) -> BlockFlow {
+        BlockFlow::StatusChanged {
+            context_id: ctx,
+            block_id,
+            status,
+            source: OpSource::Local,
+        }
+    }
+
+    fn text_ops(ctx: ContextId, block_id: BlockId) -> BlockFlow {
+        BlockFlow::TextOps {
+            context_id: ctx,
+            block_id,
+            ops: Arc::from(Vec::new()),
+            source: OpSource::Local,
+            seq_num: 0,
+            generation: 0,
+        }
+    }
+
+    #[test]
+    fn streams_ops_and_errors_are_counted_per_context() {
+        let mut tracker = ContextActivityTracker::new();
+        let t0 = tracker.origin;
+        let (busy, quiet) = (ContextId::new(), ContextId::new());
+        let stream = BlockId::new(busy, PrincipalId::nil(), 0);
+        let failed = BlockId::new(quiet, PrincipalId::nil(), 0);
+
+        tracker.record(&status(busy, stream, Status::Running), t0);
+        for _ in 0..5 {
+            tracker.record(&text_ops(busy, stream), t0);
+        }
+        tracker.record(&status(quiet, failed, Status::Error), t0);
+
+        let snap = tracker.snapshot(t0);
+        assert_eq!(snap.len(), 2);
+        assert_eq!(snap[0].context_id, busy, "busiest first");
+        assert_eq!(snap[0].ops_per_minute, 6);
+        assert_eq!(snap[0].active_streams, 1);
+        assert_eq!(snap[0].errors, 0);
+        assert_eq!(snap[1].errors, 1);
+        assert_eq!(snap[1].active_streams, 0);
+    }
+
+    #[test]
+    fn window_ages_out_but_running_streams_stay_visible() {
+        let mut tracker = ContextActivityTracker::new();
+        let t0 = tracker.origin;
+        let (streaming, finished) = (ContextId::new(), ContextId::new());
+        let a = BlockId::new(streaming, PrincipalId::nil(), 0);
+        let b = BlockId::new(finished, PrincipalId::nil(), 0);
+
+        tracker.record(&status(streaming, a, Status::Running), t0);
+        tracker.record(&status(finished, b, Status::Running), t0);
+        tracker.record(&status(finished, b, Status::Done), t0);
+
+        let later = t0 + Duration::from_secs(ACTIVITY_WINDOW_SECS as u64 + 1);
+        let snap = tracker.snapshot(later);
+        assert_eq!(
+            snap.len(),
+            1,
+            "the finished context went idle and was dropped"
+        );
+        assert_eq!(snap[0].context_id, streaming);
+        assert_eq!(snap[0].ops_per_minute, 0, "its one event aged out");
+        assert_eq!(snap[0].active_streams, 1);
+        assert!(!tracker.contexts.contains_key(&finished));
+    }
+}
@@ -0,0 +1,399 @@
+//! Per-context health checks: stuck blocks, stalled streams, failing tools.
+//!
+//! [`ContextHealthTracker`] watches the block FlowBus for three ways a
+//! context goes bad without anyone noticing:
+//!
+//! - a block sits in `Running` far longer than any real stream or shell
+//!   execution takes (its producer died, or lost track of it);
+//! - a model block is `Running` but the context has gone quiet — the LLM
+//!   stream stopped delivering events;
+//! - several tool results in a row came back as errors.
+//!
+//! The server's `subscribeActivity` bridge samples [`ContextHealthTracker::check`]
+//! next to the activity reading and pushes each new finding to clients as
+//! `onUnhealthy`; the `contextRecover` RPC cancels what can be cancelled,
+//! marks what is left, and [`ContextHealthTracker::clear`]s the context.
+//!
+//! One tracker per kernel, fed by one watchdog ([`spawn_watchdog`]), like
+//! [`crate::context_activity`]. A context is forgotten once nothing in it
+//! is running and its failure streak is broken.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use kaijutsu_crdt::{BlockId, BlockKind, Role, Status};
+use kaijutsu_types::{ContextHealth, ContextId, HealthIssue};
+use parking_lot::Mutex;
+
+use crate::flows::{BlockFlow, SharedBlockFlowBus};
+
+/// Shared handle to the kernel's tracker.
+pub type SharedContextHealth = Arc<Mutex<ContextHealthTracker>>;
+
+/// When a context counts as unhealthy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthThresholds {
+    /// A block `Running` this long is stuck, whatever it is doing.
+    pub stuck_running: Duration,
+    /// A model block `Running` in a context this quiet is a stalled stream.
+    pub silent_stream: Duration,
+    /// This many failed tool results in a row.
+    pub tool_failures: u32,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            stuck_running: Duration::from_secs(15 * 60),
+            silent_stream: Duration::from_secs(120),
+            tool_failures: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RunningBlock {
+    since: Instant,
+    model: bool,
+    tool_result: bool,
+}
+
+#[derive(Debug, Default)]
+struct ContextState {
+    running: HashMap<BlockId, RunningBlock>,
+    last_event: Option<Instant>,
+    failure_streak: u32,
+    last_failure: Option<BlockId>,
+}
+
+impl ContextState {
+    fn tool_finished(&mut self, block_id: BlockId, failed: bool) {
+        if failed {
+            self.failure_streak += 1;
+            self.last_failure = Some(block_id);
+        } else {
+            self.failure_streak = 0;
+            self.last_failure = None;
+        }
+    }
+
+    fn is_quiet(&self) -> bool {
+        self.running.is_empty() && self.failure_streak == 0
+    }
+}
+
+/// Health state for every context with something running or failing.
+#[derive(Debug)]
+pub struct ContextHealthTracker {
+    thresholds: HealthThresholds,
+    contexts: HashMap<ContextId, ContextState>,
+}
+
+impl Default for ContextHealthTracker {
+    fn default() -> Self {
+        Self::new(HealthThresholds::default())
+    }
+}
+
+impl ContextHealthTracker {
+    pub fn new(thresholds: HealthThresholds) -> Self {
+        Self {
+            thresholds,
+            contexts: HashMap::new(),
+        }
+    }
+
+    pub fn thresholds(&self) -> HealthThresholds {
+        self.thresholds
+    }
+
+    /// Fold one block event, observed at `at`, into its context's state.
+    pub fn record(&mut self, flow: &BlockFlow, at: Instant) {
+        match flow {
+            BlockFlow::Inserted {
+                context_id, block, ..
+            } => {
+                let state = self.contexts.entry(*context_id).or_default();
+                state.last_event = Some(at);
+                let tool_result = block.kind == BlockKind::ToolResult;
+                match block.status {
+                    Status::Running => {
+                        state.running.insert(
+                            block.id,
+                            RunningBlock {
+                                since: at,
+                                model: block.role == Role::Model,
+                                tool_result,
+                            },
+                        );
+                    }
+                    Status::Done | Status::Error if tool_result => {
+                        let failed = block.status == Status::Error || block.is_error;
+                        state.tool_finished(block.id, failed);
+                    }
+                    _ => {}
+                }
+            }
+            BlockFlow::StatusChanged {
+                context_id,
+                block_id,
+                status,
+                ..
+            } => {
+                let state = self.contexts.entry(*context_id).or_default();
+                state.last_event = Some(at);
+                if *status == Status::Running {
+                    // Seen first as Running here (inserted before the
+                    // watchdog started): kind unknown, so only the stuck
+                    // check applies.
+                    state.running.entry(*block_id).or_insert(RunningBlock {
+                        since: at,
+                        model: false,
+                        tool_result: false,
+                    });
+                } else if let Some(block) = state.running.remove(block_id)
+                    && block.tool_result
+                    && matches!(status, Status::Done | Status::Error)
+                {
+                    state.tool_finished(*block_id, *status == Status::Error);
+                }
+            }
+            BlockFlow::Deleted {
+                context_id,
+                block_id,
+                ..
+            } => {
+                if let Some(state) = self.contexts.get_mut(context_id) {
+                    state.running.remove(block_id);
+                    state.last_event = Some(at);
+                }
+            }
+            BlockFlow::TextOps { context_id, .. }
+            | BlockFlow::OutputChanged { context_id, .. }
+            | BlockFlow::MetadataChanged { context_id, .. }
+            | BlockFlow::Moved { context_id, .. } => {
+                if let Some(state) = self.contexts.get_mut(context_id) {
+                    state.last_event = Some(at);
+                }
+            }
+            BlockFlow::CollapsedChanged { .. }
+            | BlockFlow::ExcludedChanged { .. }
+            | BlockFlow::SyncReset { .. }
+            | BlockFlow::ContextSwitched { .. }
+            | BlockFlow::RenderCue { .. }
+            | BlockFlow::BeatSync { .. }
+            | BlockFlow::InboxPosted { .. }
+            | BlockFlow::ModelChanged { .. }
+            | BlockFlow::LockChanged { .. } => {}
+        }
+    }
+
+    /// Every unhealthy context as of `now`, ordered by context id. Absolute
+    /// readings: durations grow between calls, so callers that push
+    /// findings compare with [`HealthIssue::same_finding`]. Forgets contexts
+    /// with nothing left to watch.
+    pub fn check(&mut self, now: Instant) -> Vec<ContextHealth> {
+        let t = self.thresholds;
+        self.contexts.retain(|_, state| !state.is_quiet());
+        let mut out: Vec<ContextHealth> = self
+            .contexts
+            .iter()
+            .filter_map(|(context_id, state)| {
+                let mut running: Vec<(&BlockId, &RunningBlock)> = state.running.iter().collect();
+                running.sort_by_key(|(id, block)| (block.since, **id));
+
+                let mut issues = Vec::new();
+                let silent_for = state
+                    .last_event
+                    .map(|last| now.saturating_duration_since(last))
+                    .unwrap_or_default();
+                let silent = running
+                    .iter()
+                    .find(|(_, block)| block.model)
+                    .filter(|_| silent_for >= t.silent_stream)
+                    .map(|(id, _)| **id);
+                if let Some(block_id) = silent {
+                    issues.push(HealthIssue::SilentStream {
+                        block_id,
+                        silent_secs: silent_for.as_secs(),
+                    });
+                }
+                for (id, block) in &running {
+                    let running_for = now.saturating_duration_since(block.since);
+                    if Some(**id) != silent && running_for >= t.stuck_running {
+                        issues.push(HealthIssue::StuckRunning {
+                            block_id: **id,
+                            running_secs: running_for.as_secs(),
+                        });
+                    }
+                }
+                if state.failure_streak >= t.tool_failures
+                    && let Some(last_block) = state.last_failure
+                {
+                    issues.push(HealthIssue::ToolFailures {
+                        count: state.failure_streak,
+                        last_block,
+                    });
+                }
+
+                (!issues.is_empty()).then(|| ContextHealth {
+                    context_id: *context_id,
+                    issues,
+                })
+            })
+            .collect();
+        out.sort_by_key(|h| h.context_id);
+        out
+    }
+
+    /// Forget `context_id`'s running blocks and failure streak after a
+    /// recovery. The recovery's own `Error` marks name blocks no longer
+    /// tracked as running, so they don't restart the streak when they arrive.
+    pub fn clear(&mut self, context_id: ContextId) {
+        self.contexts.remove(&context_id);
+    }
+}
+
+/// Feed `tracker` from the block FlowBus for as long as the bus lives. Call
+/// once per kernel, from a runtime that lives as long as the kernel.
+pub fn spawn_watchdog(
+    tracker: SharedContextHealth,
+    bus: &SharedBlockFlowBus,
+) -> tokio::task::JoinHandle<()> {
+    let mut sub = bus.subscribe("block.*");
+    tokio::spawn(async move {
+        while let Some(msg) = sub.recv().await {
+            tracker.lock().record(&msg.payload, msg.timestamp);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::OpSource;
+    use kaijutsu_crdt::BlockSnapshotBuilder;
+    use kaijutsu_types::PrincipalId;
+
+    fn inserted(block: kaijutsu_crdt::BlockSnapshot) -> BlockFlow {
+        BlockFlow::Inserted {
+            context_id: block.id.context_id,
+            block: Arc::new(block),
+            after_id: None,
+            ops: Arc::from(Vec::new()),
+            source: OpSource::Local,
+        }
+    }
+
+    fn running(ctx: ContextId, seq: u64, kind: BlockKind, role: Role) -> BlockFlow {
+        let id = BlockId::new(ctx, PrincipalId::nil(), seq);
+        inserted(
+            BlockSnapshotBuilder::new(id, kind)
+                .role(role)
+                .status(Status::Running)
+                .build(),
+        )
+    }
+
+    fn status(ctx: ContextId, seq: u64, status: Status) -> BlockFlow {
+        BlockFlow::StatusChanged {
+            context_id: ctx,
+            block_id: BlockId::new(ctx, PrincipalId::nil(), seq),
+            status,
+            source: OpSource::Local,
+        }
+    }
+
+    fn tracker() -> ContextHealthTracker {
+        ContextHealthTracker::new(HealthThresholds {
+            stuck_running: Duration::from_secs(600),
+            silent_stream: Duration::from_secs(60),
+            tool_failures: 2,
+        })
+    }
+
+    #[test]
+    fn silent_model_stream_then_stuck_shell() {
+        let mut tracker = tracker();
+        let t0 = Instant::now();
+        let ctx = ContextId::new();
+        tracker.record(&running(ctx, 0, BlockKind::Text, Role::Model), t0);
+        tracker.record(&running(ctx, 1, BlockKind::ToolResult, Role::Tool), t0);
+        assert!(tracker.check(t0 + Duration::from_secs(30)).is_empty());
+
+        let report = tracker.check(t0 + Duration::from_secs(90));
+        assert_eq!(report.len(), 1);
+        assert_eq!(
+            report[0].issues,
+            vec![HealthIssue::SilentStream {
+                block_id: BlockId::new(ctx, PrincipalId::nil(), 0),
+                silent_secs: 90,
+            }],
+            "the shell isn't a stream, and nothing is stuck yet"
+        );
+
+        let later = tracker.check(t0 + Duration::from_secs(700));
+        let codes: Vec<_> = later[0].issues.iter().map(HealthIssue::code).collect();
+        assert_eq!(codes, ["silent_stream", "stuck_running"]);
+    }
+
+    #[test]
+    fn streaming_text_keeps_a_stream_healthy() {
+        let mut tracker = tracker();
+        let t0 = Instant::now();
+        let ctx = ContextId::new();
+        let block_id = BlockId::new(ctx, PrincipalId::nil(), 0);
+        tracker.record(&running(ctx, 0, BlockKind::Text, Role::Model), t0);
+        tracker.record(
+            &BlockFlow::TextOps {
+                context_id: ctx,
+                block_id,
+                ops: Arc::from(Vec::new()),
+                source: OpSource::Local,
+                seq_num: 0,
+                generation: 0,
+            },
+            t0 + Duration::from_secs(50),
+        );
+        assert!(tracker.check(t0 + Duration::from_secs(90)).is_empty());
+
+        tracker.record(&status(ctx, 0, Status::Done), t0 + Duration::from_secs(95));
+        assert!(tracker.check(t0 + Duration::from_secs(900)).is_empty());
+        assert!(tracker.contexts.is_empty(), "finished context is forgotten");
+    }
+
+    #[test]
+    fn tool_failure_streak_resets_on_success_and_clear() {
+        let mut tracker = tracker();
+        let t0 = Instant::now();
+        let ctx = ContextId::new();
+        for seq in 0..2 {
+            tracker.record(&running(ctx, seq, BlockKind::ToolResult, Role::Tool), t0);
+            tracker.record(&status(ctx, seq, Status::Error), t0);
+        }
+        let report = tracker.check(t0);
+        assert_eq!(
+            report[0].issues,
+            vec![HealthIssue::ToolFailures {
+                count: 2,
+                last_block: BlockId::new(ctx, PrincipalId::nil(), 1),
+            }]
+        );
+
+        tracker.record(&running(ctx, 2, BlockKind::ToolResult, Role::Tool), t0);
+        tracker.record(&status(ctx, 2, Status::Done), t0);
+        assert!(tracker.check(t0).is_empty());
+
+        // A recovery clears the context; its own Error marks arrive after
+        // and don't start a new streak.
+        for seq in 3..5 {
+            tracker.record(&running(ctx, seq, BlockKind::ToolResult, Role::Tool), t0);
+        }
+        tracker.record(&status(ctx, 3, Status::Error), t0);
+        tracker.clear(ctx);
+        tracker.record(&status(ctx, 4, Status::Error), t0);
+        tracker.record(&status(ctx, 3, Status::Error), t0);
+        assert!(tracker.check(t0).is_empty());
+    }
+}
@@ -0,0 +1,420 @@
+//! Per-context key-value scratch store.
+//!
+//! Agents need somewhere to keep small coordination state that isn't
+//! conversation — "last processed issue number", "which shard am I on" —
+//! without writing it into blocks the model then re-reads every turn. Each
+//! context gets one [`ContextKv`]: a DTE map of JSON values, shared by every
+//! seat in the context and exposed through the `builtin.kv` MCP server.
+//!
+//! Layout inside the DTE document mirrors `BlockDocument`: a root Set
+//! (`keys`) holds the live keys, and each key's value and attribution sit in
+//! flat root entries (`v:{key}`, `by:{key}`, `at:{key}`). Deleting a key
+//! only removes it from the Set, so a later `set` is an ordinary LWW write.
+//!
+//! [`ContextKvStore`] caches loaded documents and writes the full oplog back
+//! to `context_kv` in the kernel DB after every mutation. The stores are
+//! small by construction ([`KV_MAX_KEYS`], [`KV_MAX_VALUE_BYTES`]), so
+//! snapshot-per-write beats an oplog/compaction pipeline.
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use diamond_types_extended::{AgentId, Document, Frontier, SerializedOpsOwned, Uuid};
+use parking_lot::Mutex;
+
+use kaijutsu_types::codec;
+use kaijutsu_types::{ContextId, PrincipalId};
+
+use crate::kernel_db::{KernelDb, KernelDbError};
+
+/// Longest key accepted, in bytes.
+pub const KV_MAX_KEY_LEN: usize = 128;
+/// Largest value accepted, measured as compact JSON.
+pub const KV_MAX_VALUE_BYTES: usize = 16 * 1024;
+/// Most live keys one context may hold.
+pub const KV_MAX_KEYS: usize = 256;
+
+const KEY_KEYS: &str = "keys";
+const PREFIX_VALUE: &str = "v:";
+const PREFIX_BY: &str = "by:";
+const PREFIX_AT: &str = "at:";
+
+/// Errors from KV reads and writes.
+#[derive(Debug, thiserror::Error)]
+pub enum ContextKvError {
+    #[error("invalid key {0:?}: {1}")]
+    InvalidKey(String, &'static str),
+    #[error("value for {key:?} is {len} bytes (max {KV_MAX_VALUE_BYTES})")]
+    ValueTooLarge { key: String, len: usize },
+    #[error("context already holds {KV_MAX_KEYS} keys")]
+    TooManyKeys,
+    #[error("kv document: {0}")]
+    Document(String),
+    #[error(transparent)]
+    Db(#[from] KernelDbError),
+}
+
+pub type ContextKvResult<T> = Result<T, ContextKvError>;
+
+/// One live entry, with who wrote it last and when.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KvEntry {
+    pub key: String,
+    pub value: serde_json::Value,
+    pub updated_by: PrincipalId,
+    /// Unix millis of the last write.
+    pub updated_at: u64,
+}
+
+/// Keys are short, printable, and free of whitespace so they read the same
+/// in tool output, kaish, and logs.
+pub fn validate_key(key: &str) -> ContextKvResult<()> {
+    if key.is_empty() {
+        return Err(ContextKvError::InvalidKey(key.to_string(), "empty"));
+    }
+    if key.len() > KV_MAX_KEY_LEN {
+        return Err(ContextKvError::InvalidKey(
+            key.to_string(),
+            "longer than 128 bytes",
+        ));
+    }
+    if key.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(ContextKvError::InvalidKey(
+            key.to_string(),
+            "contains whitespace or control characters",
+        ));
+    }
+    Ok(())
+}
+
+/// A single context's KV document.
+pub struct ContextKv {
+    doc: Document,
+    agent: AgentId,
+}
+
+impl ContextKv {
+    /// Create an empty store.
+    pub fn new(principal_id: PrincipalId) -> Self {
+        let mut doc = Document::new();
+        let agent = doc.create_agent(Uuid::from_bytes(*principal_id.as_bytes()));
+        doc.transact(agent, |tx| {
+            tx.root().create_set(KEY_KEYS);
+        });
+        Self { doc, agent }
+    }
+
+    /// Rebuild a store from serialized ops (for DB restore).
+    pub fn from_ops(ops_bytes: &[u8], principal_id: PrincipalId) -> ContextKvResult<Self> {
+        let ops: SerializedOpsOwned = codec::decode(ops_bytes)
+            .map_err(|e| ContextKvError::Document(format!("decode ops: {e}")))?;
+        let mut doc = Document::new();
+        let agent = doc.create_agent(Uuid::from_bytes(*principal_id.as_bytes()));
+        doc.merge_ops(ops)
+            .map_err(|e| ContextKvError::Document(format!("merge ops: {e}")))?;
+        Ok(Self { doc, agent })
+    }
+
+    /// All ops from the beginning (for persistence).
+    pub fn all_ops(&self) -> ContextKvResult<Vec<u8>> {
+        let ops = self.doc.ops_since_owned(&Frontier::root());
+        codec::encode(&ops).map_err(|e| ContextKvError::Document(format!("encode ops: {e}")))
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.doc
+            .get_set(&[KEY_KEYS])
+            .map(|s| s.contains_str(key))
+            .unwrap_or(false)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let Some(set) = self.doc.get_set(&[KEY_KEYS]) else {
+            return Vec::new();
+        };
+        let mut keys: Vec<String> = set
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect();
+        #[allow(clippy::drop_non_drop)] // intentional: release DTE interior lock
+        drop(set);
+        keys.sort();
+        keys
+    }
+
+    /// The live entry for `key`, if any.
+    pub fn get(&self, key: &str) -> Option<KvEntry> {
+        if !self.contains(key) {
+            return None;
+        }
+        let root = self.doc.root();
+        let raw = root
+            .get(&format!("{PREFIX_VALUE}{key}"))
+            .and_then(|v| v.as_str().map(|s| s.to_string()))?;
+        let value = match serde_json::from_str(&raw) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!(key, error = %e, "context kv: unparseable value, skipping");
+                return None;
+            }
+        };
+        let updated_by = root
+            .get(&format!("{PREFIX_BY}{key}"))
+            .and_then(|v| v.as_str().and_then(|s| PrincipalId::parse(s).ok()))
+            .unwrap_or_else(PrincipalId::nil);
+        let updated_at = root
+            .get(&format!("{PREFIX_AT}{key}"))
+            .and_then(|v| v.as_int())
+            .map(|n| n.max(0) as u64)
+            .unwrap_or(0);
+        Some(KvEntry {
+            key: key.to_string(),
+            value,
+            updated_by,
+            updated_at,
+        })
+    }
+
+    /// Live entries whose key starts with `prefix`, sorted by key.
+    pub fn list(&self, prefix: &str) -> Vec<KvEntry> {
+        self.keys()
+            .into_iter()
+            .filter(|k| k.starts_with(prefix))
+            .filter_map(|k| self.get(&k))
+            .collect()
+    }
+
+    /// Write `value` under `key`, attributed to `by`.
+    pub fn set(
+        &mut self,
+        key: &str,
+        value: &serde_json::Value,
+        by: PrincipalId,
+    ) -> ContextKvResult<()> {
+        validate_key(key)?;
+        let raw = serde_json::to_string(value)
+            .map_err(|e| ContextKvError::Document(format!("encode value: {e}")))?;
+        if raw.len() > KV_MAX_VALUE_BYTES {
+            return Err(ContextKvError::ValueTooLarge {
+                key: key.to_string(),
+                len: raw.len(),
+            });
+        }
+        let is_new = !self.contains(key);
+        if is_new && self.keys().len() >= KV_MAX_KEYS {
+            return Err(ContextKvError::TooManyKeys);
+        }
+        let now = kaijutsu_types::now_millis() as i64;
+        self.doc.transact(self.agent, |tx| {
+            if is_new && let Some(mut set) = tx.get_set_mut(&[KEY_KEYS]) {
+                set.add_str(key);
+            }
+            tx.root().set(&format!("{PREFIX_VALUE}{key}"), raw.as_str());
+            tx.root()
+                .set(&format!("{PREFIX_BY}{key}"), by.to_hex().as_str());
+            tx.root().set(&format!("{PREFIX_AT}{key}"), now);
+        });
+        Ok(())
+    }
+
+    /// Remove `key`. Returns whether it was present.
+    pub fn delete(&mut self, key: &str) -> bool {
+        if !self.contains(key) {
+            return false;
+        }
+        self.doc.transact(self.agent, |tx| {
+            if let Some(mut set) = tx.get_set_mut(&[KEY_KEYS]) {
+                set.remove_str(key);
+            }
+        });
+        true
+    }
+}
+
+/// Kernel-wide cache of per-context KV documents, backed by `KernelDb`.
+pub struct ContextKvStore {
+    kvs: DashMap<ContextId, ContextKv>,
+    kernel_db: Arc<Mutex<KernelDb>>,
+    principal_id: PrincipalId,
+}
+
+impl ContextKvStore {
+    pub fn new(kernel_db: Arc<Mutex<KernelDb>>, principal_id: PrincipalId) -> Self {
+        Self {
+            kvs: DashMap::new(),
+            kernel_db,
+            principal_id,
+        }
+    }
+
+    /// Run `f` against `context_id`'s document, loading it from the DB (or
+    /// starting empty) on first touch.
+    fn with_kv<T>(
+        &self,
+        context_id: ContextId,
+        f: impl FnOnce(&mut ContextKv) -> ContextKvResult<T>,
+    ) -> ContextKvResult<T> {
+        if !self.kvs.contains_key(&context_id) {
+            let stored = self.kernel_db.lock().load_context_kv(context_id)?;
+            let kv = match stored {
+                Some(bytes) => ContextKv::from_ops(&bytes, self.principal_id)?,
+                None => ContextKv::new(self.principal_id),
+            };
+            self.kvs.entry(context_id).or_insert(kv);
+        }
+        let mut kv = self
+            .kvs
+            .get_mut(&context_id)
+            .expect("kv entry inserted above");
+        f(&mut kv)
+    }
+
+    /// Persist `context_id`'s document. The DashMap guard is released before
+    /// taking the DB lock.
+    fn save(&self, context_id: ContextId) -> ContextKvResult<()> {
+        let state = self.with_kv(context_id, |kv| kv.all_ops())?;
+        self.kernel_db.lock().save_context_kv(context_id, &state)?;
+        Ok(())
+    }
+
+    pub fn get(&self, context_id: ContextId, key: &str) -> ContextKvResult<Option<KvEntry>> {
+        self.with_kv(context_id, |kv| Ok(kv.get(key)))
+    }
+
+    pub fn list(&self, context_id: ContextId, prefix: &str) -> ContextKvResult<Vec<KvEntry>> {
+        self.with_kv(context_id, |kv| Ok(kv.list(prefix)))
+    }
+
+    pub fn set(
+        &self,
+        context_id: ContextId,
+        key: &str,
+        value: &serde_json::Value,
+        by: PrincipalId,
+    ) -> ContextKvResult<()> {
+        self.with_kv(context_id, |kv| kv.set(key, value, by))?;
+        self.save(context_id)
+    }
+
+    /// Remove `key`. Returns whether it was present.
+    pub fn delete(&self, context_id: ContextId, key: &str) -> ContextKvResult<bool> {
+        let removed = self.with_kv(context_id, |kv| Ok(kv.delete(key)))?;
+        if removed {
+            self.save(context_id)?;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel_db::{ContextRow, DocumentRow};
+    use kaijutsu_types::{ConsentMode, ContextState, DocKind};
+    use serde_json::json;
+
+    /// In-memory DB with one persisted context (the KV table FKs to it).
+    fn db_with_context() -> (Arc<Mutex<KernelDb>>, ContextId) {
+        let db = KernelDb::in_memory().unwrap();
+        let id = ContextId::new();
+        let creator = PrincipalId::new();
+        let now = kaijutsu_types::now_millis() as i64;
+        let ws_id = db.get_or_create_default_workspace(creator).unwrap();
+        db.insert_document(&DocumentRow {
+            document_id: id,
+            workspace_id: ws_id,
+            doc_kind: DocKind::Conversation,
+            language: None,
+            path: None,
+            created_at: now,
+            created_by: creator,
+        })
+        .unwrap();
+        db.insert_context(&ContextRow {
+            context_id: id,
+            label: None,
+            provider: None,
+            model: None,
+            system_prompt: None,
+            consent_mode: ConsentMode::default(),
+            context_state: ContextState::Live,
+            context_type: "default".to_string(),
+            created_at: now,
+            created_by: creator,
+            forked_from: None,
+            fork_kind: None,
+            archived_at: None,
+            workspace_id: None,
+            preset_id: None,
+            concluded_at: None,
+            last_activity_at: None,
+            promoted_at: None,
+            demoted_at: None,
+            paused_at: None,
+        })
+        .unwrap();
+        (Arc::new(Mutex::new(db)), id)
+    }
+
+    #[test]
+    fn set_get_list_delete_roundtrip() {
+        let alice = PrincipalId::new();
+        let mut kv = ContextKv::new(PrincipalId::system());
+
+        kv.set("issue.last", &json!(4428), alice).unwrap();
+        kv.set("issue.queue", &json!([1, 2, 3]), alice).unwrap();
+        kv.set("shard", &json!("b"), alice).unwrap();
+
+        let entry = kv.get("issue.last").unwrap();
+        assert_eq!(entry.value, json!(4428));
+        assert_eq!(entry.updated_by, alice);
+        assert!(entry.updated_at > 0);
+
+        let keys: Vec<_> = kv.list("issue.").into_iter().map(|e| e.key).collect();
+        assert_eq!(keys, vec!["issue.last", "issue.queue"]);
+
+        assert!(kv.delete("shard"));
+        assert!(!kv.delete("shard"));
+        assert!(kv.get("shard").is_none());
+
+        kv.set("shard", &json!("c"), alice).unwrap();
+        assert_eq!(kv.get("shard").unwrap().value, json!("c"));
+    }
+
+    #[test]
+    fn rejects_bad_keys_and_oversized_values() {
+        let mut kv = ContextKv::new(PrincipalId::system());
+        let by = PrincipalId::new();
+        assert!(matches!(
+            kv.set("", &json!(1), by),
+            Err(ContextKvError::InvalidKey(..))
+        ));
+        assert!(matches!(
+            kv.set("has space", &json!(1), by),
+            Err(ContextKvError::InvalidKey(..))
+        ));
+        let big = json!("x".repeat(KV_MAX_VALUE_BYTES));
+        assert!(matches!(
+            kv.set("big", &big, by),
+            Err(ContextKvError::ValueTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn store_persists_across_cache_loss() {
+        let (db, ctx) = db_with_context();
+        let writer = PrincipalId::new();
+
+        let store = ContextKvStore::new(db.clone(), PrincipalId::system());
+        store
+            .set(ctx, "cursor", &json!({"page": 3}), writer)
+            .unwrap();
+
+        let reopened = ContextKvStore::new(db, PrincipalId::system());
+        let entry = reopened.get(ctx, "cursor").unwrap().unwrap();
+        assert_eq!(entry.value, json!({"page": 3}));
+        assert_eq!(entry.updated_by, writer);
+        assert!(reopened.delete(ctx, "cursor").unwrap());
+        assert!(reopened.list(ctx, "").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+//! Context shares — make, open, revoke.
+//!
+//! Storage lives in `KernelDb` (`context_shares`); this module is the
+//! workflow. [`create`] freezes a context's blocks and version under the
+//! document lock, encodes them once and stores them by a fresh token.
+//! [`open`] hands the frozen copy back to anyone holding a live token — the
+//! observer path in the server's `World.openShare`, and `kj context
+//! share-html`. Expiry is read at open time; nothing sweeps old rows.
+//!
+//! Who revokes: the share's creator or the context's creator.
+
+use kaijutsu_types::{
+    BlockSnapshot, ContextId, ContextShare, DEFAULT_SHARE_TTL_SECS, MAX_SHARE_TTL_SECS,
+    PrincipalId, ShareStatus, codec, context_share::new_share_token,
+};
+use parking_lot::Mutex;
+
+use crate::block_store::{BlockStoreError, SharedBlockStore};
+use crate::kernel_db::{KernelDb, KernelDbError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ShareError {
+    #[error("no share with that token")]
+    NotFound,
+    #[error("share {0} has expired")]
+    Expired(String),
+    #[error("share {0} was revoked")]
+    Revoked(String),
+    #[error("only the share's creator or the context's creator can revoke it")]
+    NotAllowed,
+    #[error("share snapshot: {0}")]
+    Codec(#[from] codec::CodecError),
+    #[error(transparent)]
+    Db(#[from] KernelDbError),
+    #[error(transparent)]
+    Store(#[from] BlockStoreError),
+}
+
+pub type ShareResult<T> = Result<T, ShareError>;
+
+/// Freeze `context_id` as it is now and store it under a new token, open for
+/// `ttl_secs` (default [`DEFAULT_SHARE_TTL_SECS`], capped at
+/// [`MAX_SHARE_TTL_SECS`]). Ephemeral blocks are left out.
+pub fn create(
+    db: &Mutex<KernelDb>,
+    blocks: &SharedBlockStore,
+    context_id: ContextId,
+    created_by: PrincipalId,
+    ttl_secs: Option<u64>,
+    now_millis: u64,
+) -> ShareResult<ContextShare> {
+    let ttl = ttl_secs
+        .filter(|&t| t > 0)
+        .unwrap_or(DEFAULT_SHARE_TTL_SECS)
+        .min(MAX_SHARE_TTL_SECS);
+    // Version and blocks from one look at the document, so the share's
+    // version names exactly the state it holds.
+    let (version, frozen) = {
+        let entry = blocks
+            .get(context_id)
+            .ok_or(BlockStoreError::DocumentNotFound(context_id))?;
+        let frozen: Vec<BlockSnapshot> = entry
+            .doc
+            .blocks_ordered()
+            .into_iter()
+            .filter(|b| !b.ephemeral)
+            .collect();
+        (entry.version(), frozen)
+    };
+    let encoded = codec::encode(&frozen)?;
+
+    let db = db.lock();
+    let label = db
+        .get_context(context_id)?
+        .and_then(|row| row.label)
+        .unwrap_or_default();
+    let share = ContextShare {
+        token: new_share_token(),
+        context_id,
+        label,
+        version,
+        block_count: frozen.len() as u32,
+        created_by,
+        created_at: now_millis,
+        expires_at: now_millis + ttl * 1000,
+        revoked_at: None,
+    };
+    db.insert_context_share(&share, &encoded)?;
+    Ok(share)
+}
+
+/// The share behind `token` and its frozen blocks, if it is still live.
+pub fn open(
+    db: &Mutex<KernelDb>,
+    token: &str,
+    now_millis: u64,
+) -> ShareResult<(ContextShare, Vec<BlockSnapshot>)> {
+    let (share, encoded) = {
+        let db = db.lock();
+        let share = db.get_context_share(token)?.ok_or(ShareError::NotFound)?;
+        match share.status(now_millis) {
+            ShareStatus::Live => {}
+            ShareStatus::Expired => return Err(ShareError::Expired(short_token(token))),
+            ShareStatus::Revoked => return Err(ShareError::Revoked(short_token(token))),
+        }
+        let encoded = db
+            .context_share_blocks(token)?
+            .ok_or(ShareError::NotFound)?;
+        (share, encoded)
+    };
+    let blocks = codec::decode(&encoded)?;
+    Ok((share, blocks))
+}
+
+/// Close a share for good. Revoking an already-revoked share is refused so
+/// the first revocation time stands.
+pub fn revoke(
+    db: &Mutex<KernelDb>,
+    token: &str,
+    by: PrincipalId,
+    now_millis: u64,
+) -> ShareResult<ContextShare> {
+    let db = db.lock();
+    let share = db.get_context_share(token)?.ok_or(ShareError::NotFound)?;
+    if by != share.created_by {
+        let owner = db.get_context(share.context_id)?.map(|row| row.created_by);
+        if owner != Some(by) {
+            return Err(ShareError::NotAllowed);
+        }
+    }
+    if !db.revoke_context_share(token, now_millis)? {
+        return Err(ShareError::Revoked(short_token(token)));
+    }
+    Ok(ContextShare {
+        revoked_at: Some(now_millis),
+        ..share
+    })
+}
+
+/// The first eight hex digits — enough to tell shares apart in messages
+/// without echoing the whole secret.
+pub fn short_token(token: &str) -> String {
+    token.chars().take(8).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::{DocumentKind, shared_block_store};
+    use kaijutsu_types::{BlockKind, ContentType, Role, Status};
+
+    struct Fixture {
+        db: Mutex<KernelDb>,
+        blocks: SharedBlockStore,
+        ctx: ContextId,
+    }
+
+    fn fixture() -> Fixture {
+        let blocks = shared_block_store(PrincipalId::new());
+        let ctx = ContextId::new();
+        blocks
+            .create_document(ctx, DocumentKind::Conversation, None)
+            .unwrap();
+        blocks
+            .insert_block(
+                ctx,
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                "find the leak",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        Fixture {
+            db: Mutex::new(KernelDb::in_memory().unwrap()),
+            blocks,
+            ctx,
+        }
+    }
+
+    #[test]
+    fn a_share_is_frozen_at_creation() {
+        let f = fixture();
+        let amy = PrincipalId::new();
+        let share = create(&f.db, &f.blocks, f.ctx, amy, Some(60), 1_000).unwrap();
+        assert_eq!((share.block_count, share.expires_at), (1, 61_000));
+
+        f.blocks
+            .insert_block(
+                f.ctx,
+                None,
+                None,
+                Role::Model,
+                BlockKind::Text,
+                "said later",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let (opened, blocks) = open(&f.db, &share.token, 2_000).unwrap();
+        assert_eq!(opened, share);
+        assert_eq!(blocks.len(), 1, "later blocks stay out of the share");
+        assert_eq!(blocks[0].content, "find the leak");
+    }
+
+    #[test]
+    fn expired_revoked_and_unknown_shares_do_not_open() {
+        let f = fixture();
+        let amy = PrincipalId::new();
+        let share = create(&f.db, &f.blocks, f.ctx, amy, Some(60), 0).unwrap();
+        assert!(matches!(
+            open(&f.db, &share.token, 60_000),
+            Err(ShareError::Expired(_))
+        ));
+        assert!(matches!(
+            open(&f.db, "no-such-token", 0),
+            Err(ShareError::NotFound)
+        ));
+
+        assert!(matches!(
+            revoke(&f.db, &share.token, PrincipalId::new(), 10),
+            Err(ShareError::NotAllowed)
+        ));
+        let revoked = revoke(&f.db, &share.token, amy, 10).unwrap();
+        assert_eq!(revoked.revoked_at, Some(10));
+        assert!(matches!(
+            open(&f.db, &share.token, 20),
+            Err(ShareError::Revoked(_))
+        ));
+        assert!(matches!(
+            revoke(&f.db, &share.token, amy, 30),
+            Err(ShareError::Revoked(_))
+        ));
+    }
+
+    #[test]
+    fn ttl_defaults_and_caps() {
+        let f = fixture();
+        let amy = PrincipalId::new();
+        let share = create(&f.db, &f.blocks, f.ctx, amy, None, 0).unwrap();
+        assert_eq!(share.expires_at, DEFAULT_SHARE_TTL_SECS * 1000);
+        let share = create(&f.db, &f.blocks, f.ctx, amy, Some(u64::from(u32::MAX)), 0).unwrap();
+        assert_eq!(share.expires_at, MAX_SHARE_TTL_SECS * 1000);
+    }
+}
@@ -0,0 +1,142 @@
+//! Context timelines — a context's events bucketed over time.
+//!
+//! [`build`] fills a [`ContextTimeline`] for `getContextTimeline` (the app's
+//! timeline strip, MCP `context_timeline`). Blocks, tool runs (`ToolCall`
+//! blocks), drifts (`Drift` blocks) and errors (`Error` blocks and failed
+//! `ToolResult`s) are counted by block `created_at`, so they cover the
+//! document's whole life. Edits are oplog rows by their journal time: every
+//! mutation, streamed model text included, for as long as the journal holds
+//! it — a compaction folds older rows into a snapshot and that stretch of
+//! the edit series reads zero. Stores without a database count no edits.
+
+use kaijutsu_types::{BlockKind, BlockSnapshot, ContextId, ContextTimeline};
+
+use crate::block_store::{BlockStoreResult, SharedBlockStore};
+
+/// Window used when the context has no blocks to date its start.
+const EMPTY_WINDOW_MS: u64 = 3_600_000;
+
+/// `context_id`'s timeline over the last `window_secs` up to `now` (Unix
+/// millis), or over its whole life when `window_secs` is 0, in `buckets`
+/// buckets (0 for [`kaijutsu_types::DEFAULT_TIMELINE_BUCKETS`]).
+pub fn build(
+    blocks: &SharedBlockStore,
+    context_id: ContextId,
+    window_secs: u64,
+    buckets: u32,
+    now: u64,
+) -> BlockStoreResult<ContextTimeline> {
+    let snapshots = blocks.block_snapshots(context_id)?;
+    let start = match window_secs {
+        0 => snapshots
+            .iter()
+            .map(|b| b.created_at)
+            .min()
+            .unwrap_or_else(|| now.saturating_sub(EMPTY_WINDOW_MS)),
+        secs => now.saturating_sub(secs.saturating_mul(1000)),
+    };
+    let buckets = match buckets {
+        0 => kaijutsu_types::DEFAULT_TIMELINE_BUCKETS,
+        n => n,
+    };
+    // One past `now`, so an event stamped `now` still lands.
+    let mut timeline = ContextTimeline::new(context_id, start, now + 1, buckets);
+    tally(&mut timeline, &snapshots);
+
+    if let Some(db) = blocks.db() {
+        let counted = db.lock().count_oplog_buckets(
+            context_id,
+            timeline.start,
+            timeline.bucket_ms,
+            timeline.buckets.len(),
+        );
+        match counted {
+            Ok(edits) => {
+                for (bucket, edits) in timeline.buckets.iter_mut().zip(edits) {
+                    bucket.edits = edits;
+                }
+            }
+            Err(e) => tracing::warn!(
+                "timeline for {}: edit counts failed: {e}",
+                context_id.short()
+            ),
+        }
+    }
+    Ok(timeline)
+}
+
+/// Count each block into the bucket it was created in.
+fn tally(timeline: &mut ContextTimeline, snapshots: &[BlockSnapshot]) {
+    for block in snapshots {
+        let Some(bucket) = timeline.bucket_mut(block.created_at) else {
+            continue;
+        };
+        bucket.blocks += 1;
+        match block.kind {
+            BlockKind::ToolCall => bucket.tool_runs += 1,
+            BlockKind::Drift => bucket.drifts += 1,
+            BlockKind::Error => bucket.errors += 1,
+            BlockKind::ToolResult if block.is_error => bucket.errors += 1,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::{DocumentKind, shared_block_store};
+    use kaijutsu_types::{ContentType, PrincipalId, Role, Status};
+
+    #[test]
+    fn blocks_land_in_their_buckets_by_kind() {
+        let blocks = shared_block_store(PrincipalId::new());
+        let ctx = ContextId::new();
+        blocks
+            .create_document(ctx, DocumentKind::Conversation, None)
+            .unwrap();
+        for kind in [
+            BlockKind::Text,
+            BlockKind::ToolCall,
+            BlockKind::ToolCall,
+            BlockKind::Drift,
+            BlockKind::Error,
+        ] {
+            blocks
+                .insert_block(
+                    ctx,
+                    None,
+                    None,
+                    Role::User,
+                    kind,
+                    "x",
+                    Status::Done,
+                    ContentType::Plain,
+                )
+                .unwrap();
+        }
+
+        let now = kaijutsu_types::now_millis() + 1_000;
+        let whole = build(&blocks, ctx, 0, 1, now).unwrap();
+        let bucket = whole.buckets[0];
+        assert_eq!(
+            (
+                bucket.blocks,
+                bucket.tool_runs,
+                bucket.drifts,
+                bucket.errors
+            ),
+            (5, 2, 1, 1)
+        );
+        assert_eq!(bucket.edits, 0, "no database, no journal");
+
+        // A window that ended before the blocks were made sees none of them.
+        let earlier = build(&blocks, ctx, 60, 0, now - 3_600_000).unwrap();
+        assert_eq!(
+            earlier.buckets.len(),
+            kaijutsu_types::DEFAULT_TIMELINE_BUCKETS as usize
+        );
+        assert_eq!(earlier.peak(), 0);
+        assert!(build(&blocks, ContextId::new(), 0, 0, now).is_err());
+    }
+}
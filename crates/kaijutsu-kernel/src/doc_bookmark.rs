@@ -0,0 +1,329 @@
+//! Document bookmarks — make, read, diff, revert.
+//!
+//! Storage lives in `KernelDb` (`doc_bookmarks`); this module is the
+//! workflow. [`create`] freezes a document's blocks and version under the
+//! document lock, as a context share does, and stores them under a name.
+//! [`open`] is the time-travel read: the blocks as they stood. [`diff`]
+//! compares that copy with the live document, and [`revert`] writes the
+//! bookmarked text back over every block that changed since.
+//!
+//! A revert is ordinary forward edits by the reverting principal, so it
+//! syncs, journals and can itself be bookmarked or reverted. It restores
+//! text only: blocks added since the bookmark are left in place and blocks
+//! deleted since are not brought back — the [`BookmarkDiff`] it returns
+//! lists both.
+
+use std::collections::HashMap;
+
+use kaijutsu_types::{
+    BlockId, BlockSnapshot, ContextId, DocBookmark, PrincipalId, codec, validate_bookmark_name,
+};
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::block_store::{BlockStoreError, SharedBlockStore};
+use crate::kernel_db::{KernelDb, KernelDbError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum BookmarkError {
+    #[error("{0}")]
+    InvalidName(String),
+    #[error("bookmark '{0}' already exists")]
+    Exists(String),
+    #[error("no bookmark named '{0}'")]
+    NotFound(String),
+    #[error("bookmark snapshot: {0}")]
+    Codec(#[from] codec::CodecError),
+    #[error(transparent)]
+    Db(#[from] KernelDbError),
+    #[error(transparent)]
+    Store(#[from] BlockStoreError),
+}
+
+pub type BookmarkResult<T> = Result<T, BookmarkError>;
+
+/// One block whose text differs between a bookmark and now.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockChange {
+    pub block_id: BlockId,
+    /// The text at the bookmark.
+    pub then: String,
+    /// The text now.
+    pub now: String,
+}
+
+/// How a document moved on from a bookmark.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BookmarkDiff {
+    /// Blocks the document has now that the bookmark doesn't.
+    pub added: Vec<BlockId>,
+    /// Bookmarked blocks the document no longer has.
+    pub removed: Vec<BlockId>,
+    /// Blocks in both whose text differs.
+    pub changed: Vec<BlockChange>,
+}
+
+impl BookmarkDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Freeze `context_id` as it is now under `name`. Ephemeral blocks are left
+/// out. Refused when the document already has a bookmark by that name.
+pub fn create(
+    db: &Mutex<KernelDb>,
+    blocks: &SharedBlockStore,
+    context_id: ContextId,
+    name: &str,
+    created_by: PrincipalId,
+    now_millis: u64,
+) -> BookmarkResult<DocBookmark> {
+    validate_bookmark_name(name).map_err(BookmarkError::InvalidName)?;
+    let (version, frozen) = live_blocks(blocks, context_id)?;
+    let encoded = codec::encode(&frozen)?;
+    let bookmark = DocBookmark {
+        context_id,
+        name: name.to_string(),
+        version,
+        block_count: frozen.len() as u32,
+        created_by,
+        created_at: now_millis,
+    };
+    if !db.lock().insert_doc_bookmark(&bookmark, &encoded)? {
+        return Err(BookmarkError::Exists(name.to_string()));
+    }
+    Ok(bookmark)
+}
+
+/// A document's bookmarks, oldest first.
+pub fn list(db: &Mutex<KernelDb>, context_id: ContextId) -> BookmarkResult<Vec<DocBookmark>> {
+    Ok(db.lock().list_doc_bookmarks(context_id)?)
+}
+
+/// The bookmark `name` and the blocks it froze.
+pub fn open(
+    db: &Mutex<KernelDb>,
+    context_id: ContextId,
+    name: &str,
+) -> BookmarkResult<(DocBookmark, Vec<BlockSnapshot>)> {
+    let (bookmark, encoded) = {
+        let db = db.lock();
+        let not_found = || BookmarkError::NotFound(name.to_string());
+        let bookmark = db
+            .get_doc_bookmark(context_id, name)?
+            .ok_or_else(not_found)?;
+        let encoded = db
+            .doc_bookmark_blocks(context_id, name)?
+            .ok_or_else(not_found)?;
+        (bookmark, encoded)
+    };
+    Ok((bookmark, codec::decode(&encoded)?))
+}
+
+/// Forget a bookmark. The document is untouched.
+pub fn delete(db: &Mutex<KernelDb>, context_id: ContextId, name: &str) -> BookmarkResult<()> {
+    if !db.lock().delete_doc_bookmark(context_id, name)? {
+        return Err(BookmarkError::NotFound(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Compare bookmark `name` with the live document.
+pub fn diff(
+    db: &Mutex<KernelDb>,
+    blocks: &SharedBlockStore,
+    context_id: ContextId,
+    name: &str,
+) -> BookmarkResult<(DocBookmark, BookmarkDiff)> {
+    let (bookmark, then) = open(db, context_id, name)?;
+    let (_, now) = live_blocks(blocks, context_id)?;
+    Ok((bookmark, compare(&then, &now)))
+}
+
+/// Write bookmark `name`'s text back over every block changed since, as one
+/// all-or-nothing edit by `by`. Returns the diff it acted on: `changed` is
+/// what was restored, `added` and `removed` were left alone.
+pub fn revert(
+    db: &Mutex<KernelDb>,
+    blocks: &SharedBlockStore,
+    context_id: ContextId,
+    name: &str,
+    by: PrincipalId,
+) -> BookmarkResult<(DocBookmark, BookmarkDiff)> {
+    let (bookmark, changes) = diff(db, blocks, context_id, name)?;
+    let mut tx = blocks.transaction(context_id, Some(by));
+    for change in &changes.changed {
+        tx.edit_text(
+            &change.block_id,
+            0,
+            &change.then,
+            change.now.chars().count(),
+        )?;
+    }
+    tx.commit();
+    Ok((bookmark, changes))
+}
+
+/// The document's version and its non-ephemeral blocks, from one look.
+fn live_blocks(
+    blocks: &SharedBlockStore,
+    context_id: ContextId,
+) -> BookmarkResult<(u64, Vec<BlockSnapshot>)> {
+    let entry = blocks
+        .get(context_id)
+        .ok_or(BlockStoreError::DocumentNotFound(context_id))?;
+    let live = entry
+        .doc
+        .blocks_ordered()
+        .into_iter()
+        .filter(|b| !b.ephemeral)
+        .collect();
+    Ok((entry.version(), live))
+}
+
+fn compare(then: &[BlockSnapshot], now: &[BlockSnapshot]) -> BookmarkDiff {
+    let then_by_id: HashMap<BlockId, &BlockSnapshot> = then.iter().map(|b| (b.id, b)).collect();
+    let now_by_id: HashMap<BlockId, &BlockSnapshot> = now.iter().map(|b| (b.id, b)).collect();
+    let mut diff = BookmarkDiff::default();
+    for block in now {
+        match then_by_id.get(&block.id) {
+            None => diff.added.push(block.id),
+            Some(old) if old.content != block.content => diff.changed.push(BlockChange {
+                block_id: block.id,
+                then: old.content.clone(),
+                now: block.content.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.removed = then
+        .iter()
+        .filter(|b| !now_by_id.contains_key(&b.id))
+        .map(|b| b.id)
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::{DocumentKind, shared_block_store};
+    use crate::kernel_db::DocumentRow;
+    use kaijutsu_types::{BlockKind, ContentType, DocKind, Role, Status};
+
+    struct Fixture {
+        db: Mutex<KernelDb>,
+        blocks: SharedBlockStore,
+        ctx: ContextId,
+        plan: BlockId,
+    }
+
+    fn fixture() -> Fixture {
+        let db = KernelDb::in_memory().unwrap();
+        let ws_id = db
+            .get_or_create_default_workspace(PrincipalId::system())
+            .unwrap();
+        let ctx = ContextId::new();
+        db.insert_document(&DocumentRow {
+            document_id: ctx,
+            workspace_id: ws_id,
+            doc_kind: DocKind::Conversation,
+            language: None,
+            path: None,
+            created_at: 0,
+            created_by: PrincipalId::system(),
+        })
+        .unwrap();
+        let blocks = shared_block_store(PrincipalId::new());
+        blocks
+            .create_document(ctx, DocumentKind::Conversation, None)
+            .unwrap();
+        let plan = insert(&blocks, ctx, "step one\nstep two\n");
+        Fixture {
+            db: Mutex::new(db),
+            blocks,
+            ctx,
+            plan,
+        }
+    }
+
+    fn insert(blocks: &SharedBlockStore, ctx: ContextId, text: &str) -> BlockId {
+        blocks
+            .insert_block(
+                ctx,
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                text,
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap()
+    }
+
+    fn content(f: &Fixture, id: &BlockId) -> String {
+        f.blocks
+            .get_block_snapshot(f.ctx, id)
+            .unwrap()
+            .unwrap()
+            .content
+    }
+
+    #[test]
+    fn a_bookmark_reads_back_as_it_stood() {
+        let f = fixture();
+        let amy = PrincipalId::new();
+        let mark = create(&f.db, &f.blocks, f.ctx, "before-refactor", amy, 5).unwrap();
+        assert_eq!((mark.block_count, mark.created_at), (1, 5));
+        assert!(matches!(
+            create(&f.db, &f.blocks, f.ctx, "before-refactor", amy, 6),
+            Err(BookmarkError::Exists(_))
+        ));
+        assert!(matches!(
+            create(&f.db, &f.blocks, f.ctx, "not ok", amy, 6),
+            Err(BookmarkError::InvalidName(_))
+        ));
+
+        f.blocks
+            .edit_text(f.ctx, &f.plan, 0, "REWRITTEN ", 0)
+            .unwrap();
+        let (opened, then) = open(&f.db, f.ctx, "before-refactor").unwrap();
+        assert_eq!(opened, mark);
+        assert_eq!(then[0].content, "step one\nstep two\n");
+        assert_eq!(list(&f.db, f.ctx).unwrap(), vec![mark]);
+
+        delete(&f.db, f.ctx, "before-refactor").unwrap();
+        assert!(matches!(
+            open(&f.db, f.ctx, "before-refactor"),
+            Err(BookmarkError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn revert_restores_changed_text_and_reports_the_rest() {
+        let f = fixture();
+        let amy = PrincipalId::new();
+        let gone = insert(&f.blocks, f.ctx, "scratch");
+        create(&f.db, &f.blocks, f.ctx, "before", amy, 0).unwrap();
+
+        f.blocks.edit_text(f.ctx, &f.plan, 5, "ONE", 3).unwrap();
+        f.blocks.delete_block(f.ctx, &gone).unwrap();
+        let added = insert(&f.blocks, f.ctx, "new thought");
+
+        let (_, d) = diff(&f.db, &f.blocks, f.ctx, "before").unwrap();
+        assert_eq!(d.added, vec![added]);
+        assert_eq!(d.removed, vec![gone]);
+        assert_eq!(d.changed.len(), 1);
+        assert_eq!(d.changed[0].now, "step ONE\nstep two\n");
+
+        let (_, reverted) = revert(&f.db, &f.blocks, f.ctx, "before", amy).unwrap();
+        assert_eq!(reverted, d);
+        assert_eq!(content(&f, &f.plan), "step one\nstep two\n");
+        assert_eq!(content(&f, &added), "new thought", "added blocks stay");
+
+        let (_, after) = diff(&f.db, &f.blocks, f.ctx, "before").unwrap();
+        assert!(after.changed.is_empty());
+    }
+}
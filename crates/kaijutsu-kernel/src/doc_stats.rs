@@ -0,0 +1,320 @@
+//! Per-document oplog statistics and growth metrics.
+//!
+//! Every [`DocumentEntry`](crate::block_store::DocumentEntry) carries a
+//! [`DocActivity`]: ops counted per authoring principal as they are applied
+//! (`DocumentEntry::touch`) and journaled payload bytes as they hit the
+//! oplog, both also bucketed by minute for the last hour.
+//! [`BlockStore::doc_stats`](crate::BlockStore::doc_stats) folds that
+//! together with the journal counters and a sizing pass over the document
+//! into a [`DocStats`], cached per document until the document changes or
+//! [`DOC_STATS_TTL_MS`] passes — the sizing pass encodes the whole snapshot,
+//! so polling operators and agents share one computation.
+//!
+//! `kj doc stats` is the surface: one document in detail, or every resident
+//! document ranked by how close its oplog is to compaction, to pick what to
+//! checkpoint and to spot a runaway writer.
+//!
+//! Activity is process-local: it starts empty when a document is loaded, so
+//! `ops` counts since load while `journal_seq` counts the document's life.
+//! Stores that don't journal (replicas, in-memory MCP) count ops but no
+//! bytes.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::Serialize;
+
+use kaijutsu_types::{ContextId, DocKind, PrincipalId};
+
+/// Growth is reported over these trailing windows, in minutes.
+pub const GROWTH_WINDOWS_MINS: [u64; 3] = [1, 10, 60];
+
+/// Cached stats are reused for at most this long, even while the document
+/// is unchanged, so the growth windows keep sliding.
+pub const DOC_STATS_TTL_MS: u64 = 5_000;
+
+/// Oplog fill (fraction of the nearer compaction threshold) at which a
+/// document is reported as a compaction candidate.
+pub const COMPACTION_CANDIDATE_FILL: f64 = 0.5;
+
+/// The window `AgentStats::recent_ops` covers, in minutes.
+const RECENT_WINDOW_MINS: u64 = 10;
+
+const BUCKET_MS: u64 = 60_000;
+
+/// One hour of minute buckets.
+const MAX_BUCKETS: usize = 60;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    minute: u64,
+    ops: u64,
+    bytes: u64,
+}
+
+/// Running totals plus a trailing hour of minute buckets.
+#[derive(Debug, Default)]
+struct Series {
+    ops: u64,
+    bytes: u64,
+    buckets: VecDeque<Bucket>,
+}
+
+impl Series {
+    fn record(&mut self, now_ms: u64, ops: u64, bytes: u64) {
+        self.ops += ops;
+        self.bytes += bytes;
+        let minute = now_ms / BUCKET_MS;
+        match self.buckets.back_mut() {
+            Some(last) if last.minute == minute => {
+                last.ops += ops;
+                last.bytes += bytes;
+            }
+            _ => {
+                self.buckets.push_back(Bucket { minute, ops, bytes });
+                if self.buckets.len() > MAX_BUCKETS {
+                    self.buckets.pop_front();
+                }
+            }
+        }
+    }
+
+    /// `(ops, bytes)` over the trailing `mins` minutes, the current one
+    /// included.
+    fn window(&self, now_ms: u64, mins: u64) -> (u64, u64) {
+        let since = (now_ms / BUCKET_MS).saturating_sub(mins.saturating_sub(1));
+        self.buckets
+            .iter()
+            .rev()
+            .take_while(|b| b.minute >= since)
+            .fold((0, 0), |(ops, bytes), b| (ops + b.ops, bytes + b.bytes))
+    }
+}
+
+/// Write activity on one document since it was loaded.
+#[derive(Debug, Default)]
+pub struct DocActivity {
+    doc: Series,
+    agents: HashMap<PrincipalId, Series>,
+}
+
+impl DocActivity {
+    /// One op applied by `agent`.
+    pub(crate) fn record_op(&mut self, agent: PrincipalId, now_ms: u64) {
+        self.doc.record(now_ms, 1, 0);
+        self.agents.entry(agent).or_default().record(now_ms, 1, 0);
+    }
+
+    /// `bytes` of oplog payload journaled.
+    pub(crate) fn record_bytes(&mut self, bytes: u64, now_ms: u64) {
+        self.doc.record(now_ms, 0, bytes);
+    }
+
+    /// Ops since load.
+    pub fn ops(&self) -> u64 {
+        self.doc.ops
+    }
+
+    /// Growth over each of [`GROWTH_WINDOWS_MINS`].
+    pub fn growth(&self, now_ms: u64) -> Vec<GrowthWindow> {
+        GROWTH_WINDOWS_MINS
+            .iter()
+            .map(|&minutes| {
+                let (ops, bytes) = self.doc.window(now_ms, minutes);
+                GrowthWindow {
+                    minutes,
+                    ops,
+                    bytes,
+                    ops_per_min: ops as f64 / minutes as f64,
+                    bytes_per_min: bytes as f64 / minutes as f64,
+                }
+            })
+            .collect()
+    }
+
+    /// `(principal, ops since load, ops in the recent window)` per writer.
+    fn agent_ops(&self, now_ms: u64) -> impl Iterator<Item = (PrincipalId, u64, u64)> + '_ {
+        self.agents.iter().map(move |(principal, series)| {
+            let (recent, _) = series.window(now_ms, RECENT_WINDOW_MINS);
+            (*principal, series.ops, recent)
+        })
+    }
+}
+
+/// Oplog size and growth for one document, from
+/// [`BlockStore::doc_stats`](crate::BlockStore::doc_stats).
+#[derive(Debug, Clone, Serialize)]
+pub struct DocStats {
+    pub context_id: ContextId,
+    pub kind: DocKind,
+    pub version: u64,
+    pub block_count: usize,
+    /// Text bytes across all blocks.
+    pub content_bytes: u64,
+    /// Size of the encoded store snapshot — what a checkpoint would write.
+    pub snapshot_bytes: u64,
+    /// Whether this store journals to an oplog at all.
+    pub journaled: bool,
+    /// Oplog entries since the last compaction checkpoint.
+    pub oplog_ops: u64,
+    /// Oplog payload bytes since the last compaction checkpoint.
+    pub oplog_bytes: u64,
+    /// Oplog entries ever journaled for the document.
+    pub journal_seq: u64,
+    /// Ops applied since the document was loaded.
+    pub ops: u64,
+    /// Writers, busiest recent writer first.
+    pub agents: Vec<AgentStats>,
+    pub growth: Vec<GrowthWindow>,
+    pub compaction: CompactionCandidacy,
+    /// Unix millis the stats were computed at.
+    pub computed_at: u64,
+}
+
+/// One principal's share of a document.
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentStats {
+    pub principal_id: PrincipalId,
+    /// Ops applied since load.
+    pub ops: u64,
+    /// Ops applied in the last ten minutes.
+    pub recent_ops: u64,
+    /// Blocks authored.
+    pub blocks: usize,
+    /// Text bytes in the blocks authored.
+    pub content_bytes: u64,
+}
+
+/// Document growth over one trailing window.
+#[derive(Debug, Clone, Serialize)]
+pub struct GrowthWindow {
+    pub minutes: u64,
+    pub ops: u64,
+    /// Journaled oplog bytes.
+    pub bytes: u64,
+    pub ops_per_min: f64,
+    pub bytes_per_min: f64,
+}
+
+/// How close the oplog is to the automatic compaction thresholds.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactionCandidacy {
+    pub ops_threshold: u64,
+    pub bytes_threshold: u64,
+    /// Oplog ops or bytes over their threshold, whichever is nearer; 1.0
+    /// triggers compaction.
+    pub fill: f64,
+    /// `fill` is at least [`COMPACTION_CANDIDATE_FILL`] — worth a manual
+    /// checkpoint before the automatic one.
+    pub candidate: bool,
+}
+
+impl CompactionCandidacy {
+    pub fn new(oplog_ops: u64, oplog_bytes: u64, ops_threshold: u64, bytes_threshold: u64) -> Self {
+        let fill = (oplog_ops as f64 / ops_threshold as f64)
+            .max(oplog_bytes as f64 / bytes_threshold as f64);
+        Self {
+            ops_threshold,
+            bytes_threshold,
+            fill,
+            candidate: fill >= COMPACTION_CANDIDATE_FILL,
+        }
+    }
+}
+
+/// Per-principal rows: authorship from `blocks` (`(author, content bytes)`)
+/// joined with `activity`, busiest recent writer first.
+pub fn agent_stats(
+    activity: &DocActivity,
+    blocks: impl IntoIterator<Item = (PrincipalId, u64)>,
+    now_ms: u64,
+) -> Vec<AgentStats> {
+    let mut by_agent: HashMap<PrincipalId, AgentStats> = HashMap::new();
+    let row = |principal_id| AgentStats {
+        principal_id,
+        ops: 0,
+        recent_ops: 0,
+        blocks: 0,
+        content_bytes: 0,
+    };
+    for (principal, bytes) in blocks {
+        let stats = by_agent.entry(principal).or_insert_with(|| row(principal));
+        stats.blocks += 1;
+        stats.content_bytes += bytes;
+    }
+    for (principal, ops, recent) in activity.agent_ops(now_ms) {
+        let stats = by_agent.entry(principal).or_insert_with(|| row(principal));
+        stats.ops = ops;
+        stats.recent_ops = recent;
+    }
+    let mut agents: Vec<AgentStats> = by_agent.into_values().collect();
+    agents.sort_by(|a, b| {
+        (b.recent_ops, b.ops, b.content_bytes).cmp(&(a.recent_ops, a.ops, a.content_bytes))
+    });
+    agents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: u64 = BUCKET_MS;
+
+    #[test]
+    fn growth_windows_slide_with_time() {
+        let mut activity = DocActivity::default();
+        let agent = PrincipalId::new();
+        let t0 = 1_000 * MIN;
+        for _ in 0..5 {
+            activity.record_op(agent, t0);
+        }
+        activity.record_bytes(500, t0);
+        activity.record_op(agent, t0 + 30 * MIN);
+        activity.record_bytes(100, t0 + 30 * MIN);
+
+        let now = t0 + 30 * MIN;
+        let growth = activity.growth(now);
+        let window = |mins| growth.iter().find(|w| w.minutes == mins).unwrap();
+        assert_eq!((window(1).ops, window(1).bytes), (1, 100));
+        assert_eq!((window(10).ops, window(10).bytes), (1, 100));
+        assert_eq!((window(60).ops, window(60).bytes), (6, 600));
+        assert_eq!(window(60).ops_per_min, 0.1);
+
+        let later = activity.growth(t0 + 120 * MIN);
+        assert!(
+            later.iter().all(|w| w.ops == 0),
+            "an idle hour drains every window"
+        );
+        assert_eq!(activity.ops(), 6, "totals outlive the windows");
+    }
+
+    #[test]
+    fn agents_rank_by_recent_writes() {
+        let mut activity = DocActivity::default();
+        let (quiet, busy, author) = (PrincipalId::new(), PrincipalId::new(), PrincipalId::new());
+        let now = 1_000 * MIN;
+        for _ in 0..50 {
+            activity.record_op(quiet, now - 30 * MIN);
+        }
+        for _ in 0..20 {
+            activity.record_op(busy, now);
+        }
+
+        let agents = agent_stats(&activity, [(author, 10), (author, 5), (busy, 3)], now);
+        assert_eq!(agents.len(), 3);
+        assert_eq!(agents[0].principal_id, busy);
+        assert_eq!((agents[0].recent_ops, agents[0].blocks), (20, 1));
+        assert_eq!((agents[1].principal_id, agents[1].ops), (quiet, 50));
+        assert_eq!(agents[2].principal_id, author);
+        assert_eq!((agents[2].blocks, agents[2].content_bytes), (2, 15));
+    }
+
+    #[test]
+    fn candidacy_uses_the_nearer_threshold() {
+        let ops_heavy = CompactionCandidacy::new(300, 1_000, 500, 1_048_576);
+        assert!(ops_heavy.candidate);
+        assert_eq!(ops_heavy.fill, 0.6);
+        let bytes_heavy = CompactionCandidacy::new(10, 786_432, 500, 1_048_576);
+        assert!(bytes_heavy.candidate);
+        assert!(!CompactionCandidacy::new(10, 1_000, 500, 1_048_576).candidate);
+    }
+}
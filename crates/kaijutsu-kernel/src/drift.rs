@@ -23,6 +23,19 @@
 //!       ▼
 //! DriftRouter.flush() → insert_drift_block() on target document
 //! ```
+//!
+//! The router also holds digest subscriptions ([`DigestSubscription`]): a
+//! context asking for periodic distillations of another. The digest
+//! scheduler (`kj::drift::spawn_digest_scheduler`) asks [`DriftRouter::due_digests`]
+//! what is due and delivers each as a `Distill` drift block.
+//!
+//! It is also where context labels are kept unique per kernel. Labels compare
+//! by [`context_label_key`], so near-duplicates (`Bug Hunt` vs `bug-hunt`)
+//! collide too, and a conflict comes back with free alternatives. A seat
+//! about to create a context can hold a name first
+//! ([`DriftRouter::reserve_label`]); the reservation blocks everyone else
+//! until it is committed to the new context, released, or lapses after
+//! [`LABEL_RESERVATION_TTL_MS`].
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -32,7 +45,10 @@ use parking_lot::RwLock;
 use kaijutsu_crdt::{
     BlockKind, BlockSnapshot, ContextId, DriftKind, PrefixError, Role, resolve_context_prefix,
 };
-use kaijutsu_types::{ContextState, PrincipalId};
+use kaijutsu_types::{
+    BlockId, ContextNameReservation, ContextState, DigestSubscription, MAX_DIGEST_SUBSCRIPTIONS,
+    PrincipalId, ReservationId, context_label_key,
+};
 
 /// Shared, thread-safe DriftRouter reference.
 pub type SharedDriftRouter = Arc<RwLock<DriftRouter>>;
@@ -114,6 +130,23 @@ pub struct StagedDrift {
 /// Maximum number of requeue attempts before a staged drift is discarded.
 const MAX_DRIFT_RETRIES: u32 = 5;
 
+/// How long an uncommitted context-name reservation holds its name.
+pub const LABEL_RESERVATION_TTL_MS: u64 = 60_000;
+
+/// Alternatives offered for a taken label.
+const LABEL_SUGGESTIONS: usize = 3;
+
+/// A label held for a context that doesn't exist yet.
+#[derive(Debug, Clone)]
+struct LabelReservation {
+    token: ReservationId,
+    label: String,
+    principal: PrincipalId,
+    expires_at: u64,
+    /// The context being created under the label, once committed.
+    context: Option<ContextId>,
+}
+
 // ============================================================================
 // DriftRouter — central coordinator
 // ============================================================================
@@ -140,8 +173,13 @@ pub struct DriftRouter {
     next_staged_id: u64,
     /// Reverse lookup: label → ContextId (for prefix matching).
     label_to_id: HashMap<String, ContextId>,
+    /// Held labels, keyed by [`context_label_key`].
+    reservations: HashMap<String, LabelReservation>,
     /// ContextId for the lazy "lost+found" context, created on first dead letter.
     lost_found_id: Option<ContextId>,
+    /// Digest subscriptions, keyed by (subscriber, source). Mirrors the
+    /// `digest_subscriptions` table; the caller persists.
+    digests: HashMap<(ContextId, ContextId), DigestSubscription>,
 }
 
 impl Default for DriftRouter {
@@ -159,7 +197,9 @@ impl DriftRouter {
             dead_letter: Vec::new(),
             next_staged_id: 1,
             label_to_id: HashMap::new(),
+            reservations: HashMap::new(),
             lost_found_id: None,
+            digests: HashMap::new(),
         }
     }
 
@@ -177,8 +217,46 @@ impl DriftRouter {
         created_by: PrincipalId,
     ) -> Result<(), DriftError> {
         if let Some(l) = label {
-            self.check_label_available(l, id)?;
+            self.check_label_available(l, Some(id))?;
+        }
+        self.insert_registered(id, label, forked_from, created_by);
+        Ok(())
+    }
+
+    /// Register a context recovered from the KernelDb at startup. Only exact
+    /// label collisions are refused: labels that are near-duplicates of each
+    /// other predate near-duplicate enforcement and still load.
+    pub fn register_recovered(
+        &mut self,
+        id: ContextId,
+        label: Option<&str>,
+        forked_from: Option<ContextId>,
+        created_by: PrincipalId,
+    ) -> Result<(), DriftError> {
+        if let Some(l) = label
+            && let Some(&existing) = self.label_to_id.get(l)
+            && existing != id
+        {
+            return Err(DriftError::LabelInUse {
+                label: l.to_string(),
+                existing: existing.short(),
+                suggestions: Vec::new(),
+            });
+        }
+        self.insert_registered(id, label, forked_from, created_by);
+        Ok(())
+    }
+
+    fn insert_registered(
+        &mut self,
+        id: ContextId,
+        label: Option<&str>,
+        forked_from: Option<ContextId>,
+        created_by: PrincipalId,
+    ) {
+        if let Some(l) = label {
             self.label_to_id.insert(l.to_string(), id);
+            self.reservations.retain(|_, r| r.context != Some(id));
         }
 
         let handle = ContextHandle {
@@ -195,7 +273,6 @@ impl DriftRouter {
         };
 
         self.contexts.insert(id, handle);
-        Ok(())
     }
 
     /// Register a forked context, inheriting provider/model from the parent.
@@ -220,8 +297,9 @@ impl DriftRouter {
         let parent_model = parent.model.clone();
 
         if let Some(l) = label {
-            self.check_label_available(l, id)?;
+            self.check_label_available(l, Some(id))?;
             self.label_to_id.insert(l.to_string(), id);
+            self.reservations.retain(|_, r| r.context != Some(id));
         }
 
         let handle = ContextHandle {
@@ -267,6 +345,8 @@ impl DriftRouter {
             );
             self.dead_letter.extend(dead);
         }
+        self.digests
+            .retain(|(subscriber, source), _| *subscriber != id && *source != id);
     }
 
     /// Look up a context by ContextId.
@@ -286,7 +366,7 @@ impl DriftRouter {
     pub fn rename(&mut self, id: ContextId, new_label: Option<&str>) -> Result<(), DriftError> {
         // Check availability before mutating anything
         if let Some(l) = new_label {
-            self.check_label_available(l, id)?;
+            self.check_label_available(l, Some(id))?;
         }
 
         let handle = self
@@ -308,17 +388,152 @@ impl DriftRouter {
         Ok(())
     }
 
-    /// Check that a label is not already in use by a different context.
-    fn check_label_available(&self, label: &str, id: ContextId) -> Result<(), DriftError> {
-        if let Some(&existing_id) = self.label_to_id.get(label) {
-            if existing_id != id {
-                return Err(DriftError::LabelInUse {
-                    label: label.to_string(),
-                    existing: existing_id.short(),
-                });
+    /// Check that a label (or a near-duplicate of it) is neither in use by
+    /// a context other than `id` nor held by a reservation for one. A
+    /// conflict carries free alternatives.
+    pub fn check_label_available(
+        &self,
+        label: &str,
+        id: Option<ContextId>,
+    ) -> Result<(), DriftError> {
+        match self.label_conflict(label, id) {
+            None => Ok(()),
+            Some(LabelConflict::InUse(existing)) => Err(DriftError::LabelInUse {
+                label: label.to_string(),
+                existing: existing.short(),
+                suggestions: self.suggest_labels(label),
+            }),
+            Some(LabelConflict::Reserved) => Err(DriftError::LabelReserved {
+                label: label.to_string(),
+                suggestions: self.suggest_labels(label),
+            }),
+        }
+    }
+
+    fn label_conflict(&self, label: &str, id: Option<ContextId>) -> Option<LabelConflict> {
+        let key = context_label_key(label);
+        if let Some((_, &existing)) = self
+            .label_to_id
+            .iter()
+            .find(|(l, e)| Some(**e) != id && context_label_key(l) == key)
+        {
+            return Some(LabelConflict::InUse(existing));
+        }
+        let now = kaijutsu_types::now_millis();
+        self.reservations
+            .get(&key)
+            .filter(|r| r.expires_at > now && (id.is_none() || r.context != id))
+            .map(|_| LabelConflict::Reserved)
+    }
+
+    /// Free labels like `label`: `label-2`, `label-3`, … (a trailing `-N` on
+    /// `label` itself is replaced rather than extended).
+    fn suggest_labels(&self, label: &str) -> Vec<String> {
+        let label = label.trim();
+        let stem = label
+            .rsplit_once('-')
+            .filter(|(stem, n)| !stem.is_empty() && n.parse::<u32>().is_ok())
+            .map_or(label, |(stem, _)| stem);
+        (2..)
+            .map(|n| format!("{stem}-{n}"))
+            .filter(|candidate| self.label_conflict(candidate, None).is_none())
+            .take(LABEL_SUGGESTIONS)
+            .collect()
+    }
+
+    /// Hold `label` for `principal` for [`LABEL_RESERVATION_TTL_MS`]. A taken
+    /// label is not an error: the reservation comes back without a token,
+    /// carrying alternatives. Reserving a label the principal already holds
+    /// renews it.
+    pub fn reserve_label(
+        &mut self,
+        label: &str,
+        principal: PrincipalId,
+    ) -> Result<ContextNameReservation, DriftError> {
+        let label = label.trim();
+        let key = context_label_key(label);
+        if key.is_empty() {
+            return Err(DriftError::InvalidLabel(label.to_string()));
+        }
+        let now = kaijutsu_types::now_millis();
+        self.reservations.retain(|_, r| r.expires_at > now);
+
+        let renew = self
+            .reservations
+            .get(&key)
+            .is_some_and(|r| r.principal == principal && r.context.is_none());
+        if !renew {
+            match self.check_label_available(label, None) {
+                Ok(()) => {}
+                Err(DriftError::LabelInUse { suggestions, .. })
+                | Err(DriftError::LabelReserved { suggestions, .. }) => {
+                    return Ok(ContextNameReservation {
+                        token: None,
+                        label: label.to_string(),
+                        expires_at: 0,
+                        suggestions,
+                    });
+                }
+                Err(e) => return Err(e),
             }
         }
-        Ok(())
+
+        let reservation = self
+            .reservations
+            .entry(key)
+            .or_insert_with(|| LabelReservation {
+                token: ReservationId::new(),
+                label: String::new(),
+                principal,
+                expires_at: 0,
+                context: None,
+            });
+        reservation.label = label.to_string();
+        reservation.expires_at = now + LABEL_RESERVATION_TTL_MS;
+        Ok(ContextNameReservation {
+            token: Some(reservation.token),
+            label: reservation.label.clone(),
+            expires_at: reservation.expires_at,
+            suggestions: Vec::new(),
+        })
+    }
+
+    /// Commit `principal`'s reservation to the context `id` about to be
+    /// created under it, returning the label. The hold is extended so the
+    /// creation can finish; registering `id` with the label consumes it.
+    pub fn commit_label_reservation(
+        &mut self,
+        token: ReservationId,
+        principal: PrincipalId,
+        id: ContextId,
+    ) -> Result<String, DriftError> {
+        let now = kaijutsu_types::now_millis();
+        let reservation = self
+            .reservations
+            .values_mut()
+            .find(|r| r.token == token && r.principal == principal && r.expires_at > now)
+            .filter(|r| r.context.is_none())
+            .ok_or_else(|| DriftError::UnknownReservation(token.short()))?;
+        reservation.context = Some(id);
+        reservation.expires_at = now + LABEL_RESERVATION_TTL_MS;
+        Ok(reservation.label.clone())
+    }
+
+    /// Drop `principal`'s reservation. Returns whether one was held.
+    pub fn release_label_reservation(
+        &mut self,
+        token: ReservationId,
+        principal: PrincipalId,
+    ) -> bool {
+        let before = self.reservations.len();
+        self.reservations
+            .retain(|_, r| !(r.token == token && r.principal == principal));
+        self.reservations.len() != before
+    }
+
+    /// Exact label lookup — no prefix matching.
+    pub fn context_by_label(&self, label: &str) -> Option<ContextId> {
+        self.label_to_id.get(label).copied()
     }
 
     /// Resolve a query string (label, label prefix, or hex prefix) to a ContextId.
@@ -457,6 +672,89 @@ impl DriftRouter {
         Ok(id)
     }
 
+    // ========================================================================
+    // Digest subscriptions
+    // ========================================================================
+
+    /// Add or replace `sub` (keyed by its subscriber and source). Both
+    /// contexts must be registered, and a new subscription must fit under
+    /// [`MAX_DIGEST_SUBSCRIPTIONS`] for its subscriber.
+    pub fn subscribe_digest(&mut self, sub: DigestSubscription) -> Result<(), DriftError> {
+        sub.validate().map_err(DriftError::InvalidDigest)?;
+        for id in [sub.subscriber, sub.source] {
+            if !self.contexts.contains_key(&id) {
+                return Err(DriftError::UnknownContext(id.short()));
+            }
+        }
+        let key = (sub.subscriber, sub.source);
+        if !self.digests.contains_key(&key)
+            && self.digest_subscriptions(sub.subscriber).len() >= MAX_DIGEST_SUBSCRIPTIONS
+        {
+            return Err(DriftError::InvalidDigest(format!(
+                "{} already follows {MAX_DIGEST_SUBSCRIPTIONS} contexts",
+                sub.subscriber.short()
+            )));
+        }
+        self.digests.insert(key, sub);
+        Ok(())
+    }
+
+    /// Drop `subscriber`'s subscription to `source`, returning it.
+    pub fn unsubscribe_digest(
+        &mut self,
+        subscriber: ContextId,
+        source: ContextId,
+    ) -> Option<DigestSubscription> {
+        self.digests.remove(&(subscriber, source))
+    }
+
+    /// `subscriber`'s subscriptions, oldest first.
+    pub fn digest_subscriptions(&self, subscriber: ContextId) -> Vec<&DigestSubscription> {
+        let mut subs: Vec<_> = self
+            .digests
+            .values()
+            .filter(|s| s.subscriber == subscriber)
+            .collect();
+        subs.sort_by_key(|s| (s.created_at, s.source));
+        subs
+    }
+
+    /// The subscriptions due at `now` (Unix millis). `source_state` reports a
+    /// source's last block and whether a turn is running in it.
+    pub fn due_digests(
+        &self,
+        now: u64,
+        mut source_state: impl FnMut(ContextId) -> (Option<BlockId>, bool),
+    ) -> Vec<DigestSubscription> {
+        let mut due: Vec<_> = self
+            .digests
+            .values()
+            .filter(|s| {
+                let (last, busy) = source_state(s.source);
+                s.is_due(now, last, busy)
+            })
+            .cloned()
+            .collect();
+        due.sort_by_key(|s| (s.created_at, s.subscriber, s.source));
+        due
+    }
+
+    /// Record a delivered digest: taken at `at`, covering the source up to
+    /// `last_block`. Returns the updated subscription for persisting, or
+    /// `None` if it was removed meanwhile.
+    pub fn mark_digested(
+        &mut self,
+        subscriber: ContextId,
+        source: ContextId,
+        at: u64,
+        last_block: Option<BlockId>,
+    ) -> Option<DigestSubscription> {
+        let sub = self.digests.get_mut(&(subscriber, source))?;
+        sub.last_digest_at = Some(at);
+        sub.last_block = last_block;
+        Some(sub.clone())
+    }
+
     /// Cancel a staged drift by ID.
     pub fn cancel(&mut self, staged_id: u64) -> bool {
         let len_before = self.staging.len();
@@ -577,12 +875,42 @@ pub enum DriftError {
         prefix: String,
         candidates: Vec<String>,
     },
-    #[error("label '{label}' already in use by context {existing}")]
-    LabelInUse { label: String, existing: String },
+    #[error("label '{label}' already in use by context {existing}{}", try_instead(.suggestions))]
+    LabelInUse {
+        label: String,
+        existing: String,
+        suggestions: Vec<String>,
+    },
+    #[error("label '{label}' is reserved for a context being created{}", try_instead(.suggestions))]
+    LabelReserved {
+        label: String,
+        suggestions: Vec<String>,
+    },
+    #[error("invalid context label '{0}'")]
+    InvalidLabel(String),
+    #[error("no such context-name reservation: {0}")]
+    UnknownReservation(String),
     #[error("document error: {0}")]
     DocumentError(String),
     #[error("LLM error: {0}")]
     LlmError(String),
+    #[error("invalid digest subscription: {0}")]
+    InvalidDigest(String),
+}
+
+/// `" (try: a, b)"`, or nothing without suggestions.
+fn try_instead(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" (try: {})", suggestions.join(", "))
+    }
+}
+
+/// Who stands in the way of a label.
+enum LabelConflict {
+    InUse(ContextId),
+    Reserved,
 }
 
 // ============================================================================
@@ -624,6 +952,7 @@ pub fn build_distillation_prompt(
             BlockKind::Notification => " (notification)",
             BlockKind::Resource => " (resource)",
             BlockKind::Trace => " (trace)",
+            BlockKind::Repl => " (repl)",
             BlockKind::Text => "",
         };
 
@@ -1381,4 +1710,129 @@ mod tests {
         assert_eq!(router.resolve_context("alpha").unwrap(), a);
         assert_eq!(router.resolve_context("beta").unwrap(), b);
     }
+
+    #[test]
+    fn near_duplicate_labels_conflict_with_suggestions() {
+        let mut router = DriftRouter::new();
+        let a = ContextId::new();
+        router
+            .register(a, Some("Bug Hunt"), None, PrincipalId::new())
+            .unwrap();
+        router
+            .register(
+                ContextId::new(),
+                Some("bug-hunt-2"),
+                None,
+                PrincipalId::new(),
+            )
+            .unwrap();
+
+        match router.register(ContextId::new(), Some("bug_hunt"), None, PrincipalId::new()) {
+            Err(DriftError::LabelInUse { suggestions, .. }) => {
+                assert_eq!(suggestions, ["bug_hunt-3", "bug_hunt-4", "bug_hunt-5"]);
+            }
+            other => panic!("expected LabelInUse, got {other:?}"),
+        }
+        // A context may re-case its own label.
+        router.rename(a, Some("bug hunt")).unwrap();
+        // Recovery loads legacy near-duplicates as they are.
+        router
+            .register_recovered(ContextId::new(), Some("BUG-HUNT"), None, PrincipalId::new())
+            .unwrap();
+    }
+
+    #[test]
+    fn label_reservations_hold_commit_and_release() {
+        let mut router = DriftRouter::new();
+        let (amy, bob) = (PrincipalId::new(), PrincipalId::new());
+
+        let held = router.reserve_label("review", amy).unwrap();
+        let token = held.token.expect("free name is reserved");
+        assert!(held.expires_at > 0);
+
+        // Bob can't take it, by any spelling, through either door.
+        let taken = router.reserve_label("Review", bob).unwrap();
+        assert!(!taken.is_reserved());
+        assert_eq!(taken.suggestions[0], "Review-2");
+        assert!(matches!(
+            router.register(ContextId::new(), Some("review"), None, bob),
+            Err(DriftError::LabelReserved { .. })
+        ));
+        assert!(
+            router
+                .commit_label_reservation(token, bob, ContextId::new())
+                .is_err()
+        );
+
+        // Amy commits it to a new context, which then registers under it.
+        let id = ContextId::new();
+        assert_eq!(
+            router.commit_label_reservation(token, amy, id).unwrap(),
+            "review"
+        );
+        router.register(id, Some("review"), None, amy).unwrap();
+        assert!(router.reservations.is_empty(), "registering consumes it");
+
+        let other = router.reserve_label("triage", amy).unwrap().token.unwrap();
+        assert!(!router.release_label_reservation(other, bob));
+        assert!(router.release_label_reservation(other, amy));
+        assert!(router.reserve_label("triage", bob).unwrap().is_reserved());
+        assert!(matches!(
+            router.reserve_label(" -- ", amy),
+            Err(DriftError::InvalidLabel(_))
+        ));
+    }
+
+    /// Digest subscriptions need registered contexts, come due only with
+    /// new blocks, and go away with either context.
+    #[test]
+    fn test_digest_subscriptions() {
+        let mut router = DriftRouter::new();
+        let seat = ContextId::new();
+        let source = ContextId::new();
+        router
+            .register(seat, Some("seat"), None, PrincipalId::new())
+            .unwrap();
+
+        let sub = DigestSubscription {
+            subscriber: seat,
+            source,
+            every_secs: Some(300),
+            on_milestone: false,
+            focus: None,
+            created_by: PrincipalId::new(),
+            created_at: 0,
+            last_digest_at: None,
+            last_block: None,
+        };
+        assert!(matches!(
+            router.subscribe_digest(sub.clone()),
+            Err(DriftError::UnknownContext(_))
+        ));
+        router
+            .register(source, Some("source"), None, PrincipalId::new())
+            .unwrap();
+        router.subscribe_digest(sub).unwrap();
+        assert_eq!(router.digest_subscriptions(seat).len(), 1);
+
+        let block = BlockId::new(source, PrincipalId::new(), 7);
+        assert!(router.due_digests(400_000, |_| (None, false)).is_empty());
+        assert_eq!(
+            router.due_digests(400_000, |_| (Some(block), false)).len(),
+            1
+        );
+
+        let marked = router
+            .mark_digested(seat, source, 400_000, Some(block))
+            .unwrap();
+        assert_eq!(marked.last_block, Some(block));
+        assert!(
+            router
+                .due_digests(900_000, |_| (Some(block), false))
+                .is_empty()
+        );
+
+        router.unregister(source);
+        assert!(router.digest_subscriptions(seat).is_empty());
+    }
 }
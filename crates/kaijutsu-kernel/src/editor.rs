@@ -30,7 +30,7 @@
 //! is the whole point. See `docs/vi.md` ("Path resolution").
 
 use kaijutsu_crdt::{BlockId, ContextId};
-use kaijutsu_types::{PrincipalId, SessionId};
+use kaijutsu_types::{DEFAULT_LOCK_TTL_SECS, PrincipalId, SessionId};
 #[cfg(test)]
 use kaijutsu_types::paths::{CONFIG_ROOT, RC_ROOT};
 
@@ -269,6 +269,24 @@ struct EditorSession {
     peer_wrote: bool,
 }
 
+/// Keep the opener's advisory lock on an editor's block
+/// ([`crate::block_locks`]): taken at open, renewed as edits arrive once half
+/// its TTL has run, released at quit. Someone else's lock doesn't stop the
+/// editor — the CRDT merges both writers — it just isn't ours to renew.
+fn hold_lock(blocks: &SharedBlockStore, target: &EditorTarget, principal: PrincipalId) {
+    let now = kaijutsu_types::now_millis();
+    let fresh = blocks.block_lock(&target.block_id).is_some_and(|lock| {
+        lock.holder != principal
+            || lock.expires_at.saturating_sub(now) > DEFAULT_LOCK_TTL_SECS * 500
+    });
+    if fresh {
+        return;
+    }
+    if let Err(e) = blocks.lock_block(&target.block_id, principal, "", None, "editing in vi") {
+        tracing::debug!(block = %target.block_id, "editor lock not taken: {e}");
+    }
+}
+
 /// The kernel's registry of open editor sessions.
 ///
 /// Every operation is **synchronous**: the `EditorCore` (which is `!Send` via
@@ -317,6 +335,9 @@ impl EditorSessions {
                 peer_wrote: false,
             },
         );
+        if let Some(opener) = opener {
+            hold_lock(blocks, &target, opener.principal);
+        }
         Ok((id, state))
     }
 
@@ -382,6 +403,11 @@ impl EditorSessions {
                     )
                     .map_err(|e| format!("editor keys: CRDT mirror failed: {e}"))?;
             }
+            if let Some(opener) = session.opener
+                && !ops.is_empty()
+            {
+                hold_lock(blocks, &session.target, opener.principal);
+            }
             (session.core.take_close(), session.core.take_commands())
         };
 
@@ -610,6 +636,9 @@ impl EditorSessions {
     /// merged truth for the others (Amy, 2026-07-07; `docs/vi.md` → Rollback).
     pub fn quit(&mut self, id: EditorSessionId, blocks: &SharedBlockStore) -> Result<(), String> {
         let session = self.sessions.remove(&id).ok_or_else(|| no_session(id))?;
+        if let Some(opener) = session.opener {
+            let _ = blocks.unlock_block(&session.target.block_id, opener.principal);
+        }
         let sibling_bound = self.sessions.values().any(|s| s.target == session.target);
         if sibling_bound || session.peer_wrote {
             return Ok(());
@@ -704,38 +704,16 @@ fn file_context_id(path: &str) -> ContextId {
     ContextId::from_bytes(*uuid.as_bytes())
 }
 
+/// Whether a document is a file-backed one (see [`file_context_id`]) rather
+/// than a standalone `Code` document. Only file documents carry UUIDv5 ids;
+/// every other `ContextId` is minted as v7.
+pub fn is_file_document(context_id: ContextId) -> bool {
+    uuid::Uuid::from_bytes(*context_id.as_bytes()).get_version_num() == 5
+}
+
 /// Detect programming language from file extension.
-fn detect_language(path: &str) -> Option<String> {
-    let ext = path.rsplit('.').next()?;
-    let lang = match ext {
-        "rs" => "rust",
-        "py" => "python",
-        "js" => "javascript",
-        "ts" => "typescript",
-        "tsx" => "typescriptreact",
-        "jsx" => "javascriptreact",
-        "go" => "go",
-        "rb" => "ruby",
-        "lua" => "lua",
-        "sh" | "bash" => "bash",
-        "zsh" => "zsh",
-        "c" => "c",
-        "cpp" | "cc" | "cxx" => "cpp",
-        "h" => "c",
-        "hpp" => "cpp",
-        "java" => "java",
-        "kt" => "kotlin",
-        "toml" => "toml",
-        "yaml" | "yml" => "yaml",
-        "json" => "json",
-        "md" => "markdown",
-        "html" => "html",
-        "css" => "css",
-        "sql" => "sql",
-        "wgsl" => "wgsl",
-        _ => return None,
-    };
-    Some(lang.to_string())
+pub(crate) fn detect_language(path: &str) -> Option<String> {
+    kaijutsu_types::language::language_for_path(path).map(str::to_string)
 }
 
 #[cfg(test)]
@@ -0,0 +1,335 @@
+//! Code documents: a source file split into one block per top-level item.
+//!
+//! `code_import` parses a file with tree-sitter and creates a `DocKind::Code`
+//! document whose blocks mirror the file's top-level structure — each
+//! function, type, impl, or class is its own `Text` block, with the comments
+//! and attributes directly above it riding along. Consecutive imports collapse
+//! into one block. The chunks partition the source byte-for-byte (whitespace
+//! included), so `code_export` is plain concatenation in document order and
+//! an untouched import round-trips exactly.
+//!
+//! Unlike the single-block file documents in [`FileDocumentCache`], a code
+//! document is not tied to its source path: it is a structured working copy
+//! that gets written back explicitly.
+//!
+//! [`FileDocumentCache`]: super::FileDocumentCache
+
+use std::ops::Range;
+
+use kaijutsu_crdt::{BlockKind, ContentType, ContextId, Role, Status};
+use kaijutsu_types::DocKind;
+
+use crate::block_store::SharedBlockStore;
+
+/// Languages with a bundled tree-sitter grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeLanguage {
+    Rust,
+    Python,
+}
+
+impl CodeLanguage {
+    /// Parse a language name (`rust`/`rs`, `python`/`py`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Some(Self::Rust),
+            "python" | "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    /// Infer the language from a file extension.
+    pub fn detect(path: &str) -> Option<Self> {
+        super::cache::detect_language(path).and_then(|l| Self::from_name(&l))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Python => "python",
+        }
+    }
+
+    fn grammar(&self) -> tree_sitter::Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+        }
+    }
+
+    /// Nodes that belong to the item after them rather than standing alone.
+    fn is_leading(&self, kind: &str) -> bool {
+        match self {
+            Self::Rust => matches!(kind, "line_comment" | "block_comment" | "attribute_item"),
+            Self::Python => kind == "comment",
+        }
+    }
+
+    /// Items that merge with an immediately preceding item of the same kind.
+    fn is_grouped(&self, kind: &str) -> bool {
+        match self {
+            Self::Rust => matches!(kind, "use_declaration" | "extern_crate_declaration"),
+            Self::Python => matches!(kind, "import_statement" | "import_from_statement"),
+        }
+    }
+}
+
+/// One top-level item's slice of the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeChunk {
+    /// tree-sitter node kind of the item (`function_item`, `class_definition`, …).
+    pub kind: String,
+    /// The item's name, when the grammar exposes one.
+    pub name: Option<String>,
+    /// Byte range in the source, leading comments and whitespace included.
+    pub range: Range<usize>,
+}
+
+impl CodeChunk {
+    /// Short label for tool output: `function_item foo`.
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} {}", self.kind, name),
+            None => self.kind.clone(),
+        }
+    }
+}
+
+/// Split `source` into chunks that partition it exactly, one per top-level
+/// item. Source with no items at all comes back as a single chunk (none when
+/// empty). Parse errors don't fail the split: tree-sitter still yields a
+/// tree, and the partition stays lossless either way.
+pub fn split_source(source: &str, language: CodeLanguage) -> Result<Vec<CodeChunk>, String> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&language.grammar())
+        .map_err(|e| format!("failed to load {} grammar: {}", language.as_str(), e))?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| format!("{} parser produced no tree", language.as_str()))?;
+    let root = tree.root_node();
+
+    // (boundary, kind, name) for each chunk start, in order.
+    let mut starts: Vec<(usize, String, Option<String>)> = Vec::new();
+    let mut pending_lead: Option<usize> = None;
+    let mut prev_end = 0;
+    let mut cursor = root.walk();
+    for node in root.named_children(&mut cursor) {
+        let kind = node.kind();
+        if language.is_leading(kind) {
+            // A trailing comment on the previous item's last line stays put.
+            let same_line = !starts.is_empty()
+                && pending_lead.is_none()
+                && !source[prev_end..node.start_byte()].contains('\n');
+            if !same_line {
+                pending_lead.get_or_insert(node.start_byte());
+            }
+            continue;
+        }
+        let lead = pending_lead.take();
+        if lead.is_none()
+            && language.is_grouped(kind)
+            && starts.last().is_some_and(|(_, k, _)| k == kind)
+        {
+            prev_end = node.end_byte();
+            continue;
+        }
+        let first = lead.unwrap_or(node.start_byte());
+        let boundary = if starts.is_empty() {
+            0
+        } else {
+            line_start(source, first).max(prev_end)
+        };
+        if starts.last().is_some_and(|(b, _, _)| *b >= boundary) {
+            // Shares a line with the previous item — keep them together.
+            prev_end = node.end_byte();
+            continue;
+        }
+        starts.push((boundary, kind.to_string(), item_name(&node, source)));
+        prev_end = node.end_byte();
+    }
+
+    if starts.is_empty() {
+        if source.is_empty() {
+            return Ok(Vec::new());
+        }
+        return Ok(vec![CodeChunk {
+            kind: root.kind().to_string(),
+            name: None,
+            range: 0..source.len(),
+        }]);
+    }
+
+    let ends = starts
+        .iter()
+        .skip(1)
+        .map(|(b, _, _)| *b)
+        .chain(std::iter::once(source.len()));
+    Ok(starts
+        .iter()
+        .zip(ends)
+        .map(|((start, kind, name), end)| CodeChunk {
+            kind: kind.clone(),
+            name: name.clone(),
+            range: *start..end,
+        })
+        .collect())
+}
+
+fn line_start(source: &str, byte: usize) -> usize {
+    source[..byte].rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
+
+fn item_name(node: &tree_sitter::Node<'_>, source: &str) -> Option<String> {
+    let named = node
+        .child_by_field_name("name")
+        .or_else(|| node.child_by_field_name("type"))
+        .or_else(|| {
+            // Python `@decorator` wraps the real definition.
+            node.child_by_field_name("definition")
+                .and_then(|d| d.child_by_field_name("name"))
+        })?;
+    named.utf8_text(source.as_bytes()).ok().map(str::to_string)
+}
+
+/// Create a new code document holding `source`, one block per chunk.
+pub fn import_code_document(
+    store: &SharedBlockStore,
+    source: &str,
+    language: CodeLanguage,
+) -> Result<(ContextId, Vec<CodeChunk>), String> {
+    let chunks = split_source(source, language)?;
+    let doc_id = ContextId::new();
+    store
+        .create_document(doc_id, DocKind::Code, Some(language.as_str().to_string()))
+        .map_err(|e| e.to_string())?;
+
+    let mut after = None;
+    for chunk in &chunks {
+        let id = store
+            .insert_block(
+                doc_id,
+                None,
+                after.as_ref(),
+                Role::System,
+                BlockKind::Text,
+                &source[chunk.range.clone()],
+                Status::Done,
+                ContentType::Plain,
+            )
+            .map_err(|e| e.to_string())?;
+        store
+            .set_language(doc_id, &id, Some(language.as_str().to_string()))
+            .map_err(|e| e.to_string())?;
+        after = Some(id);
+    }
+    Ok((doc_id, chunks))
+}
+
+/// Reassemble a code document's text blocks, in document order.
+pub fn export_code_document(store: &SharedBlockStore, doc_id: ContextId) -> Result<String, String> {
+    let kind = store
+        .get(doc_id)
+        .map(|entry| entry.kind)
+        .ok_or_else(|| format!("document {} not found", doc_id.to_hex()))?;
+    if kind != DocKind::Code {
+        return Err(format!(
+            "document {} is a {} document, not code",
+            doc_id.to_hex(),
+            kind
+        ));
+    }
+    let blocks = store.block_snapshots(doc_id).map_err(|e| e.to_string())?;
+    Ok(blocks
+        .iter()
+        .filter(|b| b.kind == BlockKind::Text)
+        .map(|b| b.content.as_str())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::shared_block_store;
+    use kaijutsu_types::PrincipalId;
+
+    const RUST_SRC: &str = "\
+//! Module docs.
+
+use std::fmt;
+use std::io;
+
+/// A point.
+#[derive(Debug)]
+struct Point {
+    x: i32,
+}
+
+impl Point {
+    fn new() -> Self {
+        Point { x: 0 }
+    }
+}
+
+fn main() {}
+";
+
+    fn labels(chunks: &[CodeChunk]) -> Vec<String> {
+        chunks.iter().map(CodeChunk::label).collect()
+    }
+
+    #[test]
+    fn rust_splits_by_item_and_groups_imports() {
+        let chunks = split_source(RUST_SRC, CodeLanguage::Rust).unwrap();
+        assert_eq!(
+            labels(&chunks),
+            vec![
+                "use_declaration",
+                "struct_item Point",
+                "impl_item Point",
+                "function_item main",
+            ]
+        );
+        // Doc comment and attribute ride with the struct.
+        assert!(RUST_SRC[chunks[1].range.clone()].starts_with("/// A point.\n#[derive"));
+        let joined: String = chunks.iter().map(|c| &RUST_SRC[c.range.clone()]).collect();
+        assert_eq!(joined, RUST_SRC);
+    }
+
+    #[test]
+    fn python_keeps_decorators_with_their_definition() {
+        let src = "import os\nimport sys\n\n\n@cache\ndef f():\n    return 1\n\n\nclass C:\n    pass\n";
+        let chunks = split_source(src, CodeLanguage::Python).unwrap();
+        assert_eq!(
+            labels(&chunks),
+            vec!["import_statement", "decorated_definition f", "class_definition C"]
+        );
+        assert!(src[chunks[1].range.clone()].starts_with("@cache"));
+    }
+
+    #[test]
+    fn empty_and_itemless_sources() {
+        assert!(split_source("", CodeLanguage::Rust).unwrap().is_empty());
+        let chunks = split_source("// just a comment\n", CodeLanguage::Rust).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].range, 0..18);
+    }
+
+    #[test]
+    fn import_export_round_trips() {
+        let store = shared_block_store(PrincipalId::system());
+        let (doc_id, chunks) = import_code_document(&store, RUST_SRC, CodeLanguage::Rust).unwrap();
+        assert_eq!(store.block_snapshots(doc_id).unwrap().len(), chunks.len());
+        assert_eq!(store.get(doc_id).unwrap().kind, DocKind::Code);
+        assert_eq!(export_code_document(&store, doc_id).unwrap(), RUST_SRC);
+    }
+
+    #[test]
+    fn export_refuses_non_code_documents() {
+        let store = shared_block_store(PrincipalId::system());
+        let ctx = ContextId::new();
+        store.create_document(ctx, DocKind::Conversation, None).unwrap();
+        let err = export_code_document(&store, ctx).unwrap_err();
+        assert!(err.contains("not code"), "got: {err}");
+    }
+}
@@ -31,11 +31,14 @@
 //! ```
 
 pub mod cache;
+pub mod code_doc;
 pub mod guard;
 pub mod hashline;
 pub mod path;
+pub mod rename;
 pub mod vfs_walker;
 
 pub use cache::{CacheReadError, FileDocumentCache};
+pub use code_doc::{CodeChunk, CodeLanguage};
 pub use guard::WorkspaceGuard;
 pub use vfs_walker::VfsWalkerAdapter;
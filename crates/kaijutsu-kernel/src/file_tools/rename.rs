@@ -0,0 +1,269 @@
+//! Workspace-wide renames: one identifier, every file and code document.
+//!
+//! `refactor_rename` plans a rename as a single [`Changeset`]: whole-word
+//! matches of the old name in every text file under the scope (read through
+//! the [`FileDocumentCache`], so unflushed CRDT edits count) and in every
+//! text block of every `Code` document. The plan is rendered as a changeset
+//! block in the caller's context — a diff per file and per code block — and
+//! nothing is written until a second call names that block. Applying first
+//! checks that every target still holds the text the plan was made from, so
+//! a stale changeset fails whole instead of clobbering later edits.
+//!
+//! [`FileDocumentCache`]: super::FileDocumentCache
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use kaijutsu_crdt::{BlockId, BlockKind, ContextId};
+use kaijutsu_types::DocKind;
+use kaijutsu_types::diff::{LineDiff, LineTag};
+
+use super::cache::is_file_document;
+use crate::block_store::SharedBlockStore;
+
+/// What a rename reaches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RenameScope {
+    /// Files under the path and every code document.
+    #[default]
+    All,
+    /// Files under the path only.
+    Files,
+    /// Code documents only.
+    Code,
+}
+
+impl RenameScope {
+    pub fn files(&self) -> bool {
+        matches!(self, Self::All | Self::Files)
+    }
+
+    pub fn code(&self) -> bool {
+        matches!(self, Self::All | Self::Code)
+    }
+}
+
+/// One file's part of a changeset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRename {
+    pub path: String,
+    pub before: String,
+    pub after: String,
+    pub occurrences: usize,
+}
+
+/// One code-document block's part of a changeset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockRename {
+    pub document_id: ContextId,
+    pub block_id: BlockId,
+    pub before: String,
+    pub after: String,
+    pub occurrences: usize,
+}
+
+/// A planned rename across files and code blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changeset {
+    pub old: String,
+    pub new: String,
+    pub files: Vec<FileRename>,
+    pub blocks: Vec<BlockRename>,
+    /// Files with matches that the caller may not write, left out of the plan.
+    pub skipped: Vec<String>,
+}
+
+impl Changeset {
+    pub fn new(old: &str, new: &str) -> Self {
+        Self {
+            old: old.to_string(),
+            new: new.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.blocks.is_empty()
+    }
+
+    pub fn occurrences(&self) -> usize {
+        self.files.iter().map(|f| f.occurrences).sum::<usize>()
+            + self.blocks.iter().map(|b| b.occurrences).sum::<usize>()
+    }
+
+    /// One-line tally: `5 occurrences in 2 files and 1 code block`.
+    pub fn summary(&self) -> String {
+        let plural =
+            |n: usize, one: &str, many: &str| format!("{n} {}", if n == 1 { one } else { many });
+        format!(
+            "{} in {} and {}",
+            plural(self.occurrences(), "occurrence", "occurrences"),
+            plural(self.files.len(), "file", "files"),
+            plural(self.blocks.len(), "code block", "code blocks"),
+        )
+    }
+
+    /// The reviewable changeset: a markdown block with one diff per file
+    /// and per code block.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "# rename `{}` → `{}`\n\n{}\n",
+            self.old,
+            self.new,
+            self.summary()
+        );
+        for file in &self.files {
+            out.push_str(&format!(
+                "\n## {} ({})\n\n```diff\n{}```\n",
+                file.path,
+                file.occurrences,
+                render_diff(&file.before, &file.after)
+            ));
+        }
+        for block in &self.blocks {
+            out.push_str(&format!(
+                "\n## code {} · {} ({})\n\n```diff\n{}```\n",
+                block.document_id.short(),
+                block.block_id.to_key(),
+                block.occurrences,
+                render_diff(&block.before, &block.after)
+            ));
+        }
+        if !self.skipped.is_empty() {
+            out.push_str("\n## skipped (not writable)\n\n");
+            for path in &self.skipped {
+                out.push_str(&format!("- {path}\n"));
+            }
+        }
+        out
+    }
+}
+
+/// Whole-word matcher for `old`. Word boundaries are only required at ends
+/// that are word characters, so operator-ish names still match.
+pub fn rename_pattern(old: &str) -> Result<Regex, String> {
+    if old.is_empty() {
+        return Err("old name is empty".to_string());
+    }
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let mut pattern = regex::escape(old);
+    if is_word(old.chars().next()) {
+        pattern.insert_str(0, r"\b");
+    }
+    if is_word(old.chars().last()) {
+        pattern.push_str(r"\b");
+    }
+    Regex::new(&pattern).map_err(|e| e.to_string())
+}
+
+/// Replace every match of `pattern` in `text` with `new`; `None` when there
+/// is no match.
+pub fn rename_text(pattern: &Regex, text: &str, new: &str) -> Option<(String, usize)> {
+    let occurrences = pattern.find_iter(text).count();
+    (occurrences > 0).then(|| {
+        let replaced = pattern.replace_all(text, regex::NoExpand(new));
+        (replaced.into_owned(), occurrences)
+    })
+}
+
+/// Plan the rename for the text blocks of every code document. File-backed
+/// documents are left to the file half of the plan.
+pub fn plan_code_blocks(store: &SharedBlockStore, pattern: &Regex, new: &str) -> Vec<BlockRename> {
+    let mut planned = Vec::new();
+    for document_id in store.list_ids_by_kind(DocKind::Code) {
+        if is_file_document(document_id) {
+            continue;
+        }
+        let Ok(blocks) = store.block_snapshots(document_id) else {
+            continue;
+        };
+        for block in blocks.into_iter().filter(|b| b.kind == BlockKind::Text) {
+            if let Some((after, occurrences)) = rename_text(pattern, &block.content, new) {
+                planned.push(BlockRename {
+                    document_id,
+                    block_id: block.id,
+                    before: block.content,
+                    after,
+                    occurrences,
+                });
+            }
+        }
+    }
+    planned
+}
+
+/// Changed lines only, each run under a `@@ -old +new` line-number header.
+fn render_diff(before: &str, after: &str) -> String {
+    let diff = LineDiff::new(before, after);
+    let (mut old_line, mut new_line) = (1, 1);
+    let mut in_hunk = false;
+    let mut out = String::new();
+    for line in &diff.lines {
+        if line.tag == LineTag::Equal {
+            old_line += 1;
+            new_line += 1;
+            in_hunk = false;
+            continue;
+        }
+        if !in_hunk {
+            out.push_str(&format!("@@ -{old_line} +{new_line}\n"));
+            in_hunk = true;
+        }
+        out.push_str(&format!("{}{}\n", line.tag.marker(), line.text));
+        match line.tag {
+            LineTag::Delete => old_line += 1,
+            LineTag::Insert => new_line += 1,
+            LineTag::Equal => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::shared_block_store;
+    use crate::file_tools::code_doc::{CodeLanguage, import_code_document};
+    use kaijutsu_types::PrincipalId;
+
+    #[test]
+    fn renames_whole_words_only() {
+        let pattern = rename_pattern("parse").unwrap();
+        let (after, n) = rename_text(
+            &pattern,
+            "fn parse() {}\nparse_all(); reparse(); parse();\n",
+            "decode",
+        )
+        .unwrap();
+        assert_eq!(after, "fn decode() {}\nparse_all(); reparse(); decode();\n");
+        assert_eq!(n, 2);
+        assert!(rename_text(&pattern, "parser", "decode").is_none());
+        // `$` in the replacement is literal, not a capture reference.
+        let (after, _) = rename_text(&pattern, "parse", "$x").unwrap();
+        assert_eq!(after, "$x");
+        assert!(rename_pattern("").is_err());
+    }
+
+    #[test]
+    fn plans_code_blocks_and_renders_diffs() {
+        let store = shared_block_store(PrincipalId::system());
+        let src = "fn helper() {}\n\nfn main() {\n    helper();\n}\n\nfn other() {}\n";
+        let (doc_id, _) = import_code_document(&store, src, CodeLanguage::Rust).unwrap();
+
+        let pattern = rename_pattern("helper").unwrap();
+        let mut changeset = Changeset::new("helper", "assist");
+        changeset.blocks = plan_code_blocks(&store, &pattern, "assist");
+        assert_eq!(changeset.blocks.len(), 2, "two items mention helper");
+        assert!(changeset.blocks.iter().all(|b| b.document_id == doc_id));
+        assert_eq!(
+            changeset.summary(),
+            "2 occurrences in 0 files and 2 code blocks"
+        );
+
+        let rendered = changeset.render();
+        assert!(rendered.contains("@@ -2 +2\n-    helper();\n+    assist();\n"));
+        assert!(!rendered.contains("fn other"), "unchanged lines are elided");
+    }
+}
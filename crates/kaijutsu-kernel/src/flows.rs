@@ -33,7 +33,10 @@ use std::time::Instant;
 use serde::{Deserialize, Serialize};
 
 use kaijutsu_crdt::{BlockId, BlockKind, BlockSnapshot, Status};
-use kaijutsu_types::{BlockEventFilter, BlockFlowKind, ContextId, PrincipalId};
+use kaijutsu_types::{
+    BlockEventFilter, BlockFlowKind, BlockLock, ConfigApplyReport, ContextId, InboxItem,
+    PrincipalId,
+};
 
 // ============================================================================
 // Origin Tracking
@@ -187,6 +190,9 @@ impl FlowTopics for BlockFlow {
         "block.context_switched",
         "block.render_cue",
         "block.beat_sync",
+        "block.inbox",
+        "block.model",
+        "block.lock",
     ];
 
     fn topic_capacity(topic: &str) -> Option<usize> {
@@ -259,6 +265,11 @@ pub enum BlockFlow {
         /// CRDT data is never lost — only the realtime notification.
         #[serde(default)]
         seq_num: u64,
+        /// The context's sync generation, which `seq_num` counts within.
+        /// Bumped on every kernel load, so `(generation, seq_num)` never
+        /// repeats and clients can skip events they already merged.
+        #[serde(default)]
+        generation: u64,
     },
 
     /// A block was deleted.
@@ -394,6 +405,32 @@ pub enum BlockFlow {
         /// The beat coordinate + tempo at emission; the sink's phasor slews toward it.
         beat_ref: kaijutsu_audio::BeatRef,
     },
+    /// A notification landed in a principal's inbox. Addressed to a *seat*,
+    /// not a context, so it bypasses `BlockEventFilter` like the directives
+    /// above; the server bridge forwards it only to connections authenticated
+    /// as `item.recipient`.
+    InboxPosted {
+        /// `item.context_id`, or nil for items that point at no context.
+        context_id: ContextId,
+        item: InboxItem,
+    },
+    /// A context's model was switched (`setContextModel`). Context-scoped,
+    /// so it passes `BlockEventFilter` like any other event of the context.
+    ModelChanged {
+        context_id: ContextId,
+        provider: String,
+        model: String,
+        /// Who switched it.
+        by: PrincipalId,
+    },
+    /// A block was locked for editing (or the lock renewed), or released
+    /// (`lock: None`). Expiry is not announced — clients drop a lock at its
+    /// `expires_at`.
+    LockChanged {
+        context_id: ContextId,
+        block_id: BlockId,
+        lock: Option<BlockLock>,
+    },
 }
 
 impl BlockFlow {
@@ -413,6 +450,9 @@ impl BlockFlow {
             Self::ContextSwitched { .. } => "block.context_switched",
             Self::RenderCue { .. } => "block.render_cue",
             Self::BeatSync { .. } => "block.beat_sync",
+            Self::InboxPosted { .. } => "block.inbox",
+            Self::ModelChanged { .. } => "block.model",
+            Self::LockChanged { .. } => "block.lock",
         }
     }
 
@@ -431,7 +471,10 @@ impl BlockFlow {
             | Self::MetadataChanged { context_id, .. }
             | Self::ContextSwitched { context_id, .. }
             | Self::RenderCue { context_id, .. }
-            | Self::BeatSync { context_id, .. } => *context_id,
+            | Self::BeatSync { context_id, .. }
+            | Self::InboxPosted { context_id, .. }
+            | Self::ModelChanged { context_id, .. }
+            | Self::LockChanged { context_id, .. } => *context_id,
         }
     }
 
@@ -446,11 +489,14 @@ impl BlockFlow {
             | Self::ExcludedChanged { block_id, .. }
             | Self::Moved { block_id, .. }
             | Self::OutputChanged { block_id, .. }
-            | Self::MetadataChanged { block_id, .. } => Some(block_id),
+            | Self::MetadataChanged { block_id, .. }
+            | Self::LockChanged { block_id, .. } => Some(block_id),
             Self::SyncReset { .. }
             | Self::ContextSwitched { .. }
             | Self::RenderCue { .. }
-            | Self::BeatSync { .. } => None,
+            | Self::BeatSync { .. }
+            | Self::InboxPosted { .. }
+            | Self::ModelChanged { .. } => None,
         }
     }
 
@@ -477,7 +523,10 @@ impl BlockFlow {
             Self::SyncReset { .. }
             | Self::ContextSwitched { .. }
             | Self::RenderCue { .. }
-            | Self::BeatSync { .. } => OpSource::Local,
+            | Self::BeatSync { .. }
+            | Self::InboxPosted { .. }
+            | Self::ModelChanged { .. }
+            | Self::LockChanged { .. } => OpSource::Local,
         }
     }
 
@@ -507,6 +556,9 @@ impl BlockFlow {
             Self::ContextSwitched { .. } => BlockFlowKind::ContextSwitched,
             Self::RenderCue { .. } => BlockFlowKind::RenderCue,
             Self::BeatSync { .. } => BlockFlowKind::BeatSync,
+            Self::InboxPosted { .. } => BlockFlowKind::InboxPosted,
+            Self::ModelChanged { .. } => BlockFlowKind::ModelChanged,
+            Self::LockChanged { .. } => BlockFlowKind::LockChanged,
         }
     }
 
@@ -521,7 +573,11 @@ impl BlockFlow {
         // *stream* of block changes). The standalone slice forwards it to
         // every subscriber unconditionally; per-context "distributed
         // listening" is future work (docs/pcm.md).
-        if matches!(self, Self::RenderCue { .. } | Self::BeatSync { .. }) {
+        // Inbox items are gated per-recipient by the bridge instead.
+        if matches!(
+            self,
+            Self::RenderCue { .. } | Self::BeatSync { .. } | Self::InboxPosted { .. }
+        ) {
             return true;
         }
         // Event type constraint
@@ -1140,6 +1196,37 @@ impl HasSubject for EditorFlow {
     }
 }
 
+/// Config lifecycle events.
+///
+/// A config file was rewritten and the kernel re-applied it live (see
+/// `Kernel::apply_config_file`). Published on the in-process FlowBus; the
+/// server's block-subscription bridges forward it to every connection as
+/// `BlockEvents.onConfigApplied`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ConfigFlow {
+    /// The written file was diffed against the running config and the valid
+    /// changes applied.
+    Applied {
+        /// What was applied and what was refused.
+        report: ConfigApplyReport,
+    },
+}
+
+impl ConfigFlow {
+    /// Get the subject string for this event.
+    pub fn subject(&self) -> &'static str {
+        match self {
+            Self::Applied { .. } => "config.applied",
+        }
+    }
+}
+
+impl HasSubject for ConfigFlow {
+    fn subject(&self) -> &'static str {
+        ConfigFlow::subject(self)
+    }
+}
+
 // ============================================================================
 // Shared FlowBus Handle
 // ============================================================================
@@ -1156,6 +1243,9 @@ pub type SharedTurnFlowBus = Arc<FlowBus<TurnFlow>>;
 /// Thread-safe handle to an EditorFlow bus.
 pub type SharedEditorFlowBus = Arc<FlowBus<EditorFlow>>;
 
+/// Thread-safe handle to a ConfigFlow bus.
+pub type SharedConfigFlowBus = Arc<FlowBus<ConfigFlow>>;
+
 /// Create a new shared block flow bus.
 pub fn shared_block_flow_bus(capacity: usize) -> SharedBlockFlowBus {
     Arc::new(FlowBus::new(capacity))
@@ -1176,6 +1266,11 @@ pub fn shared_editor_flow_bus(capacity: usize) -> SharedEditorFlowBus {
     Arc::new(FlowBus::new(capacity))
 }
 
+/// Create a new shared config flow bus.
+pub fn shared_config_flow_bus(capacity: usize) -> SharedConfigFlowBus {
+    Arc::new(FlowBus::new(capacity))
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -1325,6 +1420,7 @@ mod tests {
                 ops: Arc::from(vec![1u8, 2, 3]),
                 source: OpSource::Local,
                 seq_num: 0,
+                generation: 0,
             });
         }
 
@@ -1368,6 +1464,7 @@ mod tests {
             ops: Arc::from(vec![1u8]),
             source: OpSource::Local,
             seq_num: 0,
+            generation: 0,
         });
         bus.publish(BlockFlow::Deleted {
             context_id: ctx,
@@ -1422,6 +1519,7 @@ mod tests {
                 ops: Arc::from(vec![0u8]),
                 source: OpSource::Local,
                 seq_num: 0,
+                generation: 0,
             });
         }
 
@@ -1819,6 +1917,7 @@ mod tests {
                 ops: Arc::from(Vec::<u8>::new()),
                 source: OpSource::Local,
                 seq_num: 0,
+                generation: 0,
             },
             BlockFlow::Deleted {
                 context_id: ctx,
@@ -1874,6 +1973,30 @@ mod tests {
                 context_id: ctx,
                 beat_ref: kaijutsu_audio::BeatRef::new(0.0, 2.0),
             },
+            BlockFlow::InboxPosted {
+                context_id: ctx,
+                item: InboxItem {
+                    id: 1,
+                    recipient: PrincipalId::new(),
+                    kind: kaijutsu_types::InboxKind::Mention,
+                    context_id: Some(ctx),
+                    sender: None,
+                    summary: "ping".into(),
+                    created_at: 0,
+                    acked_at: None,
+                },
+            },
+            BlockFlow::ModelChanged {
+                context_id: ctx,
+                provider: "anthropic".into(),
+                model: "claude-haiku".into(),
+                by: PrincipalId::new(),
+            },
+            BlockFlow::LockChanged {
+                context_id: ctx,
+                block_id: id,
+                lock: None,
+            },
         ];
 
         // Exhaustiveness gate: a new variant without an arm here breaks
@@ -1892,7 +2015,10 @@ mod tests {
                 | BlockFlow::MetadataChanged { .. }
                 | BlockFlow::ContextSwitched { .. }
                 | BlockFlow::RenderCue { .. }
-                | BlockFlow::BeatSync { .. } => {}
+                | BlockFlow::BeatSync { .. }
+                | BlockFlow::InboxPosted { .. }
+                | BlockFlow::ModelChanged { .. }
+                | BlockFlow::LockChanged { .. } => {}
             }
         }
 
@@ -0,0 +1,418 @@
+//! Hook policy — allow/deny/ask decisions for local agents' tool calls.
+//!
+//! Configured per kernel in the CRDT-owned `/etc/config/hooks.toml` (see
+//! `assets/defaults/hooks.toml` for the format). kaijutsu-mcp's hook listener
+//! evaluates every `tool.before` (Claude Code's `PreToolUse`) against it and
+//! answers with the decision, so one kernel is the guardrail for every agent
+//! attached to it. Like `webhooks.toml` the file is re-read per event: `kj
+//! config edit hooks.toml` takes effect on the next tool call.
+//!
+//! Rules are checked in order and the first match decides. A rule matches
+//! when every condition it sets holds — any listed tool glob, any listed path
+//! glob, any listed command regex, and the context's consent mode. A rule
+//! with no conditions matches everything. No match falls back to `default`.
+//!
+//! Paths are normalized lexically before matching (`.`, `..` and repeated
+//! `/` resolved, no filesystem access), so `/tmp/../etc/passwd` is held to
+//! the rules for `/etc/passwd`. Symlinks are not followed.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use kaijutsu_types::ConsentMode;
+
+/// Config file name under `/etc/config`.
+pub const HOOK_POLICY_CONFIG_FILE: &str = "hooks.toml";
+
+/// Tool-input keys that carry the file a tool acts on (`Read`/`Edit`/`Write`
+/// use `file_path`, `Glob`/`Grep`/`LS` use `path`, `NotebookEdit` uses
+/// `notebook_path`).
+const PATH_INPUT_KEYS: &[&str] = &["file_path", "path", "notebook_path"];
+
+/// Tool-input key that carries a shell command (`Bash`).
+const COMMAND_INPUT_KEY: &str = "command";
+
+/// What to tell the agent about a tool call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyDecision {
+    /// Run it.
+    #[default]
+    Allow,
+    /// Refuse it; the reason goes back to the agent.
+    Deny,
+    /// Defer to the agent's own permission prompt.
+    Ask,
+}
+
+impl PolicyDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Ask => "ask",
+        }
+    }
+}
+
+/// One `[[rules]]` entry.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRule {
+    /// Label used in the default reason and in logs.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Tool-name globs (`Bash`, `mcp__*`); empty means any tool.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Globs over the file a tool acts on; empty means any (or no) file.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Regexes searched in a `Bash` command; empty means any (or no) command.
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Only match in contexts with this consent mode.
+    #[serde(default)]
+    pub consent: Option<ConsentMode>,
+    pub decision: PolicyDecision,
+    /// Explanation sent back with `deny`/`ask`.
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(skip)]
+    compiled: Compiled,
+}
+
+/// A rule's globs and regexes, compiled once at parse time.
+#[derive(Clone, Debug, Default)]
+struct Compiled {
+    tools: Vec<Regex>,
+    paths: Vec<Regex>,
+    commands: Vec<Regex>,
+}
+
+impl PolicyRule {
+    fn compile(&mut self, index: usize) -> Result<(), String> {
+        let at = |what: &str, pattern: &str, e: regex::Error| {
+            format!("rule {}: invalid {what} '{pattern}': {e}", index + 1)
+        };
+        let tools = self
+            .tools
+            .iter()
+            .map(|g| glob_regex(g).map_err(|e| at("tool glob", g, e)))
+            .collect::<Result<_, _>>()?;
+        let paths = self
+            .paths
+            .iter()
+            .map(|g| glob_regex(g).map_err(|e| at("path glob", g, e)))
+            .collect::<Result<_, _>>()?;
+        let commands = self
+            .commands
+            .iter()
+            .map(|c| Regex::new(c).map_err(|e| at("command pattern", c, e)))
+            .collect::<Result<_, _>>()?;
+        self.compiled = Compiled {
+            tools,
+            paths,
+            commands,
+        };
+        Ok(())
+    }
+
+    fn matches(&self, tool: &str, input: &serde_json::Value, consent: Option<ConsentMode>) -> bool {
+        if self.consent.is_some() && self.consent != consent {
+            return false;
+        }
+        if !self.compiled.tools.is_empty()
+            && !self.compiled.tools.iter().any(|re| re.is_match(tool))
+        {
+            return false;
+        }
+        if !self.compiled.paths.is_empty() {
+            let paths: Vec<String> = PATH_INPUT_KEYS
+                .iter()
+                .filter_map(|key| input.get(*key)?.as_str())
+                .map(normalize_path)
+                .collect();
+            if !paths
+                .iter()
+                .any(|p| self.compiled.paths.iter().any(|re| re.is_match(p)))
+            {
+                return false;
+            }
+        }
+        if !self.compiled.commands.is_empty() {
+            let Some(command) = input.get(COMMAND_INPUT_KEY).and_then(|c| c.as_str()) else {
+                return false;
+            };
+            if !self.compiled.commands.iter().any(|re| re.is_match(command)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Parsed `hooks.toml`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HookPolicy {
+    /// Decision when no rule matches.
+    #[serde(default)]
+    pub default: PolicyDecision,
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+/// The outcome of [`HookPolicy::evaluate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyVerdict {
+    pub decision: PolicyDecision,
+    /// The matching rule's reason (or a generated one naming the rule);
+    /// `None` for the fallback default.
+    pub reason: Option<String>,
+}
+
+impl HookPolicy {
+    /// Parse and validate. Also the `kj config set` write-time check, so a bad
+    /// glob or regex is rejected at the prompt instead of never matching.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut policy: Self = toml::from_str(content).map_err(|e| format!("invalid TOML: {e}"))?;
+        for (index, rule) in policy.rules.iter_mut().enumerate() {
+            rule.compile(index)?;
+        }
+        Ok(policy)
+    }
+
+    /// Whether any rule conditions on consent mode — callers skip looking the
+    /// context's mode up when nothing would read it.
+    pub fn uses_consent(&self) -> bool {
+        self.rules.iter().any(|r| r.consent.is_some())
+    }
+
+    /// Decide a call to `tool` with `input`, in a context whose consent mode
+    /// is `consent` (`None` when unknown — consent-conditioned rules then
+    /// never match).
+    pub fn evaluate(
+        &self,
+        tool: &str,
+        input: &serde_json::Value,
+        consent: Option<ConsentMode>,
+    ) -> PolicyVerdict {
+        let Some((index, rule)) = self
+            .rules
+            .iter()
+            .enumerate()
+            .find(|(_, r)| r.matches(tool, input, consent))
+        else {
+            return PolicyVerdict {
+                decision: self.default,
+                reason: None,
+            };
+        };
+        let reason = rule.reason.clone().unwrap_or_else(|| match &rule.name {
+            Some(name) => format!("{tool} matched hook policy rule '{name}'"),
+            None => format!("{tool} matched hook policy rule {}", index + 1),
+        });
+        PolicyVerdict {
+            decision: rule.decision,
+            reason: Some(reason),
+        }
+    }
+}
+
+/// `path` with `.` segments and repeated `/` dropped and each `..` taking
+/// the segment before it — purely textual. `..` above the root stays at the
+/// root; above the start of a relative path it is kept.
+fn normalize_path(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => match segments.last() {
+                Some(&last) if last != ".." => {
+                    segments.pop();
+                }
+                _ if absolute => {}
+                _ => segments.push(".."),
+            },
+            segment => segments.push(segment),
+        }
+    }
+    let joined = segments.join("/");
+    if absolute {
+        format!("/{joined}")
+    } else {
+        joined
+    }
+}
+
+/// Compile a shell-style glob to an anchored regex: `**` spans directories
+/// (`**/` also matches none), `*` and `?` stay within one path segment.
+pub(crate) fn glob_regex(glob: &str) -> Result<Regex, regex::Error> {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const POLICY: &str = r#"
+        [[rules]]
+        name = "secrets"
+        paths = ["**/.env", "**/.ssh/**"]
+        decision = "deny"
+        reason = "secrets are off limits"
+
+        [[rules]]
+        tools = ["Bash"]
+        commands = ['^\s*rm\s+-rf\b', 'git\s+push\s+.*--force']
+        decision = "deny"
+
+        [[rules]]
+        tools = ["Bash"]
+        consent = "collaborative"
+        decision = "ask"
+
+        [[rules]]
+        tools = ["mcp__*"]
+        decision = "ask"
+        reason = "external MCP tool"
+    "#;
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let policy = HookPolicy::parse(POLICY).unwrap();
+        let collab = Some(ConsentMode::Collaborative);
+        let auto = Some(ConsentMode::Autonomous);
+
+        let verdict = policy.evaluate("Read", &json!({"file_path": "/home/me/app/.env"}), auto);
+        assert_eq!(verdict.decision, PolicyDecision::Deny);
+        assert_eq!(verdict.reason.as_deref(), Some("secrets are off limits"));
+        let verdict = policy.evaluate("Edit", &json!({"file_path": "/home/me/.ssh/config"}), auto);
+        assert_eq!(verdict.decision, PolicyDecision::Deny);
+        let verdict = policy.evaluate("Read", &json!({"file_path": "/home/me/app/env.rs"}), auto);
+        assert_eq!(verdict.decision, PolicyDecision::Allow);
+        assert_eq!(verdict.reason, None);
+
+        let verdict = policy.evaluate("Bash", &json!({"command": "rm -rf target"}), auto);
+        assert_eq!(verdict.decision, PolicyDecision::Deny);
+        assert_eq!(
+            verdict.reason.as_deref(),
+            Some("Bash matched hook policy rule 2")
+        );
+        let ls = json!({"command": "ls -la"});
+        assert_eq!(
+            policy.evaluate("Bash", &ls, auto).decision,
+            PolicyDecision::Allow
+        );
+        assert_eq!(
+            policy.evaluate("Bash", &ls, collab).decision,
+            PolicyDecision::Ask
+        );
+        assert_eq!(
+            policy.evaluate("Bash", &ls, None).decision,
+            PolicyDecision::Allow
+        );
+
+        let verdict = policy.evaluate("mcp__github__create_issue", &json!({}), auto);
+        assert_eq!(verdict.decision, PolicyDecision::Ask);
+        assert!(policy.uses_consent());
+    }
+
+    #[test]
+    fn default_applies_when_nothing_matches() {
+        let policy = HookPolicy::parse(
+            "default = \"deny\"\n[[rules]]\ntools = [\"Read\", \"Grep\"]\ndecision = \"allow\"",
+        )
+        .unwrap();
+        assert_eq!(
+            policy.evaluate("Grep", &json!({}), None).decision,
+            PolicyDecision::Allow
+        );
+        assert_eq!(
+            policy.evaluate("Write", &json!({}), None).decision,
+            PolicyDecision::Deny
+        );
+        assert!(!policy.uses_consent());
+    }
+
+    #[test]
+    fn rejects_typos_and_bad_patterns() {
+        assert!(HookPolicy::parse("[[rules]]\ndecision = \"block\"").is_err());
+        assert!(HookPolicy::parse("[[rules]]\ndecision = \"deny\"\ntool = [\"Bash\"]").is_err());
+        assert!(HookPolicy::parse("[[rules]]\ndecision = \"deny\"\ncommands = [\"(\"]").is_err());
+        assert!(HookPolicy::parse("[[rules]]\ndecision = \"deny\"\nconsent = \"yolo\"").is_err());
+        let empty = HookPolicy::parse("").unwrap();
+        assert_eq!(
+            empty.evaluate("Bash", &json!({}), None).decision,
+            PolicyDecision::Allow
+        );
+    }
+
+    #[test]
+    fn seeded_default_has_no_rules() {
+        let policy = HookPolicy::parse(crate::config_seed::DEFAULT_HOOKS).unwrap();
+        assert!(policy.rules.is_empty());
+        assert_eq!(policy.default, PolicyDecision::Allow);
+    }
+
+    #[test]
+    fn globs_respect_path_segments() {
+        let re = glob_regex("src/*.rs").unwrap();
+        assert!(re.is_match("src/lib.rs"));
+        assert!(!re.is_match("src/kj/mod.rs"));
+        let re = glob_regex("**/target/**").unwrap();
+        assert!(re.is_match("target/debug/x"));
+        assert!(re.is_match("/repo/target/debug/x"));
+        assert!(glob_regex("file?.txt").unwrap().is_match("file1.txt"));
+        assert!(!glob_regex("a.b").unwrap().is_match("axb"));
+    }
+
+    #[test]
+    fn paths_are_normalized_before_matching() {
+        let policy =
+            HookPolicy::parse("[[rules]]\npaths = [\"/etc/**\"]\ndecision = \"deny\"").unwrap();
+        for path in [
+            "/tmp/../etc/passwd",
+            "/etc/./passwd",
+            "//etc//passwd",
+            "/../../etc/passwd",
+        ] {
+            assert_eq!(
+                policy
+                    .evaluate("Read", &json!({ "file_path": path }), None)
+                    .decision,
+                PolicyDecision::Deny,
+                "{path}"
+            );
+        }
+        assert_eq!(
+            policy
+                .evaluate("Read", &json!({ "file_path": "/etc/../tmp/x" }), None)
+                .decision,
+            PolicyDecision::Allow
+        );
+        assert_eq!(normalize_path("a/../../b/./c/"), "../b/c");
+    }
+}
@@ -0,0 +1,95 @@
+//! Posting to per-principal notification inboxes.
+//!
+//! Storage lives in `KernelDb` (`inbox` table); this module is the producer
+//! side: write the row, then publish `BlockFlow::InboxPosted` so connected
+//! clients of the recipient see it immediately. A failed publish is harmless —
+//! the row is the source of truth and `listInbox` will surface it.
+//!
+//! Producers today:
+//! - `kj drift push` staging a drift → `ConsentRequest` to the target's creator
+//! - drift delivery (`flush`, `merge`) → `DriftArrival` to the target's creator
+//! - `@name` in a user block → `Mention` (see [`crate::mention`])
+
+use kaijutsu_types::{ContextId, InboxItem, InboxKind, PrincipalId};
+use parking_lot::Mutex;
+
+use crate::flows::{BlockFlow, SharedBlockFlowBus};
+use crate::kernel_db::{KernelDb, KernelDbResult};
+
+/// Longest summary stored for an item; longer text is cut at a char boundary.
+const SUMMARY_MAX_CHARS: usize = 160;
+
+/// Store an item for `recipient` and push it to their live subscriptions.
+pub fn post(
+    db: &Mutex<KernelDb>,
+    flows: Option<&SharedBlockFlowBus>,
+    recipient: PrincipalId,
+    kind: InboxKind,
+    context_id: Option<ContextId>,
+    sender: Option<PrincipalId>,
+    summary: &str,
+) -> KernelDbResult<InboxItem> {
+    let summary = truncate(summary);
+    let item = db
+        .lock()
+        .post_inbox(recipient, kind, context_id, sender, &summary)?;
+    if let Some(bus) = flows {
+        bus.publish(BlockFlow::InboxPosted {
+            context_id: context_id.unwrap_or_else(ContextId::nil),
+            item: item.clone(),
+        });
+    }
+    Ok(item)
+}
+
+/// Notify whoever created `context_id`. Skipped when the creator is the
+/// sender (no self-pings) or the system principal (nobody is sitting there).
+/// Failures are logged, never propagated — a notification must not fail the
+/// operation that caused it.
+pub fn notify_context_owner(
+    db: &Mutex<KernelDb>,
+    flows: Option<&SharedBlockFlowBus>,
+    context_id: ContextId,
+    kind: InboxKind,
+    sender: PrincipalId,
+    summary: &str,
+) -> Option<InboxItem> {
+    let owner = match db.lock().get_context(context_id) {
+        Ok(Some(row)) => row.created_by,
+        Ok(None) => return None,
+        Err(e) => {
+            tracing::warn!("inbox: context {} lookup failed: {e}", context_id.short());
+            return None;
+        }
+    };
+    if owner == sender || owner == PrincipalId::system() {
+        return None;
+    }
+    match post(db, flows, owner, kind, Some(context_id), Some(sender), summary) {
+        Ok(item) => Some(item),
+        Err(e) => {
+            tracing::warn!("inbox: failed to post {kind} for {}: {e}", context_id.short());
+            None
+        }
+    }
+}
+
+fn truncate(summary: &str) -> String {
+    let line = summary.lines().next().unwrap_or_default();
+    match line.char_indices().nth(SUMMARY_MAX_CHARS) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries_are_one_line_and_capped() {
+        assert_eq!(truncate("first\nsecond"), "first");
+        let long = "x".repeat(SUMMARY_MAX_CHARS + 10);
+        assert_eq!(truncate(&long).chars().count(), SUMMARY_MAX_CHARS + 1);
+    }
+}
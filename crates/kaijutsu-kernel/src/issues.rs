@@ -0,0 +1,774 @@
+//! Issue export — file a block subtree as an issue (or a comment on one) in
+//! GitHub or GitLab, so agent findings land in the tracker a team already
+//! watches instead of staying inside a context.
+//!
+//! Trackers are configured per kernel in the CRDT-owned
+//! `/etc/config/issues.toml` (see `assets/defaults/issues.toml` for the
+//! format). The file is re-read on every export, so `kj config edit
+//! issues.toml` takes effect immediately. Credentials are named by
+//! environment variable (`token_env`, which must start with
+//! [`TOKEN_ENV_PREFIX`]) so the secret itself need not live in the CRDT; an
+//! inline `token` is accepted for throwaway setups.
+//!
+//! [`IssueExporter::export`] renders the subtree as Markdown, POSTs it, and
+//! records the created URL back into the context as a
+//! [`NotificationKind::IssueCreated`] block under the subtree's root. The
+//! `issue_create` tool on `builtin.issues` is the caller-facing surface.
+
+use std::time::Duration;
+
+use kaijutsu_types::{
+    BlockFilter, BlockId, BlockKind, BlockSnapshot, NotificationKind, NotificationPayload,
+    PrincipalId,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::block_store::SharedBlockStore;
+use crate::config_doc::{config_context_id, read_content};
+
+/// Config file name under `/etc/config`.
+pub const ISSUES_CONFIG_FILE: &str = "issues.toml";
+
+/// Notification `instance` for the blocks that record created issues.
+pub const ISSUES_INSTANCE: &str = "builtin.issues";
+
+/// Longest rendered body. GitHub rejects bodies over 65536 characters;
+/// GitLab's limit is higher, so one cap serves both.
+pub const MAX_BODY_CHARS: usize = 60_000;
+
+/// Required prefix of a `token_env` name. The token is sent to `api_url`,
+/// which a config writer chooses, so without it any variable in the
+/// server's environment could be sent anywhere.
+pub const TOKEN_ENV_PREFIX: &str = "KAIJUTSU_ISSUES_";
+
+/// Longest title derived from block content when the caller names none.
+const TITLE_MAX_CHARS: usize = 120;
+
+/// Per-request timeout, connect through response body.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const USER_AGENT: &str = concat!("kaijutsu/", env!("CARGO_PKG_VERSION"));
+
+/// Which tracker API a [`Tracker`] speaks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackerKind {
+    Github,
+    Gitlab,
+}
+
+impl TrackerKind {
+    fn default_api_url(&self) -> &'static str {
+        match self {
+            Self::Github => "https://api.github.com",
+            Self::Gitlab => "https://gitlab.com/api/v4",
+        }
+    }
+}
+
+/// One `[[trackers]]` entry.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tracker {
+    /// Name callers pick the tracker by.
+    pub name: String,
+    pub kind: TrackerKind,
+    /// GitHub `owner/repo`; GitLab project path (`group/project`) or id.
+    pub repo: String,
+    /// API root; defaults to the public github.com / gitlab.com API.
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// Environment variable holding the API token; must start with
+    /// [`TOKEN_ENV_PREFIX`].
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// The API token itself, stored in the config CRDT.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Labels applied to every new issue.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Tracker {
+    fn api_url(&self) -> &str {
+        self.api_url
+            .as_deref()
+            .unwrap_or_else(|| self.kind.default_api_url())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let name = &self.name;
+        if name.trim().is_empty() {
+            return Err("tracker name must not be empty".to_string());
+        }
+        match self.kind {
+            TrackerKind::Github => {
+                let parts: Vec<&str> = self.repo.split('/').collect();
+                if parts.len() != 2 || parts.iter().any(|p| p.is_empty()) {
+                    return Err(format!(
+                        "tracker '{name}': github repo '{}' must be owner/repo",
+                        self.repo
+                    ));
+                }
+            }
+            TrackerKind::Gitlab => {
+                if self.repo.is_empty() || self.repo.starts_with('/') || self.repo.ends_with('/') {
+                    return Err(format!(
+                        "tracker '{name}': gitlab repo '{}' must be a project path or id",
+                        self.repo
+                    ));
+                }
+            }
+        }
+        if let Some(url) = &self.api_url
+            && reqwest::Url::parse(url)
+                .map(|u| !matches!(u.scheme(), "http" | "https"))
+                .unwrap_or(true)
+        {
+            return Err(format!(
+                "tracker '{name}': api_url '{url}' must be an http:// or https:// URL"
+            ));
+        }
+        match (&self.token_env, &self.token) {
+            (None, None) => Err(format!("tracker '{name}': set token_env or token")),
+            (Some(_), Some(_)) => Err(format!(
+                "tracker '{name}': set token_env or token, not both"
+            )),
+            (Some(var), None) if !var.starts_with(TOKEN_ENV_PREFIX) => Err(format!(
+                "tracker '{name}': token_env '{var}' must start with {TOKEN_ENV_PREFIX}"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The API token, read from the environment at call time.
+    fn resolve_token(&self) -> Result<String, IssueError> {
+        if let Some(token) = &self.token {
+            return Ok(token.clone());
+        }
+        let var = self.token_env.as_deref().unwrap_or_default();
+        match std::env::var(var) {
+            Ok(token) if !token.is_empty() => Ok(token),
+            _ => Err(IssueError::Config(format!(
+                "tracker '{}': environment variable {var} is not set",
+                self.name
+            ))),
+        }
+    }
+
+    /// `api_url` extended by `segments`, each percent-encoded — so a GitLab
+    /// project path becomes the single `group%2Fproject` segment its API
+    /// expects.
+    fn endpoint(&self, segments: &[&str]) -> Result<reqwest::Url, IssueError> {
+        let mut url = reqwest::Url::parse(self.api_url())
+            .map_err(|e| IssueError::Config(format!("tracker '{}': {e}", self.name)))?;
+        url.path_segments_mut()
+            .map_err(|()| {
+                IssueError::Config(format!("tracker '{}': api_url cannot be a base", self.name))
+            })?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// Where `target` is POSTed.
+    pub fn create_url(&self, target: &IssueTarget) -> Result<reqwest::Url, IssueError> {
+        let number = match target {
+            IssueTarget::Comment { number } => Some(number.to_string()),
+            IssueTarget::New { .. } => None,
+        };
+        let mut segments: Vec<&str> = match self.kind {
+            TrackerKind::Github => {
+                let mut s = vec!["repos"];
+                s.extend(self.repo.split('/'));
+                s.push("issues");
+                s
+            }
+            TrackerKind::Gitlab => vec!["projects", self.repo.as_str(), "issues"],
+        };
+        if let Some(number) = &number {
+            segments.push(number);
+            segments.push(match self.kind {
+                TrackerKind::Github => "comments",
+                TrackerKind::Gitlab => "notes",
+            });
+        }
+        self.endpoint(&segments)
+    }
+
+    /// The JSON request body for `target`. The tracker's configured labels
+    /// come first, then the caller's, without duplicates.
+    pub fn create_body(&self, target: &IssueTarget, body: &str) -> serde_json::Value {
+        match target {
+            IssueTarget::Comment { .. } => serde_json::json!({ "body": body }),
+            IssueTarget::New { title, labels } => {
+                let mut all = self.labels.clone();
+                for label in labels {
+                    if !all.contains(label) {
+                        all.push(label.clone());
+                    }
+                }
+                match self.kind {
+                    TrackerKind::Github => {
+                        serde_json::json!({ "title": title, "body": body, "labels": all })
+                    }
+                    TrackerKind::Gitlab => serde_json::json!({
+                        "title": title,
+                        "description": body,
+                        "labels": all.join(","),
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Pull the browser URL and issue number out of a create response.
+    /// `None` for a GitLab note, whose response carries only the note id
+    /// (see [`IssueExporter::export`]).
+    pub fn created_url(
+        &self,
+        target: &IssueTarget,
+        response: &serde_json::Value,
+    ) -> Option<(String, Option<u64>)> {
+        let url_key = match (self.kind, target) {
+            (TrackerKind::Github, _) => "html_url",
+            (TrackerKind::Gitlab, IssueTarget::New { .. }) => "web_url",
+            (TrackerKind::Gitlab, IssueTarget::Comment { .. }) => return None,
+        };
+        let number = match (self.kind, target) {
+            (_, IssueTarget::Comment { number }) => Some(*number),
+            (TrackerKind::Github, IssueTarget::New { .. }) => response["number"].as_u64(),
+            (TrackerKind::Gitlab, IssueTarget::New { .. }) => response["iid"].as_u64(),
+        };
+        Some((response[url_key].as_str()?.to_string(), number))
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder, token: &str) -> reqwest::RequestBuilder {
+        let request = request.header(reqwest::header::USER_AGENT, USER_AGENT);
+        match self.kind {
+            TrackerKind::Github => request
+                .bearer_auth(token)
+                .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+                .header("X-GitHub-Api-Version", "2022-11-28"),
+            TrackerKind::Gitlab => request.header("PRIVATE-TOKEN", token),
+        }
+    }
+}
+
+/// Parsed `issues.toml`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IssueConfig {
+    #[serde(default)]
+    pub trackers: Vec<Tracker>,
+}
+
+impl IssueConfig {
+    /// Parse and validate. Also the `kj config set` write-time check, so a
+    /// malformed repo or missing credential is caught at the prompt rather
+    /// than on the first export.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| format!("invalid TOML: {e}"))?;
+        for (i, tracker) in config.trackers.iter().enumerate() {
+            tracker.validate()?;
+            if config.trackers[..i].iter().any(|t| t.name == tracker.name) {
+                return Err(format!("duplicate tracker name '{}'", tracker.name));
+            }
+        }
+        Ok(config)
+    }
+
+    /// The tracker to export to: the one named, or the only enabled one.
+    pub fn tracker(&self, name: Option<&str>) -> Result<&Tracker, IssueError> {
+        if let Some(name) = name {
+            return match self.trackers.iter().find(|t| t.name == name) {
+                Some(t) if t.enabled => Ok(t),
+                Some(_) => Err(IssueError::Config(format!("tracker '{name}' is disabled"))),
+                None => Err(IssueError::Config(format!("no tracker named '{name}'"))),
+            };
+        }
+        let enabled: Vec<&Tracker> = self.trackers.iter().filter(|t| t.enabled).collect();
+        match enabled.as_slice() {
+            [only] => Ok(only),
+            [] => Err(IssueError::Config(format!(
+                "no issue trackers configured; add one to {}",
+                kaijutsu_types::paths::config_path(ISSUES_CONFIG_FILE)
+            ))),
+            many => Err(IssueError::Config(format!(
+                "several trackers configured ({}); name one",
+                many.iter()
+                    .map(|t| t.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+        }
+    }
+}
+
+/// What to file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IssueTarget {
+    /// Open a new issue.
+    New { title: String, labels: Vec<String> },
+    /// Comment on an existing issue (GitLab: the issue's iid).
+    Comment { number: u64 },
+}
+
+/// An [`IssueExporter::export`] call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportRequest {
+    /// Root of the exported subtree.
+    pub root: BlockId,
+    /// Tracker name; `None` picks the only enabled tracker.
+    pub tracker: Option<String>,
+    /// Title for a new issue; `None` derives one from the subtree.
+    pub title: Option<String>,
+    /// Comment on this issue instead of opening one.
+    pub issue: Option<u64>,
+    /// Labels added to the tracker's own (new issues only).
+    pub labels: Vec<String>,
+    /// Levels below the root to include; `None` = the whole subtree.
+    pub max_depth: Option<u32>,
+}
+
+/// A filed issue or comment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CreatedIssue {
+    pub tracker: String,
+    pub url: String,
+    /// Issue number (GitLab: iid).
+    pub number: Option<u64>,
+    /// The `IssueCreated` notification block recording `url`.
+    pub block_id: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum IssueError {
+    #[error("{0}")]
+    Config(String),
+    #[error("{0}")]
+    Block(String),
+    #[error("tracker '{tracker}': request failed: {message}")]
+    Http { tracker: String, message: String },
+    #[error("tracker '{tracker}' rejected the request ({status}): {message}")]
+    Rejected {
+        tracker: String,
+        status: u16,
+        message: String,
+    },
+}
+
+/// The kernel's issue exporter.
+pub struct IssueExporter {
+    blocks: SharedBlockStore,
+    client: reqwest::Client,
+}
+
+impl IssueExporter {
+    pub fn new(blocks: SharedBlockStore) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { blocks, client }
+    }
+
+    /// The current `issues.toml`. Absent (a kernel seeded before issue
+    /// export existed) is an empty config; unparseable is an error, since
+    /// the caller is waiting on the answer.
+    pub fn config(&self) -> Result<IssueConfig, IssueError> {
+        let path = kaijutsu_types::paths::config_path(ISSUES_CONFIG_FILE);
+        match read_content(&self.blocks, config_context_id(&path)) {
+            Some(content) => {
+                IssueConfig::parse(&content).map_err(|e| IssueError::Config(format!("{path}: {e}")))
+            }
+            None => Ok(IssueConfig::default()),
+        }
+    }
+
+    /// File the subtree under `request.root` and record the result as an
+    /// `IssueCreated` notification block under the root, authored by `by`.
+    pub async fn export(
+        &self,
+        request: &ExportRequest,
+        by: PrincipalId,
+    ) -> Result<CreatedIssue, IssueError> {
+        let config = self.config()?;
+        let tracker = config.tracker(request.tracker.as_deref())?;
+        let context_id = request.root.context_id;
+
+        let filter = BlockFilter {
+            parent_id: Some(request.root),
+            max_depth: request.max_depth.unwrap_or(0),
+            ..BlockFilter::default()
+        };
+        let blocks = self
+            .blocks
+            .query_blocks(context_id, &filter)
+            .map_err(|e| IssueError::Block(e.to_string()))?;
+        if blocks.is_empty() {
+            return Err(IssueError::Block(format!(
+                "block not found: {}",
+                request.root.to_key()
+            )));
+        }
+
+        let body = render_subtree(&blocks, &request.root);
+        let target = match request.issue {
+            Some(number) => IssueTarget::Comment { number },
+            None => IssueTarget::New {
+                title: request
+                    .title
+                    .clone()
+                    .filter(|t| !t.trim().is_empty())
+                    .unwrap_or_else(|| default_title(&blocks)),
+                labels: request.labels.clone(),
+            },
+        };
+
+        let token = tracker.resolve_token()?;
+        let url = tracker.create_url(&target)?;
+        let response = self
+            .send(
+                tracker,
+                tracker
+                    .authorize(self.client.post(url), &token)
+                    .json(&tracker.create_body(&target, &body)),
+            )
+            .await?;
+
+        let (url, number) = match tracker.created_url(&target, &response) {
+            Some(created) => created,
+            // A GitLab note response has no URL: fetch the issue's and
+            // anchor it at the note.
+            None => {
+                let IssueTarget::Comment { number } = target else {
+                    return Err(missing_url(tracker));
+                };
+                let note = response["id"]
+                    .as_u64()
+                    .ok_or_else(|| missing_url(tracker))?;
+                let issue_url = tracker.endpoint(&[
+                    "projects",
+                    tracker.repo.as_str(),
+                    "issues",
+                    &number.to_string(),
+                ])?;
+                let issue = self
+                    .send(
+                        tracker,
+                        tracker.authorize(self.client.get(issue_url), &token),
+                    )
+                    .await?;
+                let web = issue["web_url"]
+                    .as_str()
+                    .ok_or_else(|| missing_url(tracker))?;
+                (format!("{web}#note_{note}"), Some(number))
+            }
+        };
+
+        let payload = NotificationPayload {
+            instance: ISSUES_INSTANCE.to_string(),
+            kind: NotificationKind::IssueCreated,
+            level: None,
+            tools: Vec::new(),
+            count: None,
+            detail: Some(url.clone()),
+        };
+        let block_id = self
+            .blocks
+            .insert_notification_block_as(
+                context_id,
+                Some(&request.root),
+                &payload,
+                payload.summary_line(),
+                Some(by),
+            )
+            .map_err(|e| IssueError::Block(e.to_string()))?;
+
+        Ok(CreatedIssue {
+            tracker: tracker.name.clone(),
+            url,
+            number,
+            block_id: block_id.to_key(),
+        })
+    }
+
+    /// Send a request and return its JSON body, mapping non-2xx responses to
+    /// [`IssueError::Rejected`] with the API's own message.
+    async fn send(
+        &self,
+        tracker: &Tracker,
+        request: reqwest::RequestBuilder,
+    ) -> Result<serde_json::Value, IssueError> {
+        let http = |e: reqwest::Error| IssueError::Http {
+            tracker: tracker.name.clone(),
+            message: e.to_string(),
+        };
+        let response = request.send().await.map_err(http)?;
+        let status = response.status();
+        let text = response.text().await.map_err(http)?;
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+        if !status.is_success() {
+            let message = json["message"]
+                .as_str()
+                .or_else(|| json["error"].as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| text.chars().take(200).collect());
+            return Err(IssueError::Rejected {
+                tracker: tracker.name.clone(),
+                status: status.as_u16(),
+                message,
+            });
+        }
+        Ok(json)
+    }
+}
+
+fn missing_url(tracker: &Tracker) -> IssueError {
+    IssueError::Http {
+        tracker: tracker.name.clone(),
+        message: "response carried no URL".to_string(),
+    }
+}
+
+/// Render a subtree (DAG order, root first) as a Markdown issue body.
+/// Thinking and notification blocks are left out; tool calls and results
+/// are fenced; a footer points back at the source block.
+pub fn render_subtree(blocks: &[BlockSnapshot], root: &BlockId) -> String {
+    use kaijutsu_types::language::markdown_fence;
+    use kaijutsu_types::reflow::reflows_language;
+
+    let mut out = String::new();
+    for block in blocks {
+        match block.kind {
+            BlockKind::Thinking | BlockKind::Notification => continue,
+            BlockKind::ToolCall => {
+                let name = block.tool_name.as_deref().unwrap_or("tool");
+                let input = if block.content.is_empty() {
+                    block.tool_input.as_deref().unwrap_or_default()
+                } else {
+                    block.content.as_str()
+                };
+                out.push_str(&format!("**{} → `{name}`**\n\n", block.role.as_str()));
+                out.push_str(&markdown_fence(input, Some("json")));
+            }
+            BlockKind::ToolResult => {
+                let head = if block.is_error {
+                    "result (error)"
+                } else {
+                    "result"
+                };
+                out.push_str(&format!("**{head}**\n\n"));
+                out.push_str(&markdown_fence(&block.content, block.language.as_deref()));
+            }
+            _ => {
+                out.push_str(&format!("**{}**\n\n", block.role.as_str()));
+                if reflows_language(block.language.as_deref()) {
+                    out.push_str(block.content.trim_end());
+                    out.push('\n');
+                } else {
+                    out.push_str(&markdown_fence(&block.content, block.language.as_deref()));
+                }
+            }
+        }
+        out.push('\n');
+    }
+
+    if let Some((cut, _)) = out.char_indices().nth(MAX_BODY_CHARS) {
+        let dropped = out[cut..].chars().count();
+        out.truncate(cut);
+        out.push_str(&format!(
+            "\n\n_… {dropped} more characters not exported._\n\n"
+        ));
+    }
+    out.push_str(&format!(
+        "---\n_Exported from kaijutsu context `{}`, block `{}`._\n",
+        root.context_id.short(),
+        root.to_key()
+    ));
+    out
+}
+
+/// First line of the subtree's first text block, trimmed of Markdown
+/// heading marks and cut to [`TITLE_MAX_CHARS`].
+fn default_title(blocks: &[BlockSnapshot]) -> String {
+    let line = blocks
+        .iter()
+        .filter(|b| b.kind == BlockKind::Text)
+        .find_map(|b| b.content.lines().map(str::trim).find(|l| !l.is_empty()))
+        .map(|l| l.trim_start_matches('#').trim())
+        .unwrap_or("Findings from kaijutsu");
+    match line.char_indices().nth(TITLE_MAX_CHARS) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_types::{ContextId, Role};
+
+    fn tracker(kind: TrackerKind, repo: &str) -> Tracker {
+        Tracker {
+            name: "t".into(),
+            kind,
+            repo: repo.into(),
+            api_url: None,
+            token_env: Some("KAIJUTSU_ISSUES_TOKEN".into()),
+            token: None,
+            labels: vec!["agent".into()],
+            enabled: true,
+        }
+    }
+
+    fn new_issue() -> IssueTarget {
+        IssueTarget::New {
+            title: "Flaky test".into(),
+            labels: vec!["bug".into(), "agent".into()],
+        }
+    }
+
+    #[test]
+    fn parses_trackers_and_picks_one() {
+        let config = IssueConfig::parse(
+            r#"
+            [[trackers]]
+            name = "gh"
+            kind = "github"
+            repo = "tobert/kaijutsu"
+            token_env = "KAIJUTSU_ISSUES_GITHUB_TOKEN"
+
+            [[trackers]]
+            name = "lab"
+            kind = "gitlab"
+            repo = "group/sub/project"
+            api_url = "https://gitlab.example.com/api/v4"
+            token = "glpat-x"
+            enabled = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.trackers.len(), 2);
+        assert_eq!(config.tracker(None).unwrap().name, "gh");
+        assert!(config.tracker(Some("lab")).is_err(), "disabled");
+        assert!(config.tracker(Some("nope")).is_err());
+        assert!(IssueConfig::default().tracker(None).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_trackers() {
+        let base = "[[trackers]]\nname = \"a\"\nkind = \"github\"\n";
+        for bad in [
+            format!("{base}repo = \"noslash\"\ntoken = \"x\""),
+            format!("{base}repo = \"o/r\""),
+            format!("{base}repo = \"o/r\"\ntoken = \"x\"\ntoken_env = \"Y\""),
+            format!("{base}repo = \"o/r\"\ntoken_env = \"AWS_SECRET_ACCESS_KEY\""),
+            format!("{base}repo = \"o/r\"\ntoken = \"x\"\napi_url = \"ftp://h\""),
+            format!("{base}repo = \"o/r\"\ntoken = \"x\"\nlabel = [\"typo\"]"),
+            "[[trackers]]\nname = \"a\"\nkind = \"jira\"\nrepo = \"o/r\"\ntoken = \"x\"".into(),
+            format!("{base}repo = \"o/r\"\ntoken = \"x\"\n{base}repo = \"o/s\"\ntoken = \"x\""),
+        ] {
+            assert!(IssueConfig::parse(&bad).is_err(), "accepted: {bad}");
+        }
+    }
+
+    #[test]
+    fn seeded_default_has_no_trackers() {
+        let config = IssueConfig::parse(crate::config_seed::DEFAULT_ISSUES).unwrap();
+        assert!(config.trackers.is_empty());
+    }
+
+    #[test]
+    fn github_requests() {
+        let t = tracker(TrackerKind::Github, "tobert/kaijutsu");
+        assert_eq!(
+            t.create_url(&new_issue()).unwrap().as_str(),
+            "https://api.github.com/repos/tobert/kaijutsu/issues"
+        );
+        assert_eq!(
+            t.create_url(&IssueTarget::Comment { number: 7 })
+                .unwrap()
+                .as_str(),
+            "https://api.github.com/repos/tobert/kaijutsu/issues/7/comments"
+        );
+        assert_eq!(
+            t.create_body(&new_issue(), "b"),
+            serde_json::json!({ "title": "Flaky test", "body": "b", "labels": ["agent", "bug"] })
+        );
+        let response = serde_json::json!({
+            "number": 12,
+            "html_url": "https://github.com/tobert/kaijutsu/issues/12",
+        });
+        assert_eq!(
+            t.created_url(&new_issue(), &response),
+            Some((
+                "https://github.com/tobert/kaijutsu/issues/12".into(),
+                Some(12)
+            ))
+        );
+    }
+
+    #[test]
+    fn gitlab_requests_encode_the_project_path() {
+        let mut t = tracker(TrackerKind::Gitlab, "group/sub/project");
+        t.api_url = Some("https://gitlab.example.com/api/v4/".into());
+        assert_eq!(
+            t.create_url(&new_issue()).unwrap().as_str(),
+            "https://gitlab.example.com/api/v4/projects/group%2Fsub%2Fproject/issues"
+        );
+        assert_eq!(
+            t.create_url(&IssueTarget::Comment { number: 3 })
+                .unwrap()
+                .as_str(),
+            "https://gitlab.example.com/api/v4/projects/group%2Fsub%2Fproject/issues/3/notes"
+        );
+        assert_eq!(
+            t.create_body(&new_issue(), "b"),
+            serde_json::json!({ "title": "Flaky test", "description": "b", "labels": "agent,bug" })
+        );
+        let response =
+            serde_json::json!({ "iid": 3, "web_url": "https://gitlab.example.com/g/p/-/issues/3" });
+        assert_eq!(
+            t.created_url(&new_issue(), &response).map(|(_, n)| n),
+            Some(Some(3))
+        );
+        assert_eq!(
+            t.created_url(&IssueTarget::Comment { number: 3 }, &response),
+            None
+        );
+    }
+
+    #[test]
+    fn renders_subtree_as_markdown() {
+        let ctx = ContextId::new();
+        let principal = PrincipalId::system();
+        let id = |seq| BlockId::new(ctx, principal, seq);
+        let root = BlockSnapshot::text(id(0), None, Role::User, "# Why is CI red?\nsee below");
+        let mut thinking = BlockSnapshot::text(id(1), Some(id(0)), Role::Model, "hmm");
+        thinking.kind = BlockKind::Thinking;
+        let mut call =
+            BlockSnapshot::text(id(2), Some(id(0)), Role::Model, r#"{"cmd":"cargo test"}"#);
+        call.kind = BlockKind::ToolCall;
+        call.tool_name = Some("shell".into());
+        let mut result = BlockSnapshot::text(id(3), Some(id(2)), Role::Tool, "1 failed");
+        result.kind = BlockKind::ToolResult;
+        result.is_error = true;
+
+        let body = render_subtree(&[root.clone(), thinking, call, result], &id(0));
+        assert!(body.starts_with("**user**\n\n# Why is CI red?\nsee below\n"));
+        assert!(!body.contains("hmm"), "thinking is left out");
+        assert!(body.contains("**model → `shell`**\n\n```json\n{\"cmd\":\"cargo test\"}\n```"));
+        assert!(body.contains("**result (error)**\n\n```\n1 failed\n```"));
+        assert!(body.ends_with(&format!("block `{}`._\n", id(0).to_key())));
+        assert_eq!(default_title(&[root]), "Why is CI red?");
+    }
+}
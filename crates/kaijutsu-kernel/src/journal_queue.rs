@@ -0,0 +1,467 @@
+//! Write-behind journaling — block oplog appends coalesced into batches.
+//!
+//! By default every mutating block op appends its delta to the `oplog` table
+//! and commits before returning: one transaction (and, under
+//! [`SyncPolicy::Full`](crate::kernel_db::SyncPolicy), one fsync) per op,
+//! which caps a streaming model at the disk's commit rate. With
+//! [`JournalBatching`] set on a store, ops are queued instead and committed
+//! together — every [`JournalBatching::flush_interval`], or at once when
+//! [`JournalBatching::max_batch`] are waiting — in one transaction.
+//!
+//! What a crash can cost: ops are acknowledged (and broadcast) before they
+//! are durable, so a kill loses at most the queue — one flush interval of
+//! writes. Only one flush runs at a time, a batch commits whole or not at
+//! all, and a refused batch goes back to the front of the queue. An op takes
+//! its seq under the queue's lock, as it is pushed, so queue order is seq
+//! order on every document and what is on disk is always a prefix of what
+//! was acknowledged: a crash loses a tail, never an op from the middle of a
+//! document's oplog. A refused op takes no seq. Compaction flushes first,
+//! deleting a document drops its queued ops, and dropping the store flushes
+//! what is left; a server stopping on a signal flushes explicitly.
+//!
+//! The queue only grows past [`JournalBatching::max_batch`] while flushes
+//! fail. Past [`JournalBatching::max_queued`] new ops are refused — the
+//! write fails as it would have write-through — instead of piling up in
+//! memory behind a database that isn't taking them.
+//!
+//! Input documents (compose scratchpads) are low-rate and stay write-through.
+//! Queue depth is exposed by [`BlockStore::journal_stats`] and recorded as
+//! `kaijutsu.journal.*` metrics.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use kaijutsu_types::ContextId;
+
+use crate::block_store::{BlockStore, DbHandle, SharedBlockStore};
+use crate::kernel_db::KernelDbResult;
+
+/// Flush interval when batching is on and the operator didn't pick one.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Queue depth that flushes without waiting for the interval.
+pub const DEFAULT_MAX_BATCH: usize = 512;
+
+/// Queue depth past which ops are refused until a flush gets through.
+pub const DEFAULT_MAX_QUEUED: usize = 32 * DEFAULT_MAX_BATCH;
+
+/// Longest flush interval accepted; past it a crash costs too much.
+pub const MAX_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How a store batches its oplog writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalBatching {
+    /// Longest an acknowledged op waits in memory before it is committed.
+    pub flush_interval: Duration,
+    /// Queue depth that commits at once.
+    pub max_batch: usize,
+    /// Queue depth past which new ops are refused rather than queued.
+    pub max_queued: usize,
+}
+
+impl Default for JournalBatching {
+    fn default() -> Self {
+        Self {
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            max_batch: DEFAULT_MAX_BATCH,
+            max_queued: DEFAULT_MAX_QUEUED,
+        }
+    }
+}
+
+/// Parse `--journal-flush`: `off` (write-through), or an interval in
+/// milliseconds, bare or with an `ms`/`s` suffix (`50`, `20ms`, `1s`).
+pub fn parse_journal_flush(s: &str) -> Result<Option<JournalBatching>, String> {
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("off") || s == "0" {
+        return Ok(None);
+    }
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "ms"),
+    };
+    let n: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid flush interval '{s}'"))?;
+    let flush_interval = match unit {
+        "ms" => Duration::from_millis(n),
+        "s" => Duration::from_secs(n),
+        _ => return Err(format!("invalid flush interval unit in '{s}' (ms, s)")),
+    };
+    if flush_interval.is_zero() {
+        return Ok(None);
+    }
+    if flush_interval > MAX_FLUSH_INTERVAL {
+        return Err(format!(
+            "flush interval '{s}' is over the {}s maximum",
+            MAX_FLUSH_INTERVAL.as_secs()
+        ));
+    }
+    Ok(Some(JournalBatching {
+        flush_interval,
+        ..JournalBatching::default()
+    }))
+}
+
+/// A snapshot of a store's write-behind queue.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct JournalStats {
+    /// Whether ops are batched at all.
+    pub batching: bool,
+    /// Ops acknowledged but not yet committed.
+    pub queued: usize,
+    /// Their payload bytes.
+    pub queued_bytes: u64,
+    /// Deepest the queue has been.
+    pub peak_queued: usize,
+    /// Batches committed.
+    pub flushes: u64,
+    /// Ops committed by those batches.
+    pub flushed_ops: u64,
+    /// Batches the database refused.
+    pub failed_flushes: u64,
+    /// Ops refused because the queue was full.
+    pub refused_ops: u64,
+    /// Why the last flush failed, while the failure stands.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// One acknowledged op waiting to be journaled.
+struct QueuedOp {
+    context_id: ContextId,
+    seq: i64,
+    payload: Vec<u8>,
+    /// When the op was made (Unix millis); becomes the row's `created_at`.
+    made_at: i64,
+    /// When it was queued, for the interval check.
+    queued: Instant,
+}
+
+#[derive(Default)]
+struct Queue {
+    ops: VecDeque<QueuedOp>,
+    bytes: u64,
+}
+
+/// What [`JournalQueue::push`] did with an op.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Queued {
+    /// Queued; the interval flush will commit it.
+    Waiting,
+    /// Queued, and the queue should be flushed now.
+    FlushNow,
+    /// Not queued: `depth` ops are already waiting.
+    Refused { depth: usize },
+}
+
+/// A store's write-behind queue. Inert (`batching` is `None`) until
+/// [`BlockStore::set_journal_batching`] turns it on.
+#[derive(Default)]
+pub(crate) struct JournalQueue {
+    batching: Mutex<Option<JournalBatching>>,
+    queue: Mutex<Queue>,
+    /// Held for a whole flush, so batches commit in queue order.
+    flushing: Mutex<()>,
+    stats: Mutex<JournalStats>,
+}
+
+impl JournalQueue {
+    pub(crate) fn batching(&self) -> Option<JournalBatching> {
+        *self.batching.lock()
+    }
+
+    pub(crate) fn set_batching(&self, batching: Option<JournalBatching>) {
+        *self.batching.lock() = batching;
+    }
+
+    /// Queue an op, taking its seq from `next_seq` under the queue's lock.
+    /// Flush now when the queue is at `max_batch`, its oldest op has waited
+    /// out the interval, or batching was turned off meanwhile; refuse it
+    /// (without calling `next_seq`) when `max_queued` ops are already
+    /// waiting.
+    pub(crate) fn push(
+        &self,
+        context_id: ContextId,
+        payload: Vec<u8>,
+        made_at: i64,
+        next_seq: impl FnOnce() -> i64,
+    ) -> Queued {
+        let batching = self.batching();
+        let mut queue = self.queue.lock();
+        if let Some(b) = batching
+            && queue.ops.len() >= b.max_queued
+        {
+            let depth = queue.ops.len();
+            drop(queue);
+            self.stats.lock().refused_ops += 1;
+            return Queued::Refused { depth };
+        }
+        queue.bytes += payload.len() as u64;
+        queue.ops.push_back(QueuedOp {
+            context_id,
+            seq: next_seq(),
+            payload,
+            made_at,
+            queued: Instant::now(),
+        });
+        let depth = queue.ops.len();
+        let oldest = queue.ops.front().map(|op| op.queued.elapsed());
+        drop(queue);
+
+        let mut stats = self.stats.lock();
+        stats.peak_queued = stats.peak_queued.max(depth);
+        let flush_now = batching.is_none_or(|b| {
+            depth >= b.max_batch || oldest.is_some_and(|age| age >= b.flush_interval)
+        });
+        if flush_now {
+            Queued::FlushNow
+        } else {
+            Queued::Waiting
+        }
+    }
+
+    /// Drop `context_id`'s queued ops (its document is being deleted). Waits
+    /// out a flush in progress, which may hold some of them.
+    pub(crate) fn discard(&self, context_id: ContextId) {
+        let _flushing = self.flushing.lock();
+        let mut queue = self.queue.lock();
+        queue.ops.retain(|op| op.context_id != context_id);
+        queue.bytes = queue.ops.iter().map(|op| op.payload.len() as u64).sum();
+    }
+
+    /// Commit everything queued in one transaction, stamping each context's
+    /// last activity with its newest op. Returns the ops committed. A
+    /// refused batch is put back at the front of the queue.
+    pub(crate) fn flush(&self, db: &DbHandle) -> KernelDbResult<usize> {
+        let _flushing = self.flushing.lock();
+        let batch = {
+            let mut queue = self.queue.lock();
+            queue.bytes = 0;
+            std::mem::take(&mut queue.ops)
+        };
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let mut activity: Vec<(ContextId, i64)> = Vec::new();
+        for op in &batch {
+            match activity.iter_mut().find(|(id, _)| *id == op.context_id) {
+                Some((_, ts)) => *ts = (*ts).max(op.made_at),
+                None => activity.push((op.context_id, op.made_at)),
+            }
+        }
+        let started = Instant::now();
+        let result = db.lock().append_ops(
+            batch
+                .iter()
+                .map(|op| (op.context_id, op.seq, op.payload.as_slice(), op.made_at)),
+            &activity,
+        );
+
+        let mut stats = self.stats.lock();
+        match result {
+            Ok(n) => {
+                kaijutsu_telemetry::record_journal_flush(n, started.elapsed());
+                stats.flushes += 1;
+                stats.flushed_ops += n as u64;
+                stats.last_error = None;
+                Ok(n)
+            }
+            Err(e) => {
+                kaijutsu_telemetry::record_journal_flush_failed();
+                stats.failed_flushes += 1;
+                stats.last_error = Some(e.to_string());
+                let mut queue = self.queue.lock();
+                queue.bytes += batch.iter().map(|op| op.payload.len() as u64).sum::<u64>();
+                for op in batch.into_iter().rev() {
+                    queue.ops.push_front(op);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    pub(crate) fn stats(&self) -> JournalStats {
+        let (queued, queued_bytes) = {
+            let queue = self.queue.lock();
+            (queue.ops.len(), queue.bytes)
+        };
+        JournalStats {
+            batching: self.batching().is_some(),
+            queued,
+            queued_bytes,
+            ..self.stats.lock().clone()
+        }
+    }
+}
+
+/// Flush `store`'s queue every flush interval until the store is dropped.
+/// Batching only; a write-through store returns without a task. Call from
+/// a runtime that lives as long as the store.
+pub fn spawn_flusher(store: &SharedBlockStore) -> Option<tokio::task::JoinHandle<()>> {
+    let interval = store.journal_batching()?.flush_interval;
+    let store: Weak<BlockStore> = Arc::downgrade(store);
+    Some(tokio::spawn(async move {
+        let mut tick = tokio::time::interval(interval);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut failing = false;
+        loop {
+            tick.tick().await;
+            let Some(store) = store.upgrade() else { break };
+            match store.flush_journal() {
+                Ok(_) if failing => {
+                    failing = false;
+                    tracing::info!("journal flushes succeeding again");
+                }
+                Ok(_) => {}
+                // Once per outage, not once per tick.
+                Err(e) if !failing => {
+                    failing = true;
+                    tracing::warn!("journal flush failed, ops stay queued: {e}");
+                }
+                Err(_) => {}
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::{BlockStoreResult, DocumentKind};
+    use crate::kernel_db::KernelDb;
+    use kaijutsu_types::{BlockKind, ContentType, PrincipalId, Role, Status};
+
+    fn batched_store() -> (BlockStore, DbHandle) {
+        let db = Arc::new(Mutex::new(KernelDb::in_memory().unwrap()));
+        let creator = PrincipalId::system();
+        let ws_id = db.lock().get_or_create_default_workspace(creator).unwrap();
+        let store = BlockStore::with_db(db.clone(), ws_id, creator);
+        store
+            .set_journal_batching(Some(JournalBatching {
+                flush_interval: Duration::from_secs(3600),
+                max_batch: 1000,
+                max_queued: 3,
+            }))
+            .unwrap();
+        (store, db)
+    }
+
+    fn insert(store: &BlockStore, ctx: ContextId) -> BlockStoreResult<()> {
+        store
+            .insert_block(
+                ctx,
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                "queued",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .map(|_| ())
+    }
+
+    #[test]
+    fn ops_wait_in_the_queue_until_a_flush() {
+        let (store, db) = batched_store();
+        let ctx = ContextId::new();
+        store
+            .create_document(ctx, DocumentKind::Conversation, None)
+            .unwrap();
+        let on_disk = |db: &DbHandle| db.lock().load_oplog_since(ctx, 0).unwrap().len();
+        let before = on_disk(&db);
+
+        insert(&store, ctx).unwrap();
+        insert(&store, ctx).unwrap();
+        let stats = store.journal_stats();
+        assert!(stats.batching);
+        assert_eq!(stats.queued, 2);
+        assert!(stats.queued_bytes > 0);
+        assert_eq!(on_disk(&db), before, "nothing written yet");
+
+        assert_eq!(store.flush_journal().unwrap(), 2);
+        assert_eq!(on_disk(&db), before + 2);
+        let stats = store.journal_stats();
+        assert_eq!((stats.queued, stats.flushes, stats.flushed_ops), (0, 1, 2));
+        assert_eq!(store.flush_journal().unwrap(), 0, "empty flush is a no-op");
+
+        // A deleted document's queued ops are dropped, not flushed into
+        // a foreign-key failure.
+        insert(&store, ctx).unwrap();
+        store.delete_document(ctx).unwrap();
+        assert_eq!(store.journal_stats().queued, 0);
+        assert_eq!(store.flush_journal().unwrap(), 0);
+    }
+
+    #[test]
+    fn a_full_queue_refuses_ops_until_it_drains() {
+        let (store, _db) = batched_store();
+        let ctx = ContextId::new();
+        store
+            .create_document(ctx, DocumentKind::Conversation, None)
+            .unwrap();
+        store.flush_journal().unwrap();
+        for _ in 0..3 {
+            insert(&store, ctx).unwrap();
+        }
+        assert!(insert(&store, ctx).is_err(), "over max_queued");
+        let stats = store.journal_stats();
+        assert_eq!((stats.queued, stats.refused_ops), (3, 1));
+
+        store.flush_journal().unwrap();
+        insert(&store, ctx).unwrap();
+    }
+
+    #[test]
+    fn seqs_are_taken_in_queue_order() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+
+        let queue = JournalQueue::default();
+        let batching = JournalBatching {
+            flush_interval: Duration::from_secs(3600),
+            max_batch: 1000,
+            max_queued: 200,
+        };
+        queue.set_batching(Some(batching));
+        let ctx = ContextId::new();
+        let next = AtomicI64::new(0);
+        let next_seq = || next.fetch_add(1, Ordering::SeqCst) + 1;
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..50 {
+                        queue.push(ctx, vec![0], 0, next_seq);
+                    }
+                });
+            }
+        });
+        let seqs: Vec<i64> = queue.queue.lock().ops.iter().map(|op| op.seq).collect();
+        assert_eq!(seqs, (1..=200).collect::<Vec<_>>());
+
+        // A refused op takes no seq.
+        assert_eq!(
+            queue.push(ctx, vec![0], 0, next_seq),
+            Queued::Refused { depth: 200 }
+        );
+        assert_eq!(next.load(Ordering::SeqCst), 200);
+    }
+
+    #[test]
+    fn flush_interval_parses() {
+        assert_eq!(parse_journal_flush("off").unwrap(), None);
+        assert_eq!(parse_journal_flush("0").unwrap(), None);
+        let every = |s| parse_journal_flush(s).unwrap().unwrap().flush_interval;
+        assert_eq!(every("20"), Duration::from_millis(20));
+        assert_eq!(every("250ms"), Duration::from_millis(250));
+        assert_eq!(every("1s"), Duration::from_secs(1));
+        assert!(parse_journal_flush("10s").is_err(), "over the maximum");
+        assert!(parse_journal_flush("5m").is_err());
+        assert!(parse_journal_flush("soon").is_err());
+    }
+}
@@ -23,8 +23,8 @@ use crate::control::ConsentMode;
 use crate::drift::{SharedDriftRouter, shared_drift_router};
 use crate::execution::{ExecContext, ExecResult};
 use crate::flows::{
-    SharedBlockFlowBus, SharedEditorFlowBus, SharedTurnFlowBus, shared_block_flow_bus,
-    shared_editor_flow_bus, shared_turn_flow_bus,
+    SharedBlockFlowBus, SharedConfigFlowBus, SharedEditorFlowBus, SharedTurnFlowBus,
+    shared_block_flow_bus, shared_config_flow_bus, shared_editor_flow_bus, shared_turn_flow_bus,
 };
 use crate::llm::{LlmRegistry, Provider};
 use crate::mcp::Broker;
@@ -47,8 +47,9 @@ pub struct Kernel {
     vfs: Arc<MountTable>,
     /// Kernel state (behind RwLock for interior mutability).
     state: RwLock<KernelState>,
-    /// LLM provider registry (behind RwLock for interior mutability).
-    llm: RwLock<LlmRegistry>,
+    /// LLM provider registry (behind RwLock for interior mutability). Shared
+    /// with the translator.
+    llm: Arc<RwLock<LlmRegistry>>,
     /// Peer registry (behind RwLock for interior mutability).
     peers: RwLock<PeerRegistry>,
     /// Consent mode (collaborative vs autonomous).
@@ -129,6 +130,25 @@ pub struct Kernel {
     /// `editor_quit` publishes `Closed`; the server's `subscribe_editor` bridge
     /// serializes these onto the `EditorEvents` capnp callback.
     editor_flows: SharedEditorFlowBus,
+    /// FlowBus for live config re-applies (`ConfigFlow::Applied`), forwarded
+    /// to clients by the server's block-subscription bridges.
+    config_flows: SharedConfigFlowBus,
+    /// Sliding-window per-context activity, fed from `block_flows` by
+    /// [`crate::context_activity::spawn_aggregator`] and sampled by the
+    /// server's `subscribeActivity` bridge.
+    context_activity: crate::context_activity::SharedContextActivity,
+    /// Stuck-block / stalled-stream / tool-failure watch, fed from
+    /// `block_flows` by [`crate::context_health::spawn_watchdog`].
+    context_health: crate::context_health::SharedContextHealth,
+    /// Block translations and their cache (`crate::translate`).
+    translator: Arc<crate::translate::Translator>,
+    /// Last health check per LLM provider, filled by
+    /// [`crate::llm::health::spawn_checker`] and after each `models.toml`
+    /// apply.
+    llm_health: crate::llm::health::SharedLlmHealth,
+    /// Tool calls held for a human's approval (`consent.toml`), answered
+    /// through `consentRespond`.
+    consent_prompts: crate::consent_prompts::SharedConsentPrompts,
 }
 
 /// Removes its directory on drop. A tiny owned guard so `new_ephemeral()` test
@@ -175,17 +195,19 @@ impl Kernel {
     pub async fn new(name: impl Into<String>, data_dir: &Path) -> Self {
         let name = name.into();
         let vfs = Arc::new(MountTable::new());
+        let llm = Arc::new(RwLock::new(LlmRegistry::new()));
+        let drift = shared_drift_router();
 
         Self {
             id: kaijutsu_types::KernelId::new(),
             vfs,
             state: RwLock::new(KernelState::new(&name)),
-            llm: RwLock::new(LlmRegistry::new()),
+            llm: llm.clone(),
             peers: RwLock::new(PeerRegistry::new()),
             consent_mode: RwLock::new(ConsentMode::default()),
             block_flows: shared_block_flow_bus(DEFAULT_FLOW_CAPACITY),
             turn_flows: shared_turn_flow_bus(DEFAULT_FLOW_CAPACITY),
-            drift: shared_drift_router(),
+            drift: drift.clone(),
             cas: Self::cas_for_data_dir(data_dir),
             share_registry: Arc::new(crate::vfs::ShareRegistry::new()),
             image_backends: RwLock::new(crate::image::ImageBackendRegistry::new()),
@@ -205,6 +227,12 @@ impl Kernel {
                 crate::editor::EditorSessions::new(),
             )),
             editor_flows: shared_editor_flow_bus(DEFAULT_FLOW_CAPACITY),
+            config_flows: shared_config_flow_bus(DEFAULT_FLOW_CAPACITY),
+            context_activity: Default::default(),
+            context_health: Default::default(),
+            translator: Arc::new(crate::translate::Translator::new(llm, drift)),
+            llm_health: Default::default(),
+            consent_prompts: Default::default(),
         }
     }
 
@@ -237,17 +265,19 @@ impl Kernel {
     ) -> Self {
         let name = name.into();
         let vfs = Arc::new(MountTable::new());
+        let llm = Arc::new(RwLock::new(LlmRegistry::new()));
+        let drift = shared_drift_router();
 
         Self {
             id,
             vfs,
             state: RwLock::new(KernelState::new(&name)),
-            llm: RwLock::new(LlmRegistry::new()),
+            llm: llm.clone(),
             peers: RwLock::new(PeerRegistry::new()),
             consent_mode: RwLock::new(ConsentMode::default()),
             block_flows,
             turn_flows: shared_turn_flow_bus(DEFAULT_FLOW_CAPACITY),
-            drift: shared_drift_router(),
+            drift: drift.clone(),
             cas: Self::cas_for_data_dir(data_dir),
             share_registry: Arc::new(crate::vfs::ShareRegistry::new()),
             image_backends: RwLock::new(crate::image::ImageBackendRegistry::new()),
@@ -267,6 +297,12 @@ impl Kernel {
                 crate::editor::EditorSessions::new(),
             )),
             editor_flows: shared_editor_flow_bus(DEFAULT_FLOW_CAPACITY),
+            config_flows: shared_config_flow_bus(DEFAULT_FLOW_CAPACITY),
+            context_activity: Default::default(),
+            context_health: Default::default(),
+            translator: Arc::new(crate::translate::Translator::new(llm, drift)),
+            llm_health: Default::default(),
+            consent_prompts: Default::default(),
         }
     }
 
@@ -568,7 +604,8 @@ impl Kernel {
     /// have (the kernel does not own a `BlockStore`). Safe to call multiple
     /// times — subsequent calls replace the previous registrations.
     ///
-    /// Registered under: `builtin.block`, `builtin.file`, `builtin.kernel_info`.
+    /// Registered under: `builtin.block`, `builtin.file`, `builtin.kernel_info`,
+    /// `builtin.kv`, `builtin.issues`, `builtin.repl`.
     pub async fn register_builtin_mcp_servers(
         &self,
         documents: crate::block_store::SharedBlockStore,
@@ -578,7 +615,7 @@ impl Kernel {
     ) -> crate::mcp::McpResult<()> {
         use crate::mcp::servers::{
             BlockToolsServer, BuiltinBindingsServer, BuiltinHooksServer, BuiltinResourcesServer,
-            FileToolsServer, KernelInfoServer,
+            ContextKvServer, FileToolsServer, IssueToolsServer, KernelInfoServer, ReplServer,
         };
         use crate::mcp::servers::bindings_builtin::KERNEL_TOOLS_URI;
         use crate::mcp::{InstancePolicy, KernelNotification};
@@ -597,7 +634,10 @@ impl Kernel {
 
         self.broker
             .register_silently(
-                Arc::new(BlockToolsServer::new(documents, self.cas.clone())),
+                Arc::new(
+                    BlockToolsServer::new(documents.clone(), self.cas.clone())
+                        .with_translator(self.translator.clone()),
+                ),
                 InstancePolicy::for_kernel(self),
             )
             .await?;
@@ -620,6 +660,37 @@ impl Kernel {
             )
             .await?;
 
+        // builtin.kv: per-context scratch store agents share for coordination
+        // state that doesn't belong in conversation blocks.
+        let kv_store = Arc::new(crate::context_kv::ContextKvStore::new(
+            kernel_db.clone(),
+            PrincipalId::system(),
+        ));
+        self.broker
+            .register_silently(
+                Arc::new(ContextKvServer::new(kv_store)),
+                InstancePolicy::for_kernel(self),
+            )
+            .await?;
+
+        // builtin.issues: file block subtrees into GitHub/GitLab per the
+        // kernel's issues.toml, recording the created URL back as a block.
+        self.broker
+            .register_silently(
+                Arc::new(IssueToolsServer::new(documents.clone())),
+                InstancePolicy::for_kernel(self),
+            )
+            .await?;
+
+        // builtin.repl: Repl blocks bound to persistent interpreters. Its
+        // manager watches the block bus for input appended to session blocks.
+        self.broker
+            .register_silently(
+                Arc::new(ReplServer::new(Arc::downgrade(&self.broker), documents)),
+                InstancePolicy::for_kernel(self),
+            )
+            .await?;
+
         // Phase 3 (D-41): builtin.resources admin server. Weak<Broker> avoids
         // the Arc cycle (broker owns the instance arc, instance refers back).
         self.broker
@@ -682,6 +753,16 @@ impl Kernel {
             .register_silently(tool_search_server, InstancePolicy::for_kernel(self))
             .await?;
 
+        // builtin.tool_presets — per-context default arguments the broker
+        // fills into omitted parameters. Same Weak<Broker> pattern: it
+        // resolves visible tool names and validates against their schemas.
+        let tool_presets_server = Arc::new(crate::mcp::servers::ToolPresetsServer::new(
+            Arc::downgrade(&self.broker),
+        ));
+        self.broker
+            .register_silently(tool_presets_server, InstancePolicy::for_kernel(self))
+            .await?;
+
         // M3-D5: builtin.policy — get/set per-instance InstancePolicy.
         let policy_server = Arc::new(
             crate::mcp::servers::BuiltinPolicyServer::new(Arc::downgrade(&self.broker)),
@@ -722,6 +803,77 @@ impl Kernel {
         Ok(())
     }
 
+    /// Connect a downstream MCP server and register it for `context_id` only
+    /// (`Broker::register_scoped`). The instance id is `config.name`. Returns
+    /// the tools the server advertised at connect time.
+    pub async fn register_context_mcp_server(
+        &self,
+        context_id: kaijutsu_types::ContextId,
+        config: crate::mcp::servers::McpServerConfig,
+        fork_mode: crate::mcp::McpForkMode,
+    ) -> crate::mcp::McpResult<Vec<crate::mcp::KernelTool>> {
+        use crate::mcp::servers::ExternalMcpServer;
+        use crate::mcp::{CallContext, InstanceId, InstancePolicy, McpServerLike};
+
+        let instance = InstanceId::new(config.name.clone());
+        let server: Arc<dyn McpServerLike> = Arc::new(
+            ExternalMcpServer::connect(config, instance, self.timeouts().mcp_connect_timeout)
+                .await?,
+        );
+        let tools = server
+            .list_tools(&CallContext::system_for_context(context_id))
+            .await?;
+        if let Err(e) = self
+            .broker
+            .register_scoped(
+                server.clone(),
+                InstancePolicy::for_kernel(self),
+                context_id,
+                fork_mode,
+            )
+            .await
+        {
+            // Don't strand the child process we just spawned.
+            let _ = server.shutdown().await;
+            return Err(e);
+        }
+        Ok(tools)
+    }
+
+    /// `context_id`'s scoped MCP servers, each probed for health and tools
+    /// right now. A server whose tool listing fails reports `Health::Down`.
+    pub async fn context_mcp_servers(
+        &self,
+        context_id: kaijutsu_types::ContextId,
+    ) -> Vec<crate::mcp::ScopedInstanceStatus> {
+        use crate::mcp::{CallContext, Health, ScopedInstanceStatus};
+
+        let instances = self.broker.instances_snapshot().await;
+        let ctx = CallContext::system_for_context(context_id);
+        let mut out = Vec::new();
+        for (id, scope) in self.broker.scoped_instances(context_id).await {
+            let Some(server) = instances.get(&id) else {
+                continue;
+            };
+            let (health, tools) = match server.list_tools(&ctx).await {
+                Ok(tools) => (server.health().await, tools),
+                Err(e) => (
+                    Health::Down {
+                        reason: e.to_string(),
+                    },
+                    Vec::new(),
+                ),
+            };
+            out.push(ScopedInstanceStatus {
+                instance: id,
+                fork_mode: scope.fork_mode,
+                health,
+                tools,
+            });
+        }
+        out
+    }
+
     /// Get the block flows bus.
     pub fn block_flows(&self) -> &SharedBlockFlowBus {
         &self.block_flows
@@ -738,6 +890,26 @@ impl Kernel {
         &self.turn_flows
     }
 
+    /// Get the config flows bus (live config re-applies).
+    pub fn config_flows(&self) -> &SharedConfigFlowBus {
+        &self.config_flows
+    }
+
+    /// Get the per-context activity tracker.
+    pub fn context_activity(&self) -> &crate::context_activity::SharedContextActivity {
+        &self.context_activity
+    }
+
+    /// Get the per-context health tracker.
+    pub fn context_health(&self) -> &crate::context_health::SharedContextHealth {
+        &self.context_health
+    }
+
+    /// Get the open consent prompts and session approvals.
+    pub fn consent_prompts(&self) -> &crate::consent_prompts::SharedConsentPrompts {
+        &self.consent_prompts
+    }
+
     /// Get the drift router.
     pub fn drift(&self) -> &SharedDriftRouter {
         &self.drift
@@ -1459,6 +1631,25 @@ impl Kernel {
         &self.llm
     }
 
+    /// Block translations (`block_read --translate-to`, `translateBlock`).
+    pub fn translator(&self) -> &Arc<crate::translate::Translator> {
+        &self.translator
+    }
+
+    /// Last health check per LLM provider.
+    pub fn llm_health(&self) -> &crate::llm::health::SharedLlmHealth {
+        &self.llm_health
+    }
+
+    /// Re-check every LLM provider in the background, e.g. after the
+    /// registry was swapped.
+    pub(crate) fn recheck_llm_health(&self) {
+        let (llm, health) = (self.llm.clone(), self.llm_health.clone());
+        tokio::spawn(async move {
+            crate::llm::health::check_providers(&llm, &health).await;
+        });
+    }
+
     /// List registered LLM providers.
     pub async fn list_llm_providers(&self) -> Vec<String> {
         self.llm
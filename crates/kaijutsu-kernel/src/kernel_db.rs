@@ -24,13 +24,17 @@ use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, params};
 use tracing::{info, warn};
 
 use kaijutsu_types::{
-    BlockId, ConsentMode, ContextId, ContextState, DocKind, EdgeKind, ForkKind, KernelId, PresetId,
-    PrincipalId, WorkspaceId,
+    Assembly, BlockId, ConsentEntry, ConsentMode, ConsentVerdict, ConsentVerification,
+    ContextCloseFilter, ContextId, ContextShare, ContextState, DigestSubscription, DocBookmark,
+    DocKind, EdgeKind, ExecutionBudget, ForkKind, InboxItem, InboxKind, KernelId, LlmParams,
+    LockPolicy, Preferences, PresetId, PrincipalId, SandboxLimits, SandboxProfile, Suggestion,
+    SuggestionState, TextEdit, WorkspaceId,
 };
 
 use crate::llm::stream::{CacheTarget, CacheTtl};
 use crate::mcp::binding::ContextToolBinding;
 use crate::mcp::types::InstanceId;
+use crate::tool_presets::ToolPreset;
 
 // ============================================================================
 // Error type
@@ -66,6 +70,57 @@ pub enum KernelDbError {
 
 pub type KernelDbResult<T> = Result<T, KernelDbError>;
 
+/// How hard SQLite pushes each journal write to disk (`PRAGMA synchronous`).
+///
+/// The block oplog is appended inside every mutating block op, so with
+/// [`SyncPolicy::Full`] a write is durable before the op returns — unless
+/// the store batches its journal ([`crate::journal_queue`]), when it is
+/// durable by the end of its flush interval. The kernel
+/// keeps SQLite's default; embedders trading durability for latency (the MCP
+/// local backend's `--fsync`) pick explicitly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncPolicy {
+    /// fsync on every commit — nothing acknowledged is lost to a crash or
+    /// power cut.
+    #[default]
+    Full,
+    /// fsync at WAL checkpoints only — survives a process crash, may lose
+    /// the last commits on power loss.
+    Normal,
+    /// Never fsync; the OS decides. Survives a process crash only if the OS
+    /// stays up.
+    Off,
+}
+
+impl SyncPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Normal => "normal",
+            Self::Off => "off",
+        }
+    }
+}
+
+impl std::fmt::Display for SyncPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for SyncPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "full" | "always" => Ok(Self::Full),
+            "normal" => Ok(Self::Normal),
+            "off" | "never" => Ok(Self::Off),
+            other => Err(format!("unknown sync policy '{other}' (full, normal, off)")),
+        }
+    }
+}
+
 // ============================================================================
 // Row types
 // ============================================================================
@@ -393,7 +448,8 @@ CREATE TABLE IF NOT EXISTS documents (
     language     TEXT,
     path         TEXT,
     created_at   INTEGER NOT NULL DEFAULT (CAST((unixepoch('subsec') * 1000) AS INTEGER)),
-    created_by   BLOB NOT NULL
+    created_by   BLOB NOT NULL,
+    sync_generation INTEGER NOT NULL DEFAULT 0
 );
 CREATE INDEX IF NOT EXISTS idx_documents_workspace
     ON documents(workspace_id);
@@ -747,6 +803,241 @@ CREATE TABLE IF NOT EXISTS client_views (
     context_id  BLOB    NOT NULL,
     updated_at  INTEGER NOT NULL
 );
+
+-- ── Context Sandbox Profiles ────────────────────────────────────
+-- Per-context limits on host command execution (`kaijutsu_types::sandbox`).
+-- No row: host commands run under the loadout's `exec` authority alone. NULL
+-- limits leave the kernel's own rlimit in place. Allowed binaries live in the
+-- child table, one row each.
+CREATE TABLE IF NOT EXISTS context_sandbox (
+    context_id      BLOB    NOT NULL PRIMARY KEY REFERENCES contexts(context_id) ON DELETE CASCADE,
+    fs_from_mounts  INTEGER NOT NULL DEFAULT 0,
+    network         INTEGER NOT NULL DEFAULT 0,
+    cpu_secs        INTEGER,
+    memory_bytes    INTEGER,
+    file_size_bytes INTEGER,
+    open_files      INTEGER,
+    processes       INTEGER,
+    updated_at      INTEGER NOT NULL DEFAULT (CAST((unixepoch('subsec') * 1000) AS INTEGER))
+);
+
+CREATE TABLE IF NOT EXISTS context_sandbox_binaries (
+    context_id  BLOB NOT NULL REFERENCES context_sandbox(context_id) ON DELETE CASCADE,
+    binary      TEXT NOT NULL,
+    PRIMARY KEY (context_id, binary)
+);
+
+-- ── Context KV scratch store ────────────────────────────────────
+-- One row per context holding the full DTE oplog of its KV document
+-- (`crate::context_kv`). Rewritten on every write; the store is capped small.
+CREATE TABLE IF NOT EXISTS context_kv (
+    context_id  BLOB    NOT NULL PRIMARY KEY REFERENCES contexts(context_id) ON DELETE CASCADE,
+    state       BLOB    NOT NULL,
+    updated_at  INTEGER NOT NULL DEFAULT (CAST((unixepoch('subsec') * 1000) AS INTEGER))
+);
+
+-- ── Context Execution Budgets ───────────────────────────────────
+-- Per-context limits on tool execution (`kaijutsu_types::budget`). No row:
+-- unlimited. NULL leaves that axis unlimited. Usage is tracked in memory by
+-- `crate::budget`, not here.
+CREATE TABLE IF NOT EXISTS context_budgets (
+    context_id              BLOB    NOT NULL PRIMARY KEY REFERENCES contexts(context_id) ON DELETE CASCADE,
+    max_tool_calls_per_hour INTEGER,
+    max_shell_secs_per_hour INTEGER,
+    updated_at              INTEGER NOT NULL DEFAULT (CAST((unixepoch('subsec') * 1000) AS INTEGER))
+);
+
+-- ── Context Tool Presets ────────────────────────────────────────
+-- Per-context default arguments for one tool (`crate::tool_presets`).
+-- `params` is a JSON object; the broker fills in any key a call omits.
+CREATE TABLE IF NOT EXISTS context_tool_presets (
+    context_id  BLOB    NOT NULL REFERENCES contexts(context_id) ON DELETE CASCADE,
+    instance    TEXT    NOT NULL,
+    tool        TEXT    NOT NULL,
+    params      TEXT    NOT NULL,
+    updated_by  BLOB    NOT NULL,
+    updated_at  INTEGER NOT NULL,
+    PRIMARY KEY (context_id, instance, tool)
+);
+
+-- ── Context Lock Policies ───────────────────────────────────────
+-- What a write to a block another principal has locked does in this context
+-- (`kaijutsu_types::LockPolicy`, by its string form). No row: warn. The
+-- locks themselves are presence and live in memory (`crate::block_locks`).
+CREATE TABLE IF NOT EXISTS context_lock_policies (
+    context_id  BLOB    NOT NULL PRIMARY KEY REFERENCES contexts(context_id) ON DELETE CASCADE,
+    policy      TEXT    NOT NULL,
+    updated_at  INTEGER NOT NULL DEFAULT (CAST((unixepoch('subsec') * 1000) AS INTEGER))
+);
+
+-- ── Context LLM Parameters ──────────────────────────────────────
+-- Per-context generation parameters (`kaijutsu_types::llm_params`). No row or
+-- NULL: provider default. `tool_choice` is the ToolChoice string form.
+CREATE TABLE IF NOT EXISTS context_llm_params (
+    context_id  BLOB    NOT NULL PRIMARY KEY REFERENCES contexts(context_id) ON DELETE CASCADE,
+    temperature REAL,
+    max_tokens  INTEGER,
+    top_p       REAL,
+    tool_choice TEXT,
+    updated_at  INTEGER NOT NULL DEFAULT (CAST((unixepoch('subsec') * 1000) AS INTEGER))
+);
+
+-- ── Context Assembly ────────────────────────────────────────────
+-- Per-context prompt-assembly strategy (`kaijutsu_types::assembly`), in its
+-- spec form (`checkpoint+window:16`). No row: `linear`, or the hydration
+-- policy's window when `context_hydration` has one.
+CREATE TABLE IF NOT EXISTS context_assembly (
+    context_id  BLOB    NOT NULL PRIMARY KEY REFERENCES contexts(context_id) ON DELETE CASCADE,
+    strategy    TEXT    NOT NULL,
+    updated_at  INTEGER NOT NULL DEFAULT (CAST((unixepoch('subsec') * 1000) AS INTEGER))
+);
+
+-- ── Digest Subscriptions ────────────────────────────────────────
+-- One context following another through periodic LLM digests
+-- (`kaijutsu_types::digest`). `every_secs` NULL: milestone-only.
+-- `last_block` is the source's last block key at the last digest (or at
+-- subscribe time); the scheduler only digests once the source moves past it.
+CREATE TABLE IF NOT EXISTS digest_subscriptions (
+    subscriber_id   BLOB    NOT NULL REFERENCES contexts(context_id) ON DELETE CASCADE,
+    source_id       BLOB    NOT NULL REFERENCES contexts(context_id) ON DELETE CASCADE,
+    every_secs      INTEGER,
+    on_milestone    INTEGER NOT NULL DEFAULT 0,
+    focus           TEXT,
+    created_by      BLOB    NOT NULL,
+    created_at      INTEGER NOT NULL,
+    last_digest_at  INTEGER,
+    last_block      TEXT,
+    PRIMARY KEY (subscriber_id, source_id)
+);
+
+-- ── Inbox (per-principal notifications) ─────────────────────────
+-- One row per notification addressed to a seat: mentions, consent requests,
+-- drift arrivals, task assignments. Rows are never deleted by ack — `acked_at`
+-- flips from NULL so history stays queryable. No FK on `context_id`: an item
+-- may outlive (or point at a not-yet-persisted) context, and the pointer is
+-- informational.
+CREATE TABLE IF NOT EXISTS inbox (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    recipient   BLOB    NOT NULL,
+    kind        TEXT    NOT NULL,
+    context_id  BLOB,
+    sender      BLOB,
+    summary     TEXT    NOT NULL,
+    created_at  INTEGER NOT NULL,
+    acked_at    INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_inbox_recipient ON inbox(recipient, acked_at);
+
+-- ── Block edit suggestions ──────────────────────────────────────
+-- Proposed edits to a block (`kaijutsu_types::suggestion`), stored instead of
+-- applied. `edits` is the JSON patch against `base`, the block text when the
+-- suggestion was made. Rows stay after a decision so history is queryable.
+-- No FK on `context_id`: like the inbox, the row outlives its context.
+CREATE TABLE IF NOT EXISTS block_suggestions (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    context_id   BLOB    NOT NULL,
+    block_key    TEXT    NOT NULL,
+    proposer     BLOB    NOT NULL,
+    base         TEXT    NOT NULL,
+    edits        TEXT    NOT NULL,
+    note         TEXT,
+    state        TEXT    NOT NULL DEFAULT 'open',
+    created_at   INTEGER NOT NULL,
+    resolved_by  BLOB,
+    resolved_at  INTEGER,
+    reason       TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_block_suggestions_context ON block_suggestions(context_id, state);
+
+-- ── Context shares ──────────────────────────────────────────────
+-- Read-only snapshots of a context (`kaijutsu_types::context_share`), opened
+-- by token. `blocks` is the codec-encoded block list as it stood at
+-- `version`; it never changes after insert. Expiry is read against
+-- `expires_at`, revocation stamps `revoked_at`; rows are kept either way.
+-- No FK on `context_id`: a share outlives its context.
+CREATE TABLE IF NOT EXISTS context_shares (
+    token        TEXT    NOT NULL PRIMARY KEY,
+    context_id   BLOB    NOT NULL,
+    label        TEXT    NOT NULL DEFAULT '',
+    version      INTEGER NOT NULL,
+    block_count  INTEGER NOT NULL,
+    blocks       BLOB    NOT NULL,
+    created_by   BLOB    NOT NULL,
+    created_at   INTEGER NOT NULL,
+    expires_at   INTEGER NOT NULL,
+    revoked_at   INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_context_shares_context ON context_shares(context_id);
+
+-- ── Document bookmarks ──────────────────────────────────────────
+-- Named points in a document's history (`kaijutsu_types::doc_bookmark`).
+-- `blocks` is the codec-encoded block list as it stood at `version`;
+-- frontiers are replica-local, so the copy is what a bookmark reads,
+-- diffs and reverts against. Bookmarks go with their document.
+CREATE TABLE IF NOT EXISTS doc_bookmarks (
+    document_id  BLOB    NOT NULL,
+    name         TEXT    NOT NULL,
+    version      INTEGER NOT NULL,
+    block_count  INTEGER NOT NULL,
+    blocks       BLOB    NOT NULL,
+    created_by   BLOB    NOT NULL,
+    created_at   INTEGER NOT NULL,
+    PRIMARY KEY (document_id, name),
+    FOREIGN KEY (document_id) REFERENCES documents(document_id) ON DELETE CASCADE
+);
+
+-- Username → principal for resolving `@username` mentions. Upserted whenever
+-- a principal connects; the auth DB stays the authority, this is the kernel's
+-- own view of who has sat down here.
+CREATE TABLE IF NOT EXISTS seats (
+    principal_id  BLOB    NOT NULL PRIMARY KEY,
+    username      TEXT    NOT NULL,
+    last_seen_at  INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_seats_username ON seats(username);
+
+-- ── Principal preferences ───────────────────────────────────────
+-- Per-principal key/value defaults (`kaijutsu_types::prefs`): default model
+-- and consent for new contexts, plus opaque `ui.*` client settings. Keyed by
+-- principal so they follow a user across devices.
+CREATE TABLE IF NOT EXISTS principal_prefs (
+    principal_id  BLOB    NOT NULL,
+    key           TEXT    NOT NULL,
+    value         TEXT    NOT NULL,
+    updated_at    INTEGER NOT NULL,
+    PRIMARY KEY (principal_id, key)
+);
+
+-- ── Consent audit log ───────────────────────────────────────────
+-- Append-only record of consent decisions (`kaijutsu_types::consent`). Each
+-- row's `hash` covers the previous row's, and `signature` is an HMAC of the
+-- hash under the log key, which lives in a file outside this database
+-- (`consent_log::load_or_create_key`); `consent_log::verify` walks the chain.
+-- Nothing in KernelDb updates or deletes these rows.
+CREATE TABLE IF NOT EXISTS consent_log (
+    seq          INTEGER PRIMARY KEY,
+    at           INTEGER NOT NULL,
+    principal_id BLOB    NOT NULL,
+    context_id   BLOB,
+    action       TEXT    NOT NULL,
+    params_hash  TEXT    NOT NULL,
+    verdict      TEXT    NOT NULL,
+    basis        TEXT    NOT NULL,
+    mode         TEXT    NOT NULL,
+    prev_hash    TEXT    NOT NULL,
+    hash         TEXT    NOT NULL,
+    signature    TEXT    NOT NULL
+);
+
+-- The last entry's seq and hash, signed and rewritten with every append, so
+-- rows cut off the end of the log don't go unnoticed.
+CREATE TABLE IF NOT EXISTS consent_log_head (
+    singleton  INTEGER NOT NULL PRIMARY KEY DEFAULT 1
+        CHECK (singleton = 1),
+    seq        INTEGER NOT NULL,
+    hash       TEXT    NOT NULL,
+    signature  TEXT    NOT NULL
+);
 "#;
 
 // ============================================================================
@@ -1114,6 +1405,8 @@ fn is_duplicate_column_error(e: &rusqlite::Error) -> bool {
 /// SQLite database for kernel context metadata.
 pub struct KernelDb {
     conn: Connection,
+    /// Signs the consent log; see [`set_consent_key`](Self::set_consent_key).
+    consent_key: Option<[u8; 32]>,
 }
 
 impl KernelDb {
@@ -1144,6 +1437,7 @@ impl KernelDb {
             "ALTER TABLE contexts ADD COLUMN demoted_at INTEGER",
             "ALTER TABLE contexts ADD COLUMN paused_at INTEGER",
             "ALTER TABLE tracks ADD COLUMN deleted_at INTEGER",
+            "ALTER TABLE documents ADD COLUMN sync_generation INTEGER NOT NULL DEFAULT 0",
         ];
         for sql in alters {
             if let Err(e) = conn.execute(sql, []) {
@@ -1174,17 +1468,24 @@ impl KernelDb {
         conn.execute_batch(SCHEMA)?;
         Self::apply_additive_migrations(&conn)?;
         Self::ensure_singleton_kernel(&conn)?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            consent_key: None,
+        })
     }
 
-    /// Create an in-memory database (for testing).
+    /// Create an in-memory database (for testing). Its consent log is signed
+    /// with a key that dies with it.
     pub fn in_memory() -> KernelDbResult<Self> {
         let conn = Connection::open_in_memory()?;
         Self::init_connection(&conn)?;
         conn.execute_batch(SCHEMA)?;
         Self::apply_additive_migrations(&conn)?;
         Self::ensure_singleton_kernel(&conn)?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            consent_key: Some(rand::random()),
+        })
     }
 
     /// Read any legacy `rc_scripts` rows from a pre-files DB, as
@@ -1414,6 +1715,22 @@ impl KernelDb {
         Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
     }
 
+    /// Bump a document's sync generation and return the new value. Called
+    /// each time the document is loaded: its text-op seqs restart at 0 in
+    /// memory, so every load opens a new generation.
+    pub fn bump_sync_generation(&self, id: ContextId) -> KernelDbResult<u64> {
+        self.conn.execute(
+            "UPDATE documents SET sync_generation = sync_generation + 1 WHERE document_id = ?1",
+            params![blob_param(id.as_bytes())],
+        )?;
+        let generation: i64 = self.conn.query_row(
+            "SELECT sync_generation FROM documents WHERE document_id = ?1",
+            params![blob_param(id.as_bytes())],
+            |row| row.get(0),
+        )?;
+        Ok(generation as u64)
+    }
+
     /// Delete a document (CASCADE deletes snapshots, input_docs, and context).
     pub fn delete_document(&self, id: ContextId) -> KernelDbResult<bool> {
         let deleted = self.conn.execute(
@@ -1423,6 +1740,26 @@ impl KernelDb {
         Ok(deleted > 0)
     }
 
+    /// Conversation documents created before `created_before` (epoch millis)
+    /// that no context row owns. Path-bound and non-conversation documents
+    /// never have one and are left out. The cutoff skips documents mid-way
+    /// through `insert_context_with_document`.
+    pub fn orphan_documents(&self, created_before: i64) -> KernelDbResult<Vec<ContextId>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT d.document_id FROM documents d
+             LEFT JOIN contexts c ON c.context_id = d.document_id
+             WHERE c.context_id IS NULL
+               AND d.doc_kind = 'conversation'
+               AND d.path IS NULL
+               AND d.created_at < ?1
+             ORDER BY d.created_at",
+        )?;
+        let ids = stmt
+            .query_map(params![created_before], |row| read_context_id(row, 0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
     // ========================================================================
     // Op-Log Persistence
     // ========================================================================
@@ -1441,6 +1778,53 @@ impl KernelDb {
         Ok(())
     }
 
+    /// Append a batch of ops in one transaction — one commit (and one fsync)
+    /// however many rows. Each op is `(document_id, seq, payload, made_at)`,
+    /// stamped with the Unix millis it was made rather than written; each
+    /// `(context_id, ts)` in `activity` sets that context's
+    /// `last_activity_at`. All or nothing: on error no row is written.
+    pub fn append_ops<'a>(
+        &mut self,
+        ops: impl IntoIterator<Item = (ContextId, i64, &'a [u8], i64)>,
+        activity: &[(ContextId, i64)],
+    ) -> KernelDbResult<usize> {
+        let tx = self.conn.transaction()?;
+        let mut appended = 0;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO oplog (document_id, seq, payload, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (document_id, seq, payload, made_at) in ops {
+                insert.execute(params![
+                    blob_param(document_id.as_bytes()),
+                    seq,
+                    payload,
+                    made_at
+                ])?;
+                appended += 1;
+            }
+            let mut touch = tx.prepare_cached(
+                "UPDATE contexts SET last_activity_at = ?2 WHERE context_id = ?1",
+            )?;
+            for (context_id, ts) in activity {
+                touch.execute(params![blob_param(context_id.as_bytes()), ts])?;
+            }
+        }
+        tx.commit()?;
+        Ok(appended)
+    }
+
+    /// Drop a document's oplog entries from `from_seq` on. Used by repairing
+    /// replay to cut an entry that can no longer be applied (and everything
+    /// after it, which was written against it). Returns the number removed.
+    pub fn delete_oplog_from(&self, document_id: ContextId, from_seq: i64) -> KernelDbResult<usize> {
+        Ok(self.conn.execute(
+            "DELETE FROM oplog WHERE document_id = ?1 AND seq >= ?2",
+            params![blob_param(document_id.as_bytes()), from_seq],
+        )?)
+    }
+
     /// Load oplog entries after a given seq (for replay after snapshot restore).
     pub fn load_oplog_since(
         &self,
@@ -1459,6 +1843,41 @@ impl KernelDb {
         Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
     }
 
+    /// Journal entries per time bucket: `buckets` buckets of `bucket_ms`
+    /// from `start` (Unix millis). Entries a compaction folded into a
+    /// snapshot are gone and not counted.
+    pub fn count_oplog_buckets(
+        &self,
+        document_id: ContextId,
+        start: u64,
+        bucket_ms: u64,
+        buckets: usize,
+    ) -> KernelDbResult<Vec<u32>> {
+        let end = start + bucket_ms * buckets as u64;
+        let mut stmt = self.conn.prepare(
+            "SELECT (created_at - ?2) / ?3 AS bucket, COUNT(*) FROM oplog
+             WHERE document_id = ?1 AND created_at >= ?2 AND created_at < ?4
+             GROUP BY bucket",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                blob_param(document_id.as_bytes()),
+                start as i64,
+                bucket_ms as i64,
+                end as i64
+            ],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+        )?;
+        let mut counts = vec![0; buckets];
+        for row in rows {
+            let (bucket, count) = row?;
+            if let Some(slot) = counts.get_mut(bucket as usize) {
+                *slot = count as u32;
+            }
+        }
+        Ok(counts)
+    }
+
     /// Load the latest compaction snapshot for a document.
     pub fn load_latest_snapshot(
         &self,
@@ -1536,6 +1955,34 @@ impl KernelDb {
         Ok(row)
     }
 
+    /// Prove the database still takes writes: take the write lock and touch
+    /// the kernel row in a transaction that is then rolled back. For the
+    /// server's liveness probe; a read-only or locked file fails here.
+    pub fn probe_writable(&mut self) -> KernelDbResult<()> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+        tx.execute("UPDATE kernel SET label = label", [])?;
+        Ok(())
+    }
+
+    /// Set how hard SQLite syncs commits to disk for this connection.
+    pub fn set_sync_policy(&self, policy: SyncPolicy) -> KernelDbResult<()> {
+        self.conn
+            .execute_batch(&format!("PRAGMA synchronous = {}", policy.as_str().to_uppercase()))?;
+        Ok(())
+    }
+
+    /// `PRAGMA quick_check`: the problems SQLite finds in the file, empty
+    /// when it is sound. Opening the DB has already rolled back any
+    /// half-written transaction from the WAL; this catches damage beyond that.
+    pub fn quick_check(&self) -> KernelDbResult<Vec<String>> {
+        let mut stmt = self.conn.prepare("PRAGMA quick_check")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let problems = rows.collect::<SqliteResult<Vec<_>>>()?;
+        Ok(if problems == ["ok"] { Vec::new() } else { problems })
+    }
+
     // ========================================================================
     // Input Document Op-Log
     // ========================================================================
@@ -1660,8 +2107,8 @@ impl KernelDb {
     }
 
     /// Atomically create a forked context: the document row, the context row,
-    /// and the shell + env + capability-binding config copied from `source`,
-    /// all in ONE transaction.
+    /// and the shell + env + capability-binding + sandbox config copied from
+    /// `source`, all in ONE transaction.
     ///
     /// This folds `insert_context_with_document` + `fork_context_config` into a
     /// single all-or-nothing write. Calling them separately left a gap: the
@@ -1681,6 +2128,7 @@ impl KernelDb {
         let shell = self.get_context_shell(source)?;
         let env = self.get_context_env(source)?;
         let binding = self.get_context_binding(source)?;
+        let sandbox = self.get_context_sandbox(source)?;
 
         let ws_id = row.workspace_id.unwrap_or(default_workspace_id);
         let doc = DocumentRow {
@@ -1712,6 +2160,10 @@ impl KernelDb {
         if let Some(binding) = binding {
             Self::write_binding(&tx, row.context_id, &binding)?;
         }
+        // A fork is never a way out of the parent's sandbox.
+        if let Some(sandbox) = &sandbox {
+            Self::write_context_sandbox(&tx, row.context_id, sandbox)?;
+        }
         // Attachments travel with the fork: the child joins the SAME tracks as the
         // parent (docs/tracks.md §3 — "The context binds; the child inherits the
         // bind at fork"). The child re-binds via create/fork rc on the way up so
@@ -1914,6 +2366,64 @@ impl KernelDb {
         Ok(updated > 0)
     }
 
+    /// Archive every open context `filter` selects, in one transaction — the
+    /// bulk form of [`Self::archive_context`] for sweeping stale experiment
+    /// forks. Returns the selected ids in listing order; with `dry_run`
+    /// nothing is written. An empty filter selects nothing (see
+    /// [`ContextCloseFilter`]).
+    pub fn close_contexts(
+        &mut self,
+        filter: &ContextCloseFilter,
+        dry_run: bool,
+    ) -> KernelDbResult<Vec<ContextId>> {
+        let selected: Vec<ContextId> = self
+            .list_active_contexts()?
+            .into_iter()
+            .filter(|row| {
+                filter.matches(
+                    row.label.as_deref(),
+                    row.forked_from,
+                    row.last_activity_at.unwrap_or(row.created_at) as u64,
+                    row.concluded_at.is_some(),
+                    row.promoted_at.is_some(),
+                )
+            })
+            .map(|row| row.context_id)
+            .collect();
+        if dry_run || selected.is_empty() {
+            return Ok(selected);
+        }
+
+        let now = now_millis();
+        let tx = self.conn.transaction()?;
+        for id in &selected {
+            tx.execute(
+                "UPDATE contexts SET archived_at = ?1, promoted_at = NULL, demoted_at = NULL
+                 WHERE context_id = ?2 AND archived_at IS NULL",
+                params![now, blob_param(id.as_bytes())],
+            )?;
+        }
+        tx.commit()?;
+        Ok(selected)
+    }
+
+    /// Reopen an archived context without seating it: clears `archived_at`
+    /// and nothing else, so it returns to automatic placement. Returns true
+    /// if it was archived; false for an already-open context. Errors if the
+    /// context is unknown. Unlike [`Self::promote_context`]'s resurrection
+    /// this never touches the active ring, so it can't fail on a full one.
+    pub fn reopen_context(&self, id: ContextId) -> KernelDbResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE contexts SET archived_at = NULL
+             WHERE context_id = ?1 AND archived_at IS NOT NULL",
+            params![blob_param(id.as_bytes())],
+        )?;
+        if updated == 0 && self.get_context(id)?.is_none() {
+            return Err(KernelDbError::NotFound(format!("context {}", id.short())));
+        }
+        Ok(updated > 0)
+    }
+
     /// Update the lifecycle state of a context.
     pub fn update_context_state(
         &self,
@@ -2862,819 +3372,1422 @@ impl KernelDb {
     }
 
     // ========================================================================
-    // Context Tool Bindings (Phase 5, D-54)
+    // Context Sandbox Profiles
     // ========================================================================
 
-    /// Upsert a full `ContextToolBinding` for `context_id`.
-    ///
-    /// Transactional: bumps the parent row's `updated_at`, then wholesale
-    /// replaces the child `context_binding_instances` / `context_binding_names`
-    /// rows. Callers treat the binding as the unit of write. This matches the
-    /// in-memory model (`Broker::set_binding` is also wholesale) and keeps
-    /// restoration trivial (read parent → read children → fold).
-    pub fn upsert_context_binding(
-        &mut self,
-        context_id: ContextId,
-        binding: &ContextToolBinding,
-    ) -> KernelDbResult<()> {
-        let tx = self.conn.transaction()?;
-        Self::write_binding(&tx, context_id, binding)?;
-        tx.commit()?;
-        Ok(())
-    }
-
-    /// Wholesale-replace a context's binding (parent flags + every child row)
-    /// against `conn`. The caller owns the transaction: `upsert_context_binding`
-    /// wraps a fresh one, `fork_context_config` reuses its multi-write tx so
-    /// the binding lands atomically alongside the shell + env copy. Does NOT
-    /// commit — that is the caller's job.
-    fn write_binding(
+    /// Replace `context_id`'s sandbox profile against `conn` (a `Connection`
+    /// or an open `Transaction`). Does NOT commit.
+    fn write_context_sandbox(
         conn: &Connection,
         context_id: ContextId,
-        binding: &ContextToolBinding,
+        profile: &SandboxProfile,
     ) -> KernelDbResult<()> {
+        let ctx = blob_param(context_id.as_bytes());
+        let limit = |v: Option<u64>| v.map(|v| v.min(i64::MAX as u64) as i64);
         conn.execute(
-            "INSERT INTO context_bindings
-                 (context_id, all_instances, all_facades, binding_admin, binding_rc_write, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "INSERT INTO context_sandbox (context_id, fs_from_mounts, network, cpu_secs,
+                memory_bytes, file_size_bytes, open_files, processes, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
              ON CONFLICT(context_id) DO UPDATE SET
-                 all_instances    = excluded.all_instances,
-                 all_facades      = excluded.all_facades,
-                 binding_admin    = excluded.binding_admin,
-                 binding_rc_write = excluded.binding_rc_write,
-                 updated_at       = excluded.updated_at",
+                fs_from_mounts = excluded.fs_from_mounts,
+                network = excluded.network,
+                cpu_secs = excluded.cpu_secs,
+                memory_bytes = excluded.memory_bytes,
+                file_size_bytes = excluded.file_size_bytes,
+                open_files = excluded.open_files,
+                processes = excluded.processes,
+                updated_at = excluded.updated_at",
             params![
-                blob_param(context_id.as_bytes()),
-                binding.all_instances as i64,
-                binding.all_facades as i64,
-                binding.binding_admin as i64,
-                binding.binding_rc_write as i64,
+                ctx,
+                profile.fs_from_mounts,
+                profile.network,
+                limit(profile.limits.cpu_secs),
+                limit(profile.limits.memory_bytes),
+                limit(profile.limits.file_size_bytes),
+                limit(profile.limits.open_files),
+                limit(profile.limits.processes),
                 now_millis(),
             ],
         )?;
         conn.execute(
-            "DELETE FROM context_binding_instances WHERE context_id = ?1",
-            params![blob_param(context_id.as_bytes())],
-        )?;
-        conn.execute(
-            "DELETE FROM context_binding_names WHERE context_id = ?1",
-            params![blob_param(context_id.as_bytes())],
-        )?;
-        conn.execute(
-            "DELETE FROM context_binding_tools WHERE context_id = ?1",
-            params![blob_param(context_id.as_bytes())],
-        )?;
-        conn.execute(
-            "DELETE FROM context_binding_facades WHERE context_id = ?1",
-            params![blob_param(context_id.as_bytes())],
+            "DELETE FROM context_sandbox_binaries WHERE context_id = ?1",
+            params![ctx],
         )?;
-        conn.execute(
-            "DELETE FROM context_binding_authorities WHERE context_id = ?1",
-            params![blob_param(context_id.as_bytes())],
+        let mut stmt = conn.prepare(
+            "INSERT OR IGNORE INTO context_sandbox_binaries (context_id, binary) VALUES (?1, ?2)",
         )?;
-        for (idx, instance) in binding.allowed_instances.iter().enumerate() {
-            conn.execute(
-                "INSERT INTO context_binding_instances (context_id, instance_id, order_idx)
-                 VALUES (?1, ?2, ?3)",
-                params![
-                    blob_param(context_id.as_bytes()),
-                    instance.as_str(),
-                    idx as i64,
-                ],
-            )?;
-        }
-        for (idx, (instance, tool)) in binding.allowed_tools.iter().enumerate() {
-            conn.execute(
-                "INSERT INTO context_binding_tools
-                     (context_id, instance_id, original_tool, order_idx)
-                 VALUES (?1, ?2, ?3, ?4)",
-                params![
-                    blob_param(context_id.as_bytes()),
-                    instance.as_str(),
-                    tool,
-                    idx as i64,
-                ],
-            )?;
-        }
-        for (idx, facade) in binding.allowed_facades.iter().enumerate() {
-            conn.execute(
-                "INSERT INTO context_binding_facades (context_id, facade_id, order_idx)
-                 VALUES (?1, ?2, ?3)",
-                params![blob_param(context_id.as_bytes()), facade, idx as i64],
-            )?;
-        }
-        // Authorities are a set (BTreeSet iterates sorted) — no order_idx.
-        for authority in &binding.authorities {
-            conn.execute(
-                "INSERT INTO context_binding_authorities (context_id, authority)
-                 VALUES (?1, ?2)",
-                params![blob_param(context_id.as_bytes()), authority],
-            )?;
+        for binary in &profile.binaries {
+            stmt.execute(params![ctx, binary])?;
         }
-        for (visible_name, (instance, tool)) in &binding.name_map {
-            conn.execute(
-                "INSERT INTO context_binding_names
-                     (context_id, visible_name, instance_id, original_tool)
-                 VALUES (?1, ?2, ?3, ?4)",
-                params![
-                    blob_param(context_id.as_bytes()),
-                    visible_name,
-                    instance.as_str(),
-                    tool,
-                ],
-            )?;
+        Ok(())
+    }
+
+    /// Set `context_id`'s sandbox profile (validated), or remove it with
+    /// `None`.
+    pub fn set_context_sandbox(
+        &mut self,
+        context_id: ContextId,
+        profile: Option<&SandboxProfile>,
+    ) -> KernelDbResult<()> {
+        match profile {
+            Some(profile) => {
+                profile.validate().map_err(KernelDbError::Validation)?;
+                let tx = self.conn.transaction()?;
+                Self::write_context_sandbox(&tx, context_id, profile)?;
+                tx.commit()?;
+            }
+            None => {
+                self.conn.execute(
+                    "DELETE FROM context_sandbox WHERE context_id = ?1",
+                    params![blob_param(context_id.as_bytes())],
+                )?;
+            }
         }
         Ok(())
     }
 
-    /// Load a `ContextToolBinding` for `context_id`, or `None` if the context
-    /// has never been bound. Deny-by-default: callers treat `None` (and an
-    /// all-empty binding) as "grants nothing" — there is no first-touch
-    /// auto-populate.
-    pub fn get_context_binding(
+    /// `context_id`'s sandbox profile, or `None` when it has none.
+    pub fn get_context_sandbox(
         &self,
         context_id: ContextId,
-    ) -> KernelDbResult<Option<ContextToolBinding>> {
-        let flags: Option<(bool, bool, bool, bool)> = self
+    ) -> KernelDbResult<Option<SandboxProfile>> {
+        let ctx = blob_param(context_id.as_bytes());
+        let limit = |v: Option<i64>| v.map(|v| v.max(0) as u64);
+        let profile = self
             .conn
             .query_row(
-                "SELECT all_instances, all_facades, binding_admin, binding_rc_write
-                 FROM context_bindings WHERE context_id = ?1",
-                params![blob_param(context_id.as_bytes())],
+                "SELECT fs_from_mounts, network, cpu_secs, memory_bytes, file_size_bytes,
+                        open_files, processes
+                 FROM context_sandbox WHERE context_id = ?1",
+                params![ctx],
                 |row| {
-                    Ok((
-                        row.get::<_, i64>(0)? != 0,
-                        row.get::<_, i64>(1)? != 0,
-                        row.get::<_, i64>(2)? != 0,
-                        row.get::<_, i64>(3)? != 0,
-                    ))
+                    Ok(SandboxProfile {
+                        binaries: Vec::new(),
+                        fs_from_mounts: row.get(0)?,
+                        network: row.get(1)?,
+                        limits: SandboxLimits {
+                            cpu_secs: limit(row.get(2)?),
+                            memory_bytes: limit(row.get(3)?),
+                            file_size_bytes: limit(row.get(4)?),
+                            open_files: limit(row.get(5)?),
+                            processes: limit(row.get(6)?),
+                        },
+                    })
                 },
             )
-            .ok();
-        let Some((all_instances, all_facades, binding_admin, binding_rc_write)) = flags else {
+            .optional()?;
+        let Some(mut profile) = profile else {
             return Ok(None);
         };
+        let mut stmt = self.conn.prepare(
+            "SELECT binary FROM context_sandbox_binaries WHERE context_id = ?1 ORDER BY binary",
+        )?;
+        profile.binaries = stmt
+            .query_map(params![ctx], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(Some(profile))
+    }
 
-        let mut allowed_instances: Vec<InstanceId> = {
-            let mut stmt = self.conn.prepare(
-                "SELECT instance_id FROM context_binding_instances
-                 WHERE context_id = ?1
-                 ORDER BY order_idx ASC",
-            )?;
-            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
-                let s: String = row.get(0)?;
-                Ok(InstanceId::new(s))
-            })?;
-            let mut out = Vec::new();
-            for r in rows {
-                out.push(r?);
-            }
-            out
-        };
-        allowed_instances.shrink_to_fit();
-
-        let mut allowed_tools: Vec<(InstanceId, String)> = {
-            let mut stmt = self.conn.prepare(
-                "SELECT instance_id, original_tool FROM context_binding_tools
-                 WHERE context_id = ?1
-                 ORDER BY order_idx ASC",
-            )?;
-            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
-                let inst: String = row.get(0)?;
-                let tool: String = row.get(1)?;
-                Ok((InstanceId::new(inst), tool))
-            })?;
-            let mut out = Vec::new();
-            for r in rows {
-                out.push(r?);
-            }
-            out
-        };
-        allowed_tools.shrink_to_fit();
+    // ========================================================================
+    // Context Execution Budgets
+    // ========================================================================
 
-        let mut allowed_facades: Vec<String> = {
-            let mut stmt = self.conn.prepare(
-                "SELECT facade_id FROM context_binding_facades
-                 WHERE context_id = ?1
-                 ORDER BY order_idx ASC",
-            )?;
-            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
-                row.get::<_, String>(0)
-            })?;
-            let mut out = Vec::new();
-            for r in rows {
-                out.push(r?);
-            }
-            out
-        };
-        allowed_facades.shrink_to_fit();
+    /// Upsert `context_id`'s budget row against `conn`. The caller owns any
+    /// transaction and has validated the budget.
+    fn write_context_budget(
+        conn: &Connection,
+        context_id: ContextId,
+        budget: &ExecutionBudget,
+    ) -> KernelDbResult<()> {
+        let shell_secs = budget
+            .max_shell_secs_per_hour
+            .map(|v| v.min(i64::MAX as u64) as i64);
+        conn.execute(
+            "INSERT INTO context_budgets
+                 (context_id, max_tool_calls_per_hour, max_shell_secs_per_hour, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(context_id) DO UPDATE SET
+                max_tool_calls_per_hour = excluded.max_tool_calls_per_hour,
+                max_shell_secs_per_hour = excluded.max_shell_secs_per_hour,
+                updated_at = excluded.updated_at",
+            params![
+                blob_param(context_id.as_bytes()),
+                budget.max_tool_calls_per_hour.map(i64::from),
+                shell_secs,
+                now_millis(),
+            ],
+        )?;
+        Ok(())
+    }
 
-        let authorities: std::collections::BTreeSet<String> = {
-            let mut stmt = self.conn.prepare(
-                "SELECT authority FROM context_binding_authorities
-                 WHERE context_id = ?1",
-            )?;
-            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
-                row.get::<_, String>(0)
-            })?;
-            let mut out = std::collections::BTreeSet::new();
-            for r in rows {
-                out.insert(r?);
+    /// Set `context_id`'s execution budget (validated), or remove it with
+    /// `None`. An empty budget is stored as no budget.
+    pub fn set_context_budget(
+        &self,
+        context_id: ContextId,
+        budget: Option<&ExecutionBudget>,
+    ) -> KernelDbResult<()> {
+        match budget.filter(|b| !b.is_empty()) {
+            Some(budget) => {
+                budget.validate().map_err(KernelDbError::Validation)?;
+                Self::write_context_budget(&self.conn, context_id, budget)?;
             }
-            out
-        };
-
-        let mut name_map = std::collections::HashMap::new();
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT visible_name, instance_id, original_tool
-                 FROM context_binding_names
-                 WHERE context_id = ?1",
-            )?;
-            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
-                let visible: String = row.get(0)?;
-                let inst: String = row.get(1)?;
-                let tool: String = row.get(2)?;
-                Ok((visible, (InstanceId::new(inst), tool)))
-            })?;
-            for r in rows {
-                let (visible, pair) = r?;
-                name_map.insert(visible, pair);
+            None => {
+                self.conn.execute(
+                    "DELETE FROM context_budgets WHERE context_id = ?1",
+                    params![blob_param(context_id.as_bytes())],
+                )?;
             }
         }
-
-        Ok(Some(ContextToolBinding {
-            all_instances,
-            all_facades,
-            binding_admin,
-            binding_rc_write,
-            allowed_instances,
-            allowed_tools,
-            allowed_facades,
-            authorities,
-            name_map,
-        }))
-    }
-
-    /// Test-only fault injection: drop a binding detail table out from under
-    /// a context that already has a `context_bindings` parent row, so
-    /// `get_context_binding` fails partway through instead of returning
-    /// `Ok(None)`/`Ok(Some(_))`. Exists to exercise the "a real DB error must
-    /// surface, not silently become deny-all" fix in `Broker::binding_checked`
-    /// — there is no other way to force `get_context_binding` down its `Err`
-    /// path through public API alone. Never compiled outside test builds.
-    #[cfg(test)]
-    pub(crate) fn poison_context_binding_detail_table_for_test(&self) -> KernelDbResult<()> {
-        self.conn
-            .execute_batch("DROP TABLE context_binding_instances")?;
         Ok(())
     }
 
-    /// Delete the full binding for a context (cascades to instances + names).
-    /// Returns true if a parent row existed.
-    pub fn delete_context_binding(&self, context_id: ContextId) -> KernelDbResult<bool> {
-        let rows = self.conn.execute(
-            "DELETE FROM context_bindings WHERE context_id = ?1",
-            params![blob_param(context_id.as_bytes())],
-        )?;
-        Ok(rows > 0)
+    /// `context_id`'s execution budget, or `None` when it has none.
+    pub fn get_context_budget(
+        &self,
+        context_id: ContextId,
+    ) -> KernelDbResult<Option<ExecutionBudget>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT max_tool_calls_per_hour, max_shell_secs_per_hour
+                 FROM context_budgets WHERE context_id = ?1",
+                params![blob_param(context_id.as_bytes())],
+                |row| {
+                    let calls: Option<i64> = row.get(0)?;
+                    let secs: Option<i64> = row.get(1)?;
+                    Ok(ExecutionBudget {
+                        max_tool_calls_per_hour: calls.map(|v| v.clamp(0, u32::MAX as i64) as u32),
+                        max_shell_secs_per_hour: secs.map(|v| v.max(0) as u64),
+                    })
+                },
+            )
+            .optional()?)
     }
 
     // ========================================================================
-    // Hook Persistence
+    // Context Tool Presets
     // ========================================================================
 
-    /// Persist one hook entry. `insertion_idx` is computed inside the
-    /// INSERT as `MAX(insertion_idx) WHERE phase = ... + 1` so callers
-    /// don't have to track it; load order within a phase uses that same
-    /// column for tiebreak after `priority`. Fails with the SQLite
-    /// UNIQUE-violation if `hook_id` already exists — callers should
-    /// `delete_hook` first for replace semantics.
-    pub fn insert_hook(&self, row: &HookRow) -> KernelDbResult<()> {
-        let is_error: Option<i64> = row.action_is_error.map(|b| if b { 1 } else { 0 });
-        let match_context_bytes: Option<Vec<u8>> =
-            row.match_context.map(|c| c.as_bytes().to_vec());
-        let match_principal_str: Option<String> =
-            row.match_principal.map(|p| p.to_string());
-        self.conn.execute(
-            "INSERT INTO hooks (
-                hook_id, phase, priority, insertion_idx,
-                match_instance, match_tool, match_context, match_principal,
-                action_kind,
-                action_builtin_name, action_kaish_body, action_kaish_script_id,
-                action_result_text, action_is_error,
-                action_deny_reason,
-                action_log_target, action_log_level
-             ) VALUES (
-                ?1, ?2, ?3,
-                (SELECT COALESCE(MAX(insertion_idx), -1) + 1 FROM hooks WHERE phase = ?2),
-                ?4, ?5, ?6, ?7,
-                ?8,
-                ?9, ?10, ?11,
-                ?12, ?13,
-                ?14,
-                ?15, ?16
-             )",
+    /// Upsert one of `context_id`'s tool presets against `conn`. The caller
+    /// owns any transaction.
+    fn write_tool_preset(
+        conn: &Connection,
+        context_id: ContextId,
+        preset: &ToolPreset,
+    ) -> KernelDbResult<()> {
+        let params = serde_json::to_string(&preset.params)
+            .map_err(|e| KernelDbError::Validation(format!("tool preset params: {e}")))?;
+        conn.execute(
+            "INSERT INTO context_tool_presets
+                 (context_id, instance, tool, params, updated_by, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(context_id, instance, tool) DO UPDATE SET
+                params = excluded.params,
+                updated_by = excluded.updated_by,
+                updated_at = excluded.updated_at",
             params![
-                row.hook_id,
-                row.phase,
-                row.priority,
-                row.match_instance,
-                row.match_tool,
-                match_context_bytes,
-                match_principal_str,
-                row.action_kind,
-                row.action_builtin_name,
-                row.action_kaish_body,
-                row.action_kaish_script_id,
-                row.action_result_text,
-                is_error,
-                row.action_deny_reason,
-                row.action_log_target,
-                row.action_log_level,
+                blob_param(context_id.as_bytes()),
+                preset.instance.as_str(),
+                preset.tool,
+                params,
+                blob_param(preset.updated_by.as_bytes()),
+                preset.updated_at as i64,
             ],
         )?;
         Ok(())
     }
 
-    /// Delete a hook by id. Returns true if a row existed.
-    pub fn delete_hook(&self, hook_id: &str) -> KernelDbResult<bool> {
-        let rows = self
-            .conn
-            .execute("DELETE FROM hooks WHERE hook_id = ?1", params![hook_id])?;
-        Ok(rows > 0)
+    /// Set one tool preset for `context_id`, replacing any for that tool.
+    pub fn set_tool_preset(
+        &self,
+        context_id: ContextId,
+        preset: &ToolPreset,
+    ) -> KernelDbResult<()> {
+        Self::write_tool_preset(&self.conn, context_id, preset)
+    }
+
+    /// Drop `context_id`'s preset for a tool. Returns false when it had none.
+    pub fn delete_tool_preset(
+        &self,
+        context_id: ContextId,
+        instance: &InstanceId,
+        tool: &str,
+    ) -> KernelDbResult<bool> {
+        let changed = self.conn.execute(
+            "DELETE FROM context_tool_presets
+             WHERE context_id = ?1 AND instance = ?2 AND tool = ?3",
+            params![blob_param(context_id.as_bytes()), instance.as_str(), tool],
+        )?;
+        Ok(changed == 1)
+    }
+
+    /// `context_id`'s tool presets, ordered by instance and tool. A row whose
+    /// params no longer parse as a JSON object is skipped with a warning.
+    pub fn list_tool_presets(&self, context_id: ContextId) -> KernelDbResult<Vec<ToolPreset>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT instance, tool, params, updated_by, updated_at
+             FROM context_tool_presets WHERE context_id = ?1
+             ORDER BY instance, tool",
+        )?;
+        let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                read_principal_id(row, 3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })?;
+        let mut presets = Vec::new();
+        for row in rows {
+            let (instance, tool, params, updated_by, updated_at) = row?;
+            let Ok(params) = serde_json::from_str(&params) else {
+                warn!(context = %context_id, instance, tool, "skipping unparseable tool preset");
+                continue;
+            };
+            presets.push(ToolPreset {
+                instance: InstanceId::new(instance),
+                tool,
+                params,
+                updated_by,
+                updated_at: updated_at.max(0) as u64,
+            });
+        }
+        Ok(presets)
     }
 
     // ========================================================================
-    // Hook scripts (kernel-global shared kaish bodies)
+    // Context Lock Policies
     // ========================================================================
 
-    /// Insert a new hook script. Duplicate `script_id` returns
-    /// `KernelDbError::AlreadyExists` so callers can distinguish create
-    /// vs update.
-    pub fn insert_hook_script(&self, row: &HookScriptRow) -> KernelDbResult<()> {
-        self.conn
-            .execute(
-                "INSERT INTO hook_scripts (
-                    script_id, body, description,
-                    created_at, created_by, updated_at
-                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![
-                    row.script_id,
-                    row.body,
-                    row.description,
-                    row.created_at,
-                    blob_param(row.created_by.as_bytes()),
-                    row.updated_at,
+    /// Write `context_id`'s lock policy against `conn`. The default (warn) is
+    /// stored as no row.
+    fn write_context_lock_policy(
+        conn: &Connection,
+        context_id: ContextId,
+        policy: LockPolicy,
+    ) -> KernelDbResult<()> {
+        if policy == LockPolicy::default() {
+            conn.execute(
+                "DELETE FROM context_lock_policies WHERE context_id = ?1",
+                params![blob_param(context_id.as_bytes())],
+            )?;
+        } else {
+            conn.execute(
+                "INSERT INTO context_lock_policies (context_id, policy, updated_at)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(context_id) DO UPDATE SET
+                    policy = excluded.policy,
+                    updated_at = excluded.updated_at",
+                params![
+                    blob_param(context_id.as_bytes()),
+                    policy.as_str(),
+                    now_millis()
                 ],
-            )
-            .map_err(|e| {
-                map_unique_violation(e, format!("hook script '{}' already exists", row.script_id))
-            })?;
+            )?;
+        }
         Ok(())
     }
 
-    /// Replace the body (and optional description) of an existing
-    /// script, bumping `updated_at`. Returns `Ok(false)` if no row
-    /// exists with the given `script_id`.
-    pub fn update_hook_script(
+    /// Set `context_id`'s lock policy.
+    pub fn set_context_lock_policy(
         &self,
-        script_id: &str,
-        body: &str,
-        description: Option<&str>,
-    ) -> KernelDbResult<bool> {
-        let now = kaijutsu_types::now_millis() as i64;
-        let rows = self.conn.execute(
-            "UPDATE hook_scripts
-             SET body = ?2, description = ?3, updated_at = ?4
-             WHERE script_id = ?1",
-            params![script_id, body, description, now],
-        )?;
-        Ok(rows > 0)
+        context_id: ContextId,
+        policy: LockPolicy,
+    ) -> KernelDbResult<()> {
+        Self::write_context_lock_policy(&self.conn, context_id, policy)
     }
 
-    /// Look up a single script by id.
-    pub fn get_hook_script(&self, script_id: &str) -> KernelDbResult<Option<HookScriptRow>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT script_id, body, description,
-                    created_at, created_by, updated_at
-             FROM hook_scripts
-             WHERE script_id = ?1",
-        )?;
-        let mut rows = stmt.query(params![script_id])?;
-        if let Some(r) = rows.next()? {
-            Ok(Some(row_to_hook_script_row(r)?))
-        } else {
-            Ok(None)
+    /// `context_id`'s lock policy; the default when it has none.
+    pub fn get_context_lock_policy(&self, context_id: ContextId) -> KernelDbResult<LockPolicy> {
+        let policy: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT policy FROM context_lock_policies WHERE context_id = ?1",
+                params![blob_param(context_id.as_bytes())],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match policy {
+            Some(p) => p.parse().map_err(KernelDbError::Validation),
+            None => Ok(LockPolicy::default()),
         }
     }
 
-    /// Load every hook script in the DB, ordered by `script_id`.
-    /// Used by both the admin surface (`hook_script_list`) and
-    /// `Broker::hydrate_hooks_from_db` (to build the script lookup
-    /// table for resolving `action_kaish_script_id` references).
-    pub fn list_all_hook_scripts(&self) -> KernelDbResult<Vec<HookScriptRow>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT script_id, body, description,
-                    created_at, created_by, updated_at
-             FROM hook_scripts
-             ORDER BY script_id ASC",
+    // ========================================================================
+    // Context LLM Parameters
+    // ========================================================================
+
+    /// Upsert `context_id`'s generation parameters against `conn`. The caller
+    /// owns any transaction and has validated the params.
+    fn write_context_llm_params(
+        conn: &Connection,
+        context_id: ContextId,
+        llm: &LlmParams,
+    ) -> KernelDbResult<()> {
+        conn.execute(
+            "INSERT INTO context_llm_params
+                 (context_id, temperature, max_tokens, top_p, tool_choice, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(context_id) DO UPDATE SET
+                temperature = excluded.temperature,
+                max_tokens = excluded.max_tokens,
+                top_p = excluded.top_p,
+                tool_choice = excluded.tool_choice,
+                updated_at = excluded.updated_at",
+            params![
+                blob_param(context_id.as_bytes()),
+                llm.temperature,
+                llm.max_tokens.map(|v| v.min(i64::MAX as u64) as i64),
+                llm.top_p,
+                llm.tool_choice.as_ref().map(|c| c.to_string()),
+                now_millis(),
+            ],
         )?;
-        let rows = stmt.query_map([], row_to_hook_script_row)?;
-        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+        Ok(())
     }
 
-    /// Count hooks referencing this script. Callers use this to enforce
-    /// the "fail deletion when referenced" guard rather than cascading.
-    pub fn count_hooks_referencing_script(&self, script_id: &str) -> KernelDbResult<usize> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT COUNT(*) FROM hooks WHERE action_kaish_script_id = ?1")?;
-        let n: i64 = stmt.query_row(params![script_id], |r| r.get(0))?;
-        Ok(n as usize)
+    /// Set `context_id`'s generation parameters (validated), replacing what
+    /// was stored, or remove them with `None`. Empty params are stored as none.
+    pub fn set_context_llm_params(
+        &self,
+        context_id: ContextId,
+        llm: Option<&LlmParams>,
+    ) -> KernelDbResult<()> {
+        match llm.filter(|p| !p.is_empty()) {
+            Some(llm) => {
+                llm.validate().map_err(KernelDbError::Validation)?;
+                Self::write_context_llm_params(&self.conn, context_id, llm)?;
+            }
+            None => {
+                self.conn.execute(
+                    "DELETE FROM context_llm_params WHERE context_id = ?1",
+                    params![blob_param(context_id.as_bytes())],
+                )?;
+            }
+        }
+        Ok(())
     }
 
-    /// Delete a hook script. Caller must verify
-    /// `count_hooks_referencing_script == 0` first to avoid orphaning
-    /// `hooks.action_kaish_script_id` references — the schema has no FK
-    /// constraint by design (FKs across SQLite ATTACH boundaries hurt;
-    /// the guard lives in the application layer).
-    pub fn delete_hook_script(&self, script_id: &str) -> KernelDbResult<bool> {
-        let rows = self
+    /// `context_id`'s generation parameters, or `None` when it has none.
+    pub fn get_context_llm_params(
+        &self,
+        context_id: ContextId,
+    ) -> KernelDbResult<Option<LlmParams>> {
+        Ok(self
             .conn
-            .execute("DELETE FROM hook_scripts WHERE script_id = ?1", params![script_id])?;
-        Ok(rows > 0)
-    }
-
-    /// Load every persisted hook row, ordered by
-    /// `(phase ASC, priority ASC, insertion_idx ASC)`. The broker walks
-    /// these in order and pushes each onto the matching `HookTable`,
-    /// reconstructing the Vec insertion order from before the restart.
-    pub fn load_all_hooks(&self) -> KernelDbResult<Vec<HookRow>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT hook_id, phase, priority,
-                    match_instance, match_tool, match_context, match_principal,
-                    action_kind,
-                    action_builtin_name, action_kaish_body, action_kaish_script_id,
-                    action_result_text, action_is_error,
-                    action_deny_reason,
-                    action_log_target, action_log_level
-             FROM hooks
-             ORDER BY phase ASC, priority ASC, insertion_idx ASC",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            let match_context_bytes: Option<Vec<u8>> = row.get(5)?;
-            let match_context = match match_context_bytes {
-                Some(bytes) => Some(ContextId::try_from_slice(&bytes).ok_or_else(|| {
-                    rusqlite::Error::FromSqlConversionFailure(
-                        5,
-                        rusqlite::types::Type::Blob,
-                        "invalid ContextId bytes in hooks.match_context".into(),
-                    )
-                })?),
-                None => None,
-            };
-            let match_principal_str: Option<String> = row.get(6)?;
-            let match_principal = match match_principal_str {
-                Some(s) => Some(PrincipalId::parse(&s).map_err(|e| {
-                    rusqlite::Error::FromSqlConversionFailure(
-                        6,
-                        rusqlite::types::Type::Text,
-                        format!("invalid PrincipalId in hooks.match_principal: {e}").into(),
-                    )
-                })?),
-                None => None,
-            };
-            let is_error_int: Option<i64> = row.get(12)?;
-            let action_is_error = is_error_int.map(|i| i != 0);
-            Ok(HookRow {
-                hook_id: row.get(0)?,
-                phase: row.get(1)?,
-                priority: row.get(2)?,
-                match_instance: row.get(3)?,
-                match_tool: row.get(4)?,
-                match_context,
-                match_principal,
-                action_kind: row.get(7)?,
-                action_builtin_name: row.get(8)?,
-                action_kaish_body: row.get(9)?,
-                action_kaish_script_id: row.get(10)?,
-                action_result_text: row.get(11)?,
-                action_is_error,
-                action_deny_reason: row.get(13)?,
-                action_log_target: row.get(14)?,
-                action_log_level: row.get(15)?,
-            })
-        })?;
-        let mut out = Vec::new();
-        for r in rows {
-            out.push(r?);
-        }
-        Ok(out)
+            .query_row(
+                "SELECT temperature, max_tokens, top_p, tool_choice
+                 FROM context_llm_params WHERE context_id = ?1",
+                params![blob_param(context_id.as_bytes())],
+                |row| {
+                    let max_tokens: Option<i64> = row.get(1)?;
+                    let tool_choice: Option<String> = row.get(3)?;
+                    Ok(LlmParams {
+                        temperature: row.get(0)?,
+                        max_tokens: max_tokens.map(|v| v.max(1) as u64),
+                        top_p: row.get(2)?,
+                        tool_choice: tool_choice.and_then(|c| c.parse().ok()),
+                    })
+                },
+            )
+            .optional()?)
     }
 
     // ========================================================================
-    // Context Environment Variables
+    // Context Assembly
     // ========================================================================
 
-    /// Write one env var against `conn` (a `Connection` or an open
-    /// `Transaction`). Shared by `set_context_env` and `fork_context_config`.
-    fn write_context_env(
+    fn write_context_assembly(
         conn: &Connection,
         context_id: ContextId,
-        key: &str,
-        value: &str,
+        assembly: &Assembly,
     ) -> KernelDbResult<()> {
         conn.execute(
-            "INSERT INTO context_env (context_id, key, value)
+            "INSERT INTO context_assembly (context_id, strategy, updated_at)
              VALUES (?1, ?2, ?3)
-             ON CONFLICT(context_id, key) DO UPDATE SET value = excluded.value",
-            params![blob_param(context_id.as_bytes()), key, value],
+             ON CONFLICT(context_id) DO UPDATE SET
+                strategy = excluded.strategy,
+                updated_at = excluded.updated_at",
+            params![
+                blob_param(context_id.as_bytes()),
+                assembly.to_string(),
+                now_millis(),
+            ],
         )?;
         Ok(())
     }
 
-    /// Set a single environment variable for a context (upsert).
-    pub fn set_context_env(
+    /// Set `context_id`'s prompt-assembly strategy, or remove it with `None`
+    /// (back to the hydration policy's window, or `linear`).
+    pub fn set_context_assembly(
         &self,
         context_id: ContextId,
-        key: &str,
-        value: &str,
+        assembly: Option<&Assembly>,
     ) -> KernelDbResult<()> {
-        Self::write_context_env(&self.conn, context_id, key, value)
+        match assembly {
+            Some(assembly) => Self::write_context_assembly(&self.conn, context_id, assembly)?,
+            None => {
+                self.conn.execute(
+                    "DELETE FROM context_assembly WHERE context_id = ?1",
+                    params![blob_param(context_id.as_bytes())],
+                )?;
+            }
+        }
+        Ok(())
     }
 
-    /// Get all environment variables for a context.
-    pub fn get_context_env(&self, context_id: ContextId) -> KernelDbResult<Vec<ContextEnvRow>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT context_id, key, value FROM context_env
-             WHERE context_id = ?1 ORDER BY key",
-        )?;
-        let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
-            Ok(ContextEnvRow {
-                context_id: read_context_id(row, 0)?,
-                key: row.get(1)?,
-                value: row.get(2)?,
+    /// `context_id`'s stored assembly strategy, or `None` when it has none. An
+    /// unparseable row is corruption and fails loudly, like a corrupt
+    /// hydration policy: guessing would change what the model sees.
+    pub fn get_context_assembly(&self, context_id: ContextId) -> KernelDbResult<Option<Assembly>> {
+        let spec: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT strategy FROM context_assembly WHERE context_id = ?1",
+                params![blob_param(context_id.as_bytes())],
+                |row| row.get(0),
+            )
+            .optional()?;
+        spec.map(|spec| {
+            spec.parse().map_err(|e| {
+                KernelDbError::Validation(format!(
+                    "context {} assembly strategy {spec:?} is unparseable ({e}) — corrupt",
+                    context_id.short()
+                ))
             })
-        })?;
-        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+        })
+        .transpose()
     }
 
-    /// Delete a single environment variable. Returns true if it existed.
-    pub fn delete_context_env(&self, context_id: ContextId, key: &str) -> KernelDbResult<bool> {
-        let deleted = self.conn.execute(
-            "DELETE FROM context_env WHERE context_id = ?1 AND key = ?2",
-            params![blob_param(context_id.as_bytes()), key],
-        )?;
-        Ok(deleted > 0)
-    }
+    // ========================================================================
+    // Digest Subscriptions
+    // ========================================================================
 
-    /// Delete all environment variables for a context. Returns count deleted.
-    pub fn clear_context_env(&self, context_id: ContextId) -> KernelDbResult<u64> {
-        let deleted = self.conn.execute(
-            "DELETE FROM context_env WHERE context_id = ?1",
-            params![blob_param(context_id.as_bytes())],
+    /// Insert or replace the subscription for its (subscriber, source) pair.
+    pub fn upsert_digest_subscription(&self, sub: &DigestSubscription) -> KernelDbResult<()> {
+        self.conn.execute(
+            "INSERT INTO digest_subscriptions (subscriber_id, source_id, every_secs,
+                 on_milestone, focus, created_by, created_at, last_digest_at, last_block)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(subscriber_id, source_id) DO UPDATE SET
+                every_secs = excluded.every_secs,
+                on_milestone = excluded.on_milestone,
+                focus = excluded.focus,
+                last_digest_at = excluded.last_digest_at,
+                last_block = excluded.last_block",
+            params![
+                blob_param(sub.subscriber.as_bytes()),
+                blob_param(sub.source.as_bytes()),
+                sub.every_secs,
+                sub.on_milestone,
+                sub.focus,
+                blob_param(sub.created_by.as_bytes()),
+                sub.created_at as i64,
+                sub.last_digest_at.map(|t| t as i64),
+                sub.last_block.map(|b| b.to_key()),
+            ],
         )?;
-        Ok(deleted as u64)
+        Ok(())
     }
 
-    /// Copy all env vars from source to target. Returns count copied.
-    pub fn copy_context_env(&self, source: ContextId, target: ContextId) -> KernelDbResult<u64> {
-        let vars = self.get_context_env(source)?;
-        for var in &vars {
-            self.set_context_env(target, &var.key, &var.value)?;
-        }
-        Ok(vars.len() as u64)
-    }
-
-    // ========================================================================
-    // Claude cache breakpoints (per-context policy)
-    // ========================================================================
-
-    /// Append a cache breakpoint for `context_id`. The breakpoint takes the
-    /// next available `seq` slot, preserving declaration order across
-    /// subsequent reads. Storage is liberal — the 4-cap and dedupe live
-    /// in the Claude wire layer (`crate::llm::claude::build::plan_cache`),
-    /// not here, so rc populators can over-spec without storage rejecting
-    /// them.
-    pub fn add_cache_breakpoint(
+    /// Drop `subscriber`'s subscription to `source`. Returns whether one existed.
+    pub fn delete_digest_subscription(
         &self,
-        context_id: ContextId,
-        target: &CacheTarget,
-    ) -> KernelDbResult<i64> {
-        let next_seq: i64 = self
-            .conn
-            .query_row(
-                "SELECT COALESCE(MAX(seq), -1) + 1
-                 FROM cache_breakpoints WHERE context_id = ?1",
-                params![blob_param(context_id.as_bytes())],
-                |row| row.get(0),
-            )?;
-        let (kind, index, ttl) = encode_cache_target(target);
-        self.conn.execute(
-            "INSERT INTO cache_breakpoints
-                 (context_id, seq, target_kind, target_index, ttl)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+        subscriber: ContextId,
+        source: ContextId,
+    ) -> KernelDbResult<bool> {
+        let n = self.conn.execute(
+            "DELETE FROM digest_subscriptions WHERE subscriber_id = ?1 AND source_id = ?2",
             params![
-                blob_param(context_id.as_bytes()),
-                next_seq,
-                kind,
-                index,
-                ttl,
+                blob_param(subscriber.as_bytes()),
+                blob_param(source.as_bytes())
             ],
         )?;
-        Ok(next_seq)
+        Ok(n > 0)
     }
 
-    /// Read all cache breakpoints for `context_id`, ordered by insertion
-    /// (`seq` ASC). Rows with an unrecognized `target_kind` or `ttl`
-    /// are skipped with a `tracing::warn!` — storage is forwards-compatible
-    /// with future variants, but Claude's build layer must not see them.
-    pub fn list_cache_breakpoints(
-        &self,
-        context_id: ContextId,
-    ) -> KernelDbResult<Vec<CacheTarget>> {
+    /// Every digest subscription, oldest first — loaded into the
+    /// `DriftRouter` at startup.
+    pub fn list_digest_subscriptions(&self) -> KernelDbResult<Vec<DigestSubscription>> {
         let mut stmt = self.conn.prepare(
-            "SELECT target_kind, target_index, ttl
-             FROM cache_breakpoints
-             WHERE context_id = ?1
-             ORDER BY seq ASC",
-        )?;
-        let rows = stmt
-            .query_map(params![blob_param(context_id.as_bytes())], |row| {
-                let kind: String = row.get(0)?;
-                let index: Option<i64> = row.get(1)?;
-                let ttl: String = row.get(2)?;
-                Ok((kind, index, ttl))
-            })?
-            .collect::<SqliteResult<Vec<_>>>()?;
-        let mut out = Vec::with_capacity(rows.len());
-        for (kind, index, ttl) in rows {
-            match decode_cache_target(&kind, index, &ttl) {
-                Some(t) => out.push(t),
-                None => {
-                    warn!(
-                        context = %context_id.short(),
-                        target_kind = %kind,
-                        target_index = ?index,
-                        ttl = %ttl,
-                        "cache breakpoint row dropped: unrecognized fields"
-                    );
-                }
-            }
-        }
-        Ok(out)
-    }
-
-    /// Delete all cache breakpoints for `context_id`. Returns the row count.
-    /// Used by rc-on-drift scripts that want to clear-then-rebuild after a
-    /// conversation reshape (compact, model swap, doc inject).
-    pub fn clear_cache_breakpoints(&self, context_id: ContextId) -> KernelDbResult<u64> {
-        let deleted = self.conn.execute(
-            "DELETE FROM cache_breakpoints WHERE context_id = ?1",
-            params![blob_param(context_id.as_bytes())],
+            "SELECT subscriber_id, source_id, every_secs, on_milestone, focus, created_by,
+                    created_at, last_digest_at, last_block
+             FROM digest_subscriptions ORDER BY created_at, rowid",
         )?;
-        Ok(deleted as u64)
+        let rows = stmt.query_map([], |row| {
+            let created_at: i64 = row.get(6)?;
+            let last_digest_at: Option<i64> = row.get(7)?;
+            let last_block: Option<String> = row.get(8)?;
+            let last_block = last_block
+                .map(|key| {
+                    BlockId::from_key(&key).ok_or_else(|| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            8,
+                            rusqlite::types::Type::Text,
+                            format!("invalid block key '{key}'").into(),
+                        )
+                    })
+                })
+                .transpose()?;
+            Ok(DigestSubscription {
+                subscriber: read_context_id(row, 0)?,
+                source: read_context_id(row, 1)?,
+                every_secs: row.get(2)?,
+                on_milestone: row.get(3)?,
+                focus: row.get(4)?,
+                created_by: read_principal_id(row, 5)?,
+                created_at: created_at as u64,
+                last_digest_at: last_digest_at.map(|t| t as u64),
+                last_block,
+            })
+        })?;
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
     }
-}
 
-/// Default hydration window (in blocks) applied as a backstop when a context
-/// becomes beat-driven without an explicit policy — keeps a musician's per-turn
-/// hydration bounded so an at-tempo driver can't grow the wire history without
-/// limit (the crash-loop this guards). Matches the rc default (`kj context
-/// hydrate --window 16` in assets/defaults/rc/musician/…). See docs/chameleon.md.
-///
-/// Free-standing (not an associated const) so both `kj/fork.rs` (same crate,
-/// `crate::kernel_db::DEFAULT_HYDRATION_WINDOW`) and `kaijutsu-server`
-/// (`kaijutsu_kernel::kernel_db::DEFAULT_HYDRATION_WINDOW`) can import it by
-/// module path without naming `KernelDb`.
-pub const DEFAULT_HYDRATION_WINDOW: u32 = 16;
+    // ========================================================================
+    // Context KV
+    // ========================================================================
 
-impl KernelDb {
-    /// Set (upsert) the hydration window policy for `context_id`: from now on the
-    /// conversation hydrates only `[0, marker] ∪ last-window_size`, not its whole
-    /// history. `marker` is the pinned-prefix end P (a durable block — `[0,P]`
-    /// always hydrates and stays cache-stable); `window_size` is the sliding tail
-    /// W. The cost guard for endless musician logs (design: `docs/chameleon.md`,
-    /// the hydration marker). Upserted at musician-create and on a durable
-    /// revision (marker advance) — NOT per turn; the tail slides in memory.
-    pub fn set_hydration_policy(
-        &self,
-        context_id: ContextId,
-        marker: BlockId,
-        window_size: u32,
-    ) -> KernelDbResult<()> {
+    /// Replace `context_id`'s serialized KV document.
+    pub fn save_context_kv(&self, context_id: ContextId, state: &[u8]) -> KernelDbResult<()> {
         self.conn.execute(
-            "INSERT INTO context_hydration (context_id, marker, window_size)
-             VALUES (?1, ?2, ?3)
-             ON CONFLICT(context_id) DO UPDATE SET marker = ?2, window_size = ?3",
-            params![
-                blob_param(context_id.as_bytes()),
-                marker.to_key(),
-                window_size,
-            ],
+            "INSERT INTO context_kv (context_id, state, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(context_id) DO UPDATE SET
+                state = excluded.state,
+                updated_at = excluded.updated_at",
+            params![blob_param(context_id.as_bytes()), state, now_millis()],
         )?;
         Ok(())
     }
 
-    /// Read the hydration window policy for `context_id`, or `None` when unset —
-    /// `None` means hydrate everything (the default; every non-musician context).
-    /// A *present but malformed* row (window < 1, or a marker that no longer
-    /// parses) is corruption, and returns `Err(Validation)` so the caller fails
-    /// the turn loudly. Silently degrading corrupt config to "hydrate everything"
-    /// would disable the cost guard on a context driving at tempo (unbounded
-    /// spend) — a silent fallback on a safety mechanism. Absent row = legible
-    /// default (`Ok(None)`); malformed presence = loud failure.
-    pub fn get_hydration_policy(
-        &self,
-        context_id: ContextId,
-    ) -> KernelDbResult<Option<(BlockId, u32)>> {
-        let row = self
+    /// `context_id`'s serialized KV document, or `None` if it never wrote one.
+    pub fn load_context_kv(&self, context_id: ContextId) -> KernelDbResult<Option<Vec<u8>>> {
+        Ok(self
             .conn
             .query_row(
-                "SELECT marker, window_size FROM context_hydration WHERE context_id = ?1",
+                "SELECT state FROM context_kv WHERE context_id = ?1",
                 params![blob_param(context_id.as_bytes())],
-                |row| {
-                    let marker: String = row.get(0)?;
-                    let window: i64 = row.get(1)?;
-                    Ok((marker, window))
-                },
+                |row| row.get(0),
             )
-            .optional()?;
-        let Some((marker_key, window)) = row else {
-            return Ok(None);
-        };
-        // A 0/negative window or an unparseable marker is CORRUPT stored state,
-        // not a legible policy. Refuse loudly (the caller fails the turn) rather
-        // than silently degrade to full history — disabling the cost guard on a
-        // context driving at tempo is a silent fallback on a safety mechanism.
-        if window < 1 {
-            return Err(KernelDbError::Validation(format!(
-                "context {} hydration policy has window {window} (< 1) — corrupt",
-                context_id.short()
-            )));
-        }
-        match BlockId::from_key(&marker_key) {
-            Some(marker) => Ok(Some((marker, window as u32))),
-            None => Err(KernelDbError::Validation(format!(
-                "context {} hydration marker {marker_key:?} is unparseable — corrupt",
-                context_id.short()
-            ))),
-        }
-    }
-
-    /// Clear the hydration policy for `context_id` → revert to hydrating
-    /// everything. Returns the row count (0 or 1).
-    pub fn clear_hydration_policy(&self, context_id: ContextId) -> KernelDbResult<u64> {
-        let deleted = self.conn.execute(
-            "DELETE FROM context_hydration WHERE context_id = ?1",
-            params![blob_param(context_id.as_bytes())],
-        )?;
-        Ok(deleted as u64)
+            .optional()?)
     }
 
     // ========================================================================
-    // Tracks (clock domains — docs/tracks.md Stage 1)
+    // Context Tool Bindings (Phase 5, D-54)
     // ========================================================================
 
-    /// Upsert the durable `PersistedTrack` row. Called by the scheduler on every
-    /// policy mutation (attach, tempo, playhead advance) so the row always
-    /// reflects the running state and a cold-start can reconstruct it.
-    /// In-place (PK is `track_id`): a tempo change or playhead advance updates
-    /// one row without inserting a duplicate.
-    pub fn upsert_track(&self, t: &PersistedTrack) -> KernelDbResult<()> {
-        Self::write_track(&self.conn, t)
+    /// Upsert a full `ContextToolBinding` for `context_id`.
+    ///
+    /// Transactional: bumps the parent row's `updated_at`, then wholesale
+    /// replaces the child `context_binding_instances` / `context_binding_names`
+    /// rows. Callers treat the binding as the unit of write. This matches the
+    /// in-memory model (`Broker::set_binding` is also wholesale) and keeps
+    /// restoration trivial (read parent → read children → fold).
+    pub fn upsert_context_binding(
+        &mut self,
+        context_id: ContextId,
+        binding: &ContextToolBinding,
+    ) -> KernelDbResult<()> {
+        let tx = self.conn.transaction()?;
+        Self::write_binding(&tx, context_id, binding)?;
+        tx.commit()?;
+        Ok(())
     }
 
-    /// Upsert a track row against `conn` (a `&Connection` or `&Transaction` via
-    /// deref). Shared by `upsert_track` and transactional callers. Does NOT commit.
-    fn write_track(conn: &Connection, t: &PersistedTrack) -> KernelDbResult<()> {
-        // `score_context_id` is set-once: written on INSERT, omitted from the
-        // ON CONFLICT UPDATE, so the frequent tempo/playhead upserts can't null it.
-        // `clock_kind` is the opposite — MUTABLE, in the UPDATE SET, so a driver
-        // swap (system → modeled) persists (docs/tracks.md Stage 3).
+    /// Wholesale-replace a context's binding (parent flags + every child row)
+    /// against `conn`. The caller owns the transaction: `upsert_context_binding`
+    /// wraps a fresh one, `fork_context_config` reuses its multi-write tx so
+    /// the binding lands atomically alongside the shell + env copy. Does NOT
+    /// commit — that is the caller's job.
+    fn write_binding(
+        conn: &Connection,
+        context_id: ContextId,
+        binding: &ContextToolBinding,
+    ) -> KernelDbResult<()> {
         conn.execute(
-            "INSERT INTO tracks
-                 (track_id, period_ms, beats_per_phrase, playhead_tick, playing, score_context_id, clock_kind)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-             ON CONFLICT(track_id) DO UPDATE SET
-                 period_ms = ?2, beats_per_phrase = ?3,
-                 playhead_tick = ?4, playing = ?5, clock_kind = ?7",
+            "INSERT INTO context_bindings
+                 (context_id, all_instances, all_facades, binding_admin, binding_rc_write, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(context_id) DO UPDATE SET
+                 all_instances    = excluded.all_instances,
+                 all_facades      = excluded.all_facades,
+                 binding_admin    = excluded.binding_admin,
+                 binding_rc_write = excluded.binding_rc_write,
+                 updated_at       = excluded.updated_at",
             params![
-                t.track_id,
-                t.period_ms as i64,
-                t.beats_per_phrase as i64,
-                t.playhead_tick,
-                t.playing as i64,
-                t.score_context_id.map(|c| c.as_bytes().to_vec()),
-                t.clock_kind,
+                blob_param(context_id.as_bytes()),
+                binding.all_instances as i64,
+                binding.all_facades as i64,
+                binding.binding_admin as i64,
+                binding.binding_rc_write as i64,
+                now_millis(),
             ],
         )?;
-        Ok(())
-    }
-
-    /// Read the persisted track for `track_id`, or `None` when no such track exists.
-    /// A *present but malformed* row — an empty `track_id` or a zero `period_ms`
+        conn.execute(
+            "DELETE FROM context_binding_instances WHERE context_id = ?1",
+            params![blob_param(context_id.as_bytes())],
+        )?;
+        conn.execute(
+            "DELETE FROM context_binding_names WHERE context_id = ?1",
+            params![blob_param(context_id.as_bytes())],
+        )?;
+        conn.execute(
+            "DELETE FROM context_binding_tools WHERE context_id = ?1",
+            params![blob_param(context_id.as_bytes())],
+        )?;
+        conn.execute(
+            "DELETE FROM context_binding_facades WHERE context_id = ?1",
+            params![blob_param(context_id.as_bytes())],
+        )?;
+        conn.execute(
+            "DELETE FROM context_binding_authorities WHERE context_id = ?1",
+            params![blob_param(context_id.as_bytes())],
+        )?;
+        for (idx, instance) in binding.allowed_instances.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO context_binding_instances (context_id, instance_id, order_idx)
+                 VALUES (?1, ?2, ?3)",
+                params![
+                    blob_param(context_id.as_bytes()),
+                    instance.as_str(),
+                    idx as i64,
+                ],
+            )?;
+        }
+        for (idx, (instance, tool)) in binding.allowed_tools.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO context_binding_tools
+                     (context_id, instance_id, original_tool, order_idx)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    blob_param(context_id.as_bytes()),
+                    instance.as_str(),
+                    tool,
+                    idx as i64,
+                ],
+            )?;
+        }
+        for (idx, facade) in binding.allowed_facades.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO context_binding_facades (context_id, facade_id, order_idx)
+                 VALUES (?1, ?2, ?3)",
+                params![blob_param(context_id.as_bytes()), facade, idx as i64],
+            )?;
+        }
+        // Authorities are a set (BTreeSet iterates sorted) — no order_idx.
+        for authority in &binding.authorities {
+            conn.execute(
+                "INSERT INTO context_binding_authorities (context_id, authority)
+                 VALUES (?1, ?2)",
+                params![blob_param(context_id.as_bytes()), authority],
+            )?;
+        }
+        for (visible_name, (instance, tool)) in &binding.name_map {
+            conn.execute(
+                "INSERT INTO context_binding_names
+                     (context_id, visible_name, instance_id, original_tool)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    blob_param(context_id.as_bytes()),
+                    visible_name,
+                    instance.as_str(),
+                    tool,
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Load a `ContextToolBinding` for `context_id`, or `None` if the context
+    /// has never been bound. Deny-by-default: callers treat `None` (and an
+    /// all-empty binding) as "grants nothing" — there is no first-touch
+    /// auto-populate.
+    pub fn get_context_binding(
+        &self,
+        context_id: ContextId,
+    ) -> KernelDbResult<Option<ContextToolBinding>> {
+        let flags: Option<(bool, bool, bool, bool)> = self
+            .conn
+            .query_row(
+                "SELECT all_instances, all_facades, binding_admin, binding_rc_write
+                 FROM context_bindings WHERE context_id = ?1",
+                params![blob_param(context_id.as_bytes())],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)? != 0,
+                        row.get::<_, i64>(1)? != 0,
+                        row.get::<_, i64>(2)? != 0,
+                        row.get::<_, i64>(3)? != 0,
+                    ))
+                },
+            )
+            .ok();
+        let Some((all_instances, all_facades, binding_admin, binding_rc_write)) = flags else {
+            return Ok(None);
+        };
+
+        let mut allowed_instances: Vec<InstanceId> = {
+            let mut stmt = self.conn.prepare(
+                "SELECT instance_id FROM context_binding_instances
+                 WHERE context_id = ?1
+                 ORDER BY order_idx ASC",
+            )?;
+            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
+                let s: String = row.get(0)?;
+                Ok(InstanceId::new(s))
+            })?;
+            let mut out = Vec::new();
+            for r in rows {
+                out.push(r?);
+            }
+            out
+        };
+        allowed_instances.shrink_to_fit();
+
+        let mut allowed_tools: Vec<(InstanceId, String)> = {
+            let mut stmt = self.conn.prepare(
+                "SELECT instance_id, original_tool FROM context_binding_tools
+                 WHERE context_id = ?1
+                 ORDER BY order_idx ASC",
+            )?;
+            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
+                let inst: String = row.get(0)?;
+                let tool: String = row.get(1)?;
+                Ok((InstanceId::new(inst), tool))
+            })?;
+            let mut out = Vec::new();
+            for r in rows {
+                out.push(r?);
+            }
+            out
+        };
+        allowed_tools.shrink_to_fit();
+
+        let mut allowed_facades: Vec<String> = {
+            let mut stmt = self.conn.prepare(
+                "SELECT facade_id FROM context_binding_facades
+                 WHERE context_id = ?1
+                 ORDER BY order_idx ASC",
+            )?;
+            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
+                row.get::<_, String>(0)
+            })?;
+            let mut out = Vec::new();
+            for r in rows {
+                out.push(r?);
+            }
+            out
+        };
+        allowed_facades.shrink_to_fit();
+
+        let authorities: std::collections::BTreeSet<String> = {
+            let mut stmt = self.conn.prepare(
+                "SELECT authority FROM context_binding_authorities
+                 WHERE context_id = ?1",
+            )?;
+            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
+                row.get::<_, String>(0)
+            })?;
+            let mut out = std::collections::BTreeSet::new();
+            for r in rows {
+                out.insert(r?);
+            }
+            out
+        };
+
+        let mut name_map = std::collections::HashMap::new();
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT visible_name, instance_id, original_tool
+                 FROM context_binding_names
+                 WHERE context_id = ?1",
+            )?;
+            let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
+                let visible: String = row.get(0)?;
+                let inst: String = row.get(1)?;
+                let tool: String = row.get(2)?;
+                Ok((visible, (InstanceId::new(inst), tool)))
+            })?;
+            for r in rows {
+                let (visible, pair) = r?;
+                name_map.insert(visible, pair);
+            }
+        }
+
+        Ok(Some(ContextToolBinding {
+            all_instances,
+            all_facades,
+            binding_admin,
+            binding_rc_write,
+            allowed_instances,
+            allowed_tools,
+            allowed_facades,
+            authorities,
+            name_map,
+        }))
+    }
+
+    /// Test-only fault injection: drop a binding detail table out from under
+    /// a context that already has a `context_bindings` parent row, so
+    /// `get_context_binding` fails partway through instead of returning
+    /// `Ok(None)`/`Ok(Some(_))`. Exists to exercise the "a real DB error must
+    /// surface, not silently become deny-all" fix in `Broker::binding_checked`
+    /// — there is no other way to force `get_context_binding` down its `Err`
+    /// path through public API alone. Never compiled outside test builds.
+    #[cfg(test)]
+    pub(crate) fn poison_context_binding_detail_table_for_test(&self) -> KernelDbResult<()> {
+        self.conn
+            .execute_batch("DROP TABLE context_binding_instances")?;
+        Ok(())
+    }
+
+    /// Delete the full binding for a context (cascades to instances + names).
+    /// Returns true if a parent row existed.
+    pub fn delete_context_binding(&self, context_id: ContextId) -> KernelDbResult<bool> {
+        let rows = self.conn.execute(
+            "DELETE FROM context_bindings WHERE context_id = ?1",
+            params![blob_param(context_id.as_bytes())],
+        )?;
+        Ok(rows > 0)
+    }
+
+    // ========================================================================
+    // Hook Persistence
+    // ========================================================================
+
+    /// Persist one hook entry. `insertion_idx` is computed inside the
+    /// INSERT as `MAX(insertion_idx) WHERE phase = ... + 1` so callers
+    /// don't have to track it; load order within a phase uses that same
+    /// column for tiebreak after `priority`. Fails with the SQLite
+    /// UNIQUE-violation if `hook_id` already exists — callers should
+    /// `delete_hook` first for replace semantics.
+    pub fn insert_hook(&self, row: &HookRow) -> KernelDbResult<()> {
+        let is_error: Option<i64> = row.action_is_error.map(|b| if b { 1 } else { 0 });
+        let match_context_bytes: Option<Vec<u8>> =
+            row.match_context.map(|c| c.as_bytes().to_vec());
+        let match_principal_str: Option<String> =
+            row.match_principal.map(|p| p.to_string());
+        self.conn.execute(
+            "INSERT INTO hooks (
+                hook_id, phase, priority, insertion_idx,
+                match_instance, match_tool, match_context, match_principal,
+                action_kind,
+                action_builtin_name, action_kaish_body, action_kaish_script_id,
+                action_result_text, action_is_error,
+                action_deny_reason,
+                action_log_target, action_log_level
+             ) VALUES (
+                ?1, ?2, ?3,
+                (SELECT COALESCE(MAX(insertion_idx), -1) + 1 FROM hooks WHERE phase = ?2),
+                ?4, ?5, ?6, ?7,
+                ?8,
+                ?9, ?10, ?11,
+                ?12, ?13,
+                ?14,
+                ?15, ?16
+             )",
+            params![
+                row.hook_id,
+                row.phase,
+                row.priority,
+                row.match_instance,
+                row.match_tool,
+                match_context_bytes,
+                match_principal_str,
+                row.action_kind,
+                row.action_builtin_name,
+                row.action_kaish_body,
+                row.action_kaish_script_id,
+                row.action_result_text,
+                is_error,
+                row.action_deny_reason,
+                row.action_log_target,
+                row.action_log_level,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a hook by id. Returns true if a row existed.
+    pub fn delete_hook(&self, hook_id: &str) -> KernelDbResult<bool> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM hooks WHERE hook_id = ?1", params![hook_id])?;
+        Ok(rows > 0)
+    }
+
+    // ========================================================================
+    // Hook scripts (kernel-global shared kaish bodies)
+    // ========================================================================
+
+    /// Insert a new hook script. Duplicate `script_id` returns
+    /// `KernelDbError::AlreadyExists` so callers can distinguish create
+    /// vs update.
+    pub fn insert_hook_script(&self, row: &HookScriptRow) -> KernelDbResult<()> {
+        self.conn
+            .execute(
+                "INSERT INTO hook_scripts (
+                    script_id, body, description,
+                    created_at, created_by, updated_at
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    row.script_id,
+                    row.body,
+                    row.description,
+                    row.created_at,
+                    blob_param(row.created_by.as_bytes()),
+                    row.updated_at,
+                ],
+            )
+            .map_err(|e| {
+                map_unique_violation(e, format!("hook script '{}' already exists", row.script_id))
+            })?;
+        Ok(())
+    }
+
+    /// Replace the body (and optional description) of an existing
+    /// script, bumping `updated_at`. Returns `Ok(false)` if no row
+    /// exists with the given `script_id`.
+    pub fn update_hook_script(
+        &self,
+        script_id: &str,
+        body: &str,
+        description: Option<&str>,
+    ) -> KernelDbResult<bool> {
+        let now = kaijutsu_types::now_millis() as i64;
+        let rows = self.conn.execute(
+            "UPDATE hook_scripts
+             SET body = ?2, description = ?3, updated_at = ?4
+             WHERE script_id = ?1",
+            params![script_id, body, description, now],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Look up a single script by id.
+    pub fn get_hook_script(&self, script_id: &str) -> KernelDbResult<Option<HookScriptRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT script_id, body, description,
+                    created_at, created_by, updated_at
+             FROM hook_scripts
+             WHERE script_id = ?1",
+        )?;
+        let mut rows = stmt.query(params![script_id])?;
+        if let Some(r) = rows.next()? {
+            Ok(Some(row_to_hook_script_row(r)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Load every hook script in the DB, ordered by `script_id`.
+    /// Used by both the admin surface (`hook_script_list`) and
+    /// `Broker::hydrate_hooks_from_db` (to build the script lookup
+    /// table for resolving `action_kaish_script_id` references).
+    pub fn list_all_hook_scripts(&self) -> KernelDbResult<Vec<HookScriptRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT script_id, body, description,
+                    created_at, created_by, updated_at
+             FROM hook_scripts
+             ORDER BY script_id ASC",
+        )?;
+        let rows = stmt.query_map([], row_to_hook_script_row)?;
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+    }
+
+    /// Count hooks referencing this script. Callers use this to enforce
+    /// the "fail deletion when referenced" guard rather than cascading.
+    pub fn count_hooks_referencing_script(&self, script_id: &str) -> KernelDbResult<usize> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT COUNT(*) FROM hooks WHERE action_kaish_script_id = ?1")?;
+        let n: i64 = stmt.query_row(params![script_id], |r| r.get(0))?;
+        Ok(n as usize)
+    }
+
+    /// Delete a hook script. Caller must verify
+    /// `count_hooks_referencing_script == 0` first to avoid orphaning
+    /// `hooks.action_kaish_script_id` references — the schema has no FK
+    /// constraint by design (FKs across SQLite ATTACH boundaries hurt;
+    /// the guard lives in the application layer).
+    pub fn delete_hook_script(&self, script_id: &str) -> KernelDbResult<bool> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM hook_scripts WHERE script_id = ?1", params![script_id])?;
+        Ok(rows > 0)
+    }
+
+    /// Load every persisted hook row, ordered by
+    /// `(phase ASC, priority ASC, insertion_idx ASC)`. The broker walks
+    /// these in order and pushes each onto the matching `HookTable`,
+    /// reconstructing the Vec insertion order from before the restart.
+    pub fn load_all_hooks(&self) -> KernelDbResult<Vec<HookRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT hook_id, phase, priority,
+                    match_instance, match_tool, match_context, match_principal,
+                    action_kind,
+                    action_builtin_name, action_kaish_body, action_kaish_script_id,
+                    action_result_text, action_is_error,
+                    action_deny_reason,
+                    action_log_target, action_log_level
+             FROM hooks
+             ORDER BY phase ASC, priority ASC, insertion_idx ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let match_context_bytes: Option<Vec<u8>> = row.get(5)?;
+            let match_context = match match_context_bytes {
+                Some(bytes) => Some(ContextId::try_from_slice(&bytes).ok_or_else(|| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        5,
+                        rusqlite::types::Type::Blob,
+                        "invalid ContextId bytes in hooks.match_context".into(),
+                    )
+                })?),
+                None => None,
+            };
+            let match_principal_str: Option<String> = row.get(6)?;
+            let match_principal = match match_principal_str {
+                Some(s) => Some(PrincipalId::parse(&s).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        6,
+                        rusqlite::types::Type::Text,
+                        format!("invalid PrincipalId in hooks.match_principal: {e}").into(),
+                    )
+                })?),
+                None => None,
+            };
+            let is_error_int: Option<i64> = row.get(12)?;
+            let action_is_error = is_error_int.map(|i| i != 0);
+            Ok(HookRow {
+                hook_id: row.get(0)?,
+                phase: row.get(1)?,
+                priority: row.get(2)?,
+                match_instance: row.get(3)?,
+                match_tool: row.get(4)?,
+                match_context,
+                match_principal,
+                action_kind: row.get(7)?,
+                action_builtin_name: row.get(8)?,
+                action_kaish_body: row.get(9)?,
+                action_kaish_script_id: row.get(10)?,
+                action_result_text: row.get(11)?,
+                action_is_error,
+                action_deny_reason: row.get(13)?,
+                action_log_target: row.get(14)?,
+                action_log_level: row.get(15)?,
+            })
+        })?;
+        let mut out = Vec::new();
+        for r in rows {
+            out.push(r?);
+        }
+        Ok(out)
+    }
+
+    // ========================================================================
+    // Context Environment Variables
+    // ========================================================================
+
+    /// Write one env var against `conn` (a `Connection` or an open
+    /// `Transaction`). Shared by `set_context_env` and `fork_context_config`.
+    fn write_context_env(
+        conn: &Connection,
+        context_id: ContextId,
+        key: &str,
+        value: &str,
+    ) -> KernelDbResult<()> {
+        conn.execute(
+            "INSERT INTO context_env (context_id, key, value)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(context_id, key) DO UPDATE SET value = excluded.value",
+            params![blob_param(context_id.as_bytes()), key, value],
+        )?;
+        Ok(())
+    }
+
+    /// Set a single environment variable for a context (upsert).
+    pub fn set_context_env(
+        &self,
+        context_id: ContextId,
+        key: &str,
+        value: &str,
+    ) -> KernelDbResult<()> {
+        Self::write_context_env(&self.conn, context_id, key, value)
+    }
+
+    /// Get all environment variables for a context.
+    pub fn get_context_env(&self, context_id: ContextId) -> KernelDbResult<Vec<ContextEnvRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT context_id, key, value FROM context_env
+             WHERE context_id = ?1 ORDER BY key",
+        )?;
+        let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
+            Ok(ContextEnvRow {
+                context_id: read_context_id(row, 0)?,
+                key: row.get(1)?,
+                value: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+    }
+
+    /// Delete a single environment variable. Returns true if it existed.
+    pub fn delete_context_env(&self, context_id: ContextId, key: &str) -> KernelDbResult<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM context_env WHERE context_id = ?1 AND key = ?2",
+            params![blob_param(context_id.as_bytes()), key],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Delete all environment variables for a context. Returns count deleted.
+    pub fn clear_context_env(&self, context_id: ContextId) -> KernelDbResult<u64> {
+        let deleted = self.conn.execute(
+            "DELETE FROM context_env WHERE context_id = ?1",
+            params![blob_param(context_id.as_bytes())],
+        )?;
+        Ok(deleted as u64)
+    }
+
+    /// Copy all env vars from source to target. Returns count copied.
+    pub fn copy_context_env(&self, source: ContextId, target: ContextId) -> KernelDbResult<u64> {
+        let vars = self.get_context_env(source)?;
+        for var in &vars {
+            self.set_context_env(target, &var.key, &var.value)?;
+        }
+        Ok(vars.len() as u64)
+    }
+
+    // ========================================================================
+    // Claude cache breakpoints (per-context policy)
+    // ========================================================================
+
+    /// Append a cache breakpoint for `context_id`. The breakpoint takes the
+    /// next available `seq` slot, preserving declaration order across
+    /// subsequent reads. Storage is liberal — the 4-cap and dedupe live
+    /// in the Claude wire layer (`crate::llm::claude::build::plan_cache`),
+    /// not here, so rc populators can over-spec without storage rejecting
+    /// them.
+    pub fn add_cache_breakpoint(
+        &self,
+        context_id: ContextId,
+        target: &CacheTarget,
+    ) -> KernelDbResult<i64> {
+        let next_seq: i64 = self
+            .conn
+            .query_row(
+                "SELECT COALESCE(MAX(seq), -1) + 1
+                 FROM cache_breakpoints WHERE context_id = ?1",
+                params![blob_param(context_id.as_bytes())],
+                |row| row.get(0),
+            )?;
+        let (kind, index, ttl) = encode_cache_target(target);
+        self.conn.execute(
+            "INSERT INTO cache_breakpoints
+                 (context_id, seq, target_kind, target_index, ttl)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                blob_param(context_id.as_bytes()),
+                next_seq,
+                kind,
+                index,
+                ttl,
+            ],
+        )?;
+        Ok(next_seq)
+    }
+
+    /// Read all cache breakpoints for `context_id`, ordered by insertion
+    /// (`seq` ASC). Rows with an unrecognized `target_kind` or `ttl`
+    /// are skipped with a `tracing::warn!` — storage is forwards-compatible
+    /// with future variants, but Claude's build layer must not see them.
+    pub fn list_cache_breakpoints(
+        &self,
+        context_id: ContextId,
+    ) -> KernelDbResult<Vec<CacheTarget>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT target_kind, target_index, ttl
+             FROM cache_breakpoints
+             WHERE context_id = ?1
+             ORDER BY seq ASC",
+        )?;
+        let rows = stmt
+            .query_map(params![blob_param(context_id.as_bytes())], |row| {
+                let kind: String = row.get(0)?;
+                let index: Option<i64> = row.get(1)?;
+                let ttl: String = row.get(2)?;
+                Ok((kind, index, ttl))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let mut out = Vec::with_capacity(rows.len());
+        for (kind, index, ttl) in rows {
+            match decode_cache_target(&kind, index, &ttl) {
+                Some(t) => out.push(t),
+                None => {
+                    warn!(
+                        context = %context_id.short(),
+                        target_kind = %kind,
+                        target_index = ?index,
+                        ttl = %ttl,
+                        "cache breakpoint row dropped: unrecognized fields"
+                    );
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Delete all cache breakpoints for `context_id`. Returns the row count.
+    /// Used by rc-on-drift scripts that want to clear-then-rebuild after a
+    /// conversation reshape (compact, model swap, doc inject).
+    pub fn clear_cache_breakpoints(&self, context_id: ContextId) -> KernelDbResult<u64> {
+        let deleted = self.conn.execute(
+            "DELETE FROM cache_breakpoints WHERE context_id = ?1",
+            params![blob_param(context_id.as_bytes())],
+        )?;
+        Ok(deleted as u64)
+    }
+}
+
+/// Default hydration window (in blocks) applied as a backstop when a context
+/// becomes beat-driven without an explicit policy — keeps a musician's per-turn
+/// hydration bounded so an at-tempo driver can't grow the wire history without
+/// limit (the crash-loop this guards). Matches the rc default (`kj context
+/// hydrate --window 16` in assets/defaults/rc/musician/…). See docs/chameleon.md.
+///
+/// Free-standing (not an associated const) so both `kj/fork.rs` (same crate,
+/// `crate::kernel_db::DEFAULT_HYDRATION_WINDOW`) and `kaijutsu-server`
+/// (`kaijutsu_kernel::kernel_db::DEFAULT_HYDRATION_WINDOW`) can import it by
+/// module path without naming `KernelDb`.
+pub const DEFAULT_HYDRATION_WINDOW: u32 = 16;
+
+impl KernelDb {
+    /// Set (upsert) the hydration window policy for `context_id`: from now on the
+    /// conversation hydrates only `[0, marker] ∪ last-window_size`, not its whole
+    /// history. `marker` is the pinned-prefix end P (a durable block — `[0,P]`
+    /// always hydrates and stays cache-stable); `window_size` is the sliding tail
+    /// W. The cost guard for endless musician logs (design: `docs/chameleon.md`,
+    /// the hydration marker). Upserted at musician-create and on a durable
+    /// revision (marker advance) — NOT per turn; the tail slides in memory.
+    pub fn set_hydration_policy(
+        &self,
+        context_id: ContextId,
+        marker: BlockId,
+        window_size: u32,
+    ) -> KernelDbResult<()> {
+        self.conn.execute(
+            "INSERT INTO context_hydration (context_id, marker, window_size)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(context_id) DO UPDATE SET marker = ?2, window_size = ?3",
+            params![
+                blob_param(context_id.as_bytes()),
+                marker.to_key(),
+                window_size,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Read the hydration window policy for `context_id`, or `None` when unset —
+    /// `None` means hydrate everything (the default; every non-musician context).
+    /// A *present but malformed* row (window < 1, or a marker that no longer
+    /// parses) is corruption, and returns `Err(Validation)` so the caller fails
+    /// the turn loudly. Silently degrading corrupt config to "hydrate everything"
+    /// would disable the cost guard on a context driving at tempo (unbounded
+    /// spend) — a silent fallback on a safety mechanism. Absent row = legible
+    /// default (`Ok(None)`); malformed presence = loud failure.
+    pub fn get_hydration_policy(
+        &self,
+        context_id: ContextId,
+    ) -> KernelDbResult<Option<(BlockId, u32)>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT marker, window_size FROM context_hydration WHERE context_id = ?1",
+                params![blob_param(context_id.as_bytes())],
+                |row| {
+                    let marker: String = row.get(0)?;
+                    let window: i64 = row.get(1)?;
+                    Ok((marker, window))
+                },
+            )
+            .optional()?;
+        let Some((marker_key, window)) = row else {
+            return Ok(None);
+        };
+        // A 0/negative window or an unparseable marker is CORRUPT stored state,
+        // not a legible policy. Refuse loudly (the caller fails the turn) rather
+        // than silently degrade to full history — disabling the cost guard on a
+        // context driving at tempo is a silent fallback on a safety mechanism.
+        if window < 1 {
+            return Err(KernelDbError::Validation(format!(
+                "context {} hydration policy has window {window} (< 1) — corrupt",
+                context_id.short()
+            )));
+        }
+        match BlockId::from_key(&marker_key) {
+            Some(marker) => Ok(Some((marker, window as u32))),
+            None => Err(KernelDbError::Validation(format!(
+                "context {} hydration marker {marker_key:?} is unparseable — corrupt",
+                context_id.short()
+            ))),
+        }
+    }
+
+    /// Clear the hydration policy for `context_id` → revert to hydrating
+    /// everything. Returns the row count (0 or 1).
+    pub fn clear_hydration_policy(&self, context_id: ContextId) -> KernelDbResult<u64> {
+        let deleted = self.conn.execute(
+            "DELETE FROM context_hydration WHERE context_id = ?1",
+            params![blob_param(context_id.as_bytes())],
+        )?;
+        Ok(deleted as u64)
+    }
+
+    // ========================================================================
+    // Tracks (clock domains — docs/tracks.md Stage 1)
+    // ========================================================================
+
+    /// Upsert the durable `PersistedTrack` row. Called by the scheduler on every
+    /// policy mutation (attach, tempo, playhead advance) so the row always
+    /// reflects the running state and a cold-start can reconstruct it.
+    /// In-place (PK is `track_id`): a tempo change or playhead advance updates
+    /// one row without inserting a duplicate.
+    pub fn upsert_track(&self, t: &PersistedTrack) -> KernelDbResult<()> {
+        Self::write_track(&self.conn, t)
+    }
+
+    /// Upsert a track row against `conn` (a `&Connection` or `&Transaction` via
+    /// deref). Shared by `upsert_track` and transactional callers. Does NOT commit.
+    fn write_track(conn: &Connection, t: &PersistedTrack) -> KernelDbResult<()> {
+        // `score_context_id` is set-once: written on INSERT, omitted from the
+        // ON CONFLICT UPDATE, so the frequent tempo/playhead upserts can't null it.
+        // `clock_kind` is the opposite — MUTABLE, in the UPDATE SET, so a driver
+        // swap (system → modeled) persists (docs/tracks.md Stage 3).
+        conn.execute(
+            "INSERT INTO tracks
+                 (track_id, period_ms, beats_per_phrase, playhead_tick, playing, score_context_id, clock_kind)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(track_id) DO UPDATE SET
+                 period_ms = ?2, beats_per_phrase = ?3,
+                 playhead_tick = ?4, playing = ?5, clock_kind = ?7",
+            params![
+                t.track_id,
+                t.period_ms as i64,
+                t.beats_per_phrase as i64,
+                t.playhead_tick,
+                t.playing as i64,
+                t.score_context_id.map(|c| c.as_bytes().to_vec()),
+                t.clock_kind,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Read the persisted track for `track_id`, or `None` when no such track exists.
+    /// A *present but malformed* row — an empty `track_id` or a zero `period_ms`
     /// (a beat that never advances) — is corruption and returns `Err(Validation)`
     /// so the caller refuses to reconstruct a broken clock.
     pub fn get_track(&self, track_id: &str) -> KernelDbResult<Option<PersistedTrack>> {
@@ -3689,619 +4802,1271 @@ impl KernelDb {
         let row = self
             .conn
             .query_row(
-                "SELECT period_ms, beats_per_phrase, playhead_tick, playing, score_context_id, clock_kind
-                 FROM tracks WHERE track_id = ?1",
-                params![track_id],
+                "SELECT period_ms, beats_per_phrase, playhead_tick, playing, score_context_id, clock_kind
+                 FROM tracks WHERE track_id = ?1",
+                params![track_id],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, Option<i64>>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, Option<Vec<u8>>>(4)?,
+                        row.get::<_, String>(5)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((period_ms, beats_per_phrase, playhead_tick, playing, score_blob, clock_kind)) = row
+        else {
+            return Ok(None);
+        };
+        validated_track(
+            track_id.to_string(),
+            period_ms,
+            beats_per_phrase,
+            playhead_tick,
+            playing,
+            score_blob,
+            clock_kind,
+        )
+        .map(Some)
+    }
+
+    /// List all **live** persisted tracks — tombstoned rows (`deleted_at IS NOT
+    /// NULL`) are excluded, matching `get_track`'s implicit skip (a tombstoned
+    /// row no longer answers to its original name because `tombstone_track`
+    /// renamed it aside). Order is not guaranteed.
+    pub fn list_tracks(&self) -> KernelDbResult<Vec<PersistedTrack>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT track_id, period_ms, beats_per_phrase, playhead_tick, playing, score_context_id, clock_kind
+             FROM tracks WHERE deleted_at IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, Option<Vec<u8>>>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })?;
+        let mut tracks = Vec::new();
+        for row in rows {
+            let (track_id, period_ms, beats_per_phrase, playhead_tick, playing, score_blob, clock_kind) =
+                row?;
+            // Same validating construction path as `get_track` — a corrupt row
+            // (negative field, zero period) is refused here too, not cast into a
+            // nonsense `u64` cadence that the roster would then display.
+            tracks.push(validated_track(
+                track_id,
+                period_ms,
+                beats_per_phrase,
+                playhead_tick,
+                playing,
+                score_blob,
+                clock_kind,
+            )?);
+        }
+        Ok(tracks)
+    }
+
+    /// Delete a track row by `track_id`. Cascades to all its `attachments` rows
+    /// via the FK `ON DELETE CASCADE`. Returns the row count (0 or 1).
+    pub fn delete_track(&self, track_id: &str) -> KernelDbResult<u64> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM tracks WHERE track_id = ?1", params![track_id])?;
+        Ok(deleted as u64)
+    }
+
+    /// Tombstone a track: **rename it aside** to `{track_id}~tombstone-{deleted_at}`
+    /// and stamp `deleted_at`, atomically, in one transaction. `kj transport
+    /// delete`'s DB half — the scheduler half (stop the clock, detach every
+    /// attached context, tear down the live `Timeline`, drop the in-memory
+    /// `TrackState`) has already run by the time this is called.
+    ///
+    /// A rename, not a `DELETE`: the row (and the durable `tracks` history it
+    /// carries — `score_context_id` above all) survives, just invisible under
+    /// its old name. `list_tracks`/`get_track` (by construction — the PK no
+    /// longer matches) stop returning it, so name reuse via `kj transport attach
+    /// --track <name>` starts a genuinely fresh track + score. Recovery is
+    /// **sqlite-only by decision** (no `kj` verb): `UPDATE tracks SET track_id =
+    /// '<original>', deleted_at = NULL WHERE track_id =
+    /// '<original>~tombstone-<deleted_at>'`.
+    ///
+    /// `~` is outside the track-id charset (`[a-z0-9_-]`,
+    /// `kaijutsu_types::track::is_track_char`) and `TrackId::new` enforces that
+    /// charset on every attach-path call (`kj/transport.rs`
+    /// `resolve_track_for_ctx` / `beat_attach_payload`), so a tombstone name can
+    /// never collide with — or be re-attached under — a live track id.
+    ///
+    /// PRECONDITION, checked here loudly rather than silently repaired: every
+    /// `attachments` row for `track_id` must already be gone. The scheduler's
+    /// delete path detaches every attached context AND deletes its persisted
+    /// attachment row before calling here (the same "move to a new track"
+    /// idiom `attach` already uses for a context switching lanes). A non-zero
+    /// remaining count means that step was skipped — refuse rather than rename
+    /// the track out from under a still-live binding, which would leave the
+    /// attachment row pointing at a `track_id` that resolves to nothing.
+    ///
+    /// The epoch unit for both the suffix and `deleted_at` is milliseconds —
+    /// the same unit `concluded_at`/`last_activity_at` use (this module's
+    /// header: "All timestamps are Unix milliseconds") — and it is the SAME
+    /// read of the clock for both, so the name and the column can never
+    /// disagree by a tick. Returns the tombstone name on success; `NotFound`
+    /// if `track_id` doesn't exist (already deleted, or never existed).
+    pub fn tombstone_track(&mut self, track_id: &str) -> KernelDbResult<String> {
+        if track_id.is_empty() {
+            return Err(KernelDbError::Validation(
+                "tombstone_track called with an empty track_id".into(),
+            ));
+        }
+        let tx = self.conn.transaction()?;
+        let remaining: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM attachments WHERE track_id = ?1",
+            params![track_id],
+            |row| row.get(0),
+        )?;
+        if remaining != 0 {
+            return Err(KernelDbError::Validation(format!(
+                "tombstone_track({track_id}): refusing — {remaining} attachment row(s) still \
+                 reference this track; detach (and delete the persisted row for) every attached \
+                 context before tombstoning, never rename a track out from under a live binding"
+            )));
+        }
+        let exists: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM tracks WHERE track_id = ?1",
+            params![track_id],
+            |row| row.get(0),
+        )?;
+        if exists == 0 {
+            return Err(KernelDbError::NotFound(format!("track {track_id}")));
+        }
+        let deleted_at = now_millis();
+        let tombstone_name = format!("{track_id}~tombstone-{deleted_at}");
+        tx.execute(
+            "UPDATE tracks SET track_id = ?1, deleted_at = ?2 WHERE track_id = ?3",
+            params![tombstone_name, deleted_at, track_id],
+        )?;
+        tx.commit()?;
+        Ok(tombstone_name)
+    }
+
+    // ========================================================================
+    // Attachments (context → track binding — docs/tracks.md §2+3)
+    // ========================================================================
+
+    /// Upsert an attachment row. In-place (PK is `(track_id, context_id)`):
+    /// a wakeup change or ooda-arm toggle updates one row.
+    pub fn upsert_attachment(&self, a: &PersistedAttachment) -> KernelDbResult<()> {
+        Self::write_attachment(&self.conn, a)
+    }
+
+    /// Upsert an attachment against `conn` (a `&Connection` or `&Transaction` via
+    /// deref). Shared by `upsert_attachment` and transactional callers (the fork
+    /// copy). Does NOT commit.
+    fn write_attachment(conn: &Connection, a: &PersistedAttachment) -> KernelDbResult<()> {
+        conn.execute(
+            "INSERT INTO attachments
+                 (track_id, context_id, wakeup_every, rotate_every_phrases, ooda_armed)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(track_id, context_id) DO UPDATE SET
+                 wakeup_every = ?3, rotate_every_phrases = ?4, ooda_armed = ?5",
+            params![
+                a.track_id,
+                blob_param(a.context_id.as_bytes()),
+                a.wakeup_every as i64,
+                a.rotate_every_phrases.map(|n| n as i64),
+                a.ooda_armed as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Read one attachment by `(track_id, context_id)`, or `None` when absent.
+    pub fn get_attachment(
+        &self,
+        track_id: &str,
+        context_id: ContextId,
+    ) -> KernelDbResult<Option<PersistedAttachment>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT wakeup_every, rotate_every_phrases, ooda_armed
+                 FROM attachments WHERE track_id = ?1 AND context_id = ?2",
+                params![track_id, blob_param(context_id.as_bytes())],
                 |row| {
                     Ok((
                         row.get::<_, i64>(0)?,
-                        row.get::<_, i64>(1)?,
-                        row.get::<_, Option<i64>>(2)?,
-                        row.get::<_, i64>(3)?,
-                        row.get::<_, Option<Vec<u8>>>(4)?,
-                        row.get::<_, String>(5)?,
+                        row.get::<_, Option<i64>>(1)?,
+                        row.get::<_, i64>(2)?,
                     ))
                 },
             )
             .optional()?;
-        let Some((period_ms, beats_per_phrase, playhead_tick, playing, score_blob, clock_kind)) = row
-        else {
+        let Some((wakeup_every, rotate_every_phrases, ooda_armed)) = row else {
             return Ok(None);
         };
-        validated_track(
-            track_id.to_string(),
-            period_ms,
-            beats_per_phrase,
-            playhead_tick,
-            playing,
-            score_blob,
-            clock_kind,
-        )
-        .map(Some)
+        Ok(Some(PersistedAttachment {
+            track_id: track_id.to_string(),
+            context_id,
+            wakeup_every: wakeup_every as u64,
+            rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
+            ooda_armed: ooda_armed != 0,
+        }))
+    }
+
+    /// List all attachments for a track (all contexts attached to `track_id`).
+    pub fn list_attachments_for_track(
+        &self,
+        track_id: &str,
+    ) -> KernelDbResult<Vec<PersistedAttachment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT context_id, wakeup_every, rotate_every_phrases, ooda_armed
+             FROM attachments WHERE track_id = ?1",
+        )?;
+        let rows = stmt.query_map(params![track_id], |row| {
+            Ok((
+                row.get::<_, Vec<u8>>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (ctx_bytes, wakeup_every, rotate_every_phrases, ooda_armed) = row?;
+            let context_id = ContextId::try_from_slice(&ctx_bytes).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Blob,
+                    "invalid ContextId bytes in attachments row".into(),
+                )
+            })?;
+            out.push(PersistedAttachment {
+                track_id: track_id.to_string(),
+                context_id,
+                wakeup_every: wakeup_every as u64,
+                rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
+                ooda_armed: ooda_armed != 0,
+            });
+        }
+        Ok(out)
+    }
+
+    /// List every attachment (all tracks, all contexts) — one query, for
+    /// callers building a whole-kernel context→track map (e.g. `listContexts`
+    /// populating `trackId` per handle) instead of N per-context lookups.
+    pub fn list_all_attachments(&self) -> KernelDbResult<Vec<PersistedAttachment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT track_id, context_id, wakeup_every, rotate_every_phrases, ooda_armed
+             FROM attachments",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Vec<u8>>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (track_id, ctx_bytes, wakeup_every, rotate_every_phrases, ooda_armed) = row?;
+            let context_id = ContextId::try_from_slice(&ctx_bytes).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Blob,
+                    "invalid ContextId bytes in attachments row".into(),
+                )
+            })?;
+            out.push(PersistedAttachment {
+                track_id,
+                context_id,
+                wakeup_every: wakeup_every as u64,
+                rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
+                ooda_armed: ooda_armed != 0,
+            });
+        }
+        Ok(out)
+    }
+
+    /// List all attachments for a context (all tracks this context is attached to).
+    pub fn list_attachments_for_context(
+        &self,
+        context_id: ContextId,
+    ) -> KernelDbResult<Vec<PersistedAttachment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT track_id, wakeup_every, rotate_every_phrases, ooda_armed
+             FROM attachments WHERE context_id = ?1",
+        )?;
+        let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (track_id, wakeup_every, rotate_every_phrases, ooda_armed) = row?;
+            out.push(PersistedAttachment {
+                track_id,
+                context_id,
+                wakeup_every: wakeup_every as u64,
+                rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
+                ooda_armed: ooda_armed != 0,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Delete one attachment by `(track_id, context_id)`. Returns the row count
+    /// (0 = already absent, 1 = deleted).
+    pub fn delete_attachment(&self, track_id: &str, context_id: ContextId) -> KernelDbResult<u64> {
+        let deleted = self.conn.execute(
+            "DELETE FROM attachments WHERE track_id = ?1 AND context_id = ?2",
+            params![track_id, blob_param(context_id.as_bytes())],
+        )?;
+        Ok(deleted as u64)
+    }
+
+    /// Copy all attachments from `source` context to `child` context, keyed on
+    /// the SAME `track_id`. Called inside the `insert_forked_context` transaction
+    /// so the attachment inheritance is atomic with the context-row write.
+    ///
+    /// This is the attachment equivalent of the old `write_beat_state` fork copy
+    /// (docs/tracks.md §3 — "the child inherits the bind at fork"). A non-musician
+    /// source has no attachments, so the copy is a clean no-op in that case.
+    /// Does NOT commit.
+    fn copy_attachments_for_fork(
+        conn: &Connection,
+        source: ContextId,
+        child: ContextId,
+    ) -> KernelDbResult<()> {
+        // Read all source attachments first so the transaction holds only writes.
+        let mut stmt = conn.prepare(
+            "SELECT track_id, wakeup_every, rotate_every_phrases, ooda_armed
+             FROM attachments WHERE context_id = ?1",
+        )?;
+        let rows: Vec<_> = stmt
+            .query_map(params![blob_param(source.as_bytes())], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .collect::<Result<_, _>>()?;
+
+        for (track_id, wakeup_every, rotate_every_phrases, ooda_armed) in rows {
+            let a = PersistedAttachment {
+                track_id,
+                context_id: child,
+                wakeup_every: wakeup_every as u64,
+                rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
+                ooda_armed: ooda_armed != 0,
+            };
+            Self::write_attachment(conn, &a)?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Context Config Fork + Workspace Query
+    // ========================================================================
+
+    /// Copy shell config + env vars + capability binding + sandbox profile +
+    /// execution budget + LLM params + assembly strategy + tool presets from
+    /// source context to target. Called
+    /// during all fork operations. The binding copy makes
+    /// permissions follow the fork — under deny-by-default a fork would
+    /// otherwise start with no loadout and be locked out. The budget copy
+    /// keeps a fork from being a way out of its parent's limits.
+    ///
+    /// Atomic: the copies land in ONE transaction. A fork that fails
+    /// partway must leave NO partial config behind — a half-copied loadout
+    /// would lock the fork out (or grant a stale subset), and the caller has
+    /// already committed the context row, so a silent partial here would
+    /// strand a misconfigured context. The source is read up front so the
+    /// transaction holds only the writes.
+    pub fn fork_context_config(&mut self, source: ContextId, target: ContextId) -> KernelDbResult<()> {
+        let shell = self.get_context_shell(source)?;
+        let env = self.get_context_env(source)?;
+        let binding = self.get_context_binding(source)?;
+        let sandbox = self.get_context_sandbox(source)?;
+        let budget = self.get_context_budget(source)?;
+        let llm = self.get_context_llm_params(source)?;
+        let assembly = self.get_context_assembly(source)?;
+        let lock_policy = self.get_context_lock_policy(source)?;
+        let tool_presets = self.list_tool_presets(source)?;
+
+        let tx = self.conn.transaction()?;
+        if let Some(src) = shell {
+            Self::write_context_shell(
+                &tx,
+                &ContextShellRow {
+                    context_id: target,
+                    cwd: src.cwd,
+                    updated_at: now_millis(),
+                },
+            )?;
+        }
+        for var in &env {
+            Self::write_context_env(&tx, target, &var.key, &var.value)?;
+        }
+        if let Some(binding) = binding {
+            Self::write_binding(&tx, target, &binding)?;
+        }
+        if let Some(sandbox) = &sandbox {
+            Self::write_context_sandbox(&tx, target, sandbox)?;
+        }
+        if let Some(budget) = &budget {
+            Self::write_context_budget(&tx, target, budget)?;
+        }
+        if let Some(llm) = &llm {
+            Self::write_context_llm_params(&tx, target, llm)?;
+        }
+        if let Some(assembly) = &assembly {
+            Self::write_context_assembly(&tx, target, &assembly.rebased(target))?;
+        }
+        Self::write_context_lock_policy(&tx, target, lock_policy)?;
+        for preset in &tool_presets {
+            Self::write_tool_preset(&tx, target, preset)?;
+        }
+        tx.commit()?;
+        Ok(())
     }
 
-    /// List all **live** persisted tracks — tombstoned rows (`deleted_at IS NOT
-    /// NULL`) are excluded, matching `get_track`'s implicit skip (a tombstoned
-    /// row no longer answers to its original name because `tombstone_track`
-    /// renamed it aside). Order is not guaranteed.
-    pub fn list_tracks(&self) -> KernelDbResult<Vec<PersistedTrack>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT track_id, period_ms, beats_per_phrase, playhead_tick, playing, score_context_id, clock_kind
-             FROM tracks WHERE deleted_at IS NULL",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, i64>(1)?,
-                row.get::<_, i64>(2)?,
-                row.get::<_, Option<i64>>(3)?,
-                row.get::<_, i64>(4)?,
-                row.get::<_, Option<Vec<u8>>>(5)?,
-                row.get::<_, String>(6)?,
-            ))
-        })?;
-        let mut tracks = Vec::new();
-        for row in rows {
-            let (track_id, period_ms, beats_per_phrase, playhead_tick, playing, score_blob, clock_kind) =
-                row?;
-            // Same validating construction path as `get_track` — a corrupt row
-            // (negative field, zero period) is refused here too, not cast into a
-            // nonsense `u64` cadence that the roster would then display.
-            tracks.push(validated_track(
-                track_id,
-                period_ms,
-                beats_per_phrase,
-                playhead_tick,
-                playing,
-                score_blob,
-                clock_kind,
-            )?);
+    /// Copy the capability binding (flags + instances + tools + facades +
+    /// sticky names) from `source` to `target`. No-op if the source has no
+    /// binding. The child can later attenuate (self-narrow) but inherits the
+    /// parent's loadout as its starting point.
+    pub fn copy_context_binding(
+        &mut self,
+        source: ContextId,
+        target: ContextId,
+    ) -> KernelDbResult<bool> {
+        match self.get_context_binding(source)? {
+            Some(binding) => {
+                self.upsert_context_binding(target, &binding)?;
+                Ok(true)
+            }
+            None => Ok(false),
         }
-        Ok(tracks)
     }
 
-    /// Delete a track row by `track_id`. Cascades to all its `attachments` rows
-    /// via the FK `ON DELETE CASCADE`. Returns the row count (0 or 1).
-    pub fn delete_track(&self, track_id: &str) -> KernelDbResult<u64> {
-        let deleted = self
-            .conn
-            .execute("DELETE FROM tracks WHERE track_id = ?1", params![track_id])?;
-        Ok(deleted as u64)
+    /// Get workspace paths for a context (via contexts.workspace_id FK).
+    /// Returns None if context has no workspace bound.
+    pub fn context_workspace_paths(
+        &self,
+        context_id: ContextId,
+    ) -> KernelDbResult<Option<Vec<WorkspacePathRow>>> {
+        let ctx = self
+            .get_context(context_id)?
+            .ok_or_else(|| KernelDbError::NotFound(format!("context {}", context_id.short())))?;
+        match ctx.workspace_id {
+            Some(ws_id) => {
+                let paths = self.list_workspace_paths(ws_id)?;
+                Ok(Some(paths))
+            }
+            None => Ok(None),
+        }
     }
 
-    /// Tombstone a track: **rename it aside** to `{track_id}~tombstone-{deleted_at}`
-    /// and stamp `deleted_at`, atomically, in one transaction. `kj transport
-    /// delete`'s DB half — the scheduler half (stop the clock, detach every
-    /// attached context, tear down the live `Timeline`, drop the in-memory
-    /// `TrackState`) has already run by the time this is called.
+    // ========================================================================
+    // Workspace Permission Checking
+    // ========================================================================
+
+    /// Check whether a path is allowed by a context's workspace.
     ///
-    /// A rename, not a `DELETE`: the row (and the durable `tracks` history it
-    /// carries — `score_context_id` above all) survives, just invisible under
-    /// its old name. `list_tracks`/`get_track` (by construction — the PK no
-    /// longer matches) stop returning it, so name reuse via `kj transport attach
-    /// --track <name>` starts a genuinely fresh track + score. Recovery is
-    /// **sqlite-only by decision** (no `kj` verb): `UPDATE tracks SET track_id =
-    /// '<original>', deleted_at = NULL WHERE track_id =
-    /// '<original>~tombstone-<deleted_at>'`.
+    /// Returns `None` if the context has no workspace (unbound = kernel perimeter
+    /// defaults, no restriction). Returns `Some(read_only)` if the path falls
+    /// under a workspace path. Returns an error if the path is outside all
+    /// workspace paths (bound context, path not in scope).
+    pub fn check_workspace_path(
+        &self,
+        context_id: ContextId,
+        path: &str,
+    ) -> KernelDbResult<Option<bool>> {
+        let ws_paths = match self.context_workspace_paths(context_id)? {
+            None => return Ok(None), // unbound context — no workspace restriction
+            Some(paths) => paths,
+        };
+
+        // Find longest-prefix match among workspace paths
+        let mut best: Option<&WorkspacePathRow> = None;
+        for wp in &ws_paths {
+            if path == wp.path || path.starts_with(&format!("{}/", wp.path)) {
+                match best {
+                    None => best = Some(wp),
+                    Some(prev) if wp.path.len() > prev.path.len() => best = Some(wp),
+                    _ => {}
+                }
+            }
+        }
+
+        match best {
+            Some(wp) => Ok(Some(wp.read_only)),
+            None => Err(KernelDbError::Validation(format!(
+                "path '{}' is outside workspace scope",
+                path,
+            ))),
+        }
+    }
+
+    // ========================================================================
+    // Phase 4A: Additional methods for kj commands
+    // ========================================================================
+
+    /// Delete a structural edge between source and target.
     ///
-    /// `~` is outside the track-id charset (`[a-z0-9_-]`,
-    /// `kaijutsu_types::track::is_track_char`) and `TrackId::new` enforces that
-    /// charset on every attach-path call (`kj/transport.rs`
-    /// `resolve_track_for_ctx` / `beat_attach_payload`), so a tombstone name can
-    /// never collide with — or be re-attached under — a live track id.
+    /// Used by `kj context move` to reparent a context.
+    pub fn delete_structural_edge(
+        &self,
+        source: ContextId,
+        target: ContextId,
+    ) -> KernelDbResult<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM context_edges
+             WHERE source_id = ?1 AND target_id = ?2 AND kind = 'structural'",
+            params![blob_param(source.as_bytes()), blob_param(target.as_bytes())],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Delete a single drift edge by its UUID.
     ///
-    /// PRECONDITION, checked here loudly rather than silently repaired: every
-    /// `attachments` row for `track_id` must already be gone. The scheduler's
-    /// delete path detaches every attached context AND deletes its persisted
-    /// attachment row before calling here (the same "move to a new track"
-    /// idiom `attach` already uses for a context switching lanes). A non-zero
-    /// remaining count means that step was skipped — refuse rather than rename
-    /// the track out from under a still-live binding, which would leave the
-    /// attachment row pointing at a `track_id` that resolves to nothing.
+    /// Companion to `kj drift edge rm` — `kj drift history` emits these
+    /// UUIDs as iteration handles. Kind filter pins this to drift edges
+    /// only so a stray `delete_drift_edge` can't accidentally remove a
+    /// structural edge (which would orphan a context's parent link).
+    pub fn delete_drift_edge(&self, edge_id: uuid::Uuid) -> KernelDbResult<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM context_edges
+             WHERE edge_id = ?1 AND kind = 'drift'",
+            params![edge_id.as_bytes().as_slice()],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Hard-delete a context and all its edges (CASCADE).
     ///
-    /// The epoch unit for both the suffix and `deleted_at` is milliseconds —
-    /// the same unit `concluded_at`/`last_activity_at` use (this module's
-    /// header: "All timestamps are Unix milliseconds") — and it is the SAME
-    /// read of the clock for both, so the name and the column can never
-    /// disagree by a tick. Returns the tombstone name on success; `NotFound`
-    /// if `track_id` doesn't exist (already deleted, or never existed).
-    pub fn tombstone_track(&mut self, track_id: &str) -> KernelDbResult<String> {
-        if track_id.is_empty() {
-            return Err(KernelDbError::Validation(
-                "tombstone_track called with an empty track_id".into(),
-            ));
-        }
-        let tx = self.conn.transaction()?;
-        let remaining: i64 = tx.query_row(
-            "SELECT COUNT(*) FROM attachments WHERE track_id = ?1",
-            params![track_id],
+    /// Used by `kj context remove`. This is permanent — use `archive_context`
+    /// for soft delete.
+    pub fn delete_context(&self, id: ContextId) -> KernelDbResult<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM contexts WHERE context_id = ?1",
+            params![blob_param(id.as_bytes())],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Count contexts using a specific preset.
+    pub fn contexts_using_preset(&self, preset_id: PresetId) -> KernelDbResult<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM contexts
+             WHERE preset_id = ?1 AND archived_at IS NULL",
+            params![blob_param(preset_id.as_bytes())],
             |row| row.get(0),
         )?;
-        if remaining != 0 {
-            return Err(KernelDbError::Validation(format!(
-                "tombstone_track({track_id}): refusing — {remaining} attachment row(s) still \
-                 reference this track; detach (and delete the persisted row for) every attached \
-                 context before tombstoning, never rename a track out from under a live binding"
-            )));
-        }
-        let exists: i64 = tx.query_row(
-            "SELECT COUNT(*) FROM tracks WHERE track_id = ?1",
-            params![track_id],
+        Ok(count as usize)
+    }
+
+    /// Count contexts using a specific workspace.
+    pub fn contexts_using_workspace(&self, workspace_id: WorkspaceId) -> KernelDbResult<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM contexts
+             WHERE workspace_id = ?1 AND archived_at IS NULL",
+            params![blob_param(workspace_id.as_bytes())],
             |row| row.get(0),
         )?;
-        if exists == 0 {
-            return Err(KernelDbError::NotFound(format!("track {track_id}")));
-        }
-        let deleted_at = now_millis();
-        let tombstone_name = format!("{track_id}~tombstone-{deleted_at}");
-        tx.execute(
-            "UPDATE tracks SET track_id = ?1, deleted_at = ?2 WHERE track_id = ?3",
-            params![tombstone_name, deleted_at, track_id],
+        Ok(count as usize)
+    }
+
+    /// Find the context that currently holds a given label.
+    pub fn find_context_by_label(&self, label: &str) -> KernelDbResult<Option<ContextRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT context_id, label, provider, model,
+                    system_prompt, consent_mode, context_state, context_type,
+                    created_at, created_by, forked_from, fork_kind,
+                    archived_at, workspace_id, preset_id, concluded_at,
+                    last_activity_at, promoted_at, demoted_at, paused_at
+             FROM contexts WHERE label = ?1",
         )?;
-        tx.commit()?;
-        Ok(tombstone_name)
+
+        let mut rows = stmt.query(params![label])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(row_to_context_row(row)?))
+        } else {
+            Ok(None)
+        }
     }
 
     // ========================================================================
-    // Attachments (context → track binding — docs/tracks.md §2+3)
+    // Client Views (docs/shared-state.md "Retiring KV")
     // ========================================================================
 
-    /// Upsert an attachment row. In-place (PK is `(track_id, context_id)`):
-    /// a wakeup change or ooda-arm toggle updates one row.
-    pub fn upsert_attachment(&self, a: &PersistedAttachment) -> KernelDbResult<()> {
-        Self::write_attachment(&self.conn, a)
+    /// Record `context_id` as the last-viewed context for `client_id`
+    /// (upsert). Called on every context switch, including the restore
+    /// itself — an idempotent re-write of the same value is harmless.
+    pub fn set_client_view(&self, client_id: &str, context_id: ContextId) -> KernelDbResult<()> {
+        self.conn.execute(
+            "INSERT INTO client_views (client_id, context_id, updated_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(client_id) DO UPDATE SET
+                context_id = excluded.context_id,
+                updated_at = excluded.updated_at",
+            params![client_id, blob_param(context_id.as_bytes()), now_millis()],
+        )?;
+        Ok(())
     }
 
-    /// Upsert an attachment against `conn` (a `&Connection` or `&Transaction` via
-    /// deref). Shared by `upsert_attachment` and transactional callers (the fork
-    /// copy). Does NOT commit.
-    fn write_attachment(conn: &Connection, a: &PersistedAttachment) -> KernelDbResult<()> {
-        conn.execute(
-            "INSERT INTO attachments
-                 (track_id, context_id, wakeup_every, rotate_every_phrases, ooda_armed)
-             VALUES (?1, ?2, ?3, ?4, ?5)
-             ON CONFLICT(track_id, context_id) DO UPDATE SET
-                 wakeup_every = ?3, rotate_every_phrases = ?4, ooda_armed = ?5",
-            params![
-                a.track_id,
-                blob_param(a.context_id.as_bytes()),
-                a.wakeup_every as i64,
-                a.rotate_every_phrases.map(|n| n as i64),
-                a.ooda_armed as i64,
-            ],
+    /// Read back the last-viewed context for `client_id`, or `None` if this
+    /// client has never recorded one. No data-migration from the retired KV
+    /// key (`<client_id>.current_context`) — losing the restore value once,
+    /// for a single-user early-dev install, is acceptable.
+    pub fn get_client_view(&self, client_id: &str) -> KernelDbResult<Option<ContextId>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT context_id FROM client_views WHERE client_id = ?1")?;
+        let mut rows = stmt.query_map(params![client_id], |row| read_context_id(row, 0))?;
+        match rows.next() {
+            Some(r) => Ok(Some(r?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Client views naming a context that no longer exists. The table has no
+    /// foreign key, so `kj context remove` leaves them behind.
+    pub fn stale_client_views(&self) -> KernelDbResult<Vec<(String, ContextId)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT v.client_id, v.context_id FROM client_views v
+             LEFT JOIN contexts c ON c.context_id = v.context_id
+             WHERE c.context_id IS NULL
+             ORDER BY v.client_id",
         )?;
-        Ok(())
+        let views = stmt
+            .query_map([], |row| Ok((row.get(0)?, read_context_id(row, 1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(views)
     }
 
-    /// Read one attachment by `(track_id, context_id)`, or `None` when absent.
-    pub fn get_attachment(
+    /// Forget `client_id`'s last-viewed context.
+    pub fn delete_client_view(&self, client_id: &str) -> KernelDbResult<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM client_views WHERE client_id = ?1",
+            params![client_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    // ========================================================================
+    // Inbox (per-principal notifications)
+    // ========================================================================
+
+    /// Post a notification to `recipient`'s inbox and return the stored item.
+    pub fn post_inbox(
         &self,
-        track_id: &str,
-        context_id: ContextId,
-    ) -> KernelDbResult<Option<PersistedAttachment>> {
-        let row = self
-            .conn
-            .query_row(
-                "SELECT wakeup_every, rotate_every_phrases, ooda_armed
-                 FROM attachments WHERE track_id = ?1 AND context_id = ?2",
-                params![track_id, blob_param(context_id.as_bytes())],
-                |row| {
-                    Ok((
-                        row.get::<_, i64>(0)?,
-                        row.get::<_, Option<i64>>(1)?,
-                        row.get::<_, i64>(2)?,
-                    ))
-                },
-            )
-            .optional()?;
-        let Some((wakeup_every, rotate_every_phrases, ooda_armed)) = row else {
-            return Ok(None);
-        };
-        Ok(Some(PersistedAttachment {
-            track_id: track_id.to_string(),
+        recipient: PrincipalId,
+        kind: InboxKind,
+        context_id: Option<ContextId>,
+        sender: Option<PrincipalId>,
+        summary: &str,
+    ) -> KernelDbResult<InboxItem> {
+        let created_at = now_millis();
+        self.conn.execute(
+            "INSERT INTO inbox (recipient, kind, context_id, sender, summary, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                blob_param(recipient.as_bytes()),
+                kind.as_str(),
+                context_id.as_ref().map(|id| id.as_bytes().to_vec()),
+                sender.as_ref().map(|id| id.as_bytes().to_vec()),
+                summary,
+                created_at,
+            ],
+        )?;
+        Ok(InboxItem {
+            id: self.conn.last_insert_rowid() as u64,
+            recipient,
+            kind,
             context_id,
-            wakeup_every: wakeup_every as u64,
-            rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
-            ooda_armed: ooda_armed != 0,
-        }))
+            sender,
+            summary: summary.to_string(),
+            created_at: created_at as u64,
+            acked_at: None,
+        })
     }
 
-    /// List all attachments for a track (all contexts attached to `track_id`).
-    pub fn list_attachments_for_track(
+    /// List `recipient`'s inbox, newest first. `include_acked` adds items that
+    /// were already acknowledged; `limit` caps the result.
+    pub fn list_inbox(
         &self,
-        track_id: &str,
-    ) -> KernelDbResult<Vec<PersistedAttachment>> {
+        recipient: PrincipalId,
+        include_acked: bool,
+        limit: usize,
+    ) -> KernelDbResult<Vec<InboxItem>> {
         let mut stmt = self.conn.prepare(
-            "SELECT context_id, wakeup_every, rotate_every_phrases, ooda_armed
-             FROM attachments WHERE track_id = ?1",
+            "SELECT id, recipient, kind, context_id, sender, summary, created_at, acked_at
+             FROM inbox
+             WHERE recipient = ?1 AND (?2 OR acked_at IS NULL)
+             ORDER BY id DESC
+             LIMIT ?3",
         )?;
-        let rows = stmt.query_map(params![track_id], |row| {
-            Ok((
-                row.get::<_, Vec<u8>>(0)?,
-                row.get::<_, i64>(1)?,
-                row.get::<_, Option<i64>>(2)?,
-                row.get::<_, i64>(3)?,
-            ))
-        })?;
-        let mut out = Vec::new();
-        for row in rows {
-            let (ctx_bytes, wakeup_every, rotate_every_phrases, ooda_armed) = row?;
-            let context_id = ContextId::try_from_slice(&ctx_bytes).ok_or_else(|| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    0,
-                    rusqlite::types::Type::Blob,
-                    "invalid ContextId bytes in attachments row".into(),
-                )
-            })?;
-            out.push(PersistedAttachment {
-                track_id: track_id.to_string(),
-                context_id,
-                wakeup_every: wakeup_every as u64,
-                rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
-                ooda_armed: ooda_armed != 0,
-            });
-        }
-        Ok(out)
+        let rows = stmt.query_map(
+            params![blob_param(recipient.as_bytes()), include_acked, limit as i64],
+            row_to_inbox_item,
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
     }
 
-    /// List every attachment (all tracks, all contexts) — one query, for
-    /// callers building a whole-kernel context→track map (e.g. `listContexts`
-    /// populating `trackId` per handle) instead of N per-context lookups.
-    pub fn list_all_attachments(&self) -> KernelDbResult<Vec<PersistedAttachment>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT track_id, context_id, wakeup_every, rotate_every_phrases, ooda_armed
-             FROM attachments",
+    /// Count `recipient`'s unacknowledged items.
+    pub fn count_unacked_inbox(&self, recipient: PrincipalId) -> KernelDbResult<u64> {
+        let n: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM inbox WHERE recipient = ?1 AND acked_at IS NULL",
+            params![blob_param(recipient.as_bytes())],
+            |row| row.get(0),
         )?;
-        let rows = stmt.query_map([], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, Vec<u8>>(1)?,
-                row.get::<_, i64>(2)?,
-                row.get::<_, Option<i64>>(3)?,
-                row.get::<_, i64>(4)?,
-            ))
-        })?;
-        let mut out = Vec::new();
-        for row in rows {
-            let (track_id, ctx_bytes, wakeup_every, rotate_every_phrases, ooda_armed) = row?;
-            let context_id = ContextId::try_from_slice(&ctx_bytes).ok_or_else(|| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    0,
-                    rusqlite::types::Type::Blob,
-                    "invalid ContextId bytes in attachments row".into(),
-                )
-            })?;
-            out.push(PersistedAttachment {
-                track_id,
-                context_id,
-                wakeup_every: wakeup_every as u64,
-                rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
-                ooda_armed: ooda_armed != 0,
-            });
-        }
-        Ok(out)
+        Ok(n as u64)
     }
 
-    /// List all attachments for a context (all tracks this context is attached to).
-    pub fn list_attachments_for_context(
-        &self,
-        context_id: ContextId,
-    ) -> KernelDbResult<Vec<PersistedAttachment>> {
+    /// Acknowledge items by id, scoped to `recipient` so one seat can never
+    /// ack another's notifications. An empty `ids` acks everything unread.
+    /// Returns how many rows flipped.
+    pub fn ack_inbox(&self, recipient: PrincipalId, ids: &[u64]) -> KernelDbResult<usize> {
+        let now = now_millis();
+        let recipient = blob_param(recipient.as_bytes());
+        if ids.is_empty() {
+            return Ok(self.conn.execute(
+                "UPDATE inbox SET acked_at = ?1 WHERE recipient = ?2 AND acked_at IS NULL",
+                params![now, recipient],
+            )?);
+        }
         let mut stmt = self.conn.prepare(
-            "SELECT track_id, wakeup_every, rotate_every_phrases, ooda_armed
-             FROM attachments WHERE context_id = ?1",
+            "UPDATE inbox SET acked_at = ?1
+             WHERE id = ?2 AND recipient = ?3 AND acked_at IS NULL",
         )?;
-        let rows = stmt.query_map(params![blob_param(context_id.as_bytes())], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, i64>(1)?,
-                row.get::<_, Option<i64>>(2)?,
-                row.get::<_, i64>(3)?,
-            ))
-        })?;
-        let mut out = Vec::new();
-        for row in rows {
-            let (track_id, wakeup_every, rotate_every_phrases, ooda_armed) = row?;
-            out.push(PersistedAttachment {
-                track_id,
-                context_id,
-                wakeup_every: wakeup_every as u64,
-                rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
-                ooda_armed: ooda_armed != 0,
-            });
+        let mut flipped = 0;
+        for id in ids {
+            flipped += stmt.execute(params![now, *id as i64, recipient])?;
         }
-        Ok(out)
+        Ok(flipped)
     }
 
-    /// Delete one attachment by `(track_id, context_id)`. Returns the row count
-    /// (0 = already absent, 1 = deleted).
-    pub fn delete_attachment(&self, track_id: &str, context_id: ContextId) -> KernelDbResult<u64> {
-        let deleted = self.conn.execute(
-            "DELETE FROM attachments WHERE track_id = ?1 AND context_id = ?2",
-            params![track_id, blob_param(context_id.as_bytes())],
+    // ========================================================================
+    // Block edit suggestions
+    // ========================================================================
+
+    /// Store an open suggestion and return it.
+    pub fn insert_suggestion(
+        &self,
+        block_id: BlockId,
+        proposer: PrincipalId,
+        base: &str,
+        edits: &[TextEdit],
+        note: Option<&str>,
+    ) -> KernelDbResult<Suggestion> {
+        let created_at = now_millis();
+        let edits_json = serde_json::to_string(edits)
+            .map_err(|e| KernelDbError::Validation(format!("suggestion edits: {e}")))?;
+        self.conn.execute(
+            "INSERT INTO block_suggestions
+                 (context_id, block_key, proposer, base, edits, note, state, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'open', ?7)",
+            params![
+                blob_param(block_id.context_id.as_bytes()),
+                block_id.to_key(),
+                blob_param(proposer.as_bytes()),
+                base,
+                edits_json,
+                note,
+                created_at,
+            ],
         )?;
-        Ok(deleted as u64)
+        Ok(Suggestion {
+            id: self.conn.last_insert_rowid() as u64,
+            block_id,
+            proposer,
+            base: base.to_string(),
+            edits: edits.to_vec(),
+            note: note.map(str::to_string),
+            state: SuggestionState::Open,
+            created_at: created_at as u64,
+            resolved_by: None,
+            resolved_at: None,
+            reason: None,
+        })
     }
 
-    /// Copy all attachments from `source` context to `child` context, keyed on
-    /// the SAME `track_id`. Called inside the `insert_forked_context` transaction
-    /// so the attachment inheritance is atomic with the context-row write.
-    ///
-    /// This is the attachment equivalent of the old `write_beat_state` fork copy
-    /// (docs/tracks.md §3 — "the child inherits the bind at fork"). A non-musician
-    /// source has no attachments, so the copy is a clean no-op in that case.
-    /// Does NOT commit.
-    fn copy_attachments_for_fork(
-        conn: &Connection,
-        source: ContextId,
-        child: ContextId,
-    ) -> KernelDbResult<()> {
-        // Read all source attachments first so the transaction holds only writes.
-        let mut stmt = conn.prepare(
-            "SELECT track_id, wakeup_every, rotate_every_phrases, ooda_armed
-             FROM attachments WHERE context_id = ?1",
+    pub fn get_suggestion(&self, id: u64) -> KernelDbResult<Option<Suggestion>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT id, block_key, proposer, base, edits, note, state, created_at,
+                        resolved_by, resolved_at, reason
+                 FROM block_suggestions WHERE id = ?1",
+                params![id as i64],
+                row_to_suggestion,
+            )
+            .optional()?)
+    }
+
+    /// Suggestions in `context_id`, oldest first, optionally narrowed to one
+    /// block. Decided ones are left out unless `include_resolved`.
+    pub fn list_suggestions(
+        &self,
+        context_id: ContextId,
+        block_id: Option<BlockId>,
+        include_resolved: bool,
+    ) -> KernelDbResult<Vec<Suggestion>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, block_key, proposer, base, edits, note, state, created_at,
+                    resolved_by, resolved_at, reason
+             FROM block_suggestions
+             WHERE context_id = ?1 AND (?2 IS NULL OR block_key = ?2)
+               AND (?3 OR state = 'open')
+             ORDER BY id",
         )?;
-        let rows: Vec<_> = stmt
-            .query_map(params![blob_param(source.as_bytes())], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, i64>(1)?,
-                    row.get::<_, Option<i64>>(2)?,
-                    row.get::<_, i64>(3)?,
-                ))
-            })?
-            .collect::<Result<_, _>>()?;
+        let rows = stmt.query_map(
+            params![
+                blob_param(context_id.as_bytes()),
+                block_id.map(|b| b.to_key()),
+                include_resolved,
+            ],
+            row_to_suggestion,
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
 
-        for (track_id, wakeup_every, rotate_every_phrases, ooda_armed) in rows {
-            let a = PersistedAttachment {
-                track_id,
-                context_id: child,
-                wakeup_every: wakeup_every as u64,
-                rotate_every_phrases: rotate_every_phrases.map(|n| n as u64),
-                ooda_armed: ooda_armed != 0,
-            };
-            Self::write_attachment(conn, &a)?;
+    /// Record the decision on an open suggestion. Returns false when it was
+    /// already decided (the first decision stands).
+    pub fn resolve_suggestion(
+        &self,
+        id: u64,
+        state: SuggestionState,
+        resolved_by: PrincipalId,
+        reason: Option<&str>,
+    ) -> KernelDbResult<bool> {
+        if state == SuggestionState::Open {
+            return Err(KernelDbError::Validation(
+                "a suggestion is resolved as accepted or rejected".into(),
+            ));
         }
-        Ok(())
+        let changed = self.conn.execute(
+            "UPDATE block_suggestions
+             SET state = ?1, resolved_by = ?2, resolved_at = ?3, reason = ?4
+             WHERE id = ?5 AND state = 'open'",
+            params![
+                state.as_str(),
+                blob_param(resolved_by.as_bytes()),
+                now_millis(),
+                reason,
+                id as i64,
+            ],
+        )?;
+        Ok(changed == 1)
     }
 
     // ========================================================================
-    // Context Config Fork + Workspace Query
+    // Context shares
     // ========================================================================
 
-    /// Copy shell config + env vars + capability binding from source context
-    /// to target. Called during all fork operations. The binding copy makes
-    /// permissions follow the fork — under deny-by-default a fork would
-    /// otherwise start with no loadout and be locked out.
-    ///
-    /// Atomic: the three copies land in ONE transaction. A fork that fails
-    /// partway must leave NO partial config behind — a half-copied loadout
-    /// would lock the fork out (or grant a stale subset), and the caller has
-    /// already committed the context row, so a silent partial here would
-    /// strand a misconfigured context. The source is read up front so the
-    /// transaction holds only the writes.
-    pub fn fork_context_config(&mut self, source: ContextId, target: ContextId) -> KernelDbResult<()> {
-        let shell = self.get_context_shell(source)?;
-        let env = self.get_context_env(source)?;
-        let binding = self.get_context_binding(source)?;
-
-        let tx = self.conn.transaction()?;
-        if let Some(src) = shell {
-            Self::write_context_shell(
-                &tx,
-                &ContextShellRow {
-                    context_id: target,
-                    cwd: src.cwd,
-                    updated_at: now_millis(),
-                },
-            )?;
-        }
-        for var in &env {
-            Self::write_context_env(&tx, target, &var.key, &var.value)?;
-        }
-        if let Some(binding) = binding {
-            Self::write_binding(&tx, target, &binding)?;
-        }
-        tx.commit()?;
+    /// Store a share and its frozen, encoded blocks.
+    pub fn insert_context_share(&self, share: &ContextShare, blocks: &[u8]) -> KernelDbResult<()> {
+        self.conn.execute(
+            "INSERT INTO context_shares
+                 (token, context_id, label, version, block_count, blocks, created_by,
+                  created_at, expires_at, revoked_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                share.token,
+                blob_param(share.context_id.as_bytes()),
+                share.label,
+                share.version as i64,
+                share.block_count,
+                blocks,
+                blob_param(share.created_by.as_bytes()),
+                share.created_at as i64,
+                share.expires_at as i64,
+                share.revoked_at.map(|t| t as i64),
+            ],
+        )?;
         Ok(())
     }
 
-    /// Copy the capability binding (flags + instances + tools + facades +
-    /// sticky names) from `source` to `target`. No-op if the source has no
-    /// binding. The child can later attenuate (self-narrow) but inherits the
-    /// parent's loadout as its starting point.
-    pub fn copy_context_binding(
-        &mut self,
-        source: ContextId,
-        target: ContextId,
-    ) -> KernelDbResult<bool> {
-        match self.get_context_binding(source)? {
-            Some(binding) => {
-                self.upsert_context_binding(target, &binding)?;
-                Ok(true)
-            }
-            None => Ok(false),
-        }
+    pub fn get_context_share(&self, token: &str) -> KernelDbResult<Option<ContextShare>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT token, context_id, label, version, block_count, created_by,
+                        created_at, expires_at, revoked_at
+                 FROM context_shares WHERE token = ?1",
+                params![token],
+                row_to_context_share,
+            )
+            .optional()?)
     }
 
-    /// Get workspace paths for a context (via contexts.workspace_id FK).
-    /// Returns None if context has no workspace bound.
-    pub fn context_workspace_paths(
+    /// The encoded blocks behind a share.
+    pub fn context_share_blocks(&self, token: &str) -> KernelDbResult<Option<Vec<u8>>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT blocks FROM context_shares WHERE token = ?1",
+                params![token],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Shares of `context_id` (every context when `None`), newest first.
+    pub fn list_context_shares(
         &self,
-        context_id: ContextId,
-    ) -> KernelDbResult<Option<Vec<WorkspacePathRow>>> {
-        let ctx = self
-            .get_context(context_id)?
-            .ok_or_else(|| KernelDbError::NotFound(format!("context {}", context_id.short())))?;
-        match ctx.workspace_id {
-            Some(ws_id) => {
-                let paths = self.list_workspace_paths(ws_id)?;
-                Ok(Some(paths))
-            }
-            None => Ok(None),
-        }
+        context_id: Option<ContextId>,
+    ) -> KernelDbResult<Vec<ContextShare>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT token, context_id, label, version, block_count, created_by,
+                    created_at, expires_at, revoked_at
+             FROM context_shares
+             WHERE ?1 IS NULL OR context_id = ?1
+             ORDER BY created_at DESC, token",
+        )?;
+        let rows = stmt.query_map(
+            params![context_id.as_ref().map(|id| blob_param(id.as_bytes()))],
+            row_to_context_share,
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Stamp a share revoked. Returns false when it was already revoked or
+    /// doesn't exist.
+    pub fn revoke_context_share(&self, token: &str, at: u64) -> KernelDbResult<bool> {
+        let changed = self.conn.execute(
+            "UPDATE context_shares SET revoked_at = ?1
+             WHERE token = ?2 AND revoked_at IS NULL",
+            params![at as i64, token],
+        )?;
+        Ok(changed == 1)
     }
 
     // ========================================================================
-    // Workspace Permission Checking
+    // Document bookmarks
     // ========================================================================
 
-    /// Check whether a path is allowed by a context's workspace.
-    ///
-    /// Returns `None` if the context has no workspace (unbound = kernel perimeter
-    /// defaults, no restriction). Returns `Some(read_only)` if the path falls
-    /// under a workspace path. Returns an error if the path is outside all
-    /// workspace paths (bound context, path not in scope).
-    pub fn check_workspace_path(
+    /// Store a bookmark and its frozen, encoded blocks. Returns false, storing
+    /// nothing, when the document already has a bookmark by that name.
+    pub fn insert_doc_bookmark(
+        &self,
+        bookmark: &DocBookmark,
+        blocks: &[u8],
+    ) -> KernelDbResult<bool> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO doc_bookmarks
+                 (document_id, name, version, block_count, blocks, created_by, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                blob_param(bookmark.context_id.as_bytes()),
+                bookmark.name,
+                bookmark.version as i64,
+                bookmark.block_count,
+                blocks,
+                blob_param(bookmark.created_by.as_bytes()),
+                bookmark.created_at as i64,
+            ],
+        )?;
+        Ok(inserted == 1)
+    }
+
+    pub fn get_doc_bookmark(
         &self,
         context_id: ContextId,
-        path: &str,
-    ) -> KernelDbResult<Option<bool>> {
-        let ws_paths = match self.context_workspace_paths(context_id)? {
-            None => return Ok(None), // unbound context — no workspace restriction
-            Some(paths) => paths,
-        };
+        name: &str,
+    ) -> KernelDbResult<Option<DocBookmark>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT document_id, name, version, block_count, created_by, created_at
+                 FROM doc_bookmarks WHERE document_id = ?1 AND name = ?2",
+                params![blob_param(context_id.as_bytes()), name],
+                row_to_doc_bookmark,
+            )
+            .optional()?)
+    }
 
-        // Find longest-prefix match among workspace paths
-        let mut best: Option<&WorkspacePathRow> = None;
-        for wp in &ws_paths {
-            if path == wp.path || path.starts_with(&format!("{}/", wp.path)) {
-                match best {
-                    None => best = Some(wp),
-                    Some(prev) if wp.path.len() > prev.path.len() => best = Some(wp),
-                    _ => {}
-                }
-            }
-        }
+    /// The encoded blocks behind a bookmark.
+    pub fn doc_bookmark_blocks(
+        &self,
+        context_id: ContextId,
+        name: &str,
+    ) -> KernelDbResult<Option<Vec<u8>>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT blocks FROM doc_bookmarks WHERE document_id = ?1 AND name = ?2",
+                params![blob_param(context_id.as_bytes()), name],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
 
-        match best {
-            Some(wp) => Ok(Some(wp.read_only)),
-            None => Err(KernelDbError::Validation(format!(
-                "path '{}' is outside workspace scope",
-                path,
-            ))),
-        }
+    /// A document's bookmarks, oldest first.
+    pub fn list_doc_bookmarks(&self, context_id: ContextId) -> KernelDbResult<Vec<DocBookmark>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT document_id, name, version, block_count, created_by, created_at
+             FROM doc_bookmarks WHERE document_id = ?1
+             ORDER BY created_at, name",
+        )?;
+        let rows = stmt.query_map(
+            params![blob_param(context_id.as_bytes())],
+            row_to_doc_bookmark,
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Drop a bookmark. Returns false when there was none by that name.
+    pub fn delete_doc_bookmark(&self, context_id: ContextId, name: &str) -> KernelDbResult<bool> {
+        let changed = self.conn.execute(
+            "DELETE FROM doc_bookmarks WHERE document_id = ?1 AND name = ?2",
+            params![blob_param(context_id.as_bytes()), name],
+        )?;
+        Ok(changed == 1)
     }
 
     // ========================================================================
-    // Phase 4A: Additional methods for kj commands
+    // Principal preferences
     // ========================================================================
 
-    /// Delete a structural edge between source and target.
-    ///
-    /// Used by `kj context move` to reparent a context.
-    pub fn delete_structural_edge(
+    /// All of `principal_id`'s preferences.
+    pub fn get_prefs(&self, principal_id: PrincipalId) -> KernelDbResult<Preferences> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM principal_prefs WHERE principal_id = ?1")?;
+        let rows = stmt.query_map(params![blob_param(principal_id.as_bytes())], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        Ok(rows.collect::<Result<Preferences, _>>()?)
+    }
+
+    /// Set one preference (validated by `kaijutsu_types::validate_pref`), or
+    /// clear it with `None`. Clearing an unset key is a no-op.
+    pub fn set_pref(
         &self,
-        source: ContextId,
-        target: ContextId,
-    ) -> KernelDbResult<bool> {
-        let deleted = self.conn.execute(
-            "DELETE FROM context_edges
-             WHERE source_id = ?1 AND target_id = ?2 AND kind = 'structural'",
-            params![blob_param(source.as_bytes()), blob_param(target.as_bytes())],
-        )?;
-        Ok(deleted > 0)
+        principal_id: PrincipalId,
+        key: &str,
+        value: Option<&str>,
+    ) -> KernelDbResult<()> {
+        let principal = blob_param(principal_id.as_bytes());
+        match value {
+            Some(value) => {
+                kaijutsu_types::validate_pref(key, value).map_err(KernelDbError::Validation)?;
+                self.conn.execute(
+                    "INSERT INTO principal_prefs (principal_id, key, value, updated_at)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT(principal_id, key) DO UPDATE SET
+                        value = excluded.value,
+                        updated_at = excluded.updated_at",
+                    params![principal, key, value, now_millis()],
+                )?;
+            }
+            None => {
+                self.conn.execute(
+                    "DELETE FROM principal_prefs WHERE principal_id = ?1 AND key = ?2",
+                    params![principal, key],
+                )?;
+            }
+        }
+        Ok(())
     }
 
-    /// Delete a single drift edge by its UUID.
-    ///
-    /// Companion to `kj drift edge rm` — `kj drift history` emits these
-    /// UUIDs as iteration handles. Kind filter pins this to drift edges
-    /// only so a stray `delete_drift_edge` can't accidentally remove a
-    /// structural edge (which would orphan a context's parent link).
-    pub fn delete_drift_edge(&self, edge_id: uuid::Uuid) -> KernelDbResult<bool> {
-        let deleted = self.conn.execute(
-            "DELETE FROM context_edges
-             WHERE edge_id = ?1 AND kind = 'drift'",
-            params![edge_id.as_bytes().as_slice()],
+    /// Record that `principal_id` connected as `username` (upsert).
+    pub fn record_seat(&self, principal_id: PrincipalId, username: &str) -> KernelDbResult<()> {
+        self.conn.execute(
+            "INSERT INTO seats (principal_id, username, last_seen_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(principal_id) DO UPDATE SET
+                username = excluded.username,
+                last_seen_at = excluded.last_seen_at",
+            params![blob_param(principal_id.as_bytes()), username, now_millis()],
         )?;
-        Ok(deleted > 0)
+        Ok(())
     }
 
-    /// Hard-delete a context and all its edges (CASCADE).
-    ///
-    /// Used by `kj context remove`. This is permanent — use `archive_context`
-    /// for soft delete.
-    pub fn delete_context(&self, id: ContextId) -> KernelDbResult<bool> {
-        let deleted = self.conn.execute(
-            "DELETE FROM contexts WHERE context_id = ?1",
-            params![blob_param(id.as_bytes())],
-        )?;
-        Ok(deleted > 0)
+    /// Resolve a username to the principal most recently seen under it.
+    pub fn seat_by_username(&self, username: &str) -> KernelDbResult<Option<PrincipalId>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT principal_id FROM seats WHERE username = ?1
+                 ORDER BY last_seen_at DESC LIMIT 1",
+                params![username],
+                |row| read_principal_id(row, 0),
+            )
+            .optional()?)
     }
 
-    /// Count contexts using a specific preset.
-    pub fn contexts_using_preset(&self, preset_id: PresetId) -> KernelDbResult<usize> {
-        let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM contexts
-             WHERE preset_id = ?1 AND archived_at IS NULL",
-            params![blob_param(preset_id.as_bytes())],
-            |row| row.get(0),
+    /// Usernames starting with `prefix`, most recently seen first.
+    pub fn seat_usernames_with_prefix(
+        &self,
+        prefix: &str,
+        limit: usize,
+    ) -> KernelDbResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT username FROM seats WHERE substr(username, 1, length(?1)) = ?1
+             GROUP BY username ORDER BY MAX(last_seen_at) DESC LIMIT ?2",
         )?;
-        Ok(count as usize)
+        let rows = stmt.query_map(params![prefix, limit as i64], |row| row.get(0))?;
+        Ok(rows.collect::<SqliteResult<Vec<String>>>()?)
     }
 
-    /// Count contexts using a specific workspace.
-    pub fn contexts_using_workspace(&self, workspace_id: WorkspaceId) -> KernelDbResult<usize> {
-        let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM contexts
-             WHERE workspace_id = ?1 AND archived_at IS NULL",
-            params![blob_param(workspace_id.as_bytes())],
-            |row| row.get(0),
+    // ========================================================================
+    // Consent audit log
+    // ========================================================================
+
+    /// Set the consent log signing key, loaded once at startup from outside
+    /// the database (`consent_log::load_or_create_key`). Until it is set, a
+    /// database opened from a file can neither append nor verify.
+    pub fn set_consent_key(&mut self, key: [u8; 32]) {
+        self.consent_key = Some(key);
+    }
+
+    pub(crate) fn consent_key(&self) -> KernelDbResult<[u8; 32]> {
+        self.consent_key.ok_or_else(|| {
+            KernelDbError::Validation("the consent log has no signing key".to_string())
+        })
+    }
+
+    /// Append a decision to the consent log, chaining and signing it, and
+    /// move the signed head to it.
+    pub fn append_consent(
+        &mut self,
+        draft: &crate::consent_log::ConsentDraft,
+    ) -> KernelDbResult<ConsentEntry> {
+        let key = self.consent_key()?;
+        let tx = self.conn.transaction()?;
+        let last: Option<(i64, String)> = tx
+            .query_row(
+                "SELECT seq, hash FROM consent_log ORDER BY seq DESC LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let (prev_seq, prev_hash) = last.unwrap_or_default();
+        let mut entry = ConsentEntry {
+            seq: prev_seq as u64 + 1,
+            at: now_millis() as u64,
+            principal_id: draft.principal_id,
+            context_id: draft.context_id,
+            action: draft.action.clone(),
+            params_hash: draft.params_hash.clone(),
+            verdict: draft.verdict,
+            basis: draft.basis.clone(),
+            mode: draft.mode,
+            prev_hash,
+            hash: String::new(),
+            signature: String::new(),
+        };
+        entry.hash = crate::consent_log::entry_hash(&entry);
+        entry.signature = crate::consent_log::sign(&key, &entry.hash);
+        tx.execute(
+            "INSERT INTO consent_log (seq, at, principal_id, context_id, action, params_hash,
+                verdict, basis, mode, prev_hash, hash, signature)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                entry.seq as i64,
+                entry.at as i64,
+                blob_param(entry.principal_id.as_bytes()),
+                entry.context_id.as_ref().map(|id| id.as_bytes().to_vec()),
+                entry.action,
+                entry.params_hash,
+                entry.verdict.as_str(),
+                entry.basis,
+                entry.mode.as_str(),
+                entry.prev_hash,
+                entry.hash,
+                entry.signature,
+            ],
         )?;
-        Ok(count as usize)
+        tx.execute(
+            "INSERT INTO consent_log_head (singleton, seq, hash, signature) VALUES (1, ?1, ?2, ?3)
+             ON CONFLICT(singleton) DO UPDATE SET
+                seq = excluded.seq, hash = excluded.hash, signature = excluded.signature",
+            params![
+                entry.seq as i64,
+                entry.hash,
+                crate::consent_log::sign_head(&key, entry.seq, &entry.hash),
+            ],
+        )?;
+        tx.commit()?;
+        Ok(entry)
     }
 
-    /// Find the context that currently holds a given label.
-    pub fn find_context_by_label(&self, label: &str) -> KernelDbResult<Option<ContextRow>> {
+    /// Consent log entries after `after_seq`, oldest first.
+    pub fn list_consent(&self, after_seq: u64, limit: usize) -> KernelDbResult<Vec<ConsentEntry>> {
         let mut stmt = self.conn.prepare(
-            "SELECT context_id, label, provider, model,
-                    system_prompt, consent_mode, context_state, context_type,
-                    created_at, created_by, forked_from, fork_kind,
-                    archived_at, workspace_id, preset_id, concluded_at,
-                    last_activity_at, promoted_at, demoted_at, paused_at
-             FROM contexts WHERE label = ?1",
+            "SELECT seq, at, principal_id, context_id, action, params_hash, verdict, basis,
+                    mode, prev_hash, hash, signature
+             FROM consent_log WHERE seq > ?1 ORDER BY seq LIMIT ?2",
         )?;
-
-        let mut rows = stmt.query(params![label])?;
-        if let Some(row) = rows.next()? {
-            Ok(Some(row_to_context_row(row)?))
-        } else {
-            Ok(None)
-        }
+        let rows = stmt.query_map(params![after_seq as i64, limit as i64], row_to_consent_entry)?;
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
     }
 
-    // ========================================================================
-    // Client Views (docs/shared-state.md "Retiring KV")
-    // ========================================================================
-
-    /// Record `context_id` as the last-viewed context for `client_id`
-    /// (upsert). Called on every context switch, including the restore
-    /// itself — an idempotent re-write of the same value is harmless.
-    pub fn set_client_view(&self, client_id: &str, context_id: ContextId) -> KernelDbResult<()> {
-        self.conn.execute(
-            "INSERT INTO client_views (client_id, context_id, updated_at)
-             VALUES (?1, ?2, ?3)
-             ON CONFLICT(client_id) DO UPDATE SET
-                context_id = excluded.context_id,
-                updated_at = excluded.updated_at",
-            params![client_id, blob_param(context_id.as_bytes()), now_millis()],
-        )?;
-        Ok(())
+    /// Number of entries in the consent log.
+    pub fn count_consent(&self) -> KernelDbResult<u64> {
+        let n: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM consent_log", [], |row| row.get(0))?;
+        Ok(n as u64)
     }
 
-    /// Read back the last-viewed context for `client_id`, or `None` if this
-    /// client has never recorded one. No data-migration from the retired KV
-    /// key (`<client_id>.current_context`) — losing the restore value once,
-    /// for a single-user early-dev install, is acceptable.
-    pub fn get_client_view(&self, client_id: &str) -> KernelDbResult<Option<ContextId>> {
-        let mut stmt = self
+    /// Walk the whole consent log checking its chain and signatures, then
+    /// that it ends where the signed head says it does.
+    pub fn verify_consent_log(&self) -> KernelDbResult<ConsentVerification> {
+        const PAGE: usize = 1000;
+        let key = self.consent_key()?;
+        let head: Option<(i64, String, String)> = self
             .conn
-            .prepare("SELECT context_id FROM client_views WHERE client_id = ?1")?;
-        let mut rows = stmt.query_map(params![client_id], |row| read_context_id(row, 0))?;
-        match rows.next() {
-            Some(r) => Ok(Some(r?)),
-            None => Ok(None),
+            .query_row(
+                "SELECT seq, hash, signature FROM consent_log_head WHERE singleton = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let head = head.map(|(seq, hash, signature)| (seq as u64, hash, signature));
+        let mut result = ConsentVerification::default();
+        // Carry the last entry of each page so links across pages are checked.
+        let mut carry: Option<ConsentEntry> = None;
+        loop {
+            let after = carry.as_ref().map_or(0, |e| e.seq);
+            let page = self.list_consent(after, PAGE)?;
+            if page.is_empty() {
+                result.broken_at =
+                    crate::consent_log::check_head(carry.as_ref(), head.as_ref(), &key);
+                return Ok(result);
+            }
+            let full = page.len() == PAGE;
+            let mut window: Vec<ConsentEntry> = carry.take().into_iter().collect();
+            let carried = window.len() as u64;
+            window.extend(page);
+            let checked = crate::consent_log::verify(&window, &key);
+            result.checked += checked.checked - carried;
+            if checked.broken_at.is_some() {
+                result.broken_at = checked.broken_at;
+                return Ok(result);
+            }
+            carry = window.pop();
+            if !full {
+                result.broken_at =
+                    crate::consent_log::check_head(carry.as_ref(), head.as_ref(), &key);
+                return Ok(result);
+            }
         }
     }
 }
@@ -4310,6 +6075,133 @@ impl KernelDb {
 // Row parsers
 // ============================================================================
 
+fn row_to_consent_entry(row: &rusqlite::Row<'_>) -> SqliteResult<ConsentEntry> {
+    let parse_err = |idx: usize, what: &str, value: &str| {
+        rusqlite::Error::FromSqlConversionFailure(
+            idx,
+            rusqlite::types::Type::Text,
+            format!("invalid consent {what}: {value}").into(),
+        )
+    };
+    let verdict: String = row.get(6)?;
+    let mode: String = row.get(8)?;
+    Ok(ConsentEntry {
+        seq: row.get::<_, i64>(0)? as u64,
+        at: row.get::<_, i64>(1)? as u64,
+        principal_id: read_principal_id(row, 2)?,
+        context_id: read_opt_context_id(row, 3)?,
+        action: row.get(4)?,
+        params_hash: row.get(5)?,
+        verdict: ConsentVerdict::from_str(&verdict).map_err(|_| parse_err(6, "verdict", &verdict))?,
+        basis: row.get(7)?,
+        mode: ConsentMode::from_str(&mode).map_err(|_| parse_err(8, "mode", &mode))?,
+        prev_hash: row.get(9)?,
+        hash: row.get(10)?,
+        signature: row.get(11)?,
+    })
+}
+
+fn row_to_inbox_item(row: &rusqlite::Row<'_>) -> SqliteResult<InboxItem> {
+    let kind_str: String = row.get(2)?;
+    let kind = InboxKind::from_str(&kind_str).map_err(|_| {
+        rusqlite::Error::FromSqlConversionFailure(
+            2,
+            rusqlite::types::Type::Text,
+            format!("unknown InboxKind '{kind_str}'").into(),
+        )
+    })?;
+    let sender: Option<Vec<u8>> = row.get(4)?;
+    let sender = match sender {
+        None => None,
+        Some(bytes) => Some(PrincipalId::try_from_slice(&bytes).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                4,
+                rusqlite::types::Type::Blob,
+                "invalid PrincipalId bytes".into(),
+            )
+        })?),
+    };
+    let id: i64 = row.get(0)?;
+    let created_at: i64 = row.get(6)?;
+    let acked_at: Option<i64> = row.get(7)?;
+    Ok(InboxItem {
+        id: id as u64,
+        recipient: read_principal_id(row, 1)?,
+        kind,
+        context_id: read_opt_context_id(row, 3)?,
+        sender,
+        summary: row.get(5)?,
+        created_at: created_at as u64,
+        acked_at: acked_at.map(|t| t as u64),
+    })
+}
+
+fn row_to_suggestion(row: &rusqlite::Row<'_>) -> SqliteResult<Suggestion> {
+    let corrupt = |idx: usize, what: String| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, what.into())
+    };
+    let id: i64 = row.get(0)?;
+    let block_key: String = row.get(1)?;
+    let block_id = BlockId::from_key(&block_key)
+        .ok_or_else(|| corrupt(1, format!("invalid block key '{block_key}'")))?;
+    let edits: String = row.get(4)?;
+    let edits = serde_json::from_str(&edits).map_err(|e| corrupt(4, e.to_string()))?;
+    let state: String = row.get(6)?;
+    let state = SuggestionState::from_str(&state)
+        .map_err(|_| corrupt(6, format!("unknown SuggestionState '{state}'")))?;
+    let created_at: i64 = row.get(7)?;
+    let resolved_by: Option<Vec<u8>> = row.get(8)?;
+    let resolved_by = match resolved_by {
+        None => None,
+        Some(_) => Some(read_principal_id(row, 8)?),
+    };
+    let resolved_at: Option<i64> = row.get(9)?;
+    Ok(Suggestion {
+        id: id as u64,
+        block_id,
+        proposer: read_principal_id(row, 2)?,
+        base: row.get(3)?,
+        edits,
+        note: row.get(5)?,
+        state,
+        created_at: created_at as u64,
+        resolved_by,
+        resolved_at: resolved_at.map(|t| t as u64),
+        reason: row.get(10)?,
+    })
+}
+
+fn row_to_context_share(row: &rusqlite::Row<'_>) -> SqliteResult<ContextShare> {
+    let version: i64 = row.get(3)?;
+    let created_at: i64 = row.get(6)?;
+    let expires_at: i64 = row.get(7)?;
+    let revoked_at: Option<i64> = row.get(8)?;
+    Ok(ContextShare {
+        token: row.get(0)?,
+        context_id: read_context_id(row, 1)?,
+        label: row.get(2)?,
+        version: version as u64,
+        block_count: row.get(4)?,
+        created_by: read_principal_id(row, 5)?,
+        created_at: created_at as u64,
+        expires_at: expires_at as u64,
+        revoked_at: revoked_at.map(|t| t as u64),
+    })
+}
+
+fn row_to_doc_bookmark(row: &rusqlite::Row<'_>) -> SqliteResult<DocBookmark> {
+    let version: i64 = row.get(2)?;
+    let created_at: i64 = row.get(5)?;
+    Ok(DocBookmark {
+        context_id: read_context_id(row, 0)?,
+        name: row.get(1)?,
+        version: version as u64,
+        block_count: row.get(3)?,
+        created_by: read_principal_id(row, 4)?,
+        created_at: created_at as u64,
+    })
+}
+
 fn row_to_document_row(row: &rusqlite::Row<'_>) -> SqliteResult<DocumentRow> {
     let kind_str: String = row.get(2)?;
     Ok(DocumentRow {
@@ -4671,6 +6563,36 @@ mod tests {
         assert_eq!(loaded.last_activity_at, Some(t1));
     }
 
+    /// A batch lands whole, stamped with when each op was made, or not at
+    /// all.
+    #[test]
+    fn append_ops_commits_the_batch_or_nothing() {
+        let mut db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let row = make_context_row(Some("batched"));
+        let cid = row.context_id;
+        insert_context_with_doc(&db, &row, ws_id);
+
+        let made = row.created_at + 5_000;
+        let ops = [(cid, 1, &b"a"[..], made), (cid, 2, &b"b"[..], made + 1)];
+        assert_eq!(db.append_ops(ops, &[(cid, made + 1)]).unwrap(), 2);
+        assert_eq!(db.load_oplog_since(cid, 0).unwrap().len(), 2);
+        assert_eq!(
+            db.get_context(cid).unwrap().unwrap().last_activity_at,
+            Some(made + 1)
+        );
+        assert_eq!(
+            db.count_oplog_buckets(cid, made as u64, 1_000, 1).unwrap(),
+            vec![2],
+            "rows carry their made-at time"
+        );
+
+        // Seq 3 is new but seq 2 collides: neither is written.
+        let clash = [(cid, 3, &b"c"[..], made), (cid, 2, &b"again"[..], made)];
+        assert!(db.append_ops(clash, &[]).is_err());
+        assert_eq!(db.load_oplog_since(cid, 0).unwrap().len(), 2);
+    }
+
     // ── WAL checkpoint ──────────────────────────────────────────────────
 
     /// `checkpoint()` (TRUNCATE) must flush committed frames into the main
@@ -5519,108 +7441,387 @@ mod tests {
         );
     }
 
-    // ── 23. Context shell CRUD ────────────────────────────────────────
-
+    // ── 23. Context shell CRUD ────────────────────────────────────────
+
+    #[test]
+    fn context_shell_upsert_and_get() {
+        let db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let ctx = make_context_row(Some("shell-test"));
+        insert_context_with_doc(&db, &ctx, ws_id);
+
+        // Initially none
+        assert!(db.get_context_shell(ctx.context_id).unwrap().is_none());
+
+        // Insert
+        let row = ContextShellRow {
+            context_id: ctx.context_id,
+            cwd: Some("/home/user/src/kaijutsu".into()),
+            updated_at: now_millis() as i64,
+        };
+        db.upsert_context_shell(&row).unwrap();
+
+        let loaded = db.get_context_shell(ctx.context_id).unwrap().unwrap();
+        assert_eq!(loaded.cwd, Some("/home/user/src/kaijutsu".into()));
+
+        // Update (upsert changes cwd)
+        let row2 = ContextShellRow {
+            context_id: ctx.context_id,
+            cwd: Some("/tmp/work".into()),
+            updated_at: now_millis() as i64,
+        };
+        db.upsert_context_shell(&row2).unwrap();
+
+        let loaded = db.get_context_shell(ctx.context_id).unwrap().unwrap();
+        assert_eq!(loaded.cwd, Some("/tmp/work".into()));
+    }
+
+    #[test]
+    fn context_shell_get_unknown() {
+        let db = KernelDb::in_memory().unwrap();
+        assert!(db.get_context_shell(ContextId::new()).unwrap().is_none());
+    }
+
+    #[test]
+    fn context_shell_copy() {
+        let db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let src = make_context_row(Some("src"));
+        let tgt = make_context_row(Some("tgt"));
+        insert_context_with_doc(&db, &src, ws_id);
+        insert_context_with_doc(&db, &tgt, ws_id);
+
+        // Copy from context with shell config
+        let row = ContextShellRow {
+            context_id: src.context_id,
+            cwd: Some("/home/user/project".into()),
+            updated_at: now_millis() as i64,
+        };
+        db.upsert_context_shell(&row).unwrap();
+
+        assert!(
+            db.copy_context_shell(src.context_id, tgt.context_id)
+                .unwrap()
+        );
+
+        let copied = db.get_context_shell(tgt.context_id).unwrap().unwrap();
+        assert_eq!(copied.cwd, Some("/home/user/project".into()));
+    }
+
+    #[test]
+    fn context_shell_copy_empty() {
+        let db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let src = make_context_row(Some("src"));
+        let tgt = make_context_row(Some("tgt"));
+        insert_context_with_doc(&db, &src, ws_id);
+        insert_context_with_doc(&db, &tgt, ws_id);
+
+        // Copy from context with no shell config → returns false
+        assert!(
+            !db.copy_context_shell(src.context_id, tgt.context_id)
+                .unwrap()
+        );
+        assert!(db.get_context_shell(tgt.context_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn context_shell_cascade_delete() {
+        let db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let ctx = make_context_row(Some("cascade"));
+        insert_context_with_doc(&db, &ctx, ws_id);
+
+        db.upsert_context_shell(&ContextShellRow {
+            context_id: ctx.context_id,
+            cwd: Some("/tmp".into()),
+            updated_at: now_millis() as i64,
+        })
+        .unwrap();
+        assert!(db.get_context_shell(ctx.context_id).unwrap().is_some());
+
+        // Delete context → shell row should cascade
+        db.delete_context(ctx.context_id).unwrap();
+        assert!(db.get_context_shell(ctx.context_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn context_sandbox_roundtrip_fork_and_clear() {
+        let mut db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let src = make_context_row(Some("sandboxed"));
+        let tgt = make_context_row(Some("sandboxed-fork"));
+        insert_context_with_doc(&db, &src, ws_id);
+        insert_context_with_doc(&db, &tgt, ws_id);
+        assert!(db.get_context_sandbox(src.context_id).unwrap().is_none());
+
+        let profile = SandboxProfile {
+            binaries: vec!["rg".into(), "git".into()],
+            fs_from_mounts: true,
+            network: false,
+            limits: SandboxLimits {
+                cpu_secs: Some(30),
+                open_files: Some(256),
+                ..Default::default()
+            },
+        };
+        db.set_context_sandbox(src.context_id, Some(&profile))
+            .unwrap();
+        let loaded = db.get_context_sandbox(src.context_id).unwrap().unwrap();
+        assert_eq!(loaded.binaries, vec!["git", "rg"]);
+        assert_eq!(loaded.limits, profile.limits);
+        assert!(loaded.fs_from_mounts && !loaded.network);
+
+        db.fork_context_config(src.context_id, tgt.context_id)
+            .unwrap();
+        assert_eq!(db.get_context_sandbox(tgt.context_id).unwrap(), Some(loaded));
+
+        let bad = SandboxProfile {
+            binaries: vec!["bin/rg".into()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            db.set_context_sandbox(src.context_id, Some(&bad)),
+            Err(KernelDbError::Validation(_))
+        ));
+
+        db.set_context_sandbox(src.context_id, None).unwrap();
+        assert!(db.get_context_sandbox(src.context_id).unwrap().is_none());
+        assert!(db.get_context_sandbox(tgt.context_id).unwrap().is_some());
+    }
+
+    #[test]
+    fn context_budget_roundtrip_fork_and_clear() {
+        let mut db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let src = make_context_row(Some("budgeted"));
+        let tgt = make_context_row(Some("budgeted-fork"));
+        insert_context_with_doc(&db, &src, ws_id);
+        insert_context_with_doc(&db, &tgt, ws_id);
+        assert!(db.get_context_budget(src.context_id).unwrap().is_none());
+
+        let budget = ExecutionBudget {
+            max_tool_calls_per_hour: Some(200),
+            max_shell_secs_per_hour: None,
+        };
+        db.set_context_budget(src.context_id, Some(&budget))
+            .unwrap();
+        assert_eq!(db.get_context_budget(src.context_id).unwrap(), Some(budget));
+
+        db.fork_context_config(src.context_id, tgt.context_id)
+            .unwrap();
+        assert_eq!(db.get_context_budget(tgt.context_id).unwrap(), Some(budget));
+
+        let zero = ExecutionBudget {
+            max_shell_secs_per_hour: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            db.set_context_budget(src.context_id, Some(&zero)),
+            Err(KernelDbError::Validation(_))
+        ));
+
+        db.set_context_budget(src.context_id, Some(&ExecutionBudget::default()))
+            .unwrap();
+        assert!(db.get_context_budget(src.context_id).unwrap().is_none());
+        assert!(db.get_context_budget(tgt.context_id).unwrap().is_some());
+    }
+
+    #[test]
+    fn context_lock_policy_roundtrip_and_fork() {
+        let mut db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let src = make_context_row(Some("held"));
+        let tgt = make_context_row(Some("held-fork"));
+        insert_context_with_doc(&db, &src, ws_id);
+        insert_context_with_doc(&db, &tgt, ws_id);
+        assert_eq!(
+            db.get_context_lock_policy(src.context_id).unwrap(),
+            LockPolicy::Warn
+        );
+
+        db.set_context_lock_policy(src.context_id, LockPolicy::Hold)
+            .unwrap();
+        assert_eq!(
+            db.get_context_lock_policy(src.context_id).unwrap(),
+            LockPolicy::Hold
+        );
+        db.fork_context_config(src.context_id, tgt.context_id)
+            .unwrap();
+        assert_eq!(
+            db.get_context_lock_policy(tgt.context_id).unwrap(),
+            LockPolicy::Hold
+        );
+
+        db.set_context_lock_policy(src.context_id, LockPolicy::Warn)
+            .unwrap();
+        assert_eq!(
+            db.get_context_lock_policy(src.context_id).unwrap(),
+            LockPolicy::Warn
+        );
+    }
+
     #[test]
-    fn context_shell_upsert_and_get() {
-        let db = KernelDb::in_memory().unwrap();
+    fn context_tool_presets_roundtrip_and_fork() {
+        let mut db = KernelDb::in_memory().unwrap();
         let ws_id = setup_test_db(&db);
-        let ctx = make_context_row(Some("shell-test"));
-        insert_context_with_doc(&db, &ctx, ws_id);
-
-        // Initially none
-        assert!(db.get_context_shell(ctx.context_id).unwrap().is_none());
-
-        // Insert
-        let row = ContextShellRow {
-            context_id: ctx.context_id,
-            cwd: Some("/home/user/src/kaijutsu".into()),
-            updated_at: now_millis() as i64,
+        let src = make_context_row(Some("preset"));
+        let tgt = make_context_row(Some("preset-fork"));
+        insert_context_with_doc(&db, &src, ws_id);
+        insert_context_with_doc(&db, &tgt, ws_id);
+        let shell = InstanceId::new("builtin.shell");
+        let preset = |timeout: u64| ToolPreset {
+            instance: shell.clone(),
+            tool: "shell".into(),
+            params: serde_json::json!({ "timeout_secs": timeout })
+                .as_object()
+                .unwrap()
+                .clone(),
+            updated_by: PrincipalId::system(),
+            updated_at: 7,
         };
-        db.upsert_context_shell(&row).unwrap();
-
-        let loaded = db.get_context_shell(ctx.context_id).unwrap().unwrap();
-        assert_eq!(loaded.cwd, Some("/home/user/src/kaijutsu".into()));
 
-        // Update (upsert changes cwd)
-        let row2 = ContextShellRow {
-            context_id: ctx.context_id,
-            cwd: Some("/tmp/work".into()),
-            updated_at: now_millis() as i64,
-        };
-        db.upsert_context_shell(&row2).unwrap();
+        db.set_tool_preset(src.context_id, &preset(60)).unwrap();
+        db.set_tool_preset(src.context_id, &preset(120)).unwrap();
+        assert_eq!(
+            db.list_tool_presets(src.context_id).unwrap(),
+            vec![preset(120)]
+        );
 
-        let loaded = db.get_context_shell(ctx.context_id).unwrap().unwrap();
-        assert_eq!(loaded.cwd, Some("/tmp/work".into()));
-    }
+        db.fork_context_config(src.context_id, tgt.context_id)
+            .unwrap();
+        assert_eq!(
+            db.list_tool_presets(tgt.context_id).unwrap(),
+            vec![preset(120)]
+        );
 
-    #[test]
-    fn context_shell_get_unknown() {
-        let db = KernelDb::in_memory().unwrap();
-        assert!(db.get_context_shell(ContextId::new()).unwrap().is_none());
+        assert!(
+            db.delete_tool_preset(src.context_id, &shell, "shell")
+                .unwrap()
+        );
+        assert!(
+            !db.delete_tool_preset(src.context_id, &shell, "shell")
+                .unwrap()
+        );
+        assert!(db.list_tool_presets(src.context_id).unwrap().is_empty());
     }
 
     #[test]
-    fn context_shell_copy() {
-        let db = KernelDb::in_memory().unwrap();
+    fn context_llm_params_roundtrip_fork_and_clear() {
+        let mut db = KernelDb::in_memory().unwrap();
         let ws_id = setup_test_db(&db);
-        let src = make_context_row(Some("src"));
-        let tgt = make_context_row(Some("tgt"));
+        let src = make_context_row(Some("tuned"));
+        let tgt = make_context_row(Some("tuned-fork"));
         insert_context_with_doc(&db, &src, ws_id);
         insert_context_with_doc(&db, &tgt, ws_id);
+        assert!(db.get_context_llm_params(src.context_id).unwrap().is_none());
 
-        // Copy from context with shell config
-        let row = ContextShellRow {
-            context_id: src.context_id,
-            cwd: Some("/home/user/project".into()),
-            updated_at: now_millis() as i64,
+        let llm = LlmParams {
+            temperature: Some(0.3),
+            max_tokens: Some(8192),
+            top_p: None,
+            tool_choice: Some(kaijutsu_types::ToolChoice::Tool("shell".into())),
         };
-        db.upsert_context_shell(&row).unwrap();
+        db.set_context_llm_params(src.context_id, Some(&llm))
+            .unwrap();
+        assert_eq!(
+            db.get_context_llm_params(src.context_id).unwrap(),
+            Some(llm.clone())
+        );
 
-        assert!(
-            db.copy_context_shell(src.context_id, tgt.context_id)
-                .unwrap()
+        db.fork_context_config(src.context_id, tgt.context_id)
+            .unwrap();
+        assert_eq!(
+            db.get_context_llm_params(tgt.context_id).unwrap(),
+            Some(llm)
         );
 
-        let copied = db.get_context_shell(tgt.context_id).unwrap().unwrap();
-        assert_eq!(copied.cwd, Some("/home/user/project".into()));
+        let hot = LlmParams {
+            temperature: Some(3.0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            db.set_context_llm_params(src.context_id, Some(&hot)),
+            Err(KernelDbError::Validation(_))
+        ));
+
+        db.set_context_llm_params(src.context_id, None).unwrap();
+        assert!(db.get_context_llm_params(src.context_id).unwrap().is_none());
+        assert!(db.get_context_llm_params(tgt.context_id).unwrap().is_some());
     }
 
     #[test]
-    fn context_shell_copy_empty() {
-        let db = KernelDb::in_memory().unwrap();
+    fn context_assembly_roundtrip_fork_rebases_path_and_clear() {
+        let mut db = KernelDb::in_memory().unwrap();
         let ws_id = setup_test_db(&db);
-        let src = make_context_row(Some("src"));
-        let tgt = make_context_row(Some("tgt"));
+        let src = make_context_row(Some("assembled"));
+        let tgt = make_context_row(Some("assembled-fork"));
         insert_context_with_doc(&db, &src, ws_id);
         insert_context_with_doc(&db, &tgt, ws_id);
+        assert!(db.get_context_assembly(src.context_id).unwrap().is_none());
 
-        // Copy from context with no shell config → returns false
-        assert!(
-            !db.copy_context_shell(src.context_id, tgt.context_id)
-                .unwrap()
+        let at = BlockId::new(src.context_id, PrincipalId::new(), 4);
+        let assembly: Assembly = format!("checkpoint+path:{}+window:8", at.to_key())
+            .parse()
+            .unwrap();
+        db.set_context_assembly(src.context_id, Some(&assembly))
+            .unwrap();
+        assert_eq!(
+            db.get_context_assembly(src.context_id).unwrap(),
+            Some(assembly.clone())
         );
-        assert!(db.get_context_shell(tgt.context_id).unwrap().is_none());
+
+        // The fork's path stage points at its own copy of the block.
+        db.fork_context_config(src.context_id, tgt.context_id)
+            .unwrap();
+        assert_eq!(
+            db.get_context_assembly(tgt.context_id).unwrap(),
+            Some(assembly.rebased(tgt.context_id))
+        );
+
+        db.set_context_assembly(src.context_id, None).unwrap();
+        assert!(db.get_context_assembly(src.context_id).unwrap().is_none());
+        assert!(db.get_context_assembly(tgt.context_id).unwrap().is_some());
     }
 
     #[test]
-    fn context_shell_cascade_delete() {
+    fn digest_subscriptions_upsert_list_delete() {
         let db = KernelDb::in_memory().unwrap();
         let ws_id = setup_test_db(&db);
-        let ctx = make_context_row(Some("cascade"));
-        insert_context_with_doc(&db, &ctx, ws_id);
+        let reader = make_context_row(Some("reader"));
+        let source = make_context_row(Some("source"));
+        insert_context_with_doc(&db, &reader, ws_id);
+        insert_context_with_doc(&db, &source, ws_id);
 
-        db.upsert_context_shell(&ContextShellRow {
-            context_id: ctx.context_id,
-            cwd: Some("/tmp".into()),
-            updated_at: now_millis() as i64,
-        })
-        .unwrap();
-        assert!(db.get_context_shell(ctx.context_id).unwrap().is_some());
+        let mut sub = DigestSubscription {
+            subscriber: reader.context_id,
+            source: source.context_id,
+            every_secs: Some(600),
+            on_milestone: false,
+            focus: Some("open bugs".into()),
+            created_by: PrincipalId::system(),
+            created_at: 1_000,
+            last_digest_at: None,
+            last_block: None,
+        };
+        db.upsert_digest_subscription(&sub).unwrap();
+        assert_eq!(db.list_digest_subscriptions().unwrap(), vec![sub.clone()]);
 
-        // Delete context → shell row should cascade
-        db.delete_context(ctx.context_id).unwrap();
-        assert!(db.get_context_shell(ctx.context_id).unwrap().is_none());
+        // Delivering a digest updates the same row.
+        sub.last_digest_at = Some(5_000);
+        sub.last_block = Some(BlockId::new(source.context_id, PrincipalId::new(), 7));
+        db.upsert_digest_subscription(&sub).unwrap();
+        assert_eq!(db.list_digest_subscriptions().unwrap(), vec![sub]);
+
+        assert!(
+            db.delete_digest_subscription(reader.context_id, source.context_id)
+                .unwrap()
+        );
+        assert!(
+            !db.delete_digest_subscription(reader.context_id, source.context_id)
+                .unwrap()
+        );
+        assert!(db.list_digest_subscriptions().unwrap().is_empty());
     }
 
     // ── 23b. Context tool bindings CRUD (Phase 5, D-54) ──────────────
@@ -6414,6 +8615,293 @@ mod tests {
         assert_eq!(db.get_client_view("client-b").unwrap(), Some(ctx_b));
     }
 
+    #[test]
+    fn stale_client_views_name_only_missing_contexts() {
+        let db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let live = make_context_row(Some("still-here"));
+        insert_context_with_doc(&db, &live, ws_id);
+        let gone = ContextId::new();
+        db.set_client_view("client-a", live.context_id).unwrap();
+        db.set_client_view("client-b", gone).unwrap();
+
+        assert_eq!(
+            db.stale_client_views().unwrap(),
+            vec![("client-b".to_string(), gone)]
+        );
+        assert!(db.delete_client_view("client-b").unwrap());
+        assert!(db.stale_client_views().unwrap().is_empty());
+        assert_eq!(
+            db.get_client_view("client-a").unwrap(),
+            Some(live.context_id)
+        );
+    }
+
+    #[test]
+    fn orphan_documents_skip_owned_path_and_recent_docs() {
+        let db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let owned = make_context_row(Some("owned"));
+        insert_context_with_doc(&db, &owned, ws_id);
+        let doc = |kind: DocKind, path: Option<&str>, created_at: i64| {
+            let id = ContextId::new();
+            db.insert_document(&DocumentRow {
+                document_id: id,
+                workspace_id: ws_id,
+                doc_kind: kind,
+                language: None,
+                path: path.map(String::from),
+                created_at,
+                created_by: PrincipalId::new(),
+            })
+            .unwrap();
+            id
+        };
+        let orphan = doc(DocKind::Conversation, None, 1_000);
+        doc(DocKind::Conversation, Some("/notes/plan.md"), 1_000);
+        doc(DocKind::Code, None, 1_000);
+        doc(DocKind::Conversation, None, 5_000);
+
+        assert_eq!(db.orphan_documents(2_000).unwrap(), vec![orphan]);
+        assert!(db.delete_document(orphan).unwrap());
+        assert!(db.orphan_documents(2_000).unwrap().is_empty());
+    }
+
+    // ── Inbox ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn inbox_post_list_ack_round_trip() {
+        let db = KernelDb::in_memory().unwrap();
+        let amy = PrincipalId::new();
+        let ctx = ContextId::new();
+        let first = db
+            .post_inbox(amy, InboxKind::Mention, Some(ctx), None, "@amy look here")
+            .unwrap();
+        let second = db
+            .post_inbox(amy, InboxKind::DriftArrival, None, Some(PrincipalId::system()), "drift")
+            .unwrap();
+
+        let items = db.list_inbox(amy, false, 10).unwrap();
+        assert_eq!(items, vec![second.clone(), first.clone()], "newest first");
+        assert_eq!(db.count_unacked_inbox(amy).unwrap(), 2);
+
+        assert_eq!(db.ack_inbox(amy, &[first.id]).unwrap(), 1);
+        assert_eq!(db.ack_inbox(amy, &[first.id]).unwrap(), 0, "re-ack is a no-op");
+        let unread = db.list_inbox(amy, false, 10).unwrap();
+        assert_eq!(unread.len(), 1);
+        assert_eq!(unread[0].id, second.id);
+        let all = db.list_inbox(amy, true, 10).unwrap();
+        assert!(all.iter().find(|i| i.id == first.id).unwrap().is_acked());
+    }
+
+    #[test]
+    fn inbox_is_scoped_per_recipient() {
+        let db = KernelDb::in_memory().unwrap();
+        let amy = PrincipalId::new();
+        let bob = PrincipalId::new();
+        let item = db
+            .post_inbox(amy, InboxKind::TaskAssignment, None, Some(bob), "yours")
+            .unwrap();
+        assert!(db.list_inbox(bob, true, 10).unwrap().is_empty());
+        assert_eq!(db.ack_inbox(bob, &[item.id]).unwrap(), 0, "bob can't ack amy's item");
+        assert_eq!(db.ack_inbox(bob, &[]).unwrap(), 0);
+        assert_eq!(db.ack_inbox(amy, &[]).unwrap(), 1, "empty ids acks everything unread");
+        assert_eq!(db.count_unacked_inbox(amy).unwrap(), 0);
+    }
+
+    // ── Block edit suggestions ────────────────────────────────────────
+
+    #[test]
+    fn suggestion_insert_list_resolve_round_trip() {
+        let db = KernelDb::in_memory().unwrap();
+        let ctx = ContextId::new();
+        let block = BlockId::new(ctx, PrincipalId::new(), 3);
+        let other = BlockId::new(ctx, PrincipalId::new(), 4);
+        let (amy, bob) = (PrincipalId::new(), PrincipalId::new());
+        let edits = kaijutsu_types::suggestion::edits_between("teh cat", "the cat");
+        let first = db
+            .insert_suggestion(block, amy, "teh cat", &edits, Some("typo"))
+            .unwrap();
+        let second = db.insert_suggestion(other, amy, "x", &[], None).unwrap();
+
+        assert_eq!(db.get_suggestion(first.id).unwrap(), Some(first.clone()));
+        assert_eq!(db.list_suggestions(ctx, None, false).unwrap().len(), 2);
+        assert_eq!(
+            db.list_suggestions(ctx, Some(block), false).unwrap(),
+            vec![first.clone()]
+        );
+
+        assert!(
+            db.resolve_suggestion(first.id, SuggestionState::Accepted, bob, None)
+                .unwrap()
+        );
+        assert!(
+            !db.resolve_suggestion(first.id, SuggestionState::Rejected, bob, None)
+                .unwrap(),
+            "the first decision stands"
+        );
+        assert!(
+            db.resolve_suggestion(second.id, SuggestionState::Open, bob, None)
+                .is_err()
+        );
+        let open = db.list_suggestions(ctx, None, false).unwrap();
+        assert_eq!(
+            open.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![second.id]
+        );
+        let accepted = db.get_suggestion(first.id).unwrap().unwrap();
+        assert_eq!(accepted.state, SuggestionState::Accepted);
+        assert_eq!(accepted.resolved_by, Some(bob));
+        assert_eq!(accepted.proposed().unwrap(), "the cat");
+    }
+
+    // ── Context shares ────────────────────────────────────────────────
+
+    #[test]
+    fn context_share_insert_list_revoke_round_trip() {
+        let db = KernelDb::in_memory().unwrap();
+        let (ctx, other) = (ContextId::new(), ContextId::new());
+        let share = |token: &str, context_id: ContextId, created_at: u64| ContextShare {
+            token: token.into(),
+            context_id,
+            label: "hunt".into(),
+            version: 12,
+            block_count: 3,
+            created_by: PrincipalId::new(),
+            created_at,
+            expires_at: created_at + 1_000,
+            revoked_at: None,
+        };
+        let (old, new) = (share("aa", ctx, 1), share("bb", ctx, 2));
+        db.insert_context_share(&old, b"old").unwrap();
+        db.insert_context_share(&new, b"new").unwrap();
+        db.insert_context_share(&share("cc", other, 3), b"")
+            .unwrap();
+
+        assert_eq!(db.get_context_share("aa").unwrap(), Some(old.clone()));
+        assert_eq!(db.context_share_blocks("bb").unwrap().unwrap(), b"new");
+        assert_eq!(
+            db.list_context_shares(Some(ctx)).unwrap(),
+            vec![new.clone(), old.clone()]
+        );
+        assert_eq!(db.list_context_shares(None).unwrap().len(), 3);
+
+        assert!(db.revoke_context_share("aa", 50).unwrap());
+        assert!(!db.revoke_context_share("aa", 60).unwrap(), "revoked once");
+        assert!(!db.revoke_context_share("zz", 60).unwrap());
+        assert_eq!(
+            db.get_context_share("aa").unwrap().unwrap().revoked_at,
+            Some(50)
+        );
+        assert!(db.get_context_share("zz").unwrap().is_none());
+    }
+
+    // ── Document bookmarks ────────────────────────────────────────────
+
+    #[test]
+    fn doc_bookmarks_are_unique_per_document_and_go_with_it() {
+        let db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let doc = ContextId::new();
+        db.insert_document(&DocumentRow {
+            document_id: doc,
+            workspace_id: ws_id,
+            doc_kind: DocKind::Code,
+            language: None,
+            path: None,
+            created_at: 0,
+            created_by: PrincipalId::system(),
+        })
+        .unwrap();
+        let mark = |name: &str, created_at: u64| DocBookmark {
+            context_id: doc,
+            name: name.into(),
+            version: 4,
+            block_count: 1,
+            created_by: PrincipalId::system(),
+            created_at,
+        };
+
+        assert!(
+            db.insert_doc_bookmark(&mark("before-refactor", 2), b"a")
+                .unwrap()
+        );
+        assert!(db.insert_doc_bookmark(&mark("after", 3), b"b").unwrap());
+        assert!(
+            !db.insert_doc_bookmark(&mark("before-refactor", 9), b"c")
+                .unwrap(),
+            "names are unique per document"
+        );
+        assert_eq!(
+            db.get_doc_bookmark(doc, "before-refactor").unwrap(),
+            Some(mark("before-refactor", 2))
+        );
+        assert_eq!(db.doc_bookmark_blocks(doc, "after").unwrap().unwrap(), b"b");
+        let names: Vec<_> = db
+            .list_doc_bookmarks(doc)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(names, ["before-refactor", "after"]);
+
+        assert!(db.delete_doc_bookmark(doc, "after").unwrap());
+        assert!(!db.delete_doc_bookmark(doc, "after").unwrap());
+        db.delete_document(doc).unwrap();
+        assert!(db.list_doc_bookmarks(doc).unwrap().is_empty());
+    }
+
+    #[test]
+    fn seat_lookup_by_username() {
+        let db = KernelDb::in_memory().unwrap();
+        let amy = PrincipalId::new();
+        assert!(db.seat_by_username("amy").unwrap().is_none());
+        db.record_seat(amy, "amy").unwrap();
+        db.record_seat(amy, "amy").unwrap();
+        assert_eq!(db.seat_by_username("amy").unwrap(), Some(amy));
+        assert!(db.seat_by_username("bob").unwrap().is_none());
+
+        db.record_seat(PrincipalId::new(), "amos").unwrap();
+        db.record_seat(PrincipalId::new(), "bob").unwrap();
+        let mut am = db.seat_usernames_with_prefix("am", 10).unwrap();
+        am.sort();
+        assert_eq!(am, vec!["amos", "amy"]);
+        assert_eq!(db.seat_usernames_with_prefix("", 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn sync_policy_applies_and_quick_check_passes() {
+        let db = KernelDb::in_memory().unwrap();
+        for policy in ["full", "normal", "off"] {
+            db.set_sync_policy(policy.parse().unwrap()).unwrap();
+        }
+        assert_eq!("always".parse::<SyncPolicy>(), Ok(SyncPolicy::Full));
+        assert!("sometimes".parse::<SyncPolicy>().is_err());
+        assert!(db.quick_check().unwrap().is_empty());
+    }
+
+    #[test]
+    fn prefs_set_clear_and_scope() {
+        let db = KernelDb::in_memory().unwrap();
+        let amy = PrincipalId::new();
+        let bob = PrincipalId::new();
+        db.set_pref(amy, "default_model", Some("anthropic/claude")).unwrap();
+        db.set_pref(amy, "ui.theme", Some("dark")).unwrap();
+        db.set_pref(amy, "ui.theme", Some("light")).unwrap();
+        let prefs = db.get_prefs(amy).unwrap();
+        assert_eq!(prefs.len(), 2);
+        assert_eq!(prefs["ui.theme"], "light");
+        assert!(db.get_prefs(bob).unwrap().is_empty());
+
+        assert!(matches!(
+            db.set_pref(amy, "default_model", Some("no-slash")),
+            Err(KernelDbError::Validation(_))
+        ));
+        db.set_pref(amy, "ui.theme", None).unwrap();
+        db.set_pref(amy, "ui.never-set", None).unwrap();
+        assert_eq!(db.get_prefs(amy).unwrap().len(), 1);
+    }
+
     // ── Tracks CRUD ───────────────────────────────────────────────────
 
     fn make_track(track_id: &str, period_ms: u64) -> PersistedTrack {
@@ -7807,6 +10295,55 @@ mod tests {
         assert_eq!(loaded.demoted_at, None);
     }
 
+    #[test]
+    fn close_contexts_archives_the_selection_and_reopen_restores_it() {
+        let mut db = KernelDb::in_memory().unwrap();
+        let ws_id = setup_test_db(&db);
+        let parent = make_context_row(Some("main"));
+        insert_context_with_doc(&db, &parent, ws_id);
+        let mut forks = Vec::new();
+        for label in ["exp-a", "exp-b", "keep"] {
+            let mut row = make_context_row(Some(label));
+            row.forked_from = Some(parent.context_id);
+            insert_context_with_doc(&db, &row, ws_id);
+            forks.push(row.context_id);
+        }
+        assert_eq!(db.promote_context(forks[1]).unwrap(), PromoteOutcome::Promoted);
+
+        let nothing = ContextCloseFilter::default();
+        assert!(db.close_contexts(&nothing, false).unwrap().is_empty());
+
+        let filter = ContextCloseFilter::default()
+            .with_forked_from(parent.context_id)
+            .with_label_prefix("exp-");
+        assert_eq!(db.close_contexts(&filter, true).unwrap(), vec![forks[0]]);
+        assert!(db.get_context(forks[0]).unwrap().unwrap().archived_at.is_none());
+
+        let seats_too = ContextCloseFilter {
+            include_promoted: true,
+            ..filter
+        };
+        let closed = db.close_contexts(&seats_too, false).unwrap();
+        assert_eq!(closed.len(), 2);
+        let open: HashSet<_> = db
+            .list_active_contexts()
+            .unwrap()
+            .into_iter()
+            .map(|r| r.context_id)
+            .collect();
+        assert_eq!(open, HashSet::from([parent.context_id, forks[2]]));
+
+        assert!(db.reopen_context(forks[1]).unwrap());
+        assert!(!db.reopen_context(forks[1]).unwrap(), "already open");
+        let reopened = db.get_context(forks[1]).unwrap().unwrap();
+        assert_eq!(reopened.archived_at, None);
+        assert_eq!(reopened.promoted_at, None, "reopen takes no seat");
+        assert!(matches!(
+            db.reopen_context(ContextId::new()),
+            Err(KernelDbError::NotFound(_))
+        ));
+    }
+
     #[test]
     fn set_context_paused_roundtrip() {
         let db = KernelDb::in_memory().unwrap();
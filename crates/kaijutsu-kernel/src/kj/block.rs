@@ -11,13 +11,19 @@
 //! kaish scripts (rc lifecycle, the live-eval harness) can read block state
 //! without going through MCP. `read` closes the partial-parity gap with
 //! `block_read` (line numbers + range filtering).
+//!
+//! `suggest` / `suggestions` / `accept` / `reject` front the edit-suggestion
+//! workflow in `crate::suggestion`: a proposed edit is stored, not applied,
+//! until the block's owner accepts it.
+
+use std::sync::Arc;
 
 use clap::{Parser, Subcommand};
 use kaijutsu_cas::ContentStore;
-use kaijutsu_types::{BlockKind, ContentType, Role, Status};
+use kaijutsu_types::{BlockKind, BlockLock, ContentType, LockPolicy, Role, Status};
 use serde::Serialize;
 
-use crate::block_tools::translate::{line_range_to_char_range, line_to_char_offset};
+use crate::block_tools::LineIndex;
 use super::refs::resolve_context_arg;
 use super::{clap_help_for, KjCaller, KjDispatcher, KjResult};
 
@@ -79,7 +85,7 @@ enum BlockCommand {
         /// Target context: . (default) | .parent | <label> | <hex prefix>
         #[arg(long, short = 'c')]
         context: Option<String>,
-        /// Filter by kind: text|thinking|tool_call|tool_result|drift|file|error|notification|resource|trace
+        /// Filter by kind: text|thinking|tool_call|tool_result|drift|file|error|notification|resource|trace|repl
         #[arg(long)]
         kind: Option<String>,
         /// Filter by role: user|model|system|tool|asset
@@ -88,13 +94,19 @@ enum BlockCommand {
         /// Filter by status: pending|running|done|error
         #[arg(long)]
         status: Option<String>,
+        /// Only blocks written by this agent session (full id or prefix),
+        /// as stamped by kaijutsu-mcp
+        #[arg(long)]
+        agent_session: Option<String>,
         /// Emit a single JSON object instead of a table
         #[arg(long)]
         json: bool,
     },
     /// Inspect a single block's metadata.
     Inspect {
-        /// Block id: context_hex_principal_hex_seq (or legacy : form)
+        /// Block id: context_hex_principal_hex_seq (or legacy : form), a
+        /// block label, or a unique abbreviation like ctx@principal#seq or
+        /// principal#seq
         block_id: String,
         /// Emit a single JSON object instead of a labelled table
         #[arg(long)]
@@ -123,6 +135,17 @@ enum BlockCommand {
         /// Line range "start:end" — 0-indexed, end exclusive. Omit to read all.
         #[arg(long)]
         range: Option<String>,
+        /// Print the stored lines, ignoring the block's reflow setting
+        #[arg(long)]
+        raw: bool,
+        /// Read it translated into this language (`ja`, `pt-BR`, …); the
+        /// block is unchanged
+        #[arg(long = "translate-to", value_name = "LANG")]
+        translate_to: Option<String>,
+        /// Read the block as it stood at this document bookmark (see
+        /// `kj doc bookmark`)
+        #[arg(long, value_name = "BOOKMARK", conflicts_with = "translate_to")]
+        at: Option<String>,
     },
     /// One-step blob readback: resolve a block's payload and print or save
     /// it, following the CAS reference when the block is a derived/asset
@@ -191,7 +214,7 @@ enum BlockCommand {
         /// Role: user|model|system|tool
         #[arg(long)]
         role: String,
-        /// Kind: text|thinking|tool_call|tool_result|drift|file|error|notification|resource|trace
+        /// Kind: text|thinking|tool_call|tool_result|drift|file|error|notification|resource|trace|repl
         #[arg(long)]
         kind: String,
         /// Initial text content (empty if omitted)
@@ -206,11 +229,116 @@ enum BlockCommand {
         /// Target context: . (default) | .parent | <label> | <hex prefix>
         #[arg(long, short = 'c')]
         context: Option<String>,
+        /// Source language of the content (rust, python, json, …)
+        #[arg(long)]
+        language: Option<String>,
+    },
+    /// Set a block's source language (rust, python, json, …) for syntax
+    /// highlighting and Markdown export. Omit the language to clear it.
+    /// Mirrors MCP `block_edit`'s `language`.
+    #[command(alias = "lang")]
+    Language {
+        /// Block id
+        block_id: String,
+        /// Language tag; common aliases (rs, py, sh, yml) are folded
+        language: Option<String>,
+    },
+    /// Set a block's label (`design-decision-3`), a name unique in its
+    /// context that any block id argument accepts. Omit the label to clear
+    /// it. Mirrors MCP `block_edit`'s `label`.
+    Label {
+        /// Block id
+        block_id: String,
+        /// Label: letters, digits, '-', '_' or '.', starting with a letter
+        label: Option<String>,
+    },
+    /// Rewrap a block's prose to a width as one edit and record the
+    /// setting, which `kj block read` then wraps to. Code (a block with a
+    /// programming language, fenced or indented code in prose) keeps its
+    /// lines. Mirrors MCP `block_reflow`.
+    Reflow {
+        /// Block id
+        block_id: String,
+        /// Wrap width, 20-1000 (default: the block's setting, else 80)
+        #[arg(long)]
+        width: Option<u16>,
+        /// Keep existing newlines as hard breaks; only split long lines
+        #[arg(long)]
+        preserve_newlines: bool,
+        /// Clear the setting without touching the text
+        #[arg(long, conflicts_with_all = ["width", "preserve_newlines"])]
+        clear: bool,
+    },
+    /// Propose new text for a block without applying it. The block's
+    /// author (or the context's creator) gets an inbox item and decides
+    /// with `accept` / `reject`.
+    Suggest {
+        /// Block id
+        block_id: String,
+        /// The full text the block should have
+        #[arg(long)]
+        content: String,
+        /// Why the change is proposed
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// List open suggestions for a block, or for every block in a context.
+    Suggestions {
+        /// Block id (omit to list the whole context)
+        block_id: Option<String>,
+        /// Target context: . (default) | .parent | <label> | <hex prefix>
+        #[arg(long, short = 'c', conflicts_with = "block_id")]
+        context: Option<String>,
+        /// Include accepted and rejected suggestions
+        #[arg(long)]
+        all: bool,
+    },
+    /// Apply a suggestion. Its edits land as the proposer's ops; you are
+    /// recorded as the approver. Refused if the block changed since.
+    Accept {
+        /// Suggestion id (from `suggestions`)
+        id: u64,
+    },
+    /// Close a suggestion unapplied. The proposer may withdraw their own.
+    Reject {
+        /// Suggestion id (from `suggestions`)
+        id: u64,
+        /// Why it was turned down
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Mark a block as being edited by you until the lock expires, or renew
+    /// your lock. Others' writes warn or wait, per the context's lock
+    /// policy. Mirrors MCP `block_lock`.
+    Lock {
+        /// Block id
+        block_id: String,
+        /// Seconds until it expires (default 120, at most 3600)
+        #[arg(long)]
+        ttl: Option<u64>,
+        /// What you are doing, shown to whoever runs into the lock
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Release your lock on a block. Mirrors MCP `block_unlock`.
+    Unlock {
+        /// Block id
+        block_id: String,
+    },
+    /// List the blocks being edited in a context, or set its lock policy.
+    Locks {
+        /// Target context: . (default) | .parent | <label> | <hex prefix>
+        #[arg(long, short = 'c')]
+        context: Option<String>,
+        /// Set the policy: warn (writes go through with a warning) or hold
+        /// (writes are refused while another principal holds the block)
+        #[arg(long)]
+        policy: Option<String>,
     },
 }
 
 impl KjDispatcher {
-    pub(crate) fn dispatch_block(&self, argv: &[String], caller: &KjCaller) -> KjResult {
+    pub(crate) async fn dispatch_block(&self, argv: &[String], caller: &KjCaller) -> KjResult {
         // No argv → render help (clap reports DisplayHelp from `--help`; this
         // covers bare `kj block` for parity with the old hand-rolled path).
         if argv.is_empty() {
@@ -237,9 +365,18 @@ impl KjDispatcher {
         // (list/inspect/count/read/history/diff) stay ungated.
         let block_write_tool = match &parsed.command {
             BlockCommand::Append { .. } => Some("block_append"),
-            BlockCommand::Edit { .. } => Some("block_edit"),
+            BlockCommand::Edit { .. }
+            | BlockCommand::Language { .. }
+            | BlockCommand::Label { .. }
+            | BlockCommand::Accept { .. } => Some("block_edit"),
+            BlockCommand::Reflow { .. } => Some("block_reflow"),
             BlockCommand::Create { .. } => Some("block_create"),
             BlockCommand::Status { .. } => Some("block_status"),
+            BlockCommand::Lock { .. }
+            | BlockCommand::Locks {
+                policy: Some(_), ..
+            } => Some("block_lock"),
+            BlockCommand::Unlock { .. } => Some("block_unlock"),
             _ => None,
         };
         if let Some(tool) = block_write_tool {
@@ -257,8 +394,17 @@ impl KjDispatcher {
                 kind,
                 role,
                 status,
+                agent_session,
+                json,
+            } => self.block_list(
+                context.as_deref(),
+                kind.as_deref(),
+                role.as_deref(),
+                status.as_deref(),
+                agent_session.as_deref(),
                 json,
-            } => self.block_list(context.as_deref(), kind.as_deref(), role.as_deref(), status.as_deref(), json, caller),
+                caller,
+            ),
             BlockCommand::Inspect { block_id, json } => self.block_inspect(&block_id, json),
             BlockCommand::Count {
                 context,
@@ -269,7 +415,20 @@ impl KjDispatcher {
                 block_id,
                 no_line_numbers,
                 range,
-            } => self.block_read(&block_id, !no_line_numbers, range.as_deref()),
+                raw,
+                translate_to,
+                at,
+            } => {
+                self.block_read(
+                    &block_id,
+                    !no_line_numbers,
+                    range.as_deref(),
+                    raw,
+                    translate_to.as_deref(),
+                    at.as_deref(),
+                )
+                .await
+            }
             BlockCommand::Cat {
                 block_id,
                 latest,
@@ -302,6 +461,7 @@ impl KjDispatcher {
                 parent,
                 after,
                 context,
+                language,
             } => self.block_create(
                 context.as_deref(),
                 &role,
@@ -309,8 +469,40 @@ impl KjDispatcher {
                 content.as_deref().unwrap_or(""),
                 parent.as_deref(),
                 after.as_deref(),
+                language.as_deref(),
                 caller,
             ),
+            BlockCommand::Language { block_id, language } => {
+                self.block_language(&block_id, language.as_deref())
+            }
+            BlockCommand::Label { block_id, label } => self.block_label(&block_id, label),
+            BlockCommand::Reflow {
+                block_id,
+                width,
+                preserve_newlines,
+                clear,
+            } => self.block_reflow(&block_id, width, preserve_newlines, clear, caller),
+            BlockCommand::Suggest {
+                block_id,
+                content,
+                note,
+            } => self.block_suggest(&block_id, &content, note.as_deref(), caller),
+            BlockCommand::Suggestions {
+                block_id,
+                context,
+                all,
+            } => self.block_suggestions(block_id.as_deref(), context.as_deref(), all, caller),
+            BlockCommand::Accept { id } => self.block_accept(id, caller),
+            BlockCommand::Reject { id, reason } => self.block_reject(id, reason.as_deref(), caller),
+            BlockCommand::Lock {
+                block_id,
+                ttl,
+                note,
+            } => self.block_lock(&block_id, ttl, note.as_deref(), caller),
+            BlockCommand::Unlock { block_id } => self.block_unlock(&block_id, caller),
+            BlockCommand::Locks { context, policy } => {
+                self.block_locks(context.as_deref(), policy.as_deref(), caller)
+            }
         }
     }
 
@@ -320,6 +512,7 @@ impl KjDispatcher {
         kind_arg: Option<&str>,
         role_arg: Option<&str>,
         status_arg: Option<&str>,
+        agent_session: Option<&str>,
         json: bool,
         caller: &KjCaller,
     ) -> KjResult {
@@ -346,6 +539,9 @@ impl KjDispatcher {
                 kf.is_none_or(|k| b.kind == k)
                     && rf.is_none_or(|r| b.role == r)
                     && sf.is_none_or(|s| b.status == s)
+                    && agent_session.is_none_or(|session| {
+                        b.provenance.as_ref().is_some_and(|p| p.is_session(session))
+                    })
             })
             .collect();
 
@@ -396,18 +592,23 @@ impl KjDispatcher {
         KjResult::ok_with_data(out, id_array)
     }
 
+    /// A block id argument: the full key `block list` prints, a block label,
+    /// or any abbreviation naming exactly one resident block —
+    /// `ctx@principal#seq` with short ids (how blocks are displayed),
+    /// `principal#seq`, or a key prefix. Ambiguity errors list the
+    /// candidates.
+    fn resolve_block_arg(&self, id_str: &str) -> Result<kaijutsu_types::BlockId, String> {
+        self.blocks.resolve_block(id_str).map_err(|e| e.to_string())
+    }
+
     fn block_inspect(&self, id_str: &str, json: bool) -> KjResult {
         // Round-trip with the keys `block list` emits: `BlockId::to_key()`
         // uses `_` (legacy `:` still accepted by from_key). Without this,
         // `for b in $(kj block list); do kj block inspect $b; done` would
         // reject every iteration as malformed.
-        let block_id = match kaijutsu_types::BlockId::from_key(id_str) {
-            Some(id) => id,
-            None => {
-                return KjResult::Err(format!(
-                    "kj block inspect: malformed id '{id_str}' (expected context_hex_principal_hex_seq)"
-                ));
-            }
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block inspect: {e}")),
         };
         let ctx_id = block_id.context_id;
 
@@ -442,6 +643,9 @@ impl KjDispatcher {
             "tool_call_id": snap.tool_call_id.map(|id| id.to_key()),
             "is_error": snap.is_error,
             "exit_code": snap.exit_code,
+            "label": snap.label,
+            "reflow": snap.reflow,
+            "provenance": snap.provenance,
         });
 
         if json {
@@ -462,6 +666,24 @@ impl KjDispatcher {
             parent,
             snap.content.len(),
         );
+        let out = match &snap.label {
+            Some(label) => format!("{out}label:     {label}\n"),
+            None => out,
+        };
+        let out = match snap.reflow {
+            Some(r) if r.preserve_newlines => {
+                format!("{out}reflow:    {} cols, newlines kept\n", r.width)
+            }
+            Some(r) => format!("{out}reflow:    {} cols\n", r.width),
+            None => out,
+        };
+        let out = match &snap.provenance {
+            Some(p) => match &p.project_dir {
+                Some(dir) => format!("{out}agent:     {} in {dir}\n", p.chip()),
+                None => format!("{out}agent:     {}\n", p.chip()),
+            },
+            None => out,
+        };
         KjResult::ok_with_data(out, record)
     }
 
@@ -495,31 +717,62 @@ impl KjDispatcher {
 
     /// Read a block's content. Closes the MCP `block_read` parity gap
     /// (line numbers + range filtering) — kj inspect only shows metadata,
-    /// this returns the body.
-    fn block_read(&self, id_str: &str, line_numbers: bool, range: Option<&str>) -> KjResult {
-        let block_id = match kaijutsu_types::BlockId::from_key(id_str) {
-            Some(id) => id,
-            None => {
-                return KjResult::Err(format!(
-                    "kj block read: malformed id '{id_str}' (expected context_hex_principal_hex_seq)"
-                ));
-            }
+    /// this returns the body, wrapped to the block's reflow setting unless
+    /// `raw`, and translated first with `translate_to`
+    /// ([`crate::translate`]).
+    async fn block_read(
+        &self,
+        id_str: &str,
+        line_numbers: bool,
+        range: Option<&str>,
+        raw: bool,
+        translate_to: Option<&str>,
+        at: Option<&str>,
+    ) -> KjResult {
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block read: {e}")),
         };
         let ctx_id = block_id.context_id;
 
-        let snapshots = match self.blocks.block_snapshots(ctx_id) {
-            Ok(s) => s,
-            Err(e) => return KjResult::Err(format!("kj block read: {e}")),
+        // A bookmarked copy has no live frontier: it is neither translated
+        // nor served from the line-index cache.
+        let found = match at {
+            Some(name) => crate::doc_bookmark::open(self.kernel_db(), ctx_id, name)
+                .map(|(_, then)| then.into_iter().find(|b| b.id == block_id))
+                .map(|snap| snap.map(|snap| (snap, None)))
+                .map_err(|e| e.to_string()),
+            None => self
+                .blocks
+                .get_block_with_frontier(ctx_id, &block_id)
+                .map(|found| found.map(|(snap, frontier)| (snap, Some(frontier))))
+                .map_err(|e| e.to_string()),
         };
-        let snap = match snapshots.iter().find(|b| b.id == block_id) {
-            Some(s) => s,
-            None => {
+        let (snap, frontier) = match found {
+            Ok(Some(found)) => found,
+            Ok(None) => {
                 return KjResult::Err(format!(
-                    "kj block read: block '{id_str}' not found in {}",
-                    ctx_id.to_hex()
+                    "kj block read: block '{id_str}' not found in {}{}",
+                    ctx_id.to_hex(),
+                    at.map(|name| format!(" at bookmark {name}"))
+                        .unwrap_or_default()
                 ));
             }
+            Err(e) => return KjResult::Err(format!("kj block read: {e}")),
+        };
+        let translation = match translate_to.zip(frontier.as_ref()) {
+            Some((language, frontier)) => match self
+                .kernel()
+                .translator()
+                .translate(block_id, frontier, &snap.content, language)
+                .await
+            {
+                Ok(t) => Some(t),
+                Err(e) => return KjResult::Err(format!("kj block read: {e}")),
+            },
+            None => None,
         };
+        let source = translation.as_ref().map_or(&snap.content, |t| &t.text);
 
         // Range parse: "start:end" — 0-indexed, end exclusive (mirrors
         // BlockReadRequest.range in kaijutsu-mcp's models.rs).
@@ -531,18 +784,30 @@ impl KjDispatcher {
             },
         };
 
-        let all_lines: Vec<&str> = snap.content.split('\n').collect();
-        let total = all_lines.len();
+        let wrapped = snap
+            .reflow
+            .filter(|_| !raw && kaijutsu_types::reflow::reflows_language(snap.language.as_deref()))
+            .map(|reflow| kaijutsu_types::reflow::reflow_text(source, reflow));
+        let content = wrapped.as_deref().unwrap_or(source);
+        // The stored text's index is cached by frontier; a translated or
+        // wrapped view is indexed on the spot.
+        let index = match &frontier {
+            Some(frontier) if std::ptr::eq(content, snap.content.as_str()) => {
+                self.blocks.line_index(&block_id, frontier, content)
+            }
+            _ => Arc::new(LineIndex::new(content)),
+        };
+        let total = index.segment_count();
         let end_clamped = end.min(total);
         if start > end_clamped {
             return KjResult::Err(format!(
                 "kj block read: range start {start} > clamped end {end_clamped} (block has {total} lines)"
             ));
         }
-        let slice = &all_lines[start..end_clamped];
+        let slice = (start..end_clamped).map(|i| index.segment(content, i));
 
         let mut out = String::new();
-        for (i, line) in slice.iter().enumerate() {
+        for (i, line) in slice.enumerate() {
             if line_numbers {
                 // Display 1-indexed line numbers (matches MCP block_read
                 // convention; range itself stays 0-indexed for slicing).
@@ -563,6 +828,8 @@ impl KjDispatcher {
             "range_start": start,
             "range_end": end_clamped,
             "content_length": snap.content.len(),
+            "translated_to": translation.as_ref().map(|t| &t.language),
+            "at": at,
         });
         KjResult::ok_with_data(out, record)
     }
@@ -623,13 +890,9 @@ impl KjDispatcher {
     ) -> KjResult {
         let (ctx_id, snap) = match (block_id_arg, latest_mime) {
             (Some(id_str), None) => {
-                let block_id = match kaijutsu_types::BlockId::from_key(id_str) {
-                    Some(id) => id,
-                    None => {
-                        return KjResult::Err(format!(
-                            "kj block cat: malformed id '{id_str}' (expected context_hex_principal_hex_seq)"
-                        ));
-                    }
+                let block_id = match self.resolve_block_arg(id_str) {
+                    Ok(id) => id,
+                    Err(e) => return KjResult::Err(format!("kj block cat: {e}")),
                 };
                 let ctx_id = block_id.context_id;
                 let snapshots = match self.blocks.block_snapshots(ctx_id) {
@@ -771,15 +1034,15 @@ impl KjDispatcher {
     /// Append text to an existing block. Mirrors MCP `block_append`. Returns
     /// the new content length so callers can confirm the write took.
     fn block_append(&self, id_str: &str, text: &str, caller: &KjCaller) -> KjResult {
-        let block_id = match kaijutsu_types::BlockId::from_key(id_str) {
-            Some(id) => id,
-            None => {
-                return KjResult::Err(format!(
-                    "kj block append: malformed id '{id_str}' (expected context_hex_principal_hex_seq)"
-                ));
-            }
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block append: {e}")),
         };
         let ctx_id = block_id.context_id;
+        let warning = match self.blocks.check_lock(&block_id, Some(caller.principal_id)) {
+            Ok(w) => w,
+            Err(e) => return KjResult::Err(format!("kj block append: {e}")),
+        };
 
         // append_text_as takes Option<PrincipalId>; pass the caller's so
         // the op is attributed to whoever invoked kj, not the system agent.
@@ -802,26 +1065,27 @@ impl KjDispatcher {
             .map(|s| s.content.len())
             .unwrap_or(0);
 
-        let record = serde_json::json!({
+        let mut record = serde_json::json!({
             "block_id": id_str,
             "context_id": ctx_id.to_hex(),
             "appended_bytes": text.len(),
             "content_length": new_len,
         });
-        KjResult::ok_with_data(format!("appended {} bytes\n", text.len()), record)
+        let msg = with_lock_warning(
+            format!("appended {} bytes\n", text.len()),
+            &mut record,
+            warning,
+        );
+        KjResult::ok_with_data(msg, record)
     }
 
     /// Set a block's status. Mirrors MCP `block_status`. Parses the status
     /// string via `Status::from_str`, which already accepts the lenient set
     /// of synonyms (active→running, completed→done, etc.).
     fn block_status(&self, id_str: &str, new_status: &str) -> KjResult {
-        let block_id = match kaijutsu_types::BlockId::from_key(id_str) {
-            Some(id) => id,
-            None => {
-                return KjResult::Err(format!(
-                    "kj block status: malformed id '{id_str}' (expected context_hex_principal_hex_seq)"
-                ));
-            }
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block status: {e}")),
         };
         let ctx_id = block_id.context_id;
         let status = match Status::from_str(new_status) {
@@ -846,35 +1110,147 @@ impl KjDispatcher {
         )
     }
 
+    /// Set or clear a block's source language. The tag is normalized
+    /// (`kaijutsu_types::language::normalize_language`) so `rs` and `rust`
+    /// land as the same value.
+    fn block_language(&self, id_str: &str, language: Option<&str>) -> KjResult {
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block language: {e}")),
+        };
+        let language = match language.map(parse_language).transpose() {
+            Ok(l) => l,
+            Err(e) => return KjResult::Err(format!("kj block language: {e}")),
+        };
+        let ctx_id = block_id.context_id;
+        if let Err(e) = self
+            .blocks
+            .set_language(ctx_id, &block_id, language.clone())
+        {
+            return KjResult::Err(format!("kj block language: {e}"));
+        }
+        let record = serde_json::json!({
+            "block_id": id_str,
+            "context_id": ctx_id.to_hex(),
+            "language": language,
+        });
+        let message = match &language {
+            Some(l) => format!("language set to {l}\n"),
+            None => "language cleared\n".to_string(),
+        };
+        KjResult::ok_with_data(message, record)
+    }
+
+    /// Set or clear a block's label. Validation and the per-context
+    /// uniqueness check live in `BlockStore::set_label`.
+    fn block_label(&self, id_str: &str, label: Option<String>) -> KjResult {
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block label: {e}")),
+        };
+        let ctx_id = block_id.context_id;
+        if let Err(e) = self.blocks.set_label(ctx_id, &block_id, label.clone()) {
+            return KjResult::Err(format!("kj block label: {e}"));
+        }
+        let record = serde_json::json!({
+            "block_id": block_id.to_key(),
+            "context_id": ctx_id.to_hex(),
+            "label": label,
+        });
+        let message = match &label {
+            Some(l) => format!("label set to {l}\n"),
+            None => "label cleared\n".to_string(),
+        };
+        KjResult::ok_with_data(message, record)
+    }
+
+    /// Rewrap a block (`BlockStore::reflow_block`) or clear its reflow
+    /// setting. Width and newline handling default to the block's current
+    /// setting.
+    fn block_reflow(
+        &self,
+        id_str: &str,
+        width: Option<u16>,
+        preserve_newlines: bool,
+        clear: bool,
+        caller: &KjCaller,
+    ) -> KjResult {
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block reflow: {e}")),
+        };
+        let ctx_id = block_id.context_id;
+        if clear {
+            if let Err(e) = self.blocks.set_reflow(ctx_id, &block_id, None) {
+                return KjResult::Err(format!("kj block reflow: {e}"));
+            }
+            let record = serde_json::json!({
+                "block_id": block_id.to_key(),
+                "context_id": ctx_id.to_hex(),
+                "reflow": null,
+            });
+            return KjResult::ok_with_data("reflow cleared\n".to_string(), record);
+        }
+
+        let current = match self.blocks.get_block_snapshot(ctx_id, &block_id) {
+            Ok(snap) => snap.and_then(|s| s.reflow),
+            Err(e) => return KjResult::Err(format!("kj block reflow: {e}")),
+        };
+        let reflow = match kaijutsu_types::Reflow::new(
+            width
+                .or(current.map(|r| r.width))
+                .unwrap_or(kaijutsu_types::reflow::DEFAULT_REFLOW_WIDTH),
+            preserve_newlines || current.is_some_and(|r| r.preserve_newlines),
+        ) {
+            Ok(r) => r,
+            Err(e) => return KjResult::Err(format!("kj block reflow: {e}")),
+        };
+        let result = self
+            .blocks
+            .reflow_block(ctx_id, &block_id, reflow, Some(caller.principal_id));
+        let changed = match result {
+            Ok(changed) => changed,
+            Err(e) => return KjResult::Err(format!("kj block reflow: {e}")),
+        };
+        let record = serde_json::json!({
+            "block_id": block_id.to_key(),
+            "context_id": ctx_id.to_hex(),
+            "reflow": reflow,
+            "changed": changed,
+        });
+        let message = if changed {
+            format!("rewrapped to {} columns\n", reflow.width)
+        } else {
+            format!("already wrapped to {} columns\n", reflow.width)
+        };
+        KjResult::ok_with_data(message, record)
+    }
+
     /// Edit a block via a single line-based operation. Mirrors a single
     /// `EditOp` from MCP `block_edit`. CAS-validated when `--expected` is
     /// provided on Replace; line indices are 0-indexed and half-open.
     ///
-    /// Offsets are CHAR units throughout (`line_to_char_offset` /
-    /// `line_range_to_char_range`): `edit_text_as` feeds the CRDT text layer,
+    /// Offsets are CHAR units throughout (`LineIndex::char_offset` /
+    /// `LineIndex::char_range`): `edit_text_as` feeds the CRDT text layer,
     /// which bounds-checks against `chars().count()` and splices at char
     /// positions — byte offsets from multibyte content splice at the wrong
     /// place or trip that check spuriously (the June file-tools bug class).
     fn block_edit(&self, id_str: &str, op: EditOp, caller: &KjCaller) -> KjResult {
-        let block_id = match kaijutsu_types::BlockId::from_key(id_str) {
-            Some(id) => id,
-            None => {
-                return KjResult::Err(format!(
-                    "kj block edit: malformed id '{id_str}' (expected context_hex_principal_hex_seq)"
-                ));
-            }
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block edit: {e}")),
         };
         let ctx_id = block_id.context_id;
 
-        // Fetch current content for offset translation + CAS checks. The op
-        // is small; reading the snapshot once is cheap relative to the edit.
-        let snap = match self
+        // Fetch current content for offset translation + CAS checks, with
+        // its line index (cached while the block's text is unchanged).
+        let (snap, index) = match self
             .blocks
-            .block_snapshots(ctx_id)
+            .get_block_indexed(ctx_id, &block_id)
             .ok()
-            .and_then(|v| v.into_iter().find(|b| b.id == block_id))
+            .flatten()
         {
-            Some(s) => s,
+            Some((snap, _, index)) => (snap, index),
             None => {
                 return KjResult::Err(format!(
                     "kj block edit: block '{id_str}' not found in {}",
@@ -883,11 +1259,15 @@ impl KjDispatcher {
             }
         };
         let content = snap.content.clone();
+        let warning = match self.blocks.check_lock(&block_id, Some(caller.principal_id)) {
+            Ok(w) => w,
+            Err(e) => return KjResult::Err(format!("kj block edit: {e}")),
+        };
 
         // Translate the op into (pos, insert_text, delete_len) — CHAR units.
         let (pos, insert_text, delete_len, op_label) = match op {
             EditOp::Insert { line, content: text } => {
-                let pos = match line_to_char_offset(&content, line) {
+                let pos = match index.char_offset(line) {
                     Ok(p) => p,
                     Err(e) => return KjResult::Err(format!("kj block edit insert: {e}")),
                 };
@@ -902,7 +1282,7 @@ impl KjDispatcher {
                 start_line,
                 end_line,
             } => {
-                let (start, end) = match line_range_to_char_range(&content, start_line, end_line) {
+                let (start, end) = match index.char_range(start_line, end_line) {
                     Ok(pair) => pair,
                     Err(e) => return KjResult::Err(format!("kj block edit delete: {e}")),
                 };
@@ -926,10 +1306,8 @@ impl KjDispatcher {
                 expected,
             } => {
                 if let Some(ref want) = expected {
-                    let actual: String = content
-                        .lines()
-                        .skip(start_line as usize)
-                        .take(end_line.saturating_sub(start_line) as usize)
+                    let actual: String = index
+                        .lines(&content, start_line, end_line)
                         .collect::<Vec<_>>()
                         .join("\n");
                     if actual.trim() != want.trim() {
@@ -938,7 +1316,7 @@ impl KjDispatcher {
                         ));
                     }
                 }
-                let (start, end) = match line_range_to_char_range(&content, start_line, end_line) {
+                let (start, end) = match index.char_range(start_line, end_line) {
                     Ok(pair) => pair,
                     Err(e) => return KjResult::Err(format!("kj block edit replace: {e}")),
                 };
@@ -974,7 +1352,7 @@ impl KjDispatcher {
         // `inserted_bytes`/`deleted_bytes` labels were byte counts fed to a
         // char-indexed layer (the bug this rename rode in with).
         let inserted_chars = insert_text.chars().count();
-        let record = serde_json::json!({
+        let mut record = serde_json::json!({
             "block_id": id_str,
             "context_id": ctx_id.to_hex(),
             "op": op_label,
@@ -982,21 +1360,109 @@ impl KjDispatcher {
             "deleted_chars": delete_len,
             "content_length": new_len,
         });
-        KjResult::ok_with_data(
+        let msg = with_lock_warning(
             format!("{op_label}: +{inserted_chars}/-{delete_len} chars (total {new_len})\n"),
-            record,
+            &mut record,
+            warning,
+        );
+        KjResult::ok_with_data(msg, record)
+    }
+
+    /// Lock a block for editing by the caller. Mirrors MCP `block_lock`.
+    fn block_lock(
+        &self,
+        id_str: &str,
+        ttl_secs: Option<u64>,
+        note: Option<&str>,
+        caller: &KjCaller,
+    ) -> KjResult {
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block lock: {e}")),
+        };
+        let lock = match self.blocks.lock_block(
+            &block_id,
+            caller.principal_id,
+            "",
+            ttl_secs,
+            note.unwrap_or_default(),
+        ) {
+            Ok(lock) => lock,
+            Err(e) => return KjResult::Err(format!("kj block lock: {e}")),
+        };
+        let secs = lock.expires_at.saturating_sub(kaijutsu_types::now_millis()) / 1000;
+        KjResult::ok_with_data(format!("locked {id_str} for {secs}s\n"), lock_record(&lock))
+    }
+
+    /// Release the caller's lock on a block. Mirrors MCP `block_unlock`.
+    fn block_unlock(&self, id_str: &str, caller: &KjCaller) -> KjResult {
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block unlock: {e}")),
+        };
+        match self.blocks.unlock_block(&block_id, caller.principal_id) {
+            Ok(released) => KjResult::ok_with_data(
+                if released {
+                    format!("unlocked {id_str}\n")
+                } else {
+                    format!("{id_str} was not locked\n")
+                },
+                serde_json::json!({ "block_id": block_id.to_key(), "released": released }),
+            ),
+            Err(e) => KjResult::Err(format!("kj block unlock: {e}")),
+        }
+    }
+
+    /// List a context's live locks, after setting its policy if asked.
+    fn block_locks(
+        &self,
+        ctx_ref: Option<&str>,
+        policy: Option<&str>,
+        caller: &KjCaller,
+    ) -> KjResult {
+        let ctx_id = {
+            let db = self.kernel_db().lock();
+            match resolve_context_arg(ctx_ref, caller, &db) {
+                Ok(id) => id,
+                Err(e) => return KjResult::Err(format!("kj block locks: {e}")),
+            }
+        };
+        if let Some(policy) = policy {
+            let policy = match policy.parse::<LockPolicy>() {
+                Ok(p) => p,
+                Err(e) => return KjResult::Err(format!("kj block locks: {e}")),
+            };
+            if let Err(e) = self.blocks.set_lock_policy(ctx_id, policy) {
+                return KjResult::Err(format!("kj block locks: {e}"));
+            }
+        }
+        let policy = self.blocks.lock_policy(ctx_id);
+        let locks = self.blocks.block_locks(ctx_id);
+        let now = kaijutsu_types::now_millis();
+        let mut out = format!("policy: {policy}\n");
+        if locks.is_empty() {
+            out.push_str("(no locks)\n");
+        }
+        for lock in &locks {
+            out.push_str(&format!(
+                "{}  {}  {}s left\n",
+                lock.block_id.to_key(),
+                lock.describe(),
+                lock.expires_at.saturating_sub(now) / 1000
+            ));
+        }
+        let records: Vec<_> = locks.iter().map(lock_record).collect();
+        KjResult::ok_with_data(
+            out,
+            serde_json::json!({ "policy": policy.as_str(), "locks": records }),
         )
     }
 
     /// Version / creation info for a block. Mirrors MCP `block_history`.
     fn block_history(&self, id_str: &str) -> KjResult {
-        let block_id = match kaijutsu_types::BlockId::from_key(id_str) {
-            Some(id) => id,
-            None => {
-                return KjResult::Err(format!(
-                    "kj block history: malformed id '{id_str}' (expected context_hex_principal_hex_seq)"
-                ));
-            }
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block history: {e}")),
         };
         let ctx_id = block_id.context_id;
 
@@ -1049,16 +1515,12 @@ impl KjDispatcher {
         KjResult::ok_with_data(out, record)
     }
 
-    /// Unified line-by-line diff against an original. Mirrors MCP
-    /// `block_diff`. Without --original, prints current content.
+    /// Line diff against an original (Myers, word-refined — see
+    /// `kaijutsu_types::diff`). Without --original, prints current content.
     fn block_diff(&self, id_str: &str, original: Option<&str>) -> KjResult {
-        let block_id = match kaijutsu_types::BlockId::from_key(id_str) {
-            Some(id) => id,
-            None => {
-                return KjResult::Err(format!(
-                    "kj block diff: malformed id '{id_str}' (expected context_hex_principal_hex_seq)"
-                ));
-            }
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block diff: {e}")),
         };
         let ctx_id = block_id.context_id;
 
@@ -1103,38 +1565,15 @@ impl KjDispatcher {
             Some(s) => s,
         };
 
-        let orig_lines: Vec<&str> = original.lines().collect();
-        let curr_lines: Vec<&str> = current.lines().collect();
-        let max_lines = orig_lines.len().max(curr_lines.len());
-
+        let diff = kaijutsu_types::diff::LineDiff::new(original, current);
         let mut out = format!("diff {id_str}\n{}\n", "─".repeat(40));
-        let mut added = 0usize;
-        let mut removed = 0usize;
-        let mut changed = 0usize;
-        for i in 0..max_lines {
-            match (orig_lines.get(i).copied(), curr_lines.get(i).copied()) {
-                (Some(o), Some(c)) if o == c => {
-                    out.push_str(&format!("  {o}\n"));
-                }
-                (Some(o), Some(c)) => {
-                    out.push_str(&format!("- {o}\n"));
-                    out.push_str(&format!("+ {c}\n"));
-                    changed += 1;
-                }
-                (Some(o), None) => {
-                    out.push_str(&format!("- {o}\n"));
-                    removed += 1;
-                }
-                (None, Some(c)) => {
-                    out.push_str(&format!("+ {c}\n"));
-                    added += 1;
-                }
-                (None, None) => {}
-            }
+        for line in &diff.lines {
+            out.push_str(&format!("{} {}\n", line.tag.marker(), line.text));
         }
-        if added + removed + changed == 0 {
+        if diff.stats.is_empty() {
             out.push_str("(no changes)\n");
         }
+        let (added, removed, changed) = (diff.stats.added, diff.stats.removed, diff.stats.changed);
 
         let record = serde_json::json!({
             "block_id": id_str,
@@ -1159,6 +1598,7 @@ impl KjDispatcher {
         content: &str,
         parent: Option<&str>,
         after: Option<&str>,
+        language: Option<&str>,
         caller: &KjCaller,
     ) -> KjResult {
         let ctx_id = {
@@ -1180,30 +1620,28 @@ impl KjDispatcher {
             Some(k) => k,
             None => {
                 return KjResult::Err(format!(
-                    "kj block create: invalid kind '{kind}' (expected text|thinking|tool_call|tool_result|drift|file|error|notification|resource|trace)"
+                    "kj block create: invalid kind '{kind}' (expected text|thinking|tool_call|tool_result|drift|file|error|notification|resource|trace|repl)"
                 ));
             }
         };
         let parent_id = match parent {
             None => None,
-            Some(s) => match kaijutsu_types::BlockId::from_key(s) {
-                Some(id) => Some(id),
-                None => {
-                    return KjResult::Err(format!(
-                        "kj block create: malformed --parent id '{s}'"
-                    ));
-                }
+            Some(s) => match self.resolve_block_arg(s) {
+                Ok(id) => Some(id),
+                Err(e) => return KjResult::Err(format!("kj block create: --parent {e}")),
             },
         };
         let after_id = match after {
             None => None,
-            Some(s) => match kaijutsu_types::BlockId::from_key(s) {
-                Some(id) => Some(id),
-                None => {
-                    return KjResult::Err(format!("kj block create: malformed --after id '{s}'"));
-                }
+            Some(s) => match self.resolve_block_arg(s) {
+                Ok(id) => Some(id),
+                Err(e) => return KjResult::Err(format!("kj block create: --after {e}")),
             },
         };
+        let language = match language.map(parse_language).transpose() {
+            Ok(l) => l,
+            Err(e) => return KjResult::Err(format!("kj block create: --language {e}")),
+        };
 
         let new_id = match self.blocks.insert_block_as(
             ctx_id,
@@ -1219,6 +1657,11 @@ impl KjDispatcher {
             Ok(id) => id,
             Err(e) => return KjResult::Err(format!("kj block create: {e}")),
         };
+        if language.is_some()
+            && let Err(e) = self.blocks.set_language(ctx_id, &new_id, language)
+        {
+            return KjResult::Err(format!("kj block create: {e}"));
+        }
 
         // Iteration payload is an array of the single new id, matching the
         // `list` shape so `for id in $(kj block create ...); do …; done`
@@ -1229,6 +1672,164 @@ impl KjDispatcher {
             serde_json::Value::Array(vec![serde_json::Value::String(key)]),
         )
     }
+
+    /// Store `content` as a suggested edit to the block. The workflow
+    /// (diffing, notification, who may decide) lives in `crate::suggestion`.
+    fn block_suggest(
+        &self,
+        id_str: &str,
+        content: &str,
+        note: Option<&str>,
+        caller: &KjCaller,
+    ) -> KjResult {
+        let block_id = match self.resolve_block_arg(id_str) {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block suggest: {e}")),
+        };
+        match crate::suggestion::propose(
+            self.kernel_db(),
+            &self.blocks,
+            block_id,
+            caller.principal_id,
+            content,
+            note,
+        ) {
+            Ok(s) => KjResult::ok_with_data(
+                format!(
+                    "suggestion #{} on {} ({} edits)\n",
+                    s.id,
+                    short_key(&block_id),
+                    s.edits.len()
+                ),
+                suggestion_record(&s),
+            ),
+            Err(e) => KjResult::Err(format!("kj block suggest: {e}")),
+        }
+    }
+
+    /// Suggestions for one block or a whole context, oldest first, each
+    /// rendered as a diff of the block's text against the proposal.
+    fn block_suggestions(
+        &self,
+        id_str: Option<&str>,
+        ctx_ref: Option<&str>,
+        all: bool,
+        caller: &KjCaller,
+    ) -> KjResult {
+        let block_id = match id_str.map(|s| self.resolve_block_arg(s)).transpose() {
+            Ok(id) => id,
+            Err(e) => return KjResult::Err(format!("kj block suggestions: {e}")),
+        };
+        let suggestions = {
+            let db = self.kernel_db().lock();
+            let ctx_id = match block_id {
+                Some(id) => id.context_id,
+                None => match resolve_context_arg(ctx_ref, caller, &db) {
+                    Ok(id) => id,
+                    Err(e) => return KjResult::Err(format!("kj block suggestions: {e}")),
+                },
+            };
+            match db.list_suggestions(ctx_id, block_id, all) {
+                Ok(s) => s,
+                Err(e) => return KjResult::Err(format!("kj block suggestions: {e}")),
+            }
+        };
+
+        let records: Vec<_> = suggestions.iter().map(suggestion_record).collect();
+        if suggestions.is_empty() {
+            return KjResult::ok_with_data("(no suggestions)\n".to_string(), records.into());
+        }
+        let mut out = String::new();
+        for s in &suggestions {
+            out.push_str(&format!(
+                "#{}  {}  by {}  [{}]  {}\n",
+                s.id,
+                short_key(&s.block_id),
+                s.proposer.short(),
+                s.state,
+                s.note.as_deref().unwrap_or(""),
+            ));
+            let proposed = s.proposed().unwrap_or_default();
+            let diff = kaijutsu_types::diff::LineDiff::new(&s.base, &proposed);
+            for line in diff
+                .lines
+                .iter()
+                .filter(|l| l.tag != kaijutsu_types::diff::LineTag::Equal)
+            {
+                out.push_str(&format!("  {} {}\n", line.tag.marker(), line.text));
+            }
+        }
+        KjResult::ok_with_data(out, records.into())
+    }
+
+    fn block_accept(&self, id: u64, caller: &KjCaller) -> KjResult {
+        match crate::suggestion::accept(self.kernel_db(), &self.blocks, id, caller.principal_id) {
+            Ok(s) => KjResult::ok_with_data(
+                format!("accepted suggestion #{id} on {}\n", short_key(&s.block_id)),
+                suggestion_record(&s),
+            ),
+            Err(e) => KjResult::Err(format!("kj block accept: {e}")),
+        }
+    }
+
+    fn block_reject(&self, id: u64, reason: Option<&str>, caller: &KjCaller) -> KjResult {
+        match crate::suggestion::reject(
+            self.kernel_db(),
+            &self.blocks,
+            id,
+            caller.principal_id,
+            reason,
+        ) {
+            Ok(s) => KjResult::ok_with_data(
+                format!("rejected suggestion #{id} on {}\n", short_key(&s.block_id)),
+                suggestion_record(&s),
+            ),
+            Err(e) => KjResult::Err(format!("kj block reject: {e}")),
+        }
+    }
+}
+
+fn suggestion_record(s: &kaijutsu_types::Suggestion) -> serde_json::Value {
+    serde_json::json!({
+        "id": s.id,
+        "block_id": s.block_id.to_key(),
+        "proposer": s.proposer.to_hex(),
+        "state": s.state.as_str(),
+        "note": s.note,
+        "edits": s.edits,
+        "resolved_by": s.resolved_by.map(|p| p.to_hex()),
+        "reason": s.reason,
+    })
+}
+
+fn lock_record(lock: &BlockLock) -> serde_json::Value {
+    serde_json::json!({
+        "block_id": lock.block_id.to_key(),
+        "holder": lock.holder.to_hex(),
+        "holder_name": lock.holder_name,
+        "note": lock.note,
+        "acquired_at": lock.acquired_at,
+        "expires_at": lock.expires_at,
+    })
+}
+
+/// Add a write's lock warning, if any, to its message and record.
+fn with_lock_warning(
+    mut msg: String,
+    record: &mut serde_json::Value,
+    warning: Option<String>,
+) -> String {
+    if let Some(warning) = warning {
+        msg.push_str(&format!("warning: {warning}\n"));
+        record["warning"] = serde_json::Value::String(warning);
+    }
+    msg
+}
+
+/// Normalize a language tag argument, rejecting ones that can't be a tag.
+fn parse_language(tag: &str) -> Result<String, String> {
+    kaijutsu_types::language::normalize_language(tag)
+        .ok_or_else(|| format!("invalid language '{tag}'"))
 }
 
 /// Parse "start:end" into (start, end), end exclusive. Either side may be
@@ -1278,6 +1879,7 @@ fn parse_kind(s: &str) -> Option<BlockKind> {
         "notification" => Some(BlockKind::Notification),
         "resource" => Some(BlockKind::Resource),
         "trace" => Some(BlockKind::Trace),
+        "repl" => Some(BlockKind::Repl),
         _ => None,
     }
 }
@@ -1526,6 +2128,50 @@ mod tests {
         assert_eq!(body, "alpha\nbeta\n", "raw lines: {body:?}");
     }
 
+    #[tokio::test]
+    async fn block_read_translate_to_leaves_the_block_alone() {
+        use crate::llm::{MockClient, Provider};
+
+        let d = test_dispatcher().await;
+        {
+            let mut llm = d.kernel().llm().write().await;
+            llm.register(
+                "mock",
+                std::sync::Arc::new(Provider::Mock(MockClient::new("bonjour"))),
+            );
+            llm.set_default("mock");
+            llm.set_default_model("mock-model");
+        }
+        let principal = PrincipalId::new();
+        let ctx = register_context_with_doc(&d, Some("c"), principal);
+        let bid = insert_text_block(&d, ctx, "hello");
+        let c = caller_with_context(ctx);
+
+        let result = d
+            .dispatch(
+                &[
+                    s("block"),
+                    s("read"),
+                    bid.to_key(),
+                    s("--no-line-numbers"),
+                    s("--translate-to"),
+                    s("FR"),
+                ],
+                &c,
+            )
+            .await;
+        assert!(result.is_ok(), "read failed: {}", result.message());
+        assert_eq!(result.message(), "bonjour\n");
+
+        let result = d
+            .dispatch(
+                &[s("block"), s("read"), bid.to_key(), s("--no-line-numbers")],
+                &c,
+            )
+            .await;
+        assert_eq!(result.message(), "hello\n");
+    }
+
     #[tokio::test]
     async fn block_read_range_slices_lines() {
         let d = test_dispatcher().await;
@@ -2009,6 +2655,41 @@ mod tests {
         assert!(snap.content.is_empty(), "expected empty content");
     }
 
+    #[tokio::test]
+    async fn block_read_accepts_abbreviated_ids() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = register_context_with_doc(&d, Some("c"), principal);
+        let mut c = caller_with_context(ctx);
+        c.principal_id = principal;
+
+        let mut ids = Vec::new();
+        for content in ["first", "second"] {
+            let result = d
+                .dispatch(
+                    &[s("block"), s("create"), s("--role"), s("user"), s("--kind"), s("text"), s("--content"), s(content)],
+                    &c,
+                )
+                .await;
+            ids.push(kaijutsu_types::BlockId::from_key(result.message().trim()).unwrap());
+        }
+
+        // Display form with short ids, and principal#seq.
+        let read = d.dispatch(&[s("block"), s("read"), ids[1].to_string()], &c).await;
+        assert!(read.is_ok(), "{}", read.message());
+        assert!(read.message().contains("second"), "{}", read.message());
+        let short = format!("{}#{}", &principal.short()[..5], ids[0].seq);
+        let read = d.dispatch(&[s("block"), s("read"), short], &c).await;
+        assert!(read.message().contains("first"), "{}", read.message());
+
+        // A key prefix covering both blocks is ambiguous.
+        let key = ids[0].to_key();
+        let prefix = &key[..key.len() - ids[0].seq.to_string().len() - 1];
+        let read = d.dispatch(&[s("block"), s("read"), prefix.to_string()], &c).await;
+        assert!(!read.is_ok());
+        assert!(read.message().contains("ambiguous"), "{}", read.message());
+    }
+
     #[tokio::test]
     async fn block_create_data_is_iterable_array() {
         use crate::kj::KjResult;
@@ -2117,6 +2798,214 @@ mod tests {
         assert_eq!(snap.parent_id, Some(parent_id), "parent edge not set");
     }
 
+    #[tokio::test]
+    async fn block_create_language_is_normalized_and_clearable() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = register_context_with_doc(&d, Some("c"), principal);
+        let c = caller_with_context(ctx);
+
+        let result = d
+            .dispatch(
+                &[
+                    s("block"),
+                    s("create"),
+                    s("--role"),
+                    s("model"),
+                    s("--kind"),
+                    s("text"),
+                    s("--content"),
+                    s("fn main() {}"),
+                    s("--language"),
+                    s("rs"),
+                ],
+                &c,
+            )
+            .await;
+        assert!(result.is_ok(), "create failed: {}", result.message());
+        let key = result.message().trim().to_string();
+        let language = |d: &KjDispatcher| {
+            d.block_store()
+                .block_snapshots(ctx)
+                .unwrap()
+                .into_iter()
+                .find(|b| b.id.to_key() == key)
+                .unwrap()
+                .language
+        };
+        assert_eq!(language(&d).as_deref(), Some("rust"));
+
+        let result = d
+            .dispatch(&[s("block"), s("language"), key.clone()], &c)
+            .await;
+        assert!(result.is_ok(), "clear failed: {}", result.message());
+        assert_eq!(language(&d), None);
+
+        let result = d
+            .dispatch(&[s("block"), s("language"), key.clone(), s("c sharp")], &c)
+            .await;
+        assert!(!result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn block_label_resolves_as_a_block_id_and_stays_unique() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = register_context_with_doc(&d, Some("c"), principal);
+        let c = caller_with_context(ctx);
+        let create = |content: &'static str| {
+            vec![
+                s("block"),
+                s("create"),
+                s("--role"),
+                s("model"),
+                s("--kind"),
+                s("text"),
+                s("--content"),
+                s(content),
+            ]
+        };
+        let first = d.dispatch(&create("We chose CRDTs."), &c).await;
+        let second = d.dispatch(&create("other"), &c).await;
+        let (first, second) = (
+            first.message().trim().to_string(),
+            second.message().trim().to_string(),
+        );
+
+        let result = d
+            .dispatch(
+                &[
+                    s("block"),
+                    s("label"),
+                    first.clone(),
+                    s("design-decision-3"),
+                ],
+                &c,
+            )
+            .await;
+        assert!(result.is_ok(), "label failed: {}", result.message());
+        let result = d
+            .dispatch(&[s("block"), s("read"), s("design-decision-3")], &c)
+            .await;
+        assert!(result.is_ok(), "read by label failed: {}", result.message());
+        assert!(result.message().contains("We chose CRDTs."));
+
+        let result = d
+            .dispatch(
+                &[s("block"), s("label"), second, s("design-decision-3")],
+                &c,
+            )
+            .await;
+        assert!(!result.is_ok(), "duplicate label should be refused");
+
+        let result = d
+            .dispatch(&[s("block"), s("label"), s("design-decision-3")], &c)
+            .await;
+        assert!(result.is_ok(), "clear failed: {}", result.message());
+        let result = d
+            .dispatch(&[s("block"), s("inspect"), first, s("--json")], &c)
+            .await;
+        assert!(result.message().contains("\"label\":null"));
+    }
+
+    #[tokio::test]
+    async fn block_provenance_shows_in_inspect_and_filters_list() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = register_context_with_doc(&d, Some("c"), principal);
+        let c = caller_with_context(ctx);
+        insert_text_block(&d, ctx, "typed by a person");
+        let provenance = kaijutsu_types::AgentProvenance {
+            agent: s("claude-code"),
+            version: Some(s("2.1.3")),
+            session_id: Some(s("4f1c2a9b-7e0d-4c55-9a61-0b8e2f3d1c77")),
+            project_dir: Some(s("/mnt/project")),
+        };
+        let snap = kaijutsu_types::BlockSnapshotBuilder::new(
+            kaijutsu_types::BlockId::new(ctx, PrincipalId::new(), 1),
+            BlockKind::Text,
+        )
+        .content("mirrored from the agent")
+        .provenance(provenance)
+        .build();
+        let stamped = d
+            .block_store()
+            .insert_from_snapshot(ctx, snap, None)
+            .expect("insert_from_snapshot");
+
+        let result = d
+            .dispatch(&[s("block"), s("inspect"), stamped.to_key()], &c)
+            .await;
+        assert!(
+            result
+                .message()
+                .contains("agent:     claude-code 2.1.3 4f1c2a9b in /mnt/project"),
+            "{}",
+            result.message()
+        );
+
+        let result = d
+            .dispatch(
+                &[s("block"), s("list"), s("--agent-session"), s("4f1c2a9b")],
+                &c,
+            )
+            .await;
+        assert!(result.is_ok(), "list failed: {}", result.message());
+        assert!(result.message().contains("mirrored from the agent"));
+        assert!(!result.message().contains("typed by a person"));
+    }
+
+    #[tokio::test]
+    async fn block_reflow_rewraps_and_read_honors_it() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = register_context_with_doc(&d, Some("c"), principal);
+        let c = caller_with_context(ctx);
+        let long = "word ".repeat(30);
+        let bid = insert_text_block(&d, ctx, long.trim_end());
+        let read = |extra: &[&str]| {
+            let mut argv = vec![s("block"), s("read"), bid.to_key(), s("--no-line-numbers")];
+            argv.extend(extra.iter().map(|a| s(a)));
+            argv
+        };
+
+        let result = d
+            .dispatch(
+                &[s("block"), s("reflow"), bid.to_key(), s("--width"), s("40")],
+                &c,
+            )
+            .await;
+        assert!(result.is_ok(), "reflow failed: {}", result.message());
+        let body = d.dispatch(&read(&[]), &c).await.message().to_string();
+        assert_eq!(body.lines().count(), 4, "{body}");
+        assert!(body.lines().all(|l| l.len() <= 40), "{body}");
+
+        // A narrower setting alone wraps the read view; --raw shows the text.
+        d.block_store()
+            .set_reflow(
+                ctx,
+                &bid,
+                Some(kaijutsu_types::Reflow::new(20, false).unwrap()),
+            )
+            .unwrap();
+        let body = d.dispatch(&read(&[]), &c).await.message().to_string();
+        assert_eq!(body.lines().count(), 8, "{body}");
+        let body = d
+            .dispatch(&read(&["--raw"]), &c)
+            .await
+            .message()
+            .to_string();
+        assert_eq!(body.lines().count(), 4, "{body}");
+
+        let result = d
+            .dispatch(
+                &[s("block"), s("reflow"), bid.to_key(), s("--width"), s("5")],
+                &c,
+            )
+            .await;
+        assert!(!result.is_ok(), "out-of-range width should be refused");
+    }
+
     // ── New: block status ─────────────────────────────────────────────
 
     #[tokio::test]
@@ -2740,6 +3629,59 @@ mod tests {
         assert_eq!(snap.content, "hello world", "content not appended");
     }
 
+    #[tokio::test]
+    async fn block_lock_warns_or_holds_other_writers() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = register_context_with_doc(&d, Some("c"), principal);
+        let mut owner = caller_with_context(ctx);
+        owner.principal_id = principal;
+        let other = caller_with_context(ctx);
+        let bid = insert_text_block(&d, ctx, "hello");
+        let append = |text: &str| vec![s("block"), s("append"), bid.to_key(), s("--text"), s(text)];
+
+        let locked = d
+            .dispatch(
+                &[
+                    s("block"),
+                    s("lock"),
+                    bid.to_key(),
+                    s("--note"),
+                    s("rewording"),
+                ],
+                &owner,
+            )
+            .await;
+        assert!(locked.is_ok(), "lock failed: {}", locked.message());
+
+        let own = d.dispatch(&append("!"), &owner).await;
+        assert!(!own.message().contains("warning"), "{}", own.message());
+        let warned = d.dispatch(&append("?"), &other).await;
+        assert!(warned.is_ok(), "{}", warned.message());
+        assert!(
+            warned.message().contains("rewording"),
+            "{}",
+            warned.message()
+        );
+
+        let set = d
+            .dispatch(&[s("block"), s("locks"), s("--policy"), s("hold")], &owner)
+            .await;
+        assert!(set.message().contains("policy: hold"), "{}", set.message());
+        let held = d.dispatch(&append("?"), &other).await;
+        assert!(!held.is_ok(), "hold policy should refuse the write");
+
+        let unlocked = d
+            .dispatch(&[s("block"), s("unlock"), bid.to_key()], &owner)
+            .await;
+        assert!(
+            unlocked.message().contains("unlocked"),
+            "{}",
+            unlocked.message()
+        );
+        assert!(d.dispatch(&append("."), &other).await.is_ok());
+    }
+
     #[tokio::test]
     async fn block_append_emits_size_record() {
         use crate::kj::KjResult;
@@ -2950,6 +3892,91 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn block_diff_inserted_line_does_not_shift_the_rest() {
+        use crate::kj::KjResult;
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = register_context_with_doc(&d, Some("c"), principal);
+        let c = caller_with_context(ctx);
+        let bid = insert_text_block(&d, ctx, "a\nNEW\nb\nc");
+
+        let result = d
+            .dispatch(
+                &[s("block"), s("diff"), bid.to_key(), s("--original"), s("a\nb\nc")],
+                &c,
+            )
+            .await;
+        let body = result.message().to_string();
+        assert!(body.contains("  a\n+ NEW\n  b\n  c\n"), "{body}");
+        match result {
+            KjResult::Ok { data: Some(v), .. } => {
+                assert_eq!(v["added_lines"], 1);
+                assert_eq!(v["changed_lines"], 0);
+                assert_eq!(v["removed_lines"], 0);
+            }
+            other => panic!("expected Ok with data, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn block_suggest_waits_for_the_owner_to_accept() {
+        use crate::kj::KjResult;
+        let d = test_dispatcher().await;
+        let owner = PrincipalId::new();
+        let ctx = register_context_with_doc(&d, Some("c"), owner);
+        let bid = insert_text_block(&d, ctx, "one\ntwo\n");
+        let mut agent = caller_with_context(ctx);
+        agent.principal_id = PrincipalId::new();
+
+        let result = d
+            .dispatch(
+                &[
+                    s("block"),
+                    s("suggest"),
+                    bid.to_key(),
+                    s("--content"),
+                    s("one\n2\n"),
+                ],
+                &agent,
+            )
+            .await;
+        let id = match result {
+            KjResult::Ok { data: Some(v), .. } => v["id"].as_u64().unwrap(),
+            other => panic!("expected Ok with data, got {other:?}"),
+        };
+        let listing = d
+            .dispatch(&[s("block"), s("suggestions"), bid.to_key()], &agent)
+            .await;
+        assert!(
+            listing.message().contains("- two\n"),
+            "{}",
+            listing.message()
+        );
+        assert!(listing.message().contains("+ 2\n"), "{}", listing.message());
+
+        let denied = d
+            .dispatch(&[s("block"), s("accept"), id.to_string()], &agent)
+            .await;
+        assert!(
+            !denied.is_ok(),
+            "proposer can't accept their own suggestion"
+        );
+
+        let mut c = caller_with_context(ctx);
+        c.principal_id = owner;
+        let accepted = d
+            .dispatch(&[s("block"), s("accept"), id.to_string()], &c)
+            .await;
+        assert!(accepted.is_ok(), "accept failed: {}", accepted.message());
+        let snap = d
+            .block_store()
+            .get_block_snapshot(ctx, &bid)
+            .unwrap()
+            .unwrap();
+        assert_eq!(snap.content, "one\n2\n");
+    }
+
     // ── Range spec parser unit tests ───────────────────────────────────
 
     #[test]
@@ -1,8 +1,8 @@
 //! `kj config` — read and edit the CRDT-owned config files.
 //!
-//! Config files (`models.toml`, `system.md`, `theme.toml`, `mcp.toml`) live at
-//! `/etc/config` on the same CRDT-native backend as `/etc/rc` (slice 2,
-//! `docs/config-crdt-ownership.md`): the kernel is the sole owner — no host
+//! Config files (`models.toml`, `system.md`, `theme.toml`, `mcp.toml`,
+//! `webhooks.toml`, `hooks.toml`, `analytics.toml`, `issues.toml`, `abandon.toml`, `consent.toml`) live at `/etc/config` on the same
+//! CRDT-native backend as `/etc/rc` (slice 2, `docs/config-crdt-ownership.md`): the kernel is the sole owner — no host
 //! file, no write-through. `show`/`list` read the live CRDT; `set` writes it
 //! (requiring `--content` or piped stdin); `edit` does the same but opens an
 //! interactive vi session (the `kj rc edit` analog) when no body is given;
@@ -18,6 +18,10 @@
 //! rather than silently dropped at boot (`initialize_llm_registry`) and
 //! discovered only when a turn later hangs on the missing provider. See
 //! [`validate_config_write`].
+//!
+//! After a successful write the kernel re-applies the file where it can
+//! (`Kernel::apply_config_file`: `models.toml` swaps the LLM registry live)
+//! and the applied/rejected diff is appended to the command's output.
 
 use clap::{Parser, Subcommand};
 use kaijutsu_types::ContentType;
@@ -28,7 +32,7 @@ use super::{KjCaller, KjDispatcher, KjResult, clap_help_for};
 #[derive(Parser, Debug)]
 #[command(
     name = "config",
-    about = "CRDT-owned config: kernel-global at /etc/config (models.toml, system.md, theme.toml, mcp.toml) + per-client at /etc/client (metronome.toml)",
+    about = "CRDT-owned config: kernel-global at /etc/config (models.toml, system.md, theme.toml, mcp.toml, webhooks.toml, hooks.toml, analytics.toml, issues.toml, abandon.toml, consent.toml) + per-client at /etc/client (metronome.toml)",
     disable_help_subcommand = true,
     no_binary_name = true
 )]
@@ -138,14 +142,46 @@ fn config_canonical(path: &str) -> Result<String, String> {
 
 /// Validate a config file's content before it's written to the CRDT.
 ///
-/// Only `models.toml` gets structural validation today: the TOML must parse,
-/// and every `[providers.<name>]` table name must be a provider type
-/// `Provider::from_config` understands (`crate::llm::SUPPORTED_PROVIDER_TYPES`).
+/// `webhooks.toml` must parse as a [`crate::webhooks::WebhookConfig`] (unknown
+/// keys and event names rejected), since a typo there means hooks that never
+/// fire; `hooks.toml` likewise as a [`crate::hook_policy::HookPolicy`] (bad
+/// globs and regexes rejected), since a typo there is a guardrail that never
+/// trips. `analytics.toml` must parse as a
+/// [`crate::analytics::AnalyticsConfig`], so a bad endpoint or epsilon is
+/// refused rather than quietly leaving analytics off. `issues.toml` must
+/// parse as a [`crate::issues::IssueConfig`] (repo shape and credentials
+/// checked), so a bad tracker fails here and not mid-export. `abandon.toml`
+/// must parse as a [`crate::abandoned::AbandonConfig`] (block kinds
+/// checked), and `consent.toml` as a
+/// [`crate::consent_prompts::ConsentConfig`] (tool globs checked). Beyond that, only `models.toml` gets structural validation: the TOML
+/// must parse, and every `[providers.<name>]` table name must be a provider
+/// type `Provider::from_config` understands (`crate::llm::SUPPORTED_PROVIDER_TYPES`).
 /// This is deliberately narrow — not a general schema validator, just the one
 /// closed-set invariant that turns a silent boot-time drop
 /// (`initialize_llm_registry`) into a loud write-time rejection, per the
 /// house fail-loud posture (2026-06-30 config papercuts, Fix 2).
 fn validate_config_write(canonical: &str, content: &str) -> Result<(), String> {
+    if canonical == kaijutsu_types::paths::config_path(crate::webhooks::WEBHOOKS_CONFIG_FILE) {
+        return crate::webhooks::WebhookConfig::parse(content).map(|_| ());
+    }
+    if canonical
+        == kaijutsu_types::paths::config_path(crate::hook_policy::HOOK_POLICY_CONFIG_FILE)
+    {
+        return crate::hook_policy::HookPolicy::parse(content).map(|_| ());
+    }
+    if canonical == kaijutsu_types::paths::config_path(crate::analytics::ANALYTICS_CONFIG_FILE) {
+        return crate::analytics::AnalyticsConfig::parse(content).map(|_| ());
+    }
+    if canonical == kaijutsu_types::paths::config_path(crate::issues::ISSUES_CONFIG_FILE) {
+        return crate::issues::IssueConfig::parse(content).map(|_| ());
+    }
+    if canonical == kaijutsu_types::paths::config_path(crate::abandoned::ABANDON_CONFIG_FILE) {
+        return crate::abandoned::AbandonConfig::parse(content).map(|_| ());
+    }
+    if canonical == kaijutsu_types::paths::config_path(crate::consent_prompts::CONSENT_CONFIG_FILE)
+    {
+        return crate::consent_prompts::ConsentConfig::parse(content).map(|_| ());
+    }
     if canonical != kaijutsu_types::paths::config_path("models.toml") {
         return Ok(());
     }
@@ -161,6 +197,25 @@ fn validate_config_write(canonical: &str, content: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Every seeded `/etc/config` file that the write-time checks would reject
+/// as it stands now, as `(path, problem)`. A file that can't be read is left
+/// out — its loader falls back to the default. The server's readiness probe
+/// reports these.
+pub async fn config_problems(kernel: &crate::Kernel) -> Vec<(String, String)> {
+    use crate::vfs::VfsOps;
+
+    let mut problems = Vec::new();
+    for (path, _) in crate::config_seed::config_seed_files() {
+        let Ok(bytes) = kernel.vfs().read_all(std::path::Path::new(&path)).await else {
+            continue;
+        };
+        if let Err(e) = validate_config_write(&path, &String::from_utf8_lossy(&bytes)) {
+            problems.push((path, e));
+        }
+    }
+    problems
+}
+
 impl KjDispatcher {
     pub(crate) async fn dispatch_config(&self, argv: &[String], caller: &KjCaller) -> KjResult {
         if argv.is_empty() {
@@ -202,7 +257,10 @@ impl KjDispatcher {
             | ConfigCommand::Reset { path } => config_canonical(path).ok(),
             _ => None,
         };
-        let result = match parsed.command {
+        // An `edit` with no body only opens the editor; the write (if any)
+        // lands later, so there is nothing new to apply yet.
+        let applies = !matches!(parsed.command, ConfigCommand::Edit { content: None, .. });
+        let mut result = match parsed.command {
             ConfigCommand::List { json } => self.config_list(json).await,
             ConfigCommand::Show { path, json, raw } => self.config_show(&path, json, raw).await,
             ConfigCommand::Set { path, content } => {
@@ -217,6 +275,17 @@ impl KjDispatcher {
             && matches!(result, KjResult::Ok { .. })
         {
             self.kernel().invalidate_config_file_cache(&canonical);
+            // Live-apply what the kernel consumes (models.toml today) and
+            // append the applied/rejected diff to the write's confirmation.
+            if applies
+                && let Some(report) = self.kernel().apply_config_file(&canonical).await
+                && let KjResult::Ok { message, .. } = &mut result
+            {
+                if !message.is_empty() && !message.ends_with('\n') {
+                    message.push('\n');
+                }
+                message.push_str(&report.to_string());
+            }
         }
         result
     }
@@ -868,6 +937,41 @@ mod tests {
         );
     }
 
+    /// A valid `models.toml` write swaps the kernel's LLM registry live and
+    /// reports the diff — in the output and on the config flow bus.
+    #[tokio::test]
+    async fn set_models_toml_applies_live() {
+        let d = test_dispatcher_crdt_rc().await;
+        let c = test_caller();
+        let mut sub = d.kernel().config_flows().subscribe("config.*");
+        let toml = "default_provider = \"ollama\"\n[providers.ollama]\ndefault_model = \"qwen3\"\n";
+        let result = d
+            .dispatch(
+                &[
+                    s("config"),
+                    s("set"),
+                    s("models.toml"),
+                    s("--content"),
+                    s(toml),
+                ],
+                &c,
+            )
+            .await;
+        match &result {
+            KjResult::Ok { message, .. } => {
+                assert!(message.contains("provider ollama: added"), "{message}")
+            }
+            other => panic!("expected Ok, got {other:?}"),
+        }
+        assert_eq!(
+            d.kernel().llm().read().await.default_provider_name(),
+            Some("ollama")
+        );
+        let msg = sub.try_recv().expect("config.applied published");
+        let crate::flows::ConfigFlow::Applied { report } = msg.payload;
+        assert!(report.rejected.is_empty(), "{report}");
+    }
+
     /// Validation is narrow-scoped to `models.toml` — other config files
     /// aren't TOML at all (system.md) or just don't get the providers-table
     /// check, so `set` must not choke on content that wouldn't parse as this
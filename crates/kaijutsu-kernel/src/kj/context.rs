@@ -1,7 +1,7 @@
-//! Context subcommands: list, info, switch, create, set, log, move, archive, remove, retag.
+//! Context subcommands: list, info, switch, create, clone, set, log, move, archive, remove, retag.
 
 use clap::{Args, Parser, Subcommand};
-use kaijutsu_types::{BlockId, ConsentMode, ContentType, ContextId, ContextState, EdgeKind};
+use kaijutsu_types::{ConsentMode, ContentType, ContextId, ContextState, EdgeKind};
 
 use crate::kernel_db::{ContextEdgeRow, ContextRow, ContextShellRow, DemoteOutcome, PromoteOutcome};
 
@@ -93,6 +93,19 @@ enum ContextCommand {
         #[command(flatten)]
         config: ContextConfigArgs,
     },
+    /// Copy a context (default: current) into a new root context with no
+    /// lineage back to the source. `--squash` also rewrites the copy's
+    /// history, for sharing a cleaned-up conversation.
+    Clone {
+        context: Option<String>,
+        /// Label for the clone
+        #[arg(long, short = 'n')]
+        name: Option<String>,
+        /// Keep only the final content: drop Thinking and compacted blocks and
+        /// re-author the rest in a single pass
+        #[arg(long)]
+        squash: bool,
+    },
     /// Get-or-create the well-known "scratch" context.
     #[command(alias = "self")]
     Scratch,
@@ -165,6 +178,39 @@ enum ContextCommand {
         #[arg(long, conflicts_with_all = ["window", "mark"])]
         clear: bool,
     },
+    /// Show or set how the prompt is assembled from the block log:
+    /// `linear`, `path[:<key>]`, `window:<n>`, `checkpoint`, joined with `+`.
+    Assemble {
+        context: Option<String>,
+        /// Strategy spec, e.g. `checkpoint+window:16`. Omit to show the
+        /// current one.
+        #[arg(long)]
+        strategy: Option<String>,
+        /// Remove the stored strategy (back to the hydration window, or linear).
+        #[arg(long, conflicts_with = "strategy")]
+        clear: bool,
+    },
+    /// Freeze the context as a read-only share and print its token. Anyone
+    /// holding the token can read the snapshot — over an observer
+    /// connection (`ssh observer@host`) or via `share-html` — until it
+    /// expires or is revoked.
+    Share {
+        context: Option<String>,
+        /// Seconds until it expires (default a week, at most 30 days)
+        #[arg(long)]
+        ttl: Option<u64>,
+    },
+    /// List a context's shares, newest first.
+    Shares {
+        context: Option<String>,
+        /// Every context's shares
+        #[arg(long, conflicts_with = "context")]
+        all: bool,
+    },
+    /// Revoke a share. Its creator or the context's creator may.
+    Unshare { token: String },
+    /// Print a live share as a standalone HTML page.
+    ShareHtml { token: String },
 }
 
 /// Settable context configuration shared by `create` and `set`.
@@ -357,6 +403,7 @@ impl KjDispatcher {
                 | ContextCommand::Remove { .. }
                 | ContextCommand::Retag { .. }
                 | ContextCommand::Hydrate { .. }
+                | ContextCommand::Assemble { .. }
         ) && let Err(denied) =
             self.require_cap(caller, crate::mcp::Capability::Operator, "context")
         {
@@ -382,6 +429,14 @@ impl KjDispatcher {
                 )
                 .await
             }
+            ContextCommand::Clone {
+                context,
+                name,
+                squash,
+            } => {
+                self.context_clone(context.as_deref(), name.as_deref(), squash, caller)
+                    .await
+            }
             ContextCommand::Scratch => self.context_scratch(caller).await,
             ContextCommand::Set { context, config } => {
                 self.context_set(context.as_deref(), config.into(), caller).await
@@ -413,6 +468,19 @@ impl KjDispatcher {
                 mark,
                 clear,
             } => self.context_hydrate(context.as_deref(), window, mark.as_deref(), clear, caller),
+            ContextCommand::Assemble {
+                context,
+                strategy,
+                clear,
+            } => self.context_assemble(context.as_deref(), strategy.as_deref(), clear, caller),
+            ContextCommand::Share { context, ttl } => {
+                self.context_share(context.as_deref(), ttl, caller)
+            }
+            ContextCommand::Shares { context, all } => {
+                self.context_shares(context.as_deref(), all, caller)
+            }
+            ContextCommand::Unshare { token } => self.context_unshare(&token, caller),
+            ContextCommand::ShareHtml { token } => self.context_share_html(&token),
         }
     }
 
@@ -631,6 +699,23 @@ impl KjDispatcher {
         // (funkMusician, …) need no kernel edit. `docs/chameleon.md`,
         // "context_type is an rc bundle of features".
 
+        // Unset --model / --consent fall back to the caller's preferences. A
+        // preferred provider this kernel doesn't have is skipped, not an error.
+        let prefs = self
+            .kernel_db()
+            .lock()
+            .get_prefs(caller.principal_id)
+            .unwrap_or_default();
+        if cfg.model_spec.is_none()
+            && let Some((provider, model)) = kaijutsu_types::prefs::default_model(&prefs)
+            && self.kernel().llm().read().await.get(provider).is_some()
+        {
+            cfg.model_spec = Some(format!("{provider}/{model}"));
+        }
+        if cfg.consent_spec.is_none() {
+            cfg.consent_spec = kaijutsu_types::prefs::default_consent(&prefs).map(|c| c.to_string());
+        }
+
         // Validate + resolve the rest before any mutation so a typo'd
         // --model/--consent/--env can't leave an orphan context behind.
         let resolved_model = match self.resolve_context_config(&cfg).await {
@@ -720,6 +805,8 @@ impl KjDispatcher {
         // The beat arm now lives in the musician's `create/` rc (run above), not
         // here — see the note at the top of this fn and `docs/chameleon.md`.
 
+        self.emit_context_created(new_id);
+
         let mut msg = format!("created context '{}' ({})", label, new_id.short());
         if !config_changes.is_empty() {
             msg.push_str(&format!(" [{}]", config_changes.join(", ")));
@@ -785,6 +872,7 @@ impl KjDispatcher {
                 return KjResult::Err(format!("kj context scratch: {e}"));
             }
         }
+        self.emit_context_created(new_id);
         KjResult::ok(format!(
             "created scratch context: {} ({})",
             SCRATCH_LABEL,
@@ -792,6 +880,115 @@ impl KjDispatcher {
         ))
     }
 
+    /// `kj context clone [ctx] [--name label] [--squash]` — copy a context's
+    /// document and config into a new root context. Unlike `kj fork`, the
+    /// clone records no `forked_from`, structural edge, or fork marker, so it
+    /// can be handed on without pointing back at its source. Without
+    /// `--squash` the blocks keep their authors, seqs, and ticks (a fork-style
+    /// copy); with it the document is [`crate::block_store::BlockStore::squash_document`]'s
+    /// single-pass rewrite.
+    async fn context_clone(
+        &self,
+        source: Option<&str>,
+        label: Option<&str>,
+        squash: bool,
+        caller: &KjCaller,
+    ) -> KjResult {
+        let source_row = {
+            let db = self.kernel_db().lock();
+            let source_id = match super::refs::resolve_context_arg(source, caller, &db) {
+                Ok(id) => id,
+                Err(e) => return KjResult::Err(format!("kj context clone: {e}")),
+            };
+            match db.get_context(source_id) {
+                Ok(Some(row)) => row,
+                Ok(None) => return KjResult::Err("kj context clone: not found".to_string()),
+                Err(e) => return KjResult::Err(format!("kj context clone: {e}")),
+            }
+        };
+        let source_id = source_row.context_id;
+
+        // Same up-front label check as `kj fork`: fail before the document
+        // copy rather than strand it on a unique-constraint bounce.
+        if let Err(e) = self.ensure_label_available(label) {
+            return KjResult::Err(format!("kj context clone: {e}"));
+        }
+
+        let new_id = ContextId::new();
+        let copy = if squash {
+            self.block_store().squash_document(source_id, new_id)
+        } else {
+            self.block_store().fork_document(source_id, new_id)
+        };
+        if let Err(e) = copy {
+            return KjResult::Err(format!("kj context clone: failed to copy document: {e}"));
+        }
+
+        {
+            let mut db = self.kernel_db().lock();
+            let default_ws = match db.get_or_create_default_workspace(caller.principal_id) {
+                Ok(id) => id,
+                Err(e) => return KjResult::Err(format!("kj context clone: {e}")),
+            };
+            let row = ContextRow {
+                context_id: new_id,
+                label: label.map(str::to_string),
+                provider: source_row.provider.clone(),
+                model: source_row.model.clone(),
+                system_prompt: source_row.system_prompt.clone(),
+                consent_mode: source_row.consent_mode,
+                context_state: ContextState::Live,
+                context_type: source_row.context_type.clone(),
+                created_at: kaijutsu_types::now_millis() as i64,
+                created_by: caller.principal_id,
+                forked_from: None,
+                fork_kind: None,
+                archived_at: None,
+                workspace_id: source_row.workspace_id,
+                preset_id: None,
+                concluded_at: None,
+                last_activity_at: None,
+                promoted_at: None,
+                demoted_at: None,
+                paused_at: None,
+            };
+            // Shell/env/binding/sandbox travel with the row in one transaction,
+            // so the clone can run its tools the way the source did.
+            if let Err(e) = db.insert_forked_context(&row, default_ws, source_id) {
+                return KjResult::Err(format!("kj context clone: {e}"));
+            }
+        }
+
+        {
+            let mut drift = self.drift_router().write();
+            if let Err(e) = drift.register(new_id, label, None, caller.principal_id) {
+                return KjResult::Err(format!("kj context clone: {e}"));
+            }
+            if let (Some(p), Some(m)) = (&source_row.provider, &source_row.model)
+                && let Err(e) = drift.configure_llm(new_id, p, m)
+            {
+                return KjResult::Err(format!("kj context clone: failed to configure model: {e}"));
+            }
+        }
+
+        self.emit_context_created(new_id);
+
+        let short = new_id.short();
+        let display = label.unwrap_or(&short);
+        let how = if squash { "squashed clone" } else { "clone" };
+        let source_short = source_id.short();
+        KjResult::Ok {
+            message: format!("{how} of {source_short} created as '{display}' ({short})"),
+            content_type: ContentType::Plain,
+            ephemeral: false,
+            data: Some(serde_json::json!({
+                "context_id": new_id.to_hex(),
+                "label": label,
+                "squashed": squash,
+            })),
+        }
+    }
+
     /// `kj context set <ctx> [--model p/m] [--system-prompt text] [--consent mode] [--cwd path] [--env KEY=VALUE] [--type t]`
     async fn context_set(
         &self,
@@ -908,8 +1105,14 @@ impl KjDispatcher {
         // The prefix marker: an explicit `--mark` block key, or the context's
         // current tail (pin everything up to now).
         let marker = match mark {
-            Some(key) => match BlockId::from_key(key) {
-                Some(id) => {
+            Some(key) => match kaijutsu_types::resolve_block_prefix(
+                self.block_store()
+                    .get(target_id)
+                    .map(|entry| entry.doc.block_ids_ordered())
+                    .unwrap_or_default(),
+                key,
+            ) {
+                Ok(id) => {
                     // A parseable but non-existent marker would persist durably
                     // and then fail-safe to the whole log every turn — the cost
                     // guard silently OFF forever. Validate it lives in THIS
@@ -928,10 +1131,8 @@ impl KjDispatcher {
                         }
                     }
                 }
-                None => {
-                    return KjResult::Err(format!(
-                        "kj context hydrate: invalid --mark block id '{key}'"
-                    ));
+                Err(e) => {
+                    return KjResult::Err(format!("kj context hydrate: --mark {e}"));
                 }
             },
             None => match self.block_store().last_block_id(target_id) {
@@ -962,6 +1163,212 @@ impl KjDispatcher {
         }
     }
 
+    /// `kj context assemble [<ctx>] [--strategy <spec>]` / `--clear` — show or
+    /// set the context's prompt-assembly strategy (`kaijutsu_types::assembly`).
+    ///
+    /// The `window` stage pins to the hydration marker from `kj context
+    /// hydrate` when one is set; without a stored strategy that policy alone
+    /// decides (`window:<n>`). `path:<key>` must name a block in the context.
+    fn context_assemble(
+        &self,
+        target_arg: Option<&str>,
+        strategy: Option<&str>,
+        clear: bool,
+        caller: &KjCaller,
+    ) -> KjResult {
+        let target_id = {
+            let db = self.kernel_db().lock();
+            match super::refs::resolve_context_arg(target_arg, caller, &db) {
+                Ok(id) => id,
+                Err(e) => return KjResult::Err(format!("kj context assemble: {e}")),
+            }
+        };
+
+        if clear {
+            return match self
+                .kernel_db()
+                .lock()
+                .set_context_assembly(target_id, None)
+            {
+                Ok(()) => KjResult::ok("assembly strategy cleared".to_string()),
+                Err(e) => KjResult::Err(format!("kj context assemble: {e}")),
+            };
+        }
+
+        let Some(spec) = strategy else {
+            let (stored, policy) = {
+                let db = self.kernel_db().lock();
+                match (
+                    db.get_context_assembly(target_id),
+                    db.get_hydration_policy(target_id),
+                ) {
+                    (Ok(stored), Ok(policy)) => (stored, policy),
+                    (Err(e), _) | (_, Err(e)) => {
+                        return KjResult::Err(format!("kj context assemble: {e}"));
+                    }
+                }
+            };
+            let stored_is_set = stored.is_some();
+            let effective = crate::llm::ContextAssembler::resolve(stored, policy);
+            let source = if stored_is_set {
+                "stored"
+            } else if policy.is_some() {
+                "hydration window"
+            } else {
+                "default"
+            };
+            return KjResult::ok_with_data(
+                format!("{} ({source})", effective.assembly()),
+                serde_json::json!({
+                    "context_id": target_id.to_hex(),
+                    "strategy": effective.assembly().to_string(),
+                    "source": source,
+                    "marker": effective.marker().map(|m| m.to_key()),
+                }),
+            );
+        };
+
+        let assembly: kaijutsu_types::Assembly = match spec.parse() {
+            Ok(a) => a,
+            Err(e) => return KjResult::Err(format!("kj context assemble: {e}")),
+        };
+        // A path pinned to a block outside this context would fail every
+        // turn; refuse it now.
+        for stage in assembly.stages() {
+            if let kaijutsu_types::AssemblyStage::Path(Some(id)) = stage {
+                match self.block_store().get_block_snapshot(target_id, id) {
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        return KjResult::Err(format!(
+                            "kj context assemble: path block '{}' is not in this context",
+                            id.to_key()
+                        ));
+                    }
+                    Err(e) => {
+                        return KjResult::Err(format!(
+                            "kj context assemble: could not verify path block: {e}"
+                        ));
+                    }
+                }
+            }
+        }
+
+        match self
+            .kernel_db()
+            .lock()
+            .set_context_assembly(target_id, Some(&assembly))
+        {
+            Ok(()) => KjResult::ok_with_data(
+                format!("assembly strategy set — {assembly}"),
+                serde_json::json!({
+                    "context_id": target_id.to_hex(),
+                    "strategy": assembly.to_string(),
+                }),
+            ),
+            Err(e) => KjResult::Err(format!("kj context assemble: {e}")),
+        }
+    }
+
+    /// `kj context share [<ctx>] [--ttl <secs>]` — freeze the context under a
+    /// new token. The workflow lives in `crate::context_share`.
+    fn context_share(
+        &self,
+        target_arg: Option<&str>,
+        ttl: Option<u64>,
+        caller: &KjCaller,
+    ) -> KjResult {
+        let target_id = {
+            let db = self.kernel_db().lock();
+            match super::refs::resolve_context_arg(target_arg, caller, &db) {
+                Ok(id) => id,
+                Err(e) => return KjResult::Err(format!("kj context share: {e}")),
+            }
+        };
+        let now = kaijutsu_types::now_millis();
+        match crate::context_share::create(
+            self.kernel_db(),
+            self.block_store(),
+            target_id,
+            caller.principal_id,
+            ttl,
+            now,
+        ) {
+            Ok(share) => KjResult::ok_with_data(
+                format!(
+                    "shared {} ({} blocks at version {}) for {}h\ntoken: {}\n",
+                    share.title(),
+                    share.block_count,
+                    share.version,
+                    (share.expires_at - now) / 3_600_000,
+                    share.token,
+                ),
+                share_record(&share, now),
+            ),
+            Err(e) => KjResult::Err(format!("kj context share: {e}")),
+        }
+    }
+
+    /// `kj context shares [<ctx>] [--all]` — shares and whether each still opens.
+    fn context_shares(&self, target_arg: Option<&str>, all: bool, caller: &KjCaller) -> KjResult {
+        let shares = {
+            let db = self.kernel_db().lock();
+            let scope = if all {
+                None
+            } else {
+                match super::refs::resolve_context_arg(target_arg, caller, &db) {
+                    Ok(id) => Some(id),
+                    Err(e) => return KjResult::Err(format!("kj context shares: {e}")),
+                }
+            };
+            match db.list_context_shares(scope) {
+                Ok(shares) => shares,
+                Err(e) => return KjResult::Err(format!("kj context shares: {e}")),
+            }
+        };
+        let now = kaijutsu_types::now_millis();
+        let records: Vec<_> = shares.iter().map(|s| share_record(s, now)).collect();
+        if shares.is_empty() {
+            return KjResult::ok_with_data("(no shares)\n".to_string(), records.into());
+        }
+        let mut out = String::new();
+        for share in &shares {
+            out.push_str(&format!(
+                "{}  {}  v{}  {} blocks  by {}  [{}]\n",
+                share.token,
+                share.title(),
+                share.version,
+                share.block_count,
+                share.created_by.short(),
+                share.status(now),
+            ));
+        }
+        KjResult::ok_with_data(out, records.into())
+    }
+
+    fn context_unshare(&self, token: &str, caller: &KjCaller) -> KjResult {
+        let now = kaijutsu_types::now_millis();
+        match crate::context_share::revoke(self.kernel_db(), token, caller.principal_id, now) {
+            Ok(share) => KjResult::ok_with_data(
+                format!(
+                    "revoked share {} of {}\n",
+                    crate::context_share::short_token(token),
+                    share.title()
+                ),
+                share_record(&share, now),
+            ),
+            Err(e) => KjResult::Err(format!("kj context unshare: {e}")),
+        }
+    }
+
+    fn context_share_html(&self, token: &str) -> KjResult {
+        match crate::context_share::open(self.kernel_db(), token, kaijutsu_types::now_millis()) {
+            Ok((share, blocks)) => {
+                KjResult::ok(kaijutsu_types::context_share::render_html(&share, &blocks))
+            }
+            Err(e) => KjResult::Err(format!("kj context share-html: {e}")),
+        }
+    }
+
     /// `kj context log [<ctx>]` — show fork lineage from context up to root.
     fn context_log(&self, target_arg: Option<&str>, caller: &KjCaller) -> KjResult {
         let db = self.kernel_db().lock();
@@ -1470,6 +1877,21 @@ where
     )
 }
 
+fn share_record(share: &kaijutsu_types::ContextShare, now: u64) -> serde_json::Value {
+    serde_json::json!({
+        "token": share.token,
+        "context_id": share.context_id.to_hex(),
+        "label": share.label,
+        "version": share.version,
+        "block_count": share.block_count,
+        "created_by": share.created_by.to_hex(),
+        "created_at": share.created_at,
+        "expires_at": share.expires_at,
+        "revoked_at": share.revoked_at,
+        "status": share.status(now).as_str(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::kernel_db::ContextEdgeRow;
@@ -1655,6 +2077,80 @@ mod tests {
         assert!(!r2.is_ok(), "expected error, got: {}", r2.message());
     }
 
+    #[tokio::test]
+    async fn context_clone_squash_drops_thinking_and_lineage() {
+        use kaijutsu_types::{BlockKind, ContentType, Role, Status};
+
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let source = register_context(&d, Some("source"), None, principal);
+        let store = d.block_store();
+        store
+            .create_document(source, crate::DocumentKind::Conversation, None)
+            .unwrap();
+        for (kind, text) in [
+            (BlockKind::Text, "question"),
+            (BlockKind::Thinking, "private reasoning"),
+            (BlockKind::Text, "answer"),
+        ] {
+            store
+                .insert_block(
+                    source,
+                    None,
+                    None,
+                    Role::Model,
+                    kind,
+                    text,
+                    Status::Done,
+                    ContentType::Plain,
+                )
+                .unwrap();
+        }
+
+        let c = caller_with_context(source);
+        let result = d
+            .dispatch(
+                &[
+                    s("context"),
+                    s("clone"),
+                    s("--name"),
+                    s("shared"),
+                    s("--squash"),
+                ],
+                &c,
+            )
+            .await;
+        assert!(result.is_ok(), "clone failed: {}", result.message());
+
+        let row = d
+            .kernel_db()
+            .lock()
+            .find_context_by_label("shared")
+            .unwrap()
+            .expect("clone row");
+        assert_eq!(row.forked_from, None, "a clone carries no lineage");
+        let contents: Vec<_> = store
+            .block_snapshots(row.context_id)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.content)
+            .collect();
+        assert_eq!(contents, ["question", "answer"]);
+
+        // A plain clone keeps every block.
+        let plain = d
+            .dispatch(&[s("context"), s("clone"), s("--name"), s("copy")], &c)
+            .await;
+        assert!(plain.is_ok(), "clone failed: {}", plain.message());
+        let copy = d
+            .kernel_db()
+            .lock()
+            .find_context_by_label("copy")
+            .unwrap()
+            .expect("clone row");
+        assert_eq!(store.block_snapshots(copy.context_id).unwrap().len(), 3);
+    }
+
     #[tokio::test]
     async fn context_help() {
         let d = test_dispatcher().await;
@@ -1856,6 +2352,133 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn context_share_freezes_lists_exports_and_revokes() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = register_context(&d, Some("shared"), None, principal);
+        d.block_store()
+            .create_document(ctx, crate::DocumentKind::Conversation, None)
+            .unwrap();
+        d.block_store()
+            .insert_block(
+                ctx,
+                None,
+                None,
+                kaijutsu_crdt::Role::User,
+                kaijutsu_crdt::BlockKind::Text,
+                "a < b",
+                kaijutsu_crdt::Status::Done,
+                kaijutsu_crdt::ContentType::Plain,
+            )
+            .unwrap();
+        let c = caller_with_context(ctx);
+
+        let token = match d.dispatch(&[s("context"), s("share")], &c).await {
+            KjResult::Ok { data: Some(v), .. } => v["token"].as_str().unwrap().to_string(),
+            other => panic!("expected Ok with data, got {other:?}"),
+        };
+        let listing = d.dispatch(&[s("context"), s("shares")], &c).await;
+        assert!(
+            listing.message().contains(&format!("{token}  shared  ")),
+            "{}",
+            listing.message()
+        );
+        assert!(
+            listing.message().contains("[live]"),
+            "{}",
+            listing.message()
+        );
+
+        let html = d
+            .dispatch(&[s("context"), s("share-html"), token.clone()], &c)
+            .await;
+        assert!(
+            html.message().contains("<pre>a &lt; b</pre>"),
+            "{}",
+            html.message()
+        );
+
+        let r = d
+            .dispatch(&[s("context"), s("unshare"), token.clone()], &c)
+            .await;
+        assert!(r.is_ok(), "unshare failed: {}", r.message());
+        let closed = d
+            .dispatch(&[s("context"), s("share-html"), token], &c)
+            .await;
+        assert!(!closed.is_ok(), "a revoked share doesn't open");
+    }
+
+    #[tokio::test]
+    async fn context_assemble_shows_sets_and_clears() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = register_context(&d, Some("assembled"), None, principal);
+        let c = caller_with_context(ctx);
+
+        let r = d.dispatch(&[s("context"), s("assemble")], &c).await;
+        assert!(r.is_ok(), "show failed: {}", r.message());
+        assert!(
+            r.message().starts_with("linear (default)"),
+            "{}",
+            r.message()
+        );
+
+        let r = d
+            .dispatch(
+                &[
+                    s("context"),
+                    s("assemble"),
+                    s("--strategy"),
+                    s("checkpoint+window:8"),
+                ],
+                &c,
+            )
+            .await;
+        assert!(r.is_ok(), "set failed: {}", r.message());
+        assert_eq!(
+            d.kernel_db().lock().get_context_assembly(ctx).unwrap(),
+            Some("checkpoint+window:8".parse().unwrap())
+        );
+
+        let r = d
+            .dispatch(
+                &[s("context"), s("assemble"), s("--strategy"), s("tail:3")],
+                &c,
+            )
+            .await;
+        assert!(!r.is_ok(), "an unknown stage must be rejected");
+
+        let phantom = kaijutsu_types::BlockId::new(ctx, principal, 99).to_key();
+        let r = d
+            .dispatch(
+                &[
+                    s("context"),
+                    s("assemble"),
+                    s("--strategy"),
+                    s(&format!("path:{phantom}")),
+                ],
+                &c,
+            )
+            .await;
+        assert!(
+            !r.is_ok(),
+            "a path block outside the context must be rejected"
+        );
+
+        let r = d
+            .dispatch(&[s("context"), s("assemble"), s("--clear")], &c)
+            .await;
+        assert!(r.is_ok(), "clear failed: {}", r.message());
+        assert!(
+            d.kernel_db()
+                .lock()
+                .get_context_assembly(ctx)
+                .unwrap()
+                .is_none()
+        );
+    }
+
     #[tokio::test]
     async fn context_hydrate_requires_window_or_clear() {
         let d = test_dispatcher().await;
@@ -144,6 +144,9 @@ impl KjDispatcher {
             move |scm: SessionContextMap,
                   sid: SessionId,
                   tools: &mut kaish_kernel::ToolRegistry| {
+                // `testreport` only parses its stdin, so even a headless or
+                // read-only shell gets it.
+                tools.register(crate::runtime::testreport_builtin::TestReportBuiltin);
                 if let Some(d) = dispatcher {
                     // The opener captured for this materialized shell: who's
                     // running it + the context they're in. `vi` records it on the
@@ -203,6 +206,8 @@ impl KjDispatcher {
             // authority (deny-by-default — a context with no binding, or a
             // binding without the grant, gets no external commands). PATH is
             // the kernel's startup capture; kaish never reads OS env itself.
+            // A sandbox profile only narrows an `exec` grant: with one, host
+            // commands go through the confined launcher instead of kaish.
             let external_exec = if self
                 .kernel()
                 .broker()
@@ -210,8 +215,32 @@ impl KjDispatcher {
                 .await
                 .is_some_and(|b| b.allows(&crate::mcp::Capability::Exec))
             {
-                crate::runtime::embedded_kaish::ExternalExec::Allow {
-                    path: self.kernel().host_path().map(str::to_string),
+                let profile = self.kernel_db().lock().get_context_sandbox(context_id);
+                match profile {
+                    Ok(Some(profile)) => {
+                        crate::runtime::embedded_kaish::ExternalExec::Sandboxed(Arc::new(
+                            crate::runtime::sandbox::ExecSandbox::new(
+                                self.kernel(),
+                                self.block_store().clone(),
+                                context_id,
+                                principal,
+                                profile,
+                            )
+                            .await,
+                        ))
+                    }
+                    Ok(None) => crate::runtime::embedded_kaish::ExternalExec::Allow {
+                        path: self.kernel().host_path().map(str::to_string),
+                    },
+                    // An unreadable profile must not fall open to unconfined
+                    // exec.
+                    Err(e) => {
+                        tracing::warn!(
+                            context = %context_id.to_hex(),
+                            "failed to read sandbox profile, denying host commands: {e}"
+                        );
+                        crate::runtime::embedded_kaish::ExternalExec::Deny
+                    }
                 }
             } else {
                 crate::runtime::embedded_kaish::ExternalExec::Deny
@@ -440,6 +469,70 @@ mod tests {
         );
     }
 
+    /// A sandbox profile narrows an `exec` grant: a host binary off the
+    /// profile's allowlist is refused (126), and the refusal lands in the
+    /// context as an Error block. `mount` is the same real host binary the
+    /// exec-granted test runs successfully.
+    #[tokio::test]
+    async fn sandboxed_shell_refuses_binary_off_allowlist() {
+        let d = dispatcher_with_full_broker().await;
+        d.kernel()
+            .mount("/", crate::vfs::backends::LocalBackend::read_only("/"))
+            .await;
+        let principal = PrincipalId::new();
+        let ctx = register_context(&d, Some("sandboxed"), None, principal);
+        d.block_store()
+            .create_document(ctx, kaijutsu_types::DocKind::Conversation, None)
+            .expect("create_document");
+        grant_broad_binding(&d, ctx, true).await;
+        let profile = kaijutsu_types::SandboxProfile {
+            binaries: vec!["uname".into()],
+            network: true,
+            ..Default::default()
+        };
+        d.kernel_db()
+            .lock()
+            .set_context_sandbox(ctx, Some(&profile))
+            .unwrap();
+
+        let kaish = d
+            .materialize_context_kaish(
+                "sandbox-deny",
+                principal,
+                ctx,
+                SessionId::new(),
+                None,
+                Arc::new(NoopBlockSource),
+            )
+            .await
+            .expect("materialize context shell");
+
+        let res = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            kaish.execute_with_options("mount", ExecuteOptions::default()),
+        )
+        .await
+        .expect("a refused command must return promptly")
+        .expect("exec returns");
+        assert_eq!(res.code, 126, "`mount` should be refused: {}", res.err);
+        assert!(
+            res.err.contains("not in this context's sandbox"),
+            "{}",
+            res.err
+        );
+
+        let snapshots = d
+            .block_store()
+            .block_snapshots(ctx)
+            .expect("block_snapshots");
+        let errors: Vec<_> = snapshots
+            .iter()
+            .filter(|b| b.kind == kaijutsu_types::BlockKind::Error)
+            .collect();
+        assert_eq!(errors.len(), 1, "expected one violation block");
+        assert!(errors[0].content.contains("'mount'"), "{}", errors[0].content);
+    }
+
     /// A materialized context shell must be seeded from the context's durable
     /// env (`context_env`) — that is the whole point of "shared state that
     /// evolves over the context lifetime." This test fails if materialization
@@ -0,0 +1,337 @@
+//! Digest subscriptions: `kj drift digest add|rm|ls` and the scheduler that
+//! delivers them.
+//!
+//! A subscription lives in the [`DriftRouter`](crate::drift::DriftRouter)
+//! (mirrored to the `digest_subscriptions` table). [`spawn_digest_scheduler`]
+//! wakes every [`DIGEST_TICK`], asks the router which subscriptions are due,
+//! distills each source with the same summarizer as `kj drift pull`, and
+//! inserts the result into the subscriber as a `Distill` drift block.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Subcommand;
+use kaijutsu_crdt::DriftKind;
+use kaijutsu_types::{BlockId, ContextId, DigestSubscription, EdgeKind, PrincipalId, Status};
+
+use super::refs;
+use super::{KjCaller, KjDispatcher, KjResult};
+
+/// How often the scheduler looks for due digests.
+pub const DIGEST_TICK: Duration = Duration::from_secs(30);
+
+/// The directed prompt for a digest without its own focus.
+const DEFAULT_DIGEST_FOCUS: &str = "Write a brief digest for a collaborator following this \
+     context from elsewhere: the current goal, what changed recently, decisions made, and open \
+     questions or blockers. Skip anything they would not act on.";
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum DigestCommand {
+    /// Follow a context: LLM digests of it arrive here as drift blocks.
+    Add {
+        /// Source context reference
+        src: String,
+        /// Deliver at most every N minutes while the source has new blocks
+        #[arg(long, value_name = "MINUTES")]
+        every: Option<u32>,
+        /// Deliver when a turn in the source finishes
+        #[arg(long)]
+        milestone: bool,
+        /// What the digests should focus on
+        #[arg(long)]
+        focus: Option<String>,
+    },
+    /// Stop following a context.
+    #[command(alias = "remove")]
+    Rm {
+        /// Source context reference
+        src: String,
+    },
+    /// List this context's digest subscriptions (yields source context ids).
+    #[command(alias = "list")]
+    Ls,
+}
+
+impl KjDispatcher {
+    pub(crate) fn drift_digest(&self, op: DigestCommand, caller: &KjCaller) -> KjResult {
+        let subscriber = match caller.require_context() {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        match op {
+            DigestCommand::Add {
+                src,
+                every,
+                milestone,
+                focus,
+            } => {
+                let source = match self.resolve_digest_source(&src, caller) {
+                    Ok(id) => id,
+                    Err(e) => return KjResult::Err(format!("kj drift digest add: {e}")),
+                };
+                let every_secs = match every.map(|m| m.checked_mul(60)) {
+                    Some(None) => {
+                        return KjResult::Err(format!(
+                            "kj drift digest add: --every {} is too large",
+                            every.unwrap_or_default()
+                        ));
+                    }
+                    Some(Some(secs)) => Some(secs),
+                    None => None,
+                };
+                match self.subscribe_digest(
+                    subscriber,
+                    source,
+                    every_secs,
+                    milestone,
+                    focus,
+                    caller.principal_id,
+                ) {
+                    Ok(sub) => {
+                        KjResult::ok(format!("following {src}: digest {}", sub.trigger_label()))
+                    }
+                    Err(e) => KjResult::Err(format!("kj drift digest add: {e}")),
+                }
+            }
+            DigestCommand::Rm { src } => {
+                let source = match self.resolve_digest_source(&src, caller) {
+                    Ok(id) => id,
+                    Err(e) => return KjResult::Err(format!("kj drift digest rm: {e}")),
+                };
+                match self.unsubscribe_digest(subscriber, source) {
+                    Ok(true) => KjResult::ok(format!("stopped following {src}")),
+                    Ok(false) => KjResult::Err(format!("kj drift digest rm: not following {src}")),
+                    Err(e) => KjResult::Err(format!("kj drift digest rm: {e}")),
+                }
+            }
+            DigestCommand::Ls => {
+                let subs = self.digest_subscriptions(subscriber);
+                let ids = subs
+                    .iter()
+                    .map(|s| serde_json::Value::String(s.source.to_hex()))
+                    .collect();
+                KjResult::ok_with_data(
+                    self.format_digest_subscriptions(&subs),
+                    serde_json::Value::Array(ids),
+                )
+            }
+        }
+    }
+
+    fn resolve_digest_source(&self, src: &str, caller: &KjCaller) -> Result<ContextId, String> {
+        let db = self.kernel_db().lock();
+        refs::resolve_context_arg(Some(src), caller, &db)
+    }
+
+    fn format_digest_subscriptions(&self, subs: &[DigestSubscription]) -> String {
+        if subs.is_empty() {
+            return "no digest subscriptions".to_string();
+        }
+        let router = self.drift_router().read();
+        subs.iter()
+            .map(|s| {
+                let label = router
+                    .get(s.source)
+                    .and_then(|h| h.label.clone())
+                    .unwrap_or_else(|| s.source.short());
+                let mut line = format!("{label}  {}", s.trigger_label());
+                if let Some(focus) = &s.focus {
+                    line.push_str(&format!("  focus: {focus}"));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Have `subscriber` follow `source` (replacing any existing
+    /// subscription between them). Changes start counting from the source's
+    /// current last block, so the first digest covers only what comes next.
+    pub fn subscribe_digest(
+        &self,
+        subscriber: ContextId,
+        source: ContextId,
+        every_secs: Option<u32>,
+        on_milestone: bool,
+        focus: Option<String>,
+        created_by: PrincipalId,
+    ) -> Result<DigestSubscription, String> {
+        let sub = DigestSubscription {
+            subscriber,
+            source,
+            every_secs,
+            on_milestone,
+            focus: focus.filter(|f| !f.trim().is_empty()),
+            created_by,
+            created_at: kaijutsu_types::now_millis(),
+            last_digest_at: None,
+            last_block: self.block_store().last_block_id(source),
+        };
+        let previous = {
+            let mut router = self.drift_router().write();
+            let previous = router.unsubscribe_digest(subscriber, source);
+            if let Err(e) = router.subscribe_digest(sub.clone()) {
+                if let Some(previous) = previous {
+                    let _ = router.subscribe_digest(previous);
+                }
+                return Err(e.to_string());
+            }
+            previous
+        };
+        if let Err(e) = self.kernel_db().lock().upsert_digest_subscription(&sub) {
+            let mut router = self.drift_router().write();
+            router.unsubscribe_digest(subscriber, source);
+            if let Some(previous) = previous {
+                let _ = router.subscribe_digest(previous);
+            }
+            return Err(e.to_string());
+        }
+        Ok(sub)
+    }
+
+    /// Drop `subscriber`'s subscription to `source`; `Ok(false)` if there
+    /// was none.
+    pub fn unsubscribe_digest(
+        &self,
+        subscriber: ContextId,
+        source: ContextId,
+    ) -> Result<bool, String> {
+        let removed = self
+            .drift_router()
+            .write()
+            .unsubscribe_digest(subscriber, source)
+            .is_some();
+        let deleted = self
+            .kernel_db()
+            .lock()
+            .delete_digest_subscription(subscriber, source)
+            .map_err(|e| e.to_string())?;
+        Ok(removed || deleted)
+    }
+
+    /// `subscriber`'s digest subscriptions, oldest first.
+    pub fn digest_subscriptions(&self, subscriber: ContextId) -> Vec<DigestSubscription> {
+        self.drift_router()
+            .read()
+            .digest_subscriptions(subscriber)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Deliver every digest due now; returns how many went out. A failed
+    /// digest still counts as taken, so a broken source is retried after the
+    /// usual gap rather than on every tick.
+    pub async fn run_due_digests(&self) -> usize {
+        let now = kaijutsu_types::now_millis();
+        let due = {
+            let blocks = self.block_store();
+            self.drift_router().read().due_digests(now, |ctx| {
+                (
+                    blocks.last_block_id(ctx),
+                    blocks.live_status(ctx) == Status::Running,
+                )
+            })
+        };
+        let mut delivered = 0;
+        for sub in due {
+            let covered = match self.deliver_digest(&sub).await {
+                Ok(last) => {
+                    delivered += 1;
+                    last
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        subscriber = %sub.subscriber.short(),
+                        source = %sub.source.short(),
+                        "digest failed: {e}"
+                    );
+                    sub.last_block
+                }
+            };
+            let updated =
+                self.drift_router()
+                    .write()
+                    .mark_digested(sub.subscriber, sub.source, now, covered);
+            if let Some(updated) = updated
+                && let Err(e) = self.kernel_db().lock().upsert_digest_subscription(&updated)
+            {
+                tracing::warn!("failed to persist digest subscription: {e}");
+            }
+        }
+        delivered
+    }
+
+    /// Distill `sub.source` into `sub.subscriber`; returns the source block
+    /// the digest covers up to.
+    async fn deliver_digest(&self, sub: &DigestSubscription) -> Result<Option<BlockId>, String> {
+        let covered = self.block_store().last_block_id(sub.source);
+        let focus = sub.focus.as_deref().unwrap_or(DEFAULT_DIGEST_FOCUS);
+        let summary = self.summarize(sub.source, Some(focus)).await?;
+
+        let source_model = {
+            let router = self.drift_router().read();
+            router.get(sub.source).and_then(|h| h.model.clone())
+        };
+        let after = self.block_store().last_block_id(sub.subscriber);
+        self.block_store()
+            .insert_drift_block(
+                sub.subscriber,
+                None,
+                after.as_ref(),
+                &summary,
+                sub.source,
+                source_model,
+                DriftKind::Distill,
+            )
+            .map_err(|e| format!("failed to insert drift block: {e}"))?;
+
+        let db = self.kernel_db().lock();
+        let edge = crate::kernel_db::ContextEdgeRow {
+            edge_id: uuid::Uuid::now_v7(),
+            source_id: sub.source,
+            target_id: sub.subscriber,
+            kind: EdgeKind::Drift,
+            metadata: Some("digest".to_string()),
+            created_at: kaijutsu_types::now_millis() as i64,
+        };
+        if let Err(e) = db.insert_edge(&edge) {
+            tracing::warn!("failed to insert digest drift edge: {e}");
+        }
+        Ok(covered)
+    }
+}
+
+/// Spawn the server-lifetime digest scheduler on its own thread with a
+/// current-thread runtime, like the beat scheduler: digests run the
+/// summarizer, whose futures stay on the thread that started them.
+pub fn spawn_digest_scheduler(dispatcher: Arc<KjDispatcher>) {
+    let spawned = std::thread::Builder::new()
+        .name("digest-scheduler".to_string())
+        .spawn(move || {
+            let rt = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(e) => {
+                    tracing::error!("digest-scheduler: failed to build runtime: {e}");
+                    return;
+                }
+            };
+            rt.block_on(async move {
+                let mut tick = tokio::time::interval(DIGEST_TICK);
+                tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                loop {
+                    tick.tick().await;
+                    let delivered = dispatcher.run_due_digests().await;
+                    if delivered > 0 {
+                        tracing::debug!(delivered, "delivered digests");
+                    }
+                }
+            });
+        });
+    if let Err(e) = spawned {
+        tracing::error!("failed to spawn digest-scheduler thread: {e}");
+    }
+}
@@ -9,9 +9,18 @@
 //! ```text
 //! kj doc list [--kind <k>] [--json]
 //! kj doc tree <id> [--max-depth N] [--expand-tools]
+//! kj doc stats [<id>] [--json]
 //! kj doc create [--kind <k>] [--language <l>] [--id <hex>]
 //! kj doc delete <id> [--confirm <nonce>]
+//! kj doc bookmark <id> <name> [--delete]
+//! kj doc bookmarks <id> [--json]
+//! kj doc diff <id> <bookmark>
+//! kj doc revert <id> <bookmark>
 //! ```
+//!
+//! Bookmarks (`crate::doc_bookmark`) name a document's state so it can be
+//! read back (`kj block read --at <bookmark>`), diffed against, or
+//! reverted to.
 
 use std::str::FromStr;
 
@@ -21,6 +30,8 @@ use kaijutsu_types::{ContentType, ContextId, DocKind};
 use serde::Serialize;
 
 use super::{KjCaller, KjDispatcher, KjResult};
+use crate::doc_bookmark::BookmarkDiff;
+use crate::doc_stats::DocStats;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -60,6 +71,16 @@ enum DocCommand {
         #[arg(long = "expand-tools")]
         expand_tools: bool,
     },
+    /// Oplog size, per-writer activity, growth rate, and compaction
+    /// candidacy (see `crate::doc_stats`). With no id, every resident
+    /// document, nearest to compaction first.
+    Stats {
+        /// Document id (hex UUID); omit for all resident documents
+        doc_id: Option<String>,
+        /// Emit JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
     /// Create a new document. For Conversation kind, prefer
     /// `kj context create` (which also registers contexts metadata).
     /// Use this verb for Code/Text/Config docs that aren't conversations.
@@ -85,6 +106,43 @@ enum DocCommand {
         #[arg(long)]
         confirm: Option<String>,
     },
+    /// Name the document's current state (`before-refactor`). The server
+    /// keeps a copy of its blocks under the name, for `diff`, `revert` and
+    /// `kj block read --at`.
+    Bookmark {
+        /// Document id (hex UUID)
+        doc_id: String,
+        /// Letters, digits, '-', '_' or '.', starting with a letter
+        name: String,
+        /// Forget the bookmark instead; the document is untouched
+        #[arg(long)]
+        delete: bool,
+    },
+    /// List a document's bookmarks, oldest first.
+    Bookmarks {
+        /// Document id (hex UUID)
+        doc_id: String,
+        /// Emit JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// What changed since a bookmark: blocks added, removed, and a line
+    /// diff of each block whose text changed.
+    Diff {
+        /// Document id (hex UUID)
+        doc_id: String,
+        /// Bookmark name
+        bookmark: String,
+    },
+    /// Put every block's text back as it stood at a bookmark, as one
+    /// all-or-nothing edit. Blocks added since are kept and blocks deleted
+    /// since are not restored; both are listed.
+    Revert {
+        /// Document id (hex UUID)
+        doc_id: String,
+        /// Bookmark name
+        bookmark: String,
+    },
 }
 
 #[derive(Serialize)]
@@ -132,6 +190,16 @@ impl KjDispatcher {
                 return denied;
             }
         }
+        // A revert rewrites block text: the same gate as `kj block edit`.
+        if matches!(parsed.command, DocCommand::Revert { .. }) {
+            let cap = crate::mcp::Capability::Tool {
+                instance: crate::mcp::InstanceId::new("builtin.block"),
+                tool: "block_edit".to_string(),
+            };
+            if let Err(denied) = self.require_cap(caller, cap, "doc") {
+                return denied;
+            }
+        }
         match parsed.command {
             DocCommand::List { kind, json } => self.doc_list(kind.as_deref(), json),
             DocCommand::Tree {
@@ -139,6 +207,7 @@ impl KjDispatcher {
                 max_depth,
                 expand_tools,
             } => self.doc_tree(&doc_id, max_depth, expand_tools),
+            DocCommand::Stats { doc_id, json } => self.doc_stats(doc_id.as_deref(), json),
             DocCommand::Create {
                 kind,
                 language,
@@ -147,6 +216,14 @@ impl KjDispatcher {
             DocCommand::Delete { doc_id, confirm } => {
                 self.doc_delete(&doc_id, confirm.as_deref(), caller)
             }
+            DocCommand::Bookmark {
+                doc_id,
+                name,
+                delete,
+            } => self.doc_bookmark(&doc_id, &name, delete, caller),
+            DocCommand::Bookmarks { doc_id, json } => self.doc_bookmarks(&doc_id, json),
+            DocCommand::Diff { doc_id, bookmark } => self.doc_diff(&doc_id, &bookmark),
+            DocCommand::Revert { doc_id, bookmark } => self.doc_revert(&doc_id, &bookmark, caller),
         }
     }
 
@@ -334,6 +411,50 @@ impl KjDispatcher {
         KjResult::ok_with_data(out, record)
     }
 
+    /// Document stats from the block store's cache. One document renders in
+    /// detail; the listing is one line per document with the top writer.
+    fn doc_stats(&self, id_str: Option<&str>, json: bool) -> KjResult {
+        let stats = match id_str {
+            Some(id_str) => {
+                let ctx_id = match ContextId::parse(id_str) {
+                    Ok(id) => id,
+                    Err(e) => {
+                        return KjResult::Err(format!(
+                            "kj doc stats: invalid doc id '{id_str}': {e}"
+                        ));
+                    }
+                };
+                match self.blocks.doc_stats(ctx_id) {
+                    Ok(s) => vec![s],
+                    Err(e) => return KjResult::Err(format!("kj doc stats: {e}")),
+                }
+            }
+            None => {
+                let mut all: Vec<DocStats> = self
+                    .blocks
+                    .list_ids()
+                    .into_iter()
+                    .filter_map(|id| self.blocks.doc_stats(id).ok())
+                    .collect();
+                all.sort_by(|a, b| b.compaction.fill.total_cmp(&a.compaction.fill));
+                all
+            }
+        };
+
+        let data = serde_json::to_value(&stats).unwrap_or_default();
+        if json {
+            return KjResult::ok_with_data(data.to_string(), data);
+        }
+        if stats.is_empty() {
+            return KjResult::ok_with_data("(no documents)\n".to_string(), data);
+        }
+        let out = match (id_str, stats.first()) {
+            (Some(_), Some(one)) => format_doc_stats(one),
+            _ => stats.iter().map(format_doc_stats_row).collect(),
+        };
+        KjResult::ok_with_data(out, data)
+    }
+
     /// Create a new document. Generates a fresh UUID unless `--id <hex>` is
     /// supplied. For conversation kind, prefer `kj context create` (which
     /// also registers the contexts row) — kj doc create stops at the
@@ -364,6 +485,21 @@ impl KjDispatcher {
                 }
             },
         };
+        // An explicit id may name a document that is persisted but not
+        // resident; `create_document` would then shadow it with an empty
+        // in-memory copy. Refuse instead of colliding.
+        if id_arg.is_some() {
+            match self.kernel_db().lock().get_document(new_id) {
+                Ok(Some(_)) => {
+                    return KjResult::Err(format!(
+                        "kj doc create: document {} already exists",
+                        new_id.to_hex()
+                    ));
+                }
+                Ok(None) => {}
+                Err(e) => return KjResult::Err(format!("kj doc create: {e}")),
+            }
+        }
         if let Err(e) = self
             .blocks
             .create_document(new_id, kind_p, language.map(|s| s.to_string()))
@@ -451,6 +587,163 @@ impl KjDispatcher {
         });
         KjResult::ok_with_data(format!("deleted {}\n", id_str), record)
     }
+
+    /// `kj doc bookmark <id> <name> [--delete]`.
+    fn doc_bookmark(&self, id_str: &str, name: &str, delete: bool, caller: &KjCaller) -> KjResult {
+        let ctx_id = match ContextId::parse(id_str) {
+            Ok(id) => id,
+            Err(e) => {
+                return KjResult::Err(format!("kj doc bookmark: invalid doc id '{id_str}': {e}"));
+            }
+        };
+        if delete {
+            return match crate::doc_bookmark::delete(self.kernel_db(), ctx_id, name) {
+                Ok(()) => KjResult::ok_with_data(
+                    format!("forgot bookmark {name}\n"),
+                    serde_json::json!({ "document_id": ctx_id.to_hex(), "name": name }),
+                ),
+                Err(e) => KjResult::Err(format!("kj doc bookmark: {e}")),
+            };
+        }
+        match crate::doc_bookmark::create(
+            self.kernel_db(),
+            self.block_store(),
+            ctx_id,
+            name,
+            caller.principal_id,
+            kaijutsu_types::now_millis(),
+        ) {
+            Ok(mark) => KjResult::ok_with_data(
+                format!(
+                    "bookmarked {} as {} ({} blocks at version {})\n",
+                    ctx_id.short(),
+                    mark.name,
+                    mark.block_count,
+                    mark.version
+                ),
+                serde_json::to_value(&mark).unwrap_or_default(),
+            ),
+            Err(e) => KjResult::Err(format!("kj doc bookmark: {e}")),
+        }
+    }
+
+    /// `kj doc bookmarks <id>` — oldest first.
+    fn doc_bookmarks(&self, id_str: &str, json: bool) -> KjResult {
+        let ctx_id = match ContextId::parse(id_str) {
+            Ok(id) => id,
+            Err(e) => {
+                return KjResult::Err(format!("kj doc bookmarks: invalid doc id '{id_str}': {e}"));
+            }
+        };
+        let marks = match crate::doc_bookmark::list(self.kernel_db(), ctx_id) {
+            Ok(marks) => marks,
+            Err(e) => return KjResult::Err(format!("kj doc bookmarks: {e}")),
+        };
+        let data = serde_json::to_value(&marks).unwrap_or_default();
+        if json {
+            return KjResult::ok_with_data(data.to_string(), data);
+        }
+        if marks.is_empty() {
+            return KjResult::ok_with_data("(no bookmarks)\n".to_string(), data);
+        }
+        let out = marks
+            .iter()
+            .map(|m| {
+                format!(
+                    "{}  version {}  {} blocks  by {}\n",
+                    m.name,
+                    m.version,
+                    m.block_count,
+                    m.created_by.short()
+                )
+            })
+            .collect();
+        KjResult::ok_with_data(out, data)
+    }
+
+    /// `kj doc diff <id> <bookmark>` — the bookmark against now.
+    fn doc_diff(&self, id_str: &str, bookmark: &str) -> KjResult {
+        let ctx_id = match ContextId::parse(id_str) {
+            Ok(id) => id,
+            Err(e) => {
+                return KjResult::Err(format!("kj doc diff: invalid doc id '{id_str}': {e}"));
+            }
+        };
+        match crate::doc_bookmark::diff(self.kernel_db(), self.block_store(), ctx_id, bookmark) {
+            Ok((mark, diff)) => {
+                let mut out = format!(
+                    "diff {} since {}\n{}\n",
+                    ctx_id.short(),
+                    mark.name,
+                    "─".repeat(40)
+                );
+                out.push_str(&format_bookmark_diff(&diff));
+                if diff.is_empty() {
+                    out.push_str("(no changes)\n");
+                }
+                KjResult::ok_with_data(out, serde_json::to_value(&diff).unwrap_or_default())
+            }
+            Err(e) => KjResult::Err(format!("kj doc diff: {e}")),
+        }
+    }
+
+    /// `kj doc revert <id> <bookmark>` — rewrite changed blocks' text.
+    fn doc_revert(&self, id_str: &str, bookmark: &str, caller: &KjCaller) -> KjResult {
+        let ctx_id = match ContextId::parse(id_str) {
+            Ok(id) => id,
+            Err(e) => {
+                return KjResult::Err(format!("kj doc revert: invalid doc id '{id_str}': {e}"));
+            }
+        };
+        match crate::doc_bookmark::revert(
+            self.kernel_db(),
+            self.block_store(),
+            ctx_id,
+            bookmark,
+            caller.principal_id,
+        ) {
+            Ok((mark, diff)) => {
+                let mut out = format!(
+                    "reverted {} block{} to {}\n",
+                    diff.changed.len(),
+                    if diff.changed.len() == 1 { "" } else { "s" },
+                    mark.name
+                );
+                for id in &diff.added {
+                    out.push_str(&format!("  kept {id} (added since)\n"));
+                }
+                for id in &diff.removed {
+                    out.push_str(&format!("  not restored {id} (deleted since)\n"));
+                }
+                KjResult::ok_with_data(out, serde_json::to_value(&diff).unwrap_or_default())
+            }
+            Err(e) => KjResult::Err(format!("kj doc revert: {e}")),
+        }
+    }
+}
+
+/// A bookmark diff as text: `+`/`-` per added or removed block, then a line
+/// diff of each changed one.
+fn format_bookmark_diff(diff: &BookmarkDiff) -> String {
+    let mut out = String::new();
+    for id in &diff.added {
+        out.push_str(&format!("+ block {id}\n"));
+    }
+    for id in &diff.removed {
+        out.push_str(&format!("- block {id}\n"));
+    }
+    for change in &diff.changed {
+        out.push_str(&format!("~ block {}\n", change.block_id));
+        let lines = kaijutsu_types::diff::LineDiff::new(&change.then, &change.now);
+        for line in lines
+            .lines
+            .iter()
+            .filter(|l| l.tag != kaijutsu_types::diff::LineTag::Equal)
+        {
+            out.push_str(&format!("  {} {}\n", line.tag.marker(), line.text));
+        }
+    }
+    out
 }
 
 /// Carries a structured `record` JSON alongside the iteration-friendly
@@ -468,6 +761,100 @@ impl PreservingRecord for KjResult {
     }
 }
 
+// ── doc stats formatting ─────────────────────────────────────────────
+
+fn format_bytes(n: u64) -> String {
+    match n {
+        n if n >= 1 << 20 => format!("{:.1} MiB", n as f64 / (1u64 << 20) as f64),
+        n if n >= 1 << 10 => format!("{:.1} KiB", n as f64 / (1u64 << 10) as f64),
+        n => format!("{n} B"),
+    }
+}
+
+fn format_doc_stats(s: &DocStats) -> String {
+    let mut out = format!(
+        "{} ({}, v{}, {} block{})\n",
+        s.context_id.to_hex(),
+        s.kind.as_str(),
+        s.version,
+        s.block_count,
+        if s.block_count == 1 { "" } else { "s" }
+    );
+    if s.journaled {
+        out.push_str(&format!(
+            "oplog     {} ops, {} since checkpoint (seq {}), {:.0}% of compaction{}\n",
+            s.oplog_ops,
+            format_bytes(s.oplog_bytes),
+            s.journal_seq,
+            s.compaction.fill * 100.0,
+            if s.compaction.candidate {
+                " — candidate"
+            } else {
+                ""
+            }
+        ));
+    } else {
+        out.push_str("oplog     not journaled\n");
+    }
+    out.push_str(&format!(
+        "size      snapshot {}, content {}\n",
+        format_bytes(s.snapshot_bytes),
+        format_bytes(s.content_bytes)
+    ));
+    let growth: Vec<String> = s
+        .growth
+        .iter()
+        .map(|w| format!("{}m {} ops/{}", w.minutes, w.ops, format_bytes(w.bytes)))
+        .collect();
+    out.push_str(&format!(
+        "growth    {} ({} ops since load)\n",
+        growth.join(" · "),
+        s.ops
+    ));
+    for (i, a) in s.agents.iter().enumerate() {
+        out.push_str(&format!(
+            "{}{}  {} ops ({} in 10m), {} block{}, {}\n",
+            if i == 0 { "writers   " } else { "          " },
+            a.principal_id.short(),
+            a.ops,
+            a.recent_ops,
+            a.blocks,
+            if a.blocks == 1 { "" } else { "s" },
+            format_bytes(a.content_bytes)
+        ));
+    }
+    out
+}
+
+/// `<short_id> <kind>  <fill>% oplog <ops>/<bytes>  10m <ops>  top <principal>`
+fn format_doc_stats_row(s: &DocStats) -> String {
+    let recent = s
+        .growth
+        .iter()
+        .find(|w| w.minutes == 10)
+        .map_or(0, |w| w.ops);
+    let top = s
+        .agents
+        .first()
+        .map(|a| format!("  top {} ({} in 10m)", a.principal_id.short(), a.recent_ops))
+        .unwrap_or_default();
+    format!(
+        "{}  {}  {:>3.0}% oplog {}/{}  10m {} ops{}{}\n",
+        s.context_id.short(),
+        s.kind.as_str(),
+        s.compaction.fill * 100.0,
+        s.oplog_ops,
+        format_bytes(s.oplog_bytes),
+        recent,
+        top,
+        if s.compaction.candidate {
+            "  [compact]"
+        } else {
+            ""
+        }
+    )
+}
+
 // ── DAG tree formatter (port of kaijutsu-mcp/src/tree.rs) ────────────
 //
 // Inlined rather than imported so kj doesn't depend on kaijutsu-mcp.
@@ -790,6 +1177,39 @@ mod tests {
         assert!(result.message().contains("invalid doc id"));
     }
 
+    // ── doc stats ──────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn doc_stats_counts_writers() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let conv = register_context_with_doc(&d, Some("c"), principal);
+        let block = insert_text_block(&d, conv, "hello");
+        d.block_store()
+            .append_text_as(conv, &block, " world", Some(principal))
+            .unwrap();
+        let c = caller_with_context(conv);
+
+        let result = d
+            .dispatch(&[s("doc"), s("stats"), conv.to_hex(), s("--json")], &c)
+            .await;
+        assert!(result.is_ok(), "stats failed: {}", result.message());
+        let v: serde_json::Value = serde_json::from_str(result.message()).unwrap();
+        let stats = &v[0];
+        assert_eq!(stats["block_count"], 1);
+        assert_eq!(stats["content_bytes"], 11);
+        assert_eq!(stats["ops"], 2, "insert + append: {stats}");
+        assert_eq!(stats["compaction"]["candidate"], false);
+
+        let result = d.dispatch(&[s("doc"), s("stats")], &c).await;
+        assert!(result.is_ok(), "listing failed: {}", result.message());
+        assert!(
+            result.message().contains(&conv.short()),
+            "got: {}",
+            result.message()
+        );
+    }
+
     // ── doc create ─────────────────────────────────────────────────
 
     #[tokio::test]
@@ -848,6 +1268,19 @@ mod tests {
         assert!(d.block_store().get(chosen).is_some());
     }
 
+    #[tokio::test]
+    async fn doc_create_rejects_existing_id() {
+        let d = test_dispatcher().await;
+        let c = test_caller();
+        let chosen = ContextId::new();
+        let argv = [s("doc"), s("create"), s("--id"), chosen.to_hex()];
+
+        assert!(d.dispatch(&argv, &c).await.is_ok());
+        let again = d.dispatch(&argv, &c).await;
+        assert!(!again.is_ok());
+        assert!(again.message().contains("already exists"), "{}", again.message());
+    }
+
     #[tokio::test]
     async fn doc_create_invalid_kind_errors() {
         let d = test_dispatcher().await;
@@ -924,4 +1357,99 @@ mod tests {
         assert!(!result.is_ok());
         assert!(result.message().contains("invalid doc id"));
     }
+
+    // ── doc bookmarks ──────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn doc_bookmark_diff_and_revert_round_trip() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let conv = register_context_with_doc(&d, Some("c"), principal);
+        let plan = insert_text_block(&d, conv, "step one\nstep two\n");
+        let c = test_caller();
+
+        let result = d
+            .dispatch(
+                &[s("doc"), s("bookmark"), conv.to_hex(), s("before-refactor")],
+                &c,
+            )
+            .await;
+        assert!(result.is_ok(), "bookmark failed: {}", result.message());
+        d.block_store()
+            .edit_text(conv, &plan, 5, "ONE", 3)
+            .expect("edit");
+
+        let result = d
+            .dispatch(&[s("doc"), s("bookmarks"), conv.to_hex()], &c)
+            .await;
+        assert!(
+            result.message().starts_with("before-refactor"),
+            "got: {}",
+            result.message()
+        );
+
+        let result = d
+            .dispatch(
+                &[s("doc"), s("diff"), conv.to_hex(), s("before-refactor")],
+                &c,
+            )
+            .await;
+        assert!(result.is_ok(), "diff failed: {}", result.message());
+        assert!(
+            result.message().contains("- step one"),
+            "got: {}",
+            result.message()
+        );
+        assert!(
+            result.message().contains("+ step ONE"),
+            "got: {}",
+            result.message()
+        );
+
+        let result = d
+            .dispatch(
+                &[
+                    s("block"),
+                    s("read"),
+                    plan.to_key(),
+                    s("--at"),
+                    s("before-refactor"),
+                ],
+                &c,
+            )
+            .await;
+        assert!(
+            result.message().contains("step one"),
+            "got: {}",
+            result.message()
+        );
+
+        let result = d
+            .dispatch(
+                &[s("doc"), s("revert"), conv.to_hex(), s("before-refactor")],
+                &c,
+            )
+            .await;
+        assert!(result.is_ok(), "revert failed: {}", result.message());
+        let content = d
+            .block_store()
+            .get_block_snapshot(conv, &plan)
+            .unwrap()
+            .unwrap()
+            .content;
+        assert_eq!(content, "step one\nstep two\n");
+    }
+
+    #[tokio::test]
+    async fn doc_bookmark_unknown_name_errors() {
+        let d = test_dispatcher().await;
+        let conv = register_context_with_doc(&d, Some("c"), PrincipalId::new());
+        let c = test_caller();
+
+        let result = d
+            .dispatch(&[s("doc"), s("diff"), conv.to_hex(), s("nope")], &c)
+            .await;
+        assert!(!result.is_ok());
+        assert!(result.message().contains("no bookmark named 'nope'"));
+    }
 }
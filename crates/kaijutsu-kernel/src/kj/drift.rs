@@ -1,4 +1,4 @@
-//! Drift subcommands: push, flush, queue, cancel.
+//! Drift subcommands: push, flush, queue, cancel, digest.
 //!
 //! Migrated to clap_derive following the `block`/`cas` template. One
 //! `DriftArgs` struct + `DriftCommand` enum at the top; `dispatch_drift`
@@ -10,8 +10,9 @@
 
 use clap::{Parser, Subcommand};
 use kaijutsu_crdt::DriftKind;
-use kaijutsu_types::{ContentType, EdgeKind};
+use kaijutsu_types::{ContentType, EdgeKind, InboxKind};
 
+use super::digest::DigestCommand;
 use super::format::format_drift_queue;
 use super::refs;
 use super::{clap_help_for, KjCaller, KjDispatcher, KjResult};
@@ -75,6 +76,11 @@ enum DriftCommand {
         #[command(subcommand)]
         op: EdgeCommand,
     },
+    /// Follow other contexts through periodic LLM digests.
+    Digest {
+        #[command(subcommand)]
+        op: DigestCommand,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -106,8 +112,9 @@ impl KjDispatcher {
             }
         };
 
-        // The cross-context write surface (push/pull/merge/flush/cancel) is
-        // gated on `drift`; the read-only views (queue/history/edge) are not.
+        // The cross-context write surface (push/pull/merge/flush/cancel and
+        // digest add/rm) is gated on `drift`; the read-only views
+        // (queue/history/edge/digest ls) are not.
         if matches!(
             parsed.command,
             DriftCommand::Push { .. }
@@ -115,6 +122,9 @@ impl KjDispatcher {
                 | DriftCommand::Merge { .. }
                 | DriftCommand::Flush
                 | DriftCommand::Cancel { .. }
+                | DriftCommand::Digest {
+                    op: DigestCommand::Add { .. } | DigestCommand::Rm { .. }
+                }
         ) && let Err(denied) = self.require_cap(caller, crate::mcp::Capability::Drift, "drift")
         {
             return denied;
@@ -135,6 +145,7 @@ impl KjDispatcher {
             DriftCommand::Edge { op } => match op {
                 EdgeCommand::Rm { uuid } => self.drift_edge_rm(&uuid),
             },
+            DriftCommand::Digest { op } => self.drift_digest(op, caller),
         }
     }
 
@@ -196,6 +207,16 @@ impl KjDispatcher {
             }
         };
 
+        // Staged drift waits on a flush — ask the target's owner.
+        crate::inbox::notify_context_owner(
+            self.kernel_db(),
+            self.block_store().block_flows(),
+            target_id,
+            InboxKind::ConsentRequest,
+            caller.principal_id,
+            &format!("drift #{} from {} awaits flush", staged_id, context_id.short()),
+        );
+
         KjResult::ok(format!("staged drift #{} → {}", staged_id, dst_query))
     }
 
@@ -359,6 +380,15 @@ impl KjDispatcher {
             return KjResult::Err(format!("kj drift merge: failed to insert drift block: {e}"));
         }
 
+        crate::inbox::notify_context_owner(
+            self.kernel_db(),
+            self.block_store().block_flows(),
+            target_id,
+            InboxKind::DriftArrival,
+            caller.principal_id,
+            &format!("merge drift arrived from {}", context_id.short()),
+        );
+
         // Record drift edge
         {
             let db = self.kernel_db().lock();
@@ -445,6 +475,15 @@ impl KjDispatcher {
                 Ok(_) => {
                     injected += 1;
 
+                    crate::inbox::notify_context_owner(
+                        self.kernel_db(),
+                        self.block_store().block_flows(),
+                        drift.target_ctx,
+                        InboxKind::DriftArrival,
+                        caller.principal_id,
+                        &format!("{} drift arrived from {}", drift.drift_kind, drift.source_ctx.short()),
+                    );
+
                     // Record the drift edge in context_edges so `kj drift
                     // history` can find it. `drift_kind` and `source_model`
                     // are recoverable from the inserted block itself, but we
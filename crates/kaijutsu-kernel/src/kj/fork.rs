@@ -278,8 +278,9 @@ impl KjDispatcher {
             .map_err(|(s, e)| format!("--include '{s}': {e}"))?;
         let cli_inc_opt = if cli_includes.is_empty() { None } else { Some(cli_inc) };
 
-        // CLI excludes — a range, or (NotARange = the wrong colon count) the exact
-        // `--exclude <block-key>` form. A typo'd/absent key fails LOUD: a silent
+        // CLI excludes — a range, or (NotARange = the wrong colon count) the
+        // `--exclude <block>` form: a key or a unique abbreviation of one in this
+        // context. A typo'd/absent/ambiguous block fails LOUD: a silent
         // no-op would leave the offending block in a "repaired" child.
         let mut cli_exc_ranges: Vec<std::ops::Range<usize>> = Vec::new();
         let mut exclude_block_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
@@ -287,9 +288,11 @@ impl KjDispatcher {
             match kaijutsu_crdt::parse_range(spec, len) {
                 Ok(r) => cli_exc_ranges.push(r),
                 Err(RangeError::NotARange(_)) => {
-                    let id = kaijutsu_types::BlockId::from_key(spec).ok_or_else(|| {
-                        format!("--exclude '{spec}': not a range and not a valid block key")
-                    })?;
+                    let id = kaijutsu_types::resolve_block_prefix(
+                        snapshots.iter().map(|b| b.id),
+                        spec,
+                    )
+                    .map_err(|e| format!("--exclude '{spec}': not a range, and as a block: {e}"))?;
                     if !snapshots.iter().any(|b| b.id == id) {
                         return Err(format!("--exclude block '{spec}' is not in this context"));
                     }
@@ -364,7 +367,7 @@ impl KjDispatcher {
     /// unique-constraint bounce from deep inside the insert. The DB's unique
     /// index stays the real guard: a label that wins the race between this check
     /// and the insert still fails loud there. `None`/free label → `Ok`.
-    fn ensure_label_available(&self, label: Option<&str>) -> Result<(), String> {
+    pub(super) fn ensure_label_available(&self, label: Option<&str>) -> Result<(), String> {
         let Some(label) = label else {
             return Ok(());
         };
@@ -412,14 +415,16 @@ impl KjDispatcher {
         self.fork_full(&args, caller).await
     }
 
-    /// Apply MCP fork mode exclusions to a newly forked context.
+    /// Carry the source context's scoped MCP servers into a new fork.
     ///
-    /// Servers with `McpForkMode::Exclude` have their tools denied via ToolFilter.
-    /// Called after drift.register_fork() so the context handle exists.
-    async fn apply_fork_mcp_exclusions(&self, _new_id: ContextId) {
-        // MCP fork-mode exclusions were removed alongside the legacy MCP
-        // pool in Phase 1 M5. A Phase 2+ replacement will live against
-        // ExternalMcpServer health/policy.
+    /// Instances registered with `McpForkMode::Inherit` become visible (and
+    /// bound) in the fork; `McpForkMode::Exclude` ones stay behind. Called
+    /// after drift.register_fork() so the context handle exists.
+    async fn inherit_fork_mcp_scopes(&self, source_id: ContextId, new_id: ContextId) {
+        self.kernel()
+            .broker()
+            .inherit_instance_scopes(source_id, new_id)
+            .await;
     }
 
     async fn fork_full(&self, args: &ForkArgs, caller: &KjCaller) -> KjResult {
@@ -707,7 +712,7 @@ impl KjDispatcher {
             return KjResult::Err(format!("kj fork: failed to inject fork note: {e}"));
         }
 
-        self.apply_fork_mcp_exclusions(new_id).await;
+        self.inherit_fork_mcp_scopes(source_id, new_id).await;
 
         // Fork marker: get source label + block count for the summary
         let source_label = {
@@ -943,7 +948,7 @@ impl KjDispatcher {
             }
         }
 
-        self.apply_fork_mcp_exclusions(new_id).await;
+        self.inherit_fork_mcp_scopes(source_id, new_id).await;
 
         let source_label = {
             let db = self.kernel_db().lock();
@@ -1213,7 +1218,7 @@ impl KjDispatcher {
             }
         }
 
-        self.apply_fork_mcp_exclusions(new_root_id).await;
+        self.inherit_fork_mcp_scopes(source_id, new_root_id).await;
 
         // If --prompt given, inject the fork note on the subtree root before the
         // fork marker — matching fork_full's placement so the autonomous turn's
@@ -1309,7 +1314,8 @@ impl KjDispatcher {
     /// POSIX semantics: by default the caller stays on the parent and keeps
     /// running — the child id is returned in `data` so `for x in $(kj fork …)`
     /// and `kaish-last` can pick it up. `--switch` opts into moving the caller
-    /// into the child (the old unconditional behaviour).
+    /// into the child (the old unconditional behaviour). Also fires the
+    /// `context.created` webhook for the child (for a subtree, its root).
     fn fork_outcome(
         &self,
         new_id: ContextId,
@@ -1317,6 +1323,7 @@ impl KjDispatcher {
         switch: bool,
         message: String,
     ) -> KjResult {
+        self.emit_context_created(new_id);
         if switch {
             KjResult::Switch(new_id, message)
         } else {
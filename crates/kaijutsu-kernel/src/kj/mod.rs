@@ -3,7 +3,7 @@
 //! Three modalities, one implementation:
 //! - kaish builtin (`kj context list --tree`)
 //! - MCP tool (`shell("kj context list --tree")`)
-//! - Future: standalone CLI binary
+//! - standalone CLI (`kaijutsu-cli`'s `kj shell`, `kj docs list`, ...)
 //!
 //! All commands go through `KjDispatcher`, which holds Arc refs to shared
 //! kernel state and is constructed once per server.
@@ -19,6 +19,7 @@ pub mod cp;
 pub mod config;
 pub mod context;
 pub mod context_shell;
+pub mod digest;
 pub mod doc;
 pub mod drift;
 pub mod drive;
@@ -254,6 +255,15 @@ pub struct KjDispatcher {
     /// ([`crate::mcp::servers::ShellServer`]) reads it so the model's `kj`
     /// search/synthesis tools work — without it the model shell is degraded.
     semantic_index: parking_lot::RwLock<Option<Arc<kaijutsu_index::SemanticIndex>>>,
+    /// The kernel's full-text block index (`search.db`), installed by the
+    /// server via [`Self::set_search_index`] and kept current by
+    /// [`crate::search_index::spawn_indexer`]. `None` in tests and embedders
+    /// that don't run one; `kj search --ranked` then refuses.
+    search_index: parking_lot::RwLock<Option<Arc<crate::search_index::SearchIndex>>>,
+    /// Outbound webhook sender (`/etc/config/webhooks.toml`). The block-flow
+    /// events are watched by `webhooks::spawn_watcher`, started by the server;
+    /// `context.created` is emitted from the creating paths.
+    webhooks: Arc<crate::webhooks::Webhooks>,
 }
 
 impl KjDispatcher {
@@ -267,6 +277,7 @@ impl KjDispatcher {
             .lock()
             .kernel_id()
             .expect("KernelDb singleton row must exist");
+        let webhooks = Arc::new(crate::webhooks::Webhooks::new(blocks.clone(), kernel_id));
         Self {
             drift,
             blocks,
@@ -275,6 +286,8 @@ impl KjDispatcher {
             kernel,
             weak_self: parking_lot::RwLock::new(None),
             semantic_index: parking_lot::RwLock::new(None),
+            search_index: parking_lot::RwLock::new(None),
+            webhooks,
         }
     }
 
@@ -293,6 +306,42 @@ impl KjDispatcher {
         *self.semantic_index.write() = index;
     }
 
+    /// Install the kernel's full-text block index. Call once at bootstrap,
+    /// like [`Self::set_semantic_index`].
+    pub fn set_search_index(&self, index: Option<Arc<crate::search_index::SearchIndex>>) {
+        *self.search_index.write() = index;
+    }
+
+    /// The installed full-text index, if any.
+    pub fn search_index(&self) -> Option<Arc<crate::search_index::SearchIndex>> {
+        self.search_index.read().clone()
+    }
+
+    /// The kernel's outbound webhook sender.
+    pub fn webhooks(&self) -> &Arc<crate::webhooks::Webhooks> {
+        &self.webhooks
+    }
+
+    /// Fire `context.created` for a freshly created (or forked) context. Reads
+    /// the persisted row, so call it after the KernelDb write.
+    pub fn emit_context_created(&self, context_id: ContextId) {
+        let row = match self.kernel_db.lock().get_context(context_id) {
+            Ok(Some(row)) => row,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("webhooks: context {} lookup failed: {e}", context_id.short());
+                return;
+            }
+        };
+        self.webhooks.emit(crate::webhooks::WebhookEvent::ContextCreated {
+            context_id: context_id.to_hex(),
+            label: row.label,
+            context_type: row.context_type,
+            created_by: row.created_by.to_hex(),
+            forked_from: row.forked_from.map(|c| c.to_hex()),
+        });
+    }
+
     /// The installed semantic index, if any. Read by in-kernel shell
     /// materialization so the model's `kj search`/synthesis tools work.
     pub fn semantic_index(&self) -> Option<Arc<kaijutsu_index::SemanticIndex>> {
@@ -393,7 +442,7 @@ impl KjDispatcher {
         // `kj block` operates by --context ref when given one, so it can
         // run without an active context.
         if cmd == "block" {
-            return self.dispatch_block(&argv[1..], caller);
+            return self.dispatch_block(&argv[1..], caller).await;
         }
         // `kj binding` / `kj policy` take an optional <ctx>/<instance> arg and
         // default to the active context, so rc scripts (which run with an
@@ -7,7 +7,8 @@
 //!   `kj context set --model …`. The structured `.data` is an array of those
 //!   specs (alias names + fully-qualified `provider/model`) so
 //!   `for m in $(kj models)` iterates usable handles, per the kj list-data
-//!   convention.
+//!   convention. Each provider shows its last health check (see
+//!   [`crate::llm::health`]); `--check` probes every provider first.
 //! - `kj model` reports the *effective* model for a context — the column on
 //!   the context row when set, otherwise the registry default it falls through
 //!   to. Defaults to the current context; `--context <ref>` targets another.
@@ -22,9 +23,7 @@ use kaijutsu_types::ContentType;
 use super::refs;
 use super::{clap_help_for, KjCaller, KjDispatcher, KjResult};
 
-/// `kj models` is pure discovery — no positionals, no value flags. The empty
-/// struct exists so help routes through the shared `clap_help_for` path for
-/// consistency with the other clap-migrated subcommands. Note: bare
+/// `kj models` is pure discovery — no positionals, no value flags. Note: bare
 /// `kj models` (no argv) deliberately *lists* rather than showing help, so the
 /// dispatch path handles the empty-argv case before clap ever sees it.
 #[derive(Parser, Debug)]
@@ -34,7 +33,11 @@ use super::{clap_help_for, KjCaller, KjDispatcher, KjResult};
     disable_help_subcommand = true,
     no_binary_name = true
 )]
-pub(crate) struct ModelsArgs {}
+pub(crate) struct ModelsArgs {
+    /// Health-check every provider now instead of showing the last check
+    #[arg(long)]
+    check: bool,
+}
 
 /// `kj model` — report the effective model for a context. The only knob is
 /// `--context <ref>`, which targets a context other than the caller's current.
@@ -55,12 +58,18 @@ impl KjDispatcher {
     /// `kj models` — list providers, their models, and `--model` aliases.
     pub(crate) async fn dispatch_models(&self, argv: &[String]) -> KjResult {
         // Bare `kj models` (empty argv) LISTS — preserving the historical
-        // behavior — so we only intercept explicit help requests here. An
-        // empty `ModelsArgs` means there are no other args to parse: the list
-        // path below needs no clap pass.
+        // behavior — so we only intercept explicit help requests here.
         if matches!(argv.first().map(|s| s.as_str()), Some("help" | "--help" | "-h")) {
             return clap_help_for::<ModelsArgs>();
         }
+        let args = match ModelsArgs::try_parse_from(argv) {
+            Ok(a) => a,
+            Err(e) => return KjResult::Err(format!("kj models: {e}")),
+        };
+        if args.check {
+            crate::llm::health::check_providers(self.kernel().llm(), self.kernel().llm_health())
+                .await;
+        }
 
         let registry = self.kernel().llm().read().await;
         let default_provider = registry.default_provider_name().map(str::to_string);
@@ -105,6 +114,17 @@ impl KjDispatcher {
                 format!("### {name}")
             };
             lines.push(header);
+            if let Some(h) = self.kernel().llm_health().get(name) {
+                let detail = if h.detail.is_empty() {
+                    String::new()
+                } else {
+                    format!(" — {}", h.detail)
+                };
+                lines.push(format!(
+                    "_health: **{}** ({} ms){detail}_",
+                    h.status, h.latency_ms
+                ));
+            }
             if models.is_empty() {
                 lines.push("- _(no models advertised)_".to_string());
             }
@@ -246,7 +266,7 @@ mod tests {
 
     use crate::kj::test_helpers::*;
     use crate::llm::toml_config::ModelAlias;
-    use crate::llm::{Provider, ProviderConfig, claude, deepseek};
+    use crate::llm::{MockClient, Provider, ProviderConfig, claude, deepseek};
     use kaijutsu_types::PrincipalId;
 
     fn s(v: &str) -> String {
@@ -324,6 +344,35 @@ mod tests {
         assert_eq!(specs, sorted, "specs are sorted and deduped");
     }
 
+    #[tokio::test]
+    async fn models_check_reports_provider_health() {
+        let d = test_dispatcher().await;
+        d.kernel().llm().write().await.register(
+            "mock",
+            Arc::new(Provider::Mock(
+                MockClient::new("ok").with_auth_failure("invalid x-api-key"),
+            )),
+        );
+        let c = test_caller();
+
+        let before = d.dispatch(&[s("models")], &c).await;
+        assert!(
+            !before.message().contains("health:"),
+            "no check yet: {}",
+            before.message()
+        );
+
+        let result = d.dispatch(&[s("models"), s("--check")], &c).await;
+        assert!(
+            result.is_ok(),
+            "models --check failed: {}",
+            result.message()
+        );
+        let msg = result.message();
+        assert!(msg.contains("health: **auth_failed**"), "{msg}");
+        assert!(msg.contains("invalid x-api-key"), "{msg}");
+    }
+
     #[tokio::test]
     async fn models_without_providers_reports_empty() {
         let d = test_dispatcher().await;
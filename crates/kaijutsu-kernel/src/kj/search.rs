@@ -5,18 +5,26 @@
 //! the kernel. `--context <ref>` scopes to a single named context the
 //! same way `kj block` resolves refs.
 //!
+//! `--ranked` asks the kernel's full-text index
+//! ([`crate::search_index`]) instead of scanning: the pattern is a query
+//! (words, `"phrases"`, `prefix*`, `OR`/`NOT`), hits come back best first,
+//! and `--author`/`--since`/`--until` narrow them.
+//!
 //! ```text
 //! kj search <pattern> [--context <ref> | --all]
 //!                     [--kind <k>] [--role <r>]
 //!                     [--context-lines N] [--max-matches N]
+//!                     [--ranked [--author <who>] [--since <t>] [--until <t>]]
 //!                     [--json]
 //! ```
 
 use clap::Parser;
-use kaijutsu_types::{BlockId, BlockKind, ContentType, ContextId, Role};
+use kaijutsu_types::{BlockId, BlockKind, ContentType, ContextId, PrincipalId, Role};
 use regex::Regex;
 use serde::Serialize;
 
+use crate::search_index::SearchQuery;
+
 use super::refs::resolve_context_arg;
 use super::{KjCaller, KjDispatcher, KjResult};
 
@@ -35,7 +43,7 @@ pub(crate) struct SearchArgs {
     /// Search all active contexts instead of just one
     #[arg(long, conflicts_with = "context")]
     all: bool,
-    /// Filter by kind: text|thinking|tool_call|tool_result|drift|file|error|notification|resource|trace
+    /// Filter by kind: text|thinking|tool_call|tool_result|drift|file|error|notification|resource|trace|repl
     #[arg(long)]
     kind: Option<String>,
     /// Filter by role: user|model|system|tool|asset
@@ -47,6 +55,18 @@ pub(crate) struct SearchArgs {
     /// Maximum number of matches to return
     #[arg(long = "max-matches", default_value_t = 100)]
     max_matches: usize,
+    /// Query the kernel's full-text index: ranked hits, phrase queries
+    #[arg(long)]
+    ranked: bool,
+    /// Only blocks written by this principal (username or id hex)
+    #[arg(long, requires = "ranked")]
+    author: Option<String>,
+    /// Only blocks created at or after: unix millis, or an age (30m, 2h, 7d, 1w)
+    #[arg(long, requires = "ranked")]
+    since: Option<String>,
+    /// Only blocks created before: unix millis, or an age (30m, 2h, 7d, 1w)
+    #[arg(long, requires = "ranked")]
+    until: Option<String>,
     /// Emit a JSON envelope instead of grep-style text
     #[arg(long)]
     json: bool,
@@ -84,6 +104,10 @@ impl KjDispatcher {
             }
         };
 
+        if parsed.ranked {
+            return self.search_ranked(&parsed, caller);
+        }
+
         let regex = match Regex::new(&parsed.pattern) {
             Ok(r) => r,
             Err(e) => return KjResult::Err(format!("kj search: invalid regex: {e}")),
@@ -112,18 +136,18 @@ impl KjDispatcher {
         let role_filter = parsed.role.as_deref().and_then(Role::from_str);
 
         let mut matches: Vec<SearchMatch> = Vec::new();
-        let cl = parsed.context_lines as usize;
+        let cl = parsed.context_lines;
         let max = parsed.max_matches;
 
         'outer: for ctx_id in context_ids {
-            let snapshots = match self.blocks.block_snapshots(ctx_id) {
+            let snapshots = match self.blocks.block_snapshots_with_frontiers(ctx_id) {
                 // Missing document or sync error in one context shouldn't abort
                 // the whole walk — skip it. Same shape as MCP kernel_search.
                 Ok(s) => s,
                 Err(_) => continue,
             };
 
-            for snap in snapshots {
+            for (snap, frontier) in snapshots {
                 if let Some(k) = kind_filter
                     && snap.kind != k
                 {
@@ -135,25 +159,30 @@ impl KjDispatcher {
                     continue;
                 }
 
-                let lines: Vec<&str> = snap.content.lines().collect();
-                for (idx, line) in lines.iter().enumerate() {
+                let content = &snap.content;
+                // Indexed on the first match only; most blocks have none.
+                let mut index = None;
+                for (idx, line) in content.lines().enumerate() {
                     if !regex.is_match(line) {
                         continue;
                     }
-                    let before: Vec<String> = (0..cl)
-                        .filter_map(|i| idx.checked_sub(i + 1).map(|j| lines[j].to_string()))
-                        .collect::<Vec<_>>()
-                        .into_iter()
-                        .rev()
+                    let index = index.get_or_insert_with(|| {
+                        self.blocks.line_index(&snap.id, &frontier, content)
+                    });
+                    let line_no = idx as u32;
+                    let before: Vec<String> = index
+                        .lines(content, line_no.saturating_sub(cl), line_no)
+                        .map(str::to_string)
                         .collect();
-                    let after: Vec<String> = (1..=cl)
-                        .filter_map(|i| lines.get(idx + i).map(|s| s.to_string()))
+                    let after: Vec<String> = index
+                        .lines(content, line_no + 1, (line_no + 1).saturating_add(cl))
+                        .map(str::to_string)
                         .collect();
                     matches.push(SearchMatch {
                         context_id: ctx_id.to_hex(),
                         block_id: snap.id.to_key(),
                         line: idx as u32,
-                        content: (*line).to_string(),
+                        content: line.to_string(),
                         before,
                         after,
                     });
@@ -217,6 +246,125 @@ impl KjDispatcher {
         }
         KjResult::ok_with_data(out, id_array)
     }
+
+    /// `kj search --ranked`: one query against the full-text index.
+    fn search_ranked(&self, parsed: &SearchArgs, caller: &KjCaller) -> KjResult {
+        let Some(index) = self.search_index() else {
+            return KjResult::Err(
+                "kj search: --ranked needs the kernel search index, which isn't running"
+                    .to_string(),
+            );
+        };
+        let context_id = if parsed.all {
+            None
+        } else {
+            let db = self.kernel_db().lock();
+            match resolve_context_arg(parsed.context.as_deref(), caller, &db) {
+                Ok(id) => Some(id),
+                Err(e) => return KjResult::Err(format!("kj search: {e}")),
+            }
+        };
+        let author = match parsed.author.as_deref() {
+            None => None,
+            Some(who) => match PrincipalId::parse(who) {
+                Ok(id) => Some(id),
+                Err(_) => match self.kernel_db().lock().seat_by_username(who) {
+                    Ok(Some(id)) => Some(id),
+                    Ok(None) => return KjResult::Err(format!("kj search: no principal '{who}'")),
+                    Err(e) => return KjResult::Err(format!("kj search: {e}")),
+                },
+            },
+        };
+        let now = kaijutsu_types::now_millis();
+        let bound =
+            |arg: &Option<String>| arg.as_deref().map(|t| parse_time_bound(t, now)).transpose();
+        let (since, until) = match (bound(&parsed.since), bound(&parsed.until)) {
+            (Ok(since), Ok(until)) => (since, until),
+            (Err(e), _) | (_, Err(e)) => return KjResult::Err(format!("kj search: {e}")),
+        };
+
+        let query = SearchQuery {
+            text: parsed.pattern.clone(),
+            context_id,
+            kind: parsed.kind.as_deref().and_then(parse_kind),
+            role: parsed.role.as_deref().and_then(Role::from_str),
+            author,
+            since,
+            until,
+            limit: parsed.max_matches,
+        };
+        let hits = match index.search(&query) {
+            Ok(hits) => hits,
+            Err(e) => return KjResult::Err(format!("kj search: {e}")),
+        };
+
+        let id_array = serde_json::Value::Array(
+            hits.iter()
+                .map(|h| serde_json::Value::String(h.block_id.to_key()))
+                .collect(),
+        );
+        if parsed.json {
+            let rows: Vec<_> = hits
+                .iter()
+                .map(|h| {
+                    serde_json::json!({
+                        "context_id": h.block_id.context_id.to_hex(),
+                        "block_id": h.block_id.to_key(),
+                        "kind": h.kind.as_str(),
+                        "role": h.role.as_str(),
+                        "created_at": h.created_at,
+                        "score": h.score,
+                        "snippet": h.snippet,
+                    })
+                })
+                .collect();
+            let envelope = serde_json::json!({
+                "hits": rows,
+                "total": rows.len(),
+                "truncated": rows.len() >= parsed.max_matches,
+            });
+            return KjResult::ok_with_data(envelope.to_string(), id_array);
+        }
+        if hits.is_empty() {
+            return KjResult::ok_with_data("(no matches)\n".to_string(), id_array);
+        }
+        let mut out = String::new();
+        for h in &hits {
+            out.push_str(&format!(
+                "{}:{}#{}  {:.2}  {}\n",
+                h.block_id.context_id.short(),
+                h.block_id.principal_id.short(),
+                h.block_id.seq,
+                h.score,
+                first_line(&h.snippet),
+            ));
+        }
+        KjResult::ok_with_data(out, id_array)
+    }
+}
+
+/// A `--since`/`--until` value: unix millis, or an age before `now`
+/// (`30m`, `2h`, `7d`, `1w`).
+fn parse_time_bound(arg: &str, now: u64) -> Result<u64, String> {
+    if let Ok(millis) = arg.parse::<u64>() {
+        return Ok(millis);
+    }
+    let bad = || format!("bad time '{arg}' (unix millis, or 30m / 2h / 7d / 1w)");
+    let unit_ms: u64 = match arg.chars().last() {
+        Some('s') => 1_000,
+        Some('m') => 60_000,
+        Some('h') => 3_600_000,
+        Some('d') => 86_400_000,
+        Some('w') => 7 * 86_400_000,
+        _ => return Err(bad()),
+    };
+    let count: u64 = arg[..arg.len() - 1].parse().map_err(|_| bad())?;
+    Ok(now.saturating_sub(count.saturating_mul(unit_ms)))
+}
+
+/// A snippet on one line, for the text listing.
+fn first_line(snippet: &str) -> String {
+    snippet.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 fn parse_kind(s: &str) -> Option<BlockKind> {
@@ -231,6 +379,7 @@ fn parse_kind(s: &str) -> Option<BlockKind> {
         "notification" => Some(BlockKind::Notification),
         "resource" => Some(BlockKind::Resource),
         "trace" => Some(BlockKind::Trace),
+        "repl" => Some(BlockKind::Repl),
         _ => None,
     }
 }
@@ -482,4 +631,55 @@ mod tests {
         let v: serde_json::Value = serde_json::from_str(result.message()).unwrap();
         assert_eq!(v["total"], 2, "both contexts must contribute: {v}");
     }
+
+    #[tokio::test]
+    async fn search_ranked_uses_the_index() {
+        let d = test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx_a = register_context_with_doc(&d, Some("a"), principal);
+        let ctx_b = register_context_with_doc(&d, Some("b"), principal);
+        let _ = insert_text_block(&d, ctx_a, TypesRole::User, "the merge lost an edit");
+        let _ = insert_text_block(&d, ctx_b, TypesRole::Model, "merge merge merge");
+        let c = caller_with_context(ctx_a);
+
+        let argv = [
+            s("search"),
+            s("merge"),
+            s("--ranked"),
+            s("--all"),
+            s("--json"),
+        ];
+        let result = d.dispatch(&argv, &c).await;
+        assert!(!result.is_ok(), "no index installed yet");
+
+        let index = std::sync::Arc::new(crate::search_index::SearchIndex::in_memory().unwrap());
+        for ctx in [ctx_a, ctx_b] {
+            let blocks = d.block_store().block_snapshots(ctx).unwrap();
+            index.reindex_context(ctx, &blocks).unwrap();
+        }
+        d.set_search_index(Some(index));
+
+        let result = d.dispatch(&argv, &c).await;
+        assert!(
+            result.is_ok(),
+            "search --ranked failed: {}",
+            result.message()
+        );
+        let v: serde_json::Value = serde_json::from_str(result.message()).unwrap();
+        assert_eq!(v["total"], 2, "{v}");
+        assert_eq!(
+            v["hits"][0]["context_id"],
+            ctx_b.to_hex(),
+            "denser match first"
+        );
+
+        let result = d
+            .dispatch(
+                &[s("search"), s("\"merge lost\""), s("--ranked"), s("--json")],
+                &c,
+            )
+            .await;
+        let v: serde_json::Value = serde_json::from_str(result.message()).unwrap();
+        assert_eq!(v["total"], 1, "phrase, current context only: {v}");
+    }
 }
@@ -11,12 +11,31 @@
 //! - Can be forked (heavy copy, isolated) or threaded (light, shared VFS)
 //! - Has a DriftRouter for cross-context communication (shared across fork/thread)
 
+pub mod abandoned;
+pub mod analytics;
+pub mod block_fanout;
+pub mod block_locks;
 pub mod block_store;
+pub mod block_tail;
 pub mod block_tools;
+pub mod block_transaction;
+pub mod budget;
 pub mod image;
+pub mod inbox;
+pub mod completion;
+pub mod config_apply;
 pub mod config_doc;
 pub mod config_seed;
+pub mod consent_log;
+pub mod consent_prompts;
+pub mod context_activity;
+pub mod context_health;
+pub mod context_kv;
+pub mod context_share;
+pub mod context_timeline;
 pub mod control;
+pub mod doc_bookmark;
+pub mod doc_stats;
 pub mod drift;
 pub mod editor;
 pub mod execution;
@@ -24,17 +43,29 @@ pub mod file_tools;
 pub mod flows;
 pub mod hyoushigi;
 pub mod input_doc;
+pub mod hook_policy;
+pub mod issues;
+pub mod journal_queue;
 pub mod kernel;
 pub mod kernel_db;
 pub mod kj;
 pub mod llm;
 pub mod mcp;
+pub mod mention;
+pub mod model_switch;
 pub mod peers;
+pub mod push_validation;
+pub mod repl;
 pub mod runtime;
+pub mod search_index;
 pub mod seed_presets;
 pub mod seed_scripts;
 pub mod state;
+pub mod suggestion;
+pub mod tool_presets;
+pub mod translate;
 pub mod vfs;
+pub mod webhooks;
 
 /// Stack size for any thread that drives rc lifecycles (the server's beat
 /// scheduler and SSH session threads; the equivalent test harnesses).
@@ -55,7 +86,8 @@ pub use peers::{
 };
 pub use block_store::DocumentKind;
 pub use block_store::{
-    BlockStore, BlockStoreError, BlockStoreResult, DbHandle, SharedBlockStore, shared_block_store,
+    BlockStore, BlockStoreError, BlockStoreResult, ContextSyncState, DbHandle, OplogRepair,
+    SharedBlockStore, shared_block_store, shared_block_store_with_db,
 };
 
 pub use config_seed::DEFAULT_SYSTEM_PROMPT;
@@ -88,6 +120,7 @@ pub use llm::{
     ToolDefinition as LlmToolDefinition,
     Usage as LlmUsage,
     // Conversation session
+    ContextAssembler,
     ConversationMailbox,
     // Hydration
     hydrate_from_blocks,
@@ -128,22 +161,25 @@ pub use file_tools::FileDocumentCache;
 // broadcast on each ExternalMcpServer.
 pub use flows::{
     BlockFlow,
+    ConfigFlow,
     FlowBus,
     FlowMessage,
     HasSubject,
     InputDocFlow,
     OpSource,
     SharedBlockFlowBus,
+    SharedConfigFlowBus,
     SharedInputDocFlowBus,
     Subscription,
     shared_block_flow_bus,
+    shared_config_flow_bus,
     shared_input_doc_flow_bus,
 };
 pub use input_doc::InputDocEntry;
 pub use kernel_db::{
     ContextEdgeRow, ContextEnvRow, ContextRow, ContextShellRow, DocSnapshotRow, DocumentRow,
-    InputDocSnapshotRow, KernelDb, KernelDbError, KernelDbResult, PresetRow, WorkspacePathRow,
-    WorkspaceRow,
+    InputDocSnapshotRow, KernelDb, KernelDbError, KernelDbResult, PresetRow, SyncPolicy,
+    WorkspacePathRow, WorkspaceRow,
 };
 pub use kj::{KjCaller, KjDispatcher, KjResult};
 
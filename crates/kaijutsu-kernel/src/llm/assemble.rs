@@ -0,0 +1,273 @@
+//! assemble.rs — the standard prompt-context assemblers.
+//!
+//! A context's [`Assembly`] (`kaijutsu_types::assembly`) names which blocks of
+//! its log reach the model: the linear transcript, the DAG path to a block, a
+//! pinned prefix plus a recent window, or everything from the last checkpoint
+//! — composed left to right, each stage narrowing what the previous one kept.
+//! [`ContextAssembler`] applies one to a block log and produces the order-free
+//! keep-set; [`plan_splice`](super::splice) owns the cut hygiene when the
+//! mailbox folds it (turn-boundary snapping, tool pairs, archived-gap seams).
+//!
+//! The LLM stream and the `contextPreview` RPC both go through
+//! [`ContextAssembler::hydrate`], so a preview is exactly the wire history a
+//! turn would send.
+
+use std::collections::HashSet;
+
+use kaijutsu_crdt::IntervalSet;
+use kaijutsu_types::{Assembly, AssemblyStage, BlockId, BlockKind, BlockSnapshot, DriftKind};
+use tracing::warn;
+
+use super::ConversationMailbox;
+
+/// The effective assembly for one context, with the hydration marker the
+/// `window` stage pins to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContextAssembler {
+    assembly: Assembly,
+    marker: Option<BlockId>,
+}
+
+impl ContextAssembler {
+    pub fn new(assembly: Assembly, marker: Option<BlockId>) -> Self {
+        Self { assembly, marker }
+    }
+
+    /// Resolve a context's assembler from its stored strategy and its
+    /// hydration policy (`kj context hydrate`). A stored strategy wins and
+    /// takes only the marker from the policy; a policy alone is
+    /// `window:<n>` pinned at its marker; neither is `linear`.
+    pub fn resolve(stored: Option<Assembly>, policy: Option<(BlockId, u32)>) -> Self {
+        let marker = policy.map(|(marker, _)| marker);
+        let assembly = match (stored, policy) {
+            (Some(assembly), _) => assembly,
+            (None, Some((_, window))) => Assembly::new(vec![AssemblyStage::Window(window)]),
+            (None, None) => Assembly::linear(),
+        };
+        Self { assembly, marker }
+    }
+
+    pub fn assembly(&self) -> &Assembly {
+        &self.assembly
+    }
+
+    pub fn marker(&self) -> Option<BlockId> {
+        self.marker
+    }
+
+    /// The keep-set over `blocks` (indices into the slice). Fails only on a
+    /// `path` stage naming a block that isn't in the log.
+    pub fn select(&self, blocks: &[BlockSnapshot]) -> Result<IntervalSet, String> {
+        let mut kept: Vec<usize> = (0..blocks.len()).collect();
+        for stage in self.assembly.stages() {
+            kept = self.apply(*stage, blocks, kept)?;
+        }
+        Ok(IntervalSet::from_ranges(kept.into_iter().map(|i| i..i + 1)))
+    }
+
+    fn apply(
+        &self,
+        stage: AssemblyStage,
+        blocks: &[BlockSnapshot],
+        kept: Vec<usize>,
+    ) -> Result<Vec<usize>, String> {
+        Ok(match stage {
+            AssemblyStage::Linear => kept,
+            AssemblyStage::Checkpoint => {
+                let is_checkpoint = |i: &usize| {
+                    let b = &blocks[*i];
+                    b.kind == BlockKind::Drift && b.drift_kind == Some(DriftKind::Distill)
+                };
+                match kept.iter().rposition(is_checkpoint) {
+                    Some(pos) => kept[pos..].to_vec(),
+                    None => kept,
+                }
+            }
+            AssemblyStage::Path(target) => {
+                let target_idx = match target {
+                    Some(id) => blocks
+                        .iter()
+                        .position(|b| b.id == id)
+                        .ok_or_else(|| format!("path block {id} is not in this context"))?,
+                    None => match kept.last() {
+                        Some(&i) => i,
+                        None => return Ok(kept),
+                    },
+                };
+                // The target's ancestor chain, target included. A cycle can't
+                // occur in a well-formed log, but the visited set bounds the
+                // walk regardless.
+                let mut chain = HashSet::new();
+                let mut cursor = Some(blocks[target_idx].id);
+                while let Some(id) = cursor
+                    && chain.insert(id)
+                {
+                    cursor = blocks.iter().find(|b| b.id == id).and_then(|b| b.parent_id);
+                }
+                kept.into_iter()
+                    .filter(|&i| {
+                        i <= target_idx
+                            && (blocks[i].parent_id.is_none() || chain.contains(&blocks[i].id))
+                    })
+                    .collect()
+            }
+            AssemblyStage::Window(n) => {
+                let pinned_end = match self.marker {
+                    Some(marker) => match blocks.iter().position(|b| b.id == marker) {
+                        Some(idx) => idx,
+                        None => {
+                            // Never hide context behind a stale marker: keep
+                            // everything and say so (this runs every turn).
+                            warn!(
+                                marker = %marker,
+                                blocks = blocks.len(),
+                                "hydration marker not in block log; window stage bypassed — \
+                                 re-set with `kj context hydrate`"
+                            );
+                            return Ok(kept);
+                        }
+                    },
+                    // No marker: pin the first kept block; the splicer extends
+                    // it to the end of its turn.
+                    None => match kept.first() {
+                        Some(&i) => i,
+                        None => return Ok(kept),
+                    },
+                };
+                let tail_start = kept.len().saturating_sub(n as usize);
+                kept.iter()
+                    .enumerate()
+                    .filter(|&(pos, &i)| i <= pinned_end || pos >= tail_start)
+                    .map(|(_, &i)| i)
+                    .collect()
+            }
+        })
+    }
+
+    /// Fold `blocks` into `mailbox` under this assembly. Linear assemblies
+    /// catch up incrementally; anything else rebuilds from its keep-set every
+    /// call, since a sliding selection can drop blocks an append-only fold
+    /// can't. Returns how many blocks of the log were kept.
+    pub fn hydrate(
+        &self,
+        mailbox: &mut ConversationMailbox,
+        blocks: &[BlockSnapshot],
+    ) -> Result<usize, String> {
+        if self.assembly.is_linear() {
+            mailbox.catch_up(blocks);
+            return Ok(blocks.len());
+        }
+        let keep_set = self.select(blocks)?;
+        let kept = keep_set.count();
+        mailbox.rehydrate_selected(blocks, keep_set.runs());
+        Ok(kept)
+    }
+}
+
+impl Default for ContextAssembler {
+    fn default() -> Self {
+        Self::new(Assembly::linear(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_types::{BlockSnapshotBuilder, ContextId, PrincipalId, Role};
+
+    struct Log {
+        ctx: ContextId,
+        principal: PrincipalId,
+        blocks: Vec<BlockSnapshot>,
+    }
+
+    impl Log {
+        fn new() -> Self {
+            Self {
+                ctx: ContextId::new(),
+                principal: PrincipalId::new(),
+                blocks: Vec::new(),
+            }
+        }
+
+        fn push(&mut self, role: Role, kind: BlockKind, parent: Option<BlockId>) -> BlockId {
+            let id = BlockId::new(self.ctx, self.principal, self.blocks.len() as u64);
+            let mut builder = BlockSnapshotBuilder::new(id, kind).role(role).content("x");
+            if let Some(parent) = parent {
+                builder = builder.parent_id(parent);
+            }
+            if kind == BlockKind::Drift {
+                builder = builder.drift_kind(DriftKind::Distill);
+            }
+            self.blocks.push(builder.build());
+            id
+        }
+
+        fn turn(&mut self) -> BlockId {
+            self.push(Role::User, BlockKind::Text, None);
+            self.push(Role::Model, BlockKind::Text, None)
+        }
+
+        fn select(&self, spec: &str, marker: Option<BlockId>) -> Vec<std::ops::Range<usize>> {
+            ContextAssembler::new(spec.parse().unwrap(), marker)
+                .select(&self.blocks)
+                .unwrap()
+                .into_runs()
+        }
+    }
+
+    #[test]
+    fn linear_keeps_everything() {
+        let mut log = Log::new();
+        log.turn();
+        log.turn();
+        assert_eq!(log.select("linear", None), vec![0..4]);
+    }
+
+    #[test]
+    fn window_pins_marker_or_first_block() {
+        let mut log = Log::new();
+        let marker = log.turn();
+        for _ in 0..4 {
+            log.turn();
+        }
+        assert_eq!(log.select("window:2", Some(marker)), vec![0..2, 8..10]);
+        assert_eq!(log.select("window:2", None), vec![0..1, 8..10]);
+        // A stale marker keeps everything rather than hiding context.
+        let stale = BlockId::new(ContextId::new(), PrincipalId::new(), 0);
+        assert_eq!(log.select("window:2", Some(stale)), vec![0..10]);
+    }
+
+    #[test]
+    fn checkpoint_starts_at_latest_distill() {
+        let mut log = Log::new();
+        log.turn();
+        log.push(Role::System, BlockKind::Drift, None);
+        log.turn();
+        log.push(Role::System, BlockKind::Drift, None);
+        log.turn();
+        assert_eq!(log.select("checkpoint", None), vec![5..8]);
+        // Composed: the window pins the summary and keeps the last turn.
+        log.turn();
+        assert_eq!(log.select("checkpoint+window:2", None), vec![5..6, 8..10]);
+    }
+
+    #[test]
+    fn path_drops_side_branches_and_later_blocks() {
+        let mut log = Log::new();
+        let q = log.push(Role::User, BlockKind::Text, None);
+        log.push(Role::Model, BlockKind::Text, Some(q)); // 1: side branch
+        let a = log.push(Role::Model, BlockKind::Text, None); // 2
+        let target = log.push(Role::Model, BlockKind::Text, Some(a)); // 3: on path
+        log.turn(); // 4, 5: after the target
+        let spec = format!("path:{}", target.to_key());
+        assert_eq!(log.select(&spec, None), vec![0..1, 2..4]);
+        let missing = BlockId::new(ContextId::new(), PrincipalId::new(), 9);
+        let assembly = Assembly::new(vec![AssemblyStage::Path(Some(missing))]);
+        assert!(
+            ContextAssembler::new(assembly, None)
+                .select(&log.blocks)
+                .is_err()
+        );
+    }
+}
@@ -19,7 +19,7 @@ use std::collections::HashSet;
 
 use super::types::{
     CacheControl, ImageSource, MessageContent, MessageRole, MessagesRequest, RequestContent,
-    RequestMessage, RequestTool, SystemBlock, SystemPrompt,
+    RequestMessage, RequestTool, SystemBlock, SystemPrompt, ToolChoice,
 };
 use crate::llm::stream::{BuildOpts, CacheTarget, CacheTtl};
 use crate::llm::{ContentBlock, Message, MessageContent as KaiContent, Role};
@@ -138,8 +138,9 @@ pub fn build_request(
         messages: request_messages,
         system,
         tools,
-        tool_choice: None,
+        tool_choice: opts.tool_choice.as_ref().map(build_tool_choice),
         temperature: opts.temperature,
+        top_p: opts.top_p,
         stream: streaming.then_some(true),
         thinking: None, // stream() applies the model-gated default
         stop_sequences: vec![],
@@ -218,6 +219,15 @@ fn apply_cache_to_last_eligible(blocks: &mut [RequestContent], ttl: CacheTtl) ->
     false
 }
 
+fn build_tool_choice(choice: &kaijutsu_types::ToolChoice) -> ToolChoice {
+    match choice {
+        kaijutsu_types::ToolChoice::Auto => ToolChoice::Auto,
+        kaijutsu_types::ToolChoice::Any => ToolChoice::Any,
+        kaijutsu_types::ToolChoice::None => ToolChoice::None,
+        kaijutsu_types::ToolChoice::Tool(name) => ToolChoice::Tool { name: name.clone() },
+    }
+}
+
 fn build_tools(
     tools: &[crate::llm::ToolDefinition],
     cache_ttl: Option<CacheTtl>,
@@ -962,4 +972,30 @@ mod tests {
         let req = build_request(&o, &[Message::user("hi")], false);
         assert!(req.tools.is_empty());
     }
+
+    #[test]
+    fn params_map_onto_wire_fields() {
+        let o = opts("claude-haiku-4-5")
+            .with_tools(vec![tool("shell")])
+            .with_params(&kaijutsu_types::LlmParams {
+                temperature: Some(0.2),
+                max_tokens: Some(4096),
+                top_p: Some(0.95),
+                tool_choice: Some(kaijutsu_types::ToolChoice::Tool("shell".into())),
+            });
+        let req = build_request(&o, &[Message::user("hi")], false);
+        assert_eq!(req.max_tokens, 4096);
+        let v = serde_json::to_value(&req).unwrap();
+        assert_eq!(v["temperature"], 0.2);
+        assert_eq!(v["top_p"], 0.95);
+        assert_eq!(
+            v["tool_choice"],
+            serde_json::json!({"type": "tool", "name": "shell"})
+        );
+
+        let req = build_request(&opts("claude-haiku-4-5"), &[Message::user("hi")], false);
+        let v = serde_json::to_value(&req).unwrap();
+        assert!(v.get("tool_choice").is_none());
+        assert!(v.get("top_p").is_none());
+    }
 }
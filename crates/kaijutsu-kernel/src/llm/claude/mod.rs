@@ -149,6 +149,18 @@ impl Client {
         Ok(text)
     }
 
+    /// Check the key, and that `model` exists when given: `GET /v1/models/
+    /// {model}` (or the model list). A missing model is a 404, which maps to
+    /// [`LlmError::InvalidRequest`].
+    pub async fn check_model(&self, model: Option<&str>) -> LlmResult<()> {
+        let url = match model {
+            Some(model) => format!("{}/v1/models/{model}", self.base_url),
+            None => format!("{}/v1/models?limit=1", self.base_url),
+        };
+        let response = self.http.get(url).send().await.map_err(http_error)?;
+        self.error_for_status(response).await.map(|_| ())
+    }
+
     /// Start a streaming completion.
     ///
     /// POSTs `MessagesRequest { stream: true, … }` and wraps the
@@ -40,6 +40,9 @@ pub struct MessagesRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+
     /// `true` enables SSE streaming; the response Content-Type is
     /// `text/event-stream` and bodies are emitted as the events listed
     /// in `super::sse`.
@@ -180,7 +183,6 @@ pub struct RequestTool {
 /// disable tools for a turn).
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
-#[allow(dead_code)] // variants reserved for Phase 2.5+
 pub enum ToolChoice {
     Auto,
     Any,
@@ -378,6 +380,7 @@ mod tests {
             tools: vec![],
             tool_choice: None,
             temperature: None,
+            top_p: None,
             stream: None,
             thinking: None,
             stop_sequences: vec![],
@@ -388,6 +391,8 @@ mod tests {
         assert!(v.get("tools").is_none(), "empty tools must skip-serialize");
         assert!(v.get("stream").is_none());
         assert!(v.get("thinking").is_none());
+        assert!(v.get("tool_choice").is_none());
+        assert!(v.get("top_p").is_none());
         assert_eq!(v["model"], "claude-haiku-4-5");
         assert_eq!(v["max_tokens"], 1024);
     }
@@ -8,7 +8,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Configuration for an LLM provider.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProviderConfig {
     /// Provider type identifier (e.g., "anthropic", "gemini", "ollama").
     pub provider_type: String,
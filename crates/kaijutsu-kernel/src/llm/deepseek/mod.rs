@@ -56,6 +56,11 @@ impl Client {
         self.0.prompt(model, system, prompt).await
     }
 
+    /// Check the key and that `model` is served.
+    pub async fn check_model(&self, model: Option<&str>) -> LlmResult<()> {
+        self.0.check_model(model).await
+    }
+
     /// Start a streaming completion.
     pub async fn stream(
         &self,
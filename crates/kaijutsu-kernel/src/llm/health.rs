@@ -0,0 +1,295 @@
+//! Provider health — find a rejected key or a missing model before a turn
+//! does.
+//!
+//! [`check_providers`] probes every registered provider with
+//! [`Provider::check_model`] — a model lookup that spends no tokens — against
+//! the model a turn would use, times it, and files the outcome as a
+//! [`ProviderHealth`] in the kernel's [`LlmHealth`]. [`spawn_checker`] runs
+//! it at startup and every [`CHECK_INTERVAL`] after; a live `models.toml`
+//! re-apply runs it once more. `getLlmConfig` and `kj models` report the
+//! results. When the last check found a rejected key or a missing model, the
+//! agent loop probes again before a turn and, if it still fails, refuses the
+//! turn with that reason instead of letting it die on a stream error.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+use serde::Serialize;
+
+use super::{LlmError, LlmRegistry, Provider};
+use crate::kernel::Kernel;
+
+/// Time between periodic checks.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// A probe slower than this counts as unreachable.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Outcome of one probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    /// The key was rejected (or is missing).
+    AuthFailed,
+    /// The key works but the model isn't served.
+    ModelUnavailable,
+    /// No answer: DNS, connect, TLS, timeout.
+    Unreachable,
+    /// Answered, but with a rate limit or server error — may pass on retry.
+    Degraded,
+}
+
+impl HealthStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::AuthFailed => "auth_failed",
+            Self::ModelUnavailable => "model_unavailable",
+            Self::Unreachable => "unreachable",
+            Self::Degraded => "degraded",
+        }
+    }
+
+    fn from_error(e: &LlmError) -> Self {
+        match e {
+            LlmError::AuthError(_) => Self::AuthFailed,
+            LlmError::InvalidRequest(_) => Self::ModelUnavailable,
+            LlmError::NetworkError(_) | LlmError::Unavailable(_) => Self::Unreachable,
+            LlmError::RateLimited(_) | LlmError::ApiError(_) | LlmError::CompletionError(_) => {
+                Self::Degraded
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The last check of one provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProviderHealth {
+    pub provider: String,
+    /// The model probed; empty when the provider has none configured.
+    pub model: String,
+    pub status: HealthStatus,
+    /// Round trip of the probe.
+    pub latency_ms: u64,
+    /// The provider's error, when not `Ok`.
+    pub detail: String,
+    /// Unix millis.
+    pub checked_at: u64,
+}
+
+impl ProviderHealth {
+    /// Why a turn on this provider can't succeed, if the check says so.
+    /// Transient failures (unreachable, degraded) don't block — the turn's
+    /// own retries may get through.
+    pub fn turn_blocker(&self) -> Option<String> {
+        let what = match self.status {
+            HealthStatus::AuthFailed => "API key rejected",
+            HealthStatus::ModelUnavailable => "model not available",
+            _ => return None,
+        };
+        Some(format!(
+            "{} {what} (health check: {}) — fix models.toml or the key and retry",
+            self.provider, self.detail
+        ))
+    }
+}
+
+/// Last check per provider, by name.
+#[derive(Debug, Default)]
+pub struct LlmHealth(RwLock<HashMap<String, ProviderHealth>>);
+
+/// Shared handle to the kernel's health table.
+pub type SharedLlmHealth = Arc<LlmHealth>;
+
+impl LlmHealth {
+    pub fn get(&self, provider: &str) -> Option<ProviderHealth> {
+        self.0.read().get(provider).cloned()
+    }
+
+    /// Every provider's last check, sorted by name.
+    pub fn all(&self) -> Vec<ProviderHealth> {
+        let mut all: Vec<ProviderHealth> = self.0.read().values().cloned().collect();
+        all.sort_by(|a, b| a.provider.cmp(&b.provider));
+        all
+    }
+
+    /// File one provider's check, replacing its last.
+    pub fn record(&self, health: ProviderHealth) {
+        self.0.write().insert(health.provider.clone(), health);
+    }
+
+    /// Replace the table with one full round of checks; providers no longer
+    /// registered drop out.
+    fn replace(&self, results: &[ProviderHealth]) {
+        let mut table = self.0.write();
+        table.clear();
+        for health in results {
+            table.insert(health.provider.clone(), health.clone());
+        }
+    }
+}
+
+/// Probe `provider` for `model`.
+pub async fn probe(name: &str, provider: &Provider, model: Option<&str>) -> ProviderHealth {
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(PROBE_TIMEOUT, provider.check_model(model)).await;
+    let (status, detail) = match outcome {
+        Ok(Ok(())) => (HealthStatus::Ok, String::new()),
+        Ok(Err(e)) => (HealthStatus::from_error(&e), e.to_string()),
+        Err(_) => (
+            HealthStatus::Unreachable,
+            format!("no answer in {}s", PROBE_TIMEOUT.as_secs()),
+        ),
+    };
+    ProviderHealth {
+        provider: name.to_string(),
+        model: model.unwrap_or_default().to_string(),
+        status,
+        latency_ms: started.elapsed().as_millis() as u64,
+        detail,
+        checked_at: kaijutsu_types::now_millis(),
+    }
+}
+
+/// The model a turn on `name` would use: the registry default for the
+/// default provider, else the provider's configured default.
+fn probe_model(registry: &LlmRegistry, name: &str) -> Option<String> {
+    if registry.default_provider_name() == Some(name)
+        && let Some(model) = registry.default_model()
+    {
+        return Some(model.to_string());
+    }
+    registry
+        .provider_config(name)
+        .and_then(|c| c.default_model.clone())
+}
+
+/// Probe every registered provider at once and record the results.
+pub async fn check_providers(
+    llm: &tokio::sync::RwLock<LlmRegistry>,
+    health: &LlmHealth,
+) -> Vec<ProviderHealth> {
+    // Probes run without the registry lock; a turn may need it meanwhile.
+    let targets: Vec<(String, Arc<Provider>, Option<String>)> = {
+        let registry = llm.read().await;
+        registry
+            .list()
+            .into_iter()
+            .filter_map(|name| {
+                let provider = registry.get(name)?;
+                Some((name.to_string(), provider, probe_model(&registry, name)))
+            })
+            .collect()
+    };
+    let results = futures::future::join_all(
+        targets
+            .iter()
+            .map(|(name, provider, model)| probe(name, provider, model.as_deref())),
+    )
+    .await;
+    for h in results.iter().filter(|h| h.status != HealthStatus::Ok) {
+        tracing::warn!(
+            provider = %h.provider,
+            model = %h.model,
+            status = %h.status,
+            "LLM provider health check failed: {}",
+            h.detail
+        );
+    }
+    health.replace(&results);
+    results
+}
+
+/// Check every provider now and each [`CHECK_INTERVAL`] after. Call once,
+/// from a runtime that lives as long as the kernel.
+pub fn spawn_checker(kernel: Arc<Kernel>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            check_providers(kernel.llm(), kernel.llm_health()).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockClient;
+
+    #[tokio::test]
+    async fn check_records_each_provider_and_blocks_on_a_bad_key() {
+        let mut registry = LlmRegistry::new();
+        registry.register("good", Arc::new(Provider::Mock(MockClient::new("ok"))));
+        registry.register(
+            "bad",
+            Arc::new(Provider::Mock(
+                MockClient::new("ok").with_auth_failure("invalid x-api-key"),
+            )),
+        );
+        registry.set_default("good");
+        registry.set_default_model("mock-model");
+        let llm = tokio::sync::RwLock::new(registry);
+        let health = LlmHealth::default();
+
+        let results = check_providers(&llm, &health).await;
+        assert_eq!(results.len(), 2);
+
+        let good = health.get("good").unwrap();
+        assert_eq!(good.status, HealthStatus::Ok);
+        assert_eq!(good.model, "mock-model");
+        assert_eq!(good.turn_blocker(), None);
+
+        let bad = health.get("bad").unwrap();
+        assert_eq!(bad.status, HealthStatus::AuthFailed);
+        assert_eq!(bad.model, "", "no model configured for it");
+        let blocker = bad.turn_blocker().unwrap();
+        assert!(blocker.contains("API key rejected"), "{blocker}");
+        assert!(blocker.contains("invalid x-api-key"), "{blocker}");
+
+        assert_eq!(
+            health
+                .all()
+                .iter()
+                .map(|h| h.provider.as_str())
+                .collect::<Vec<_>>(),
+            vec!["bad", "good"]
+        );
+
+        // A provider dropped from the registry drops out on the next round.
+        *llm.write().await = LlmRegistry::new();
+        check_providers(&llm, &health).await;
+        assert!(health.all().is_empty());
+    }
+
+    #[test]
+    fn errors_classify_by_kind() {
+        let status = |e: LlmError| HealthStatus::from_error(&e);
+        assert_eq!(
+            status(LlmError::AuthError("401".into())),
+            HealthStatus::AuthFailed
+        );
+        assert_eq!(
+            status(LlmError::InvalidRequest("404 not_found_error".into())),
+            HealthStatus::ModelUnavailable
+        );
+        assert_eq!(
+            status(LlmError::NetworkError("connect".into())),
+            HealthStatus::Unreachable
+        );
+        assert_eq!(
+            status(LlmError::RateLimited("429".into())),
+            HealthStatus::Degraded
+        );
+    }
+}
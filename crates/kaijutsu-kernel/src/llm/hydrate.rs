@@ -19,7 +19,11 @@
 
 use std::collections::HashMap;
 
-use kaijutsu_types::{BlockId, BlockKind, BlockSnapshot, ContentType, Role as BlockRole};
+use kaijutsu_types::{
+    BlockId, BlockKind, BlockSnapshot, ContentType, NotificationKind, Role as BlockRole,
+};
+
+use kaijutsu_types::language::markdown_fence;
 
 use super::{ContentBlock, Message, MessageContent, Role};
 
@@ -94,6 +98,11 @@ impl HydrationState {
         {
             return;
         }
+        // REPL session blocks are input logs; their output children carry
+        // the echoed input.
+        if block.kind == BlockKind::Repl && block.parent_id.is_none() {
+            return;
+        }
         if block.content.is_empty()
             && block.kind != BlockKind::ToolCall
             && block.kind != BlockKind::ToolResult
@@ -212,6 +221,17 @@ impl HydrationState {
                     }
                 }
             }
+            (BlockRole::User, BlockKind::Repl) => {
+                // A person's REPL submission — surface the transcript the way
+                // a user-run shell command is. Agent submissions (Tool role)
+                // stay skipped: `repl_send`'s tool result already carries them.
+                let name = block.language.as_deref().unwrap_or("repl");
+                self.flush_all();
+                self.messages.push(Message::user(format!(
+                    "[User ran in the {name} REPL]\n{}",
+                    markdown_fence(block.content.trim_end(), block.language.as_deref())
+                )));
+            }
             (BlockRole::Tool, BlockKind::ToolResult) => {
                 let user_code = block
                     .tool_call_id
@@ -330,6 +350,13 @@ impl HydrationState {
                 // LLM as a user message so the model sees tool-world changes.
                 let envelope = kaijutsu_types::format_notification_for_llm(block);
                 self.flush_all();
+                if block
+                    .notification
+                    .as_ref()
+                    .is_some_and(|n| n.kind == NotificationKind::ModelChanged)
+                {
+                    self.drop_reasoning();
+                }
                 self.messages.push(Message::user(envelope));
             }
             (_, BlockKind::Resource) => {
@@ -574,6 +601,19 @@ impl HydrationState {
         }
     }
 
+    /// Strip `Reasoning` blocks from everything emitted so far.
+    ///
+    /// Runs at a `ModelChanged` notification: thinking signatures are only
+    /// valid for the model that produced them, so turns before the switch are
+    /// replayed to the new model as plain text and tool use.
+    fn drop_reasoning(&mut self) {
+        for msg in &mut self.messages {
+            if let MessageContent::Blocks(blocks) = &mut msg.content {
+                blocks.retain(|b| !matches!(b, ContentBlock::Reasoning { .. }));
+            }
+        }
+    }
+
     /// Flush any pending tool results into a user message.
     fn flush_tool_results(&mut self) {
         if self.tool_results.is_empty() {
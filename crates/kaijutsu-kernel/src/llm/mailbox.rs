@@ -213,14 +213,27 @@ impl ConversationMailbox {
                  hydrating FULL history (cost guard OFF). Re-set with `kj context hydrate`."
             );
         }
+        let keep_set = hydration_keep_set(blocks, Some(marker), window);
+        self.rehydrate_selected(blocks, &keep_set);
+    }
+
+    /// Rebuild the session against an arbitrary keep-set over the log — the
+    /// general form of [`rehydrate_windowed`], used by the context assemblers
+    /// (`llm::assemble`). `keep_set` is order-free runs of indices into
+    /// `blocks`; it is spliced clean (turn-boundary snaps, tool-pairs kept
+    /// whole, archived gaps marked with a user-role seam) before folding.
+    ///
+    /// Marks the session windowed, so a later [`catch_up`] rebuilds from
+    /// scratch rather than folding the dropped blocks in out of order.
+    ///
+    /// [`rehydrate_windowed`]: Self::rehydrate_windowed
+    /// [`catch_up`]: Self::catch_up
+    pub fn rehydrate_selected(&mut self, blocks: &[BlockSnapshot], keep_set: &[Range<usize>]) {
         self.state = HydrationState::new();
         self.seen.clear();
         let by_id: HashMap<BlockId, &BlockSnapshot> =
             blocks.iter().map(|b| (b.id, b)).collect();
-        // The order-free `window` keep-set, spliced clean: turn-boundary snaps,
-        // tool-pairs kept whole, archived gaps marked with a user-role seam.
-        let keep_set = hydration_keep_set(blocks, Some(marker), window);
-        for item in plan_splice(blocks, &keep_set) {
+        for item in plan_splice(blocks, keep_set) {
             match item {
                 SpliceItem::Keep(i) => {
                     let block = &blocks[i];
@@ -27,9 +27,11 @@
 //! not implemented — add a real provider (or point the OpenAI-compatible
 //! core at Google's OpenAI-shaped endpoint) when it's needed.
 
+pub mod assemble;
 pub mod claude;
 pub mod config;
 pub mod deepseek;
+pub mod health;
 mod hydrate;
 pub mod image_cache;
 pub mod mailbox;
@@ -40,6 +42,7 @@ pub mod system_prompt;
 pub mod toml_config;
 
 // Re-export key types
+pub use assemble::ContextAssembler;
 pub use config::ProviderConfig;
 pub use mailbox::ConversationMailbox;
 pub use stream::{
@@ -68,6 +71,9 @@ pub struct MockClient {
     /// can model a slow provider (e.g. exercising the distill `patient` hold).
     /// Zero by default; the streaming path ignores it.
     pub delay: std::time::Duration,
+    /// When set, [`Provider::check_model`] fails as a rejected key with this
+    /// message, so a test can model a misconfigured provider.
+    pub auth_failure: Option<String>,
 }
 
 #[cfg(any(test, feature = "test-mock"))]
@@ -76,9 +82,16 @@ impl MockClient {
         Self {
             canned_response: response.into(),
             delay: std::time::Duration::ZERO,
+            auth_failure: None,
         }
     }
 
+    /// Builder: make the health probe report a rejected key.
+    pub fn with_auth_failure(mut self, message: impl Into<String>) -> Self {
+        self.auth_failure = Some(message.into());
+        self
+    }
+
     /// Builder: make `prompt`/`prompt_with_system` sleep `delay` before
     /// returning the canned response.
     pub fn with_delay(mut self, delay: std::time::Duration) -> Self {
@@ -575,6 +588,22 @@ impl Provider {
         }
     }
 
+    /// Check the key and, when given, that `model` is served — a model
+    /// lookup that spends no tokens (see [`health`]).
+    #[tracing::instrument(skip(self), fields(llm.provider = self.name()))]
+    pub async fn check_model(&self, model: Option<&str>) -> LlmResult<()> {
+        match self {
+            Self::Claude(client) => client.check_model(model).await,
+            Self::DeepSeek(client) => client.check_model(model).await,
+            Self::OpenAi(client) => client.check_model(model).await,
+            #[cfg(any(test, feature = "test-mock"))]
+            Self::Mock(mock) => match &mock.auth_failure {
+                Some(message) => Err(LlmError::AuthError(message.clone())),
+                None => Ok(()),
+            },
+        }
+    }
+
     /// Models this provider exposes by default.
     pub fn available_models(&self) -> Vec<&'static str> {
         match self {
@@ -1099,7 +1128,8 @@ mod tests {
     mod hydration {
         use super::super::*;
         use kaijutsu_types::{
-            BlockId, BlockSnapshot, ContextId, PrincipalId, Role as BlockRole, ToolKind,
+            BlockId, BlockKind, BlockSnapshot, BlockSnapshotBuilder, ContextId, PrincipalId,
+            Role as BlockRole, ToolKind,
         };
 
         fn ctx() -> ContextId {
@@ -1681,6 +1711,38 @@ mod tests {
             assert!(text.contains("Compiling kaijutsu"), "got: {text}");
         }
 
+        #[test]
+        fn repl_output_hydrates_only_when_a_person_typed_it() {
+            let c = ctx();
+            let u = user();
+            let session = BlockSnapshotBuilder::new(BlockId::new(c, u, 0), BlockKind::Repl)
+                .role(BlockRole::Tool)
+                .language("python")
+                .content("1 + 1\n")
+                .build();
+            let output = |seq, role| {
+                BlockSnapshotBuilder::new(BlockId::new(c, u, seq), BlockKind::Repl)
+                    .parent_id(session.id)
+                    .role(role)
+                    .language("python")
+                    .content(">>> 1 + 1\n2\n")
+                    .build()
+            };
+
+            let msgs = hydrate_from_blocks(&[
+                session.clone(),
+                output(1, BlockRole::User),
+                output(2, BlockRole::Tool),
+            ]);
+            assert_eq!(msgs.len(), 1, "session and agent output are skipped");
+            let text = msgs[0].as_text().unwrap();
+            assert!(
+                text.starts_with("[User ran in the python REPL]"),
+                "got: {text}"
+            );
+            assert!(text.contains("```python\n>>> 1 + 1\n2\n```"), "got: {text}");
+        }
+
         #[test]
         fn user_shell_command_empty_output() {
             let c = ctx();
@@ -2592,6 +2654,44 @@ mod tests {
             );
         }
 
+        #[test]
+        fn model_changed_notification_drops_earlier_reasoning() {
+            // Thinking signatures belong to the model that produced them; a
+            // switched-to model must not be handed the old model's signed
+            // reasoning. Turns after the switch keep theirs.
+            let c = ctx();
+            let u = user();
+            let m = model();
+            let s = system();
+            let mut before = BlockSnapshot::thinking(BlockId::new(c, m, 0), None, "old");
+            before.signature = Some("sig_old".into());
+            let mut after = BlockSnapshot::thinking(BlockId::new(c, m, 2), None, "new");
+            after.signature = Some("sig_new".into());
+            let blocks = vec![
+                BlockSnapshot::text(BlockId::new(c, u, 0), None, BlockRole::User, "hi"),
+                before,
+                BlockSnapshot::text(BlockId::new(c, m, 1), None, BlockRole::Model, "hello"),
+                BlockSnapshot::notification_block(
+                    BlockId::new(c, s, 0),
+                    None,
+                    notif_payload("kernel", kaijutsu_types::NotificationKind::ModelChanged),
+                    "[kernel] model changed: a/x → b/y",
+                ),
+                after,
+                BlockSnapshot::text(BlockId::new(c, m, 3), None, BlockRole::Model, "again"),
+            ];
+            let msgs = hydrate_from_blocks(&blocks);
+            assert_eq!(msgs.len(), 4);
+            let has_reasoning = |msg: &Message| match &msg.content {
+                MessageContent::Blocks(blocks) => blocks
+                    .iter()
+                    .any(|b| matches!(b, ContentBlock::Reasoning { .. })),
+                MessageContent::Text(_) => false,
+            };
+            assert!(!has_reasoning(&msgs[1]), "pre-switch reasoning is stripped");
+            assert!(has_reasoning(&msgs[3]), "post-switch reasoning is kept");
+        }
+
         // ── (Tool, Text) content-typed blocks (svg_block / abc_block, A1) ──
 
         #[test]
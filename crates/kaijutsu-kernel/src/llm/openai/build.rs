@@ -23,8 +23,9 @@ use crate::llm::stream::BuildOpts;
 use crate::llm::{ContentBlock, Message, MessageContent, Role};
 
 use super::types::{
-    ChatRequest, ContentPart, ImageUrl, MessageContent as WireContent, RequestFunctionCall,
-    RequestMessage, RequestTool, RequestToolCall, StreamOptions, ToolFunction,
+    ChatRequest, ContentPart, ImageUrl, MessageContent as WireContent, NamedFunction,
+    NamedToolChoice, RequestFunctionCall, RequestMessage, RequestTool, RequestToolCall,
+    StreamOptions, ToolChoice, ToolChoiceMode, ToolFunction,
 };
 
 /// Build a `/chat/completions` request body.
@@ -76,8 +77,23 @@ pub fn build_request(
         stream: streaming.then_some(true),
         stream_options: streaming.then_some(StreamOptions { include_usage: true }),
         tools,
-        tool_choice: None, // server-side default `auto`
+        // None leaves the server-side default `auto`.
+        tool_choice: opts.tool_choice.as_ref().map(build_tool_choice),
         temperature: opts.temperature,
+        top_p: opts.top_p,
+    }
+}
+
+/// `any` is OpenAI's `required`; a named tool is the function-object form.
+fn build_tool_choice(choice: &kaijutsu_types::ToolChoice) -> ToolChoice {
+    match choice {
+        kaijutsu_types::ToolChoice::Auto => ToolChoice::Mode(ToolChoiceMode::Auto),
+        kaijutsu_types::ToolChoice::Any => ToolChoice::Mode(ToolChoiceMode::Required),
+        kaijutsu_types::ToolChoice::None => ToolChoice::Mode(ToolChoiceMode::None),
+        kaijutsu_types::ToolChoice::Tool(name) => ToolChoice::Function(NamedToolChoice {
+            kind: "function",
+            function: NamedFunction { name: name.clone() },
+        }),
     }
 }
 
@@ -556,4 +572,30 @@ mod tests {
             "genuine reasoning must survive even in generic mode"
         );
     }
+
+    #[test]
+    fn params_map_onto_wire_fields() {
+        let o = opts().with_params(&kaijutsu_types::LlmParams {
+            temperature: Some(0.5),
+            top_p: Some(0.8),
+            tool_choice: Some(kaijutsu_types::ToolChoice::Any),
+            ..Default::default()
+        });
+        let req = build_request(&o, &[Message::user("hi")], false, true);
+        let v = serde_json::to_value(&req).unwrap();
+        assert_eq!(v["temperature"], 0.5);
+        assert_eq!(v["top_p"], 0.8);
+        assert_eq!(v["tool_choice"], "required");
+
+        let o = opts().with_params(&kaijutsu_types::LlmParams {
+            tool_choice: Some(kaijutsu_types::ToolChoice::Tool("ls".into())),
+            ..Default::default()
+        });
+        let req = build_request(&o, &[Message::user("hi")], false, true);
+        let v = serde_json::to_value(&req).unwrap();
+        assert_eq!(
+            v["tool_choice"],
+            serde_json::json!({"type": "function", "function": {"name": "ls"}})
+        );
+    }
 }
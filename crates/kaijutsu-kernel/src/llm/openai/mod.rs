@@ -33,7 +33,7 @@ use crate::llm::{LlmError, LlmResult, Message};
 
 use self::sse::{OpenAiSseEvent, decode_event};
 use self::stream::StateMachine;
-use self::types::{ApiError, ChatResponse};
+use self::types::{ApiError, ChatResponse, ModelList};
 
 /// Fallback endpoint when no `base_url` is configured — OpenAI's own API.
 /// Local providers (lemonade, Ollama) always set `base_url` in config.
@@ -155,6 +155,32 @@ impl Client {
         Ok(text)
     }
 
+    /// Check the key, and that `model` is served when given, against `GET
+    /// /models` — which OpenAI, DeepSeek, Ollama and lemonade all answer.
+    pub async fn check_model(&self, model: Option<&str>) -> LlmResult<()> {
+        let response = self
+            .auth(self.http.get(format!("{}/models", self.base_url)))
+            .send()
+            .await
+            .map_err(http_error)?;
+        let response = self.error_for_status(response).await?;
+        let Some(model) = model else {
+            return Ok(());
+        };
+        let listed: ModelList = response
+            .json()
+            .await
+            .map_err(|e| LlmError::ApiError(format!("model list JSON parse: {e}")))?;
+        if listed.data.iter().any(|m| m.id == model) {
+            Ok(())
+        } else {
+            Err(LlmError::InvalidRequest(format!(
+                "model '{model}' is not served by {}",
+                self.provider_name
+            )))
+        }
+    }
+
     /// Start a streaming completion. POSTs `stream: true` and wraps the
     /// `text/event-stream` response in a [`Stream`].
     pub async fn stream(&self, opts: BuildOpts, messages: Vec<Message>) -> LlmResult<Stream> {
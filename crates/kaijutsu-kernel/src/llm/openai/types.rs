@@ -61,6 +61,9 @@ pub struct ChatRequest {
     /// doesn't error on it.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
 }
 
 impl ChatRequest {
@@ -227,15 +230,35 @@ pub struct ToolFunction {
 }
 
 /// `tool_choice` — defaults to `auto` server-side; only set to override.
+/// Either a bare mode string or a named function object.
 #[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "snake_case")]
-#[allow(dead_code)] // variants reserved for forced-tool paths
+#[serde(untagged)]
 pub enum ToolChoice {
+    Mode(ToolChoiceMode),
+    Function(NamedToolChoice),
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoiceMode {
     Auto,
     None,
     Required,
 }
 
+/// `{"type": "function", "function": {"name": …}}` — force one tool.
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedToolChoice {
+    #[serde(rename = "type")]
+    pub kind: &'static str, // always "function"
+    pub function: NamedFunction,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedFunction {
+    pub name: String,
+}
+
 // ============================================================================
 // Streaming response types (text/event-stream chunks)
 // ============================================================================
@@ -378,6 +401,18 @@ pub struct ApiErrorBody {
     pub kind: Option<String>,
 }
 
+/// `GET /models` response: `{"data": [{"id": ...}, ...]}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelList {
+    #[serde(default)]
+    pub data: Vec<ListedModel>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListedModel {
+    pub id: String,
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -397,6 +432,7 @@ mod tests {
             tools: vec![],
             tool_choice: None,
             temperature: None,
+            top_p: None,
         };
         let v = serde_json::to_value(&req).unwrap();
         assert_eq!(v["model"], "deepseek-v4-flash");
@@ -405,6 +441,7 @@ mod tests {
         assert!(v.get("tools").is_none(), "empty tools must skip");
         assert!(v.get("tool_choice").is_none());
         assert!(v.get("temperature").is_none());
+        assert!(v.get("top_p").is_none());
         // messages[0] is a bare user text message
         assert_eq!(v["messages"][0]["role"], "user");
         assert_eq!(v["messages"][0]["content"], "hi");
@@ -425,6 +462,7 @@ mod tests {
             tools: vec![],
             tool_choice: None,
             temperature: None,
+            top_p: None,
         };
         let v = serde_json::to_value(&req).unwrap();
         assert_eq!(v["stream"], true);
@@ -19,6 +19,7 @@
 //!          └──────────────────────────────────┘
 //! ```
 
+use kaijutsu_types::{LlmParams, ToolChoice};
 use serde::{Deserialize, Serialize};
 
 use super::ToolDefinition;
@@ -142,6 +143,9 @@ pub struct BuildOpts {
     pub system: Option<String>,
     pub max_tokens: u64,
     pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    /// `None` leaves the provider default (auto when tools are offered).
+    pub tool_choice: Option<ToolChoice>,
     pub tools: Vec<ToolDefinition>,
     /// Cache breakpoint policy for Claude prompt caching. Empty = no
     /// `cache_control` applied. See [`CacheTarget`].
@@ -155,6 +159,8 @@ impl BuildOpts {
             system: None,
             max_tokens: 64_000,
             temperature: None,
+            top_p: None,
+            tool_choice: None,
             tools: Vec::new(),
             cache_breakpoints: Vec::new(),
         }
@@ -175,6 +181,18 @@ impl BuildOpts {
         self
     }
 
+    /// Apply a context's (or request's) generation parameters. Unset fields
+    /// keep what the builder already has.
+    pub fn with_params(mut self, params: &LlmParams) -> Self {
+        if let Some(max_tokens) = params.max_tokens {
+            self.max_tokens = max_tokens;
+        }
+        self.temperature = params.temperature.or(self.temperature);
+        self.top_p = params.top_p.or(self.top_p);
+        self.tool_choice = params.tool_choice.clone().or(self.tool_choice);
+        self
+    }
+
     pub fn with_tools(mut self, tools: Vec<ToolDefinition>) -> Self {
         self.tools = tools;
         self
@@ -380,6 +398,30 @@ mod tests {
         assert!(opts.tools.is_empty());
     }
 
+    #[test]
+    fn build_opts_with_params_keeps_unset_fields() {
+        let opts = BuildOpts::new("claude-haiku-4-5")
+            .with_max_tokens(2048)
+            .with_temperature(0.7)
+            .with_params(&LlmParams {
+                top_p: Some(0.9),
+                tool_choice: Some(ToolChoice::None),
+                ..Default::default()
+            });
+        assert_eq!(opts.max_tokens, 2048);
+        assert_eq!(opts.temperature, Some(0.7));
+        assert_eq!(opts.top_p, Some(0.9));
+        assert_eq!(opts.tool_choice, Some(ToolChoice::None));
+
+        let opts = opts.with_params(&LlmParams {
+            max_tokens: Some(512),
+            temperature: Some(0.0),
+            ..Default::default()
+        });
+        assert_eq!(opts.max_tokens, 512);
+        assert_eq!(opts.temperature, Some(0.0));
+    }
+
     #[test]
     fn finish_reason_as_str() {
         assert_eq!(FinishReason::EndTurn.as_str(), "end_turn");
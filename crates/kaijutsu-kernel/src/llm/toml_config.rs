@@ -28,7 +28,7 @@ pub struct LlmConfig {
 }
 
 /// A model alias maps a short name to a specific provider and model.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModelAlias {
     pub provider: String,
     pub model: String,
@@ -17,7 +17,9 @@ use std::sync::{Arc, Weak};
 
 use std::collections::HashSet;
 
-use kaijutsu_types::{BlockId, ContextId, NotificationPayload, ResourcePayload};
+use kaijutsu_types::{
+    BlockId, ConsentMode, ConsentVerdict, ContextId, NotificationPayload, ResourcePayload,
+};
 use tokio::sync::{Mutex, RwLock, Semaphore, broadcast};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
@@ -30,11 +32,16 @@ use super::hook_table::{HookAction, HookBody, HookEntry, McpHookPhase, HookTable
 use super::hooks_builtin::BuiltinHookRegistry;
 use super::policy::InstancePolicy;
 use super::server_like::{McpServerLike, ServerNotification};
+use super::tool_trace::ToolTrace;
 use super::types::{
-    InstanceId, KernelCallParams, KernelNotification, KernelReadResource, KernelResourceContents,
-    KernelResourceList, KernelTool, KernelToolResult, LogLevel, NotifKind, ToolContent,
+    InstanceId, InstanceScope, KernelCallParams, KernelNotification, KernelReadResource,
+    KernelResourceContents, KernelResourceList, KernelTool, KernelToolResult, LogLevel,
+    McpForkMode, NotifKind, ToolContent,
 };
+use crate::analytics::UsageAnalytics;
 use crate::block_store::{DbHandle, SharedBlockStore};
+use crate::budget::BudgetTracker;
+use crate::tool_presets::{ToolPresets, annotate_schema};
 
 /// Coerce a candidate visible tool name into the alphabet Anthropic accepts
 /// for `tools[].custom.name`: `^[a-zA-Z0-9_-]{1,128}$`. Replaces any other
@@ -169,6 +176,10 @@ pub struct Broker {
     /// `set_kj_dispatcher` at bootstrap; `None` → hook scripts run
     /// without `kj` in their tool registry.
     kj_dispatcher: RwLock<Option<Weak<crate::kj::KjDispatcher>>>,
+    /// Context-scoped instances (`register_scoped`): visible only in the
+    /// listed contexts, even to a context whose binding grants `*`. Instances
+    /// absent from this map are kernel-wide.
+    instance_scopes: RwLock<HashMap<InstanceId, InstanceScope>>,
     /// Deny-by-default switch for *unbound* contexts at `call_tool`. A real
     /// kernel engages this in `Kernel::new` (`engage_unbound_deny`), so a
     /// context with no binding is refused. Bare `Broker::new()` unit tests —
@@ -177,6 +188,19 @@ pub struct Broker {
     /// *has* a binding is always enforced against it (an empty binding denies),
     /// regardless of this flag.
     enforce_unbound_deny: std::sync::atomic::AtomicBool,
+    /// Record/replay trace for server calls (`set_tool_trace`). `None` →
+    /// every call goes to its server, unrecorded.
+    tool_trace: RwLock<Option<Arc<ToolTrace>>>,
+    /// Per-context execution budgets and the kernel-wide halt. Every
+    /// `call_tool` is admitted through it; the shell paths charge their wall
+    /// time to it (`budgets()`).
+    budgets: Arc<BudgetTracker>,
+    /// Opt-in tool-call counts and latencies (`analytics()`); off until
+    /// `analytics.toml` turns it on.
+    analytics: Arc<UsageAnalytics>,
+    /// Per-context default arguments (`tool_presets()`), filled into every
+    /// `call_tool` before its PreCall hooks run.
+    tool_presets: Arc<ToolPresets>,
 }
 
 impl Default for Broker {
@@ -212,7 +236,12 @@ impl Broker {
             db: RwLock::new(None),
             kernel: RwLock::new(None),
             kj_dispatcher: RwLock::new(None),
+            instance_scopes: RwLock::new(HashMap::new()),
             enforce_unbound_deny: std::sync::atomic::AtomicBool::new(false),
+            tool_trace: RwLock::new(None),
+            budgets: Arc::new(BudgetTracker::new()),
+            analytics: Arc::new(UsageAnalytics::new()),
+            tool_presets: Arc::new(ToolPresets::new()),
         }
     }
 
@@ -234,6 +263,21 @@ impl Broker {
             .store(false, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// Install (or with `None`, remove) a record/replay trace for server
+    /// calls. While one is installed in replay mode, no server is called:
+    /// results come from the trace. See [`super::tool_trace`].
+    pub async fn set_tool_trace(&self, trace: Option<Arc<ToolTrace>>) {
+        if let Some(t) = &trace {
+            tracing::info!(path = %t.path().display(), mode = ?t.mode(), "tool trace installed");
+        }
+        *self.tool_trace.write().await = trace;
+    }
+
+    /// The installed record/replay trace, if any.
+    pub async fn tool_trace(&self) -> Option<Arc<ToolTrace>> {
+        self.tool_trace.read().await.clone()
+    }
+
     /// Wire a `Weak<Kernel>` so `HookBody::Kaish` evaluation can construct
     /// an `EmbeddedKaish`. Called at kernel bootstrap. Stored as `Weak`
     /// to avoid the `Kernel`/`Broker` cycle (Kernel holds `Arc<Broker>`).
@@ -272,9 +316,27 @@ impl Broker {
     /// path for bad rows.
     pub async fn set_db(self: &Arc<Self>, db: DbHandle) {
         *self.db.write().await = Some(db.clone());
+        self.budgets.set_db(db.clone());
+        self.tool_presets.set_db(db.clone());
         self.hydrate_hooks_from_db(&db).await;
     }
 
+    /// The kernel's execution budget tracker and kill switch.
+    pub fn budgets(&self) -> &Arc<BudgetTracker> {
+        &self.budgets
+    }
+
+    /// The kernel's opt-in usage aggregator. Every call that reaches its
+    /// server is recorded here.
+    pub fn analytics(&self) -> &Arc<UsageAnalytics> {
+        &self.analytics
+    }
+
+    /// The kernel's per-context tool parameter presets.
+    pub fn tool_presets(&self) -> &Arc<ToolPresets> {
+        &self.tool_presets
+    }
+
     /// Load every persisted hook row and reconstruct `HookTables` in
     /// place. Called from `set_db` at bootstrap. Rows whose action
     /// shape can't be reified (unknown builtin name, invalid enum
@@ -576,7 +638,9 @@ impl Broker {
         let handle = tokio::spawn(async move {
             pump_loop(broker, id_for_pump, rx).await;
         });
-        self.pump_handles.lock().await.insert(id.clone(), handle);
+        if let Some(replaced) = self.pump_handles.lock().await.insert(id.clone(), handle) {
+            replaced.abort();
+        }
 
         // Phase 5 (D-55): publish a kernel-level ToolsChanged so
         // `builtin.bindings`'s bridge task can turn this into a
@@ -669,6 +733,7 @@ impl Broker {
         // `self.subscriptions` AFTER our teardown swept the table, leaving
         // a stale row pointing at a removed instance.
         let server_arc = self.instances.write().await.remove(id);
+        self.instance_scopes.write().await.remove(id);
         self.policies.write().await.remove(id);
         self.semaphores.write().await.remove(id);
         self.teardown_subscriptions_for_instance(id, server_arc.as_ref())
@@ -705,6 +770,136 @@ impl Broker {
         Ok(())
     }
 
+    /// Register `server` as visible only in `context_id` and bind it there.
+    /// Forks of that context pick it up per `fork_mode`
+    /// ([`Broker::inherit_instance_scopes`]). Re-registering an id already
+    /// scoped to `context_id` replaces the server; any other existing
+    /// registration under the id is an [`McpError::InstanceConflict`] — a
+    /// context must not be able to shadow a kernel-wide instance.
+    pub async fn register_scoped(
+        self: &Arc<Self>,
+        server: Arc<dyn McpServerLike>,
+        policy: InstancePolicy,
+        context_id: ContextId,
+        fork_mode: McpForkMode,
+    ) -> McpResult<()> {
+        let id = server.instance_id().clone();
+        // Check and claim under both write locks (instances first, the order
+        // `list_visible_tools` reads them in), so two registrations racing
+        // for one id can't both pass the check.
+        let replaced = {
+            let mut instances = self.instances.write().await;
+            let mut scopes = self.instance_scopes.write().await;
+            let owned_here = scopes
+                .get(&id)
+                .is_some_and(|s| s.contexts.len() == 1 && s.contexts.contains(&context_id));
+            if !owned_here && instances.contains_key(&id) {
+                return Err(McpError::InstanceConflict {
+                    instance: id,
+                    context: context_id,
+                });
+            }
+            scopes.insert(
+                id.clone(),
+                InstanceScope {
+                    contexts: HashSet::from([context_id]),
+                    fork_mode,
+                },
+            );
+            instances.insert(id.clone(), server.clone())
+        };
+        self.register(server.clone(), policy).await?;
+        self.bind(context_id, id.clone()).await;
+        // Re-registering replaces the context's server; the old process
+        // would otherwise run on with nothing left to stop it.
+        if let Some(old) = replaced
+            && !Arc::ptr_eq(&old, &server)
+            && let Err(e) = old.shutdown().await
+        {
+            tracing::debug!(instance = %id, error = ?e, "replaced scoped instance shutdown failed");
+        }
+        Ok(())
+    }
+
+    /// Drop a scoped instance from `context_id`. The instance is unregistered
+    /// and shut down once no context holds it; returns whether that happened.
+    /// Kernel-wide instances and instances scoped elsewhere are refused.
+    pub async fn release_scoped(
+        self: &Arc<Self>,
+        context_id: ContextId,
+        id: &InstanceId,
+    ) -> McpResult<bool> {
+        let now_unused = {
+            let mut scopes = self.instance_scopes.write().await;
+            let scope = scopes
+                .get_mut(id)
+                .filter(|s| s.contexts.contains(&context_id))
+                .ok_or_else(|| McpError::InstanceNotFound(id.clone()))?;
+            scope.contexts.remove(&context_id);
+            scope.contexts.is_empty()
+        };
+        self.unbind(context_id, id).await;
+        if !now_unused {
+            return Ok(false);
+        }
+        let server = self.instances.read().await.get(id).cloned();
+        self.unregister(id).await?;
+        if let Some(server) = server
+            && let Err(e) = server.shutdown().await
+        {
+            tracing::debug!(instance = %id, error = ?e, "scoped instance shutdown failed");
+        }
+        Ok(true)
+    }
+
+    /// Scoped instances visible in `context_id`, with their scope.
+    pub async fn scoped_instances(
+        &self,
+        context_id: ContextId,
+    ) -> Vec<(InstanceId, InstanceScope)> {
+        let mut out: Vec<_> = self
+            .instance_scopes
+            .read()
+            .await
+            .iter()
+            .filter(|(_, s)| s.contexts.contains(&context_id))
+            .map(|(id, s)| (id.clone(), s.clone()))
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+
+    /// Extend every `McpForkMode::Inherit` instance scoped to `source` to its
+    /// fork `forked`, and bind it there. `Exclude` instances stay behind.
+    pub async fn inherit_instance_scopes(&self, source: ContextId, forked: ContextId) {
+        let inherited: Vec<InstanceId> = {
+            let mut scopes = self.instance_scopes.write().await;
+            scopes
+                .iter_mut()
+                .filter(|(_, s)| {
+                    s.fork_mode == McpForkMode::Inherit && s.contexts.contains(&source)
+                })
+                .map(|(id, s)| {
+                    s.contexts.insert(forked);
+                    id.clone()
+                })
+                .collect()
+        };
+        for id in inherited {
+            self.bind(forked, id).await;
+        }
+    }
+
+    /// Whether `id` may be seen from `context_id`: kernel-wide instances
+    /// always, scoped ones only from their contexts.
+    async fn instance_visible_in(&self, id: &InstanceId, context_id: ContextId) -> bool {
+        self.instance_scopes
+            .read()
+            .await
+            .get(id)
+            .is_none_or(|s| s.contexts.contains(&context_id))
+    }
+
     pub async fn list_instances(&self) -> Vec<InstanceId> {
         self.instances
             .read()
@@ -1133,14 +1328,27 @@ impl Broker {
         // single `instance:tool` still has that instance's server queried.
         // `all_instances` ("*") means every registered instance, which
         // `candidate_instances()` cannot enumerate, so query the full registry.
+        // Scoped instances (`register_scoped`) are invisible outside their
+        // contexts, even under `*`.
         let servers: Vec<Arc<dyn McpServerLike>> = {
             let guard = self.instances.read().await;
+            let scopes = self.instance_scopes.read().await;
+            let in_scope = |id: &InstanceId| {
+                scopes
+                    .get(id)
+                    .is_none_or(|s| s.contexts.contains(&context_id))
+            };
             if binding.all_instances {
-                guard.values().cloned().collect()
+                guard
+                    .iter()
+                    .filter(|(id, _)| in_scope(id))
+                    .map(|(_, s)| s.clone())
+                    .collect()
             } else {
                 binding
                     .candidate_instances()
                     .iter()
+                    .filter(|id| in_scope(id))
                     .filter_map(|id| guard.get(id).cloned())
                     .collect()
             }
@@ -1196,9 +1404,17 @@ impl Broker {
             .await
             .insert(context_id, binding.clone());
 
-        // Build the visible-name → KernelTool map.
+        // Build the visible-name → KernelTool map. Context presets show up
+        // as schema defaults: an omitted argument becomes the preset value.
+        let presets = self.tool_presets.list(context_id);
         let mut out: Vec<(String, KernelTool)> = Vec::new();
-        for kt in all {
+        for mut kt in all {
+            if let Some(preset) = presets
+                .iter()
+                .find(|p| p.instance == kt.instance && p.tool == kt.name)
+            {
+                annotate_schema(&mut kt.input_schema, &preset.params);
+            }
             let key = (kt.instance.clone(), kt.name.clone());
             if let Some((visible_name, _)) = binding
                 .name_map
@@ -1211,6 +1427,38 @@ impl Broker {
         Ok(out)
     }
 
+    /// Append this call's gate decision to the consent log (`consent_log`).
+    /// A no-op when no DB is wired (bare-broker tests).
+    async fn audit_consent(
+        &self,
+        params: &KernelCallParams,
+        ctx: &CallContext,
+        verdict: ConsentVerdict,
+        basis: String,
+    ) {
+        let Some(db) = self.db.read().await.clone() else {
+            return;
+        };
+        let kernel = self.kernel.read().await.as_ref().and_then(Weak::upgrade);
+        let mode = match kernel {
+            Some(kernel) => kernel.consent_mode().await,
+            None => ConsentMode::default(),
+        };
+        let arguments = serde_json::to_vec(&params.arguments).unwrap_or_default();
+        crate::consent_log::record(
+            &db,
+            crate::consent_log::ConsentDraft {
+                principal_id: ctx.principal_id,
+                context_id: Some(ctx.context_id),
+                action: format!("mcp {}/{}", params.instance, params.tool),
+                params_hash: crate::consent_log::params_hash(&arguments),
+                verdict,
+                basis,
+                mode,
+            },
+        );
+    }
+
     /// The one tool-call pipeline. Phase 4 wires hook evaluation at three
     /// pinch points: `PreCall` before the server call, `PostCall` on success,
     /// `OnError` on failure. ShortCircuit in any phase bypasses the server
@@ -1251,7 +1499,7 @@ impl Broker {
     )]
     async fn call_tool_inner(
         &self,
-        params: KernelCallParams,
+        mut params: KernelCallParams,
         ctx: &CallContext,
         cancel: CancellationToken,
     ) -> McpResult<KernelToolResult> {
@@ -1280,13 +1528,32 @@ impl Broker {
                 .enforce_unbound_deny
                 .load(std::sync::atomic::Ordering::Relaxed),
         };
+        // A scoped instance is refused outside its contexts whatever the
+        // binding says — `*` must not reach another context's private server.
+        let allowed = allowed
+            && self
+                .instance_visible_in(&params.instance, ctx.context_id)
+                .await;
         if !allowed {
+            self.audit_consent(&params, ctx, ConsentVerdict::Denied, "capability".into())
+                .await;
             return Err(McpError::CapabilityDenied {
                 instance: params.instance.clone(),
                 tool: params.tool.clone(),
             });
         }
 
+        // Execution budget and kill switch. Counted only once the capability
+        // gate passes, so refused calls don't spend the budget.
+        if let Err(e) = self
+            .budgets
+            .admit_tool_call(ctx.context_id, std::time::Instant::now())
+        {
+            self.audit_consent(&params, ctx, ConsentVerdict::Denied, "budget".into())
+                .await;
+            return Err(McpError::Policy(e));
+        }
+
         let policy = self
             .policies
             .read()
@@ -1314,14 +1581,31 @@ impl Broker {
             None => None,
         };
 
+        // Context presets fill whatever the caller left out, so hooks, the
+        // consent log and the server all see the arguments the call runs with.
+        let filled = self.tool_presets.apply(ctx.context_id, &mut params);
+        if !filled.is_empty() {
+            tracing::debug!(?filled, "tool preset filled omitted arguments");
+        }
+
         // PreCall — may short-circuit the call entirely, or deny it outright.
         match self
             .evaluate_phase(McpHookPhase::PreCall, &params, ctx, PhasePayload::None)
             .await?
         {
-            PhaseOutcome::Continue => {}
+            PhaseOutcome::Continue => {
+                self.audit_consent(&params, ctx, ConsentVerdict::Approved, "binding".into())
+                    .await;
+            }
             PhaseOutcome::ShortCircuit { hook_id, result } => {
                 emit_short_circuit_attribution(McpHookPhase::PreCall, &hook_id);
+                self.audit_consent(
+                    &params,
+                    ctx,
+                    ConsentVerdict::Approved,
+                    format!("hook:{hook_id}"),
+                )
+                .await;
                 // PostCall still runs on short-circuit per §4.3 evaluation law
                 // — it observes that a (synthetic) result was produced.
                 // Result can itself short-circuit or deny.
@@ -1347,6 +1631,13 @@ impl Broker {
             }
             PhaseOutcome::Deny { hook_id, reason } => {
                 emit_deny_attribution(McpHookPhase::PreCall, &hook_id, &reason);
+                self.audit_consent(
+                    &params,
+                    ctx,
+                    ConsentVerdict::Denied,
+                    format!("hook:{hook_id}"),
+                )
+                .await;
                 return Err(McpError::Denied { by_hook: hook_id });
             }
         }
@@ -1355,6 +1646,7 @@ impl Broker {
         let timeout_ms = policy.call_timeout.as_millis() as u64;
         let call_params_for_hooks = params.clone();
         let cancel_for_call = cancel.clone();
+        let trace = self.tool_trace.read().await.clone();
         let call_fut = async {
             let span = tracing::info_span!(
                 "server.call_tool",
@@ -1362,13 +1654,24 @@ impl Broker {
                 tool = %params.tool,
             );
             let _enter = span.enter();
-            server.call_tool(params, ctx, cancel_for_call).await
+            // Replay serves the recorded outcome in place of the server;
+            // record writes down what the server returned.
+            if let Some(replayed) = trace.as_ref().and_then(|t| t.take(&params)) {
+                return replayed;
+            }
+            let recorded_params = trace.as_ref().map(|_| params.clone());
+            let result = server.call_tool(params, ctx, cancel_for_call).await;
+            if let (Some(t), Some(p)) = (&trace, &recorded_params) {
+                t.write(p, ctx, &result);
+            }
+            result
         };
 
         // Race the call against (a) the per-instance timeout and (b) an
         // externally-supplied cancellation (M2-B5). Without (b) a hard
         // interrupt would wait the full call_timeout for builtin servers
         // that don't observe the token themselves.
+        let started = std::time::Instant::now();
         let call_result: Result<McpResult<KernelToolResult>, McpError> = tokio::select! {
             biased;
             _ = cancel.cancelled() => Err(McpError::Cancelled),
@@ -1379,6 +1682,12 @@ impl Broker {
                 }))
             }
         };
+        self.analytics.record(
+            call_params_for_hooks.instance.as_str(),
+            &call_params_for_hooks.tool,
+            started.elapsed(),
+            matches!(&call_result, Ok(Ok(r)) if !r.is_error),
+        );
 
         match call_result {
             Ok(Ok(result)) => {
@@ -2186,6 +2495,15 @@ fn error_to_hook_json(e: &McpError) -> String {
             "BindingUnavailable",
             serde_json::json!({"context": context.to_string(), "reason": reason}),
         ),
+        McpError::InstanceConflict { instance, context } => (
+            "InstanceConflict",
+            serde_json::json!({"instance": instance.as_str(), "context": context.to_string()}),
+        ),
+        McpError::ReplayMiss { instance, tool } => (
+            "ReplayMiss",
+            serde_json::json!({"instance": instance.as_str(), "tool": tool}),
+        ),
+        McpError::Replayed(_) => ("Replayed", serde_json::Value::Null),
         McpError::HookRecursionLimit { depth } => (
             "HookRecursionLimit",
             serde_json::json!({"depth": depth}),
@@ -2208,6 +2526,11 @@ fn error_to_hook_json(e: &McpError) -> String {
                     "max": max,
                 }),
             ),
+            PolicyError::BudgetExhausted { context, limit } => (
+                "BudgetExhausted",
+                serde_json::json!({"context": context.to_string(), "limit": limit}),
+            ),
+            PolicyError::Halted { reason } => ("Halted", serde_json::json!({"reason": reason})),
         },
     };
     serde_json::json!({
@@ -2860,6 +3183,8 @@ mod tests {
                 + Sync,
         >,
         notif_tx: broadcast::Sender<ServerNotification>,
+        /// `shutdown` calls seen.
+        shutdowns: std::sync::atomic::AtomicUsize,
     }
 
     impl MockServer {
@@ -2870,6 +3195,7 @@ mod tests {
                 tools: std::sync::Mutex::new(Vec::new()),
                 on_call: Arc::new(|_p| Box::pin(async { Ok(KernelToolResult::text("ok")) })),
                 notif_tx,
+                shutdowns: std::sync::atomic::AtomicUsize::new(0),
             }
         }
 
@@ -2933,6 +3259,12 @@ mod tests {
         fn notifications(&self) -> broadcast::Receiver<ServerNotification> {
             self.notif_tx.subscribe()
         }
+
+        async fn shutdown(&self) -> McpResult<()> {
+            self.shutdowns
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
     }
 
     fn params(instance: &str, tool: &str) -> KernelCallParams {
@@ -3022,6 +3354,115 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn tool_trace_records_then_replays_without_calling_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.jsonl");
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let ctx = CallContext::test();
+
+        let echo = |calls: Arc<std::sync::atomic::AtomicUsize>| {
+            MockServer::new("echo").with_tool("say").on_call(move |p| {
+                let calls = calls.clone();
+                async move {
+                    let n = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Ok(KernelToolResult::text(format!("{}#{n}", p.arguments["word"])))
+                }
+            })
+        };
+        let broker = Arc::new(Broker::new());
+        broker
+            .register(Arc::new(echo(calls.clone())), InstancePolicy::default())
+            .await
+            .unwrap();
+        broker
+            .set_tool_trace(Some(Arc::new(ToolTrace::record(&path).unwrap())))
+            .await;
+        let mut say = params("echo", "say");
+        say.arguments = json!({ "word": "hi" });
+        for _ in 0..2 {
+            broker
+                .call_tool(say.clone(), &ctx, CancellationToken::new())
+                .await
+                .unwrap();
+        }
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        broker.set_tool_trace(None).await;
+
+        // Replay on a fresh broker: same answers, server never called.
+        let replay = Arc::new(Broker::new());
+        replay
+            .register(Arc::new(echo(calls.clone())), InstancePolicy::default())
+            .await
+            .unwrap();
+        replay
+            .set_tool_trace(Some(Arc::new(ToolTrace::replay(&path).unwrap())))
+            .await;
+        for expected in ["\"hi\"#0", "\"hi\"#1"] {
+            let result = replay
+                .call_tool(say.clone(), &ctx, CancellationToken::new())
+                .await
+                .unwrap();
+            assert!(
+                matches!(result.content.as_slice(), [ToolContent::Text(t)] if t == expected),
+                "{result:?}"
+            );
+        }
+        let err = replay
+            .call_tool(say, &ctx, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, McpError::ReplayMiss { .. }), "{err:?}");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn tool_presets_fill_only_omitted_arguments() {
+        let ctx = CallContext::test();
+        let server = MockServer::new("echo")
+            .with_tool("say")
+            .on_call(|p| async move {
+                Ok(KernelToolResult::text(format!(
+                    "{}/{}",
+                    p.arguments["word"], p.arguments["loud"]
+                )))
+            });
+        let broker = Arc::new(Broker::new());
+        broker
+            .register(Arc::new(server), InstancePolicy::default())
+            .await
+            .unwrap();
+        broker
+            .tool_presets()
+            .set(
+                ctx.context_id,
+                crate::tool_presets::ToolPreset {
+                    instance: InstanceId::new("echo"),
+                    tool: "say".into(),
+                    params: json!({ "word": "hi", "loud": true })
+                        .as_object()
+                        .unwrap()
+                        .clone(),
+                    updated_by: ctx.principal_id,
+                    updated_at: 0,
+                },
+            )
+            .unwrap();
+
+        let mut say = params("echo", "say");
+        say.arguments = json!({ "word": "yo" });
+        for (call, expected) in [(say, "\"yo\"/true"), (params("echo", "say"), "\"hi\"/true")] {
+            let result = broker
+                .call_tool(call, &ctx, CancellationToken::new())
+                .await
+                .unwrap();
+            assert!(
+                matches!(result.content.as_slice(), [ToolContent::Text(t)] if t == expected),
+                "{result:?}"
+            );
+        }
+    }
+
     #[tokio::test]
     async fn unregister_then_call_errors() {
         let broker = Arc::new(Broker::new());
@@ -3060,6 +3501,85 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn scoped_instance_is_private_to_its_contexts() {
+        let broker = Arc::new(Broker::new());
+        let owner = ContextId::new();
+        let other = ContextId::new();
+        let mut everything = ContextToolBinding::new();
+        everything.grant(crate::mcp::Capability::AllInstances);
+        broker.set_binding(other, everything).await;
+
+        let server = Arc::new(MockServer::new("playwright").with_tool("navigate"));
+        broker
+            .register_scoped(server, InstancePolicy::default(), owner, McpForkMode::Inherit)
+            .await
+            .unwrap();
+
+        let owner_ctx = CallContext::system_for_context(owner);
+        let visible = broker.list_visible_tools(owner, &owner_ctx).await.unwrap();
+        assert!(visible.iter().any(|(_, kt)| kt.name == "navigate"));
+
+        // `*` in another context does not reach the scoped instance.
+        let other_ctx = CallContext::system_for_context(other);
+        let visible = broker.list_visible_tools(other, &other_ctx).await.unwrap();
+        assert!(visible.is_empty(), "scoped tool leaked: {visible:?}");
+        let err = broker
+            .call_tool(params("playwright", "navigate"), &other_ctx, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, McpError::CapabilityDenied { .. }), "got {err:?}");
+
+        // A fork inherits it; releasing from the owner keeps it alive for
+        // the fork, releasing from the fork unregisters it.
+        let fork = ContextId::new();
+        broker.inherit_instance_scopes(owner, fork).await;
+        let id = InstanceId::new("playwright");
+        assert!(!broker.release_scoped(owner, &id).await.unwrap());
+        assert_eq!(broker.scoped_instances(fork).await.len(), 1);
+        assert!(broker.release_scoped(fork, &id).await.unwrap());
+        assert!(broker.list_instances().await.is_empty());
+
+        // Kernel-wide ids can't be shadowed from a context.
+        broker
+            .register(Arc::new(MockServer::new("shared")), InstancePolicy::default())
+            .await
+            .unwrap();
+        let err = broker
+            .register_scoped(
+                Arc::new(MockServer::new("shared")),
+                InstancePolicy::default(),
+                owner,
+                McpForkMode::Exclude,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, McpError::InstanceConflict { .. }), "got {err:?}");
+    }
+
+    #[tokio::test]
+    async fn rescoping_an_instance_shuts_the_old_server_down() {
+        let broker = Arc::new(Broker::new());
+        let owner = ContextId::new();
+        let first = Arc::new(MockServer::new("playwright").with_tool("navigate"));
+        let second = Arc::new(MockServer::new("playwright").with_tool("navigate"));
+        for server in [&first, &second] {
+            broker
+                .register_scoped(
+                    server.clone(),
+                    InstancePolicy::default(),
+                    owner,
+                    McpForkMode::Inherit,
+                )
+                .await
+                .unwrap();
+        }
+        let count = |s: &MockServer| s.shutdowns.load(std::sync::atomic::Ordering::SeqCst);
+        assert_eq!(count(&first), 1, "the replaced server is stopped");
+        assert_eq!(count(&second), 0);
+        assert_eq!(broker.list_instances().await.len(), 1);
+    }
+
     #[tokio::test]
     async fn policy_concurrency_cap_fires() {
         // locks broker.rs try_acquire_owned semantics — over-cap callers fail
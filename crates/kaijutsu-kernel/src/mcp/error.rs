@@ -37,6 +37,13 @@ pub enum PolicyError {
         size: usize,
         max: usize,
     },
+    /// The context spent one axis of its `ExecutionBudget` for the current
+    /// window (`crate::budget`).
+    #[error("context {context} exhausted its execution budget ({limit})")]
+    BudgetExhausted { context: ContextId, limit: String },
+    /// The kernel-wide tool halt is engaged.
+    #[error("tool execution is halted: {reason}")]
+    Halted { reason: String },
 }
 
 /// Coalescer-side errors (§5.3). Seat only in Phase 1 — no emitters yet.
@@ -105,6 +112,23 @@ pub enum McpError {
     #[error("could not load context {context}'s tool binding: {reason}")]
     BindingUnavailable { context: ContextId, reason: String },
 
+    /// A context tried to register a scoped instance under an id that is
+    /// already registered kernel-wide or scoped to other contexts.
+    #[error("instance {instance} is already registered outside context {context}")]
+    InstanceConflict {
+        instance: InstanceId,
+        context: ContextId,
+    },
+
+    /// Replay mode (`ToolTrace`) has no recorded result left for this call:
+    /// the run diverged from the one that was recorded.
+    #[error("no recorded result for tool `{tool}` on {instance} in the replay trace")]
+    ReplayMiss { instance: InstanceId, tool: String },
+
+    /// A server error served from a replay trace, as its recorded message.
+    #[error("{0}")]
+    Replayed(String),
+
     #[error("hook recursion depth exceeded ({depth})")]
     HookRecursionLimit { depth: u32 },
 
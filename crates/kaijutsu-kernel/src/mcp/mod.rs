@@ -16,6 +16,7 @@ pub mod hooks_builtin;
 pub mod policy;
 pub mod server_like;
 pub mod servers;
+pub mod tool_trace;
 pub mod types;
 
 pub use binding::{Capability, ContextToolBinding, ResolvedName};
@@ -29,8 +30,9 @@ pub use hook_table::{
 pub use hooks_builtin::{BuiltinHookRegistry, NoOpHook, TracingAuditHook};
 pub use policy::InstancePolicy;
 pub use server_like::{McpServerLike, ServerNotification};
+pub use tool_trace::{ToolTrace, ToolTraceEntry, ToolTraceMode};
 pub use types::{
-    ElicitationRequest, Health, InstanceId, KernelCallParams, KernelNotification,
+    ElicitationRequest, Health, InstanceId, InstanceScope, KernelCallParams, KernelNotification,
     KernelReadResource, KernelResource, KernelResourceContents, KernelResourceList, KernelTool,
-    KernelToolResult, LogLevel, NotifKind, ToolContent,
+    KernelToolResult, LogLevel, McpForkMode, NotifKind, ScopedInstanceStatus, ToolContent,
 };
@@ -11,17 +11,17 @@ use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
 
 use crate::block_store::SharedBlockStore;
-// The `*_char_*` twins, NOT the byte variants: `apply_op` feeds
+use crate::block_transaction::BlockTransaction;
+// `apply_op` takes CHAR offsets from the index, NOT byte offsets: it feeds
 // `edit_text_as`, and the CRDT text layer is char-indexed (byte offsets
 // corrupt multibyte content — the June file-tools bug class).
-use crate::block_tools::translate::{
-    content_with_line_numbers, extract_lines_with_numbers, line_count, line_range_to_char_range,
-    line_to_char_offset, validate_expected_text,
-};
+use crate::block_tools::LineIndex;
+use crate::block_tools::translate::{content_with_line_numbers, line_count};
 use kaijutsu_crdt::{BlockId, BlockKind, ContentType, Role, Status};
-use kaijutsu_types::ContextId;
+use kaijutsu_types::{BlockLock, ContextId};
 use kaijutsu_cas::ContentStore;
 use crate::execution::{ExecContext, ExecResult};
+use crate::translate::Translator;
 
 use super::super::context::CallContext;
 use super::super::error::{McpError, McpResult};
@@ -45,11 +45,20 @@ pub struct BlockCreateParams {
     /// Metadata (path, language, tool_name, etc.).
     #[serde(default)]
     pub metadata: Option<serde_json::Value>,
+    /// Source language of the content (rust, python, json, …), used for
+    /// syntax highlighting and Markdown export.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Human-readable anchor (`design-decision-3`), unique in the context;
+    /// accepted anywhere a block id is.
+    #[serde(default)]
+    pub label: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct BlockAppendParams {
-    /// Block ID to append to.
+    /// Block ID to append to: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
     pub block_id: String,
     /// Content to append.
     pub content: String,
@@ -57,10 +66,20 @@ pub struct BlockAppendParams {
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct BlockEditParams {
-    /// Block ID to edit.
+    /// Block ID to edit: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
     pub block_id: String,
     /// List of edit operations to apply atomically.
+    #[serde(default)]
     pub operations: Vec<EditOp>,
+    /// Set the block's source language (rust, python, json, …); an empty
+    /// string clears it.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Set the block's label (`design-decision-3`, unique in the context);
+    /// an empty string clears it.
+    #[serde(default)]
+    pub label: Option<String>,
 }
 
 /// Edit operation on a block.
@@ -84,7 +103,8 @@ pub enum EditOp {
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct BlockSpliceParams {
-    /// Block ID to edit.
+    /// Block ID to edit: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
     pub block_id: String,
     /// CHARACTER offset (not bytes — the CRDT text layer is char-indexed;
     /// the tool description has always said "character-based" but this
@@ -99,7 +119,8 @@ pub struct BlockSpliceParams {
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct BlockReadParams {
-    /// Block ID to read.
+    /// Block ID to read: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
     pub block_id: String,
     /// Include line numbers.
     #[serde(default = "default_true")]
@@ -107,6 +128,14 @@ pub struct BlockReadParams {
     /// [start_line, end_line] (0-indexed, exclusive end).
     #[serde(default)]
     pub range: Option<(u32, u32)>,
+    /// Return the stored lines, ignoring the block's reflow setting.
+    #[serde(default)]
+    pub raw: bool,
+    /// Read the block translated into this language (a BCP 47 tag such as
+    /// `ja` or `pt-BR`). The block itself is unchanged; a translation is
+    /// cached until the block is next edited.
+    #[serde(default)]
+    pub translate_to: Option<String>,
 }
 
 fn default_true() -> bool {
@@ -115,7 +144,8 @@ fn default_true() -> bool {
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct BlockSearchParams {
-    /// Block ID to search.
+    /// Block ID to search: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
     pub block_id: String,
     /// Regex or literal pattern.
     pub query: String,
@@ -156,12 +186,55 @@ fn default_depth() -> u32 {
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct BlockStatusParams {
-    /// Block ID to update.
+    /// Block ID to update: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
     pub block_id: String,
     /// New status.
     pub status: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BlockReflowParams {
+    /// Block ID to rewrap: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
+    pub block_id: String,
+    /// Wrap width in characters (20-1000). Defaults to the block's current
+    /// setting, else 80.
+    #[serde(default)]
+    pub width: Option<u16>,
+    /// Treat existing newlines as hard breaks and only split long lines.
+    /// Defaults to the block's current setting, else false.
+    #[serde(default)]
+    pub preserve_newlines: Option<bool>,
+    /// Clear the block's reflow setting without touching its text.
+    #[serde(default)]
+    pub clear: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BlockLockParams {
+    /// Block ID to lock: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
+    pub block_id: String,
+    /// Seconds until the lock expires (default 120, at most 3600). Lock the
+    /// block again to renew it.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    /// What you are doing, shown to whoever runs into the lock.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BlockUnlockParams {
+    /// Block ID to release: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
+    pub block_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BlockLocksParams {}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct KernelSearchParams {
     /// Regex pattern to search for.
@@ -230,6 +303,8 @@ pub struct BlockToolsServer {
     instance_id: InstanceId,
     documents: SharedBlockStore,
     cas: Arc<FileStore>,
+    /// Backs `block_read`'s `translate_to`; unset = translation refused.
+    translator: Option<Arc<Translator>>,
     notif_tx: broadcast::Sender<ServerNotification>,
 }
 
@@ -242,9 +317,16 @@ impl BlockToolsServer {
             instance_id: InstanceId::new(Self::INSTANCE),
             documents,
             cas,
+            translator: None,
             notif_tx,
         }
     }
+
+    /// Enable `block_read`'s `translate_to`.
+    pub fn with_translator(mut self, translator: Arc<Translator>) -> Self {
+        self.translator = Some(translator);
+        self
+    }
 }
 
 fn tool_def<P: JsonSchema>(
@@ -261,6 +343,47 @@ fn tool_def<P: JsonSchema>(
     })
 }
 
+/// Parse a `language` argument: `""` clears (`None`), anything else must
+/// normalize to a tag.
+fn parse_language(tag: &str) -> McpResult<Option<String>> {
+    if tag.trim().is_empty() {
+        return Ok(None);
+    }
+    kaijutsu_types::language::normalize_language(tag)
+        .map(Some)
+        .ok_or_else(|| McpError::Protocol(format!("invalid language '{tag}'")))
+}
+
+/// Parse a `label` argument: `""` clears (`None`), anything else must be a
+/// valid label. Uniqueness is checked by the store.
+fn parse_label(label: &str) -> McpResult<Option<String>> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Ok(None);
+    }
+    kaijutsu_types::validate_block_label(label).map_err(McpError::Protocol)?;
+    Ok(Some(label.to_string()))
+}
+
+/// A lock as the lock tools report it.
+fn lock_json(lock: &BlockLock) -> serde_json::Value {
+    serde_json::json!({
+        "block_id": lock.block_id.to_key(),
+        "holder": lock.holder_label(),
+        "note": lock.note,
+        "acquired_at": lock.acquired_at,
+        "expires_at": lock.expires_at
+    })
+}
+
+/// Add a write's lock warning, if any, to its result.
+fn with_warning(mut result: serde_json::Value, warning: Option<String>) -> serde_json::Value {
+    if let Some(warning) = warning {
+        result["warning"] = serde_json::Value::String(warning);
+    }
+    result
+}
+
 #[async_trait]
 impl McpServerLike for BlockToolsServer {
     fn instance_id(&self) -> &InstanceId {
@@ -277,6 +400,10 @@ impl McpServerLike for BlockToolsServer {
             tool_def::<BlockSearchParams>(&self.instance_id, "block_search", "Search within a block using regex or literal patterns")?,
             tool_def::<BlockListParams>(&self.instance_id, "block_list", "List blocks with optional filters")?,
             tool_def::<BlockStatusParams>(&self.instance_id, "block_status", "Set block status (pending, running, done, error, cancelled)")?,
+            tool_def::<BlockReflowParams>(&self.instance_id, "block_reflow", "Rewrap a block's prose to a width in one edit and record the setting; code is left as is")?,
+            tool_def::<BlockLockParams>(&self.instance_id, "block_lock", "Mark a block as being edited by you until the lock expires; others' writes warn or wait, per the context's lock policy")?,
+            tool_def::<BlockUnlockParams>(&self.instance_id, "block_unlock", "Release your edit lock on a block")?,
+            tool_def::<BlockLocksParams>(&self.instance_id, "block_locks", "List the blocks being edited in this context, by whom, and the context's lock policy")?,
             tool_def::<KernelSearchParams>(&self.instance_id, "kernel_search", "Search across all blocks using regex, with filters and context")?,
             tool_def::<SvgBlockParams>(&self.instance_id, "svg_block", "Append an SVG block to the current context. Renders as vector graphics inline.")?,
             tool_def::<AbcBlockParams>(&self.instance_id, "abc_block", "Append an ABC music notation block. Validates parse; renders as sheet music inline.")?,
@@ -301,25 +428,42 @@ impl McpServerLike for BlockToolsServer {
                 let kind = self.parse_kind(&p.kind)?;
                 let content = p.content.unwrap_or_default();
                 let parent_id = p.parent_id.as_ref().map(|s| self.parse_block_id(s)).transpose()?;
+                let language = p.language.as_deref().map(parse_language).transpose()?.flatten();
+                let label = p.label.as_deref().map(parse_label).transpose()?.flatten();
                 let context_id = tool_ctx.context_id;
 
                 if !self.documents.contains(context_id) {
                     return Err(McpError::Protocol(format!("no document for context {}", context_id.short())));
                 }
+                if let Some(label) = &label
+                    && let Some(holder) = self.documents.get(context_id).and_then(|e| e.doc.block_by_label(label))
+                {
+                    return Err(McpError::Protocol(format!("label '{label}' already names block {holder}")));
+                }
 
-                let block_id = self.documents
-                    .insert_block_as(
-                        context_id,
+                // A failed metadata write takes the new block back out.
+                let mut tx = self
+                    .documents
+                    .transaction(context_id, Some(tool_ctx.principal_id));
+                let block_id = tx
+                    .insert_block(
                         parent_id.as_ref(),
-                        None,
                         role,
                         kind,
                         &content,
                         Status::Done,
                         ContentType::Plain,
-                        Some(tool_ctx.principal_id),
                     )
                     .map_err(|e| McpError::Protocol(e.to_string()))?;
+                if language.is_some() {
+                    tx.set_language(&block_id, language)
+                        .map_err(|e| McpError::Protocol(e.to_string()))?;
+                }
+                if label.is_some() {
+                    tx.set_label(&block_id, label)
+                        .map_err(|e| McpError::Protocol(e.to_string()))?;
+                }
+                tx.commit();
 
                 let version = self.documents.get(context_id).map(|c| c.version()).unwrap_or(0);
                 let res_json = serde_json::json!({
@@ -351,33 +495,37 @@ impl McpServerLike for BlockToolsServer {
                     snapshot.content.chars().count()
                 };
 
+                let warning = self.check_lock(&block_id, &tool_ctx)?;
                 self.documents
                     .edit_text_as(context_id, &block_id, char_offset, &p.content, 0, Some(tool_ctx.principal_id))
                     .map_err(|e| McpError::Protocol(e.to_string()))?;
 
                 let version = self.documents.get(context_id).map(|c| c.version()).unwrap_or(0);
-                let res_json = serde_json::json!({
-                    "block_id": p.block_id,
-                    "version": version
-                });
+                let res_json = with_warning(
+                    serde_json::json!({
+                        "block_id": block_id.to_key(),
+                        "version": version
+                    }),
+                    warning,
+                );
                 ExecResult::success(res_json.to_string())
             }
             "block_edit" => {
                 let p: BlockEditParams = serde_json::from_value(params.arguments)
                     .map_err(McpError::InvalidParams)?;
                 let (context_id, block_id) = self.find_block(&p.block_id)?;
+                let language = p.language.as_deref().map(parse_language).transpose()?;
+                let label = p.label.as_deref().map(parse_label).transpose()?;
 
                 // Pre-validate CAS checks
                 {
-                    let content = self.documents
-                        .get(context_id)
-                        .and_then(|entry| {
-                            entry
-                                .doc
-                                .get_block_snapshot(&block_id)
-                                .map(|s| s.content.clone())
-                        })
-                        .unwrap_or_default();
+                    let (content, index) = self
+                        .documents
+                        .get_block_indexed(context_id, &block_id)
+                        .ok()
+                        .flatten()
+                        .map(|(snap, _, index)| (snap.content, index))
+                        .unwrap_or_else(|| (String::new(), Arc::new(LineIndex::new(""))));
 
                     for (idx, op) in p.operations.iter().enumerate() {
                         if let EditOp::Replace {
@@ -387,21 +535,36 @@ impl McpServerLike for BlockToolsServer {
                             ..
                         } = op
                         {
-                            validate_expected_text(&content, *start_line, *end_line, expected)
+                            index
+                                .validate_expected(&content, *start_line, *end_line, expected)
                                 .map_err(|e| McpError::Protocol(format!("CAS error at op {}: {}", idx, e)))?;
                         }
                     }
                 }
+                let warning = self.check_lock(&block_id, &tool_ctx)?;
+                // One transaction for the call: a failing op drops `tx`,
+                // which undoes the ops and label before it.
+                let mut tx = self
+                    .documents
+                    .transaction(context_id, Some(tool_ctx.principal_id));
+                // Label first: a taken label fails before any text changes.
+                if let Some(label) = label {
+                    tx.set_label(&block_id, label)
+                        .map_err(|e| McpError::Protocol(e.to_string()))?;
+                }
 
                 for (idx, op) in p.operations.into_iter().enumerate() {
-                    self.apply_op(context_id, &block_id, op, &tool_ctx)
+                    self.apply_op(&mut tx, context_id, &block_id, op)
                         .map_err(|e| McpError::Protocol(format!("edit error at op {}: {}", idx, e)))?;
                 }
+                if let Some(language) = language {
+                    tx.set_language(&block_id, language)
+                        .map_err(|e| McpError::Protocol(e.to_string()))?;
+                }
+                tx.commit();
 
                 let version = self.documents.get(context_id).map(|c| c.version()).unwrap_or(0);
-                let res_json = serde_json::json!({
-                    "version": version
-                });
+                let res_json = with_warning(serde_json::json!({ "version": version }), warning);
                 ExecResult::success(res_json.to_string())
             }
             "block_splice" => {
@@ -410,6 +573,7 @@ impl McpServerLike for BlockToolsServer {
                 let (context_id, block_id) = self.find_block(&p.block_id)?;
                 let insert = p.insert.unwrap_or_default();
 
+                let warning = self.check_lock(&block_id, &tool_ctx)?;
                 self.documents
                     .edit_text_as(
                         context_id,
@@ -422,8 +586,46 @@ impl McpServerLike for BlockToolsServer {
                     .map_err(|e| McpError::Protocol(e.to_string()))?;
 
                 let version = self.documents.get(context_id).map(|c| c.version()).unwrap_or(0);
+                let res_json = with_warning(serde_json::json!({ "version": version }), warning);
+                ExecResult::success(res_json.to_string())
+            }
+            "block_lock" => {
+                let p: BlockLockParams = serde_json::from_value(params.arguments)
+                    .map_err(McpError::InvalidParams)?;
+                let (_, block_id) = self.find_block(&p.block_id)?;
+                let lock = self
+                    .documents
+                    .lock_block(
+                        &block_id,
+                        tool_ctx.principal_id,
+                        "",
+                        p.ttl_secs,
+                        p.note.as_deref().unwrap_or_default(),
+                    )
+                    .map_err(|e| McpError::Protocol(e.to_string()))?;
+                ExecResult::success(lock_json(&lock).to_string())
+            }
+            "block_unlock" => {
+                let p: BlockUnlockParams = serde_json::from_value(params.arguments)
+                    .map_err(McpError::InvalidParams)?;
+                let (_, block_id) = self.find_block(&p.block_id)?;
+                let released = self
+                    .documents
+                    .unlock_block(&block_id, tool_ctx.principal_id)
+                    .map_err(|e| McpError::Protocol(e.to_string()))?;
                 let res_json = serde_json::json!({
-                    "version": version
+                    "block_id": block_id.to_key(),
+                    "released": released
+                });
+                ExecResult::success(res_json.to_string())
+            }
+            "block_locks" => {
+                let context_id = tool_ctx.context_id;
+                let locks: Vec<serde_json::Value> =
+                    self.documents.block_locks(context_id).iter().map(lock_json).collect();
+                let res_json = serde_json::json!({
+                    "policy": self.documents.lock_policy(context_id).as_str(),
+                    "locks": locks
                 });
                 ExecResult::success(res_json.to_string())
             }
@@ -432,26 +634,56 @@ impl McpServerLike for BlockToolsServer {
                     .map_err(McpError::InvalidParams)?;
                 let (context_id, block_id) = self.find_block(&p.block_id)?;
 
-                let entry = self.documents
-                    .get(context_id)
-                    .ok_or_else(|| McpError::Protocol("document not found".into()))?;
-
-                let snapshot = entry
-                    .doc
-                    .get_block_snapshot(&block_id)
-                    .ok_or_else(|| McpError::Protocol(format!("block not found: {}", p.block_id)))?;
+                // Read under the document guard, released before translating.
+                let (snapshot, frontier, version) = {
+                    let entry = self.documents
+                        .get(context_id)
+                        .ok_or_else(|| McpError::Protocol("document not found".into()))?;
+                    let (snapshot, frontier) = entry
+                        .doc
+                        .get_block_snapshot(&block_id)
+                        .zip(entry.doc.block_frontier(&block_id))
+                        .ok_or_else(|| McpError::Protocol(format!("block not found: {}", p.block_id)))?;
+                    (snapshot, frontier, entry.version())
+                };
 
-                let content = &snapshot.content;
-                let total_lines = line_count(content);
+                let translation = match p.translate_to.as_deref() {
+                    Some(language) => {
+                        let translator = self.translator.as_ref().ok_or_else(|| {
+                            McpError::Protocol("translation is not available on this server".into())
+                        })?;
+                        let translation = translator
+                            .translate(block_id, &frontier, &snapshot.content, language)
+                            .await
+                            .map_err(|e| McpError::Protocol(e.to_string()))?;
+                        Some(translation)
+                    }
+                    None => None,
+                };
+                let source = translation.as_ref().map_or(&snapshot.content, |t| &t.text);
+
+                // A reflow setting wraps the view; the stored text is untouched.
+                let wrapped = snapshot
+                    .reflow
+                    .filter(|_| !p.raw)
+                    .filter(|_| kaijutsu_types::reflow::reflows_language(snapshot.language.as_deref()))
+                    .map(|reflow| kaijutsu_types::reflow::reflow_text(source, reflow));
+                let content = wrapped.as_ref().unwrap_or(source);
+                // The stored text's index is cached by frontier; a translated
+                // or wrapped view is indexed on the spot.
+                let index = if std::ptr::eq(content, &snapshot.content) {
+                    self.documents.line_index(&block_id, &frontier, content)
+                } else {
+                    Arc::new(LineIndex::new(content))
+                };
+                let total_lines = index.line_count();
 
                 let formatted_content = if let Some((start, end)) = p.range {
                     if p.line_numbers {
-                        extract_lines_with_numbers(content, start, end)
+                        index.numbered(content, start, end)
                     } else {
-                        content
-                            .lines()
-                            .skip(start as usize)
-                            .take((end.saturating_sub(start)) as usize)
+                        index
+                            .lines(content, start, end)
                             .collect::<Vec<_>>()
                             .join("\n")
                     }
@@ -466,12 +698,20 @@ impl McpServerLike for BlockToolsServer {
                     "role": format!("{:?}", snapshot.role).to_lowercase(),
                     "kind": format!("{:?}", snapshot.kind).to_lowercase(),
                     "status": format!("{:?}", snapshot.status).to_lowercase(),
-                    "version": entry.version(),
+                    "version": version,
                     "line_count": total_lines,
+                    "translation": translation.as_ref().map(|t| serde_json::json!({
+                        "language": t.language,
+                        "cached": t.cached,
+                    })),
                     "metadata": {
                         "tool_name": snapshot.tool_name,
                         "tool_call_id": snapshot.tool_call_id,
                         "is_error": snapshot.is_error,
+                        "language": snapshot.language,
+                        "label": snapshot.label,
+                        "reflow": snapshot.reflow,
+                        "provenance": snapshot.provenance,
                     }
                 });
                 ExecResult::success(res_json.to_string())
@@ -481,26 +721,22 @@ impl McpServerLike for BlockToolsServer {
                     .map_err(McpError::InvalidParams)?;
                 let (context_id, block_id) = self.find_block(&p.block_id)?;
 
-                let entry = self.documents
-                    .get(context_id)
-                    .ok_or_else(|| McpError::Protocol("document not found".into()))?;
-
-                let snapshot = entry
-                    .doc
-                    .get_block_snapshot(&block_id)
+                let (snapshot, _, index) = self
+                    .documents
+                    .get_block_indexed(context_id, &block_id)
+                    .map_err(|e| McpError::Protocol(e.to_string()))?
                     .ok_or_else(|| McpError::Protocol(format!("block not found: {}", p.block_id)))?;
 
                 let content = &snapshot.content;
-                let lines: Vec<&str> = content.lines().collect();
 
                 let regex = regex::Regex::new(&p.query)
                     .or_else(|_| regex::Regex::new(&regex::escape(&p.query)))
                     .map_err(|e| McpError::Protocol(format!("Invalid regex: {}", e)))?;
 
                 let mut search_matches = Vec::new();
-                let total_lines = lines.len() as u32;
+                let total_lines = index.line_count();
 
-                for (line_num, line) in lines.iter().enumerate() {
+                for (line_num, line) in content.lines().enumerate() {
                     if search_matches.len() >= p.max_matches as usize {
                         break;
                     }
@@ -513,7 +749,7 @@ impl McpServerLike for BlockToolsServer {
                         let ctx_start = (line_num as u32).saturating_sub(p.context_lines);
                         let ctx_end = ((line_num as u32) + p.context_lines + 1).min(total_lines);
 
-                        let context_content = extract_lines_with_numbers(content, ctx_start, ctx_end);
+                        let context_content = index.numbered(content, ctx_start, ctx_end);
 
                         search_matches.push(SearchMatch {
                             line: line_num as u32,
@@ -607,6 +843,40 @@ impl McpServerLike for BlockToolsServer {
                 });
                 ExecResult::success(res_json.to_string())
             }
+            "block_reflow" => {
+                let p: BlockReflowParams = serde_json::from_value(params.arguments)
+                    .map_err(McpError::InvalidParams)?;
+                let (context_id, block_id) = self.find_block(&p.block_id)?;
+
+                let (reflow, changed) = if p.clear {
+                    self.documents
+                        .set_reflow(context_id, &block_id, None)
+                        .map_err(|e| McpError::Protocol(e.to_string()))?;
+                    (None, false)
+                } else {
+                    let current = self.documents
+                        .get_block_snapshot(context_id, &block_id)
+                        .map_err(|e| McpError::Protocol(e.to_string()))?
+                        .and_then(|s| s.reflow);
+                    let reflow = kaijutsu_types::Reflow::new(
+                        p.width.or(current.map(|r| r.width)).unwrap_or(kaijutsu_types::reflow::DEFAULT_REFLOW_WIDTH),
+                        p.preserve_newlines.or(current.map(|r| r.preserve_newlines)).unwrap_or(false),
+                    )
+                    .map_err(McpError::Protocol)?;
+                    let changed = self.documents
+                        .reflow_block(context_id, &block_id, reflow, Some(tool_ctx.principal_id))
+                        .map_err(|e| McpError::Protocol(e.to_string()))?;
+                    (Some(reflow), changed)
+                };
+
+                let version = self.documents.get(context_id).map(|c| c.version()).unwrap_or(0);
+                let res_json = serde_json::json!({
+                    "version": version,
+                    "changed": changed,
+                    "reflow": reflow,
+                });
+                ExecResult::success(res_json.to_string())
+            }
             "kernel_search" => {
                 let p: KernelSearchParams = serde_json::from_value(params.arguments)
                     .map_err(McpError::InvalidParams)?;
@@ -631,12 +901,13 @@ impl McpServerLike for BlockToolsServer {
                 };
 
                 'outer: for context_id in context_ids {
-                    let snapshots = match self.documents.block_snapshots(context_id) {
+                    let snapshots = match self.documents.block_snapshots_with_frontiers(context_id)
+                    {
                         Ok(s) => s,
                         Err(_) => continue,
                     };
 
-                    for snapshot in snapshots {
+                    for (snapshot, frontier) in snapshots {
                         if let Some(ref kind) = kind_filter
                             && snapshot.kind != *kind
                         {
@@ -648,24 +919,31 @@ impl McpServerLike for BlockToolsServer {
                             continue;
                         }
 
-                        let lines: Vec<&str> = snapshot.content.lines().collect();
-                        for (line_idx, line) in lines.iter().enumerate() {
+                        let content = &snapshot.content;
+                        // Indexed on the first match only; most blocks have none.
+                        let mut index: Option<Arc<LineIndex>> = None;
+                        for (line_idx, line) in content.lines().enumerate() {
                             if regex.is_match(line) {
-                                let before: Vec<String> = (0..p.context_lines as usize)
-                                    .filter_map(|i| {
-                                        if line_idx > i {
-                                            Some(lines[line_idx - i - 1].to_string())
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .collect::<Vec<_>>()
-                                    .into_iter()
-                                    .rev()
+                                let index = index.get_or_insert_with(|| {
+                                    self.documents.line_index(&snapshot.id, &frontier, content)
+                                });
+                                let line_no = line_idx as u32;
+                                let before: Vec<String> = index
+                                    .lines(
+                                        content,
+                                        line_no.saturating_sub(p.context_lines),
+                                        line_no,
+                                    )
+                                    .map(str::to_string)
                                     .collect();
 
-                                let after: Vec<String> = (1..=p.context_lines as usize)
-                                    .filter_map(|i| lines.get(line_idx + i).map(|s| s.to_string()))
+                                let after: Vec<String> = index
+                                    .lines(
+                                        content,
+                                        line_no + 1,
+                                        (line_no + 1).saturating_add(p.context_lines),
+                                    )
+                                    .map(str::to_string)
                                     .collect();
 
                                 search_matches.push(KernelSearchMatch {
@@ -767,9 +1045,22 @@ impl McpServerLike for BlockToolsServer {
 }
 
 impl BlockToolsServer {
+    /// Consult `block_id`'s edit lock before the caller writes it: the
+    /// warning to return with the result, or the refusal when the context
+    /// holds writes off.
+    fn check_lock(&self, block_id: &BlockId, tool_ctx: &ExecContext) -> McpResult<Option<String>> {
+        self.documents
+            .check_lock(block_id, Some(tool_ctx.principal_id))
+            .map_err(|e| McpError::Protocol(e.to_string()))
+    }
+
+    /// A full block key, a block label, or an abbreviation that names exactly
+    /// one resident block (`ctx@principal#seq` with short ids, a key prefix,
+    /// …).
     fn parse_block_id(&self, s: &str) -> McpResult<BlockId> {
-        BlockId::from_key(s)
-            .ok_or_else(|| McpError::Protocol(format!("invalid block_id format: {}", s)))
+        self.documents
+            .resolve_block(s)
+            .map_err(|e| McpError::Protocol(format!("invalid block_id: {e}")))
     }
 
     fn parse_role(&self, s: &str) -> McpResult<Role> {
@@ -848,63 +1139,45 @@ impl BlockToolsServer {
 
     fn apply_op(
         &self,
+        tx: &mut BlockTransaction<'_>,
         context_id: ContextId,
         block_id: &BlockId,
         op: EditOp,
-        ctx: &ExecContext,
     ) -> McpResult<()> {
-        let content = {
-            let entry = self
-                .documents
-                .get(context_id)
-                .ok_or_else(|| McpError::Protocol("document not found".into()))?;
-
-            entry
-                .doc
-                .get_block_snapshot(block_id)
-                .map(|s| s.content.clone())
-                .ok_or_else(|| McpError::Protocol(format!("block not found: {}", block_id)))?
-        };
+        // Each op re-reads the block, so a batch sees its earlier ops; the
+        // index is cached per frontier, so an unchanged block isn't rescanned.
+        let (content, index) = self
+            .documents
+            .get_block_indexed(context_id, block_id)
+            .map_err(|e| McpError::Protocol(e.to_string()))?
+            .map(|(snap, _, index)| (snap.content, index))
+            .ok_or_else(|| McpError::Protocol(format!("block not found: {}", block_id)))?;
 
         match op {
             EditOp::Insert {
                 line,
                 content: text,
             } => {
-                let pos = line_to_char_offset(&content, line)
+                let pos = index
+                    .char_offset(line)
                     .map_err(|e| McpError::Protocol(e.to_string()))?;
                 let text_with_newline = if text.ends_with('\n') || content.is_empty() {
                     text
                 } else {
                     format!("{}\n", text)
                 };
-                self.documents
-                    .edit_text_as(
-                        context_id,
-                        block_id,
-                        pos,
-                        &text_with_newline,
-                        0,
-                        Some(ctx.principal_id),
-                    )
+                tx.edit_text(block_id, pos, &text_with_newline, 0)
                     .map_err(|e| McpError::Protocol(e.to_string()))?;
             }
             EditOp::Delete {
                 start_line,
                 end_line,
             } => {
-                let (start, end) = line_range_to_char_range(&content, start_line, end_line)
+                let (start, end) = index
+                    .char_range(start_line, end_line)
                     .map_err(|e| McpError::Protocol(e.to_string()))?;
                 if start < end {
-                    self.documents
-                        .edit_text_as(
-                            context_id,
-                            block_id,
-                            start,
-                            "",
-                            end - start,
-                            Some(ctx.principal_id),
-                        )
+                    tx.edit_text(block_id, start, "", end - start)
                         .map_err(|e| McpError::Protocol(e.to_string()))?;
                 }
             }
@@ -915,25 +1188,20 @@ impl BlockToolsServer {
                 expected_text,
             } => {
                 if let Some(expected) = expected_text {
-                    validate_expected_text(&content, start_line, end_line, &expected).map_err(|e| McpError::Protocol(e.to_string()))?;
+                    index
+                        .validate_expected(&content, start_line, end_line, &expected)
+                        .map_err(|e| McpError::Protocol(e.to_string()))?;
                 }
 
-                let (start, end) = line_range_to_char_range(&content, start_line, end_line)
+                let (start, end) = index
+                    .char_range(start_line, end_line)
                     .map_err(|e| McpError::Protocol(e.to_string()))?;
                 let text_with_newline = if text.ends_with('\n') || text.is_empty() {
                     text
                 } else {
                     format!("{}\n", text)
                 };
-                self.documents
-                    .edit_text_as(
-                        context_id,
-                        block_id,
-                        start,
-                        &text_with_newline,
-                        end - start,
-                        Some(ctx.principal_id),
-                    )
+                tx.edit_text(block_id, start, &text_with_newline, end - start)
                     .map_err(|e| McpError::Protocol(e.to_string()))?;
             }
         }
@@ -1047,7 +1315,139 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn list_tools_exposes_all_thirteen() {
+    async fn block_language_set_on_create_and_cleared_on_edit() {
+        let (broker, ctx, _db, store) = setup().await;
+        let result = call(
+            &broker,
+            &ctx,
+            "block_create",
+            serde_json::json!({
+                "role": "model",
+                "kind": "text",
+                "content": "print(1)",
+                "language": "py",
+            }),
+        )
+        .await;
+        assert!(!result.is_error, "unexpected error: {:?}", result.content);
+        let created: serde_json::Value = serde_json::from_str(&text_of(&result)).unwrap();
+        let key = created["block_id"].as_str().unwrap().to_string();
+        let block_id = kaijutsu_types::BlockId::from_key(&key).unwrap();
+        let language = || {
+            store
+                .get_block_snapshot(ctx.context_id, &block_id)
+                .unwrap()
+                .unwrap()
+                .language
+        };
+        assert_eq!(language().as_deref(), Some("python"));
+
+        let result = call(
+            &broker,
+            &ctx,
+            "block_edit",
+            serde_json::json!({"block_id": key, "language": ""}),
+        )
+        .await;
+        assert!(!result.is_error, "unexpected error: {:?}", result.content);
+        assert_eq!(language(), None);
+    }
+
+    #[tokio::test]
+    async fn block_label_addresses_the_block_and_stays_unique() {
+        let (broker, ctx, _db, store) = setup().await;
+        let create = |content: &str, label: &str| {
+            serde_json::json!({
+                "role": "model",
+                "kind": "text",
+                "content": content,
+                "label": label,
+            })
+        };
+        let result = call(&broker, &ctx, "block_create", create("We chose CRDTs.", "design-decision-3")).await;
+        assert!(!result.is_error, "unexpected error: {:?}", result.content);
+        let created: serde_json::Value = serde_json::from_str(&text_of(&result)).unwrap();
+        let block_id = kaijutsu_types::BlockId::from_key(created["block_id"].as_str().unwrap()).unwrap();
+
+        // The label reads like a block id.
+        let result = call(
+            &broker,
+            &ctx,
+            "block_read",
+            serde_json::json!({"block_id": "design-decision-3"}),
+        )
+        .await;
+        assert!(!result.is_error, "unexpected error: {:?}", result.content);
+        assert!(text_of(&result).contains("We chose CRDTs."));
+
+        // A second block can't take it.
+        let dup = call_res(&broker, &ctx, "block_create", create("dup", "design-decision-3")).await;
+        assert!(dup.is_err(), "duplicate label should be refused");
+        assert_eq!(store.block_snapshots(ctx.context_id).unwrap().len(), 1);
+
+        // Clearing through block_edit frees it.
+        let result = call(
+            &broker,
+            &ctx,
+            "block_edit",
+            serde_json::json!({"block_id": "design-decision-3", "label": ""}),
+        )
+        .await;
+        assert!(!result.is_error, "unexpected error: {:?}", result.content);
+        let snap = store.get_block_snapshot(ctx.context_id, &block_id).unwrap().unwrap();
+        assert_eq!(snap.label, None);
+    }
+
+    #[tokio::test]
+    async fn block_reflow_rewraps_and_block_read_honors_setting() {
+        let (broker, ctx, _db, store) = setup().await;
+        let long = "word ".repeat(30).trim_end().to_string();
+        let block_id = store
+            .insert_block(
+                ctx.context_id,
+                None,
+                None,
+                Role::Model,
+                BlockKind::Text,
+                &long,
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let key = block_id.to_key();
+        let read = |raw: bool| {
+            call(&broker, &ctx, "block_read", serde_json::json!({"block_id": key, "line_numbers": false, "raw": raw}))
+        };
+
+        // A setting alone wraps the view, not the text.
+        store
+            .set_reflow(ctx.context_id, &block_id, Some(kaijutsu_types::Reflow::new(40, false).unwrap()))
+            .unwrap();
+        let view: serde_json::Value = serde_json::from_str(&text_of(&read(false).await)).unwrap();
+        assert_eq!(view["line_count"], 4);
+        let raw: serde_json::Value = serde_json::from_str(&text_of(&read(true).await)).unwrap();
+        assert_eq!(raw["content"], long.as_str());
+
+        // block_reflow rewrites the text; width defaults to the stored setting.
+        let result = call(&broker, &ctx, "block_reflow", serde_json::json!({"block_id": key})).await;
+        assert!(!result.is_error, "unexpected error: {:?}", result.content);
+        let out: serde_json::Value = serde_json::from_str(&text_of(&result)).unwrap();
+        assert_eq!(out["changed"], true);
+        assert_eq!(out["reflow"]["width"], 40);
+        let snap = store.get_block_snapshot(ctx.context_id, &block_id).unwrap().unwrap();
+        assert_eq!(snap.content.lines().count(), 4);
+
+        let bad = call_res(&broker, &ctx, "block_reflow", serde_json::json!({"block_id": key, "width": 5})).await;
+        assert!(bad.is_err(), "out-of-range width should be refused");
+
+        let result = call(&broker, &ctx, "block_reflow", serde_json::json!({"block_id": key, "clear": true})).await;
+        assert!(!result.is_error, "unexpected error: {:?}", result.content);
+        let snap = store.get_block_snapshot(ctx.context_id, &block_id).unwrap().unwrap();
+        assert_eq!(snap.reflow, None);
+    }
+
+    #[tokio::test]
+    async fn list_tools_exposes_all_fourteen() {
         let (broker, ctx, _db, _store) = setup().await;
         let visible = {
             let mut binding = crate::mcp::ContextToolBinding::new();
@@ -1065,6 +1465,7 @@ mod tests {
             "block_search",
             "block_list",
             "block_status",
+            "block_reflow",
             "kernel_search",
             "svg_block",
             "abc_block",
@@ -1612,6 +2013,53 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_batch_edit_failing_op_rolls_back_earlier_ops() {
+        let (broker, ctx, _db, store) = setup().await;
+        let block_id = store
+            .insert_block(
+                ctx.context_id,
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                "aaa\nbbb\nccc\n",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+
+        // The third op's range only fails once it is reached — past any
+        // pre-validation — after the first two and the label have applied.
+        let res = call_res(
+            &broker,
+            &ctx,
+            "block_edit",
+            serde_json::json!({
+                "block_id": block_id.to_key(),
+                "label": "draft",
+                "operations": [
+                    {"op": "replace", "start_line": 0, "end_line": 1, "content": "AAA"},
+                    {"op": "insert", "line": 0, "content": "top"},
+                    {"op": "delete", "start_line": 40, "end_line": 41}
+                ]
+            }),
+        )
+        .await;
+        let err = res.unwrap_err();
+        assert!(err.to_string().contains("op 2"), "error: {err}");
+
+        let snapshot = store
+            .get_block_snapshot(ctx.context_id, &block_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            snapshot.content, "aaa\nbbb\nccc\n",
+            "earlier ops must be undone"
+        );
+        assert_eq!(snapshot.label, None, "the label must be undone");
+    }
+
     #[tokio::test]
     async fn test_svg_block_inserts_svg_content_type() {
         let (broker, ctx, _db, store) = setup().await;
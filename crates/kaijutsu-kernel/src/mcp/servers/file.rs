@@ -1,6 +1,8 @@
 //! `FileToolsServer` — virtual MCP server exposing file tools (read, edit,
-//! write, glob, grep) through the broker.
+//! write, glob, grep, code_import, code_export, refactor_rename) through the
+//! broker.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -9,10 +11,14 @@ use serde::Deserialize;
 use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
 
+use kaijutsu_crdt::{BlockId, BlockKind, ContentType, Role, Status};
+
 use crate::file_tools::{
     FileDocumentCache, WorkspaceGuard, CacheReadError,
+    code_doc::{CodeLanguage, export_code_document, import_code_document},
     path::{resolve_str, is_rc_path, rc_write_denied, deny_etc_write},
     hashline::line_hash,
+    rename::{Changeset, FileRename, RenameScope, plan_code_blocks, rename_pattern, rename_text},
     vfs_walker::VfsWalkerAdapter,
 };
 use crate::vfs::{MountTable, VfsOps};
@@ -106,6 +112,55 @@ pub struct GrepParams {
     pub context_lines: u32,
 }
 
+/// Parameters for the `code_import` tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CodeImportParams {
+    /// Source file to import.
+    pub path: String,
+    /// Language (`rust`, `python`). Omit to infer from the file extension.
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// Parameters for the `code_export` tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CodeExportParams {
+    /// Code document ID (hex, as returned by `code_import`).
+    pub document_id: String,
+    /// File path to write the reassembled source to.
+    pub path: String,
+}
+
+/// Parameters for the `refactor_rename` tool. Plan with `old` + `new`, then
+/// apply by passing the returned changeset ID as `apply`.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RefactorRenameParams {
+    /// Identifier to rename, matched as a whole word. Required to plan.
+    #[serde(default)]
+    pub old: Option<String>,
+    /// Replacement name. Required to plan.
+    #[serde(default)]
+    pub new: Option<String>,
+    /// What to rename in: `all` (files and code documents, the default),
+    /// `files`, or `code`.
+    #[serde(default)]
+    pub scope: RenameScope,
+    /// Directory (or file) whose files are renamed in. Defaults to the
+    /// context cwd.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Optional glob filter for filenames (e.g., `**/*.rs`).
+    #[serde(default)]
+    pub glob: Option<String>,
+    /// Changeset ID from a previous plan: write that changeset instead of
+    /// planning a new one.
+    #[serde(default)]
+    pub apply: Option<String>,
+}
+
 // ── Server ─────────────────────────────────────────────────────────────────
 
 pub struct FileToolsServer {
@@ -114,6 +169,8 @@ pub struct FileToolsServer {
     vfs: Arc<MountTable>,
     guard: Option<WorkspaceGuard>,
     notif_tx: broadcast::Sender<ServerNotification>,
+    /// Planned renames awaiting `apply`, keyed by their changeset block.
+    changesets: parking_lot::Mutex<HashMap<BlockId, Changeset>>,
 }
 
 impl FileToolsServer {
@@ -131,6 +188,7 @@ impl FileToolsServer {
             vfs,
             guard,
             notif_tx,
+            changesets: parking_lot::Mutex::new(HashMap::new()),
         }
     }
 }
@@ -172,6 +230,15 @@ impl McpServerLike for FileToolsServer {
             tool_def::<GrepParams>(&self.instance_id, "grep",
                 "Search file content with regex, CRDT-aware (sees uncommitted edits)"
             )?,
+            tool_def::<CodeImportParams>(&self.instance_id, "code_import",
+                "Import a source file as a Code document: tree-sitter splits it into one block per top-level item (functions, types, impls, classes; leading comments ride with their item, consecutive imports share a block). Returns the document ID and block layout. Supported: rust, python."
+            )?,
+            tool_def::<CodeExportParams>(&self.instance_id, "code_export",
+                "Write a Code document back to a file: its text blocks concatenated in document order. An unedited import round-trips byte-for-byte."
+            )?,
+            tool_def::<RefactorRenameParams>(&self.instance_id, "refactor_rename",
+                "Rename an identifier (whole-word matches) across files under a path and the blocks of every Code document, as one changeset. The first call (`old`, `new`, optional `scope`/`path`/`glob`) writes nothing: it posts a changeset block with a diff per file and per code block for review and returns its ID. Call again with `apply` set to that ID to write it; a changeset whose targets changed since planning is refused whole."
+            )?,
         ])
     }
 
@@ -427,6 +494,54 @@ impl McpServerLike for FileToolsServer {
                     }
                 }
             }
+            "code_import" => {
+                let p: CodeImportParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                let path = resolve_str(&tool_ctx.cwd, &p.path).map_err(|e| McpError::Protocol(e.to_string()))?;
+                if let Some(ref guard) = self.guard
+                    && let Err(denied) = guard.check_read(&tool_ctx, &path)
+                {
+                    denied
+                } else {
+                    self.code_import(path, p.language.as_deref()).await
+                }
+            }
+            "code_export" => {
+                let p: CodeExportParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                let path = resolve_str(&tool_ctx.cwd, &p.path).map_err(|e| McpError::Protocol(e.to_string()))?;
+                if is_rc_path(&path) {
+                    if !self.guard.as_ref().is_some_and(|g| g.context_allows_rc_write(&tool_ctx)) {
+                        rc_write_denied(&path)
+                    } else if let Some(ref guard) = self.guard
+                        && let Err(denied) = guard.check_write(&tool_ctx, &path)
+                    {
+                        denied
+                    } else {
+                        self.code_export(&p.document_id, path).await
+                    }
+                } else if let Some(denied) = deny_etc_write(&path) {
+                    denied
+                } else if let Some(ref guard) = self.guard
+                    && let Err(denied) = guard.check_write(&tool_ctx, &path)
+                {
+                    denied
+                } else {
+                    self.code_export(&p.document_id, path).await
+                }
+            }
+            "refactor_rename" => {
+                let p: RefactorRenameParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                match (&p.apply, &p.old, &p.new) {
+                    (Some(id), None, None) => self.refactor_apply(id, &tool_ctx).await,
+                    (None, Some(old), Some(new)) => self.refactor_plan(old, new, &p, &tool_ctx).await,
+                    _ => ExecResult::failure(
+                        1,
+                        "refactor_rename needs `old` and `new` to plan, or only `apply` to write a planned changeset",
+                    ),
+                }
+            }
             other => {
                 return Err(McpError::ToolNotFound {
                     instance: self.instance_id.clone(),
@@ -466,6 +581,262 @@ impl FileToolsServer {
         }
     }
 
+    async fn code_import(&self, path: String, language: Option<&str>) -> ExecResult {
+        let language = match language {
+            Some(name) => CodeLanguage::from_name(name),
+            None => CodeLanguage::detect(&path),
+        };
+        let Some(language) = language else {
+            return ExecResult::failure(
+                1,
+                format!("{}: unsupported or unknown language (supported: rust, python)", path),
+            );
+        };
+        let source = match self.cache.try_read_content(&path).await {
+            Ok(content) => content,
+            Err(CacheReadError::NotCached) => {
+                return ExecResult::failure(1, format!("{}: not found or not a text file", path));
+            }
+            Err(CacheReadError::Backend(e)) => return ExecResult::failure(1, e),
+        };
+        match import_code_document(self.cache.block_store(), &source, language) {
+            Ok((doc_id, chunks)) => {
+                let layout = chunks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| format!("{:>4}  {}", i, c.label()))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ExecResult::success(format!(
+                    "Imported {} as {} document {} ({} block{})\n\n{}",
+                    path,
+                    language.as_str(),
+                    doc_id.to_hex(),
+                    chunks.len(),
+                    if chunks.len() == 1 { "" } else { "s" },
+                    layout
+                ))
+            }
+            Err(e) => ExecResult::failure(1, format!("{}: {}", path, e)),
+        }
+    }
+
+    async fn code_export(&self, document_id: &str, path: String) -> ExecResult {
+        let doc_id = match kaijutsu_types::ContextId::parse(document_id) {
+            Ok(id) => id,
+            Err(e) => return ExecResult::failure(1, format!("invalid document_id: {}", e)),
+        };
+        match export_code_document(self.cache.block_store(), doc_id) {
+            Ok(content) => self.write_file(path, content).await,
+            Err(e) => ExecResult::failure(1, e),
+        }
+    }
+
+    /// The denial for writing `path` as `tool_ctx`, if any — the same checks
+    /// `write` and `edit` make.
+    fn write_denied(&self, tool_ctx: &ExecContext, path: &str) -> Option<ExecResult> {
+        if is_rc_path(path) {
+            if !self.guard.as_ref().is_some_and(|g| g.context_allows_rc_write(tool_ctx)) {
+                return Some(rc_write_denied(path));
+            }
+        } else if let Some(denied) = deny_etc_write(path) {
+            return Some(denied);
+        }
+        self.guard
+            .as_ref()
+            .and_then(|guard| guard.check_write(tool_ctx, path).err())
+    }
+
+    /// Plan a rename and post it as a changeset block; writes nothing.
+    async fn refactor_plan(
+        &self,
+        old: &str,
+        new: &str,
+        p: &RefactorRenameParams,
+        tool_ctx: &ExecContext,
+    ) -> ExecResult {
+        if new.is_empty() || old == new {
+            return ExecResult::failure(1, "new name must be non-empty and differ from the old one");
+        }
+        let pattern = match rename_pattern(old) {
+            Ok(re) => re,
+            Err(e) => return ExecResult::failure(1, e),
+        };
+        let mut changeset = Changeset::new(old, new);
+
+        if p.scope.files() {
+            let root = match &p.path {
+                Some(pp) => match resolve_str(&tool_ctx.cwd, pp) {
+                    Ok(s) => s,
+                    Err(e) => return ExecResult::failure(1, e.to_string()),
+                },
+                None => tool_ctx.cwd.to_string_lossy().into_owned(),
+            };
+            if let Some(ref guard) = self.guard
+                && let Err(denied) = guard.check_read(tool_ctx, &root)
+            {
+                return denied;
+            }
+            let adapter = VfsWalkerAdapter(&self.vfs);
+            let options = kaish_glob::WalkOptions {
+                respect_gitignore: true,
+                ..Default::default()
+            };
+            let mut walker = kaish_glob::FileWalker::new(&adapter, &root).with_options(options);
+            if let Some(ref glob_pattern) = p.glob {
+                match kaish_glob::GlobPath::new(glob_pattern) {
+                    Ok(g) => walker = walker.with_pattern(g),
+                    Err(e) => return ExecResult::failure(1, format!("Invalid glob: {}", e)),
+                }
+            }
+            let files = match walker.collect().await {
+                Ok(f) => f,
+                Err(e) => return ExecResult::failure(1, format!("Walk failed: {}", e)),
+            };
+            for file_path in &files {
+                if let Ok(attr) = self.vfs.getattr(file_path).await
+                    && attr.size as usize > MAX_RENAME_FILE_SIZE
+                {
+                    continue;
+                }
+                let path = file_path.display().to_string();
+                let content = match self.cache.try_read_content(&path).await {
+                    Ok(c) => c,
+                    Err(_) => continue,
+                };
+                let Some((after, occurrences)) = rename_text(&pattern, &content, new) else {
+                    continue;
+                };
+                if self.write_denied(tool_ctx, &path).is_some() {
+                    changeset.skipped.push(path);
+                    continue;
+                }
+                changeset.files.push(FileRename {
+                    path,
+                    before: content,
+                    after,
+                    occurrences,
+                });
+            }
+        }
+        if p.scope.code() {
+            changeset.blocks = plan_code_blocks(self.cache.block_store(), &pattern, new);
+        }
+
+        if changeset.is_empty() {
+            let mut msg = format!("No occurrences of `{}` to rename.", old);
+            if !changeset.skipped.is_empty() {
+                msg.push_str(&format!(" Not writable: {}", changeset.skipped.join(", ")));
+            }
+            return ExecResult::success(msg);
+        }
+
+        let rendered = changeset.render();
+        let store = self.cache.block_store();
+        let after = store.last_block_id(tool_ctx.context_id);
+        let block_id = match store.insert_block_as(
+            tool_ctx.context_id,
+            None,
+            after.as_ref(),
+            Role::Tool,
+            BlockKind::Text,
+            rendered.as_str(),
+            Status::Pending,
+            ContentType::Markdown,
+            Some(tool_ctx.principal_id),
+        ) {
+            Ok(id) => id,
+            Err(e) => return ExecResult::failure(1, format!("failed to post changeset: {}", e)),
+        };
+        let summary = changeset.summary();
+        self.changesets.lock().insert(block_id, changeset);
+
+        let key = block_id.to_key();
+        ExecResult::success(format!(
+            "Planned rename `{}` → `{}`: {}. Nothing is written yet — review changeset {}, \
+             then call refactor_rename with apply=\"{}\".\n\n{}",
+            old, new, summary, key, key, rendered
+        ))
+    }
+
+    /// Write a planned changeset, all or nothing: every target must still
+    /// hold the text it was planned from.
+    async fn refactor_apply(&self, id: &str, tool_ctx: &ExecContext) -> ExecResult {
+        let store = self.cache.block_store();
+        let block_id = match store.resolve_block(id) {
+            Ok(id) => id,
+            Err(e) => return ExecResult::failure(1, format!("invalid changeset id '{}': {}", id, e)),
+        };
+        if block_id.context_id != tool_ctx.context_id {
+            return ExecResult::failure(1, format!("changeset {} belongs to another context", id));
+        }
+        let Some(changeset) = self.changesets.lock().remove(&block_id) else {
+            return ExecResult::failure(
+                1,
+                format!("no pending changeset {} (already applied, or planned before a restart)", id),
+            );
+        };
+        let fail = |msg: String| {
+            let _ = store.set_status(block_id.context_id, &block_id, Status::Error);
+            ExecResult::failure(1, msg)
+        };
+
+        let mut stale = Vec::new();
+        for file in &changeset.files {
+            if let Some(denied) = self.write_denied(tool_ctx, &file.path) {
+                let _ = store.set_status(block_id.context_id, &block_id, Status::Error);
+                return denied;
+            }
+            match self.cache.try_read_content(&file.path).await {
+                Ok(current) if current == file.before => {}
+                _ => stale.push(file.path.clone()),
+            }
+        }
+        for block in &changeset.blocks {
+            match store.get_block_snapshot(block.document_id, &block.block_id) {
+                Ok(Some(snap)) if snap.content == block.before => {}
+                _ => stale.push(format!("code block {}", block.block_id.to_key())),
+            }
+        }
+        if !stale.is_empty() {
+            return fail(format!(
+                "changeset {} is stale — changed since it was planned: {}. Plan the rename again.",
+                id,
+                stale.join(", ")
+            ));
+        }
+
+        for block in &changeset.blocks {
+            if let Err(e) = store.edit_text_as(
+                block.document_id,
+                &block.block_id,
+                0,
+                &block.after,
+                block.before.chars().count(),
+                Some(tool_ctx.principal_id),
+            ) {
+                return fail(format!("renaming in code block {}: {}", block.block_id.to_key(), e));
+            }
+        }
+        for file in &changeset.files {
+            if let Err(e) = self.cache.create_or_replace(&file.path, &file.after).await {
+                return fail(format!("renaming in {}: {}", file.path, e));
+            }
+            self.cache.mark_dirty(&file.path);
+            if let Err(e) = self.cache.flush_one(&file.path).await {
+                return fail(format!("renamed in CRDT but failed to flush {}: {}", file.path, e));
+            }
+        }
+        let _ = store.set_status(block_id.context_id, &block_id, Status::Done);
+
+        ExecResult::success(format!(
+            "Applied rename `{}` → `{}`: {}.",
+            changeset.old,
+            changeset.new,
+            changeset.summary()
+        ))
+    }
+
     async fn apply_edit_plan(&self, p: EditParams, path: String, _tool_ctx: &ExecContext) -> ExecResult {
         match (&p.anchor, &p.old_string) {
             (Some(_), Some(_)) => {
@@ -583,6 +954,9 @@ impl FileToolsServer {
 
 // ── Private Helpers for Read/Edit ──────────────────────────────────────────
 
+/// Files larger than this are left out of a rename (as `grep` skips them).
+const MAX_RENAME_FILE_SIZE: usize = 1_000_000;
+
 const DEFAULT_LINE_LIMIT: u32 = 2000;
 const MAX_LINE_CHARS: usize = 2000;
 
@@ -865,13 +1239,13 @@ fn extract_context(content: &str, pos: usize, match_len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::block_store::{shared_block_store, shared_block_store_with_db, DocumentKind};
+    use crate::block_store::{shared_block_store, shared_block_store_with_db, DocumentKind, SharedBlockStore};
     use crate::file_tools::FileDocumentCache;
     use crate::kernel_db::KernelDb;
     use crate::mcp::{Broker, InstancePolicy, ToolContent};
     use crate::vfs::backends::MemoryBackend;
     use crate::vfs::MountTable;
-    use kaijutsu_types::PrincipalId;
+    use kaijutsu_types::{ContextId, PrincipalId};
 
     async fn broker_with_file(path: &str, content: &str) -> (Arc<Broker>, Arc<FileDocumentCache>) {
         let blocks = shared_block_store(PrincipalId::system());
@@ -890,6 +1264,15 @@ mod tests {
     }
 
     async fn call(broker: &Broker, tool: &str, args: serde_json::Value) -> KernelToolResult {
+        call_in(broker, &CallContext::test(), tool, args).await
+    }
+
+    async fn call_in(
+        broker: &Broker,
+        ctx: &CallContext,
+        tool: &str,
+        args: serde_json::Value,
+    ) -> KernelToolResult {
         broker
             .call_tool(
                 KernelCallParams {
@@ -897,7 +1280,7 @@ mod tests {
                     tool: tool.to_string(),
                     arguments: args,
                 },
-                &CallContext::test(),
+                ctx,
                 CancellationToken::new(),
             )
             .await
@@ -985,6 +1368,87 @@ mod tests {
         assert_eq!(cache.read_content(path).await.unwrap(), "one\ntwo\nthree\n");
     }
 
+    #[tokio::test]
+    async fn code_import_export_round_trips_via_broker() {
+        let src = "use std::fmt;\n\nfn a() {}\n\nfn b() {}\n";
+        let (broker, cache) = broker_with_file("/tmp/lib.rs", src).await;
+
+        let res = call(&broker, "code_import", serde_json::json!({ "path": "/tmp/lib.rs" })).await;
+        let out = text_of(&res);
+        assert!(!res.is_error, "import failed: {out}");
+        assert!(out.contains("(3 blocks)"), "got: {out}");
+        let doc_id = out
+            .split_whitespace()
+            .skip_while(|w| *w != "document")
+            .nth(1)
+            .expect("document id in output")
+            .to_string();
+
+        let res = call(
+            &broker,
+            "code_export",
+            serde_json::json!({ "document_id": doc_id, "path": "/tmp/out.rs" }),
+        )
+        .await;
+        assert!(!res.is_error, "export failed: {}", text_of(&res));
+        assert_eq!(cache.read_content("/tmp/out.rs").await.unwrap(), src);
+    }
+
+    /// The changeset ID a `refactor_rename` plan asks to be applied.
+    fn changeset_key(out: &str) -> String {
+        out.split("apply=\"").nth(1).and_then(|s| s.split('"').next()).expect("changeset id").to_string()
+    }
+
+    fn code_of(store: &SharedBlockStore, doc_id: ContextId) -> String {
+        store.block_snapshots(doc_id).unwrap().iter().map(|b| b.content.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn refactor_rename_plans_then_applies_via_broker() {
+        let (broker, cache) = broker_with_file("/tmp/util.rs", "pub use crate::helper;\n").await;
+        cache.mark_dirty("/tmp/util.rs");
+        cache.flush_one("/tmp/util.rs").await.unwrap();
+        let store = cache.block_store();
+        let src = "fn helper() {}\n\nfn main() {\n    helper();\n}\n";
+        let (doc_id, _) = import_code_document(store, src, CodeLanguage::Rust).unwrap();
+        let ctx = CallContext::test();
+        store.create_document(ctx.context_id, DocumentKind::Conversation, None).unwrap();
+
+        let plan = serde_json::json!({ "old": "helper", "new": "assist", "path": "/tmp" });
+        let res = call_in(&broker, &ctx, "refactor_rename", plan).await;
+        let out = text_of(&res);
+        assert!(!res.is_error, "plan failed: {out}");
+        assert!(out.contains("3 occurrences in 1 file and 2 code blocks"), "got: {out}");
+        assert_eq!(cache.read_content("/tmp/util.rs").await.unwrap(), "pub use crate::helper;\n");
+        let key = changeset_key(&out);
+
+        // Another context cannot apply it.
+        let res = call(&broker, "refactor_rename", serde_json::json!({ "apply": key })).await;
+        assert!(res.is_error);
+
+        let res = call_in(&broker, &ctx, "refactor_rename", serde_json::json!({ "apply": key })).await;
+        assert!(!res.is_error, "apply failed: {}", text_of(&res));
+        assert_eq!(cache.read_content("/tmp/util.rs").await.unwrap(), "pub use crate::assist;\n");
+        assert_eq!(code_of(store, doc_id), "fn assist() {}\n\nfn main() {\n    assist();\n}\n");
+        let block_id = store.resolve_block(&key).unwrap();
+        let changeset = store.get_block_snapshot(ctx.context_id, &block_id).unwrap().unwrap();
+        assert_eq!(changeset.status, Status::Done);
+
+        // Applied once only.
+        let res = call_in(&broker, &ctx, "refactor_rename", serde_json::json!({ "apply": key })).await;
+        assert!(res.is_error);
+
+        // A target edited after planning makes the whole changeset stale.
+        let plan = serde_json::json!({ "old": "assist", "new": "aid", "path": "/tmp" });
+        let out = text_of(&call_in(&broker, &ctx, "refactor_rename", plan).await);
+        let key = changeset_key(&out);
+        cache.create_or_replace("/tmp/util.rs", "pub use crate::assist as a;\n").await.unwrap();
+        let res = call_in(&broker, &ctx, "refactor_rename", serde_json::json!({ "apply": key })).await;
+        assert!(res.is_error);
+        assert!(text_of(&res).contains("stale"), "got: {}", text_of(&res));
+        assert!(code_of(store, doc_id).contains("assist"), "no code block was renamed");
+    }
+
     #[tokio::test]
     async fn glob_via_broker() {
         let db = Arc::new(parking_lot::Mutex::new(KernelDb::in_memory().unwrap()));
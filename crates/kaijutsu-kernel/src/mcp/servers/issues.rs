@@ -0,0 +1,138 @@
+//! `IssueToolsServer` — files block subtrees into external issue trackers.
+//!
+//! One tool, `issue_create { block_id, tracker?, title?, issue?, labels?,
+//! max_depth? }`: export the block and its descendants as a new GitHub or
+//! GitLab issue, or as a comment on `issue`. Trackers and credentials come
+//! from the kernel's `issues.toml`; the engine lives in [`crate::issues`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::block_store::SharedBlockStore;
+use crate::issues::{ExportRequest, IssueExporter};
+
+use super::super::context::CallContext;
+use super::super::error::{McpError, McpResult};
+use super::super::server_like::{McpServerLike, ServerNotification};
+use super::super::types::{InstanceId, KernelCallParams, KernelTool, KernelToolResult};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct IssueCreateParams {
+    /// Root of the subtree to export: a full key, a block label, or a unique
+    /// abbreviation (`ctx@principal#seq` with short ids, or a key prefix).
+    pub block_id: String,
+    /// Tracker name from issues.toml. May be omitted when exactly one
+    /// tracker is enabled.
+    #[serde(default)]
+    pub tracker: Option<String>,
+    /// Title for a new issue. Defaults to the first line of the subtree's
+    /// first text block.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Comment on this existing issue number instead of opening a new one.
+    #[serde(default)]
+    pub issue: Option<u64>,
+    /// Labels added to the tracker's configured ones (new issues only).
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Levels below the block to include. Omit for the whole subtree.
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+}
+
+pub struct IssueToolsServer {
+    instance_id: InstanceId,
+    documents: SharedBlockStore,
+    exporter: Arc<IssueExporter>,
+    notif_tx: broadcast::Sender<ServerNotification>,
+}
+
+impl IssueToolsServer {
+    pub const INSTANCE: &'static str = crate::issues::ISSUES_INSTANCE;
+
+    pub fn new(documents: SharedBlockStore) -> Self {
+        let (notif_tx, _) = broadcast::channel(16);
+        Self {
+            instance_id: InstanceId::new(Self::INSTANCE),
+            exporter: Arc::new(IssueExporter::new(documents.clone())),
+            documents,
+            notif_tx,
+        }
+    }
+}
+
+#[async_trait]
+impl McpServerLike for IssueToolsServer {
+    fn instance_id(&self) -> &InstanceId {
+        &self.instance_id
+    }
+
+    async fn list_tools(&self, _ctx: &CallContext) -> McpResult<Vec<KernelTool>> {
+        Ok(vec![KernelTool {
+            instance: self.instance_id.clone(),
+            name: "issue_create".to_string(),
+            description: Some(
+                "File a block and its replies as a GitHub/GitLab issue (or a comment on one) and record the URL in this context"
+                    .to_string(),
+            ),
+            input_schema: serde_json::to_value(schemars::schema_for!(IssueCreateParams))
+                .map_err(McpError::InvalidParams)?,
+        }])
+    }
+
+    async fn call_tool(
+        &self,
+        params: KernelCallParams,
+        ctx: &CallContext,
+        _cancel: CancellationToken,
+    ) -> McpResult<KernelToolResult> {
+        match params.tool.as_str() {
+            "issue_create" => {
+                let parsed: IssueCreateParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                let root = match self.documents.resolve_block(&parsed.block_id) {
+                    Ok(id) => id,
+                    Err(e) => {
+                        return Ok(KernelToolResult::error_text(format!(
+                            "invalid block_id: {e}"
+                        )));
+                    }
+                };
+                let request = ExportRequest {
+                    root,
+                    tracker: parsed.tracker,
+                    title: parsed.title,
+                    issue: parsed.issue,
+                    labels: parsed.labels,
+                    max_depth: parsed.max_depth,
+                };
+                // Config, tracker, and network failures are all things the
+                // caller can act on, so they go back as tool errors.
+                match self.exporter.export(&request, ctx.principal_id).await {
+                    Ok(created) => Ok(KernelToolResult {
+                        is_error: false,
+                        content: vec![],
+                        structured: Some(
+                            serde_json::to_value(&created).map_err(McpError::InvalidParams)?,
+                        ),
+                    }),
+                    Err(e) => Ok(KernelToolResult::error_text(e.to_string())),
+                }
+            }
+            _ => Err(McpError::ToolNotFound {
+                instance: self.instance_id.clone(),
+                tool: params.tool,
+            }),
+        }
+    }
+
+    fn notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        self.notif_tx.subscribe()
+    }
+}
@@ -0,0 +1,197 @@
+//! `ContextKvServer` — per-context key-value scratch store for agents.
+//!
+//! Three tools, all scoped to the calling context (`CallContext::context_id`),
+//! so every seat in a context sees the same keys:
+//! - `kv_get { key }` — the value plus who wrote it and when, or `found: false`.
+//! - `kv_set { key, value }` — write any JSON value; `null` deletes the key.
+//! - `kv_list { prefix? }` — entries sorted by key, optionally filtered.
+//!
+//! Storage and limits live in [`crate::context_kv`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::context_kv::{ContextKvError, ContextKvStore, KvEntry};
+
+use super::super::context::CallContext;
+use super::super::error::{McpError, McpResult};
+use super::super::server_like::{McpServerLike, ServerNotification};
+use super::super::types::{InstanceId, KernelCallParams, KernelTool, KernelToolResult};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct KvGetParams {
+    /// Key to read.
+    pub key: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct KvSetParams {
+    /// Key to write (no whitespace, at most 128 bytes).
+    pub key: String,
+    /// Any JSON value. `null` deletes the key.
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct KvListParams {
+    /// Only return keys starting with this prefix.
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+pub struct ContextKvServer {
+    instance_id: InstanceId,
+    store: Arc<ContextKvStore>,
+    notif_tx: broadcast::Sender<ServerNotification>,
+}
+
+impl ContextKvServer {
+    pub const INSTANCE: &'static str = "builtin.kv";
+
+    pub fn new(store: Arc<ContextKvStore>) -> Self {
+        let (notif_tx, _) = broadcast::channel(16);
+        Self {
+            instance_id: InstanceId::new(Self::INSTANCE),
+            store,
+            notif_tx,
+        }
+    }
+}
+
+fn entry_json(entry: &KvEntry) -> serde_json::Value {
+    serde_json::json!({
+        "key": entry.key,
+        "value": entry.value,
+        "updated_by": entry.updated_by.short(),
+        "updated_at": entry.updated_at,
+    })
+}
+
+fn structured(payload: serde_json::Value) -> KernelToolResult {
+    KernelToolResult {
+        is_error: false,
+        content: vec![],
+        structured: Some(payload),
+    }
+}
+
+/// Caller mistakes (bad key, too big, full) go back to the model as tool
+/// errors; storage failures are protocol errors.
+fn kv_failure(tool: &str, e: ContextKvError) -> McpResult<KernelToolResult> {
+    match e {
+        ContextKvError::InvalidKey(..)
+        | ContextKvError::ValueTooLarge { .. }
+        | ContextKvError::TooManyKeys => Ok(KernelToolResult::error_text(e.to_string())),
+        other => Err(McpError::Protocol(format!("{tool}: {other}"))),
+    }
+}
+
+#[async_trait]
+impl McpServerLike for ContextKvServer {
+    fn instance_id(&self) -> &InstanceId {
+        &self.instance_id
+    }
+
+    async fn list_tools(&self, _ctx: &CallContext) -> McpResult<Vec<KernelTool>> {
+        Ok(vec![
+            KernelTool {
+                instance: self.instance_id.clone(),
+                name: "kv_get".to_string(),
+                description: Some(
+                    "Read a key from this context's shared key-value scratch store".to_string(),
+                ),
+                input_schema: serde_json::to_value(schemars::schema_for!(KvGetParams))
+                    .map_err(McpError::InvalidParams)?,
+            },
+            KernelTool {
+                instance: self.instance_id.clone(),
+                name: "kv_set".to_string(),
+                description: Some(
+                    "Write a JSON value to this context's shared key-value scratch store (null deletes)"
+                        .to_string(),
+                ),
+                input_schema: serde_json::to_value(schemars::schema_for!(KvSetParams))
+                    .map_err(McpError::InvalidParams)?,
+            },
+            KernelTool {
+                instance: self.instance_id.clone(),
+                name: "kv_list".to_string(),
+                description: Some(
+                    "List entries in this context's shared key-value scratch store".to_string(),
+                ),
+                input_schema: serde_json::to_value(schemars::schema_for!(KvListParams))
+                    .map_err(McpError::InvalidParams)?,
+            },
+        ])
+    }
+
+    async fn call_tool(
+        &self,
+        params: KernelCallParams,
+        ctx: &CallContext,
+        _cancel: CancellationToken,
+    ) -> McpResult<KernelToolResult> {
+        match params.tool.as_str() {
+            "kv_get" => {
+                let parsed: KvGetParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                match self.store.get(ctx.context_id, &parsed.key) {
+                    Ok(Some(entry)) => {
+                        let mut payload = entry_json(&entry);
+                        payload["found"] = serde_json::Value::Bool(true);
+                        Ok(structured(payload))
+                    }
+                    Ok(None) => Ok(structured(serde_json::json!({
+                        "key": parsed.key,
+                        "found": false,
+                    }))),
+                    Err(e) => kv_failure("kv_get", e),
+                }
+            }
+            "kv_set" => {
+                let parsed: KvSetParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                let result = if parsed.value.is_null() {
+                    self.store
+                        .delete(ctx.context_id, &parsed.key)
+                        .map(|removed| serde_json::json!({ "key": parsed.key, "deleted": removed }))
+                } else {
+                    self.store
+                        .set(ctx.context_id, &parsed.key, &parsed.value, ctx.principal_id)
+                        .map(|()| serde_json::json!({ "key": parsed.key, "ok": true }))
+                };
+                match result {
+                    Ok(payload) => Ok(structured(payload)),
+                    Err(e) => kv_failure("kv_set", e),
+                }
+            }
+            "kv_list" => {
+                let parsed: KvListParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                let prefix = parsed.prefix.unwrap_or_default();
+                match self.store.list(ctx.context_id, &prefix) {
+                    Ok(entries) => Ok(structured(serde_json::json!({
+                        "entries": entries.iter().map(entry_json).collect::<Vec<_>>(),
+                    }))),
+                    Err(e) => kv_failure("kv_list", e),
+                }
+            }
+            _ => Err(McpError::ToolNotFound {
+                instance: self.instance_id.clone(),
+                tool: params.tool,
+            }),
+        }
+    }
+
+    fn notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        self.notif_tx.subscribe()
+    }
+}
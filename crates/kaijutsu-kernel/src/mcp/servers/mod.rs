@@ -18,19 +18,27 @@ pub mod block;
 pub mod external;
 pub mod file;
 pub mod hooks_builtin;
+pub mod issues;
 pub mod kernel_info;
+pub mod kv;
 pub mod policy_admin;
+pub mod repl;
 pub mod resources_builtin;
 pub mod shell;
+pub mod tool_presets;
 pub mod tool_search;
 
 pub use bindings_builtin::BuiltinBindingsServer;
 pub use block::BlockToolsServer;
-pub use external::ExternalMcpServer;
+pub use external::{ExternalMcpServer, McpServerConfig, McpTransport};
 pub use file::FileToolsServer;
 pub use hooks_builtin::BuiltinHooksServer;
+pub use issues::IssueToolsServer;
 pub use kernel_info::KernelInfoServer;
+pub use kv::ContextKvServer;
 pub use policy_admin::BuiltinPolicyServer;
+pub use repl::ReplServer;
 pub use resources_builtin::BuiltinResourcesServer;
 pub use shell::ShellServer;
+pub use tool_presets::ToolPresetsServer;
 pub use tool_search::BuiltinToolSearchServer;
@@ -0,0 +1,337 @@
+//! `ReplServer` — interactive REPL blocks (`builtin.repl`).
+//!
+//! Three tools over [`crate::repl::ReplManager`]:
+//!
+//! - `repl_start { language }` opens a `Repl` session block at the end of the
+//!   calling context, backed by a persistent `python`/`node` process or the
+//!   context's kaish.
+//! - `repl_send { block_id, input, timeout_ms?, interactive? }` appends input
+//!   to the session block, sends it, and returns the output once it settles.
+//! - `repl_stop { block_id }` shuts the session down.
+//!
+//! Host interpreters run arbitrary code outside kaish, so they need the
+//! context's `exec` authority, and a context with a sandbox profile gets
+//! kaish only: the confined launcher runs single commands, not long-lived
+//! processes.
+
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use kaijutsu_types::{ReplLanguage, Role};
+
+use crate::block_store::SharedBlockStore;
+use crate::repl::{ReplBackend, ReplManager};
+
+use super::super::broker::Broker;
+use super::super::context::CallContext;
+use super::super::error::{McpError, McpResult};
+use super::super::server_like::{McpServerLike, ServerNotification};
+use super::super::types::{
+    InstanceId, KernelCallParams, KernelTool, KernelToolResult, ToolContent,
+};
+
+/// Default wait for output to settle in `repl_send`.
+const DEFAULT_SEND_TIMEOUT_MS: u64 = 10_000;
+
+/// Longest `repl_send` wait a caller may ask for.
+const MAX_SEND_TIMEOUT_MS: u64 = 120_000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReplStartParams {
+    /// Interpreter: `python`, `node`, or `kaish`.
+    pub language: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReplSendParams {
+    /// The session block `repl_start` returned.
+    pub block_id: String,
+    /// Code to send. Sent as lines; a trailing newline is added if missing.
+    pub input: String,
+    /// How long to wait for output to go quiet (default 10000, max 120000).
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Input a person typed (the app's REPL cell) rather than an agent's.
+    /// Its output is then shown to the model as a user message.
+    #[serde(default)]
+    pub interactive: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReplStopParams {
+    /// The session block to shut down.
+    pub block_id: String,
+}
+
+pub struct ReplServer {
+    instance_id: InstanceId,
+    broker: Weak<Broker>,
+    documents: SharedBlockStore,
+    manager: Arc<ReplManager>,
+    notif_tx: broadcast::Sender<ServerNotification>,
+}
+
+impl ReplServer {
+    pub const INSTANCE: &'static str = "builtin.repl";
+
+    /// Build the server and spawn its manager's block watcher and idle
+    /// reaper.
+    pub fn new(broker: Weak<Broker>, documents: SharedBlockStore) -> Self {
+        let (notif_tx, _) = broadcast::channel(16);
+        let manager = ReplManager::new(documents.clone());
+        manager.spawn_tasks();
+        Self {
+            instance_id: InstanceId::new(Self::INSTANCE),
+            broker,
+            documents,
+            manager,
+            notif_tx,
+        }
+    }
+
+    fn broker(&self) -> McpResult<Arc<Broker>> {
+        self.broker.upgrade().ok_or_else(|| McpError::InstanceDown {
+            instance: self.instance_id.clone(),
+            reason: "broker dropped".to_string(),
+        })
+    }
+
+    async fn start(
+        &self,
+        params: ReplStartParams,
+        ctx: &CallContext,
+    ) -> McpResult<KernelToolResult> {
+        let Some(language) = ReplLanguage::parse(&params.language) else {
+            return Ok(KernelToolResult::error_text(format!(
+                "unknown language '{}' (expected python, node, or kaish)",
+                params.language
+            )));
+        };
+        let broker = self.broker()?;
+        broker
+            .budgets()
+            .admit_shell(ctx.context_id, std::time::Instant::now())?;
+        let dispatcher = broker
+            .kj_dispatcher()
+            .await
+            .ok_or_else(|| McpError::InstanceDown {
+                instance: self.instance_id.clone(),
+                reason: "kj dispatcher not wired (Broker::set_kj_dispatcher)".to_string(),
+            })?;
+
+        let backend = if language.is_host_process() {
+            let exec = broker
+                .binding(&ctx.context_id)
+                .await
+                .is_some_and(|b| b.allows(&crate::mcp::Capability::Exec));
+            if !exec {
+                return Ok(KernelToolResult::error_text(format!(
+                    "a {language} REPL runs host code and needs the exec authority; \
+                     use language kaish"
+                )));
+            }
+            match dispatcher
+                .kernel_db()
+                .lock()
+                .get_context_sandbox(ctx.context_id)
+            {
+                Ok(None) => {}
+                Ok(Some(_)) => {
+                    return Ok(KernelToolResult::error_text(
+                        "this context is sandboxed; only kaish REPLs are available",
+                    ));
+                }
+                // An unreadable profile must not fall open to unconfined
+                // exec.
+                Err(e) => {
+                    return Ok(KernelToolResult::error_text(format!(
+                        "failed to read sandbox profile: {e}"
+                    )));
+                }
+            }
+            ReplBackend::Host {
+                path: dispatcher.kernel().host_path().map(str::to_string),
+                cwd: ctx.cwd.clone(),
+            }
+        } else {
+            let kaish = dispatcher
+                .materialize_context_kaish(
+                    "repl",
+                    ctx.principal_id,
+                    ctx.context_id,
+                    ctx.session_id,
+                    dispatcher.semantic_index(),
+                    dispatcher.block_source(),
+                )
+                .await
+                .map_err(|e| McpError::Protocol(format!("materialize context shell: {e}")))?;
+            ReplBackend::Kaish(Box::new(kaish))
+        };
+
+        match self
+            .manager
+            .start(ctx.context_id, language, backend, ctx.principal_id)
+            .await
+        {
+            Ok(block_id) => Ok(KernelToolResult {
+                is_error: false,
+                content: vec![ToolContent::Text(format!(
+                    "{language} REPL started: {}",
+                    block_id.to_key()
+                ))],
+                structured: Some(serde_json::json!({
+                    "block_id": block_id.to_key(),
+                    "language": language,
+                })),
+            }),
+            Err(e) => Ok(KernelToolResult::error_text(e.to_string())),
+        }
+    }
+
+    async fn send(&self, params: ReplSendParams, ctx: &CallContext) -> McpResult<KernelToolResult> {
+        let block_id = match self.documents.resolve_block(&params.block_id) {
+            Ok(id) => id,
+            Err(e) => {
+                return Ok(KernelToolResult::error_text(format!(
+                    "invalid block_id: {e}"
+                )));
+            }
+        };
+        self.broker()?
+            .budgets()
+            .admit_shell(ctx.context_id, std::time::Instant::now())?;
+        let timeout = Duration::from_millis(
+            params
+                .timeout_ms
+                .unwrap_or(DEFAULT_SEND_TIMEOUT_MS)
+                .min(MAX_SEND_TIMEOUT_MS),
+        );
+        let role = if params.interactive {
+            Role::User
+        } else {
+            Role::Tool
+        };
+        match self
+            .manager
+            .send(&block_id, &params.input, role, ctx.principal_id, timeout)
+            .await
+        {
+            Ok(out) => {
+                let mut body = out.transcript.clone();
+                if !out.settled && out.alive {
+                    body.push_str("[still running]\n");
+                }
+                if !out.alive {
+                    body.push_str("[session ended]\n");
+                }
+                Ok(KernelToolResult {
+                    is_error: false,
+                    content: vec![ToolContent::Text(body)],
+                    structured: Some(serde_json::json!({
+                        "output_block_id": out.block_id.to_key(),
+                        "transcript": out.transcript,
+                        "settled": out.settled,
+                        "alive": out.alive,
+                    })),
+                })
+            }
+            Err(e) => Ok(KernelToolResult::error_text(e.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl McpServerLike for ReplServer {
+    fn instance_id(&self) -> &InstanceId {
+        &self.instance_id
+    }
+
+    async fn list_tools(&self, _ctx: &CallContext) -> McpResult<Vec<KernelTool>> {
+        let tool = |name: &str, description: &str, schema: schemars::Schema| -> McpResult<_> {
+            Ok(KernelTool {
+                instance: self.instance_id.clone(),
+                name: name.to_string(),
+                description: Some(description.to_string()),
+                input_schema: serde_json::to_value(schema).map_err(McpError::InvalidParams)?,
+            })
+        };
+        Ok(vec![
+            tool(
+                "repl_start",
+                "Start an interactive REPL (python, node, or kaish) as a block in this context; state persists between repl_send calls",
+                schemars::schema_for!(ReplStartParams),
+            )?,
+            tool(
+                "repl_send",
+                "Send code to a REPL started with repl_start and return its output once it settles",
+                schemars::schema_for!(ReplSendParams),
+            )?,
+            tool(
+                "repl_stop",
+                "Shut down a REPL session",
+                schemars::schema_for!(ReplStopParams),
+            )?,
+        ])
+    }
+
+    async fn call_tool(
+        &self,
+        params: KernelCallParams,
+        ctx: &CallContext,
+        _cancel: CancellationToken,
+    ) -> McpResult<KernelToolResult> {
+        match params.tool.as_str() {
+            "repl_start" => {
+                let parsed: ReplStartParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                self.start(parsed, ctx).await
+            }
+            "repl_send" => {
+                let parsed: ReplSendParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                self.send(parsed, ctx).await
+            }
+            "repl_stop" => {
+                let parsed: ReplStopParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                let block_id = match self.documents.resolve_block(&parsed.block_id) {
+                    Ok(id) => id,
+                    Err(e) => {
+                        return Ok(KernelToolResult::error_text(format!(
+                            "invalid block_id: {e}"
+                        )));
+                    }
+                };
+                if self.manager.stop(&block_id) {
+                    Ok(KernelToolResult {
+                        is_error: false,
+                        content: vec![ToolContent::Text("REPL stopped".to_string())],
+                        structured: None,
+                    })
+                } else {
+                    Ok(KernelToolResult::error_text(format!(
+                        "no live REPL session for block {}",
+                        block_id.to_key()
+                    )))
+                }
+            }
+            _ => Err(McpError::ToolNotFound {
+                instance: self.instance_id.clone(),
+                tool: params.tool,
+            }),
+        }
+    }
+
+    fn notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        self.notif_tx.subscribe()
+    }
+}
@@ -21,6 +21,7 @@
 //! tool; `toolie` (no facade) stays excluded.
 
 use std::sync::{Arc, Weak};
+use std::time::Instant;
 
 use async_trait::async_trait;
 use schemars::JsonSchema;
@@ -196,14 +197,21 @@ impl McpServerLike for ShellServer {
         }
         .map_err(|e| McpError::Protocol(format!("materialize context shell: {e}")))?;
 
+        // The broker already counted this call against the tool budget; the
+        // shell budget is wall time, charged once the command finishes.
+        let budgets = broker.budgets();
+        budgets.admit_shell(ctx.context_id, Instant::now())?;
+
         let mut opts = kaish_kernel::ExecuteOptions::default();
         if let Some(stdin) = parsed.stdin {
             opts = opts.with_stdin(stdin);
         }
-        let result = kaish
-            .execute_with_options(&parsed.command, opts)
-            .await
-            .map_err(|e| McpError::Protocol(format!("shell execution failed: {e}")))?;
+        let started = Instant::now();
+        let result = kaish.execute_with_options(&parsed.command, opts).await;
+        let finished = Instant::now();
+        budgets.record_shell(ctx.context_id, finished, finished - started);
+        let result =
+            result.map_err(|e| McpError::Protocol(format!("shell execution failed: {e}")))?;
 
         Ok(shell_result_to_kernel(result))
     }
@@ -0,0 +1,238 @@
+//! `ToolPresetsServer` — manage the calling context's tool parameter presets.
+//!
+//! Three tools, all scoped to the calling context (`CallContext::context_id`):
+//! - `tool_presets_list {}` — every preset, by visible tool name.
+//! - `tool_presets_set { tool, params }` — merge `params` into the tool's
+//!   preset; a `null` value drops that key. Keys must be parameters the
+//!   tool declares.
+//! - `tool_presets_clear { tool }` — drop the tool's preset.
+//!
+//! `tool` is the name as the context sees it in its tool list. The presets
+//! themselves, and how the broker applies them, live in
+//! [`crate::tool_presets`].
+
+use std::sync::{Arc, Weak};
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::tool_presets::{ToolPreset, validate_params};
+
+use super::super::broker::Broker;
+use super::super::context::CallContext;
+use super::super::error::{McpError, McpResult};
+use super::super::server_like::{McpServerLike, ServerNotification};
+use super::super::types::{InstanceId, KernelCallParams, KernelTool, KernelToolResult};
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ToolPresetsListParams {}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ToolPresetsSetParams {
+    /// Tool name as it appears in this context's tool list.
+    pub tool: String,
+    /// Default arguments to merge into the preset. `null` drops a key.
+    pub params: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ToolPresetsClearParams {
+    /// Tool name as it appears in this context's tool list.
+    pub tool: String,
+}
+
+pub struct ToolPresetsServer {
+    instance_id: InstanceId,
+    broker: Weak<Broker>,
+    notif_tx: broadcast::Sender<ServerNotification>,
+}
+
+impl ToolPresetsServer {
+    pub const INSTANCE: &'static str = "builtin.tool_presets";
+
+    pub fn new(broker: Weak<Broker>) -> Self {
+        let (notif_tx, _) = broadcast::channel(16);
+        Self {
+            instance_id: InstanceId::new(Self::INSTANCE),
+            broker,
+            notif_tx,
+        }
+    }
+
+    fn broker(&self) -> McpResult<Arc<Broker>> {
+        self.broker.upgrade().ok_or_else(|| McpError::InstanceDown {
+            instance: self.instance_id.clone(),
+            reason: "broker dropped".to_string(),
+        })
+    }
+}
+
+fn structured(payload: Value) -> KernelToolResult {
+    KernelToolResult {
+        is_error: false,
+        content: vec![],
+        structured: Some(payload),
+    }
+}
+
+fn preset_json(name: &str, preset: &ToolPreset) -> Value {
+    serde_json::json!({
+        "tool": name,
+        "instance": preset.instance,
+        "params": preset.params,
+        "updated_by": preset.updated_by.short(),
+        "updated_at": preset.updated_at,
+    })
+}
+
+/// The visible tool named `name` in the calling context.
+async fn visible_tool(
+    broker: &Broker,
+    ctx: &CallContext,
+    name: &str,
+) -> McpResult<Option<KernelTool>> {
+    Ok(broker
+        .list_visible_tools(ctx.context_id, ctx)
+        .await?
+        .into_iter()
+        .find(|(visible, _)| visible == name)
+        .map(|(_, tool)| tool))
+}
+
+fn unknown_tool(name: &str) -> KernelToolResult {
+    KernelToolResult::error_text(format!("no tool named '{name}' in this context"))
+}
+
+#[async_trait]
+impl McpServerLike for ToolPresetsServer {
+    fn instance_id(&self) -> &InstanceId {
+        &self.instance_id
+    }
+
+    async fn list_tools(&self, _ctx: &CallContext) -> McpResult<Vec<KernelTool>> {
+        Ok(vec![
+            KernelTool {
+                instance: self.instance_id.clone(),
+                name: "tool_presets_list".to_string(),
+                description: Some(
+                    "List this context's tool parameter presets (defaults filled into omitted arguments)"
+                        .to_string(),
+                ),
+                input_schema: serde_json::to_value(schemars::schema_for!(ToolPresetsListParams))
+                    .map_err(McpError::InvalidParams)?,
+            },
+            KernelTool {
+                instance: self.instance_id.clone(),
+                name: "tool_presets_set".to_string(),
+                description: Some(
+                    "Set default arguments for a tool in this context; calls that omit them get the preset (null drops a key)"
+                        .to_string(),
+                ),
+                input_schema: serde_json::to_value(schemars::schema_for!(ToolPresetsSetParams))
+                    .map_err(McpError::InvalidParams)?,
+            },
+            KernelTool {
+                instance: self.instance_id.clone(),
+                name: "tool_presets_clear".to_string(),
+                description: Some("Drop a tool's parameter preset in this context".to_string()),
+                input_schema: serde_json::to_value(schemars::schema_for!(ToolPresetsClearParams))
+                    .map_err(McpError::InvalidParams)?,
+            },
+        ])
+    }
+
+    async fn call_tool(
+        &self,
+        params: KernelCallParams,
+        ctx: &CallContext,
+        _cancel: CancellationToken,
+    ) -> McpResult<KernelToolResult> {
+        let broker = self.broker()?;
+        let presets = broker.tool_presets();
+        match params.tool.as_str() {
+            "tool_presets_list" => {
+                let _: ToolPresetsListParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                let visible = broker.list_visible_tools(ctx.context_id, ctx).await?;
+                let entries: Vec<Value> = presets
+                    .list(ctx.context_id)
+                    .iter()
+                    .map(|preset| {
+                        let name = visible
+                            .iter()
+                            .find(|(_, t)| t.instance == preset.instance && t.name == preset.tool)
+                            .map(|(name, _)| name.clone())
+                            .unwrap_or_else(|| format!("{}:{}", preset.instance, preset.tool));
+                        preset_json(&name, preset)
+                    })
+                    .collect();
+                Ok(structured(serde_json::json!({ "presets": entries })))
+            }
+            "tool_presets_set" => {
+                let parsed: ToolPresetsSetParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                let Some(tool) = visible_tool(&broker, ctx, &parsed.tool).await? else {
+                    return Ok(unknown_tool(&parsed.tool));
+                };
+                let mut merged = presets
+                    .get(ctx.context_id, &tool.instance, &tool.name)
+                    .map(|p| p.params)
+                    .unwrap_or_default();
+                for (key, value) in parsed.params {
+                    if value.is_null() {
+                        merged.remove(&key);
+                    } else {
+                        merged.insert(key, value);
+                    }
+                }
+                if let Err(e) = validate_params(&merged, &tool.input_schema) {
+                    return Ok(KernelToolResult::error_text(format!(
+                        "{}: {e}",
+                        parsed.tool
+                    )));
+                }
+                let preset = ToolPreset {
+                    instance: tool.instance,
+                    tool: tool.name,
+                    params: merged,
+                    updated_by: ctx.principal_id,
+                    updated_at: kaijutsu_types::now_millis(),
+                };
+                let payload = preset_json(&parsed.tool, &preset);
+                presets
+                    .set(ctx.context_id, preset)
+                    .map_err(|e| McpError::Protocol(format!("tool_presets_set: {e}")))?;
+                Ok(structured(payload))
+            }
+            "tool_presets_clear" => {
+                let parsed: ToolPresetsClearParams =
+                    serde_json::from_value(params.arguments).map_err(McpError::InvalidParams)?;
+                let Some(tool) = visible_tool(&broker, ctx, &parsed.tool).await? else {
+                    return Ok(unknown_tool(&parsed.tool));
+                };
+                let cleared = presets
+                    .clear(ctx.context_id, &tool.instance, &tool.name)
+                    .map_err(|e| McpError::Protocol(format!("tool_presets_clear: {e}")))?;
+                Ok(structured(serde_json::json!({
+                    "tool": parsed.tool,
+                    "cleared": cleared,
+                })))
+            }
+            _ => Err(McpError::ToolNotFound {
+                instance: self.instance_id.clone(),
+                tool: params.tool,
+            }),
+        }
+    }
+
+    fn notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        self.notif_tx.subscribe()
+    }
+}
@@ -0,0 +1,377 @@
+//! Record/replay of tool execution through the broker.
+//!
+//! A [`ToolTrace`] in `Record` mode appends every server call the broker
+//! makes — instance, tool, arguments, the calling environment and the
+//! outcome — to a JSON-lines trace file. In `Replay` mode the broker serves
+//! results from a trace instead of calling the server, so an agent loop can
+//! be rerun against exactly the tool outputs it saw the first time
+//! (reproducible tests, debugging flaky automation).
+//!
+//! Only the server call is recorded or replayed. Capability checks, policy
+//! and hooks still run live around it, since they are kernel logic under test.
+//!
+//! Replay matches on `(instance, tool, arguments)`. Identical calls are served
+//! in recorded order; a call with no recording left fails with
+//! [`McpError::ReplayMiss`] rather than falling through to the server.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::context::CallContext;
+use super::error::{McpError, McpResult};
+use super::types::{InstanceId, KernelCallParams, KernelToolResult, ToolContent};
+
+/// Whether a trace records calls or serves them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolTraceMode {
+    Record,
+    Replay,
+}
+
+/// The environment a call ran in, kept for debugging. Not used for matching:
+/// ids are minted fresh on every run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TraceEnv {
+    pub context_id: String,
+    pub principal_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+}
+
+/// Serialized [`ToolContent`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum TraceContent {
+    Text(String),
+    Json(serde_json::Value),
+}
+
+/// A recorded server outcome.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum TraceOutcome {
+    Ok {
+        is_error: bool,
+        content: Vec<TraceContent>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        structured: Option<serde_json::Value>,
+    },
+    /// The server call failed; replayed as [`McpError::Replayed`].
+    Err { message: String },
+}
+
+/// One line of a trace file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ToolTraceEntry {
+    pub seq: u64,
+    pub instance: InstanceId,
+    pub tool: String,
+    pub arguments: serde_json::Value,
+    pub env: TraceEnv,
+    #[serde(flatten)]
+    pub outcome: TraceOutcome,
+}
+
+impl TraceOutcome {
+    fn from_result(result: &McpResult<KernelToolResult>) -> Self {
+        match result {
+            Ok(r) => Self::Ok {
+                is_error: r.is_error,
+                content: r
+                    .content
+                    .iter()
+                    .map(|c| match c {
+                        ToolContent::Text(s) => TraceContent::Text(s.clone()),
+                        ToolContent::Json(v) => TraceContent::Json(v.clone()),
+                    })
+                    .collect(),
+                structured: r.structured.clone(),
+            },
+            Err(e) => Self::Err {
+                message: e.to_string(),
+            },
+        }
+    }
+
+    fn into_result(self) -> McpResult<KernelToolResult> {
+        match self {
+            Self::Ok {
+                is_error,
+                content,
+                structured,
+            } => Ok(KernelToolResult {
+                is_error,
+                content: content
+                    .into_iter()
+                    .map(|c| match c {
+                        TraceContent::Text(s) => ToolContent::Text(s),
+                        TraceContent::Json(v) => ToolContent::Json(v),
+                    })
+                    .collect(),
+                structured,
+            }),
+            Self::Err { message } => Err(McpError::Replayed(message)),
+        }
+    }
+}
+
+/// Replay lookup key. Arguments are compared in canonical form, so object
+/// key order doesn't matter.
+type ReplayKey = (InstanceId, String, String);
+
+fn replay_key(instance: &InstanceId, tool: &str, arguments: &serde_json::Value) -> ReplayKey {
+    let mut canonical = String::new();
+    write_canonical(arguments, &mut canonical);
+    (instance.clone(), tool.to_string(), canonical)
+}
+
+/// Serialize `value` with object keys sorted at every level.
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+enum TraceState {
+    Record {
+        file: std::fs::File,
+        next_seq: u64,
+    },
+    Replay {
+        pending: HashMap<ReplayKey, VecDeque<TraceOutcome>>,
+    },
+}
+
+/// A trace file being recorded or replayed. Installed on the broker with
+/// [`Broker::set_tool_trace`](super::Broker::set_tool_trace).
+pub struct ToolTrace {
+    path: PathBuf,
+    state: Mutex<TraceState>,
+}
+
+impl std::fmt::Debug for ToolTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolTrace")
+            .field("path", &self.path)
+            .field("mode", &self.mode())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ToolTrace {
+    /// Start recording to `path`, truncating any existing trace.
+    pub fn record(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let file = std::fs::File::create(&path)?;
+        Ok(Self {
+            path,
+            state: Mutex::new(TraceState::Record { file, next_seq: 0 }),
+        })
+    }
+
+    /// Load a recorded trace from `path` for replay.
+    pub fn replay(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let reader = std::io::BufReader::new(std::fs::File::open(&path)?);
+        let mut pending: HashMap<ReplayKey, VecDeque<TraceOutcome>> = HashMap::new();
+        for (n, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: ToolTraceEntry = serde_json::from_str(&line).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{}:{}: {e}", path.display(), n + 1),
+                )
+            })?;
+            pending
+                .entry(replay_key(&entry.instance, &entry.tool, &entry.arguments))
+                .or_default()
+                .push_back(entry.outcome);
+        }
+        Ok(Self {
+            path,
+            state: Mutex::new(TraceState::Replay { pending }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn mode(&self) -> ToolTraceMode {
+        match &*self.state.lock() {
+            TraceState::Record { .. } => ToolTraceMode::Record,
+            TraceState::Replay { .. } => ToolTraceMode::Replay,
+        }
+    }
+
+    /// Recorded calls not yet served (always 0 while recording). A replay
+    /// that ends with calls left over took a different path than the run
+    /// that recorded it.
+    pub fn remaining(&self) -> usize {
+        match &*self.state.lock() {
+            TraceState::Record { .. } => 0,
+            TraceState::Replay { pending } => pending.values().map(VecDeque::len).sum(),
+        }
+    }
+
+    /// The next recorded outcome for `params`, or `None` while recording.
+    pub(crate) fn take(&self, params: &KernelCallParams) -> Option<McpResult<KernelToolResult>> {
+        let mut state = self.state.lock();
+        let TraceState::Replay { pending } = &mut *state else {
+            return None;
+        };
+        let key = replay_key(&params.instance, &params.tool, &params.arguments);
+        let outcome = pending.get_mut(&key).and_then(VecDeque::pop_front);
+        Some(match outcome {
+            Some(outcome) => outcome.into_result(),
+            None => Err(McpError::ReplayMiss {
+                instance: params.instance.clone(),
+                tool: params.tool.clone(),
+            }),
+        })
+    }
+
+    /// Append one call to the trace; a no-op while replaying. A write failure
+    /// is logged, not returned — the call itself succeeded.
+    pub(crate) fn write(
+        &self,
+        params: &KernelCallParams,
+        ctx: &CallContext,
+        result: &McpResult<KernelToolResult>,
+    ) {
+        let mut state = self.state.lock();
+        let TraceState::Record { file, next_seq } = &mut *state else {
+            return;
+        };
+        let entry = ToolTraceEntry {
+            seq: *next_seq,
+            instance: params.instance.clone(),
+            tool: params.tool.clone(),
+            arguments: params.arguments.clone(),
+            env: TraceEnv {
+                context_id: ctx.context_id.to_string(),
+                principal_id: ctx.principal_id.to_string(),
+                cwd: ctx.cwd.clone(),
+            },
+            outcome: TraceOutcome::from_result(result),
+        };
+        *next_seq += 1;
+        let written = serde_json::to_string(&entry)
+            .map_err(std::io::Error::from)
+            .and_then(|line| writeln!(file, "{line}"));
+        if let Err(e) = written {
+            tracing::warn!(
+                path = %self.path.display(),
+                error = %e,
+                "failed to append tool trace entry",
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(tool: &str, arguments: serde_json::Value) -> KernelCallParams {
+        KernelCallParams {
+            instance: InstanceId::new("builtin.file"),
+            tool: tool.to_string(),
+            arguments,
+        }
+    }
+
+    #[test]
+    fn recorded_calls_replay_in_order_per_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.jsonl");
+        let ctx = CallContext::test();
+
+        let recorder = ToolTrace::record(&path).unwrap();
+        recorder.write(
+            &call("read", json!({"path": "a"})),
+            &ctx,
+            &Ok(KernelToolResult::text("one")),
+        );
+        recorder.write(
+            &call("read", json!({"path": "b"})),
+            &ctx,
+            &Ok(KernelToolResult::text("b")),
+        );
+        recorder.write(
+            &call("read", json!({"path": "a"})),
+            &ctx,
+            &Ok(KernelToolResult::text("two")),
+        );
+        recorder.write(
+            &call("write", json!({})),
+            &ctx,
+            &Err(McpError::Protocol("disk full".into())),
+        );
+        drop(recorder);
+
+        let replay = ToolTrace::replay(&path).unwrap();
+        assert_eq!(replay.mode(), ToolTraceMode::Replay);
+        assert_eq!(replay.remaining(), 4);
+
+        let text = |r: McpResult<KernelToolResult>| match r.unwrap().content.as_slice() {
+            [ToolContent::Text(s)] => s.clone(),
+            other => panic!("unexpected content {other:?}"),
+        };
+        assert_eq!(
+            text(replay.take(&call("read", json!({"path": "a"}))).unwrap()),
+            "one"
+        );
+        assert_eq!(
+            text(replay.take(&call("read", json!({"path": "a"}))).unwrap()),
+            "two"
+        );
+        assert_eq!(
+            text(replay.take(&call("read", json!({"path": "b"}))).unwrap()),
+            "b"
+        );
+        let err = replay.take(&call("write", json!({}))).unwrap().unwrap_err();
+        assert!(
+            matches!(err, McpError::Replayed(ref m) if m.contains("disk full")),
+            "{err:?}"
+        );
+        let miss = replay
+            .take(&call("read", json!({"path": "a"})))
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(miss, McpError::ReplayMiss { .. }), "{miss:?}");
+        assert_eq!(replay.remaining(), 0);
+    }
+}
@@ -144,6 +144,50 @@ pub enum Health {
     Down { reason: String },
 }
 
+/// What a context-scoped instance does when its context forks.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum McpForkMode {
+    /// The fork sees the instance too (shared process, not a copy).
+    #[default]
+    Inherit,
+    /// The instance stays with the source context only.
+    Exclude,
+}
+
+impl McpForkMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            McpForkMode::Inherit => "inherit",
+            McpForkMode::Exclude => "exclude",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "inherit" => Some(McpForkMode::Inherit),
+            "exclude" => Some(McpForkMode::Exclude),
+            _ => None,
+        }
+    }
+}
+
+/// Where a context-scoped instance is visible (see `Broker::register_scoped`).
+#[derive(Clone, Debug)]
+pub struct InstanceScope {
+    pub contexts: std::collections::HashSet<kaijutsu_types::ContextId>,
+    pub fork_mode: McpForkMode,
+}
+
+/// A context-scoped instance as reported to clients: scope, a live health
+/// probe, and the tools it advertises.
+#[derive(Clone, Debug)]
+pub struct ScopedInstanceStatus {
+    pub instance: InstanceId,
+    pub fork_mode: McpForkMode,
+    pub health: Health,
+    pub tools: Vec<KernelTool>,
+}
+
 /// Logging severity on notifications (§4.1).
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum LogLevel {
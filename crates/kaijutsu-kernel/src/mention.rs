@@ -0,0 +1,250 @@
+//! `@`-mention resolution and routing for user blocks.
+//!
+//! Two steps, split around the block insert:
+//!
+//! 1. [`resolve`] scans the prompt text before the block exists and maps each
+//!    `@name` to a seat (username, via `KernelDb::seat_by_username`) or, failing
+//!    that, a context (exact label, via the drift router). The result is stored
+//!    on the block as write-once metadata (`BlockSnapshot::mentions`).
+//! 2. [`route`] acts on the inserted block's mentions: a principal gets an
+//!    `InboxKind::Mention` item; a context gets the block staged as a push
+//!    drift (flushed like any `kj drift push`) and its owner a `Mention` item
+//!    pointing at it. This is what makes "@reviewer look at this" reach a
+//!    sibling context's agent.
+//!
+//! Mentions of the sender or of the block's own context are recorded but not
+//! routed. So are context mentions from a context without the `drift`
+//! authority: staging a drift is what `kj drift push` needs that authority
+//! for, and a mention must not be a way around it.
+
+use kaijutsu_crdt::DriftKind;
+use kaijutsu_types::{
+    BlockMention, BlockSnapshot, ContextId, InboxItem, InboxKind, MentionTarget, mention_names,
+};
+use parking_lot::Mutex;
+
+use crate::drift::SharedDriftRouter;
+use crate::flows::SharedBlockFlowBus;
+use crate::kernel_db::KernelDb;
+
+/// Resolve the `@name` tokens in `text`. Seats win over context labels when
+/// a name is both; unknown names are dropped.
+pub fn resolve(db: &Mutex<KernelDb>, drift: &SharedDriftRouter, text: &str) -> Vec<BlockMention> {
+    let mut resolved = Vec::new();
+    for name in mention_names(text) {
+        match db.lock().seat_by_username(name) {
+            Ok(Some(id)) => {
+                resolved.push(BlockMention::principal(name, id));
+                continue;
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("mention: seat lookup for @{name} failed: {e}"),
+        }
+        if let Some(id) = drift.read().context_by_label(name) {
+            resolved.push(BlockMention::context(name, id));
+        }
+    }
+    resolved
+}
+
+/// What [`route`] did.
+#[derive(Debug, Default)]
+pub struct Routed {
+    /// Inbox items posted (mentioned seats and mentioned contexts' owners).
+    pub inbox: Vec<InboxItem>,
+    /// Staged drift ids, one per mentioned context.
+    pub drifts: Vec<u64>,
+}
+
+/// Route `block`'s mentions. `may_drift` is whether the block's context
+/// holds the `drift` authority; without it, context mentions are left
+/// unrouted. Failures are logged, never propagated — a mention must not
+/// fail the prompt that carried it.
+pub fn route(
+    db: &Mutex<KernelDb>,
+    flows: Option<&SharedBlockFlowBus>,
+    drift: &SharedDriftRouter,
+    block: &BlockSnapshot,
+    sender_name: &str,
+    may_drift: bool,
+) -> Routed {
+    let mut routed = Routed::default();
+    let context_id = block.id.context_id;
+    let sender = block.author();
+    let text = block.content.trim();
+
+    for mention in &block.mentions {
+        match mention.target {
+            MentionTarget::Principal(recipient) => {
+                if recipient == sender {
+                    continue;
+                }
+                let summary = format!("@{sender_name}: {text}");
+                match crate::inbox::post(
+                    db,
+                    flows,
+                    recipient,
+                    InboxKind::Mention,
+                    Some(context_id),
+                    Some(sender),
+                    &summary,
+                ) {
+                    Ok(item) => routed.inbox.push(item),
+                    Err(e) => tracing::warn!("mention: failed to post @{}: {e}", mention.name),
+                }
+            }
+            MentionTarget::Context(target) => {
+                if target == context_id {
+                    continue;
+                }
+                if !may_drift {
+                    tracing::debug!(
+                        "mention: @{} not drifted, {} lacks the drift authority",
+                        mention.name,
+                        context_id.short()
+                    );
+                    continue;
+                }
+                let Some(staged_id) = stage(drift, block, sender_name, target) else {
+                    continue;
+                };
+                routed.drifts.push(staged_id);
+                if let Some(item) = crate::inbox::notify_context_owner(
+                    db,
+                    flows,
+                    target,
+                    InboxKind::Mention,
+                    sender,
+                    &format!("@{sender_name} in {}: {text} (drift #{staged_id})", context_id.short()),
+                ) {
+                    routed.inbox.push(item);
+                }
+            }
+        }
+    }
+    routed
+}
+
+/// Stage `block` as a push drift into `target`, prefixed with where it came
+/// from so the receiving agent can find the original.
+fn stage(
+    drift: &SharedDriftRouter,
+    block: &BlockSnapshot,
+    sender_name: &str,
+    target: ContextId,
+) -> Option<u64> {
+    let content = format!(
+        "@{sender_name} mentioned this context (block {}):\n\n{}",
+        block.id.to_key(),
+        block.content
+    );
+    match drift
+        .write()
+        .stage(block.id.context_id, target, content, None, DriftKind::Push)
+    {
+        Ok(id) => Some(id),
+        Err(e) => {
+            tracing::warn!("mention: failed to stage drift to {}: {e}", target.short());
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drift::shared_drift_router;
+    use crate::flows::{BlockFlow, shared_block_flow_bus};
+    use kaijutsu_crdt::{BlockId, BlockKind};
+    use kaijutsu_types::{BlockSnapshotBuilder, PrincipalId};
+
+    fn seat(db: &Mutex<KernelDb>, name: &str) -> PrincipalId {
+        let id = PrincipalId::new();
+        db.lock().record_seat(id, name).unwrap();
+        id
+    }
+
+    fn user_block(
+        ctx: ContextId,
+        author: PrincipalId,
+        text: &str,
+        mentions: Vec<BlockMention>,
+    ) -> BlockSnapshot {
+        BlockSnapshotBuilder::new(BlockId::new(ctx, author, 1), BlockKind::Text)
+            .content(text)
+            .mentions(mentions)
+            .build()
+    }
+
+    #[test]
+    fn resolve_prefers_seats_then_exact_labels() {
+        let db = Mutex::new(KernelDb::in_memory().unwrap());
+        let drift = shared_drift_router();
+        let amy = seat(&db, "amy");
+        let review = ContextId::new();
+        drift.write().register(review, Some("review"), None, amy).unwrap();
+        drift.write().register(ContextId::new(), Some("amy"), None, amy).unwrap();
+
+        let mentions = resolve(&db, &drift, "@amy @review @rev @nobody");
+        assert_eq!(
+            mentions,
+            vec![
+                BlockMention::principal("amy", amy),
+                BlockMention::context("review", review),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn principal_mention_posts_inbox_item() {
+        let db = Mutex::new(KernelDb::in_memory().unwrap());
+        let drift = shared_drift_router();
+        let bus = shared_block_flow_bus(16);
+        let mut sub = bus.subscribe("block.inbox");
+        let amy = seat(&db, "amy");
+        let bob = seat(&db, "bob");
+
+        let ctx = ContextId::new();
+        let text = "@amy @bob ping";
+        let block = user_block(ctx, bob, text, resolve(&db, &drift, text));
+        let routed = route(&db, Some(&bus), &drift, &block, "bob", false);
+        assert_eq!(routed.inbox.len(), 1, "self-mention is recorded but not routed");
+        assert_eq!(routed.inbox[0].recipient, amy);
+        assert_eq!(routed.inbox[0].summary, "@bob: @amy @bob ping");
+
+        match sub.recv().await.unwrap().payload {
+            BlockFlow::InboxPosted { context_id, item } => {
+                assert_eq!(context_id, ctx);
+                assert_eq!(item, routed.inbox[0]);
+            }
+            other => panic!("expected InboxPosted, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn context_mention_stages_drift() {
+        let db = Mutex::new(KernelDb::in_memory().unwrap());
+        let drift = shared_drift_router();
+        let bob = PrincipalId::new();
+        let here = ContextId::new();
+        let review = ContextId::new();
+        drift.write().register(here, Some("main"), None, bob).unwrap();
+        drift.write().register(review, Some("review"), None, bob).unwrap();
+
+        let text = "@review look at this, not @main";
+        let block = user_block(here, bob, text, resolve(&db, &drift, text));
+        assert_eq!(block.mentions.len(), 2);
+        let routed = route(&db, None, &drift, &block, "bob", false);
+        assert!(routed.drifts.is_empty(), "no drift authority, no drift");
+        assert!(drift.read().queue().is_empty());
+
+        let routed = route(&db, None, &drift, &block, "bob", true);
+        assert_eq!(routed.drifts.len(), 1, "own context is not drifted to");
+
+        let queue = drift.read().queue().to_vec();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].target_ctx, review);
+        assert_eq!(queue[0].drift_kind, DriftKind::Push);
+        assert!(queue[0].content.ends_with(text));
+    }
+}
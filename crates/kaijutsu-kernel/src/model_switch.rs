@@ -0,0 +1,189 @@
+//! Switching a context's model mid-conversation.
+//!
+//! `kj context set --model` only rewrites the row and the drift router. A
+//! switch made from a seat (the model picker, the `setContextModel` RPC) also
+//! has to be visible in the conversation and on every other seat, so
+//! [`switch_context_model`] additionally:
+//!
+//! - records a `ModelChanged` notification block in the context. Hydration
+//!   reads it as the seam where earlier turns' reasoning signatures stop being
+//!   valid (they belong to the old model) and strips them from the replay;
+//! - publishes `BlockFlow::ModelChanged` so connected clients relabel the
+//!   context without polling.
+
+use kaijutsu_types::{ContextId, NotificationKind, NotificationPayload, PrincipalId};
+use parking_lot::Mutex;
+
+use crate::block_store::{BlockStoreError, SharedBlockStore};
+use crate::flows::BlockFlow;
+use crate::kernel::Kernel;
+use crate::kernel_db::{KernelDb, KernelDbError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModelSwitchError {
+    /// The spec didn't resolve against the LLM registry.
+    #[error("{0}")]
+    Spec(String),
+    #[error("context {0} not found")]
+    ContextNotFound(ContextId),
+    #[error(transparent)]
+    Db(#[from] KernelDbError),
+    #[error(transparent)]
+    Store(#[from] BlockStoreError),
+}
+
+/// Outcome of [`switch_context_model`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelSwitch {
+    pub provider: String,
+    pub model: String,
+    /// False when the context was already on this model — nothing was written.
+    pub changed: bool,
+}
+
+/// Point `context_id` at the model named by `spec` (`provider/model` or an
+/// alias, resolved like `kj context set --model`) on behalf of `by`.
+///
+/// Validates before any mutation. On a real change: updates the context row
+/// and drift router, records a `ModelChanged` notification block authored by
+/// `by`, and publishes `BlockFlow::ModelChanged`.
+pub async fn switch_context_model(
+    kernel: &Kernel,
+    db: &Mutex<KernelDb>,
+    blocks: &SharedBlockStore,
+    context_id: ContextId,
+    spec: &str,
+    by: PrincipalId,
+) -> Result<ModelSwitch, ModelSwitchError> {
+    let (provider, model) = {
+        let registry = kernel.llm().read().await;
+        match crate::kj::parse::resolve_model_choice(&registry, spec.trim()) {
+            Ok((Some(provider), Some(model))) => (provider, model),
+            Ok(_) => return Err(ModelSwitchError::Spec("empty model spec".to_string())),
+            Err(e) => return Err(ModelSwitchError::Spec(e)),
+        }
+    };
+
+    let previous = {
+        let db = db.lock();
+        let row = db
+            .get_context(context_id)?
+            .ok_or(ModelSwitchError::ContextNotFound(context_id))?;
+        if row.provider.as_deref() == Some(provider.as_str())
+            && row.model.as_deref() == Some(model.as_str())
+        {
+            return Ok(ModelSwitch {
+                provider,
+                model,
+                changed: false,
+            });
+        }
+        db.update_model(context_id, Some(&provider), Some(&model))?;
+        match (row.provider, row.model) {
+            (Some(p), Some(m)) => format!("{p}/{m}"),
+            (None, Some(m)) => m,
+            _ => "default".to_string(),
+        }
+    };
+    // A context the router hasn't registered yet picks the row up on register.
+    let _ = kernel
+        .drift()
+        .write()
+        .configure_llm(context_id, &provider, &model);
+
+    let payload = NotificationPayload {
+        instance: "kernel".to_string(),
+        kind: NotificationKind::ModelChanged,
+        level: None,
+        tools: Vec::new(),
+        count: None,
+        detail: Some(format!("{previous} → {provider}/{model}")),
+    };
+    blocks.insert_notification_block_as(
+        context_id,
+        None,
+        &payload,
+        payload.summary_line(),
+        Some(by),
+    )?;
+
+    kernel.block_flows().publish(BlockFlow::ModelChanged {
+        context_id,
+        provider: provider.clone(),
+        model: model.clone(),
+        by,
+    });
+
+    Ok(ModelSwitch {
+        provider,
+        model,
+        changed: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kj::test_helpers::{register_context, test_dispatcher};
+    use crate::llm::{MockClient, Provider};
+    use kaijutsu_types::BlockKind;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn switch_records_block_and_publishes_once() {
+        let d = test_dispatcher().await;
+        let owner = PrincipalId::new();
+        let ctx = register_context(&d, Some("main"), None, owner);
+        d.block_store()
+            .create_document(ctx, crate::DocumentKind::Conversation, None)
+            .unwrap();
+        {
+            let mut reg = d.kernel().llm().write().await;
+            reg.register("anthropic", Arc::new(Provider::Mock(MockClient::new("A"))));
+            reg.register("deepseek", Arc::new(Provider::Mock(MockClient::new("D"))));
+            reg.set_default("anthropic");
+        }
+        let mut sub = d.kernel().block_flows().subscribe("block.model");
+
+        let switch = |spec: &'static str| {
+            switch_context_model(d.kernel(), d.kernel_db(), d.block_store(), ctx, spec, owner)
+        };
+        let first = switch("deepseek/deepseek-v4-pro").await.unwrap();
+        assert!(first.changed);
+        assert_eq!(first.provider, "deepseek");
+        let again = switch("deepseek/deepseek-v4-pro").await.unwrap();
+        assert!(!again.changed, "same model is a no-op");
+        assert!(matches!(
+            switch("nowhere/model").await,
+            Err(ModelSwitchError::Spec(_))
+        ));
+
+        let row = d.kernel_db().lock().get_context(ctx).unwrap().unwrap();
+        assert_eq!(row.model.as_deref(), Some("deepseek-v4-pro"));
+        let notes: Vec<_> = d
+            .block_store()
+            .block_snapshots(ctx)
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.kind == BlockKind::Notification)
+            .collect();
+        assert_eq!(notes.len(), 1, "only the real switch is recorded");
+        assert_eq!(notes[0].id.principal_id, owner);
+
+        match sub.try_recv().map(|m| m.payload) {
+            Some(BlockFlow::ModelChanged {
+                context_id,
+                model,
+                by,
+                ..
+            }) => {
+                assert_eq!(
+                    (context_id, model.as_str(), by),
+                    (ctx, "deepseek-v4-pro", owner)
+                );
+            }
+            other => panic!("expected ModelChanged, got {other:?}"),
+        }
+        assert!(sub.try_recv().is_none());
+    }
+}
@@ -0,0 +1,320 @@
+//! Shape checks for a client's pushed ops, before they merge.
+//!
+//! [`validate_push`] looks at the blocks a `pushOps` payload creates and
+//! checks each against the document it lands in:
+//!
+//! - the block, its parent and its tool call live in the pushed context;
+//! - its parent is in the document or created by the same push, and the
+//!   parent chain ends within [`MAX_DAG_DEPTH`] (a cycle never ends);
+//! - a tool result names a tool call that exists;
+//! - code, text, config and symlink documents hold text blocks only, and no
+//!   tool or asset roles;
+//! - config and symlink documents hold a single block.
+//!
+//! Text ops, header updates and deletions of existing blocks aren't checked:
+//! they can't change a block's kind, role or parent. The kernel's own writes
+//! don't come through here — it builds well-formed blocks itself.
+//! [`BlockStore::merge_pushed_ops`](crate::block_store::BlockStore::merge_pushed_ops)
+//! runs the check under the document lock and refuses the whole push on any
+//! violation.
+
+use std::collections::{HashMap, HashSet};
+
+use kaijutsu_crdt::block_store::{BlockStore as CrdtBlockStore, SyncPayload};
+use kaijutsu_types::{
+    BlockId, BlockKind, BlockSnapshot, ContextId, DocKind, MAX_DAG_DEPTH, PushRule, PushViolation,
+    Role,
+};
+
+/// Every violation in `payload`, in payload order; empty when it may merge.
+pub fn validate_push(
+    context_id: ContextId,
+    kind: DocKind,
+    doc: &CrdtBlockStore,
+    payload: &SyncPayload,
+) -> Vec<PushViolation> {
+    let deleted: HashSet<BlockId> = payload.deleted_blocks.iter().copied().collect();
+    // Blocks this push creates: new to the document and not deleted again by
+    // the same push. Re-sent blocks the document already has merge as no-ops.
+    let created: Vec<&BlockSnapshot> = payload
+        .new_blocks
+        .iter()
+        .filter(|s| doc.get_block_header(&s.id).is_none() && !deleted.contains(&s.id))
+        .collect();
+    let by_id: HashMap<BlockId, &BlockSnapshot> = created.iter().map(|s| (s.id, *s)).collect();
+    // A block's kind and parent as they stand after the push.
+    let lookup = |id: &BlockId| -> Option<(BlockKind, Option<BlockId>)> {
+        if let Some(snap) = by_id.get(id) {
+            return Some((snap.kind, snap.parent_id));
+        }
+        if deleted.contains(id) {
+            return None;
+        }
+        doc.get_block_header(id).map(|h| (h.kind, h.parent_id))
+    };
+
+    let mut violations = Vec::new();
+    let mut flag = |block_id: BlockId, rule: PushRule, detail: String| {
+        violations.push(PushViolation {
+            block_id,
+            rule,
+            detail,
+        })
+    };
+    let foreign = |id: &BlockId| id.context_id != context_id;
+
+    let single_block = matches!(kind, DocKind::Config | DocKind::Symlink);
+    let mut live = doc.block_count()
+        - deleted
+            .iter()
+            .filter(|id| doc.get_block_header(id).is_some())
+            .count();
+
+    for snap in created {
+        if foreign(&snap.id) {
+            flag(
+                snap.id,
+                PushRule::ForeignBlock,
+                format!("block belongs to context {}", snap.id.context_id),
+            );
+            continue;
+        }
+
+        if let Some(parent) = snap.parent_id {
+            if foreign(&parent) {
+                flag(
+                    snap.id,
+                    PushRule::ForeignBlock,
+                    format!("parent {parent} belongs to another context"),
+                );
+            } else if lookup(&parent).is_none() {
+                flag(
+                    snap.id,
+                    PushRule::UnknownParent,
+                    format!("parent {parent} is not in the document"),
+                );
+            } else {
+                let mut ancestors = 0;
+                let mut current = Some(parent);
+                while let Some(id) = current {
+                    if ancestors >= MAX_DAG_DEPTH {
+                        flag(
+                            snap.id,
+                            PushRule::TooDeep,
+                            format!("parent chain is a cycle or deeper than {MAX_DAG_DEPTH}"),
+                        );
+                        break;
+                    }
+                    ancestors += 1;
+                    current = lookup(&id).and_then(|(_, parent)| parent);
+                }
+            }
+        }
+
+        if snap.kind == BlockKind::ToolResult {
+            match snap.tool_call_id {
+                None => flag(
+                    snap.id,
+                    PushRule::OrphanToolResult,
+                    "tool result has no tool call".to_string(),
+                ),
+                Some(call) if foreign(&call) => flag(
+                    snap.id,
+                    PushRule::ForeignBlock,
+                    format!("tool call {call} belongs to another context"),
+                ),
+                Some(call) => match lookup(&call) {
+                    None => flag(
+                        snap.id,
+                        PushRule::OrphanToolResult,
+                        format!("tool call {call} is not in the document"),
+                    ),
+                    Some((call_kind, _)) if call_kind != BlockKind::ToolCall => flag(
+                        snap.id,
+                        PushRule::OrphanToolResult,
+                        format!("{call} is a {} block, not a tool call", call_kind.as_str()),
+                    ),
+                    Some(_) => {}
+                },
+            }
+        }
+
+        if kind != DocKind::Conversation {
+            if snap.kind != BlockKind::Text {
+                flag(
+                    snap.id,
+                    PushRule::KindNotAllowed,
+                    format!(
+                        "{} documents hold text blocks, not {}",
+                        kind.as_str(),
+                        snap.kind.as_str()
+                    ),
+                );
+            }
+            if matches!(snap.role, Role::Tool | Role::Asset) {
+                flag(
+                    snap.id,
+                    PushRule::RoleNotAllowed,
+                    format!(
+                        "{} documents hold no {} blocks",
+                        kind.as_str(),
+                        snap.role.as_str()
+                    ),
+                );
+            }
+        }
+
+        if single_block {
+            live += 1;
+            if live > 1 {
+                flag(
+                    snap.id,
+                    PushRule::SingleBlock,
+                    format!("{} documents hold a single block", kind.as_str()),
+                );
+            }
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_types::{BlockSnapshotBuilder, PrincipalId, Status};
+
+    struct Doc {
+        ctx: ContextId,
+        author: PrincipalId,
+        store: CrdtBlockStore,
+        next_seq: u64,
+    }
+
+    impl Doc {
+        fn new() -> Self {
+            let ctx = ContextId::new();
+            let author = PrincipalId::new();
+            Self {
+                ctx,
+                author,
+                store: CrdtBlockStore::new(ctx, PrincipalId::new()),
+                next_seq: 0,
+            }
+        }
+
+        fn next_id(&mut self) -> BlockId {
+            self.next_seq += 1;
+            BlockId::new(self.ctx, self.author, self.next_seq)
+        }
+
+        fn block(&mut self, kind: BlockKind) -> BlockSnapshotBuilder {
+            let id = self.next_id();
+            Self::block_with_id(id, kind)
+        }
+
+        fn block_with_id(id: BlockId, kind: BlockKind) -> BlockSnapshotBuilder {
+            BlockSnapshotBuilder::new(id, kind)
+                .role(Role::User)
+                .status(Status::Done)
+        }
+
+        fn check(&self, kind: DocKind, new_blocks: Vec<BlockSnapshot>) -> Vec<PushRule> {
+            let payload = SyncPayload {
+                block_ops: Vec::new(),
+                new_blocks,
+                updated_headers: Vec::new(),
+                deleted_blocks: Vec::new(),
+            };
+            validate_push(self.ctx, kind, &self.store, &payload)
+                .into_iter()
+                .map(|v| v.rule)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn well_formed_pushes_pass() {
+        let mut doc = Doc::new();
+        let call = doc.block(BlockKind::ToolCall).role(Role::Model).build();
+        let result = doc
+            .block(BlockKind::ToolResult)
+            .role(Role::Tool)
+            .parent_id(call.id)
+            .tool_call_id(call.id)
+            .build();
+        assert_eq!(doc.check(DocKind::Conversation, vec![call, result]), vec![]);
+
+        let text = doc.block(BlockKind::Text).build();
+        assert_eq!(doc.check(DocKind::Config, vec![text]), vec![]);
+    }
+
+    #[test]
+    fn tool_results_need_their_call() {
+        let mut doc = Doc::new();
+        let text = doc.block(BlockKind::Text).build();
+        let no_call = doc.block(BlockKind::ToolResult).role(Role::Tool).build();
+        let missing = BlockId::new(doc.ctx, doc.author, 999);
+        let missing_call = doc
+            .block(BlockKind::ToolResult)
+            .role(Role::Tool)
+            .tool_call_id(missing)
+            .build();
+        let wrong_kind = doc
+            .block(BlockKind::ToolResult)
+            .role(Role::Tool)
+            .tool_call_id(text.id)
+            .build();
+        assert_eq!(
+            doc.check(
+                DocKind::Conversation,
+                vec![text, no_call, missing_call, wrong_kind]
+            ),
+            vec![PushRule::OrphanToolResult; 3]
+        );
+    }
+
+    #[test]
+    fn parents_must_exist_and_stay_in_bounds() {
+        let mut doc = Doc::new();
+        let missing = BlockId::new(doc.ctx, doc.author, 999);
+        let orphan = doc.block(BlockKind::Text).parent_id(missing).build();
+        let elsewhere = BlockId::new(ContextId::new(), doc.author, 1);
+        let foreign_parent = doc.block(BlockKind::Text).parent_id(elsewhere).build();
+        assert_eq!(
+            doc.check(DocKind::Conversation, vec![orphan, foreign_parent]),
+            vec![PushRule::UnknownParent, PushRule::ForeignBlock]
+        );
+
+        // Two new blocks naming each other as parent never reach a root.
+        let (a, b) = (doc.next_id(), doc.next_id());
+        let cycle = vec![
+            Doc::block_with_id(a, BlockKind::Text).parent_id(b).build(),
+            Doc::block_with_id(b, BlockKind::Text).parent_id(a).build(),
+        ];
+        assert_eq!(
+            doc.check(DocKind::Conversation, cycle),
+            vec![PushRule::TooDeep; 2]
+        );
+    }
+
+    #[test]
+    fn non_conversation_documents_restrict_kinds_roles_and_count() {
+        let mut doc = Doc::new();
+        let call = doc.block(BlockKind::ToolCall).build();
+        let tool_text = doc.block(BlockKind::Text).role(Role::Tool).build();
+        assert_eq!(
+            doc.check(DocKind::Code, vec![call, tool_text]),
+            vec![PushRule::KindNotAllowed, PushRule::RoleNotAllowed]
+        );
+
+        let (one, two) = (
+            doc.block(BlockKind::Text).build(),
+            doc.block(BlockKind::Text).build(),
+        );
+        assert_eq!(
+            doc.check(DocKind::Symlink, vec![one.clone(), two.clone()]),
+            vec![PushRule::SingleBlock]
+        );
+        assert_eq!(doc.check(DocKind::Text, vec![one, two]), vec![]);
+    }
+}
@@ -0,0 +1,775 @@
+//! Interactive REPL sessions bound to persistent interpreter processes.
+//!
+//! A session is a [`BlockKind::Repl`] block with no parent plus a live
+//! interpreter: a host `python3`/`node` child process, or a kaish shell
+//! materialized once and kept (so its variables and cwd persist between
+//! submissions). The session block's text is the input log. Whenever it
+//! gains complete lines — from `repl_send`, or from a client appending to the
+//! block — [`ReplManager`] pumps them to the interpreter and opens a child
+//! `Repl` block holding the echoed input; stdout and stderr stream into that
+//! block as they arrive and it goes `Done` once output settles.
+//!
+//! Sessions are per context (at most [`MAX_SESSIONS_PER_CONTEXT`]) and shut
+//! down after [`REPL_IDLE_TIMEOUT`] without input or output, when their block
+//! is deleted, or on `repl_stop`. The session block ends `Done`, or `Error`
+//! when the process died with a nonzero status. Nothing survives a kernel
+//! restart: a `Running` session block loaded cold has no process behind it.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio_util::sync::CancellationToken;
+
+use kaijutsu_types::{
+    BlockId, BlockKind, ContentType, ContextId, PrincipalId, ReplLanguage, Role, Status, repl_echo,
+};
+
+use crate::block_store::{BlockStoreError, SharedBlockStore};
+use crate::flows::BlockFlow;
+use crate::runtime::embedded_kaish::EmbeddedKaish;
+
+/// Live sessions allowed per context.
+pub const MAX_SESSIONS_PER_CONTEXT: usize = 4;
+
+/// A session with no input or output for this long is shut down.
+pub const REPL_IDLE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Output is considered complete after this long without any.
+pub const REPL_SETTLE: Duration = Duration::from_millis(500);
+
+/// How often idle sessions are looked for.
+const REAP_INTERVAL: Duration = Duration::from_secs(30);
+
+const READ_CHUNK: usize = 4096;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplError {
+    #[error("context {context} already has {max} live REPL sessions")]
+    TooManySessions { context: String, max: usize },
+    #[error("no live REPL session for block {0}")]
+    NotFound(String),
+    #[error("failed to start {language}: {message}")]
+    Spawn { language: String, message: String },
+    #[error("REPL process is gone: {0}")]
+    Exited(String),
+    #[error(transparent)]
+    Store(#[from] BlockStoreError),
+}
+
+/// How to run a session's interpreter.
+pub enum ReplBackend {
+    /// A host child process. `path` is the `PATH` it gets (its env is
+    /// otherwise empty); `cwd` its working directory.
+    Host {
+        path: Option<String>,
+        cwd: Option<PathBuf>,
+    },
+    /// The context's kaish, materialized for this session.
+    Kaish(Box<EmbeddedKaish>),
+}
+
+enum Interpreter {
+    Host(ChildStdin),
+    Kaish(Box<EmbeddedKaish>),
+}
+
+/// Input side: how much of the session block has been sent.
+struct InputState {
+    /// Byte offset into the session block's content.
+    sent: usize,
+    interpreter: Interpreter,
+}
+
+/// Output side: where output currently streams.
+struct OutputState {
+    block: Option<BlockId>,
+    last_output: Instant,
+}
+
+/// One live session.
+pub struct ReplSession {
+    pub block_id: BlockId,
+    pub language: ReplLanguage,
+    /// Who started it; authors submissions picked up from block edits.
+    pub owner: PrincipalId,
+    input: tokio::sync::Mutex<InputState>,
+    output: parking_lot::Mutex<OutputState>,
+    last_input: parking_lot::Mutex<Instant>,
+    cancel: CancellationToken,
+}
+
+impl ReplSession {
+    fn context_id(&self) -> ContextId {
+        self.block_id.context_id
+    }
+
+    fn last_active(&self) -> Instant {
+        (*self.last_input.lock()).max(self.output.lock().last_output)
+    }
+
+    /// Whether output has been quiet for [`REPL_SETTLE`].
+    fn settled(&self) -> bool {
+        self.output.lock().last_output.elapsed() >= REPL_SETTLE
+    }
+}
+
+/// Result of one submission.
+#[derive(Debug, Clone)]
+pub struct ReplOutput {
+    /// The child block holding the echoed input and its output.
+    pub block_id: BlockId,
+    pub transcript: String,
+    /// Output went quiet before the timeout.
+    pub settled: bool,
+    /// The session is still live.
+    pub alive: bool,
+}
+
+/// The kernel's live REPL sessions.
+pub struct ReplManager {
+    documents: SharedBlockStore,
+    sessions: parking_lot::Mutex<HashMap<BlockId, Arc<ReplSession>>>,
+}
+
+impl ReplManager {
+    pub fn new(documents: SharedBlockStore) -> Arc<Self> {
+        Arc::new(Self {
+            documents,
+            sessions: parking_lot::Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Spawn the block watcher (edits to session blocks pump input;
+    /// deleting one stops it) and the idle reaper. Both hold a `Weak` and
+    /// exit once the manager is dropped.
+    pub fn spawn_tasks(self: &Arc<Self>) {
+        if let Some(bus) = self.documents.block_flows() {
+            let mut sub = bus.subscribe("block.*");
+            let manager = Arc::downgrade(self);
+            tokio::spawn(async move {
+                while let Some(msg) = sub.recv().await {
+                    let Some(manager) = manager.upgrade() else {
+                        break;
+                    };
+                    manager.on_flow(&msg.payload);
+                }
+            });
+        }
+
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(REAP_INTERVAL);
+            loop {
+                tick.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.reap_idle(REPL_IDLE_TIMEOUT);
+            }
+        });
+    }
+
+    /// Start a session in `context_id`: insert its block at the end of the
+    /// document and bring up the interpreter.
+    pub async fn start(
+        self: &Arc<Self>,
+        context_id: ContextId,
+        language: ReplLanguage,
+        backend: ReplBackend,
+        principal: PrincipalId,
+    ) -> Result<BlockId, ReplError> {
+        let live = self.sessions_in(context_id).len();
+        if live >= MAX_SESSIONS_PER_CONTEXT {
+            return Err(ReplError::TooManySessions {
+                context: context_id.short(),
+                max: MAX_SESSIONS_PER_CONTEXT,
+            });
+        }
+
+        let (interpreter, child) = match backend {
+            ReplBackend::Host { path, cwd } => {
+                let mut child = spawn_host(language, path, cwd).map_err(|e| ReplError::Spawn {
+                    language: language.to_string(),
+                    message: e.to_string(),
+                })?;
+                let stdin = child.stdin.take().ok_or_else(|| ReplError::Spawn {
+                    language: language.to_string(),
+                    message: "no stdin pipe".to_string(),
+                })?;
+                (Interpreter::Host(stdin), Some(child))
+            }
+            ReplBackend::Kaish(kaish) => (Interpreter::Kaish(kaish), None),
+        };
+
+        let after = self.documents.last_block_id(context_id);
+        let block_id = self.documents.insert_block_as(
+            context_id,
+            None,
+            after.as_ref(),
+            Role::Tool,
+            BlockKind::Repl,
+            "",
+            Status::Running,
+            ContentType::Plain,
+            Some(principal),
+        )?;
+        self.documents
+            .set_language(context_id, &block_id, Some(language.as_str().to_string()))?;
+
+        let now = Instant::now();
+        let session = Arc::new(ReplSession {
+            block_id,
+            language,
+            owner: principal,
+            input: tokio::sync::Mutex::new(InputState {
+                sent: 0,
+                interpreter,
+            }),
+            output: parking_lot::Mutex::new(OutputState {
+                block: None,
+                last_output: now,
+            }),
+            last_input: parking_lot::Mutex::new(now),
+            cancel: CancellationToken::new(),
+        });
+        self.sessions.lock().insert(block_id, session.clone());
+
+        if let Some(mut child) = child {
+            if let Some(stdout) = child.stdout.take() {
+                self.spawn_reader(&session, stdout);
+            }
+            if let Some(stderr) = child.stderr.take() {
+                self.spawn_reader(&session, stderr);
+            }
+            let manager = Arc::downgrade(self);
+            let session = session.clone();
+            tokio::spawn(async move {
+                let failed = tokio::select! {
+                    status = child.wait() => !status.is_ok_and(|s| s.success()),
+                    _ = session.cancel.cancelled() => {
+                        let _ = child.kill().await;
+                        false
+                    }
+                };
+                if let Some(manager) = manager.upgrade() {
+                    manager.close(&session.block_id, failed);
+                }
+            });
+        }
+
+        tracing::info!(
+            context = %context_id.short(),
+            block = %block_id.to_key(),
+            %language,
+            "REPL session started"
+        );
+        Ok(block_id)
+    }
+
+    /// Append `input` to the session block (a trailing newline is added if
+    /// missing), send it, and wait up to `timeout` for output to settle.
+    /// The output block is authored with `role`: `User` for input a person
+    /// typed, `Tool` for an agent's.
+    pub async fn send(
+        &self,
+        block_id: &BlockId,
+        input: &str,
+        role: Role,
+        principal: PrincipalId,
+        timeout: Duration,
+    ) -> Result<ReplOutput, ReplError> {
+        let session = self.session(block_id)?;
+        let mut text = input.to_string();
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+
+        let output = {
+            // Hold the input lock across the append so the watcher, woken by
+            // the same edit, finds nothing left to send.
+            let mut input = session.input.lock().await;
+            self.documents.append_text_as(
+                session.context_id(),
+                block_id,
+                &text,
+                Some(principal),
+            )?;
+            self.pump_locked(&session, &mut input, role, principal)
+                .await?
+        };
+        let Some(output) = output else {
+            return Err(ReplError::Exited(block_id.to_key()));
+        };
+
+        let deadline = Instant::now() + timeout;
+        let settled = loop {
+            let alive = !session.cancel.is_cancelled();
+            // Kaish finishes inside the pump and hands the block back.
+            let current = session.output.lock().block == Some(output);
+            if !alive || !current || session.settled() {
+                break alive;
+            }
+            if Instant::now() >= deadline {
+                break false;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
+        if settled {
+            self.finish_output(&session, &output);
+        }
+        let transcript = self
+            .documents
+            .get_block_snapshot(session.context_id(), &output)?
+            .map(|b| b.content)
+            .unwrap_or_default();
+        Ok(ReplOutput {
+            block_id: output,
+            transcript,
+            settled,
+            alive: !session.cancel.is_cancelled(),
+        })
+    }
+
+    /// Stop a session. Returns whether one was live.
+    pub fn stop(&self, block_id: &BlockId) -> bool {
+        self.close(block_id, false)
+    }
+
+    /// Live session blocks in `context_id`.
+    pub fn sessions_in(&self, context_id: ContextId) -> Vec<(BlockId, ReplLanguage)> {
+        self.sessions
+            .lock()
+            .values()
+            .filter(|s| s.context_id() == context_id)
+            .map(|s| (s.block_id, s.language))
+            .collect()
+    }
+
+    /// Stop sessions quiet for at least `idle`. Returns how many.
+    pub fn reap_idle(&self, idle: Duration) -> usize {
+        let stale: Vec<BlockId> = self
+            .sessions
+            .lock()
+            .values()
+            .filter(|s| s.last_active().elapsed() >= idle)
+            .map(|s| s.block_id)
+            .collect();
+        for block_id in &stale {
+            tracing::info!(block = %block_id.to_key(), "REPL session idle, shutting down");
+            self.close(block_id, false);
+        }
+        stale.len()
+    }
+
+    fn session(&self, block_id: &BlockId) -> Result<Arc<ReplSession>, ReplError> {
+        self.sessions
+            .lock()
+            .get(block_id)
+            .cloned()
+            .ok_or_else(|| ReplError::NotFound(block_id.to_key()))
+    }
+
+    fn on_flow(self: &Arc<Self>, flow: &BlockFlow) {
+        match flow {
+            BlockFlow::TextOps { block_id, .. } => {
+                let Ok(session) = self.session(block_id) else {
+                    return;
+                };
+                let manager = self.clone();
+                tokio::spawn(async move {
+                    let mut input = session.input.lock().await;
+                    let owner = session.owner;
+                    if let Err(e) = manager
+                        .pump_locked(&session, &mut input, Role::User, owner)
+                        .await
+                    {
+                        tracing::warn!(block = %session.block_id.to_key(), "REPL input failed: {e}");
+                    }
+                });
+            }
+            BlockFlow::Deleted { block_id, .. } => {
+                self.close(block_id, false);
+            }
+            _ => {}
+        }
+    }
+
+    /// Send the session block's complete, unsent lines. Returns the output
+    /// block opened for them, or `None` when there was nothing to send.
+    async fn pump_locked(
+        &self,
+        session: &Arc<ReplSession>,
+        input: &mut InputState,
+        role: Role,
+        principal: PrincipalId,
+    ) -> Result<Option<BlockId>, ReplError> {
+        let ctx = session.context_id();
+        let content = self
+            .documents
+            .get_block_snapshot(ctx, &session.block_id)?
+            .map(|b| b.content)
+            .unwrap_or_default();
+        let Some(batch) = next_batch(&content, &mut input.sent) else {
+            return Ok(None);
+        };
+        *session.last_input.lock() = Instant::now();
+
+        let previous = session.output.lock().block.take();
+        if let Some(previous) = previous {
+            let _ = self.documents.set_status(ctx, &previous, Status::Done);
+        }
+        let after = self.documents.last_block_id(ctx);
+        let output = self.documents.insert_block_as(
+            ctx,
+            Some(&session.block_id),
+            after.as_ref(),
+            role,
+            BlockKind::Repl,
+            repl_echo(session.language, batch),
+            Status::Running,
+            ContentType::Plain,
+            Some(principal),
+        )?;
+        self.documents
+            .set_language(ctx, &output, Some(session.language.as_str().to_string()))?;
+        {
+            let mut out = session.output.lock();
+            out.block = Some(output);
+            out.last_output = Instant::now();
+        }
+
+        match &mut input.interpreter {
+            Interpreter::Host(stdin) => {
+                let written = async {
+                    stdin.write_all(batch.as_bytes()).await?;
+                    stdin.flush().await
+                };
+                if let Err(e) = written.await {
+                    let _ = self.documents.set_status(ctx, &output, Status::Error);
+                    return Err(ReplError::Exited(e.to_string()));
+                }
+            }
+            Interpreter::Kaish(kaish) => {
+                let result = kaish
+                    .execute_with_options(batch, kaish_kernel::ExecuteOptions::default())
+                    .await;
+                let (text, failed) = match result {
+                    Ok(r) => {
+                        let mut text = r.text_out().into_owned();
+                        text.push_str(&r.err);
+                        (text, r.code != 0)
+                    }
+                    Err(e) => (format!("{e}\n"), true),
+                };
+                self.emit(session, &text);
+                let status = if failed { Status::Error } else { Status::Done };
+                let _ = self.documents.set_status(ctx, &output, status);
+                session.output.lock().block = None;
+            }
+        }
+        Ok(Some(output))
+    }
+
+    /// Stream interpreter output into the current output block, opening one
+    /// (with no echo) for output that arrives before any input.
+    fn emit(&self, session: &ReplSession, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let ctx = session.context_id();
+        let current = session.output.lock().block;
+        let block = match current {
+            Some(block) => block,
+            None => {
+                let after = self.documents.last_block_id(ctx);
+                let opened = self.documents.insert_block_as(
+                    ctx,
+                    Some(&session.block_id),
+                    after.as_ref(),
+                    Role::Tool,
+                    BlockKind::Repl,
+                    "",
+                    Status::Running,
+                    ContentType::Plain,
+                    Some(PrincipalId::system()),
+                );
+                match opened {
+                    Ok(block) => {
+                        session.output.lock().block = Some(block);
+                        block
+                    }
+                    Err(e) => {
+                        tracing::warn!(block = %session.block_id.to_key(), "REPL output lost: {e}");
+                        return;
+                    }
+                }
+            }
+        };
+        if let Err(e) =
+            self.documents
+                .append_text_as(ctx, &block, text, Some(PrincipalId::system()))
+        {
+            tracing::warn!(block = %block.to_key(), "REPL output lost: {e}");
+        }
+        session.output.lock().last_output = Instant::now();
+    }
+
+    /// Mark `output` done if it is still the block output streams into.
+    fn finish_output(&self, session: &ReplSession, output: &BlockId) {
+        let mut out = session.output.lock();
+        if out.block == Some(*output) {
+            out.block = None;
+            let _ = self
+                .documents
+                .set_status(session.context_id(), output, Status::Done);
+        }
+    }
+
+    fn spawn_reader<R>(self: &Arc<Self>, session: &Arc<ReplSession>, mut pipe: R)
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let manager = Arc::downgrade(self);
+        let session = session.clone();
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; READ_CHUNK];
+            let mut pending = Vec::new();
+            loop {
+                let n = match pipe.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                pending.extend_from_slice(&buf[..n]);
+                let text = take_utf8(&mut pending);
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.emit(&session, &text);
+            }
+        });
+    }
+
+    /// Remove a session, kill its process, and settle its blocks. Returns
+    /// whether it was live.
+    fn close(&self, block_id: &BlockId, failed: bool) -> bool {
+        let Some(session) = self.sessions.lock().remove(block_id) else {
+            return false;
+        };
+        session.cancel.cancel();
+        let ctx = session.context_id();
+        if let Some(output) = session.output.lock().block.take() {
+            let _ = self.documents.set_status(ctx, &output, Status::Done);
+        }
+        let status = if failed { Status::Error } else { Status::Done };
+        // The block may be what was deleted.
+        let _ = self.documents.set_status(ctx, block_id, status);
+        tracing::info!(block = %block_id.to_key(), failed, "REPL session closed");
+        true
+    }
+}
+
+/// Spawn the host interpreter with prompts off — the echo in each output
+/// block stands in for them.
+fn spawn_host(
+    language: ReplLanguage,
+    path: Option<String>,
+    cwd: Option<PathBuf>,
+) -> std::io::Result<Child> {
+    let (program, args): (&str, &[&str]) = match language {
+        ReplLanguage::Python => (
+            "python3",
+            &["-q", "-u", "-i", "-c", "import sys; sys.ps1 = sys.ps2 = ''"],
+        ),
+        ReplLanguage::Node => (
+            "node",
+            &[
+                "-e",
+                "require('repl').start({ prompt: '', terminal: false, useColors: false })",
+            ],
+        ),
+        ReplLanguage::Kaish => {
+            return Err(std::io::Error::other("kaish is not a host process"));
+        }
+    };
+    let mut cmd = Command::new(program);
+    cmd.args(args)
+        .current_dir(
+            cwd.filter(|d| d.is_dir())
+                .unwrap_or_else(|| PathBuf::from("/")),
+        )
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(path) = path {
+        cmd.env("PATH", path);
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        cmd.env("HOME", home);
+    }
+    cmd.spawn()
+}
+
+/// The complete lines of `content` past `*sent`, advancing `*sent` over
+/// them. An offset the content no longer supports (the block was edited
+/// above it) restarts from the end, so nothing is sent twice.
+fn next_batch<'a>(content: &'a str, sent: &mut usize) -> Option<&'a str> {
+    if *sent > content.len() || !content.is_char_boundary(*sent) {
+        *sent = content.len();
+        return None;
+    }
+    let pending = &content[*sent..];
+    let end = pending.rfind('\n')? + 1;
+    *sent += end;
+    Some(&pending[..end])
+}
+
+/// Decode the longest valid UTF-8 prefix of `bytes`, leaving a split
+/// character's head for the next read. Invalid bytes are replaced.
+fn take_utf8(bytes: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => bytes.len(),
+    };
+    let text = String::from_utf8_lossy(&bytes[..valid]).into_owned();
+    bytes.drain(..valid);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_stop_at_the_last_complete_line() {
+        let mut sent = 0;
+        assert_eq!(next_batch("x = 1", &mut sent), None);
+        assert_eq!(next_batch("x = 1\nprint(", &mut sent), Some("x = 1\n"));
+        assert_eq!(sent, 6);
+        assert_eq!(
+            next_batch("x = 1\nprint(x)\ny\n", &mut sent),
+            Some("print(x)\ny\n")
+        );
+        assert_eq!(next_batch("x = 1\nprint(x)\ny\n", &mut sent), None);
+    }
+
+    #[test]
+    fn shrunk_input_restarts_from_the_end() {
+        let mut sent = 10;
+        assert_eq!(next_batch("héllo\n", &mut sent), None);
+        assert_eq!(sent, 7);
+        let mut sent = 2;
+        assert_eq!(next_batch("héllo\n", &mut sent), None, "mid-character");
+        assert_eq!(sent, 7);
+    }
+
+    #[test]
+    fn split_characters_wait_for_their_tail() {
+        let mut bytes = "ok é".as_bytes().to_vec();
+        let tail = bytes.pop().unwrap();
+        assert_eq!(take_utf8(&mut bytes), "ok ");
+        bytes.push(tail);
+        assert_eq!(take_utf8(&mut bytes), "é");
+        assert!(bytes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn kaish_session_streams_each_submission_into_a_child_block() {
+        let d = crate::kj::test_helpers::test_dispatcher().await;
+        let principal = PrincipalId::new();
+        let ctx = crate::kj::test_helpers::register_context(&d, Some("repl"), None, principal);
+        let kaish = d
+            .materialize_context_kaish(
+                "repl-test",
+                principal,
+                ctx,
+                kaijutsu_types::SessionId::new(),
+                None,
+                d.block_source(),
+            )
+            .await
+            .unwrap();
+        let store = d.block_store();
+        store
+            .create_document(ctx, kaijutsu_types::DocKind::Conversation, None)
+            .unwrap();
+        let manager = ReplManager::new(store.clone());
+        let session = manager
+            .start(
+                ctx,
+                ReplLanguage::Kaish,
+                ReplBackend::Kaish(Box::new(kaish)),
+                principal,
+            )
+            .await
+            .unwrap();
+
+        let out = manager
+            .send(
+                &session,
+                "X=42; echo $X",
+                Role::Tool,
+                principal,
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+        assert_eq!(out.transcript, "$ X=42; echo $X\n42\n");
+        let again = manager
+            .send(
+                &session,
+                "echo $X",
+                Role::Tool,
+                principal,
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+        assert!(
+            again.transcript.ends_with("42\n"),
+            "state persists: {again:?}"
+        );
+
+        let block = store
+            .get_block_snapshot(ctx, &out.block_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (block.kind, block.parent_id),
+            (BlockKind::Repl, Some(session))
+        );
+        assert_eq!(block.status, Status::Done);
+        assert_eq!(
+            store
+                .get_block_snapshot(ctx, &session)
+                .unwrap()
+                .unwrap()
+                .content,
+            "X=42; echo $X\necho $X\n"
+        );
+
+        assert!(manager.stop(&session));
+        assert!(manager.sessions_in(ctx).is_empty());
+        let closed = store.get_block_snapshot(ctx, &session).unwrap().unwrap();
+        assert_eq!(closed.status, Status::Done);
+        assert!(matches!(
+            manager
+                .send(
+                    &session,
+                    "echo hi",
+                    Role::Tool,
+                    principal,
+                    Duration::from_secs(1)
+                )
+                .await,
+            Err(ReplError::NotFound(_))
+        ));
+    }
+}
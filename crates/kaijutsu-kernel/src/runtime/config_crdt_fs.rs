@@ -1040,7 +1040,7 @@ mod tests {
 
         assert!(fs.is_empty(), "fresh config mount owns nothing");
         let n = fs.seed_entries(crate::config_seed::config_seed_files()).unwrap();
-        assert_eq!(n, 4, "the four config files seed on a fresh mount");
+        assert_eq!(n, 10, "the ten config files seed on a fresh mount");
 
         // models.toml round-trips through the VFS (read mount-relative).
         let models = fs.read_all(p("models.toml")).await.unwrap();
@@ -48,6 +48,7 @@ use super::input_filesystem::InputFilesystem;
 use super::kaish_backend::KaijutsuBackend;
 use super::mount_backend::MountBackend;
 use super::read_only_fs::ReadOnlyFs;
+use super::sandbox::ExecSandbox;
 use super::context_engine::{SessionContextExt, SessionContextMap};
 
 /// Embedded kaish executor backed by CRDT blocks.
@@ -83,6 +84,10 @@ pub enum ExternalExec {
     /// Host subprocess exec enabled. `path` seeds `$PATH` in the shell's scope
     /// (kaish never reads OS env); absolute paths work regardless of `path`.
     Allow { path: Option<String> },
+    /// Host subprocesses only through the context's sandbox profile: kaish's
+    /// own external exec stays off and the `MountBackend` launches them
+    /// confined (see `runtime::sandbox`).
+    Sandboxed(Arc<ExecSandbox>),
 }
 
 impl EmbeddedKaish {
@@ -232,11 +237,13 @@ impl EmbeddedKaish {
                 file_cache,
             ))
         } else {
-            Arc::new(MountBackend::new(
-                mount_table,
-                docs_backend.clone(),
-                file_cache,
-            ))
+            let backend = MountBackend::new(mount_table, docs_backend.clone(), file_cache);
+            match &external_exec {
+                ExternalExec::Sandboxed(sandbox) => {
+                    Arc::new(backend.with_exec_sandbox(sandbox.clone()))
+                }
+                _ => Arc::new(backend),
+            }
         };
 
         let docs_fs = Arc::new(KaijutsuFilesystem::new(docs_backend));
@@ -311,6 +318,11 @@ impl EmbeddedKaish {
                         .insert("PATH".to_string(), kaish_kernel::ast::Value::String(p.clone()));
                 }
             }
+            // kaish must not spawn anything itself: unknown commands reach the
+            // MountBackend, which hands them to the sandbox.
+            ExternalExec::Sandboxed(_) => {
+                config = config.with_allow_external_commands(false);
+            }
         }
 
         // The CRDT document views (`/v/docs`, `/v/input`) are mounted directly
@@ -492,105 +492,9 @@ impl Tool for KjBuiltin {
             .downcast_mut::<ExecContext>()
             .expect("kj builtin always runs against the kernel ExecContext");
 
-        // Build argv from positional args + named args + flags.
-        // kaish splits `kj fork --name exploration` into:
-        //   positional: ["fork"], named: {"name": "exploration"}
-        // We reconstruct the flat argv that KjDispatcher.dispatch() expects.
-        let mut argv: Vec<String> = args
-            .positional
-            .iter()
-            .map(|v| match v {
-                Value::String(s) => s.clone(),
-                Value::Int(n) => n.to_string(),
-                Value::Float(f) => f.to_string(),
-                Value::Bool(b) => b.to_string(),
-                other => format!("{other:?}"),
-            })
-            .collect();
-
-        // Reconstruct --key value pairs from named args.
-        for (key, val) in &args.named {
-            let flag = if key.len() == 1 {
-                format!("-{key}")
-            } else {
-                format!("--{key}")
-            };
-            match val {
-                // Repeatable value flags (clap `ArgAction::Append`, i.e. a
-                // `Vec<String>` arg like fork's `--include` / `--exclude`) are
-                // accumulated by kaish under one `named` key as a
-                // `Value::Json(Array(...))` — one element per occurrence (see
-                // kaish `push_repeatable_value`). Emit the flag once per element
-                // so clap's `Vec<_>` re-parse sees each value as its own
-                // occurrence. Before this, the array fell through to the
-                // `{other:?}` arm below and reached clap as a single
-                // Debug-formatted token like `Json(Array [String("0:1")])`,
-                // which the range parser then rejected as a bad endpoint — the
-                // live `kj fork --include 0:1` failure. A `serde_json` string
-                // element must be pushed raw (its `to_string()` re-quotes it).
-                Value::Json(serde_json::Value::Array(items)) => {
-                    for item in items {
-                        argv.push(flag.clone());
-                        match item {
-                            serde_json::Value::String(s) => argv.push(s.clone()),
-                            other => argv.push(other.to_string()),
-                        }
-                    }
-                }
-                Value::String(s) => {
-                    argv.push(flag);
-                    argv.push(s.clone());
-                }
-                Value::Int(n) => {
-                    argv.push(flag);
-                    argv.push(n.to_string());
-                }
-                Value::Float(f) => {
-                    argv.push(flag);
-                    argv.push(f.to_string());
-                }
-                Value::Bool(b) => {
-                    argv.push(flag);
-                    argv.push(b.to_string());
-                }
-                other => {
-                    argv.push(flag);
-                    argv.push(format!("{other:?}"));
-                }
-            }
-        }
-
-        // Reconstruct boolean flags. `json` is skipped here so the global
-        // `--json` flag never reaches `KjDispatcher::dispatch()`'s per-subcommand
-        // clap re-parse: kaish 0.13 owns `--json` entirely now
-        // (`GlobalFlags::apply_from_args`, called by the kaish kernel before
-        // `execute()` runs, reads kaish's OWN structured `args.flags` — a
-        // `HashSet<String>` of flag *names*, no dashes — to set
-        // `ctx.output_format`; `finalize_output`/`apply_output_format` then
-        // render this call's `ExecResult` after `execute()` returns — see
-        // `schema()`'s `owns_output` note). Most kj leaves don't declare their
-        // own `json` field, so handing "--json" to the re-parse below would
-        // make those leaves reject it as an unrecognized argument. (A handful
-        // of leaves — `doc list`, `config show`/`list`, `rc list`/`show`,
-        // `search` — DO carry their own local `json: bool` field for a
-        // differently-shaped internal message; that field is unreachable via
-        // this live kaish bridge either way, since --json never survives to
-        // here regardless of this filter — only `KjDispatcher::dispatch()`
-        // called directly, as the dispatcher-level unit tests do, ever sets
-        // it. See the kaish 0.13 `--json` migration audit in docs/issues.md.)
-        //
-        // Using kaish's structured `args.flags` (rather than string-matching
-        // "--json" tokens in already-flattened argv, the old approach) also
-        // keeps a literal `"--json"` *value* safe — e.g. `kj config set foo
-        // --content --json`, where "--json" is `--content`'s value, not a
-        // flag — since `args.flags` only ever contains flag *names*.
-        for flag in args.flags.iter().filter(|f| f.as_str() != "json") {
-            if flag.len() == 1 {
-                argv.push(format!("-{flag}"));
-            } else {
-                argv.push(format!("--{flag}"));
-            }
-        }
+        // Flat argv for KjDispatcher.dispatch(); `json` is kaish's (see
+        // `tool_args_to_argv`).
+        let mut argv = tool_args_to_argv(&args, &["json"]);
 
         // Extract --confirm <nonce> before dispatch
         let confirm_nonce = crate::kj::parse::extract_named_arg(&argv, &["--confirm"]);
@@ -649,6 +553,19 @@ impl Tool for KjBuiltin {
                 Ok(()) => caller.confirmed = true,
                 Err(e) => return ExecResult::failure(1, format!("kj: {e}")),
             }
+            let kernel = self.dispatcher.kernel();
+            crate::consent_log::record(
+                self.dispatcher.kernel_db(),
+                crate::consent_log::ConsentDraft {
+                    principal_id: caller.principal_id,
+                    context_id: caller.context_id,
+                    action: cmd_scope.clone(),
+                    params_hash: crate::consent_log::params_hash(argv.join("\0").as_bytes()),
+                    verdict: kaijutsu_types::ConsentVerdict::Approved,
+                    basis: "latch".into(),
+                    mode: kernel.consent_mode().await,
+                },
+            );
         }
 
         // Server-crate commands intercepted here because they require
@@ -758,6 +675,113 @@ impl Tool for KjBuiltin {
     }
 }
 
+/// Flatten kaish `ToolArgs` back into an argv: positionals first, then
+/// `--key value` pairs from named args, then boolean flags (minus
+/// `skip_flags`). Shared by `kj` and the sandboxed host-command launcher
+/// (`runtime::sandbox`).
+pub(crate) fn tool_args_to_argv(args: &ToolArgs, skip_flags: &[&str]) -> Vec<String> {
+    // Build argv from positional args + named args + flags.
+    // kaish splits `kj fork --name exploration` into:
+    //   positional: ["fork"], named: {"name": "exploration"}
+    // We reconstruct the flat argv that KjDispatcher.dispatch() expects.
+    let mut argv: Vec<String> = args
+        .positional
+        .iter()
+        .map(|v| match v {
+            Value::String(s) => s.clone(),
+            Value::Int(n) => n.to_string(),
+            Value::Float(f) => f.to_string(),
+            Value::Bool(b) => b.to_string(),
+            other => format!("{other:?}"),
+        })
+        .collect();
+
+    // Reconstruct --key value pairs from named args.
+    for (key, val) in &args.named {
+        let flag = if key.len() == 1 {
+            format!("-{key}")
+        } else {
+            format!("--{key}")
+        };
+        match val {
+            // Repeatable value flags (clap `ArgAction::Append`, i.e. a
+            // `Vec<String>` arg like fork's `--include` / `--exclude`) are
+            // accumulated by kaish under one `named` key as a
+            // `Value::Json(Array(...))` — one element per occurrence (see
+            // kaish `push_repeatable_value`). Emit the flag once per element
+            // so clap's `Vec<_>` re-parse sees each value as its own
+            // occurrence. Before this, the array fell through to the
+            // `{other:?}` arm below and reached clap as a single
+            // Debug-formatted token like `Json(Array [String("0:1")])`,
+            // which the range parser then rejected as a bad endpoint — the
+            // live `kj fork --include 0:1` failure. A `serde_json` string
+            // element must be pushed raw (its `to_string()` re-quotes it).
+            Value::Json(serde_json::Value::Array(items)) => {
+                for item in items {
+                    argv.push(flag.clone());
+                    match item {
+                        serde_json::Value::String(s) => argv.push(s.clone()),
+                        other => argv.push(other.to_string()),
+                    }
+                }
+            }
+            Value::String(s) => {
+                argv.push(flag);
+                argv.push(s.clone());
+            }
+            Value::Int(n) => {
+                argv.push(flag);
+                argv.push(n.to_string());
+            }
+            Value::Float(f) => {
+                argv.push(flag);
+                argv.push(f.to_string());
+            }
+            Value::Bool(b) => {
+                argv.push(flag);
+                argv.push(b.to_string());
+            }
+            other => {
+                argv.push(flag);
+                argv.push(format!("{other:?}"));
+            }
+        }
+    }
+
+    // Reconstruct boolean flags. `kj` skips `json` so the global
+    // `--json` flag never reaches `KjDispatcher::dispatch()`'s per-subcommand
+    // clap re-parse: kaish 0.13 owns `--json` entirely now
+    // (`GlobalFlags::apply_from_args`, called by the kaish kernel before
+    // `execute()` runs, reads kaish's OWN structured `args.flags` — a
+    // `HashSet<String>` of flag *names*, no dashes — to set
+    // `ctx.output_format`; `finalize_output`/`apply_output_format` then
+    // render this call's `ExecResult` after `execute()` returns — see
+    // `schema()`'s `owns_output` note). Most kj leaves don't declare their
+    // own `json` field, so handing "--json" to the re-parse below would
+    // make those leaves reject it as an unrecognized argument. (A handful
+    // of leaves — `doc list`, `config show`/`list`, `rc list`/`show`,
+    // `search` — DO carry their own local `json: bool` field for a
+    // differently-shaped internal message; that field is unreachable via
+    // this live kaish bridge either way, since --json never survives to
+    // here regardless of this filter — only `KjDispatcher::dispatch()`
+    // called directly, as the dispatcher-level unit tests do, ever sets
+    // it. See the kaish 0.13 `--json` migration audit in docs/issues.md.)
+    //
+    // Using kaish's structured `args.flags` (rather than string-matching
+    // "--json" tokens in already-flattened argv, the old approach) also
+    // keeps a literal `"--json"` *value* safe — e.g. `kj config set foo
+    // --content --json`, where "--json" is `--content`'s value, not a
+    // flag — since `args.flags` only ever contains flag *names*.
+    for flag in args.flags.iter().filter(|f| !skip_flags.contains(&f.as_str())) {
+        if flag.len() == 1 {
+            argv.push(format!("-{flag}"));
+        } else {
+            argv.push(format!("--{flag}"));
+        }
+    }
+    argv
+}
+
 /// Read the rc recursion depth from the shell scope's `KJ_RC_DEPTH`.
 ///
 /// The rc runner (`kj/lifecycle.rs::run_kai_script`) seeds this overlay var
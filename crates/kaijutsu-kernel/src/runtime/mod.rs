@@ -12,8 +12,11 @@
 //! - `docs_filesystem` — `/v/docs` (CRDT blocks-as-files).
 //! - `input_filesystem` — `/v/input` (compose CRDT).
 //! - `read_only_fs` — read-only wrapper for the `/v/*` CRDT mounts (toolie).
+//! - `sandbox` — confined host-command launcher for sandboxed contexts.
 //! - `context_engine` — per-session "current context" registry.
 //! - `kj_builtin` — the `kj` kaish Tool.
+//! - `testreport_builtin` — `testreport`, structured results from piped test
+//!   runner output.
 
 pub mod config_crdt_fs;
 pub mod context_engine;
@@ -24,5 +27,7 @@ pub mod kaish_backend;
 pub mod kj_builtin;
 pub mod mount_backend;
 pub mod read_only_fs;
+pub mod sandbox;
 pub mod synthesis;
+pub mod testreport_builtin;
 pub mod vi_builtin;
@@ -10,6 +10,7 @@
 //! MountBackend (implements kaish KernelBackend)
 //! ├── File ops → MountTable → LocalBackend → real filesystem
 //! └── Tool calls → docs_tools → ToolNotFound
+//!                             └── (sandboxed context) ExecSandbox → host binary
 //! ```
 
 use std::path::{Path, PathBuf};
@@ -29,6 +30,7 @@ use crate::file_tools::{CacheReadError, FileDocumentCache};
 use crate::vfs::{FileType, MountTable, SetAttr, VfsError, VfsOps};
 
 use super::kaish_backend::KaijutsuBackend;
+use super::sandbox::ExecSandbox;
 
 /// Routes file *content* operations through the shared CRDT
 /// `FileDocumentCache` and directory/metadata/tool operations through
@@ -57,6 +59,9 @@ pub struct MountBackend {
     /// `/v/input` mounts are gated separately by wrapping them in
     /// [`super::read_only_fs::ReadOnlyFs`] (they don't route through here).
     read_only: bool,
+    /// Set for a sandboxed context: commands no tool answers run as host
+    /// binaries through the sandbox (kaish's own external exec is off).
+    exec_sandbox: Option<Arc<ExecSandbox>>,
 }
 
 impl MountBackend {
@@ -71,6 +76,7 @@ impl MountBackend {
             file_cache,
             docs_tools,
             read_only: false,
+            exec_sandbox: None,
         }
     }
 
@@ -88,9 +94,16 @@ impl MountBackend {
             file_cache,
             docs_tools,
             read_only: true,
+            exec_sandbox: None,
         }
     }
 
+    /// Route host commands through `sandbox` (see `runtime::sandbox`).
+    pub fn with_exec_sandbox(mut self, sandbox: Arc<ExecSandbox>) -> Self {
+        self.exec_sandbox = Some(sandbox);
+        self
+    }
+
     /// The single read-only gate every mutating op consults. Returns
     /// `Err(PermissionDenied)` when this backend is read-only, `Ok(())`
     /// otherwise — so the op refuses by construction rather than relying on the
@@ -676,7 +689,16 @@ impl KernelBackend for MountBackend {
         args: ToolArgs,
         ctx: &mut dyn ToolCtx,
     ) -> BackendResult<ToolResult> {
-        self.docs_tools.call_tool(name, args, ctx).await
+        let Some(sandbox) = &self.exec_sandbox else {
+            return self.docs_tools.call_tool(name, args, ctx).await;
+        };
+        // Tools win over host binaries of the same name, as they do when
+        // kaish spawns externals itself.
+        if self.docs_tools.get_tool(name).await?.is_some() {
+            return self.docs_tools.call_tool(name, args, ctx).await;
+        }
+        let real_cwd = self.mount_table.resolve_real_path_sync(ctx.cwd());
+        Ok(sandbox.run(name, &args, real_cwd, ctx).await)
     }
 
     async fn list_tools(&self) -> BackendResult<Vec<ToolInfo>> {
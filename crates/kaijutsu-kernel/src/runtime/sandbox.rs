@@ -0,0 +1,602 @@
+//! Sandboxed host-command launcher for contexts with a sandbox profile.
+//!
+//! A context whose loadout grants `exec` *and* which has a
+//! [`SandboxProfile`] gets a shell with kaish's own external exec disabled
+//! ([`ExternalExec::Sandboxed`](super::embedded_kaish::ExternalExec)):
+//! unknown commands fall through `MountBackend::call_tool` to
+//! [`ExecSandbox::run`], which launches the host binary itself so it can
+//! confine it first:
+//!
+//! - the binary must be on the profile's allowlist
+//!   ([`SandboxProfile::allows_binary`]);
+//! - `limits` are set as rlimits in the child, clamped to the kernel's own
+//!   hard limits;
+//! - `network: false` unshares a user + network namespace (Linux);
+//! - `fs_from_mounts` applies a Landlock ruleset: read access to
+//!   [`SANDBOX_SYSTEM_PATHS`] and read-only mounts, full access to writable
+//!   mounts (Linux ≥ 5.13). Execute is granted only on the resolved binary
+//!   and what the kernel loads to start it (its ELF loader, or a script's
+//!   `#!` interpreter), so the process can't exec anything else.
+//!
+//! The allowlist is checked once, on the command kaish hands over. Without
+//! `fs_from_mounts` nothing stops that process from exec'ing other
+//! binaries — an allowlisted shell or interpreter runs whatever it's told.
+//! Pair the allowlist with `fs_from_mounts` when it must bind children too.
+//!
+//! Enforcement fails closed: a restriction the host can't apply (Landlock
+//! missing, user namespaces disabled, not Linux) refuses the command instead
+//! of running it unconfined. Every refusal is reported as an Error block in
+//! the context, on top of the command's non-zero exit. The profile applies
+//! whatever the context's consent mode — consent gates *tool calls*; this
+//! gates what the process can do once running.
+//!
+//! The child gets a clean environment: `PATH` (the kernel's startup capture)
+//! and the shell's `HOME`, nothing inherited from the kernel process. Its
+//! argv is rebuilt from kaish's parsed arguments (positionals, then
+//! `--key value` pairs, then flags).
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use kaijutsu_types::sandbox::SANDBOX_SYSTEM_PATHS;
+use kaijutsu_types::{ContextId, PrincipalId, SandboxProfile};
+use kaish_kernel::tools::{ToolArgs, ToolCtx};
+use kaish_kernel::{ExecContext, ToolResult};
+use tokio::io::AsyncWriteExt;
+
+use crate::Kernel as KaijutsuKernel;
+use crate::block_store::SharedBlockStore;
+
+/// Why a sandboxed command was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum SandboxViolation {
+    #[error("'{program}' is not in this context's sandbox (allowed: {allowed})")]
+    Binary { program: String, allowed: String },
+    #[error("'{program}': {what} can't be enforced here: {reason}")]
+    Unenforceable {
+        program: String,
+        what: &'static str,
+        reason: String,
+    },
+}
+
+/// A context's sandbox, resolved against the kernel at shell materialization.
+pub struct ExecSandbox {
+    context_id: ContextId,
+    principal_id: PrincipalId,
+    profile: SandboxProfile,
+    /// `$PATH` for name lookup and the child's environment.
+    path: Option<String>,
+    /// Real directories behind the VFS mounts, with whether each is writable.
+    /// Only consulted when `profile.fs_from_mounts` is set.
+    fs_scope: Vec<(PathBuf, bool)>,
+    blocks: SharedBlockStore,
+}
+
+impl std::fmt::Debug for ExecSandbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecSandbox")
+            .field("context_id", &self.context_id)
+            .field("profile", &self.profile)
+            .field("fs_scope", &self.fs_scope)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ExecSandbox {
+    /// Resolve `profile` for `context_id`: captures the kernel's `$PATH` and
+    /// the real roots of its current VFS mounts (virtual mounts have none and
+    /// grant nothing).
+    pub async fn new(
+        kernel: &KaijutsuKernel,
+        blocks: SharedBlockStore,
+        context_id: ContextId,
+        principal_id: PrincipalId,
+        profile: SandboxProfile,
+    ) -> Self {
+        let mount_table = kernel.vfs();
+        let fs_scope = mount_table
+            .list_mounts()
+            .await
+            .into_iter()
+            .filter_map(|m| {
+                mount_table
+                    .resolve_real_path_sync(&m.path)
+                    .map(|real| (real, !m.read_only))
+            })
+            .collect();
+        Self {
+            context_id,
+            principal_id,
+            profile,
+            path: kernel.host_path().map(str::to_string),
+            fs_scope,
+            blocks,
+        }
+    }
+
+    pub fn profile(&self) -> &SandboxProfile {
+        &self.profile
+    }
+
+    /// Find the binary `program` names: a path (anything with a `/`) is taken
+    /// relative to `real_cwd`, a bare name is looked up on `$PATH`.
+    fn resolve(&self, program: &str, real_cwd: Option<&Path>) -> Option<PathBuf> {
+        if program.contains('/') {
+            let path = Path::new(program);
+            let path = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                real_cwd?.join(path)
+            };
+            return path
+                .is_file()
+                .then(|| dunce::canonicalize(&path).unwrap_or(path));
+        }
+        std::env::split_paths(self.path.as_deref()?)
+            .map(|dir| dir.join(program))
+            .find(|candidate| is_executable(candidate))
+    }
+
+    /// Run `program` with `args` under the profile. `real_cwd` is the host
+    /// directory behind the shell's cwd (`None` for a virtual cwd, in which
+    /// case the command runs from `/`). Refusals come back as a failed
+    /// result and an Error block.
+    pub async fn run(
+        &self,
+        program: &str,
+        args: &ToolArgs,
+        real_cwd: Option<PathBuf>,
+        ctx: &mut dyn ToolCtx,
+    ) -> ToolResult {
+        let Some(resolved) = self.resolve(program, real_cwd.as_deref()) else {
+            return ToolResult::failure(127, format!("{program}: command not found"));
+        };
+        let resolved_str = resolved.to_string_lossy();
+        if !self.profile.allows_binary(program, &resolved_str) {
+            let allowed = if self.profile.binaries.is_empty() {
+                "none".to_string()
+            } else {
+                self.profile.binaries.join(", ")
+            };
+            return self.refuse(SandboxViolation::Binary {
+                program: program.to_string(),
+                allowed,
+            });
+        }
+
+        let (stdin, home) = match ctx.as_any_mut().downcast_mut::<ExecContext>() {
+            Some(exec) => {
+                let stdin = match exec.read_stdin_to_text().await {
+                    Ok(stdin) => stdin,
+                    Err(e) => {
+                        return ToolResult::failure(1, format!("{program}: reading stdin: {e}"));
+                    }
+                };
+                let home = exec
+                    .scope
+                    .get("HOME")
+                    .map(kaish_kernel::interpreter::value_to_string);
+                (stdin, home)
+            }
+            None => (None, None),
+        };
+
+        let mut cmd = tokio::process::Command::new(&resolved);
+        cmd.args(super::kj_builtin::tool_args_to_argv(args, &[]))
+            .current_dir(real_cwd.as_deref().unwrap_or(Path::new("/")))
+            .env_clear()
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(path) = &self.path {
+            cmd.env("PATH", path);
+        }
+        if let Some(home) = home {
+            cmd.env("HOME", home);
+        }
+        if let Err(violation) = self.confine(&mut cmd, program, &resolved) {
+            return self.refuse(violation);
+        }
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            // The confinement hooks run in the child before exec; their
+            // failure surfaces here.
+            Err(e) if self.confines() => {
+                return self.refuse(SandboxViolation::Unenforceable {
+                    program: program.to_string(),
+                    what: "the sandbox",
+                    reason: e.to_string(),
+                });
+            }
+            Err(e) => return ToolResult::failure(126, format!("{program}: {e}")),
+        };
+        if let (Some(text), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            // A child that exits without reading its stdin closes the pipe;
+            // that's its business, not an error.
+            let _ = pipe.write_all(text.as_bytes()).await;
+        }
+        let output = match child.wait_with_output().await {
+            Ok(output) => output,
+            Err(e) => return ToolResult::failure(1, format!("{program}: {e}")),
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let mut stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        if output.status.success() {
+            return ToolResult::success(stdout);
+        }
+        let code = match output.status.code() {
+            Some(code) => code,
+            None => {
+                let signal = exit_signal(&output.status).unwrap_or(0);
+                if !self.profile.limits.is_empty() {
+                    stderr.push_str(&format!(
+                        "{program}: killed by signal {signal} (sandbox limits: {})\n",
+                        self.profile.limits
+                    ));
+                }
+                128 + signal
+            }
+        };
+        // A failed result carries one stream: what the command printed to
+        // stdout goes ahead of its stderr.
+        ToolResult::failure(code, stdout + &stderr)
+    }
+
+    /// Whether the profile restricts the process beyond the allowlist.
+    fn confines(&self) -> bool {
+        !self.profile.network || self.profile.fs_from_mounts || !self.profile.limits.is_empty()
+    }
+
+    /// Install the profile's process restrictions on `cmd`, applied in the
+    /// child between fork and exec.
+    #[cfg(unix)]
+    fn confine(
+        &self,
+        cmd: &mut tokio::process::Command,
+        program: &str,
+        resolved: &Path,
+    ) -> Result<(), SandboxViolation> {
+        if !self.confines() {
+            return Ok(());
+        }
+        let rlimits = self.rlimits();
+        #[cfg(target_os = "linux")]
+        let mut ruleset = if self.profile.fs_from_mounts {
+            Some(
+                self.landlock_ruleset(resolved)
+                    .map_err(|e| SandboxViolation::Unenforceable {
+                        program: program.to_string(),
+                        what: "filesystem scope",
+                        reason: e.to_string(),
+                    })?,
+            )
+        } else {
+            None
+        };
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = resolved;
+            let what = if !self.profile.network {
+                Some("network: off")
+            } else if self.profile.fs_from_mounts {
+                Some("filesystem scope")
+            } else {
+                None
+            };
+            if let Some(what) = what {
+                return Err(SandboxViolation::Unenforceable {
+                    program: program.to_string(),
+                    what,
+                    reason: "needs Linux namespaces and Landlock".to_string(),
+                });
+            }
+        }
+        #[cfg(target_os = "linux")]
+        let unshare_net = !self.profile.network;
+
+        // SAFETY: the hook runs in the forked child before exec. It only makes
+        // raw syscalls (setrlimit, unshare, Landlock's prctl/restrict) on
+        // values prepared in the parent.
+        unsafe {
+            cmd.pre_exec(move || {
+                for (resource, limit) in &rlimits {
+                    rustix::process::setrlimit(*resource, limit.clone())?;
+                }
+                #[cfg(target_os = "linux")]
+                {
+                    if unshare_net {
+                        rustix::thread::unshare(
+                            rustix::thread::UnshareFlags::NEWUSER
+                                | rustix::thread::UnshareFlags::NEWNET,
+                        )?;
+                    }
+                    if let Some(ruleset) = ruleset.take() {
+                        ruleset
+                            .restrict_self()
+                            .map_err(|e| std::io::Error::other(e.to_string()))?;
+                    }
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn confine(
+        &self,
+        _cmd: &mut tokio::process::Command,
+        program: &str,
+        _resolved: &Path,
+    ) -> Result<(), SandboxViolation> {
+        if !self.confines() {
+            return Ok(());
+        }
+        Err(SandboxViolation::Unenforceable {
+            program: program.to_string(),
+            what: "the sandbox",
+            reason: "process restrictions need a Unix host".to_string(),
+        })
+    }
+
+    /// The profile's limits as rlimits, clamped to the kernel's hard limits
+    /// (an unprivileged process can't raise them).
+    #[cfg(unix)]
+    fn rlimits(&self) -> Vec<(rustix::process::Resource, rustix::process::Rlimit)> {
+        use rustix::process::{Resource, Rlimit, getrlimit};
+
+        let limits = &self.profile.limits;
+        [
+            (Resource::Cpu, limits.cpu_secs),
+            (Resource::As, limits.memory_bytes),
+            (Resource::Fsize, limits.file_size_bytes),
+            (Resource::Nofile, limits.open_files),
+            (Resource::Nproc, limits.processes),
+        ]
+        .into_iter()
+        .filter_map(|(resource, value)| {
+            let value = value?;
+            let hard = getrlimit(resource).maximum;
+            let value = hard.map_or(value, |hard| value.min(hard));
+            Some((
+                resource,
+                Rlimit {
+                    current: Some(value),
+                    maximum: Some(value),
+                },
+            ))
+        })
+        .collect()
+    }
+
+    /// Build (in the parent) the Landlock ruleset for `fs_from_mounts`: read
+    /// beneath the system paths and read-only mounts; everything but execute
+    /// beneath writable mounts; read and execute on the binary and its
+    /// [`exec_companions`] only. Fails if the running kernel has no Landlock
+    /// support.
+    #[cfg(target_os = "linux")]
+    fn landlock_ruleset(
+        &self,
+        binary: &Path,
+    ) -> Result<landlock::RulesetCreated, landlock::RulesetError> {
+        use landlock::{
+            ABI, Access, AccessFs, CompatLevel, Compatible, Ruleset, RulesetAttr,
+            RulesetCreatedAttr, path_beneath_rules,
+        };
+
+        let abi = ABI::V1;
+        let read: Vec<PathBuf> = SANDBOX_SYSTEM_PATHS
+            .iter()
+            .map(PathBuf::from)
+            .chain(
+                self.fs_scope
+                    .iter()
+                    .filter(|(_, writable)| !writable)
+                    .map(|(path, _)| path.clone()),
+            )
+            .filter(|p| p.exists())
+            .collect();
+        let write: Vec<&PathBuf> = self
+            .fs_scope
+            .iter()
+            .filter(|(path, writable)| *writable && path.exists())
+            .map(|(path, _)| path)
+            .collect();
+        let exec: Vec<PathBuf> = std::iter::once(binary.to_path_buf())
+            .chain(exec_companions(binary))
+            .filter(|p| p.exists())
+            .collect();
+        let mut write_access = AccessFs::from_all(abi);
+        write_access.remove(AccessFs::Execute);
+        Ruleset::default()
+            .set_compatibility(CompatLevel::HardRequirement)
+            .handle_access(AccessFs::from_all(abi))?
+            .create()?
+            .add_rules(path_beneath_rules(
+                read,
+                AccessFs::ReadFile | AccessFs::ReadDir,
+            ))?
+            .add_rules(path_beneath_rules(
+                exec,
+                AccessFs::ReadFile | AccessFs::Execute,
+            ))?
+            .add_rules(path_beneath_rules(write, write_access))
+    }
+
+    /// Log `violation`, record it as an Error block in the context, and turn
+    /// it into the command's failure.
+    fn refuse(&self, violation: SandboxViolation) -> ToolResult {
+        tracing::warn!(
+            context_id = %self.context_id,
+            "sandbox refused host command: {violation}"
+        );
+        let summary = format!("sandbox: {violation}\nprofile: {}", self.profile);
+        let after = self.blocks.last_block_id(self.context_id);
+        if let Err(e) = self.blocks.insert_block_as(
+            self.context_id,
+            None,
+            after.as_ref(),
+            kaijutsu_crdt::Role::System,
+            kaijutsu_crdt::BlockKind::Error,
+            summary,
+            kaijutsu_crdt::Status::Error,
+            kaijutsu_crdt::ContentType::Plain,
+            Some(self.principal_id),
+        ) {
+            tracing::warn!(
+                context_id = %self.context_id,
+                "failed to insert sandbox violation block: {e}"
+            );
+        }
+        ToolResult::failure(126, format!("{violation}\n"))
+    }
+}
+
+/// What the kernel opens for execution alongside `binary`: its ELF loader
+/// (`PT_INTERP`), or a script's `#!` interpreter and that interpreter's
+/// loader. Best effort — an unreadable or unusual file yields nothing, and
+/// the exec then fails under Landlock rather than running wider.
+#[cfg(target_os = "linux")]
+fn exec_companions(binary: &Path) -> Vec<PathBuf> {
+    if let Some(loader) = elf_interpreter(binary) {
+        return vec![loader];
+    }
+    let Some(interpreter) = shebang_interpreter(binary) else {
+        return Vec::new();
+    };
+    let loader = elf_interpreter(&interpreter);
+    std::iter::once(interpreter).chain(loader).collect()
+}
+
+/// The `PT_INTERP` path of an ELF executable, if it has one.
+#[cfg(target_os = "linux")]
+fn elf_interpreter(path: &Path) -> Option<PathBuf> {
+    use std::io::{Read, Seek, SeekFrom};
+    use std::os::unix::ffi::OsStrExt;
+
+    const PT_INTERP: u32 = 3;
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut header = [0u8; 64];
+    file.read_exact(&mut header[..52]).ok()?;
+    if &header[..4] != b"\x7fELF" {
+        return None;
+    }
+    let wide = header[4] == 2;
+    let little = header[5] == 1;
+    let int = |bytes: &[u8]| -> u64 {
+        let push = |value: u64, byte: &u8| (value << 8) | u64::from(*byte);
+        if little {
+            bytes.iter().rev().fold(0, push)
+        } else {
+            bytes.iter().fold(0, push)
+        }
+    };
+    if wide {
+        file.read_exact(&mut header[52..]).ok()?;
+    }
+    let (phoff, phentsize, phnum, min_entry) = if wide {
+        (
+            int(&header[32..40]),
+            int(&header[54..56]),
+            int(&header[56..58]),
+            56,
+        )
+    } else {
+        (
+            int(&header[28..32]),
+            int(&header[42..44]),
+            int(&header[44..46]),
+            32,
+        )
+    };
+    if phentsize < min_entry {
+        return None;
+    }
+    let mut entry = vec![0u8; phentsize as usize];
+    for i in 0..phnum.min(256) {
+        file.seek(SeekFrom::Start(phoff + i * phentsize)).ok()?;
+        file.read_exact(&mut entry).ok()?;
+        if int(&entry[..4]) != u64::from(PT_INTERP) {
+            continue;
+        }
+        let (offset, size) = if wide {
+            (int(&entry[8..16]), int(&entry[32..40]))
+        } else {
+            (int(&entry[4..8]), int(&entry[16..20]))
+        };
+        if size == 0 || size > 4096 {
+            return None;
+        }
+        let mut interp = vec![0u8; size as usize];
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        file.read_exact(&mut interp).ok()?;
+        let end = interp.iter().position(|&b| b == 0).unwrap_or(interp.len());
+        return Some(PathBuf::from(std::ffi::OsStr::from_bytes(&interp[..end])));
+    }
+    None
+}
+
+/// The interpreter named on a script's `#!` line.
+#[cfg(target_os = "linux")]
+fn shebang_interpreter(path: &Path) -> Option<PathBuf> {
+    use std::io::Read;
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut head = [0u8; 256];
+    let mut file = std::fs::File::open(path).ok()?;
+    let n = file.read(&mut head).ok()?;
+    let line = head[..n].strip_prefix(b"#!")?;
+    let line = &line[..line.iter().position(|&b| b == b'\n')?];
+    let interpreter = line
+        .split(|b| b.is_ascii_whitespace())
+        .find(|word| !word.is_empty())?;
+    Some(PathBuf::from(std::ffi::OsStr::from_bytes(interpreter)))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_bring_their_interpreter_along() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("hello");
+        std::fs::write(&script, "#! /bin/sh -e\necho hi\n").unwrap();
+        let companions = exec_companions(&script);
+        assert_eq!(companions.first(), Some(&PathBuf::from("/bin/sh")));
+
+        std::fs::write(&script, "echo no shebang\n").unwrap();
+        assert!(exec_companions(&script).is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! `testreport` kaish builtin — structured results from a test run.
+//!
+//! `cargo test 2>&1 | testreport` parses the runner output piped in on stdin
+//! ([`TestReport::parse`]) and returns it as the command's structured `.data`,
+//! which the server stores on the `ToolResult` block. Agents (and the
+//! `test_results` MCP tool) then read per-test outcomes, durations, and
+//! failure excerpts from the block instead of regex-scraping its text.
+//!
+//! It parses rather than runs: the runner itself is an ordinary command in
+//! the pipeline, so the context's `exec` grant and sandbox profile apply to
+//! it exactly as they would without `testreport`.
+
+use async_trait::async_trait;
+
+use kaish_kernel::interpreter::ExecResult;
+use kaish_kernel::tools::{ParamSchema, ToolArgs, ToolCtx, ToolSchema};
+use kaish_kernel::{ExecContext, Tool, ast::Value};
+
+use kaijutsu_types::{TestReport, TestRunner};
+
+/// kaish builtin that turns piped test-runner output into a [`TestReport`].
+pub struct TestReportBuiltin;
+
+#[async_trait]
+impl Tool for TestReportBuiltin {
+    fn name(&self) -> &str {
+        "testreport"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchema::new(
+            "testreport",
+            "Parse test-runner output piped on stdin (cargo test, pytest) into \
+             structured per-test results. Exits 1 when any test failed.",
+        )
+        .param(
+            ParamSchema::optional(
+                "runner",
+                "string",
+                Value::Null,
+                "cargo or pytest (default: detect from the output)",
+            )
+            .positional(),
+        )
+        .example(
+            "Structured cargo test results",
+            "cargo test 2>&1 | testreport",
+        )
+        .example("Verbose pytest run", "pytest -v 2>&1 | testreport pytest")
+    }
+
+    async fn execute(&self, args: ToolArgs, ctx: &mut dyn ToolCtx) -> ExecResult {
+        let runner = match args.positional.first() {
+            Some(Value::String(name)) => match name.parse::<TestRunner>() {
+                Ok(runner) => Some(runner),
+                Err(_) => {
+                    return ExecResult::failure(
+                        1,
+                        format!("testreport: unknown runner '{name}' (cargo, pytest)"),
+                    );
+                }
+            },
+            Some(other) => {
+                return ExecResult::failure(1, format!("testreport: bad runner {other:?}"));
+            }
+            None => None,
+        };
+
+        let ctx = ctx
+            .as_any_mut()
+            .downcast_mut::<ExecContext>()
+            .expect("testreport always runs against the kernel ExecContext");
+        let output = match ctx.read_stdin_to_text().await {
+            Ok(Some(text)) if !text.is_empty() => text,
+            Ok(_) => {
+                return ExecResult::failure(
+                    1,
+                    "testreport: no input\nusage: cargo test 2>&1 | testreport [runner]",
+                );
+            }
+            Err(e) => return ExecResult::failure(1, format!("testreport: reading stdin: {e}")),
+        };
+
+        let Some(report) = TestReport::parse(runner, &output) else {
+            return ExecResult::failure(
+                1,
+                "testreport: no test results in the input (did the build fail?)",
+            );
+        };
+
+        let mut text = report.summary();
+        for case in report.failures() {
+            text.push_str(&format!("\nFAILED {}", case.name));
+            if let Some(failure) = &case.failure {
+                for line in failure.lines() {
+                    text.push_str(&format!("\n    {line}"));
+                }
+            }
+        }
+        // The summary is the output either way; failed tests fail the command
+        // so `&&` chains and the block status reflect the run.
+        let mut result = ExecResult::success(text);
+        if report.failed > 0 {
+            result.code = 1;
+        }
+        result.data = Some(kaish_kernel::interpreter::json_to_value(report.to_json()));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kj::test_helpers::test_dispatcher_crdt_rc;
+    use crate::runtime::context_engine::{SessionContextMap, session_context_map};
+    use crate::runtime::embedded_kaish::{EmbeddedKaish, ExternalExec};
+    use kaijutsu_types::{ContextId, PrincipalId, SessionId};
+    use kaish_kernel::ExecuteOptions;
+
+    async fn embedded_with_testreport() -> EmbeddedKaish {
+        let dispatcher = test_dispatcher_crdt_rc().await;
+        let session_id = SessionId::new();
+        let session_contexts = session_context_map();
+        let ctx = ContextId::new();
+        session_contexts.insert(session_id, ctx);
+        EmbeddedKaish::with_identity(
+            "test-testreport",
+            dispatcher.block_store().clone(),
+            dispatcher.kernel().clone(),
+            None,
+            PrincipalId::system(),
+            ctx,
+            session_id,
+            session_contexts,
+            ExternalExec::Deny,
+            |_scm: SessionContextMap, _sid: SessionId, tools: &mut kaish_kernel::ToolRegistry| {
+                tools.register(TestReportBuiltin);
+            },
+        )
+        .expect("EmbeddedKaish init")
+    }
+
+    /// Piped runner output comes back as a `test_report` payload in `.data`,
+    /// and a failing run fails the command.
+    #[tokio::test]
+    async fn piped_output_becomes_structured_data() {
+        let kaish = embedded_with_testreport().await;
+        let line = "test result: FAILED. 4 passed; 1 failed; 0 ignored; \
+                    0 measured; 0 filtered out; finished in 0.50s";
+        let res = kaish
+            .execute_with_options(
+                &format!("echo '{line}' | testreport cargo"),
+                ExecuteOptions::default(),
+            )
+            .await
+            .expect("kaish exec");
+
+        assert_eq!(res.code, 1, "a failed test fails the command: {res:?}");
+        assert!(res.text_out().starts_with("cargo: 4 passed, 1 failed"));
+        let json = kaish_kernel::interpreter::value_to_json(res.data.as_ref().expect("data"));
+        let report = TestReport::from_json(&json).expect("a test_report payload");
+        assert_eq!((report.passed, report.failed), (4, 1));
+    }
+
+    #[tokio::test]
+    async fn output_without_results_fails_loud() {
+        let kaish = embedded_with_testreport().await;
+        let res = kaish
+            .execute_with_options(
+                "echo 'error: could not compile' | testreport",
+                ExecuteOptions::default(),
+            )
+            .await
+            .expect("kaish exec");
+        assert!(!res.ok());
+        assert!(res.data.is_none());
+    }
+}
@@ -0,0 +1,600 @@
+//! Kernel-wide full-text block index (`{data_dir}/search.db`).
+//!
+//! `kj search` and MCP `kernel_search` scan every line of every document with
+//! a regex. That is fine for one context and hopeless for months of
+//! multi-agent history. [`SearchIndex`] keeps an inverted index of block text
+//! in SQLite FTS5 — ranked (BM25), with phrase and prefix queries — beside a
+//! metadata table the filters run against: context, kind, role, author
+//! (the block id's principal) and creation time.
+//!
+//! The index is fed incrementally from the block FlowBus by [`spawn_indexer`]:
+//! inserts are indexed from the event's snapshot, text and metadata changes
+//! mark the block dirty and it is re-read from the [`BlockStore`] on the next
+//! flush (streams land once, not once per token), and deletes drop the row.
+//! Contexts the index has never seen are backfilled when the indexer starts.
+//!
+//! **Staleness**: the bus is lossy under overflow. A missed edit is picked up
+//! by the block's next change or status transition, and a `SyncReset`
+//! re-reads the whole context.
+//!
+//! [`BlockStore`]: crate::block_store::BlockStore
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use kaijutsu_types::{BlockId, BlockKind, BlockSnapshot, ContextId, PrincipalId, Role};
+
+use crate::block_store::SharedBlockStore;
+use crate::flows::BlockFlow;
+
+/// How often the indexer re-reads blocks dirtied by text or metadata events.
+pub const INDEX_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default and maximum number of hits one search returns.
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+pub const MAX_SEARCH_LIMIT: usize = 500;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS block_meta (
+    rowid INTEGER PRIMARY KEY,
+    block_key TEXT NOT NULL UNIQUE,
+    context_id TEXT NOT NULL,
+    principal_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    role TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS block_meta_context ON block_meta(context_id);
+CREATE INDEX IF NOT EXISTS block_meta_created ON block_meta(created_at);
+
+-- rowid matches block_meta.rowid.
+CREATE VIRTUAL TABLE IF NOT EXISTS block_fts USING fts5(
+    content,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+-- Contexts backfilled at least once; later changes arrive as flow events.
+CREATE TABLE IF NOT EXISTS indexed_contexts (
+    context_id TEXT PRIMARY KEY,
+    indexed_at INTEGER NOT NULL
+);
+"#;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SearchIndexError {
+    #[error("search index: {0}")]
+    Db(#[from] rusqlite::Error),
+    /// The query had no terms, or FTS5 rejected it.
+    #[error("invalid search query: {0}")]
+    Query(String),
+}
+
+pub type SearchIndexResult<T> = Result<T, SearchIndexError>;
+
+/// One search. Everything but `text` narrows the result.
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// Terms, all required; `"quoted phrases"`, `prefix*`, and the
+    /// operators `OR` / `NOT` as in FTS5.
+    pub text: String,
+    pub context_id: Option<ContextId>,
+    pub kind: Option<BlockKind>,
+    pub role: Option<Role>,
+    /// The principal that wrote the block (its id's principal).
+    pub author: Option<PrincipalId>,
+    /// Created at or after (Unix millis).
+    pub since: Option<u64>,
+    /// Created before (Unix millis).
+    pub until: Option<u64>,
+    /// Hits to return; 0 means [`DEFAULT_SEARCH_LIMIT`].
+    pub limit: usize,
+}
+
+/// A ranked match.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SearchHit {
+    pub block_id: BlockId,
+    pub kind: BlockKind,
+    pub role: Role,
+    pub created_at: u64,
+    /// BM25 relevance, higher is better.
+    pub score: f64,
+    /// The matching passage, terms bracketed `[like this]`.
+    pub snippet: String,
+}
+
+/// The kernel's persistent full-text index. See the [module docs](self).
+pub struct SearchIndex {
+    conn: Mutex<Connection>,
+}
+
+impl SearchIndex {
+    /// Open (or create) the index at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> SearchIndexResult<Self> {
+        if let Some(parent) = path.as_ref().parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = NORMAL;
+             PRAGMA busy_timeout = 5000;",
+        )?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// An in-memory index (for testing).
+    pub fn in_memory() -> SearchIndexResult<Self> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Index or re-index one block.
+    pub fn upsert(&self, snap: &BlockSnapshot) -> SearchIndexResult<()> {
+        let conn = self.conn.lock();
+        upsert_in(&conn, snap)
+    }
+
+    /// Drop a block from the index.
+    pub fn remove(&self, block_id: &BlockId) -> SearchIndexResult<()> {
+        let conn = self.conn.lock();
+        let key = block_id.to_key();
+        let rowid: Option<i64> = conn
+            .query_row(
+                "SELECT rowid FROM block_meta WHERE block_key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(rowid) = rowid {
+            conn.execute("DELETE FROM block_fts WHERE rowid = ?1", params![rowid])?;
+            conn.execute("DELETE FROM block_meta WHERE rowid = ?1", params![rowid])?;
+        }
+        Ok(())
+    }
+
+    /// Replace everything indexed for `context_id` with `blocks`, in one
+    /// transaction, and mark the context indexed.
+    pub fn reindex_context(
+        &self,
+        context_id: ContextId,
+        blocks: &[BlockSnapshot],
+    ) -> SearchIndexResult<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let ctx = context_id.to_hex();
+        tx.execute(
+            "DELETE FROM block_fts WHERE rowid IN
+                 (SELECT rowid FROM block_meta WHERE context_id = ?1)",
+            params![ctx],
+        )?;
+        tx.execute("DELETE FROM block_meta WHERE context_id = ?1", params![ctx])?;
+        for snap in blocks {
+            upsert_in(&tx, snap)?;
+        }
+        tx.execute(
+            "INSERT INTO indexed_contexts (context_id, indexed_at) VALUES (?1, ?2)
+             ON CONFLICT(context_id) DO UPDATE SET indexed_at = excluded.indexed_at",
+            params![ctx, kaijutsu_types::now_millis() as i64],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Whether `context_id` has been backfilled.
+    pub fn is_context_indexed(&self, context_id: ContextId) -> SearchIndexResult<bool> {
+        let conn = self.conn.lock();
+        let found: Option<i64> = conn
+            .query_row(
+                "SELECT 1 FROM indexed_contexts WHERE context_id = ?1",
+                params![context_id.to_hex()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// Number of indexed blocks.
+    pub fn len(&self) -> SearchIndexResult<u64> {
+        let conn = self.conn.lock();
+        let n: i64 = conn.query_row("SELECT COUNT(*) FROM block_meta", [], |row| row.get(0))?;
+        Ok(n as u64)
+    }
+
+    pub fn is_empty(&self) -> SearchIndexResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Ranked search, best match first.
+    pub fn search(&self, query: &SearchQuery) -> SearchIndexResult<Vec<SearchHit>> {
+        let fts = fts_query(&query.text)?;
+        let limit = match query.limit {
+            0 => DEFAULT_SEARCH_LIMIT,
+            n => n.min(MAX_SEARCH_LIMIT),
+        };
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT m.block_key, m.kind, m.role, m.created_at,
+                    bm25(block_fts) AS rank,
+                    snippet(block_fts, 0, '[', ']', '…', 16)
+             FROM block_fts JOIN block_meta m ON m.rowid = block_fts.rowid
+             WHERE block_fts MATCH ?1
+               AND (?2 IS NULL OR m.context_id = ?2)
+               AND (?3 IS NULL OR m.kind = ?3)
+               AND (?4 IS NULL OR m.role = ?4)
+               AND (?5 IS NULL OR m.principal_id = ?5)
+               AND (?6 IS NULL OR m.created_at >= ?6)
+               AND (?7 IS NULL OR m.created_at < ?7)
+             ORDER BY rank
+             LIMIT ?8",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                fts,
+                query.context_id.map(|c| c.to_hex()),
+                query.kind.map(|k| k.as_str()),
+                query.role.map(|r| r.as_str()),
+                query.author.map(|p| p.to_hex()),
+                query.since.map(|t| t as i64),
+                query.until.map(|t| t as i64),
+                limit as i64,
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, f64>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            },
+        );
+        let mut hits = Vec::new();
+        for row in rows.map_err(match_error)? {
+            let (key, kind, role, created_at, rank, snippet) = row.map_err(match_error)?;
+            // Rows written by an older build with a kind or role this one
+            // doesn't know are skipped rather than failing the search.
+            let (Some(block_id), Some(kind), Some(role)) = (
+                BlockId::from_key(&key),
+                BlockKind::from_str(&kind),
+                Role::from_str(&role),
+            ) else {
+                continue;
+            };
+            hits.push(SearchHit {
+                block_id,
+                kind,
+                role,
+                created_at: created_at as u64,
+                // bm25() is lower-is-better; flip it so callers sort naturally.
+                score: -rank,
+                snippet,
+            });
+        }
+        Ok(hits)
+    }
+}
+
+/// FTS5 parses the MATCH expression when the statement steps; a malformed
+/// one is the caller's query, not a broken index.
+fn match_error(e: rusqlite::Error) -> SearchIndexError {
+    match e {
+        rusqlite::Error::SqliteFailure(_, Some(msg)) if msg.contains("fts5") => {
+            SearchIndexError::Query(msg)
+        }
+        e => e.into(),
+    }
+}
+
+fn upsert_in(conn: &Connection, snap: &BlockSnapshot) -> SearchIndexResult<()> {
+    let rowid: i64 = conn.query_row(
+        "INSERT INTO block_meta (block_key, context_id, principal_id, kind, role, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(block_key) DO UPDATE SET
+             kind = excluded.kind, role = excluded.role
+         RETURNING rowid",
+        params![
+            snap.id.to_key(),
+            snap.id.context_id.to_hex(),
+            snap.id.principal_id.to_hex(),
+            snap.kind.as_str(),
+            snap.role.as_str(),
+            snap.created_at as i64,
+        ],
+        |row| row.get(0),
+    )?;
+    conn.execute("DELETE FROM block_fts WHERE rowid = ?1", params![rowid])?;
+    conn.execute(
+        "INSERT INTO block_fts (rowid, content) VALUES (?1, ?2)",
+        params![rowid, indexed_text(snap)],
+    )?;
+    Ok(())
+}
+
+/// What a block is found by: its text, plus a tool call's name and input.
+fn indexed_text(snap: &BlockSnapshot) -> String {
+    let mut text = snap.content.clone();
+    for extra in [&snap.tool_name, &snap.tool_input].into_iter().flatten() {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(extra);
+    }
+    text
+}
+
+/// Translate a user query into an FTS5 MATCH expression. Bare words are
+/// quoted so punctuation (`foo-bar`, `a.b`) is matched as text instead of
+/// parsed as FTS5 syntax; `"phrases"`, a trailing `*` and the operators
+/// `OR`/`NOT`/`AND` keep their meaning.
+pub fn fts_query(text: &str) -> SearchIndexResult<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('"') {
+            let (phrase, tail) = after.split_once('"').unwrap_or((after, ""));
+            if !phrase.trim().is_empty() {
+                terms.push(quote(phrase));
+            }
+            rest = tail.trim_start();
+            continue;
+        }
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '"')
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        rest = rest[end..].trim_start();
+        match word {
+            "OR" | "NOT" | "AND" => terms.push(word.to_string()),
+            _ => match word.strip_suffix('*') {
+                Some(stem) if !stem.is_empty() => terms.push(format!("{}*", quote(stem))),
+                _ => terms.push(quote(word)),
+            },
+        }
+    }
+    let is_operator = |t: &String| matches!(t.as_str(), "OR" | "NOT" | "AND");
+    if terms.iter().all(is_operator) {
+        return Err(SearchIndexError::Query("no search terms".to_string()));
+    }
+    Ok(terms.join(" "))
+}
+
+fn quote(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+/// Backfill contexts the index hasn't seen, then keep it current from the
+/// store's FlowBus. Call once, from a runtime that lives as long as the
+/// kernel. Returns `None` when the store has no FlowBus.
+pub fn spawn_indexer(
+    index: Arc<SearchIndex>,
+    blocks: SharedBlockStore,
+) -> Option<tokio::task::JoinHandle<()>> {
+    // Subscribe before the backfill so nothing written during it is missed.
+    let mut sub = blocks.block_flows()?.subscribe("block.*");
+    Some(tokio::spawn(async move {
+        for context_id in blocks.list_ids() {
+            match index.is_context_indexed(context_id) {
+                Ok(true) => {}
+                Ok(false) => reindex(&index, &blocks, context_id),
+                Err(e) => tracing::warn!("search index: {e}"),
+            }
+        }
+
+        let mut dirty: HashSet<BlockId> = HashSet::new();
+        let mut flush = tokio::time::interval(INDEX_FLUSH_INTERVAL);
+        flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                msg = sub.recv() => {
+                    let Some(msg) = msg else { break };
+                    apply(&index, &blocks, &mut dirty, &msg.payload);
+                }
+                _ = flush.tick() => {
+                    for block_id in dirty.drain() {
+                        refresh(&index, &blocks, &block_id);
+                    }
+                }
+            }
+        }
+    }))
+}
+
+fn apply(
+    index: &SearchIndex,
+    blocks: &SharedBlockStore,
+    dirty: &mut HashSet<BlockId>,
+    flow: &BlockFlow,
+) {
+    let result = match flow {
+        BlockFlow::Inserted { block, .. } => index.upsert(block),
+        BlockFlow::Deleted { block_id, .. } => {
+            dirty.remove(block_id);
+            index.remove(block_id)
+        }
+        BlockFlow::SyncReset { context_id, .. } => {
+            dirty.retain(|id| id.context_id != *context_id);
+            reindex(index, blocks, *context_id);
+            Ok(())
+        }
+        BlockFlow::TextOps { block_id, .. }
+        | BlockFlow::StatusChanged { block_id, .. }
+        | BlockFlow::MetadataChanged { block_id, .. } => {
+            dirty.insert(*block_id);
+            Ok(())
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        tracing::warn!("search index: {e}");
+    }
+}
+
+fn refresh(index: &SearchIndex, blocks: &SharedBlockStore, block_id: &BlockId) {
+    let result = match blocks.get_block_snapshot(block_id.context_id, block_id) {
+        Ok(Some(snap)) => index.upsert(&snap),
+        // Gone from the store (deleted, or the document was dropped).
+        Ok(None) | Err(_) => index.remove(block_id),
+    };
+    if let Err(e) = result {
+        tracing::warn!("search index: {e}");
+    }
+}
+
+fn reindex(index: &SearchIndex, blocks: &SharedBlockStore, context_id: ContextId) {
+    let snapshots = match blocks.block_snapshots(context_id) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!("search index: context {}: {e}", context_id.short());
+            return;
+        }
+    };
+    if let Err(e) = index.reindex_context(context_id, &snapshots) {
+        tracing::warn!("search index: context {}: {e}", context_id.short());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_types::BlockSnapshotBuilder;
+
+    fn block(ctx: ContextId, seq: u64, role: Role, content: &str) -> BlockSnapshot {
+        let mut snap = BlockSnapshotBuilder::new(
+            BlockId::new(ctx, PrincipalId::system(), seq),
+            BlockKind::Text,
+        )
+        .role(role)
+        .content(content)
+        .build();
+        snap.created_at = 1_000 * seq;
+        snap
+    }
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ranks_phrases_and_filters() {
+        let index = SearchIndex::in_memory().unwrap();
+        let (a, b) = (ContextId::new(), ContextId::new());
+        index
+            .upsert(&block(a, 1, Role::User, "the CRDT merge lost an edit"))
+            .unwrap();
+        index
+            .upsert(&block(
+                a,
+                2,
+                Role::Model,
+                "merge the CRDT: merge, merge, merge",
+            ))
+            .unwrap();
+        index
+            .upsert(&block(b, 3, Role::User, "an unrelated note about lunch"))
+            .unwrap();
+
+        let hits = index.search(&query("merge")).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].block_id.seq, 2, "more occurrences rank higher");
+        assert!(hits[0].snippet.contains("[merge]"));
+
+        let hits = index.search(&query("\"merge lost\"")).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].block_id.seq, 1);
+
+        let hits = index
+            .search(&SearchQuery {
+                role: Some(Role::User),
+                ..query("merge")
+            })
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+
+        let hits = index
+            .search(&SearchQuery {
+                context_id: Some(b),
+                ..query("merge OR lunch")
+            })
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].block_id.context_id, b);
+
+        let hits = index
+            .search(&SearchQuery {
+                since: Some(2_000),
+                until: Some(3_000),
+                ..query("merge")
+            })
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].block_id.seq, 2);
+    }
+
+    #[test]
+    fn upsert_replaces_text_and_remove_drops_it() {
+        let index = SearchIndex::in_memory().unwrap();
+        let ctx = ContextId::new();
+        let mut snap = block(ctx, 1, Role::User, "first draft");
+        index.upsert(&snap).unwrap();
+        snap.content = "second draft".to_string();
+        index.upsert(&snap).unwrap();
+
+        assert!(index.search(&query("first")).unwrap().is_empty());
+        assert_eq!(index.search(&query("second")).unwrap().len(), 1);
+        assert_eq!(index.len().unwrap(), 1);
+
+        index.remove(&snap.id).unwrap();
+        assert!(index.search(&query("draft")).unwrap().is_empty());
+        assert!(index.is_empty().unwrap());
+    }
+
+    #[test]
+    fn reindex_context_replaces_only_that_context() {
+        let index = SearchIndex::in_memory().unwrap();
+        let (a, b) = (ContextId::new(), ContextId::new());
+        index
+            .upsert(&block(a, 1, Role::User, "stale words"))
+            .unwrap();
+        index
+            .upsert(&block(b, 2, Role::User, "stale words"))
+            .unwrap();
+
+        index
+            .reindex_context(a, &[block(a, 3, Role::User, "fresh words")])
+            .unwrap();
+        assert!(index.is_context_indexed(a).unwrap());
+        assert!(!index.is_context_indexed(b).unwrap());
+        let stale = index.search(&query("stale")).unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].block_id.context_id, b);
+        assert_eq!(index.search(&query("fresh")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn query_translation_quotes_bare_words() {
+        assert_eq!(fts_query("foo-bar").unwrap(), "\"foo-bar\"");
+        assert_eq!(
+            fts_query("\"exact phrase\" crdt* OR dag").unwrap(),
+            "\"exact phrase\" \"crdt\"* OR \"dag\""
+        );
+        assert_eq!(fts_query("say \"hi").unwrap(), "\"say\" \"hi\"");
+        assert!(fts_query("   ").is_err());
+        assert!(fts_query("OR NOT").is_err());
+    }
+}
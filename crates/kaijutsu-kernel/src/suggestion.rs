@@ -0,0 +1,363 @@
+//! Block edit suggestions — propose, accept, reject.
+//!
+//! Storage lives in `KernelDb` (`block_suggestions`); this module is the
+//! workflow. [`propose`] diffs the proposed text against the block as it is
+//! now and stores the patch without touching the block. [`accept`] applies
+//! it as ordinary CRDT text ops authored by the **proposer** — the words are
+//! theirs — while the suggestion row records the **approver**, so the change
+//! carries both names. [`reject`] closes it unapplied.
+//!
+//! Who decides: the block's author or the context's creator. A proposer may
+//! also reject (withdraw) their own suggestion. Each step posts an
+//! `InboxKind::Suggestion` item to the other side.
+
+use kaijutsu_types::suggestion::edits_between;
+use kaijutsu_types::{BlockId, InboxKind, PrincipalId, Suggestion, SuggestionState};
+use parking_lot::Mutex;
+
+use crate::block_store::{BlockStoreError, SharedBlockStore};
+use crate::kernel_db::{KernelDb, KernelDbError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SuggestionError {
+    #[error("no suggestion #{0}")]
+    NotFound(u64),
+    #[error("suggestion #{id} is already {state}")]
+    Decided { id: u64, state: SuggestionState },
+    #[error("block {0} not found")]
+    BlockNotFound(BlockId),
+    #[error("the suggested text is the same as the block's")]
+    NoChange,
+    #[error(
+        "block changed since suggestion #{0} was made; propose the edit again against its current text"
+    )]
+    Stale(u64),
+    #[error("only the block's author or the context's creator can {0} this suggestion")]
+    NotAllowed(&'static str),
+    #[error(transparent)]
+    Db(#[from] KernelDbError),
+    #[error(transparent)]
+    Store(#[from] BlockStoreError),
+}
+
+pub type SuggestionResult<T> = Result<T, SuggestionError>;
+
+/// Suggest replacing `block_id`'s text with `proposed`. Stores the patch
+/// from the current text and notifies the block's owner.
+pub fn propose(
+    db: &Mutex<KernelDb>,
+    blocks: &SharedBlockStore,
+    block_id: BlockId,
+    proposer: PrincipalId,
+    proposed: &str,
+    note: Option<&str>,
+) -> SuggestionResult<Suggestion> {
+    let base = current_text(blocks, block_id)?;
+    let edits = edits_between(&base, proposed);
+    if edits.is_empty() {
+        return Err(SuggestionError::NoChange);
+    }
+    let suggestion = db
+        .lock()
+        .insert_suggestion(block_id, proposer, &base, &edits, note)?;
+
+    let summary = format!(
+        "suggested edit #{} on {}: {}",
+        suggestion.id,
+        block_id.to_key(),
+        note.unwrap_or("no note")
+    );
+    let author = block_id.principal_id;
+    if author != proposer && author != PrincipalId::system() {
+        notify(db, blocks, author, block_id, proposer, &summary);
+    } else {
+        crate::inbox::notify_context_owner(
+            db,
+            blocks.block_flows(),
+            block_id.context_id,
+            InboxKind::Suggestion,
+            proposer,
+            &summary,
+        );
+    }
+    Ok(suggestion)
+}
+
+/// Apply an open suggestion. Fails without writing anything if the block
+/// changed since the suggestion was made.
+pub fn accept(
+    db: &Mutex<KernelDb>,
+    blocks: &SharedBlockStore,
+    id: u64,
+    approver: PrincipalId,
+) -> SuggestionResult<Suggestion> {
+    let suggestion = open_suggestion(db, id)?;
+    if !may_decide(db, suggestion.block_id, approver)? {
+        return Err(SuggestionError::NotAllowed("accept"));
+    }
+    let block_id = suggestion.block_id;
+    if current_text(blocks, block_id)? != suggestion.base {
+        return Err(SuggestionError::Stale(id));
+    }
+    // Claim the decision before writing, so two approvers can't both apply.
+    if !db
+        .lock()
+        .resolve_suggestion(id, SuggestionState::Accepted, approver, None)?
+    {
+        return Err(decided(db, id));
+    }
+    // Back to front, so each edit's position still points into the base.
+    for edit in suggestion.edits.iter().rev() {
+        blocks.edit_text_as(
+            block_id.context_id,
+            &block_id,
+            edit.pos,
+            &edit.insert,
+            edit.delete,
+            Some(suggestion.proposer),
+        )?;
+    }
+
+    if approver != suggestion.proposer {
+        let summary = format!("suggestion #{id} on {} was accepted", block_id.to_key());
+        notify(
+            db,
+            blocks,
+            suggestion.proposer,
+            block_id,
+            approver,
+            &summary,
+        );
+    }
+    reload(db, id)
+}
+
+/// Close an open suggestion without applying it. The proposer may withdraw
+/// their own.
+pub fn reject(
+    db: &Mutex<KernelDb>,
+    blocks: &SharedBlockStore,
+    id: u64,
+    by: PrincipalId,
+    reason: Option<&str>,
+) -> SuggestionResult<Suggestion> {
+    let suggestion = open_suggestion(db, id)?;
+    if by != suggestion.proposer && !may_decide(db, suggestion.block_id, by)? {
+        return Err(SuggestionError::NotAllowed("reject"));
+    }
+    if !db
+        .lock()
+        .resolve_suggestion(id, SuggestionState::Rejected, by, reason)?
+    {
+        return Err(decided(db, id));
+    }
+
+    if by != suggestion.proposer {
+        let summary = format!(
+            "suggestion #{id} on {} was rejected: {}",
+            suggestion.block_id.to_key(),
+            reason.unwrap_or("no reason given")
+        );
+        notify(
+            db,
+            blocks,
+            suggestion.proposer,
+            suggestion.block_id,
+            by,
+            &summary,
+        );
+    }
+    reload(db, id)
+}
+
+fn current_text(blocks: &SharedBlockStore, block_id: BlockId) -> SuggestionResult<String> {
+    blocks
+        .get_block_snapshot(block_id.context_id, &block_id)?
+        .map(|snap| snap.content)
+        .ok_or(SuggestionError::BlockNotFound(block_id))
+}
+
+fn open_suggestion(db: &Mutex<KernelDb>, id: u64) -> SuggestionResult<Suggestion> {
+    let suggestion = db
+        .lock()
+        .get_suggestion(id)?
+        .ok_or(SuggestionError::NotFound(id))?;
+    if suggestion.state != SuggestionState::Open {
+        return Err(SuggestionError::Decided {
+            id,
+            state: suggestion.state,
+        });
+    }
+    Ok(suggestion)
+}
+
+fn decided(db: &Mutex<KernelDb>, id: u64) -> SuggestionError {
+    match db.lock().get_suggestion(id) {
+        Ok(Some(s)) => SuggestionError::Decided { id, state: s.state },
+        Ok(None) => SuggestionError::NotFound(id),
+        Err(e) => e.into(),
+    }
+}
+
+fn reload(db: &Mutex<KernelDb>, id: u64) -> SuggestionResult<Suggestion> {
+    db.lock()
+        .get_suggestion(id)?
+        .ok_or(SuggestionError::NotFound(id))
+}
+
+/// The block's author or its context's creator.
+fn may_decide(db: &Mutex<KernelDb>, block_id: BlockId, who: PrincipalId) -> SuggestionResult<bool> {
+    if who == block_id.principal_id {
+        return Ok(true);
+    }
+    let owner = db
+        .lock()
+        .get_context(block_id.context_id)?
+        .map(|row| row.created_by);
+    Ok(owner == Some(who))
+}
+
+/// Failures are logged — a notification must not fail the step behind it.
+fn notify(
+    db: &Mutex<KernelDb>,
+    blocks: &SharedBlockStore,
+    recipient: PrincipalId,
+    block_id: BlockId,
+    sender: PrincipalId,
+    summary: &str,
+) {
+    if let Err(e) = crate::inbox::post(
+        db,
+        blocks.block_flows(),
+        recipient,
+        InboxKind::Suggestion,
+        Some(block_id.context_id),
+        Some(sender),
+        summary,
+    ) {
+        tracing::warn!("suggestion: failed to notify {}: {e}", recipient.short());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::{DocumentKind, shared_block_store};
+    use kaijutsu_types::{BlockKind, ContentType, ContextId, Role, Status};
+
+    struct Fixture {
+        db: Mutex<KernelDb>,
+        blocks: SharedBlockStore,
+        block: BlockId,
+        author: PrincipalId,
+    }
+
+    fn fixture(text: &str) -> Fixture {
+        let blocks = shared_block_store(PrincipalId::new());
+        let ctx = ContextId::new();
+        blocks
+            .create_document(ctx, DocumentKind::Conversation, None)
+            .unwrap();
+        let author = PrincipalId::new();
+        let block = blocks
+            .insert_block_as(
+                ctx,
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                text,
+                Status::Done,
+                ContentType::Plain,
+                Some(author),
+            )
+            .unwrap();
+        Fixture {
+            db: Mutex::new(KernelDb::in_memory().unwrap()),
+            blocks,
+            block,
+            author,
+        }
+    }
+
+    fn text(f: &Fixture) -> String {
+        current_text(&f.blocks, f.block).unwrap()
+    }
+
+    #[test]
+    fn accept_applies_the_patch_and_records_both_principals() {
+        let f = fixture("the quick brwn fox\njumps over\n");
+        let agent = PrincipalId::new();
+        let s = propose(
+            &f.db,
+            &f.blocks,
+            f.block,
+            agent,
+            "the quick brown fox\njumps over the dog\n",
+            Some("typo + ending"),
+        )
+        .unwrap();
+        assert_eq!(
+            text(&f),
+            "the quick brwn fox\njumps over\n",
+            "stored, not applied"
+        );
+        let inbox = f.db.lock().list_inbox(f.author, false, 10).unwrap();
+        assert_eq!(inbox[0].kind, InboxKind::Suggestion);
+
+        let outsider = PrincipalId::new();
+        assert!(matches!(
+            accept(&f.db, &f.blocks, s.id, outsider),
+            Err(SuggestionError::NotAllowed(_))
+        ));
+        let accepted = accept(&f.db, &f.blocks, s.id, f.author).unwrap();
+        assert_eq!(text(&f), "the quick brown fox\njumps over the dog\n");
+        assert_eq!(accepted.state, SuggestionState::Accepted);
+        assert_eq!(
+            (accepted.proposer, accepted.resolved_by),
+            (agent, Some(f.author))
+        );
+        assert!(matches!(
+            accept(&f.db, &f.blocks, s.id, f.author),
+            Err(SuggestionError::Decided { .. })
+        ));
+        assert_eq!(
+            f.db.lock().list_inbox(agent, false, 10).unwrap()[0].kind,
+            InboxKind::Suggestion
+        );
+    }
+
+    #[test]
+    fn stale_and_empty_suggestions_are_refused() {
+        let f = fixture("draft");
+        let agent = PrincipalId::new();
+        assert!(matches!(
+            propose(&f.db, &f.blocks, f.block, agent, "draft", None),
+            Err(SuggestionError::NoChange)
+        ));
+        let s = propose(&f.db, &f.blocks, f.block, agent, "final", None).unwrap();
+        f.blocks
+            .append_text_as(f.block.context_id, &f.block, "!", Some(f.author))
+            .unwrap();
+        assert!(matches!(
+            accept(&f.db, &f.blocks, s.id, f.author),
+            Err(SuggestionError::Stale(_))
+        ));
+        assert_eq!(text(&f), "draft!");
+    }
+
+    #[test]
+    fn proposer_can_withdraw_but_outsiders_cannot_reject() {
+        let f = fixture("draft");
+        let agent = PrincipalId::new();
+        let s = propose(&f.db, &f.blocks, f.block, agent, "final", None).unwrap();
+        assert!(matches!(
+            reject(&f.db, &f.blocks, s.id, PrincipalId::new(), None),
+            Err(SuggestionError::NotAllowed(_))
+        ));
+        let withdrawn = reject(&f.db, &f.blocks, s.id, agent, Some("never mind")).unwrap();
+        assert_eq!(withdrawn.state, SuggestionState::Rejected);
+        assert_eq!(withdrawn.reason.as_deref(), Some("never mind"));
+        assert_eq!(text(&f), "draft");
+    }
+}
@@ -0,0 +1,301 @@
+//! Per-context tool parameter presets.
+//!
+//! Agents in one context tend to pass the same arguments over and over — a
+//! longer shell timeout, a smaller `max_matches`, line numbers on every
+//! read. A [`ToolPreset`] stores those once per `(context, tool)`: the
+//! broker fills in any preset key a call omits before hooks or the server
+//! see it, and shows the preset values as schema `default`s in the visible
+//! tool list, so every agent in the context gets the same behaviour without
+//! spelling it out. An argument the caller does pass always wins.
+//!
+//! Presets persist in the kernel DB (`context_tool_presets`), follow a fork,
+//! and are cached here on first use. One tracker per kernel, owned by the
+//! broker ([`Broker::tool_presets`]); the `builtin.tool_presets` server is
+//! how agents manage them.
+//!
+//! [`Broker::tool_presets`]: crate::mcp::Broker::tool_presets
+
+use std::collections::HashMap;
+
+use kaijutsu_types::{ContextId, PrincipalId};
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::block_store::DbHandle;
+use crate::kernel_db::KernelDbResult;
+use crate::mcp::types::{InstanceId, KernelCallParams};
+
+/// Most keys one preset may hold.
+pub const MAX_PRESET_KEYS: usize = 32;
+/// Largest preset accepted, measured as compact JSON.
+pub const MAX_PRESET_BYTES: usize = 4 * 1024;
+
+/// Default arguments for one tool in one context.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolPreset {
+    pub instance: InstanceId,
+    pub tool: String,
+    pub params: Map<String, Value>,
+    pub updated_by: PrincipalId,
+    /// Unix millis of the last write.
+    pub updated_at: u64,
+}
+
+/// Check `params` against the tool's input schema: within the size limits,
+/// and every key a property the schema declares. A schema that declares no
+/// properties accepts any key.
+pub fn validate_params(params: &Map<String, Value>, schema: &Value) -> Result<(), String> {
+    if params.len() > MAX_PRESET_KEYS {
+        return Err(format!(
+            "preset has {} keys (max {MAX_PRESET_KEYS})",
+            params.len()
+        ));
+    }
+    let len = serde_json::to_string(params).map_or(0, |s| s.len());
+    if len > MAX_PRESET_BYTES {
+        return Err(format!("preset is {len} bytes (max {MAX_PRESET_BYTES})"));
+    }
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return Ok(());
+    };
+    if let Some(unknown) = params.keys().find(|k| !properties.contains_key(*k)) {
+        let mut known: Vec<&str> = properties.keys().map(String::as_str).collect();
+        known.sort_unstable();
+        return Err(format!(
+            "tool has no parameter '{unknown}' (parameters: {})",
+            known.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Fill every preset key `arguments` leaves out. A null `arguments` is an
+/// empty call; anything else that isn't an object is left alone for the
+/// server to reject. A key passed explicitly — even as `null` — is the
+/// caller's. Returns the keys filled in.
+pub fn fill_omitted(arguments: &mut Value, params: &Map<String, Value>) -> Vec<String> {
+    if params.is_empty() {
+        return Vec::new();
+    }
+    if arguments.is_null() {
+        *arguments = Value::Object(Map::new());
+    }
+    let Some(args) = arguments.as_object_mut() else {
+        return Vec::new();
+    };
+    let mut filled = Vec::new();
+    for (key, value) in params {
+        if !args.contains_key(key) {
+            args.insert(key.clone(), value.clone());
+            filled.push(key.clone());
+        }
+    }
+    filled
+}
+
+/// Show `params` as the `default` of the matching schema properties, so
+/// the model knows what an omitted argument becomes.
+pub fn annotate_schema(schema: &mut Value, params: &Map<String, Value>) {
+    let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for (key, value) in params {
+        if let Some(Value::Object(property)) = properties.get_mut(key) {
+            property.insert("default".to_string(), value.clone());
+        }
+    }
+}
+
+/// The kernel's tool presets, cached per context.
+#[derive(Debug, Default)]
+pub struct ToolPresets {
+    db: Mutex<Option<DbHandle>>,
+    /// Loaded contexts' presets; an empty list caches "none".
+    cache: Mutex<HashMap<ContextId, Vec<ToolPreset>>>,
+}
+
+impl ToolPresets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wire the kernel DB that presets are loaded from and saved to. Without
+    /// one, presets live only in memory (bare-broker tests).
+    pub fn set_db(&self, db: DbHandle) {
+        *self.db.lock() = Some(db);
+    }
+
+    /// The context's presets, loading them from the DB on first use. A DB
+    /// error is logged and treated as "no presets" without caching, so the
+    /// next call retries.
+    pub fn list(&self, context_id: ContextId) -> Vec<ToolPreset> {
+        if let Some(cached) = self.cache.lock().get(&context_id) {
+            return cached.clone();
+        }
+        let Some(db) = self.db.lock().clone() else {
+            return Vec::new();
+        };
+        let loaded = db.lock().list_tool_presets(context_id);
+        match loaded {
+            Ok(presets) => {
+                self.cache.lock().insert(context_id, presets.clone());
+                presets
+            }
+            Err(e) => {
+                tracing::warn!(context = %context_id, error = %e, "failed to load tool presets");
+                Vec::new()
+            }
+        }
+    }
+
+    /// The context's preset for one tool.
+    pub fn get(
+        &self,
+        context_id: ContextId,
+        instance: &InstanceId,
+        tool: &str,
+    ) -> Option<ToolPreset> {
+        self.list(context_id)
+            .into_iter()
+            .find(|p| &p.instance == instance && p.tool == tool)
+    }
+
+    /// Store `preset` for its tool, persisting it first. An empty preset
+    /// clears the tool's instead. Callers check the params with
+    /// [`validate_params`].
+    pub fn set(&self, context_id: ContextId, preset: ToolPreset) -> KernelDbResult<()> {
+        if preset.params.is_empty() {
+            self.clear(context_id, &preset.instance, &preset.tool)?;
+            return Ok(());
+        }
+        if let Some(db) = self.db.lock().clone() {
+            db.lock().set_tool_preset(context_id, &preset)?;
+        }
+        let mut presets = self.list(context_id);
+        presets.retain(|p| !(p.instance == preset.instance && p.tool == preset.tool));
+        presets.push(preset);
+        presets.sort_by(|a, b| (&a.instance, &a.tool).cmp(&(&b.instance, &b.tool)));
+        self.cache.lock().insert(context_id, presets);
+        Ok(())
+    }
+
+    /// Drop the context's preset for one tool. Returns whether it had one.
+    pub fn clear(
+        &self,
+        context_id: ContextId,
+        instance: &InstanceId,
+        tool: &str,
+    ) -> KernelDbResult<bool> {
+        let mut presets = self.list(context_id);
+        let had = presets
+            .iter()
+            .any(|p| &p.instance == instance && p.tool == tool);
+        if let Some(db) = self.db.lock().clone() {
+            db.lock().delete_tool_preset(context_id, instance, tool)?;
+        }
+        presets.retain(|p| !(&p.instance == instance && p.tool == tool));
+        self.cache.lock().insert(context_id, presets);
+        Ok(had)
+    }
+
+    /// Fill the context's preset into a call's omitted arguments. Returns
+    /// the keys filled in.
+    pub fn apply(&self, context_id: ContextId, params: &mut KernelCallParams) -> Vec<String> {
+        match self.get(context_id, &params.instance, &params.tool) {
+            Some(preset) => fill_omitted(&mut params.arguments, &preset.params),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn obj(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn preset(params: Value) -> ToolPreset {
+        ToolPreset {
+            instance: InstanceId::new("builtin.file"),
+            tool: "search".into(),
+            params: obj(params),
+            updated_by: PrincipalId::system(),
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn omitted_arguments_are_filled_and_passed_ones_win() {
+        let params = obj(json!({ "max_matches": 20, "line_numbers": true }));
+
+        let mut args = json!({ "max_matches": 5, "pattern": "todo" });
+        assert_eq!(fill_omitted(&mut args, &params), vec!["line_numbers"]);
+        assert_eq!(
+            args,
+            json!({ "max_matches": 5, "pattern": "todo", "line_numbers": true })
+        );
+
+        let mut explicit_null = json!({ "max_matches": null, "line_numbers": false });
+        assert!(fill_omitted(&mut explicit_null, &params).is_empty());
+
+        let mut none = Value::Null;
+        assert_eq!(fill_omitted(&mut none, &params).len(), 2);
+        assert_eq!(none, Value::Object(params.clone()));
+
+        let mut not_an_object = json!("todo");
+        assert!(fill_omitted(&mut not_an_object, &params).is_empty());
+    }
+
+    #[test]
+    fn params_must_name_schema_properties() {
+        let mut schema = json!({
+            "type": "object",
+            "properties": {
+                "pattern": { "type": "string" },
+                "max_matches": { "type": "integer" },
+            },
+        });
+        assert!(validate_params(&obj(json!({ "max_matches": 20 })), &schema).is_ok());
+        let err = validate_params(&obj(json!({ "max_mathces": 20 })), &schema).unwrap_err();
+        assert!(
+            err.contains("max_mathces") && err.contains("max_matches"),
+            "{err}"
+        );
+        assert!(validate_params(&obj(json!({ "anything": 1 })), &json!({})).is_ok());
+
+        annotate_schema(&mut schema, &obj(json!({ "max_matches": 20, "gone": 1 })));
+        assert_eq!(schema["properties"]["max_matches"]["default"], json!(20));
+        assert!(schema["properties"].get("gone").is_none());
+    }
+
+    #[test]
+    fn tracker_applies_and_clears_per_context() {
+        let presets = ToolPresets::new();
+        let ctx = ContextId::new();
+        let other = ContextId::new();
+        presets
+            .set(ctx, preset(json!({ "max_matches": 20 })))
+            .unwrap();
+
+        let call = |args: Value| KernelCallParams {
+            instance: InstanceId::new("builtin.file"),
+            tool: "search".into(),
+            arguments: args,
+        };
+        let mut params = call(json!({ "pattern": "x" }));
+        assert_eq!(presets.apply(ctx, &mut params), vec!["max_matches"]);
+        assert_eq!(params.arguments["max_matches"], json!(20));
+
+        let mut elsewhere = call(json!({ "pattern": "x" }));
+        assert!(presets.apply(other, &mut elsewhere).is_empty());
+
+        presets.set(ctx, preset(json!({}))).unwrap();
+        assert!(presets.list(ctx).is_empty(), "an empty preset clears");
+        let file = InstanceId::new("builtin.file");
+        assert!(!presets.clear(ctx, &file, "search").unwrap());
+    }
+}
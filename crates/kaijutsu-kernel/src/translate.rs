@@ -0,0 +1,318 @@
+//! Block translation — read a block in another language without editing it.
+//!
+//! A translation is an LLM rendering of a block's text, cached per
+//! `(block, language)` together with the block's text frontier when it was
+//! made. An edit moves the frontier, so the next read misses and replaces the
+//! stale entry. The source block is never written; everyone else keeps
+//! reading it as it is.
+//!
+//! Readers: MCP `block_read` (`translate_to`), `kj block read
+//! --translate-to`, and the `translateBlock` RPC behind the app's translated
+//! view, which defaults to the seat's preferred language.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use diamond_types_extended::Frontier;
+use kaijutsu_types::BlockId;
+use parking_lot::Mutex;
+use tokio::sync::RwLock;
+
+use crate::drift::SharedDriftRouter;
+use crate::llm::LlmRegistry;
+
+/// Translations kept; the oldest is dropped past this.
+pub const TRANSLATION_CACHE_LEN: usize = 4096;
+
+const TRANSLATION_SYSTEM_PROMPT: &str = "You translate messages from a shared working \
+conversation. Reply with the translation only: no preamble, notes, or quotation marks. Keep \
+Markdown structure, code blocks, inline code, identifiers, file paths, URLs and command lines \
+exactly as written; translate only the prose. If the text is already in the target language, \
+return it unchanged.";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TranslateError {
+    #[error("invalid language tag '{0}' — use a BCP 47 tag like `ja` or `pt-BR`")]
+    InvalidLanguage(String),
+    #[error("no LLM configured for translation")]
+    NoLlm,
+    #[error("translation failed on {provider}/{model}: {message}")]
+    Llm {
+        provider: String,
+        model: String,
+        message: String,
+    },
+}
+
+/// One block, translated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Translation {
+    /// The normalized target tag.
+    pub language: String,
+    pub text: String,
+    /// Served from the cache rather than the LLM.
+    pub cached: bool,
+}
+
+/// Normalize a BCP 47-style language tag: `JA` → `ja`, `pt_br` → `pt-BR`,
+/// `zh-hant` → `zh-Hant`. `None` unless it is a 2–3 letter language subtag
+/// followed by alphanumeric subtags of 2–8 characters.
+pub fn normalize_locale(tag: &str) -> Option<String> {
+    let mut parts = tag.trim().split(['-', '_']);
+    let lang = parts.next()?;
+    if !(2..=3).contains(&lang.len()) || !lang.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut out = lang.to_ascii_lowercase();
+    for sub in parts {
+        if !(2..=8).contains(&sub.len()) || !sub.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        out.push('-');
+        let alpha = sub.chars().all(|c| c.is_ascii_alphabetic());
+        match sub.len() {
+            // Region: `BR`.
+            2 if alpha => out.push_str(&sub.to_ascii_uppercase()),
+            // Script: `Hant`.
+            4 if alpha => {
+                out.push_str(&sub[..1].to_ascii_uppercase());
+                out.push_str(&sub[1..].to_ascii_lowercase());
+            }
+            _ => out.push_str(&sub.to_ascii_lowercase()),
+        }
+    }
+    Some(out)
+}
+
+struct CachedTranslation {
+    frontier: Frontier,
+    text: String,
+}
+
+#[derive(Default)]
+struct TranslationCache {
+    entries: HashMap<(BlockId, String), CachedTranslation>,
+    /// Keys oldest first, for eviction.
+    order: VecDeque<(BlockId, String)>,
+}
+
+impl TranslationCache {
+    fn get(&self, block_id: BlockId, language: &str, frontier: &Frontier) -> Option<String> {
+        self.entries
+            .get(&(block_id, language.to_string()))
+            .filter(|cached| cached.frontier == *frontier)
+            .map(|cached| cached.text.clone())
+    }
+
+    fn insert(&mut self, block_id: BlockId, language: String, frontier: Frontier, text: String) {
+        let key = (block_id, language);
+        let cached = CachedTranslation { frontier, text };
+        if self.entries.insert(key.clone(), cached).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > TRANSLATION_CACHE_LEN {
+            if let Some(old) = self.order.pop_front() {
+                self.entries.remove(&old);
+            }
+        }
+    }
+}
+
+/// Translates blocks and remembers the results. Kernel-owned; see
+/// [`crate::kernel::Kernel::translator`].
+pub struct Translator {
+    llm: Arc<RwLock<LlmRegistry>>,
+    drift: SharedDriftRouter,
+    cache: Mutex<TranslationCache>,
+}
+
+impl Translator {
+    pub fn new(llm: Arc<RwLock<LlmRegistry>>, drift: SharedDriftRouter) -> Self {
+        Self {
+            llm,
+            drift,
+            cache: Mutex::new(TranslationCache::default()),
+        }
+    }
+
+    /// Translate `content` — the text of `block_id` at `frontier` — into
+    /// `language`. Uses the block's context's own provider and model, else
+    /// the registry default.
+    pub async fn translate(
+        &self,
+        block_id: BlockId,
+        frontier: &Frontier,
+        content: &str,
+        language: &str,
+    ) -> Result<Translation, TranslateError> {
+        let language = normalize_locale(language)
+            .ok_or_else(|| TranslateError::InvalidLanguage(language.to_string()))?;
+        if let Some(text) = self.cache.lock().get(block_id, &language, frontier) {
+            return Ok(Translation {
+                language,
+                text,
+                cached: true,
+            });
+        }
+        if content.trim().is_empty() {
+            return Ok(Translation {
+                language,
+                text: content.to_string(),
+                cached: false,
+            });
+        }
+
+        // Sync router lock first, dropped before the async registry lock.
+        let ctx_pair = self
+            .drift
+            .read()
+            .get(block_id.context_id)
+            .map(|h| (h.provider.clone(), h.model.clone()));
+        let (provider, provider_name, model) = {
+            let registry = self.llm.read().await;
+            let pinned = match ctx_pair {
+                Some((Some(p_name), Some(m))) => registry.get(&p_name).map(|p| (p, p_name, m)),
+                _ => None,
+            };
+            match pinned {
+                Some(pinned) => pinned,
+                None => {
+                    let p = registry.default_provider().ok_or(TranslateError::NoLlm)?;
+                    let name = registry
+                        .default_provider_name()
+                        .unwrap_or_default()
+                        .to_string();
+                    let m = registry
+                        .default_model()
+                        .ok_or(TranslateError::NoLlm)?
+                        .to_string();
+                    (p, name, m)
+                }
+            }
+        };
+
+        let prompt = format!("Translate into {language}:\n\n{content}");
+        let text = provider
+            .prompt_with_system(&model, Some(TRANSLATION_SYSTEM_PROMPT), &prompt)
+            .await
+            .map_err(|e| TranslateError::Llm {
+                provider: provider_name,
+                model,
+                message: e.to_string(),
+            })?
+            .trim()
+            .to_string();
+        self.cache
+            .lock()
+            .insert(block_id, language.clone(), frontier.clone(), text.clone());
+        Ok(Translation {
+            language,
+            text,
+            cached: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_store::BlockStore;
+    use crate::drift::shared_drift_router;
+    use crate::llm::{MockClient, Provider};
+    use kaijutsu_types::{BlockKind, ContentType, ContextId, DocKind, PrincipalId, Role, Status};
+
+    #[test]
+    fn normalize_locale_cases_subtags() {
+        assert_eq!(normalize_locale("JA").as_deref(), Some("ja"));
+        assert_eq!(normalize_locale(" pt_br ").as_deref(), Some("pt-BR"));
+        assert_eq!(
+            normalize_locale("zh-hant-tw").as_deref(),
+            Some("zh-Hant-TW")
+        );
+        assert_eq!(normalize_locale("es-419").as_deref(), Some("es-419"));
+        assert_eq!(normalize_locale("japanese"), None);
+        assert_eq!(normalize_locale("en-"), None);
+        assert_eq!(normalize_locale(""), None);
+    }
+
+    fn registry(response: &str) -> LlmRegistry {
+        let mut registry = LlmRegistry::new();
+        registry.register("mock", Arc::new(Provider::Mock(MockClient::new(response))));
+        registry.set_default("mock");
+        registry.set_default_model("mock-model");
+        registry
+    }
+
+    #[tokio::test]
+    async fn translation_is_cached_until_the_block_changes() {
+        let store = BlockStore::new(PrincipalId::new());
+        let ctx = ContextId::new();
+        store
+            .create_document(ctx, DocKind::Conversation, None)
+            .unwrap();
+        let block_id = store
+            .insert_block(
+                ctx,
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                "good morning",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        let llm = Arc::new(RwLock::new(registry("おはようございます")));
+        let translator = Translator::new(llm.clone(), shared_drift_router());
+
+        let (snap, frontier) = store
+            .get_block_with_frontier(ctx, &block_id)
+            .unwrap()
+            .unwrap();
+        let first = translator
+            .translate(block_id, &frontier, &snap.content, "JA")
+            .await
+            .unwrap();
+        assert_eq!(first.language, "ja");
+        assert_eq!(first.text, "おはようございます");
+        assert!(!first.cached);
+
+        // Same frontier: served from the cache, the LLM isn't asked again.
+        *llm.write().await = registry("こんにちは");
+        let again = translator
+            .translate(block_id, &frontier, &snap.content, "ja")
+            .await
+            .unwrap();
+        assert_eq!(again.text, "おはようございます");
+        assert!(again.cached);
+
+        // An edit moves the frontier: translated afresh.
+        store.append_text(ctx, &block_id, ", team").unwrap();
+        let (snap, frontier) = store
+            .get_block_with_frontier(ctx, &block_id)
+            .unwrap()
+            .unwrap();
+        let edited = translator
+            .translate(block_id, &frontier, &snap.content, "ja")
+            .await
+            .unwrap();
+        assert_eq!(edited.text, "こんにちは");
+        assert!(!edited.cached);
+
+        assert!(matches!(
+            translator
+                .translate(block_id, &frontier, &snap.content, "klingon")
+                .await,
+            Err(TranslateError::InvalidLanguage(_))
+        ));
+        assert_eq!(
+            store
+                .get_block_snapshot(ctx, &block_id)
+                .unwrap()
+                .unwrap()
+                .content,
+            "good morning, team",
+            "the source block is untouched"
+        );
+    }
+}
@@ -0,0 +1,531 @@
+//! Outbound webhooks — POST a signed JSON payload to configured URLs when
+//! something the outside world cares about happens in this kernel.
+//!
+//! Configured per kernel in the CRDT-owned `/etc/config/webhooks.toml` (see
+//! `assets/defaults/webhooks.toml` for the format). The file is re-read on
+//! every event, so `kj config edit webhooks.toml` takes effect immediately and
+//! a kernel with no hooks pays one CRDT read per event and nothing else.
+//!
+//! Events:
+//! - `block.error` — a block was inserted with, or moved to, `Status::Error`
+//! - `context.created` — a context was created or forked
+//! - `drift.merged` — a `DriftKind::Merge` block landed in a context
+//!
+//! The block events are observed on the FlowBus by [`spawn_watcher`];
+//! `context.created` has no flow, so the creating paths call
+//! [`KjDispatcher::emit_context_created`](crate::KjDispatcher::emit_context_created).
+//!
+//! Delivery is fire-and-forget: each (event, hook) pair is its own task with
+//! exponential-backoff retry. Nothing here can fail or slow the operation
+//! that raised the event.
+//!
+//! A signing secret is named by environment variable (`secret_env`, which
+//! must start with [`SECRET_ENV_PREFIX`]) so it need not live in the CRDT,
+//! where anyone who can read `/etc/config` can read it. An inline `secret`
+//! is for tests and throwaway setups.
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use kaijutsu_crdt::{DriftKind, Status};
+use kaijutsu_types::{BlockSnapshot, KernelId};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::block_store::SharedBlockStore;
+use crate::config_doc::{config_context_id, read_content};
+use crate::flows::BlockFlow;
+
+/// Config file name under `/etc/config`.
+pub const WEBHOOKS_CONFIG_FILE: &str = "webhooks.toml";
+
+/// Header carrying `sha256=<hex HMAC of the body>` when a hook has a secret.
+pub const SIGNATURE_HEADER: &str = "X-Kaijutsu-Signature";
+
+/// Required prefix of a `secret_env` name, so a config writer can't point a
+/// hook at an unrelated variable in the server's environment.
+pub const SECRET_ENV_PREFIX: &str = "KAIJUTSU_WEBHOOK_";
+
+/// Header carrying the event name (`block.error`, …).
+pub const EVENT_HEADER: &str = "X-Kaijutsu-Event";
+
+/// Delivery attempts per (event, hook) before giving up.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry; doubles on each subsequent one.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Per-request timeout, connect through response headers.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest block excerpt carried in a payload `summary`.
+const SUMMARY_MAX_CHARS: usize = 280;
+
+/// Which events a hook subscribes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEventKind {
+    #[serde(rename = "block.error")]
+    BlockError,
+    #[serde(rename = "context.created")]
+    ContextCreated,
+    #[serde(rename = "drift.merged")]
+    DriftMerged,
+}
+
+impl WebhookEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BlockError => "block.error",
+            Self::ContextCreated => "context.created",
+            Self::DriftMerged => "drift.merged",
+        }
+    }
+}
+
+/// One `[[hooks]]` entry.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookTarget {
+    pub url: String,
+    /// Events to deliver; empty means all.
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+    /// Environment variable holding the HMAC-SHA256 key for
+    /// [`SIGNATURE_HEADER`], read at delivery time.
+    #[serde(default)]
+    pub secret_env: Option<String>,
+    /// The key itself, stored in the config CRDT — tests and throwaway
+    /// setups only. Unsigned when neither this nor `secret_env` is set.
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WebhookTarget {
+    fn wants(&self, kind: WebhookEventKind) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.contains(&kind))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let url = &self.url;
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(format!(
+                "webhook url '{url}' must start with http:// or https://"
+            ));
+        }
+        match (&self.secret_env, &self.secret) {
+            (Some(_), Some(_)) => Err(format!(
+                "webhook '{url}': set secret_env or secret, not both"
+            )),
+            (Some(var), None) if !var.starts_with(SECRET_ENV_PREFIX) => Err(format!(
+                "webhook '{url}': secret_env '{var}' must start with {SECRET_ENV_PREFIX}"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The signing key, if the hook signs: the inline secret, or
+    /// `secret_env` read from the environment at call time.
+    fn resolve_secret(&self) -> Result<Option<String>, String> {
+        if let Some(secret) = &self.secret {
+            return Ok(Some(secret.clone()));
+        }
+        let Some(var) = &self.secret_env else {
+            return Ok(None);
+        };
+        match std::env::var(var) {
+            Ok(secret) if !secret.is_empty() => Ok(Some(secret)),
+            _ => Err(format!("environment variable {var} is not set")),
+        }
+    }
+}
+
+/// Parsed `webhooks.toml`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    #[serde(default)]
+    pub hooks: Vec<WebhookTarget>,
+}
+
+impl WebhookConfig {
+    /// Parse and validate. Also the `kj config set` write-time check, so a
+    /// typo is rejected at the prompt instead of silently never firing.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| format!("invalid TOML: {e}"))?;
+        for hook in &config.hooks {
+            hook.validate()?;
+        }
+        Ok(config)
+    }
+}
+
+/// What happened. Serialized flat into the payload next to `kernel_id` and
+/// `timestamp`, tagged by `event`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event")]
+pub enum WebhookEvent {
+    #[serde(rename = "block.error")]
+    BlockError {
+        context_id: String,
+        block_id: String,
+        summary: String,
+    },
+    #[serde(rename = "context.created")]
+    ContextCreated {
+        context_id: String,
+        label: Option<String>,
+        context_type: String,
+        created_by: String,
+        forked_from: Option<String>,
+    },
+    #[serde(rename = "drift.merged")]
+    DriftMerged {
+        source_context: Option<String>,
+        target_context: String,
+        block_id: String,
+        source_model: Option<String>,
+        summary: String,
+    },
+}
+
+impl WebhookEvent {
+    pub fn kind(&self) -> WebhookEventKind {
+        match self {
+            Self::BlockError { .. } => WebhookEventKind::BlockError,
+            Self::ContextCreated { .. } => WebhookEventKind::ContextCreated,
+            Self::DriftMerged { .. } => WebhookEventKind::DriftMerged,
+        }
+    }
+
+    /// The event a block-flow message represents, if any.
+    fn from_flow(flow: &BlockFlow, blocks: &SharedBlockStore) -> Option<Self> {
+        match flow {
+            BlockFlow::Inserted { block, .. } => {
+                if block.drift_kind == Some(DriftKind::Merge) {
+                    Some(Self::drift_merged(block))
+                } else if block.status == Status::Error {
+                    Some(Self::block_error(block))
+                } else {
+                    None
+                }
+            }
+            BlockFlow::StatusChanged {
+                context_id,
+                block_id,
+                status: Status::Error,
+                ..
+            } => {
+                let block = blocks.get_block_snapshot(*context_id, block_id).ok()??;
+                Some(Self::block_error(&block))
+            }
+            _ => None,
+        }
+    }
+
+    fn block_error(block: &BlockSnapshot) -> Self {
+        Self::BlockError {
+            context_id: block.id.context_id.to_hex(),
+            block_id: block.id.to_key(),
+            summary: excerpt(&block.content),
+        }
+    }
+
+    fn drift_merged(block: &BlockSnapshot) -> Self {
+        Self::DriftMerged {
+            source_context: block.source_context.map(|c| c.to_hex()),
+            target_context: block.id.context_id.to_hex(),
+            block_id: block.id.to_key(),
+            source_model: block.source_model.clone(),
+            summary: excerpt(&block.content),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    kernel_id: String,
+    timestamp: u64,
+    #[serde(flatten)]
+    event: &'a WebhookEvent,
+}
+
+/// The kernel's webhook sender.
+pub struct Webhooks {
+    blocks: SharedBlockStore,
+    kernel_id: KernelId,
+    client: reqwest::Client,
+    /// Runtime deliveries are spawned on, pinned by [`spawn_watcher`]. Until
+    /// then (tests, embedded use) the caller's runtime is used.
+    runtime: OnceLock<tokio::runtime::Handle>,
+}
+
+impl Webhooks {
+    pub fn new(blocks: SharedBlockStore, kernel_id: KernelId) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            blocks,
+            kernel_id,
+            client,
+            runtime: OnceLock::new(),
+        }
+    }
+
+    /// The current `webhooks.toml`. Absent (a kernel seeded before webhooks
+    /// existed) is an empty config; unparseable is logged and treated the same.
+    pub fn config(&self) -> WebhookConfig {
+        let path = kaijutsu_types::paths::config_path(WEBHOOKS_CONFIG_FILE);
+        let Some(content) = read_content(&self.blocks, config_context_id(&path)) else {
+            return WebhookConfig::default();
+        };
+        WebhookConfig::parse(&content).unwrap_or_else(|e| {
+            tracing::warn!("{path}: {e}; no webhooks will fire until it is fixed");
+            WebhookConfig::default()
+        })
+    }
+
+    /// Send `event` to every hook subscribed to it. Returns how many
+    /// deliveries were started.
+    pub fn emit(&self, event: WebhookEvent) -> usize {
+        let kind = event.kind();
+        let targets: Vec<WebhookTarget> = self
+            .config()
+            .hooks
+            .into_iter()
+            .filter(|h| h.wants(kind))
+            .collect();
+        if targets.is_empty() {
+            return 0;
+        }
+        let runtime = match self.runtime.get() {
+            Some(handle) => handle.clone(),
+            None => match tokio::runtime::Handle::try_current() {
+                Ok(handle) => handle,
+                Err(_) => {
+                    tracing::warn!("webhooks: no async runtime, dropping {}", kind.as_str());
+                    return 0;
+                }
+            },
+        };
+        let payload = Payload {
+            kernel_id: self.kernel_id.to_hex(),
+            timestamp: kaijutsu_types::now_millis(),
+            event: &event,
+        };
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => Arc::<[u8]>::from(body),
+            Err(e) => {
+                tracing::warn!("webhooks: failed to serialize {}: {e}", kind.as_str());
+                return 0;
+            }
+        };
+        let started = targets.len();
+        for target in targets {
+            runtime.spawn(deliver(self.client.clone(), target, kind, body.clone()));
+        }
+        started
+    }
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `body` under `secret`.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// POST `body` to one hook, retrying transient failures with backoff.
+async fn deliver(
+    client: reqwest::Client,
+    target: WebhookTarget,
+    kind: WebhookEventKind,
+    body: Arc<[u8]>,
+) {
+    // A hook that should sign but can't is skipped: sending it unsigned
+    // would only be rejected by a receiver that checks.
+    let secret = match target.resolve_secret() {
+        Ok(secret) => secret,
+        Err(e) => {
+            tracing::warn!(
+                "webhooks: not sending {} to {}: {e}",
+                kind.as_str(),
+                target.url
+            );
+            return;
+        }
+    };
+    let mut backoff = INITIAL_BACKOFF;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = client
+            .post(&target.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, kind.as_str())
+            .body(body.to_vec());
+        if let Some(secret) = &secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, &body));
+        }
+
+        let retry = match request.send().await {
+            Ok(resp) if resp.status().is_success() => return,
+            Ok(resp) => {
+                let status = resp.status();
+                let transient = status.is_server_error()
+                    || status == reqwest::StatusCode::REQUEST_TIMEOUT
+                    || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                if !transient {
+                    tracing::warn!(
+                        "webhooks: {} rejected {} with {status}; not retrying",
+                        target.url,
+                        kind.as_str()
+                    );
+                    return;
+                }
+                format!("{status}")
+            }
+            Err(e) => e.to_string(),
+        };
+
+        if attempt == MAX_ATTEMPTS {
+            tracing::warn!(
+                "webhooks: giving up on {} for {} after {MAX_ATTEMPTS} attempts: {retry}",
+                target.url,
+                kind.as_str()
+            );
+            return;
+        }
+        tracing::debug!(
+            "webhooks: {} attempt {attempt} failed ({retry}); retrying in {backoff:?}",
+            target.url
+        );
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+}
+
+/// Watch the block FlowBus and emit `block.error` / `drift.merged`, and pin
+/// all later deliveries to the current runtime. Call once, from a runtime
+/// that lives as long as the kernel. Returns `None` when the store has no
+/// FlowBus (nothing to watch).
+pub fn spawn_watcher(webhooks: Arc<Webhooks>) -> Option<tokio::task::JoinHandle<()>> {
+    let _ = webhooks.runtime.set(tokio::runtime::Handle::current());
+    let mut sub = webhooks.blocks.block_flows()?.subscribe("block.*");
+    Some(tokio::spawn(async move {
+        while let Some(msg) = sub.recv().await {
+            if let Some(event) = WebhookEvent::from_flow(&msg.payload, &webhooks.blocks) {
+                webhooks.emit(event);
+            }
+        }
+    }))
+}
+
+fn excerpt(content: &str) -> String {
+    match content.char_indices().nth(SUMMARY_MAX_CHARS) {
+        Some((cut, _)) => format!("{}…", &content[..cut]),
+        None => content.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hooks_and_filters_events() {
+        let config = WebhookConfig::parse(
+            r#"
+            [[hooks]]
+            url = "https://ci.example.com/hook"
+            events = ["block.error"]
+            secret = "s3cret"
+
+            [[hooks]]
+            url = "http://localhost:9000/all"
+
+            [[hooks]]
+            url = "http://localhost:9000/off"
+            enabled = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.hooks.len(), 3);
+        let wants = |kind| config.hooks.iter().filter(|h| h.wants(kind)).count();
+        assert_eq!(wants(WebhookEventKind::BlockError), 2);
+        assert_eq!(wants(WebhookEventKind::DriftMerged), 1);
+    }
+
+    #[test]
+    fn rejects_typos_and_bad_urls() {
+        assert!(WebhookConfig::parse("[[hooks]]\nurl = \"ftp://x\"").is_err());
+        assert!(
+            WebhookConfig::parse("[[hooks]]\nurl = \"http://x\"\nevents = [\"block.eror\"]")
+                .is_err()
+        );
+        assert!(WebhookConfig::parse("[[hooks]]\nurl = \"http://x\"\nsecrt = \"s\"").is_err());
+        assert_eq!(WebhookConfig::parse("").unwrap(), WebhookConfig::default());
+    }
+
+    #[test]
+    fn secret_env_is_prefixed_and_exclusive() {
+        let hook = |secrets: &str| {
+            WebhookConfig::parse(&format!("[[hooks]]\nurl = \"http://x\"\n{secrets}"))
+        };
+        let config = hook("secret_env = \"KAIJUTSU_WEBHOOK_UNSET_FOR_TEST\"").unwrap();
+        assert!(config.hooks[0].resolve_secret().is_err(), "unset variable");
+        assert!(hook("secret_env = \"AWS_SECRET_ACCESS_KEY\"").is_err());
+        assert!(hook("secret_env = \"KAIJUTSU_WEBHOOK_X\"\nsecret = \"s\"").is_err());
+        let config = hook("secret = \"s\"").unwrap();
+        assert_eq!(config.hooks[0].resolve_secret(), Ok(Some("s".into())));
+        let config = hook("").unwrap();
+        assert_eq!(config.hooks[0].resolve_secret(), Ok(None));
+    }
+
+    #[test]
+    fn seeded_default_has_no_hooks() {
+        let config = WebhookConfig::parse(crate::config_seed::DEFAULT_WEBHOOKS).unwrap();
+        assert!(config.hooks.is_empty());
+    }
+
+    #[test]
+    fn signature_is_hmac_sha256() {
+        // RFC 4231 test case 2.
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn payload_is_flat_and_tagged() {
+        let event = WebhookEvent::BlockError {
+            context_id: "ctx".into(),
+            block_id: "blk".into(),
+            summary: "boom".into(),
+        };
+        let payload = Payload {
+            kernel_id: "k".into(),
+            timestamp: 7,
+            event: &event,
+        };
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "kernel_id": "k",
+                "timestamp": 7,
+                "event": "block.error",
+                "context_id": "ctx",
+                "block_id": "blk",
+                "summary": "boom",
+            })
+        );
+    }
+}
@@ -1495,6 +1495,7 @@ async fn kernel_tools_resource_end_to_end() {
         "builtin.block",
         "builtin.file",
         "builtin.kernel_info",
+        "builtin.kv",
         "builtin.resources",
         "builtin.hooks",
         "builtin.bindings",
@@ -0,0 +1,267 @@
+//! Autosave for the in-memory local backend.
+//!
+//! Without `--persist` the local store lives only as long as the process, so
+//! `serve` exports it when the MCP session ends gracefully: one timestamped
+//! directory under the autosave root holding, per document, a Markdown
+//! rendering to read (`{id}.md`) and its encoded store snapshot — blocks plus
+//! the full per-block oplog — to restore from (`{id}.oplog`), with a
+//! `manifest.json` tying them together. The manifest is written last, so a
+//! directory without one is an interrupted export and is ignored.
+//! `serve --restore-last` loads the newest complete export back into the
+//! fresh store at startup.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use kaijutsu_crdt::{BlockKind, BlockSnapshot, ContextId};
+use kaijutsu_kernel::SharedBlockStore;
+use kaijutsu_types::DocKind;
+use kaijutsu_types::language::markdown_fence;
+use kaijutsu_types::reflow::reflows_language;
+
+/// File naming the documents of one export.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Exports kept under the autosave root; older ones are pruned after each
+/// export.
+pub const AUTOSAVE_KEEP: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    documents: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    id: ContextId,
+    kind: DocKind,
+    language: Option<String>,
+    blocks: usize,
+}
+
+/// Default autosave root: `~/.local/share/kaijutsu/mcp-autosave` (XDG
+/// `data_local_dir`), or `./mcp-autosave` when XDG can't be resolved.
+pub fn default_root() -> PathBuf {
+    match dirs::data_local_dir() {
+        Some(dir) => dir.join("kaijutsu").join("mcp-autosave"),
+        None => PathBuf::from("mcp-autosave"),
+    }
+}
+
+/// Export every document in `store` into `dir`, creating it. Returns the
+/// number of documents written; an empty store writes nothing.
+pub fn export_store(store: &SharedBlockStore, dir: &Path) -> Result<usize> {
+    let mut ids = store.list_ids();
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    ids.sort();
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+
+    let mut documents = Vec::with_capacity(ids.len());
+    for id in ids {
+        let (kind, language, blocks, _) = store.get_document_state(id)?;
+        let sync = store.context_sync_state(id)?;
+        let stem = id.to_hex();
+        fs::write(
+            dir.join(format!("{stem}.md")),
+            render_document(id, kind, &blocks),
+        )?;
+        fs::write(dir.join(format!("{stem}.oplog")), &sync.ops)?;
+        documents.push(ManifestEntry {
+            id,
+            kind,
+            language,
+            blocks: blocks.len(),
+        });
+    }
+
+    let count = documents.len();
+    let manifest = serde_json::to_string_pretty(&Manifest { documents })?;
+    fs::write(dir.join(MANIFEST_FILE), manifest)?;
+    Ok(count)
+}
+
+/// The newest complete export under `root`, if any. Export directories are
+/// named by UTC timestamp, so the newest sorts last.
+pub fn latest_export(root: &Path) -> Option<PathBuf> {
+    exports(root).pop()
+}
+
+/// Load the documents of the export in `dir` into `store`. Documents the
+/// store already holds are left alone. Returns the number restored.
+pub fn restore_export(store: &SharedBlockStore, dir: &Path) -> Result<usize> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let manifest: Manifest = serde_json::from_slice(
+        &fs::read(&manifest_path)
+            .with_context(|| format!("reading {}", manifest_path.display()))?,
+    )?;
+
+    let mut restored = 0;
+    for entry in manifest.documents {
+        if store.contains(entry.id) {
+            tracing::warn!(id = %entry.id.short(), "Autosaved document already present; skipped");
+            continue;
+        }
+        let path = dir.join(format!("{}.oplog", entry.id.to_hex()));
+        let bytes = fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+        store.create_document_from_snapshot(entry.id, entry.kind, entry.language, &bytes)?;
+        restored += 1;
+    }
+    Ok(restored)
+}
+
+/// Remove all but the newest `keep` exports under `root`. Returns how many
+/// were removed.
+pub fn prune_exports(root: &Path, keep: usize) -> usize {
+    let all = exports(root);
+    let excess = all.len().saturating_sub(keep);
+    all.into_iter()
+        .take(excess)
+        .filter(|dir| match fs::remove_dir_all(dir) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!(dir = %dir.display(), "Autosave prune failed: {e}");
+                false
+            }
+        })
+        .count()
+}
+
+/// Complete exports under `root`, oldest first.
+fn exports(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.join(MANIFEST_FILE).is_file())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// One document as Markdown: a heading per block, code and tool payloads
+/// fenced.
+fn render_document(id: ContextId, kind: DocKind, blocks: &[BlockSnapshot]) -> String {
+    let mut out = format!("# {} `{}`\n\n", kind.as_str(), id.to_hex());
+    for block in blocks {
+        let role = block.role.as_str();
+        match block.kind {
+            BlockKind::ToolCall => {
+                let name = block.tool_name.as_deref().unwrap_or("tool");
+                let input = if block.content.is_empty() {
+                    block.tool_input.as_deref().unwrap_or_default()
+                } else {
+                    block.content.as_str()
+                };
+                out.push_str(&format!("## {role} → `{name}`\n\n"));
+                out.push_str(&markdown_fence(input, Some("json")));
+            }
+            BlockKind::ToolResult => {
+                let head = if block.is_error {
+                    "result (error)"
+                } else {
+                    "result"
+                };
+                out.push_str(&format!("## {head}\n\n"));
+                out.push_str(&markdown_fence(&block.content, block.language.as_deref()));
+            }
+            kind => {
+                out.push_str(&format!("## {role} · {}\n\n", kind.as_str()));
+                if reflows_language(block.language.as_deref()) {
+                    out.push_str(block.content.trim_end());
+                    out.push('\n');
+                } else {
+                    out.push_str(&markdown_fence(&block.content, block.language.as_deref()));
+                }
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_crdt::{ContentType, PrincipalId, Role, Status};
+    use kaijutsu_kernel::shared_block_store;
+
+    fn temp_root() -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!(
+            "kaijutsu-mcp-autosave-{}-{nanos}",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn export_restores_into_a_fresh_store() {
+        let store = shared_block_store(PrincipalId::new());
+        let ctx = ContextId::new();
+        store
+            .create_document(ctx, DocKind::Conversation, None)
+            .unwrap();
+        let first = store
+            .insert_block(
+                ctx,
+                None,
+                None,
+                Role::User,
+                BlockKind::Text,
+                "hello",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+        store
+            .insert_block(
+                ctx,
+                None,
+                Some(&first),
+                Role::Model,
+                BlockKind::Text,
+                "hi there",
+                Status::Done,
+                ContentType::Plain,
+            )
+            .unwrap();
+
+        let root = temp_root();
+        assert_eq!(
+            export_store(&shared_block_store(PrincipalId::new()), &root.join("empty")).unwrap(),
+            0
+        );
+        assert_eq!(latest_export(&root), None, "empty stores leave no export");
+
+        let dir = root.join("20261016-120000");
+        assert_eq!(export_store(&store, &dir).unwrap(), 1);
+        let markdown = fs::read_to_string(dir.join(format!("{}.md", ctx.to_hex()))).unwrap();
+        assert!(markdown.contains("hello") && markdown.contains("hi there"));
+        assert_eq!(latest_export(&root), Some(dir.clone()));
+
+        let fresh = shared_block_store(PrincipalId::new());
+        assert_eq!(restore_export(&fresh, &dir).unwrap(), 1);
+        assert_eq!(
+            fresh.get_content(ctx).unwrap(),
+            store.get_content(ctx).unwrap()
+        );
+        assert_eq!(
+            restore_export(&fresh, &dir).unwrap(),
+            0,
+            "present docs skipped"
+        );
+
+        export_store(&store, &root.join("20261016-130000")).unwrap();
+        assert_eq!(prune_exports(&root, 1), 1);
+        assert_eq!(exports(&root), vec![root.join("20261016-130000")]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -32,6 +32,7 @@ use tokio::task::JoinHandle;
 
 use kaijutsu_client::{ActorHandle, ConnectionStatus, DocSyncBackend, ServerEvent, SyncEffect, SyncedDocument};
 use kaijutsu_crdt::{BlockId, BlockKind, ContentType, ContextId, Frontier, Role, Status, ToolKind};
+use kaijutsu_types::AgentProvenance;
 
 /// Channel capacity for the doc task's command mpsc. Generous — a burst of
 /// hook events, hydrated resync triggers (Lagged event + Lagged status +
@@ -63,11 +64,36 @@ pub enum AuthoredBlock {
         is_error: bool,
         tool_kind: Option<ToolKind>,
     },
+    /// An open tool call, left Running until a matching [`Self::ToolResult`]
+    /// completes it (a `tool.before` hook whose result arrives later).
+    ToolCall {
+        tool_name: String,
+        tool_input: serde_json::Value,
+        tool_kind: Option<ToolKind>,
+    },
+    /// The result for an open [`Self::ToolCall`]; completes the call
+    /// (Done/Error).
+    ToolResult {
+        call_id: BlockId,
+        content: String,
+        is_error: bool,
+        tool_kind: Option<ToolKind>,
+    },
+    /// A finished block under `parent` (an event hook's output), Done or
+    /// Error.
+    Child {
+        parent: BlockId,
+        kind: BlockKind,
+        role: Role,
+        content: String,
+        is_error: bool,
+    },
 }
 
 /// Why a [`DocCommand::Resync`] was requested — carried for logging /
 /// coalescing visibility only, not branched on inside the resync itself
-/// (every reason runs the identical flush→fetch→apply routine).
+/// (every reason runs the identical flush→fetch→apply routine, except that
+/// [`ResyncReason::QuarantineResolved`] empties the quarantine first).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResyncReason {
     /// A `ServerEvent::SyncReset` was applied and reported `NeedsResync`.
@@ -82,6 +108,9 @@ pub enum ResyncReason {
     /// `execute_and_poll_shell`'s stall fallback: no `change` watch progress
     /// for the current backoff window while a command is pending.
     StallFallback,
+    /// `sync_quarantine` with `resolve`: the quarantined payloads are
+    /// dropped before the fetch, so the snapshot replaces what they carried.
+    QuarantineResolved,
 }
 
 /// Failure modes the doc task reports back through a command's oneshot ack.
@@ -125,10 +154,14 @@ pub enum DocCommand {
     /// Author one or more blocks locally (HookListener's job). Acked once
     /// applied to the document — NOT once pushed to the server; push happens
     /// afterward, best-effort, and its own failure doesn't fail this ack (a
-    /// later push/resync will carry the ops).
+    /// later push/resync will carry the ops). The ack carries one id per
+    /// [`AuthoredBlock`]: the block it inserted (the call, for a
+    /// [`AuthoredBlock::ToolCallResult`] pair). `provenance`, when set, is
+    /// stamped on every block inserted — results included.
     AuthorBlocks {
         blocks: Vec<AuthoredBlock>,
-        done: oneshot::Sender<Result<(), DocTaskError>>,
+        provenance: Option<AgentProvenance>,
+        done: oneshot::Sender<Result<Vec<BlockId>, DocTaskError>>,
     },
     /// Run a resync: flush unpushed local ops, fetch the server's
     /// authoritative snapshot, apply it. `done` is `None` for
@@ -152,10 +185,20 @@ pub struct DocTaskHandle {
 
 impl DocTaskHandle {
     /// Author blocks and wait for them to be applied to the document.
-    pub async fn author_blocks(&self, blocks: Vec<AuthoredBlock>) -> Result<(), DocTaskError> {
+    /// Returns the inserted ids, one per [`AuthoredBlock`]. `provenance`
+    /// names the hosting agent session the blocks came from.
+    pub async fn author_blocks(
+        &self,
+        blocks: Vec<AuthoredBlock>,
+        provenance: Option<AgentProvenance>,
+    ) -> Result<Vec<BlockId>, DocTaskError> {
         let (done, ack) = oneshot::channel();
         self.tx
-            .send(DocCommand::AuthorBlocks { blocks, done })
+            .send(DocCommand::AuthorBlocks {
+                blocks,
+                provenance,
+                done,
+            })
             .await
             .map_err(|_| DocTaskError::Shutdown)?;
         ack.await.map_err(|_| DocTaskError::Shutdown)?
@@ -244,8 +287,12 @@ async fn run_doc_task<B: DocSyncBackend>(
                     .await;
                 }
             }
-            DocCommand::AuthorBlocks { blocks, done } => {
-                let result = author_blocks_sync(&synced, blocks);
+            DocCommand::AuthorBlocks {
+                blocks,
+                provenance,
+                done,
+            } => {
+                let result = author_blocks_sync(&synced, blocks, provenance.as_ref());
                 bump(&change);
                 let ok = result.is_ok();
                 let _ = done.send(result);
@@ -291,30 +338,35 @@ fn apply_event_sync(
 
 /// Apply a batch of [`AuthoredBlock`]s under one lock acquisition. Mirrors
 /// exactly what `HookListener::insert_text_block` / `insert_tool_blocks` did
-/// imperatively before this module existed.
+/// imperatively before this module existed, plus the provenance stamp —
+/// set before the push takes the creation ops, so it rides the new-block
+/// snapshots.
 fn author_blocks_sync(
     synced: &Arc<parking_lot::Mutex<Option<SyncedDocument>>>,
     blocks: Vec<AuthoredBlock>,
-) -> Result<(), DocTaskError> {
+    provenance: Option<&AgentProvenance>,
+) -> Result<Vec<BlockId>, DocTaskError> {
     let mut guard = synced.lock();
     let Some(doc) = guard.as_mut() else {
         return Err(DocTaskError::NoDocument);
     };
+    let mut ids = Vec::with_capacity(blocks.len());
+    // Every block inserted, including results the ack doesn't report.
+    let mut inserted = Vec::with_capacity(blocks.len());
     for block in blocks {
-        match block {
-            AuthoredBlock::Text { role, content } => {
-                doc.doc_mut()
-                    .insert_block(
-                        None,
-                        None,
-                        role,
-                        BlockKind::Text,
-                        content,
-                        Status::Done,
-                        ContentType::Plain,
-                    )
-                    .map_err(|e| DocTaskError::Insert(e.to_string()))?;
-            }
+        let id = match block {
+            AuthoredBlock::Text { role, content } => doc
+                .doc_mut()
+                .insert_block(
+                    None,
+                    None,
+                    role,
+                    BlockKind::Text,
+                    content,
+                    Status::Done,
+                    ContentType::Plain,
+                )
+                .map_err(|e| DocTaskError::Insert(e.to_string()))?,
             AuthoredBlock::ToolCallResult {
                 tool_name,
                 tool_input,
@@ -326,17 +378,65 @@ fn author_blocks_sync(
                     .doc_mut()
                     .insert_tool_call(None, None, tool_name, tool_input, tool_kind, None)
                     .map_err(|e| DocTaskError::Insert(e.to_string()))?;
-                doc.doc_mut()
+                let result_id = doc
+                    .doc_mut()
                     .insert_tool_result_block(&call_id, None, result_content, is_error, None, tool_kind)
                     .map_err(|e| DocTaskError::Insert(e.to_string()))?;
+                inserted.push(result_id);
                 let final_status = if is_error { Status::Error } else { Status::Done };
                 doc.doc_mut()
                     .set_status(&call_id, final_status)
                     .map_err(|e| DocTaskError::Insert(e.to_string()))?;
+                call_id
             }
+            AuthoredBlock::ToolCall {
+                tool_name,
+                tool_input,
+                tool_kind,
+            } => doc
+                .doc_mut()
+                .insert_tool_call(None, None, tool_name, tool_input, tool_kind, None)
+                .map_err(|e| DocTaskError::Insert(e.to_string()))?,
+            AuthoredBlock::ToolResult {
+                call_id,
+                content,
+                is_error,
+                tool_kind,
+            } => {
+                let result_id = doc
+                    .doc_mut()
+                    .insert_tool_result_block(&call_id, None, content, is_error, None, tool_kind)
+                    .map_err(|e| DocTaskError::Insert(e.to_string()))?;
+                let final_status = if is_error { Status::Error } else { Status::Done };
+                doc.doc_mut()
+                    .set_status(&call_id, final_status)
+                    .map_err(|e| DocTaskError::Insert(e.to_string()))?;
+                result_id
+            }
+            AuthoredBlock::Child {
+                parent,
+                kind,
+                role,
+                content,
+                is_error,
+            } => {
+                let status = if is_error { Status::Error } else { Status::Done };
+                doc.doc_mut()
+                    .insert_block(Some(&parent), None, role, kind, content, status, ContentType::Plain)
+                    .map_err(|e| DocTaskError::Insert(e.to_string()))?
+            }
+        };
+        inserted.push(id);
+        ids.push(id);
+    }
+    if let Some(provenance) = provenance {
+        for id in &inserted {
+            doc.doc_mut()
+                .set_provenance(id, provenance.clone())
+                .map_err(|e| DocTaskError::Insert(e.to_string()))?;
         }
     }
-    Ok(())
+    Ok(ids)
 }
 
 /// Push whatever's changed since `pushed_frontier` — NOT since the inbound
@@ -384,7 +484,10 @@ async fn push_new_ops<B: DocSyncBackend>(
             return Err(DocTaskError::Flush(format!("encode failed: {e}")));
         }
     };
-    match backend.push_ops(context_id, &bytes).await {
+    match backend
+        .push_ops(context_id, &bytes, Some(pushed_frontier))
+        .await
+    {
         Ok(_ack_version) => {
             *pushed_frontier = new_frontier;
             Ok(())
@@ -459,8 +562,12 @@ async fn do_coalesced_resync<B: DocSyncBackend>(
                     dones.push(d);
                 }
             }
-            Ok(DocCommand::AuthorBlocks { blocks, done }) => {
-                let result = author_blocks_sync(synced, blocks);
+            Ok(DocCommand::AuthorBlocks {
+                blocks,
+                provenance,
+                done,
+            }) => {
+                let result = author_blocks_sync(synced, blocks, provenance.as_ref());
                 bump(change);
                 let _ = done.send(result);
             }
@@ -507,6 +614,13 @@ async fn do_coalesced_resync<B: DocSyncBackend>(
         return;
     }
 
+    if reasons.contains(&ResyncReason::QuarantineResolved)
+        && let Some(doc) = synced.lock().as_mut()
+    {
+        let dropped = doc.resolve_quarantine();
+        tracing::info!(%context_id, dropped, "doc task: quarantine resolved");
+    }
+
     let result = match backend.get_context_sync(context_id).await {
         Ok(state) => {
             let mut guard = synced.lock();
@@ -691,15 +805,26 @@ mod tests {
                 let store = self.server_doc.lock().unwrap();
                 snapshot_bytes(&store)
             };
-            Ok(SyncState { context_id, version: 1, ops })
+            Ok(SyncState {
+                context_id,
+                version: 1,
+                ops,
+                generation: 0,
+                seq_num: 0,
+            })
         }
 
-        async fn push_ops(&self, context_id: ContextId, ops: &[u8]) -> Result<u64, CallError> {
+        async fn push_ops(
+            &self,
+            context_id: ContextId,
+            ops: &[u8],
+            _known: Option<&HashMap<BlockId, Frontier>>,
+        ) -> Result<u64, CallError> {
             assert_eq!(context_id, self.ctx, "fake backend push for wrong context");
             let remaining = self.push_fail_countdown.load(Ordering::SeqCst);
             if remaining > 0 {
                 self.push_fail_countdown.store(remaining - 1, Ordering::SeqCst);
-                return Err(CallError::Rpc("simulated push failure".to_string()));
+                return Err(CallError::ServerError("simulated push failure".to_string()));
             }
             let payload: SyncPayload =
                 kaijutsu_types::codec::decode(ops).expect("decode pushed SyncPayload");
@@ -787,10 +912,13 @@ mod tests {
         let author_handle = handle.clone();
         let author_task = tokio::spawn(async move {
             author_handle
-                .author_blocks(vec![AuthoredBlock::Text {
-                    role: Role::User,
-                    content: "hello-mid-fetch".to_string(),
-                }])
+                .author_blocks(
+                    vec![AuthoredBlock::Text {
+                        role: Role::User,
+                        content: "hello-mid-fetch".to_string(),
+                    }],
+                    None,
+                )
                 .await
         });
         // Give the author's send a moment to actually land in the mpsc
@@ -844,10 +972,13 @@ mod tests {
 
         for i in 0..3 {
             handle
-                .author_blocks(vec![AuthoredBlock::Text {
-                    role: Role::User,
-                    content: format!("msg-{i}"),
-                }])
+                .author_blocks(
+                    vec![AuthoredBlock::Text {
+                        role: Role::User,
+                        content: format!("msg-{i}"),
+                    }],
+                    None,
+                )
                 .await
                 .unwrap();
         }
@@ -868,6 +999,49 @@ mod tests {
         task.abort();
     }
 
+    /// Provenance rides the pushed snapshots of every block a command
+    /// inserts — the result of a call/result pair too, not just the call.
+    #[tokio::test]
+    async fn provenance_is_stamped_on_every_authored_block() {
+        let ctx = ContextId::new();
+        let synced = seeded_synced(ctx);
+        let (change_tx, _change_rx) = watch::channel(0u64);
+        let backend = FakeBackend::new(ctx);
+
+        let (handle, task) = spawn_doc_task(backend.clone(), ctx, Arc::clone(&synced), change_tx);
+
+        let provenance = AgentProvenance {
+            agent: "claude-code".to_string(),
+            version: Some("2.1.3".to_string()),
+            session_id: Some("4f1c2a9b".to_string()),
+            project_dir: Some("/mnt/project".to_string()),
+        };
+        handle
+            .author_blocks(
+                vec![AuthoredBlock::ToolCallResult {
+                    tool_name: "Bash".to_string(),
+                    tool_input: serde_json::json!({"command": "ls"}),
+                    result_content: "Cargo.toml".to_string(),
+                    is_error: false,
+                    tool_kind: Some(ToolKind::Mcp),
+                }],
+                Some(provenance.clone()),
+            )
+            .await
+            .unwrap();
+
+        let pushes = backend.push_payloads();
+        let blocks = &pushes.last().expect("one push").new_blocks;
+        assert_eq!(blocks.len(), 2, "call and result");
+        assert!(
+            blocks
+                .iter()
+                .all(|b| b.provenance.as_ref() == Some(&provenance))
+        );
+
+        task.abort();
+    }
+
     /// TDD item (c): N Resync commands already queued by the time the task
     /// starts processing the first one must coalesce into exactly ONE
     /// fetch, with every caller's ack completed once it's done.
@@ -964,6 +1138,7 @@ mod tests {
                 role: Role::User,
                 content: "unpushed".to_string(),
             }],
+            provenance: None,
             done: author_done,
         })
         .await
@@ -0,0 +1,558 @@
+//! `kaijutsu-mcp doctor` — connectivity and capability diagnosis.
+//!
+//! "It doesn't connect" has a dozen causes: no ssh-agent, a server that isn't
+//! running, a key the server never saw, a stale `known_hosts` entry, a server
+//! built from an older schema, a hook socket left behind by a crashed session.
+//! [`run`] walks them in dependency order and returns a [`Report`]; a check
+//! whose prerequisite failed is skipped rather than reported as a second
+//! failure, so the first `FAIL` is the one to fix.
+//!
+//! Must be called within a `LocalSet` (the RPC probes are `!Send`).
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use kaijutsu_client::{
+    ConnectError, RpcError, SshConfig, SshError, agent_key_fingerprints, connect_ssh,
+};
+use serde::Serialize;
+use tokio::io::AsyncReadExt;
+
+use crate::KaijutsuMcp;
+use crate::hook_listener::{PING_TIMEOUT, candidate_sockets, default_socket_path, ping_socket};
+
+/// Bound on the TCP connect + SSH banner read.
+const REACH_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Bound on the SSH handshake + auth + each RPC probe.
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Bound on the in-process MCP round trip.
+const STDIO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where to point the checks — the same knobs as `serve --connect`.
+#[derive(Debug, Clone)]
+pub struct DoctorOptions {
+    pub host: String,
+    pub port: u16,
+    pub context_name: String,
+    /// Explicit `--hook-socket`; otherwise the default path and discovery.
+    pub hook_socket: Option<PathBuf>,
+}
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    /// Works, but something will bite later (no hook socket, no context yet).
+    Warn,
+    Fail,
+    /// Not run: a prerequisite failed.
+    Skip,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Pass => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+            Status::Skip => "skip",
+        }
+    }
+}
+
+/// One line of the report.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warn or fail.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self::new(name, Status::Pass, detail)
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>) -> Self {
+        Self::new(name, Status::Warn, detail)
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self::new(name, Status::Fail, detail)
+    }
+
+    fn skip(name: &'static str, why: impl Into<String>) -> Self {
+        Self::new(name, Status::Skip, why)
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// Every check, in the order run.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub target: String,
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// True when nothing failed (warnings are fine).
+    pub fn ok(&self) -> bool {
+        self.checks.iter().all(|c| c.status != Status::Fail)
+    }
+
+    fn push(&mut self, check: Check) -> bool {
+        let passed = matches!(check.status, Status::Pass | Status::Warn);
+        self.checks.push(check);
+        passed
+    }
+
+    fn count(&self, status: Status) -> usize {
+        self.checks.iter().filter(|c| c.status == status).count()
+    }
+
+    /// Human-readable report, one check per line with hints indented below.
+    pub fn render(&self) -> String {
+        let width = self.checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+        let mut out = format!("kaijutsu-mcp doctor — {}\n\n", self.target);
+        for check in &self.checks {
+            out.push_str(&format!(
+                "  {:<4}  {:<width$}  {}\n",
+                check.status.label(),
+                check.name,
+                check.detail,
+            ));
+            if let Some(hint) = &check.hint {
+                out.push_str(&format!("        {:<width$}  → {hint}\n", ""));
+            }
+        }
+        let (failed, warned) = (self.count(Status::Fail), self.count(Status::Warn));
+        out.push('\n');
+        out.push_str(&match (failed, warned) {
+            (0, 0) => "all checks passed".to_string(),
+            (0, w) => format!("no failures, {w} warning(s)"),
+            (f, w) => format!("{f} failed, {w} warning(s)"),
+        });
+        out.push('\n');
+        out
+    }
+}
+
+/// Run every check against `opts`.
+pub async fn run(opts: &DoctorOptions) -> Report {
+    let mut report = Report {
+        target: format!("{}:{}", opts.host, opts.port),
+        checks: Vec::new(),
+    };
+
+    let agent = report.push(check_agent().await);
+    let reachable = report.push(check_reachable(&opts.host, opts.port).await);
+    if agent && reachable {
+        check_remote(opts, &mut report).await;
+    } else {
+        let why = if agent {
+            "server unreachable"
+        } else {
+            "no usable ssh-agent"
+        };
+        for name in ["auth", "kernel", "context", "schema"] {
+            report.push(Check::skip(name, why));
+        }
+    }
+
+    report.push(check_hook_socket(opts.hook_socket.clone()).await);
+    report.push(check_mcp_stdio().await);
+    report
+}
+
+async fn check_agent() -> Check {
+    match agent_key_fingerprints().await {
+        Ok(keys) => Check::pass(
+            "ssh-agent",
+            format!("{} key(s), first {}", keys.len(), keys[0]),
+        ),
+        Err(e) => Check::fail("ssh-agent", e.to_string()).hint(ssh_hint(&e)),
+    }
+}
+
+/// TCP connect, then read the server's identification line — proves an SSH
+/// server (not some other service) is listening before auth muddies errors.
+async fn check_reachable(host: &str, port: u16) -> Check {
+    let probe = async {
+        let mut stream = tokio::net::TcpStream::connect((host, port)).await?;
+        let mut buf = [0u8; 255];
+        let n = stream.read(&mut buf).await?;
+        Ok::<_, std::io::Error>(String::from_utf8_lossy(&buf[..n]).trim().to_string())
+    };
+    match tokio::time::timeout(REACH_TIMEOUT, probe).await {
+        Ok(Ok(banner)) if banner.starts_with("SSH-") => Check::pass("server", banner),
+        Ok(Ok(banner)) => Check::fail(
+            "server",
+            format!("{host}:{port} answered, but not with SSH: {banner:?}"),
+        )
+        .hint("check --host/--port — another service owns that port"),
+        Ok(Err(e)) => Check::fail("server", format!("{host}:{port}: {e}")).hint(format!(
+            "is kaijutsu-server running? `kaijutsu-server --port {port}`"
+        )),
+        Err(_) => Check::fail(
+            "server",
+            format!("{host}:{port}: no answer in {REACH_TIMEOUT:?}"),
+        )
+        .hint("a firewall or a wedged server; try `ssh -p PORT HOST` for comparison"),
+    }
+}
+
+/// Auth, kernel, context, and schema — one SSH session shared by all four.
+async fn check_remote(opts: &DoctorOptions, report: &mut Report) {
+    let mut schema = SchemaProbe::default();
+    let config = SshConfig {
+        host: opts.host.clone(),
+        port: opts.port,
+        username: whoami::username(),
+        ..SshConfig::default()
+    };
+
+    let client = match tokio::time::timeout(RPC_TIMEOUT, connect_ssh(config)).await {
+        Ok(Ok(client)) => client,
+        Ok(Err(e)) => {
+            let check = match &e {
+                ConnectError::Ssh(ssh) => Check::fail("auth", e.to_string()).hint(ssh_hint(ssh)),
+                _ => Check::fail("auth", e.to_string()),
+            };
+            report.push(check);
+            for name in ["kernel", "context", "schema"] {
+                report.push(Check::skip(name, "not authenticated"));
+            }
+            return;
+        }
+        Err(_) => {
+            report.push(Check::fail(
+                "auth",
+                format!("handshake timed out after {RPC_TIMEOUT:?}"),
+            ));
+            for name in ["kernel", "context", "schema"] {
+                report.push(Check::skip(name, "not authenticated"));
+            }
+            return;
+        }
+    };
+
+    match schema.call("whoami", client.whoami()).await {
+        Ok(id) => report.push(Check::pass(
+            "auth",
+            format!("as {} ({})", id.username, id.principal_id.short()),
+        )),
+        Err(e) => report.push(Check::fail("auth", e)),
+    };
+
+    let kernel = match schema.call("bindKernel", client.bind_kernel()).await {
+        Ok((kernel, _)) => kernel,
+        Err(e) => {
+            report.push(Check::fail("kernel", e));
+            report.push(Check::skip("context", "no kernel"));
+            report.push(schema.check());
+            return;
+        }
+    };
+    let kernel_check = match schema.call("getInfo", kernel.get_info()).await {
+        Ok(info) => match schema.call("ping", kernel.ping()).await {
+            Ok((_, server_ms)) => Check::pass(
+                "kernel",
+                format!(
+                    "{} ({}), clock skew {}",
+                    info.name,
+                    info.id.short(),
+                    skew(server_ms)
+                ),
+            ),
+            Err(e) => Check::fail("kernel", format!("{} bound, ping failed: {e}", info.name)),
+        },
+        Err(e) => Check::fail("kernel", e),
+    };
+    report.push(kernel_check);
+
+    let context_check = match schema.call("listContexts", kernel.list_contexts()).await {
+        Ok(contexts) => match contexts.iter().find(|c| c.label == opts.context_name) {
+            Some(ctx) => Check::pass(
+                "context",
+                format!(
+                    "'{}' ({}), {} in kernel",
+                    ctx.label,
+                    ctx.id.short(),
+                    contexts.len()
+                ),
+            ),
+            None => Check::warn(
+                "context",
+                format!(
+                    "no context labeled '{}' ({} in kernel)",
+                    opts.context_name,
+                    contexts.len()
+                ),
+            )
+            .hint("register_session creates one on first use"),
+        },
+        Err(e) => Check::fail("context", e),
+    };
+    report.push(context_check);
+    report.push(schema.check());
+}
+
+/// Tracks which RPCs the server answered, and whether any failure looks like
+/// a schema mismatch (an unimplemented method, or a value the client's
+/// schema can't decode) rather than an ordinary error.
+#[derive(Default)]
+struct SchemaProbe {
+    answered: usize,
+    mismatched: Vec<&'static str>,
+}
+
+impl SchemaProbe {
+    async fn call<T>(
+        &mut self,
+        method: &'static str,
+        fut: impl Future<Output = Result<T, RpcError>>,
+    ) -> Result<T, String> {
+        match tokio::time::timeout(RPC_TIMEOUT, fut).await {
+            Ok(Ok(value)) => {
+                self.answered += 1;
+                Ok(value)
+            }
+            Ok(Err(e)) => {
+                if is_schema_mismatch(&e) {
+                    self.mismatched.push(method);
+                }
+                Err(format!("{method}: {e}"))
+            }
+            Err(_) => Err(format!("{method}: no answer in {RPC_TIMEOUT:?}")),
+        }
+    }
+
+    fn check(&self) -> Check {
+        if self.mismatched.is_empty() {
+            return Check::pass(
+                "schema",
+                format!("{} probe call(s) answered", self.answered),
+            );
+        }
+        Check::fail(
+            "schema",
+            format!(
+                "server and client disagree on {}",
+                self.mismatched.join(", ")
+            ),
+        )
+        .hint("rebuild kaijutsu-server and kaijutsu-mcp from the same checkout")
+    }
+}
+
+fn is_schema_mismatch(e: &RpcError) -> bool {
+    match e {
+        RpcError::NotInSchema(_) => true,
+        RpcError::Capnp(e) => e.kind == capnp::ErrorKind::Unimplemented,
+        _ => false,
+    }
+}
+
+/// Server clock minus ours, signed, for the kernel line.
+fn skew(server_ms: u64) -> String {
+    let local_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    format!("{:+}ms", server_ms as i64 - local_ms)
+}
+
+/// The remedy for each SSH failure, phrased for someone at a shell.
+fn ssh_hint(e: &SshError) -> String {
+    match e {
+        SshError::AgentFailed(_) => {
+            "start an agent (`eval $(ssh-agent)`) and `ssh-add` a key; SSH_AUTH_SOCK must be set"
+                .to_string()
+        }
+        SshError::NoKeysAvailable => "`ssh-add` a key the server knows".to_string(),
+        SshError::AuthFailed(_) => {
+            "on the server: `kaijutsu-server add-key ~/.ssh/id_ed25519.pub`".to_string()
+        }
+        SshError::KeyLoadFailed(_) => "check the key file path and passphrase".to_string(),
+        SshError::HostKeyMismatch {
+            known_hosts_path,
+            line,
+            ..
+        } => {
+            format!("if the server was reinstalled, delete {known_hosts_path} line {line}")
+        }
+        SshError::HostKeyVerificationFailed(_) => {
+            "check ~/.ssh/known_hosts is readable".to_string()
+        }
+        SshError::ConnectionFailed(_) | SshError::ChannelFailed(_) | SshError::Disconnected => {
+            "check the server log for the rejected session".to_string()
+        }
+    }
+}
+
+/// Hook sockets are optional — hooks fail open without one — so nothing here
+/// is worse than a warning.
+async fn check_hook_socket(explicit: Option<PathBuf>) -> Check {
+    if explicit.is_none() && default_socket_path().is_none() {
+        return Check::warn("hook-socket", "$XDG_RUNTIME_DIR is not set")
+            .hint("serve runs without a hook socket unless --hook-socket is given");
+    }
+    let present: Vec<PathBuf> = candidate_sockets(explicit)
+        .into_iter()
+        .filter(|p| p.exists())
+        .collect();
+    if present.is_empty() {
+        return Check::warn("hook-socket", "no hook sockets found")
+            .hint("none is listening until `kaijutsu-mcp serve` runs; hooks fail open meanwhile");
+    }
+
+    let pings = futures::future::join_all(
+        present
+            .iter()
+            .map(|path| tokio::time::timeout(PING_TIMEOUT, ping_socket(path))),
+    )
+    .await;
+    let live: Vec<String> = pings
+        .into_iter()
+        .filter_map(|p| p.ok().flatten())
+        .map(|p| {
+            let context = p.context_name.as_deref().unwrap_or("no context");
+            format!("pid {} ({context})", p.pid)
+        })
+        .collect();
+    if live.is_empty() {
+        return Check::warn(
+            "hook-socket",
+            format!("{} socket(s), none answering", present.len()),
+        )
+        .hint("stale sockets from exited sessions; the next `serve` sweeps them");
+    }
+    Check::pass(
+        "hook-socket",
+        format!(
+            "{}/{} answering: {}",
+            live.len(),
+            present.len(),
+            live.join(", ")
+        ),
+    )
+}
+
+/// Serve an in-memory `KaijutsuMcp` over an in-process pipe and drive it
+/// with a real MCP client: initialize, then list tools. Catches a broken
+/// binary (tool schema that fails to build, handshake regression) without
+/// needing a host agent.
+async fn check_mcp_stdio() -> Check {
+    let probe = async {
+        let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+        let (server, client) = tokio::join!(
+            rmcp::serve_server(KaijutsuMcp::new(), server_io),
+            rmcp::serve_client((), client_io),
+        );
+        let (server, client) = (
+            server.map_err(|e| e.to_string())?,
+            client.map_err(|e| e.to_string())?,
+        );
+        let protocol = client
+            .peer_info()
+            .map(|info| info.protocol_version.to_string())
+            .unwrap_or_default();
+        let tools = client.list_all_tools().await.map_err(|e| e.to_string())?;
+        let _ = client.cancel().await;
+        let _ = server.cancel().await;
+        Ok::<_, String>((protocol, tools.len()))
+    };
+    let check = match tokio::time::timeout(STDIO_TIMEOUT, probe).await {
+        Ok(Ok((_, 0))) => Check::fail("mcp-stdio", "handshake ok, but no tools listed"),
+        Ok(Ok((protocol, tools))) => {
+            Check::pass("mcp-stdio", format!("protocol {protocol}, {tools} tools"))
+        }
+        Ok(Err(e)) => Check::fail("mcp-stdio", e),
+        Err(_) => Check::fail("mcp-stdio", format!("no answer in {STDIO_TIMEOUT:?}")),
+    };
+    if check.status == Status::Fail {
+        return check.hint("this binary is broken; rebuild kaijutsu-mcp");
+    }
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_fails_only_on_fail_and_renders_hints() {
+        let mut report = Report {
+            target: "localhost:2222".to_string(),
+            checks: Vec::new(),
+        };
+        assert!(report.push(Check::pass("ssh-agent", "1 key(s)")));
+        assert!(report.push(Check::warn("hook-socket", "none").hint("run serve")));
+        assert!(report.ok(), "warnings alone are not a failure");
+
+        assert!(!report.push(Check::fail("server", "refused").hint("start it")));
+        assert!(!report.push(Check::skip("auth", "server unreachable")));
+        assert!(!report.ok());
+
+        let text = report.render();
+        assert!(text.contains("FAIL  server"), "{text}");
+        assert!(text.contains("→ start it"), "{text}");
+        assert!(text.ends_with("1 failed, 1 warning(s)\n"), "{text}");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][2]["status"], "fail");
+        assert!(json["checks"][0].get("hint").is_none());
+    }
+
+    #[test]
+    fn unimplemented_and_undecodable_are_schema_mismatches() {
+        assert!(is_schema_mismatch(&RpcError::Capnp(
+            capnp::Error::unimplemented("method not implemented".to_string())
+        )));
+        assert!(is_schema_mismatch(&RpcError::NotInSchema(
+            capnp::NotInSchema(9)
+        )));
+        assert!(!is_schema_mismatch(&RpcError::Capnp(capnp::Error::failed(
+            "boom".to_string()
+        ))));
+        assert!(!is_schema_mismatch(&RpcError::NotConnected));
+    }
+
+    #[tokio::test]
+    async fn refused_port_fails_reachability_with_a_hint() {
+        // Bind then drop: the port is free, so the connect is refused.
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let check = check_reachable("127.0.0.1", port).await;
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.hint.unwrap().contains("kaijutsu-server"));
+    }
+
+    #[tokio::test]
+    async fn mcp_stdio_round_trip_lists_tools() {
+        let check = check_mcp_stdio().await;
+        assert_eq!(check.status, Status::Pass, "{check:?}");
+        assert!(check.detail.contains("tools"), "{}", check.detail);
+    }
+}
@@ -0,0 +1,571 @@
+//! Event hooks: local commands run when blocks in the joined context change.
+//!
+//! The hook socket carries the agent's events *into* the kernel; event hooks
+//! go the other way. A JSON file (`--event-hooks`, default
+//! `~/.config/kaijutsu/event-hooks.json`) registers commands, each with a
+//! trigger and optional block filters:
+//!
+//! ```json
+//! {"hooks": [{
+//!   "name": "fmt",
+//!   "on": "status", "status": "done", "kind": "file",
+//!   "command": ["sh", "-c", "rustfmt --edition 2024 --emit stdout"],
+//!   "debounce_ms": 500, "timeout_secs": 10
+//! }]}
+//! ```
+//!
+//! A matching event arms the hook for that block and every further event
+//! restarts the wait, so a streaming block runs it once, `debounce_ms` after
+//! it goes quiet. Filters are checked when the hook fires, against the block
+//! as it is then. The command gets the block's content on stdin and `KJ_*`
+//! variables naming it, and is killed after `timeout_secs`. Its output is
+//! attached under the block as a child — a `Trace` block by default (the
+//! operator sees it, the model doesn't), a `Text` one with `"attach": "text"`,
+//! or nothing with `"none"`. A failed or timed-out run attaches as an Error
+//! block; a clean run with no output attaches nothing.
+//!
+//! Remote mode only: the events are the server's block stream for the
+//! context `register_session` joined, and results are authored through the
+//! doc task. Hooks never fire on the blocks they attached.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use kaijutsu_client::ServerEvent;
+use kaijutsu_crdt::{BlockId, BlockKind, BlockSnapshot, ContextId, Role, Status};
+use kaijutsu_types::AgentProvenance;
+
+use crate::RemoteState;
+use crate::doc_task::AuthoredBlock;
+use crate::hook_listener::truncate;
+
+/// Quiet time after the last matching event before a hook runs.
+pub const DEFAULT_DEBOUNCE_MS: u64 = 250;
+
+/// How long a hook command may run before it is killed.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Output kept from one run; the rest is cut.
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// The block event a hook runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trigger {
+    /// A block was added.
+    Inserted,
+    /// A block's text changed.
+    Edited,
+    /// A block's status changed (narrow it with `status`).
+    Status,
+}
+
+impl Trigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Trigger::Inserted => "inserted",
+            Trigger::Edited => "edited",
+            Trigger::Status => "status",
+        }
+    }
+}
+
+/// Where a run's output goes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Attach {
+    /// A `Trace` child — visible to the operator, never to the model.
+    #[default]
+    Trace,
+    /// A system `Text` child the model reads too.
+    Text,
+    /// Discarded.
+    None,
+}
+
+/// One registered command.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventHook {
+    pub name: String,
+    pub on: Trigger,
+    pub kind: Option<BlockKind>,
+    pub role: Option<Role>,
+    pub status: Option<Status>,
+    pub tool_name: Option<String>,
+    /// Program and arguments; no shell unless you name one.
+    pub command: Vec<String>,
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default)]
+    pub attach: Attach,
+}
+
+fn default_debounce_ms() -> u64 {
+    DEFAULT_DEBOUNCE_MS
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+impl EventHook {
+    /// Whether `block`, as it is now, passes this hook's filters.
+    pub fn matches(&self, block: &BlockSnapshot) -> bool {
+        self.kind.is_none_or(|k| k == block.kind)
+            && self.role.is_none_or(|r| r == block.role)
+            && self.status.is_none_or(|s| s == block.status)
+            && self
+                .tool_name
+                .as_deref()
+                .is_none_or(|name| block.tool_name.as_deref() == Some(name))
+    }
+}
+
+/// The event hooks file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventHookConfig {
+    #[serde(default)]
+    pub hooks: Vec<EventHook>,
+}
+
+impl EventHookConfig {
+    /// Read and check the hooks file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("in {}", path.display()))
+    }
+
+    /// Parse a hooks file, rejecting nameless hooks, duplicate names, and
+    /// empty commands.
+    pub fn parse(text: &str) -> Result<Self> {
+        let config: Self = serde_json::from_str(text)?;
+        let mut names = HashSet::new();
+        for hook in &config.hooks {
+            if hook.name.is_empty() {
+                bail!("an event hook has no name");
+            }
+            if !names.insert(hook.name.as_str()) {
+                bail!("event hook `{}` is defined twice", hook.name);
+            }
+            if hook
+                .command
+                .first()
+                .is_none_or(|program| program.is_empty())
+            {
+                bail!("event hook `{}` has no command", hook.name);
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Default hooks file: `~/.config/kaijutsu/event-hooks.json` (XDG
+/// `config_dir`).
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("kaijutsu").join("event-hooks.json"))
+}
+
+/// The block a server event is about, and the trigger it counts as.
+fn event_trigger(event: &ServerEvent) -> Option<(ContextId, BlockId, Trigger)> {
+    match event {
+        ServerEvent::BlockInserted {
+            context_id, block, ..
+        } => Some((*context_id, block.id, Trigger::Inserted)),
+        ServerEvent::BlockTextOps {
+            context_id,
+            block_id,
+            ..
+        } => Some((*context_id, *block_id, Trigger::Edited)),
+        ServerEvent::BlockStatusChanged {
+            context_id,
+            block_id,
+            ..
+        } => Some((*context_id, *block_id, Trigger::Status)),
+        _ => None,
+    }
+}
+
+/// Armed (hook, block) pairs and when each is due. Re-arming restarts the
+/// wait.
+#[derive(Debug, Default)]
+struct Debouncer {
+    pending: HashMap<(usize, BlockId), (Instant, Trigger)>,
+}
+
+impl Debouncer {
+    fn arm(&mut self, hook: usize, block: BlockId, trigger: Trigger, due: Instant) {
+        self.pending.insert((hook, block), (due, trigger));
+    }
+
+    fn next_due(&self) -> Option<Instant> {
+        self.pending.values().map(|(due, _)| *due).min()
+    }
+
+    /// Remove and return everything due by `now`.
+    fn take_due(&mut self, now: Instant) -> Vec<(usize, BlockId, Trigger)> {
+        let mut due = Vec::new();
+        self.pending.retain(|&(hook, block), &mut (at, trigger)| {
+            if at <= now {
+                due.push((hook, block, trigger));
+                false
+            } else {
+                true
+            }
+        });
+        due
+    }
+}
+
+/// What one run of a hook command produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookRun {
+    /// stdout, then stderr, cut at 64 KiB.
+    pub output: String,
+    /// `None` when killed by a signal or the timeout.
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+}
+
+impl HookRun {
+    pub fn failed(&self) -> bool {
+        self.timed_out || self.exit_code != Some(0)
+    }
+
+    /// The child block's content, or `None` for a clean, silent run.
+    fn report(&self, hook: &EventHook) -> Option<String> {
+        if self.timed_out {
+            return Some(format!(
+                "event hook `{}` timed out after {}s",
+                hook.name, hook.timeout_secs
+            ));
+        }
+        if self.failed() {
+            let status = match self.exit_code {
+                Some(code) => format!("exited {code}"),
+                None => "was killed".to_string(),
+            };
+            return Some(format!(
+                "event hook `{}` {status}\n{}",
+                hook.name, self.output
+            ));
+        }
+        (!self.output.trim().is_empty()).then(|| self.output.clone())
+    }
+}
+
+/// Run `hook`'s command for `block`: content on stdin, `KJ_*` variables
+/// naming the hook, event, and block.
+pub async fn run_hook(
+    hook: &EventHook,
+    block: &BlockSnapshot,
+    trigger: Trigger,
+) -> std::io::Result<HookRun> {
+    let Some((program, args)) = hook.command.split_first() else {
+        return Err(std::io::Error::other("empty command"));
+    };
+    let mut command = tokio::process::Command::new(program);
+    command
+        .args(args)
+        .env("KJ_HOOK", &hook.name)
+        .env("KJ_EVENT", trigger.as_str())
+        .env("KJ_BLOCK_ID", block.id.to_key())
+        .env("KJ_CONTEXT_ID", block.id.context_id.to_hex())
+        .env("KJ_BLOCK_KIND", block.kind.as_str())
+        .env("KJ_BLOCK_ROLE", block.role.as_str())
+        .env("KJ_BLOCK_STATUS", block.status.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(ref name) = block.tool_name {
+        command.env("KJ_TOOL_NAME", name);
+    }
+    let mut child = command.spawn()?;
+    // Fed from its own task: a command that writes before it has read all
+    // of stdin would otherwise deadlock against us.
+    if let Some(mut stdin) = child.stdin.take() {
+        let content = block.content.clone();
+        tokio::spawn(async move {
+            let _ = stdin.write_all(content.as_bytes()).await;
+        });
+    }
+    let timeout = Duration::from_secs(hook.timeout_secs);
+    // On timeout the future (and with it the child) is dropped, which kills it.
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => {
+            let output = output?;
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            if !output.stderr.is_empty() {
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&String::from_utf8_lossy(&output.stderr));
+            }
+            Ok(HookRun {
+                output: truncate(&text, MAX_OUTPUT_BYTES),
+                exit_code: output.status.code(),
+                timed_out: false,
+            })
+        }
+        Err(_) => Ok(HookRun {
+            output: String::new(),
+            exit_code: None,
+            timed_out: true,
+        }),
+    }
+}
+
+/// Watch the joined context's block events and run `config`'s hooks on
+/// them until the actor's event stream closes. Results are stamped with
+/// `provenance`.
+pub fn spawn_event_hooks(
+    remote: RemoteState,
+    config: EventHookConfig,
+    provenance: Option<AgentProvenance>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let hooks: Arc<[EventHook]> = config.hooks.into();
+        // Blocks the hooks attached — never triggers themselves.
+        let attached: Arc<Mutex<HashSet<BlockId>>> = Arc::default();
+        let mut events = remote.actor.subscribe_events();
+        let mut debouncer = Debouncer::default();
+        loop {
+            let next_due = debouncer.next_due();
+            tokio::select! {
+                ev = events.recv() => match ev {
+                    Ok(event) => {
+                        let Some((context_id, block_id, trigger)) = event_trigger(&event) else {
+                            continue;
+                        };
+                        if *remote.shared_context_id.lock() != Some(context_id) {
+                            continue;
+                        }
+                        let now = Instant::now();
+                        for (i, hook) in hooks.iter().enumerate() {
+                            if hook.on == trigger {
+                                let due = now + Duration::from_millis(hook.debounce_ms);
+                                debouncer.arm(i, block_id, trigger, due);
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!("event hooks: missed {n} block events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = sleep_until(next_due) => {
+                    for (i, block_id, trigger) in debouncer.take_due(Instant::now()) {
+                        fire(&remote, &hooks, i, block_id, trigger, &attached, &provenance);
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Sleep until `due`, or forever when nothing is armed.
+async fn sleep_until(due: Option<Instant>) {
+    match due {
+        Some(due) => tokio::time::sleep_until(due).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Run hook `i` for `block_id` in the background if the block still passes
+/// its filters, attaching the result.
+fn fire(
+    remote: &RemoteState,
+    hooks: &Arc<[EventHook]>,
+    i: usize,
+    block_id: BlockId,
+    trigger: Trigger,
+    attached: &Arc<Mutex<HashSet<BlockId>>>,
+    provenance: &Option<AgentProvenance>,
+) {
+    if attached.lock().contains(&block_id) {
+        return;
+    }
+    let Some(block) = remote
+        .synced
+        .lock()
+        .as_ref()
+        .and_then(|doc| doc.get_block(&block_id))
+    else {
+        return;
+    };
+    let hook = &hooks[i];
+    if !hook.matches(&block) {
+        return;
+    }
+    let remote = remote.clone();
+    let hooks = Arc::clone(hooks);
+    let attached = Arc::clone(attached);
+    let provenance = provenance.clone();
+    tokio::spawn(async move {
+        let hook = &hooks[i];
+        let run = match run_hook(hook, &block, trigger).await {
+            Ok(run) => run,
+            Err(e) => HookRun {
+                output: format!("could not start `{}`: {e}", hook.command[0]),
+                exit_code: None,
+                timed_out: false,
+            },
+        };
+        tracing::debug!(
+            hook = %hook.name,
+            block = %block_id.to_key(),
+            exit_code = ?run.exit_code,
+            timed_out = run.timed_out,
+            "event hook ran"
+        );
+        let (kind, role) = match hook.attach {
+            Attach::None => return,
+            Attach::Trace => (BlockKind::Trace, Role::System),
+            Attach::Text => (BlockKind::Text, Role::System),
+        };
+        let Some(content) = run.report(hook) else {
+            return;
+        };
+        let Some(handle) = remote.doc_task.lock().clone() else {
+            return;
+        };
+        let child = AuthoredBlock::Child {
+            parent: block_id,
+            kind,
+            role,
+            content,
+            is_error: run.failed(),
+        };
+        match handle.author_blocks(vec![child], provenance).await {
+            Ok(ids) => attached.lock().extend(ids),
+            Err(e) => tracing::warn!(hook = %hook.name, "event hook result not attached: {e}"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use kaijutsu_crdt::{BlockSnapshotBuilder, PrincipalId};
+
+    fn hook(json: &str) -> EventHook {
+        EventHookConfig::parse(&format!(r#"{{"hooks": [{json}]}}"#))
+            .unwrap()
+            .hooks
+            .remove(0)
+    }
+
+    fn block(kind: BlockKind, status: Status, content: &str) -> BlockSnapshot {
+        let id = BlockId::new(ContextId::new(), PrincipalId::new(), 1);
+        BlockSnapshotBuilder::new(id, kind)
+            .role(Role::Model)
+            .status(status)
+            .content(content)
+            .build()
+    }
+
+    #[test]
+    fn config_defaults_and_validation() {
+        let h = hook(r#"{"name": "fmt", "on": "status", "command": ["fmt"]}"#);
+        assert_eq!(h.on, Trigger::Status);
+        assert_eq!(h.debounce_ms, DEFAULT_DEBOUNCE_MS);
+        assert_eq!(h.timeout_secs, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(h.attach, Attach::Trace);
+
+        for bad in [
+            r#"{"hooks": [{"name": "x", "on": "status", "command": []}]}"#,
+            r#"{"hooks": [{"name": "", "on": "status", "command": ["fmt"]}]}"#,
+            r#"{"hooks": [{"name": "x", "on": "status", "command": ["a"]},
+                          {"name": "x", "on": "edited", "command": ["b"]}]}"#,
+            r#"{"hooks": [{"name": "x", "on": "status", "command": ["a"], "kinds": "text"}]}"#,
+        ] {
+            assert!(EventHookConfig::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn filters_match_the_current_block() {
+        let h = hook(
+            r#"{"name": "fmt", "on": "status", "kind": "tool_call", "status": "done",
+                "command": ["fmt"]}"#,
+        );
+        assert!(h.matches(&block(BlockKind::ToolCall, Status::Done, "")));
+        assert!(!h.matches(&block(BlockKind::ToolCall, Status::Running, "")));
+        assert!(!h.matches(&block(BlockKind::Text, Status::Done, "")));
+    }
+
+    #[test]
+    fn rearming_restarts_the_wait() {
+        let mut debouncer = Debouncer::default();
+        let block_id = BlockId::new(ContextId::new(), PrincipalId::new(), 1);
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+        debouncer.arm(0, block_id, Trigger::Edited, at(100));
+        debouncer.arm(0, block_id, Trigger::Edited, at(300));
+        assert!(debouncer.take_due(at(200)).is_empty());
+        assert_eq!(debouncer.next_due(), Some(at(300)));
+        let due = debouncer.take_due(at(300));
+        assert_eq!(due, vec![(0, block_id, Trigger::Edited)]);
+        assert_eq!(debouncer.next_due(), None);
+    }
+
+    #[tokio::test]
+    async fn runs_with_content_on_stdin_and_reports_failures() {
+        let b = block(BlockKind::Text, Status::Done, "hello");
+        let h = hook(
+            r#"{"name": "up", "on": "status",
+                "command": ["sh", "-c", "tr a-z A-Z; echo \" $KJ_HOOK $KJ_BLOCK_KIND\""]}"#,
+        );
+        let run = run_hook(&h, &b, Trigger::Status).await.unwrap();
+        assert_eq!(run.output, "HELLO up text\n");
+        assert!(!run.failed());
+        assert_eq!(run.report(&h).as_deref(), Some("HELLO up text\n"));
+
+        let h = hook(r#"{"name": "quiet", "on": "status", "command": ["true"]}"#);
+        let run = run_hook(&h, &b, Trigger::Status).await.unwrap();
+        assert_eq!(run.report(&h), None, "a clean, silent run attaches nothing");
+
+        let h = hook(
+            r#"{"name": "lint", "on": "status", "command": ["sh", "-c", "echo bad >&2; exit 3"]}"#,
+        );
+        let run = run_hook(&h, &b, Trigger::Status).await.unwrap();
+        assert_eq!(run.exit_code, Some(3));
+        assert_eq!(
+            run.report(&h).as_deref(),
+            Some("event hook `lint` exited 3\nbad\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn a_slow_command_is_killed_at_the_timeout() {
+        let b = block(BlockKind::Text, Status::Done, "");
+        let h = hook(
+            r#"{"name": "slow", "on": "status", "timeout_secs": 1, "command": ["sleep", "30"]}"#,
+        );
+        let started = std::time::Instant::now();
+        let run = run_hook(&h, &b, Trigger::Status).await.unwrap();
+        assert!(run.timed_out);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(
+            run.report(&h).as_deref(),
+            Some("event hook `slow` timed out after 1s")
+        );
+    }
+}
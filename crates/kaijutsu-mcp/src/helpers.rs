@@ -2,12 +2,104 @@
 //!
 //! Most of the parsing/formatting helpers were retired with the
 //! MCP slim-down (block_*, doc_*, kernel_search moved to `kj`).
-//! Block resolution now lives on `KaijutsuMcp` (`locate_block`/`read_block`),
-//! which is backend-agnostic; what stays here is the key parser they use.
+//! Block resolution now lives on `KaijutsuMcp` (`resolve_block_id`,
+//! `locate_block`/`read_block`), which is backend-agnostic; what stays here is
+//! the file-change rendering behind the `summarize_for_pr` prompt.
 
-use kaijutsu_crdt::BlockId;
+/// Longest run of lines shown per side of a rendered file change.
+const DIFF_MAX_LINES: usize = 80;
 
-/// Parse block ID from key string.
-pub fn parse_block_id(s: &str) -> Option<BlockId> {
-    BlockId::from_key(s)
+/// Render a file-mutating tool call (`edit`/`write`, under any server prefix)
+/// as a diff-style snippet. `None` for any other tool or unparseable input.
+///
+/// This is reconstructed from the call's arguments, not from the file, so an
+/// `edit` shows only the replaced span (diffed line by line, unchanged lines as
+/// context) and a `write` shows the whole new body.
+pub fn file_change_diff(tool_name: &str, tool_input: &str) -> Option<String> {
+    let op = tool_name
+        .rsplit(['.', ':', '/'])
+        .next()
+        .unwrap_or(tool_name)
+        .rsplit("__")
+        .next()
+        .unwrap_or(tool_name);
+    let input: serde_json::Value = serde_json::from_str(tool_input).ok()?;
+    let path = input.get("path")?.as_str()?;
+    let mut out = format!("--- a/{path}\n+++ b/{path}\n");
+    match op {
+        "edit" => {
+            let new = input.get("new_string")?.as_str()?;
+            if let Some(anchor) = input.get("anchor").and_then(|a| a.as_str()) {
+                out.push_str(&format!("@@ {anchor} @@\n"));
+            } else {
+                out.push_str("@@\n");
+                let old = input.get("old_string")?.as_str()?;
+                let diff = kaijutsu_types::diff::LineDiff::new(old, new);
+                let total = diff.lines.len();
+                for line in diff.lines.iter().take(DIFF_MAX_LINES) {
+                    out.push(line.tag.marker());
+                    out.push_str(line.text);
+                    out.push('\n');
+                }
+                if total > DIFF_MAX_LINES {
+                    out.push_str(&format!("… ({} more lines)\n", total - DIFF_MAX_LINES));
+                }
+                return Some(out);
+            }
+            push_lines(&mut out, '+', new);
+        }
+        "write" => {
+            out.push_str("@@ (whole file) @@\n");
+            push_lines(&mut out, '+', input.get("content")?.as_str()?);
+        }
+        _ => return None,
+    }
+    Some(out)
+}
+
+fn push_lines(out: &mut String, sign: char, text: &str) {
+    let total = text.lines().count();
+    for line in text.lines().take(DIFF_MAX_LINES) {
+        out.push(sign);
+        out.push_str(line);
+        out.push('\n');
+    }
+    if total > DIFF_MAX_LINES {
+        out.push_str(&format!("{sign}… ({} more lines)\n", total - DIFF_MAX_LINES));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_string_edits_and_writes() {
+        let diff = file_change_diff(
+            "builtin.file.edit",
+            r#"{"path":"src/a.rs","old_string":"let x = 1;","new_string":"let x = 2;"}"#,
+        )
+        .unwrap();
+        assert_eq!(diff, "--- a/src/a.rs\n+++ b/src/a.rs\n@@\n-let x = 1;\n+let x = 2;\n");
+
+        let diff = file_change_diff("write", r#"{"path":"b.txt","content":"one\ntwo"}"#).unwrap();
+        assert!(diff.ends_with("@@ (whole file) @@\n+one\n+two\n"), "{diff}");
+    }
+
+    #[test]
+    fn multi_line_edits_keep_unchanged_lines_as_context() {
+        let diff = file_change_diff(
+            "edit",
+            r#"{"path":"a.rs","old_string":"fn a() {\n    1\n}","new_string":"fn a() {\n    2\n}"}"#,
+        )
+        .unwrap();
+        assert!(diff.ends_with("@@\n fn a() {\n-    1\n+    2\n }\n"), "{diff}");
+    }
+
+    #[test]
+    fn ignores_other_tools_and_bad_input() {
+        assert!(file_change_diff("read", r#"{"path":"a"}"#).is_none());
+        assert!(file_change_diff("edit", "not json").is_none());
+        assert!(file_change_diff("edit", r#"{"new_string":"x"}"#).is_none());
+    }
 }
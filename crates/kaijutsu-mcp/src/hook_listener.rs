@@ -8,21 +8,40 @@
 //! 1. Creates CRDT blocks in the shared store
 //! 2. Pushes ops to the server (if remote)
 //! 3. Checks for pending drift and injects it into the response
+//!
+//! `tool.before` is first checked against the kernel's hook policy
+//! (`/etc/config/hooks.toml`, see `kaijutsu_kernel::hook_policy`): a `deny`
+//! comes back as the response and the call is never mirrored; an `ask` is
+//! passed through for the adapter to defer to the agent's permission prompt.
+//!
+//! Tool events mirror live: `tool.before` opens a Running ToolCall block and
+//! the matching `tool.after`/`tool.error` (same session, same `tool_use_id`)
+//! attaches the result and completes it, so the context shows what the agent
+//! is doing while it does it.
 
+use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::time::Duration;
 
+use parking_lot::Mutex;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixListener;
 
-use kaijutsu_crdt::{BlockKind, ContentType, ContextId, PrincipalId, Role, Status, ToolKind};
+use kaijutsu_crdt::{
+    BlockId, BlockKind, ContentType, ContextId, PrincipalId, Role, Status, ToolKind,
+};
 use kaijutsu_kernel::SharedBlockStore;
+use kaijutsu_kernel::config_doc::{config_context_id, read_content};
+use kaijutsu_kernel::hook_policy::{
+    HOOK_POLICY_CONFIG_FILE, HookPolicy, PolicyDecision, PolicyVerdict,
+};
+use kaijutsu_types::{AgentProvenance, ConsentMode};
 
 use crate::RemoteState;
 use crate::doc_task::AuthoredBlock;
 use crate::hook_types::{
-    HookEvent, HookResponse, KAIJUTSU_MCP_TOOLS, PingResponse, normalize_tool_name,
+    HookEvent, HookResponse, KAIJUTSU_MCP_TOOLS, PingResponse, ToolInfo, normalize_tool_name,
     short_session_suffix,
 };
 
@@ -39,6 +58,38 @@ const SWEEP_CONNECT_TIMEOUT: Duration = Duration::from_millis(200);
 /// Maximum size of a block's content created from hook events.
 const DEFAULT_MAX_BLOCK_SIZE: usize = 4096;
 
+/// Most tool calls kept open waiting for their result. A tool that never runs
+/// (denied, interrupted) sends no `tool.after`; past this the oldest open call
+/// is forgotten rather than the list growing without bound.
+const MAX_OPEN_TOOL_CALLS: usize = 64;
+
+/// Result text for a tool call still open when its turn or session ends.
+const UNFINISHED_TOOL_RESULT: &str = "(no result — the tool was denied or interrupted)";
+
+/// A ToolCall block opened by `tool.before`, waiting for its result.
+struct OpenToolCall {
+    session_id: Option<String>,
+    tool_use_id: Option<String>,
+    name: String,
+    input: serde_json::Value,
+    call_id: BlockId,
+}
+
+impl OpenToolCall {
+    /// Whether `tool`, reported for `session_id`, is this call's result.
+    /// `tool_use_id` decides when both sides carry one; otherwise the call
+    /// pairs on tool name + input (first open match wins).
+    fn matches(&self, session_id: Option<&str>, tool: &ToolInfo) -> bool {
+        if self.session_id.as_deref() != session_id {
+            return false;
+        }
+        match (&self.tool_use_id, &tool.tool_use_id) {
+            (Some(open), Some(reported)) => open == reported,
+            _ => self.name == tool.name && self.input == tool.input,
+        }
+    }
+}
+
 /// Hook listener — receives events over a Unix socket and writes CRDT blocks.
 pub struct HookListener {
     /// Local-mode block store (in-process). `None` in remote mode, where blocks
@@ -64,6 +115,12 @@ pub struct HookListener {
     /// Guards `set_context_model` (from `session.start`'s `model` field) to
     /// at most one call per process.
     context_model_set: Mutex<bool>,
+    /// ToolCall blocks opened by `tool.before`, oldest first.
+    open_tool_calls: Mutex<VecDeque<OpenToolCall>>,
+    /// The hosting agent, from startup detection — stamped on every block
+    /// this listener mirrors. Its session id is left to `session_id`, which
+    /// follows the hook events.
+    agent: Option<AgentProvenance>,
 }
 
 impl HookListener {
@@ -72,7 +129,7 @@ impl HookListener {
         if let Some(id) = self.local_context_id {
             return Some(id);
         }
-        self.shared_context_id.lock().ok().and_then(|g| *g)
+        *self.shared_context_id.lock()
     }
 
     /// Create a listener backed by a local-only store.
@@ -86,6 +143,8 @@ impl HookListener {
             session_id: Arc::new(Mutex::new(None)),
             pending_label_rename: Mutex::new(None),
             context_model_set: Mutex::new(false),
+            open_tool_calls: Mutex::new(VecDeque::new()),
+            agent: None,
         }
     }
 
@@ -113,7 +172,28 @@ impl HookListener {
             session_id,
             pending_label_rename: Mutex::new(pending_label_rename),
             context_model_set: Mutex::new(false),
+            open_tool_calls: Mutex::new(VecDeque::new()),
+            agent: None,
+        }
+    }
+
+    /// Stamp mirrored blocks with the hosting agent's provenance. Remote mode
+    /// only: local mode's store lives and dies with this one process, so
+    /// there is no other agent to tell apart.
+    pub fn with_agent(mut self, agent: AgentProvenance) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    /// Provenance for a block authored now: the detected agent plus the
+    /// session id the hook events last reported. Startup detection's own
+    /// session id can name a previous session, so it is only a fallback.
+    fn provenance(&self) -> Option<AgentProvenance> {
+        let mut provenance = self.agent.clone()?;
+        if let Some(session_id) = self.session_id.lock().clone() {
+            provenance.session_id = Some(session_id);
         }
+        Some(provenance)
     }
 
     /// Start listening on a Unix socket. Runs until the socket is closed or
@@ -175,7 +255,7 @@ impl HookListener {
         // Handle ping — return status without creating blocks
         if event.event == "ping" {
             let pending = self.pending_drift_count().await;
-            let session_id = self.session_id.lock().ok().and_then(|g| g.clone());
+            let session_id = self.session_id.lock().clone();
             let ping = PingResponse {
                 status: "ok".to_string(),
                 pid: std::process::id(),
@@ -199,9 +279,8 @@ impl HookListener {
         // starts a new session id on the same process — in both cases the
         // stored id must follow the event or ping-based socket resolution
         // matches the wrong session forever. Other events only fill a void.
-        if let Some(ref event_session_id) = event.session_id
-            && let Ok(mut guard) = self.session_id.lock()
-        {
+        if let Some(ref event_session_id) = event.session_id {
+            let mut guard = self.session_id.lock();
             let stale = guard.as_deref().is_some_and(|cur| cur != event_session_id.as_str());
             if guard.is_none() || (event.event == "session.start" && stale) {
                 tracing::info!(
@@ -249,6 +328,7 @@ impl HookListener {
         }
 
         let mut author_error: Option<String> = None;
+        let mut ask_reason: Option<String> = None;
 
         // 2. Create blocks based on event type
         match event.event.as_str() {
@@ -271,15 +351,8 @@ impl HookListener {
                     && let Some(ctx_id) = self.context_id()
                 {
                     if let Some(model) = event.model.as_deref() {
-                        // Recover a poisoned lock (rather than `.ok()`-skip
-                        // like `session_id` below) — this flag exists to
-                        // stop a *duplicate* RPC, so losing track of "already
-                        // called" under poisoning is the wrong failure mode.
-                        let already_set = {
-                            let mut guard =
-                                self.context_model_set.lock().unwrap_or_else(|e| e.into_inner());
-                            std::mem::replace(&mut *guard, true)
-                        };
+                        let already_set =
+                            std::mem::replace(&mut *self.context_model_set.lock(), true);
                         if !already_set {
                             // Only adapter today is claude-code (docs/hooks.md) — its
                             // models are always served by the "anthropic" provider.
@@ -294,14 +367,7 @@ impl HookListener {
                     }
 
                     if let Some(session_id) = event.session_id.as_deref() {
-                        // Recover a poisoned lock like `context_model_set`
-                        // above: skipping here would leave the context's
-                        // auto-generated label permanently suffix-less.
-                        let pending = self
-                            .pending_label_rename
-                            .lock()
-                            .unwrap_or_else(|e| e.into_inner())
-                            .take();
+                        let pending = self.pending_label_rename.lock().take();
                         if let Some(label) = pending {
                             let suffix = short_session_suffix(session_id);
                             let renamed = format!("{label}-{suffix}");
@@ -320,6 +386,9 @@ impl HookListener {
             }
 
             "session.end" => {
+                if let Err(e) = self.close_open_tool_calls(event.session_id.as_deref()).await {
+                    author_error.get_or_insert(e);
+                }
                 let content = match event.reason.as_deref() {
                     Some(reason) => format!("Session ended: {reason}"),
                     None => "Session ended".to_string(),
@@ -338,23 +407,45 @@ impl HookListener {
                 }
             }
 
-            "tool.after" => {
-                if let Some(ref tool) = event.tool
-                    && let Err(e) = self.insert_tool_blocks(tool, false).await
-                {
-                    author_error.get_or_insert(e);
+            "tool.before" => {
+                if let Some(ref tool) = event.tool {
+                    let verdict = self.evaluate_policy(tool).await;
+                    let reason = verdict.reason.unwrap_or_default();
+                    match verdict.decision {
+                        // A denied call never runs, so it opens no block —
+                        // the refusal is recorded instead. Returns before the
+                        // drift check: queued drift waits for a call that runs.
+                        PolicyDecision::Deny => {
+                            let note = format!("Hook policy denied {}: {reason}", tool.name);
+                            let _ = self.insert_text_block(Role::System, &note).await;
+                            return HookResponse::deny(reason);
+                        }
+                        PolicyDecision::Ask => ask_reason = Some(reason),
+                        PolicyDecision::Allow => {}
+                    }
+                    if let Err(e) = self.open_tool_call(tool, event.session_id.as_deref()).await {
+                        author_error.get_or_insert(e);
+                    }
                 }
             }
 
-            "tool.error" => {
+            "tool.after" | "tool.error" => {
+                let is_error = event.event == "tool.error";
                 if let Some(ref tool) = event.tool
-                    && let Err(e) = self.insert_tool_blocks(tool, true).await
+                    && let Err(e) = self
+                        .complete_tool_call(tool, event.session_id.as_deref(), is_error)
+                        .await
                 {
                     author_error.get_or_insert(e);
                 }
             }
 
             "agent.stop" => {
+                // Every tool of the turn has reported by now; a call still
+                // open never ran.
+                if let Err(e) = self.close_open_tool_calls(event.session_id.as_deref()).await {
+                    author_error.get_or_insert(e);
+                }
                 // Claude Code's Stop hook payload carries no response text —
                 // only a transcript path. Fall back to the last assistant
                 // message in the JSONL transcript when `response` is absent.
@@ -432,25 +523,96 @@ impl HookListener {
                 }
             }
 
-            // tool.before — no block
             _ => {}
         }
 
         // 3. Check for pending drift, then fold in any authoring failure —
         // LOUD (the caller already `tracing::error!`'d it) and visible
         // however the hook reply protocol permits: the `context` field,
-        // alongside any real drift.
-        let response = self.maybe_inject_drift().await;
-        match author_error {
-            Some(err) => {
-                let note = format!("[kaijutsu-mcp mirror error] {err}");
-                let context = match response.context {
-                    Some(existing) => format!("{existing}\n\n{note}"),
-                    None => note,
-                };
-                HookResponse::allow_with_context(context)
+        // alongside any real drift. A policy `ask` rides the same response.
+        let mut response = self.maybe_inject_drift().await;
+        if let Some(reason) = ask_reason {
+            response = HookResponse {
+                context: response.context,
+                ..HookResponse::ask(reason)
+            };
+        }
+        if let Some(err) = author_error {
+            let note = format!("[kaijutsu-mcp mirror error] {err}");
+            response.context = Some(match response.context {
+                Some(existing) => format!("{existing}\n\n{note}"),
+                None => note,
+            });
+        }
+        response
+    }
+
+    // -- Hook policy --
+
+    /// Decide a `tool.before` against the kernel's `hooks.toml`. Fails open
+    /// like the rest of the hook path: no policy (an older kernel, a fetch
+    /// error) or an unparseable one allows.
+    async fn evaluate_policy(&self, tool: &ToolInfo) -> PolicyVerdict {
+        let Some(policy) = self.load_policy().await else {
+            return PolicyVerdict {
+                decision: PolicyDecision::Allow,
+                reason: None,
+            };
+        };
+        let consent = if policy.uses_consent() {
+            self.consent_mode().await
+        } else {
+            None
+        };
+        let verdict = policy.evaluate(&tool.name, &tool.input, consent);
+        if verdict.decision != PolicyDecision::Allow {
+            tracing::info!(
+                tool = %tool.name,
+                decision = verdict.decision.as_str(),
+                reason = ?verdict.reason,
+                "Hook policy decision"
+            );
+        }
+        verdict
+    }
+
+    /// The current `hooks.toml`, re-read per event so an edit applies to the
+    /// next tool call. Local mode reads the in-process store; remote mode asks
+    /// the kernel.
+    async fn load_policy(&self) -> Option<HookPolicy> {
+        let content = if let Some(store) = &self.local_store {
+            let path = kaijutsu_types::paths::config_path(HOOK_POLICY_CONFIG_FILE);
+            read_content(store, config_context_id(&path))?
+        } else {
+            let remote = self.remote.as_ref()?;
+            match remote.actor.get_config(HOOK_POLICY_CONFIG_FILE.to_string()).await {
+                Ok(content) => content,
+                Err(e) => {
+                    tracing::debug!("Hook policy: {HOOK_POLICY_CONFIG_FILE} unavailable: {e}");
+                    return None;
+                }
+            }
+        };
+        match HookPolicy::parse(&content) {
+            Ok(policy) => Some(policy),
+            Err(e) => {
+                tracing::warn!("{HOOK_POLICY_CONFIG_FILE}: {e}; hook policy off until it is fixed");
+                None
+            }
+        }
+    }
+
+    /// The joined context's consent mode (remote only — a local store has no
+    /// context rows).
+    async fn consent_mode(&self) -> Option<ConsentMode> {
+        let remote = self.remote.as_ref()?;
+        let ctx_id = self.context_id()?;
+        match remote.actor.get_context_stats(ctx_id).await {
+            Ok(stats) => stats.consent_mode?.parse().ok(),
+            Err(e) => {
+                tracing::debug!("Hook policy: context stats unavailable: {e}");
+                None
             }
-            None => response,
         }
     }
 
@@ -494,8 +656,8 @@ impl HookListener {
             return Ok(());
         };
         let block = AuthoredBlock::Text { role, content: content.to_string() };
-        match handle.author_blocks(vec![block]).await {
-            Ok(()) => Ok(()),
+        match handle.author_blocks(vec![block], self.provenance()).await {
+            Ok(_) => Ok(()),
             Err(e) => {
                 tracing::error!("Hook insert_text_block: AuthorBlocks failed — mirror desynced: {e}");
                 Err(format!("failed to mirror text block: {e}"))
@@ -503,11 +665,9 @@ impl HookListener {
         }
     }
 
-    async fn insert_tool_blocks(
-        &self,
-        tool: &crate::hook_types::ToolInfo,
-        is_error: bool,
-    ) -> Result<(), String> {
+    /// Author a completed call + result pair in one go — a `tool.after` with
+    /// no open call to attach to.
+    async fn insert_tool_blocks(&self, tool: &ToolInfo, is_error: bool) -> Result<(), String> {
         let Some(ctx_id) = self.context_id() else {
             tracing::debug!(
                 "Hook insert_tool_blocks: no context yet (register_session not called)"
@@ -515,12 +675,7 @@ impl HookListener {
             return Ok(());
         };
         let input = tool.input.clone();
-        let content = if is_error {
-            tool.error.as_deref().unwrap_or("(error)")
-        } else {
-            tool.output.as_deref().unwrap_or("(no output)")
-        };
-        let truncated = truncate(content, self.max_block_size);
+        let truncated = self.result_text(tool, is_error);
 
         if let Some(store) = &self.local_store {
             // Insert tool call block
@@ -532,7 +687,7 @@ impl HookListener {
                 input,
                 Some(ToolKind::Mcp),
                 Some(PrincipalId::system()),
-                None,
+                tool.tool_use_id.clone(),
                 None,
             ) {
                 Ok(id) => id,
@@ -550,7 +705,7 @@ impl HookListener {
                 None,
                 Some(ToolKind::Mcp),
                 Some(PrincipalId::system()),
-                None,
+                tool.tool_use_id.clone(),
             ) {
                 tracing::warn!("Hook insert_tool_result error: {e}");
             }
@@ -580,8 +735,8 @@ impl HookListener {
             is_error,
             tool_kind: Some(ToolKind::Mcp),
         };
-        match handle.author_blocks(vec![block]).await {
-            Ok(()) => Ok(()),
+        match handle.author_blocks(vec![block], self.provenance()).await {
+            Ok(_) => Ok(()),
             Err(e) => {
                 tracing::error!("Hook insert_tool_blocks: AuthorBlocks failed — mirror desynced: {e}");
                 Err(format!("failed to mirror tool call/result: {e}"))
@@ -589,6 +744,181 @@ impl HookListener {
         }
     }
 
+    /// `tool.before`: open a Running ToolCall block and remember it until its
+    /// result arrives.
+    async fn open_tool_call(
+        &self,
+        tool: &ToolInfo,
+        session_id: Option<&str>,
+    ) -> Result<(), String> {
+        let Some(ctx_id) = self.context_id() else {
+            tracing::debug!("Hook open_tool_call: no context yet (register_session not called)");
+            return Ok(());
+        };
+        let call_id = if let Some(store) = &self.local_store {
+            match store.insert_tool_call_as(
+                ctx_id,
+                None,
+                None,
+                &tool.name,
+                tool.input.clone(),
+                Some(ToolKind::Mcp),
+                Some(PrincipalId::system()),
+                tool.tool_use_id.clone(),
+                None,
+            ) {
+                Ok(id) => id,
+                Err(e) => {
+                    tracing::warn!("Hook insert_tool_call error: {e}");
+                    return Ok(());
+                }
+            }
+        } else {
+            let Some(handle) = self.doc_task_handle() else {
+                tracing::debug!("Hook open_tool_call: doc task not ready yet");
+                return Ok(());
+            };
+            let block = AuthoredBlock::ToolCall {
+                tool_name: tool.name.clone(),
+                tool_input: tool.input.clone(),
+                tool_kind: Some(ToolKind::Mcp),
+            };
+            match handle.author_blocks(vec![block], self.provenance()).await {
+                Ok(ids) => match ids.first() {
+                    Some(id) => *id,
+                    None => return Ok(()),
+                },
+                Err(e) => {
+                    tracing::error!("Hook open_tool_call: AuthorBlocks failed — mirror desynced: {e}");
+                    return Err(format!("failed to mirror tool call: {e}"));
+                }
+            }
+        };
+
+        let mut open = self.open_tool_calls.lock();
+        if open.len() >= MAX_OPEN_TOOL_CALLS
+            && let Some(dropped) = open.pop_front()
+        {
+            tracing::warn!(tool = %dropped.name, "Hook tool call never completed; forgetting it");
+        }
+        open.push_back(OpenToolCall {
+            session_id: session_id.map(String::from),
+            tool_use_id: tool.tool_use_id.clone(),
+            name: tool.name.clone(),
+            input: tool.input.clone(),
+            call_id,
+        });
+        Ok(())
+    }
+
+    /// `tool.after`/`tool.error`: attach the result to the call `tool.before`
+    /// opened. Falls back to authoring the pair when there is none — no
+    /// `tool.before` hook installed, or the call was opened in a context this
+    /// listener has since left.
+    async fn complete_tool_call(
+        &self,
+        tool: &ToolInfo,
+        session_id: Option<&str>,
+        is_error: bool,
+    ) -> Result<(), String> {
+        let open = {
+            let mut open = self.open_tool_calls.lock();
+            open.iter()
+                .position(|c| c.matches(session_id, tool))
+                .and_then(|i| open.remove(i))
+        };
+        match (open, self.context_id()) {
+            (Some(call), Some(ctx_id)) if call.call_id.context_id == ctx_id => {
+                let content = self.result_text(tool, is_error);
+                self.insert_tool_result(ctx_id, &call, &content, is_error).await
+            }
+            _ => self.insert_tool_blocks(tool, is_error).await,
+        }
+    }
+
+    /// Close `session_id`'s still-open calls with an error result, so a
+    /// denied or interrupted tool doesn't show as running forever.
+    async fn close_open_tool_calls(&self, session_id: Option<&str>) -> Result<(), String> {
+        let closing: Vec<OpenToolCall> = {
+            let mut open = self.open_tool_calls.lock();
+            let (closing, keep): (Vec<_>, VecDeque<_>) = open
+                .drain(..)
+                .partition(|c| c.session_id.as_deref() == session_id);
+            *open = keep;
+            closing
+        };
+        let Some(ctx_id) = self.context_id() else {
+            return Ok(());
+        };
+        let mut first_error = None;
+        for call in closing.iter().filter(|c| c.call_id.context_id == ctx_id) {
+            if let Err(e) = self
+                .insert_tool_result(ctx_id, call, UNFINISHED_TOOL_RESULT, true)
+                .await
+            {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Attach a result to an open call and complete it (Done/Error).
+    async fn insert_tool_result(
+        &self,
+        ctx_id: ContextId,
+        call: &OpenToolCall,
+        content: &str,
+        is_error: bool,
+    ) -> Result<(), String> {
+        if let Some(store) = &self.local_store {
+            if let Err(e) = store.insert_tool_result_as(
+                ctx_id,
+                &call.call_id,
+                None,
+                content,
+                is_error,
+                None,
+                Some(ToolKind::Mcp),
+                Some(PrincipalId::system()),
+                call.tool_use_id.clone(),
+            ) {
+                tracing::warn!("Hook insert_tool_result error: {e}");
+            }
+            let final_status = if is_error { Status::Error } else { Status::Done };
+            if let Err(e) = store.set_status(ctx_id, &call.call_id, final_status) {
+                tracing::warn!("Hook set_status (tool call) error: {e}");
+            }
+            return Ok(());
+        }
+        let Some(handle) = self.doc_task_handle() else {
+            tracing::debug!("Hook insert_tool_result: doc task not ready yet");
+            return Ok(());
+        };
+        let block = AuthoredBlock::ToolResult {
+            call_id: call.call_id,
+            content: content.to_string(),
+            is_error,
+            tool_kind: Some(ToolKind::Mcp),
+        };
+        match handle.author_blocks(vec![block], self.provenance()).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::error!("Hook insert_tool_result: AuthorBlocks failed — mirror desynced: {e}");
+                Err(format!("failed to mirror tool result: {e}"))
+            }
+        }
+    }
+
+    /// The tool's output (or error), truncated to the block size cap.
+    fn result_text(&self, tool: &ToolInfo, is_error: bool) -> String {
+        let content = if is_error {
+            tool.error.as_deref().unwrap_or("(error)")
+        } else {
+            tool.output.as_deref().unwrap_or("(no output)")
+        };
+        truncate(content, self.max_block_size)
+    }
+
     /// The doc task's command-channel handle, if `register_session` has
     /// spawned one yet (remote mode only — `None` in local mode or before
     /// registration completes).
@@ -597,7 +927,6 @@ impl HookListener {
             .as_ref()?
             .doc_task
             .lock()
-            .unwrap_or_else(|e| e.into_inner())
             .clone()
     }
 
@@ -661,7 +990,7 @@ impl HookListener {
 }
 
 /// Truncate a string to `max_len` bytes at a char boundary.
-fn truncate(s: &str, max_len: usize) -> String {
+pub(crate) fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
     } else {
@@ -755,7 +1084,7 @@ pub fn candidate_sockets(explicit: Option<PathBuf>) -> Vec<PathBuf> {
 ///
 /// `None` on any failure (missing socket, connect/IO error, malformed JSON)
 /// — resolution treats that candidate as dead, never as a match.
-async fn ping_socket(path: &Path) -> Option<PingResponse> {
+pub(crate) async fn ping_socket(path: &Path) -> Option<PingResponse> {
     let response = send_hook_event(path, r#"{"event":"ping","source":"kaijutsu-mcp-hook"}"#)
         .await
         .ok()
@@ -938,7 +1267,6 @@ mod tests {
     use kaijutsu_types::DocKind;
 
     use super::*;
-    use crate::hook_types::ToolInfo;
 
     /// A fresh, empty temp directory for this test, never reused across
     /// tests or runs.
@@ -997,6 +1325,7 @@ mod tests {
             output: Some("total 0".to_string()),
             error: None,
             duration_ms: Some(12),
+            tool_use_id: None,
         });
 
         listener.process_event(&event).await;
@@ -1023,6 +1352,7 @@ mod tests {
             output: None,
             error: Some("exit 1".to_string()),
             duration_ms: Some(3),
+            tool_use_id: None,
         });
 
         listener.process_event(&event).await;
@@ -1035,6 +1365,155 @@ mod tests {
         assert_eq!(call.status, Status::Error);
     }
 
+    fn bash_tool(command: &str, tool_use_id: Option<&str>) -> ToolInfo {
+        ToolInfo {
+            name: "Bash".to_string(),
+            input: serde_json::json!({ "command": command }),
+            output: None,
+            error: None,
+            duration_ms: None,
+            tool_use_id: tool_use_id.map(String::from),
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_before_opens_a_running_call_that_tool_after_completes() {
+        let (listener, store, ctx_id) = local_listener_with_context();
+        let mut before = empty_hook_event("tool.before");
+        before.session_id = Some("s1".to_string());
+        before.tool = Some(bash_tool("cargo build", Some("toolu_1")));
+        listener.process_event(&before).await;
+
+        let snapshots = store.block_snapshots(ctx_id).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].kind, BlockKind::ToolCall);
+        assert_eq!(snapshots[0].status, Status::Running);
+
+        // Same tool_use_id, different input: the id decides the pairing.
+        let mut after = empty_hook_event("tool.after");
+        after.session_id = Some("s1".to_string());
+        let mut tool = bash_tool("cargo build --release", Some("toolu_1"));
+        tool.output = Some("Finished".to_string());
+        after.tool = Some(tool);
+        listener.process_event(&after).await;
+
+        let snapshots = store.block_snapshots(ctx_id).unwrap();
+        let calls: Vec<_> = snapshots.iter().filter(|b| b.kind == BlockKind::ToolCall).collect();
+        assert_eq!(calls.len(), 1, "the result must attach to the open call");
+        assert_eq!(calls[0].status, Status::Done);
+        let result = snapshots
+            .iter()
+            .find(|b| b.kind == BlockKind::ToolResult)
+            .expect("tool result inserted");
+        assert_eq!(result.tool_call_id, Some(calls[0].id));
+        assert_eq!(result.content, "Finished");
+        assert!(listener.open_tool_calls.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn tool_after_from_another_session_does_not_complete_the_call() {
+        let (listener, store, ctx_id) = local_listener_with_context();
+        let mut before = empty_hook_event("tool.before");
+        before.session_id = Some("s1".to_string());
+        before.tool = Some(bash_tool("ls", None));
+        listener.process_event(&before).await;
+
+        let mut after = empty_hook_event("tool.after");
+        after.session_id = Some("s2".to_string());
+        after.tool = Some(bash_tool("ls", None));
+        listener.process_event(&after).await;
+
+        let snapshots = store.block_snapshots(ctx_id).unwrap();
+        let running = snapshots
+            .iter()
+            .filter(|b| b.kind == BlockKind::ToolCall && b.status == Status::Running)
+            .count();
+        assert_eq!(running, 1, "s1's call stays open; s2 gets its own pair");
+        assert_eq!(listener.open_tool_calls.lock().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn agent_stop_closes_calls_that_never_reported() {
+        let (listener, store, ctx_id) = local_listener_with_context();
+        let mut before = empty_hook_event("tool.before");
+        before.tool = Some(bash_tool("rm -rf target", Some("toolu_denied")));
+        listener.process_event(&before).await;
+
+        let mut stop = empty_hook_event("agent.stop");
+        stop.response = Some("done".to_string());
+        listener.process_event(&stop).await;
+
+        let snapshots = store.block_snapshots(ctx_id).unwrap();
+        let call = snapshots
+            .iter()
+            .find(|b| b.kind == BlockKind::ToolCall)
+            .expect("tool call block inserted");
+        assert_eq!(call.status, Status::Error);
+        assert!(
+            snapshots
+                .iter()
+                .any(|b| b.kind == BlockKind::ToolResult && b.content == UNFINISHED_TOOL_RESULT)
+        );
+        assert!(listener.open_tool_calls.lock().is_empty());
+    }
+
+    /// Write `/etc/config/hooks.toml` into the listener's local store.
+    fn seed_hook_policy(store: &SharedBlockStore, policy: &str) {
+        let fs = kaijutsu_kernel::runtime::config_crdt_fs::ConfigCrdtFs::new(
+            store.clone(),
+            kaijutsu_types::paths::CONFIG_ROOT,
+        );
+        let path = kaijutsu_types::paths::config_path(HOOK_POLICY_CONFIG_FILE);
+        assert_eq!(fs.seed_entries(vec![(path, policy)]).unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn hook_policy_denies_and_asks_before_the_call_opens() {
+        let (listener, store, ctx_id) = local_listener_with_context();
+        seed_hook_policy(
+            &store,
+            r#"
+            [[rules]]
+            tools = ["Bash"]
+            commands = ['rm\s+-rf']
+            decision = "deny"
+            reason = "no recursive deletes"
+
+            [[rules]]
+            paths = ["**/Cargo.lock"]
+            decision = "ask"
+            "#,
+        );
+
+        let mut denied = empty_hook_event("tool.before");
+        denied.tool = Some(bash_tool("rm -rf /", None));
+        let response = listener.process_event(&denied).await;
+        assert!(response.is_deny());
+        assert_eq!(response.reason.as_deref(), Some("no recursive deletes"));
+        let snapshots = store.block_snapshots(ctx_id).unwrap();
+        assert!(snapshots.iter().all(|b| b.kind != BlockKind::ToolCall));
+        assert!(snapshots.iter().any(|b| b.content.contains("no recursive deletes")));
+        assert!(listener.open_tool_calls.lock().is_empty());
+
+        let mut asked = empty_hook_event("tool.before");
+        asked.tool = Some(ToolInfo {
+            name: "Edit".to_string(),
+            input: serde_json::json!({ "file_path": "/repo/Cargo.lock" }),
+            output: None,
+            error: None,
+            duration_ms: None,
+            tool_use_id: None,
+        });
+        let response = listener.process_event(&asked).await;
+        assert_eq!(response.block, "ask");
+        assert_eq!(listener.open_tool_calls.lock().len(), 1, "an asked call may still run");
+
+        let mut allowed = empty_hook_event("tool.before");
+        allowed.tool = Some(bash_tool("ls", None));
+        let response = listener.process_event(&allowed).await;
+        assert_eq!(response.block, "allow");
+    }
+
     // -- item 9: agent.compact --
 
     #[tokio::test]
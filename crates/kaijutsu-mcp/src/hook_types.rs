@@ -76,6 +76,10 @@ pub struct ToolInfo {
     /// Execution duration in milliseconds.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub duration_ms: Option<u64>,
+    /// Source tool's id for this invocation. Pairs a `tool.before` with its
+    /// `tool.after`/`tool.error`; without it they pair on name + input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
 }
 
 /// File modification details.
@@ -108,9 +112,10 @@ pub struct HookResponse {
     /// Drift context to inject into the agent's next turn.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub context: Option<String>,
-    /// `"allow"` or `"deny"` — maps to exit code 0 or 2.
+    /// `"allow"`, `"deny"`, or `"ask"`. Deny maps to exit code 2; ask exits 0
+    /// and the adapter defers to the agent's own permission prompt.
     pub block: String,
-    /// Explanation for deny decisions.
+    /// Explanation for deny and ask decisions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
 }
@@ -143,6 +148,15 @@ impl HookResponse {
         }
     }
 
+    /// Defer the action to the agent's own permission prompt, with a reason.
+    pub fn ask(reason: impl Into<String>) -> Self {
+        Self {
+            context: None,
+            block: "ask".to_string(),
+            reason: Some(reason.into()),
+        }
+    }
+
     /// Whether this response denies the action.
     pub fn is_deny(&self) -> bool {
         self.block == "deny"
@@ -185,6 +199,7 @@ pub const KAIJUTSU_MCP_TOOLS: &[&str] = &[
     "doc_tree",
     "kaish_exec",
     "shell",
+    "shell_rerun",
     "kernel_search",
     "list_kernel_tools",
     "whoami",
@@ -198,6 +213,32 @@ pub const KAIJUTSU_MCP_TOOLS: &[&str] = &[
     "submit_input",
     "register_session",
     "invoke_peer",
+    "context_info",
+    "contexts_close",
+    "context_reopen",
+    "context_recover",
+    "sync_quarantine",
+    "budget_status",
+    "llm_params_set",
+    "context_preview",
+    "broadcast_prompt",
+    "broadcast_status",
+    "digest_subscribe",
+    "doc_peek",
+    "bookmark_create",
+    "bookmark_list",
+    "context_timeline",
+    "block_reorder",
+    "block_tail",
+    "block_permalink",
+    "dag_query",
+    "test_results",
+    "mcp_server_register",
+    "mcp_server_unregister",
+    "mcp_server_list",
+    "inbox_list",
+    "preference_set",
+    "consent_log",
 ];
 
 /// Strip a leading `mcp__<server>__` prefix from a hook-reported tool name.
@@ -11,15 +11,28 @@
 //!
 //! ## Module Structure
 //!
+//! - `autosave`: Local in-memory store → Markdown + oplog export on shutdown
+//! - `doctor`: `kaijutsu-mcp doctor` connectivity and capability checks
+//! - `event_hooks`: Local commands run on the joined context's block events
 //! - `models`: Request and response types for MCP tools
 //! - `helpers`: Parsing and utility functions
+//! - `log_bridge`: Kernel log notifications → MCP `notifications/message`
+//! - `result_guard`: Oversized tool results → preview + `kaijutsu://results/{id}`
 //! - `tree`: DAG visualization as ASCII tree
 
+pub mod autosave;
 pub mod doc_task;
+pub mod doctor;
+pub mod event_hooks;
 mod helpers;
 pub mod hook_listener;
 pub mod hook_types;
+mod list_changed;
+mod log_bridge;
 mod models;
+pub mod resource_cache;
+pub mod result_guard;
+pub mod schema;
 mod tree;
 
 use regex::Regex;
@@ -36,11 +49,16 @@ impl Drop for AbortOnDrop {
 use rmcp::{
     ErrorData as McpError, RoleServer, ServerHandler,
     handler::server::{
-        router::prompt::PromptRouter, router::tool::ToolRouter, wrapper::Parameters,
+        router::prompt::PromptRouter, router::tool::ToolRouter, tool::ToolCallContext,
+        wrapper::Parameters,
     },
     model::{
         // Resource types
         AnnotateAble,
+        // Tool types
+        CallToolRequestParams,
+        CallToolResult,
+        ListToolsResult,
         // Cancellation types
         CancelledNotificationParam,
         // Completion types
@@ -51,15 +69,17 @@ use rmcp::{
         GetPromptRequestParams,
         GetPromptResult,
         ListPromptsResult,
+        ListResourceTemplatesResult,
         ListResourcesResult,
         LoggingLevel,
         PaginatedRequestParams,
         PromptMessage,
         PromptMessageRole,
         RawResource,
+        RawResourceTemplate,
         ReadResourceRequestParams,
         ReadResourceResult,
-        ResourceContents,
+        ResourceTemplate,
         // Server types
         ServerCapabilities,
         ServerInfo,
@@ -70,19 +90,29 @@ use rmcp::{
     },
     prompt, prompt_handler, prompt_router,
     schemars::JsonSchema,
-    service::{NotificationContext, RequestContext},
-    tool, tool_handler, tool_router,
+    service::{NotificationContext, Peer, RequestContext},
+    tool, tool_router,
 };
 
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
-use kaijutsu_client::{ActorHandle, SshConfig, SyncedDocument, connect_ssh, spawn_actor};
-use kaijutsu_crdt::{BlockId, ContextId, ConversationDAG, PrincipalId};
+use kaijutsu_client::{
+    ActorHandle, CallError, SshConfig, SyncedDocument, connect_ssh, spawn_actor,
+};
+use kaijutsu_crdt::{
+    BlockFilter, BlockId, BlockKind, ContextId, ConversationDAG, DagQuery, DagSelection,
+    PrincipalId,
+};
 use kaijutsu_kernel::{SharedBlockStore, shared_block_store};
 use tokio::sync::watch;
 
 use doc_task::{DocTaskHandle, ResyncReason, spawn_doc_task, spawn_event_bridge};
+use list_changed::spawn_list_changed_bridge;
+use log_bridge::spawn_log_bridge;
+use resource_cache::ResourceCache;
+use result_guard::ResultGuard;
 
 // Re-export public types
 use helpers::*;
@@ -123,6 +153,24 @@ pub struct EditingAssistantArgs {
     pub edit_type: Option<String>,
 }
 
+/// Arguments for the PR summary prompt
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[schemars(description = "PR summary parameters")]
+pub struct SummarizeForPrArgs {
+    #[schemars(description = "Document (context) ID holding the conversation")]
+    pub document_id: String,
+    #[schemars(
+        description = "First block of the slice, inclusive (default: start of the document). Ignored with subtree"
+    )]
+    pub from_block: Option<String>,
+    #[schemars(
+        description = "Last block of the slice, inclusive (default: end of the document). Ignored with subtree"
+    )]
+    pub to_block: Option<String>,
+    #[schemars(description = "Root block of a DAG subtree to summarize instead of a range")]
+    pub subtree: Option<String>,
+}
+
 // ============================================================================
 // Backend Abstraction
 // ============================================================================
@@ -254,11 +302,12 @@ pub struct RemoteState {
     /// Send+Sync actor handle for RPC operations
     pub actor: ActorHandle,
     /// The single joined context's synced CRDT document — `None` until
-    /// `register_session`. Owns the `SyncManager`. `parking_lot::Mutex`: the
-    /// guard never poisons (a panic under lock can't cascade-kill every later
-    /// `lock()`) and `lock()` is a cheap non-async critical section — held only
-    /// for fast doc reads/applies, never across an `.await`.
-    pub synced: Arc<parking_lot::Mutex<Option<SyncedDocument>>>,
+    /// `register_session`. Only the doc task (`doc_task.rs`) mutates it; every
+    /// other path reads. `parking_lot::Mutex` (as for all shared state in this
+    /// crate): the guard never poisons (a panic under lock can't cascade-kill
+    /// every later `lock()`) and `lock()` is a cheap non-async critical
+    /// section — held only for fast doc reads/applies, never across an `.await`.
+    pub synced: Arc<Mutex<Option<SyncedDocument>>>,
     /// Wake signal: a monotonic generation counter the background listener bumps
     /// (`send_modify`) after each applied event. Waiters (the shell completion
     /// poll) `subscribe()` and `await changed()`. Unlike a bare `Notify`, the
@@ -291,6 +340,12 @@ pub struct JoinedContext {
     /// stall fallback still needs to be alive and processing `Resync`
     /// commands.
     _doc_task: Arc<AbortOnDrop>,
+    /// Abort handle for the log bridge (kernel `Log` notifications → MCP
+    /// `notifications/message`).
+    _log_task: Arc<AbortOnDrop>,
+    /// Abort handle for the list-changed bridge (context switches and
+    /// reconnects → MCP `notifications/resources/list_changed`).
+    _list_changed_task: Arc<AbortOnDrop>,
 }
 
 impl JoinedContext {
@@ -321,6 +376,12 @@ pub struct McpServerState {
     pub log_level: Arc<Mutex<LoggingLevel>>,
     /// Resource subscriptions (URI -> subscription active)
     pub subscriptions: Arc<Mutex<std::collections::HashSet<String>>>,
+    /// The connected MCP client, captured on `initialized`. Server-initiated
+    /// notifications (kernel logs) go out through it.
+    pub peer: Arc<Mutex<Option<Peer<RoleServer>>>>,
+    /// Documents the client's last `resources/list` covered; `None` until it
+    /// lists, and again once told the list changed (see `list_changed`).
+    pub listed_docs: Arc<Mutex<Option<std::collections::BTreeSet<ContextId>>>>,
 }
 
 impl Default for McpServerState {
@@ -328,6 +389,8 @@ impl Default for McpServerState {
         Self {
             log_level: Arc::new(Mutex::new(LoggingLevel::Info)),
             subscriptions: Arc::new(Mutex::new(std::collections::HashSet::new())),
+            peer: Arc::new(Mutex::new(None)),
+            listed_docs: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -338,11 +401,15 @@ pub struct KaijutsuMcp {
     backend: Backend,
     tool_router: ToolRouter<Self>,
     /// Backing router for the served prompts (analyze_document, search_context,
-    /// editing_assistant). Wired into rmcp via `#[prompt_handler]`; the field
+    /// editing_assistant, summarize_for_pr). Wired into rmcp via `#[prompt_handler]`; the field
     /// itself isn't read directly, hence the allow.
     #[allow(dead_code)]
     prompt_router: PromptRouter<Self>,
     server_state: McpServerState,
+    /// Truncates oversized tool results; keeps the originals for paging.
+    result_guard: ResultGuard,
+    /// Rendered document-backed resources, keyed by URI + version.
+    resource_cache: ResourceCache,
     /// Handle to abort the background event listener when all clones are dropped.
     _bg_task: Option<Arc<AbortOnDrop>>,
     /// Agent session ID (e.g., Claude Code session UUID).
@@ -378,6 +445,8 @@ impl KaijutsuMcp {
             tool_router: Self::tool_router(),
             prompt_router: Self::prompt_router(),
             server_state: McpServerState::default(),
+            result_guard: ResultGuard::default(),
+            resource_cache: ResourceCache::default(),
             _bg_task: None,
             session_id: Arc::new(Mutex::new(None)),
             context_name: "local".to_string(),
@@ -392,6 +461,57 @@ impl KaijutsuMcp {
         Self::with_store(shared_block_store(principal))
     }
 
+    /// Create a local MCP server whose store is journaled to a SQLite file.
+    ///
+    /// Every mutating block op appends to the oplog before it returns, so a
+    /// tool call is only acknowledged once its edit is in the journal; `sync`
+    /// sets how hard that write is pushed to disk. Existing documents are
+    /// replayed at startup. A document whose oplog won't replay is left out
+    /// (and its oplog kept intact) unless `repair` is set, in which case the
+    /// oplog is cut at the bad entry and the rest is loaded.
+    pub fn persistent(
+        path: &std::path::Path,
+        sync: kaijutsu_kernel::SyncPolicy,
+        repair: bool,
+    ) -> Result<Self, anyhow::Error> {
+        let db = kaijutsu_kernel::KernelDb::open(path)?;
+        db.set_sync_policy(sync)?;
+        let problems = db.quick_check()?;
+        if !problems.is_empty() {
+            anyhow::bail!(
+                "journal {} failed integrity check: {}",
+                path.display(),
+                problems.join("; ")
+            );
+        }
+        // One stable principal so every run reuses the same default workspace.
+        let principal = PrincipalId::system();
+        let workspace = db.get_or_create_default_workspace(principal)?;
+        let store = kaijutsu_kernel::shared_block_store_with_db(
+            Arc::new(Mutex::new(db)),
+            workspace,
+            principal,
+        );
+        store.load_from_db()?;
+        if repair {
+            for r in store.repair_from_db()? {
+                tracing::warn!(
+                    document = %r.context_id.short(),
+                    from_seq = r.from_seq,
+                    dropped = r.dropped,
+                    "Repaired journal: dropped ops that would not replay"
+                );
+            }
+        }
+        tracing::info!(
+            path = %path.display(),
+            %sync,
+            documents = store.len(),
+            "Replayed journal"
+        );
+        Ok(Self::with_store(store))
+    }
+
     /// Connect to a running kaijutsu-server via SSH.
     ///
     /// Uses ssh-agent for authentication. Must be called within a `LocalSet`.
@@ -462,7 +582,7 @@ impl KaijutsuMcp {
                 actor,
                 // SyncedDocument is built once the context is known, in
                 // register_session. `change` wakes the shell poll on each apply.
-                synced: Arc::new(parking_lot::Mutex::new(None)),
+                synced: Arc::new(Mutex::new(None)),
                 change: watch::channel(0u64).0,
                 joined: Arc::new(tokio::sync::RwLock::new(None)),
                 shared_context_id,
@@ -471,6 +591,8 @@ impl KaijutsuMcp {
             tool_router: Self::tool_router(),
             prompt_router: Self::prompt_router(),
             server_state: McpServerState::default(),
+            result_guard: ResultGuard::default(),
+            resource_cache: ResourceCache::default(),
             _bg_task: None,
             session_id: Arc::new(Mutex::new(cc_session_id.map(String::from))),
             context_name: context_name.to_string(),
@@ -479,6 +601,13 @@ impl KaijutsuMcp {
         })
     }
 
+    /// Set the tool result size limit in bytes (0 = unlimited). Larger
+    /// results come back as a preview plus a `kaijutsu://results/{id}` URI.
+    pub fn with_max_result_bytes(mut self, max_bytes: usize) -> Self {
+        self.result_guard = ResultGuard::new(max_bytes);
+        self
+    }
+
     /// Get the backend variant (for hook listener setup, etc.).
     pub fn backend(&self) -> &Backend {
         &self.backend
@@ -545,10 +674,39 @@ impl KaijutsuMcp {
         self.with_doc(ctx, |doc| doc.get_block_snapshot(id)).flatten()
     }
 
+    /// Resolve a block reference: a full key, a block label, or an
+    /// abbreviation naming exactly one resident block — `ctx@principal#seq`
+    /// with short ids, `principal#seq`, a key prefix (see
+    /// `kaijutsu_types::resolve_block_ref`). An ambiguous reference errors
+    /// with its candidates.
+    fn resolve_block_id(&self, s: &str) -> Result<BlockId, String> {
+        if let Some(full) = BlockId::from_key(s) {
+            return Ok(full);
+        }
+        let contexts = self.context_ids();
+        let labels: Vec<(String, BlockId)> = contexts
+            .iter()
+            .flat_map(|ctx| {
+                self.with_doc(*ctx, |doc| doc.label_index())
+                    .unwrap_or_default()
+            })
+            .collect();
+        let candidates: Vec<BlockId> = contexts
+            .into_iter()
+            .filter(|ctx| BlockId::query_may_match_context(s, *ctx))
+            .flat_map(|ctx| {
+                self.with_doc(ctx, |doc| doc.block_ids_ordered())
+                    .unwrap_or_default()
+            })
+            .collect();
+        kaijutsu_types::resolve_block_ref(candidates, labels, s)
+            .map_err(|e| format!("invalid block ID: {e}"))
+    }
+
     /// Resolve a block-id string to `(ContextId, BlockId)` if it's resident,
     /// regardless of backend. Replaces the free `find_block(store, ..)` helper.
     fn locate_block(&self, block_id_str: &str) -> Option<(ContextId, BlockId)> {
-        let block_id = parse_block_id(block_id_str)?;
+        let block_id = self.resolve_block_id(block_id_str).ok()?;
         let ctx = block_id.context_id;
         self.read_block(ctx, &block_id).map(|_| (ctx, block_id))
     }
@@ -605,11 +763,14 @@ impl KaijutsuMcp {
     /// If `query` is Some, resolves via label/hex prefix lookup (Remote) or
     /// direct parse (Local). If None, falls back to the current joined
     /// context (Remote) or errors (Local).
+    ///
+    /// The resolved context is stamped on the calling `mcp.*` span (with the
+    /// process identity) so tool spans filter by context like RPC spans do.
     async fn resolve_input_context(
         &self,
         query: Option<&str>,
     ) -> Result<kaijutsu_crdt::ContextId, String> {
-        match (&self.backend, query) {
+        let resolved = match (&self.backend, query) {
             // Explicit context provided — resolve it
             (Backend::Remote(remote), Some(q)) => self.resolve_context(&remote.actor, q).await,
             (Backend::Local(_), Some(q)) => {
@@ -628,7 +789,53 @@ impl KaijutsuMcp {
             (Backend::Local(_), None) => {
                 Err("Error: context_id is required in local mode".to_string())
             }
+        };
+        if let Ok(ctx_id) = &resolved {
+            kaijutsu_telemetry::TraceIdentity::current()
+                .with_context(*ctx_id)
+                .record(&tracing::Span::current());
+        }
+        resolved
+    }
+
+    /// Context statistics as JSON, shared by the `context_info` tool and the
+    /// `kaijutsu://context/{short_id}` resource.
+    async fn context_info_json(
+        &self,
+        ctx_id: kaijutsu_crdt::ContextId,
+    ) -> Result<serde_json::Value, String> {
+        let stats = match self.actor() {
+            Some(actor) => actor
+                .get_context_stats(ctx_id)
+                .await
+                .map_err(|e| format!("Error getting context stats: {e}"))?,
+            // Local mode has no KernelDb or drift router — tally what the
+            // store holds and leave the metadata half empty.
+            None => {
+                let mut stats = self
+                    .with_doc(ctx_id, |doc| {
+                        kaijutsu_types::ContextStats::tally(&doc.blocks_ordered())
+                    })
+                    .ok_or_else(|| format!("Error: context {} not found", ctx_id.short()))?;
+                stats.context_id = Some(ctx_id);
+                stats
+            }
+        };
+
+        let mut json =
+            serde_json::to_value(&stats).map_err(|e| format!("Error serializing: {e}"))?;
+        json["context_short"] = serde_json::Value::String(ctx_id.short());
+        // Generation params live in the KernelDb — remote only. `{}` means
+        // provider defaults.
+        if let Some(actor) = self.actor() {
+            let params = actor
+                .get_llm_params(ctx_id)
+                .await
+                .map_err(|e| format!("Error getting LLM params: {e}"))?;
+            json["llm_params"] =
+                serde_json::to_value(&params).map_err(|e| format!("Error serializing: {e}"))?;
         }
+        Ok(json)
     }
 
     /// Shared polling loop for shell command completion.
@@ -762,7 +969,7 @@ impl KaijutsuMcp {
             // exactly as if it had arrived locally — no separate fetch/decode
             // path to keep in sync with Phase 2 below. `change` bumps as
             // part of this uniformly with every other doc-task mutation.
-            let doc_task = remote.doc_task.lock().ok().and_then(|g| g.clone());
+            let doc_task = remote.doc_task.lock().clone();
             match doc_task {
                 Some(handle) => {
                     if let Err(e) = handle.resync(ResyncReason::StallFallback).await {
@@ -883,7 +1090,7 @@ impl KaijutsuMcp {
                     format!("Tool error: {}", result.output)
                 }
             }
-            Err(e) => format!("Error: {e}"),
+            Err(e) => call_error_text("kaish_exec", &e),
         }
     }
 
@@ -914,7 +1121,7 @@ impl KaijutsuMcp {
                 serde_json::to_string_pretty(&tools)
                     .unwrap_or_else(|e| format!("Error serializing: {e}"))
             }
-            Err(e) => format!("Error: {e}"),
+            Err(e) => call_error_text("list_kernel_tools", &e),
         }
     }
 
@@ -937,7 +1144,7 @@ impl KaijutsuMcp {
         // when execution completes.
         let cmd_block_id = match actor.shell_execute(&req.command, ctx_id, false).await {
             Ok(id) => id,
-            Err(e) => return format!("Error starting command: {e}"),
+            Err(e) => return call_error_text("starting command", &e),
         };
 
         tracing::info!(
@@ -960,6 +1167,69 @@ impl KaijutsuMcp {
         .to_json()
     }
 
+    #[tool(
+        description = "Re-run a shell command block from your current context exactly as it ran: same command line, working directory, and env overrides (recorded on the block), whatever the context's cwd/env are now. Creates a new command block linked to the original (shell_command.rerun_of) and returns the same JSON object as `shell`. Requires --connect and register_session.",
+        annotations(open_world_hint = true)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.shell_rerun")]
+    pub async fn shell_rerun(&self, Parameters(req): Parameters<ShellRerunRequest>) -> String {
+        let (ctx_id, actor) = match self.require_joined().await {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
+        let remote = match self.remote() {
+            Some(r) => r,
+            None => return "Error: shell_rerun requires --connect to server".to_string(),
+        };
+        let block_id = match self.resolve_block_id(&req.block_id) {
+            Ok(id) => id,
+            Err(e) => return format!("Error: {e}"),
+        };
+        if block_id.context_id != ctx_id {
+            return format!(
+                "Error: block {} is not in your current context",
+                block_id.to_key()
+            );
+        }
+        let block = match self.read_block(ctx_id, &block_id) {
+            Some(block) => block,
+            None => return format!("Error: block {} not found", block_id.to_key()),
+        };
+        if !block.is_shell() || block.kind != kaijutsu_crdt::BlockKind::ToolCall {
+            return format!("Error: block {} is not a shell command", block_id.to_key());
+        }
+        let command = block
+            .tool_input
+            .as_deref()
+            .and_then(|input| serde_json::from_str::<serde_json::Value>(input).ok())
+            .and_then(|input| input.get("code")?.as_str().map(str::to_owned))
+            .unwrap_or_default();
+        let cmd_block_id = match actor.shell_rerun(&block_id, false).await {
+            Ok(id) => id,
+            Err(e) => return call_error_text("re-running command", &e),
+        };
+
+        tracing::info!(
+            command = %command,
+            rerun_of = %block_id.to_key(),
+            cmd_block = %cmd_block_id.to_key(),
+            ctx = %ctx_id,
+            "Shell command re-run dispatched"
+        );
+
+        let timeout_secs = req.timeout_secs.unwrap_or(300).min(600);
+        self.execute_and_poll_shell(
+            remote,
+            ctx_id,
+            cmd_block_id,
+            &command,
+            timeout_secs,
+            "Shell command",
+        )
+        .await
+        .to_json()
+    }
+
     // ========================================================================
     // Session Registration
     // ========================================================================
@@ -1016,34 +1286,54 @@ impl KaijutsuMcp {
         }
 
         // Generate label
-        let label = req.label.unwrap_or_else(|| {
-            let session = self.session_id.lock().ok().and_then(|g| g.clone());
+        let generated = req.label.is_none();
+        let mut label = req.label.unwrap_or_else(|| {
+            let session = self.session_id.lock().clone();
             session.unwrap_or_else(|| format!("mcp-{}", &ContextId::new().short()))
         });
 
-        // 1. Create context on the server. MCP-attached contexts default to
-        // the "mcp" mode bundle so their rc lifecycle + tool policy runs.
-        let context_type = req.context_type.unwrap_or_else(|| "mcp".to_string());
-        let context_id = match remote
-            .actor
-            .create_context_typed(&label, &context_type)
-            .await
+        // 1. Reserve the name — unique per kernel, near-duplicates included —
+        // then create the context under it. A taken generated name falls to
+        // the server's first suggestion; a taken chosen one is the caller's
+        // call. MCP-attached contexts default to the "mcp" mode bundle so
+        // their rc lifecycle + tool policy runs.
+        let mut reservation = match remote.actor.reserve_context_name(&label).await {
+            Ok(r) => r,
+            Err(e) => return call_error_text("reserving context name", &e),
+        };
+        if !reservation.is_reserved()
+            && generated
+            && let Some(alternative) = reservation.suggestions.first()
         {
+            label = alternative.clone();
+            reservation = match remote.actor.reserve_context_name(&label).await {
+                Ok(r) => r,
+                Err(e) => return call_error_text("reserving context name", &e),
+            };
+        }
+        let Some(token) = reservation.token else {
+            return format!(
+                "Error: context name '{label}' is taken; try: {}",
+                reservation.suggestions.join(", ")
+            );
+        };
+        let context_type = req.context_type.unwrap_or_else(|| "mcp".to_string());
+        let context_id = match remote.actor.commit_context_name(token, &context_type).await {
             Ok(id) => id,
-            Err(e) => return format!("Error creating context: {e}"),
+            Err(e) => return call_error_text("creating context", &e),
         };
 
         // 2. Join it via the actor (updates actor's internal state for reconnects).
         // The actor's `instance` was set at spawn_actor time; the join_context
         // RPC now only takes the context id.
         if let Err(e) = remote.actor.join_context(context_id).await {
-            return format!("Error joining context: {e}");
+            return call_error_text("joining context", &e);
         }
 
         // 3. Sync initial state from server
         let sync_state = match remote.actor.get_context_sync(context_id).await {
             Ok(s) => s,
-            Err(e) => return format!("Error syncing context: {e}"),
+            Err(e) => return call_error_text("syncing context", &e),
         };
 
         // 4. Build the synced document from the server snapshot. SyncedDocument
@@ -1071,10 +1361,7 @@ impl KaijutsuMcp {
             Arc::clone(&remote.synced),
             remote.change.clone(),
         );
-        {
-            let mut g = remote.doc_task.lock().unwrap_or_else(|e| e.into_inner());
-            *g = Some(doc_task_handle.clone());
-        }
+        *remote.doc_task.lock() = Some(doc_task_handle.clone());
 
         // 6. Bridge the actor's block-events and connection-status broadcast
         // streams into the doc task's command channel — a `NeedsResync`
@@ -1084,6 +1371,19 @@ impl KaijutsuMcp {
         let bridge_abort = bridge_join.abort_handle();
         let doc_task_abort = doc_task_join.abort_handle();
 
+        // Forward the context's kernel-side log notifications to the MCP
+        // client, filtered by its `logging/setLevel`. Best-effort and
+        // independent of the doc task — no supervision needed.
+        let log_abort = spawn_log_bridge(
+            remote.actor.clone(),
+            context_id,
+            self.server_state.clone(),
+        )
+        .abort_handle();
+        let list_changed_abort =
+            spawn_list_changed_bridge(remote.actor.clone(), self.server_state.clone())
+                .abort_handle();
+
         // Supervise both tasks. The doc task is the sole writer of
         // SyncedDocument; if it panics or its channel closes (impossible in
         // practice — the handle stored in `remote.doc_task` keeps a sender
@@ -1137,13 +1437,13 @@ impl KaijutsuMcp {
                 context_id,
                 _bridge_task: Arc::new(AbortOnDrop(bridge_abort)),
                 _doc_task: Arc::new(AbortOnDrop(doc_task_abort)),
+                _log_task: Arc::new(AbortOnDrop(log_abort)),
+                _list_changed_task: Arc::new(AbortOnDrop(list_changed_abort)),
             });
         }
 
         // 8. Update shared context_id for hook listener
-        if let Ok(mut ctx) = remote.shared_context_id.lock() {
-            *ctx = Some(context_id);
-        }
+        *remote.shared_context_id.lock() = Some(context_id);
 
         tracing::info!(
             context_id = %context_id,
@@ -1165,12 +1465,12 @@ impl KaijutsuMcp {
     // ========================================================================
 
     #[tool(
-        description = "Get this MCP server's identity: context short ID, context name, authenticated user, agent session info. Useful for understanding your position in the drift network.",
+        description = "Get this MCP server's identity: context short ID, context name, authenticated user, agent session info, and the user's preferences (default_model, default_consent, ui.*). Useful for understanding your position in the drift network.",
         annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
     )]
     #[tracing::instrument(skip(self), name = "mcp.whoami")]
     pub async fn whoami(&self) -> String {
-        let session_id = self.session_id.lock().ok().and_then(|g| g.clone());
+        let session_id = self.session_id.lock().clone();
 
         let actor = match self.actor() {
             Some(a) => a,
@@ -1188,14 +1488,21 @@ impl KaijutsuMcp {
 
         let identity = match actor.whoami().await {
             Ok(id) => id,
-            Err(e) => return format!("Error getting identity: {e}"),
+            Err(e) => return call_error_text("getting identity", &e),
         };
 
         let (context_id, ctx_label) = match actor.get_context_id().await {
             Ok(pair) => pair,
-            Err(e) => return format!("Error getting context: {e}"),
+            Err(e) => return call_error_text("getting context", &e),
         };
 
+        // Preferences are informational here; an older server without the
+        // RPC shouldn't break whoami.
+        let preferences = actor.get_preferences().await.unwrap_or_else(|e| {
+            tracing::debug!("whoami: get_preferences failed: {e}");
+            Default::default()
+        });
+
         serde_json::json!({
             "username": identity.username,
             "display_name": identity.display_name,
@@ -1204,156 +1511,1208 @@ impl KaijutsuMcp {
             "context_name": self.context_name,
             "session_id": session_id,
             "agent_name": self.agent_name,
+            "preferences": preferences,
         })
         .to_string()
     }
 
     // ========================================================================
-    // Peer Invocation (drift navigation)
+    // Context Statistics
     // ========================================================================
 
     #[tool(
-        description = "Invoke a peer through the kernel. Peers are named RPC participants attached to the kernel (e.g., kaijutsu-app registers as a peer and exposes actions like 'switch_context' and 'active_context' for drift navigation). Params and result are JSON. Requires --connect.",
-        annotations(
-            destructive_hint = false,
-            idempotent_hint = false,
-            open_world_hint = true
-        )
+        description = "Aggregated statistics for a context: block counts by kind and role, total bytes, last activity per principal, model, consent mode, drift in/out counts, and pending approvals (staged drifts awaiting commit). Omit context_id to use the current context. Local mode reports block tallies only.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
     )]
-    #[tracing::instrument(skip(self, req), name = "mcp.invoke_peer")]
-    async fn invoke_peer(&self, Parameters(req): Parameters<InvokePeerRequest>) -> String {
-        let actor = match self.actor() {
-            Some(a) => a,
-            None => return "Error: invoke_peer requires --connect".to_string(),
+    #[tracing::instrument(skip(self, req), name = "mcp.context_info")]
+    async fn context_info(&self, Parameters(req): Parameters<ContextInfoRequest>) -> String {
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
         };
+        match self.context_info_json(ctx_id).await {
+            Ok(json) => serde_json::to_string_pretty(&json)
+                .unwrap_or_else(|e| format!("Error serializing: {e}")),
+            Err(e) => e,
+        }
+    }
 
-        let params = match serde_json::to_vec(&normalize_peer_params(&req.params)) {
-            Ok(v) => v,
-            Err(e) => return format!("Error: failed to serialize params: {e}"),
+    // ========================================================================
+    // Sync Quarantine
+    // ========================================================================
+
+    #[tool(
+        description = "Show incoming sync payloads this server failed to apply to the joined document and is holding in quarantine, instead of silently diverging: per payload the block it arrived for, whether it is corrupt (undecodable, never retried) or causal (merge failed, retried after each successful sync), the last error, its size, and how many attempts failed. Set resolve=true to drop them all and force a full resync of the document from the server. Requires --connect and register_session.",
+        annotations(destructive_hint = false, idempotent_hint = false, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.sync_quarantine")]
+    async fn sync_quarantine(&self, Parameters(req): Parameters<SyncQuarantineRequest>) -> String {
+        let Backend::Remote(remote) = &self.backend else {
+            return "Error: sync_quarantine requires --connect".to_string();
         };
-        match actor.invoke_peer(&req.nick, &req.action, &params).await {
-            Ok(result) => String::from_utf8_lossy(&result).to_string(),
-            Err(e) => format!("Error: {e}"),
+        let report = remote.synced.lock().as_ref().map(|doc| {
+            let held: Vec<_> = doc
+                .quarantine()
+                .iter()
+                .map(|q| {
+                    serde_json::json!({
+                        "block_id": q.block_id.map(|id| id.to_key()),
+                        "reason": q.reason.as_str(),
+                        "error": q.error,
+                        "bytes": q.size(),
+                        "attempts": q.attempts,
+                    })
+                })
+                .collect();
+            (doc.context_id(), held)
+        });
+        let Some((context_id, held)) = report else {
+            return "Error: no active context — call register_session first".to_string();
+        };
+
+        let mut resolved = false;
+        if req.resolve {
+            let Some(doc_task) = remote.doc_task.lock().clone() else {
+                return "Error: sync_quarantine: doc task not ready".to_string();
+            };
+            if let Err(e) = doc_task.resync(ResyncReason::QuarantineResolved).await {
+                return format!("Error: sync_quarantine: {e}");
+            }
+            resolved = true;
         }
+
+        serde_json::to_string_pretty(&serde_json::json!({
+            "context_id": context_id.short(),
+            "quarantined": held.len(),
+            "payloads": held,
+            "resolved": resolved,
+        }))
+        .unwrap_or_else(|e| format!("Error serializing: {e}"))
     }
 
     // ========================================================================
-    // Input Document Tools (CRDT compose scratchpad)
+    // Context Cleanup
     // ========================================================================
 
     #[tool(
-        description = "Read the current input document text for a context. The input document is a CRDT-backed scratchpad shared across all participants (compose box, agents, MCP tools). Omit context_id to use the current context.",
-        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+        description = "Close (archive) every open context matching all the given filters in one call — e.g. the stale experiment forks of a context. At least one of label_prefix, forked_from, idle_minutes or concluded_only is required. Promoted (ring-0) contexts are skipped unless include_promoted. Closed contexts disappear from drift_ls and the constellation but keep their history; bring one back with context_reopen. Use dry_run to preview. Requires --connect.",
+        annotations(destructive_hint = true, idempotent_hint = true, open_world_hint = false)
     )]
-    #[tracing::instrument(skip(self, req), name = "mcp.read_input")]
-    async fn read_input(&self, Parameters(req): Parameters<InputReadRequest>) -> String {
-        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+    #[tracing::instrument(skip(self, req), name = "mcp.contexts_close")]
+    async fn contexts_close(&self, Parameters(req): Parameters<ContextsCloseRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: contexts_close requires --connect".to_string();
+        };
+        let now = kaijutsu_types::now_millis();
+        let mut filter = kaijutsu_types::ContextCloseFilter {
+            label_prefix: req.label_prefix.filter(|p| !p.is_empty()),
+            forked_from: None,
+            idle_before: req
+                .idle_minutes
+                .map(|m| now.saturating_sub(m.saturating_mul(60_000))),
+            concluded_only: req.concluded_only,
+            include_promoted: req.include_promoted,
+        };
+        if let Some(parent) = req.forked_from.as_deref() {
+            match self.resolve_context(actor, parent).await {
+                Ok(id) => filter.forked_from = Some(id),
+                Err(e) => return e,
+            }
+        }
+        if filter.is_empty() {
+            return "Error: contexts_close needs at least one of label_prefix, forked_from, \
+                    idle_minutes or concluded_only"
+                .to_string();
+        }
+        let closed = match actor.close_contexts(filter, req.dry_run).await {
+            Ok(ids) => ids,
+            Err(e) => return call_error_text("contexts_close", &e),
+        };
+        // Labels are a courtesy for reviewing a dry run; a failed listing
+        // still reports the ids.
+        let labels: std::collections::HashMap<ContextId, String> = actor
+            .list_contexts()
+            .await
+            .map(|contexts| contexts.into_iter().map(|c| (c.id, c.label)).collect())
+            .unwrap_or_default();
+        let closed: Vec<_> = closed
+            .iter()
+            .map(|id| {
+                serde_json::json!({
+                    "context_id": id.short(),
+                    "label": labels.get(id).filter(|l| !l.is_empty()),
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&serde_json::json!({
+            "dry_run": req.dry_run,
+            "count": closed.len(),
+            "closed": closed,
+        }))
+        .unwrap_or_else(|e| format!("Error serializing: {e}"))
+    }
+
+    #[tool(
+        description = "Reopen a context closed by contexts_close (or archived from the time well). It returns to drift_ls and the constellation with its history intact, without taking a ring-0 seat. Requires --connect.",
+        annotations(destructive_hint = false, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.context_reopen")]
+    async fn context_reopen(&self, Parameters(req): Parameters<ContextReopenRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: context_reopen requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_context(actor, &req.context_id).await {
             Ok(id) => id,
             Err(e) => return e,
         };
-
-        match &self.backend {
-            Backend::Local(store) => {
-                // Ensure input doc exists
-                let _ = store.create_input_doc(ctx_id);
-                match store.get_input_text(ctx_id) {
-                    Ok(text) => serde_json::json!({
-                        "context_id": ctx_id.short(),
-                        "content": text,
-                        "length": text.len(),
-                    })
-                    .to_string(),
-                    Err(e) => format!("Error: {}", e),
-                }
-            }
-            Backend::Remote(remote) => {
-                match remote.actor.get_input_state(ctx_id).await {
-                    Ok(state) => serde_json::json!({
-                        "context_id": ctx_id.short(),
-                        "content": state.content,
-                        "length": state.content.len(),
-                        "version": state.version,
-                    })
-                    .to_string(),
-                    Err(e) => format!("Error: {}", e),
-                }
-            }
+        match actor.reopen_context(ctx_id).await {
+            Ok(()) => serde_json::json!({
+                "success": true,
+                "context_id": ctx_id.short(),
+            })
+            .to_string(),
+            Err(e) => call_error_text("context_reopen", &e),
         }
     }
 
     #[tool(
-        description = "Replace all text in the input document. Clears existing content and writes the new text. The input document is shared — changes are visible to all participants immediately. Omit context_id to use the current context.",
-        annotations(destructive_hint = false, open_world_hint = false)
+        description = "Recover a wedged context: hard-interrupt its LLM stream and any shell command this session is running there, then mark every block still Running as error. Use when the kernel reports the context unhealthy (a block stuck Running, a model stream gone silent). Returns whether a stream was interrupted and the blocks marked. Omit context_id to use the current context. Requires --connect.",
+        annotations(
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
-    #[tracing::instrument(skip(self, req), name = "mcp.write_input")]
-    async fn write_input(&self, Parameters(req): Parameters<InputWriteRequest>) -> String {
+    #[tracing::instrument(skip(self, req), name = "mcp.context_recover")]
+    async fn context_recover(&self, Parameters(req): Parameters<ContextRecoverRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: context_recover requires --connect".to_string();
+        };
         let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
             Ok(id) => id,
             Err(e) => return e,
         };
-
-        match &self.backend {
-            Backend::Local(store) => {
-                // Ensure input doc exists
-                let _ = store.create_input_doc(ctx_id);
-                // Clear then write
-                let _ = store.clear_input(ctx_id);
-                if !req.text.is_empty()
-                    && let Err(e) = store.edit_input(ctx_id, 0, &req.text, 0)
-                {
-                    return format!("Error: {}", e);
-                }
-                serde_json::json!({
-                    "success": true,
-                    "context_id": ctx_id.short(),
-                    "length": req.text.len(),
-                })
-                .to_string()
-            }
-            Backend::Remote(remote) => {
-                // Get current state to know how much to delete
-                let current_len = match remote.actor.get_input_state(ctx_id).await {
-                    Ok(state) => state.content.len() as u64,
-                    Err(e) => return format!("Error getting current state: {}", e),
-                };
-                // Delete all, then insert new text in one operation
-                match remote
-                    .actor
-                    .edit_input(ctx_id, 0, &req.text, current_len)
-                    .await
-                {
-                    Ok(version) => serde_json::json!({
-                        "success": true,
-                        "context_id": ctx_id.short(),
-                        "length": req.text.len(),
-                        "version": version,
-                    })
-                    .to_string(),
-                    Err(e) => format!("Error: {}", e),
-                }
-            }
+        match actor.context_recover(ctx_id).await {
+            Ok(recovery) => serde_json::json!({
+                "context_id": ctx_id.short(),
+                "interrupted": recovery.interrupted,
+                "marked": recovery.marked.iter().map(|id| id.to_key()).collect::<Vec<_>>(),
+            })
+            .to_string(),
+            Err(e) => call_error_text("context_recover", &e),
         }
     }
 
+    // ========================================================================
+    // Execution Budgets
+    // ========================================================================
+
     #[tool(
-        description = "Surgical edit on the input document: insert and/or delete characters at a specific position. More efficient than write_input for small edits to large text. Omit context_id to use the current context.",
-        annotations(destructive_hint = false, open_world_hint = false)
+        description = "A context's execution budget (max tool calls and shell seconds per hour), what it has spent in the current hour-long window, what remains, and whether the kernel-wide tool halt is engaged. null limits mean unlimited. Omit context_id to use the current context. Requires --connect.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
     )]
-    #[tracing::instrument(skip(self, req), name = "mcp.edit_input")]
-    async fn edit_input(&self, Parameters(req): Parameters<InputEditRequest>) -> String {
+    #[tracing::instrument(skip(self, req), name = "mcp.budget_status")]
+    async fn budget_status(&self, Parameters(req): Parameters<BudgetStatusRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: budget_status requires --connect".to_string();
+        };
         let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
             Ok(id) => id,
             Err(e) => return e,
         };
+        match actor.get_budget_status(ctx_id).await {
+            Ok(status) => serde_json::json!({
+                "context_id": ctx_id.short(),
+                "window_secs": kaijutsu_types::BUDGET_WINDOW_SECS,
+                "budget": status.budget,
+                "tool_calls": status.tool_calls,
+                "tool_calls_remaining": status.tool_calls_remaining(),
+                "shell_secs": status.shell_secs,
+                "shell_secs_remaining": status.shell_secs_remaining(),
+                "halt": status.halt,
+            })
+            .to_string(),
+            Err(e) => call_error_text("budget_status", &e),
+        }
+    }
 
-        match &self.backend {
-            Backend::Local(store) => {
-                // Ensure input doc exists
-                let _ = store.create_input_doc(ctx_id);
-                match store.edit_input(ctx_id, req.pos as usize, &req.insert, req.delete as usize) {
-                    Ok(_ops) => {
-                        let text = store.get_input_text(ctx_id).unwrap_or_default();
-                        serde_json::json!({
+    // ========================================================================
+    // LLM Parameters
+    // ========================================================================
+
+    #[tool(
+        description = "Set a context's LLM generation parameters — temperature, max_tokens, top_p, tool_choice — used for its turns instead of the provider defaults. Given fields are merged over what is stored; clear=true drops the stored set first. Forks inherit them; context_info shows them. Omit context_id to use the current context. Requires --connect.",
+        annotations(destructive_hint = false, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.llm_params_set")]
+    async fn llm_params_set(&self, Parameters(req): Parameters<LlmParamsSetRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: llm_params_set requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        let tool_choice = match req.tool_choice.as_deref().map(str::parse) {
+            Some(Ok(choice)) => Some(choice),
+            Some(Err(e)) => return format!("Error: {e}"),
+            None => None,
+        };
+        let update = kaijutsu_types::LlmParams {
+            temperature: req.temperature,
+            max_tokens: req.max_tokens,
+            top_p: req.top_p,
+            tool_choice,
+        };
+        if let Err(e) = update.validate() {
+            return format!("Error: {e}");
+        }
+        // The server merges, so concurrent setters don't drop each other's
+        // fields; `clear` replaces the stored set instead.
+        let merge = !req.clear;
+        let params = (merge || !update.is_empty()).then_some(update);
+        match actor.set_llm_params(ctx_id, params, merge).await {
+            Ok(params) => serde_json::json!({
+                "context_id": ctx_id.short(),
+                "llm_params": params,
+            })
+            .to_string(),
+            Err(e) => call_error_text("llm_params_set", &e),
+        }
+    }
+
+    #[tool(
+        description = "Show exactly what a turn in a context would send the model, without sending it: provider and model, the full system prompt, tool definitions, generation params, and the wire messages chosen by the context's assembly strategy (with how many of its blocks were kept). Pass strategy to try another assembly. Auto-compaction does not run and images stay CAS references. Omit context_id to use the current context. Requires --connect.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.context_preview")]
+    async fn context_preview(&self, Parameters(req): Parameters<ContextPreviewRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: context_preview requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        if let Some(Err(e)) = req
+            .strategy
+            .as_deref()
+            .map(str::parse::<kaijutsu_types::Assembly>)
+        {
+            return format!("Error: {e}");
+        }
+        match actor.context_preview(ctx_id, req.strategy).await {
+            Ok(preview) => serde_json::json!({
+                "context_id": ctx_id.short(),
+                "provider": preview.provider,
+                "model": preview.model,
+                "strategy": preview.strategy,
+                "blocks": { "total": preview.total_blocks, "kept": preview.kept_blocks },
+                "llm_params": preview.params,
+                "system_prompt": preview.system_prompt,
+                "tools": preview.tools,
+                "messages": preview.messages,
+            })
+            .to_string(),
+            Err(e) => call_error_text("context_preview", &e),
+        }
+    }
+
+    // ========================================================================
+    // Broadcast Prompts
+    // ========================================================================
+
+    #[tool(
+        description = "Send one prompt to several contexts at once (1 to 16), each turn on its own model, and collect the answers side by side in a new comparison context. Each target goes pending → running → done | failed on its own. Returns the broadcast id and per-target progress; set timeout_secs to wait for the answers, or follow up with broadcast_status. Read the answers in comparison_context_id. Requires --connect.",
+        annotations(destructive_hint = false, idempotent_hint = false, open_world_hint = true)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.broadcast_prompt")]
+    async fn broadcast_prompt(
+        &self,
+        Parameters(req): Parameters<BroadcastPromptRequest>,
+    ) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: broadcast_prompt requires --connect".to_string();
+        };
+        let mut targets = Vec::with_capacity(req.targets.len());
+        for spec in &req.targets {
+            let context_id = match self.resolve_input_context(Some(&spec.context_id)).await {
+                Ok(id) => id,
+                Err(e) => return e,
+            };
+            targets.push(kaijutsu_types::BroadcastTarget {
+                context_id,
+                model: spec.model.clone().filter(|m| !m.is_empty()),
+            });
+        }
+        if let Err(e) = kaijutsu_types::broadcast::validate_targets(&targets) {
+            return format!("Error: {e}");
+        }
+        let status = match actor
+            .broadcast_prompt(&req.prompt, targets, req.label)
+            .await
+        {
+            Ok(status) => status,
+            Err(e) => return call_error_text("broadcast_prompt", &e),
+        };
+        await_broadcast(actor, status, req.timeout_secs.unwrap_or(0)).await
+    }
+
+    #[tool(
+        description = "Progress of a broadcast_prompt: per-target state (pending, running, done, failed), answer block ids, and errors. Set timeout_secs to wait for it to finish. Broadcasts are forgotten when the server restarts. Requires --connect.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.broadcast_status")]
+    async fn broadcast_status(
+        &self,
+        Parameters(req): Parameters<BroadcastStatusRequest>,
+    ) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: broadcast_status requires --connect".to_string();
+        };
+        let status = match actor.get_broadcast(req.id).await {
+            Ok(status) => status,
+            Err(e) => return call_error_text("broadcast_status", &e),
+        };
+        await_broadcast(actor, status, req.timeout_secs.unwrap_or(0)).await
+    }
+
+    // ========================================================================
+    // Digest Subscriptions
+    // ========================================================================
+
+    #[tool(
+        description = "Follow another context through periodic LLM digests, delivered as drift blocks into the subscriber (default: the current context). Set every_minutes, on_milestone (a source turn finished), or both; a digest only goes out when the source has new blocks. Subscribing again replaces the old settings; remove=true unsubscribes; omit source to list. Returns the subscriber's subscriptions. Needs the subscriber's drift capability to change them. Requires --connect.",
+        annotations(
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = true
+        )
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.digest_subscribe")]
+    async fn digest_subscribe(
+        &self,
+        Parameters(req): Parameters<DigestSubscribeRequest>,
+    ) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: digest_subscribe requires --connect".to_string();
+        };
+        let subscriber = match self.resolve_input_context(req.subscriber.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        let source = match req.source.as_deref() {
+            Some(query) => match self.resolve_context(actor, query).await {
+                Ok(id) => Some(id),
+                Err(e) => return e,
+            },
+            None => None,
+        };
+        let every_secs = match req.every_minutes.map(|m| m.checked_mul(60)) {
+            Some(None) => return "Error: every_minutes is too large".to_string(),
+            Some(Some(secs)) => Some(secs),
+            None => None,
+        };
+        let subs = match actor
+            .digest_subscribe(
+                subscriber,
+                source,
+                every_secs,
+                req.on_milestone,
+                req.focus,
+                req.remove,
+            )
+            .await
+        {
+            Ok(subs) => subs,
+            Err(e) => return call_error_text("digest_subscribe", &e),
+        };
+        serde_json::json!({
+            "subscriber": subscriber.short(),
+            "subscriptions": subs
+                .iter()
+                .map(|s| serde_json::json!({
+                    "source": s.source.short(),
+                    "trigger": s.trigger_label(),
+                    "focus": s.focus,
+                    "last_digest_at": s.last_digest_at,
+                }))
+                .collect::<Vec<_>>(),
+        })
+        .to_string()
+    }
+
+    // ========================================================================
+    // Document Peek
+    // ========================================================================
+
+    #[tool(
+        description = "Look at another context without joining it: its blocks (id, role, kind, status, content preview) and version. Your session stays in the current context and takes no seat there. Use it to check a sibling before deciding to drift to it or pull from it. Needs block_read in your current context. Requires --connect.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.doc_peek")]
+    async fn doc_peek(&self, Parameters(req): Parameters<DocPeekRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: doc_peek requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_context(actor, &req.context_id).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        let document = match actor.peek_document(ctx_id, 0, false).await {
+            Ok(document) => document,
+            Err(e) => return call_error_text("doc_peek", &e),
+        };
+        let full = req.full_content.unwrap_or(false);
+        let blocks: Vec<serde_json::Value> = document
+            .blocks
+            .iter()
+            .map(|b| {
+                let content = if full || b.content.chars().count() <= 200 {
+                    b.content.clone()
+                } else {
+                    format!("{}...", b.content.chars().take(200).collect::<String>())
+                };
+                serde_json::json!({
+                    "id": b.id.to_key(),
+                    "parent_id": b.parent_id.map(|p| p.to_key()),
+                    "role": b.role.as_str(),
+                    "kind": b.kind.as_str(),
+                    "status": b.status.as_str(),
+                    "content": content,
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&serde_json::json!({
+            "context_id": ctx_id.short(),
+            "version": document.version,
+            "block_count": blocks.len(),
+            "blocks": blocks,
+        }))
+        .unwrap_or_else(|e| format!("Error serializing: {e}"))
+    }
+
+    // ========================================================================
+    // Document Bookmarks
+    // ========================================================================
+
+    #[tool(
+        description = "Bookmark a document's current state under a name (\"before-refactor\"). The server keeps a copy of its blocks, so you can later read a block as it stood (`kj block read <id> --at <name>`), see what changed since (`kj doc diff <doc> <name>`), or put the text back (`kj doc revert <doc> <name>`). Names are unique per document. Omit context_id to use the current context. Requires --connect.",
+        annotations(
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.bookmark_create")]
+    async fn bookmark_create(&self, Parameters(req): Parameters<BookmarkCreateRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: bookmark_create requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        match actor.create_bookmark(ctx_id, req.name).await {
+            Ok(bookmark) => bookmark_json(&bookmark).to_string(),
+            Err(e) => call_error_text("bookmark_create", &e),
+        }
+    }
+
+    #[tool(
+        description = "List a document's bookmarks (made with bookmark_create), oldest first: name, document version, block count, who made it and when. Omit context_id to use the current context. Requires --connect.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.bookmark_list")]
+    async fn bookmark_list(&self, Parameters(req): Parameters<BookmarkListRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: bookmark_list requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        match actor.list_bookmarks(ctx_id).await {
+            Ok(bookmarks) => serde_json::json!({
+                "context_id": ctx_id.short(),
+                "bookmarks": bookmarks.iter().map(bookmark_json).collect::<Vec<_>>(),
+            })
+            .to_string(),
+            Err(e) => call_error_text("bookmark_list", &e),
+        }
+    }
+
+    // ========================================================================
+    // Context Timeline
+    // ========================================================================
+
+    #[tool(
+        description = "When things happened in a context: its life (or the last window_secs) split into equal time buckets, each counting blocks created, edits, tool runs, drifts received and errors. Use it to find when a long session went wrong, then read the blocks from that stretch. Empty buckets are left out; start times are Unix millis. Omit context_id to use the current context. Requires --connect.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.context_timeline")]
+    async fn context_timeline(
+        &self,
+        Parameters(req): Parameters<ContextTimelineRequest>,
+    ) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: context_timeline requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        match actor
+            .get_context_timeline(
+                ctx_id,
+                req.window_secs.unwrap_or(0),
+                req.buckets.unwrap_or(0),
+            )
+            .await
+        {
+            Ok(timeline) => {
+                let buckets: Vec<_> = timeline
+                    .buckets
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| b.events() + b.edits > 0)
+                    .map(|(i, b)| {
+                        serde_json::json!({
+                            "index": i,
+                            "start": timeline.bucket_start(i),
+                            "blocks": b.blocks,
+                            "edits": b.edits,
+                            "tool_runs": b.tool_runs,
+                            "drifts": b.drifts,
+                            "errors": b.errors,
+                        })
+                    })
+                    .collect();
+                serde_json::json!({
+                    "context_id": ctx_id.short(),
+                    "start": timeline.start,
+                    "end": timeline.end(),
+                    "bucket_ms": timeline.bucket_ms,
+                    "bucket_count": timeline.buckets.len(),
+                    "buckets": buckets,
+                })
+                .to_string()
+            }
+            Err(e) => call_error_text("context_timeline", &e),
+        }
+    }
+
+    // ========================================================================
+    // Block Ordering
+    // ========================================================================
+
+    #[tool(
+        description = "Reorder a block among its siblings without reparenting. after_id must be a sibling (same parent); omit it to move the block ahead of its first sibling. The move is CRDT-safe: concurrent reorders converge on every peer.",
+        annotations(destructive_hint = false, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.block_reorder")]
+    async fn block_reorder(&self, Parameters(req): Parameters<BlockReorderRequest>) -> String {
+        let block_id = match self.resolve_block_id(&req.block_id) {
+            Ok(id) => id,
+            Err(e) => return format!("Error: {e}"),
+        };
+        let after_id = match req.after_id.as_deref() {
+            Some(s) => match self.resolve_block_id(s) {
+                Ok(id) => Some(id),
+                Err(e) => return format!("Error: {e}"),
+            },
+            None => None,
+        };
+        let ctx_id = block_id.context_id;
+
+        let result = match &self.backend {
+            Backend::Local(store) => store
+                .reorder_block(ctx_id, &block_id, after_id.as_ref())
+                .map_err(|e| e.to_string())
+                .and_then(|()| store.version(ctx_id).map_err(|e| e.to_string())),
+            Backend::Remote(remote) => remote
+                .actor
+                .reorder_block(ctx_id, &block_id, after_id.as_ref())
+                .await
+                .map_err(|e| e.to_string()),
+        };
+
+        match result {
+            Ok(version) => serde_json::json!({
+                "success": true,
+                "context_id": ctx_id.short(),
+                "block_id": block_id.to_key(),
+                "after_id": after_id.map(|id| id.to_key()),
+                "version": version,
+            })
+            .to_string(),
+            Err(e) => format!("Error: {e}"),
+        }
+    }
+
+    #[tool(
+        description = "Follow a block's text like `tail -f`: returns everything past from_offset, waiting for appends while the block is running, until it completes or timeout_secs passes. Use it to watch another agent's long output instead of polling. Returns {text, from_offset, offset, status, complete}; when complete is false the block is still running — call again with from_offset = offset to keep following.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.block_tail")]
+    async fn block_tail(&self, Parameters(req): Parameters<BlockTailRequest>) -> String {
+        let block_id = match self.resolve_block_id(&req.block_id) {
+            Ok(id) => id,
+            Err(e) => return format!("Error: {e}"),
+        };
+        let from_offset = req.from_offset.unwrap_or(0);
+
+        let (text, offset, status) = match &self.backend {
+            // Nothing else writes to a local store, so there is nothing to
+            // wait for: report what is there now.
+            Backend::Local(store) => {
+                let snap = match store.get_block_snapshot(block_id.context_id, &block_id) {
+                    Ok(Some(snap)) => snap,
+                    Ok(None) => return format!("Error: block {} not found", req.block_id),
+                    Err(e) => return format!("Error: {e}"),
+                };
+                let text: String = snap.content.chars().skip(from_offset as usize).collect();
+                let offset = snap.content.chars().count() as u64;
+                (text, offset, Some(snap.status))
+            }
+            Backend::Remote(remote) => {
+                let timeout = std::time::Duration::from_secs(req.timeout_secs.unwrap_or(60).min(600));
+                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+                let result = remote
+                    .actor
+                    .tail_block(block_id, from_offset, timeout, tx)
+                    .await;
+                let mut text = String::new();
+                let mut offset = from_offset;
+                while let Ok(chunk) = rx.try_recv() {
+                    offset = chunk.offset + chunk.text.chars().count() as u64;
+                    text.push_str(&chunk.text);
+                }
+                match result {
+                    Ok(end) => {
+                        let Some(status) = end.status else {
+                            return serde_json::json!({
+                                "block_id": block_id.to_key(),
+                                "text": text,
+                                "from_offset": from_offset,
+                                "offset": end.offset,
+                                "status": "deleted",
+                                "complete": true,
+                            })
+                            .to_string();
+                        };
+                        (text, end.offset, Some(status))
+                    }
+                    // Still running: hand back what arrived so the caller can resume.
+                    Err(CallError::Timeout(_)) => (text, offset, None),
+                    Err(e) => return format!("Error: {e}"),
+                }
+            }
+        };
+
+        let complete = status.is_some_and(|s| s.is_terminal());
+        serde_json::json!({
+            "block_id": block_id.to_key(),
+            "text": text,
+            "from_offset": from_offset,
+            "offset": offset,
+            "status": status.map(|s| s.as_str()).unwrap_or("running"),
+            "complete": complete,
+        })
+        .to_string()
+    }
+
+    #[tool(
+        description = "Get a block's permalink, kaijutsu://kernel/context/block-key, for referencing it from issues, chats, or other contexts. kaijutsu-app opens these: it joins the context and scrolls to the block (`kaijutsu <link>`, `:goto <link>`, or the app peer's open_permalink action). Requires --connect.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.block_permalink")]
+    async fn block_permalink(&self, Parameters(req): Parameters<BlockPermalinkRequest>) -> String {
+        let Backend::Remote(remote) = &self.backend else {
+            return "Error: permalinks name a kernel; requires --connect".to_string();
+        };
+        let block_id = match self.resolve_block_id(&req.block_id) {
+            Ok(id) => id,
+            Err(e) => return format!("Error: {e}"),
+        };
+        let link = kaijutsu_types::Permalink::new(remote.kernel_id, block_id);
+        serde_json::json!({
+            "url": link.to_string(),
+            "kernel_id": remote.kernel_id.to_hex(),
+            "context_id": block_id.context_id.to_hex(),
+            "block_id": block_id.to_key(),
+        })
+        .to_string()
+    }
+
+    // ========================================================================
+    // DAG Query
+    // ========================================================================
+
+    #[tool(
+        description = "Extract a precise slice of a context's conversation DAG: ancestors of a block, its descendants, the path between two blocks, a depth-bounded subtree, or the whole DAG — optionally filtered by kind and role. Returns blocks in traversal order with depth, role, kind, and content, ready to paste into a prompt. Omit context_id to use the current context (remote mode reads the joined context only).",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.dag_query")]
+    async fn dag_query(&self, Parameters(req): Parameters<DagQueryRequest>) -> String {
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        let query = match build_dag_query(&req, |s| self.resolve_block_id(s)) {
+            Ok(q) => q,
+            Err(e) => return format!("Error: {e}"),
+        };
+
+        let result = self.with_doc(ctx_id, |doc| {
+            let dag = ConversationDAG::from_store(doc);
+            dag.query(&query).map(|matches| {
+                matches
+                    .iter()
+                    .map(|m| {
+                        let content = match req.max_content_chars {
+                            Some(max) => m.block.content.chars().take(max).collect(),
+                            None => m.block.content.clone(),
+                        };
+                        serde_json::json!({
+                            "block_id": m.block.id.to_key(),
+                            "parent_id": m.block.parent_id.map(|id| id.to_key()),
+                            "depth": m.depth,
+                            "role": m.block.role.as_str(),
+                            "kind": m.block.kind.as_str(),
+                            "status": m.block.status.as_str(),
+                            "tool_name": m.block.tool_name,
+                            "content": content,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+        });
+
+        match result {
+            Some(Ok(blocks)) => serde_json::to_string_pretty(&serde_json::json!({
+                "context_id": ctx_id.short(),
+                "op": req.op.as_str(),
+                "count": blocks.len(),
+                "blocks": blocks,
+            }))
+            .unwrap_or_else(|e| format!("Error serializing: {e}")),
+            Some(Err(e)) => format!("Error: {e}"),
+            None => format!("Error: context {} not found", ctx_id.short()),
+        }
+    }
+
+    // ========================================================================
+    // Test Results
+    // ========================================================================
+
+    #[tool(
+        description = "Structured test results recorded in a context by the kaish `testreport` builtin (e.g. `cargo test 2>&1 | testreport`): per-run totals and duration, plus each test's outcome, duration, and failure excerpt. Newest run first. Omit context_id to use the current context (remote mode reads the joined context only).",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.test_results")]
+    async fn test_results(&self, Parameters(req): Parameters<TestResultsRequest>) -> String {
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        let limit = req.limit.unwrap_or(1).max(1);
+        let failures_only = req.failures_only.unwrap_or(false);
+
+        let runs = self.with_doc(ctx_id, |doc| {
+            doc.blocks_ordered()
+                .iter()
+                .rev()
+                .filter(|b| b.kind == BlockKind::ToolResult)
+                .filter_map(|b| {
+                    let json = b.output.as_ref()?.to_json();
+                    let mut report = kaijutsu_types::TestReport::from_json(&json)?;
+                    if failures_only {
+                        report
+                            .tests
+                            .retain(|t| t.outcome == kaijutsu_types::TestOutcome::Failed);
+                    }
+                    let mut run = report.to_json();
+                    run["block_id"] = serde_json::json!(b.id.to_key());
+                    Some(run)
+                })
+                .take(limit)
+                .collect::<Vec<_>>()
+        });
+
+        match runs {
+            Some(runs) if runs.is_empty() => format!(
+                "No test results in context {} — pipe a test run through `testreport` first",
+                ctx_id.short()
+            ),
+            Some(runs) => serde_json::to_string_pretty(&serde_json::json!({
+                "context_id": ctx_id.short(),
+                "runs": runs,
+            }))
+            .unwrap_or_else(|e| format!("Error serializing: {e}")),
+            None => format!("Error: context {} not found", ctx_id.short()),
+        }
+    }
+
+    // ========================================================================
+    // Context-Scoped MCP Servers
+    // ========================================================================
+
+    #[tool(
+        description = "Attach a downstream MCP server (e.g. Playwright) to one context only. Its tools become callable from that context — and from forks unless fork_mode is \"exclude\" — and stay invisible everywhere else. stdio servers are spawned on the kernel host and need the context's exec authority; http servers need operator. Returns the tools the server advertised. Omit context_id to use the current context. Requires --connect.",
+        annotations(
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = true
+        )
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.mcp_server_register")]
+    async fn mcp_server_register(
+        &self,
+        Parameters(req): Parameters<McpServerRegisterRequest>,
+    ) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: mcp_server_register requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        let mut env: Vec<(String, String)> = req.env.into_iter().collect();
+        env.sort();
+        let spec = kaijutsu_client::McpServerSpec {
+            instance: req.instance,
+            transport: req
+                .transport
+                .map(|t| t.as_str().to_string())
+                .unwrap_or_default(),
+            command: req.command.unwrap_or_default(),
+            args: req.args,
+            env,
+            cwd: req.cwd,
+            url: req.url,
+            fork_mode: req
+                .fork_mode
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default(),
+        };
+        match actor.register_mcp_server(ctx_id, spec).await {
+            Ok(server) => {
+                self.server_state.notify_tool_list_changed().await;
+                mcp_server_json(ctx_id, &server).to_string()
+            }
+            Err(e) => call_error_text("mcp_server_register", &e),
+        }
+    }
+
+    #[tool(
+        description = "Detach a context-scoped MCP server registered with mcp_server_register. The server process stops once no context uses it. Omit context_id to use the current context. Requires --connect.",
+        annotations(
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.mcp_server_unregister")]
+    async fn mcp_server_unregister(
+        &self,
+        Parameters(req): Parameters<McpServerUnregisterRequest>,
+    ) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: mcp_server_unregister requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        match actor.unregister_mcp_server(ctx_id, &req.instance).await {
+            Ok(stopped) => {
+                self.server_state.notify_tool_list_changed().await;
+                serde_json::json!({
+                    "success": true,
+                    "context_id": ctx_id.short(),
+                    "instance": req.instance,
+                    "stopped": stopped,
+                })
+                .to_string()
+            }
+            Err(e) => call_error_text("mcp_server_unregister", &e),
+        }
+    }
+
+    #[tool(
+        description = "List the MCP servers scoped to a context with a live health check (ready | degraded | down) and their current tools. Omit context_id to use the current context. Requires --connect.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.mcp_server_list")]
+    async fn mcp_server_list(&self, Parameters(req): Parameters<McpServerListRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: mcp_server_list requires --connect".to_string();
+        };
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        match actor.list_context_mcp_servers(ctx_id).await {
+            Ok(servers) => {
+                let servers: Vec<_> = servers.iter().map(|s| mcp_server_json(ctx_id, s)).collect();
+                serde_json::to_string_pretty(&servers)
+                    .unwrap_or_else(|e| format!("Error serializing: {e}"))
+            }
+            Err(e) => call_error_text("mcp_server_list", &e),
+        }
+    }
+
+    // ========================================================================
+    // Inbox
+    // ========================================================================
+
+    #[tool(
+        description = "List notifications addressed to you: mentions (@username in a prompt), consent requests (drift awaiting flush into a context you own), drift arrivals, and task assignments. Newest first; unread only unless include_acked. Set ack=true to mark the returned items read. Requires --connect.",
+        annotations(destructive_hint = false, idempotent_hint = false, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.inbox_list")]
+    async fn inbox_list(&self, Parameters(req): Parameters<InboxListRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: inbox_list requires --connect".to_string();
+        };
+        let page = match actor
+            .list_inbox(req.include_acked, req.limit.unwrap_or(20))
+            .await
+        {
+            Ok(page) => page,
+            Err(e) => return call_error_text("inbox_list", &e),
+        };
+        let mut unacked = page.unacked;
+        if req.ack {
+            let ids: Vec<u64> = page
+                .items
+                .iter()
+                .filter(|item| !item.is_acked())
+                .map(|item| item.id)
+                .collect();
+            if !ids.is_empty() {
+                match actor.ack_inbox(ids).await {
+                    Ok((_, remaining)) => unacked = remaining,
+                    Err(e) => return call_error_text("acking", &e),
+                }
+            }
+        }
+        let items: Vec<_> = page
+            .items
+            .iter()
+            .map(|item| {
+                serde_json::json!({
+                    "id": item.id,
+                    "kind": item.kind.as_str(),
+                    "context_id": item.context_id.map(|c| c.short()),
+                    "sender": item.sender.map(|p| p.to_string()),
+                    "summary": item.summary,
+                    "created_at": item.created_at,
+                    "acked": item.is_acked() || req.ack,
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&serde_json::json!({
+            "unacked": unacked,
+            "items": items,
+        }))
+        .unwrap_or_else(|e| format!("Error serializing: {e}"))
+    }
+
+    // ========================================================================
+    // Preferences
+    // ========================================================================
+
+    #[tool(
+        description = "Set or clear one of your preferences, stored on the server so every client you connect from sees it. Keys: default_model (\"provider/model\", used for contexts you create), default_consent (\"collaborative\" or \"autonomous\"), or any ui.* key. Omit value to clear. Returns all preferences. Requires --connect.",
+        annotations(destructive_hint = false, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.preference_set")]
+    async fn preference_set(&self, Parameters(req): Parameters<PreferenceSetRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: preference_set requires --connect".to_string();
+        };
+        match actor.set_preference(req.key, req.value).await {
+            Ok(prefs) => serde_json::to_string_pretty(&prefs)
+                .unwrap_or_else(|e| format!("Error serializing: {e}")),
+            Err(e) => call_error_text("preference_set", &e),
+        }
+    }
+
+    // ========================================================================
+    // Consent audit log
+    // ========================================================================
+
+    #[tool(
+        description = "Read the kernel's consent audit log: every MCP tool call let through or refused at the capability gate (with the deciding binding, capability rule, or hook) and every destructive kj command confirmed with its latch nonce. Each entry records who, when, the action, a SHA-256 of the parameters, the consent mode, and a hash-chained HMAC signature. Entries are oldest first; page with after_seq. Set verify=true to check the whole chain. The output is the export format. Requires --connect.",
+        annotations(destructive_hint = false, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.consent_log")]
+    async fn consent_log(&self, Parameters(req): Parameters<ConsentLogRequest>) -> String {
+        let Some(actor) = self.actor() else {
+            return "Error: consent_log requires --connect".to_string();
+        };
+        match actor
+            .list_consent_log(req.after_seq, req.limit.unwrap_or(50), req.verify)
+            .await
+        {
+            Ok(page) => serde_json::to_string_pretty(&serde_json::json!({
+                "total": page.total,
+                "verification": page.verification,
+                "entries": page.entries,
+            }))
+            .unwrap_or_else(|e| format!("Error serializing: {e}")),
+            Err(e) => call_error_text("consent_log", &e),
+        }
+    }
+
+    // ========================================================================
+    // Peer Invocation (drift navigation)
+    // ========================================================================
+
+    #[tool(
+        description = "Invoke a peer through the kernel. Peers are named RPC participants attached to the kernel (e.g., kaijutsu-app registers as a peer and exposes actions like 'switch_context' and 'active_context' for drift navigation). Params and result are JSON. Requires --connect.",
+        annotations(
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = true
+        )
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.invoke_peer")]
+    async fn invoke_peer(&self, Parameters(req): Parameters<InvokePeerRequest>) -> String {
+        let actor = match self.actor() {
+            Some(a) => a,
+            None => return "Error: invoke_peer requires --connect".to_string(),
+        };
+
+        let params = match serde_json::to_vec(&normalize_peer_params(&req.params)) {
+            Ok(v) => v,
+            Err(e) => return format!("Error: failed to serialize params: {e}"),
+        };
+        match actor.invoke_peer(&req.nick, &req.action, &params).await {
+            Ok(result) => String::from_utf8_lossy(&result).to_string(),
+            Err(e) => call_error_text("invoke_peer", &e),
+        }
+    }
+
+    // ========================================================================
+    // Input Document Tools (CRDT compose scratchpad)
+    // ========================================================================
+
+    #[tool(
+        description = "Read the current input document text for a context. The input document is a CRDT-backed scratchpad shared across all participants (compose box, agents, MCP tools). Omit context_id to use the current context.",
+        annotations(read_only_hint = true, idempotent_hint = true, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.read_input")]
+    async fn read_input(&self, Parameters(req): Parameters<InputReadRequest>) -> String {
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+
+        match &self.backend {
+            Backend::Local(store) => {
+                // Ensure input doc exists
+                let _ = store.create_input_doc(ctx_id);
+                match store.get_input_text(ctx_id) {
+                    Ok(text) => serde_json::json!({
+                        "context_id": ctx_id.short(),
+                        "content": text,
+                        "length": text.len(),
+                    })
+                    .to_string(),
+                    Err(e) => format!("Error: {}", e),
+                }
+            }
+            Backend::Remote(remote) => {
+                match remote.actor.get_input_state(ctx_id).await {
+                    Ok(state) => serde_json::json!({
+                        "context_id": ctx_id.short(),
+                        "content": state.content,
+                        "length": state.content.len(),
+                        "version": state.version,
+                    })
+                    .to_string(),
+                    Err(e) => call_error_text("read_input", &e),
+                }
+            }
+        }
+    }
+
+    #[tool(
+        description = "Replace all text in the input document. Clears existing content and writes the new text. The input document is shared — changes are visible to all participants immediately. Omit context_id to use the current context.",
+        annotations(destructive_hint = false, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.write_input")]
+    async fn write_input(&self, Parameters(req): Parameters<InputWriteRequest>) -> String {
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+
+        match &self.backend {
+            Backend::Local(store) => {
+                // Ensure input doc exists
+                let _ = store.create_input_doc(ctx_id);
+                // Clear then write
+                let _ = store.clear_input(ctx_id);
+                if !req.text.is_empty()
+                    && let Err(e) = store.edit_input(ctx_id, 0, &req.text, 0)
+                {
+                    return format!("Error: {}", e);
+                }
+                serde_json::json!({
+                    "success": true,
+                    "context_id": ctx_id.short(),
+                    "length": req.text.len(),
+                })
+                .to_string()
+            }
+            Backend::Remote(remote) => {
+                // Get current state to know how much to delete
+                let current_len = match remote.actor.get_input_state(ctx_id).await {
+                    Ok(state) => state.content.len() as u64,
+                    Err(e) => return call_error_text("getting current state", &e),
+                };
+                // Delete all, then insert new text in one operation
+                match remote
+                    .actor
+                    .edit_input(ctx_id, 0, &req.text, current_len)
+                    .await
+                {
+                    Ok(version) => serde_json::json!({
+                        "success": true,
+                        "context_id": ctx_id.short(),
+                        "length": req.text.len(),
+                        "version": version,
+                    })
+                    .to_string(),
+                    Err(e) => call_error_text("write_input", &e),
+                }
+            }
+        }
+    }
+
+    #[tool(
+        description = "Surgical edit on the input document: insert and/or delete characters at a specific position. More efficient than write_input for small edits to large text. Omit context_id to use the current context.",
+        annotations(destructive_hint = false, open_world_hint = false)
+    )]
+    #[tracing::instrument(skip(self, req), name = "mcp.edit_input")]
+    async fn edit_input(&self, Parameters(req): Parameters<InputEditRequest>) -> String {
+        let ctx_id = match self.resolve_input_context(req.context_id.as_deref()).await {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+
+        match &self.backend {
+            Backend::Local(store) => {
+                // Ensure input doc exists
+                let _ = store.create_input_doc(ctx_id);
+                match store.edit_input(ctx_id, req.pos as usize, &req.insert, req.delete as usize) {
+                    Ok(_ops) => {
+                        let text = store.get_input_text(ctx_id).unwrap_or_default();
+                        serde_json::json!({
                             "success": true,
                             "context_id": ctx_id.short(),
                             "length": text.len(),
@@ -1375,7 +2734,7 @@ impl KaijutsuMcp {
                         "version": version,
                     })
                     .to_string(),
-                    Err(e) => format!("Error: {}", e),
+                    Err(e) => call_error_text("edit_input", &e),
                 }
             }
         }
@@ -1398,7 +2757,7 @@ impl KaijutsuMcp {
                 "Error: submit_input requires --connect to kaijutsu-server".to_string()
             }
             Backend::Remote(remote) => {
-                let is_shell = req.mode.as_deref() == Some("shell");
+                let is_shell = req.mode == Some(InputMode::Shell);
                 match remote.actor.submit_input(ctx_id, is_shell).await {
                     Ok(result) => serde_json::json!({
                         "success": true,
@@ -1406,7 +2765,7 @@ impl KaijutsuMcp {
                         "block_id": result.block_id.to_key(),
                     })
                     .to_string(),
-                    Err(e) => format!("Error: {}", e),
+                    Err(e) => call_error_text("submit_input", &e),
                 }
             }
         }
@@ -1652,12 +3011,11 @@ impl KaijutsuMcp {
         content.push_str(&format!("**Edit type:** {}\n\n", edit_type));
 
         content.push_str("## Current Content\n\n");
-        content.push_str("```\n");
-        content.push_str(&snapshot.content);
-        if !snapshot.content.ends_with('\n') {
-            content.push('\n');
-        }
-        content.push_str("```\n\n");
+        content.push_str(&kaijutsu_types::language::markdown_fence(
+            &snapshot.content,
+            snapshot.language.as_deref(),
+        ));
+        content.push('\n');
 
         // Add parent context if available
         if let Some(parent_id) = snapshot.parent_id
@@ -1670,10 +3028,13 @@ impl KaijutsuMcp {
                 parent_snap.content.clone()
             };
             content.push_str(&format!(
-                "[{}/{}]\n```\n{}\n```\n\n",
+                "[{}/{}]\n{}\n",
                 parent_snap.role.as_str(),
                 parent_snap.kind.as_str(),
-                preview
+                kaijutsu_types::language::markdown_fence(
+                    &preview,
+                    parent_snap.language.as_deref()
+                )
             ));
         }
 
@@ -1718,9 +3079,147 @@ impl KaijutsuMcp {
         )])
         .with_description(format!("Editing assistant for block '{}'", args.block_id)))
     }
+
+    /// Draft a commit message / PR description from a slice of conversation.
+    ///
+    /// Assembles the conversation (by block range or DAG subtree) and the file
+    /// changes its successful `edit`/`write` tool calls made, then asks for a
+    /// title and description.
+    #[prompt(
+        name = "summarize_for_pr",
+        description = "Build a PR title/description prompt from a conversation slice and the code changes made in it"
+    )]
+    fn summarize_for_pr(
+        &self,
+        Parameters(args): Parameters<SummarizeForPrArgs>,
+    ) -> Result<GetPromptResult, McpError> {
+        let context_id = ContextId::parse(&args.document_id).map_err(|e| {
+            McpError::invalid_params(
+                format!("Invalid document ID '{}': {}", args.document_id, e),
+                None,
+            )
+        })?;
+        let parse = |key: &str| {
+            self.resolve_block_id(key)
+                .map_err(|e| McpError::invalid_params(e, None))
+        };
+        let subtree = args.subtree.as_deref().map(parse).transpose()?;
+        let from = args.from_block.as_deref().map(parse).transpose()?;
+        let to = args.to_block.as_deref().map(parse).transpose()?;
+
+        let (blocks, subtree_ids) = self
+            .with_doc(context_id, |doc| {
+                let ids = subtree.map(|root| {
+                    let dag = ConversationDAG::from_store(doc);
+                    dag.subtree(&root)
+                        .into_iter()
+                        .map(|b| b.id)
+                        .collect::<std::collections::HashSet<_>>()
+                });
+                (doc.blocks_ordered(), ids)
+            })
+            .ok_or_else(|| {
+                McpError::invalid_params(format!("Document '{}' not found", args.document_id), None)
+            })?;
+
+        // Select the slice, keeping document order either way.
+        let slice: Vec<_> = if let Some(ids) = subtree_ids {
+            if ids.is_empty() {
+                return Err(McpError::invalid_params(
+                    format!("Subtree root '{}' not found", args.subtree.unwrap_or_default()),
+                    None,
+                ));
+            }
+            blocks.into_iter().filter(|b| ids.contains(&b.id)).collect()
+        } else {
+            let position = |id: BlockId, arg: &str| {
+                blocks.iter().position(|b| b.id == id).ok_or_else(|| {
+                    McpError::invalid_params(format!("Block '{}' not in document", arg), None)
+                })
+            };
+            let start = match from {
+                Some(id) => position(id, args.from_block.as_deref().unwrap_or_default())?,
+                None => 0,
+            };
+            let end = match to {
+                Some(id) => position(id, args.to_block.as_deref().unwrap_or_default())?,
+                None => blocks.len().saturating_sub(1),
+            };
+            if blocks.is_empty() || start > end {
+                return Err(McpError::invalid_params("Empty block range", None));
+            }
+            blocks[start..=end].to_vec()
+        };
+
+        // Tool calls whose result came back as an error changed nothing.
+        let failed_calls: std::collections::HashSet<BlockId> = slice
+            .iter()
+            .filter(|b| b.kind == BlockKind::ToolResult && b.is_error)
+            .filter_map(|b| b.tool_call_id)
+            .collect();
+
+        let mut conversation = String::new();
+        let mut changes = String::new();
+        let mut change_count = 0;
+        for block in &slice {
+            match block.kind {
+                BlockKind::Text if !block.content.trim().is_empty() => {
+                    conversation.push_str(&format!(
+                        "**{}:** {}\n\n",
+                        block.role.as_str(),
+                        block.content.trim()
+                    ));
+                }
+                BlockKind::ToolCall if !failed_calls.contains(&block.id) => {
+                    let (Some(name), Some(input)) = (&block.tool_name, &block.tool_input) else {
+                        continue;
+                    };
+                    if let Some(diff) = file_change_diff(name, input) {
+                        change_count += 1;
+                        changes.push_str("```diff\n");
+                        changes.push_str(&diff);
+                        changes.push_str("```\n\n");
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut content = String::new();
+        content.push_str(&format!("# Pull request from {}\n\n", args.document_id));
+        content.push_str(&format!("**Blocks in slice:** {}\n", slice.len()));
+        content.push_str(&format!("**File changes:** {}\n\n", change_count));
+
+        content.push_str("## Conversation\n\n");
+        if conversation.is_empty() {
+            content.push_str("*No text blocks in this slice.*\n\n");
+        } else {
+            content.push_str(&conversation);
+        }
+
+        content.push_str("## Code Changes\n\n");
+        if changes.is_empty() {
+            content.push_str("*No file edits in this slice.*\n\n");
+        } else {
+            content.push_str(&changes);
+        }
+
+        content.push_str("## Instructions\n\n");
+        content.push_str("Write a pull request for the work above:\n");
+        content.push_str("- A title under 72 characters, imperative mood\n");
+        content.push_str("- A description that opens with what changed and why\n");
+        content.push_str("- How it was verified, if the conversation says\n");
+        content.push_str("- Anything deliberately left out of scope\n");
+        content.push_str("Describe the change itself, not the conversation that produced it.\n");
+
+        Ok(GetPromptResult::new(vec![PromptMessage::new_text(
+            PromptMessageRole::User,
+            content,
+        )])
+        .with_description(format!("PR summary for document '{}'", args.document_id)))
+    }
 }
 
-#[tool_handler]
 #[prompt_handler]
 impl ServerHandler for KaijutsuMcp {
     fn get_info(&self) -> ServerInfo {
@@ -1729,14 +3228,56 @@ impl ServerHandler for KaijutsuMcp {
                 .enable_tools()
                 .enable_prompts()
                 .enable_prompts_list_changed()
+                .enable_tools_list_changed()
                 .enable_resources()
                 .enable_resources_subscribe()
+                .enable_resources_list_changed()
                 .enable_logging()
                 .enable_completions()
                 .build(),
         ).with_instructions("Kaijutsu CRDT kernel MCP server. Provides tools for collaborative document and block editing with CRDT-backed consistency.")
     }
 
+    // ========================================================================
+    // Tools
+    // ========================================================================
+
+    /// Dispatch through the tool router, then cap the result's size (see
+    /// `result_guard`). A call that created or dropped a document tells the
+    /// client its resource list changed (see `list_changed`).
+    fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<CallToolResult, McpError>> + Send + '_ {
+        async move {
+            let tool = request.name.to_string();
+            let result = self
+                .tool_router
+                .call(ToolCallContext::new(self, request, context))
+                .await?;
+            self.server_state
+                .refresh_resource_list(self.context_ids())
+                .await;
+            Ok(self.result_guard.guard(&tool, result))
+        }
+    }
+
+    /// List the router's tools.
+    fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
+        async move {
+            Ok(ListToolsResult {
+                meta: None,
+                next_cursor: None,
+                tools: self.tool_router.list_all(),
+            })
+        }
+    }
+
     // ========================================================================
     // Resources
     // ========================================================================
@@ -1747,6 +3288,12 @@ impl ServerHandler for KaijutsuMcp {
     /// - `kaijutsu://docs` - List all documents
     /// - `kaijutsu://docs/{doc_id}` - Document metadata and block list
     /// - `kaijutsu://blocks/{doc_id}/{block_key}` - Block content
+    /// - `kaijutsu://tree/{doc_id}` - Block DAG as an ASCII tree
+    /// - `kaijutsu://context/{short_id}` - Context metadata and statistics
+    /// - `kaijutsu://results/{id}` - Full text of a truncated tool result
+    ///
+    /// Reads always reflect the current state, so a client can pin the tree
+    /// or context view and re-read it instead of calling tools.
     fn list_resources(
         &self,
         _request: Option<PaginatedRequestParams>,
@@ -1791,6 +3338,35 @@ impl ServerHandler for KaijutsuMcp {
                         }
                         .no_annotation(),
                     );
+                    resources.push(
+                        RawResource {
+                            uri: format!("kaijutsu://tree/{}", doc_hex),
+                            name: format!("tree-{}", doc_id.short()),
+                            title: Some(format!("Tree: {}", doc_id.short())),
+                            description: Some("Block DAG as an ASCII tree".to_string()),
+                            mime_type: Some("text/plain".to_string()),
+                            size: None,
+                            icons: None,
+                            meta: None,
+                        }
+                        .no_annotation(),
+                    );
+                    resources.push(
+                        RawResource {
+                            uri: format!("kaijutsu://context/{}", doc_id.short()),
+                            name: format!("context-{}", doc_id.short()),
+                            title: Some(format!("Context: {}", doc_id.short())),
+                            description: Some(
+                                "Context metadata: block tallies, model, drift counts"
+                                    .to_string(),
+                            ),
+                            mime_type: Some("application/json".to_string()),
+                            size: None,
+                            icons: None,
+                            meta: None,
+                        }
+                        .no_annotation(),
+                    );
 
                     // Add each block as a resource
                     for snapshot in blocks {
@@ -1820,6 +3396,28 @@ impl ServerHandler for KaijutsuMcp {
                 }
             }
 
+            // Truncated tool results still held for paging
+            for stored in self.result_guard.list() {
+                resources.push(
+                    RawResource {
+                        uri: stored.uri(),
+                        name: format!("result-{}", stored.id),
+                        title: Some(format!("Result of {}", stored.tool)),
+                        description: Some(format!(
+                            "Full output of a truncated {} call, {} bytes",
+                            stored.tool,
+                            stored.text.len()
+                        )),
+                        mime_type: Some("text/plain".to_string()),
+                        size: Some(stored.text.len() as u32),
+                        icons: None,
+                        meta: None,
+                    }
+                    .no_annotation(),
+                );
+            }
+
+            self.server_state.record_listed_docs(self.context_ids());
             Ok(ListResourcesResult {
                 meta: None,
                 next_cursor: None,
@@ -1828,7 +3426,60 @@ impl ServerHandler for KaijutsuMcp {
         }
     }
 
+    /// List URI templates for the parameterized resources, so clients can
+    /// build URIs for documents that appear after the listing.
+    fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<ListResourceTemplatesResult, McpError>> + Send + '_
+    {
+        async move {
+            let resource_templates = vec![
+                resource_template(
+                    "kaijutsu://docs/{doc_id}",
+                    "document",
+                    "Document metadata and block list",
+                    "application/json",
+                ),
+                resource_template(
+                    "kaijutsu://blocks/{doc_id}/{block_key}",
+                    "block",
+                    "Block content",
+                    "text/plain",
+                ),
+                resource_template(
+                    "kaijutsu://tree/{doc_id}",
+                    "tree",
+                    "Block DAG of a document as an ASCII tree",
+                    "text/plain",
+                ),
+                resource_template(
+                    "kaijutsu://context/{short_id}",
+                    "context",
+                    "Context metadata and statistics; short id, prefix or label",
+                    "application/json",
+                ),
+                resource_template(
+                    "kaijutsu://results/{id}",
+                    "result",
+                    "Full text of a truncated tool result; page with ?offset=N&length=M",
+                    "text/plain",
+                ),
+            ];
+            Ok(ListResourceTemplatesResult {
+                meta: None,
+                next_cursor: None,
+                resource_templates,
+            })
+        }
+    }
+
     /// Read a specific resource.
+    ///
+    /// Every reply is stamped with a version in its contents `_meta`; a client
+    /// that sends the stamp back gets a not-modified reply instead of the
+    /// text (see [`resource_cache`]).
     fn read_resource(
         &self,
         request: ReadResourceRequestParams,
@@ -1836,6 +3487,10 @@ impl ServerHandler for KaijutsuMcp {
     ) -> impl std::future::Future<Output = Result<ReadResourceResult, McpError>> + Send + '_ {
         async move {
             let uri = &request.uri;
+            let client_version = resource_cache::if_none_match(request.meta.as_ref());
+            let reply = |text: &str, version: &str| {
+                resource_cache::versioned_result(uri, text, version, client_version)
+            };
 
             // Parse URI: kaijutsu://docs, kaijutsu://docs/{id}, kaijutsu://blocks/{id}/{key}
             if uri == "kaijutsu://docs" {
@@ -1858,10 +3513,7 @@ impl ServerHandler for KaijutsuMcp {
                 let content =
                     serde_json::to_string_pretty(&docs).unwrap_or_else(|_| "[]".to_string());
 
-                return Ok(ReadResourceResult::new(vec![ResourceContents::text(
-                    content,
-                    uri.clone(),
-                )]));
+                return Ok(reply(&content, &resource_cache::content_stamp(&content)));
             }
 
             if let Some(doc_id_str) = uri.strip_prefix("kaijutsu://docs/") {
@@ -1871,46 +3523,88 @@ impl ServerHandler for KaijutsuMcp {
                         None,
                     )
                 })?;
-                // Return document metadata and block list
+                // Return document metadata and block list, rendered under the
+                // doc lock so the text always matches its version stamp
                 let extracted = self.with_doc(doc_ctx_id, |doc| {
-                    let blocks: Vec<serde_json::Value> = doc
-                        .blocks_ordered()
-                        .iter()
-                        .map(|s| {
-                            serde_json::json!({
-                                "id": s.id.to_key(),
-                                "role": s.role.as_str(),
-                                "kind": s.kind.as_str(),
-                                "status": s.status.as_str(),
-                                "content_preview": if s.content.len() > 100 {
-                                    format!("{}...", &s.content[..100])
-                                } else {
-                                    s.content.clone()
-                                }
+                    let stamp = self.resource_cache.doc_stamp(doc.version());
+                    let content = self.resource_cache.get_or_render(uri, &stamp, || {
+                        let blocks: Vec<serde_json::Value> = doc
+                            .blocks_ordered()
+                            .iter()
+                            .map(|s| {
+                                serde_json::json!({
+                                    "id": s.id.to_key(),
+                                    "role": s.role.as_str(),
+                                    "kind": s.kind.as_str(),
+                                    "status": s.status.as_str(),
+                                    "content_preview": if s.content.len() > 100 {
+                                        format!("{}...", &s.content[..100])
+                                    } else {
+                                        s.content.clone()
+                                    }
+                                })
                             })
-                        })
-                        .collect();
-                    (blocks, doc.version())
+                            .collect();
+                        let result = serde_json::json!({
+                            "id": doc_id_str,
+                            "kind": "Conversation",
+                            "language": serde_json::Value::Null,
+                            "version": doc.version(),
+                            "blocks": blocks
+                        });
+                        serde_json::to_string_pretty(&result).unwrap_or_else(|_| "{}".to_string())
+                    });
+                    (content, stamp)
                 });
-                let (blocks, version) = extracted.ok_or_else(|| {
+                let (content, stamp) = extracted.ok_or_else(|| {
                     McpError::invalid_params(format!("Document '{}' not found", doc_id_str), None)
                 })?;
 
-                let result = serde_json::json!({
-                    "id": doc_id_str,
-                    "kind": "Conversation",
-                    "language": serde_json::Value::Null,
-                    "version": version,
-                    "blocks": blocks
-                });
+                return Ok(reply(&content, &stamp));
+            }
+
+            if let Some(doc_id_str) = uri.strip_prefix("kaijutsu://tree/") {
+                let doc_ctx_id = self
+                    .resolve_input_context(Some(doc_id_str))
+                    .await
+                    .map_err(|e| McpError::invalid_params(e, None))?;
+                let (content, stamp) = self
+                    .with_doc(doc_ctx_id, |doc| {
+                        let stamp = self.resource_cache.doc_stamp(doc.version());
+                        let content = self.resource_cache.get_or_render(uri, &stamp, || {
+                            let lines =
+                                format_dag_tree(&ConversationDAG::from_store(doc), None, false);
+                            if lines.is_empty() {
+                                "(empty)".to_string()
+                            } else {
+                                lines.join("\n")
+                            }
+                        });
+                        (content, stamp)
+                    })
+                    .ok_or_else(|| {
+                        McpError::invalid_params(
+                            format!("Document '{}' not found", doc_id_str),
+                            None,
+                        )
+                    })?;
 
+                return Ok(reply(&content, &stamp));
+            }
+
+            if let Some(short_id) = uri.strip_prefix("kaijutsu://context/") {
+                let ctx_id = self
+                    .resolve_input_context(Some(short_id))
+                    .await
+                    .map_err(|e| McpError::invalid_params(e, None))?;
+                let json = self
+                    .context_info_json(ctx_id)
+                    .await
+                    .map_err(|e| McpError::internal_error(e, None))?;
                 let content =
-                    serde_json::to_string_pretty(&result).unwrap_or_else(|_| "{}".to_string());
+                    serde_json::to_string_pretty(&json).unwrap_or_else(|_| "{}".to_string());
 
-                return Ok(ReadResourceResult::new(vec![ResourceContents::text(
-                    content,
-                    uri.clone(),
-                )]));
+                return Ok(reply(&content, &resource_cache::content_stamp(&content)));
             }
 
             if let Some(rest) = uri.strip_prefix("kaijutsu://blocks/") {
@@ -1941,10 +3635,15 @@ impl ServerHandler for KaijutsuMcp {
                     .read_block(found_ctx_id, &block_id)
                     .ok_or_else(|| McpError::invalid_params("Block not found", None))?;
 
-                return Ok(ReadResourceResult::new(vec![ResourceContents::text(
-                    snapshot.content.clone(),
-                    uri.clone(),
-                )]));
+                return Ok(reply(
+                    &snapshot.content,
+                    &resource_cache::content_stamp(&snapshot.content),
+                ));
+            }
+
+            if let Some(page) = self.result_guard.read(uri) {
+                let page = page.map_err(|e| McpError::invalid_params(e, None))?;
+                return Ok(reply(&page, &resource_cache::content_stamp(&page)));
             }
 
             Err(McpError::invalid_params(
@@ -1961,12 +3660,7 @@ impl ServerHandler for KaijutsuMcp {
         _context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<(), McpError>> + Send + '_ {
         async move {
-            let mut subs = self
-                .server_state
-                .subscriptions
-                .lock()
-                .map_err(|_| McpError::internal_error("Lock error", None))?;
-            subs.insert(request.uri);
+            self.server_state.subscriptions.lock().insert(request.uri);
             Ok(())
         }
     }
@@ -1978,12 +3672,7 @@ impl ServerHandler for KaijutsuMcp {
         _context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<(), McpError>> + Send + '_ {
         async move {
-            let mut subs = self
-                .server_state
-                .subscriptions
-                .lock()
-                .map_err(|_| McpError::internal_error("Lock error", None))?;
-            subs.remove(&request.uri);
+            self.server_state.subscriptions.lock().remove(&request.uri);
             Ok(())
         }
     }
@@ -2032,6 +3721,18 @@ impl ServerHandler for KaijutsuMcp {
                                 Vec::new()
                             }
                         }
+                        "summarize_for_pr" => {
+                            if request.argument.name == "document_id" {
+                                self.context_ids()
+                                    .into_iter()
+                                    .map(|id| id.to_hex())
+                                    .filter(|id| id.contains(&request.argument.value))
+                                    .take(10)
+                                    .collect()
+                            } else {
+                                Vec::new()
+                            }
+                        }
                         "search_context" => {
                             if request.argument.name == "document_id" {
                                 self.context_ids()
@@ -2050,15 +3751,25 @@ impl ServerHandler for KaijutsuMcp {
                 rmcp::model::Reference::Resource(resource_ref) => {
                     // Complete resource URIs
                     let prefix = &resource_ref.uri;
-                    if prefix.starts_with("kaijutsu://docs") {
-                        self.context_ids()
+                    let uri_for: Option<fn(ContextId) -> String> =
+                        if prefix.starts_with("kaijutsu://docs") {
+                            Some(|id| format!("kaijutsu://docs/{}", id.to_hex()))
+                        } else if prefix.starts_with("kaijutsu://tree") {
+                            Some(|id| format!("kaijutsu://tree/{}", id.to_hex()))
+                        } else if prefix.starts_with("kaijutsu://context") {
+                            Some(|id| format!("kaijutsu://context/{}", id.short()))
+                        } else {
+                            None
+                        };
+                    match uri_for {
+                        Some(uri_for) => self
+                            .context_ids()
                             .into_iter()
-                            .map(|id| format!("kaijutsu://docs/{}", id.to_hex()))
+                            .map(uri_for)
                             .filter(|uri| uri.contains(&request.argument.value))
                             .take(10)
-                            .collect()
-                    } else {
-                        Vec::new()
+                            .collect(),
+                        None => Vec::new(),
                     }
                 }
             };
@@ -2073,6 +3784,17 @@ impl ServerHandler for KaijutsuMcp {
     // Logging
     // ========================================================================
 
+    /// Capture the client peer so the log bridge can push
+    /// `notifications/message` to it.
+    fn on_initialized(
+        &self,
+        context: NotificationContext<RoleServer>,
+    ) -> impl std::future::Future<Output = ()> + Send + '_ {
+        async move {
+            *self.server_state.peer.lock() = Some(context.peer);
+        }
+    }
+
     /// Set the logging level.
     fn set_level(
         &self,
@@ -2080,12 +3802,7 @@ impl ServerHandler for KaijutsuMcp {
         _context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<(), McpError>> + Send + '_ {
         async move {
-            let mut level = self
-                .server_state
-                .log_level
-                .lock()
-                .map_err(|_| McpError::internal_error("Lock error", None))?;
-            *level = request.level;
+            *self.server_state.log_level.lock() = request.level;
             tracing::info!("Log level set to {:?}", request.level);
             Ok(())
         }
@@ -2112,6 +3829,78 @@ impl ServerHandler for KaijutsuMcp {
     }
 }
 
+/// A resource template entry for `list_resource_templates`.
+fn resource_template(
+    uri_template: &str,
+    name: &str,
+    description: &str,
+    mime_type: &str,
+) -> ResourceTemplate {
+    RawResourceTemplate {
+        uri_template: uri_template.to_string(),
+        name: name.to_string(),
+        title: None,
+        description: Some(description.to_string()),
+        mime_type: Some(mime_type.to_string()),
+        icons: None,
+    }
+    .no_annotation()
+}
+
+/// Render an actor failure as a tool reply: `Error [<code>]: <what>: <detail>`.
+/// The code is [`CallError::code`] — stable, so an agent can branch on it
+/// instead of matching text — with `, retryable` appended when the same call
+/// may succeed if tried again.
+/// Poll a broadcast until it finishes or `timeout_secs` (capped at 600)
+/// passes, then report it.
+async fn await_broadcast(
+    actor: &ActorHandle,
+    mut status: kaijutsu_types::BroadcastStatus,
+    timeout_secs: u64,
+) -> String {
+    let deadline =
+        std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs.min(600));
+    while !status.is_finished() && std::time::Instant::now() < deadline {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        status = match actor.get_broadcast(status.id).await {
+            Ok(status) => status,
+            Err(e) => return call_error_text("broadcast_status", &e),
+        };
+    }
+    broadcast_json(&status)
+}
+
+/// Broadcast progress as tool output.
+fn broadcast_json(status: &kaijutsu_types::BroadcastStatus) -> String {
+    let targets: Vec<_> = status
+        .targets
+        .iter()
+        .map(|t| {
+            serde_json::json!({
+                "context_id": t.context_id.short(),
+                "model": t.model,
+                "state": t.state.as_str(),
+                "response_block": t.response_block.map(|b| b.to_key()),
+                "error": t.error,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "id": status.id,
+        "comparison_context_id": status.comparison_context_id.short(),
+        "finished": status.is_finished(),
+        "done": status.count(kaijutsu_types::BroadcastState::Done),
+        "failed": status.count(kaijutsu_types::BroadcastState::Failed),
+        "targets": targets,
+    })
+    .to_string()
+}
+
+fn call_error_text(what: &str, e: &CallError) -> String {
+    let retryable = if e.is_retryable() { ", retryable" } else { "" };
+    format!("Error [{}{retryable}]: {what}: {e}", e.code())
+}
+
 /// Normalize peer-invocation `params` before serializing to the wire bytes the
 /// peer's `dispatch_peer_action` deserializes.
 ///
@@ -2134,9 +3923,76 @@ fn normalize_peer_params(params: &serde_json::Value) -> serde_json::Value {
     params.clone()
 }
 
+/// Translate a `dag_query` request into a [`DagQuery`], naming blocks through
+/// `resolve`.
+fn build_dag_query(
+    req: &DagQueryRequest,
+    resolve: impl Fn(&str) -> Result<BlockId, String>,
+) -> Result<DagQuery, String> {
+    let block = |field: &str, value: Option<&str>| -> Result<BlockId, String> {
+        let s = value.ok_or_else(|| format!("{field} is required for op '{}'", req.op.as_str()))?;
+        resolve(s)
+    };
+    let selection = match req.op {
+        DagOp::All => DagSelection::All,
+        DagOp::Ancestors => DagSelection::Ancestors(block("block_id", req.block_id.as_deref())?),
+        DagOp::Descendants => {
+            DagSelection::Descendants(block("block_id", req.block_id.as_deref())?)
+        }
+        DagOp::Path => DagSelection::Path {
+            from: block("block_id", req.block_id.as_deref())?,
+            to: block("to_block_id", req.to_block_id.as_deref())?,
+        },
+        DagOp::Subtree => DagSelection::Subtree {
+            root: block("block_id", req.block_id.as_deref())?,
+            max_depth: req.max_depth,
+        },
+    };
+
+    Ok(DagQuery::new(selection).with_filter(BlockFilter {
+        kinds: req.kinds.iter().map(|&k| k.into()).collect(),
+        roles: req.roles.iter().map(|&r| r.into()).collect(),
+        limit: req.limit.unwrap_or(0),
+        ..Default::default()
+    }))
+}
+
+/// JSON shape shared by `mcp_server_register` and `mcp_server_list`.
+fn mcp_server_json(
+    ctx_id: ContextId,
+    server: &kaijutsu_client::ContextMcpServerInfo,
+) -> serde_json::Value {
+    let tools: Vec<_> = server
+        .tools
+        .iter()
+        .map(|t| serde_json::json!({ "name": t.name, "description": t.description }))
+        .collect();
+    serde_json::json!({
+        "context_id": ctx_id.short(),
+        "instance": server.instance,
+        "fork_mode": server.fork_mode,
+        "health": server.health,
+        "health_reason": (!server.health_reason.is_empty()).then_some(&server.health_reason),
+        "tools": tools,
+    })
+}
+
+/// JSON shape shared by `bookmark_create` and `bookmark_list`.
+fn bookmark_json(bookmark: &kaijutsu_types::DocBookmark) -> serde_json::Value {
+    serde_json::json!({
+        "name": bookmark.name,
+        "context_id": bookmark.context_id.short(),
+        "version": bookmark.version,
+        "block_count": bookmark.block_count,
+        "created_by": bookmark.created_by.short(),
+        "created_at": bookmark.created_at,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use kaijutsu_crdt::Role;
 
     /// The bug: an object param arrives double-encoded as a JSON string. We must
     /// unwrap exactly one layer so the peer receives an object, not a string.
@@ -2172,6 +4028,18 @@ mod tests {
         assert_eq!(normalize_peer_params(&n), n);
     }
 
+    /// Actor failures lead with a stable code an agent can branch on.
+    #[test]
+    fn call_error_text_leads_with_code() {
+        let denied = CallError::from_rpc_message("Failed: shell denied: no exec");
+        assert_eq!(
+            call_error_text("invoke_peer", &denied),
+            "Error [permission_denied]: invoke_peer: permission denied: Failed: shell denied: no exec"
+        );
+        let timeout = CallError::Timeout(std::time::Duration::from_secs(30));
+        assert!(call_error_text("read_input", &timeout).starts_with("Error [timeout, retryable]:"));
+    }
+
     use kaijutsu_crdt::ContextId;
 
     // =========================================================================
@@ -2345,6 +4213,225 @@ mod tests {
         );
     }
 
+    // =========================================================================
+    // Context Statistics (Local mode)
+    // =========================================================================
+
+    #[tokio::test]
+    async fn test_context_info_local_tallies_blocks() {
+        use kaijutsu_crdt::{BlockKind, ContentType, Role, Status};
+        use kaijutsu_types::DocKind;
+
+        let store = shared_block_store(PrincipalId::new());
+        let ctx_id = ContextId::new();
+        store.create_document(ctx_id, DocKind::Conversation, None).unwrap();
+        let first = store
+            .insert_block(
+                ctx_id, None, None, Role::User, BlockKind::Text, "hello",
+                Status::Done, ContentType::Plain,
+            )
+            .unwrap();
+        store
+            .insert_block(
+                ctx_id, None, Some(&first), Role::Model, BlockKind::Text, "hi",
+                Status::Done, ContentType::Plain,
+            )
+            .unwrap();
+
+        let mcp = KaijutsuMcp::with_store(store);
+        let result = mcp
+            .context_info(Parameters(ContextInfoRequest {
+                context_id: Some(ctx_id.to_hex()),
+            }))
+            .await;
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["block_count"].as_u64(), Some(2));
+        assert_eq!(parsed["total_bytes"].as_u64(), Some(7));
+        assert_eq!(parsed["by_role"]["user"].as_u64(), Some(1));
+        assert_eq!(parsed["by_role"]["model"].as_u64(), Some(1));
+        assert_eq!(parsed["context_short"].as_str(), Some(ctx_id.short().as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_context_info_local_unknown_context() {
+        let mcp = KaijutsuMcp::new();
+        let result = mcp
+            .context_info(Parameters(ContextInfoRequest {
+                context_id: Some(ContextId::new().to_hex()),
+            }))
+            .await;
+        assert!(result.contains("not found"), "unknown context should error: {result}");
+    }
+
+    #[tokio::test]
+    async fn test_block_reorder_local_moves_among_siblings() {
+        use kaijutsu_crdt::{BlockKind, ContentType, Role, Status};
+        use kaijutsu_types::DocKind;
+
+        let store = shared_block_store(PrincipalId::new());
+        let ctx_id = ContextId::new();
+        store.create_document(ctx_id, DocKind::Conversation, None).unwrap();
+        let parent = store
+            .insert_block(
+                ctx_id, None, None, Role::User, BlockKind::Text, "question",
+                Status::Done, ContentType::Plain,
+            )
+            .unwrap();
+        let a = store
+            .insert_block(
+                ctx_id, Some(&parent), Some(&parent), Role::Model, BlockKind::Text, "a",
+                Status::Done, ContentType::Plain,
+            )
+            .unwrap();
+        let b = store
+            .insert_block(
+                ctx_id, Some(&parent), Some(&a), Role::Model, BlockKind::Text, "b",
+                Status::Done, ContentType::Plain,
+            )
+            .unwrap();
+
+        let mcp = KaijutsuMcp::with_store(store.clone());
+        let result = mcp
+            .block_reorder(Parameters(BlockReorderRequest {
+                block_id: b.to_key(),
+                after_id: None,
+            }))
+            .await;
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["success"], true);
+        let order: Vec<_> = store
+            .block_snapshots(ctx_id)
+            .unwrap()
+            .into_iter()
+            .map(|snap| snap.id)
+            .collect();
+        assert_eq!(order, vec![parent, b, a]);
+
+        // The parent isn't a sibling — reorder refuses to reparent.
+        let result = mcp
+            .block_reorder(Parameters(BlockReorderRequest {
+                block_id: a.to_key(),
+                after_id: Some(parent.to_key()),
+            }))
+            .await;
+        assert!(result.starts_with("Error:"), "non-sibling anchor should error: {result}");
+    }
+
+    #[tokio::test]
+    async fn test_dag_query_local_path_and_filter() {
+        use kaijutsu_crdt::{ContentType, Status};
+        use kaijutsu_types::DocKind;
+
+        let store = shared_block_store(PrincipalId::new());
+        let ctx_id = ContextId::new();
+        store.create_document(ctx_id, DocKind::Conversation, None).unwrap();
+        let question = store
+            .insert_block(
+                ctx_id, None, None, Role::User, BlockKind::Text, "question",
+                Status::Done, ContentType::Plain,
+            )
+            .unwrap();
+        let thinking = store
+            .insert_block(
+                ctx_id, Some(&question), Some(&question), Role::Model, BlockKind::Thinking,
+                "hmm", Status::Done, ContentType::Plain,
+            )
+            .unwrap();
+        let answer = store
+            .insert_block(
+                ctx_id, Some(&question), Some(&thinking), Role::Model, BlockKind::Text,
+                "answer", Status::Done, ContentType::Plain,
+            )
+            .unwrap();
+
+        let request = |op: DagOp| DagQueryRequest {
+            op,
+            block_id: None,
+            to_block_id: None,
+            max_depth: None,
+            kinds: Vec::new(),
+            roles: Vec::new(),
+            limit: None,
+            max_content_chars: None,
+            context_id: Some(ctx_id.to_hex()),
+        };
+        let ids = |result: &str| -> Vec<String> {
+            let parsed: serde_json::Value = serde_json::from_str(result).unwrap();
+            parsed["blocks"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|b| b["block_id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let mcp = KaijutsuMcp::with_store(store);
+        let result = mcp
+            .dag_query(Parameters(DagQueryRequest {
+                block_id: Some(thinking.to_key()),
+                to_block_id: Some(answer.to_key()),
+                ..request(DagOp::Path)
+            }))
+            .await;
+        assert_eq!(
+            ids(&result),
+            vec![thinking.to_key(), question.to_key(), answer.to_key()]
+        );
+
+        let result = mcp
+            .dag_query(Parameters(DagQueryRequest {
+                kinds: vec![BlockKindName::Text],
+                roles: vec![RoleName::Model],
+                ..request(DagOp::All)
+            }))
+            .await;
+        assert_eq!(ids(&result), vec![answer.to_key()]);
+
+        let result = mcp.dag_query(Parameters(request(DagOp::Ancestors))).await;
+        assert!(result.starts_with("Error:"), "ancestors needs block_id: {result}");
+    }
+
+    #[test]
+    fn choice_fields_coerce_and_name_valid_values() {
+        let req: DagQueryRequest = serde_json::from_value(serde_json::json!({
+            "op": " Subtree ",
+            "kinds": ["Tool-Call", "toolresult"],
+            "roles": ["assistant"],
+        }))
+        .unwrap();
+        assert_eq!(req.op, DagOp::Subtree);
+        assert_eq!(
+            req.kinds,
+            vec![BlockKindName::ToolCall, BlockKindName::ToolResult]
+        );
+        assert_eq!(req.roles, vec![RoleName::Model]);
+
+        let err = serde_json::from_value::<DagQueryRequest>(serde_json::json!({"op": "sideways"}))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("invalid op 'sideways' (expected one of: all, ancestors, descendants, path, subtree)"),
+            "{err}"
+        );
+
+        let schema =
+            serde_json::to_string(&schemars::schema_for!(McpServerRegisterRequest)).unwrap();
+        assert!(schema.contains(r#""stdio","http""#), "{schema}");
+        assert!(schema.contains(r#""inherit","exclude""#), "{schema}");
+    }
+
+    #[test]
+    fn choice_names_match_the_types_they_stand_for() {
+        for &value in BlockKindName::VALUES {
+            let kind: BlockKind = value.parse::<BlockKindName>().unwrap().into();
+            assert_eq!(kind.as_str(), value);
+        }
+        for &value in RoleName::VALUES {
+            let role: Role = value.parse::<RoleName>().unwrap().into();
+            assert_eq!(role.as_str(), value);
+        }
+    }
+
     // ========================================================================
     // ShellCompletion JSON envelope
     //
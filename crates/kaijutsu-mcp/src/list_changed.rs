@@ -0,0 +1,157 @@
+//! Tell the MCP client when its cached tool and resource lists go stale.
+//!
+//! The resource list has one entry per resident document (see
+//! `list_resources`), so it moves whenever a document appears or goes away —
+//! a `kj doc`/`kj context` command run through a tool, joining a context, or
+//! a server-side context switch. The documents the client last listed are
+//! remembered in [`McpServerState`]; after every tool call the resident set is
+//! diffed against them, and [`spawn_list_changed_bridge`] watches the
+//! joined context's event stream for switches and reconnects. Either way the
+//! client gets one `notifications/resources/list_changed` and nothing more
+//! until it lists again.
+//!
+//! The MCP tool list itself is fixed, but the kernel tools behind
+//! `kaish_exec`/`list_kernel_tools` change when a context-scoped MCP server
+//! is attached or detached; those calls send `notifications/tools/list_changed`.
+
+use std::collections::BTreeSet;
+
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use kaijutsu_client::{ActorHandle, ServerEvent};
+use kaijutsu_crdt::ContextId;
+
+use crate::McpServerState;
+
+impl McpServerState {
+    /// Remember the documents a `resources/list` answer covered.
+    pub(crate) fn record_listed_docs(&self, docs: impl IntoIterator<Item = ContextId>) {
+        *self.listed_docs.lock() = Some(docs.into_iter().collect());
+    }
+
+    /// Notify the client if `resident` differs from the documents it last
+    /// listed.
+    pub(crate) async fn refresh_resource_list(
+        &self,
+        resident: impl IntoIterator<Item = ContextId>,
+    ) {
+        let resident: BTreeSet<ContextId> = resident.into_iter().collect();
+        let stale = {
+            let mut listed = self.listed_docs.lock();
+            let stale = listed.as_ref().is_some_and(|docs| *docs != resident);
+            if stale {
+                *listed = None;
+            }
+            stale
+        };
+        if stale {
+            self.notify_resource_list_changed().await;
+        }
+    }
+
+    /// Notify the client that its resource list is stale, unless it has not
+    /// listed since the last notification.
+    pub(crate) async fn invalidate_resource_list(&self) {
+        if self.listed_docs.lock().take().is_some() {
+            self.notify_resource_list_changed().await;
+        }
+    }
+
+    async fn notify_resource_list_changed(&self) {
+        let peer = self.peer.lock().clone();
+        if let Some(peer) = peer
+            && let Err(e) = peer.notify_resource_list_changed().await
+        {
+            tracing::debug!("resources/list_changed notify failed: {e}");
+        }
+    }
+
+    /// Notify the client that the tools it can reach changed.
+    pub(crate) async fn notify_tool_list_changed(&self) {
+        let peer = self.peer.lock().clone();
+        if let Some(peer) = peer
+            && let Err(e) = peer.notify_tool_list_changed().await
+        {
+            tracing::debug!("tools/list_changed notify failed: {e}");
+        }
+    }
+}
+
+/// Watch the actor's event stream for changes to the resident document set
+/// and invalidate the client's resource list on each.
+pub(crate) fn spawn_list_changed_bridge(
+    actor: ActorHandle,
+    state: McpServerState,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut event_rx = actor.subscribe_events();
+        loop {
+            match event_rx.recv().await {
+                Ok(event) if moves_resident_docs(&event) => {
+                    state.invalidate_resource_list().await;
+                }
+                Ok(_) => continue,
+                // A lagged stream may have skipped a switch; assume it did.
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    state.invalidate_resource_list().await;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+/// Whether an event can change which documents are resident: a server-side
+/// context switch, or a reconnect (documents may have come and gone during
+/// the outage).
+fn moves_resident_docs(event: &ServerEvent) -> bool {
+    matches!(
+        event,
+        ServerEvent::ContextSwitched { .. } | ServerEvent::Reconnected
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn resource_list_goes_stale_once_per_listing() {
+        let state = McpServerState::default();
+        let a = ContextId::new();
+        let b = ContextId::new();
+
+        // Never listed: nothing to invalidate.
+        state.refresh_resource_list([a]).await;
+        assert!(state.listed_docs.lock().is_none());
+
+        state.record_listed_docs([a]);
+        state.refresh_resource_list([a]).await;
+        assert!(
+            state.listed_docs.lock().is_some(),
+            "unchanged set stays fresh"
+        );
+
+        state.refresh_resource_list([a, b]).await;
+        assert!(
+            state.listed_docs.lock().is_none(),
+            "a new document invalidates"
+        );
+
+        state.record_listed_docs([a, b]);
+        state.invalidate_resource_list().await;
+        assert!(state.listed_docs.lock().is_none());
+    }
+
+    #[test]
+    fn only_switches_and_reconnects_move_resident_docs() {
+        assert!(moves_resident_docs(&ServerEvent::Reconnected));
+        assert!(moves_resident_docs(&ServerEvent::ContextSwitched {
+            context_id: ContextId::new(),
+        }));
+        assert!(!moves_resident_docs(&ServerEvent::InputCleared {
+            context_id: ContextId::new(),
+        }));
+    }
+}
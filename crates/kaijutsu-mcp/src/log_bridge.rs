@@ -0,0 +1,143 @@
+//! Forward kernel-side log notifications to the MCP client.
+//!
+//! The broker turns each `ServerNotification::Log` from an MCP instance into a
+//! `BlockKind::Notification` block in every bound context. In remote mode the
+//! joined context's block stream already reaches us through the actor, so the
+//! bridge just watches it for `Log` payloads and re-emits them as MCP
+//! `notifications/message` — filtered by the level the client set via
+//! `logging/setLevel` (see [`McpServerState`]). This is what lets an agent
+//! like Claude Code show server-side tool execution logs inline.
+
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use kaijutsu_client::{ActorHandle, ServerEvent};
+use kaijutsu_crdt::{BlockKind, ContextId};
+use kaijutsu_types::{LogLevel, NotificationKind, NotificationPayload};
+
+use crate::McpServerState;
+
+/// Watch `context_id`'s inserted blocks and forward `Log` notifications to
+/// whichever MCP peer is attached to `state`. Nothing is sent before the
+/// client finishes `initialize` (no peer yet).
+pub(crate) fn spawn_log_bridge(
+    actor: ActorHandle,
+    context_id: ContextId,
+    state: McpServerState,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut event_rx = actor.subscribe_events();
+        loop {
+            let block = match event_rx.recv().await {
+                Ok(ServerEvent::BlockInserted {
+                    context_id: ctx,
+                    block,
+                    ..
+                }) if ctx == context_id => block,
+                Ok(_) => continue,
+                // Logs are best-effort: a lagged stream just loses lines.
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::debug!("log bridge: dropped {n} events");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            if block.kind != BlockKind::Notification {
+                continue;
+            }
+            let Some(payload) = block.notification.as_ref() else {
+                continue;
+            };
+            let min_level = *state.log_level.lock();
+            let Some(message) = logging_message(payload, min_level) else {
+                continue;
+            };
+            let peer = state.peer.lock().clone();
+            if let Some(peer) = peer
+                && let Err(e) = peer.notify_logging_message(message).await
+            {
+                tracing::debug!("log bridge: notify failed: {e}");
+            }
+        }
+    })
+}
+
+/// Build the MCP log message for a notification payload, or `None` if it is
+/// not a `Log` notification or falls below `min_level`.
+fn logging_message(
+    payload: &NotificationPayload,
+    min_level: LoggingLevel,
+) -> Option<LoggingMessageNotificationParam> {
+    if payload.kind != NotificationKind::Log {
+        return None;
+    }
+    let level = to_mcp_level(payload.level.unwrap_or_default());
+    if severity(level) < severity(min_level) {
+        return None;
+    }
+    let logger = match payload.tools.first() {
+        Some(tool) => format!("{}/{}", payload.instance, tool),
+        None => payload.instance.clone(),
+    };
+    let data = serde_json::Value::String(payload.detail.clone().unwrap_or_default());
+    Some(LoggingMessageNotificationParam::new(level, data).with_logger(logger))
+}
+
+/// Kernel levels have no `Notice`/`Critical`/…; `Trace` folds into `Debug`.
+fn to_mcp_level(level: LogLevel) -> LoggingLevel {
+    match level {
+        LogLevel::Trace | LogLevel::Debug => LoggingLevel::Debug,
+        LogLevel::Info => LoggingLevel::Info,
+        LogLevel::Warn => LoggingLevel::Warning,
+        LogLevel::Error => LoggingLevel::Error,
+    }
+}
+
+/// RFC 5424 ordering of MCP levels, least severe first.
+fn severity(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        // Critical / Alert / Emergency — always shown.
+        _ => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_payload(level: LogLevel, tool: Option<&str>) -> NotificationPayload {
+        NotificationPayload {
+            instance: "builtin.shell".into(),
+            kind: NotificationKind::Log,
+            level: Some(level),
+            tools: tool.into_iter().map(String::from).collect(),
+            count: None,
+            detail: Some("spawned kaish".into()),
+        }
+    }
+
+    #[test]
+    fn forwards_at_or_above_client_level() {
+        let msg = logging_message(&log_payload(LogLevel::Warn, Some("exec")), LoggingLevel::Info)
+            .expect("warn passes an info threshold");
+        assert_eq!(msg.level, LoggingLevel::Warning);
+        assert_eq!(msg.logger.as_deref(), Some("builtin.shell/exec"));
+        assert_eq!(msg.data, serde_json::json!("spawned kaish"));
+
+        assert!(logging_message(&log_payload(LogLevel::Debug, None), LoggingLevel::Info).is_none());
+        assert!(logging_message(&log_payload(LogLevel::Trace, None), LoggingLevel::Debug).is_some());
+    }
+
+    #[test]
+    fn skips_non_log_notifications() {
+        let mut payload = log_payload(LogLevel::Error, None);
+        payload.kind = NotificationKind::ToolAdded;
+        assert!(logging_message(&payload, LoggingLevel::Debug).is_none());
+    }
+}
@@ -7,15 +7,32 @@
 //!   # MCP stdio server (default when no subcommand given)
 //!   cargo run -p kaijutsu-mcp
 //!   cargo run -p kaijutsu-mcp -- serve --connect
+//!   cargo run -p kaijutsu-mcp -- serve --persist ~/.local/share/kaijutsu/mcp.db
+//!   cargo run -p kaijutsu-mcp -- serve --restore-last
 //!
 //!   # One-shot hook client — reads stdin, sends to daemon socket
 //!   cargo run -p kaijutsu-mcp -- hook
 //!   cargo run -p kaijutsu-mcp -- hook --socket /tmp/kj-hook.sock
 //!
+//!   # Diagnose "it doesn't connect" — agent, server, auth, schema, hooks
+//!   cargo run -p kaijutsu-mcp -- doctor
+//!   cargo run -p kaijutsu-mcp -- doctor --host build-box --json
+//!
+//!   # The tool surface as JSON; fail on breaking changes against a baseline
+//!   cargo run -p kaijutsu-mcp -- schema > tools.json
+//!   cargo run -p kaijutsu-mcp -- schema --check tools.json
+//!
 //! ## Backward Compatibility
 //!
 //! The old flags (`--connect`, `--host`, `--port`, etc.) still work when no
 //! subcommand is specified — they default to `serve`.
+//!
+//! ## Autosave
+//!
+//! Without `--connect` or `--persist` the store is in memory only, so a
+//! graceful shutdown exports every document (Markdown + oplog, see
+//! [`kaijutsu_mcp::autosave`]) to a timestamped directory under
+//! `--autosave-dir`; `--restore-last` loads the newest one back at startup.
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -25,11 +42,16 @@ use clap::{Args, Parser, Subcommand};
 use rmcp::{ServiceExt, transport::stdio};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
+use kaijutsu_kernel::SharedBlockStore;
 use kaijutsu_mcp::KaijutsuMcp;
+use kaijutsu_mcp::autosave;
+use kaijutsu_mcp::doctor::DoctorOptions;
+use kaijutsu_mcp::event_hooks::{self, EventHookConfig};
 use kaijutsu_mcp::hook_listener::{
     HookListener, PING_TIMEOUT, candidate_sockets, default_socket_path, resolve_hook_socket,
     send_hook_event, sweep_stale_sockets,
 };
+use kaijutsu_types::AgentProvenance;
 
 /// MCP server exposing kaijutsu CRDT kernel.
 #[derive(Parser, Debug)]
@@ -51,6 +73,13 @@ enum Command {
     Serve(ServeArgs),
     /// One-shot hook client: reads stdin JSON, sends to daemon socket, prints response.
     Hook(HookArgs),
+    /// Check ssh-agent, server, auth, kernel, context, schema, hook socket,
+    /// and MCP stdio; print a report and exit non-zero if any check failed.
+    Doctor(DoctorArgs),
+    /// Print every tool's name, description, annotations and JSON schemas;
+    /// with --check, compare against a baseline and exit non-zero on a
+    /// breaking change.
+    Schema(SchemaArgs),
 }
 
 /// Connection and server arguments — shared shape for top-level + serve subcommand.
@@ -80,6 +109,51 @@ struct ServeArgs {
     /// Default: $XDG_RUNTIME_DIR/kaijutsu/hook-{ppid}.sock
     #[arg(long)]
     hook_socket: Option<PathBuf>,
+
+    /// Journal the local store to this SQLite file and replay it at startup.
+    /// Tool calls are acknowledged only after their ops are written.
+    #[arg(long, conflicts_with = "connect")]
+    persist: Option<PathBuf>,
+
+    /// How hard --persist syncs each write: full (fsync every commit),
+    /// normal (survives a process crash, not power loss), or off
+    #[arg(long, default_value = "full", requires = "persist")]
+    fsync: kaijutsu_kernel::SyncPolicy,
+
+    /// With --persist, cut oplogs that won't replay at the first bad entry
+    /// instead of leaving those documents unloaded
+    #[arg(long, requires = "persist")]
+    repair: bool,
+
+    /// Where the in-memory store is exported on shutdown, one timestamped
+    /// directory per session.
+    /// Default: ~/.local/share/kaijutsu/mcp-autosave
+    #[arg(long, conflicts_with_all = ["connect", "persist"])]
+    autosave_dir: Option<PathBuf>,
+
+    /// Don't export the in-memory store on shutdown
+    #[arg(long, conflicts_with_all = ["connect", "persist"])]
+    no_autosave: bool,
+
+    /// Load the newest autosave export into the in-memory store at startup
+    #[arg(long, conflicts_with_all = ["connect", "persist"])]
+    restore_last: bool,
+
+    /// Local commands to run on block events in the joined context (JSON;
+    /// --connect only).
+    /// Default: ~/.config/kaijutsu/event-hooks.json, if present
+    #[arg(long)]
+    event_hooks: Option<PathBuf>,
+
+    /// Tool results larger than this many bytes come back as a preview plus a
+    /// kaijutsu://results/{id} resource holding the full text (0 = no limit)
+    #[arg(long, default_value_t = kaijutsu_mcp::result_guard::DEFAULT_MAX_RESULT_BYTES)]
+    max_result_bytes: usize,
+
+    /// Refuse to start if the tool surface changed incompatibly since the
+    /// last start (otherwise each break is logged)
+    #[arg(long)]
+    strict_schema: bool,
 }
 
 /// Hook client arguments.
@@ -91,6 +165,39 @@ struct HookArgs {
     socket: Option<PathBuf>,
 }
 
+/// Schema arguments.
+#[derive(Args, Debug)]
+struct SchemaArgs {
+    /// Baseline surface (an earlier `schema` output) to check this build against
+    #[arg(long)]
+    check: Option<PathBuf>,
+}
+
+/// Doctor arguments — the `serve --connect` target to diagnose.
+#[derive(Args, Debug)]
+struct DoctorArgs {
+    /// SSH host to check
+    #[arg(long, default_value = "localhost")]
+    host: String,
+
+    /// SSH port to check
+    #[arg(long, default_value_t = 2222)]
+    port: u16,
+
+    /// Context name expected within the kernel
+    #[arg(long, default_value = "default")]
+    context_name: String,
+
+    /// Hook socket to check.
+    /// Default: $XDG_RUNTIME_DIR/kaijutsu/hook-{ppid}.sock, plus discovery
+    #[arg(long)]
+    hook_socket: Option<PathBuf>,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize tracing to stderr (MCP uses stdio for protocol)
@@ -113,12 +220,31 @@ async fn main() -> Result<()> {
     match cli.command {
         Some(Command::Hook(args)) => run_hook_client(args).await,
         Some(Command::Serve(args)) => run_serve(args).await,
+        Some(Command::Doctor(args)) => run_doctor(args).await,
+        Some(Command::Schema(args)) => run_schema(args),
         None => run_serve(cli.serve).await,
     }
 }
 
+/// The detected agent as block provenance. The project dir falls back to
+/// our cwd — the agent spawned us there.
+fn agent_provenance(agent: &dyn kaijutsu_agent_tools::AgentSession) -> AgentProvenance {
+    let project_dir = agent
+        .project_dir()
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::current_dir().ok());
+    AgentProvenance {
+        agent: agent.agent_name().to_string(),
+        version: agent.version().map(String::from),
+        session_id: agent.session_id().map(String::from),
+        project_dir: project_dir.map(|dir| dir.display().to_string()),
+    }
+}
+
 /// MCP stdio server + hook socket listener.
 async fn run_serve(args: ServeArgs) -> Result<()> {
+    check_tool_surface(args.strict_schema)?;
+
     // Detect hosting agent (Claude Code, etc.)
     let agent = kaijutsu_agent_tools::detect();
     if let Some(ref a) = agent {
@@ -136,6 +262,9 @@ async fn run_serve(args: ServeArgs) -> Result<()> {
         .as_ref()
         .and_then(|a| a.session_id().map(String::from));
 
+    // Stamped on every block the hook listener mirrors.
+    let agent_provenance = agent.as_deref().map(agent_provenance);
+
     // Cap'n Proto RPC requires LocalSet for !Send types
     let local_set = tokio::task::LocalSet::new();
     local_set.run_until(async {
@@ -144,6 +273,10 @@ async fn run_serve(args: ServeArgs) -> Result<()> {
         // the label needs a rename once a hook event tells us the session
         // id (HookListener::remote, session.start handling).
         let mut pending_label_rename: Option<String> = None;
+        let autosave_root = args
+            .autosave_dir
+            .clone()
+            .unwrap_or_else(autosave::default_root);
 
         let mcp = if args.connect {
             tracing::info!(
@@ -210,11 +343,47 @@ async fn run_serve(args: ServeArgs) -> Result<()> {
             }
 
             mcp
+        } else if let Some(path) = &args.persist {
+            tracing::info!(path = %path.display(), fsync = %args.fsync, "Starting with journaled store");
+            KaijutsuMcp::persistent(path, args.fsync, args.repair)?
         } else {
             tracing::info!("Starting with in-memory store");
-            KaijutsuMcp::new()
+            let mcp = KaijutsuMcp::new();
+            if args.restore_last
+                && let kaijutsu_mcp::Backend::Local(store) = mcp.backend()
+            {
+                restore_last_autosave(store, &autosave_root)?;
+            }
+            mcp
+        };
+        let mcp = mcp.with_max_result_bytes(args.max_result_bytes);
+
+        // In-memory mode: keep a handle on the store to export it once the
+        // session ends (serving consumes `mcp`).
+        let autosave_store = match mcp.backend() {
+            kaijutsu_mcp::Backend::Local(store)
+                if args.persist.is_none() && !args.no_autosave =>
+            {
+                Some(store.clone())
+            }
+            _ => None,
         };
 
+        // Event hooks ride the server's block stream, so only a connected
+        // session runs them.
+        match mcp.backend() {
+            kaijutsu_mcp::Backend::Remote(remote) => {
+                if let Some(config) = load_event_hooks(args.event_hooks.as_deref())? {
+                    tracing::info!(hooks = config.hooks.len(), "Event hooks registered");
+                    event_hooks::spawn_event_hooks(remote.clone(), config, agent_provenance.clone());
+                }
+            }
+            kaijutsu_mcp::Backend::Local(_) if args.event_hooks.is_some() => {
+                tracing::warn!("--event-hooks needs --connect — event hooks disabled");
+            }
+            kaijutsu_mcp::Backend::Local(_) => {}
+        }
+
         // Start hook socket listener as a background task
         let socket_path = args.hook_socket.or_else(default_socket_path);
         let Some(socket_path) = socket_path else {
@@ -227,8 +396,12 @@ async fn run_serve(args: ServeArgs) -> Result<()> {
                     tracing::error!("MCP server error: {:?}", e);
                 })?;
             tracing::info!("kaijutsu-mcp server ready (no hook socket)");
-            service.waiting().await?;
+            let quit = service.waiting().await;
             tracing::info!("kaijutsu-mcp server shutting down");
+            if let Some(store) = &autosave_store {
+                autosave_on_shutdown(store, &autosave_root);
+            }
+            quit?;
             return Ok(());
         };
 
@@ -251,12 +424,16 @@ async fn run_serve(args: ServeArgs) -> Result<()> {
             }
             kaijutsu_mcp::Backend::Remote(remote) => {
                 // shared_context_id is updated by register_session when a context is joined
-                Arc::new(HookListener::remote(
+                let listener = HookListener::remote(
                     remote.clone(),
                     Arc::clone(&remote.shared_context_id),
                     Arc::clone(mcp.session_id_arc()),
                     pending_label_rename.clone(),
-                ))
+                );
+                Arc::new(match agent_provenance.clone() {
+                    Some(agent) => listener.with_agent(agent),
+                    None => listener,
+                })
             }
         };
 
@@ -280,16 +457,108 @@ async fn run_serve(args: ServeArgs) -> Result<()> {
         tracing::info!("kaijutsu-mcp server ready");
 
         // Wait for the service to complete
-        service.waiting().await?;
+        let quit = service.waiting().await;
 
         // Cleanup socket on exit
         let _ = tokio::fs::remove_file(&socket_path).await;
 
         tracing::info!("kaijutsu-mcp server shutting down");
+        if let Some(store) = &autosave_store {
+            autosave_on_shutdown(store, &autosave_root);
+        }
+        quit?;
         Ok(())
     }).await
 }
 
+/// The event hooks to register: `path`, or the default file if it exists.
+/// A file that doesn't parse fails startup rather than silently running
+/// without its hooks.
+fn load_event_hooks(path: Option<&Path>) -> Result<Option<EventHookConfig>> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match event_hooks::default_path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(None),
+        },
+    };
+    let config = EventHookConfig::load(&path)?;
+    Ok((!config.hooks.is_empty()).then_some(config))
+}
+
+/// Run every doctor check and print the report. Exits 1 if any check failed.
+async fn run_doctor(args: DoctorArgs) -> Result<()> {
+    let opts = DoctorOptions {
+        host: args.host,
+        port: args.port,
+        context_name: args.context_name,
+        hook_socket: args.hook_socket,
+    };
+    // The RPC probes are !Send, like the serve path.
+    let report = tokio::task::LocalSet::new()
+        .run_until(kaijutsu_mcp::doctor::run(&opts))
+        .await;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.render());
+    }
+    if !report.ok() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Print the tool surface, or check it against `--check`. Exits 1 on a
+/// breaking change.
+fn run_schema(args: SchemaArgs) -> Result<()> {
+    let current = kaijutsu_mcp::schema::surface();
+    let Some(path) = args.check else {
+        println!("{}", serde_json::to_string_pretty(&current)?);
+        return Ok(());
+    };
+    let baseline: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    let breaks = kaijutsu_mcp::schema::check(&baseline, &current);
+    if breaks.is_empty() {
+        println!("tool surface compatible with {}", path.display());
+        return Ok(());
+    }
+    for b in &breaks {
+        eprintln!("incompatible: {b}");
+    }
+    std::process::exit(1);
+}
+
+/// Compare the tool surface with the one the last start recorded, then
+/// record this one. A break is logged; with `strict` it fails startup and
+/// the old baseline stays, so every strict start fails until it is fixed.
+fn check_tool_surface(strict: bool) -> Result<()> {
+    use kaijutsu_mcp::schema;
+    let Some(path) = schema::default_record_path() else {
+        return Ok(());
+    };
+    let breaks = match schema::check_recorded(&path) {
+        Ok(breaks) => breaks,
+        Err(e) => {
+            tracing::warn!("tool surface check skipped: {e:#}");
+            Vec::new()
+        }
+    };
+    for b in &breaks {
+        tracing::error!(tool = %b.tool, "incompatible tool schema change: {}", b.change);
+    }
+    if strict && !breaks.is_empty() {
+        anyhow::bail!(
+            "{} incompatible tool schema change(s) since the last start (--strict-schema)",
+            breaks.len()
+        );
+    }
+    if let Err(e) = schema::record(&path) {
+        tracing::warn!("recording the tool surface failed: {e:#}");
+    }
+    Ok(())
+}
+
 /// One-shot hook client: reads stdin, sends to socket, prints response.
 /// Fail-open: exits 0 if socket is unreachable, or if anything about the
 /// input/resolution is ambiguous.
@@ -363,6 +632,43 @@ async fn run_hook_client(args: HookArgs) -> Result<()> {
     Ok(())
 }
 
+/// Export the in-memory store to a fresh directory under `root`, then prune
+/// old exports. Failures are logged — shutdown goes on regardless.
+fn autosave_on_shutdown(store: &SharedBlockStore, root: &Path) {
+    let unix_secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // The pid keeps two sessions ending in the same second apart.
+    let dir = root.join(format!(
+        "{}-{}",
+        format_export_stamp(unix_secs),
+        std::process::id()
+    ));
+    match autosave::export_store(store, &dir) {
+        Ok(0) => tracing::info!("In-memory store is empty — nothing to autosave"),
+        Ok(documents) => {
+            tracing::info!(documents, dir = %dir.display(), "Autosaved in-memory store");
+            let pruned = autosave::prune_exports(root, autosave::AUTOSAVE_KEEP);
+            if pruned > 0 {
+                tracing::info!(pruned, "Pruned old autosave exports");
+            }
+        }
+        Err(e) => tracing::error!(dir = %dir.display(), "Autosave failed: {e:#}"),
+    }
+}
+
+/// `--restore-last`: load the newest export under `root` into `store`.
+fn restore_last_autosave(store: &SharedBlockStore, root: &Path) -> Result<()> {
+    let Some(dir) = autosave::latest_export(root) else {
+        tracing::warn!(root = %root.display(), "--restore-last: no autosave export found");
+        return Ok(());
+    };
+    let documents = autosave::restore_export(store, &dir)?;
+    tracing::info!(documents, dir = %dir.display(), "Restored autosave export");
+    Ok(())
+}
+
 /// Parse hook stdin as JSON and re-serialize compact (single line), also
 /// extracting `session_id` (if present) for socket resolution. `None` if
 /// `input` isn't valid JSON — the caller must never forward garbage.
@@ -403,6 +709,17 @@ fn format_stamp(unix_secs: u64) -> String {
     format!("{month:02}{day:02}-{hour:02}{minute:02}")
 }
 
+/// Format a Unix timestamp (seconds) as `YYYYMMDD-HHMMSS`, UTC — the
+/// autosave directory name, which sorts chronologically.
+fn format_export_stamp(unix_secs: u64) -> String {
+    let (year, month, day) = civil_from_days((unix_secs / 86400) as i64);
+    let secs_of_day = unix_secs % 86400;
+    let hour = secs_of_day / 3600;
+    let minute = (secs_of_day % 3600) / 60;
+    let second = secs_of_day % 60;
+    format!("{year:04}{month:02}{day:02}-{hour:02}{minute:02}{second:02}")
+}
+
 /// Civil (year, month, day) date from days-since-Unix-epoch. Howard
 /// Hinnant's `civil_from_days` algorithm (proleptic Gregorian) — avoids
 /// pulling in a full date/time crate for a once-at-startup label stamp.
@@ -458,6 +775,14 @@ mod tests {
         assert_eq!(format_stamp(1_234_567_890), "0213-2331");
     }
 
+    #[test]
+    fn format_export_stamp_sorts_by_time() {
+        // Ground truth via `date -u -d @<secs> +%Y%m%d-%H%M%S`.
+        assert_eq!(format_export_stamp(0), "19700101-000000");
+        assert_eq!(format_export_stamp(1_234_567_890), "20090213-233130");
+        assert!(format_export_stamp(1_234_567_890) < format_export_stamp(1_700_000_000));
+    }
+
     // -- auto_register_label (item 4) --
 
     #[test]
@@ -5,13 +5,202 @@
 //! - kaish_exec as the escape hatch into kernel tools
 //! - {read,write,edit,submit}_input for the shared input scratchpad
 //! - register_session, invoke_peer for peer/session concerns
+//! - context_info for aggregated context statistics
+//! - block_reorder for sibling-scoped block ordering
+//! - dag_query for extracting conversation slices from the block DAG
+//! - test_results for structured test runs recorded by `testreport`
+//! - mcp_server_{register,unregister,list} for context-scoped MCP servers
 //!
 //! The block_*, doc_*, kernel_search, and stage_commit request types
 //! were removed when their corresponding tools moved to `kj`.
+//!
+//! Fields with a closed set of values are enums declared with
+//! [`choice_enum!`]: the schema advertises the values, and a bad one fails
+//! at deserialization with the valid values listed, before a handler runs.
+
+use std::collections::HashMap;
+use std::str::FromStr;
 
+use kaijutsu_types::{BlockKind, Role};
 use rmcp::schemars;
 use serde::Deserialize;
 
+// ============================================================================
+// Closed-set fields
+// ============================================================================
+
+/// Declare a string-valued request field with a fixed set of values.
+///
+/// Input is coerced before matching: surrounding whitespace is trimmed, case
+/// is ignored and `-` reads as `_`, so `"Tool-Call"` is `tool_call`. Each
+/// variant may list aliases after its canonical value. The JSON schema is an
+/// inline `{"type": "string", "enum": [...]}` of the canonical values, and
+/// every enum rejects input with the same [`choice_error`] message.
+macro_rules! choice_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident ($what:literal) {
+            $($variant:ident = $value:literal $(| $alias:literal)*),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        $vis enum $name {
+            $($variant),+
+        }
+
+        impl $name {
+            /// Canonical values, as advertised in the schema.
+            pub const VALUES: &'static [&'static str] = &[$($value),+];
+
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $value),+
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let wanted = normalize_choice(s);
+                $(
+                    if [$value $(, $alias)*].iter().any(|v| normalize_choice(v) == wanted) {
+                        return Ok(Self::$variant);
+                    }
+                )+
+                Err(choice_error($what, s, Self::VALUES))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                String::deserialize(d)?.parse().map_err(serde::de::Error::custom)
+            }
+        }
+
+        impl schemars::JsonSchema for $name {
+            fn inline_schema() -> bool {
+                true
+            }
+
+            fn schema_name() -> std::borrow::Cow<'static, str> {
+                stringify!($name).into()
+            }
+
+            fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+                schemars::json_schema!({ "type": "string", "enum": Self::VALUES })
+            }
+        }
+    };
+}
+
+fn normalize_choice(s: &str) -> String {
+    s.trim().to_ascii_lowercase().replace('-', "_")
+}
+
+/// The one error shape for a value outside a closed set.
+pub fn choice_error(what: &str, got: &str, valid: &[&str]) -> String {
+    format!(
+        "invalid {what} '{got}' (expected one of: {})",
+        valid.join(", ")
+    )
+}
+
+choice_enum! {
+    /// How `submit_input` files the input document.
+    pub enum InputMode ("mode") {
+        Chat = "chat",
+        Shell = "shell",
+    }
+}
+
+choice_enum! {
+    /// `dag_query` traversal.
+    pub enum DagOp ("op") {
+        All = "all",
+        Ancestors = "ancestors",
+        Descendants = "descendants",
+        Path = "path",
+        Subtree = "subtree",
+    }
+}
+
+choice_enum! {
+    /// A block role, as accepted in filters.
+    pub enum RoleName ("role") {
+        User = "user" | "human",
+        Model = "model" | "assistant" | "agent",
+        System = "system",
+        Tool = "tool",
+        Asset = "asset",
+    }
+}
+
+impl From<RoleName> for Role {
+    fn from(role: RoleName) -> Self {
+        match role {
+            RoleName::User => Role::User,
+            RoleName::Model => Role::Model,
+            RoleName::System => Role::System,
+            RoleName::Tool => Role::Tool,
+            RoleName::Asset => Role::Asset,
+        }
+    }
+}
+
+choice_enum! {
+    /// A block kind, as accepted in filters.
+    pub enum BlockKindName ("kind") {
+        Text = "text",
+        Thinking = "thinking",
+        ToolCall = "tool_call" | "toolcall",
+        ToolResult = "tool_result" | "toolresult",
+        Drift = "drift",
+        File = "file",
+        Error = "error",
+        Notification = "notification",
+        Resource = "resource",
+        Trace = "trace",
+        Repl = "repl",
+    }
+}
+
+impl From<BlockKindName> for BlockKind {
+    fn from(kind: BlockKindName) -> Self {
+        match kind {
+            BlockKindName::Text => BlockKind::Text,
+            BlockKindName::Thinking => BlockKind::Thinking,
+            BlockKindName::ToolCall => BlockKind::ToolCall,
+            BlockKindName::ToolResult => BlockKind::ToolResult,
+            BlockKindName::Drift => BlockKind::Drift,
+            BlockKindName::File => BlockKind::File,
+            BlockKindName::Error => BlockKind::Error,
+            BlockKindName::Notification => BlockKind::Notification,
+            BlockKindName::Resource => BlockKind::Resource,
+            BlockKindName::Trace => BlockKind::Trace,
+            BlockKindName::Repl => BlockKind::Repl,
+        }
+    }
+}
+
+choice_enum! {
+    /// How the kernel reaches a context-scoped MCP server.
+    pub enum McpTransport ("transport") {
+        Stdio = "stdio",
+        Http = "http",
+    }
+}
+
+choice_enum! {
+    /// Whether forks see a context-scoped MCP server.
+    pub enum ForkMode ("fork_mode") {
+        Inherit = "inherit",
+        Exclude = "exclude",
+    }
+}
+
 // ============================================================================
 // Kaish Execution Types
 // ============================================================================
@@ -53,6 +242,19 @@ pub struct ShellRequest {
     pub timeout_secs: Option<u64>,
 }
 
+/// Re-run an earlier shell command block in the joined context, with the
+/// working directory and env overrides it originally ran with.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ShellRerunRequest {
+    /// The shell command block (ToolCall) to re-run: full key, label, or
+    /// unambiguous short form.
+    #[schemars(description = "Shell command block to re-run (full key, label, or short form)")]
+    pub block_id: String,
+    /// Timeout in seconds (default: 300)
+    #[schemars(description = "Timeout in seconds (default: 300, max: 600)")]
+    pub timeout_secs: Option<u64>,
+}
+
 // ============================================================================
 // Input Document Types
 // ============================================================================
@@ -104,7 +306,7 @@ pub struct InputSubmitRequest {
     /// Input mode: "chat" (default) or "shell".
     #[serde(default)]
     #[schemars(description = "Input mode: 'chat' (default) or 'shell'.")]
-    pub mode: Option<String>,
+    pub mode: Option<InputMode>,
 }
 
 // ============================================================================
@@ -125,6 +327,524 @@ pub struct RegisterSessionRequest {
     pub context_type: Option<String>,
 }
 
+// ============================================================================
+// Context Statistics
+// ============================================================================
+
+/// Aggregated statistics for a context.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ContextInfoRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+}
+
+// ============================================================================
+// Sync Quarantine
+// ============================================================================
+
+/// Inspect (and optionally resolve) the joined document's sync quarantine.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SyncQuarantineRequest {
+    /// Drop the quarantined payloads and force a full resync.
+    #[serde(default)]
+    #[schemars(description = "Drop the quarantined payloads and force a full resync of the document (default false).")]
+    pub resolve: bool,
+}
+
+// ============================================================================
+// Context Cleanup
+// ============================================================================
+
+/// Close (archive) every open context matching the filters.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ContextsCloseRequest {
+    /// Close contexts whose label starts with this.
+    #[schemars(description = "Close contexts whose label starts with this prefix.")]
+    pub label_prefix: Option<String>,
+    /// Close direct forks of this context (hex or label).
+    #[schemars(description = "Close direct forks of this context (hex UUID or label).")]
+    pub forked_from: Option<String>,
+    /// Close contexts with no activity in the last N minutes.
+    #[schemars(description = "Close contexts with no block activity in the last N minutes.")]
+    pub idle_minutes: Option<u64>,
+    /// Close only concluded contexts.
+    #[serde(default)]
+    #[schemars(description = "Close only concluded contexts (default false).")]
+    pub concluded_only: bool,
+    /// Also close contexts holding a ring-0 seat.
+    #[serde(default)]
+    #[schemars(description = "Also close promoted (ring-0) contexts, which are skipped by default.")]
+    pub include_promoted: bool,
+    /// Report what would close without closing it.
+    #[serde(default)]
+    #[schemars(description = "Report the matching contexts without closing them (default false).")]
+    pub dry_run: bool,
+}
+
+/// Reopen a closed context.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ContextReopenRequest {
+    /// Context ID (hex or label) of the closed context.
+    #[schemars(description = "Context ID (hex UUID or label) of the closed context.")]
+    pub context_id: String,
+}
+
+/// Recover a wedged context.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ContextRecoverRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+}
+
+// ============================================================================
+// Execution Budgets
+// ============================================================================
+
+/// A context's execution budget and what it has spent.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BudgetStatusRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+}
+
+// ============================================================================
+// LLM Parameters
+// ============================================================================
+
+/// Set a context's generation parameters.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LlmParamsSetRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+
+    /// Sampling temperature.
+    #[schemars(description = "Sampling temperature, 0 to 2 (Anthropic models accept at most 1).")]
+    #[schemars(range(min = 0.0, max = 2.0))]
+    pub temperature: Option<f64>,
+
+    /// Output token cap per model call.
+    #[schemars(description = "Output token cap per model call (> 0).")]
+    #[schemars(range(min = 1))]
+    pub max_tokens: Option<u64>,
+
+    /// Nucleus sampling.
+    #[schemars(description = "Nucleus sampling probability mass, greater than 0 and at most 1.")]
+    #[schemars(range(min = 0.0, max = 1.0))]
+    pub top_p: Option<f64>,
+
+    /// Tool choice.
+    #[schemars(
+        description = "How the model may use tools: \"auto\", \"any\" (must call a tool), \"none\", or \"tool:<name>\" (must call that tool)."
+    )]
+    pub tool_choice: Option<String>,
+
+    /// Drop the stored params before applying the given fields.
+    #[serde(default)]
+    #[schemars(
+        description = "Drop every stored parameter first (default false). With no other fields this resets the context to provider defaults."
+    )]
+    pub clear: bool,
+}
+
+/// Preview a context's next prompt.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ContextPreviewRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+
+    /// Assembly strategy to try instead of the context's.
+    #[schemars(
+        description = "Assembly strategy to preview instead of the context's stored one: stages joined with '+' — \"linear\", \"path[:<block key>]\", \"window:<n>\", \"checkpoint\" (e.g. \"checkpoint+window:16\"). Not stored."
+    )]
+    pub strategy: Option<String>,
+}
+
+// ============================================================================
+// Broadcast Prompts
+// ============================================================================
+
+/// One context a broadcast goes to.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BroadcastTargetSpec {
+    /// Context ID (hex or label).
+    #[schemars(description = "Context ID (hex UUID or label)")]
+    pub context_id: String,
+
+    /// Model for this context's turn.
+    #[schemars(
+        description = "Model for this context's turn. Omit to use the context's own model."
+    )]
+    pub model: Option<String>,
+}
+
+/// Send one prompt to several contexts at once.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BroadcastPromptRequest {
+    /// The prompt every target receives.
+    #[schemars(description = "The prompt every target context receives")]
+    pub prompt: String,
+
+    /// Contexts to send it to, each optionally with its own model.
+    #[schemars(description = "Target contexts (1 to 16, no repeats), each with an optional model")]
+    pub targets: Vec<BroadcastTargetSpec>,
+
+    /// Label for the comparison context.
+    #[schemars(
+        description = "Label for the new context the answers are collected in. Omit to derive one from the prompt."
+    )]
+    pub label: Option<String>,
+
+    /// Seconds to wait for the answers (default 0, max 600).
+    #[schemars(
+        description = "Seconds to wait for every target to finish before returning (default 0 = return at once, max 600)"
+    )]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Progress of a broadcast.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BroadcastStatusRequest {
+    /// Broadcast ID from broadcast_prompt.
+    #[schemars(description = "Broadcast ID returned by broadcast_prompt")]
+    pub id: u64,
+
+    /// Seconds to wait for the broadcast to finish (default 0, max 600).
+    #[schemars(
+        description = "Seconds to wait for every target to finish before returning (default 0 = report now, max 600)"
+    )]
+    pub timeout_secs: Option<u64>,
+}
+
+// ============================================================================
+// Digest Subscriptions
+// ============================================================================
+
+/// Follow other contexts through periodic LLM digests.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DigestSubscribeRequest {
+    /// Context to summarize. Omit to only list subscriptions.
+    #[schemars(
+        description = "Context to follow (hex UUID or label). Omit to list the subscriber's digest subscriptions without changing them."
+    )]
+    pub source: Option<String>,
+
+    /// Context the digests land in. Omit to use the current context.
+    #[schemars(
+        description = "Context the digests are delivered into (hex UUID or label). Omit to use the current context."
+    )]
+    pub subscriber: Option<String>,
+
+    /// Deliver at most every N minutes while the source has new blocks.
+    #[schemars(
+        description = "Deliver a digest at most every N minutes (minimum 1) while the source has new blocks"
+    )]
+    pub every_minutes: Option<u32>,
+
+    /// Deliver when a turn in the source finishes.
+    #[schemars(
+        description = "Deliver a digest when a turn in the source finishes (a milestone). Combine with every_minutes or use alone."
+    )]
+    #[serde(default)]
+    pub on_milestone: bool,
+
+    /// What the digests should focus on.
+    #[schemars(
+        description = "What the digests should focus on, e.g. 'API changes and blockers'. Omit for a general brief."
+    )]
+    pub focus: Option<String>,
+
+    /// Unsubscribe from `source` instead.
+    #[schemars(description = "Stop following source instead of subscribing")]
+    #[serde(default)]
+    pub remove: bool,
+}
+
+// ============================================================================
+// Document Peek
+// ============================================================================
+
+/// Read-only look at another context without joining it.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DocPeekRequest {
+    /// Context ID (hex or label) to look at.
+    #[schemars(description = "Context ID (hex UUID or label) to look at")]
+    pub context_id: String,
+
+    /// Return each block's full text instead of a preview.
+    #[schemars(
+        description = "Return each block's full text instead of a 200-character preview (default false)"
+    )]
+    pub full_content: Option<bool>,
+}
+
+// ============================================================================
+// Document Bookmarks
+// ============================================================================
+
+/// Bookmark a document's current state under a name.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BookmarkCreateRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+
+    /// Bookmark name, unique per document.
+    #[schemars(
+        description = "Bookmark name, e.g. \"before-refactor\": 1-64 letters, digits, '-', '_' or '.', starting with a letter. Unique per document."
+    )]
+    pub name: String,
+}
+
+/// A document's bookmarks.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BookmarkListRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+}
+
+// ============================================================================
+// Context Timeline
+// ============================================================================
+
+/// A context's events bucketed over time.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ContextTimelineRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+
+    /// Trailing window in seconds; omit or 0 for the context's whole life.
+    #[schemars(
+        description = "How far back to look, in seconds (e.g. 3600 for the last hour). Omit or 0 for the context's whole life."
+    )]
+    pub window_secs: Option<u64>,
+
+    /// Number of buckets.
+    #[schemars(description = "Number of equal time buckets (default 60, max 500)")]
+    pub buckets: Option<u32>,
+}
+
+// ============================================================================
+// Block Ordering
+// ============================================================================
+
+/// Reorder a block among its siblings without reparenting.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BlockReorderRequest {
+    /// Block to move (full block key).
+    #[schemars(description = "Block ID (full key) of the block to move")]
+    pub block_id: String,
+    /// Sibling to land after. Omit to move ahead of the first sibling.
+    #[schemars(
+        description = "Block ID of a sibling (same parent) to place the block after. Omit to move it ahead of its first sibling."
+    )]
+    pub after_id: Option<String>,
+}
+
+/// Mint a shareable permalink for a block.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BlockPermalinkRequest {
+    /// Block to link (full key, label, or unambiguous id abbreviation).
+    #[schemars(
+        description = "Block to link: full block key, block label, or an unambiguous id abbreviation"
+    )]
+    pub block_id: String,
+}
+
+/// Follow a block's text as it grows.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BlockTailRequest {
+    /// Block to follow (full block key).
+    #[schemars(description = "Block ID (full key) of the block to follow")]
+    pub block_id: String,
+    /// Char offset to start from (default 0, the whole text).
+    #[schemars(
+        description = "Char offset to start from (default 0 = whole text). Pass the previous call's `offset` to resume without re-reading."
+    )]
+    pub from_offset: Option<u64>,
+    /// Seconds to follow before returning what arrived (default 60, max 600).
+    #[schemars(
+        description = "Seconds to follow before returning with what has arrived so far (default 60, max 600)"
+    )]
+    pub timeout_secs: Option<u64>,
+}
+
+// ============================================================================
+// DAG Query
+// ============================================================================
+
+/// Extract a slice of a context's block DAG.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DagQueryRequest {
+    /// Traversal: all | ancestors | descendants | path | subtree.
+    #[schemars(
+        description = "Traversal: \"all\" (whole DAG, depth-first), \"ancestors\" (parent up to root), \"descendants\" (everything below block_id), \"path\" (block_id up to the common ancestor and down to to_block_id), or \"subtree\" (block_id and below, bounded by max_depth)"
+    )]
+    pub op: DagOp,
+    /// Anchor block (required for every op except `all`).
+    #[schemars(description = "Anchor block ID (full key). Required for every op except \"all\".")]
+    pub block_id: Option<String>,
+    /// Path destination.
+    #[schemars(description = "path: destination block ID (full key)")]
+    pub to_block_id: Option<String>,
+    /// Subtree depth bound.
+    #[schemars(description = "subtree: levels below block_id to include (omit for unbounded)")]
+    pub max_depth: Option<usize>,
+    /// Keep only these block kinds.
+    #[schemars(description = "Keep only these kinds (e.g. [\"text\", \"tool_call\"]). Empty = all.")]
+    #[serde(default)]
+    pub kinds: Vec<BlockKindName>,
+    /// Keep only these roles.
+    #[schemars(description = "Keep only these roles (user, model, system, tool, asset). Empty = all.")]
+    #[serde(default)]
+    pub roles: Vec<RoleName>,
+    /// Cap on returned blocks.
+    #[schemars(description = "Maximum number of blocks to return (omit for all)")]
+    pub limit: Option<u32>,
+    /// Truncate each block's content to this many characters.
+    #[schemars(description = "Truncate each block's content to this many characters (omit for full content)")]
+    pub max_content_chars: Option<usize>,
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+}
+
+// ============================================================================
+// Test Results
+// ============================================================================
+
+/// Read structured test runs recorded by the kaish `testreport` builtin.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct TestResultsRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+    /// How many runs to return, newest first.
+    #[schemars(description = "Number of test runs to return, newest first (default 1)")]
+    pub limit: Option<usize>,
+    /// Drop passing and ignored tests from each run.
+    #[schemars(description = "Only list failed tests (default false; totals always included)")]
+    pub failures_only: Option<bool>,
+}
+
+// ============================================================================
+// Context-Scoped MCP Servers
+// ============================================================================
+
+/// Attach a downstream MCP server to a single context.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct McpServerRegisterRequest {
+    /// Broker instance id — unique across the kernel.
+    #[schemars(description = "Instance name for the server (unique kernel-wide, e.g. \"playwright\")")]
+    pub instance: String,
+    /// "stdio" (default) or "http".
+    #[schemars(description = "Transport: \"stdio\" (default, spawns command) or \"http\" (streamable HTTP at url)")]
+    pub transport: Option<McpTransport>,
+    /// Executable for stdio servers.
+    #[schemars(description = "stdio: executable to spawn (e.g. \"npx\")")]
+    pub command: Option<String>,
+    /// Arguments for stdio servers.
+    #[schemars(description = "stdio: arguments (e.g. [\"@playwright/mcp@latest\"])")]
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment for stdio servers.
+    #[schemars(description = "stdio: extra environment variables")]
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Working directory for stdio servers.
+    #[schemars(description = "stdio: working directory (default: kernel's)")]
+    pub cwd: Option<String>,
+    /// Endpoint for http servers.
+    #[schemars(description = "http: endpoint URL")]
+    pub url: Option<String>,
+    /// Whether forks of the context see the server.
+    #[schemars(description = "\"inherit\" (default: forks see the server) or \"exclude\" (this context only)")]
+    pub fork_mode: Option<ForkMode>,
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+}
+
+/// Detach a context-scoped MCP server.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct McpServerUnregisterRequest {
+    /// Instance name given at registration.
+    #[schemars(description = "Instance name given to mcp_server_register")]
+    pub instance: String,
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+}
+
+/// List a context's MCP servers with a health check.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct McpServerListRequest {
+    /// Context ID (hex or label). Omit to use the current context.
+    #[schemars(description = "Context ID (hex UUID or label). Omit to use the current context.")]
+    pub context_id: Option<String>,
+}
+
+// ============================================================================
+// Inbox
+// ============================================================================
+
+/// List the connected principal's notification inbox.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct InboxListRequest {
+    /// Include already-acknowledged items (default false).
+    #[serde(default)]
+    #[schemars(description = "Include already-acknowledged items (default false).")]
+    pub include_acked: bool,
+    /// Maximum items to return (default 20).
+    #[schemars(description = "Maximum items to return (default 20).")]
+    pub limit: Option<u32>,
+    /// Acknowledge the returned unread items.
+    #[serde(default)]
+    #[schemars(description = "Acknowledge the returned unread items so they stop counting as unread (default false).")]
+    pub ack: bool,
+}
+
+// ============================================================================
+// Preferences
+// ============================================================================
+
+/// Set or clear one of the connected principal's preferences.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PreferenceSetRequest {
+    /// Preference key.
+    #[schemars(description = "Preference key: default_model, default_consent, or ui.<name>.")]
+    pub key: String,
+    /// New value; omit to clear.
+    #[schemars(description = "New value; omit to clear the preference.")]
+    pub value: Option<String>,
+}
+
+// ============================================================================
+// Consent audit log
+// ============================================================================
+
+/// Read (and optionally verify) the kernel's consent audit log.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ConsentLogRequest {
+    /// Return entries after this sequence number (default 0, the start).
+    #[serde(default)]
+    #[schemars(description = "Return entries after this sequence number (default 0 = from the start). Page with the last seq you received.")]
+    pub after_seq: u64,
+    /// Maximum entries to return (default 50).
+    #[schemars(description = "Maximum entries to return (default 50).")]
+    pub limit: Option<u32>,
+    /// Check the whole log's hash chain and signatures.
+    #[serde(default)]
+    #[schemars(description = "Also verify the whole log's hash chain and signatures (default false).")]
+    pub verify: bool,
+}
+
 // ============================================================================
 // Peer Coordination
 // ============================================================================
@@ -0,0 +1,212 @@
+//! Version stamps, conditional reads, and a read-through cache for
+//! `read_resource`.
+//!
+//! Every resource read carries a version stamp in its contents `_meta`
+//! under [`VERSION_META_KEY`]. Resources rendered from a whole document
+//! (`docs/{id}`, `tree/{id}`) are stamped with the document version, which
+//! moves on every local edit and every merged remote op, prefixed with the
+//! cache's start time so a stamp from before a restart never matches. The
+//! rest (a block's text, the context summary, result pages) are stamped with
+//! a hash of their text, which is cheap next to producing it. A client that sends the last stamp
+//! back in the request `_meta` under [`IF_NONE_MATCH_META_KEY`] gets an
+//! empty body flagged [`NOT_MODIFIED_META_KEY`] instead of the full text.
+//!
+//! [`ResourceCache`] keeps the rendered text of the document-stamped
+//! resources keyed by URI + version, so a client polling an unchanged tree doesn't
+//! re-walk the DAG. One version is kept per URI; a newer one replaces it.
+//! Past [`CACHE_MAX_ENTRIES`] URIs the least recently rendered is evicted.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use rmcp::model::{Meta, ReadResourceResult, ResourceContents};
+
+/// Contents `_meta` key holding the version stamp.
+pub const VERSION_META_KEY: &str = "io.kaijutsu.v1.version";
+
+/// Request `_meta` key a client sets to the stamp it already holds.
+pub const IF_NONE_MATCH_META_KEY: &str = "io.kaijutsu.v1.if_none_match";
+
+/// Contents `_meta` flag set on a not-modified (empty) reply.
+pub const NOT_MODIFIED_META_KEY: &str = "io.kaijutsu.v1.not_modified";
+
+/// URIs whose rendered text is kept.
+const CACHE_MAX_ENTRIES: usize = 128;
+
+/// Stamp for a resource with no cheaper version than its own text.
+pub fn content_stamp(text: &str) -> String {
+    format!("h{:016x}", fnv1a64(text.as_bytes()))
+}
+
+/// FNV-1a, 64-bit. Hand-rolled so stamps stay stable across Rust releases
+/// (`DefaultHasher` makes no such promise) and a client can hold one across
+/// server restarts.
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut h = OFFSET_BASIS;
+    for &b in bytes {
+        h ^= b as u64;
+        h = h.wrapping_mul(PRIME);
+    }
+    h
+}
+
+/// The stamp a client sent back in the request `_meta`, if any.
+pub fn if_none_match(meta: Option<&Meta>) -> Option<&str> {
+    meta?.0.get(IF_NONE_MATCH_META_KEY)?.as_str()
+}
+
+/// Build the read reply: the text stamped with `version`, or an empty
+/// not-modified reply when the client already holds `version`.
+pub fn versioned_result(
+    uri: &str,
+    text: &str,
+    version: &str,
+    client_version: Option<&str>,
+) -> ReadResourceResult {
+    let not_modified = client_version == Some(version);
+    let mut meta = Meta(serde_json::Map::new());
+    meta.0
+        .insert(VERSION_META_KEY.to_string(), version.to_string().into());
+    if not_modified {
+        meta.0
+            .insert(NOT_MODIFIED_META_KEY.to_string(), true.into());
+    }
+    let body = if not_modified { "" } else { text };
+    let mut contents = ResourceContents::text(body, uri.to_string());
+    if let ResourceContents::TextResourceContents { meta: slot, .. } = &mut contents {
+        *slot = Some(meta);
+    }
+    ReadResourceResult::new(vec![contents])
+}
+
+#[derive(Debug)]
+struct CachedResource {
+    version: String,
+    text: Arc<str>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, CachedResource>,
+    /// URIs, least recently rendered first.
+    order: VecDeque<String>,
+}
+
+/// Rendered text of versioned resources. Clones share the cache.
+#[derive(Debug, Clone)]
+pub struct ResourceCache {
+    /// Unix millis at creation; document versions restart with the process.
+    epoch: u64,
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl Default for ResourceCache {
+    fn default() -> Self {
+        Self {
+            epoch: kaijutsu_types::now_millis(),
+            state: Arc::default(),
+        }
+    }
+}
+
+impl ResourceCache {
+    /// Stamp for a resource derived from one document at `version`.
+    pub fn doc_stamp(&self, version: u64) -> String {
+        format!("v{:x}.{version}", self.epoch)
+    }
+
+    /// The text of `uri` at `version`: cached if this version was rendered
+    /// before, otherwise `render`ed and kept.
+    pub fn get_or_render(
+        &self,
+        uri: &str,
+        version: &str,
+        render: impl FnOnce() -> String,
+    ) -> Arc<str> {
+        if let Some(hit) = self.state.lock().entries.get(uri)
+            && hit.version == version
+        {
+            return hit.text.clone();
+        }
+        // Render without the lock held; a racing render of the same version
+        // produces the same text, so last-writer-wins is fine.
+        let text: Arc<str> = render().into();
+        let mut state = self.state.lock();
+        state.order.retain(|u| u != uri);
+        state.order.push_back(uri.to_string());
+        state.entries.insert(
+            uri.to_string(),
+            CachedResource {
+                version: version.to_string(),
+                text: text.clone(),
+            },
+        );
+        while state.order.len() > CACHE_MAX_ENTRIES {
+            if let Some(evicted) = state.order.pop_front() {
+                state.entries.remove(&evicted);
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn meta_of(result: &ReadResourceResult) -> &Meta {
+        match &result.contents[0] {
+            ResourceContents::TextResourceContents { meta, .. } => meta.as_ref().unwrap(),
+            other => panic!("expected text contents, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn cache_rerenders_only_on_version_change() {
+        let cache = ResourceCache::default();
+        let renders = Cell::new(0);
+        let render = |text: &str| {
+            renders.set(renders.get() + 1);
+            text.to_string()
+        };
+
+        let uri = "kaijutsu://tree/abc";
+        let v1 = cache.doc_stamp(1);
+        let v2 = cache.doc_stamp(2);
+        assert_eq!(&*cache.get_or_render(uri, &v1, || render("a")), "a");
+        assert_eq!(&*cache.get_or_render(uri, &v1, || render("x")), "a");
+        assert_eq!(renders.get(), 1);
+        assert_eq!(&*cache.get_or_render(uri, &v2, || render("b")), "b");
+        assert_eq!(renders.get(), 2);
+
+        for i in 0..CACHE_MAX_ENTRIES {
+            cache.get_or_render(&format!("kaijutsu://tree/{i}"), &v1, String::new);
+        }
+        assert!(
+            !cache.state.lock().entries.contains_key(uri),
+            "least recently rendered URI is evicted first"
+        );
+    }
+
+    #[test]
+    fn matching_stamp_returns_not_modified() {
+        let uri = "kaijutsu://docs/abc";
+        let fresh = versioned_result(uri, "body", "v7", Some("v6"));
+        assert_eq!(meta_of(&fresh).0[VERSION_META_KEY], "v7");
+        assert!(!meta_of(&fresh).0.contains_key(NOT_MODIFIED_META_KEY));
+
+        let same = versioned_result(uri, "body", "v7", Some("v7"));
+        assert_eq!(meta_of(&same).0[NOT_MODIFIED_META_KEY], true);
+        match &same.contents[0] {
+            ResourceContents::TextResourceContents { text, .. } => assert!(text.is_empty()),
+            other => panic!("expected text contents, got {other:?}"),
+        }
+
+        assert_eq!(content_stamp("same"), content_stamp("same"));
+        assert_ne!(content_stamp("same"), content_stamp("diff"));
+    }
+}
@@ -0,0 +1,294 @@
+//! Tool result size guard.
+//!
+//! A tool whose text output exceeds the limit (`--max-result-bytes`) is
+//! answered with a preview — the head and tail of the output, cut on line
+//! boundaries where possible — plus a `kaijutsu://results/{id}` resource URI
+//! holding the full text. The client pages through it with
+//! `kaijutsu://results/{id}?offset=N&length=M`; without a range the first
+//! limit's worth comes back. One `cat` of a big file no longer floods the
+//! model's context or trips the client's message cap.
+//!
+//! Full results are kept in memory for the life of the server, oldest
+//! evicted first past [`STORE_MAX_ENTRIES`] or [`STORE_MAX_BYTES`].
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+use rmcp::model::{CallToolResult, Content};
+
+/// Default output limit per tool call, in bytes.
+pub const DEFAULT_MAX_RESULT_BYTES: usize = 64 * 1024;
+
+/// Full results kept for paging.
+const STORE_MAX_ENTRIES: usize = 32;
+
+/// Total bytes of full results kept for paging.
+const STORE_MAX_BYTES: usize = 32 * 1024 * 1024;
+
+/// URI prefix of stored results.
+pub const RESULTS_URI_PREFIX: &str = "kaijutsu://results/";
+
+/// A full result held for paging.
+#[derive(Debug, Clone)]
+pub struct StoredResult {
+    pub id: u64,
+    pub tool: String,
+    pub text: Arc<str>,
+}
+
+impl StoredResult {
+    pub fn uri(&self) -> String {
+        format!("{RESULTS_URI_PREFIX}{}", self.id)
+    }
+}
+
+/// Truncates oversized tool results and keeps the originals for paging.
+/// Clones share the store.
+#[derive(Debug, Clone)]
+pub struct ResultGuard {
+    /// Output limit in bytes; 0 disables the guard.
+    max_bytes: usize,
+    next_id: Arc<AtomicU64>,
+    store: Arc<Mutex<VecDeque<StoredResult>>>,
+}
+
+impl Default for ResultGuard {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_RESULT_BYTES)
+    }
+}
+
+impl ResultGuard {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            next_id: Arc::new(AtomicU64::new(1)),
+            store: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Pass `result` through, or replace its text with a preview and a
+    /// pointer to the stored original when the text is over the limit.
+    /// Non-text content (images, resources) is left alone.
+    pub fn guard(&self, tool: &str, mut result: CallToolResult) -> CallToolResult {
+        if self.max_bytes == 0 {
+            return result;
+        }
+        let text_bytes: usize = result
+            .content
+            .iter()
+            .filter_map(|c| c.as_text())
+            .map(|t| t.text.len())
+            .sum();
+        if text_bytes <= self.max_bytes {
+            return result;
+        }
+
+        let full: String = result
+            .content
+            .iter()
+            .filter_map(|c| c.as_text())
+            .map(|t| t.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let stored = self.store(tool, full);
+        let preview = preview(&stored.text, self.max_bytes);
+        let notice = format!(
+            "[result truncated: {} of {} bytes shown. Full output: {} — read it in pages with \
+             {}?offset=N&length=M]",
+            preview.len(),
+            stored.text.len(),
+            stored.uri(),
+            stored.uri(),
+        );
+        tracing::info!(
+            tool,
+            bytes = stored.text.len(),
+            uri = %stored.uri(),
+            "Tool result over limit, returned a preview"
+        );
+
+        let mut content: Vec<Content> = result
+            .content
+            .into_iter()
+            .filter(|c| c.as_text().is_none())
+            .collect();
+        content.insert(0, Content::text(format!("{preview}\n{notice}")));
+        result.content = content;
+        result
+    }
+
+    fn store(&self, tool: &str, text: String) -> StoredResult {
+        let stored = StoredResult {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            tool: tool.to_string(),
+            text: text.into(),
+        };
+        let mut store = self.store.lock();
+        store.push_back(stored.clone());
+        let mut total: usize = store.iter().map(|r| r.text.len()).sum();
+        // Never evict the result just added, however big.
+        while store.len() > 1 && (store.len() > STORE_MAX_ENTRIES || total > STORE_MAX_BYTES) {
+            if let Some(old) = store.pop_front() {
+                total -= old.text.len();
+            }
+        }
+        stored
+    }
+
+    /// Stored results, oldest first.
+    pub fn list(&self) -> Vec<StoredResult> {
+        self.store.lock().iter().cloned().collect()
+    }
+
+    /// Read a page of a stored result from its URI (`kaijutsu://results/{id}`
+    /// with optional `offset` and `length` query parameters, in bytes; the
+    /// range is widened to char boundaries). `None` for a URI that isn't a
+    /// result; `Err` for one that is malformed or evicted.
+    pub fn read(&self, uri: &str) -> Option<Result<String, String>> {
+        let rest = uri.strip_prefix(RESULTS_URI_PREFIX)?;
+        Some(self.read_page(rest))
+    }
+
+    fn read_page(&self, rest: &str) -> Result<String, String> {
+        let (id, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let id: u64 = id.parse().map_err(|_| format!("invalid result id '{id}'"))?;
+        let mut offset = 0usize;
+        let mut length = if self.max_bytes == 0 {
+            DEFAULT_MAX_RESULT_BYTES
+        } else {
+            self.max_bytes
+        };
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value: usize = value
+                .parse()
+                .map_err(|_| format!("invalid {key} '{value}'"))?;
+            match key {
+                "offset" => offset = value,
+                "length" => length = value,
+                other => return Err(format!("unknown parameter '{other}'")),
+            }
+        }
+        let text = self
+            .store
+            .lock()
+            .iter()
+            .find(|r| r.id == id)
+            .map(|r| r.text.clone())
+            .ok_or_else(|| format!("result {id} not found (evicted or never stored)"))?;
+        let start = floor_char_boundary(&text, offset.min(text.len()));
+        let end = ceil_char_boundary(&text, start.saturating_add(length).min(text.len()));
+        Ok(text[start..end].to_string())
+    }
+}
+
+/// Head and tail of `text` within `budget` bytes, with an elision marker.
+/// Three quarters of the budget go to the head; each cut moves back to a
+/// newline when one falls in the second half of its share.
+fn preview(text: &str, budget: usize) -> String {
+    let head_budget = budget * 3 / 4;
+    let tail_budget = budget - head_budget;
+
+    let mut head_end = floor_char_boundary(text, head_budget);
+    if let Some(nl) = text[..head_end].rfind('\n')
+        && nl >= head_budget / 2
+    {
+        head_end = nl + 1;
+    }
+
+    let mut tail_start = ceil_char_boundary(text, text.len() - tail_budget);
+    if let Some(nl) = text[tail_start..].find('\n')
+        && nl < tail_budget / 2
+    {
+        tail_start += nl + 1;
+    }
+
+    let omitted = tail_start - head_end;
+    format!(
+        "{}\n… [{omitted} bytes omitted] …\n{}",
+        &text[..head_end],
+        &text[tail_start..]
+    )
+}
+
+fn floor_char_boundary(s: &str, mut i: usize) -> usize {
+    while !s.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+fn ceil_char_boundary(s: &str, mut i: usize) -> usize {
+    while !s.is_char_boundary(i) {
+        i += 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_of(result: &CallToolResult) -> String {
+        result.content[0].as_text().unwrap().text.clone()
+    }
+
+    #[test]
+    fn small_results_pass_through() {
+        let guard = ResultGuard::new(1024);
+        let result = guard.guard("shell", CallToolResult::success(vec![Content::text("ok")]));
+        assert_eq!(text_of(&result), "ok");
+        assert!(guard.list().is_empty());
+    }
+
+    #[test]
+    fn oversized_result_is_previewed_and_pageable() {
+        let guard = ResultGuard::new(1000);
+        let body: String = (0..500).map(|i| format!("line {i}\n")).collect();
+        let result = guard.guard(
+            "shell",
+            CallToolResult::success(vec![Content::text(body.clone())]),
+        );
+
+        let preview = text_of(&result);
+        assert!(preview.starts_with("line 0\n"));
+        assert!(preview.contains("line 499\n"));
+        assert!(preview.contains("bytes omitted"));
+        assert!(preview.contains("kaijutsu://results/1"));
+        assert!(preview.len() < 1300, "{}", preview.len());
+
+        let page = guard.read("kaijutsu://results/1?offset=0&length=14").unwrap();
+        assert_eq!(page.unwrap(), "line 0\nline 1\n");
+        let rest = guard
+            .read(&format!("kaijutsu://results/1?offset={}", body.len() - 9))
+            .unwrap();
+        assert_eq!(rest.unwrap(), "line 499\n");
+        assert!(guard.read("kaijutsu://results/9").unwrap().is_err());
+        assert!(guard.read("kaijutsu://docs").is_none());
+    }
+
+    #[test]
+    fn preview_respects_char_boundaries() {
+        let text = "日本語".repeat(400);
+        let out = preview(&text, 100);
+        assert!(out.contains("bytes omitted"));
+    }
+
+    #[test]
+    fn store_evicts_oldest() {
+        let guard = ResultGuard::new(1);
+        for _ in 0..STORE_MAX_ENTRIES + 2 {
+            guard.guard("t", CallToolResult::success(vec![Content::text("xx")]));
+        }
+        let ids: Vec<u64> = guard.list().iter().map(|r| r.id).collect();
+        assert_eq!(ids.len(), STORE_MAX_ENTRIES);
+        assert_eq!(ids[0], 3);
+    }
+}
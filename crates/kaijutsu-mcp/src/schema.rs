@@ -0,0 +1,317 @@
+//! `kaijutsu-mcp schema` — the tool surface as a machine-readable document.
+//!
+//! [`surface`] describes every tool the router serves, exactly as
+//! `tools/list` advertises it — name, description, annotations, the input
+//! JSON Schema built from the request types in `models`, and the output
+//! schema where a tool declares one — sorted by name, so SDKs, docs and test
+//! harnesses can be generated from one file.
+//!
+//! [`check`] compares two surfaces and lists the changes that break an
+//! existing caller: a tool removed, a parameter removed or retyped, a new
+//! required parameter, an enum value dropped, a result field removed.
+//! Additions — tools, optional parameters, enum values — and description
+//! edits are compatible. `schema --check <baseline>` exits non-zero on any
+//! break; `serve` runs the same check at startup against the surface the
+//! previous start recorded ([`default_record_path`]).
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::KaijutsuMcp;
+
+/// Bumped when the document's own layout changes, not the tools in it.
+pub const SURFACE_FORMAT: u32 = 1;
+
+/// The tool surface of this build.
+pub fn surface() -> Value {
+    let mut tools: Vec<Value> = KaijutsuMcp::tool_router()
+        .list_all()
+        .iter()
+        .map(|tool| serde_json::to_value(tool).unwrap_or(Value::Null))
+        .collect();
+    tools.sort_by(|a, b| tool_name(a).cmp(tool_name(b)));
+    json!({
+        "format": SURFACE_FORMAT,
+        "server": "kaijutsu-mcp",
+        "version": env!("CARGO_PKG_VERSION"),
+        "tools": tools,
+    })
+}
+
+fn tool_name(tool: &Value) -> &str {
+    tool["name"].as_str().unwrap_or_default()
+}
+
+/// One change that breaks callers of the older surface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Incompatibility {
+    pub tool: String,
+    pub change: String,
+}
+
+impl std::fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.tool, self.change)
+    }
+}
+
+/// Everything in `current` that breaks a caller written against `baseline`.
+pub fn check(baseline: &Value, current: &Value) -> Vec<Incompatibility> {
+    let tools = |surface: &Value| -> Vec<Value> {
+        surface["tools"].as_array().cloned().unwrap_or_default()
+    };
+    let current_tools = tools(current);
+    let mut breaks = Vec::new();
+    for old in tools(baseline) {
+        let name = tool_name(&old).to_string();
+        let mut push = |change: String| {
+            breaks.push(Incompatibility {
+                tool: name.clone(),
+                change,
+            })
+        };
+        let Some(new) = current_tools.iter().find(|t| tool_name(t) == name) else {
+            push("tool removed".into());
+            continue;
+        };
+        check_params(&old["inputSchema"], &new["inputSchema"], &mut push);
+        check_output(&old["outputSchema"], &new["outputSchema"], &mut push);
+    }
+    breaks
+}
+
+fn check_params(old: &Value, new: &Value, push: &mut impl FnMut(String)) {
+    let old_required = required(old);
+    let new_required = required(new);
+    for (param, old_schema) in properties(old) {
+        let Some(new_schema) = new["properties"].get(param.as_str()) else {
+            push(format!("parameter `{param}` removed"));
+            continue;
+        };
+        let old_schema = resolve(old, old_schema);
+        let new_schema = resolve(new, new_schema);
+        let (old_types, new_types) = (types(old_schema), types(new_schema));
+        if !old_types.is_empty() && !new_types.is_empty() && !old_types.is_subset(&new_types) {
+            push(format!(
+                "parameter `{param}` changed type from {} to {}",
+                join(&old_types),
+                join(&new_types)
+            ));
+        }
+        if let (Some(old_enum), Some(new_enum)) =
+            (old_schema["enum"].as_array(), new_schema["enum"].as_array())
+        {
+            for value in old_enum.iter().filter(|v| !new_enum.contains(v)) {
+                push(format!("parameter `{param}` no longer accepts {value}"));
+            }
+        }
+        if new_required.contains(&param) && !old_required.contains(&param) {
+            push(format!("parameter `{param}` is now required"));
+        }
+    }
+    for param in new_required.difference(&old_required) {
+        if old["properties"].get(param.as_str()).is_none() {
+            push(format!("new required parameter `{param}`"));
+        }
+    }
+}
+
+fn check_output(old: &Value, new: &Value, push: &mut impl FnMut(String)) {
+    if old.is_null() {
+        return;
+    }
+    if new.is_null() {
+        push("output schema removed".into());
+        return;
+    }
+    for (field, _) in properties(old) {
+        if new["properties"].get(field.as_str()).is_none() {
+            push(format!("result field `{field}` removed"));
+        }
+    }
+}
+
+fn properties(schema: &Value) -> Vec<(String, &Value)> {
+    schema["properties"]
+        .as_object()
+        .map(|props| props.iter().map(|(k, v)| (k.clone(), v)).collect())
+        .unwrap_or_default()
+}
+
+fn required(schema: &Value) -> BTreeSet<String> {
+    schema["required"]
+        .as_array()
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(|n| n.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Follow a local `$ref` (into `$defs` or `definitions` of `root`), and see
+/// through an `anyOf` that only adds `null` — schemars' shape for an optional
+/// nested type.
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    if let Some(variants) = schema["anyOf"].as_array() {
+        let non_null: Vec<&Value> = variants.iter().filter(|v| v["type"] != "null").collect();
+        if let [only] = non_null[..] {
+            return resolve(root, only);
+        }
+    }
+    let Some(target) = schema["$ref"].as_str() else {
+        return schema;
+    };
+    let mut node = root;
+    for part in target.trim_start_matches("#/").split('/') {
+        node = &node[part];
+    }
+    if node.is_null() { schema } else { node }
+}
+
+fn types(schema: &Value) -> BTreeSet<String> {
+    match &schema["type"] {
+        Value::String(t) => BTreeSet::from([t.clone()]),
+        Value::Array(ts) => ts
+            .iter()
+            .filter_map(|t| t.as_str().map(String::from))
+            .collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+fn join(types: &BTreeSet<String>) -> String {
+    types.iter().cloned().collect::<Vec<_>>().join("|")
+}
+
+/// Where `serve` records the surface between starts:
+/// `~/.local/share/kaijutsu/mcp-tool-schema.json`.
+pub fn default_record_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("kaijutsu").join("mcp-tool-schema.json"))
+}
+
+/// Check this build's surface against the one recorded at `path`. No
+/// recording yet means nothing to break.
+pub fn check_recorded(path: &Path) -> Result<Vec<Incompatibility>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => {
+            let baseline: Value = serde_json::from_str(&text)
+                .with_context(|| format!("parsing {}", path.display()))?;
+            Ok(check(&baseline, &surface()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+    }
+}
+
+/// Record this build's surface at `path` as the next start's baseline.
+pub fn record(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&surface())?)
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A surface holding one tool, `demo`.
+    fn one_tool(input: Value) -> Value {
+        json!({ "tools": [{ "name": "demo", "inputSchema": input }] })
+    }
+
+    #[test]
+    fn surface_lists_every_tool_sorted_with_an_input_schema() {
+        let surface = surface();
+        let tools = surface["tools"].as_array().unwrap();
+        assert!(tools.len() > 10, "{} tools", tools.len());
+        let names: Vec<&str> = tools.iter().map(tool_name).collect();
+        assert!(names.is_sorted(), "{names:?}");
+        assert!(names.contains(&"kaish_exec"));
+        for tool in tools {
+            assert!(tool["inputSchema"].is_object(), "{}", tool_name(tool));
+        }
+        assert!(check(&surface, &surface).is_empty());
+    }
+
+    #[test]
+    fn check_reports_breaking_changes_only() {
+        let base = one_tool(json!({
+            "type": "object",
+            "properties": {
+                "mode": {"type": "string", "enum": ["fast", "slow"]},
+                "limit": {"type": "integer"},
+                "note": {"type": "string"},
+            },
+            "required": ["mode"],
+        }));
+
+        let widened = one_tool(json!({
+            "type": "object",
+            "properties": {
+                "mode": {"type": "string", "enum": ["fast", "slow", "auto"]},
+                "limit": {"type": ["integer", "null"]},
+                "note": {"type": "string"},
+                "extra": {"type": "boolean"},
+            },
+            "required": ["mode"],
+        }));
+        assert_eq!(check(&base, &widened), vec![]);
+
+        let broken = one_tool(json!({
+            "type": "object",
+            "properties": {
+                "mode": {"type": "string", "enum": ["fast"]},
+                "limit": {"type": "string"},
+                "target": {"type": "string"},
+            },
+            "required": ["mode", "limit", "target"],
+        }));
+        let mut changes: Vec<String> = check(&base, &broken)
+            .into_iter()
+            .map(|b| b.change)
+            .collect();
+        changes.sort();
+        assert_eq!(
+            changes,
+            vec![
+                "parameter `limit` changed type from integer to string",
+                "parameter `limit` is now required",
+                "parameter `mode` no longer accepts \"slow\"",
+                "parameter `note` removed",
+                "new required parameter `target`",
+            ]
+        );
+
+        assert_eq!(
+            check(&base, &json!({ "tools": [] })),
+            vec![Incompatibility {
+                tool: "demo".into(),
+                change: "tool removed".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn check_follows_refs_into_defs() {
+        let with_def = |values: Value| {
+            one_tool(json!({
+                "type": "object",
+                "properties": {
+                    "kind": {"anyOf": [{"$ref": "#/$defs/Kind"}, {"type": "null"}]},
+                },
+                "$defs": {"Kind": {"type": "string", "enum": values}},
+            }))
+        };
+        let breaks = check(&with_def(json!(["a", "b"])), &with_def(json!(["a"])));
+        assert_eq!(breaks.len(), 1);
+        assert_eq!(breaks[0].change, "parameter `kind` no longer accepts \"b\"");
+    }
+}
@@ -138,6 +138,18 @@ fn adapter_script_emits_single_line_json() {
     assert!(ev.tool.and_then(|t| t.output).is_some());
 }
 
+#[test]
+fn pre_tool_use_carries_tool_use_id() {
+    // The listener pairs tool.before with its tool.after on tool_use_id; a
+    // dropped id degrades pairing to name + input.
+    let ev = map_claude("pre_tool_use.json", "tool.before");
+    assert_eq!(ev.event, "tool.before");
+    let tool = ev.tool.expect("tool present on tool.before");
+    assert_eq!(tool.name, "Bash");
+    assert_eq!(tool.tool_use_id.as_deref(), Some("toolu_01ABCDEF"));
+    assert!(tool.output.is_none());
+}
+
 #[test]
 fn post_tool_use_failure_carries_error() {
     let ev = map_claude("post_tool_use_failure.json", "tool.error");
@@ -61,6 +61,7 @@ async fn connect_mcp(addr: SocketAddr) -> KaijutsuMcp {
         username: "test_user".to_string(),
         key_source: KeySource::ephemeral(),
         insecure: true,
+        compress: true,
     };
     KaijutsuMcp::connect_with_config(config, "hook-e2e-test", None)
         .await
@@ -0,0 +1,1066 @@
+//! Scheduled backups — copy the kernel's databases to an export target on a
+//! timer, with retention, plus an on-demand run (`backupNow` RPC,
+//! `kaijutsu-server backup-now`).
+//!
+//! A run captures everything needed to bring a server back:
+//! - `kernel.db` — every document (oplogs + compaction snapshots), the
+//!   context tree, presets, workspaces
+//! - `auth.db` — principals and SSH keys (skipped for an in-memory auth DB)
+//! - `manifest.json` — written last; a run without one is incomplete
+//!
+//! Each database is copied with `VACUUM INTO` on a separate read-only
+//! connection: a transactionally consistent copy, taken while the server
+//! keeps writing, that never holds the kernel's own `KernelDb` lock. The
+//! scratch copy sits beside the database it copies, and it and every file a
+//! local target writes are created 0600 — a backup holds the same secrets
+//! as the live databases.
+//!
+//! Runs land under `<target>/<UTC stamp>/` (`20260101T030000Z/kernel.db`).
+//! Stamps sort lexicographically, so retention is "keep the newest `keep`
+//! complete runs, delete everything older".
+//!
+//! Targets ([`ExportTarget`]):
+//! - a local directory — `--backup-to /srv/backups`
+//! - an S3-compatible bucket — `--backup-to s3://bucket/prefix`. Credentials
+//!   and region come from the standard `AWS_*` environment variables;
+//!   `AWS_ENDPOINT_URL` points it at MinIO, R2, Garage, etc. Requests are
+//!   path-style and SigV4-signed by hand (no AWS SDK).
+//!
+//! The scheduler is one task on the server's ambient runtime. `backupNow`
+//! arrives on a capnp RPC thread, so it is forwarded to that task over a
+//! channel rather than run in place: runs never overlap, and the S3 client's
+//! connection pool stays on the runtime that owns it.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+
+/// Default time between scheduled runs (`--backup-every`).
+pub const DEFAULT_BACKUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default number of complete runs kept by retention (`--backup-keep`).
+pub const DEFAULT_BACKUP_KEEP: usize = 7;
+
+/// Shortest accepted `--backup-every`; anything tighter is a typo.
+const MIN_BACKUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Written last in every run; marks the run complete.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Per-request timeout for S3 calls. Generous: a PUT carries a whole DB.
+const S3_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("backup config: {0}")]
+    Config(String),
+    #[error("backup I/O: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("backup copy of {}: {source}", path.display())]
+    Sqlite {
+        path: PathBuf,
+        source: rusqlite::Error,
+    },
+    #[error("backup upload: {0}")]
+    Remote(String),
+    #[error("backup scheduler is not running")]
+    Stopped,
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Where backups go, as given to `--backup-to`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackupTarget {
+    /// A local directory (created on first run).
+    Dir(PathBuf),
+    /// `s3://bucket/prefix` — prefix may be empty.
+    S3 { bucket: String, prefix: String },
+}
+
+impl FromStr for BackupTarget {
+    type Err = BackupError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(BackupError::Config("empty backup target".into()));
+        }
+        let Some(rest) = s.strip_prefix("s3://") else {
+            let path: PathBuf = shellexpand::tilde(s).as_ref().into();
+            return Ok(Self::Dir(path));
+        };
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(BackupError::Config(format!("'{s}' names no bucket")));
+        }
+        Ok(Self::S3 {
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for BackupTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dir(path) => write!(f, "{}", path.display()),
+            Self::S3 { bucket, prefix } if prefix.is_empty() => write!(f, "s3://{bucket}"),
+            Self::S3 { bucket, prefix } => write!(f, "s3://{bucket}/{prefix}"),
+        }
+    }
+}
+
+impl BackupTarget {
+    /// Open the target. S3 reads its credentials from the environment here,
+    /// so a missing key fails at startup rather than at the first run.
+    pub fn open(&self) -> Result<Arc<dyn ExportTarget>, BackupError> {
+        Ok(match self {
+            Self::Dir(path) => Arc::new(LocalDir::new(path.clone())),
+            Self::S3 { bucket, prefix } => {
+                Arc::new(S3Bucket::from_env(bucket.clone(), prefix.clone())?)
+            }
+        })
+    }
+}
+
+/// Scheduled-backup settings (`--backup-to`, `--backup-every`, `--backup-keep`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackupConfig {
+    pub target: BackupTarget,
+    pub interval: Duration,
+    /// Complete runs kept by retention; at least 1.
+    pub keep: usize,
+}
+
+impl BackupConfig {
+    pub fn new(target: BackupTarget) -> Self {
+        Self {
+            target,
+            interval: DEFAULT_BACKUP_INTERVAL,
+            keep: DEFAULT_BACKUP_KEEP,
+        }
+    }
+}
+
+/// Parse a `--backup-every` interval: bare seconds or a number with an
+/// `s`/`m`/`h`/`d` suffix (`90`, `30m`, `6h`, `1d`).
+pub fn parse_interval(s: &str) -> Result<Duration, BackupError> {
+    let s = s.trim();
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let n: u64 = digits
+        .parse()
+        .map_err(|_| BackupError::Config(format!("invalid interval '{s}'")))?;
+    let secs = match unit {
+        "s" => n,
+        "m" => n * 60,
+        "h" => n * 60 * 60,
+        "d" => n * 24 * 60 * 60,
+        _ => {
+            return Err(BackupError::Config(format!(
+                "invalid interval unit in '{s}' (s, m, h, d)"
+            )));
+        }
+    };
+    let interval = Duration::from_secs(secs);
+    if interval < MIN_BACKUP_INTERVAL {
+        return Err(BackupError::Config(format!(
+            "interval '{s}' is under the {}s minimum",
+            MIN_BACKUP_INTERVAL.as_secs()
+        )));
+    }
+    Ok(interval)
+}
+
+/// The databases one run copies.
+#[derive(Clone, Debug)]
+pub struct BackupSources {
+    pub kernel_db: PathBuf,
+    /// `None` for an in-memory auth DB (tests, ephemeral servers).
+    pub auth_db: Option<PathBuf>,
+}
+
+// ============================================================================
+// Export targets
+// ============================================================================
+
+/// A flat key → bytes store that backups are written to. Keys are
+/// `/`-separated and relative to the target (`<stamp>/kernel.db`).
+#[async_trait]
+pub trait ExportTarget: Send + Sync {
+    /// Human-readable location, for logs and reports.
+    fn location(&self) -> String;
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), BackupError>;
+    /// Every key under the target.
+    async fn list(&self) -> Result<Vec<String>, BackupError>;
+    async fn delete(&self, key: &str) -> Result<(), BackupError>;
+}
+
+/// Backups in a local directory.
+pub struct LocalDir {
+    root: PathBuf,
+}
+
+impl LocalDir {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn list_blocking(root: &Path) -> std::io::Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            for entry in entries {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if let Ok(rel) = path.strip_prefix(root) {
+                    let key: Vec<_> = rel.iter().map(|c| c.to_string_lossy()).collect();
+                    keys.push(key.join("/"));
+                }
+            }
+        }
+        Ok(keys)
+    }
+}
+
+#[async_trait]
+impl ExportTarget for LocalDir {
+    fn location(&self) -> String {
+        self.root.display().to_string()
+    }
+
+    /// Write beside the final name and rename, so a crash mid-write never
+    /// leaves a truncated file under a real key.
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), BackupError> {
+        let path = self.root.join(key);
+        tokio::task::spawn_blocking(move || {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut partial = path.clone().into_os_string();
+            partial.push(".partial");
+            let _ = std::fs::remove_file(&partial);
+            create_private(Path::new(&partial))?.write_all(&body)?;
+            std::fs::rename(&partial, &path)
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>, BackupError> {
+        let root = self.root.clone();
+        Ok(
+            tokio::task::spawn_blocking(move || Self::list_blocking(&root))
+                .await
+                .map_err(std::io::Error::other)??,
+        )
+    }
+
+    /// Removing a run's last file also removes its (now empty) directory.
+    async fn delete(&self, key: &str) -> Result<(), BackupError> {
+        let path = self.root.join(key);
+        let root = self.root.clone();
+        tokio::task::spawn_blocking(move || {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+            if let Some(parent) = path.parent()
+                && parent != root
+            {
+                // Fails (harmlessly) while the directory still has files.
+                let _ = std::fs::remove_dir(parent);
+            }
+            Ok(())
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+        Ok(())
+    }
+}
+
+/// Static credentials for SigV4 signing.
+#[derive(Clone)]
+struct S3Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+/// Backups in an S3-compatible bucket, addressed path-style
+/// (`<endpoint>/<bucket>/<key>`).
+pub struct S3Bucket {
+    http: reqwest::Client,
+    /// Scheme + authority, no trailing slash.
+    endpoint: String,
+    /// Authority alone — what the `host` header carries.
+    host: String,
+    region: String,
+    bucket: String,
+    prefix: String,
+    credentials: S3Credentials,
+}
+
+impl S3Bucket {
+    /// Build from `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` (required),
+    /// `AWS_SESSION_TOKEN`, `AWS_REGION` (default `us-east-1`) and
+    /// `AWS_ENDPOINT_URL` (default AWS's regional endpoint).
+    pub fn from_env(bucket: String, prefix: String) -> Result<Self, BackupError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let required =
+            |name: &str| var(name).ok_or_else(|| BackupError::Config(format!("{name} is not set")));
+        let credentials = S3Credentials {
+            access_key_id: required("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: required("AWS_SECRET_ACCESS_KEY")?,
+            session_token: var("AWS_SESSION_TOKEN"),
+        };
+        let region = var("AWS_REGION")
+            .or_else(|| var("AWS_DEFAULT_REGION"))
+            .unwrap_or_else(|| "us-east-1".to_string());
+        let endpoint =
+            var("AWS_ENDPOINT_URL").unwrap_or_else(|| format!("https://s3.{region}.amazonaws.com"));
+        Self::new(&endpoint, region, bucket, prefix, credentials)
+    }
+
+    fn new(
+        endpoint: &str,
+        region: String,
+        bucket: String,
+        prefix: String,
+        credentials: S3Credentials,
+    ) -> Result<Self, BackupError> {
+        let endpoint = endpoint.trim_end_matches('/').to_string();
+        let host = endpoint
+            .strip_prefix("https://")
+            .or_else(|| endpoint.strip_prefix("http://"))
+            .filter(|h| !h.is_empty() && !h.contains('/'))
+            .ok_or_else(|| {
+                BackupError::Config(format!(
+                    "S3 endpoint '{endpoint}' must be http(s)://host[:port] with no path"
+                ))
+            })?
+            .to_string();
+        let http = reqwest::Client::builder()
+            .timeout(S3_REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| BackupError::Config(format!("S3 client: {e}")))?;
+        Ok(Self {
+            http,
+            endpoint,
+            host,
+            region,
+            bucket,
+            prefix,
+            credentials,
+        })
+    }
+
+    fn object_key(&self, key: &str) -> String {
+        if self.prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}/{key}", self.prefix)
+        }
+    }
+
+    /// Send one signed request. `query` pairs must be sorted by name.
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: Option<&str>,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, BackupError> {
+        let mut path = format!("/{}", uri_encode(&self.bucket, true));
+        if let Some(key) = key {
+            path.push('/');
+            path.push_str(&uri_encode(key, false));
+        }
+        let query = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", uri_encode(k, true), uri_encode(v, true)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let amz_date = utc_stamp(SystemTime::now());
+        let authorization = sigv4_authorization(&SigV4Request {
+            method: method.as_str(),
+            path: &path,
+            query: &query,
+            host: &self.host,
+            payload_hash: &payload_hash,
+            amz_date: &amz_date,
+            region: &self.region,
+            credentials: &self.credentials,
+        });
+
+        let url = if query.is_empty() {
+            format!("{}{path}", self.endpoint)
+        } else {
+            format!("{}{path}?{query}", self.endpoint)
+        };
+        let mut request = self
+            .http
+            .request(method.clone(), url)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("x-amz-date", &amz_date)
+            .header("authorization", authorization)
+            .body(body);
+        if let Some(token) = &self.credentials.session_token {
+            request = request.header("x-amz-security-token", token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| BackupError::Remote(format!("{method} {path}: {e}")))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let detail = response.text().await.unwrap_or_default();
+            return Err(BackupError::Remote(format!(
+                "{method} {path}: {status}: {}",
+                xml_tag(&detail, "Message").unwrap_or(detail.trim())
+            )));
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl ExportTarget for S3Bucket {
+    fn location(&self) -> String {
+        BackupTarget::S3 {
+            bucket: self.bucket.clone(),
+            prefix: self.prefix.clone(),
+        }
+        .to_string()
+    }
+
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), BackupError> {
+        let key = self.object_key(key);
+        self.send(reqwest::Method::PUT, Some(&key), &[], body)
+            .await?;
+        Ok(())
+    }
+
+    /// ListObjectsV2, following continuation tokens.
+    async fn list(&self) -> Result<Vec<String>, BackupError> {
+        let prefix = if self.prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", self.prefix)
+        };
+        let mut keys = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = Vec::with_capacity(3);
+            if let Some(token) = &token {
+                query.push(("continuation-token", token.as_str()));
+            }
+            query.push(("list-type", "2"));
+            query.push(("prefix", prefix.as_str()));
+            let response = self
+                .send(reqwest::Method::GET, None, &query, Vec::new())
+                .await?;
+            let xml = response
+                .text()
+                .await
+                .map_err(|e| BackupError::Remote(format!("list response: {e}")))?;
+            keys.extend(
+                xml_tags(&xml, "Key").filter_map(|k| k.strip_prefix(&prefix).map(xml_unescape)),
+            );
+            match xml_tag(&xml, "NextContinuationToken") {
+                Some(next) if xml_tag(&xml, "IsTruncated") == Some("true") => {
+                    token = Some(xml_unescape(next));
+                }
+                _ => break,
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BackupError> {
+        let key = self.object_key(key);
+        self.send(reqwest::Method::DELETE, Some(&key), &[], Vec::new())
+            .await?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// SigV4
+// ============================================================================
+
+struct SigV4Request<'a> {
+    method: &'a str,
+    /// Already URI-encoded.
+    path: &'a str,
+    /// Canonical query string: encoded, sorted by name.
+    query: &'a str,
+    host: &'a str,
+    payload_hash: &'a str,
+    /// `YYYYMMDDTHHMMSSZ`
+    amz_date: &'a str,
+    region: &'a str,
+    credentials: &'a S3Credentials,
+}
+
+/// The `Authorization` header value for an S3 request.
+fn sigv4_authorization(req: &SigV4Request<'_>) -> String {
+    let mut headers = vec![
+        ("host", req.host),
+        ("x-amz-content-sha256", req.payload_hash),
+        ("x-amz-date", req.amz_date),
+    ];
+    if let Some(token) = &req.credentials.session_token {
+        headers.push(("x-amz-security-token", token.as_str()));
+    }
+    let canonical_headers: String = headers.iter().map(|(k, v)| format!("{k}:{v}\n")).collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(k, _)| *k)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{}",
+        req.method, req.path, req.query, req.payload_hash
+    );
+
+    let date = &req.amz_date[..8];
+    let scope = format!("{date}/{}/s3/aws4_request", req.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{scope}\n{}",
+        req.amz_date,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = signing_key(&req.credentials.secret_access_key, date, req.region, "s3");
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        req.credentials.access_key_id
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// SigV4 signing key for one day, region and service.
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{secret}").as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+/// SigV4 URI encoding: everything but unreserved characters is
+/// percent-encoded; `/` too when `encode_slash` (query values, bucket).
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+/// Contents of every `<tag>…</tag>` in `xml`. Enough for S3's flat list
+/// and error documents; not a general XML parser.
+fn xml_tags<'a>(xml: &'a str, tag: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let mut rest = xml;
+    std::iter::from_fn(move || {
+        let start = rest.find(&open)? + open.len();
+        let len = rest[start..].find(&close)?;
+        let value = &rest[start..start + len];
+        rest = &rest[start + len + close.len()..];
+        Some(value)
+    })
+}
+
+fn xml_tag<'a>(xml: &'a str, tag: &'a str) -> Option<&'a str> {
+    xml_tags(xml, tag).next()
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// ============================================================================
+// Runs
+// ============================================================================
+
+/// The outcome of one backup run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackupReport {
+    pub location: String,
+    /// The run's directory under the target (its UTC stamp).
+    pub run: String,
+    /// Keys written, relative to the target.
+    pub files: Vec<String>,
+    pub bytes: u64,
+    /// Runs removed by retention.
+    pub pruned: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+    run: &'a str,
+    created_at: u64,
+    server_version: &'static str,
+    files: &'a [ManifestFile],
+}
+
+#[derive(Serialize)]
+struct ManifestFile {
+    name: String,
+    bytes: u64,
+    sha256: String,
+}
+
+/// Run one backup: copy each database, write the manifest, then prune runs
+/// beyond the newest `keep` complete ones.
+pub async fn run_backup(
+    target: &dyn ExportTarget,
+    sources: &BackupSources,
+    keep: usize,
+) -> Result<BackupReport, BackupError> {
+    let now = SystemTime::now();
+    let run = utc_stamp(now);
+    let mut databases = vec![("kernel.db", sources.kernel_db.clone())];
+    if let Some(auth_db) = &sources.auth_db {
+        databases.push(("auth.db", auth_db.clone()));
+    }
+
+    let mut files = Vec::new();
+    let mut manifest_files = Vec::new();
+    let mut bytes = 0u64;
+    for (name, path) in databases {
+        let scratch = path.with_file_name(format!(".{name}.backup-{}-{run}", std::process::id()));
+        let body = tokio::task::spawn_blocking(move || copy_database(&path, &scratch))
+            .await
+            .map_err(std::io::Error::other)??;
+        let key = format!("{run}/{name}");
+        bytes += body.len() as u64;
+        manifest_files.push(ManifestFile {
+            name: name.to_string(),
+            bytes: body.len() as u64,
+            sha256: hex::encode(Sha256::digest(&body)),
+        });
+        target.put(&key, body).await?;
+        files.push(key);
+    }
+
+    let manifest = serde_json::to_vec_pretty(&Manifest {
+        run: &run,
+        created_at: now
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        server_version: env!("CARGO_PKG_VERSION"),
+        files: &manifest_files,
+    })
+    .map_err(std::io::Error::other)?;
+    let key = format!("{run}/{MANIFEST_FILE}");
+    bytes += manifest.len() as u64;
+    target.put(&key, manifest).await?;
+    files.push(key);
+
+    let pruned = prune(target, keep).await?;
+    Ok(BackupReport {
+        location: target.location(),
+        run,
+        files,
+        bytes,
+        pruned,
+    })
+}
+
+/// Create `path` for writing, readable by its owner only. Fails if it exists.
+fn create_private(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// Consistent copy of the SQLite database at `path`, via `VACUUM INTO` a
+/// scratch file that is removed again before returning. The scratch file is
+/// created empty (and 0600) first; `VACUUM INTO` fills an empty file in
+/// place, keeping its mode.
+fn copy_database(path: &Path, scratch: &Path) -> Result<Vec<u8>, BackupError> {
+    let sqlite = |source| BackupError::Sqlite {
+        path: path.to_path_buf(),
+        source,
+    };
+    let _ = std::fs::remove_file(scratch);
+    create_private(scratch)?;
+    let conn = rusqlite::Connection::open_with_flags(
+        path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(sqlite)?;
+    conn.busy_timeout(Duration::from_secs(5)).map_err(sqlite)?;
+    let result = conn
+        .execute("VACUUM INTO ?1", [scratch.to_string_lossy().into_owned()])
+        .map_err(sqlite)
+        .and_then(|_| Ok(std::fs::read(scratch)?));
+    let _ = std::fs::remove_file(scratch);
+    result
+}
+
+/// Delete every run older than the newest `keep` complete runs. Incomplete
+/// runs newer than that are left alone — one may still be in flight.
+async fn prune(target: &dyn ExportTarget, keep: usize) -> Result<Vec<String>, BackupError> {
+    let keys = target.list().await?;
+    let mut runs: std::collections::BTreeMap<&str, Vec<&str>> = Default::default();
+    for key in &keys {
+        if let Some((run, _)) = key.split_once('/')
+            && is_run_stamp(run)
+        {
+            runs.entry(run).or_default().push(key);
+        }
+    }
+    let complete: Vec<&str> = runs
+        .iter()
+        .rev()
+        .filter(|(run, keys)| keys.iter().any(|k| *k == format!("{run}/{MANIFEST_FILE}")))
+        .map(|(run, _)| *run)
+        .collect();
+    let Some(oldest_kept) = complete.get(keep.max(1) - 1) else {
+        return Ok(Vec::new());
+    };
+
+    let mut pruned = Vec::new();
+    for (run, keys) in runs.range(..*oldest_kept) {
+        // Manifest first: a half-deleted run reads as incomplete, not intact.
+        let mut keys = keys.clone();
+        keys.sort_by_key(|k| !k.ends_with(MANIFEST_FILE));
+        for key in keys {
+            target.delete(key).await?;
+        }
+        pruned.push(run.to_string());
+    }
+    Ok(pruned)
+}
+
+/// `YYYYMMDDTHHMMSSZ` — the only directory names retention touches.
+fn is_run_stamp(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 16
+        && b[8] == b'T'
+        && b[15] == b'Z'
+        && b[..8].iter().chain(&b[9..15]).all(u8::is_ascii_digit)
+}
+
+/// `time` in UTC as `YYYYMMDDTHHMMSSZ` — the run stamp and SigV4's
+/// `x-amz-date`.
+fn utc_stamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+type BackupReply = oneshot::Sender<Result<BackupReport, BackupError>>;
+
+/// Handle to the running backup scheduler; requests an out-of-schedule run.
+#[derive(Clone)]
+pub struct BackupHandle {
+    tx: mpsc::UnboundedSender<BackupReply>,
+}
+
+impl BackupHandle {
+    /// Run a backup now and wait for it. Queued behind a run in progress.
+    pub async fn backup_now(&self) -> Result<BackupReport, BackupError> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(reply).map_err(|_| BackupError::Stopped)?;
+        rx.await.map_err(|_| BackupError::Stopped)?
+    }
+}
+
+/// Spawn the backup scheduler on the ambient runtime. The first scheduled
+/// run is one `interval` after startup; a failed run is logged and retried
+/// at the next tick.
+pub fn spawn_backup_scheduler(
+    config: BackupConfig,
+    sources: BackupSources,
+) -> Result<BackupHandle, BackupError> {
+    let target = config.target.open()?;
+    let (tx, mut rx) = mpsc::unbounded_channel::<BackupReply>();
+    log::info!(
+        "Backups to {} every {}s, keeping {}",
+        target.location(),
+        config.interval.as_secs(),
+        config.keep
+    );
+
+    tokio::spawn(async move {
+        let start = tokio::time::Instant::now() + config.interval;
+        let mut tick = tokio::time::interval_at(start, config.interval);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            let reply = tokio::select! {
+                _ = tick.tick() => None,
+                request = rx.recv() => match request {
+                    Some(reply) => Some(reply),
+                    None => break,
+                },
+            };
+            let result = run_backup(target.as_ref(), &sources, config.keep).await;
+            match &result {
+                Ok(report) => log::info!(
+                    "Backup {} → {}: {} files, {} bytes, pruned {}",
+                    report.run,
+                    report.location,
+                    report.files.len(),
+                    report.bytes,
+                    report.pruned.len()
+                ),
+                Err(e) => log::error!("Backup to {} failed: {e}", target.location()),
+            }
+            if let Some(reply) = reply {
+                let _ = reply.send(result);
+            }
+        }
+    });
+    Ok(BackupHandle { tx })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sqlite_file(path: &Path, rows: usize) {
+        let conn = rusqlite::Connection::open(path).unwrap();
+        conn.execute_batch("PRAGMA journal_mode = WAL; CREATE TABLE t (v TEXT);")
+            .unwrap();
+        for i in 0..rows {
+            conn.execute("INSERT INTO t (v) VALUES (?1)", [i.to_string()])
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn target_parses_dirs_and_buckets() {
+        assert_eq!(
+            "/srv/backups".parse::<BackupTarget>().unwrap(),
+            BackupTarget::Dir("/srv/backups".into())
+        );
+        let s3: BackupTarget = "s3://bucket/kaijutsu/prod/".parse().unwrap();
+        assert_eq!(
+            s3,
+            BackupTarget::S3 {
+                bucket: "bucket".into(),
+                prefix: "kaijutsu/prod".into()
+            }
+        );
+        assert_eq!(s3.to_string(), "s3://bucket/kaijutsu/prod");
+        assert_eq!(
+            "s3://bucket".parse::<BackupTarget>().unwrap().to_string(),
+            "s3://bucket"
+        );
+        assert!("s3://".parse::<BackupTarget>().is_err());
+        assert!("".parse::<BackupTarget>().is_err());
+    }
+
+    #[test]
+    fn intervals_take_units_and_reject_tiny_values() {
+        assert_eq!(parse_interval("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_interval("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_interval("6h").unwrap(), Duration::from_secs(21_600));
+        assert_eq!(parse_interval("1d").unwrap(), DEFAULT_BACKUP_INTERVAL);
+        assert!(parse_interval("10s").is_err());
+        assert!(parse_interval("6w").is_err());
+        assert!(parse_interval("h").is_err());
+    }
+
+    #[test]
+    fn utc_stamps_are_sortable_run_names() {
+        let at = |secs| utc_stamp(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(0), "19700101T000000Z");
+        assert_eq!(at(951_829_509), "20000229T130509Z");
+        assert!(is_run_stamp(&at(951_829_509)));
+        assert!(!is_run_stamp("manifest.json"));
+        assert!(!is_run_stamp("20000229-130509Z"));
+    }
+
+    /// The derived-key example from the AWS SigV4 documentation.
+    #[test]
+    fn signing_key_matches_aws_example() {
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn uri_encoding_and_list_parsing() {
+        assert_eq!(uri_encode("a b/c~", false), "a%20b/c~");
+        assert_eq!(uri_encode("a b/c~", true), "a%20b%2Fc~");
+        let xml = "<ListBucketResult><IsTruncated>false</IsTruncated>\
+                   <Contents><Key>p/1/a&amp;b</Key></Contents>\
+                   <Contents><Key>p/2/c</Key></Contents></ListBucketResult>";
+        let keys: Vec<_> = xml_tags(xml, "Key").map(xml_unescape).collect();
+        assert_eq!(keys, ["p/1/a&b", "p/2/c"]);
+        assert_eq!(xml_tag(xml, "IsTruncated"), Some("false"));
+        assert_eq!(xml_tag(xml, "NextContinuationToken"), None);
+    }
+
+    #[tokio::test]
+    async fn run_copies_databases_and_writes_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let kernel_db = dir.path().join("kernel.db");
+        let auth_db = dir.path().join("auth.db");
+        sqlite_file(&kernel_db, 100);
+        sqlite_file(&auth_db, 3);
+        let target = LocalDir::new(dir.path().join("backups"));
+        let sources = BackupSources {
+            kernel_db,
+            auth_db: Some(auth_db),
+        };
+
+        let report = run_backup(&target, &sources, 3).await.unwrap();
+        assert_eq!(
+            report.files,
+            [
+                format!("{}/kernel.db", report.run),
+                format!("{}/auth.db", report.run),
+                format!("{}/manifest.json", report.run),
+            ]
+        );
+        assert!(report.pruned.is_empty());
+
+        // The copy is a standalone database with every row.
+        let copy = dir
+            .path()
+            .join("backups")
+            .join(&report.run)
+            .join("kernel.db");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&copy).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "backups are private");
+        }
+        let conn = rusqlite::Connection::open(copy).unwrap();
+        let rows: i64 = conn
+            .query_row("SELECT count(*) FROM t", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(rows, 100);
+
+        // No scratch copy is left beside the databases.
+        let stray: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .filter(|name| name.to_string_lossy().contains(".backup-"))
+            .collect();
+        assert!(stray.is_empty(), "{stray:?}");
+    }
+
+    #[tokio::test]
+    async fn retention_keeps_newest_complete_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = LocalDir::new(dir.path().to_path_buf());
+        for run in ["20260101T000000Z", "20260102T000000Z", "20260103T000000Z"] {
+            target
+                .put(&format!("{run}/kernel.db"), vec![1])
+                .await
+                .unwrap();
+            target
+                .put(&format!("{run}/manifest.json"), vec![2])
+                .await
+                .unwrap();
+        }
+        // Newest run is incomplete, and a stray file isn't a run at all.
+        target
+            .put("20260104T000000Z/kernel.db", vec![1])
+            .await
+            .unwrap();
+        target.put("notes/readme.txt", vec![3]).await.unwrap();
+
+        let pruned = prune(&target, 2).await.unwrap();
+        assert_eq!(pruned, ["20260101T000000Z"]);
+        let mut keys = target.list().await.unwrap();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "20260102T000000Z/kernel.db",
+                "20260102T000000Z/manifest.json",
+                "20260103T000000Z/kernel.db",
+                "20260103T000000Z/manifest.json",
+                "20260104T000000Z/kernel.db",
+                "notes/readme.txt",
+            ]
+        );
+        assert!(!dir.path().join("20260101T000000Z").exists());
+
+        // Fewer complete runs than `keep`: nothing goes.
+        assert!(prune(&target, 5).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,401 @@
+//! Broadcast prompts — the kernel side of `broadcastPrompt`.
+//!
+//! [`start_broadcast`] creates a comparison context holding the prompt, then
+//! runs one task per target: insert the prompt as a user block, spawn the
+//! target's LLM turn (on the target's own model), wait for the stream to end,
+//! and append whatever the model answered to the comparison context under a
+//! heading naming the target. Targets run concurrently and fail independently;
+//! [`BroadcastRegistry`] keeps each run's progress for `getBroadcast`.
+//!
+//! Runs live in memory only. The answers themselves are ordinary blocks, so
+//! they outlive a restart even though the run's status does not.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use kaijutsu_crdt::{BlockId, BlockKind, ContentType, Role, Status};
+use kaijutsu_kernel::SharedBlockStore;
+use kaijutsu_types::broadcast::validate_targets;
+use kaijutsu_types::{
+    BroadcastState, BroadcastStatus, BroadcastTarget, BroadcastTargetStatus, ContextId,
+    PrincipalId, SessionId,
+};
+use parking_lot::Mutex;
+
+use crate::llm_stream::spawn_llm_for_prompt;
+use crate::rpc::{SharedKernel, context_cwd, create_context_inner};
+
+/// Finished runs kept for `getBroadcast`; the oldest finished ones go first.
+const RETAINED_BROADCASTS: usize = 32;
+
+#[derive(Default)]
+struct Runs {
+    next_id: u64,
+    by_id: BTreeMap<u64, BroadcastStatus>,
+}
+
+/// Kernel-wide table of broadcast runs.
+#[derive(Clone, Default)]
+pub struct BroadcastRegistry {
+    inner: Arc<Mutex<Runs>>,
+}
+
+impl BroadcastRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a new run with every target pending.
+    fn register(
+        &self,
+        comparison_context_id: ContextId,
+        prompt: &str,
+        targets: &[BroadcastTarget],
+    ) -> BroadcastStatus {
+        let mut runs = self.inner.lock();
+        runs.next_id += 1;
+        let status = BroadcastStatus {
+            id: runs.next_id,
+            comparison_context_id,
+            prompt: prompt.to_string(),
+            targets: targets.iter().map(BroadcastTargetStatus::pending).collect(),
+            started_at: kaijutsu_types::now_millis(),
+        };
+        runs.by_id.insert(status.id, status.clone());
+        if runs.by_id.len() > RETAINED_BROADCASTS
+            && let Some(oldest) = runs
+                .by_id
+                .iter()
+                .find(|(_, s)| s.is_finished())
+                .map(|(id, _)| *id)
+        {
+            runs.by_id.remove(&oldest);
+        }
+        status
+    }
+
+    pub fn get(&self, id: u64) -> Option<BroadcastStatus> {
+        self.inner.lock().by_id.get(&id).cloned()
+    }
+
+    fn update(&self, id: u64, target: usize, f: impl FnOnce(&mut BroadcastTargetStatus)) {
+        if let Some(t) = self
+            .inner
+            .lock()
+            .by_id
+            .get_mut(&id)
+            .and_then(|s| s.targets.get_mut(target))
+        {
+            f(t);
+        }
+    }
+}
+
+/// Start a broadcast and return its initial status; the turns run in the
+/// background. Fails before anything is written if the targets are invalid
+/// or name a context that doesn't exist.
+pub(crate) async fn start_broadcast(
+    kernel: &SharedKernel,
+    prompt: &str,
+    targets: Vec<BroadcastTarget>,
+    label: Option<&str>,
+    principal_id: PrincipalId,
+    session_id: SessionId,
+) -> Result<BroadcastStatus, capnp::Error> {
+    validate_targets(&targets).map_err(capnp::Error::failed)?;
+    if prompt.trim().is_empty() {
+        return Err(capnp::Error::failed("broadcast prompt is empty".into()));
+    }
+    if let Some(missing) = targets
+        .iter()
+        .find(|t| kernel.documents.get(t.context_id).is_none())
+    {
+        return Err(capnp::Error::failed(format!(
+            "context {} not found",
+            missing.context_id
+        )));
+    }
+
+    let comparison = ContextId::new();
+    let label = label
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("broadcast: {}", first_line(prompt)));
+    create_context_inner(
+        kernel,
+        comparison,
+        "default",
+        Some(&label),
+        principal_id,
+        None,
+        session_id,
+    )
+    .await?;
+    let prompt_block = kernel
+        .documents
+        .insert_block_as(
+            comparison,
+            None,
+            None,
+            Role::User,
+            BlockKind::Text,
+            prompt,
+            Status::Done,
+            ContentType::Plain,
+            Some(principal_id),
+        )
+        .map_err(|e| capnp::Error::failed(format!("failed to record broadcast prompt: {e}")))?;
+
+    let status = kernel.broadcasts.register(comparison, prompt, &targets);
+    log::info!(
+        "broadcast {} → {} contexts, comparing in {}",
+        status.id,
+        targets.len(),
+        comparison.short()
+    );
+    for (index, target) in targets.into_iter().enumerate() {
+        let kernel = kernel.clone();
+        let prompt = prompt.to_string();
+        let id = status.id;
+        tokio::task::spawn_local(async move {
+            let outcome = run_target(
+                &kernel,
+                id,
+                index,
+                &target,
+                &prompt,
+                principal_id,
+                session_id,
+            )
+            .await;
+            let heading = target_heading(&kernel, &target);
+            let (body, state) = match &outcome {
+                Ok((_, text)) => (text.clone(), BroadcastState::Done),
+                Err(e) => (format!("_failed: {e}_"), BroadcastState::Failed),
+            };
+            kernel.broadcasts.update(id, index, |t| {
+                t.state = state;
+                match outcome {
+                    Ok((block, _)) => t.response_block = Some(block),
+                    Err(e) => t.error = Some(e),
+                }
+            });
+            if let Err(e) = kernel.documents.insert_block_as(
+                comparison,
+                Some(&prompt_block),
+                kernel.documents.last_block_id(comparison).as_ref(),
+                Role::Model,
+                BlockKind::Text,
+                format!("### {heading}\n\n{body}"),
+                Status::Done,
+                ContentType::Markdown,
+                Some(PrincipalId::system()),
+            ) {
+                log::warn!("broadcast {id}: failed to record answer for {heading}: {e}");
+            }
+        });
+    }
+    Ok(status)
+}
+
+/// Run one target's turn to the end and collect its answer.
+async fn run_target(
+    kernel: &SharedKernel,
+    id: u64,
+    index: usize,
+    target: &BroadcastTarget,
+    prompt: &str,
+    principal_id: PrincipalId,
+    session_id: SessionId,
+) -> Result<(BlockId, String), String> {
+    let context_id = target.context_id;
+    let documents = &kernel.documents;
+    let user_block = documents
+        .insert_block_as(
+            context_id,
+            None,
+            documents.last_block_id(context_id).as_ref(),
+            Role::User,
+            BlockKind::Text,
+            prompt,
+            Status::Done,
+            ContentType::Plain,
+            Some(principal_id),
+        )
+        .map_err(|e| format!("failed to insert prompt: {e}"))?;
+
+    let cwd = context_cwd(kernel, context_id).unwrap_or_else(|| std::path::PathBuf::from("/"));
+    let tool_ctx =
+        kaijutsu_kernel::ExecContext::new(principal_id, context_id, cwd, session_id, kernel.id);
+    // Human-initiated, so announce_completion=false like `prompt` (design §7).
+    let stream = spawn_llm_for_prompt(
+        kernel,
+        context_id,
+        target.model.as_deref(),
+        None,
+        &user_block,
+        tool_ctx,
+        principal_id,
+        false,
+    )
+    .await
+    .map_err(|e| e.extra)?;
+    kernel
+        .broadcasts
+        .update(id, index, |t| t.state = BroadcastState::Running);
+    stream
+        .await
+        .map_err(|e| format!("turn task ended abnormally: {e}"))?;
+
+    collect_response(documents, context_id, &user_block)
+}
+
+/// The answer to the prompt at `after`: the last non-empty model text block
+/// after it. A turn that errored out reports its last error block instead.
+fn collect_response(
+    documents: &SharedBlockStore,
+    context_id: ContextId,
+    after: &BlockId,
+) -> Result<(BlockId, String), String> {
+    let blocks = documents
+        .block_snapshots(context_id)
+        .map_err(|e| format!("failed to read answer: {e}"))?;
+    let start = blocks
+        .iter()
+        .position(|b| &b.id == after)
+        .ok_or_else(|| "prompt block disappeared".to_string())?;
+    let turn = &blocks[start + 1..];
+    if let Some(answer) = turn.iter().rev().find(|b| {
+        b.role == Role::Model && b.kind == BlockKind::Text && !b.content.trim().is_empty()
+    }) {
+        return Ok((answer.id, answer.content.clone()));
+    }
+    Err(turn
+        .iter()
+        .rev()
+        .find(|b| b.kind == BlockKind::Error)
+        .map(|b| b.content.clone())
+        .unwrap_or_else(|| "the turn ended without an answer".to_string()))
+}
+
+/// `label (model)` for a target, falling back to the short context id.
+fn target_heading(kernel: &SharedKernel, target: &BroadcastTarget) -> String {
+    let row = kernel
+        .kernel_db
+        .lock()
+        .get_context(target.context_id)
+        .ok()
+        .flatten();
+    let name = row
+        .as_ref()
+        .and_then(|r| r.label.clone())
+        .unwrap_or_else(|| target.context_id.short());
+    let model = target
+        .model
+        .clone()
+        .or_else(|| row.and_then(|r| r.model))
+        .unwrap_or_else(|| "default model".to_string());
+    format!("{name} ({model})")
+}
+
+fn first_line(prompt: &str) -> String {
+    let line = prompt.trim().lines().next().unwrap_or_default();
+    match line.char_indices().nth(48) {
+        Some((at, _)) => format!("{}…", &line[..at]),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaijutsu_kernel::{DocumentKind, shared_block_store};
+
+    fn insert(
+        documents: &SharedBlockStore,
+        ctx: ContextId,
+        role: Role,
+        kind: BlockKind,
+        content: &str,
+    ) -> BlockId {
+        documents
+            .insert_block_as(
+                ctx,
+                None,
+                documents.last_block_id(ctx).as_ref(),
+                role,
+                kind,
+                content,
+                Status::Done,
+                ContentType::Plain,
+                Some(PrincipalId::new()),
+            )
+            .expect("insert block")
+    }
+
+    #[test]
+    fn collect_response_takes_the_last_answer_after_the_prompt() {
+        let documents = shared_block_store(PrincipalId::new());
+        let ctx = ContextId::new();
+        documents
+            .create_document(ctx, DocumentKind::Conversation, None)
+            .expect("create document");
+        insert(
+            &documents,
+            ctx,
+            Role::Model,
+            BlockKind::Text,
+            "an older answer",
+        );
+        let prompt = insert(&documents, ctx, Role::User, BlockKind::Text, "which?");
+        assert_eq!(
+            collect_response(&documents, ctx, &prompt).unwrap_err(),
+            "the turn ended without an answer"
+        );
+
+        insert(
+            &documents,
+            ctx,
+            Role::Model,
+            BlockKind::Error,
+            "rate limited",
+        );
+        assert_eq!(
+            collect_response(&documents, ctx, &prompt).unwrap_err(),
+            "rate limited"
+        );
+
+        insert(
+            &documents,
+            ctx,
+            Role::Model,
+            BlockKind::Text,
+            "let me check",
+        );
+        insert(&documents, ctx, Role::Model, BlockKind::ToolCall, "{}");
+        let answer = insert(&documents, ctx, Role::Model, BlockKind::Text, "this one");
+        assert_eq!(
+            collect_response(&documents, ctx, &prompt).unwrap(),
+            (answer, "this one".to_string())
+        );
+    }
+
+    #[test]
+    fn registry_tracks_targets_and_evicts_oldest_finished() {
+        let registry = BroadcastRegistry::new();
+        let targets = [BroadcastTarget {
+            context_id: ContextId::new(),
+            model: Some("m".into()),
+        }];
+        let first = registry.register(ContextId::new(), "p", &targets);
+        assert_eq!(first.targets[0].state, BroadcastState::Pending);
+        registry.update(first.id, 0, |t| t.state = BroadcastState::Done);
+        assert!(registry.get(first.id).unwrap().is_finished());
+
+        let unfinished = registry.register(ContextId::new(), "p", &targets);
+        for _ in 0..RETAINED_BROADCASTS {
+            registry.register(ContextId::new(), "p", &targets);
+        }
+        assert!(registry.get(first.id).is_none(), "finished run evicted");
+        assert!(registry.get(unfinished.id).is_some(), "running run kept");
+    }
+}
@@ -0,0 +1,252 @@
+//! Text-op batching for the block event bridge.
+//!
+//! A streaming model turn publishes one `BlockFlow::TextOps` per token. Sent
+//! one callback each, that is one capnp round-trip, one SSH packet and one
+//! client wakeup per token, for every attached seat. [`BlockEventBatcher`]
+//! sits between a subscriber's FlowBus subscription and its callback and
+//! folds consecutive text ops of one document into a single
+//! `onBlockTextOpsBatch`.
+//!
+//! Batching is adaptive. An edit that arrives while the bridge is idle goes
+//! out at once — whatever else is already queued rides along, but nothing
+//! waits. Only when text ops keep coming (the last batch went out less than
+//! [`EVENT_BATCH_WINDOW`] ago) does the bridge hold a batch open for the rest
+//! of the window. A keystroke is never delayed; a token stream costs one
+//! callback per window.
+//!
+//! A batch holds one document's text ops in the order they were published.
+//! Any other event — another document's ops, an insert, a status change —
+//! closes it, and is handed out right after, so the order a subscriber sees
+//! is the order the bus delivered.
+
+use std::time::{Duration, Instant};
+
+use kaijutsu_kernel::{BlockFlow, Subscription};
+use kaijutsu_types::{BlockEventFilter, ContextId};
+
+/// How long a batch stays open under a steady stream of text ops.
+pub const EVENT_BATCH_WINDOW: Duration = Duration::from_millis(30);
+
+/// Text ops per batch; a fuller batch goes out without waiting out the
+/// window.
+pub const MAX_BATCH_OPS: usize = 256;
+
+/// What the bridge forwards next.
+#[derive(Debug)]
+pub enum BridgeEvent {
+    /// One event, forwarded as it is.
+    Flow(BlockFlow),
+    /// Two or more `BlockFlow::TextOps` of `context_id`, oldest first.
+    TextOps {
+        context_id: ContextId,
+        flows: Vec<BlockFlow>,
+    },
+}
+
+struct OpenBatch {
+    context_id: ContextId,
+    flows: Vec<BlockFlow>,
+    /// When the batch goes out even if more ops could join.
+    deadline: Instant,
+}
+
+/// A block subscription that hands out text ops in per-document batches.
+///
+/// [`next`](Self::next) is cancel-safe: the open batch and any event read
+/// past it live in the batcher, so a `select!` that drops the future loses
+/// nothing.
+pub struct BlockEventBatcher {
+    sub: Subscription<BlockFlow>,
+    /// Server-side filter; events it rejects are dropped here.
+    filter: Option<BlockEventFilter>,
+    open: Option<OpenBatch>,
+    /// An event that closed the last batch, handed out next.
+    held: Option<BlockFlow>,
+    /// When the last batch of text ops went out.
+    last_batch: Option<Instant>,
+}
+
+impl BlockEventBatcher {
+    pub fn new(sub: Subscription<BlockFlow>, filter: Option<BlockEventFilter>) -> Self {
+        Self {
+            sub,
+            filter,
+            open: None,
+            held: None,
+            last_batch: None,
+        }
+    }
+
+    /// The next event or batch. `None` once the subscription is closed and
+    /// everything read from it has been handed out.
+    pub async fn next(&mut self) -> Option<BridgeEvent> {
+        if self.open.is_none() {
+            let flow = match self.held.take() {
+                Some(flow) => flow,
+                None => self.recv().await?,
+            };
+            if !matches!(flow, BlockFlow::TextOps { .. }) {
+                return Some(BridgeEvent::Flow(flow));
+            }
+            let now = Instant::now();
+            let streaming = self
+                .last_batch
+                .is_some_and(|at| now.duration_since(at) < EVENT_BATCH_WINDOW);
+            self.open = Some(OpenBatch {
+                context_id: flow.context_id(),
+                flows: vec![flow],
+                deadline: if streaming {
+                    now + EVENT_BATCH_WINDOW
+                } else {
+                    now
+                },
+            });
+        }
+
+        loop {
+            let open = self.open.as_ref()?;
+            if open.flows.len() >= MAX_BATCH_OPS {
+                break;
+            }
+            let deadline = open.deadline;
+            let next = match self.try_recv() {
+                Some(flow) => Some(flow),
+                None if Instant::now() < deadline => {
+                    let deadline = tokio::time::Instant::from_std(deadline);
+                    tokio::time::timeout_at(deadline, self.recv())
+                        .await
+                        .ok()
+                        .flatten()
+                }
+                None => None,
+            };
+            let Some(flow) = next else { break };
+            if let Some(open) = self.open.as_mut()
+                && matches!(flow, BlockFlow::TextOps { .. })
+                && flow.context_id() == open.context_id
+            {
+                open.flows.push(flow);
+            } else {
+                self.held = Some(flow);
+                break;
+            }
+        }
+        self.flush()
+    }
+
+    fn flush(&mut self) -> Option<BridgeEvent> {
+        let OpenBatch {
+            context_id,
+            mut flows,
+            ..
+        } = self.open.take()?;
+        self.last_batch = Some(Instant::now());
+        if flows.len() == 1 {
+            return flows.pop().map(BridgeEvent::Flow);
+        }
+        Some(BridgeEvent::TextOps { context_id, flows })
+    }
+
+    fn passes(&self, flow: &BlockFlow) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| flow.matches_filter(filter))
+    }
+
+    async fn recv(&mut self) -> Option<BlockFlow> {
+        loop {
+            let flow = self.sub.recv().await?.payload;
+            if self.passes(&flow) {
+                return Some(flow);
+            }
+        }
+    }
+
+    fn try_recv(&mut self) -> Option<BlockFlow> {
+        loop {
+            let flow = self.sub.try_recv()?.payload;
+            if self.passes(&flow) {
+                return Some(flow);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use kaijutsu_kernel::{FlowBus, OpSource};
+    use kaijutsu_types::{BlockId, PrincipalId};
+
+    fn text_ops(block_id: BlockId, seq_num: u64) -> BlockFlow {
+        BlockFlow::TextOps {
+            context_id: block_id.context_id,
+            block_id,
+            ops: Arc::from(vec![seq_num as u8]),
+            source: OpSource::Local,
+            seq_num,
+            generation: 1,
+        }
+    }
+
+    fn seqs(event: BridgeEvent) -> Vec<u64> {
+        let flows = match event {
+            BridgeEvent::Flow(flow) => vec![flow],
+            BridgeEvent::TextOps { flows, .. } => flows,
+        };
+        flows
+            .into_iter()
+            .map(|flow| match flow {
+                BlockFlow::TextOps { seq_num, .. } => seq_num,
+                other => panic!("expected text ops, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn queued_ops_of_one_document_go_out_together() {
+        let bus = FlowBus::<BlockFlow>::new(64);
+        let mut batcher = BlockEventBatcher::new(bus.subscribe("block.*"), None);
+        let block = BlockId::new(ContextId::new(), PrincipalId::new(), 1);
+        let other = BlockId::new(ContextId::new(), PrincipalId::new(), 1);
+
+        for seq in 0..3 {
+            bus.publish(text_ops(block, seq));
+        }
+        bus.publish(text_ops(other, 0));
+
+        assert_eq!(seqs(batcher.next().await.unwrap()), vec![0, 1, 2]);
+        match batcher.next().await.unwrap() {
+            BridgeEvent::Flow(flow) => assert_eq!(flow.context_id(), other.context_id),
+            batch => panic!("a lone op goes out as itself, got {batch:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn a_stream_waits_out_the_window_an_idle_edit_does_not() {
+        let bus = FlowBus::<BlockFlow>::new(64);
+        let mut batcher = BlockEventBatcher::new(bus.subscribe("block.*"), None);
+        let block = BlockId::new(ContextId::new(), PrincipalId::new(), 1);
+
+        bus.publish(text_ops(block, 0));
+        assert_eq!(seqs(batcher.next().await.unwrap()), vec![0]);
+
+        // Within a window of that send: the batch stays open for more.
+        bus.publish(text_ops(block, 1));
+        let publisher = bus.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(EVENT_BATCH_WINDOW / 3).await;
+            publisher.publish(text_ops(block, 2));
+        });
+        assert_eq!(seqs(batcher.next().await.unwrap()), vec![1, 2]);
+
+        // Idle again: the next op goes straight out.
+        tokio::time::sleep(EVENT_BATCH_WINDOW * 2).await;
+        bus.publish(text_ops(block, 3));
+        let started = Instant::now();
+        assert_eq!(seqs(batcher.next().await.unwrap()), vec![3]);
+        assert!(started.elapsed() < EVENT_BATCH_WINDOW);
+    }
+}
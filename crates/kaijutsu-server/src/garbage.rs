@@ -0,0 +1,141 @@
+//! Garbage scan — state a long-lived server holds but can no longer reach
+//! (`scanGarbage` RPC, `kj gc`).
+//!
+//! Four kinds, each an id some table or map still carries after the thing it
+//! names is gone:
+//! - **orphan documents** — conversation documents no context row owns: a
+//!   `kj context remove` whose document delete failed, or a create that died
+//!   between the two inserts. Their oplogs and snapshots go with them.
+//! - **ghost contexts** — registered in the drift router with no `contexts`
+//!   row: the kernel that recorded them is gone, but this process still
+//!   routes drift to them.
+//! - **stale sessions** — a connection's current context (its seat) naming a
+//!   deleted context.
+//! - **stale client views** — a client's last-viewed context naming a deleted
+//!   context; the reconnect restore would fail on it.
+//!
+//! The scan only reports. With `prune` it also removes what it found; every
+//! removal is idempotent, so a context deleted mid-scan is harmless.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use kaijutsu_kernel::KernelDbError;
+use kaijutsu_types::{ContextId, SessionId};
+
+use crate::rpc::SharedKernelState;
+
+/// Documents younger than this are never orphans: a context create inserts
+/// its document before its context row.
+pub const ORPHAN_GRACE: Duration = Duration::from_secs(60);
+
+/// What one scan found (and, with `pruned`, removed).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GarbageReport {
+    pub orphan_documents: Vec<ContextId>,
+    pub ghost_contexts: Vec<ContextId>,
+    /// `(session, context)` pairs.
+    pub stale_sessions: Vec<(SessionId, ContextId)>,
+    /// `(client_id, context)` pairs.
+    pub stale_client_views: Vec<(String, ContextId)>,
+    pub pruned: bool,
+}
+
+impl GarbageReport {
+    /// Total items found.
+    pub fn total(&self) -> usize {
+        self.orphan_documents.len()
+            + self.ghost_contexts.len()
+            + self.stale_sessions.len()
+            + self.stale_client_views.len()
+    }
+}
+
+/// Scan the kernel for unreachable state; with `prune`, remove it.
+///
+/// The in-memory maps are read before the database: a context is written to
+/// `kernel.db` before it is registered or joined, so anything the maps hold
+/// that the database lacks is really gone, not half-created.
+pub fn scan(state: &SharedKernelState, prune: bool) -> Result<GarbageReport, KernelDbError> {
+    let registered: Vec<ContextId> = {
+        let drift = state.kernel.drift().read();
+        drift.list_contexts().iter().map(|h| h.id).collect()
+    };
+    let sessions: Vec<(SessionId, ContextId)> = state
+        .session_contexts
+        .iter()
+        .map(|e| (*e.key(), *e.value()))
+        .collect();
+
+    let cutoff = kaijutsu_types::now_millis() as i64 - ORPHAN_GRACE.as_millis() as i64;
+    let mut report = {
+        let db = state.kernel_db.lock();
+        let live: HashSet<ContextId> = db
+            .list_all_contexts()?
+            .into_iter()
+            .map(|row| row.context_id)
+            .collect();
+        GarbageReport {
+            orphan_documents: db.orphan_documents(cutoff)?,
+            ghost_contexts: registered
+                .into_iter()
+                .filter(|id| !live.contains(id))
+                .collect(),
+            stale_sessions: sessions
+                .into_iter()
+                .filter(|(_, ctx)| !live.contains(ctx))
+                .collect(),
+            stale_client_views: db.stale_client_views()?,
+            pruned: false,
+        }
+    };
+
+    if report.total() > 0 {
+        tracing::info!(
+            orphan_documents = report.orphan_documents.len(),
+            ghost_contexts = report.ghost_contexts.len(),
+            stale_sessions = report.stale_sessions.len(),
+            stale_client_views = report.stale_client_views.len(),
+            prune,
+            "garbage scan"
+        );
+    }
+    if prune {
+        prune_report(state, &report)?;
+        report.pruned = true;
+    }
+    Ok(report)
+}
+
+fn prune_report(state: &SharedKernelState, report: &GarbageReport) -> Result<(), KernelDbError> {
+    // `delete_document` takes the kernel DB lock itself.
+    for &id in &report.orphan_documents {
+        if let Err(e) = state.documents.delete_document(id) {
+            tracing::warn!(document = %id.short(), "pruning orphan document failed: {e}");
+        }
+    }
+    if !report.ghost_contexts.is_empty() {
+        let mut drift = state.kernel.drift().write();
+        for &id in &report.ghost_contexts {
+            drift.unregister(id);
+        }
+    }
+    for &id in &report.ghost_contexts {
+        if state.documents.contains(id)
+            && let Err(e) = state.documents.delete_document(id)
+        {
+            tracing::warn!(context = %id.short(), "pruning ghost context document failed: {e}");
+        }
+    }
+    for (session, ctx) in &report.stale_sessions {
+        // Only if the session hasn't moved on since the scan.
+        state
+            .session_contexts
+            .remove_if(session, |_, current| current == ctx);
+    }
+    let db = state.kernel_db.lock();
+    for (client_id, _) in &report.stale_client_views {
+        db.delete_client_view(client_id)?;
+    }
+    Ok(())
+}
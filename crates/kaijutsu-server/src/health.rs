@@ -0,0 +1,460 @@
+//! Health listener — liveness and readiness over plain HTTP, for systemd
+//! watchdogs, Kubernetes probes and load balancers (`--health <ADDR>`).
+//!
+//! Off unless asked for. The listener is bound before the kernel is built,
+//! so a probe during startup gets a 503 rather than a refused connection.
+//! Three endpoints, all `GET` (or `HEAD`), all answering JSON:
+//!
+//! - `/livez` — is the process worth keeping? The runtime still schedules
+//!   tasks (a heartbeat ticks every second and must be recent) and the
+//!   kernel DB still takes writes. Failing this means restart.
+//! - `/readyz` — should traffic come here? The SSH listener is accepting,
+//!   the kernel DB is open, the oplog's write-behind queue is flushing,
+//!   and every `/etc/config` file passes its write-time checks. Failing
+//!   this means wait, or go look — not restart, which would lose the queue.
+//! - `/healthz` — both, plus build info and the write-behind queue's depth,
+//!   for a human with `curl`.
+//!
+//! Each check gets [`PROBE_TIMEOUT`]; a DB held by a stuck writer fails the
+//! probe instead of hanging it. The server speaks just enough HTTP/1.1 for
+//! a probe: one request per connection, no keep-alive, no bodies in.
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use kaijutsu_kernel::journal_queue::JournalStats;
+use kaijutsu_kernel::kernel_db::KernelDb;
+
+use crate::rpc::SharedKernel;
+
+/// How often the heartbeat task ticks.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A heartbeat older than this means the runtime has stopped scheduling.
+pub const HEARTBEAT_STALE: Duration = Duration::from_secs(10);
+
+/// Time each check gets before it counts as failed.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest request head read; a probe's is a few dozen bytes.
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// Parse `--health`: `host:port`, or a bare port on loopback.
+pub fn parse_health_addr(s: &str) -> Result<SocketAddr, String> {
+    if let Ok(port) = s.parse::<u16>() {
+        return Ok(SocketAddr::from(([127, 0, 0, 1], port)));
+    }
+    s.parse()
+        .map_err(|_| format!("'{s}' is not a port or host:port address"))
+}
+
+/// What the probes look at, filled in as the server comes up.
+pub struct HealthState {
+    started: Instant,
+    /// Millis since `started` at the last heartbeat.
+    heartbeat_ms: AtomicU64,
+    listening: AtomicBool,
+    kernel: OnceLock<SharedKernel>,
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthState {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            heartbeat_ms: AtomicU64::new(0),
+            listening: AtomicBool::new(false),
+            kernel: OnceLock::new(),
+        }
+    }
+
+    /// The kernel is built; DB and config checks can run.
+    pub fn set_kernel(&self, kernel: SharedKernel) {
+        let _ = self.kernel.set(kernel);
+    }
+
+    /// The SSH listener is accepting connections.
+    pub fn mark_listening(&self) {
+        self.listening.store(true, Ordering::Relaxed);
+    }
+
+    fn beat(&self) {
+        let ms = self.started.elapsed().as_millis() as u64;
+        self.heartbeat_ms.store(ms, Ordering::Relaxed);
+    }
+
+    fn heartbeat_age(&self) -> Duration {
+        let last = Duration::from_millis(self.heartbeat_ms.load(Ordering::Relaxed));
+        self.started.elapsed().saturating_sub(last)
+    }
+}
+
+/// One named check's outcome.
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str) -> Self {
+        Self {
+            name,
+            ok: true,
+            detail: None,
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            ok: false,
+            detail: Some(detail.into()),
+        }
+    }
+
+    fn from_result(name: &'static str, result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self::pass(name),
+            Err(detail) => Self::fail(name, detail),
+        }
+    }
+}
+
+/// Build facts reported by `/healthz`.
+#[derive(Debug, Serialize)]
+struct BuildInfo {
+    version: &'static str,
+    target: String,
+    profile: &'static str,
+}
+
+fn build_info() -> BuildInfo {
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        target: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+        profile: if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        },
+    }
+}
+
+/// Run a blocking DB check off the runtime, bounded by [`PROBE_TIMEOUT`].
+async fn db_check<F>(name: &'static str, kernel: &SharedKernel, check: F) -> Check
+where
+    F: FnOnce(&mut KernelDb) -> Result<(), String> + Send + 'static,
+{
+    let db = kernel.kernel_db.clone();
+    let task = tokio::task::spawn_blocking(move || check(&mut db.lock()));
+    match tokio::time::timeout(PROBE_TIMEOUT, task).await {
+        Ok(Ok(result)) => Check::from_result(name, result),
+        Ok(Err(e)) => Check::fail(name, format!("check panicked: {e}")),
+        Err(_) => Check::fail(name, format!("no answer in {}s", PROBE_TIMEOUT.as_secs())),
+    }
+}
+
+/// The write-behind queue is draining: its last flush, if any, committed.
+fn journal_check(stats: &JournalStats) -> Check {
+    match &stats.last_error {
+        None => Check::pass("journal"),
+        Some(e) => Check::fail(
+            "journal",
+            format!("{} ops queued, last flush failed: {e}", stats.queued),
+        ),
+    }
+}
+
+/// Liveness: the runtime is scheduling and the DB takes writes.
+pub async fn liveness(state: &HealthState) -> Vec<Check> {
+    let age = state.heartbeat_age();
+    let mut checks = vec![if age < HEARTBEAT_STALE {
+        Check::pass("event_loop")
+    } else {
+        Check::fail(
+            "event_loop",
+            format!("last heartbeat {}s ago", age.as_secs()),
+        )
+    }];
+    if let Some(kernel) = state.kernel.get() {
+        checks.push(
+            db_check("kernel_db_writable", kernel, |db| {
+                db.probe_writable().map_err(|e| e.to_string())
+            })
+            .await,
+        );
+    }
+    checks
+}
+
+/// Readiness: accepting SSH, DB open, config valid.
+pub async fn readiness(state: &HealthState) -> Vec<Check> {
+    let mut checks = vec![if state.listening.load(Ordering::Relaxed) {
+        Check::pass("ssh_listener")
+    } else {
+        Check::fail("ssh_listener", "not accepting yet")
+    }];
+    let Some(kernel) = state.kernel.get() else {
+        checks.push(Check::fail("kernel", "starting"));
+        return checks;
+    };
+    checks.push(
+        db_check("kernel_db", kernel, |db| {
+            db.kernel_id().map(|_| ()).map_err(|e| e.to_string())
+        })
+        .await,
+    );
+    checks.push(journal_check(&kernel.documents.journal_stats()));
+    let problems = tokio::time::timeout(
+        PROBE_TIMEOUT,
+        kaijutsu_kernel::kj::config::config_problems(&kernel.kernel),
+    )
+    .await;
+    checks.push(match problems {
+        Ok(problems) if problems.is_empty() => Check::pass("config"),
+        Ok(problems) => Check::fail(
+            "config",
+            problems
+                .iter()
+                .map(|(path, e)| format!("{path}: {e}"))
+                .collect::<Vec<_>>()
+                .join("; "),
+        ),
+        Err(_) => Check::fail(
+            "config",
+            format!("no answer in {}s", PROBE_TIMEOUT.as_secs()),
+        ),
+    });
+    checks
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    status: &'static str,
+    checks: &'a [Check],
+    uptime_secs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build: Option<BuildInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    journal: Option<JournalStats>,
+}
+
+/// Status line and JSON body for `path`.
+async fn respond(state: &HealthState, path: &str) -> (&'static str, String) {
+    let (checks, build, journal) = match path {
+        "/livez" => (liveness(state).await, None, None),
+        "/readyz" => (readiness(state).await, None, None),
+        "/healthz" => {
+            let mut checks = liveness(state).await;
+            checks.extend(readiness(state).await);
+            let journal = state.kernel.get().map(|k| k.documents.journal_stats());
+            (checks, Some(build_info()), journal)
+        }
+        _ => return ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+    };
+    let ok = checks.iter().all(|c| c.ok);
+    let report = Report {
+        status: if ok { "ok" } else { "fail" },
+        checks: &checks,
+        uptime_secs: state.started.elapsed().as_secs(),
+        build,
+        journal,
+    };
+    let body = serde_json::to_string(&report).unwrap_or_default();
+    let status = if ok {
+        "200 OK"
+    } else {
+        "503 Service Unavailable"
+    };
+    (status, body)
+}
+
+/// The method and path (query stripped) of a request head.
+fn request_target(head: &str) -> Option<(&str, &str)> {
+    let mut parts = head.lines().next()?.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    parts.next().filter(|v| v.starts_with("HTTP/"))?;
+    let path = target.split_once('?').map_or(target, |(path, _)| path);
+    Some((method, path))
+}
+
+async fn handle(mut stream: TcpStream, state: Arc<HealthState>) -> std::io::Result<()> {
+    let mut head = Vec::with_capacity(256);
+    let mut buf = [0u8; 1024];
+    let read = async {
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < MAX_REQUEST_HEAD {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            head.extend_from_slice(&buf[..n]);
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    if tokio::time::timeout(PROBE_TIMEOUT, read).await.is_err() {
+        return Ok(());
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let (status, body, send_body) = match request_target(&head) {
+        Some(("GET", path)) => {
+            let (status, body) = respond(&state, path).await;
+            (status, body, true)
+        }
+        Some(("HEAD", path)) => {
+            let (status, body) = respond(&state, path).await;
+            (status, body, false)
+        }
+        Some(_) => (
+            "405 Method Not Allowed",
+            r#"{"error":"method not allowed"}"#.to_string(),
+            true,
+        ),
+        None => (
+            "400 Bad Request",
+            r#"{"error":"bad request"}"#.to_string(),
+            true,
+        ),
+    };
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    if send_body {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Bind the health listener and serve it on the ambient runtime. Binding
+/// fails startup: an operator who asked for probes must not find them
+/// missing.
+pub async fn serve(addr: SocketAddr, state: Arc<HealthState>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    log::info!("Health endpoint on http://{addr} (/livez, /readyz, /healthz)");
+    serve_on_listener(listener, state);
+    Ok(())
+}
+
+/// Serve probes, and tick the heartbeat, on a pre-bound listener.
+pub fn serve_on_listener(listener: TcpListener, state: Arc<HealthState>) {
+    state.beat();
+    let beat = state.clone();
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            tick.tick().await;
+            beat.beat();
+        }
+    });
+
+    tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log::warn!("health accept: {e}");
+                    continue;
+                }
+            };
+            let state = state.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle(stream, state).await {
+                    log::debug!("health probe: {e}");
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ports_and_addresses() {
+        assert_eq!(
+            parse_health_addr("9090").unwrap(),
+            SocketAddr::from(([127, 0, 0, 1], 9090))
+        );
+        assert_eq!(
+            parse_health_addr("0.0.0.0:8080").unwrap(),
+            SocketAddr::from(([0, 0, 0, 0], 8080))
+        );
+        assert!(parse_health_addr("localhost").is_err());
+    }
+
+    #[test]
+    fn request_line_parsing() {
+        assert_eq!(
+            request_target("GET /readyz?verbose=1 HTTP/1.1\r\nHost: x\r\n\r\n"),
+            Some(("GET", "/readyz"))
+        );
+        assert_eq!(request_target("GET /livez\r\n"), None);
+        assert_eq!(request_target(""), None);
+    }
+
+    #[test]
+    fn journal_check_fails_while_flushes_fail() {
+        assert!(journal_check(&JournalStats::default()).ok);
+        let stuck = JournalStats {
+            queued: 7,
+            last_error: Some("disk I/O error".into()),
+            ..Default::default()
+        };
+        let check = journal_check(&stuck);
+        assert!(!check.ok);
+        assert_eq!(
+            check.detail.as_deref(),
+            Some("7 ops queued, last flush failed: disk I/O error")
+        );
+    }
+
+    #[tokio::test]
+    async fn probes_fail_until_the_server_is_up() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        serve_on_listener(listener, Arc::new(HealthState::new()));
+
+        let get = |path: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(format!("GET {path} HTTP/1.1\r\nHost: x\r\n\r\n").as_bytes())
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        let live = get("/livez").await;
+        assert!(live.starts_with("HTTP/1.1 200 OK"), "{live}");
+        let ready = get("/readyz").await;
+        assert!(ready.starts_with("HTTP/1.1 503"), "{ready}");
+        assert!(
+            ready.contains(r#""name":"ssh_listener","ok":false"#),
+            "{ready}"
+        );
+        assert!(get("/nope").await.starts_with("HTTP/1.1 404"));
+
+        let health = get("/healthz").await;
+        assert!(health.contains(env!("CARGO_PKG_VERSION")), "{health}");
+    }
+}
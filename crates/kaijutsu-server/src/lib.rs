@@ -3,15 +3,22 @@
 //! SSH + Cap'n Proto server for kaijutsu.
 
 pub mod auth_db;
+pub mod backup;
 pub mod beat;
+pub mod broadcast;
 pub mod clock;
 pub mod constants;
+pub mod event_batch;
+pub mod garbage;
+pub mod health;
 pub mod interrupt;
 pub mod llm_stream;
 pub mod rpc;
+pub mod seat;
 pub mod sftp;
 pub mod share;
 pub mod ssh;
+pub mod throttle;
 
 // Generated Cap'n Proto code
 pub mod kaijutsu_capnp {
@@ -19,6 +26,7 @@ pub mod kaijutsu_capnp {
 }
 
 pub use auth_db::{AuthDb, SshKeyRecord};
+pub use backup::{BackupConfig, BackupHandle, BackupReport, BackupTarget};
 pub use kaijutsu_kernel::runtime::docs_filesystem::KaijutsuFilesystem;
 pub use kaijutsu_kernel::runtime::embedded_kaish::EmbeddedKaish;
 pub use kaijutsu_kernel::runtime::input_filesystem::InputFilesystem;
@@ -27,3 +35,4 @@ pub use kaijutsu_kernel::runtime::mount_backend::MountBackend;
 pub use kaijutsu_kernel::{ContextHandle, DriftError, DriftRouter, StagedDrift};
 pub use rpc::{ConnectionState, ServerRegistry, SharedKernel, SharedKernelState, WorldImpl};
 pub use ssh::{KeySource, SshServer, SshServerConfig};
+pub use throttle::ConnectionLimits;
@@ -19,10 +19,10 @@ use kaijutsu_crdt::{BlockKind, ContentType, Role, Status};
 use kaijutsu_kernel::flows::TurnFlow;
 use kaijutsu_kernel::kernel_db::KernelDb;
 use kaijutsu_kernel::llm::stream::{BuildOpts, CacheTarget, StreamEvent};
-use kaijutsu_kernel::llm::{ContentBlock, ToolDefinition};
+use kaijutsu_kernel::llm::{ContentBlock, ContextAssembler, ToolDefinition};
 use kaijutsu_kernel::{Kernel, LlmMessage, Provider, SharedBlockStore};
 use kaijutsu_types::ToolKind as TypesToolKind;
-use kaijutsu_types::{ConsentMode, ContextId, PrincipalId};
+use kaijutsu_types::{ConsentMode, ContextId, LlmParams, PrincipalId};
 
 use crate::interrupt::ContextInterruptState;
 use crate::rpc::{ConversationCache, SharedKernelState};
@@ -83,14 +83,21 @@ fn hydrate_messages(
     context_id: ContextId,
     after_block_id: &kaijutsu_crdt::BlockId,
     mailbox: &mut kaijutsu_kernel::ConversationMailbox,
-    // The hydration window policy `(marker, window)`, or `None` to hydrate the
-    // whole history (the default; every non-musician context). When set, the
-    // turn hydrates only `[0, marker] ∪ last-window` — the cost guard for
-    // endless musician logs (design: docs/chameleon.md).
-    policy: Option<(kaijutsu_crdt::BlockId, u32)>,
+    // The context's prompt assembly (`kj context assemble`, or its hydration
+    // window): `linear` folds the whole history incrementally; anything else
+    // rebuilds the selected blocks each turn — e.g. `[0, marker] ∪ last-window`,
+    // the cost guard for endless musician logs (design: docs/chameleon.md).
+    assembler: &ContextAssembler,
 ) -> Result<Vec<LlmMessage>, ()> {
     let read = documents.block_snapshots(context_id);
-    handle_hydration_outcome(documents, context_id, after_block_id, read, mailbox, policy)
+    handle_hydration_outcome(
+        documents,
+        context_id,
+        after_block_id,
+        read,
+        mailbox,
+        assembler,
+    )
 }
 
 /// Turn a block-log read into the wire-history snapshot, or fail the turn.
@@ -105,40 +112,39 @@ fn handle_hydration_outcome(
     after_block_id: &kaijutsu_crdt::BlockId,
     read: kaijutsu_kernel::BlockStoreResult<Vec<kaijutsu_crdt::BlockSnapshot>>,
     mailbox: &mut kaijutsu_kernel::ConversationMailbox,
-    policy: Option<(kaijutsu_crdt::BlockId, u32)>,
+    assembler: &ContextAssembler,
 ) -> Result<Vec<LlmMessage>, ()> {
     match read {
-        Ok(blocks) => {
-            match policy {
-                // Windowed context: rebuild `[0, marker] ∪ last-window` each turn
-                // (a sliding tail can drop a block, which the append-only
-                // catch_up can't express). Applies on cold start too — this is
-                // the same path a restart re-hydrates through, so the marker
-                // bounds cold-start hydration as well as steady state.
-                Some((marker, window)) => {
-                    mailbox.rehydrate_windowed(&blocks, marker, window as usize);
-                    let snapshot = mailbox.snapshot();
-                    log::info!(
-                        "Mailbox windowed-rehydrated (marker {marker}, window {window}): \
-                         {} blocks in log → {} messages on the wire for context {context_id}",
-                        blocks.len(),
-                        snapshot.len(),
-                    );
-                    Ok(snapshot)
-                }
-                None => {
-                    let new_blocks = mailbox.catch_up(&blocks);
-                    let snapshot = mailbox.snapshot();
-                    log::info!(
-                        "Mailbox caught up: +{} new blocks, {} messages on the wire for context {}",
-                        new_blocks,
-                        snapshot.len(),
-                        context_id
-                    );
-                    Ok(snapshot)
-                }
+        // A non-linear assembly rebuilds its selection every turn (a sliding
+        // tail can drop a block, which the append-only catch_up can't
+        // express). Applies on cold start too — this is the same path a
+        // restart re-hydrates through, so a window bounds cold-start hydration
+        // as well as steady state.
+        Ok(blocks) => match assembler.hydrate(mailbox, &blocks) {
+            Ok(kept) => {
+                let snapshot = mailbox.snapshot();
+                log::info!(
+                    "Mailbox assembled ({}): {kept} of {} blocks → {} messages on the wire \
+                     for context {context_id}",
+                    assembler.assembly(),
+                    blocks.len(),
+                    snapshot.len(),
+                );
+                Ok(snapshot)
             }
-        }
+            // Only a `path` stage naming a block that isn't in the log gets
+            // here. Fail the turn rather than guess another selection.
+            Err(e) => {
+                log::error!("Prompt assembly failed for context {context_id}: {e}");
+                let detail = format!(
+                    "Could not assemble this context's prompt ({}): {e}. \
+                     Fix it with `kj context assemble --strategy` or `--clear`.",
+                    assembler.assembly()
+                );
+                insert_pre_stream_error_block(documents, context_id, after_block_id, &detail);
+                Err(())
+            }
+        },
         Err(e) => {
             // Hydration failed. Do NOT fall back to the mailbox snapshot +
             // appended user message — an empty/stale session means the model
@@ -176,15 +182,252 @@ async fn build_tool_definitions(
         .collect())
 }
 
+/// Load the static system prompt from the CRDT-owned config (sole owner;
+/// seeded from the embedded default on a fresh kernel). A read/UTF-8 failure
+/// falls back to the embedded default — loudly, never a silent empty prompt.
+async fn load_system_prompt(kernel: &Kernel) -> String {
+    use kaijutsu_kernel::vfs::VfsOps;
+    match kernel
+        .vfs()
+        .read_all(std::path::Path::new("/etc/config/system.md"))
+        .await
+    {
+        Ok(bytes) => String::from_utf8(bytes).unwrap_or_else(|e| {
+            log::warn!("system.md in the CRDT is not UTF-8: {e}; using embedded default");
+            kaijutsu_kernel::DEFAULT_SYSTEM_PROMPT.to_string()
+        }),
+        Err(e) => {
+            log::warn!("read /etc/config/system.md failed: {e}; using embedded default");
+            kaijutsu_kernel::DEFAULT_SYSTEM_PROMPT.to_string()
+        }
+    }
+}
+
+/// Resolve provider + model from the LLM registry. `model` is the explicit
+/// param or, failing that, the context's (DriftRouter) model; with neither,
+/// the kernel default. Returns `(provider, model, max_output_tokens)`.
+async fn resolve_provider(
+    kernel: &Kernel,
+    model: Option<String>,
+    ctx_provider_name: Option<&str>,
+) -> Result<(Arc<Provider>, String, u64), &'static str> {
+    let registry = kernel.llm().read().await;
+    let max_tokens = registry.max_output_tokens();
+
+    match model {
+        Some(name) => ctx_provider_name
+            .and_then(|pn| registry.get(pn))
+            .or_else(|| registry.default_provider())
+            .map(|p| (p, name, max_tokens))
+            .ok_or("No LLM provider configured (check models.toml)"),
+        None => match registry.default_provider() {
+            Some(p) => {
+                let m = registry
+                    .default_model()
+                    .unwrap_or(kaijutsu_kernel::DEFAULT_MODEL)
+                    .to_string();
+                Ok((p, m, max_tokens))
+            }
+            None => Err("No LLM provider configured (check models.toml)"),
+        },
+    }
+}
+
+/// Why a turn on `provider`/`model` can't succeed, per its health check.
+/// A failing check is re-run first, so a key fixed since the last round
+/// doesn't block the turn; an unreachable provider never blocks (the stream
+/// retries on its own).
+async fn provider_health_blocker(
+    kernel: &Kernel,
+    provider: &Arc<Provider>,
+    model: &str,
+) -> Option<String> {
+    use kaijutsu_kernel::llm::health::{HealthStatus, probe};
+
+    let name = {
+        let registry = kernel.llm().read().await;
+        registry
+            .list()
+            .into_iter()
+            .find(|n| registry.get(n).is_some_and(|p| Arc::ptr_eq(&p, provider)))?
+            .to_string()
+    };
+    let last = kernel.llm_health().get(&name)?;
+    // A missing model only counts against the model that was probed.
+    if last.status == HealthStatus::ModelUnavailable && last.model != model {
+        return None;
+    }
+    last.turn_blocker()?;
+    let now = probe(&name, provider, Some(model)).await;
+    let blocker = now.turn_blocker();
+    kernel.llm_health().record(now);
+    blocker
+}
+
+/// Generation parameters: per-request override > per-context > provider
+/// default. A DB read failure is non-fatal — the turn runs on provider
+/// defaults, like cache breakpoints. Not validated here.
+fn load_llm_params(
+    kernel_db: &parking_lot::Mutex<KernelDb>,
+    context_id: ContextId,
+    over: Option<&LlmParams>,
+) -> LlmParams {
+    let stored = match kernel_db.lock().get_context_llm_params(context_id) {
+        Ok(p) => p.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("Failed to read LLM params for {context_id}: {e} — using provider defaults");
+            LlmParams::default()
+        }
+    };
+    match over {
+        Some(over) => stored.overlay(over),
+        None => stored,
+    }
+}
+
+/// The context's effective prompt assembly: its stored strategy, else its
+/// hydration window, else linear. Corrupt stored rows are errors.
+fn load_assembler(
+    kernel_db: &parking_lot::Mutex<KernelDb>,
+    context_id: ContextId,
+) -> kaijutsu_kernel::kernel_db::KernelDbResult<ContextAssembler> {
+    let db = kernel_db.lock();
+    let policy = db.get_hydration_policy(context_id)?;
+    let stored = db.get_context_assembly(context_id)?;
+    Ok(ContextAssembler::resolve(stored, policy))
+}
+
+/// The full system prompt: static base + rc sections (the `.md` lifecycle
+/// scripts) + the `<situation>` addendum.
+///
+/// rc sections come from `(Role::System, BlockKind::Text)` blocks in the
+/// conversation — typically dropped in by rc-on-create/-on-fork. They land
+/// between the static base and the `<situation>` addendum (matching the doc
+/// layout: base → rc → situation).
+fn situational_system_prompt(
+    documents: &SharedBlockStore,
+    context_id: ContextId,
+    base: &str,
+    situational: &kaijutsu_kernel::SituationalContext,
+) -> String {
+    let rc_sections = documents
+        .block_snapshots(context_id)
+        .map(|b| kaijutsu_kernel::extract_system_prompt_sections(&b))
+        .unwrap_or_default();
+    kaijutsu_kernel::build_system_prompt(base, situational, &rc_sections)
+}
+
+/// Everything a turn in a context would send the model, assembled but not
+/// sent (`contextPreview`).
+pub(crate) struct ContextPreview {
+    pub provider: String,
+    pub model: String,
+    /// The effective assembly spec the messages were selected with.
+    pub strategy: String,
+    pub system_prompt: String,
+    pub tools: Vec<ToolDefinition>,
+    pub messages: Vec<LlmMessage>,
+    pub llm_params: LlmParams,
+    pub total_blocks: usize,
+    pub kept_blocks: usize,
+}
+
+/// Build a context's [`ContextPreview`] through the same steps as
+/// [`spawn_llm_for_prompt`] and [`process_llm_stream`]: system prompt,
+/// provider + model, generation params, the broker's tool list, and the
+/// mailbox hydrated under the context's assembly (or `strategy`, to try one
+/// out without storing it).
+///
+/// Read-only, so it differs from a real turn in two places: auto-compaction
+/// does not run first, and image blocks stay CAS references instead of being
+/// inlined. The conversation cache is untouched — the preview hydrates a
+/// fresh mailbox.
+pub(crate) async fn preview_context(
+    kernel: &SharedKernelState,
+    context_id: ContextId,
+    strategy: Option<kaijutsu_types::Assembly>,
+    principal_id: PrincipalId,
+) -> Result<ContextPreview, String> {
+    let kernel_arc = &kernel.kernel;
+    let documents = &kernel.documents;
+    let base_prompt = load_system_prompt(kernel_arc).await;
+
+    let (ctx_model, ctx_provider_name, ctx_label, ctx_state) = {
+        let drift = kernel_arc.drift().read();
+        match drift.get(context_id) {
+            Some(h) => (
+                h.model.clone(),
+                h.provider.clone(),
+                h.label.clone(),
+                Some(h.state),
+            ),
+            None => (None, None, None, None),
+        }
+    };
+    let (provider, model_name, _) =
+        resolve_provider(kernel_arc, ctx_model, ctx_provider_name.as_deref()).await?;
+    let llm_params = load_llm_params(&kernel.kernel_db, context_id, None);
+
+    let tools = build_tool_definitions(kernel_arc, context_id, principal_id)
+        .await
+        .map_err(|e| format!("could not resolve tool bindings: {e}"))?;
+    let situational = kaijutsu_kernel::SituationalContext {
+        context_id: Some(context_id),
+        context_label: ctx_label,
+        context_state: ctx_state,
+        provider: ctx_provider_name,
+        model: Some(model_name.clone()),
+        tool_names: tools.iter().map(|t| t.name.clone()).collect(),
+    };
+    let system_prompt =
+        situational_system_prompt(documents, context_id, &base_prompt, &situational);
+
+    let assembler = match strategy {
+        Some(assembly) => {
+            let marker = kernel
+                .kernel_db
+                .lock()
+                .get_hydration_policy(context_id)
+                .map_err(|e| e.to_string())?
+                .map(|(marker, _)| marker);
+            ContextAssembler::new(assembly, marker)
+        }
+        None => load_assembler(&kernel.kernel_db, context_id).map_err(|e| e.to_string())?,
+    };
+    let blocks = documents
+        .block_snapshots(context_id)
+        .map_err(|e| e.to_string())?;
+    let mut mailbox = kaijutsu_kernel::ConversationMailbox::new();
+    let kept_blocks = assembler.hydrate(&mut mailbox, &blocks)?;
+
+    Ok(ContextPreview {
+        provider: provider.name().to_string(),
+        model: model_name,
+        strategy: assembler.assembly().to_string(),
+        system_prompt,
+        tools,
+        messages: mailbox.snapshot(),
+        llm_params,
+        total_blocks: blocks.len(),
+        kept_blocks,
+    })
+}
+
 /// Resolve LLM provider and spawn streaming for a user prompt.
 ///
 /// Shared by `prompt` and `submit_input` handlers. Creates the assistant response
 /// flow (thinking -> text -> tool calls -> results) as background blocks via
 /// `process_llm_stream`.
+///
+/// `params` overrides the context's stored generation parameters for this
+/// turn only, field by field (`LlmRequest.params`). Returns the stream task's
+/// handle; awaiting it waits for the turn to end (broadcast prompts do).
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn spawn_llm_for_prompt(
     kernel: &SharedKernelState,
     context_id: ContextId,
     model: Option<&str>,
+    params: Option<&LlmParams>,
     after_block_id: &kaijutsu_crdt::BlockId,
     tool_ctx: kaijutsu_kernel::ExecContext,
     user_principal_id: PrincipalId,
@@ -195,7 +438,7 @@ pub(crate) async fn spawn_llm_for_prompt(
     // keeps the publish-at-stream-end from silently extending Completed to every
     // interactive prompt.
     announce_completion: bool,
-) -> Result<(), capnp::Error> {
+) -> Result<tokio::task::JoinHandle<()>, capnp::Error> {
     let documents = kernel.documents.clone();
     let kernel_arc = kernel.kernel.clone();
     let kernel_db = kernel.kernel_db.clone();
@@ -207,26 +450,7 @@ pub(crate) async fn spawn_llm_for_prompt(
     let (interrupt, interrupt_generation) = kernel.create_interrupt(context_id).await;
     let context_interrupts = kernel.context_interrupts.clone();
 
-    // Load system prompt from the CRDT-owned config (sole owner; seeded from the
-    // embedded default on a fresh kernel). A read/UTF-8 failure falls back to the
-    // embedded default — loudly, never a silent empty prompt.
-    let system_prompt = {
-        use kaijutsu_kernel::vfs::VfsOps;
-        match kernel_arc
-            .vfs()
-            .read_all(std::path::Path::new("/etc/config/system.md"))
-            .await
-        {
-            Ok(bytes) => String::from_utf8(bytes).unwrap_or_else(|e| {
-                log::warn!("system.md in the CRDT is not UTF-8: {e}; using embedded default");
-                kaijutsu_kernel::DEFAULT_SYSTEM_PROMPT.to_string()
-            }),
-            Err(e) => {
-                log::warn!("read /etc/config/system.md failed: {e}; using embedded default");
-                kaijutsu_kernel::DEFAULT_SYSTEM_PROMPT.to_string()
-            }
-        }
-    };
+    let system_prompt = load_system_prompt(&kernel_arc).await;
 
     // Read per-context model from DriftRouter (quick read, release lock).
     // Capture label/state alongside for the situational system-prompt addendum.
@@ -263,33 +487,13 @@ pub(crate) async fn spawn_llm_for_prompt(
         }
     };
 
-    // Resolve provider + model from LLM registry
     // Priority: explicit param > per-context (DriftRouter) > kernel default
-    let provider_resolution: Result<(Arc<Provider>, String, u64), &'static str> = {
-        let registry = kernel_arc.llm().read().await;
-        let max_tokens = registry.max_output_tokens();
-
-        let effective_model = model.map(|m| m.to_string()).or(ctx_model);
-
-        match effective_model {
-            Some(name) => ctx_provider_name
-                .as_deref()
-                .and_then(|pn| registry.get(pn))
-                .or_else(|| registry.default_provider())
-                .map(|p| (p, name, max_tokens))
-                .ok_or("No LLM provider configured (check models.toml)"),
-            None => match registry.default_provider() {
-                Some(p) => {
-                    let m = registry
-                        .default_model()
-                        .unwrap_or(kaijutsu_kernel::DEFAULT_MODEL)
-                        .to_string();
-                    Ok((p, m, max_tokens))
-                }
-                None => Err("No LLM provider configured (check models.toml)"),
-            },
-        }
-    };
+    let provider_resolution = resolve_provider(
+        &kernel_arc,
+        model.map(|m| m.to_string()).or(ctx_model),
+        ctx_provider_name.as_deref(),
+    )
+    .await;
     let (provider, model_name, max_output_tokens) = match provider_resolution {
         Ok(v) => v,
         Err(detail) => {
@@ -299,6 +503,22 @@ pub(crate) async fn spawn_llm_for_prompt(
         }
     };
 
+    // A key the health check saw rejected (or a model it didn't find) fails
+    // here with that reason rather than as a stream error.
+    if let Some(detail) = provider_health_blocker(&kernel_arc, &provider, &model_name).await {
+        log::warn!("Turn refused for context {context_id}: {detail}");
+        insert_pre_stream_error_block(&documents, context_id, after_block_id, &detail);
+        return Err(capnp::Error::failed(detail));
+    }
+
+    // Generation parameters, validated before anything is spawned so a bad
+    // override fails the prompt instead of the provider call.
+    let llm_params = load_llm_params(&kernel_db, context_id, params);
+    if let Err(detail) = llm_params.validate() {
+        insert_pre_stream_error_block(&documents, context_id, after_block_id, &detail);
+        return Err(capnp::Error::failed(detail));
+    }
+
     // Compaction pressure (M1-A5): if the context's live block count is over
     // threshold, summarize the older half into a Drift block and mark the
     // originals compacted so the hydrator skips them. Logs but does not
@@ -332,11 +552,6 @@ pub(crate) async fn spawn_llm_for_prompt(
     // sections (the `.md` lifecycle scripts) + per-call facts so the model
     // has context name, lifecycle state, and current tool inventory without
     // losing the static stance set in assets/defaults/system.md.
-    //
-    // rc sections come from `(Role::System, BlockKind::Text)` blocks in the
-    // conversation — typically dropped in by rc-on-create/-on-fork. They
-    // land between the static base and the `<situation>` addendum (matching
-    // the doc layout: base → rc → situation).
     let situational = kaijutsu_kernel::SituationalContext {
         context_id: Some(context_id),
         context_label: ctx_label,
@@ -345,12 +560,8 @@ pub(crate) async fn spawn_llm_for_prompt(
         model: Some(model_name.clone()),
         tool_names: tools.iter().map(|t| t.name.clone()).collect(),
     };
-    let rc_sections = documents
-        .block_snapshots(context_id)
-        .map(|b| kaijutsu_kernel::extract_system_prompt_sections(&b))
-        .unwrap_or_default();
     let system_prompt =
-        kaijutsu_kernel::build_system_prompt(&system_prompt, &situational, &rc_sections);
+        situational_system_prompt(&documents, context_id, &system_prompt, &situational);
 
     log::info!(
         "Spawning LLM stream: context={}, model={}",
@@ -360,7 +571,7 @@ pub(crate) async fn spawn_llm_for_prompt(
 
     let after_block_id = *after_block_id;
 
-    tokio::task::spawn_local(process_llm_stream(
+    Ok(tokio::task::spawn_local(process_llm_stream(
         provider,
         documents,
         context_id,
@@ -371,6 +582,7 @@ pub(crate) async fn spawn_llm_for_prompt(
         after_block_id,
         system_prompt,
         max_output_tokens,
+        llm_params,
         conversation_cache,
         user_principal_id,
         tool_ctx,
@@ -378,9 +590,7 @@ pub(crate) async fn spawn_llm_for_prompt(
         interrupt_generation,
         context_interrupts,
         announce_completion,
-    ));
-
-    Ok(())
+    )))
 }
 
 /// Agentic-loop iteration cap by consent mode (M1-A6).
@@ -455,7 +665,7 @@ mod hydration_tests {
             &user_block_id,
             read,
             &mut mailbox,
-            None,
+            &ContextAssembler::default(),
         );
 
         // (b) No messages produced — the caller returns early, so the LLM is
@@ -507,8 +717,13 @@ mod hydration_tests {
             .expect("insert user block");
 
         let mut mailbox = kaijutsu_kernel::ConversationMailbox::new();
-        let result =
-            hydrate_messages(&documents, context_id, &user_block_id, &mut mailbox, None);
+        let result = hydrate_messages(
+            &documents,
+            context_id,
+            &user_block_id,
+            &mut mailbox,
+            &ContextAssembler::default(),
+        );
         let messages = result.expect("successful hydration");
         assert!(
             !messages.is_empty(),
@@ -516,7 +731,7 @@ mod hydration_tests {
         );
     }
 
-    /// With a hydration policy `Some((marker, window))`, the turn hydrates only
+    /// With a hydration policy `(marker, window)`, the turn hydrates only
     /// `[0, marker] ∪ last-window` — the archived middle never reaches the wire.
     /// Pins the windowed branch of the hydrate path end to end (read → window →
     /// snapshot), where a mis-wire (e.g. always passing None) would hide.
@@ -551,9 +766,9 @@ mod hydration_tests {
         let last = insert(Role::Model, "a2"); // tail (window 2) = [q2, a2]
 
         let mut mailbox = kaijutsu_kernel::ConversationMailbox::new();
-        let messages =
-            hydrate_messages(&documents, context_id, &last, &mut mailbox, Some((marker, 2)))
-                .expect("windowed hydration");
+        let assembler = ContextAssembler::resolve(None, Some((marker, 2)));
+        let messages = hydrate_messages(&documents, context_id, &last, &mut mailbox, &assembler)
+            .expect("windowed hydration");
         let wire: String = messages
             .iter()
             .filter_map(|m| m.as_text().map(str::to_string))
@@ -607,6 +822,8 @@ async fn process_llm_stream(
     after_block_id: kaijutsu_crdt::BlockId,
     system_prompt: String,
     max_output_tokens: u64,
+    // Effective generation parameters (context's, overlaid by the request's).
+    llm_params: LlmParams,
     conversation_cache: Arc<ConversationCache>,
     // The turn's principal — authors the TurnFlow outcome event (and reserved
     // for future per-user attribution on model-generated blocks).
@@ -637,23 +854,24 @@ async fn process_llm_stream(
     // are skipped, so this is O(new blocks), not O(history).
     // block_snapshots() reads from in-memory DashMap; sub-millisecond
     // for typical conversations.
-    // Read the per-context hydration window policy. A read failure (DB error) or
-    // a corrupt stored policy (unparseable marker / bad window) is a LOUD failure,
-    // not a silent degrade: quietly hydrating full history would disable the cost
-    // guard on a context driving at tempo (unbounded spend) — a silent fallback on
-    // a safety mechanism. Fail the turn like any other hydration failure; an
-    // announced turn must still publish exactly one terminal event (§7).
-    let hydration_policy = match kernel_db.lock().get_hydration_policy(context_id) {
-        Ok(p) => p,
+    // Read the per-context assembly (stored strategy + hydration window policy).
+    // A read failure (DB error) or a corrupt stored row (unparseable marker, bad
+    // window, bad strategy) is a LOUD failure, not a silent degrade: quietly
+    // hydrating full history would disable the cost guard on a context driving
+    // at tempo (unbounded spend) — a silent fallback on a safety mechanism. Fail
+    // the turn like any other hydration failure; an announced turn must still
+    // publish exactly one terminal event (§7).
+    let assembler = match load_assembler(&kernel_db, context_id) {
+        Ok(a) => a,
         Err(e) => {
             log::error!(
-                "Hydration policy read failed for context {context_id}: {e}; failing the turn"
+                "Prompt assembly read failed for context {context_id}: {e}; failing the turn"
             );
             if announce_completion {
                 kernel.turn_flows().publish(TurnFlow::Failed {
                     context_id,
                     principal_id: user_principal_id,
-                    error: format!("hydration policy unreadable: {e}"),
+                    error: format!("prompt assembly unreadable: {e}"),
                 });
             }
             return;
@@ -664,7 +882,7 @@ async fn process_llm_stream(
         context_id,
         &after_block_id,
         &mut mailbox,
-        hydration_policy,
+        &assembler,
     ) {
         Ok(messages) => messages,
         // Hydration failed and surfaced a visible Error block; fail the turn
@@ -826,6 +1044,7 @@ async fn process_llm_stream(
         let build_opts = BuildOpts::new(&model_name)
             .with_system(&system_prompt)
             .with_max_tokens(max_output_tokens)
+            .with_params(&llm_params)
             .with_tools(tools.clone())
             .with_cache_breakpoints(cache_breakpoints);
 
@@ -1379,17 +1598,39 @@ async fn process_llm_stream(
                     // (M2-B5) flows through to the broker so a hard
                     // interrupt aborts in-flight work — without this the
                     // user waits the full 120s timeout.
+                    //
+                    // A call `consent.toml` asks about waits for the user's
+                    // answer first, outside the timeout; a refusal reaches the
+                    // model as an ordinary tool failure.
                     const TOOL_TIMEOUT_SECS: u64 = 120;
-                    let result = tokio::time::timeout(
-                        std::time::Duration::from_secs(TOOL_TIMEOUT_SECS),
-                        kernel.dispatch_tool_via_broker_with_cancel(
-                            &tool_name,
-                            &params,
-                            &tool_ctx,
-                            interrupt.cancel.clone(),
-                        ),
+                    let consent = kaijutsu_kernel::consent_prompts::ask(
+                        &kernel,
+                        &documents,
+                        tool_ctx.context_id,
+                        tool_ctx.principal_id,
+                        tool_ctx.session_id,
+                        &tool_name,
+                        &input,
+                        &interrupt.cancel,
                     )
                     .await;
+                    let result = match consent {
+                        Err(refusal) => Ok(Ok(kaijutsu_kernel::execution::ExecResult::failure(
+                            1, refusal,
+                        ))),
+                        Ok(()) => {
+                            tokio::time::timeout(
+                                std::time::Duration::from_secs(TOOL_TIMEOUT_SECS),
+                                kernel.dispatch_tool_via_broker_with_cancel(
+                                    &tool_name,
+                                    &params,
+                                    &tool_ctx,
+                                    interrupt.cancel.clone(),
+                                ),
+                            )
+                            .await
+                        }
+                    };
 
                     let (result_content, is_error, error_payload) = match result {
                         Err(_elapsed) => {
@@ -1463,6 +1704,19 @@ async fn process_llm_stream(
                             log::error!("Failed to write tool result text: {}", e);
                         }
 
+                        // Tag the output's language (from the tool's `path`
+                        // argument, else sniffed) for highlighting.
+                        if !is_error
+                            && let Some(lang) = kaijutsu_types::language::language_for_tool_output(
+                                &input,
+                                &result_content,
+                            )
+                            && let Err(e) =
+                                documents.set_language(context_id, rb_id, Some(lang.to_string()))
+                        {
+                            log::error!("Failed to set tool result language: {}", e);
+                        }
+
                         // Step 6: Set final status on result and call blocks
                         let final_status = if is_error {
                             Status::Error
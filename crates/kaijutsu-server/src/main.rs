@@ -6,7 +6,7 @@
 //!
 //! ```bash
 //! # Run the server (default)
-//! kaijutsu-server [port]
+//! kaijutsu-server [port] [--wire-format cbor|json]
 //!
 //! # Key management
 //! kaijutsu-server add-key <pubkey-file> [--nick NAME]
@@ -14,14 +14,34 @@
 //! kaijutsu-server list-keys [username]
 //! kaijutsu-server import <authorized_keys_file>
 //! kaijutsu-server set-nick <old> <new>
+//!
+//! # Backups
+//! kaijutsu-server --backup-to s3://bucket/kaijutsu [--backup-every 6h] [--backup-keep 7]
+//! kaijutsu-server backup-now --backup-to /srv/backups
+//!
+//! # Per-connection limits (shared servers)
+//! kaijutsu-server --max-channels 8 --max-frames-per-sec 2000 --max-bytes-per-sec 4M
+//!
+//! # HTTP health probes (/livez, /readyz, /healthz)
+//! kaijutsu-server --health 127.0.0.1:8087
+//!
+//! # Share-only logins as SSH user `observer`, any key
+//! kaijutsu-server --allow-observers
 //! ```
 
 use std::env;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::process::ExitCode;
 
+use kaijutsu_kernel::journal_queue::{self, JournalBatching};
+use kaijutsu_kernel::mcp::{ToolTrace, ToolTraceMode};
+use kaijutsu_server::backup::{self, BackupConfig, BackupSources, BackupTarget};
 use kaijutsu_server::constants::DEFAULT_SSH_PORT;
+use kaijutsu_server::throttle::{self, ConnectionLimits};
 use kaijutsu_server::{AuthDb, SshServer, SshServerConfig};
+use kaijutsu_types::SSH_OBSERVER_USER;
+use kaijutsu_types::codec::WireFormat;
 use russh::keys::ssh_key::{self, HashAlg};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -40,26 +60,59 @@ COMMANDS:
     list-keys [username]          List keys (all or for a specific user)
     import <file>                 Import keys from authorized_keys file
     set-nick <old> <new>          Rename a user
+    backup-now                    Back up kernel.db + auth.db once to --backup-to
 
 OPTIONS:
     --port <PORT>                 SSH port (default: {port})
     --nick <NAME>                 Username for the key (default: derived from fingerprint)
+    --wire-format <cbor|json>     Encoding for ops pushed in block events (default: cbor).
+                                  json is for debugging; clients decode either
+    --record-tools <FILE>         Record every tool call and its result to FILE (JSON lines)
+    --replay-tools <FILE>         Serve tool results from a recorded FILE instead of
+                                  running the tools
+    --backup-to <DEST>            Back up kernel.db + auth.db to a directory or
+                                  s3://bucket/prefix (AWS_ACCESS_KEY_ID,
+                                  AWS_SECRET_ACCESS_KEY, AWS_REGION, AWS_ENDPOINT_URL)
+    --backup-every <INTERVAL>     Time between backups: 90, 30m, 6h, 1d (default: 1d)
+    --backup-keep <N>             Backups kept; older ones are deleted (default: {keep})
+    --max-channels <N>            Concurrent SSH channels per connection (default: {channels};
+                                  0 = unlimited)
+    --max-frames-per-sec <N>      RPC reads + writes per second per connection; over it the
+                                  connection is slowed, not dropped (default: 0 = unlimited)
+    --max-bytes-per-sec <RATE>    RPC bytes per second per connection, both directions:
+                                  262144, 512k, 4M (default: 0 = unlimited)
+    --health <ADDR>               Serve HTTP health probes (/livez, /readyz, /healthz) on
+                                  ADDR; a bare port binds 127.0.0.1 (default: off)
+    --journal-flush <INTERVAL>    Commit block ops in batches this often: 20ms, 250ms, 1s,
+                                  up to 5s; a crash loses at most one interval of writes.
+                                  off commits every op before it returns (default: off)
+    --allow-observers             Accept SSH user "observer" with any key; such connections
+                                  can only open context shares by token (default: off)
     --help, -h                    Show this help
 
 EXAMPLES:
     kaijutsu-server                           # Run server on port {port}
     kaijutsu-server --port 2222               # Run server on port 2222
+    kaijutsu-server --wire-format json        # Readable ops on the wire
+    kaijutsu-server --replay-tools run.jsonl  # Rerun against recorded tool output
     kaijutsu-server add-key ~/.ssh/id_ed25519.pub --nick amy
     kaijutsu-server import ~/.ssh/authorized_keys
     kaijutsu-server list-users
     kaijutsu-server list-keys amy
     kaijutsu-server set-nick xyz789ab amy
     kaijutsu-server remove-user olduser
+    kaijutsu-server --backup-to ~/backups --backup-every 6h
+    kaijutsu-server backup-now --backup-to s3://ops/kaijutsu
+    kaijutsu-server --max-bytes-per-sec 4M    # Keep one seat's bulk sync from starving others
+    kaijutsu-server --health 8087             # Probes on 127.0.0.1:8087 for a supervisor
+    kaijutsu-server --journal-flush 50ms      # Batch op commits; a crash loses up to 50ms
 
 DATABASE:
     Keys are stored in: {db_path}
 "#,
         port = DEFAULT_SSH_PORT,
+        keep = backup::DEFAULT_BACKUP_KEEP,
+        channels = throttle::DEFAULT_MAX_CHANNELS,
         db_path = AuthDb::default_path().display()
     );
 }
@@ -81,11 +134,116 @@ async fn main() -> ExitCode {
         None
     };
 
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+
+    // `--wire-format` applies to every command; pull it out before dispatch.
+    // It only reaches `codec::encode_wire`: kernel.db stays CBOR.
+    if let Some(i) = args.iter().position(|a| a == "--wire-format") {
+        let Some(value) = args.get(i + 1) else {
+            eprintln!("--wire-format requires a value (cbor or json)");
+            return ExitCode::FAILURE;
+        };
+        match value.parse::<WireFormat>() {
+            Ok(format) => {
+                kaijutsu_types::codec::set_wire_format(format);
+                tracing::info!("wire format: {format}");
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        args.drain(i..=i + 1);
+    }
+
+    // `--record-tools` / `--replay-tools` only apply to the server itself.
+    let mut tool_trace = None;
+    for (flag, mode) in [
+        ("--record-tools", ToolTraceMode::Record),
+        ("--replay-tools", ToolTraceMode::Replay),
+    ] {
+        let Some(i) = args.iter().position(|a| a == flag) else {
+            continue;
+        };
+        let Some(path) = args.get(i + 1).map(PathBuf::from) else {
+            eprintln!("{flag} requires a file path");
+            return ExitCode::FAILURE;
+        };
+        if tool_trace.is_some() {
+            eprintln!("--record-tools and --replay-tools are mutually exclusive");
+            return ExitCode::FAILURE;
+        }
+        let opened = match mode {
+            ToolTraceMode::Record => ToolTrace::record(&path),
+            ToolTraceMode::Replay => ToolTrace::replay(&path),
+        };
+        match opened {
+            Ok(trace) => tool_trace = Some(trace),
+            Err(e) => {
+                eprintln!("{flag} {}: {e}", path.display());
+                return ExitCode::FAILURE;
+            }
+        }
+        args.drain(i..=i + 1);
+    }
+
+    // `--backup-*` configure the server's schedule and `backup-now`.
+    let backup = match take_backup_config(&mut args) {
+        Ok(backup) => backup,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // `--max-*` per-connection limits only apply to the server itself.
+    let limits = match take_connection_limits(&mut args) {
+        Ok(limits) => limits,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // `--health` only applies to the server itself.
+    let health = match take_health_addr(&mut args) {
+        Ok(health) => health,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // `--journal-flush` only applies to the server itself.
+    let journal = match take_journal_batching(&mut args) {
+        Ok(journal) => journal,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // `--allow-observers` only applies to the server itself.
+    let allow_observers = match args.iter().position(|a| a == "--allow-observers") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
 
     // Parse command
     if args.len() < 2 {
-        return run_server(DEFAULT_SSH_PORT).await;
+        return run_server(
+            DEFAULT_SSH_PORT,
+            tool_trace,
+            backup,
+            limits,
+            health,
+            journal,
+            allow_observers,
+        )
+        .await;
     }
 
     match args[1].as_str() {
@@ -98,7 +256,16 @@ async fn main() -> ExitCode {
                 .get(2)
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(DEFAULT_SSH_PORT);
-            run_server(port).await
+            run_server(
+                port,
+                tool_trace,
+                backup,
+                limits,
+                health,
+                journal,
+                allow_observers,
+            )
+            .await
         }
         "add-key" => cmd_add_key(&args[2..]),
         "remove-user" => cmd_remove_user(&args[2..]),
@@ -106,10 +273,20 @@ async fn main() -> ExitCode {
         "list-keys" => cmd_list_keys(&args[2..]),
         "import" => cmd_import(&args[2..]),
         "set-nick" => cmd_set_nick(&args[2..]),
+        "backup-now" => cmd_backup_now(backup).await,
         arg => {
             // Try parsing as port number for backwards compatibility
             if let Ok(port) = arg.parse::<u16>() {
-                return run_server(port).await;
+                return run_server(
+                    port,
+                    tool_trace,
+                    backup,
+                    limits,
+                    health,
+                    journal,
+                    allow_observers,
+                )
+                .await;
             }
             eprintln!("Unknown command: {}", arg);
             print_usage();
@@ -118,13 +295,33 @@ async fn main() -> ExitCode {
     }
 }
 
-async fn run_server(port: u16) -> ExitCode {
+async fn run_server(
+    port: u16,
+    tool_trace: Option<ToolTrace>,
+    backup: Option<BackupConfig>,
+    limits: ConnectionLimits,
+    health: Option<SocketAddr>,
+    journal: Option<JournalBatching>,
+    allow_observers: bool,
+) -> ExitCode {
     tracing::info!("Starting kaijutsu server on SSH port {}...", port);
 
-    let config = SshServerConfig::production(port);
+    let mut config = SshServerConfig::production(port)
+        .with_limits(limits)
+        .with_journal(journal)
+        .with_observers(allow_observers);
+    if let Some(trace) = tool_trace {
+        config = config.with_tool_trace(trace);
+    }
+    if let Some(backup) = backup {
+        config = config.with_backup(backup);
+    }
+    if let Some(addr) = health {
+        config = config.with_health(addr);
+    }
     let server = SshServer::new(config);
 
-    if let Err(e) = server.run().await {
+    if let Err(e) = server.run_until(shutdown_signal()).await {
         tracing::error!("Server error: {}", e);
         return ExitCode::FAILURE;
     }
@@ -132,6 +329,173 @@ async fn run_server(port: u16) -> ExitCode {
     ExitCode::SUCCESS
 }
 
+/// Resolves on Ctrl-C, or SIGTERM on Unix — what a supervisor sends to stop
+/// the server.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+            }
+            Err(e) => {
+                tracing::warn!("can't listen for SIGTERM: {e}");
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Pull `--max-channels` / `--max-frames-per-sec` / `--max-bytes-per-sec` out
+/// of `args`, over the defaults.
+fn take_connection_limits(args: &mut Vec<String>) -> Result<ConnectionLimits, String> {
+    let mut take = |flag: &str| -> Result<Option<String>, String> {
+        let Some(i) = args.iter().position(|a| a == flag) else {
+            return Ok(None);
+        };
+        let value = args
+            .get(i + 1)
+            .cloned()
+            .ok_or_else(|| format!("{flag} requires a value"))?;
+        args.drain(i..=i + 1);
+        Ok(Some(value))
+    };
+    let mut limits = ConnectionLimits::default();
+    if let Some(n) = take("--max-channels")? {
+        limits.max_channels = n
+            .parse()
+            .map_err(|_| format!("--max-channels: '{n}' is not a count"))?;
+    }
+    if let Some(n) = take("--max-frames-per-sec")? {
+        limits.max_frames_per_sec = n
+            .parse()
+            .map_err(|_| format!("--max-frames-per-sec: '{n}' is not a count"))?;
+    }
+    if let Some(rate) = take("--max-bytes-per-sec")? {
+        limits.max_bytes_per_sec =
+            throttle::parse_byte_rate(&rate).map_err(|e| format!("--max-bytes-per-sec: {e}"))?;
+    }
+    Ok(limits)
+}
+
+/// Pull `--health <ADDR>` out of `args`.
+fn take_health_addr(args: &mut Vec<String>) -> Result<Option<SocketAddr>, String> {
+    let Some(i) = args.iter().position(|a| a == "--health") else {
+        return Ok(None);
+    };
+    let value = args
+        .get(i + 1)
+        .cloned()
+        .ok_or_else(|| "--health requires an address or port".to_string())?;
+    args.drain(i..=i + 1);
+    kaijutsu_server::health::parse_health_addr(&value)
+        .map(Some)
+        .map_err(|e| format!("--health: {e}"))
+}
+
+/// Pull `--journal-flush <INTERVAL>` out of `args`; without it, every op is
+/// written through.
+fn take_journal_batching(args: &mut Vec<String>) -> Result<Option<JournalBatching>, String> {
+    let Some(i) = args.iter().position(|a| a == "--journal-flush") else {
+        return Ok(None);
+    };
+    let value = args
+        .get(i + 1)
+        .cloned()
+        .ok_or_else(|| "--journal-flush requires an interval or off".to_string())?;
+    args.drain(i..=i + 1);
+    journal_queue::parse_journal_flush(&value).map_err(|e| format!("--journal-flush: {e}"))
+}
+
+/// Pull `--backup-to` / `--backup-every` / `--backup-keep` out of `args`.
+fn take_backup_config(args: &mut Vec<String>) -> Result<Option<BackupConfig>, String> {
+    let mut take = |flag: &str| -> Result<Option<String>, String> {
+        let Some(i) = args.iter().position(|a| a == flag) else {
+            return Ok(None);
+        };
+        let value = args
+            .get(i + 1)
+            .cloned()
+            .ok_or_else(|| format!("{flag} requires a value"))?;
+        args.drain(i..=i + 1);
+        Ok(Some(value))
+    };
+    let to = take("--backup-to")?;
+    let every = take("--backup-every")?;
+    let keep = take("--backup-keep")?;
+
+    let Some(to) = to else {
+        if every.is_some() || keep.is_some() {
+            return Err("--backup-every and --backup-keep need --backup-to".to_string());
+        }
+        return Ok(None);
+    };
+    let target: BackupTarget = to.parse().map_err(|e| format!("--backup-to: {e}"))?;
+    let mut config = BackupConfig::new(target);
+    if let Some(every) = every {
+        config.interval =
+            backup::parse_interval(&every).map_err(|e| format!("--backup-every: {e}"))?;
+    }
+    if let Some(keep) = keep {
+        config.keep = keep
+            .parse()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("--backup-keep: '{keep}' is not a positive count"))?;
+    }
+    Ok(Some(config))
+}
+
+/// Run one backup of the on-disk databases, whether or not a server is up
+/// (the copy is a consistent read alongside a running server's writes).
+async fn cmd_backup_now(backup: Option<BackupConfig>) -> ExitCode {
+    let Some(config) = backup else {
+        eprintln!("Usage: kaijutsu-server backup-now --backup-to <DIR|s3://bucket/prefix>");
+        return ExitCode::FAILURE;
+    };
+    let kernel_db = kaijutsu_server::rpc::kernel_data_dir().join("kernel.db");
+    if !kernel_db.exists() {
+        eprintln!("No kernel database at {}", kernel_db.display());
+        return ExitCode::FAILURE;
+    }
+    let auth_db = AuthDb::default_path();
+    let sources = BackupSources {
+        kernel_db,
+        auth_db: auth_db.exists().then_some(auth_db),
+    };
+    let target = match config.target.open() {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match backup::run_backup(target.as_ref(), &sources, config.keep).await {
+        Ok(report) => {
+            println!("Backed up to {}/{}:", report.location, report.run);
+            for file in &report.files {
+                println!("  {}", file);
+            }
+            println!("  {} bytes", report.bytes);
+            for run in &report.pruned {
+                println!("Pruned {}", run);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Backup failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
 /// Add a public key to the database
 fn cmd_add_key(args: &[String]) -> ExitCode {
     if args.is_empty() {
@@ -161,6 +525,10 @@ fn cmd_add_key(args: &[String]) -> ExitCode {
             }
         }
     }
+    if nick == Some(SSH_OBSERVER_USER) {
+        eprintln!("'{SSH_OBSERVER_USER}' is reserved for share-only observer logins");
+        return ExitCode::FAILURE;
+    }
 
     // Expand path (handle ~)
     let key_path: PathBuf = shellexpand::tilde(key_file).as_ref().into();
@@ -443,6 +811,10 @@ fn cmd_set_nick(args: &[String]) -> ExitCode {
 
     let old_username = &args[0];
     let new_username = &args[1];
+    if new_username == SSH_OBSERVER_USER {
+        eprintln!("'{SSH_OBSERVER_USER}' is reserved for share-only observer logins");
+        return ExitCode::FAILURE;
+    }
 
     let db = match AuthDb::open(AuthDb::default_path()) {
         Ok(db) => db,
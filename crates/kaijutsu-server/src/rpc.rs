@@ -73,6 +73,7 @@ use capnp::capability::Promise;
 use capnp_rpc::pry;
 
 use kaijutsu_kernel::runtime::embedded_kaish::EmbeddedKaish;
+use crate::event_batch::{BlockEventBatcher, BridgeEvent};
 use crate::interrupt::ContextInterruptState;
 use crate::kaijutsu_capnp::*;
 use crate::llm_stream::spawn_llm_for_prompt;
@@ -90,6 +91,7 @@ use kaijutsu_kernel::kernel_db::{
 use kaijutsu_kernel::{
     // FlowBus
     BlockFlow,
+    ConfigFlow,
     // Conversation session
     ConversationMailbox,
     InputDocFlow,
@@ -105,8 +107,6 @@ use kaijutsu_kernel::{
     SharedBlockStore,
     SharedInputDocFlowBus,
     VfsOps,
-    // VFS activity digest stream (Lane K, FSN slice-1)
-    ActivityCursor,
     block_store::BlockStore,
     flows::EditorFlow,
     flows::TurnFlow,
@@ -114,7 +114,9 @@ use kaijutsu_kernel::{
     shared_input_doc_flow_bus,
 };
 use kaijutsu_types::paths;
-use kaijutsu_types::{ContextId, KernelId, Principal, PrincipalId, SessionId};
+use kaijutsu_types::{
+    ContextId, KernelId, MentionTarget, Principal, PrincipalId, ReservationId, SessionId,
+};
 // Alias to avoid conflict with kaijutsu_capnp::ToolKind (glob-imported)
 use kaijutsu_types::ToolKind as TypesToolKind;
 use serde_json;
@@ -124,11 +126,15 @@ use tracing::Instrument;
 ///
 /// Returns a tracing span linked to the remote parent (or a root span if empty).
 /// Safe to call even when trace is not present — returns a detached span.
+/// `authority` (the authenticated principal and this kernel) is stamped on
+/// the span as `kaijutsu.*` attributes; the caller's identity baggage only
+/// fills what it leaves unset, i.e. the context.
 fn extract_rpc_trace(
     trace: capnp::Result<trace_context::Reader<'_>>,
     name: &'static str,
+    authority: kaijutsu_telemetry::TraceIdentity,
 ) -> tracing::Span {
-    let (traceparent, tracestate) = match trace {
+    let (traceparent, tracestate, baggage) = match trace {
         Ok(t) => {
             let tp = t
                 .get_traceparent()
@@ -140,13 +146,20 @@ fn extract_rpc_trace(
                 .ok()
                 .and_then(|r| r.to_str().ok())
                 .unwrap_or("");
-            (tp.to_string(), ts.to_string())
+            let bg = t
+                .get_baggage()
+                .ok()
+                .and_then(|r| r.to_str().ok())
+                .unwrap_or("");
+            (tp.to_string(), ts.to_string(), bg.to_string())
         }
-        Err(_) => (String::new(), String::new()),
+        Err(_) => (String::new(), String::new(), String::new()),
     };
     let span = kaijutsu_telemetry::extract_trace_context(&traceparent, &tracestate);
     // Override the default "rpc.request" name with the actual method name
     let named_span = tracing::info_span!(parent: &span, "rpc", method = name);
+    let claimed = kaijutsu_telemetry::TraceIdentity::from_baggage_header(&baggage);
+    authority.or(&claimed).record(&named_span);
     named_span
 }
 
@@ -252,6 +265,11 @@ pub struct SharedKernelState {
     /// SQLite persistence for context metadata, edges, presets, workspaces.
     /// Arc<parking_lot::Mutex> (not tokio) — shared with KjDispatcher, all ops sync and sub-ms.
     pub kernel_db: Arc<parking_lot::Mutex<KernelDb>>,
+    /// Resolved data directory: `kernel.db`, the semantic index.
+    pub data_dir: std::path::PathBuf,
+    /// The backup scheduler, installed by the SSH server when started with
+    /// `--backup-to`. Unset = `backupNow` fails.
+    pub backups: std::sync::OnceLock<crate::backup::BackupHandle>,
     /// Semantic vector index for context search/clustering.
     /// None if embedding model not configured or unavailable.
     pub semantic_index: Option<Arc<kaijutsu_index::SemanticIndex>>,
@@ -278,6 +296,10 @@ pub struct SharedKernelState {
     /// `parking_lot::Mutex` because all ops are insert/remove/replace and
     /// complete in microseconds.
     pub subscription_registry: Arc<parking_lot::Mutex<HashMap<(PrincipalId, String), tokio::task::AbortHandle>>>,
+    /// Resumable seats, keyed by session — see [`crate::seat`].
+    pub seats: crate::seat::SeatRegistry,
+    /// Broadcast prompt runs — see [`crate::broadcast`].
+    pub broadcasts: crate::broadcast::BroadcastRegistry,
 }
 
 pub type SharedKernel = Arc<SharedKernelState>;
@@ -411,6 +433,7 @@ pub fn spawn_turn_driver(registry: Arc<ServerRegistry>) {
                     kernel,
                     context_id,
                     model.as_deref(),
+                    None,
                     &after_block_id,
                     tool_ctx,
                     principal_id,
@@ -427,7 +450,7 @@ pub fn spawn_turn_driver(registry: Arc<ServerRegistry>) {
                     // Spawn succeeded — the stream owns the terminal Completed/Failed
                     // publish at its end. Nothing to publish here; doing so would
                     // double-announce (and at the wrong time, with no output id).
-                    Ok(()) => {}
+                    Ok(_) => {}
                     Err(e) => {
                         let err = e.to_string();
                         log::warn!(
@@ -552,6 +575,10 @@ pub struct ConnectionState {
     /// spawn_local tasks (FlowBus bridge, etc.) tokio::select! on this so
     /// they shut down promptly instead of leaking onto the LocalSet.
     conn_cancel: CancellationToken,
+    /// Kernel seat table; unset = seats aren't resumable (tests).
+    seats: Option<crate::seat::SeatRegistry>,
+    /// The seat this connection holds, once it has joined a context.
+    seat: Option<crate::seat::SeatClaim>,
 }
 
 impl ConnectionState {
@@ -569,9 +596,18 @@ impl ConnectionState {
             output_subscribers: Vec::new(),
             elicitation_subscribers: Vec::new(),
             conn_cancel: CancellationToken::new(),
+            seats: None,
+            seat: None,
         }
     }
 
+    /// Make this connection's seat resumable through the kernel's seat
+    /// table (see [`crate::seat`]).
+    pub fn with_seats(mut self, seats: crate::seat::SeatRegistry) -> Self {
+        self.seats = Some(seats);
+        self
+    }
+
     /// Cancellation token that fires when the connection's RPC system
     /// tears down. Background tasks spawned on the connection's LocalSet
     /// should observe this in a `tokio::select!` so they can exit promptly.
@@ -655,18 +691,27 @@ impl Drop for ConnectionState {
         // release their references. Without this, a wedged capnp callback
         // can pin those tasks indefinitely on the LocalSet.
         self.conn_cancel.cancel();
+        // Park the seat for a resume. If a reconnect already took it over,
+        // the session (and its context entry) belongs to the new
+        // connection — leave it be.
+        let owns_session = match (&self.seats, self.seat) {
+            (Some(seats), Some(claim)) => seats.park(claim, std::time::Instant::now()),
+            _ => true,
+        };
         // Clean up per-session context tracking. Mirrors the explicit
         // remove that used to live at the tail of `run_rpc`; the Drop
         // guard runs even when the RPC system future is dropped mid-flight
         // (e.g., from a wedge + thread teardown), so the map can't leak.
-        self.session_contexts.remove(&self.session_id);
+        if owns_session {
+            self.session_contexts.remove(&self.session_id);
+        }
     }
 }
 
 /// Get the stable data directory for kernel persistent storage.
 /// Creates the directory if it doesn't exist.
 /// Returns: ~/.local/share/kaijutsu/kernel/
-fn kernel_data_dir() -> std::path::PathBuf {
+pub fn kernel_data_dir() -> std::path::PathBuf {
     let dir = kaish_kernel::xdg_data_home()
         .join("kaijutsu")
         .join("kernel");
@@ -1014,6 +1059,10 @@ pub async fn create_shared_kernel(
     // at a tempdir to inject a mock models.toml.
     config_dir: Option<&Path>,
     data_dir: Option<&Path>,
+    // The consent log's signing key file, kept outside kernel.db (next to the
+    // host key). None signs with a key that dies with the process — tests and
+    // ephemeral servers.
+    consent_key: Option<&Path>,
 ) -> Result<SharedKernel, capnp::Error> {
     // Create shared FlowBus instances - shared between Kernel and BlockStore
     let block_flows = shared_block_flow_bus(1024);
@@ -1034,7 +1083,7 @@ pub async fn create_shared_kernel(
     // falling back to in-memory hides data loss: persisted contexts on disk
     // become invisible while the server pretends to be a fresh kernel.
     let db_path = resolved_data_dir.join("kernel.db");
-    let kernel_db = KernelDb::open(&db_path).map_err(|e| {
+    let mut kernel_db = KernelDb::open(&db_path).map_err(|e| {
         capnp::Error::failed(format!(
             "Failed to open KernelDb at {}: {}",
             db_path.display(),
@@ -1042,6 +1091,17 @@ pub async fn create_shared_kernel(
         ))
     })?;
     log::info!("Opened KernelDb at {}", db_path.display());
+    let consent_key = match consent_key {
+        Some(path) => kaijutsu_kernel::consent_log::load_or_create_key(path).map_err(|e| {
+            capnp::Error::failed(format!(
+                "Failed to load consent log key {}: {}",
+                path.display(),
+                e
+            ))
+        })?,
+        None => rand::random(),
+    };
+    kernel_db.set_consent_key(consent_key);
 
     // KernelId is the kernel's birth certificate — written once on first
     // DB open, never changes thereafter. Clients use this to detect that
@@ -1323,7 +1383,7 @@ pub async fn create_shared_kernel(
     if !all_contexts.is_empty() {
         let mut drift = kernel_arc.drift().write();
         for row in &all_contexts {
-            if let Err(e) = drift.register(
+            if let Err(e) = drift.register_recovered(
                 row.context_id,
                 row.label.as_deref(),
                 row.forked_from,
@@ -1354,6 +1414,22 @@ pub async fn create_shared_kernel(
         }
     }
 
+    // Digest subscriptions ride on the registered contexts; one whose
+    // subscriber or source didn't come back is skipped (the FK cascade
+    // removes it once that context is deleted for good).
+    match kernel_db_arc.lock().list_digest_subscriptions() {
+        Ok(subs) => {
+            let mut drift = kernel_arc.drift().write();
+            for sub in subs {
+                let (subscriber, source) = (sub.subscriber.short(), sub.source.short());
+                if let Err(e) = drift.subscribe_digest(sub) {
+                    log::warn!("Skipping digest {subscriber} ← {source}: {e}");
+                }
+            }
+        }
+        Err(e) => log::warn!("Failed to load digest subscriptions: {e}"),
+    }
+
     // Initialize LLM registry + embedding config from models.toml
     let embedding_config = initialize_kernel_models(&kernel_arc).await;
 
@@ -1457,6 +1533,16 @@ pub async fn create_shared_kernel(
     // block-backed source — the index is built here (it needs the ONNX
     // embedder) but consumed kernel-side. `None` when embeddings are off.
     kj_dispatcher.set_semantic_index(semantic_index.clone());
+    // The full-text index behind `kj search --ranked` and searchBlocks.
+    // Always on — SQLite only — and kept current from the block flow.
+    match kaijutsu_kernel::search_index::SearchIndex::open(resolved_data_dir.join("search.db")) {
+        Ok(idx) => {
+            let idx = Arc::new(idx);
+            kaijutsu_kernel::search_index::spawn_indexer(idx.clone(), documents.clone());
+            kj_dispatcher.set_search_index(Some(idx));
+        }
+        Err(e) => log::warn!("Search index unavailable: {}", e),
+    }
     // Wire the dispatcher into the broker so HookBody::Kaish can
     // register `kj` as a tool inside hook kaish sessions.
     kernel_arc
@@ -1471,12 +1557,16 @@ pub async fn create_shared_kernel(
         documents,
         conversation_cache: Arc::new(ConversationCache::new(64)),
         kernel_db: kernel_db_arc,
+        data_dir: resolved_data_dir,
+        backups: std::sync::OnceLock::new(),
         semantic_index,
         context_interrupts: Arc::new(TokioRwLock::new(HashMap::new())),
         interrupt_generation: AtomicU64::new(0),
         kj_dispatcher,
         session_contexts,
         subscription_registry: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+        seats: crate::seat::SeatRegistry::default(),
+        broadcasts: crate::broadcast::BroadcastRegistry::new(),
     };
 
     // ROOT bootstrap: a brand-new kernel (nothing recovered above) has no
@@ -1519,6 +1609,9 @@ pub async fn create_shared_kernel(
 pub struct WorldImpl {
     registry: Arc<ServerRegistry>,
     connection: Rc<RefCell<ConnectionState>>,
+    /// An observer connection (SSH user `observer`): it may open context
+    /// shares by token, and is refused the kernel.
+    observer: bool,
 }
 
 impl WorldImpl {
@@ -1526,8 +1619,29 @@ impl WorldImpl {
         Self {
             registry,
             connection,
+            observer: false,
+        }
+    }
+
+    /// A World for an observer connection: `openShare` only.
+    pub fn observer(
+        registry: Arc<ServerRegistry>,
+        connection: Rc<RefCell<ConnectionState>>,
+    ) -> Self {
+        Self {
+            observer: true,
+            ..Self::new(registry, connection)
         }
     }
+
+    fn refuse_observer(&self) -> Result<(), capnp::Error> {
+        if self.observer {
+            return Err(capnp::Error::failed(
+                "observer connections can only open context shares".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl world::Server for WorldImpl {
@@ -1549,16 +1663,36 @@ impl world::Server for WorldImpl {
 
     fn list_kernels(
         self: Rc<Self>,
-        _params: world::ListKernelsParams,
+        params: world::ListKernelsParams,
         mut results: world::ListKernelsResults,
     ) -> Promise<(), capnp::Error> {
+        pry!(self.refuse_observer());
+        let p = pry!(params.get());
+        let query = if p.has_query() {
+            pry!(parse_kernel_list_query(pry!(p.get_query())))
+        } else {
+            kaijutsu_types::KernelListQuery::default()
+        };
+
+        // One shared kernel per server today; the query still applies so
+        // callers can page uniformly once the registry holds more.
         let kernel = &self.registry.kernel;
-        let mut kernels = results.get().init_kernels(1);
-        let mut k = kernels.reborrow().get(0);
-        k.set_id(kernel.id.as_bytes());
-        k.set_name(&kernel.name);
-        k.set_user_count(1);
-        k.set_agent_count(0);
+        let matching: Vec<&SharedKernel> = std::iter::once(kernel)
+            .filter(|k| query.matches(&k.name))
+            .collect();
+        let total = matching.len() as u32;
+        let page = query.paginate(matching);
+
+        let mut r = results.get();
+        r.set_total(total);
+        let mut kernels = r.init_kernels(page.len() as u32);
+        for (i, kernel) in page.iter().enumerate() {
+            let mut k = kernels.reborrow().get(i as u32);
+            k.set_id(kernel.id.as_bytes());
+            k.set_name(&kernel.name);
+            k.set_user_count(1);
+            k.set_agent_count(0);
+        }
         Promise::ok(())
     }
 
@@ -1569,6 +1703,7 @@ impl world::Server for WorldImpl {
     ) -> Promise<(), capnp::Error> {
         let _params_reader = pry!(params.get());
         let _span = tracing::info_span!("rpc", method = "bind_kernel").entered();
+        pry!(self.refuse_observer());
 
         // No kernel creation — hand out the shared kernel capability.
         let kernel = self.registry.kernel.clone();
@@ -1580,6 +1715,66 @@ impl world::Server for WorldImpl {
         results.get().set_kernel_id(kernel.id.as_bytes());
         Promise::ok(())
     }
+
+    fn bind_seat(
+        self: Rc<Self>,
+        params: world::BindSeatParams,
+        mut results: world::BindSeatResults,
+    ) -> Promise<(), capnp::Error> {
+        let _params_reader = pry!(params.get());
+        let _span = tracing::info_span!("rpc", method = "bind_seat").entered();
+        pry!(self.refuse_observer());
+
+        // Same principal and kernel, fresh session: the binding's
+        // ConnectionState is its own, so joinContext, subscriptions and the
+        // seat it takes don't touch the connection's other bindings. Its
+        // Drop (capability released, or the whole connection torn down)
+        // parks the seat and cancels its bridge tasks.
+        let kernel = self.registry.kernel.clone();
+        let (principal, session_contexts) = {
+            let conn = self.connection.borrow();
+            (conn.principal.clone(), conn.session_contexts.clone())
+        };
+        let seat_connection = Rc::new(RefCell::new(
+            ConnectionState::new(principal, session_contexts).with_seats(kernel.seats.clone()),
+        ));
+        let kernel_impl = KernelImpl::new(kernel.clone(), seat_connection);
+        results.get().set_kernel(capnp_rpc::new_client(kernel_impl));
+        results.get().set_kernel_id(kernel.id.as_bytes());
+        Promise::ok(())
+    }
+
+    fn open_share(
+        self: Rc<Self>,
+        params: world::OpenShareParams,
+        mut results: world::OpenShareResults,
+    ) -> Promise<(), capnp::Error> {
+        let token = pry!(pry!(pry!(params.get()).get_token()).to_str()).to_owned();
+        let _span = tracing::info_span!("rpc", method = "open_share").entered();
+
+        let (share, blocks) = match kaijutsu_kernel::context_share::open(
+            &self.registry.kernel.kernel_db,
+            &token,
+            kaijutsu_types::now_millis(),
+        ) {
+            Ok(opened) => opened,
+            Err(e) => {
+                log::info!(
+                    "open_share {} by {}: {e}",
+                    kaijutsu_kernel::context_share::short_token(&token),
+                    self.connection.borrow().principal.username,
+                );
+                return Promise::err(capnp::Error::failed(format!("open share: {e}")));
+            }
+        };
+        let mut r = results.get();
+        set_context_share(r.reborrow().init_share(), &share);
+        let mut list = r.init_blocks(blocks.len() as u32);
+        for (i, block) in blocks.iter().enumerate() {
+            set_block_snapshot(&mut list.reborrow().get(i as u32), block);
+        }
+        Promise::ok(())
+    }
 }
 
 // ============================================================================
@@ -1598,6 +1793,19 @@ impl KernelImpl {
     ) -> Self {
         Self { kernel, connection }
     }
+
+    /// [`extract_rpc_trace`] for this connection: the principal it
+    /// authenticated and this kernel, never what the caller's baggage claims.
+    fn rpc_trace(
+        &self,
+        trace: capnp::Result<trace_context::Reader<'_>>,
+        name: &'static str,
+    ) -> tracing::Span {
+        let authority = kaijutsu_telemetry::TraceIdentity::default()
+            .with_principal(self.connection.borrow().principal.id)
+            .with_kernel(self.kernel.id);
+        extract_rpc_trace(trace, name, authority)
+    }
 }
 
 /// The shared context-creation recipe, called by both the `createContext` RPC
@@ -1612,7 +1820,7 @@ impl KernelImpl {
 /// everything downstream is best-effort. Wire-result writing is the caller's
 /// job — this never touches capnp results.
 #[allow(clippy::too_many_arguments)]
-async fn create_context_inner(
+pub(crate) async fn create_context_inner(
     state: &SharedKernelState,
     context_id: ContextId,
     context_type: &str,
@@ -1621,6 +1829,19 @@ async fn create_context_inner(
     parent_ctx: Option<ContextId>,
     session_id: SessionId,
 ) -> Result<(), capnp::Error> {
+    // Refuse a taken (or near-duplicate, or reserved) label before anything
+    // is created, so the error carries suggestions. The drift register below
+    // re-checks under its write lock.
+    if let Some(label) = label
+        && let Err(e) = state
+            .kernel
+            .drift()
+            .read()
+            .check_label_available(label, Some(context_id))
+    {
+        return Err(capnp::Error::failed(format!("label conflict: {e}")));
+    }
+
     // Create the conversation document for this context.
     if let Err(e) =
         state
@@ -1645,15 +1866,36 @@ async fn create_context_inner(
     // Read LLM defaults so new contexts start with a model set. If no provider
     // is configured, leave both None so the user gets a clear error on use
     // rather than a silently-injected hardcoded model.
+    // The creator's `default_model` / `default_consent` preferences win over
+    // the kernel defaults (a preferred provider this kernel doesn't have is
+    // skipped, loudly).
+    let prefs = state.kernel_db.lock().get_prefs(created_by).unwrap_or_else(|e| {
+        log::warn!("prefs lookup for {} failed: {e}", created_by.short());
+        Default::default()
+    });
     let (default_provider, default_model) = {
         let registry = state.kernel.llm().read().await;
-        let provider = registry.default_provider_name().map(|s| s.to_string());
-        let model = registry.default_model().map(|s| s.to_string());
+        let preferred = kaijutsu_types::prefs::default_model(&prefs).filter(|(p, _)| {
+            let known = registry.get(p).is_some();
+            if !known {
+                log::warn!("preferred provider '{p}' is not configured; using the kernel default");
+            }
+            known
+        });
+        let (provider, model) = match preferred {
+            Some((p, m)) => (Some(p.to_string()), Some(m.to_string())),
+            None => (
+                registry.default_provider_name().map(|s| s.to_string()),
+                registry.default_model().map(|s| s.to_string()),
+            ),
+        };
         if provider.is_none() && model.is_none() {
             log::warn!("No LLM provider configured — new context will have no model set");
         }
         (provider, model)
     };
+    let consent_mode = kaijutsu_types::prefs::default_consent(&prefs)
+        .unwrap_or(kaijutsu_kernel::control::ConsentMode::Collaborative);
 
     // Write-through: KernelDb first, then DriftRouter. Both must succeed or we
     // roll in-memory state back — never a ghost live-in-memory-but-missing-from-DB
@@ -1666,7 +1908,7 @@ async fn create_context_inner(
             provider: default_provider.clone(),
             model: default_model.clone(),
             system_prompt: None,
-            consent_mode: kaijutsu_kernel::control::ConsentMode::Collaborative,
+            consent_mode,
             context_state: kaijutsu_types::ContextState::Live,
             context_type: context_type.to_string(),
             created_at: kaijutsu_types::now_millis() as i64,
@@ -1735,6 +1977,8 @@ async fn create_context_inner(
         log::warn!("rc create lifecycle for {}: {e}", context_id.short());
     }
 
+    state.kj_dispatcher.emit_context_created(context_id);
+
     // The beat arm now lives in the musician's `create/` rc (run above via
     // run_rc_lifecycle), not a Rust `context_type == "musician"` branch here —
     // this used to duplicate the same arm logic the `kj context create` builtin
@@ -1752,7 +1996,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::GetInfoResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _span = extract_rpc_trace(p.get_trace(), "get_info").entered();
+        let _span = self.rpc_trace(p.get_trace(), "get_info").entered();
         let mut info = results.get().init_info();
         info.set_id(self.kernel.id.as_bytes());
         info.set_name(&self.kernel.name);
@@ -1769,7 +2013,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::ExecuteResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let trace_span = extract_rpc_trace(p.get_trace(), "execute");
+        let trace_span = self.rpc_trace(p.get_trace(), "execute");
         let code = pry!(pry!(p.get_code()).to_str()).to_owned();
         let kernel = self.kernel.clone();
         let connection = self.connection.clone();
@@ -1782,6 +2026,14 @@ impl kernel::Server for KernelImpl {
                 // instance per execute call — durable env + cwd persist in the
                 // DB, transient scope dies with the instance.
                 let started_ctx = connection.borrow().require_context()?;
+                // Interactive input is never metered, but the kill switch
+                // stops it like any other shell command.
+                kernel
+                    .kernel
+                    .broker()
+                    .budgets()
+                    .check_halt()
+                    .map_err(|e| capnp::Error::failed(e.to_string()))?;
                 let kaish = materialize_context_shell(&kernel, &connection).await?;
 
                 // Reject concurrent executions — kaish kernel is serial.
@@ -1883,7 +2135,7 @@ impl kernel::Server for KernelImpl {
         _results: kernel::InterruptResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _span = extract_rpc_trace(p.get_trace(), "interrupt").entered();
+        let _span = self.rpc_trace(p.get_trace(), "interrupt").entered();
         let exec_id = p.get_exec_id();
 
         let conn = self.connection.borrow();
@@ -1900,17 +2152,48 @@ impl kernel::Server for KernelImpl {
         params: kernel::CompleteParams,
         mut results: kernel::CompleteResults,
     ) -> Promise<(), capnp::Error> {
-        // Wire-compatible stub: the schema keeps `complete` reserved for
-        // kaish completions but the kernel doesn't surface a completion API
-        // yet. Returns an empty list so clients render nothing rather than
-        // wiring up dead code paths. Trace is still attached so the round
-        // trip shows up in OTel.
         let p = pry!(params.get());
-        let _span = extract_rpc_trace(p.get_trace(), "complete").entered();
+        let span = self.rpc_trace(p.get_trace(), "complete");
+        let partial = pry!(pry!(p.get_partial()).to_str()).to_owned();
+        let cursor = p.get_cursor() as usize;
+        // No joined context → nothing to complete against; answer empty
+        // rather than erroring on every keystroke before join.
+        let Ok(context_id) = self.connection.borrow().require_context() else {
+            results.get().init_completions(0);
+            return Promise::ok(());
+        };
+        let kernel = self.kernel.clone();
 
-        let mut builder = results.get().init_completions(0);
-        let _ = builder.reborrow();
-        Promise::ok(())
+        Promise::from_future(
+            async move {
+                use kaijutsu_kernel::completion::{CompletionKind as Kind, Completer};
+                let cwd = context_cwd(&kernel, context_id);
+                let completer = Completer {
+                    db: &kernel.kernel_db,
+                    drift: kernel.kernel.drift(),
+                    store: &kernel.documents,
+                    vfs: kernel.kernel.vfs(),
+                };
+                let completions = completer
+                    .complete(context_id, cwd.as_deref(), &partial, cursor)
+                    .await;
+
+                let mut builder = results.get().init_completions(completions.len() as u32);
+                for (i, c) in completions.iter().enumerate() {
+                    let mut entry = builder.reborrow().get(i as u32);
+                    entry.set_text(&c.text);
+                    entry.set_display_text(&c.display);
+                    entry.set_kind(match c.kind {
+                        Kind::Command => crate::kaijutsu_capnp::CompletionKind::Command,
+                        Kind::Path => crate::kaijutsu_capnp::CompletionKind::Path,
+                        Kind::Mention => crate::kaijutsu_capnp::CompletionKind::Mention,
+                        Kind::Block => crate::kaijutsu_capnp::CompletionKind::Block,
+                    });
+                }
+                Ok(())
+            }
+            .instrument(span),
+        )
     }
 
     fn subscribe_output(
@@ -1931,7 +2214,9 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::GetCommandHistoryResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _span = extract_rpc_trace(p.get_trace(), "get_command_history").entered();
+        let _span = self
+            .rpc_trace(p.get_trace(), "get_command_history")
+            .entered();
         let limit = p.get_limit() as usize;
 
         let conn = self.connection.borrow();
@@ -2069,7 +2354,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::ExecuteToolResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let trace_span = extract_rpc_trace(p.get_trace(), "execute_tool");
+        let trace_span = self.rpc_trace(p.get_trace(), "execute_tool");
         let call = pry!(p.get_call());
         let tool_name = pry!(pry!(call.get_tool()).to_str()).to_owned();
         let tool_params = pry!(pry!(call.get_params()).to_str()).to_owned();
@@ -2146,7 +2431,7 @@ impl kernel::Server for KernelImpl {
             (conn.principal.id, ctx)
         };
 
-        let span = extract_rpc_trace(pry!(params.get()).get_trace(), "get_tool_schemas");
+        let span = self.rpc_trace(pry!(params.get()).get_trace(), "get_tool_schemas");
         Promise::from_future(
             async move {
                 let visible = kernel_arc
@@ -2188,7 +2473,9 @@ impl kernel::Server for KernelImpl {
             // Get the FlowBus instances from the kernel
             let block_flows = self.kernel.kernel.block_flows().clone();
             let input_flows = self.kernel.documents.input_flows().cloned();
+            let config_flows = self.kernel.kernel.config_flows().clone();
             let kernel_id = self.kernel.id;
+            let principal_id = self.connection.borrow().principal.id;
             // Connection-lifetime cancellation. Cleared on ConnectionState
             // Drop, so the bridge unwinds when the RPC system tears down
             // (even mid-callback). Per-send `timeout` below bounds the
@@ -2197,11 +2484,15 @@ impl kernel::Server for KernelImpl {
 
             // Spawn a bridge task that forwards FlowBus events to the callback
             // Use spawn_local because Cap'n Proto callbacks are not Send
-            // Uses tokio::select! to multiplex block + input doc events on one callback
+            // Uses tokio::select! to multiplex block + input doc + config events
+            // on one callback
             tokio::task::spawn_local(async move {
-                let mut block_sub = block_flows.subscribe("block.*");
+                // Text ops come out of the batcher coalesced per document
+                // (see event_batch); everything else passes straight through.
+                let mut block_sub = BlockEventBatcher::new(block_flows.subscribe("block.*"), None);
                 // Input flows are optional at this subscription site.
                 let mut input_sub = input_flows.map(|f| f.subscribe("input.*"));
+                let mut config_sub = config_flows.subscribe("config.*");
                 let mut health = SubscriberHealth::new(SUBSCRIBER_FAILURE_STREAK_TIMEOUT);
                 log::debug!(
                     "Started FlowBus subscription for kernel {} (input_flows={})",
@@ -2228,8 +2519,11 @@ impl kernel::Server for KernelImpl {
                             log::debug!("FlowBus bridge cancelled with connection");
                             break;
                         }
-                        Some(msg) = block_sub.recv() => {
-                            match msg.payload {
+                        Some(event) = block_sub.next() => match event {
+                            BridgeEvent::TextOps { context_id, ref flows } => {
+                                forward_text_ops_batch(&callback, context_id, flows, CALLBACK_TIMEOUT, kernel_id).await
+                            }
+                            BridgeEvent::Flow(payload) => match payload {
                                 BlockFlow::Inserted { context_id, ref block, ref after_id, ref ops, .. } => {
                                     let mut req = callback.on_block_inserted_request();
                                     {
@@ -2412,7 +2706,7 @@ impl kernel::Server for KernelImpl {
                                         }
                                     }
                                 }
-                                BlockFlow::TextOps { context_id, ref block_id, ref ops, seq_num, .. } => {
+                                BlockFlow::TextOps { context_id, ref block_id, ref ops, seq_num, generation, .. } => {
                                     let mut req = callback.on_block_text_ops_request();
                                     {
                                         let mut params = req.get();
@@ -2420,6 +2714,7 @@ impl kernel::Server for KernelImpl {
                                         set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
                                         params.set_ops(ops);
                                         params.set_seq_num(seq_num);
+                                        params.set_generation(generation);
                                     }
                                     match tokio::time::timeout(
                                         CALLBACK_TIMEOUT, req.send().promise,
@@ -2585,6 +2880,106 @@ impl kernel::Server for KernelImpl {
                                         }
                                     }
                                 }
+                                BlockFlow::InboxPosted { ref item, .. } => {
+                                    // Addressed to one seat: other principals'
+                                    // connections never see it.
+                                    if item.recipient != principal_id {
+                                        continue;
+                                    }
+                                    let mut req = callback.on_inbox_item_request();
+                                    set_inbox_item(req.get().init_item(), item);
+                                    match tokio::time::timeout(
+                                        CALLBACK_TIMEOUT, req.send().promise,
+                                    ).await {
+                                        Ok(Ok(_)) => true,
+                                        Ok(Err(e)) => {
+                                            log::debug!(
+                                                "FlowBus callback failed for {kernel_id}: {e}",
+                                            );
+                                            false
+                                        }
+                                        Err(_) => {
+                                            log::warn!(
+                                                "FlowBus callback timed out after {:?} \
+                                                 for kernel {kernel_id} — peer is not \
+                                                 reading; dropping subscriber",
+                                                CALLBACK_TIMEOUT,
+                                            );
+                                            false
+                                        }
+                                    }
+                                }
+                                BlockFlow::ModelChanged {
+                                    context_id,
+                                    ref provider,
+                                    ref model,
+                                    by,
+                                } => {
+                                    let mut req = callback.on_context_model_changed_request();
+                                    {
+                                        let mut params = req.get();
+                                        params.set_context_id(context_id.as_bytes());
+                                        params.set_provider(provider);
+                                        params.set_model(model);
+                                        params.set_by(by.as_bytes());
+                                    }
+                                    match tokio::time::timeout(
+                                        CALLBACK_TIMEOUT, req.send().promise,
+                                    ).await {
+                                        Ok(Ok(_)) => true,
+                                        Ok(Err(e)) => {
+                                            log::debug!(
+                                                "FlowBus callback failed for {kernel_id}: {e}",
+                                            );
+                                            false
+                                        }
+                                        Err(_) => {
+                                            log::warn!(
+                                                "FlowBus callback timed out after {:?} \
+                                                 for kernel {kernel_id} — peer is not \
+                                                 reading; dropping subscriber",
+                                                CALLBACK_TIMEOUT,
+                                            );
+                                            false
+                                        }
+                                    }
+                                }
+                                BlockFlow::LockChanged {
+                                    context_id,
+                                    ref block_id,
+                                    ref lock,
+                                } => {
+                                    let mut req = callback.on_block_lock_changed_request();
+                                    {
+                                        let mut params = req.get();
+                                        params.set_context_id(context_id.as_bytes());
+                                        set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                                        params.set_locked(lock.is_some());
+                                        if let Some(lock) = lock {
+                                            set_block_lock(params.reborrow().init_lock(), lock);
+                                        }
+                                    }
+                                    match tokio::time::timeout(
+                                        CALLBACK_TIMEOUT, req.send().promise,
+                                    ).await {
+                                        Ok(Ok(_)) => true,
+                                        Ok(Err(e)) => {
+                                            log::debug!(
+                                                "FlowBus callback failed for {kernel_id}: {e}",
+                                            );
+                                            false
+                                        }
+                                        Err(_) => {
+                                            log::warn!(
+                                                "FlowBus callback timed out after {:?} \
+                                                 for kernel {kernel_id} — peer is not \
+                                                 reading; dropping subscriber",
+                                                CALLBACK_TIMEOUT,
+                                            );
+                                            false
+                                        }
+                                    }
+                                }
                                 BlockFlow::BeatSync { context_id, ref beat_ref } => {
                                     let mut req = callback.on_beat_sync_request();
                                     {
@@ -2613,8 +3008,8 @@ impl kernel::Server for KernelImpl {
                                         }
                                     }
                                 }
-                            }
-                        }
+                            },
+                        },
                         Some(msg) = async {
                             match &mut input_sub {
                                 Some(sub) => sub.recv().await,
@@ -2680,6 +3075,9 @@ impl kernel::Server for KernelImpl {
                                 }
                             }
                         }
+                        Some(msg) = config_sub.recv() => {
+                            forward_config_flow(&callback, &msg.payload, CALLBACK_TIMEOUT, kernel_id).await
+                        }
                         else => break,
                     };
 
@@ -2714,7 +3112,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::EditorOpenResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let span = extract_rpc_trace(p.get_trace(), "editor_open");
+        let span = self.rpc_trace(p.get_trace(), "editor_open");
         let path = pry!(pry!(p.get_path()).to_str()).to_owned();
         let kernel = self.kernel.clone();
         Promise::from_future(
@@ -2737,7 +3135,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::EditorKeysResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let span = extract_rpc_trace(p.get_trace(), "editor_keys");
+        let span = self.rpc_trace(p.get_trace(), "editor_keys");
         let session_id = p.get_session_id();
         let keys = pry!(pry!(p.get_keys()).to_str()).to_owned();
         let id = kaijutsu_kernel::editor::EditorSessionId::from_u64(session_id);
@@ -2763,7 +3161,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::EditorStateResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _guard = extract_rpc_trace(p.get_trace(), "editor_state").entered();
+        let _guard = self.rpc_trace(p.get_trace(), "editor_state").entered();
         let session_id = p.get_session_id();
         let id = kaijutsu_kernel::editor::EditorSessionId::from_u64(session_id);
         match self.kernel.kernel.editor_state(id) {
@@ -2781,7 +3179,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::EditorSaveResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _guard = extract_rpc_trace(p.get_trace(), "editor_save").entered();
+        let _guard = self.rpc_trace(p.get_trace(), "editor_save").entered();
         let session_id = p.get_session_id();
         let id = kaijutsu_kernel::editor::EditorSessionId::from_u64(session_id);
         match self.kernel.kernel.editor_save(id) {
@@ -2799,7 +3197,7 @@ impl kernel::Server for KernelImpl {
         _results: kernel::EditorQuitResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _guard = extract_rpc_trace(p.get_trace(), "editor_quit").entered();
+        let _guard = self.rpc_trace(p.get_trace(), "editor_quit").entered();
         let session_id = p.get_session_id();
         let id = kaijutsu_kernel::editor::EditorSessionId::from_u64(session_id);
         match self.kernel.kernel.editor_quit(id, &self.kernel.documents) {
@@ -2900,9 +3298,18 @@ impl kernel::Server for KernelImpl {
         let vfs = self.kernel.kernel.vfs().clone();
         let kernel_id = self.kernel.id;
         let conn_cancel = self.connection.borrow().cancel_token();
+        // A resumed seat picks up where its last stream left off, so the
+        // first digest after a reconnect is a delta, not a full resend.
+        let seats = self.kernel.seats.clone();
+        let seat = self.connection.borrow().seat;
+        let resumed_cursor = seat.and_then(|claim| {
+            seats
+                .with_state(claim, |s| s.activity_cursor.take())
+                .flatten()
+        });
 
         tokio::task::spawn_local(async move {
-            let mut cursor = ActivityCursor::default();
+            let mut cursor = resumed_cursor.unwrap_or_default();
             let mut health = SubscriberHealth::new(SUBSCRIBER_FAILURE_STREAK_TIMEOUT);
             const CALLBACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
             let mut ticker = tokio::time::interval(std::time::Duration::from_millis(interval_ms as u64));
@@ -2963,6 +3370,9 @@ impl kernel::Server for KernelImpl {
                     }
                 }
             }
+            if let Some(claim) = seat {
+                seats.with_state(claim, |s| s.activity_cursor = Some(cursor));
+            }
             log::debug!("vfs activity bridge task for kernel {} ended", kernel_id);
         });
         Promise::ok(())
@@ -2991,7 +3401,7 @@ impl kernel::Server for KernelImpl {
     ) -> Promise<(), capnp::Error> {
         log::debug!("prompt() called for kernel {}", self.kernel.id);
         let params = pry!(params.get());
-        let trace_span = extract_rpc_trace(params.get_trace(), "prompt");
+        let trace_span = self.rpc_trace(params.get_trace(), "prompt");
         let request = pry!(params.get_request());
         let content = pry!(pry!(request.get_content()).to_str()).to_owned();
         let context_id_bytes = pry!(request.get_context_id());
@@ -3011,6 +3421,15 @@ impl kernel::Server for KernelImpl {
             .and_then(|m| m.to_str().ok())
             .filter(|s| !s.is_empty())
             .map(|s| s.to_owned());
+        // Per-request overrides of the context's generation params; unset
+        // (or all-default) means "use the context's".
+        let llm_params = if request.has_params() {
+            let over = pry!(parse_llm_params(pry!(request.get_params())));
+            pry!(over.validate().map_err(capnp::Error::failed));
+            Some(over).filter(|p| !p.is_empty())
+        } else {
+            None
+        };
 
         let kernel = self.kernel.clone();
         let (user_principal_id, session_id) = {
@@ -3086,6 +3505,7 @@ impl kernel::Server for KernelImpl {
                     &kernel,
                     context_id,
                     model.as_deref(),
+                    llm_params.as_ref(),
                     &user_block_id,
                     tool_ctx,
                     user_principal_id,
@@ -3120,7 +3540,13 @@ impl kernel::Server for KernelImpl {
         let semantic_index = self.kernel.semantic_index.clone();
         let documents = self.kernel.documents.clone();
 
-        let span = extract_rpc_trace(pry!(params.get()).get_trace(), "list_contexts");
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "list_contexts");
+        let query = if p.has_query() {
+            pry!(parse_context_list_query(pry!(p.get_query())))
+        } else {
+            kaijutsu_types::ContextListQuery::default()
+        };
         Promise::from_future(
             async move {
                 // Build KernelDb lookup for fork_kind + archived_at (fields not on DriftRouter)
@@ -3148,7 +3574,25 @@ impl kernel::Server for KernelImpl {
 
                 // Read from the kernel's drift router — runtime authority for provider/model
                 let drift = kernel_arc.drift().read();
-                let contexts = drift.list_contexts();
+                let contexts: Vec<_> = drift
+                    .list_contexts()
+                    .into_iter()
+                    .filter(|ctx| {
+                        let row = db_map.get(&ctx.id);
+                        let last_activity = row
+                            .and_then(|r| r.last_activity_at)
+                            .map(|ts| ts as u64)
+                            .unwrap_or(ctx.created_at);
+                        query.matches(
+                            ctx.label.as_deref(),
+                            row.map(|r| r.created_by),
+                            last_activity,
+                        )
+                    })
+                    .collect();
+                let total = contexts.len() as u32;
+                let contexts = query.paginate(contexts);
+                results.get().set_total(total);
                 let mut ctx_list = results.get().init_contexts(contexts.len() as u32);
 
                 for (i, ctx) in contexts.iter().enumerate() {
@@ -3290,6 +3734,133 @@ impl kernel::Server for KernelImpl {
         })
     }
 
+    /// Hold a context name for this principal (see `DriftRouter::reserve_label`).
+    fn reserve_context_name(
+        self: Rc<Self>,
+        params: kernel::ReserveContextNameParams,
+        mut results: kernel::ReserveContextNameResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self
+            .rpc_trace(p.get_trace(), "reserve_context_name")
+            .entered();
+        let label = pry!(pry!(p.get_label()).to_str());
+        let principal = self.connection.borrow().principal.id;
+        let reservation = pry!(
+            self.kernel
+                .kernel
+                .drift()
+                .write()
+                .reserve_label(label, principal)
+                .map_err(|e| capnp::Error::failed(format!("reserveContextName: {e}")))
+        );
+
+        let mut b = results.get().init_reservation();
+        b.set_token(reservation.token.as_ref().map_or(&[][..], |t| t.as_bytes()));
+        b.set_label(&reservation.label);
+        b.set_expires_at(reservation.expires_at);
+        let mut list = b.init_suggestions(reservation.suggestions.len() as u32);
+        for (i, suggestion) in reservation.suggestions.iter().enumerate() {
+            list.set(i as u32, suggestion);
+        }
+        Promise::ok(())
+    }
+
+    /// Create a context under a name reserved with `reserveContextName`.
+    /// Same recipe as `createContext`; the reservation is bound to the new
+    /// id first, so no other seat can take the name mid-creation.
+    fn commit_context_name(
+        self: Rc<Self>,
+        params: kernel::CommitContextNameParams,
+        mut results: kernel::CommitContextNameResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "commit_context_name");
+        let token = pry!(
+            ReservationId::try_from_slice(pry!(p.get_token()))
+                .ok_or_else(|| capnp::Error::failed("invalid reservation token".into()))
+        );
+        let context_type = match pry!(pry!(p.get_context_type()).to_str()) {
+            "" => "default".to_string(),
+            t => t.to_string(),
+        };
+
+        let kernel = self.kernel.clone();
+        let connection = self.connection.clone();
+        let (session_id, created_by) = {
+            let conn = connection.borrow();
+            (conn.session_id, conn.principal.id)
+        };
+        let parent_ctx = connection
+            .borrow()
+            .session_contexts
+            .get(&session_id)
+            .map(|r| *r);
+
+        Promise::from_future(
+            async move {
+                let context_id = ContextId::new();
+                let label = kernel
+                    .kernel
+                    .drift()
+                    .write()
+                    .commit_label_reservation(token, created_by, context_id)
+                    .map_err(|e| capnp::Error::failed(format!("commitContextName: {e}")))?;
+                log::info!(
+                    "commit_context_name: label='{}' kernel='{}'",
+                    label,
+                    kernel.id.to_hex()
+                );
+                let created = create_context_inner(
+                    &kernel,
+                    context_id,
+                    &context_type,
+                    Some(&label),
+                    created_by,
+                    parent_ctx,
+                    session_id,
+                )
+                .await;
+                if let Err(e) = created {
+                    kernel
+                        .kernel
+                        .drift()
+                        .write()
+                        .release_label_reservation(token, created_by);
+                    return Err(e);
+                }
+                results.get().set_id(context_id.as_bytes());
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    /// Give back a name held with `reserveContextName`.
+    fn release_context_name(
+        self: Rc<Self>,
+        params: kernel::ReleaseContextNameParams,
+        mut results: kernel::ReleaseContextNameResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self
+            .rpc_trace(p.get_trace(), "release_context_name")
+            .entered();
+        let token = pry!(
+            ReservationId::try_from_slice(pry!(p.get_token()))
+                .ok_or_else(|| capnp::Error::failed("invalid reservation token".into()))
+        );
+        let principal = self.connection.borrow().principal.id;
+        let released = self
+            .kernel
+            .kernel
+            .drift()
+            .write()
+            .release_label_reservation(token, principal);
+        results.get().set_released(released);
+        Promise::ok(())
+    }
+
     /// Join an existing context, returning its context_id.
     ///
     /// The context must already exist (created via `createContext`). Returns an
@@ -3300,7 +3871,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::JoinContextResults,
     ) -> Promise<(), capnp::Error> {
         let params = pry!(params.get());
-        // Schema: joinContext(contextId :Data, instance :Text) -> (contextId :Data)
+        // Schema: joinContext(contextId, instance, trace, resumeToken) -> (contextId, seat)
         let context_id_bytes = pry!(params.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes).ok_or_else(|| capnp::Error::failed(
@@ -3308,6 +3879,11 @@ impl kernel::Server for KernelImpl {
             ))
         );
         let instance = pry!(pry!(params.get_instance()).to_str()).to_owned();
+        let resume_token = if params.has_resume_token() {
+            pry!(pry!(params.get_resume_token()).to_str()).to_owned()
+        } else {
+            String::new()
+        };
 
         let kernel = self.kernel.clone();
         let connection = self.connection.clone();
@@ -3319,7 +3895,7 @@ impl kernel::Server for KernelImpl {
             kernel.id.to_hex()
         );
 
-        let span = extract_rpc_trace(params.get_trace(), "join_context");
+        let span = self.rpc_trace(params.get_trace(), "join_context");
         Promise::from_future(
             async move {
                 // Context must already exist — no auto-creation
@@ -3358,6 +3934,8 @@ impl kernel::Server for KernelImpl {
                         kaijutsu_telemetry::context_root_span(&trace_id, "join_context").entered();
                 }
 
+                let seat = take_seat(&kernel, &connection, &resume_token);
+
                 // Update connection's active context in the global map
                 let session_id = connection.borrow().session_id;
                 connection
@@ -3365,8 +3943,31 @@ impl kernel::Server for KernelImpl {
                     .session_contexts
                     .insert(session_id, context_id);
 
-                // Return the context_id
-                results.get().set_context_id(context_id.as_bytes());
+                let pending_consent: Vec<_> = {
+                    let principal_id = connection.borrow().principal.id;
+                    match kernel.kernel_db.lock().list_inbox(principal_id, false, 50) {
+                        Ok(items) => items
+                            .into_iter()
+                            .filter(|i| i.kind == kaijutsu_types::InboxKind::ConsentRequest)
+                            .collect(),
+                        Err(e) => {
+                            log::warn!("join_context: pending consent lookup failed: {e}");
+                            Vec::new()
+                        }
+                    }
+                };
+
+                let mut r = results.get();
+                r.set_context_id(context_id.as_bytes());
+                let mut b = r.init_seat();
+                b.set_token(&seat.token);
+                b.set_session_id(session_id.as_bytes());
+                b.set_resumed(seat.resumed);
+                b.set_grace_secs(kernel.seats.grace().as_secs() as u32);
+                let mut list = b.init_pending_consent(pending_consent.len() as u32);
+                for (i, item) in pending_consent.iter().enumerate() {
+                    set_inbox_item(list.reborrow().get(i as u32), item);
+                }
 
                 Ok(())
             }
@@ -3399,7 +4000,7 @@ impl kernel::Server for KernelImpl {
         // context's binding; a fresh binding seed is auto-populated by
         // dispatch_tool_via_broker on first touch.
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "call_mcp_tool").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "call_mcp_tool").entered();
         let call = pry!(p.get_call());
         let tool_name = pry!(pry!(call.get_tool()).to_str()).to_owned();
         let arguments = pry!(pry!(call.get_arguments()).to_str()).to_owned();
@@ -3452,7 +4053,7 @@ impl kernel::Server for KernelImpl {
             self.kernel.id.to_hex()
         );
         let params = pry!(params.get());
-        let trace_span = extract_rpc_trace(params.get_trace(), "shell_execute");
+        let trace_span = self.rpc_trace(params.get_trace(), "shell_execute");
         let code = pry!(pry!(params.get_code()).to_str()).to_owned();
         let context_id_bytes = pry!(params.get_context_id());
         let context_id = pry!(
@@ -3489,6 +4090,7 @@ impl kernel::Server for KernelImpl {
                     context_id,
                     user_principal_id,
                     user_initiated,
+                    None,
                     &kernel,
                     &connection,
                 )
@@ -3502,10 +4104,91 @@ impl kernel::Server for KernelImpl {
         )
     }
 
-    // =========================================================================
-    // Shell state (cwd, last result)
-    // =========================================================================
-
+    fn shell_rerun(
+        self: Rc<Self>,
+        params: kernel::ShellRerunParams,
+        mut results: kernel::ShellRerunResults,
+    ) -> Promise<(), capnp::Error> {
+        let params = pry!(params.get());
+        let trace_span = self.rpc_trace(params.get_trace(), "shell_rerun");
+        let block_id = pry!(parse_block_id_from_reader(&pry!(params.get_block_id())));
+        let context_id = block_id.context_id;
+        let user_initiated = params.get_user_initiated();
+
+        let kernel = self.kernel.clone();
+        let connection = self.connection.clone();
+        let user_principal_id = self.connection.borrow().principal.id;
+
+        Promise::from_future(
+            async move {
+                let block = kernel
+                    .documents
+                    .get_block_snapshot(context_id, &block_id)
+                    .map_err(|e| capnp::Error::failed(e.to_string()))?
+                    .ok_or_else(|| {
+                        capnp::Error::failed(format!("block {} not found", block_id.to_key()))
+                    })?;
+                if block.kind != BlockKind::ToolCall
+                    || block.tool_kind != Some(TypesToolKind::Shell)
+                {
+                    return Err(capnp::Error::failed(format!(
+                        "block {} is not a shell command",
+                        block_id.to_key()
+                    )));
+                }
+                let code = block
+                    .tool_input
+                    .as_deref()
+                    .and_then(|input| serde_json::from_str::<serde_json::Value>(input).ok())
+                    .and_then(|input| input.get("code")?.as_str().map(str::to_owned))
+                    .ok_or_else(|| {
+                        capnp::Error::failed(format!(
+                            "shell command {} has no code to re-run",
+                            block_id.to_key()
+                        ))
+                    })?;
+                log::info!(
+                    "Shell rerun: block={}, code={}, user_initiated={}",
+                    block_id.to_key(),
+                    code,
+                    user_initiated
+                );
+
+                // Same facade gate as shellExecute.
+                kernel
+                    .kernel
+                    .broker()
+                    .check_facade(&context_id, "shell")
+                    .await
+                    .map_err(|e| capnp::Error::failed(format!("shell denied: {e}")))?;
+
+                let rerun = ShellRerun {
+                    of: block_id,
+                    recorded: block.shell_command,
+                };
+                let command_block_id = execute_shell_command(
+                    &code,
+                    context_id,
+                    user_principal_id,
+                    user_initiated,
+                    Some(rerun),
+                    &kernel,
+                    &connection,
+                )
+                .await?;
+
+                let mut block_id_builder = results.get().init_command_block_id();
+                set_block_id_builder(&mut block_id_builder, &command_block_id);
+                Ok(())
+            }
+            .instrument(trace_span),
+        )
+    }
+
+    // =========================================================================
+    // Shell state (cwd, last result)
+    // =========================================================================
+
     fn get_cwd(
         self: Rc<Self>,
         _params: kernel::GetCwdParams,
@@ -3620,7 +4303,9 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::PushOpsResults,
     ) -> Promise<(), capnp::Error> {
         let params_reader = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(params_reader.get_trace(), "push_ops").entered();
+        let _trace_guard = self
+            .rpc_trace(params_reader.get_trace(), "push_ops")
+            .entered();
         let context_id_bytes = pry!(params_reader.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -3656,9 +4341,42 @@ impl kernel::Server for KernelImpl {
             }
         };
 
-        // Merge the sync payload into the document
-        let ack_version = match documents.merge_ops(context_id, payload) {
+        // Locks bind pushed writes like any other: a block another principal
+        // holds under `hold` refuses the whole push; under `warn` it goes
+        // through, and the warning is only logged (pushOps has no channel
+        // for it). New blocks can't be locked yet.
+        let writer = self.connection.borrow().principal.id;
+        let touched = payload
+            .block_ops
+            .iter()
+            .map(|(id, _)| id)
+            .chain(payload.updated_headers.iter().map(|h| &h.id))
+            .chain(payload.deleted_blocks.iter());
+        for block_id in touched {
+            match documents.check_lock(block_id, Some(writer)) {
+                Ok(None) => {}
+                Ok(Some(warning)) => log::info!("push_ops: {warning}"),
+                Err(e) => {
+                    return Promise::err(capnp::Error::failed(format!("push_ops: {e}")));
+                }
+            }
+        }
+
+        // Check the payload against the document's shape, then merge it
+        let ack_version = match documents.merge_pushed_ops(context_id, payload) {
             Ok(version) => version,
+            Err(kaijutsu_kernel::block_store::BlockStoreError::Rejected(violations)) => {
+                log::warn!(
+                    "push_ops for context {} rejected: {}",
+                    context_id,
+                    kaijutsu_types::describe_violations(&violations)
+                );
+                let mut list = results.get().init_violations(violations.len() as u32);
+                for (i, violation) in violations.iter().enumerate() {
+                    set_push_violation(list.reborrow().get(i as u32), violation);
+                }
+                return Promise::ok(());
+            }
             Err(e) => {
                 return Promise::err(capnp::Error::failed(format!("failed to merge ops: {}", e)));
             }
@@ -3964,7 +4682,7 @@ impl kernel::Server for KernelImpl {
         };
         drop(doc_entry);
 
-        let span = extract_rpc_trace(params_reader.get_trace(), "cherry_pick_block");
+        let span = self.rpc_trace(params_reader.get_trace(), "cherry_pick_block");
         Promise::from_future(
             async move {
                 // Look up target context in drift router for trace linkage
@@ -4022,7 +4740,9 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::GetContextHistoryResults,
     ) -> Promise<(), capnp::Error> {
         let params_reader = pry!(params.get());
-        let _span = extract_rpc_trace(params_reader.get_trace(), "get_context_history").entered();
+        let _span = self
+            .rpc_trace(params_reader.get_trace(), "get_context_history")
+            .entered();
         let context_id_bytes = pry!(params_reader.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -4207,7 +4927,7 @@ impl kernel::Server for KernelImpl {
         let ctx_id = pry!(self.connection.borrow().require_context());
         let kernel_arc = self.kernel.kernel.clone();
 
-        let span = extract_rpc_trace(pry!(params.get()).get_trace(), "get_context_id");
+        let span = self.rpc_trace(pry!(params.get()).get_trace(), "get_context_id");
         Promise::from_future(
             async move {
                 results.get().set_id(ctx_id.as_bytes());
@@ -4245,7 +4965,7 @@ impl kernel::Server for KernelImpl {
         };
 
         let shared_kernel = self.kernel.clone();
-        let span = extract_rpc_trace(params_reader.get_trace(), "configure_llm");
+        let span = self.rpc_trace(params_reader.get_trace(), "configure_llm");
         Promise::from_future(
             async move {
                 // Validate provider before persisting — never write bad data
@@ -4368,7 +5088,7 @@ impl kernel::Server for KernelImpl {
     ) -> Promise<(), capnp::Error> {
         let kernel_arc = self.kernel.kernel.clone();
 
-        let span = extract_rpc_trace(pry!(params.get()).get_trace(), "get_llm_config");
+        let span = self.rpc_trace(pry!(params.get()).get_trace(), "get_llm_config");
         Promise::from_future(
             async move {
                 let registry = kernel_arc.llm().read().await;
@@ -4394,10 +5114,18 @@ impl kernel::Server for KernelImpl {
                     }
                     // Populate full models list from aliases + default
                     let model_ids = registry.models_for_provider(name);
-                    let mut models_list = entry.init_models(model_ids.len() as u32);
+                    let mut models_list = entry.reborrow().init_models(model_ids.len() as u32);
                     for (j, model_id) in model_ids.iter().enumerate() {
                         models_list.set(j as u32, model_id);
                     }
+                    if let Some(health) = kernel_arc.llm_health().get(name) {
+                        let mut h = entry.init_health();
+                        h.set_status(health.status.as_str());
+                        h.set_model(&health.model);
+                        h.set_latency_ms(health.latency_ms);
+                        h.set_detail(&health.detail);
+                        h.set_checked_at(health.checked_at);
+                    }
                 }
 
                 Ok(())
@@ -4721,7 +5449,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::ListTracksResults,
     ) -> Promise<(), capnp::Error> {
         let kernel_arc = self.kernel.kernel.clone();
-        let span = extract_rpc_trace(pry!(params.get()).get_trace(), "list_tracks");
+        let span = self.rpc_trace(pry!(params.get()).get_trace(), "list_tracks");
         Promise::from_future(
             async move {
                 let tracks = match kernel_arc.request_track_snapshot() {
@@ -4757,7 +5485,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::CompactContextResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _span = extract_rpc_trace(p.get_trace(), "compact_context").entered();
+        let _span = self.rpc_trace(p.get_trace(), "compact_context").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -4792,7 +5520,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::EditInputResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let span = extract_rpc_trace(p.get_trace(), "edit_input");
+        let span = self.rpc_trace(p.get_trace(), "edit_input");
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -4843,7 +5571,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::GetInputStateResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "get_input_state").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "get_input_state").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -4875,7 +5603,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::PushInputOpsResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "push_input_ops").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "push_input_ops").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -4909,7 +5637,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::SubmitInputResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let trace_span = extract_rpc_trace(p.get_trace(), "submit_input");
+        let trace_span = self.rpc_trace(p.get_trace(), "submit_input");
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -4956,6 +5684,7 @@ impl kernel::Server for KernelImpl {
                         context_id,
                         user_principal_id,
                         true,
+                        None,
                         &kernel,
                         &connection,
                     )
@@ -4987,24 +5716,48 @@ impl kernel::Server for KernelImpl {
                         kernel.id,
                     );
 
-                    // Create user message block at the end of the document
+                    // Create user message block at the end of the document,
+                    // carrying its resolved @-mentions.
+                    let mentions = kaijutsu_kernel::mention::resolve(
+                        &kernel.kernel_db,
+                        kernel.kernel.drift(),
+                        &text,
+                    );
                     let last_block = documents.last_block_id(context_id);
                     let user_block_id = documents
-                        .insert_block_as(
+                        .insert_user_block_as(
                             context_id,
-                            None,
                             last_block.as_ref(),
-                            Role::User,
-                            BlockKind::Text,
                             &text,
-                            Status::Done,
-                            ContentType::Plain,
-                            Some(user_principal_id),
+                            mentions,
+                            user_principal_id,
                         )
                         .map_err(|e| {
                             capnp::Error::failed(format!("failed to insert user block: {}", e))
                         })?;
 
+                    if let Ok(Some(block)) = documents.get_block_snapshot(context_id, &user_block_id)
+                        && !block.mentions.is_empty()
+                    {
+                        let sender_name = connection.borrow().principal.username.clone();
+                        // A context mention stages a drift, which takes the
+                        // same `drift` authority as `kj drift push`.
+                        let may_drift = kernel
+                            .kernel
+                            .broker()
+                            .binding_checked(&context_id)
+                            .await
+                            .is_ok_and(|b| b.allows(&kaijutsu_kernel::mcp::Capability::Drift));
+                        kaijutsu_kernel::mention::route(
+                            &kernel.kernel_db,
+                            Some(kernel.kernel.block_flows()),
+                            kernel.kernel.drift(),
+                            &block,
+                            &sender_name,
+                            may_drift,
+                        );
+                    }
+
                     // Spawn LLM streaming in background. Interactive chat prompt
                     // via submit_input: announce_completion=false (design §7) —
                     // a human-prompted turn never feeds the musician's OODA Act.
@@ -5012,6 +5765,7 @@ impl kernel::Server for KernelImpl {
                         &kernel,
                         context_id,
                         None,
+                        None,
                         &user_block_id,
                         tool_ctx,
                         user_principal_id,
@@ -5035,7 +5789,7 @@ impl kernel::Server for KernelImpl {
         _results: kernel::ClearInputResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "clear_input").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "clear_input").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -5056,7 +5810,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::SearchSimilarResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let span = extract_rpc_trace(p.get_trace(), "search_similar");
+        let span = self.rpc_trace(p.get_trace(), "search_similar");
         let query = pry!(pry!(p.get_query()).to_str()).to_string();
         let k = p.get_k() as usize;
         let kernel = self.kernel.clone();
@@ -5101,7 +5855,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::GetNeighborsResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let span = extract_rpc_trace(p.get_trace(), "get_neighbors");
+        let span = self.rpc_trace(p.get_trace(), "get_neighbors");
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -5148,7 +5902,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::GetClustersResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let span = extract_rpc_trace(p.get_trace(), "get_clusters");
+        let span = self.rpc_trace(p.get_trace(), "get_clusters");
         let min_cluster_size = p.get_min_cluster_size() as usize;
         let kernel = self.kernel.clone();
 
@@ -5189,7 +5943,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::GetBlocksResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "get_blocks").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "get_blocks").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -5250,7 +6004,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::GetContextSyncResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "get_context_sync").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "get_context_sync").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -5258,7 +6012,7 @@ impl kernel::Server for KernelImpl {
         );
 
         let documents = &self.kernel.documents;
-        let (ops, version) = pry!(
+        let state = pry!(
             documents
                 .context_sync_state(context_id)
                 .map_err(|e| capnp::Error::failed(e.to_string()))
@@ -5266,8 +6020,10 @@ impl kernel::Server for KernelImpl {
 
         let mut r = results.get();
         r.set_context_id(context_id.as_bytes());
-        r.set_ops(&ops);
-        r.set_version(version);
+        r.set_ops(&state.ops);
+        r.set_version(state.version);
+        r.set_generation(state.generation);
+        r.set_seq_num(state.seq_num);
 
         Promise::ok(())
     }
@@ -5290,12 +6046,26 @@ impl kernel::Server for KernelImpl {
         // — the alternative (no dedupe for empty instance) leaks tasks again.
         let instance = pry!(pry!(p.get_instance()).to_str()).to_owned();
 
-        // Parse the BlockEventFilter from the capnp struct
+        // Parse the BlockEventFilter from the capnp struct. The seat
+        // remembers it; a resumed seat that resubscribes without one gets
+        // its last filter back.
+        let seat = self.connection.borrow().seat;
         let filter = if p.has_filter() {
-            let f = pry!(p.get_filter());
-            parse_block_event_filter(f)
+            let f = parse_block_event_filter(pry!(p.get_filter()));
+            if let Some(claim) = seat {
+                self.kernel
+                    .seats
+                    .with_state(claim, |s| s.block_filter = Some(f.clone()));
+            }
+            f
         } else {
-            kaijutsu_types::BlockEventFilter::default()
+            seat.and_then(|claim| {
+                self.kernel
+                    .seats
+                    .with_state(claim, |s| s.block_filter.clone())
+                    .flatten()
+            })
+            .unwrap_or_default()
         };
 
         let has_filter = filter.has_active_constraint();
@@ -5309,6 +6079,7 @@ impl kernel::Server for KernelImpl {
         {
             let block_flows = self.kernel.kernel.block_flows().clone();
             let input_flows = self.kernel.documents.input_flows().cloned();
+            let config_flows = self.kernel.kernel.config_flows().clone();
             let kernel_id = self.kernel.id;
             let conn_cancel = self.connection.borrow().cancel_token();
             let principal_id = self.connection.borrow().principal.id;
@@ -5316,8 +6087,16 @@ impl kernel::Server for KernelImpl {
             let dedupe_key = (principal_id, instance.clone());
 
             let task = tokio::task::spawn_local(async move {
-                let mut block_sub = block_flows.subscribe(subscribe_pattern);
+                // The batcher applies the server-side filter before anything
+                // is serialized to the wire.
+                let mut block_sub = BlockEventBatcher::new(
+                    block_flows.subscribe(subscribe_pattern),
+                    has_filter.then_some(filter),
+                );
                 let mut input_sub = input_flows.map(|f| f.subscribe("input.*"));
+                // Config re-applies are kernel-wide, not block events: the
+                // filter doesn't apply to them.
+                let mut config_sub = config_flows.subscribe("config.*");
                 let mut health = SubscriberHealth::new(SUBSCRIBER_FAILURE_STREAK_TIMEOUT);
                 log::debug!(
                     "Started filtered FlowBus subscription for kernel {} (filter_active={}, pattern={})",
@@ -5336,14 +6115,12 @@ impl kernel::Server for KernelImpl {
                             log::debug!("Filtered FlowBus bridge cancelled with connection");
                             break;
                         }
-                        Some(msg) = block_sub.recv() => {
-                            // Apply server-side filter before serializing to wire
-                            if has_filter && !msg.payload.matches_filter(&filter) {
-                                continue;
+                        Some(event) = block_sub.next() => match event {
+                            BridgeEvent::TextOps { context_id, ref flows } => {
+                                forward_text_ops_batch(&callback, context_id, flows, CALLBACK_TIMEOUT, kernel_id).await
                             }
-
                             // Same dispatch as subscribe_blocks — forward to callback
-                            match msg.payload {
+                            BridgeEvent::Flow(payload) => match payload {
                                 BlockFlow::Inserted { context_id, ref block, ref after_id, ref ops, .. } => {
                                     let mut req = callback.on_block_inserted_request();
                                     {
@@ -5525,7 +6302,7 @@ impl kernel::Server for KernelImpl {
                                         }
                                     }
                                 }
-                                BlockFlow::TextOps { context_id, ref block_id, ref ops, seq_num, .. } => {
+                                BlockFlow::TextOps { context_id, ref block_id, ref ops, seq_num, generation, .. } => {
                                     let mut req = callback.on_block_text_ops_request();
                                     {
                                         let mut params = req.get();
@@ -5533,6 +6310,7 @@ impl kernel::Server for KernelImpl {
                                         set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
                                         params.set_ops(ops);
                                         params.set_seq_num(seq_num);
+                                        params.set_generation(generation);
                                     }
                                     match tokio::time::timeout(
                                         CALLBACK_TIMEOUT, req.send().promise,
@@ -5698,6 +6476,106 @@ impl kernel::Server for KernelImpl {
                                         }
                                     }
                                 }
+                                BlockFlow::InboxPosted { ref item, .. } => {
+                                    // Addressed to one seat: other principals'
+                                    // connections never see it.
+                                    if item.recipient != principal_id {
+                                        continue;
+                                    }
+                                    let mut req = callback.on_inbox_item_request();
+                                    set_inbox_item(req.get().init_item(), item);
+                                    match tokio::time::timeout(
+                                        CALLBACK_TIMEOUT, req.send().promise,
+                                    ).await {
+                                        Ok(Ok(_)) => true,
+                                        Ok(Err(e)) => {
+                                            log::debug!(
+                                                "FlowBus callback failed for {kernel_id}: {e}",
+                                            );
+                                            false
+                                        }
+                                        Err(_) => {
+                                            log::warn!(
+                                                "FlowBus callback timed out after {:?} \
+                                                 for kernel {kernel_id} — peer is not \
+                                                 reading; dropping subscriber",
+                                                CALLBACK_TIMEOUT,
+                                            );
+                                            false
+                                        }
+                                    }
+                                }
+                                BlockFlow::ModelChanged {
+                                    context_id,
+                                    ref provider,
+                                    ref model,
+                                    by,
+                                } => {
+                                    let mut req = callback.on_context_model_changed_request();
+                                    {
+                                        let mut params = req.get();
+                                        params.set_context_id(context_id.as_bytes());
+                                        params.set_provider(provider);
+                                        params.set_model(model);
+                                        params.set_by(by.as_bytes());
+                                    }
+                                    match tokio::time::timeout(
+                                        CALLBACK_TIMEOUT, req.send().promise,
+                                    ).await {
+                                        Ok(Ok(_)) => true,
+                                        Ok(Err(e)) => {
+                                            log::debug!(
+                                                "FlowBus callback failed for {kernel_id}: {e}",
+                                            );
+                                            false
+                                        }
+                                        Err(_) => {
+                                            log::warn!(
+                                                "FlowBus callback timed out after {:?} \
+                                                 for kernel {kernel_id} — peer is not \
+                                                 reading; dropping subscriber",
+                                                CALLBACK_TIMEOUT,
+                                            );
+                                            false
+                                        }
+                                    }
+                                }
+                                BlockFlow::LockChanged {
+                                    context_id,
+                                    ref block_id,
+                                    ref lock,
+                                } => {
+                                    let mut req = callback.on_block_lock_changed_request();
+                                    {
+                                        let mut params = req.get();
+                                        params.set_context_id(context_id.as_bytes());
+                                        set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                                        params.set_locked(lock.is_some());
+                                        if let Some(lock) = lock {
+                                            set_block_lock(params.reborrow().init_lock(), lock);
+                                        }
+                                    }
+                                    match tokio::time::timeout(
+                                        CALLBACK_TIMEOUT, req.send().promise,
+                                    ).await {
+                                        Ok(Ok(_)) => true,
+                                        Ok(Err(e)) => {
+                                            log::debug!(
+                                                "FlowBus callback failed for {kernel_id}: {e}",
+                                            );
+                                            false
+                                        }
+                                        Err(_) => {
+                                            log::warn!(
+                                                "FlowBus callback timed out after {:?} \
+                                                 for kernel {kernel_id} — peer is not \
+                                                 reading; dropping subscriber",
+                                                CALLBACK_TIMEOUT,
+                                            );
+                                            false
+                                        }
+                                    }
+                                }
                                 BlockFlow::BeatSync { context_id, ref beat_ref } => {
                                     let mut req = callback.on_beat_sync_request();
                                     {
@@ -5726,8 +6604,8 @@ impl kernel::Server for KernelImpl {
                                         }
                                     }
                                 }
-                            }
-                        }
+                            },
+                        },
                         Some(msg) = async {
                             match &mut input_sub {
                                 Some(sub) => sub.recv().await,
@@ -5793,6 +6671,9 @@ impl kernel::Server for KernelImpl {
                                 }
                             }
                         }
+                        Some(msg) = config_sub.recv() => {
+                            forward_config_flow(&callback, &msg.payload, CALLBACK_TIMEOUT, kernel_id).await
+                        }
                         else => break,
                     };
 
@@ -5918,7 +6799,7 @@ impl kernel::Server for KernelImpl {
         params: kernel::ListPresetsParams,
         mut results: kernel::ListPresetsResults,
     ) -> Promise<(), capnp::Error> {
-        let _span = extract_rpc_trace(pry!(params.get()).get_trace(), "list_presets");
+        let _span = self.rpc_trace(pry!(params.get()).get_trace(), "list_presets");
         let _kernel_id = self.kernel.id;
 
         let presets = {
@@ -5945,7 +6826,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::SetContextStateResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "set_context_state").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "set_context_state").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6038,7 +6919,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::ConcludeResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "conclude").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "conclude").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6170,7 +7051,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::PromoteContextResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "promote_context").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "promote_context").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6233,7 +7114,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::DemoteContextResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "demote_context").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "demote_context").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6288,7 +7169,9 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::SetContextPausedResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "set_context_paused").entered();
+        let _trace_guard = self
+            .rpc_trace(p.get_trace(), "set_context_paused")
+            .entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6326,7 +7209,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::ArchiveContextResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "archive_context").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "archive_context").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6385,13 +7268,126 @@ impl kernel::Server for KernelImpl {
         Promise::ok(())
     }
 
+    /// Bulk archive: every open context the filter selects, in one DB
+    /// transaction. Same drift-router sync as `archive_context`, per closed
+    /// id; a dry run reports the selection and touches nothing.
+    fn close_contexts(
+        self: Rc<Self>,
+        params: kernel::CloseContextsParams,
+        mut results: kernel::CloseContextsResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _trace_guard = self.rpc_trace(p.get_trace(), "close_contexts").entered();
+        let filter = pry!(parse_context_close_filter(pry!(p.get_filter())));
+        let dry_run = p.get_dry_run();
+
+        let closed = {
+            let mut db = self.kernel.kernel_db.lock();
+            pry!(
+                db.close_contexts(&filter, dry_run)
+                    .map_err(|e| capnp::Error::failed(format!("close_contexts: {e}")))
+            )
+        };
+
+        if !dry_run && !closed.is_empty() {
+            match self.kernel.kernel.drift().try_write() {
+                Some(mut drift) => {
+                    for id in &closed {
+                        if let Err(e) = drift.set_state(*id, kaijutsu_types::ContextState::Archived)
+                        {
+                            log::error!("close_contexts: drift set_state failed: {e}");
+                        }
+                    }
+                }
+                None => {
+                    log::warn!(
+                        "close_contexts: drift router busy (write); DB archived, router state lags"
+                    );
+                }
+            }
+        }
+
+        log::info!(
+            "close_contexts: {} context(s){} filter={:?}",
+            closed.len(),
+            if dry_run { " (dry run)" } else { "" },
+            filter
+        );
+        let mut list = results.get().init_closed(closed.len() as u32);
+        for (i, id) in closed.iter().enumerate() {
+            list.set(i as u32, id.as_bytes());
+        }
+        Promise::ok(())
+    }
+
+    /// Reopen a closed context into automatic placement — the undo for
+    /// `archive_context`/`close_contexts` that, unlike promote's
+    /// resurrection, takes no ring-0 seat.
+    fn reopen_context(
+        self: Rc<Self>,
+        params: kernel::ReopenContextParams,
+        mut results: kernel::ReopenContextResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _trace_guard = self.rpc_trace(p.get_trace(), "reopen_context").entered();
+        let context_id_bytes = pry!(p.get_context_id());
+        let context_id = pry!(
+            ContextId::try_from_slice(context_id_bytes)
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+
+        let concluded = {
+            let db = self.kernel.kernel_db.lock();
+            let reopened = db
+                .reopen_context(context_id)
+                .and_then(|reopened| Ok((reopened, db.get_context(context_id)?)));
+            match reopened {
+                Ok((false, _)) => {
+                    // Already open — idempotent success, nothing to sync.
+                    results.get().set_success(true);
+                    return Promise::ok(());
+                }
+                Ok((true, row)) => row.is_some_and(|r| r.concluded_at.is_some()),
+                Err(e) => {
+                    results.get().set_success(false);
+                    results.get().set_error(&e.to_string());
+                    return Promise::ok(());
+                }
+            }
+        };
+
+        // Reverse the archive's DriftRouter sync, as promote's resurrection
+        // does: conclusion is orthogonal to placement.
+        let state = if concluded {
+            kaijutsu_types::ContextState::Concluded
+        } else {
+            kaijutsu_types::ContextState::Live
+        };
+        match self.kernel.kernel.drift().try_write() {
+            Some(mut drift) => {
+                if let Err(e) = drift.set_state(context_id, state) {
+                    log::error!("reopen_context: drift set_state failed: {e}");
+                }
+            }
+            None => {
+                log::warn!(
+                    "reopen_context: drift router busy (write); DB reopened, router state lags"
+                );
+            }
+        }
+
+        log::info!("reopen_context: context={}", context_id.short());
+        results.get().set_success(true);
+        Promise::ok(())
+    }
+
     fn commit_capture(
         self: Rc<Self>,
         params: kernel::CommitCaptureParams,
         mut results: kernel::CommitCaptureResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "commit_capture").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "commit_capture").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6460,7 +7456,9 @@ impl kernel::Server for KernelImpl {
         _results: kernel::ReportClockEstimateResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "report_clock_estimate").entered();
+        let _trace_guard = self
+            .rpc_trace(p.get_trace(), "report_clock_estimate")
+            .entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6497,7 +7495,9 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::SetBlockExcludedResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "set_block_excluded").entered();
+        let _trace_guard = self
+            .rpc_trace(p.get_trace(), "set_block_excluded")
+            .entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6552,7 +7552,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::ListDeadLettersResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "list_dead_letters").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "list_dead_letters").entered();
         let kernel = self.kernel.kernel.clone();
         Promise::from_future(async move {
             let drift = kernel.drift().read();
@@ -6587,7 +7587,9 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::ReplayDeadLetterResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "replay_dead_letter").entered();
+        let _trace_guard = self
+            .rpc_trace(p.get_trace(), "replay_dead_letter")
+            .entered();
         let id = p.get_id();
         let kernel = self.kernel.kernel.clone();
         Promise::from_future(async move {
@@ -6604,7 +7606,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::ContextLeaveResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "context_leave").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "context_leave").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6634,7 +7636,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::MoveBlockResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _trace_guard = extract_rpc_trace(p.get_trace(), "move_block").entered();
+        let _trace_guard = self.rpc_trace(p.get_trace(), "move_block").entered();
         let context_id_bytes = pry!(p.get_context_id());
         let context_id = pry!(
             ContextId::try_from_slice(context_id_bytes)
@@ -6680,7 +7682,7 @@ impl kernel::Server for KernelImpl {
         mut results: kernel::PingResults,
     ) -> Promise<(), capnp::Error> {
         let p = pry!(params.get());
-        let _span = extract_rpc_trace(p.get_trace(), "ping").entered();
+        let _span = self.rpc_trace(p.get_trace(), "ping").entered();
         let now_ms = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_millis() as u64)
@@ -6691,31 +7693,1952 @@ impl kernel::Server for KernelImpl {
         Promise::ok(())
     }
 
-}
+    /// Aggregated statistics for one context (`ContextStats`): block tallies
+    /// from the document, metadata from the KernelDb row, drift edge counts,
+    /// and the staged-drift approval queue. Computed on demand — the walk is
+    /// one `block_snapshots` pass, no caching. A context with neither a
+    /// document nor a DB row is an error; one with only the row (cold,
+    /// never joined) reports zero blocks rather than failing.
+    fn get_context_stats(
+        self: Rc<Self>,
+        params: kernel::GetContextStatsParams,
+        mut results: kernel::GetContextStatsResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _trace_guard = self.rpc_trace(p.get_trace(), "get_context_stats").entered();
+        let context_id_bytes = pry!(p.get_context_id());
+        let context_id = pry!(
+            ContextId::try_from_slice(context_id_bytes)
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
 
-// ============================================================================
-// Shell Value Conversion Helpers
-// ============================================================================
+        let blocks = self.kernel.documents.block_snapshots(context_id).ok();
+        let mut stats = kaijutsu_types::ContextStats::tally(blocks.iter().flatten());
+        stats.context_id = Some(context_id);
 
-/// Convert kaish `ast::Value` → Cap'n Proto `ShellValue` builder.
-fn value_to_shell_value(mut builder: shell_value::Builder<'_>, value: &kaish_kernel::ast::Value) {
-    use kaish_kernel::ast::Value;
-    match value {
-        Value::Null => builder.set_null(()),
-        Value::Bool(b) => builder.set_bool(*b),
-        Value::Int(i) => builder.set_int(*i),
-        Value::Float(f) => builder.set_float(*f),
-        Value::String(s) => builder.set_string(s),
-        Value::Json(j) => builder.set_json(serde_json::to_string(j).unwrap_or_default()),
-        Value::Bytes(b) => builder.set_bytes(b),
-    }
-}
+        let row = {
+            let db = self.kernel.kernel_db.lock();
+            let row = pry!(
+                db.get_context(context_id)
+                    .map_err(|e| capnp::Error::failed(e.to_string()))
+            );
+            stats.drift_in = db
+                .edges_to(context_id, Some(kaijutsu_types::EdgeKind::Drift))
+                .map(|edges| edges.len() as u64)
+                .unwrap_or(0);
+            stats.drift_out = db
+                .edges_from(context_id, Some(kaijutsu_types::EdgeKind::Drift))
+                .map(|edges| edges.len() as u64)
+                .unwrap_or(0);
+            row
+        };
+        if blocks.is_none() && row.is_none() {
+            return Promise::err(capnp::Error::failed(format!(
+                "context {} not found",
+                context_id.short()
+            )));
+        }
+        if let Some(row) = row {
+            stats.label = row.label;
+            stats.provider = row.provider;
+            stats.model = row.model;
+            stats.consent_mode = Some(row.consent_mode.as_str().to_string());
+            stats.context_state = Some(row.context_state.as_str().to_string());
+        }
 
-/// Convert Cap'n Proto `ShellValue` reader → kaish `ast::Value`.
-fn shell_value_to_value(
-    reader: shell_value::Reader<'_>,
-) -> Result<kaish_kernel::ast::Value, capnp::Error> {
-    use kaish_kernel::ast::Value;
+        // The drift router is the runtime authority for provider/model (same
+        // precedence as list_contexts) and owns the staging queue. try_read:
+        // a busy router only leaves those fields at their DB values.
+        if let Some(drift) = self.kernel.kernel.drift().try_read() {
+            if let Some(handle) = drift.get(context_id) {
+                if handle.provider.is_some() {
+                    stats.provider = handle.provider.clone();
+                }
+                if handle.model.is_some() {
+                    stats.model = handle.model.clone();
+                }
+            }
+            stats.pending_approvals = drift
+                .queue()
+                .iter()
+                .filter(|staged| staged.target_ctx == context_id)
+                .count() as u64;
+        }
+
+        set_context_stats(&mut results.get().init_stats(), &stats);
+        Promise::ok(())
+    }
+
+    /// Reorder a block among its siblings (sibling-scoped `moveBlock`).
+    fn reorder_block(
+        self: Rc<Self>,
+        params: kernel::ReorderBlockParams,
+        mut results: kernel::ReorderBlockResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _trace_guard = self.rpc_trace(p.get_trace(), "reorder_block").entered();
+        let context_id_bytes = pry!(p.get_context_id());
+        let context_id = pry!(
+            ContextId::try_from_slice(context_id_bytes)
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+        let block_id_reader = pry!(p.get_block_id());
+        let block_id = pry!(parse_block_id_from_reader(&block_id_reader));
+        let after_id = if p.get_has_after() {
+            let after_reader = pry!(p.get_after());
+            Some(pry!(parse_block_id_from_reader(&after_reader)))
+        } else {
+            None
+        };
+
+        if let Err(e) = self
+            .kernel
+            .documents
+            .reorder_block(context_id, &block_id, after_id.as_ref())
+        {
+            return Promise::err(capnp::Error::failed(e.to_string()));
+        }
+
+        match self.kernel.documents.version(context_id) {
+            Ok(ack) => {
+                results.get().set_ack_version(ack);
+                Promise::ok(())
+            }
+            Err(e) => Promise::err(capnp::Error::failed(e.to_string())),
+        }
+    }
+
+    fn set_block_label(
+        self: Rc<Self>,
+        params: kernel::SetBlockLabelParams,
+        mut results: kernel::SetBlockLabelResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _trace_guard = self.rpc_trace(p.get_trace(), "set_block_label").entered();
+        let context_id_bytes = pry!(p.get_context_id());
+        let context_id = pry!(
+            ContextId::try_from_slice(context_id_bytes)
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+        let block_id_reader = pry!(p.get_block_id());
+        let block_id = pry!(parse_block_id_from_reader(&block_id_reader));
+        let label = pry!(pry!(p.get_label()).to_str()).trim();
+        let label = (!label.is_empty()).then(|| label.to_owned());
+
+        if let Err(e) = self
+            .kernel
+            .documents
+            .set_label(context_id, &block_id, label)
+        {
+            return Promise::err(capnp::Error::failed(e.to_string()));
+        }
+
+        match self.kernel.documents.version(context_id) {
+            Ok(ack) => {
+                results.get().set_ack_version(ack);
+                Promise::ok(())
+            }
+            Err(e) => Promise::err(capnp::Error::failed(e.to_string())),
+        }
+    }
+
+    fn resolve_block(
+        self: Rc<Self>,
+        params: kernel::ResolveBlockParams,
+        mut results: kernel::ResolveBlockResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _trace_guard = self.rpc_trace(p.get_trace(), "resolve_block").entered();
+        let query = pry!(pry!(p.get_query()).to_str());
+        let block_id = pry!(
+            self.kernel
+                .documents
+                .resolve_block(query)
+                .map_err(|e| capnp::Error::failed(e.to_string()))
+        );
+        set_block_id_builder(&mut results.get().init_block_id(), &block_id);
+        Promise::ok(())
+    }
+
+    /// A backup copies every context and every user's keys off the box, so
+    /// the caller's own context needs the Admin authority, like releasing
+    /// the tool halt.
+    fn backup_now(
+        self: Rc<Self>,
+        params: kernel::BackupNowParams,
+        mut results: kernel::BackupNowResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "backup_now").entered();
+        let caller_context = pry!(self.connection.borrow().require_context());
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(async move {
+            use kaijutsu_kernel::mcp::Capability;
+            check_caller_authority(&kernel, caller_context, Capability::Admin, "backupNow").await?;
+            let Some(backups) = kernel.backups.get() else {
+                return Err(capnp::Error::failed(
+                    "backups are not configured (start the server with --backup-to)".into(),
+                ));
+            };
+            let report = backups
+                .backup_now()
+                .await
+                .map_err(|e| capnp::Error::failed(e.to_string()))?;
+
+            let mut r = results.get().init_report();
+            r.set_location(&report.location);
+            r.set_run(&report.run);
+            r.set_bytes(report.bytes);
+            let mut files = r.reborrow().init_files(report.files.len() as u32);
+            for (i, file) in report.files.iter().enumerate() {
+                files.set(i as u32, file);
+            }
+            let mut pruned = r.init_pruned(report.pruned.len() as u32);
+            for (i, run) in report.pruned.iter().enumerate() {
+                pruned.set(i as u32, run);
+            }
+            Ok(())
+        })
+    }
+
+    fn get_llm_params(
+        self: Rc<Self>,
+        params: kernel::GetLlmParamsParams,
+        mut results: kernel::GetLlmParamsResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "get_llm_params").entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let stored = pry!(
+            self.kernel
+                .kernel_db
+                .lock()
+                .get_context_llm_params(context_id)
+                .map_err(|e| capnp::Error::failed(format!("get_llm_params: {e}")))
+        );
+        set_llm_params(results.get().init_params(), &stored.unwrap_or_default());
+        Promise::ok(())
+    }
+
+    fn set_llm_params(
+        self: Rc<Self>,
+        params: kernel::SetLlmParamsParams,
+        mut results: kernel::SetLlmParamsResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "set_llm_params").entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let llm = if p.get_clear() {
+            None
+        } else {
+            Some(pry!(parse_llm_params(pry!(p.get_params()))))
+        };
+        let merge = p.get_merge();
+        let caller_context = pry!(self.connection.borrow().require_context());
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(async move {
+            use kaijutsu_kernel::mcp::Capability;
+            check_caller_authority(
+                &kernel,
+                caller_context,
+                Capability::Operator,
+                "setLlmParams",
+            )
+            .await?;
+            let db_err = |e| capnp::Error::failed(format!("set_llm_params: {e}"));
+            // Read, merge and write under one lock, so concurrent merges
+            // each land instead of the last overwriting the others.
+            let db = kernel.kernel_db.lock();
+            let llm = match llm {
+                Some(update) if merge => Some(
+                    db.get_context_llm_params(context_id)
+                        .map_err(db_err)?
+                        .unwrap_or_default()
+                        .overlay(&update),
+                ),
+                llm => llm,
+            };
+            db.set_context_llm_params(context_id, llm.as_ref())
+                .map_err(db_err)?;
+            let stored = db
+                .get_context_llm_params(context_id)
+                .map_err(db_err)?
+                .unwrap_or_default();
+            drop(db);
+            tracing::info!(
+                context = %context_id.to_hex(),
+                params = ?stored,
+                "llm params updated"
+            );
+            set_llm_params(results.get().init_params(), &stored);
+            Ok(())
+        })
+    }
+
+    fn broadcast_prompt(
+        self: Rc<Self>,
+        params: kernel::BroadcastPromptParams,
+        mut results: kernel::BroadcastPromptResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "broadcast_prompt");
+        let content = pry!(pry!(p.get_content()).to_str()).to_owned();
+        let label = pry!(pry!(p.get_label()).to_str()).to_owned();
+        let targets = pry!(
+            pry!(p.get_targets())
+                .iter()
+                .map(parse_broadcast_target)
+                .collect::<Result<Vec<_>, _>>()
+        );
+        let kernel = self.kernel.clone();
+        let (principal_id, session_id) = {
+            let conn = self.connection.borrow();
+            (conn.principal.id, conn.session_id)
+        };
+        Promise::from_future(
+            async move {
+                let status = crate::broadcast::start_broadcast(
+                    &kernel,
+                    &content,
+                    targets,
+                    Some(label.as_str()).filter(|l| !l.is_empty()),
+                    principal_id,
+                    session_id,
+                )
+                .await?;
+                set_broadcast_status(results.get().init_status(), &status);
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    fn get_broadcast(
+        self: Rc<Self>,
+        params: kernel::GetBroadcastParams,
+        mut results: kernel::GetBroadcastResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "get_broadcast").entered();
+        let id = p.get_id();
+        let status = pry!(
+            self.kernel
+                .broadcasts
+                .get(id)
+                .ok_or_else(|| capnp::Error::failed(format!("unknown broadcast {id}")))
+        );
+        set_broadcast_status(results.get().init_status(), &status);
+        Promise::ok(())
+    }
+
+    /// A look at a context the caller hasn't joined. Takes no seat and
+    /// leaves the session's active context alone; the optional event stream
+    /// is its own short-lived bridge task, not a (principal, instance)
+    /// subscription, so it can't displace the connection's real one.
+    fn peek_document(
+        self: Rc<Self>,
+        params: kernel::PeekDocumentParams,
+        mut results: kernel::PeekDocumentResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "peek_document");
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id()))
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+        let callback = if p.has_callback() {
+            Some(pry!(p.get_callback()))
+        } else {
+            None
+        };
+        let ttl = match p.get_ttl_secs() {
+            0 => PEEK_DEFAULT_TTL,
+            secs => std::time::Duration::from_secs(secs as u64).min(PEEK_MAX_TTL),
+        };
+        let (caller_context, conn_cancel) = {
+            let conn = self.connection.borrow();
+            (pry!(conn.require_context()), conn.cancel_token())
+        };
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(
+            async move {
+                use kaijutsu_kernel::mcp::{Capability, InstanceId};
+                let binding = kernel
+                    .kernel
+                    .broker()
+                    .binding_checked(&caller_context)
+                    .await
+                    .map_err(|e| capnp::Error::failed(e.to_string()))?;
+                let read = Capability::Tool {
+                    instance: InstanceId::new("builtin.block"),
+                    tool: "block_read".to_string(),
+                };
+                if !binding.allows(&read) {
+                    return Err(capnp::Error::failed(format!(
+                        "peekDocument denied: context {} lacks `builtin.block:block_read` \
+                         (kj binding allow builtin.block:block_read)",
+                        caller_context.short()
+                    )));
+                }
+                if !kernel.documents.contains(context_id) {
+                    return Err(capnp::Error::failed(format!(
+                        "context {context_id} not found"
+                    )));
+                }
+
+                // Subscribe before snapshotting, so nothing lands in between;
+                // the client drops replays by (generation, seqNum).
+                let block_sub = callback
+                    .as_ref()
+                    .map(|_| kernel.kernel.block_flows().subscribe("block.*"));
+                let blocks = kernel
+                    .documents
+                    .block_snapshots(context_id)
+                    .map_err(|e| capnp::Error::failed(e.to_string()))?;
+                let sync = kernel
+                    .documents
+                    .context_sync_state(context_id)
+                    .map_err(|e| capnp::Error::failed(e.to_string()))?;
+
+                let streaming = block_sub.is_some();
+                let peek_cancel = CancellationToken::new();
+                if let (Some(callback), Some(mut block_sub)) = (callback, block_sub) {
+                    let peek_cancel = peek_cancel.clone();
+                    let kernel_id = kernel.id;
+                    tokio::task::spawn_local(async move {
+                        const CALLBACK_TIMEOUT: std::time::Duration =
+                            std::time::Duration::from_secs(5);
+                        let expiry = tokio::time::sleep(ttl);
+                        tokio::pin!(expiry);
+                        let mut health = SubscriberHealth::new(SUBSCRIBER_FAILURE_STREAK_TIMEOUT);
+                        loop {
+                            let sent = tokio::select! {
+                                _ = conn_cancel.cancelled() => break,
+                                _ = peek_cancel.cancelled() => break,
+                                _ = &mut expiry => break,
+                                Some(msg) = block_sub.recv() => {
+                                    if msg.payload.context_id() != context_id {
+                                        continue;
+                                    }
+                                    forward_peek_flow(
+                                        &callback,
+                                        &msg.payload,
+                                        CALLBACK_TIMEOUT,
+                                        kernel_id,
+                                    )
+                                    .await
+                                }
+                                else => break,
+                            };
+                            if let Some(ok) = sent
+                                && !health.record(ok)
+                            {
+                                log::warn!(
+                                    "peek of {} stopping: callback failures continuous for over {:?}",
+                                    context_id.short(),
+                                    SUBSCRIBER_FAILURE_STREAK_TIMEOUT,
+                                );
+                                break;
+                            }
+                        }
+                        log::debug!("peek of {} ended", context_id.short());
+                    });
+                }
+
+                let mut r = results.get();
+                {
+                    let mut state = r.reborrow().init_state();
+                    state.set_context_id(context_id.as_bytes());
+                    state.set_version(sync.version);
+                    state.set_ops(&sync.ops);
+                    let mut list = state.init_blocks(blocks.len() as u32);
+                    for (i, block) in blocks.iter().enumerate() {
+                        set_block_snapshot(&mut list.reborrow().get(i as u32), block);
+                    }
+                }
+                r.set_generation(sync.generation);
+                r.set_seq_num(sync.seq_num);
+                r.set_expires_in_secs(if streaming { ttl.as_secs() as u32 } else { 0 });
+                r.set_peek(capnp_rpc::new_client(PeekImpl {
+                    cancel: peek_cancel,
+                }));
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    fn set_context_model(
+        self: Rc<Self>,
+        params: kernel::SetContextModelParams,
+        mut results: kernel::SetContextModelResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "set_context_model");
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id()))
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+        let spec = pry!(pry!(p.get_model()).to_str()).to_owned();
+        let principal_id = self.connection.borrow().principal.id;
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(
+            async move {
+                use kaijutsu_kernel::mcp::Capability;
+                let binding = kernel
+                    .kernel
+                    .broker()
+                    .binding_checked(&context_id)
+                    .await
+                    .map_err(|e| capnp::Error::failed(e.to_string()))?;
+                if !binding.allows(&Capability::Operator) {
+                    return Err(capnp::Error::failed(format!(
+                        "setContextModel denied: context {} lacks the `operator` authority \
+                         (kj binding allow operator)",
+                        context_id.short()
+                    )));
+                }
+
+                let switch = kaijutsu_kernel::model_switch::switch_context_model(
+                    &kernel.kernel,
+                    &kernel.kernel_db,
+                    &kernel.documents,
+                    context_id,
+                    &spec,
+                    principal_id,
+                )
+                .await
+                .map_err(|e| capnp::Error::failed(format!("setContextModel: {e}")))?;
+                if switch.changed {
+                    log::info!(
+                        "Context {} model switched to {}/{}",
+                        context_id.short(),
+                        switch.provider,
+                        switch.model
+                    );
+                }
+
+                let mut r = results.get();
+                r.set_provider(&switch.provider);
+                r.set_model(&switch.model);
+                r.set_changed(switch.changed);
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    fn context_preview(
+        self: Rc<Self>,
+        params: kernel::ContextPreviewParams,
+        mut results: kernel::ContextPreviewResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "context_preview");
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id()))
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+        let spec = pry!(pry!(p.get_strategy()).to_str()).trim().to_owned();
+        let strategy = if spec.is_empty() {
+            None
+        } else {
+            Some(pry!(spec.parse::<kaijutsu_types::Assembly>().map_err(
+                |e| capnp::Error::failed(format!("contextPreview: {e}"))
+            )))
+        };
+        let principal_id = self.connection.borrow().principal.id;
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(
+            async move {
+                let preview =
+                    crate::llm_stream::preview_context(&kernel, context_id, strategy, principal_id)
+                        .await
+                        .map_err(|e| capnp::Error::failed(format!("contextPreview: {e}")))?;
+                let tools_json = serde_json::to_string(&preview.tools)
+                    .map_err(|e| capnp::Error::failed(format!("contextPreview: {e}")))?;
+                let messages_json = serde_json::to_string(&preview.messages)
+                    .map_err(|e| capnp::Error::failed(format!("contextPreview: {e}")))?;
+
+                let mut out = results.get().init_preview();
+                out.set_provider(&preview.provider);
+                out.set_model(&preview.model);
+                out.set_strategy(&preview.strategy);
+                out.set_system_prompt(&preview.system_prompt);
+                out.set_tools_json(&tools_json);
+                out.set_messages_json(&messages_json);
+                set_llm_params(out.reborrow().init_params(), &preview.llm_params);
+                out.set_total_blocks(preview.total_blocks as u64);
+                out.set_kept_blocks(preview.kept_blocks as u64);
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    /// Recover a wedged context: hard-interrupt its LLM stream (plus this
+    /// connection's shell executions when it is driving the context), then
+    /// mark every block still `Running` as `Error` — with the stream gone
+    /// nothing is left to finish them. Clears the health watchdog's state
+    /// for the context so a fresh problem reports afresh.
+    fn context_recover(
+        self: Rc<Self>,
+        params: kernel::ContextRecoverParams,
+        mut results: kernel::ContextRecoverResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "context_recover");
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id()))
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+        {
+            let conn = self.connection.borrow();
+            if conn.require_context().ok() == Some(context_id) {
+                conn.cancel_running_executions();
+            }
+        }
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(
+            async move {
+                let interrupted = match kernel.get_interrupt(context_id).await {
+                    Some(interrupt) => {
+                        interrupt.hard();
+                        true
+                    }
+                    None => false,
+                };
+
+                let blocks = kernel
+                    .documents
+                    .block_snapshots(context_id)
+                    .map_err(|e| capnp::Error::failed(format!("contextRecover: {e}")))?;
+                let mut marked = Vec::new();
+                for block in blocks.iter().filter(|b| b.status == Status::Running) {
+                    match kernel
+                        .documents
+                        .set_status(context_id, &block.id, Status::Error)
+                    {
+                        Ok(()) => marked.push(block.id),
+                        Err(e) => log::warn!(
+                            "contextRecover: failed to mark {} as error: {e}",
+                            block.id.to_key()
+                        ),
+                    }
+                }
+                kernel.kernel.context_health().lock().clear(context_id);
+
+                log::info!(
+                    "contextRecover: context={}, interrupted={}, marked={}",
+                    context_id,
+                    interrupted,
+                    marked.len()
+                );
+
+                let mut r = results.get();
+                r.set_interrupted(interrupted);
+                let mut list = r.init_marked(marked.len() as u32);
+                for (i, id) in marked.iter().enumerate() {
+                    set_block_id_builder(&mut list.reborrow().get(i as u32), id);
+                }
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    /// Subscribe to, unsubscribe from, or list digests of other contexts —
+    /// the RPC face of `kj drift digest`. Changes need the subscriber's
+    /// `drift` authority, as the kj verb does.
+    fn digest_subscribe(
+        self: Rc<Self>,
+        params: kernel::DigestSubscribeParams,
+        mut results: kernel::DigestSubscribeResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "digest_subscribe");
+        let subscriber = pry!(
+            ContextId::try_from_slice(pry!(p.get_subscriber()))
+                .ok_or_else(|| capnp::Error::failed("invalid subscriber context ID".into()))
+        );
+        let source_bytes = pry!(p.get_source());
+        let source = if source_bytes.is_empty() {
+            None
+        } else {
+            Some(pry!(ContextId::try_from_slice(source_bytes).ok_or_else(
+                || capnp::Error::failed("invalid source context ID".into())
+            )))
+        };
+        let every_secs = Some(p.get_every_secs()).filter(|&s| s > 0);
+        let on_milestone = p.get_on_milestone();
+        let focus = pry!(pry!(p.get_focus()).to_str()).to_string();
+        let remove = p.get_remove();
+        let principal = self.connection.borrow().principal.id;
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(
+            async move {
+                let dispatcher = &kernel.kj_dispatcher;
+                if let Some(source) = source {
+                    let binding = kernel
+                        .kernel
+                        .broker()
+                        .binding_checked(&subscriber)
+                        .await
+                        .map_err(|e| capnp::Error::failed(e.to_string()))?;
+                    if !binding.allows(&kaijutsu_kernel::mcp::Capability::Drift) {
+                        return Err(capnp::Error::failed(format!(
+                            "digestSubscribe denied: context {} lacks the `drift` authority \
+                             (kj binding allow drift)",
+                            subscriber.short()
+                        )));
+                    }
+                    if remove {
+                        dispatcher
+                            .unsubscribe_digest(subscriber, source)
+                            .map_err(|e| capnp::Error::failed(format!("digestSubscribe: {e}")))?;
+                    } else {
+                        dispatcher
+                            .subscribe_digest(
+                                subscriber,
+                                source,
+                                every_secs,
+                                on_milestone,
+                                Some(focus),
+                                principal,
+                            )
+                            .map_err(|e| capnp::Error::failed(format!("digestSubscribe: {e}")))?;
+                    }
+                }
+
+                let subs = dispatcher.digest_subscriptions(subscriber);
+                let mut list = results.get().init_subscriptions(subs.len() as u32);
+                for (i, sub) in subs.iter().enumerate() {
+                    let mut b = list.reborrow().get(i as u32);
+                    b.set_subscriber(sub.subscriber.as_bytes());
+                    b.set_source(sub.source.as_bytes());
+                    b.set_every_secs(sub.every_secs.unwrap_or(0));
+                    b.set_on_milestone(sub.on_milestone);
+                    b.set_focus(sub.focus.as_deref().unwrap_or(""));
+                    b.set_created_by(sub.created_by.as_bytes());
+                    b.set_created_at(sub.created_at);
+                    b.set_last_digest_at(sub.last_digest_at.unwrap_or(0));
+                }
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    fn register_mcp_server(
+        self: Rc<Self>,
+        params: kernel::RegisterMcpServerParams,
+        mut results: kernel::RegisterMcpServerResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "register_mcp_server");
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id()))
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+        let (config, fork_mode) = pry!(parse_mcp_server_spec(pry!(p.get_spec())));
+
+        let kernel = self.kernel.clone();
+        Promise::from_future(
+            async move {
+                // Spawning a stdio server runs a host process; reaching out
+                // over HTTP is operator territory. Gate on the context's own
+                // authorities, like `kj` verbs.
+                use kaijutsu_kernel::mcp::Capability;
+                use kaijutsu_kernel::mcp::servers::McpTransport;
+                let (needed, name) = match config.transport {
+                    McpTransport::Stdio => (Capability::Exec, "exec"),
+                    McpTransport::StreamableHttp => (Capability::Operator, "operator"),
+                };
+                let binding = kernel
+                    .kernel
+                    .broker()
+                    .binding_checked(&context_id)
+                    .await
+                    .map_err(|e| capnp::Error::failed(e.to_string()))?;
+                if !binding.allows(&needed) {
+                    return Err(capnp::Error::failed(format!(
+                        "registerMcpServer denied: context {} lacks the `{name}` authority \
+                         (kj binding allow {name})",
+                        context_id.short()
+                    )));
+                }
+
+                let instance = config.name.clone();
+                let tools = kernel
+                    .kernel
+                    .register_context_mcp_server(context_id, config, fork_mode)
+                    .await
+                    .map_err(|e| capnp::Error::failed(format!("registerMcpServer: {e}")))?;
+                let status = kaijutsu_kernel::mcp::ScopedInstanceStatus {
+                    instance: kaijutsu_kernel::mcp::InstanceId::new(instance),
+                    fork_mode,
+                    health: kaijutsu_kernel::mcp::Health::Ready,
+                    tools,
+                };
+                set_context_mcp_server(&mut results.get().init_server(), &status);
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    fn unregister_mcp_server(
+        self: Rc<Self>,
+        params: kernel::UnregisterMcpServerParams,
+        mut results: kernel::UnregisterMcpServerResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "unregister_mcp_server");
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id()))
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+        let instance =
+            kaijutsu_kernel::mcp::InstanceId::new(pry!(pry!(p.get_instance()).to_str()));
+
+        let kernel = self.kernel.clone();
+        Promise::from_future(
+            async move {
+                check_mcp_authority(&kernel, context_id, "unregisterMcpServer").await?;
+                let stopped = kernel
+                    .kernel
+                    .broker()
+                    .release_scoped(context_id, &instance)
+                    .await
+                    .map_err(|e| capnp::Error::failed(format!("unregisterMcpServer: {e}")))?;
+                results.get().set_stopped(stopped);
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    fn list_context_mcp_servers(
+        self: Rc<Self>,
+        params: kernel::ListContextMcpServersParams,
+        mut results: kernel::ListContextMcpServersResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "list_context_mcp_servers");
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id()))
+                .ok_or_else(|| capnp::Error::failed("invalid context ID".into()))
+        );
+
+        let kernel = self.kernel.clone();
+        Promise::from_future(
+            async move {
+                check_mcp_authority(&kernel, context_id, "listContextMcpServers").await?;
+                let servers = kernel.kernel.context_mcp_servers(context_id).await;
+                let mut list = results.get().init_servers(servers.len() as u32);
+                for (i, status) in servers.iter().enumerate() {
+                    set_context_mcp_server(&mut list.reborrow().get(i as u32), status);
+                }
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    fn list_inbox(
+        self: Rc<Self>,
+        params: kernel::ListInboxParams,
+        mut results: kernel::ListInboxResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "list_inbox").entered();
+        let include_acked = p.get_include_acked();
+        // 0 = server default; a badge only needs `unacked`, not the rows.
+        let limit = match p.get_limit() {
+            0 => 50,
+            n => n as usize,
+        };
+        let principal_id = self.connection.borrow().principal.id;
+
+        let (items, unacked) = {
+            let db = self.kernel.kernel_db.lock();
+            let items = pry!(
+                db.list_inbox(principal_id, include_acked, limit)
+                    .map_err(|e| capnp::Error::failed(format!("list_inbox: {e}")))
+            );
+            let unacked = pry!(
+                db.count_unacked_inbox(principal_id)
+                    .map_err(|e| capnp::Error::failed(format!("list_inbox: {e}")))
+            );
+            (items, unacked)
+        };
+
+        let mut r = results.get();
+        r.set_unacked(unacked);
+        let mut list = r.init_items(items.len() as u32);
+        for (i, item) in items.iter().enumerate() {
+            set_inbox_item(list.reborrow().get(i as u32), item);
+        }
+        Promise::ok(())
+    }
+
+    fn ack_inbox(
+        self: Rc<Self>,
+        params: kernel::AckInboxParams,
+        mut results: kernel::AckInboxResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "ack_inbox").entered();
+        let ids: Vec<u64> = pry!(p.get_ids()).iter().collect();
+        let principal_id = self.connection.borrow().principal.id;
+
+        let (acked, unacked) = {
+            let db = self.kernel.kernel_db.lock();
+            let acked = pry!(
+                db.ack_inbox(principal_id, &ids)
+                    .map_err(|e| capnp::Error::failed(format!("ack_inbox: {e}")))
+            );
+            let unacked = pry!(
+                db.count_unacked_inbox(principal_id)
+                    .map_err(|e| capnp::Error::failed(format!("ack_inbox: {e}")))
+            );
+            (acked, unacked)
+        };
+
+        let mut r = results.get();
+        r.set_acked(acked as u32);
+        r.set_unacked(unacked);
+        Promise::ok(())
+    }
+
+    fn tail_block(
+        self: Rc<Self>,
+        params: kernel::TailBlockParams,
+        mut results: kernel::TailBlockResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "tail_block").entered();
+        let block_id = pry!(parse_block_id_from_reader(&pry!(p.get_block_id())));
+        let callback = pry!(p.get_callback());
+        let mut tail = pry!(
+            kaijutsu_kernel::block_tail::BlockTail::new(
+                self.kernel.documents.clone(),
+                block_id,
+                p.get_from_offset(),
+            )
+            .map_err(|e| capnp::Error::failed(format!("tail_block: {e}")))
+        );
+
+        // Dropping the call on the client cancels this future (and with it
+        // the FlowBus subscription).
+        Promise::from_future(async move {
+            use kaijutsu_kernel::block_tail::TailEvent;
+            while let Some(event) = tail.next().await {
+                match event {
+                    TailEvent::Append { offset, text } => {
+                        let mut req = callback.on_append_request();
+                        req.get().set_offset(offset);
+                        req.get().set_text(&text);
+                        req.send().promise.await?;
+                    }
+                    TailEvent::End { offset, status } => {
+                        let mut r = results.get();
+                        r.set_offset(offset);
+                        match status {
+                            Some(status) => r.set_status(status_to_capnp(status)),
+                            None => r.set_deleted(true),
+                        }
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// One block's events on their own bridge task, fed by the kernel's
+    /// per-block fan-out rather than the whole `block.*` bus. Like a peek it
+    /// is not a (principal, instance) subscription: it ends with the `sub`
+    /// cap, the connection, or the block.
+    fn subscribe_block(
+        self: Rc<Self>,
+        params: kernel::SubscribeBlockParams,
+        mut results: kernel::SubscribeBlockResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "subscribe_block").entered();
+        let block_id = pry!(parse_block_id_from_reader(&pry!(p.get_block_id())));
+        let callback = pry!(p.get_callback());
+        let conn_cancel = self.connection.borrow().cancel_token();
+        let documents = self.kernel.documents.clone();
+        let kernel_id = self.kernel.id;
+
+        // Subscribe before snapshotting, so nothing lands in between.
+        let mut block_sub = documents.subscribe_block(block_id);
+        let snapshot = pry!(
+            documents
+                .get_block_snapshot(block_id.context_id, &block_id)
+                .map_err(|e| capnp::Error::failed(format!("subscribe_block: {e}")))
+        );
+        let Some(snapshot) = snapshot else {
+            return Promise::err(capnp::Error::failed(format!(
+                "block {} not found",
+                block_id.to_key()
+            )));
+        };
+
+        let sub_cancel = CancellationToken::new();
+        {
+            let sub_cancel = sub_cancel.clone();
+            tokio::task::spawn_local(async move {
+                use kaijutsu_kernel::block_fanout::BlockSubEvent;
+                const CALLBACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+                let mut health = SubscriberHealth::new(SUBSCRIBER_FAILURE_STREAK_TIMEOUT);
+                loop {
+                    let flow = tokio::select! {
+                        _ = conn_cancel.cancelled() => break,
+                        _ = sub_cancel.cancelled() => break,
+                        event = block_sub.recv() => match event {
+                            Some(BlockSubEvent::Flow(flow)) => flow,
+                            // Fell behind: have the client re-read the block.
+                            Some(BlockSubEvent::Lagged(_)) => BlockFlow::SyncReset {
+                                context_id: block_id.context_id,
+                                generation: documents
+                                    .get(block_id.context_id)
+                                    .map(|doc| doc.sync_generation())
+                                    .unwrap_or_default(),
+                            },
+                            None => break,
+                        },
+                    };
+                    let deleted = matches!(flow, BlockFlow::Deleted { .. });
+                    let sent =
+                        forward_peek_flow(&callback, &flow, CALLBACK_TIMEOUT, kernel_id).await;
+                    if deleted {
+                        break;
+                    }
+                    if let Some(ok) = sent
+                        && !health.record(ok)
+                    {
+                        log::warn!(
+                            "block subscription {} stopping: callback failures continuous for over {:?}",
+                            block_id.to_key(),
+                            SUBSCRIBER_FAILURE_STREAK_TIMEOUT,
+                        );
+                        break;
+                    }
+                }
+                log::debug!("block subscription {} ended", block_id.to_key());
+            });
+        }
+
+        let mut r = results.get();
+        set_block_snapshot(&mut r.reborrow().init_block(), &snapshot);
+        r.set_sub(capnp_rpc::new_client(PeekImpl { cancel: sub_cancel }));
+        Promise::ok(())
+    }
+
+    /// Ranked full-text search over the kernel's search index.
+    fn search_blocks(
+        self: Rc<Self>,
+        params: kernel::SearchBlocksParams,
+        mut results: kernel::SearchBlocksResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "search_blocks");
+        let q = pry!(p.get_query());
+        // Empty bytes = no filter; anything else must be a whole id.
+        let context_bytes = pry!(q.get_context_id());
+        let context_id = if context_bytes.is_empty() {
+            None
+        } else {
+            Some(pry!(ContextId::try_from_slice(context_bytes).ok_or_else(
+                || capnp::Error::failed("invalid context ID".into())
+            )))
+        };
+        let author_bytes = pry!(q.get_author());
+        let author = if author_bytes.is_empty() {
+            None
+        } else {
+            Some(pry!(PrincipalId::try_from_slice(author_bytes).ok_or_else(
+                || capnp::Error::failed("invalid author ID".into())
+            )))
+        };
+        let query = kaijutsu_kernel::search_index::SearchQuery {
+            text: pry!(pry!(q.get_text()).to_str()).to_string(),
+            context_id,
+            kind: if q.get_has_kind() {
+                Some(block_kind_from_capnp(pry!(q.get_kind())))
+            } else {
+                None
+            },
+            role: if q.get_has_role() {
+                Some(role_from_capnp(pry!(q.get_role())))
+            } else {
+                None
+            },
+            author,
+            since: Some(q.get_since()).filter(|&t| t > 0),
+            until: Some(q.get_until()).filter(|&t| t > 0),
+            limit: q.get_limit() as usize,
+        };
+        let Some(index) = self.kernel.kj_dispatcher.search_index() else {
+            return Promise::err(capnp::Error::failed(
+                "search index is not available on this server".into(),
+            ));
+        };
+
+        Promise::from_future(
+            async move {
+                let hits = tokio::task::spawn_blocking(move || index.search(&query))
+                    .await
+                    .map_err(|e| capnp::Error::failed(format!("spawn_blocking: {}", e)))?
+                    .map_err(|e| capnp::Error::failed(format!("search: {}", e)))?;
+
+                let mut list = results.get().init_hits(hits.len() as u32);
+                for (i, hit) in hits.iter().enumerate() {
+                    let mut entry = list.reborrow().get(i as u32);
+                    set_block_id_builder(&mut entry.reborrow().init_block_id(), &hit.block_id);
+                    entry.set_kind(block_kind_to_capnp(hit.kind));
+                    entry.set_role(role_to_capnp(hit.role));
+                    entry.set_created_at(hit.created_at);
+                    entry.set_score(hit.score);
+                    entry.set_snippet(&hit.snippet);
+                }
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    /// Pruning deletes documents outright, so the caller's own context needs
+    /// the Admin authority, like `backupNow`. Naming some other context, even
+    /// an admin one, is refused.
+    fn scan_garbage(
+        self: Rc<Self>,
+        params: kernel::ScanGarbageParams,
+        mut results: kernel::ScanGarbageResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "scan_garbage");
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let prune = p.get_prune();
+        let caller_context = pry!(self.connection.borrow().require_context());
+        if context_id != caller_context {
+            return Promise::err(capnp::Error::failed(format!(
+                "scanGarbage denied: context {} is not your joined context",
+                context_id.short()
+            )));
+        }
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(
+            async move {
+                use kaijutsu_kernel::mcp::Capability;
+                check_caller_authority(&kernel, caller_context, Capability::Admin, "scanGarbage")
+                    .await?;
+                let report =
+                    tokio::task::spawn_blocking(move || crate::garbage::scan(&kernel, prune))
+                        .await
+                        .map_err(|e| capnp::Error::failed(format!("spawn_blocking: {}", e)))?
+                        .map_err(|e| capnp::Error::failed(format!("scan_garbage: {e}")))?;
+
+                let mut r = results.get().init_report();
+                r.set_pruned(report.pruned);
+                let mut docs = r
+                    .reborrow()
+                    .init_orphan_documents(report.orphan_documents.len() as u32);
+                for (i, id) in report.orphan_documents.iter().enumerate() {
+                    docs.set(i as u32, id.as_bytes());
+                }
+                let mut ghosts = r
+                    .reborrow()
+                    .init_ghost_contexts(report.ghost_contexts.len() as u32);
+                for (i, id) in report.ghost_contexts.iter().enumerate() {
+                    ghosts.set(i as u32, id.as_bytes());
+                }
+                let mut sessions = r
+                    .reborrow()
+                    .init_stale_sessions(report.stale_sessions.len() as u32);
+                for (i, (session, ctx)) in report.stale_sessions.iter().enumerate() {
+                    let mut entry = sessions.reborrow().get(i as u32);
+                    entry.set_session_id(session.as_bytes());
+                    entry.set_context_id(ctx.as_bytes());
+                }
+                let mut views = r.init_stale_client_views(report.stale_client_views.len() as u32);
+                for (i, (client_id, ctx)) in report.stale_client_views.iter().enumerate() {
+                    let mut entry = views.reborrow().get(i as u32);
+                    entry.set_client_id(client_id);
+                    entry.set_context_id(ctx.as_bytes());
+                }
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    fn set_seat_language(
+        self: Rc<Self>,
+        params: kernel::SetSeatLanguageParams,
+        mut results: kernel::SetSeatLanguageResults,
+    ) -> Promise<(), capnp::Error> {
+        use kaijutsu_kernel::translate::{TranslateError, normalize_locale};
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "set_seat_language").entered();
+        let raw = pry!(pry!(p.get_language()).to_str());
+        let language = if raw.trim().is_empty() {
+            None
+        } else {
+            Some(pry!(normalize_locale(raw).ok_or_else(|| {
+                capnp::Error::failed(TranslateError::InvalidLanguage(raw.to_string()).to_string())
+            })))
+        };
+        let Some(claim) = self.connection.borrow().seat else {
+            return Promise::err(capnp::Error::failed(
+                "no seat — call joinContext first".into(),
+            ));
+        };
+        let held = self
+            .kernel
+            .seats
+            .with_state(claim, |s| s.language = language.clone());
+        if held.is_none() {
+            return Promise::err(capnp::Error::failed(
+                "this connection no longer holds its seat".into(),
+            ));
+        }
+        results
+            .get()
+            .set_language(language.as_deref().unwrap_or_default());
+        Promise::ok(())
+    }
+
+    fn translate_block(
+        self: Rc<Self>,
+        params: kernel::TranslateBlockParams,
+        mut results: kernel::TranslateBlockResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let span = self.rpc_trace(p.get_trace(), "translate_block");
+        let block_id = pry!(parse_block_id_from_reader(&pry!(p.get_block_id())));
+        let requested = pry!(pry!(p.get_language()).to_str()).trim().to_owned();
+        let language = if requested.is_empty() {
+            let seat = self.connection.borrow().seat;
+            let preferred = seat.and_then(|claim| {
+                self.kernel
+                    .seats
+                    .with_state(claim, |s| s.language.clone())
+                    .flatten()
+            });
+            pry!(preferred.ok_or_else(|| {
+                capnp::Error::failed(
+                    "no language given and this seat has none set (setSeatLanguage)".into(),
+                )
+            }))
+        } else {
+            requested
+        };
+        let found = pry!(
+            self.kernel
+                .documents
+                .get_block_with_frontier(block_id.context_id, &block_id)
+                .map_err(|e| capnp::Error::failed(format!("translate_block: {e}")))
+        );
+        let Some((snapshot, frontier)) = found else {
+            return Promise::err(capnp::Error::failed(format!(
+                "block {} not found",
+                block_id.to_key()
+            )));
+        };
+        let translator = self.kernel.kernel.translator().clone();
+
+        Promise::from_future(
+            async move {
+                let translation = translator
+                    .translate(block_id, &frontier, &snapshot.content, &language)
+                    .await
+                    .map_err(|e| capnp::Error::failed(e.to_string()))?;
+                let mut r = results.get();
+                r.set_text(&translation.text);
+                r.set_language(&translation.language);
+                r.set_cached(translation.cached);
+                Ok(())
+            }
+            .instrument(span),
+        )
+    }
+
+    fn lock_block(
+        self: Rc<Self>,
+        params: kernel::LockBlockParams,
+        mut results: kernel::LockBlockResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "lock_block").entered();
+        let block_id = pry!(parse_block_id_from_reader(&pry!(p.get_block_id())));
+        let note = pry!(pry!(p.get_note()).to_str());
+        let ttl_secs = Some(p.get_ttl_secs()).filter(|&t| t > 0);
+        let (holder, holder_name) = {
+            let conn = self.connection.borrow();
+            (conn.principal.id, conn.principal.username.clone())
+        };
+        let lock = pry!(
+            self.kernel
+                .documents
+                .lock_block(&block_id, holder, &holder_name, ttl_secs, note)
+                .map_err(|e| capnp::Error::failed(format!("lock_block: {e}")))
+        );
+        set_block_lock(results.get().init_lock(), &lock);
+        Promise::ok(())
+    }
+
+    fn unlock_block(
+        self: Rc<Self>,
+        params: kernel::UnlockBlockParams,
+        mut results: kernel::UnlockBlockResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "unlock_block").entered();
+        let block_id = pry!(parse_block_id_from_reader(&pry!(p.get_block_id())));
+        let holder = self.connection.borrow().principal.id;
+        let released = pry!(
+            self.kernel
+                .documents
+                .unlock_block(&block_id, holder)
+                .map_err(|e| capnp::Error::failed(format!("unlock_block: {e}")))
+        );
+        results.get().set_released(released);
+        Promise::ok(())
+    }
+
+    fn list_block_locks(
+        self: Rc<Self>,
+        params: kernel::ListBlockLocksParams,
+        mut results: kernel::ListBlockLocksResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "list_block_locks").entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let documents = &self.kernel.documents;
+        let locks = documents.block_locks(context_id);
+        let mut r = results.get();
+        r.set_policy(documents.lock_policy(context_id).as_str());
+        let mut list = r.init_locks(locks.len() as u32);
+        for (i, lock) in locks.iter().enumerate() {
+            set_block_lock(list.reborrow().get(i as u32), lock);
+        }
+        Promise::ok(())
+    }
+
+    fn set_lock_policy(
+        self: Rc<Self>,
+        params: kernel::SetLockPolicyParams,
+        _results: kernel::SetLockPolicyResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "set_lock_policy").entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let policy: kaijutsu_types::LockPolicy = pry!(
+            pry!(pry!(p.get_policy()).to_str())
+                .parse()
+                .map_err(capnp::Error::failed)
+        );
+        let caller_context = pry!(self.connection.borrow().require_context());
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(async move {
+            use kaijutsu_kernel::mcp::Capability;
+            check_caller_authority(&kernel, caller_context, Capability::Admin, "setLockPolicy")
+                .await?;
+            kernel
+                .documents
+                .set_lock_policy(context_id, policy)
+                .map_err(|e| capnp::Error::failed(format!("set_lock_policy: {e}")))?;
+            tracing::info!(
+                context = %context_id.to_hex(),
+                policy = %policy,
+                "lock policy updated"
+            );
+            Ok(())
+        })
+    }
+
+    fn create_bookmark(
+        self: Rc<Self>,
+        params: kernel::CreateBookmarkParams,
+        mut results: kernel::CreateBookmarkResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "create_bookmark").entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let name = pry!(pry!(p.get_name()).to_str());
+        let created_by = self.connection.borrow().principal.id;
+        let bookmark = pry!(
+            kaijutsu_kernel::doc_bookmark::create(
+                &self.kernel.kernel_db,
+                &self.kernel.documents,
+                context_id,
+                name,
+                created_by,
+                kaijutsu_types::now_millis(),
+            )
+            .map_err(|e| capnp::Error::failed(format!("create_bookmark: {e}")))
+        );
+        set_doc_bookmark(results.get().init_bookmark(), &bookmark);
+        Promise::ok(())
+    }
+
+    fn list_bookmarks(
+        self: Rc<Self>,
+        params: kernel::ListBookmarksParams,
+        mut results: kernel::ListBookmarksResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "list_bookmarks").entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let bookmarks = pry!(
+            kaijutsu_kernel::doc_bookmark::list(&self.kernel.kernel_db, context_id)
+                .map_err(|e| capnp::Error::failed(format!("list_bookmarks: {e}")))
+        );
+        let mut list = results.get().init_bookmarks(bookmarks.len() as u32);
+        for (i, bookmark) in bookmarks.iter().enumerate() {
+            set_doc_bookmark(list.reborrow().get(i as u32), bookmark);
+        }
+        Promise::ok(())
+    }
+
+    fn list_consent_prompts(
+        self: Rc<Self>,
+        params: kernel::ListConsentPromptsParams,
+        mut results: kernel::ListConsentPromptsResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self
+            .rpc_trace(p.get_trace(), "list_consent_prompts")
+            .entered();
+        let principal_id = self.connection.borrow().principal.id;
+        let prompts = self
+            .kernel
+            .kernel
+            .consent_prompts()
+            .pending_for(principal_id);
+        let mut list = results.get().init_prompts(prompts.len() as u32);
+        for (i, prompt) in prompts.iter().enumerate() {
+            set_consent_prompt(list.reborrow().get(i as u32), prompt);
+        }
+        Promise::ok(())
+    }
+
+    fn consent_respond(
+        self: Rc<Self>,
+        params: kernel::ConsentRespondParams,
+        mut results: kernel::ConsentRespondResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "consent_respond").entered();
+        let decision_str = pry!(pry!(p.get_decision()).to_str());
+        let decision: kaijutsu_types::ConsentDecision = pry!(decision_str.parse().map_err(|_| {
+            capnp::Error::failed(format!(
+                "consent_respond: unknown decision '{decision_str}' \
+                 (expected approve, deny or approve_for_session)"
+            ))
+        }));
+        let (principal_id, session_id) = {
+            let conn = self.connection.borrow();
+            (conn.principal.id, conn.session_id)
+        };
+        let prompt = pry!(
+            self.kernel
+                .kernel
+                .consent_prompts()
+                .respond(p.get_prompt_id(), decision, principal_id, session_id)
+                .map_err(|e| capnp::Error::failed(format!("consent_respond: {e}")))
+        );
+        set_consent_prompt(results.get().init_prompt(), &prompt);
+        Promise::ok(())
+    }
+
+    fn get_context_timeline(
+        self: Rc<Self>,
+        params: kernel::GetContextTimelineParams,
+        mut results: kernel::GetContextTimelineResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self
+            .rpc_trace(p.get_trace(), "get_context_timeline")
+            .entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let timeline = pry!(
+            kaijutsu_kernel::context_timeline::build(
+                &self.kernel.documents,
+                context_id,
+                p.get_window_secs(),
+                p.get_buckets(),
+                kaijutsu_types::now_millis(),
+            )
+            .map_err(|e| capnp::Error::failed(format!("get_context_timeline: {e}")))
+        );
+        set_context_timeline(results.get().init_timeline(), &timeline);
+        Promise::ok(())
+    }
+
+    fn get_preferences(
+        self: Rc<Self>,
+        params: kernel::GetPreferencesParams,
+        mut results: kernel::GetPreferencesResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "get_preferences").entered();
+        let principal_id = self.connection.borrow().principal.id;
+        let prefs = pry!(
+            self.kernel
+                .kernel_db
+                .lock()
+                .get_prefs(principal_id)
+                .map_err(|e| capnp::Error::failed(format!("get_preferences: {e}")))
+        );
+        set_preferences(results.get().init_prefs(prefs.len() as u32), &prefs);
+        Promise::ok(())
+    }
+
+    fn set_preference(
+        self: Rc<Self>,
+        params: kernel::SetPreferenceParams,
+        mut results: kernel::SetPreferenceResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "set_preference").entered();
+        let key = pry!(pry!(p.get_key()).to_str()).to_owned();
+        let value = pry!(pry!(p.get_value()).to_str()).to_owned();
+        let value = (!p.get_clear()).then_some(value.as_str());
+        let principal_id = self.connection.borrow().principal.id;
+        let prefs = {
+            let db = self.kernel.kernel_db.lock();
+            pry!(
+                db.set_pref(principal_id, &key, value)
+                    .and_then(|()| db.get_prefs(principal_id))
+                    .map_err(|e| capnp::Error::failed(format!("set_preference: {e}")))
+            )
+        };
+        set_preferences(results.get().init_prefs(prefs.len() as u32), &prefs);
+        Promise::ok(())
+    }
+
+    fn list_consent_log(
+        self: Rc<Self>,
+        params: kernel::ListConsentLogParams,
+        mut results: kernel::ListConsentLogResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "list_consent_log").entered();
+        let after_seq = p.get_after_seq();
+        let limit = match p.get_limit() {
+            0 => 100,
+            n => n as usize,
+        };
+        let (entries, total, verification) = {
+            let db = self.kernel.kernel_db.lock();
+            let err = |e| capnp::Error::failed(format!("list_consent_log: {e}"));
+            let entries = pry!(db.list_consent(after_seq, limit).map_err(err));
+            let total = pry!(db.count_consent().map_err(err));
+            let verification = if p.get_verify() {
+                Some(pry!(db.verify_consent_log().map_err(err)))
+            } else {
+                None
+            };
+            (entries, total, verification)
+        };
+
+        let mut r = results.get();
+        r.set_total(total);
+        if let Some(v) = verification {
+            r.set_checked(v.checked);
+            if let Some((seq, reason)) = v.broken_at {
+                r.set_broken_at_seq(seq);
+                r.set_broken_reason(&reason);
+            }
+        }
+        let mut list = r.init_entries(entries.len() as u32);
+        for (i, entry) in entries.iter().enumerate() {
+            set_consent_entry(list.reborrow().get(i as u32), entry);
+        }
+        Promise::ok(())
+    }
+
+    fn get_sandbox_profile(
+        self: Rc<Self>,
+        params: kernel::GetSandboxProfileParams,
+        mut results: kernel::GetSandboxProfileResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self
+            .rpc_trace(p.get_trace(), "get_sandbox_profile")
+            .entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let profile = pry!(
+            self.kernel
+                .kernel_db
+                .lock()
+                .get_context_sandbox(context_id)
+                .map_err(|e| capnp::Error::failed(format!("get_sandbox_profile: {e}")))
+        );
+        let mut r = results.get();
+        r.set_set(profile.is_some());
+        if let Some(profile) = &profile {
+            set_sandbox_profile(r.init_profile(), profile);
+        }
+        Promise::ok(())
+    }
+
+    fn set_sandbox_profile(
+        self: Rc<Self>,
+        params: kernel::SetSandboxProfileParams,
+        mut results: kernel::SetSandboxProfileResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self
+            .rpc_trace(p.get_trace(), "set_sandbox_profile")
+            .entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let profile = if p.get_clear() {
+            None
+        } else {
+            Some(pry!(parse_sandbox_profile(pry!(p.get_profile()))))
+        };
+        let caller_context = pry!(self.connection.borrow().require_context());
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(async move {
+            use kaijutsu_kernel::mcp::Capability;
+            check_caller_authority(
+                &kernel,
+                caller_context,
+                Capability::Admin,
+                "setSandboxProfile",
+            )
+            .await?;
+            kernel
+                .kernel_db
+                .lock()
+                .set_context_sandbox(context_id, profile.as_ref())
+                .map_err(|e| capnp::Error::failed(format!("set_sandbox_profile: {e}")))?;
+            tracing::info!(
+                context = %context_id.to_hex(),
+                profile = %profile.as_ref().map_or_else(|| "none".to_string(), |p| p.to_string()),
+                "sandbox profile updated"
+            );
+            let mut r = results.get();
+            r.set_set(profile.is_some());
+            if let Some(profile) = &profile {
+                set_sandbox_profile(r.init_profile(), profile);
+            }
+            Ok(())
+        })
+    }
+
+    fn get_budget_status(
+        self: Rc<Self>,
+        params: kernel::GetBudgetStatusParams,
+        mut results: kernel::GetBudgetStatusResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "get_budget_status").entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let status = self
+            .kernel
+            .kernel
+            .broker()
+            .budgets()
+            .status(context_id, std::time::Instant::now());
+        set_budget_status(results.get().init_status(), &status);
+        Promise::ok(())
+    }
+
+    fn set_execution_budget(
+        self: Rc<Self>,
+        params: kernel::SetExecutionBudgetParams,
+        mut results: kernel::SetExecutionBudgetResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self
+            .rpc_trace(p.get_trace(), "set_execution_budget")
+            .entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let budget = if p.get_clear() {
+            None
+        } else {
+            Some(parse_execution_budget(pry!(p.get_budget())))
+        };
+        let caller_context = pry!(self.connection.borrow().require_context());
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(async move {
+            use kaijutsu_kernel::mcp::Capability;
+            check_caller_authority(
+                &kernel,
+                caller_context,
+                Capability::Admin,
+                "setExecutionBudget",
+            )
+            .await?;
+            let budgets = kernel.kernel.broker().budgets();
+            budgets
+                .set_budget(context_id, budget)
+                .map_err(|e| capnp::Error::failed(format!("set_execution_budget: {e}")))?;
+            tracing::info!(
+                context = %context_id.to_hex(),
+                budget = ?budget,
+                "execution budget updated"
+            );
+            let status = budgets.status(context_id, std::time::Instant::now());
+            set_budget_status(results.get().init_status(), &status);
+            Ok(())
+        })
+    }
+
+    /// Engaging the halt is open to every caller — stopping a runaway agent
+    /// must never wait on authority. Releasing it is not: it needs the Admin
+    /// authority in the caller's own context, so a halted agent can't undo
+    /// the stop by naming some admin context.
+    fn set_tool_halt(
+        self: Rc<Self>,
+        params: kernel::SetToolHaltParams,
+        mut results: kernel::SetToolHaltResults,
+    ) -> Promise<(), capnp::Error> {
+        let p = pry!(params.get());
+        let _span = self.rpc_trace(p.get_trace(), "set_tool_halt").entered();
+        let context_id = pry!(
+            ContextId::try_from_slice(pry!(p.get_context_id())).ok_or_else(|| {
+                capnp::Error::failed("invalid context ID (expected 16 bytes)".into())
+            })
+        );
+        let halted = p.get_halted();
+        let reason = pry!(pry!(p.get_reason()).to_str()).to_owned();
+        let (principal, caller_context) = {
+            let conn = self.connection.borrow();
+            (conn.principal.id, conn.require_context())
+        };
+        let kernel = self.kernel.clone();
+
+        Promise::from_future(async move {
+            let broker = kernel.kernel.broker();
+            if halted {
+                let reason = if reason.trim().is_empty() {
+                    "halted by operator".to_string()
+                } else {
+                    reason
+                };
+                broker.budgets().halt(reason, principal);
+            } else {
+                use kaijutsu_kernel::mcp::Capability;
+                check_caller_authority(&kernel, caller_context?, Capability::Admin, "setToolHalt")
+                    .await?;
+                broker.budgets().resume();
+            }
+            let status = broker
+                .budgets()
+                .status(context_id, std::time::Instant::now());
+            set_budget_status(results.get().init_status(), &status);
+            Ok(())
+        })
+    }
+
+    /// Push channel: samples the kernel's `ContextActivityTracker` on a
+    /// per-connection timer and streams the non-idle contexts to the
+    /// subscriber. Same shape as `subscribe_vfs_activity` — a timer, not a
+    /// FlowBus bridge; the tracker itself is fed once per kernel by
+    /// `context_activity::spawn_aggregator`. Each push is a complete reading,
+    /// so "already delivered" is just the last reading sent: an unchanged
+    /// tick sends nothing, and a failed send is retried by the next tick.
+    /// Each tick also checks the kernel's `ContextHealthTracker` and sends
+    /// `onUnhealthy` for a context only when it has a finding not yet
+    /// delivered on this connection.
+    fn subscribe_activity(
+        self: Rc<Self>,
+        params: kernel::SubscribeActivityParams,
+        _results: kernel::SubscribeActivityResults,
+    ) -> Promise<(), capnp::Error> {
+        let _span = tracing::info_span!("rpc", method = "subscribe_activity").entered();
+        let p = pry!(params.get());
+        let callback = pry!(p.get_callback());
+        let requested_ms = p.get_interval_ms();
+        let interval_ms = if requested_ms == 0 {
+            ACTIVITY_DEFAULT_INTERVAL_MS
+        } else {
+            requested_ms.max(ACTIVITY_MIN_INTERVAL_MS)
+        };
+
+        let tracker = self.kernel.kernel.context_activity().clone();
+        let watchdog = self.kernel.kernel.context_health().clone();
+        let kernel_id = self.kernel.id;
+        let conn_cancel = self.connection.borrow().cancel_token();
+
+        tokio::task::spawn_local(async move {
+            let mut last_sent: Option<Vec<kaijutsu_types::ContextActivity>> = None;
+            let mut unhealthy_sent: HashMap<ContextId, Vec<kaijutsu_types::HealthIssue>> =
+                HashMap::new();
+            let mut health = SubscriberHealth::new(SUBSCRIBER_FAILURE_STREAK_TIMEOUT);
+            const CALLBACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+            let mut ticker = tokio::time::interval(std::time::Duration::from_millis(interval_ms as u64));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            log::debug!(
+                "Started activity subscription for kernel {} (interval {}ms)",
+                kernel_id,
+                interval_ms
+            );
+
+            loop {
+                tokio::select! {
+                    _ = conn_cancel.cancelled() => {
+                        log::debug!("activity bridge cancelled with connection");
+                        break;
+                    }
+                    _ = ticker.tick() => {
+                        let now = std::time::Instant::now();
+                        let findings = watchdog.lock().check(now);
+                        unhealthy_sent.retain(|ctx, _| findings.iter().any(|h| h.context_id == *ctx));
+                        for health in findings {
+                            let sent = unhealthy_sent.get(&health.context_id);
+                            let is_new = health.issues.iter().any(|issue| {
+                                !sent.is_some_and(|s| s.iter().any(|old| old.same_finding(issue)))
+                            });
+                            if !is_new {
+                                continue;
+                            }
+                            let mut req = callback.on_unhealthy_request();
+                            req.get().set_context_id(health.context_id.as_bytes());
+                            set_health_issues(
+                                req.get().init_issues(health.issues.len() as u32),
+                                &health.issues,
+                            );
+                            if await_editor_callback(req.send().promise, CALLBACK_TIMEOUT, kernel_id).await {
+                                log::info!(
+                                    "context {} unhealthy: {}",
+                                    health.context_id.short(),
+                                    health.issues.iter().map(|i| i.code()).collect::<Vec<_>>().join(", ")
+                                );
+                                unhealthy_sent.insert(health.context_id, health.issues);
+                            }
+                        }
+
+                        let reading = tracker.lock().snapshot(now);
+                        if last_sent.as_ref() == Some(&reading) {
+                            continue;
+                        }
+
+                        let mut req = callback.on_activity_request();
+                        {
+                            let mut contexts = req.get().init_contexts(reading.len() as u32);
+                            for (i, activity) in reading.iter().enumerate() {
+                                let mut entry = contexts.reborrow().get(i as u32);
+                                entry.set_context_id(activity.context_id.as_bytes());
+                                entry.set_ops_per_minute(activity.ops_per_minute);
+                                entry.set_active_streams(activity.active_streams);
+                                entry.set_errors(activity.errors);
+                            }
+                        }
+                        req.get().set_window_secs(kaijutsu_types::ACTIVITY_WINDOW_SECS);
+
+                        let success = await_editor_callback(req.send().promise, CALLBACK_TIMEOUT, kernel_id).await;
+                        if success {
+                            last_sent = Some(reading);
+                        }
+
+                        if !health.record(success) {
+                            log::warn!(
+                                "activity bridge for kernel {} stopping: callback \
+                                 failures continuous for over {:?} — reaping subscriber",
+                                kernel_id,
+                                SUBSCRIBER_FAILURE_STREAK_TIMEOUT,
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+            log::debug!("activity bridge task for kernel {} ended", kernel_id);
+        });
+        Promise::ok(())
+    }
+}
+
+// ============================================================================
+// Shell Value Conversion Helpers
+// ============================================================================
+
+/// Convert kaish `ast::Value` → Cap'n Proto `ShellValue` builder.
+fn value_to_shell_value(mut builder: shell_value::Builder<'_>, value: &kaish_kernel::ast::Value) {
+    use kaish_kernel::ast::Value;
+    match value {
+        Value::Null => builder.set_null(()),
+        Value::Bool(b) => builder.set_bool(*b),
+        Value::Int(i) => builder.set_int(*i),
+        Value::Float(f) => builder.set_float(*f),
+        Value::String(s) => builder.set_string(s),
+        Value::Json(j) => builder.set_json(serde_json::to_string(j).unwrap_or_default()),
+        Value::Bytes(b) => builder.set_bytes(b),
+    }
+}
+
+/// Convert Cap'n Proto `ShellValue` reader → kaish `ast::Value`.
+fn shell_value_to_value(
+    reader: shell_value::Reader<'_>,
+) -> Result<kaish_kernel::ast::Value, capnp::Error> {
+    use kaish_kernel::ast::Value;
     match reader.which()? {
         shell_value::Null(()) => Ok(Value::Null),
         shell_value::Bool(b) => Ok(Value::Bool(b)),
@@ -6981,6 +9904,16 @@ fn build_block_metadata(
         builder.set_has_stderr(true);
         builder.set_stderr(stderr);
     }
+    if let Some(ref language) = meta.language {
+        builder.set_language(language);
+    }
+    if let Some(ref label) = meta.label {
+        builder.set_label(label);
+    }
+    if let Some(reflow) = meta.reflow {
+        builder.set_reflow_width(reflow.width);
+        builder.set_reflow_preserve_newlines(reflow.preserve_newlines);
+    }
 }
 
 /// Fill a Cap'n Proto `RenderCue` builder from the typed cue (docs/pcm.md "The
@@ -7011,6 +9944,366 @@ fn set_beat_ref(mut builder: crate::kaijutsu_capnp::beat_ref::Builder<'_>, beat_
     builder.set_epoch_ns(beat_ref.epoch_ns);
 }
 
+/// The seat a joining connection ends up in (joinContext).
+struct JoinedSeat {
+    token: String,
+    resumed: bool,
+}
+
+/// Resume the seat `resume_token` names, or keep (opening on first join)
+/// the connection's own. A resumed seat's session replaces the
+/// connection's; a token that doesn't resume is logged and
+/// ignored.
+fn take_seat(
+    kernel: &SharedKernel,
+    connection: &Rc<RefCell<ConnectionState>>,
+    resume_token: &str,
+) -> JoinedSeat {
+    let seats = &kernel.seats;
+    let mut conn = connection.borrow_mut();
+    let principal_id = conn.principal.id;
+    if !resume_token.is_empty() {
+        match seats.resume(resume_token, principal_id, std::time::Instant::now()) {
+            Ok(resumed) => {
+                if let Some(own) = conn.seat.take()
+                    && own != resumed.claim
+                {
+                    seats.close(own);
+                    conn.session_contexts.remove(&own.session_id);
+                }
+                log::info!(
+                    "join_context: {} resumed session {}",
+                    conn.principal.username,
+                    resumed.claim.session_id.short()
+                );
+                conn.session_id = resumed.claim.session_id;
+                conn.seat = Some(resumed.claim);
+                return JoinedSeat {
+                    token: resumed.token,
+                    resumed: true,
+                };
+            }
+            Err(e) => log::info!(
+                "join_context: seat resume for {} refused: {e}",
+                conn.principal.username
+            ),
+        }
+    }
+    if let Some(claim) = conn.seat {
+        if let Some(token) = seats.token(claim) {
+            return JoinedSeat {
+                token,
+                resumed: false,
+            };
+        }
+        // A reconnect took this seat over; its session isn't ours anymore.
+        conn.session_id = SessionId::new();
+    }
+    let (claim, token) = seats.open(principal_id, conn.session_id);
+    conn.seat = Some(claim);
+    JoinedSeat {
+        token,
+        resumed: false,
+    }
+}
+
+/// Fill a Cap'n Proto `InboxItem` builder. Absent optional ids go out as
+/// empty Data; an unacked item carries `ackedAt = 0`.
+fn set_inbox_item(
+    mut builder: crate::kaijutsu_capnp::inbox_item::Builder<'_>,
+    item: &kaijutsu_types::InboxItem,
+) {
+    builder.set_id(item.id);
+    builder.set_recipient(item.recipient.as_bytes());
+    builder.set_kind(item.kind.as_str());
+    if let Some(ctx) = item.context_id {
+        builder.set_context_id(ctx.as_bytes());
+    }
+    if let Some(sender) = item.sender {
+        builder.set_sender(sender.as_bytes());
+    }
+    builder.set_summary(&item.summary);
+    builder.set_created_at(item.created_at);
+    builder.set_acked_at(item.acked_at.unwrap_or(0));
+}
+
+/// Fill a Cap'n Proto `BlockLock` builder.
+fn set_block_lock(
+    mut builder: crate::kaijutsu_capnp::block_lock::Builder<'_>,
+    lock: &kaijutsu_types::BlockLock,
+) {
+    set_block_id_builder(&mut builder.reborrow().init_block_id(), &lock.block_id);
+    builder.set_holder(lock.holder.as_bytes());
+    builder.set_holder_name(&lock.holder_name);
+    builder.set_note(&lock.note);
+    builder.set_acquired_at(lock.acquired_at);
+    builder.set_expires_at(lock.expires_at);
+}
+
+/// Fill a Cap'n Proto `ContextShare` builder.
+fn set_context_share(
+    mut builder: crate::kaijutsu_capnp::context_share::Builder<'_>,
+    share: &kaijutsu_types::ContextShare,
+) {
+    builder.set_token(&share.token);
+    builder.set_context_id(share.context_id.as_bytes());
+    builder.set_label(&share.label);
+    builder.set_version(share.version);
+    builder.set_block_count(share.block_count);
+    builder.set_created_by(share.created_by.as_bytes());
+    builder.set_created_at(share.created_at);
+    builder.set_expires_at(share.expires_at);
+}
+
+/// Fill a Cap'n Proto `DocBookmark` builder.
+fn set_doc_bookmark(
+    mut builder: crate::kaijutsu_capnp::doc_bookmark::Builder<'_>,
+    bookmark: &kaijutsu_types::DocBookmark,
+) {
+    builder.set_context_id(bookmark.context_id.as_bytes());
+    builder.set_name(&bookmark.name);
+    builder.set_version(bookmark.version);
+    builder.set_block_count(bookmark.block_count);
+    builder.set_created_by(bookmark.created_by.as_bytes());
+    builder.set_created_at(bookmark.created_at);
+}
+
+/// Fill a Cap'n Proto `PushViolation` builder.
+fn set_push_violation(
+    mut builder: crate::kaijutsu_capnp::push_violation::Builder<'_>,
+    violation: &kaijutsu_types::PushViolation,
+) {
+    set_block_id_builder(&mut builder.reborrow().init_block_id(), &violation.block_id);
+    builder.set_rule(violation.rule.as_str());
+    builder.set_detail(&violation.detail);
+}
+
+fn set_config_apply_report(
+    mut builder: crate::kaijutsu_capnp::config_apply_report::Builder<'_>,
+    report: &kaijutsu_types::ConfigApplyReport,
+) {
+    builder.set_path(&report.path);
+    {
+        let mut list = builder
+            .reborrow()
+            .init_applied(report.applied.len() as u32);
+        for (i, change) in report.applied.iter().enumerate() {
+            let mut c = list.reborrow().get(i as u32);
+            c.set_item(&change.item);
+            c.set_detail(&change.detail);
+        }
+    }
+    let mut list = builder.init_rejected(report.rejected.len() as u32);
+    for (i, change) in report.rejected.iter().enumerate() {
+        let mut c = list.reborrow().get(i as u32);
+        c.set_item(&change.item);
+        c.set_detail(&change.detail);
+    }
+}
+
+fn set_consent_entry(
+    mut builder: crate::kaijutsu_capnp::consent_entry::Builder<'_>,
+    entry: &kaijutsu_types::ConsentEntry,
+) {
+    builder.set_seq(entry.seq);
+    builder.set_at(entry.at);
+    builder.set_principal_id(entry.principal_id.as_bytes());
+    if let Some(ctx) = entry.context_id {
+        builder.set_context_id(ctx.as_bytes());
+    }
+    builder.set_action(&entry.action);
+    builder.set_params_hash(&entry.params_hash);
+    builder.set_verdict(entry.verdict.as_str());
+    builder.set_basis(&entry.basis);
+    builder.set_mode(entry.mode.as_str());
+    builder.set_prev_hash(&entry.prev_hash);
+    builder.set_hash(&entry.hash);
+    builder.set_signature(&entry.signature);
+}
+
+fn set_consent_prompt(
+    mut builder: crate::kaijutsu_capnp::consent_prompt::Builder<'_>,
+    prompt: &kaijutsu_types::ConsentPrompt,
+) {
+    builder.set_id(prompt.id);
+    builder.set_context_id(prompt.context_id.as_bytes());
+    builder.set_principal_id(prompt.principal_id.as_bytes());
+    builder.set_tool(&prompt.tool);
+    builder.set_preview(&prompt.preview);
+    builder.set_requested_at(prompt.requested_at);
+    builder.set_expires_at(prompt.expires_at);
+}
+
+fn set_context_timeline(
+    mut builder: crate::kaijutsu_capnp::context_timeline::Builder<'_>,
+    timeline: &kaijutsu_types::ContextTimeline,
+) {
+    builder.set_context_id(timeline.context_id.as_bytes());
+    builder.set_start(timeline.start);
+    builder.set_bucket_ms(timeline.bucket_ms);
+    let mut list = builder.init_buckets(timeline.buckets.len() as u32);
+    for (i, bucket) in timeline.buckets.iter().enumerate() {
+        let mut b = list.reborrow().get(i as u32);
+        b.set_blocks(bucket.blocks);
+        b.set_edits(bucket.edits);
+        b.set_tool_runs(bucket.tool_runs);
+        b.set_drifts(bucket.drifts);
+        b.set_errors(bucket.errors);
+    }
+}
+
+fn set_preferences(
+    mut list: capnp::struct_list::Builder<'_, crate::kaijutsu_capnp::preference::Owned>,
+    prefs: &kaijutsu_types::Preferences,
+) {
+    for (i, (key, value)) in prefs.iter().enumerate() {
+        let mut entry = list.reborrow().get(i as u32);
+        entry.set_key(key);
+        entry.set_value(value);
+    }
+}
+
+fn set_sandbox_profile(
+    mut builder: crate::kaijutsu_capnp::sandbox_profile::Builder<'_>,
+    profile: &kaijutsu_types::SandboxProfile,
+) {
+    let mut binaries = builder.reborrow().init_binaries(profile.binaries.len() as u32);
+    for (i, binary) in profile.binaries.iter().enumerate() {
+        binaries.set(i as u32, binary.as_str());
+    }
+    builder.set_fs_from_mounts(profile.fs_from_mounts);
+    builder.set_network(profile.network);
+    let limits = &profile.limits;
+    builder.set_cpu_secs(limits.cpu_secs.unwrap_or(0));
+    builder.set_memory_bytes(limits.memory_bytes.unwrap_or(0));
+    builder.set_file_size_bytes(limits.file_size_bytes.unwrap_or(0));
+    builder.set_open_files(limits.open_files.unwrap_or(0));
+    builder.set_processes(limits.processes.unwrap_or(0));
+}
+
+fn parse_sandbox_profile(
+    reader: crate::kaijutsu_capnp::sandbox_profile::Reader<'_>,
+) -> Result<kaijutsu_types::SandboxProfile, capnp::Error> {
+    let limit = |v: u64| (v != 0).then_some(v);
+    Ok(kaijutsu_types::SandboxProfile {
+        binaries: reader
+            .get_binaries()?
+            .iter()
+            .map(|b| Ok(b?.to_str()?.to_owned()))
+            .collect::<Result<_, capnp::Error>>()?,
+        fs_from_mounts: reader.get_fs_from_mounts(),
+        network: reader.get_network(),
+        limits: kaijutsu_types::SandboxLimits {
+            cpu_secs: limit(reader.get_cpu_secs()),
+            memory_bytes: limit(reader.get_memory_bytes()),
+            file_size_bytes: limit(reader.get_file_size_bytes()),
+            open_files: limit(reader.get_open_files()),
+            processes: limit(reader.get_processes()),
+        },
+    })
+}
+
+fn set_budget_status(
+    mut builder: crate::kaijutsu_capnp::budget_status::Builder<'_>,
+    status: &kaijutsu_types::BudgetStatus,
+) {
+    builder.set_context_id(status.context_id.as_bytes());
+    builder.set_has_budget(status.budget.is_some());
+    let budget = status.budget.unwrap_or_default();
+    let mut b = builder.reborrow().init_budget();
+    b.set_max_tool_calls_per_hour(budget.max_tool_calls_per_hour.unwrap_or(0));
+    b.set_max_shell_secs_per_hour(budget.max_shell_secs_per_hour.unwrap_or(0));
+    builder.set_tool_calls(status.tool_calls);
+    builder.set_shell_secs(status.shell_secs);
+    builder.set_window_secs(kaijutsu_types::BUDGET_WINDOW_SECS);
+    builder.set_halted(status.halt.is_some());
+    if let Some(halt) = &status.halt {
+        builder.set_halt_reason(&halt.reason);
+        builder.set_halted_by(halt.by.as_bytes());
+        builder.set_halted_at(halt.at);
+    }
+}
+
+fn parse_execution_budget(
+    reader: crate::kaijutsu_capnp::execution_budget::Reader<'_>,
+) -> kaijutsu_types::ExecutionBudget {
+    let calls = reader.get_max_tool_calls_per_hour();
+    let shell_secs = reader.get_max_shell_secs_per_hour();
+    kaijutsu_types::ExecutionBudget {
+        max_tool_calls_per_hour: (calls != 0).then_some(calls),
+        max_shell_secs_per_hour: (shell_secs != 0).then_some(shell_secs),
+    }
+}
+
+fn set_llm_params(
+    mut builder: crate::kaijutsu_capnp::llm_params::Builder<'_>,
+    params: &kaijutsu_types::LlmParams,
+) {
+    builder.set_has_temperature(params.temperature.is_some());
+    builder.set_temperature(params.temperature.unwrap_or(0.0));
+    builder.set_max_tokens(params.max_tokens.unwrap_or(0));
+    builder.set_has_top_p(params.top_p.is_some());
+    builder.set_top_p(params.top_p.unwrap_or(0.0));
+    if let Some(choice) = &params.tool_choice {
+        builder.set_tool_choice(&choice.to_string());
+    }
+}
+
+/// Read an `LlmParams` struct; fails on an unparseable `toolChoice`. Range
+/// checks are left to `LlmParams::validate`.
+fn parse_llm_params(
+    reader: crate::kaijutsu_capnp::llm_params::Reader<'_>,
+) -> Result<kaijutsu_types::LlmParams, capnp::Error> {
+    let tool_choice = reader.get_tool_choice()?.to_str()?;
+    let max_tokens = reader.get_max_tokens();
+    Ok(kaijutsu_types::LlmParams {
+        temperature: reader
+            .get_has_temperature()
+            .then(|| reader.get_temperature()),
+        max_tokens: (max_tokens != 0).then_some(max_tokens),
+        top_p: reader.get_has_top_p().then(|| reader.get_top_p()),
+        tool_choice: if tool_choice.is_empty() {
+            None
+        } else {
+            Some(tool_choice.parse().map_err(capnp::Error::failed)?)
+        },
+    })
+}
+
+fn parse_broadcast_target(
+    reader: crate::kaijutsu_capnp::broadcast_target::Reader<'_>,
+) -> Result<kaijutsu_types::BroadcastTarget, capnp::Error> {
+    let context_id = ContextId::try_from_slice(reader.get_context_id()?)
+        .ok_or_else(|| capnp::Error::failed("invalid target context ID".into()))?;
+    let model = reader.get_model()?.to_str()?;
+    Ok(kaijutsu_types::BroadcastTarget {
+        context_id,
+        model: (!model.is_empty()).then(|| model.to_string()),
+    })
+}
+
+fn set_broadcast_status(
+    mut builder: crate::kaijutsu_capnp::broadcast_status::Builder<'_>,
+    status: &kaijutsu_types::BroadcastStatus,
+) {
+    builder.set_id(status.id);
+    builder.set_comparison_context_id(status.comparison_context_id.as_bytes());
+    builder.set_prompt(&status.prompt);
+    builder.set_started_at(status.started_at);
+    let mut targets = builder.init_targets(status.targets.len() as u32);
+    for (i, target) in status.targets.iter().enumerate() {
+        let mut t = targets.reborrow().get(i as u32);
+        t.set_context_id(target.context_id.as_bytes());
+        t.set_model(target.model.as_deref().unwrap_or(""));
+        t.set_state(target.state.as_str());
+        if let Some(block) = &target.response_block {
+            t.set_has_response(true);
+            set_block_id_builder(&mut t.reborrow().init_response_block(), block);
+        }
+        t.set_error(target.error.as_deref().unwrap_or(""));
+    }
+}
+
 /// The FlowBus topic pattern a **filtered** client block-subscription listens on.
 ///
 /// This pattern must be a *superset* of everything `BlockFlow::matches_filter`
@@ -7031,6 +10324,7 @@ fn filtered_subscribe_pattern(event_types: &[kaijutsu_types::BlockFlowKind]) ->
     match event_types {
         [kaijutsu_types::BlockFlowKind::RenderCue] => "block.render_cue",
         [kaijutsu_types::BlockFlowKind::BeatSync] => "block.beat_sync",
+        [kaijutsu_types::BlockFlowKind::InboxPosted] => "block.inbox",
         _ => "block.*",
     }
 }
@@ -7081,7 +10375,10 @@ fn value_to_env_string(value: &kaish_kernel::ast::Value) -> String {
 /// Read a context's durable cwd from L1 (`context_shell.cwd`). Returns `None`
 /// when unset or unreadable — callers apply their own default (the interactive
 /// shell lands in `/docs`; tool-context resolution defaults to `/`).
-fn context_cwd(kernel: &SharedKernelState, context_id: ContextId) -> Option<std::path::PathBuf> {
+pub(crate) fn context_cwd(
+    kernel: &SharedKernelState,
+    context_id: ContextId,
+) -> Option<std::path::PathBuf> {
     kernel
         .kernel_db
         .lock()
@@ -7222,18 +10519,102 @@ fn persist_shell_state(
     }
 }
 
+/// A re-run of an earlier shell command block (`shellRerun`).
+struct ShellRerun {
+    of: kaijutsu_crdt::BlockId,
+    /// How the original ran. `None` for a block from before shell metadata
+    /// was recorded; it re-runs in the shell's current state.
+    recorded: Option<kaijutsu_types::ShellCommand>,
+}
+
+/// Put a freshly materialized shell back in the state a recorded command ran
+/// in: its cwd, and exactly its env overrides (exporting the recorded values,
+/// unsetting context env the command didn't have). Applied before the
+/// command's `state_before` snapshot, so none of it is persisted to L1.
+async fn replay_shell_state(
+    kaish: &EmbeddedKaish,
+    recorded: &kaijutsu_types::ShellCommand,
+    kernel_db: &Arc<parking_lot::Mutex<KernelDb>>,
+    context_id: ContextId,
+) -> Result<(), capnp::Error> {
+    if !kaish
+        .try_set_cwd(std::path::PathBuf::from(&recorded.cwd))
+        .await
+    {
+        return Err(capnp::Error::failed(format!(
+            "cannot re-run: working directory {} no longer exists",
+            recorded.cwd
+        )));
+    }
+    let current = kernel_db
+        .lock()
+        .get_context_env(context_id)
+        .unwrap_or_default();
+    let mut lines: Vec<String> = current
+        .iter()
+        .filter(|var| !recorded.env.contains_key(&var.key))
+        .map(|var| format!("unset {}", var.key))
+        .collect();
+    for (key, value) in &recorded.env {
+        // Shell-escape value to avoid injection, as apply_context_config does.
+        lines.push(format!("export {}='{}'", key, value.replace('\'', "'\\''")));
+    }
+    for line in lines {
+        kaish
+            .execute_with_options(&line, kaish_kernel::ExecuteOptions::default())
+            .await
+            .map_err(|e| capnp::Error::failed(format!("cannot re-run: {line}: {e}")))?;
+    }
+    Ok(())
+}
+
 async fn execute_shell_command(
     code: &str,
     context_id: ContextId,
     user_principal_id: PrincipalId,
     user_initiated: bool,
+    rerun: Option<ShellRerun>,
     kernel: &SharedKernelState,
     connection: &Rc<RefCell<ConnectionState>>,
 ) -> Result<kaijutsu_crdt::BlockId, capnp::Error> {
+    // The kill switch stops every shell command; the context's shell budget
+    // meters only commands an agent issued, never ones a person typed.
+    let budgets = kernel.kernel.broker().budgets().clone();
+    let admitted = if user_initiated {
+        budgets.check_halt()
+    } else {
+        budgets.admit_shell(context_id, std::time::Instant::now())
+    };
+    admitted.map_err(|e| capnp::Error::failed(e.to_string()))?;
+
     // Materialize a single-use context shell seeded from L1 (durable env + cwd).
     // No caching: transient scope evaporates when this instance drops, so the
     // context's durable state only ever changes through `kj context set`.
     let kaish = materialize_context_shell(kernel, connection).await?;
+    let recorded = rerun.as_ref().and_then(|r| r.recorded.as_ref());
+    if let Some(recorded) = recorded {
+        replay_shell_state(&kaish, recorded, &kernel.kernel_db, context_id).await?;
+    }
+
+    // What the command runs with, recorded on its block for audit and re-run.
+    let env = match recorded {
+        Some(recorded) => recorded.env.clone(),
+        None => kernel
+            .kernel_db
+            .lock()
+            .get_context_env(context_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|var| (var.key, var.value))
+            .collect(),
+    };
+    let shell_command = kaijutsu_types::ShellCommand {
+        argv: kaijutsu_types::parse_argv(code),
+        cwd: kaish.cwd().await.to_string_lossy().into_owned(),
+        env,
+        requested_by: user_principal_id,
+        rerun_of: rerun.as_ref().map(|r| r.of),
+    };
 
     let documents = kernel.documents.clone();
     let kernel_arc = kernel.kernel.clone();
@@ -7261,17 +10642,7 @@ async fn execute_shell_command(
         None
     };
     let command_block_id = documents
-        .insert_tool_call_as(
-            context_id,
-            None,
-            last_block.as_ref(),
-            "shell",
-            serde_json::json!({"code": code}),
-            Some(TypesToolKind::Shell),
-            Some(user_principal_id),
-            None,
-            role,
-        )
+        .insert_shell_command_as(context_id, last_block.as_ref(), code, shell_command, role)
         .map_err(|e| capnp::Error::failed(format!("failed to insert shell command: {}", e)))?;
 
     // Create ToolResult block (empty, will be filled by execution — system-authored)
@@ -7329,10 +10700,15 @@ async fn execute_shell_command(
             "shell_execute: executing code via EmbeddedKaish: {:?}",
             code
         );
-        match kaish
+        let started = std::time::Instant::now();
+        let outcome = kaish
             .execute_with_options(&code, kaish_kernel::ExecuteOptions::default())
-            .await
-        {
+            .await;
+        if !user_initiated {
+            let finished = std::time::Instant::now();
+            budgets.record_shell(context_id, finished, finished - started);
+        }
+        match outcome {
             Ok(result) => {
                 log::info!(
                     "shell_execute: kaish returned code={} original_code={:?} did_spill={} out_len={} err_len={}",
@@ -7391,6 +10767,20 @@ async fn execute_shell_command(
                     }
                 }
 
+                // Tag output whose syntax is recognizable — `cat main.rs`, a
+                // JSON document, a diff — for highlighting and Markdown export.
+                if result.content_type.is_none()
+                    && let Some(lang) =
+                        kaijutsu_types::language::language_for_command_output(&code, &out_text)
+                    && let Err(e) = documents_clone.set_language(
+                        context_id,
+                        &output_block_id_clone,
+                        Some(lang.to_string()),
+                    )
+                {
+                    log::error!("Failed to set language: {}", e);
+                }
+
                 // Read baggage: mark blocks ephemeral if tool signaled it
                 if result
                     .baggage
@@ -7520,6 +10910,40 @@ fn parse_block_id_from_reader(
 }
 
 /// Set BlockId fields on a Cap'n Proto builder (binary format).
+/// Fill a capnp `HealthIssue` list from the watchdog's findings.
+fn set_health_issues(
+    mut list: capnp::struct_list::Builder<'_, crate::kaijutsu_capnp::health_issue::Owned>,
+    issues: &[kaijutsu_types::HealthIssue],
+) {
+    use kaijutsu_types::HealthIssue;
+    for (i, issue) in issues.iter().enumerate() {
+        let entry = list.reborrow().get(i as u32);
+        match issue {
+            HealthIssue::StuckRunning {
+                block_id,
+                running_secs,
+            } => {
+                let mut g = entry.init_stuck_running();
+                set_block_id_builder(&mut g.reborrow().init_block_id(), block_id);
+                g.set_seconds(*running_secs);
+            }
+            HealthIssue::SilentStream {
+                block_id,
+                silent_secs,
+            } => {
+                let mut g = entry.init_silent_stream();
+                set_block_id_builder(&mut g.reborrow().init_block_id(), block_id);
+                g.set_seconds(*silent_secs);
+            }
+            HealthIssue::ToolFailures { count, last_block } => {
+                let mut g = entry.init_tool_failures();
+                set_block_id_builder(&mut g.reborrow().init_last_block(), last_block);
+                g.set_count(*count);
+            }
+        }
+    }
+}
+
 fn set_block_id_builder(
     builder: &mut crate::kaijutsu_capnp::block_id::Builder,
     block_id: &kaijutsu_crdt::BlockId,
@@ -7578,6 +11002,7 @@ fn set_block_snapshot(
         kaijutsu_crdt::BlockKind::Notification => crate::kaijutsu_capnp::BlockKind::Notification,
         kaijutsu_crdt::BlockKind::Resource => crate::kaijutsu_capnp::BlockKind::Resource,
         kaijutsu_crdt::BlockKind::Trace => crate::kaijutsu_capnp::BlockKind::Trace,
+        kaijutsu_crdt::BlockKind::Repl => crate::kaijutsu_capnp::BlockKind::Repl,
     });
 
     // Set basic fields (no author — derived from id.principal_id)
@@ -7635,6 +11060,16 @@ fn set_block_snapshot(
     if block.content_type != ContentType::Plain {
         builder.set_content_type(block.content_type.as_mime());
     }
+    if let Some(ref language) = block.language {
+        builder.set_language(language);
+    }
+    if let Some(ref label) = block.label {
+        builder.set_label(label);
+    }
+    if let Some(reflow) = block.reflow {
+        builder.set_reflow_width(reflow.width);
+        builder.set_reflow_preserve_newlines(reflow.preserve_newlines);
+    }
 
     // Set ephemeral flag
     builder.set_ephemeral(block.ephemeral);
@@ -7653,6 +11088,49 @@ fn set_block_snapshot(
         builder.set_track(track.as_str());
     }
 
+    if !block.mentions.is_empty() {
+        let mut list = builder.reborrow().init_mentions(block.mentions.len() as u32);
+        for (i, mention) in block.mentions.iter().enumerate() {
+            let mut m = list.reborrow().get(i as u32);
+            m.set_name(&mention.name);
+            match mention.target {
+                MentionTarget::Principal(id) => m.set_principal_id(id.as_bytes()),
+                MentionTarget::Context(id) => m.set_context_id(id.as_bytes()),
+            }
+        }
+    }
+
+    if let Some(ref command) = block.shell_command {
+        let mut sc = builder.reborrow().init_shell_command();
+        {
+            let mut argv = sc.reborrow().init_argv(command.argv.len() as u32);
+            for (i, arg) in command.argv.iter().enumerate() {
+                argv.set(i as u32, arg);
+            }
+        }
+        sc.set_cwd(&command.cwd);
+        {
+            let mut env = sc.reborrow().init_env(command.env.len() as u32);
+            for (i, (key, value)) in command.env.iter().enumerate() {
+                let mut e = env.reborrow().get(i as u32);
+                e.set_key(key);
+                e.set_value(value);
+            }
+        }
+        sc.set_requested_by(command.requested_by.as_bytes());
+        if let Some(ref of) = command.rerun_of {
+            set_block_id_builder(&mut sc.init_rerun_of(), of);
+        }
+    }
+
+    if let Some(ref provenance) = block.provenance {
+        let mut p = builder.reborrow().init_provenance();
+        p.set_agent(&provenance.agent);
+        p.set_version(provenance.version.as_deref().unwrap_or(""));
+        p.set_session_id(provenance.session_id.as_deref().unwrap_or(""));
+        p.set_project_dir(provenance.project_dir.as_deref().unwrap_or(""));
+    }
+
     // Set drift-specific fields if present
     if let Some(ref ctx) = block.source_context {
         builder.set_source_context(ctx.as_bytes());
@@ -7702,6 +11180,7 @@ fn set_block_snapshot(
                     crate::kaijutsu_capnp::BlockKind::Resource
                 }
                 kaijutsu_crdt::BlockKind::Trace => crate::kaijutsu_capnp::BlockKind::Trace,
+                kaijutsu_crdt::BlockKind::Repl => crate::kaijutsu_capnp::BlockKind::Repl,
             });
         }
     }
@@ -7725,6 +11204,12 @@ fn set_block_snapshot(
             kaijutsu_crdt::NotificationKind::Coalesced => {
                 crate::kaijutsu_capnp::NotificationKind::Coalesced
             }
+            kaijutsu_crdt::NotificationKind::ModelChanged => {
+                crate::kaijutsu_capnp::NotificationKind::ModelChanged
+            }
+            kaijutsu_crdt::NotificationKind::IssueCreated => {
+                crate::kaijutsu_capnp::NotificationKind::IssueCreated
+            }
         });
         if let Some(level) = payload.level {
             np.set_has_level(true);
@@ -7832,6 +11317,62 @@ fn drift_kind_to_capnp(dk: kaijutsu_crdt::DriftKind) -> crate::kaijutsu_capnp::D
     }
 }
 
+/// Convert a CRDT BlockKind to Cap'n Proto BlockKind.
+fn block_kind_to_capnp(kind: BlockKind) -> crate::kaijutsu_capnp::BlockKind {
+    match kind {
+        BlockKind::Text => crate::kaijutsu_capnp::BlockKind::Text,
+        BlockKind::Thinking => crate::kaijutsu_capnp::BlockKind::Thinking,
+        BlockKind::ToolCall => crate::kaijutsu_capnp::BlockKind::ToolCall,
+        BlockKind::ToolResult => crate::kaijutsu_capnp::BlockKind::ToolResult,
+        BlockKind::Drift => crate::kaijutsu_capnp::BlockKind::Drift,
+        BlockKind::File => crate::kaijutsu_capnp::BlockKind::File,
+        BlockKind::Error => crate::kaijutsu_capnp::BlockKind::Error,
+        BlockKind::Notification => crate::kaijutsu_capnp::BlockKind::Notification,
+        BlockKind::Resource => crate::kaijutsu_capnp::BlockKind::Resource,
+        BlockKind::Trace => crate::kaijutsu_capnp::BlockKind::Trace,
+        BlockKind::Repl => crate::kaijutsu_capnp::BlockKind::Repl,
+    }
+}
+
+/// Convert a Cap'n Proto BlockKind to CRDT BlockKind.
+fn block_kind_from_capnp(kind: crate::kaijutsu_capnp::BlockKind) -> BlockKind {
+    match kind {
+        crate::kaijutsu_capnp::BlockKind::Text => BlockKind::Text,
+        crate::kaijutsu_capnp::BlockKind::Thinking => BlockKind::Thinking,
+        crate::kaijutsu_capnp::BlockKind::ToolCall => BlockKind::ToolCall,
+        crate::kaijutsu_capnp::BlockKind::ToolResult => BlockKind::ToolResult,
+        crate::kaijutsu_capnp::BlockKind::Drift => BlockKind::Drift,
+        crate::kaijutsu_capnp::BlockKind::File => BlockKind::File,
+        crate::kaijutsu_capnp::BlockKind::Error => BlockKind::Error,
+        crate::kaijutsu_capnp::BlockKind::Notification => BlockKind::Notification,
+        crate::kaijutsu_capnp::BlockKind::Resource => BlockKind::Resource,
+        crate::kaijutsu_capnp::BlockKind::Trace => BlockKind::Trace,
+        crate::kaijutsu_capnp::BlockKind::Repl => BlockKind::Repl,
+    }
+}
+
+/// Convert a CRDT Role to Cap'n Proto Role.
+fn role_to_capnp(role: Role) -> crate::kaijutsu_capnp::Role {
+    match role {
+        Role::User => crate::kaijutsu_capnp::Role::User,
+        Role::Model => crate::kaijutsu_capnp::Role::Model,
+        Role::System => crate::kaijutsu_capnp::Role::System,
+        Role::Tool => crate::kaijutsu_capnp::Role::Tool,
+        Role::Asset => crate::kaijutsu_capnp::Role::Asset,
+    }
+}
+
+/// Convert a Cap'n Proto Role to CRDT Role.
+fn role_from_capnp(role: crate::kaijutsu_capnp::Role) -> Role {
+    match role {
+        crate::kaijutsu_capnp::Role::User => Role::User,
+        crate::kaijutsu_capnp::Role::Model => Role::Model,
+        crate::kaijutsu_capnp::Role::System => Role::System,
+        crate::kaijutsu_capnp::Role::Tool => Role::Tool,
+        crate::kaijutsu_capnp::Role::Asset => Role::Asset,
+    }
+}
+
 /// Parse a Cap'n Proto BlockQuery union into a Rust BlockQuery.
 fn parse_block_query(
     reader: &crate::kaijutsu_capnp::block_query::Reader<'_>,
@@ -7872,6 +11413,7 @@ fn parse_block_filter(
                 crate::kaijutsu_capnp::BlockKind::Notification => BlockKind::Notification,
                 crate::kaijutsu_capnp::BlockKind::Resource => BlockKind::Resource,
                 crate::kaijutsu_capnp::BlockKind::Trace => BlockKind::Trace,
+                crate::kaijutsu_capnp::BlockKind::Repl => BlockKind::Repl,
             });
         }
         if kinds.is_empty() {
@@ -8007,6 +11549,15 @@ fn parse_block_event_filter(
                             crate::kaijutsu_capnp::BlockFlowKind::BeatSync => {
                                 kaijutsu_types::BlockFlowKind::BeatSync
                             }
+                            crate::kaijutsu_capnp::BlockFlowKind::InboxPosted => {
+                                kaijutsu_types::BlockFlowKind::InboxPosted
+                            }
+                            crate::kaijutsu_capnp::BlockFlowKind::ModelChanged => {
+                                kaijutsu_types::BlockFlowKind::ModelChanged
+                            }
+                            crate::kaijutsu_capnp::BlockFlowKind::LockChanged => {
+                                kaijutsu_types::BlockFlowKind::LockChanged
+                            }
                         })
                     })
                     .collect()
@@ -8033,6 +11584,7 @@ fn parse_block_event_filter(
                             crate::kaijutsu_capnp::BlockKind::Notification => BlockKind::Notification,
                             crate::kaijutsu_capnp::BlockKind::Resource => BlockKind::Resource,
                             crate::kaijutsu_capnp::BlockKind::Trace => BlockKind::Trace,
+                            crate::kaijutsu_capnp::BlockKind::Repl => BlockKind::Repl,
                         })
                     })
                     .collect()
@@ -8091,6 +11643,284 @@ async fn await_editor_callback<T>(
     }
 }
 
+/// Forward one `ConfigFlow` event on a block subscription's callback. Shared
+/// by `subscribe_blocks` and `subscribe_blocks_filtered`; `true` when the peer
+/// accepted it.
+async fn forward_config_flow(
+    callback: &crate::kaijutsu_capnp::block_events::Client,
+    flow: &ConfigFlow,
+    timeout: std::time::Duration,
+    kernel_id: impl std::fmt::Display,
+) -> bool {
+    match flow {
+        ConfigFlow::Applied { report } => {
+            let mut req = callback.on_config_applied_request();
+            set_config_apply_report(req.get().init_report(), report);
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+    }
+}
+
+/// Forward a batch of one context's `BlockFlow::TextOps` (see
+/// `event_batch`) as a single `onBlockTextOpsBatch`; `true` when the peer
+/// accepted it.
+async fn forward_text_ops_batch(
+    callback: &crate::kaijutsu_capnp::block_events::Client,
+    context_id: ContextId,
+    flows: &[BlockFlow],
+    timeout: std::time::Duration,
+    kernel_id: impl std::fmt::Display,
+) -> bool {
+    let mut req = callback.on_block_text_ops_batch_request();
+    {
+        let mut params = req.get();
+        params.set_context_id(context_id.as_bytes());
+        let mut entries = params.init_entries(flows.len() as u32);
+        for (i, flow) in flows.iter().enumerate() {
+            if let BlockFlow::TextOps {
+                block_id,
+                ops,
+                seq_num,
+                generation,
+                ..
+            } = flow
+            {
+                let mut entry = entries.reborrow().get(i as u32);
+                set_block_id_builder(&mut entry.reborrow().init_block_id(), block_id);
+                entry.set_ops(ops);
+                entry.set_seq_num(*seq_num);
+                entry.set_generation(*generation);
+            }
+        }
+    }
+    await_editor_callback(req.send().promise, timeout, kernel_id).await
+}
+
+/// Forward one `BlockFlow` event on a `peekDocument` stream. A peek carries
+/// document events only — directives, session control and inbox items are
+/// for joined connections — so the rest return `None`; otherwise `Some(true)`
+/// when the peer accepted it.
+async fn forward_peek_flow(
+    callback: &crate::kaijutsu_capnp::block_events::Client,
+    flow: &BlockFlow,
+    timeout: std::time::Duration,
+    kernel_id: impl std::fmt::Display,
+) -> Option<bool> {
+    let sent = match flow {
+        BlockFlow::Inserted {
+            context_id,
+            block,
+            after_id,
+            ops,
+            ..
+        } => {
+            let mut req = callback.on_block_inserted_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                params.set_has_after_id(after_id.is_some());
+                if let Some(after) = after_id {
+                    set_block_id_builder(&mut params.reborrow().init_after_id(), after);
+                }
+                params.set_ops(ops);
+                set_block_snapshot(&mut params.init_block(), block);
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::Deleted {
+            context_id,
+            block_id,
+            ..
+        } => {
+            let mut req = callback.on_block_deleted_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                set_block_id_builder(&mut params.init_block_id(), block_id);
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::StatusChanged {
+            context_id,
+            block_id,
+            status,
+            ..
+        } => {
+            let mut req = callback.on_block_status_changed_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                params.set_status(status_to_capnp(*status));
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::CollapsedChanged {
+            context_id,
+            block_id,
+            collapsed,
+            ..
+        } => {
+            let mut req = callback.on_block_collapsed_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                params.set_collapsed(*collapsed);
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::ExcludedChanged {
+            context_id,
+            block_id,
+            excluded,
+            ..
+        } => {
+            let mut req = callback.on_block_excluded_changed_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                params.set_excluded(*excluded);
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::Moved {
+            context_id,
+            block_id,
+            after_id,
+            ..
+        } => {
+            let mut req = callback.on_block_moved_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                params.set_has_after_id(after_id.is_some());
+                if let Some(after) = after_id {
+                    set_block_id_builder(&mut params.init_after_id(), after);
+                }
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::TextOps {
+            context_id,
+            block_id,
+            ops,
+            seq_num,
+            generation,
+            ..
+        } => {
+            let mut req = callback.on_block_text_ops_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                params.set_ops(ops);
+                params.set_seq_num(*seq_num);
+                params.set_generation(*generation);
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::SyncReset {
+            context_id,
+            generation,
+        } => {
+            let mut req = callback.on_sync_reset_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                params.set_generation(*generation);
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::OutputChanged {
+            context_id,
+            block_id,
+            output,
+            ..
+        } => {
+            let mut req = callback.on_block_output_changed_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                if let Some(output_data) = output {
+                    build_output_data(params.init_output(), output_data);
+                }
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::MetadataChanged {
+            context_id,
+            block_id,
+            metadata,
+            ..
+        } => {
+            let mut req = callback.on_block_metadata_changed_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                build_block_metadata(params.init_metadata(), metadata);
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::LockChanged {
+            context_id,
+            block_id,
+            lock,
+        } => {
+            let mut req = callback.on_block_lock_changed_request();
+            {
+                let mut params = req.get();
+                params.set_context_id(context_id.as_bytes());
+                set_block_id_builder(&mut params.reborrow().init_block_id(), block_id);
+                params.set_locked(lock.is_some());
+                if let Some(lock) = lock {
+                    set_block_lock(params.init_lock(), lock);
+                }
+            }
+            await_editor_callback(req.send().promise, timeout, kernel_id).await
+        }
+        BlockFlow::ContextSwitched { .. }
+        | BlockFlow::RenderCue { .. }
+        | BlockFlow::BeatSync { .. }
+        | BlockFlow::InboxPosted { .. }
+        | BlockFlow::ModelChanged { .. } => return None,
+    };
+    Some(sent)
+}
+
+/// Default and ceiling for a `peekDocument` event stream's lifetime. A peek
+/// is a glance before deciding to join or drift, not a standing subscription.
+const PEEK_DEFAULT_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+const PEEK_MAX_TTL: std::time::Duration = std::time::Duration::from_secs(600);
+
+/// The `Peek` capability handed back by `peekDocument` and `subscribeBlock`.
+/// Its bridge task watches `cancel`, fired by `close` or when the client
+/// drops the cap.
+struct PeekImpl {
+    cancel: CancellationToken,
+}
+
+impl Drop for PeekImpl {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+impl peek::Server for PeekImpl {
+    fn close(
+        self: Rc<Self>,
+        _params: peek::CloseParams,
+        _results: peek::CloseResults,
+    ) -> Promise<(), capnp::Error> {
+        self.cancel.cancel();
+        Promise::ok(())
+    }
+}
+
 /// Failure tolerance for a FlowBus→client callback bridge.
 ///
 /// The per-callback 5s timeout (see `CALLBACK_TIMEOUT`) is load-bearing: a
@@ -8199,6 +12029,12 @@ const VFS_ACTIVITY_DEFAULT_INTERVAL_MS: u32 = 1000;
 /// diff/commit that lets a dropped entry return on a later tick.
 const VFS_ACTIVITY_DIGEST_MAX_ENTRIES: usize = 256;
 
+/// Floor and default for `subscribeActivity`'s tick interval — the same
+/// policy as the VFS digest: a decorative signal, clamped rather than
+/// rejected.
+const ACTIVITY_MIN_INTERVAL_MS: u32 = 500;
+const ACTIVITY_DEFAULT_INTERVAL_MS: u32 = 1000;
+
 /// Convert a CRDT Status to Cap'n Proto Status.
 fn status_to_capnp(status: kaijutsu_crdt::Status) -> crate::kaijutsu_capnp::Status {
     match status {
@@ -8209,6 +12045,271 @@ fn status_to_capnp(status: kaijutsu_crdt::Status) -> crate::kaijutsu_capnp::Stat
     }
 }
 
+/// Parse a `ContextListQuery` from the wire. Zero/empty fields mean "no filter".
+fn parse_context_list_query(
+    reader: crate::kaijutsu_capnp::context_list_query::Reader<'_>,
+) -> Result<kaijutsu_types::ContextListQuery, capnp::Error> {
+    let label_prefix = reader.get_label_prefix()?.to_str()?;
+    let owner = reader.get_owner()?;
+    let owner = if owner.is_empty() {
+        None
+    } else {
+        Some(
+            PrincipalId::try_from_slice(owner)
+                .ok_or_else(|| capnp::Error::failed("invalid owner principal ID".into()))?,
+        )
+    };
+    Ok(kaijutsu_types::ContextListQuery {
+        label_prefix: (!label_prefix.is_empty()).then(|| label_prefix.to_owned()),
+        active_since: Some(reader.get_active_since()).filter(|&t| t > 0),
+        owner,
+        offset: reader.get_offset(),
+        limit: Some(reader.get_limit()).filter(|&l| l > 0),
+    })
+}
+
+/// Parse a `ContextCloseFilter` from the wire. Zero/empty fields mean "no filter".
+fn parse_context_close_filter(
+    reader: crate::kaijutsu_capnp::context_close_filter::Reader<'_>,
+) -> Result<kaijutsu_types::ContextCloseFilter, capnp::Error> {
+    let label_prefix = reader.get_label_prefix()?.to_str()?;
+    let forked_from = reader.get_forked_from()?;
+    let forked_from = if forked_from.is_empty() {
+        None
+    } else {
+        Some(
+            ContextId::try_from_slice(forked_from)
+                .ok_or_else(|| capnp::Error::failed("invalid forkedFrom context ID".into()))?,
+        )
+    };
+    Ok(kaijutsu_types::ContextCloseFilter {
+        label_prefix: (!label_prefix.is_empty()).then(|| label_prefix.to_owned()),
+        forked_from,
+        idle_before: Some(reader.get_idle_before()).filter(|&t| t > 0),
+        concluded_only: reader.get_concluded_only(),
+        include_promoted: reader.get_include_promoted(),
+    })
+}
+
+/// Parse a `KernelListQuery` from the wire. Zero/empty fields mean "no filter".
+fn parse_kernel_list_query(
+    reader: crate::kaijutsu_capnp::kernel_list_query::Reader<'_>,
+) -> Result<kaijutsu_types::KernelListQuery, capnp::Error> {
+    let name_prefix = reader.get_name_prefix()?.to_str()?;
+    Ok(kaijutsu_types::KernelListQuery {
+        name_prefix: (!name_prefix.is_empty()).then(|| name_prefix.to_owned()),
+        offset: reader.get_offset(),
+        limit: Some(reader.get_limit()).filter(|&l| l > 0),
+    })
+}
+
+/// Gate for the context-scoped MCP verbs that don't name a transport:
+/// `registerMcpServer` asks for `exec` (stdio) or `operator` (http), so
+/// stopping or listing a context's servers takes either one.
+async fn check_mcp_authority(
+    kernel: &SharedKernel,
+    context_id: ContextId,
+    method: &str,
+) -> Result<(), capnp::Error> {
+    use kaijutsu_kernel::mcp::Capability;
+    let binding = kernel
+        .kernel
+        .broker()
+        .binding_checked(&context_id)
+        .await
+        .map_err(|e| capnp::Error::failed(e.to_string()))?;
+    if !binding.allows(&Capability::Exec) && !binding.allows(&Capability::Operator) {
+        return Err(capnp::Error::failed(format!(
+            "{method} denied: context {} lacks the `exec` or `operator` authority \
+             (kj binding allow exec)",
+            context_id.short()
+        )));
+    }
+    Ok(())
+}
+
+/// Gate for admin and operator settings. The authority comes from the
+/// caller's own joined context, never from a context id the caller names:
+/// context ids are listed to everyone, so naming one proves nothing.
+async fn check_caller_authority(
+    kernel: &SharedKernel,
+    caller_context: ContextId,
+    authority: kaijutsu_kernel::mcp::Capability,
+    method: &str,
+) -> Result<(), capnp::Error> {
+    use kaijutsu_kernel::mcp::Capability;
+    let binding = kernel
+        .kernel
+        .broker()
+        .binding_checked(&caller_context)
+        .await
+        .map_err(|e| capnp::Error::failed(e.to_string()))?;
+    if !binding.allows(&authority) {
+        let name = match authority {
+            Capability::Operator => "operator",
+            _ => "admin",
+        };
+        return Err(capnp::Error::failed(format!(
+            "{method} denied: needs the `{name}` authority in your context {} \
+             (kj binding allow {name})",
+            caller_context.short()
+        )));
+    }
+    Ok(())
+}
+
+/// Parse an `McpServerSpec` into the kernel's connect config + fork mode.
+fn parse_mcp_server_spec(
+    reader: crate::kaijutsu_capnp::mcp_server_spec::Reader<'_>,
+) -> Result<
+    (
+        kaijutsu_kernel::mcp::servers::McpServerConfig,
+        kaijutsu_kernel::mcp::McpForkMode,
+    ),
+    capnp::Error,
+> {
+    use kaijutsu_kernel::mcp::McpForkMode;
+    use kaijutsu_kernel::mcp::servers::{McpServerConfig, McpTransport};
+
+    let name = reader.get_instance()?.to_str()?.to_owned();
+    if name.is_empty() {
+        return Err(capnp::Error::failed("McpServerSpec: instance is required".into()));
+    }
+    let transport = match reader.get_transport()?.to_str()? {
+        "" | "stdio" => McpTransport::Stdio,
+        "http" => McpTransport::StreamableHttp,
+        other => {
+            return Err(capnp::Error::failed(format!(
+                "McpServerSpec: unknown transport {other:?} (stdio | http)"
+            )));
+        }
+    };
+    let fork_mode = match reader.get_fork_mode()?.to_str()? {
+        "" => McpForkMode::default(),
+        s => McpForkMode::parse(s).ok_or_else(|| {
+            capnp::Error::failed(format!(
+                "McpServerSpec: unknown forkMode {s:?} (inherit | exclude)"
+            ))
+        })?,
+    };
+
+    let mut args = Vec::new();
+    for arg in reader.get_args()?.iter() {
+        args.push(arg?.to_str()?.to_owned());
+    }
+    let mut env = std::collections::HashMap::new();
+    for entry in reader.get_env()?.iter() {
+        let entry = entry?.to_str()?;
+        let (key, value) = entry.split_once('=').ok_or_else(|| {
+            capnp::Error::failed(format!("McpServerSpec: env entry {entry:?} is not KEY=VALUE"))
+        })?;
+        env.insert(key.to_owned(), value.to_owned());
+    }
+    let command = reader.get_command()?.to_str()?.to_owned();
+    let cwd = reader.get_cwd()?.to_str()?;
+    let url = reader.get_url()?.to_str()?;
+    match transport {
+        McpTransport::Stdio if command.is_empty() => {
+            return Err(capnp::Error::failed(
+                "McpServerSpec: stdio transport requires command".into(),
+            ));
+        }
+        McpTransport::StreamableHttp if url.is_empty() => {
+            return Err(capnp::Error::failed(
+                "McpServerSpec: http transport requires url".into(),
+            ));
+        }
+        _ => {}
+    }
+
+    Ok((
+        McpServerConfig {
+            name,
+            command,
+            args,
+            env,
+            cwd: (!cwd.is_empty()).then(|| cwd.to_owned()),
+            transport,
+            url: (!url.is_empty()).then(|| url.to_owned()),
+        },
+        fork_mode,
+    ))
+}
+
+/// Set ContextMcpServer fields on a Cap'n Proto builder.
+fn set_context_mcp_server(
+    builder: &mut crate::kaijutsu_capnp::context_mcp_server::Builder,
+    status: &kaijutsu_kernel::mcp::ScopedInstanceStatus,
+) {
+    use kaijutsu_kernel::mcp::Health;
+
+    builder.set_instance(status.instance.as_str());
+    builder.set_fork_mode(status.fork_mode.as_str());
+    let (health, reason) = match &status.health {
+        Health::Ready => ("ready", ""),
+        Health::Degraded { reason } => ("degraded", reason.as_str()),
+        Health::Down { reason } => ("down", reason.as_str()),
+    };
+    builder.set_health(health);
+    builder.set_health_reason(reason);
+    let mut tools = builder.reborrow().init_tools(status.tools.len() as u32);
+    for (i, tool) in status.tools.iter().enumerate() {
+        let mut t = tools.reborrow().get(i as u32);
+        t.set_name(&tool.name);
+        t.set_description(tool.description.as_deref().unwrap_or(""));
+        t.set_input_schema(tool.input_schema.to_string());
+    }
+}
+
+/// Set ContextStats fields on a Cap'n Proto builder. `None` metadata goes
+/// out as empty Text (the wire's "unknown" sentinel).
+fn set_context_stats(
+    builder: &mut crate::kaijutsu_capnp::context_stats::Builder,
+    stats: &kaijutsu_types::ContextStats,
+) {
+    if let Some(id) = stats.context_id {
+        builder.set_context_id(id.as_bytes());
+    }
+    builder.set_label(stats.label.as_deref().unwrap_or(""));
+    builder.set_provider(stats.provider.as_deref().unwrap_or(""));
+    builder.set_model(stats.model.as_deref().unwrap_or(""));
+    builder.set_consent_mode(stats.consent_mode.as_deref().unwrap_or(""));
+    builder.set_context_state(stats.context_state.as_deref().unwrap_or(""));
+    builder.set_block_count(stats.block_count);
+    builder.set_total_bytes(stats.total_bytes);
+    {
+        let mut list = builder.reborrow().init_by_kind(stats.by_kind.len() as u32);
+        for (i, (name, count)) in stats.by_kind.iter().enumerate() {
+            let mut entry = list.reborrow().get(i as u32);
+            entry.set_name(name);
+            entry.set_count(*count);
+        }
+    }
+    {
+        let mut list = builder.reborrow().init_by_role(stats.by_role.len() as u32);
+        for (i, (name, count)) in stats.by_role.iter().enumerate() {
+            let mut entry = list.reborrow().get(i as u32);
+            entry.set_name(name);
+            entry.set_count(*count);
+        }
+    }
+    {
+        let mut list = builder
+            .reborrow()
+            .init_principals(stats.principals.len() as u32);
+        for (i, p) in stats.principals.iter().enumerate() {
+            let mut entry = list.reborrow().get(i as u32);
+            entry.set_principal_id(p.principal_id.as_bytes());
+            entry.set_block_count(p.block_count);
+            entry.set_last_activity_at(p.last_activity_at);
+        }
+    }
+    builder.set_drift_in(stats.drift_in);
+    builder.set_drift_out(stats.drift_out);
+    builder.set_pending_approvals(stats.pending_approvals);
+    builder.set_last_activity_at(stats.last_activity_at);
+}
+
 /// Parse a BlockSnapshot from a Cap'n Proto reader.
 // SeatHandle interface removed — replaced by ContextMembership.
 
@@ -9072,4 +13173,40 @@ mod connection_state_tests {
         drop(state);
         assert!(a.is_cancelled() && b.is_cancelled());
     }
+
+    #[test]
+    fn drop_parks_seat_and_spares_a_taken_over_session() {
+        let session_contexts = session_context_map();
+        let seats = crate::seat::SeatRegistry::default();
+        let principal = test_principal();
+        let principal_id = principal.id;
+        let mut state =
+            ConnectionState::new(principal, session_contexts.clone()).with_seats(seats.clone());
+        let (claim, token) = seats.open(principal_id, state.session_id);
+        state.seat = Some(claim);
+        session_contexts.insert(state.session_id, kaijutsu_types::ContextId::new());
+
+        // A reconnect resumes the seat before the old connection drops.
+        let resumed = seats
+            .resume(&token, principal_id, std::time::Instant::now())
+            .unwrap();
+        drop(state);
+        assert!(
+            session_contexts.get(&resumed.claim.session_id).is_some(),
+            "a taken-over session's context entry belongs to the new connection",
+        );
+
+        // The new connection drops in turn: its seat parks, resumable.
+        let mut next = ConnectionState::new(test_principal(), session_contexts.clone())
+            .with_seats(seats.clone());
+        next.session_id = resumed.claim.session_id;
+        next.seat = Some(resumed.claim);
+        drop(next);
+        assert!(session_contexts.get(&resumed.claim.session_id).is_none());
+        assert!(
+            seats
+                .resume(&resumed.token, principal_id, std::time::Instant::now())
+                .is_ok()
+        );
+    }
 }
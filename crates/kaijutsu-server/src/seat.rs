@@ -0,0 +1,342 @@
+//! Resumable seats — a connection's session identity surviving a reconnect.
+//!
+//! A connection occupies a seat: its [`SessionId`] plus the per-session
+//! state hung off it (last block-event filter, VFS activity cursor).
+//! `joinContext` hands the client an opaque seat token. When the connection
+//! drops, the seat is parked for [`SEAT_RESUME_GRACE`] instead of forgotten;
+//! a reconnect presenting the token as the same principal takes the seat
+//! back — same `SessionId`, same state — rather than minting a new one. Each
+//! resume rotates the token. Pending consent prompts aren't seat state: they
+//! live in the principal's inbox and `joinContext` re-delivers them.
+//!
+//! A client can come back before the server has noticed the old socket die
+//! (keepalive takes ~90s). The resume then takes the seat over from the
+//! stale connection, and that connection's eventual teardown finds it no
+//! longer owns the seat and leaves it alone — see [`SeatClaim`].
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use kaijutsu_kernel::ActivityCursor;
+use kaijutsu_types::{BlockEventFilter, PrincipalId, SessionId};
+use parking_lot::Mutex;
+
+/// How long a disconnected seat stays resumable.
+pub const SEAT_RESUME_GRACE: Duration = Duration::from_secs(120);
+
+/// Why a seat token was not honoured.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SeatResumeError {
+    #[error("unknown or expired seat token")]
+    Unknown,
+    #[error("seat token belongs to another principal")]
+    WrongPrincipal,
+}
+
+/// Per-session state a resumed seat gets back.
+#[derive(Debug, Clone, Default)]
+pub struct SeatState {
+    /// Filter of the last `subscribeBlocksFiltered`.
+    pub block_filter: Option<BlockEventFilter>,
+    /// Where the last `subscribeVfsActivity` stream left off.
+    pub activity_cursor: Option<ActivityCursor>,
+    /// Preferred reading language (`setSeatLanguage`), the default target
+    /// of `translateBlock`.
+    pub language: Option<String>,
+}
+
+/// A connection's hold on its seat. Every resume issues a new claim, so a
+/// stale connection's claim stops matching once its seat has been taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeatClaim {
+    pub session_id: SessionId,
+    owner: u64,
+}
+
+/// A seat taken back by [`SeatRegistry::resume`].
+#[derive(Debug)]
+pub struct ResumedSeat {
+    pub claim: SeatClaim,
+    /// The rotated token; the presented one no longer works.
+    pub token: String,
+    pub state: SeatState,
+}
+
+struct Seat {
+    principal_id: PrincipalId,
+    token: String,
+    owner: u64,
+    state: SeatState,
+    /// Set while no connection holds the seat.
+    parked_at: Option<Instant>,
+}
+
+#[derive(Default)]
+struct Seats {
+    by_session: HashMap<SessionId, Seat>,
+    by_token: HashMap<String, SessionId>,
+    next_owner: u64,
+}
+
+impl Seats {
+    fn next_owner(&mut self) -> u64 {
+        self.next_owner += 1;
+        self.next_owner
+    }
+
+    /// Drop parked seats whose grace has run out.
+    fn sweep(&mut self, grace: Duration, now: Instant) {
+        let expired: Vec<SessionId> = self
+            .by_session
+            .iter()
+            .filter(|(_, seat)| {
+                seat.parked_at
+                    .is_some_and(|at| now.saturating_duration_since(at) >= grace)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired {
+            if let Some(seat) = self.by_session.remove(&id) {
+                self.by_token.remove(&seat.token);
+            }
+        }
+    }
+
+    fn owned_mut(&mut self, claim: SeatClaim) -> Option<&mut Seat> {
+        self.by_session
+            .get_mut(&claim.session_id)
+            .filter(|seat| seat.owner == claim.owner)
+    }
+}
+
+/// Kernel-wide seat table, shared by every connection.
+#[derive(Clone)]
+pub struct SeatRegistry {
+    grace: Duration,
+    inner: Arc<Mutex<Seats>>,
+}
+
+impl Default for SeatRegistry {
+    fn default() -> Self {
+        Self::new(SEAT_RESUME_GRACE)
+    }
+}
+
+impl SeatRegistry {
+    pub fn new(grace: Duration) -> Self {
+        Self {
+            grace,
+            inner: Arc::new(Mutex::new(Seats::default())),
+        }
+    }
+
+    pub fn grace(&self) -> Duration {
+        self.grace
+    }
+
+    /// Open a seat for a connection's own session. The token stays the same
+    /// for the life of the claim.
+    pub fn open(&self, principal_id: PrincipalId, session_id: SessionId) -> (SeatClaim, String) {
+        let mut seats = self.inner.lock();
+        seats.sweep(self.grace, Instant::now());
+        let owner = seats.next_owner();
+        let token = new_token();
+        seats.by_token.insert(token.clone(), session_id);
+        seats.by_session.insert(
+            session_id,
+            Seat {
+                principal_id,
+                token: token.clone(),
+                owner,
+                state: SeatState::default(),
+                parked_at: None,
+            },
+        );
+        (SeatClaim { session_id, owner }, token)
+    }
+
+    /// The current token of a seat the claim still holds.
+    pub fn token(&self, claim: SeatClaim) -> Option<String> {
+        self.inner
+            .lock()
+            .owned_mut(claim)
+            .map(|seat| seat.token.clone())
+    }
+
+    /// Take back the seat `token` names — parked, or still held by a
+    /// connection the server hasn't noticed is dead.
+    pub fn resume(
+        &self,
+        token: &str,
+        principal_id: PrincipalId,
+        now: Instant,
+    ) -> Result<ResumedSeat, SeatResumeError> {
+        let mut seats = self.inner.lock();
+        seats.sweep(self.grace, now);
+        let session_id = *seats.by_token.get(token).ok_or(SeatResumeError::Unknown)?;
+        let owner = seats.next_owner();
+        let new_token = new_token();
+        let seat = seats
+            .by_session
+            .get_mut(&session_id)
+            .ok_or(SeatResumeError::Unknown)?;
+        if seat.principal_id != principal_id {
+            return Err(SeatResumeError::WrongPrincipal);
+        }
+        seat.owner = owner;
+        seat.parked_at = None;
+        let old_token = std::mem::replace(&mut seat.token, new_token.clone());
+        let state = seat.state.clone();
+        seats.by_token.remove(&old_token);
+        seats.by_token.insert(new_token.clone(), session_id);
+        Ok(ResumedSeat {
+            claim: SeatClaim { session_id, owner },
+            token: new_token,
+            state,
+        })
+    }
+
+    /// Run `f` on the seat's state if the claim still holds it (parked or
+    /// not). `None` once the seat was taken over or expired.
+    pub fn with_state<R>(
+        &self,
+        claim: SeatClaim,
+        f: impl FnOnce(&mut SeatState) -> R,
+    ) -> Option<R> {
+        self.inner
+            .lock()
+            .owned_mut(claim)
+            .map(|seat| f(&mut seat.state))
+    }
+
+    /// The claim's connection went away: keep the seat resumable for the
+    /// grace period. Returns false if the seat had already been taken over,
+    /// in which case the caller must not tear down session state.
+    pub fn park(&self, claim: SeatClaim, now: Instant) -> bool {
+        let mut seats = self.inner.lock();
+        let parked = match seats.owned_mut(claim) {
+            Some(seat) => {
+                seat.parked_at = Some(now);
+                true
+            }
+            None => false,
+        };
+        seats.sweep(self.grace, now);
+        parked
+    }
+
+    /// Principals holding a seat at `now`, live or parked within the grace.
+    /// One missing from a later call has lost its last seat.
+    pub fn seated_principals(&self, now: Instant) -> HashSet<PrincipalId> {
+        let mut seats = self.inner.lock();
+        seats.sweep(self.grace, now);
+        seats
+            .by_session
+            .values()
+            .map(|seat| seat.principal_id)
+            .collect()
+    }
+
+    /// Give up a seat outright (a connection resuming another seat drops
+    /// the one it opened itself).
+    pub fn close(&self, claim: SeatClaim) {
+        let mut seats = self.inner.lock();
+        if seats.owned_mut(claim).is_some()
+            && let Some(seat) = seats.by_session.remove(&claim.session_id)
+        {
+            seats.by_token.remove(&seat.token);
+        }
+    }
+}
+
+/// 256 random bits, hex.
+fn new_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parked_seat_resumes_with_state_and_rotated_token() {
+        let seats = SeatRegistry::default();
+        let principal = PrincipalId::new();
+        let session = SessionId::new();
+        let (claim, token) = seats.open(principal, session);
+        seats.with_state(claim, |s| {
+            s.block_filter = Some(BlockEventFilter {
+                context_ids: vec![kaijutsu_types::ContextId::new()],
+                ..Default::default()
+            })
+        });
+
+        let now = Instant::now();
+        assert!(seats.park(claim, now));
+        let resumed = seats.resume(&token, principal, now).unwrap();
+        assert_eq!(resumed.claim.session_id, session);
+        assert!(resumed.state.block_filter.is_some());
+        assert_ne!(resumed.token, token);
+        assert_eq!(
+            seats.resume(&token, principal, now).unwrap_err(),
+            SeatResumeError::Unknown
+        );
+        assert_eq!(seats.token(resumed.claim), Some(resumed.token));
+    }
+
+    #[test]
+    fn resume_rejects_other_principals_and_expired_seats() {
+        let seats = SeatRegistry::new(Duration::from_secs(10));
+        let principal = PrincipalId::new();
+        let (claim, token) = seats.open(principal, SessionId::new());
+        let now = Instant::now();
+        seats.park(claim, now);
+
+        assert_eq!(
+            seats.resume(&token, PrincipalId::new(), now).unwrap_err(),
+            SeatResumeError::WrongPrincipal
+        );
+        assert_eq!(
+            seats
+                .resume(&token, principal, now + Duration::from_secs(10))
+                .unwrap_err(),
+            SeatResumeError::Unknown
+        );
+    }
+
+    #[test]
+    fn a_principal_stays_seated_through_the_grace() {
+        let seats = SeatRegistry::new(Duration::from_secs(10));
+        let principal = PrincipalId::new();
+        let (claim, _) = seats.open(principal, SessionId::new());
+        let now = Instant::now();
+        seats.park(claim, now);
+
+        assert!(seats.seated_principals(now).contains(&principal));
+        assert!(
+            seats
+                .seated_principals(now + Duration::from_secs(10))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn takeover_leaves_stale_connection_powerless() {
+        let seats = SeatRegistry::default();
+        let principal = PrincipalId::new();
+        let (stale, token) = seats.open(principal, SessionId::new());
+        let now = Instant::now();
+
+        // Reconnect before the old connection was torn down.
+        let resumed = seats.resume(&token, principal, now).unwrap();
+        assert!(seats.with_state(stale, |s| s.block_filter = None).is_none());
+        assert!(!seats.park(stale, now), "stale teardown must not park");
+        seats.close(stale);
+        assert!(seats.token(resumed.claim).is_some());
+    }
+}
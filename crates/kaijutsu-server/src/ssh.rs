@@ -4,7 +4,7 @@
 //! Public key authentication with user identity from SQLite.
 
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
@@ -24,11 +24,15 @@ use russh::{Channel, ChannelId};
 use tokio::net::TcpListener;
 use tokio_util::compat::TokioAsyncReadCompatExt;
 
-use kaijutsu_types::{Principal, SSH_RPC_SUBSYSTEM, SSH_SFTP_SUBSYSTEM, SSH_SHARE_SUBSYSTEM};
+use kaijutsu_types::rpc_compress::{CompressedStream, RpcCompression, SSH_RPC_ZSTD_SUBSYSTEM};
+use kaijutsu_types::{
+    Principal, SSH_OBSERVER_USER, SSH_RPC_SUBSYSTEM, SSH_SFTP_SUBSYSTEM, SSH_SHARE_SUBSYSTEM,
+};
 
 use crate::auth_db::AuthDb;
 use crate::kaijutsu_capnp;
 use crate::rpc::{ConnectionState, ServerRegistry, WorldImpl};
+use crate::throttle::{ConnectionLimits, RateLimiter, SharedRateLimiter, ThrottledStream};
 
 /// Source for the SSH host key.
 #[derive(Clone)]
@@ -47,6 +51,15 @@ impl KeySource {
             .join("host_key")
     }
 
+    /// Where the consent log's signing key lives: next to a persistent host
+    /// key; nowhere (a per-process key) for an ephemeral one.
+    pub fn consent_key_path(&self) -> Option<PathBuf> {
+        match self {
+            KeySource::Persistent(path) => Some(path.with_file_name("consent_key")),
+            KeySource::Ephemeral => None,
+        }
+    }
+
     /// Load or generate the host key.
     pub fn load_or_generate(&self) -> Result<PrivateKey, std::io::Error> {
         match self {
@@ -112,12 +125,31 @@ pub struct SshServerConfig {
     /// Allow anonymous connections (auto-register unknown keys).
     /// Only for testing - production should always be false.
     pub allow_anonymous: bool,
+    /// Accept [`SSH_OBSERVER_USER`] logins with any key (`--allow-observers`):
+    /// share-only connections for people without a registered key. Off by
+    /// default — with it off, the observer login is refused.
+    pub allow_observers: bool,
     /// Config directory override. None = use XDG default (~/.config/kaijutsu).
     pub config_dir: Option<PathBuf>,
     /// Data directory override. None = use XDG default (~/.local/share/kaijutsu/kernel).
     pub data_dir: Option<PathBuf>,
     /// Maximum number of concurrent SSH connections. Default: 100.
     pub max_connections: usize,
+    /// Per-connection channel cap and frame/byte rates (`--max-channels`,
+    /// `--max-frames-per-sec`, `--max-bytes-per-sec`).
+    pub limits: ConnectionLimits,
+    /// Record or replay every broker tool call (`--record-tools` /
+    /// `--replay-tools`). `None` = tools run live, unrecorded.
+    pub tool_trace: Option<std::sync::Arc<kaijutsu_kernel::mcp::ToolTrace>>,
+    /// Scheduled backups (`--backup-to`). `None` = no backups, and the
+    /// `backupNow` RPC fails.
+    pub backup: Option<crate::backup::BackupConfig>,
+    /// HTTP liveness/readiness probes (`--health`). `None` = no health
+    /// listener.
+    pub health: Option<SocketAddr>,
+    /// Write-behind batching of the block oplog (`--journal-flush`). `None`
+    /// = every op commits before it returns.
+    pub journal: Option<kaijutsu_kernel::journal_queue::JournalBatching>,
     /// RAII guard for an `ephemeral()` test dir: removes the dir when the config
     /// (and so the server task that owns it) is dropped, so repeated local test
     /// runs don't accumulate dirs in `/tmp`. `None` for production / explicit-dir
@@ -171,9 +203,15 @@ impl SshServerConfig {
             key_source: KeySource::Ephemeral,
             auth_db_path: None,
             allow_anonymous: true, // Tests need to accept any key
+            allow_observers: false,
             config_dir: Some(path.clone()),
             data_dir: Some(path.clone()),
             max_connections: 100,
+            limits: ConnectionLimits::default(),
+            tool_trace: None,
+            backup: None,
+            health: None,
+            journal: None,
             _cleanup: Some(std::sync::Arc::new(TempDirGuard(path))),
         }
     }
@@ -185,13 +223,59 @@ impl SshServerConfig {
             key_source: KeySource::Persistent(KeySource::default_path()),
             auth_db_path: Some(AuthDb::default_path()),
             allow_anonymous: false,
+            allow_observers: false,
             config_dir: None, // Use XDG default
             data_dir: None,   // Use XDG default
             max_connections: 100,
+            limits: ConnectionLimits::default(),
+            tool_trace: None,
+            backup: None,
+            health: None,
+            journal: None,
             _cleanup: None,
         }
     }
 
+    /// Record tool calls to, or replay them from, `trace`.
+    pub fn with_tool_trace(mut self, trace: kaijutsu_kernel::mcp::ToolTrace) -> Self {
+        self.tool_trace = Some(std::sync::Arc::new(trace));
+        self
+    }
+
+    /// Back up the kernel and auth databases on `backup`'s schedule.
+    pub fn with_backup(mut self, backup: crate::backup::BackupConfig) -> Self {
+        self.backup = Some(backup);
+        self
+    }
+
+    /// Accept share-only [`SSH_OBSERVER_USER`] logins.
+    pub fn with_observers(mut self, allow: bool) -> Self {
+        self.allow_observers = allow;
+        self
+    }
+
+    /// Serve `/livez`, `/readyz` and `/healthz` over HTTP on `addr`.
+    pub fn with_health(mut self, addr: SocketAddr) -> Self {
+        self.health = Some(addr);
+        self
+    }
+
+    /// Batch block oplog writes per `journal`, or commit each op through
+    /// when `None`.
+    pub fn with_journal(
+        mut self,
+        journal: Option<kaijutsu_kernel::journal_queue::JournalBatching>,
+    ) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    /// Apply `limits` to every connection.
+    pub fn with_limits(mut self, limits: ConnectionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Use a persistent host key at the given path.
     pub fn with_host_key_path(mut self, path: PathBuf) -> Self {
         self.key_source = KeySource::Persistent(path);
@@ -211,9 +295,19 @@ impl SshServer {
 
     /// Run the SSH server, binding to the configured address.
     pub async fn run(&self) -> Result<(), std::io::Error> {
+        self.run_until(std::future::pending()).await
+    }
+
+    /// Like [`run`](Self::run), stopping when `shutdown` resolves. A stop
+    /// commits the block oplog's write-behind queue first, so a graceful
+    /// shutdown loses no acknowledged ops.
+    pub async fn run_until(
+        &self,
+        shutdown: impl std::future::Future<Output = ()>,
+    ) -> Result<(), std::io::Error> {
         let socket = TcpListener::bind(self.config.bind_addr).await?;
         log::info!("Starting SSH server on {}", self.config.bind_addr);
-        self.run_on_listener(socket).await
+        self.run_on_listener_until(socket, shutdown).await
     }
 
     /// Run the SSH server on a pre-bound listener.
@@ -222,6 +316,26 @@ impl SshServer {
     /// listener here. The listener stays bound during initialization, so
     /// incoming connections queue in the OS backlog instead of getting refused.
     pub async fn run_on_listener(&self, socket: TcpListener) -> Result<(), std::io::Error> {
+        self.run_on_listener_until(socket, std::future::pending())
+            .await
+    }
+
+    async fn run_on_listener_until(
+        &self,
+        socket: TcpListener,
+        shutdown: impl std::future::Future<Output = ()>,
+    ) -> Result<(), std::io::Error> {
+        // Health probes come up first, so a supervisor sees "alive, not
+        // ready" through kernel startup rather than a refused connection.
+        let health = match self.config.health {
+            Some(addr) => {
+                let state = Arc::new(crate::health::HealthState::new());
+                crate::health::serve(addr, state.clone()).await?;
+                Some(state)
+            }
+            None => None,
+        };
+
         // Load or generate the host key
         let host_key = self.config.key_source.load_or_generate()?;
         log::info!(
@@ -271,6 +385,10 @@ impl SshServer {
         if allow_anonymous {
             log::warn!("Anonymous mode enabled - unknown keys will be auto-registered");
         }
+        let allow_observers = self.config.allow_observers;
+        if allow_observers {
+            log::info!("Observer logins enabled - any key may open context shares");
+        }
 
         // External MCP pool pre-init removed in Phase 1 M5; a Phase 2
         // replacement will run ExternalMcpServer startup from mcp.toml
@@ -281,15 +399,36 @@ impl SshServer {
         let shared_kernel = crate::rpc::create_shared_kernel(
             self.config.config_dir.as_deref(),
             self.config.data_dir.as_deref(),
+            self.config.key_source.consent_key_path().as_deref(),
         )
         .await
         .map_err(|e| std::io::Error::other(format!("Failed to create shared kernel: {}", e)))?;
+        if let Some(trace) = &self.config.tool_trace {
+            shared_kernel
+                .kernel
+                .broker()
+                .set_tool_trace(Some(trace.clone()))
+                .await;
+        }
+
+        // Write-behind oplog batching: commit block ops in batches on a
+        // timer instead of one transaction each.
+        shared_kernel
+            .documents
+            .set_journal_batching(self.config.journal)
+            .map_err(std::io::Error::other)?;
+        if kaijutsu_kernel::journal_queue::spawn_flusher(&shared_kernel.documents).is_some() {
+            log::info!("Journal batching: {:?}", self.config.journal);
+        }
 
         let registry = Arc::new(ServerRegistry {
             kernel: shared_kernel,
         });
 
         log::info!("Shared kernel created: {}", registry.kernel.name);
+        if let Some(health) = &health {
+            health.set_kernel(registry.kernel.clone());
+        }
 
         // Bring the turn driver online before accepting connections so an
         // autonomous turn requested by an early `kj fork --prompt` isn't
@@ -305,21 +444,95 @@ impl SshServer {
         // sessions when a *peer* writes a block one is bound to (see vi.md 1b).
         crate::rpc::spawn_editor_reconciler(registry.clone());
 
+        // Per-context activity metrics for `subscribeActivity`: one tracker
+        // per kernel, fed from the block flows for the server's lifetime.
+        kaijutsu_kernel::context_activity::spawn_aggregator(
+            registry.kernel.kernel.context_activity().clone(),
+            registry.kernel.kernel.block_flows(),
+        );
+
+        // The context health watchdog behind `onUnhealthy` pushes and
+        // `contextRecover`: stuck blocks, stalled streams, failing tools.
+        kaijutsu_kernel::context_health::spawn_watchdog(
+            registry.kernel.kernel.context_health().clone(),
+            registry.kernel.kernel.block_flows(),
+        );
+
+        // Abandoned blocks (`/etc/config/abandon.toml`): fail blocks left
+        // Pending too long, or Running after their author's last seat expired.
+        let seats = registry.kernel.seats.clone();
+        kaijutsu_kernel::abandoned::spawn_reaper(
+            registry.kernel.kernel.clone(),
+            registry.kernel.documents.clone(),
+            move |now| seats.seated_principals(now),
+        );
+
+        // Digest subscriptions (`kj drift digest`, `digestSubscribe`): distill
+        // followed contexts into their subscribers as drift when due.
+        kaijutsu_kernel::kj::digest::spawn_digest_scheduler(registry.kernel.kj_dispatcher.clone());
+
+        // Outbound webhooks (`/etc/config/webhooks.toml`): watch the block
+        // flows for error/merge events and deliver on this server-lifetime
+        // runtime rather than whichever connection thread raised the event.
+        kaijutsu_kernel::webhooks::spawn_watcher(
+            registry.kernel.kj_dispatcher.webhooks().clone(),
+        );
+
+        // Opt-in usage analytics (`/etc/config/analytics.toml`): keeps the
+        // broker's switch in step with the config and ships each window.
+        kaijutsu_kernel::analytics::spawn_flusher(registry.kernel.kernel.clone());
+
+        // LLM provider health: probe every configured provider now and on a
+        // timer, so a bad key shows up in `kj models` and fails a turn with a
+        // clear message instead of a stream error.
+        kaijutsu_kernel::llm::health::spawn_checker(registry.kernel.kernel.clone());
+
+        // Scheduled backups of kernel.db + auth.db. A bad target (no S3
+        // credentials, malformed endpoint) fails startup: an operator who
+        // asked for backups must not find out at restore time.
+        if let Some(backup) = &self.config.backup {
+            let sources = crate::backup::BackupSources {
+                kernel_db: registry.kernel.data_dir.join("kernel.db"),
+                auth_db: self.config.auth_db_path.clone(),
+            };
+            let handle = crate::backup::spawn_backup_scheduler(backup.clone(), sources)
+                .map_err(std::io::Error::other)?;
+            let _ = registry.kernel.backups.set(handle);
+        }
+
+        let documents = registry.kernel.documents.clone();
         let active_connections = Arc::new(AtomicUsize::new(0));
         log::info!("Max connections: {}", self.config.max_connections);
+        log::info!("Per-connection limits: {:?}", self.config.limits);
 
         let mut server = Server {
             auth_db: Arc::new(Mutex::new(auth_db)),
             allow_anonymous,
+            allow_observers,
             registry,
             active_connections,
             max_connections: self.config.max_connections,
+            limits: self.config.limits,
         };
 
-        server
-            .run_on_socket(Arc::new(config), &socket)
-            .await
-            .map_err(std::io::Error::other)
+        if let Some(health) = &health {
+            health.mark_listening();
+        }
+        let result = tokio::select! {
+            result = server.run_on_socket(Arc::new(config), &socket) => {
+                result.map_err(std::io::Error::other)
+            }
+            () = shutdown => {
+                log::info!("Shutting down");
+                Ok(())
+            }
+        };
+        match documents.flush_journal() {
+            Ok(0) => {}
+            Ok(n) => log::info!("Journal: committed {n} queued ops on shutdown"),
+            Err(e) => log::error!("Journal flush on shutdown failed, queued ops lost: {e}"),
+        }
+        result
     }
 }
 
@@ -327,12 +540,15 @@ impl SshServer {
 struct Server {
     auth_db: Arc<Mutex<AuthDb>>,
     allow_anonymous: bool,
+    allow_observers: bool,
     /// Shared kernel and MCP pool (created at server startup)
     registry: Arc<ServerRegistry>,
     /// Number of currently active SSH connections.
     active_connections: Arc<AtomicUsize>,
     /// Maximum allowed concurrent connections.
     max_connections: usize,
+    /// Limits applied to each connection.
+    limits: ConnectionLimits,
 }
 
 impl server::Server for Server {
@@ -343,9 +559,11 @@ impl server::Server for Server {
             self.auth_db.clone(),
             peer_addr,
             self.allow_anonymous,
+            self.allow_observers,
             self.registry.clone(),
             self.active_connections.clone(),
             self.max_connections,
+            self.limits,
         )
     }
 
@@ -359,7 +577,12 @@ struct ConnectionHandler {
     auth_db: Arc<Mutex<AuthDb>>,
     peer_addr: Option<SocketAddr>,
     allow_anonymous: bool,
+    /// Whether [`SSH_OBSERVER_USER`] logins are accepted at all.
+    allow_observers: bool,
     identity: Option<Principal>,
+    /// Authenticated as [`SSH_OBSERVER_USER`]: an unregistered, throwaway
+    /// principal whose RPC World only opens context shares.
+    observer: bool,
     /// Shared kernel and MCP pool (created at server startup)
     registry: Arc<ServerRegistry>,
     /// Channels opened but not yet bound to a subsystem. `channel_open_session`
@@ -372,27 +595,42 @@ struct ConnectionHandler {
     max_connections: usize,
     /// Whether this handler has been counted in active_connections.
     counted: bool,
+    /// Per-connection channel cap and rates.
+    limits: ConnectionLimits,
+    /// Every session channel currently open, pending or bound — what
+    /// `limits.max_channels` counts.
+    open_channels: HashSet<ChannelId>,
+    /// Frame/byte budget shared by this connection's RPC channels.
+    rate_limiter: SharedRateLimiter,
 }
 
 impl ConnectionHandler {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         auth_db: Arc<Mutex<AuthDb>>,
         peer_addr: Option<SocketAddr>,
         allow_anonymous: bool,
+        allow_observers: bool,
         registry: Arc<ServerRegistry>,
         active_connections: Arc<AtomicUsize>,
         max_connections: usize,
+        limits: ConnectionLimits,
     ) -> Self {
         Self {
             auth_db,
             peer_addr,
             allow_anonymous,
+            allow_observers,
             identity: None,
+            observer: false,
             registry,
             pending_channels: HashMap::new(),
             active_connections,
             max_connections,
             counted: false,
+            limits,
+            open_channels: HashSet::new(),
+            rate_limiter: RateLimiter::shared(&limits),
         }
     }
 
@@ -410,9 +648,16 @@ impl ConnectionHandler {
     /// panic on the RPC thread is logged but does not take down the server —
     /// the default panic hook plus this `catch_unwind` boundary contain damage
     /// to that one connection.
-    fn spawn_rpc_thread(&self, channel: Channel<Msg>, principal: Principal) -> bool {
+    fn spawn_rpc_thread(
+        &self,
+        channel: Channel<Msg>,
+        principal: Principal,
+        compression: RpcCompression,
+    ) -> bool {
         let stream = channel.into_stream();
         let registry = self.registry.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        let observer = self.observer;
         let username_for_thread = principal.username.clone();
         let session_label = format!(
             "kjutsu-rpc-{}-{:?}",
@@ -444,7 +689,15 @@ impl ConnectionHandler {
             let local = tokio::task::LocalSet::new();
             let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                 local.block_on(&rt, async move {
-                    run_rpc(stream, principal, registry).await;
+                    run_rpc(
+                        stream,
+                        principal,
+                        registry,
+                        compression,
+                        rate_limiter,
+                        observer,
+                    )
+                    .await;
                 });
             }));
             if let Err(panic) = result {
@@ -479,6 +732,15 @@ impl Drop for ConnectionHandler {
                 prev - 1,
             );
         }
+        let (parks, parked_for) = self.rate_limiter.lock().throttled();
+        if parks > 0 {
+            log::info!(
+                "Connection {:?} was throttled {} times ({:?} total) by its rate limits",
+                self.peer_addr,
+                parks,
+                parked_for,
+            );
+        }
     }
 }
 
@@ -497,25 +759,65 @@ impl Drop for ConnectionHandler {
 ///     `RPC_WATCHDOG_INTERVAL` while the RPC system has not returned. Without
 ///     thread injection there is no safe way to force-kill a wedged
 ///     `current_thread` runtime from outside; the watchdog is for diagnosis.
+///
+/// `rate_limiter` is the connection's frame/byte budget (see
+/// [`crate::throttle`]); over it, the stream parks rather than erroring.
+/// An `observer` session gets a World that only opens context shares, and
+/// takes no seat.
 async fn run_rpc(
     stream: russh::ChannelStream<Msg>,
     principal: Principal,
     registry: Arc<ServerRegistry>,
+    compression: RpcCompression,
+    rate_limiter: SharedRateLimiter,
+    observer: bool,
 ) {
     // Stamp a liveness timestamp on every byte that moves in either
     // direction, so the watchdog can tell a healthy long-lived session
     // (traffic flowing) from a genuinely stalled one (open but silent).
     let last_activity = Rc::new(Cell::new(Instant::now()));
     let stream = ActivityStream::new(stream.compat(), last_activity.clone());
-    let (reader, writer) = futures::AsyncReadExt::split(stream);
+    // A parked stream moves no bytes, so a throttled session reads as idle
+    // to the watchdog only if it stays parked past the warn threshold.
+    let stream = ThrottledStream::new(stream, rate_limiter);
+    // Compression wraps outside the activity stamp and the throttle, which
+    // keep counting wire bytes.
+    let (reader, writer): (
+        Box<dyn futures::AsyncRead + Unpin>,
+        Box<dyn futures::AsyncWrite + Unpin>,
+    ) = match compression {
+        RpcCompression::None => {
+            let (r, w) = futures::AsyncReadExt::split(stream);
+            (Box::new(r), Box::new(w))
+        }
+        RpcCompression::Zstd => {
+            let (r, w) = futures::AsyncReadExt::split(CompressedStream::new(stream));
+            (Box::new(r), Box::new(w))
+        }
+    };
+
+    // Seat the principal so `@username` mentions can resolve to it.
+    if !observer
+        && let Err(e) = registry
+            .kernel
+            .kernel_db
+            .lock()
+            .record_seat(principal.id, &principal.username)
+    {
+        log::warn!("failed to record seat for {}: {e}", principal.username);
+    }
 
     let session_contexts = registry.kernel.session_contexts.clone();
-    let connection = Rc::new(RefCell::new(ConnectionState::new(
-        principal.clone(),
-        session_contexts.clone(),
-    )));
+    let connection = Rc::new(RefCell::new(
+        ConnectionState::new(principal.clone(), session_contexts.clone())
+            .with_seats(registry.kernel.seats.clone()),
+    ));
     let session_id = connection.borrow().session_id;
-    let world = WorldImpl::new(registry, connection);
+    let world = if observer {
+        WorldImpl::observer(registry, connection)
+    } else {
+        WorldImpl::new(registry, connection)
+    };
     let client: kaijutsu_capnp::world::Client = capnp_rpc::new_client(world);
 
     let network = twoparty::VatNetwork::new(
@@ -527,10 +829,11 @@ async fn run_rpc(
     let rpc_system = RpcSystem::new(Box::new(network), Some(client.clone().client));
 
     log::info!(
-        "RPC session started for {} ({}) session={}",
+        "RPC session started for {} ({}) session={} compression={:?}",
         principal.username,
         principal.display_name,
         session_id.short(),
+        compression,
     );
 
     // Concurrent watchdog: logs if rpc_system stops responding. When
@@ -744,6 +1047,18 @@ impl server::Handler for ConnectionHandler {
             );
         }
 
+        if !self.limits.allows_channel(self.open_channels.len()) {
+            log::warn!(
+                "Channel rejected for {} ({:?}): at channel cap ({}/{})",
+                principal.username,
+                self.peer_addr,
+                self.open_channels.len(),
+                self.limits.max_channels,
+            );
+            kaijutsu_telemetry::record_ssh_channel_rejected();
+            return Ok(false);
+        }
+
         // Stash the channel inert. It carries no traffic until the client
         // names a subsystem via `subsystem_request`, which drains the map and
         // dispatches by name. This retention-and-dispatch scaffold is shared by
@@ -757,6 +1072,7 @@ impl server::Handler for ConnectionHandler {
             principal.display_name,
         );
         self.pending_channels.insert(channel_id, channel);
+        self.open_channels.insert(channel_id);
 
         Ok(true)
     }
@@ -799,16 +1115,31 @@ impl server::Handler for ConnectionHandler {
             }
         };
 
+        // An observer reads shares over RPC; files, shares of its own and
+        // everything else stay closed to it.
+        if self.observer && !matches!(name, SSH_RPC_SUBSYSTEM | SSH_RPC_ZSTD_SUBSYSTEM) {
+            log::warn!(
+                "Subsystem {:?} refused to observer on channel {}",
+                name,
+                channel
+            );
+            session.channel_failure(channel)?;
+            session.close(channel)?;
+            return Ok(());
+        }
+
         match name {
-            SSH_RPC_SUBSYSTEM => {
+            SSH_RPC_SUBSYSTEM | SSH_RPC_ZSTD_SUBSYSTEM => {
                 log::info!(
                     "Binding channel {} to {} for {} ({})",
                     channel,
-                    SSH_RPC_SUBSYSTEM,
+                    name,
                     principal.username,
                     principal.display_name,
                 );
-                if self.spawn_rpc_thread(chan, principal) {
+                let compression = RpcCompression::from_subsystem(name)
+                    .expect("matched an RPC subsystem name");
+                if self.spawn_rpc_thread(chan, principal, compression) {
                     session.channel_success(channel)?;
                 } else {
                     // Spawn failed; `chan` was consumed, drop closes it.
@@ -899,6 +1230,25 @@ impl server::Handler for ConnectionHandler {
             peer
         );
 
+        // An observer only ever opens context shares, and the share token is
+        // the credential that matters, so any key is accepted and nothing is
+        // registered: each connection is a fresh throwaway principal. Only
+        // when the operator opted in — otherwise the name is refused outright
+        // rather than falling through to key lookup.
+        if user == SSH_OBSERVER_USER {
+            if !self.allow_observers {
+                log::info!("Observer auth refused from {} (observers disabled)", peer);
+                return Ok(Auth::Reject {
+                    proceed_with_methods: None,
+                    partial_success: false,
+                });
+            }
+            log::info!("Observer auth accepted from {} [{}]", peer, fingerprint);
+            self.identity = Some(Principal::new(SSH_OBSERVER_USER, "Observer"));
+            self.observer = true;
+            return Ok(Auth::Accept);
+        }
+
         // Clone what we need for spawn_blocking
         let db = self.auth_db.clone();
         let fp = fingerprint.clone();
@@ -950,7 +1300,14 @@ impl server::Handler for ConnectionHandler {
                         });
                     }
 
-                    const RESERVED: &[&str] = &["root", "admin", "system", "nobody", "daemon"];
+                    const RESERVED: &[&str] = &[
+                        "root",
+                        "admin",
+                        "system",
+                        "nobody",
+                        "daemon",
+                        SSH_OBSERVER_USER,
+                    ];
                     if RESERVED.contains(&safe_user.as_str())
                         || safe_user.chars().all(|c| c.is_ascii_digit())
                     {
@@ -1050,6 +1407,7 @@ impl server::Handler for ConnectionHandler {
         // Drop any still-unbound channel so a client that opens then closes
         // without naming a subsystem doesn't leak an entry.
         self.pending_channels.remove(&channel);
+        self.open_channels.remove(&channel);
         log::debug!("Channel {} closed", channel);
         Ok(())
     }
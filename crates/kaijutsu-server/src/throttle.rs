@@ -0,0 +1,391 @@
+//! Per-connection SSH limits.
+//!
+//! One seat's bulk sync (a big `pushOps`, a fresh client pulling every
+//! document) shares the server's runtime, kernel locks, and socket buffers
+//! with every other seat's live event stream. [`ConnectionLimits`] caps what a
+//! single SSH connection may hold and move:
+//!
+//! - **channels** — concurrent session channels, enforced at
+//!   `channel_open_session` (over the cap, the open is refused);
+//! - **frames/sec** and **bytes/sec** — enforced on the RPC channel streams by
+//!   [`ThrottledStream`], shared across all RPC channels of the connection.
+//!
+//! Throttling is backpressure, not rejection: traffic over the rate parks the
+//! stream until the budget refills, the SSH window fills, and the peer slows
+//! down. Nothing is dropped and no error reaches the client.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// Default cap on concurrent session channels per connection. A seat uses
+/// one RPC channel plus, transiently, SFTP and share channels.
+pub const DEFAULT_MAX_CHANNELS: usize = 32;
+
+/// Limits applied to each SSH connection. `0` disables a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionLimits {
+    /// Concurrent session channels (pending or bound). Default:
+    /// [`DEFAULT_MAX_CHANNELS`].
+    pub max_channels: usize,
+    /// Reads and writes per second across the connection's RPC channels —
+    /// roughly one per SSH data packet. Default: unlimited.
+    pub max_frames_per_sec: u32,
+    /// Bytes per second, both directions, across the connection's RPC
+    /// channels. Counted on the wire (after compression). Default: unlimited.
+    pub max_bytes_per_sec: u64,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        Self {
+            max_channels: DEFAULT_MAX_CHANNELS,
+            max_frames_per_sec: 0,
+            max_bytes_per_sec: 0,
+        }
+    }
+}
+
+impl ConnectionLimits {
+    /// No limits at all.
+    pub fn unlimited() -> Self {
+        Self {
+            max_channels: 0,
+            max_frames_per_sec: 0,
+            max_bytes_per_sec: 0,
+        }
+    }
+
+    /// Whether a connection holding `open` channels may open another.
+    pub fn allows_channel(&self, open: usize) -> bool {
+        self.max_channels == 0 || open < self.max_channels
+    }
+}
+
+/// Parse a byte rate: a plain count, or one with a binary `k`/`m`/`g` suffix
+/// (`512k`, `4M`). `0` means unlimited.
+pub fn parse_byte_rate(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (digits, scale) = match s.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&s[..i], 1 << 10),
+        Some((i, 'm' | 'M')) => (&s[..i], 1 << 20),
+        Some((i, 'g' | 'G')) => (&s[..i], 1 << 30),
+        _ => (s, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+        .ok_or_else(|| format!("'{s}' is not a byte rate (e.g. 262144, 512k, 4M)"))
+}
+
+/// A token bucket that may go into debt.
+///
+/// A read or write is never split to fit the budget: it goes through whole
+/// and its cost is deducted afterwards, possibly driving the balance
+/// negative. The next operation then waits until the balance is back to
+/// zero. Burst capacity is one second of rate.
+#[derive(Debug)]
+struct TokenBucket {
+    /// Tokens per second.
+    rate: f64,
+    /// Current balance; negative while in debt.
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, now: Instant) -> Self {
+        Self {
+            rate: rate as f64,
+            tokens: rate as f64,
+            last: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last = now;
+    }
+
+    /// How long until the balance is out of debt, or `None` if it is now.
+    fn wait(&mut self, now: Instant) -> Option<Duration> {
+        self.refill(now);
+        (self.tokens < 0.0).then(|| Duration::from_secs_f64(-self.tokens / self.rate))
+    }
+
+    fn spend(&mut self, cost: u64, now: Instant) {
+        self.refill(now);
+        self.tokens -= cost as f64;
+    }
+}
+
+/// The rate half of [`ConnectionLimits`] for one connection, shared by its
+/// RPC channel streams (each on its own thread, hence the mutex).
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    frames: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+    /// Times a stream was parked, for the end-of-connection summary.
+    throttle_count: u64,
+    /// Total time streams spent parked.
+    throttled_for: Duration,
+}
+
+/// A [`RateLimiter`] shared across a connection's channels.
+pub type SharedRateLimiter = Arc<Mutex<RateLimiter>>;
+
+impl RateLimiter {
+    pub fn new(limits: &ConnectionLimits) -> Self {
+        let now = Instant::now();
+        Self {
+            frames: (limits.max_frames_per_sec > 0)
+                .then(|| TokenBucket::new(u64::from(limits.max_frames_per_sec), now)),
+            bytes: (limits.max_bytes_per_sec > 0)
+                .then(|| TokenBucket::new(limits.max_bytes_per_sec, now)),
+            ..Default::default()
+        }
+    }
+
+    pub fn shared(limits: &ConnectionLimits) -> SharedRateLimiter {
+        Arc::new(Mutex::new(Self::new(limits)))
+    }
+
+    /// Whether any rate limit is configured.
+    pub fn is_limited(&self) -> bool {
+        self.frames.is_some() || self.bytes.is_some()
+    }
+
+    /// How long the next read or write must wait, or `None` to go now.
+    fn wait(&mut self, now: Instant) -> Option<Duration> {
+        let frames = self.frames.as_mut().and_then(|b| b.wait(now));
+        let bytes = self.bytes.as_mut().and_then(|b| b.wait(now));
+        let wait = frames.max(bytes)?;
+        self.throttle_count += 1;
+        self.throttled_for += wait;
+        Some(wait)
+    }
+
+    /// Charge one frame of `len` bytes.
+    fn spend(&mut self, len: usize, now: Instant) {
+        if let Some(frames) = &mut self.frames {
+            frames.spend(1, now);
+        }
+        if let Some(bytes) = &mut self.bytes {
+            bytes.spend(len as u64, now);
+        }
+    }
+
+    /// `(times parked, total time parked)` so far.
+    pub fn throttled(&self) -> (u64, Duration) {
+        (self.throttle_count, self.throttled_for)
+    }
+}
+
+/// Wraps an RPC channel stream, parking reads and writes while the
+/// connection's [`RateLimiter`] is in debt. A zero-byte poll (EOF) costs
+/// nothing. Reads and writes park independently, so a throttled writer never
+/// holds up the waker of a pending reader.
+pub struct ThrottledStream<S> {
+    inner: S,
+    limiter: SharedRateLimiter,
+    read_sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+    write_sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl<S> ThrottledStream<S> {
+    pub fn new(inner: S, limiter: SharedRateLimiter) -> Self {
+        Self {
+            inner,
+            limiter,
+            read_sleep: None,
+            write_sleep: None,
+        }
+    }
+}
+
+/// Ready once the limiter is out of debt; otherwise arms `sleep` and parks.
+fn poll_budget(
+    limiter: &SharedRateLimiter,
+    sleep: &mut Option<Pin<Box<tokio::time::Sleep>>>,
+    direction: &'static str,
+    cx: &mut Context<'_>,
+) -> Poll<()> {
+    loop {
+        if let Some(s) = sleep {
+            if s.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            *sleep = None;
+        }
+        let Some(wait) = limiter.lock().wait(Instant::now()) else {
+            return Poll::Ready(());
+        };
+        kaijutsu_telemetry::record_ssh_throttled(direction, wait);
+        *sleep = Some(Box::pin(tokio::time::sleep(wait)));
+    }
+}
+
+impl<S: futures::io::AsyncRead + Unpin> futures::io::AsyncRead for ThrottledStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        if poll_budget(&this.limiter, &mut this.read_sleep, "read", cx).is_pending() {
+            return Poll::Pending;
+        }
+        let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result
+            && *n > 0
+        {
+            this.limiter.lock().spend(*n, Instant::now());
+        }
+        result
+    }
+}
+
+impl<S: futures::io::AsyncWrite + Unpin> futures::io::AsyncWrite for ThrottledStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        if poll_budget(&this.limiter, &mut this.write_sleep, "write", cx).is_pending() {
+            return Poll::Pending;
+        }
+        let result = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &result
+            && *n > 0
+        {
+            this.limiter.lock().spend(*n, Instant::now());
+        }
+        result
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::AsyncWriteExt;
+
+    fn limits(frames: u32, bytes: u64) -> ConnectionLimits {
+        ConnectionLimits {
+            max_channels: 0,
+            max_frames_per_sec: frames,
+            max_bytes_per_sec: bytes,
+        }
+    }
+
+    #[test]
+    fn bucket_allows_a_burst_then_charges_debt() {
+        let t0 = Instant::now();
+        let mut limiter = RateLimiter::new(&limits(0, 1000));
+        limiter.bytes = Some(TokenBucket::new(1000, t0));
+
+        // A full second of budget goes through at once.
+        assert_eq!(limiter.wait(t0), None);
+        limiter.spend(1000, t0);
+        assert_eq!(limiter.wait(t0), None, "exactly at zero is not debt");
+
+        // An oversized write still goes through whole, then the next waits
+        // for the debt to refill.
+        limiter.spend(500, t0);
+        assert_eq!(limiter.wait(t0), Some(Duration::from_millis(500)));
+        assert_eq!(
+            limiter.wait(t0 + Duration::from_millis(500)),
+            None,
+            "debt is repaid at the configured rate"
+        );
+        assert_eq!(limiter.throttled(), (1, Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn frame_and_byte_limits_take_the_longer_wait() {
+        let t0 = Instant::now();
+        let mut limiter = RateLimiter::new(&limits(10, 1000));
+        limiter.frames = Some(TokenBucket::new(10, t0));
+        limiter.bytes = Some(TokenBucket::new(1000, t0));
+
+        for _ in 0..11 {
+            limiter.spend(1, t0);
+        }
+        // One frame in debt at 10/s = 100ms; bytes are well within budget.
+        assert_eq!(limiter.wait(t0), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn idle_refill_is_capped_at_one_second() {
+        let t0 = Instant::now();
+        let mut bucket = TokenBucket::new(100, t0);
+        bucket.spend(150, t0 + Duration::from_secs(60));
+        assert!(
+            bucket.wait(t0 + Duration::from_secs(60)).is_some(),
+            "a long idle stretch must not bank more than one second of burst"
+        );
+    }
+
+    #[test]
+    fn channel_cap_and_rate_parsing() {
+        let capped = ConnectionLimits {
+            max_channels: 2,
+            ..ConnectionLimits::default()
+        };
+        assert!(capped.allows_channel(1));
+        assert!(!capped.allows_channel(2));
+        assert!(ConnectionLimits::unlimited().allows_channel(10_000));
+        assert!(!RateLimiter::new(&ConnectionLimits::default()).is_limited());
+
+        assert_eq!(parse_byte_rate("4096"), Ok(4096));
+        assert_eq!(parse_byte_rate("512k"), Ok(512 * 1024));
+        assert_eq!(parse_byte_rate("4M"), Ok(4 * 1024 * 1024));
+        assert!(parse_byte_rate("fast").is_err());
+        assert!(parse_byte_rate("k").is_err());
+    }
+
+    #[tokio::test]
+    async fn unlimited_stream_passes_writes_through() {
+        let limiter = RateLimiter::shared(&ConnectionLimits::unlimited());
+        let mut stream =
+            ThrottledStream::new(futures::io::Cursor::new(Vec::new()), limiter.clone());
+
+        for _ in 0..100 {
+            stream.write_all(&[0u8; 64]).await.unwrap();
+        }
+
+        assert_eq!(stream.inner.get_ref().len(), 6400);
+        assert_eq!(limiter.lock().throttled().0, 0);
+    }
+
+    #[tokio::test]
+    async fn over_budget_write_parks_until_refilled() {
+        let limiter = RateLimiter::shared(&limits(0, 1000));
+        let mut stream =
+            ThrottledStream::new(futures::io::Cursor::new(Vec::new()), limiter.clone());
+
+        let start = Instant::now();
+        // 1000 bytes of burst, then 50 bytes of debt → the next write waits
+        // ~50ms instead of failing.
+        stream.write_all(&[0u8; 1050]).await.unwrap();
+        stream.write_all(&[0u8; 1]).await.unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(40));
+        assert_eq!(stream.inner.get_ref().len(), 1051);
+        assert_eq!(limiter.lock().throttled().0, 1);
+    }
+}
@@ -104,6 +104,25 @@ pub async fn start_server_with_state_dir(state_dir: std::path::PathBuf) -> Socke
     addr
 }
 
+/// Start a server that accepts share-only `observer` logins.
+#[allow(dead_code)] // Shared helper: not every test binary that compiles `common` uses it.
+pub async fn start_server_with_observers() -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = SshServerConfig::ephemeral(addr.port()).with_observers(true);
+
+    tokio::task::spawn_local(async move {
+        let server = SshServer::new(config);
+        if let Err(e) = server.run_on_listener(listener).await {
+            log::error!("Server error: {}", e);
+        }
+    });
+
+    tokio::task::yield_now().await;
+    addr
+}
+
 /// Connect to server with ephemeral key.
 #[allow(dead_code)] // Shared helper: not every test binary that compiles `common` uses it.
 pub async fn connect_client(addr: SocketAddr) -> RpcClient {
@@ -113,6 +132,7 @@ pub async fn connect_client(addr: SocketAddr) -> RpcClient {
         username: "test_user".to_string(),
         key_source: KeySource::ephemeral(),
         insecure: true,
+        compress: true,
     };
 
     let mut ssh_client = kaijutsu_client::SshClient::new(config);
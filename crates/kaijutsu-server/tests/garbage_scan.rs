@@ -0,0 +1,88 @@
+//! Garbage scan: each kind of unreachable state is reported, pruning removes
+//! it, and a second scan comes back clean. Live state (ROOT, its document,
+//! a session sitting in it) is never touched.
+
+use kaijutsu_kernel::kernel_db::DocumentRow;
+use kaijutsu_server::garbage;
+use kaijutsu_types::{ContextId, DocKind, PrincipalId, SessionId};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn scan_reports_then_prunes_unreachable_state() {
+    let tmp = tempfile::tempdir().unwrap();
+    let shared = kaijutsu_server::rpc::create_shared_kernel(None, Some(tmp.path()), None)
+        .await
+        .expect("create_shared_kernel");
+    let root = shared.kernel_db.lock().list_all_contexts().unwrap()[0].context_id;
+
+    // A conversation document left behind by a half-finished create, old
+    // enough to be past the grace period.
+    let orphan = ContextId::new();
+    {
+        let db = shared.kernel_db.lock();
+        let ws = db
+            .get_or_create_default_workspace(PrincipalId::system())
+            .unwrap();
+        db.insert_document(&DocumentRow {
+            document_id: orphan,
+            workspace_id: ws,
+            doc_kind: DocKind::Conversation,
+            language: None,
+            path: None,
+            created_at: 1_000,
+            created_by: PrincipalId::system(),
+        })
+        .unwrap();
+        db.set_client_view("laptop", root).unwrap();
+    }
+    // A context the router still knows but the database does not.
+    let ghost = ContextId::new();
+    shared
+        .kernel
+        .drift()
+        .write()
+        .register(ghost, Some("ghost"), None, PrincipalId::system())
+        .unwrap();
+    // A seat sitting in that context, and one sitting in ROOT.
+    let stale_session = SessionId::new();
+    let live_session = SessionId::new();
+    shared.session_contexts.insert(stale_session, ghost);
+    shared.session_contexts.insert(live_session, root);
+    shared
+        .kernel_db
+        .lock()
+        .set_client_view("desktop", ghost)
+        .unwrap();
+
+    let report = garbage::scan(&shared, false).unwrap();
+    assert_eq!(report.orphan_documents, vec![orphan]);
+    assert_eq!(report.ghost_contexts, vec![ghost]);
+    assert_eq!(report.stale_sessions, vec![(stale_session, ghost)]);
+    assert_eq!(
+        report.stale_client_views,
+        vec![("desktop".to_string(), ghost)]
+    );
+    assert!(!report.pruned);
+    assert!(
+        shared.kernel.drift().read().get(ghost).is_some(),
+        "a scan without prune changes nothing"
+    );
+
+    let pruned = garbage::scan(&shared, true).unwrap();
+    assert!(pruned.pruned);
+    assert_eq!(pruned.total(), report.total());
+    assert!(shared.kernel.drift().read().get(ghost).is_none());
+    assert!(!shared.session_contexts.contains_key(&stale_session));
+    assert_eq!(
+        shared.session_contexts.get(&live_session).map(|c| *c),
+        Some(root)
+    );
+    {
+        let db = shared.kernel_db.lock();
+        assert!(db.get_client_view("desktop").unwrap().is_none());
+        assert_eq!(db.get_client_view("laptop").unwrap(), Some(root));
+    }
+
+    let clean = garbage::scan(&shared, false).unwrap();
+    assert_eq!(clean.total(), 0, "pruned state stays gone: {clean:?}");
+    assert!(shared.documents.contains(root));
+}
@@ -0,0 +1,117 @@
+//! End-to-end test for context shares opened over an observer connection.
+//!
+//! A seated client shares a context with `kj context share`; a second client
+//! connects as SSH user `observer` with a throwaway key and reads the frozen
+//! copy by token — and can't bind a kernel or list kernels. Observer logins
+//! are opt-in: a server without `allow_observers` refuses them.
+
+mod common;
+use common::*;
+
+use kaijutsu_client::{KeySource, RpcClient, SshConfig};
+use kaijutsu_types::{BlockKind, BlockQuery, ContextId, SSH_OBSERVER_USER, Status};
+
+/// Run `code` in `context_id` and wait for its output block to settle.
+async fn shell_exec_wait(
+    kernel: &kaijutsu_client::KernelHandle,
+    code: &str,
+    context_id: ContextId,
+) -> (String, Status) {
+    let cmd_block_id = kernel
+        .shell_execute(code, context_id, false)
+        .await
+        .unwrap_or_else(|e| panic!("shell_execute({code:?}) failed: {e}"));
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    loop {
+        assert!(std::time::Instant::now() < deadline, "{code:?} timed out");
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let blocks = kernel
+            .get_blocks(context_id, &BlockQuery::All)
+            .await
+            .unwrap_or_else(|e| panic!("get_blocks failed while polling {code:?}: {e}"));
+        if let Some(output) = blocks
+            .iter()
+            .find(|b| b.kind == BlockKind::ToolResult && b.tool_call_id == Some(cmd_block_id))
+            && matches!(output.status, Status::Done | Status::Error)
+        {
+            return (output.content.clone(), output.status);
+        }
+    }
+}
+
+fn observer_config(addr: std::net::SocketAddr) -> SshConfig {
+    SshConfig {
+        host: addr.ip().to_string(),
+        port: addr.port(),
+        username: SSH_OBSERVER_USER.to_string(),
+        key_source: KeySource::ephemeral(),
+        insecure: true,
+        compress: true,
+    }
+}
+
+async fn connect_observer(addr: std::net::SocketAddr) -> RpcClient {
+    let mut ssh_client = kaijutsu_client::SshClient::new(observer_config(addr));
+    let rpc_channel = ssh_client.connect().await.expect("SSH connect failed");
+    RpcClient::new(rpc_channel.into_stream())
+        .await
+        .expect("RPC client init failed")
+}
+
+#[test]
+fn observer_opens_a_share_and_nothing_else() {
+    run_local(async {
+        let addr = start_server_with_observers().await;
+        let client = connect_client(addr).await;
+        let (kernel, _kernel_id) = client.bind_kernel().await.unwrap();
+        let ctx = kernel.create_context("review").await.unwrap();
+        kernel.join_context(ctx, "test").await.unwrap();
+
+        let (output, status) = shell_exec_wait(&kernel, "echo 'look here'", ctx).await;
+        assert_eq!(status, Status::Done, "echo failed: {output}");
+        let (output, status) = shell_exec_wait(&kernel, "kj context share", ctx).await;
+        assert_eq!(status, Status::Done, "kj context share failed: {output}");
+        let token = output
+            .lines()
+            .find_map(|l| l.strip_prefix("token: "))
+            .unwrap_or_else(|| panic!("no token in share output: {output}"))
+            .trim()
+            .to_string();
+
+        let observer = connect_observer(addr).await;
+        let me = observer.whoami().await.unwrap();
+        assert_eq!(me.username, SSH_OBSERVER_USER);
+        assert!(
+            observer.bind_kernel().await.is_err(),
+            "observers must not bind a kernel"
+        );
+        assert!(
+            observer.list_kernels().await.is_err(),
+            "observers must not list kernels"
+        );
+
+        let (share, blocks) = observer.open_share(&token).await.unwrap();
+        assert_eq!(share.context_id, ctx);
+        assert_eq!(share.label, "review");
+        assert_eq!(blocks.len() as u32, share.block_count);
+        assert!(blocks.iter().any(|b| b.content.contains("look here")));
+
+        assert!(observer.open_share("not-a-token").await.is_err());
+        let (output, status) =
+            shell_exec_wait(&kernel, &format!("kj context unshare {token}"), ctx).await;
+        assert_eq!(status, Status::Done, "kj context unshare failed: {output}");
+        assert!(observer.open_share(&token).await.is_err());
+    });
+}
+
+#[test]
+fn observer_login_is_refused_unless_enabled() {
+    run_local(async {
+        let addr = start_server().await;
+        let mut ssh_client = kaijutsu_client::SshClient::new(observer_config(addr));
+        assert!(
+            ssh_client.connect().await.is_err(),
+            "observer logins are off by default"
+        );
+    });
+}
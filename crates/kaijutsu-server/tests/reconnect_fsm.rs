@@ -118,6 +118,7 @@ fn spawn_test_actor(addr: SocketAddr, instance: &str) -> ActorHandle {
         username: "test_user".to_string(),
         key_source: KeySource::ephemeral(),
         insecure: true,
+        compress: true,
     };
     spawn_actor(config, None, instance.to_string(), false)
 }
@@ -442,7 +443,7 @@ fn join_context_to_missing_context_settles_terminal() {
         let bogus = ContextId::new();
         let result = actor.join_context(bogus).await;
         // We don't pin the exact variant — the kernel may return
-        // `CallError::Rpc(...)` if the call reached the server and got
+        // `CallError::NotFound(...)` if the call reached the server and got
         // rejected, or `NotReady` if the reconnect-on-error path engaged.
         // The load-bearing assertion is that we do NOT silently succeed.
         assert!(
@@ -33,6 +33,7 @@ fn test_unknown_subsystem_is_not_bound_to_rpc() {
             username: "test_user".to_string(),
             key_source: kaijutsu_client::KeySource::ephemeral(),
             insecure: true,
+            compress: true,
         };
         let mut ssh = kaijutsu_client::SshClient::new(config);
 
@@ -452,6 +453,18 @@ async fn setup_execute_context(
     (client, kernel)
 }
 
+/// The ROOT context a fresh kernel seeds: the one context with `admin`.
+async fn root_context(kernel: &kaijutsu_client::KernelHandle) -> kaijutsu_types::ContextId {
+    kernel
+        .list_contexts()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|c| c.label == "ROOT")
+        .expect("a fresh kernel seeds ROOT")
+        .id
+}
+
 #[test]
 fn test_execute_returns_immediately() {
     run_local(async {
@@ -1020,6 +1033,373 @@ fn test_archive_context_rpc_is_single_context_and_idempotent() {
     });
 }
 
+/// `closeContexts` archives exactly what its filter selects (a dry run
+/// reports without writing), and `reopenContext` brings one back unseated.
+#[test]
+fn test_close_contexts_by_filter_and_reopen_over_rpc() {
+    run_local(async {
+        let addr = start_server().await;
+        let (_client, kernel) = setup_execute_context(addr).await;
+
+        let stale_a = kernel.create_context("sweep-a").await.unwrap();
+        let stale_b = kernel.create_context("sweep-b").await.unwrap();
+        let keep = kernel.create_context("keeper").await.unwrap();
+        let filter = kaijutsu_types::ContextCloseFilter::default().with_label_prefix("sweep-");
+
+        let mut preview = kernel.close_contexts(&filter, true).await.unwrap();
+        preview.sort();
+        let mut expected = vec![stale_a, stale_b];
+        expected.sort();
+        assert_eq!(preview, expected);
+        fn archived(
+            contexts: &[kaijutsu_client::ContextInfo],
+            id: kaijutsu_types::ContextId,
+        ) -> bool {
+            contexts.iter().find(|c| c.id == id).unwrap().archived
+        }
+        let contexts = kernel.list_contexts().await.unwrap();
+        assert!(!archived(&contexts, stale_a), "a dry run closes nothing");
+
+        let mut closed = kernel.close_contexts(&filter, false).await.unwrap();
+        closed.sort();
+        assert_eq!(closed, expected);
+        let contexts = kernel.list_contexts().await.unwrap();
+        assert!(archived(&contexts, stale_a));
+        assert!(archived(&contexts, stale_b));
+        assert!(!archived(&contexts, keep));
+
+        // An empty filter selects nothing rather than everything.
+        let nothing = kaijutsu_types::ContextCloseFilter::default();
+        let none = kernel.close_contexts(&nothing, false).await.unwrap();
+        assert!(none.is_empty());
+
+        kernel.reopen_context(stale_a).await.unwrap();
+        let contexts = kernel.list_contexts().await.unwrap();
+        let row = contexts.iter().find(|c| c.id == stale_a).unwrap();
+        assert!(!row.archived);
+        assert!(row.promoted_at.is_none(), "reopen takes no ring-0 seat");
+        assert!(archived(&contexts, stale_b));
+
+        let unknown = kaijutsu_types::ContextId::new();
+        let err = kernel.reopen_context(unknown).await.unwrap_err();
+        assert!(matches!(err, kaijutsu_client::RpcError::ServerError(_)));
+    });
+}
+
+/// `setExecutionBudget` stores a budget `getBudgetStatus` reports back, and
+/// the kill switch refuses shell commands — even user-typed ones — while
+/// engaged. Anyone may engage the halt; setting a budget or releasing the
+/// halt needs `admin` in the caller's own context, so the budgeted context
+/// can do neither and a seat in ROOT can do both.
+#[test]
+fn test_execution_budget_and_tool_halt_over_rpc() {
+    run_local(async {
+        let addr = start_server().await;
+        let (_client, kernel) = setup_execute_context(addr).await;
+        let ctx = kernel.create_context("budgeted").await.unwrap();
+        kernel.join_context(ctx, "test-budget").await.unwrap();
+        let root = root_context(&kernel).await;
+
+        let status = kernel.get_budget_status(ctx).await.unwrap();
+        assert_eq!(status.budget, None);
+        assert!(status.halt.is_none());
+
+        let budget = kaijutsu_types::ExecutionBudget {
+            max_tool_calls_per_hour: Some(50),
+            max_shell_secs_per_hour: None,
+        };
+        let err = kernel.set_execution_budget(ctx, Some(&budget)).await;
+        assert!(err.is_err(), "no admin, no budget write: {err:?}");
+
+        let status = kernel.set_tool_halt(ctx, true, "runaway").await.unwrap();
+        assert_eq!(status.halt.map(|h| h.reason), Some("runaway".to_string()));
+        let err = kernel.shell_execute("echo blocked", ctx, true).await;
+        assert!(err.is_err(), "a halted kernel refuses shell commands");
+        let err = kernel.set_tool_halt(root, false, "").await;
+        assert!(err.is_err(), "naming ROOT lends no authority: {err:?}");
+
+        kernel.join_context(root, "test-budget").await.unwrap();
+        let status = kernel
+            .set_execution_budget(ctx, Some(&budget))
+            .await
+            .unwrap();
+        assert_eq!(status.budget, Some(budget));
+        assert_eq!(status.tool_calls_remaining(), Some(50));
+        let status = kernel.get_budget_status(ctx).await.unwrap();
+        assert_eq!(status.budget, Some(budget));
+
+        let status = kernel.set_execution_budget(ctx, None).await.unwrap();
+        assert_eq!(status.budget, None);
+        assert!(status.halt.is_some(), "clearing a budget keeps the halt");
+        let status = kernel.set_tool_halt(ctx, false, "").await.unwrap();
+        assert!(status.halt.is_none());
+    });
+}
+
+/// A sandbox profile is the context's own fence, so writing one needs the
+/// `admin` authority in the caller's context; a seat in an RPC-created
+/// context has none and is refused, and nothing is stored. A seat in ROOT
+/// may fence that context.
+#[test]
+fn test_set_sandbox_profile_needs_admin() {
+    run_local(async {
+        let addr = start_server().await;
+        let client = connect_client(addr).await;
+        let (kernel, _) = client.bind_kernel().await.unwrap();
+        let ctx = kernel.create_context("fenced").await.unwrap();
+        kernel.join_context(ctx, "test-sandbox").await.unwrap();
+
+        let profile = kaijutsu_types::SandboxProfile {
+            network: true,
+            ..Default::default()
+        };
+        let err = kernel.set_sandbox_profile(ctx, Some(&profile)).await;
+        assert!(err.is_err(), "no admin, no profile write: {err:?}");
+        assert!(kernel.set_sandbox_profile(ctx, None).await.is_err());
+        assert_eq!(kernel.get_sandbox_profile(ctx).await.unwrap(), None);
+
+        let root = root_context(&kernel).await;
+        kernel.join_context(root, "test-sandbox").await.unwrap();
+        kernel
+            .set_sandbox_profile(ctx, Some(&profile))
+            .await
+            .unwrap();
+        assert_eq!(
+            kernel.get_sandbox_profile(ctx).await.unwrap(),
+            Some(profile)
+        );
+    });
+}
+
+/// A context's lock policy is an admin setting, like its sandbox profile:
+/// refused from a plain context, allowed from a seat in ROOT.
+#[test]
+fn test_set_lock_policy_needs_admin() {
+    run_local(async {
+        let addr = start_server().await;
+        let client = connect_client(addr).await;
+        let (kernel, _) = client.bind_kernel().await.unwrap();
+        let ctx = kernel.create_context("held").await.unwrap();
+        kernel.join_context(ctx, "test-locks").await.unwrap();
+
+        let err = kernel
+            .set_lock_policy(ctx, kaijutsu_types::LockPolicy::Hold)
+            .await;
+        assert!(err.is_err(), "no admin, no policy write: {err:?}");
+
+        let root = root_context(&kernel).await;
+        kernel.join_context(root, "test-locks").await.unwrap();
+        kernel
+            .set_lock_policy(ctx, kaijutsu_types::LockPolicy::Hold)
+            .await
+            .unwrap();
+    });
+}
+
+/// `scanGarbage` is authorized by the caller's own context: a seat in a
+/// context without `admin` is refused whether it names its own context or
+/// ROOT's, and a seat that has joined ROOT may scan.
+#[test]
+fn test_scan_garbage_authorizes_the_caller() {
+    run_local(async {
+        let addr = start_server().await;
+        let client = connect_client(addr).await;
+        let (kernel, _) = client.bind_kernel().await.unwrap();
+        let ctx = kernel.create_context("plain").await.unwrap();
+        kernel.join_context(ctx, "test-gc").await.unwrap();
+        let root = root_context(&kernel).await;
+
+        let err = kernel.scan_garbage(root, true).await;
+        assert!(err.is_err(), "naming ROOT lends no authority: {err:?}");
+        let err = kernel.scan_garbage(ctx, true).await;
+        assert!(err.is_err(), "no admin, no scan: {err:?}");
+
+        kernel.join_context(root, "test-gc").await.unwrap();
+        let report = kernel.scan_garbage(root, false).await.unwrap();
+        assert!(!report.pruned);
+    });
+}
+
+/// `getContextStats` stitches the DB row onto the block tally: a freshly
+/// joined context reports its label, model-less metadata, and the default
+/// consent/state; an unknown context fails loud rather than returning zeros.
+#[test]
+fn test_get_context_stats_reports_metadata_and_rejects_unknown() {
+    run_local(async {
+        let addr = start_server().await;
+        let client = connect_client(addr).await;
+        let (kernel, _) = client.bind_kernel().await.unwrap();
+        let ctx = kernel.create_context("stats-me").await.unwrap();
+        kernel.join_context(ctx, "test-stats").await.unwrap();
+
+        let stats = kernel.get_context_stats(ctx).await.unwrap();
+        assert_eq!(stats.context_id, Some(ctx));
+        assert_eq!(stats.label.as_deref(), Some("stats-me"));
+        assert_eq!(stats.consent_mode.as_deref(), Some("collaborative"));
+        assert_eq!(stats.context_state.as_deref(), Some("live"));
+        let kind_total: u64 = stats.by_kind.values().sum();
+        assert_eq!(kind_total, stats.block_count);
+        assert_eq!(stats.drift_in, 0);
+        assert_eq!(stats.pending_approvals, 0);
+
+        let unknown = kaijutsu_crdt::ContextId::new();
+        assert!(kernel.get_context_stats(unknown).await.is_err());
+    });
+}
+
+#[test]
+fn test_peek_document_reads_a_sibling_without_joining_it() {
+    run_local(async {
+        let addr = start_server().await;
+        let client = connect_client(addr).await;
+        let (kernel, _) = client.bind_kernel().await.unwrap();
+        let home = kernel.create_context("peek-home").await.unwrap();
+        let sibling = kernel.create_context("peek-sibling").await.unwrap();
+
+        // Peeking needs an active context to check the binding against.
+        assert!(kernel.peek_document(sibling, None, 0).await.is_err());
+
+        kernel.join_context(home, "test-peek").await.unwrap();
+        let (peeked, _peek) = kernel.peek_document(sibling, None, 0).await.unwrap();
+        assert_eq!(peeked.context_id, sibling);
+        assert_eq!(peeked.expires_in_secs, 0, "no callback, no stream");
+        let blocks = kernel
+            .get_blocks(sibling, &kaijutsu_types::BlockQuery::All)
+            .await
+            .unwrap();
+        assert_eq!(peeked.blocks.len(), blocks.len());
+        let sync = kernel.get_context_sync(sibling).await.unwrap();
+        assert_eq!(peeked.version, sync.version);
+
+        let (active, _) = kernel.get_context_id().await.unwrap();
+        assert_eq!(active, home, "a peek must not move the session");
+
+        let unknown = kaijutsu_crdt::ContextId::new();
+        assert!(kernel.peek_document(unknown, None, 0).await.is_err());
+    });
+}
+
+/// Each `bindSeat` binding is a session of its own: seats on one connection
+/// sit in different contexts, get their own seat tokens, and leave the
+/// connection's `bindKernel` session alone.
+#[test]
+fn test_bind_seat_sessions_are_independent() {
+    run_local(async {
+        let addr = start_server().await;
+        let client = connect_client(addr).await;
+        let (kernel, kernel_id) = client.bind_kernel().await.unwrap();
+        let home = kernel.create_context("seat-home").await.unwrap();
+        let left = kernel.create_context("seat-left").await.unwrap();
+        let right = kernel.create_context("seat-right").await.unwrap();
+        kernel.join_context(home, "test-seat").await.unwrap();
+
+        let (seat_a, seat_a_kernel) = client.bind_seat().await.unwrap();
+        let (seat_b, _) = client.bind_seat().await.unwrap();
+        assert_eq!(seat_a_kernel, kernel_id, "seats share the bound kernel");
+        let (_, info_a) = seat_a
+            .join_context_resuming(left, "test-seat/seat-1", None)
+            .await
+            .unwrap();
+        let (_, info_b) = seat_b
+            .join_context_resuming(right, "test-seat/seat-2", None)
+            .await
+            .unwrap();
+        let (info_a, info_b) = (info_a.unwrap(), info_b.unwrap());
+        assert_ne!(info_a.token, info_b.token);
+        assert_ne!(info_a.session_id, info_b.session_id);
+
+        assert_eq!(seat_a.get_context_id().await.unwrap().0, left);
+        assert_eq!(seat_b.get_context_id().await.unwrap().0, right);
+        assert_eq!(
+            kernel.get_context_id().await.unwrap().0,
+            home,
+            "joining on a seat must not move the connection's session"
+        );
+    });
+}
+
+/// `setContextModel` validates the spec before touching anything: an
+/// unknown provider fails and leaves the context's model as it was.
+#[test]
+fn test_switch_context_model_rejects_unknown_provider() {
+    run_local(async {
+        let addr = start_server().await;
+        let client = connect_client(addr).await;
+        let (kernel, _) = client.bind_kernel().await.unwrap();
+        let ctx = kernel.create_context("switch-model").await.unwrap();
+        let before = kernel.list_contexts().await.unwrap();
+        let before = before.iter().find(|c| c.id == ctx).unwrap();
+
+        assert!(
+            kernel
+                .switch_context_model(ctx, "no-such-provider/some-model")
+                .await
+                .is_err()
+        );
+
+        let after = kernel.list_contexts().await.unwrap();
+        let after = after.iter().find(|c| c.id == ctx).unwrap();
+        assert_eq!(
+            (&after.provider, &after.model),
+            (&before.provider, &before.model)
+        );
+    });
+}
+
+/// `setLlmParams` needs the operator authority in the caller's joined
+/// context, and a merge keeps the fields stored by earlier setters.
+#[test]
+fn test_set_llm_params_authorizes_the_caller_and_merges() {
+    run_local(async {
+        let addr = start_server().await;
+        let client = connect_client(addr).await;
+        let (kernel, _) = client.bind_kernel().await.unwrap();
+        let ctx = kernel.create_context("params").await.unwrap();
+        let player = kernel
+            .create_context_typed("player", "musician")
+            .await
+            .unwrap();
+        let temperature = kaijutsu_types::LlmParams {
+            temperature: Some(0.5),
+            ..Default::default()
+        };
+
+        let err = kernel
+            .set_llm_params(ctx, Some(temperature.clone()), true)
+            .await;
+        assert!(err.is_err(), "no joined context, no authority: {err:?}");
+        kernel.join_context(player, "test-params").await.unwrap();
+        let err = kernel
+            .set_llm_params(ctx, Some(temperature.clone()), true)
+            .await;
+        assert!(err.is_err(), "a musician lacks operator: {err:?}");
+
+        kernel.join_context(ctx, "test-params").await.unwrap();
+        kernel
+            .set_llm_params(ctx, Some(temperature), true)
+            .await
+            .unwrap();
+        let max_tokens = kaijutsu_types::LlmParams {
+            max_tokens: Some(256),
+            ..Default::default()
+        };
+        let stored = kernel
+            .set_llm_params(ctx, Some(max_tokens.clone()), true)
+            .await
+            .unwrap();
+        assert_eq!(stored.temperature, Some(0.5), "merged, not replaced");
+        assert_eq!(stored.max_tokens, Some(256));
+
+        let stored = kernel
+            .set_llm_params(ctx, Some(max_tokens.clone()), false)
+            .await
+            .unwrap();
+        assert_eq!(stored, max_tokens, "without merge the set is replaced");
+        let stored = kernel.set_llm_params(ctx, None, false).await.unwrap();
+        assert!(stored.is_empty());
+    });
+}
+
 /// `setLastContext` auto-promotes a context that has never had an explicit
 /// ring placement (design brief's "auto-promote on visit" rule) — but a
 /// context that's been explicitly demoted stays demoted; explicit demotion
@@ -26,6 +26,7 @@ fn ephemeral_config(addr: std::net::SocketAddr) -> SshConfig {
         username: "test_user".to_string(),
         key_source: KeySource::ephemeral(),
         insecure: true,
+        compress: true,
     }
 }
 
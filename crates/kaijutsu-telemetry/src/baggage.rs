@@ -0,0 +1,184 @@
+//! Identity baggage — principal, kernel, and context IDs on every span.
+//!
+//! A [`TraceIdentity`] rides W3C `baggage` across the Cap'n Proto boundary
+//! (next to `traceparent`/`tracestate`) and is stamped onto spans as the
+//! `kaijutsu.principal_id` / `kaijutsu.kernel_id` / `kaijutsu.context_id`
+//! attributes, so Jaeger/Tempo can filter a trace by who or where.
+//!
+//! There is no process-wide identity: one process can hold several
+//! connections, and one connection several seats, each speaking for a
+//! different principal or context. Whoever sends a request supplies the
+//! identity it speaks as (the client keeps one per connection and binding);
+//! **scoped baggage** ([`TraceIdentity::attach`]) overrides it for the
+//! duration of a guard, e.g. an operation aimed at a different context.
+//!
+//! Baggage is client-asserted: the server stamps the principal it
+//! authenticated and its own kernel over whatever the baggage claimed, and
+//! keeps only the context from it.
+
+use std::collections::HashMap;
+
+use kaijutsu_types::{ContextId, KernelId, PrincipalId};
+use opentelemetry::baggage::BaggageExt;
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::{Context, ContextGuard, KeyValue};
+use opentelemetry_sdk::propagation::BaggagePropagator;
+
+/// Baggage key and span attribute for the acting principal.
+pub const PRINCIPAL_ID_KEY: &str = "kaijutsu.principal_id";
+/// Baggage key and span attribute for the kernel.
+pub const KERNEL_ID_KEY: &str = "kaijutsu.kernel_id";
+/// Baggage key and span attribute for the context.
+pub const CONTEXT_ID_KEY: &str = "kaijutsu.context_id";
+
+/// The who/where of a span. Every field is optional — unset fields are simply
+/// not propagated or recorded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TraceIdentity {
+    pub principal_id: Option<PrincipalId>,
+    pub kernel_id: Option<KernelId>,
+    pub context_id: Option<ContextId>,
+}
+
+impl TraceIdentity {
+    pub fn with_principal(mut self, id: PrincipalId) -> Self {
+        self.principal_id = Some(id);
+        self
+    }
+
+    pub fn with_kernel(mut self, id: KernelId) -> Self {
+        self.kernel_id = Some(id);
+        self
+    }
+
+    pub fn with_context(mut self, id: ContextId) -> Self {
+        self.context_id = Some(id);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.principal_id.is_none() && self.kernel_id.is_none() && self.context_id.is_none()
+    }
+
+    /// Fill unset fields from `fallback`; fields already set win.
+    pub fn or(self, fallback: &TraceIdentity) -> Self {
+        Self {
+            principal_id: self.principal_id.or(fallback.principal_id),
+            kernel_id: self.kernel_id.or(fallback.kernel_id),
+            context_id: self.context_id.or(fallback.context_id),
+        }
+    }
+
+    /// The scoped baggage on the current OTel context. Callers back it with
+    /// the identity they speak as: `TraceIdentity::current().or(&mine)`.
+    pub fn current() -> Self {
+        Self::from_context(&Context::current())
+    }
+
+    /// Read identity baggage from an OTel context. Malformed values are dropped.
+    pub fn from_context(cx: &Context) -> Self {
+        let baggage = cx.baggage();
+        let get = |key: &'static str| baggage.get(key).map(|v| v.as_str().into_owned());
+        Self {
+            principal_id: get(PRINCIPAL_ID_KEY).and_then(|s| PrincipalId::parse(&s).ok()),
+            kernel_id: get(KERNEL_ID_KEY).and_then(|s| KernelId::parse(&s).ok()),
+            context_id: get(CONTEXT_ID_KEY).and_then(|s| ContextId::parse(&s).ok()),
+        }
+    }
+
+    /// Attach this identity as baggage on the current OTel context until the
+    /// guard drops. Fields left unset keep whatever the outer scope carried.
+    #[must_use = "the identity detaches when the guard drops"]
+    pub fn attach(&self) -> ContextGuard {
+        Context::current_with_baggage(self.key_values()).attach()
+    }
+
+    /// Stamp the identity onto a span as `kaijutsu.*` attributes.
+    pub fn record(&self, span: &tracing::Span) {
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        for kv in self.key_values() {
+            span.set_attribute(kv.key, kv.value);
+        }
+    }
+
+    /// Encode as a W3C `baggage` header value (empty when nothing is set).
+    pub fn to_baggage_header(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let cx = Context::new().with_baggage(self.key_values());
+        let mut carrier = HashMap::new();
+        BaggagePropagator::new().inject_context(&cx, &mut carrier);
+        carrier.remove("baggage").unwrap_or_default()
+    }
+
+    /// Decode a W3C `baggage` header value. Unknown members are ignored.
+    pub fn from_baggage_header(header: &str) -> Self {
+        if header.is_empty() {
+            return Self::default();
+        }
+        let mut carrier = HashMap::new();
+        carrier.insert("baggage".to_string(), header.to_string());
+        Self::from_context(&BaggagePropagator::new().extract(&carrier))
+    }
+
+    fn key_values(&self) -> Vec<KeyValue> {
+        let mut kvs = Vec::with_capacity(3);
+        if let Some(id) = self.principal_id {
+            kvs.push(KeyValue::new(PRINCIPAL_ID_KEY, id.to_hex()));
+        }
+        if let Some(id) = self.kernel_id {
+            kvs.push(KeyValue::new(KERNEL_ID_KEY, id.to_hex()));
+        }
+        if let Some(id) = self.context_id {
+            kvs.push(KeyValue::new(CONTEXT_ID_KEY, id.to_hex()));
+        }
+        kvs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baggage_header_roundtrip() {
+        let identity = TraceIdentity::default()
+            .with_principal(PrincipalId::new())
+            .with_kernel(KernelId::new())
+            .with_context(ContextId::new());
+        let header = identity.to_baggage_header();
+        assert!(header.contains(CONTEXT_ID_KEY), "header: {header}");
+        assert_eq!(TraceIdentity::from_baggage_header(&header), identity);
+    }
+
+    #[test]
+    fn empty_identity_is_empty_header() {
+        assert_eq!(TraceIdentity::default().to_baggage_header(), "");
+        assert_eq!(TraceIdentity::from_baggage_header(""), TraceIdentity::default());
+    }
+
+    #[test]
+    fn malformed_and_foreign_members_are_dropped() {
+        let ctx = ContextId::new();
+        let header = format!(
+            "{PRINCIPAL_ID_KEY}=not-a-uuid,vendor.key=1,{CONTEXT_ID_KEY}={}",
+            ctx.to_hex()
+        );
+        let identity = TraceIdentity::from_baggage_header(&header);
+        assert_eq!(identity.principal_id, None);
+        assert_eq!(identity.context_id, Some(ctx));
+    }
+
+    #[test]
+    fn scoped_baggage_overrides_fields_it_sets() {
+        let kernel = KernelId::new();
+        let outer = TraceIdentity::default().with_kernel(kernel);
+        let ctx = ContextId::new();
+        let _guard = TraceIdentity::default().with_context(ctx).attach();
+        let merged = TraceIdentity::from_context(&Context::current()).or(&outer);
+        assert_eq!(merged.kernel_id, Some(kernel));
+        assert_eq!(merged.context_id, Some(ctx));
+    }
+}
@@ -1,8 +1,9 @@
 //! OpenTelemetry integration for kaijutsu.
 //!
 //! Provides OTel tracing layer setup, W3C Trace Context propagation for
-//! distributed tracing across the Cap'n Proto SSH boundary, and a custom
-//! sampler with differentiated rates by span category.
+//! distributed tracing across the Cap'n Proto SSH boundary, identity baggage
+//! (principal/kernel/context IDs, see [`baggage`]), and a custom sampler with
+//! differentiated rates by span category.
 //!
 //! # Activation
 //!
@@ -21,13 +22,16 @@
 //!
 //! Set `OTEL_SDK_DISABLED=true` to explicitly disable even when the endpoint is set.
 
+pub mod baggage;
 pub mod metrics;
 mod otel;
 
+pub use baggage::TraceIdentity;
 pub use metrics::{
     TokenCounts, record_beat_fired, record_beat_sync_published, record_cwd_restore_failed,
-    record_dj_clock_transition, record_grid_reseed, record_llm_usage, record_metronome_click,
-    record_phasor_slew, record_stale_cue_dropped,
+    record_dj_clock_transition, record_grid_reseed, record_journal_flush,
+    record_journal_flush_failed, record_llm_usage, record_metronome_click, record_phasor_slew,
+    record_ssh_channel_rejected, record_ssh_throttled, record_stale_cue_dropped,
 };
 pub use otel::{OtelGuard, otel_layer};
 
@@ -68,6 +72,13 @@ pub fn inject_trace_context() -> (String, String) {
     otel::inject_trace_context_impl()
 }
 
+/// Encode the scoped [`TraceIdentity`] as a W3C `baggage` header value.
+///
+/// Travels next to `traceparent`/`tracestate`; empty when nothing is scoped.
+pub fn inject_baggage() -> String {
+    TraceIdentity::current().to_baggage_header()
+}
+
 /// Extract W3C Trace Context and create a child span linked to the remote parent.
 pub fn extract_trace_context(traceparent: &str, tracestate: &str) -> tracing::Span {
     otel::extract_trace_context_impl(traceparent, tracestate)
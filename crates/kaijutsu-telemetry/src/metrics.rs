@@ -12,6 +12,7 @@
 //! `llm.*` span fields — spans and metrics use different namespaces.
 
 use std::sync::LazyLock;
+use std::time::Duration;
 
 use opentelemetry::KeyValue;
 use opentelemetry::global;
@@ -136,6 +137,144 @@ pub fn record_cwd_restore_failed() {
     CONTEXT_SHELL_METRICS.record_cwd_restore_failed();
 }
 
+/// SSH per-connection limit instruments (`kaijutsu-server`'s `throttle`
+/// module), lazily bound to the global meter provider. Sustained throttling
+/// on one seat is the limits doing their job; throttling on every seat says
+/// the limits are set below ordinary traffic.
+pub struct SshMetrics {
+    /// `kaijutsu.ssh.throttle` — times an RPC stream was parked for being
+    /// over its connection's frame or byte rate, by `direction`
+    /// (`read` | `write`).
+    throttles: Counter<u64>,
+    /// `kaijutsu.ssh.throttle_wait` — how long each park lasted, by
+    /// `direction`.
+    throttle_wait: Histogram<f64>,
+    /// `kaijutsu.ssh.channel_rejected` — session channel opens refused for
+    /// being over the per-connection channel cap.
+    channels_rejected: Counter<u64>,
+}
+
+impl SshMetrics {
+    /// Build the instruments from a meter. Public so tests can bind a meter
+    /// backed by an in-memory reader.
+    pub fn new(meter: &Meter) -> Self {
+        let throttles = meter
+            .u64_counter("kaijutsu.ssh.throttle")
+            .with_unit("{park}")
+            .with_description("RPC stream parks for exceeding a per-connection rate, by direction")
+            .build();
+        let throttle_wait = meter
+            .f64_histogram("kaijutsu.ssh.throttle_wait")
+            .with_unit("s")
+            .with_description("Time an RPC stream spent parked by the rate limiter, by direction")
+            .build();
+        let channels_rejected = meter
+            .u64_counter("kaijutsu.ssh.channel_rejected")
+            .with_unit("{channel}")
+            .with_description("Session channel opens refused by the per-connection channel cap")
+            .build();
+        Self {
+            throttles,
+            throttle_wait,
+            channels_rejected,
+        }
+    }
+
+    /// Record one park of `wait` on the `direction` half of a stream.
+    pub fn record_throttled(&self, direction: &str, wait: Duration) {
+        let attrs = [KeyValue::new("direction", direction.to_owned())];
+        self.throttles.add(1, &attrs);
+        self.throttle_wait.record(wait.as_secs_f64(), &attrs);
+    }
+
+    /// Record one refused channel open.
+    pub fn record_channel_rejected(&self) {
+        self.channels_rejected.add(1, &[]);
+    }
+}
+
+static SSH_METRICS: LazyLock<SshMetrics> =
+    LazyLock::new(|| SshMetrics::new(&global::meter("kaijutsu")));
+
+/// Record one rate-limit park to the global meter provider — see
+/// [`SshMetrics::record_throttled`].
+pub fn record_ssh_throttled(direction: &str, wait: Duration) {
+    SSH_METRICS.record_throttled(direction, wait);
+}
+
+/// Record one refused channel open to the global meter provider.
+pub fn record_ssh_channel_rejected() {
+    SSH_METRICS.record_channel_rejected();
+}
+
+/// Write-behind journal instruments (`kaijutsu-kernel`'s `journal_queue`),
+/// lazily bound to the global meter provider. Depth per flush near the batch
+/// cap says the flush interval is too long for the write rate; any failed
+/// flush means acknowledged ops are piling up in memory.
+pub struct JournalMetrics {
+    /// `kaijutsu.journal.queue_depth` — ops waiting in the queue when a
+    /// flush drained it.
+    queue_depth: Histogram<u64>,
+    /// `kaijutsu.journal.flush_duration` — how long each batch took to
+    /// commit.
+    flush_duration: Histogram<f64>,
+    /// `kaijutsu.journal.flush_failed` — batches the database refused; their
+    /// ops stay queued for the next flush.
+    flush_failed: Counter<u64>,
+}
+
+impl JournalMetrics {
+    /// Build the instruments from a meter. Public so tests can bind a meter
+    /// backed by an in-memory reader.
+    pub fn new(meter: &Meter) -> Self {
+        let queue_depth = meter
+            .u64_histogram("kaijutsu.journal.queue_depth")
+            .with_unit("{op}")
+            .with_description("Ops queued for the oplog when a write-behind flush drained them")
+            .build();
+        let flush_duration = meter
+            .f64_histogram("kaijutsu.journal.flush_duration")
+            .with_unit("s")
+            .with_description("Time to commit one write-behind batch to the oplog")
+            .build();
+        let flush_failed = meter
+            .u64_counter("kaijutsu.journal.flush_failed")
+            .with_unit("{flush}")
+            .with_description("Write-behind batches the database refused, left queued")
+            .build();
+        Self {
+            queue_depth,
+            flush_duration,
+            flush_failed,
+        }
+    }
+
+    /// Record one committed batch of `ops` that took `took`.
+    pub fn record_flush(&self, ops: usize, took: Duration) {
+        self.queue_depth.record(ops as u64, &[]);
+        self.flush_duration.record(took.as_secs_f64(), &[]);
+    }
+
+    /// Record one batch the database refused.
+    pub fn record_flush_failed(&self) {
+        self.flush_failed.add(1, &[]);
+    }
+}
+
+static JOURNAL_METRICS: LazyLock<JournalMetrics> =
+    LazyLock::new(|| JournalMetrics::new(&global::meter("kaijutsu")));
+
+/// Record one committed write-behind batch to the global meter provider —
+/// see [`JournalMetrics::record_flush`].
+pub fn record_journal_flush(ops: usize, took: Duration) {
+    JOURNAL_METRICS.record_flush(ops, took);
+}
+
+/// Record one refused write-behind batch to the global meter provider.
+pub fn record_journal_flush_failed() {
+    JOURNAL_METRICS.record_flush_failed();
+}
+
 /// Beat-timing instruments (phase-align, `docs/tracks.md`/`docs/midi.md`) —
 /// the empirical tuning loop for the grid/deadband/fold-window knobs
 /// (`beat.rs::GRID_RESEED_AFTER_PERIODS`, `timebase.rs::DEFAULT_PHASE_DEADBAND`,
@@ -586,4 +725,35 @@ mod tests {
             "two stale-reason transitions, discoverable by reason too"
         );
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn records_ssh_limit_metrics() {
+        let exporter = InMemoryMetricExporter::default();
+        let provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(exporter.clone())
+            .build();
+        let metrics = SshMetrics::new(&provider.meter("test"));
+
+        metrics.record_throttled("write", Duration::from_millis(40));
+        metrics.record_throttled("write", Duration::from_millis(10));
+        metrics.record_throttled("read", Duration::from_millis(5));
+        metrics.record_channel_rejected();
+
+        provider.force_flush().expect("flush");
+        let rm = exporter.get_finished_metrics().expect("metrics exported");
+
+        assert_eq!(
+            counter_sum(&rm, "kaijutsu.ssh.throttle", "direction", "write"),
+            2
+        );
+        assert_eq!(
+            counter_sum(&rm, "kaijutsu.ssh.throttle", "direction", "read"),
+            1
+        );
+        assert_eq!(
+            histogram_row_count(&rm, "kaijutsu.ssh.throttle_wait", "direction", "write"),
+            2
+        );
+        assert_eq!(counter_total(&rm, "kaijutsu.ssh.channel_rejected"), 1);
+    }
 }
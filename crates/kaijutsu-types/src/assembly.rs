@@ -0,0 +1,228 @@
+//! Per-context prompt-assembly strategies.
+//!
+//! An [`Assembly`] decides which blocks of a context's log go on the wire for
+//! a turn. It is a pipeline of [`AssemblyStage`]s, each narrowing the
+//! selection the previous one left, written `stage+stage+...`:
+//!
+//! | spec            | keeps                                                   |
+//! |-----------------|---------------------------------------------------------|
+//! | `linear`        | the whole transcript (the default)                      |
+//! | `path[:<key>]`  | top-level blocks up to `<key>` plus its ancestor chain  |
+//! | `window:<n>`    | the pinned prefix plus the last `n` blocks              |
+//! | `checkpoint`    | the latest distill summary and everything after it      |
+//!
+//! e.g. `checkpoint+window:16`. The kernel's `llm::assemble` module applies
+//! them; a context's stored strategy is set with `kj context assemble` and
+//! inspected with the `contextPreview` RPC.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BlockId, ContextId};
+
+/// Widest accepted `window:<n>`.
+pub const MAX_ASSEMBLY_WINDOW: u32 = 100_000;
+
+/// One selection step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssemblyStage {
+    /// Keep everything.
+    Linear,
+    /// The conversation as it stood at a block: every top-level block up to
+    /// it plus the block's own ancestor chain, dropping nested side branches
+    /// (sub-agent subtrees, forks folded back in). `None` = the last block.
+    Path(Option<BlockId>),
+    /// The pinned prefix — up to and including the context's hydration
+    /// marker, or the first turn when it has none — plus the last `n` blocks.
+    Window(u32),
+    /// From the most recent `Distill` drift block (a compaction or
+    /// distillation summary) onward; everything when there is none.
+    Checkpoint,
+}
+
+impl fmt::Display for AssemblyStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linear => f.write_str("linear"),
+            Self::Path(None) => f.write_str("path"),
+            Self::Path(Some(id)) => write!(f, "path:{}", id.to_key()),
+            Self::Window(n) => write!(f, "window:{n}"),
+            Self::Checkpoint => f.write_str("checkpoint"),
+        }
+    }
+}
+
+impl FromStr for AssemblyStage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg.trim())),
+            None => (s, None),
+        };
+        match (name, arg) {
+            ("linear", None) => Ok(Self::Linear),
+            ("checkpoint", None) => Ok(Self::Checkpoint),
+            ("path", None) => Ok(Self::Path(None)),
+            ("path", Some(key)) => BlockId::from_key(key)
+                .map(|id| Self::Path(Some(id)))
+                .ok_or_else(|| format!("invalid block key '{key}' in path stage")),
+            ("window", Some(n)) => match n.parse::<u32>() {
+                Ok(n) if (1..=MAX_ASSEMBLY_WINDOW).contains(&n) => Ok(Self::Window(n)),
+                _ => Err(format!(
+                    "window size must be 1-{MAX_ASSEMBLY_WINDOW}, got '{n}'"
+                )),
+            },
+            ("window", None) => Err("window needs a size: window:<n>".to_string()),
+            _ => Err(format!(
+                "unknown assembly stage '{s}' (linear, path[:<key>], window:<n>, checkpoint)"
+            )),
+        }
+    }
+}
+
+/// A context's assembly pipeline. Never empty; the default is `linear`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Assembly {
+    stages: Vec<AssemblyStage>,
+}
+
+impl Assembly {
+    /// The whole transcript, unwindowed.
+    pub fn linear() -> Self {
+        Self {
+            stages: vec![AssemblyStage::Linear],
+        }
+    }
+
+    /// A pipeline from explicit stages; empty means `linear`.
+    pub fn new(stages: Vec<AssemblyStage>) -> Self {
+        if stages.is_empty() {
+            Self::linear()
+        } else {
+            Self { stages }
+        }
+    }
+
+    pub fn stages(&self) -> &[AssemblyStage] {
+        &self.stages
+    }
+
+    /// Whether every stage keeps everything, so the conversation can be
+    /// folded incrementally instead of rebuilt each turn.
+    pub fn is_linear(&self) -> bool {
+        self.stages.iter().all(|s| *s == AssemblyStage::Linear)
+    }
+
+    /// This pipeline for a fork: `path` stages point at the child's copy of
+    /// their block (a fork keeps each block's principal and seq and swaps
+    /// only the context).
+    pub fn rebased(&self, context_id: ContextId) -> Self {
+        let stages = self
+            .stages
+            .iter()
+            .map(|stage| match stage {
+                AssemblyStage::Path(Some(id)) => {
+                    AssemblyStage::Path(Some(BlockId::new(context_id, id.principal_id, id.seq)))
+                }
+                other => *other,
+            })
+            .collect();
+        Self { stages }
+    }
+}
+
+impl Default for Assembly {
+    fn default() -> Self {
+        Self::linear()
+    }
+}
+
+impl fmt::Display for Assembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stage) in self.stages.iter().enumerate() {
+            if i > 0 {
+                f.write_str("+")?;
+            }
+            write!(f, "{stage}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Assembly {
+    type Err = String;
+
+    /// Stages joined with `+`, e.g. `checkpoint+window:16`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err("empty assembly strategy".to_string());
+        }
+        s.split('+')
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::new)
+    }
+}
+
+impl From<Assembly> for String {
+    fn from(assembly: Assembly) -> Self {
+        assembly.to_string()
+    }
+}
+
+impl TryFrom<String> for Assembly {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrincipalId;
+
+    #[test]
+    fn specs_round_trip() {
+        let key = BlockId::new(ContextId::new(), PrincipalId::new(), 7).to_key();
+        for s in [
+            "linear".to_string(),
+            "checkpoint+window:16".to_string(),
+            "path".to_string(),
+            format!("path:{key}+window:4"),
+        ] {
+            let assembly: Assembly = s.parse().unwrap();
+            assert_eq!(assembly.to_string(), s);
+            let json = serde_json::to_string(&assembly).unwrap();
+            assert_eq!(serde_json::from_str::<Assembly>(&json).unwrap(), assembly);
+        }
+    }
+
+    #[test]
+    fn bad_specs_are_rejected() {
+        for s in [
+            "",
+            "window",
+            "window:0",
+            "window:x",
+            "path:nope",
+            "linear:1",
+            "tail:3",
+        ] {
+            assert!(s.parse::<Assembly>().is_err(), "{s} should not parse");
+        }
+    }
+
+    #[test]
+    fn linear_detection() {
+        assert!(Assembly::default().is_linear());
+        assert!("linear+linear".parse::<Assembly>().unwrap().is_linear());
+        assert!(!"window:8".parse::<Assembly>().unwrap().is_linear());
+    }
+}
@@ -22,6 +22,9 @@ use strum::EnumString;
 
 use crate::OutputData;
 use crate::ids::{ContextId, PrincipalId};
+use crate::mention::BlockMention;
+use crate::provenance::AgentProvenance;
+use crate::shell_command::ShellCommand;
 use crate::tick::Tick;
 use crate::track::TrackId;
 
@@ -94,6 +97,214 @@ impl BlockId {
     }
 }
 
+// ── Prefix resolution ───────────────────────────────────────────────────────
+
+/// An abbreviated block reference, as parsed from user input.
+enum BlockRef<'a> {
+    /// `ctx@principal#seq`, `ctx#seq` / `principal#seq`, `@principal#seq`,
+    /// `#seq`, or `ctx_principal_seq` with shortened ids. Each id part is a
+    /// full id or a prefix of its `short()` form (see `matches_short`); the
+    /// seq is exact.
+    Parts {
+        context: Option<&'a str>,
+        principal: Option<&'a str>,
+        /// A lone id before `#` — either the context or the principal.
+        either: Option<&'a str>,
+        seq: u64,
+    },
+    /// Anything else: a prefix of the full `to_key()` form, like a truncated
+    /// paste of a key.
+    KeyPrefix(&'a str),
+}
+
+impl<'a> BlockRef<'a> {
+    fn parse(query: &'a str) -> Self {
+        let part = |s: &'a str| (!s.is_empty()).then_some(s);
+        if let Some((ids, seq)) = query.rsplit_once('#') {
+            let Ok(seq) = seq.parse() else {
+                return Self::KeyPrefix(query);
+            };
+            return match ids.split_once('@') {
+                Some((ctx, principal)) => Self::Parts {
+                    context: part(ctx),
+                    principal: part(principal),
+                    either: None,
+                    seq,
+                },
+                None => Self::Parts {
+                    context: None,
+                    principal: None,
+                    either: part(ids),
+                    seq,
+                },
+            };
+        }
+        let parts: Vec<&str> = query.splitn(3, '_').collect();
+        if let [ctx, principal, seq] = parts.as_slice()
+            && let Ok(seq) = seq.parse()
+        {
+            return Self::Parts {
+                context: part(ctx),
+                principal: part(principal),
+                either: None,
+                seq,
+            };
+        }
+        Self::KeyPrefix(query)
+    }
+
+    fn matches(&self, id: &BlockId) -> bool {
+        match *self {
+            Self::Parts {
+                context,
+                principal,
+                either,
+                seq,
+            } => {
+                id.seq == seq
+                    && context.is_none_or(|c| id.context_id.matches_short(c))
+                    && principal.is_none_or(|p| id.principal_id.matches_short(p))
+                    && either.is_none_or(|e| {
+                        id.context_id.matches_short(e) || id.principal_id.matches_short(e)
+                    })
+            }
+            Self::KeyPrefix(prefix) => id.to_key().starts_with(prefix),
+        }
+    }
+}
+
+impl BlockId {
+    /// Could a block in `context_id` be named by `query`? Lets a caller skip
+    /// whole documents before listing their blocks for
+    /// [`resolve_block_prefix`].
+    pub fn query_may_match_context(query: &str, context_id: ContextId) -> bool {
+        if let Some(full) = Self::from_key(query) {
+            return full.context_id == context_id;
+        }
+        match BlockRef::parse(query) {
+            BlockRef::Parts {
+                context: Some(c), ..
+            } => context_id.matches_short(c),
+            BlockRef::Parts { .. } => true,
+            BlockRef::KeyPrefix(prefix) => {
+                let hex = context_id.to_hex();
+                match prefix.split_once('_') {
+                    Some((ctx, _)) => ctx == hex,
+                    None => hex.starts_with(prefix),
+                }
+            }
+        }
+    }
+}
+
+/// Resolve a possibly-abbreviated block reference against `blocks`, like git
+/// resolves a short SHA.
+///
+/// A full key (`BlockId::from_key`) is returned as-is without consulting
+/// `blocks`, so callers keep their own not-found handling for it. Otherwise
+/// the query is matched as:
+/// 1. the `Display` form with shortened ids — `ctx@principal#seq`,
+///    `ctx#seq` or `principal#seq`, `@principal#seq`, `#seq` — or the key form
+///    with shortened ids, `ctx_principal_seq`;
+/// 2. failing that, a prefix of the full key.
+///
+/// Exactly one block must match; an ambiguous query lists the candidates in
+/// `Display` form, which itself resolves.
+pub fn resolve_block_prefix(
+    blocks: impl IntoIterator<Item = BlockId>,
+    query: &str,
+) -> Result<BlockId, crate::PrefixError> {
+    if let Some(full) = BlockId::from_key(query) {
+        return Ok(full);
+    }
+    let query = query.trim();
+    let reference = BlockRef::parse(query);
+    let blocks: Vec<BlockId> = blocks.into_iter().collect();
+    let mut matches: Vec<BlockId> = blocks
+        .iter()
+        .copied()
+        .filter(|b| reference.matches(b))
+        .collect();
+    // A `_`-form that names no block may still be a truncated key.
+    if matches.is_empty() && matches!(reference, BlockRef::Parts { .. }) && query.contains('_') {
+        let prefix = BlockRef::KeyPrefix(query);
+        matches = blocks
+            .iter()
+            .copied()
+            .filter(|b| prefix.matches(b))
+            .collect();
+    }
+    matches.sort();
+    matches.dedup();
+    match matches.as_slice() {
+        [] => Err(crate::PrefixError::NoMatch(query.to_string())),
+        [one] => Ok(*one),
+        many => Err(crate::PrefixError::Ambiguous {
+            prefix: query.to_string(),
+            candidates: many.iter().map(|b| b.to_string()).collect(),
+        }),
+    }
+}
+
+/// Longest accepted block label; see [`validate_block_label`].
+pub const MAX_BLOCK_LABEL_LEN: usize = 64;
+
+/// Check a block label (`design-decision-3`): 1–64 ASCII letters, digits,
+/// `-`, `_` or `.`, starting with a letter. The alphabet keeps labels apart
+/// from the `@`/`#` reference syntax and safe in shell arguments and URIs.
+pub fn validate_block_label(label: &str) -> Result<(), String> {
+    if label.is_empty() || label.len() > MAX_BLOCK_LABEL_LEN {
+        return Err(format!(
+            "block label must be 1-{MAX_BLOCK_LABEL_LEN} characters, got {}",
+            label.len()
+        ));
+    }
+    if !label.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(format!("block label '{label}' must start with a letter"));
+    }
+    if let Some(bad) = label
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        return Err(format!(
+            "block label '{label}' contains '{bad}'; use letters, digits, '-', '_' or '.'"
+        ));
+    }
+    Ok(())
+}
+
+/// [`resolve_block_prefix`], but an exact block label wins first.
+///
+/// `labels` pairs each label with its block, from every document the caller
+/// searches ([`BlockSnapshot::label`] is unique per context, not globally). A
+/// label held in more than one context is ambiguous and lists the blocks in
+/// `Display` form, which resolves. A full key still short-circuits.
+pub fn resolve_block_ref<L: AsRef<str>>(
+    blocks: impl IntoIterator<Item = BlockId>,
+    labels: impl IntoIterator<Item = (L, BlockId)>,
+    query: &str,
+) -> Result<BlockId, crate::PrefixError> {
+    if let Some(full) = BlockId::from_key(query) {
+        return Ok(full);
+    }
+    let trimmed = query.trim();
+    let mut labeled: Vec<BlockId> = labels
+        .into_iter()
+        .filter(|(label, _)| label.as_ref() == trimmed)
+        .map(|(_, id)| id)
+        .collect();
+    labeled.sort();
+    labeled.dedup();
+    match labeled.as_slice() {
+        [] => resolve_block_prefix(blocks, query),
+        [one] => Ok(*one),
+        many => Err(crate::PrefixError::Ambiguous {
+            prefix: trimmed.to_string(),
+            candidates: many.iter().map(|b| b.to_string()).collect(),
+        }),
+    }
+}
+
 impl std::fmt::Display for BlockId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -564,7 +775,11 @@ impl std::fmt::Display for LogLevel {
 /// What the notification is about.
 ///
 /// `Coalesced` is a summary block emitted by the broker when a burst of
-/// same-key notifications exceeds the coalescer window (§5.3).
+/// same-key notifications exceeds the coalescer window (§5.3). `ModelChanged`
+/// is the kernel's record of a mid-conversation model switch; hydration
+/// stops replaying the previous model's reasoning signatures at it.
+/// `IssueCreated` records an issue or comment filed in an external tracker
+/// from a block subtree (`issue_create`); `detail` carries the URL.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, EnumString)]
 #[serde(rename_all = "snake_case")]
 #[strum(ascii_case_insensitive)]
@@ -574,6 +789,8 @@ pub enum NotificationKind {
     Log,
     PromptsChanged,
     Coalesced,
+    ModelChanged,
+    IssueCreated,
 }
 
 impl NotificationKind {
@@ -584,6 +801,8 @@ impl NotificationKind {
             NotificationKind::Log => "log",
             NotificationKind::PromptsChanged => "prompts_changed",
             NotificationKind::Coalesced => "coalesced",
+            NotificationKind::ModelChanged => "model_changed",
+            NotificationKind::IssueCreated => "issue_created",
         }
     }
 }
@@ -598,7 +817,8 @@ impl std::fmt::Display for NotificationKind {
 ///
 /// The emitting broker populates `instance` (the MCP server identifier),
 /// `kind` (what happened), and kind-specific fields (`tools` for ToolAdded/Removed,
-/// `level` + `detail` for Log, `count` for Coalesced).
+/// `level` + `detail` for Log, `count` for Coalesced, `detail` for ModelChanged
+/// and IssueCreated).
 ///
 /// `tools` is always a list (possibly empty). A single ToolAdded/ToolRemoved
 /// event carries one entry; a batched event (e.g. all tools exposed by an
@@ -667,6 +887,14 @@ impl NotificationPayload {
                 let kind_hint = self.detail.as_deref().unwrap_or("event");
                 format!("[{}] coalesced: {} further {} events", self.instance, count, kind_hint)
             }
+            NotificationKind::ModelChanged => {
+                let detail = self.detail.as_deref().unwrap_or("");
+                format!("[{}] model changed: {}", self.instance, detail)
+            }
+            NotificationKind::IssueCreated => {
+                let detail = self.detail.as_deref().unwrap_or("");
+                format!("[{}] issue created: {}", self.instance, detail)
+            }
         }
     }
 }
@@ -998,6 +1226,16 @@ pub enum BlockKind {
     #[serde(rename = "trace")]
     #[strum(serialize = "trace")]
     Trace,
+    /// Interactive REPL bound to a persistent interpreter process. The
+    /// session block's `content` is its input log (appending sends each
+    /// completed line) and `language` names the interpreter; each
+    /// submission's output streams into a child `Repl` block opening with
+    /// the echoed input — `Role::User` when a person typed it (hydrated as
+    /// a user message), `Role::Tool` when an agent's `repl_send` did (the
+    /// tool result already carries it).
+    #[serde(rename = "repl")]
+    #[strum(serialize = "repl")]
+    Repl,
 }
 
 impl BlockKind {
@@ -1020,6 +1258,7 @@ impl BlockKind {
             BlockKind::Notification => "notification",
             BlockKind::Resource => "resource",
             BlockKind::Trace => "trace",
+            BlockKind::Repl => "repl",
         }
     }
 
@@ -1052,6 +1291,11 @@ impl BlockKind {
     pub fn is_resource(&self) -> bool {
         matches!(self, BlockKind::Resource)
     }
+
+    /// Check if this is a REPL block (session or output).
+    pub fn is_repl(&self) -> bool {
+        matches!(self, BlockKind::Repl)
+    }
 }
 
 impl std::fmt::Display for BlockKind {
@@ -1299,6 +1543,25 @@ pub struct BlockSnapshot {
     /// detection and use the declared type directly.
     #[serde(default)]
     pub content_type: ContentType,
+    /// Source language of the text (`rust`, `json`, `diff`) for syntax
+    /// highlighting and Markdown fencing; see [`crate::language`]. Set at
+    /// creation or auto-detected on tool output; no LWW clock — replicated
+    /// via `MetadataChanged` / snapshot like `stderr`. `None` when unknown.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Human-readable anchor (`design-decision-3`), unique within the
+    /// context and accepted anywhere a block id is; see
+    /// [`validate_block_label`]. Survives reordering and restructuring, unlike
+    /// positional keys. Replicated via `MetadataChanged` / snapshot like
+    /// `language`. `None` when unlabeled.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Preferred word wrap for the block's prose (see [`crate::reflow`]):
+    /// readers wrap their view to it and `block_reflow` rewrites the text.
+    /// Replicated via `MetadataChanged` / snapshot like `label`. `None` leaves
+    /// lines as written.
+    #[serde(default)]
+    pub reflow: Option<crate::Reflow>,
 
     // Ordering
     /// Fractional index for sibling ordering (base-62 lexicographic).
@@ -1333,6 +1596,25 @@ pub struct BlockSnapshot {
     #[serde(default)]
     pub track: Option<TrackId>,
 
+    /// Resolved `@name` mentions in a user block's text — principals by
+    /// username, contexts by label. Write-once at creation, like `track`;
+    /// empty on every other block. See [`crate::mention`].
+    #[serde(default)]
+    pub mentions: Vec<BlockMention>,
+
+    /// How a shell command block ran — argv, cwd, env overrides, and who
+    /// asked. Write-once at creation, like `mentions`; `None` on every other
+    /// block. See [`crate::shell_command`].
+    #[serde(default)]
+    pub shell_command: Option<ShellCommand>,
+
+    /// The hosting agent session that authored the block (agent, version,
+    /// session id, project dir). Write-once at creation, like
+    /// `shell_command`; `None` unless an agent-hosted MCP wrote it. See
+    /// [`crate::provenance`].
+    #[serde(default)]
+    pub provenance: Option<AgentProvenance>,
+
     // CRDT metadata
     /// Aggregate Lamport timestamp — `max(all per-field timestamps)`.
     /// Propagated during sync so receivers can advance their clocks.
@@ -1380,6 +1662,9 @@ pub struct BlockMetadata {
     pub ephemeral: bool,
     pub tool_use_id: Option<String>,
     pub stderr: Option<String>,
+    pub language: Option<String>,
+    pub label: Option<String>,
+    pub reflow: Option<crate::Reflow>,
 }
 
 impl BlockSnapshot {
@@ -1397,6 +1682,9 @@ impl BlockSnapshot {
             ephemeral: self.ephemeral,
             tool_use_id: self.tool_use_id.clone(),
             stderr: self.stderr.clone(),
+            language: self.language.clone(),
+            label: self.label.clone(),
+            reflow: self.reflow,
         }
     }
 
@@ -1441,9 +1729,15 @@ impl BlockSnapshot {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -1491,9 +1785,15 @@ impl BlockSnapshot {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -1553,9 +1853,15 @@ impl BlockSnapshot {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -1615,9 +1921,15 @@ impl BlockSnapshot {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -1681,9 +1993,15 @@ impl BlockSnapshot {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -1738,9 +2056,15 @@ impl BlockSnapshot {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -1793,9 +2117,15 @@ impl BlockSnapshot {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -1848,9 +2178,15 @@ impl BlockSnapshot {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -1899,9 +2235,15 @@ impl BlockSnapshot {
             notification: None,
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -1961,9 +2303,15 @@ impl BlockSnapshot {
             notification: None,
             resource: Some(payload),
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -2021,9 +2369,15 @@ impl BlockSnapshot {
             notification: Some(payload),
             resource: None,
             content_type: ContentType::Plain,
+            language: None,
+            label: None,
+            reflow: None,
             order_key: None,
             tick: None,
             track: None,
+            mentions: Vec::new(),
+            shell_command: None,
+            provenance: None,
             updated_at: 0,
             status_at: 0,
             collapsed_at: 0,
@@ -2088,6 +2442,9 @@ impl BlockSnapshot {
             && self.content_type == other.content_type
             && self.order_key == other.order_key
             && self.track == other.track
+            && self.mentions == other.mentions
+            && self.shell_command == other.shell_command
+            && self.provenance == other.provenance
     }
 }
 
@@ -2149,9 +2506,15 @@ impl BlockSnapshotBuilder {
                 notification: None,
                 resource: None,
                 content_type: ContentType::Plain,
+                language: None,
+                label: None,
+                reflow: None,
                 order_key: None,
                 tick: None,
                 track: None,
+                mentions: Vec::new(),
+                shell_command: None,
+                provenance: None,
                 updated_at: 0,
                 status_at: 0,
                 collapsed_at: 0,
@@ -2279,6 +2642,21 @@ impl BlockSnapshotBuilder {
         self
     }
 
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.snap.language = Some(language.into());
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.snap.label = Some(label.into());
+        self
+    }
+
+    pub fn reflow(mut self, reflow: crate::Reflow) -> Self {
+        self.snap.reflow = Some(reflow);
+        self
+    }
+
     pub fn ephemeral(mut self, ephemeral: bool) -> Self {
         self.snap.ephemeral = ephemeral;
         self
@@ -2297,6 +2675,21 @@ impl BlockSnapshotBuilder {
         self
     }
 
+    pub fn mentions(mut self, mentions: Vec<BlockMention>) -> Self {
+        self.snap.mentions = mentions;
+        self
+    }
+
+    pub fn shell_command(mut self, command: ShellCommand) -> Self {
+        self.snap.shell_command = Some(command);
+        self
+    }
+
+    pub fn provenance(mut self, provenance: AgentProvenance) -> Self {
+        self.snap.provenance = Some(provenance);
+        self
+    }
+
     pub fn order_key(mut self, key: impl Into<String>) -> Self {
         self.snap.order_key = Some(key.into());
         self
@@ -2449,6 +2842,13 @@ pub enum BlockFlowKind {
     /// A low-rate beat reference for a sink's continuous timebase (the metronome
     /// phasor). Also a directive — `matches_filter` bypasses it like `RenderCue`.
     BeatSync,
+    /// A notification for one principal's inbox. Bypasses `matches_filter`;
+    /// the bridge gates it on the connection's principal instead.
+    InboxPosted,
+    /// A context's model was switched.
+    ModelChanged,
+    /// A block was locked for editing or released.
+    LockChanged,
 }
 
 /// Server-side filter for block event subscriptions.
@@ -2582,6 +2982,96 @@ mod tests {
         assert!(display.ends_with("#5"));
     }
 
+    #[test]
+    fn test_resolve_block_prefix_forms() {
+        let ctx = test_context();
+        let (alice, bob) = (test_agent(), test_agent());
+        let a1 = BlockId::new(ctx, alice, 1);
+        let a12 = BlockId::new(ctx, alice, 12);
+        let b1 = BlockId::new(ctx, bob, 1);
+        let blocks = [a1, a12, b1];
+        let resolve = |q: &str| resolve_block_prefix(blocks, q);
+
+        // Full key passes through untouched.
+        assert_eq!(resolve(&a12.to_key()).unwrap(), a12);
+        // Display form, whole and with shorter id prefixes.
+        assert_eq!(resolve(&a1.to_string()).unwrap(), a1);
+        let short = |id: PrincipalId| id.short()[..6].to_string();
+        assert_eq!(resolve(&format!("@{}#1", short(bob))).unwrap(), b1);
+        assert_eq!(resolve(&format!("{}#12", short(alice))).unwrap(), a12);
+        assert_eq!(resolve("#12").unwrap(), a12);
+        // Key form with short ids, and a truncated key.
+        assert_eq!(
+            resolve(&format!("{}_{}_1", ctx.short(), alice.short())).unwrap(),
+            a1
+        );
+        let truncated = |id: PrincipalId| format!("{}_{}", ctx.to_hex(), &id.to_hex()[..20]);
+        assert_eq!(resolve(&truncated(bob)).unwrap(), b1);
+        assert!(matches!(
+            resolve(&truncated(alice)),
+            Err(crate::PrefixError::Ambiguous { .. })
+        ));
+
+        // Ambiguity lists resolvable candidates; misses say so.
+        match resolve("#1").unwrap_err() {
+            crate::PrefixError::Ambiguous { candidates, .. } => {
+                assert_eq!(candidates.len(), 2);
+                for c in candidates {
+                    assert!(blocks.contains(&resolve(&c).unwrap()));
+                }
+            }
+            other => panic!("expected ambiguity, got {other:?}"),
+        }
+        assert!(matches!(resolve("#99"), Err(crate::PrefixError::NoMatch(_))));
+    }
+
+    #[test]
+    fn test_resolve_block_ref_prefers_labels() {
+        let (ctx, other) = (test_context(), test_context());
+        let agent = test_agent();
+        let a1 = BlockId::new(ctx, agent, 1);
+        let a2 = BlockId::new(ctx, agent, 2);
+        let o1 = BlockId::new(other, agent, 1);
+        let blocks = [a1, a2, o1];
+        let labels = [("design-decision-3", a2), ("intro", a1), ("intro", o1)];
+        let resolve = |q: &str| resolve_block_ref(blocks, labels, q);
+
+        assert_eq!(resolve("design-decision-3").unwrap(), a2);
+        assert_eq!(resolve(&a1.to_key()).unwrap(), a1);
+        assert_eq!(resolve(&a2.to_string()).unwrap(), a2);
+        match resolve("intro").unwrap_err() {
+            crate::PrefixError::Ambiguous { candidates, .. } => {
+                assert_eq!(candidates.len(), 2);
+                assert!(candidates.contains(&o1.to_string()));
+            }
+            other => panic!("expected ambiguity, got {other:?}"),
+        }
+        assert!(matches!(resolve("nope"), Err(crate::PrefixError::NoMatch(_))));
+    }
+
+    #[test]
+    fn test_validate_block_label() {
+        for ok in ["intro", "design-decision-3", "v1.2_final", "A"] {
+            assert!(validate_block_label(ok).is_ok(), "{ok}");
+        }
+        for bad in ["", "3rd", "-x", "a b", "a#1", "a@b", "ctx:x"] {
+            assert!(validate_block_label(bad).is_err(), "{bad}");
+        }
+        assert!(validate_block_label(&"x".repeat(MAX_BLOCK_LABEL_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_block_query_may_match_context() {
+        let ctx = test_context();
+        let other = test_context();
+        let id = BlockId::new(ctx, test_agent(), 3);
+        assert!(BlockId::query_may_match_context(&id.to_key(), ctx));
+        assert!(!BlockId::query_may_match_context(&id.to_key(), other));
+        assert!(BlockId::query_may_match_context(&id.to_string(), ctx));
+        assert!(!BlockId::query_may_match_context(&id.to_string(), other));
+        assert!(BlockId::query_may_match_context("#3", other));
+    }
+
     #[test]
     fn test_block_id_serde_json_roundtrip() {
         let id = BlockId::new(test_context(), test_agent(), 42);
@@ -0,0 +1,103 @@
+//! Advisory block locks — "I'm editing this".
+//!
+//! A [`BlockLock`] marks one block as being edited by one principal until it
+//! expires. Locks are advisory: the CRDT merges concurrent writes either way.
+//! What a lock changes is what other writers hear — per the context's
+//! [`LockPolicy`], a write to a block someone else holds is either let
+//! through with a warning or held off until the lock is released or expires.
+//! The holder's own writes are never affected.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::BlockId;
+use crate::ids::PrincipalId;
+
+/// Lifetime of a lock taken without one.
+pub const DEFAULT_LOCK_TTL_SECS: u64 = 120;
+
+/// Longest lock accepted; renew to hold a block longer.
+pub const MAX_LOCK_TTL_SECS: u64 = 3600;
+
+/// One block marked as being edited.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockLock {
+    pub block_id: BlockId,
+    pub holder: PrincipalId,
+    /// The holder's username where known, else empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub holder_name: String,
+    /// What the holder is doing, for whoever runs into the lock.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    /// Unix millis.
+    pub acquired_at: u64,
+    /// Unix millis; the lock is gone from this instant.
+    pub expires_at: u64,
+}
+
+impl BlockLock {
+    pub fn is_expired(&self, now_millis: u64) -> bool {
+        now_millis >= self.expires_at
+    }
+
+    /// The holder as people read it: the username, else the id's short form.
+    pub fn holder_label(&self) -> String {
+        if self.holder_name.is_empty() {
+            self.holder.short()
+        } else {
+            self.holder_name.clone()
+        }
+    }
+
+    /// `amy (fixing the intro)`: the holder, with the note if there is one.
+    pub fn describe(&self) -> String {
+        if self.note.is_empty() {
+            self.holder_label()
+        } else {
+            format!("{} ({})", self.holder_label(), self.note)
+        }
+    }
+}
+
+/// What a context does with a write to a block another principal holds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockPolicy {
+    /// Apply it and tell the writer who holds the block.
+    #[default]
+    Warn,
+    /// Refuse it until the lock is released or expires.
+    Hold,
+}
+
+impl LockPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Warn => "warn",
+            Self::Hold => "hold",
+        }
+    }
+}
+
+impl fmt::Display for LockPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LockPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warn" => Ok(Self::Warn),
+            "hold" => Ok(Self::Hold),
+            other => Err(format!(
+                "unknown lock policy '{other}' (expected warn or hold)"
+            )),
+        }
+    }
+}
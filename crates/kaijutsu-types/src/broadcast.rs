@@ -0,0 +1,191 @@
+//! Broadcast prompts — one prompt, several contexts, answers side by side.
+//!
+//! `broadcastPrompt` inserts the same user block into each target context and
+//! runs a turn there, each on its own model, all at once. Every target moves
+//! `pending → running → done | failed` independently; as each answer lands
+//! it is appended to a fresh comparison context, so the responses can be read
+//! next to each other. Progress is polled with `getBroadcast`.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumString;
+
+use crate::block::BlockId;
+use crate::ids::ContextId;
+
+/// Most contexts one broadcast may address.
+pub const MAX_BROADCAST_TARGETS: usize = 16;
+
+/// One context a broadcast goes to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastTarget {
+    pub context_id: ContextId,
+    /// Model for this context's turn; `None` = the context's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Where one target's turn is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum BroadcastState {
+    /// Not started yet.
+    #[default]
+    Pending,
+    /// The prompt is in the context and its turn is streaming.
+    Running,
+    /// The turn finished with an answer.
+    Done,
+    /// The turn could not start, or ended without an answer.
+    Failed,
+}
+
+impl BroadcastState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Done => "done",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// Whether the target is finished, either way.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Done | Self::Failed)
+    }
+}
+
+impl fmt::Display for BroadcastState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Progress of one target.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastTargetStatus {
+    pub context_id: ContextId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub state: BroadcastState,
+    /// The answer block in the target context, once `Done`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_block: Option<BlockId>,
+    /// Why the target failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BroadcastTargetStatus {
+    pub fn pending(target: &BroadcastTarget) -> Self {
+        Self {
+            context_id: target.context_id,
+            model: target.model.clone(),
+            state: BroadcastState::Pending,
+            response_block: None,
+            error: None,
+        }
+    }
+}
+
+/// One broadcast and the progress of each of its targets.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastStatus {
+    /// Kernel-assigned, increasing; valid until the server restarts.
+    pub id: u64,
+    /// Where the answers are collected.
+    pub comparison_context_id: ContextId,
+    pub prompt: String,
+    pub targets: Vec<BroadcastTargetStatus>,
+    /// Unix millis.
+    pub started_at: u64,
+}
+
+impl BroadcastStatus {
+    /// Whether every target has finished.
+    pub fn is_finished(&self) -> bool {
+        self.targets.iter().all(|t| t.state.is_terminal())
+    }
+
+    /// How many targets are in `state`.
+    pub fn count(&self, state: BroadcastState) -> usize {
+        self.targets.iter().filter(|t| t.state == state).count()
+    }
+}
+
+/// Check a broadcast's targets before anything is sent: at least one, at
+/// most [`MAX_BROADCAST_TARGETS`], no context twice.
+pub fn validate_targets(targets: &[BroadcastTarget]) -> Result<(), String> {
+    if targets.is_empty() {
+        return Err("a broadcast needs at least one target context".to_string());
+    }
+    if targets.len() > MAX_BROADCAST_TARGETS {
+        return Err(format!(
+            "a broadcast may target at most {MAX_BROADCAST_TARGETS} contexts, got {}",
+            targets.len()
+        ));
+    }
+    for (i, target) in targets.iter().enumerate() {
+        if targets[..i]
+            .iter()
+            .any(|t| t.context_id == target.context_id)
+        {
+            return Err(format!(
+                "context {} is targeted twice",
+                target.context_id.short()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> BroadcastTarget {
+        BroadcastTarget {
+            context_id: ContextId::new(),
+            model: None,
+        }
+    }
+
+    #[test]
+    fn state_round_trips_and_knows_when_finished() {
+        for state in [
+            BroadcastState::Pending,
+            BroadcastState::Running,
+            BroadcastState::Done,
+            BroadcastState::Failed,
+        ] {
+            assert_eq!(state.as_str().parse::<BroadcastState>(), Ok(state));
+        }
+
+        let targets = [target(), target()];
+        let mut status = BroadcastStatus {
+            id: 1,
+            comparison_context_id: ContextId::new(),
+            prompt: "which is faster?".into(),
+            targets: targets.iter().map(BroadcastTargetStatus::pending).collect(),
+            started_at: 0,
+        };
+        assert!(!status.is_finished());
+        status.targets[0].state = BroadcastState::Done;
+        status.targets[1].state = BroadcastState::Failed;
+        assert!(status.is_finished());
+        assert_eq!(status.count(BroadcastState::Done), 1);
+    }
+
+    #[test]
+    fn validate_targets_rejects_empty_oversized_and_duplicate() {
+        assert!(validate_targets(&[]).is_err());
+        let many: Vec<_> = (0..=MAX_BROADCAST_TARGETS).map(|_| target()).collect();
+        assert!(validate_targets(&many).is_err());
+        let one = target();
+        assert!(validate_targets(&[one.clone(), target()]).is_ok());
+        assert!(validate_targets(&[one.clone(), one]).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Per-context execution budgets and the kernel-wide tool halt.
+//!
+//! A context with an [`ExecutionBudget`] may make at most
+//! `max_tool_calls_per_hour` broker tool calls and spend at most
+//! `max_shell_secs_per_hour` seconds of wall time in shell commands over a
+//! sliding [`BUDGET_WINDOW_SECS`] window. A call past either limit is refused
+//! before it runs; a shell command already running when the limit is crossed
+//! finishes. Budgets are set over RPC (`setExecutionBudget`) — never by the
+//! context itself — and a context without one is unlimited.
+//!
+//! The halt ([`ToolHalt`]) is the kill switch: while engaged, every tool call
+//! and shell command in the kernel is refused, whatever the budgets say.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ids::{ContextId, PrincipalId};
+
+/// Length of the sliding budget window.
+pub const BUDGET_WINDOW_SECS: u64 = 3600;
+
+/// Limits on a context's tool execution. `None` leaves that axis unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionBudget {
+    /// Broker tool calls per window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tool_calls_per_hour: Option<u32>,
+    /// Seconds of shell wall time per window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_shell_secs_per_hour: Option<u64>,
+}
+
+impl ExecutionBudget {
+    /// Whether no limit is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the budget before storing it. A zero limit would refuse
+    /// everything; the halt is the tool for that.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_tool_calls_per_hour == Some(0) {
+            return Err("max_tool_calls_per_hour must be greater than 0".to_string());
+        }
+        if self.max_shell_secs_per_hour == Some(0) {
+            return Err("max_shell_secs_per_hour must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The engaged kill switch.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolHalt {
+    pub reason: String,
+    /// Who engaged it.
+    pub by: PrincipalId,
+    /// Unix millis when it was engaged.
+    pub at: u64,
+}
+
+/// A context's budget and what it has used of it (`getBudgetStatus`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub context_id: ContextId,
+    /// `None` when the context has no budget (unlimited).
+    pub budget: Option<ExecutionBudget>,
+    /// Tool calls admitted in the current window.
+    pub tool_calls: u32,
+    /// Shell wall time spent in the current window, in whole seconds.
+    pub shell_secs: u64,
+    /// The kernel-wide halt, if engaged.
+    pub halt: Option<ToolHalt>,
+}
+
+impl BudgetStatus {
+    /// Tool calls left in the window, or `None` when unlimited.
+    pub fn tool_calls_remaining(&self) -> Option<u32> {
+        let max = self.budget?.max_tool_calls_per_hour?;
+        Some(max.saturating_sub(self.tool_calls))
+    }
+
+    /// Shell seconds left in the window, or `None` when unlimited.
+    pub fn shell_secs_remaining(&self) -> Option<u64> {
+        let max = self.budget?.max_shell_secs_per_hour?;
+        Some(max.saturating_sub(self.shell_secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_limits_are_rejected() {
+        assert!(ExecutionBudget::default().validate().is_ok());
+        let calls = ExecutionBudget {
+            max_tool_calls_per_hour: Some(0),
+            ..Default::default()
+        };
+        assert!(calls.validate().is_err());
+        let shell = ExecutionBudget {
+            max_shell_secs_per_hour: Some(0),
+            ..Default::default()
+        };
+        assert!(shell.validate().is_err());
+    }
+
+    #[test]
+    fn remaining_saturates_and_is_none_when_unlimited() {
+        let mut status = BudgetStatus {
+            context_id: ContextId::new(),
+            budget: None,
+            tool_calls: 12,
+            shell_secs: 90,
+            halt: None,
+        };
+        assert_eq!(status.tool_calls_remaining(), None);
+        status.budget = Some(ExecutionBudget {
+            max_tool_calls_per_hour: Some(10),
+            max_shell_secs_per_hour: Some(120),
+        });
+        assert_eq!(status.tool_calls_remaining(), Some(0));
+        assert_eq!(status.shell_secs_remaining(), Some(30));
+    }
+}
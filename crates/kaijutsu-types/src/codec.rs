@@ -1,11 +1,30 @@
-//! Central, versioned CBOR codec for Kaijutsu.
+//! Central, versioned codec for Kaijutsu.
 //!
 //! Every encoded buffer begins with a single format byte so the on-disk and
-//! on-wire representation can evolve. `FORMAT_V1` is CBOR via `ciborium`.
+//! on-wire representation can evolve. `FORMAT_V1` is CBOR via `ciborium` —
+//! compact and fast, and what everything writes by default. `FORMAT_JSON` is
+//! `serde_json`, for reading ops and payloads off the wire while debugging.
+//!
+//! [`encode`] always writes CBOR: it is what kernel.db snapshots and oplog
+//! rows go through. Only [`encode_wire`], used for the ops the server pushes
+//! to clients in block events, honours [`set_wire_format`].
+//!
+//! Because the format byte travels with every buffer, [`decode`] accepts
+//! either format regardless of what the reader itself writes: a server run
+//! with `--wire-format json` talks to CBOR clients without negotiation.
+
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 /// Format byte for version 1: CBOR (ciborium) payload.
 const FORMAT_V1: u8 = 1;
 
+/// Format byte for JSON (serde_json) payload.
+const FORMAT_JSON: u8 = 2;
+
+/// Format [`encode_wire`] writes; set once at startup via [`set_wire_format`].
+static WIRE_FORMAT: AtomicU8 = AtomicU8::new(FORMAT_V1);
+
 /// Errors produced while encoding or decoding through the central codec.
 #[derive(Debug, thiserror::Error)]
 pub enum CodecError {
@@ -13,26 +32,116 @@ pub enum CodecError {
     Encode(String),
     #[error("cbor decode: {0}")]
     Decode(String),
+    #[error("json encode: {0}")]
+    JsonEncode(String),
+    #[error("json decode: {0}")]
+    JsonDecode(String),
     #[error("unknown serialization format byte: {0}")]
     UnknownFormat(u8),
     #[error("empty buffer")]
     Empty,
 }
 
-/// Encode `value` as a versioned CBOR buffer: a `FORMAT_V1` byte followed by
-/// the ciborium-encoded payload.
+/// Payload encoding selected by the leading format byte.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WireFormat {
+    /// CBOR — the default.
+    #[default]
+    Cbor,
+    /// JSON — larger and slower, but readable in a packet dump or log.
+    Json,
+}
+
+impl WireFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WireFormat::Cbor => "cbor",
+            WireFormat::Json => "json",
+        }
+    }
+
+    fn byte(self) -> u8 {
+        match self {
+            WireFormat::Cbor => FORMAT_V1,
+            WireFormat::Json => FORMAT_JSON,
+        }
+    }
+}
+
+impl std::fmt::Display for WireFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WireFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cbor" | "binary" => Ok(WireFormat::Cbor),
+            "json" => Ok(WireFormat::Json),
+            other => Err(format!("unknown wire format '{other}' (expected cbor or json)")),
+        }
+    }
+}
+
+/// Set the format [`encode_wire`] writes for the rest of the process.
+/// Storage encodes through [`encode`] are unaffected.
+pub fn set_wire_format(format: WireFormat) {
+    WIRE_FORMAT.store(format.byte(), Ordering::Relaxed);
+}
+
+/// The format [`encode_wire`] currently writes.
+pub fn wire_format() -> WireFormat {
+    match WIRE_FORMAT.load(Ordering::Relaxed) {
+        FORMAT_JSON => WireFormat::Json,
+        _ => WireFormat::Cbor,
+    }
+}
+
+/// Encode `value` as a versioned CBOR buffer: a format byte followed by
+/// the payload. Everything persisted goes through here.
 pub fn encode<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, CodecError> {
-    let mut buf = vec![FORMAT_V1];
-    ciborium::into_writer(value, &mut buf).map_err(|e| CodecError::Encode(e.to_string()))?;
+    encode_as(WireFormat::Cbor, value)
+}
+
+/// Encode `value` for pushing to clients, in the process [`wire_format`]
+/// (CBOR unless overridden). Never use this for anything written to disk.
+pub fn encode_wire<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, CodecError> {
+    encode_as(wire_format(), value)
+}
+
+/// Encode `value` in an explicit `format`, ignoring the process default.
+///
+/// JSON can't represent everything CBOR can (maps with non-string keys are
+/// the usual culprit); such values are a [`CodecError::JsonEncode`], not a
+/// quiet switch to CBOR.
+pub fn encode_as<T: serde::Serialize>(
+    format: WireFormat,
+    value: &T,
+) -> Result<Vec<u8>, CodecError> {
+    let mut buf = vec![format.byte()];
+    match format {
+        WireFormat::Cbor => {
+            ciborium::into_writer(value, &mut buf).map_err(|e| CodecError::Encode(e.to_string()))?
+        }
+        WireFormat::Json => serde_json::to_writer(&mut buf, value)
+            .map_err(|e| CodecError::JsonEncode(e.to_string()))?,
+    }
     Ok(buf)
 }
 
-/// Decode a versioned CBOR buffer produced by [`encode`].
+/// Decode a versioned buffer produced by [`encode`] or [`encode_as`], in
+/// whichever format its first byte names.
 pub fn decode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError> {
     match bytes.split_first() {
         Some((&FORMAT_V1, rest)) => {
             ciborium::from_reader(rest).map_err(|e| CodecError::Decode(e.to_string()))
         }
+        Some((&FORMAT_JSON, rest)) => {
+            serde_json::from_slice(rest).map_err(|e| CodecError::JsonDecode(e.to_string()))
+        }
         Some((&other, _)) => Err(CodecError::UnknownFormat(other)),
         None => Err(CodecError::Empty),
     }
@@ -60,9 +169,36 @@ mod tests {
 
     #[test]
     fn unknown_format_byte() {
-        let buf = [0x02_u8, 0x00, 0x00];
+        let buf = [0x7f_u8, 0x00, 0x00];
         let err = decode::<Vec<u8>>(&buf).expect_err("should reject unknown format");
-        assert!(matches!(err, CodecError::UnknownFormat(2)));
+        assert!(matches!(err, CodecError::UnknownFormat(0x7f)));
+    }
+
+    #[test]
+    fn json_round_trip_and_readable() {
+        let value: (String, u64) = ("hello".to_string(), 42_u64);
+        let encoded = encode_as(WireFormat::Json, &value).expect("encode");
+        assert_eq!(encoded[0], FORMAT_JSON);
+        assert_eq!(&encoded[1..], br#"["hello",42]"#);
+        let decoded: (String, u64) = decode(&encoded).expect("decode");
+        assert_eq!(value, decoded);
+    }
+
+    #[test]
+    fn json_rejects_non_string_keys() {
+        type Pairs = std::collections::BTreeMap<(u64, u64), u64>;
+        let value: Pairs = [((1, 2), 3)].into();
+        let err = encode_as(WireFormat::Json, &value).expect_err("json can't key on tuples");
+        assert!(matches!(err, CodecError::JsonEncode(_)));
+        let encoded = encode(&value).expect("cbor encode");
+        assert_eq!(encoded[0], FORMAT_V1);
+    }
+
+    #[test]
+    fn wire_format_parses() {
+        assert_eq!("json".parse::<WireFormat>(), Ok(WireFormat::Json));
+        assert_eq!("Binary".parse::<WireFormat>(), Ok(WireFormat::Cbor));
+        assert!("bincode".parse::<WireFormat>().is_err());
     }
 
     #[test]
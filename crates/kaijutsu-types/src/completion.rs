@@ -0,0 +1,100 @@
+//! Prompt completion: which token the cursor is in and what it could become.
+//!
+//! The app asks the kernel for completions of the token under the cursor; the
+//! kernel answers with whole replacements for that token. Both sides find the
+//! token with [`token_at`] so the client knows exactly which bytes a
+//! completion replaces.
+//!
+//! | Token            | Completes to                                   |
+//! |------------------|------------------------------------------------|
+//! | `@name`          | seat usernames and context labels              |
+//! | `/verb` (first)  | `kj` subcommands                               |
+//! | `#ref`           | block keys in the current context              |
+//! | `a/b`, `./`, `/x`| VFS paths, relative to the context's cwd       |
+
+use std::ops::Range;
+
+/// What the token under the cursor is asking for. Each variant carries the
+/// text typed so far, without its sigil.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionToken<'a> {
+    Mention(&'a str),
+    Command(&'a str),
+    Block(&'a str),
+    Path(&'a str),
+}
+
+/// The token ending at byte `cursor`: the run of non-whitespace before it.
+/// Returns its byte range and classification, or `None` when the cursor is
+/// not in something completable. A cursor past the end or inside a char is
+/// clamped back to a boundary.
+pub fn token_at(text: &str, cursor: usize) -> Option<(Range<usize>, CompletionToken<'_>)> {
+    let mut cursor = cursor.min(text.len());
+    while !text.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+    let start = text[..cursor]
+        .rfind(char::is_whitespace)
+        .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let token = &text[start..cursor];
+
+    let kind = if let Some(rest) = token.strip_prefix('@') {
+        CompletionToken::Mention(rest)
+    } else if let Some(rest) = token.strip_prefix('#') {
+        CompletionToken::Block(rest)
+    } else if let Some(rest) = token.strip_prefix('/')
+        && text[..start].trim().is_empty()
+        && !rest.contains('/')
+    {
+        CompletionToken::Command(rest)
+    } else if token.contains('/') {
+        CompletionToken::Path(token)
+    } else {
+        return None;
+    };
+    Some((start..cursor, kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sigils_classify_the_token() {
+        assert_eq!(
+            token_at("hey @am", 7),
+            Some((4..7, CompletionToken::Mention("am")))
+        );
+        assert_eq!(token_at("see #12", 7), Some((4..7, CompletionToken::Block("12"))));
+        assert_eq!(token_at("  /con", 6), Some((2..6, CompletionToken::Command("con"))));
+        assert_eq!(
+            token_at("cat src/ma", 10),
+            Some((4..10, CompletionToken::Path("src/ma")))
+        );
+        assert_eq!(token_at("plain word", 10), None);
+    }
+
+    #[test]
+    fn slash_is_a_command_only_at_the_start() {
+        assert_eq!(
+            token_at("run /usr", 8),
+            Some((4..8, CompletionToken::Path("/usr")))
+        );
+        assert_eq!(
+            token_at("/usr/lo", 7),
+            Some((0..7, CompletionToken::Path("/usr/lo")))
+        );
+    }
+
+    #[test]
+    fn cursor_mid_text_and_off_boundary() {
+        // Only the part before the cursor counts.
+        assert_eq!(
+            token_at("@amy later", 3),
+            Some((0..3, CompletionToken::Mention("am")))
+        );
+        assert_eq!(token_at("é @x", 1), None);
+        assert_eq!(token_at("@x", 99), Some((0..2, CompletionToken::Mention("x"))));
+    }
+}
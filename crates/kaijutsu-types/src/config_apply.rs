@@ -0,0 +1,84 @@
+//! Live-applied config changes.
+//!
+//! When a config file under `/etc/config` is rewritten (`kj config
+//! set/edit/reset`), the kernel re-reads it and applies what it can without
+//! a restart. The outcome is a [`ConfigApplyReport`]: each change it took and
+//! each one it refused, with the reason. The report is returned to the writer
+//! and pushed to every connected client.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// One change found between the running config and the written file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    /// What changed: `provider anthropic`, `default_provider`, `alias fast`,
+    /// or the whole file.
+    pub item: String,
+    /// How it changed (`added`, `removed`, `"a" → "b"`), or why it was
+    /// refused.
+    pub detail: String,
+}
+
+impl ConfigChange {
+    pub fn new(item: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            item: item.into(),
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.item, self.detail)
+    }
+}
+
+/// The result of applying one rewritten config file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigApplyReport {
+    /// Canonical path of the file (`/etc/config/models.toml`).
+    pub path: String,
+    /// Changes now in effect.
+    pub applied: Vec<ConfigChange>,
+    /// Changes that were not applied; the running config keeps its old value.
+    pub rejected: Vec<ConfigChange>,
+}
+
+impl ConfigApplyReport {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Nothing differed from the running config.
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.rejected.is_empty()
+    }
+}
+
+impl fmt::Display for ConfigApplyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "{}: no changes to apply", self.path);
+        }
+        write!(
+            f,
+            "{}: {} applied, {} rejected",
+            self.path,
+            self.applied.len(),
+            self.rejected.len()
+        )?;
+        for change in &self.applied {
+            write!(f, "\n  + {change}")?;
+        }
+        for change in &self.rejected {
+            write!(f, "\n  ! {change}")?;
+        }
+        Ok(())
+    }
+}
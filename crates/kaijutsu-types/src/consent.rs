@@ -0,0 +1,197 @@
+//! Consent audit log entries.
+//!
+//! Every consent decision the kernel makes — an MCP tool call let through or
+//! refused at the broker gate, a destructive `kj` command confirmed with its
+//! latch nonce — is appended to a per-kernel log. Entries are hash-chained
+//! (each `hash` covers the previous entry's) and signed with a kernel-held
+//! key, so an exported log can be checked for gaps, edits and reordering by
+//! the kernel that wrote it.
+//!
+//! In collaborative mode some tool calls wait on a human instead: a
+//! [`ConsentPrompt`] goes to the principal driving the turn, who answers it
+//! with a [`ConsentDecision`].
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumString;
+
+use crate::enums::ConsentMode;
+use crate::ids::{ContextId, PrincipalId};
+
+/// Outcome of a consent decision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum ConsentVerdict {
+    Approved,
+    Denied,
+}
+
+impl ConsentVerdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approved => "approved",
+            Self::Denied => "denied",
+        }
+    }
+}
+
+impl fmt::Display for ConsentVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One decision in the consent log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsentEntry {
+    /// Position in the log, from 1, without gaps.
+    pub seq: u64,
+    /// Unix millis.
+    pub at: u64,
+    /// Who asked (the caller of the tool, the confirmer of the latch).
+    pub principal_id: PrincipalId,
+    pub context_id: Option<ContextId>,
+    /// What was decided on: `mcp <instance>/<tool>`, `tool <name>` (a
+    /// prompted model call) or `kj <command>`.
+    pub action: String,
+    /// SHA-256 (hex) of the call's parameters, so the log proves which
+    /// arguments were approved without storing them.
+    pub params_hash: String,
+    pub verdict: ConsentVerdict,
+    /// What decided: `binding`, `capability`, `hook:<id>`, `latch`, or
+    /// `prompt` (a human answered a [`ConsentPrompt`]).
+    pub basis: String,
+    /// The kernel's consent mode at the time.
+    pub mode: ConsentMode,
+    /// `hash` of the previous entry (empty for the first).
+    pub prev_hash: String,
+    /// SHA-256 (hex) over this entry's fields and `prev_hash`.
+    pub hash: String,
+    /// HMAC-SHA256 (hex) of `hash` under the kernel's log key.
+    pub signature: String,
+}
+
+impl ConsentEntry {
+    /// The bytes `hash` is computed over: every field but `hash` and
+    /// `signature`, newline-separated in declaration order.
+    pub fn canonical(&self) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            self.seq,
+            self.at,
+            self.principal_id.to_hex(),
+            self.context_id.map(|c| c.to_hex()).unwrap_or_default(),
+            self.action,
+            self.params_hash,
+            self.verdict,
+            self.basis,
+            self.mode,
+            self.prev_hash,
+        )
+    }
+}
+
+/// A human's answer to a [`ConsentPrompt`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum ConsentDecision {
+    /// Run this call.
+    Approve,
+    /// Refuse this call; the model is told it was declined.
+    Deny,
+    /// Run this call and every later call of the same tool in the same
+    /// context, by the same principal in the same session, without asking,
+    /// until the server restarts.
+    ApproveForSession,
+}
+
+impl ConsentDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approve => "approve",
+            Self::Deny => "deny",
+            Self::ApproveForSession => "approve_for_session",
+        }
+    }
+
+    /// How the decision is recorded in the consent log.
+    pub fn verdict(&self) -> ConsentVerdict {
+        match self {
+            Self::Approve | Self::ApproveForSession => ConsentVerdict::Approved,
+            Self::Deny => ConsentVerdict::Denied,
+        }
+    }
+}
+
+impl fmt::Display for ConsentDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A model's tool call held until a human approves or denies it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsentPrompt {
+    /// Kernel-assigned, unique until the server restarts.
+    pub id: u64,
+    pub context_id: ContextId,
+    /// Who is asked: the principal driving the turn.
+    pub principal_id: PrincipalId,
+    /// The tool as the model named it.
+    pub tool: String,
+    /// What the call would do: the command for a shell call, a unified diff
+    /// for an edit or write, else the arguments as JSON. Capped in length.
+    pub preview: String,
+    /// Unix millis.
+    pub requested_at: u64,
+    /// Unix millis after which the call is refused unanswered.
+    pub expires_at: u64,
+}
+
+/// Result of checking a log's chain and signatures.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsentVerification {
+    /// Entries checked.
+    pub checked: u64,
+    /// First entry that failed, with the reason; `None` when all passed.
+    pub broken_at: Option<(u64, String)>,
+}
+
+impl ConsentVerification {
+    pub fn is_valid(&self) -> bool {
+        self.broken_at.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn verdict_as_str_roundtrip() {
+        for v in [ConsentVerdict::Approved, ConsentVerdict::Denied] {
+            assert_eq!(ConsentVerdict::from_str(v.as_str()).unwrap(), v);
+            assert_eq!(serde_json::to_string(&v).unwrap(), format!("\"{v}\""));
+        }
+    }
+
+    #[test]
+    fn decision_as_str_roundtrip() {
+        for d in [
+            ConsentDecision::Approve,
+            ConsentDecision::Deny,
+            ConsentDecision::ApproveForSession,
+        ] {
+            assert_eq!(ConsentDecision::from_str(d.as_str()).unwrap(), d);
+            assert_eq!(serde_json::to_string(&d).unwrap(), format!("\"{d}\""));
+        }
+        assert_eq!(
+            ConsentDecision::ApproveForSession.verdict(),
+            ConsentVerdict::Approved
+        );
+    }
+}
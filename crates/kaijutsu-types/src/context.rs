@@ -6,7 +6,8 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::ids::{ContextId, PrincipalId};
+use crate::block::BlockId;
+use crate::ids::{ContextId, PrincipalId, ReservationId};
 
 /// Seats per time-well ring — the canonical "10". Ring 0 renders exactly
 /// this many slots, addressed by digit hotkeys 0-9, and the kernel refuses
@@ -88,6 +89,386 @@ pub fn fork_lineage(contexts: &[Context], start: ContextId) -> Vec<&Context> {
     chain
 }
 
+// ============================================================================
+// Context statistics
+// ============================================================================
+
+/// Per-principal activity within one context: how many blocks a principal
+/// authored and when the most recent one was created.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrincipalActivity {
+    pub principal_id: PrincipalId,
+    pub block_count: u64,
+    /// Unix millis of the principal's newest block.
+    pub last_activity_at: u64,
+}
+
+/// Aggregated statistics for one context — the `getContextStats` RPC payload
+/// and the MCP `context_info` tool's answer.
+///
+/// Block tallies come from [`ContextStats::tally`] over the document's
+/// snapshots; the metadata half (model, consent, drift edges, pending
+/// approvals) is stamped by the kernel from its DB row and drift router.
+/// Counts are keyed by the stable `as_str()` names so the JSON shape never
+/// depends on enum discriminants.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextStats {
+    pub context_id: Option<ContextId>,
+    pub label: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// `ConsentMode::as_str()` — `None` when the context has no DB row.
+    pub consent_mode: Option<String>,
+    /// `ContextState::as_str()` — `None` when the context has no DB row.
+    pub context_state: Option<String>,
+    pub block_count: u64,
+    /// Sum of block content lengths in bytes (stderr included).
+    pub total_bytes: u64,
+    pub by_kind: std::collections::BTreeMap<String, u64>,
+    pub by_role: std::collections::BTreeMap<String, u64>,
+    /// Newest-first by `last_activity_at`.
+    pub principals: Vec<PrincipalActivity>,
+    /// Drift edges landing in this context.
+    pub drift_in: u64,
+    /// Drift edges leaving this context.
+    pub drift_out: u64,
+    /// Staged drifts targeting this context that still await a commit — the
+    /// collaborative-consent approval queue.
+    pub pending_approvals: u64,
+    /// Unix millis of the newest block, 0 when the context is empty.
+    pub last_activity_at: u64,
+}
+
+impl ContextStats {
+    /// Tally block counts, bytes, and per-principal activity from a context's
+    /// snapshots. Metadata fields stay at their defaults for the caller to fill.
+    pub fn tally<'a>(blocks: impl IntoIterator<Item = &'a crate::BlockSnapshot>) -> Self {
+        let mut stats = Self::default();
+        let mut principals: std::collections::HashMap<PrincipalId, PrincipalActivity> =
+            std::collections::HashMap::new();
+        for block in blocks {
+            stats.block_count += 1;
+            stats.total_bytes += block.content.len() as u64
+                + block.stderr.as_ref().map_or(0, |s| s.len() as u64);
+            *stats.by_kind.entry(block.kind.as_str().to_string()).or_default() += 1;
+            *stats.by_role.entry(block.role.as_str().to_string()).or_default() += 1;
+            stats.last_activity_at = stats.last_activity_at.max(block.created_at);
+
+            let entry = principals
+                .entry(block.id.principal_id)
+                .or_insert(PrincipalActivity {
+                    principal_id: block.id.principal_id,
+                    block_count: 0,
+                    last_activity_at: 0,
+                });
+            entry.block_count += 1;
+            entry.last_activity_at = entry.last_activity_at.max(block.created_at);
+        }
+        stats.principals = principals.into_values().collect();
+        stats
+            .principals
+            .sort_by(|a, b| b.last_activity_at.cmp(&a.last_activity_at));
+        stats
+    }
+}
+
+/// Live activity for one context over the kernel's sliding window — the
+/// `subscribeActivity` push payload. Absolute readings of "now", not deltas:
+/// each push is the complete set of non-idle contexts, and a context missing
+/// from it is idle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextActivity {
+    pub context_id: ContextId,
+    /// Block events (inserts, text ops, status/output/metadata changes) in
+    /// the last [`ACTIVITY_WINDOW_SECS`], scaled to a per-minute rate.
+    pub ops_per_minute: u32,
+    /// Blocks currently `Running` — live model streams and shell executions.
+    pub active_streams: u32,
+    /// Blocks that went to `Error` within the window.
+    pub errors: u32,
+}
+
+/// Width of the sliding window behind [`ContextActivity`]'s rates.
+pub const ACTIVITY_WINDOW_SECS: u32 = 60;
+
+impl ContextActivity {
+    /// Nothing happening and nothing running.
+    pub fn is_idle(&self) -> bool {
+        self.ops_per_minute == 0 && self.active_streams == 0 && self.errors == 0
+    }
+}
+
+/// One thing the kernel's health watchdog found wrong with a context.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum HealthIssue {
+    /// A block has sat in `Running` for `running_secs` — a stream or shell
+    /// execution that never finished.
+    StuckRunning {
+        block_id: BlockId,
+        running_secs: u64,
+    },
+    /// A model block is `Running` but the context has had no block events
+    /// for `silent_secs` — the LLM stream stalled.
+    SilentStream { block_id: BlockId, silent_secs: u64 },
+    /// The last `count` tool results in the context all failed; `last_block`
+    /// is the most recent.
+    ToolFailures { count: u32, last_block: BlockId },
+}
+
+impl HealthIssue {
+    /// Stable snake_case name, for display and logs.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::StuckRunning { .. } => "stuck_running",
+            Self::SilentStream { .. } => "silent_stream",
+            Self::ToolFailures { .. } => "tool_failures",
+        }
+    }
+
+    /// The block the issue is about.
+    pub fn block_id(&self) -> BlockId {
+        match self {
+            Self::StuckRunning { block_id, .. } | Self::SilentStream { block_id, .. } => *block_id,
+            Self::ToolFailures { last_block, .. } => *last_block,
+        }
+    }
+
+    /// Same kind of problem with the same block — ignores the growing
+    /// durations, so a push channel can tell a new finding from an old one.
+    pub fn same_finding(&self, other: &Self) -> bool {
+        self.code() == other.code() && self.block_id() == other.block_id()
+    }
+}
+
+/// A context the health watchdog considers unhealthy, with what it found.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextHealth {
+    pub context_id: ContextId,
+    pub issues: Vec<HealthIssue>,
+}
+
+/// What `contextRecover` did to a context.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextRecovery {
+    /// A live LLM stream was found and hard-interrupted.
+    pub interrupted: bool,
+    /// Blocks left `Running` with no producer, now marked `Error`.
+    pub marked: Vec<BlockId>,
+}
+
+// ============================================================================
+// Context names
+// ============================================================================
+
+/// The form two context labels are compared in for uniqueness: lowercase,
+/// with each run of whitespace, `-`, `_`, and `.` folded to one `-` and the
+/// ends trimmed. `Bug Hunt`, `bug-hunt`, and `bug_hunt.` are one name.
+pub fn context_label_key(label: &str) -> String {
+    let mut key = String::with_capacity(label.len());
+    let mut gap = false;
+    for c in label.chars() {
+        if c.is_whitespace() || matches!(c, '-' | '_' | '.') {
+            gap = true;
+            continue;
+        }
+        if gap && !key.is_empty() {
+            key.push('-');
+        }
+        gap = false;
+        key.extend(c.to_lowercase());
+    }
+    key
+}
+
+/// A context name held by `reserveContextName` until it is committed
+/// (creating the context) or released, or lapses at `expires_at`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextNameReservation {
+    /// Redeems the reservation; `None` when the name is taken.
+    pub token: Option<ReservationId>,
+    pub label: String,
+    /// Unix millis; zero when the name is taken.
+    pub expires_at: u64,
+    /// Free alternatives to a taken name.
+    pub suggestions: Vec<String>,
+}
+
+impl ContextNameReservation {
+    pub fn is_reserved(&self) -> bool {
+        self.token.is_some()
+    }
+}
+
+// ============================================================================
+// Listing queries
+// ============================================================================
+
+/// Server-side filter + page for `listContexts`.
+///
+/// The default query matches everything — the unfiltered listing older
+/// clients still get. Filters AND together; `offset`/`limit` apply after
+/// filtering, in the kernel's listing order (oldest first).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextListQuery {
+    /// Keep contexts whose label starts with this. Unlabeled contexts never match.
+    pub label_prefix: Option<String>,
+    /// Keep contexts active at or after this Unix-millis instant (last block
+    /// activity, falling back to creation time).
+    pub active_since: Option<u64>,
+    /// Keep contexts created by this principal.
+    pub owner: Option<PrincipalId>,
+    /// Entries to skip after filtering.
+    pub offset: u32,
+    /// Page size. `None` = no limit.
+    pub limit: Option<u32>,
+}
+
+impl ContextListQuery {
+    pub fn with_label_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.label_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn with_active_since(mut self, millis: u64) -> Self {
+        self.active_since = Some(millis);
+        self
+    }
+
+    pub fn with_owner(mut self, owner: PrincipalId) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    pub fn with_page(mut self, offset: u32, limit: u32) -> Self {
+        self.offset = offset;
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether a context passes the filters. `created_by` is `None` when the
+    /// kernel has no DB row for it (such a context never matches an owner filter).
+    pub fn matches(
+        &self,
+        label: Option<&str>,
+        created_by: Option<PrincipalId>,
+        last_activity_at: u64,
+    ) -> bool {
+        if let Some(prefix) = &self.label_prefix
+            && !label.is_some_and(|l| l.starts_with(prefix.as_str()))
+        {
+            return false;
+        }
+        if let Some(since) = self.active_since
+            && last_activity_at < since
+        {
+            return false;
+        }
+        if let Some(owner) = self.owner
+            && created_by != Some(owner)
+        {
+            return false;
+        }
+        true
+    }
+
+    /// Cut a filtered listing down to this query's page.
+    pub fn paginate<T>(&self, items: Vec<T>) -> Vec<T> {
+        paginate(items, self.offset, self.limit)
+    }
+}
+
+/// Skip `offset` entries and keep at most `limit` (`None` = the rest).
+pub(crate) fn paginate<T>(items: Vec<T>, offset: u32, limit: Option<u32>) -> Vec<T> {
+    let iter = items.into_iter().skip(offset as usize);
+    match limit {
+        Some(limit) => iter.take(limit as usize).collect(),
+        None => iter.collect(),
+    }
+}
+
+/// Selection for `closeContexts`: which open contexts to archive in one call.
+///
+/// Filters AND together. The default filter is empty and matches nothing —
+/// bulk-closing every context must be asked for with at least one filter,
+/// never fallen into. Ring-0 (promoted) contexts are hand-placed seats and
+/// are skipped unless `include_promoted` is set.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextCloseFilter {
+    /// Close contexts whose label starts with this. Unlabeled contexts never match.
+    pub label_prefix: Option<String>,
+    /// Close direct forks of this context.
+    pub forked_from: Option<ContextId>,
+    /// Close contexts idle since before this Unix-millis instant (last block
+    /// activity, falling back to creation time).
+    pub idle_before: Option<u64>,
+    /// Close only concluded contexts.
+    pub concluded_only: bool,
+    /// Also close contexts holding a ring-0 seat.
+    pub include_promoted: bool,
+}
+
+impl ContextCloseFilter {
+    pub fn with_label_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.label_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn with_forked_from(mut self, parent: ContextId) -> Self {
+        self.forked_from = Some(parent);
+        self
+    }
+
+    pub fn with_idle_before(mut self, millis: u64) -> Self {
+        self.idle_before = Some(millis);
+        self
+    }
+
+    pub fn concluded_only(mut self) -> Self {
+        self.concluded_only = true;
+        self
+    }
+
+    /// True when no selecting filter is set — such a filter closes nothing.
+    /// `include_promoted` widens a selection but doesn't make one.
+    pub fn is_empty(&self) -> bool {
+        self.label_prefix.is_none()
+            && self.forked_from.is_none()
+            && self.idle_before.is_none()
+            && !self.concluded_only
+    }
+
+    /// Whether an open context is selected. Always false for an empty filter.
+    pub fn matches(
+        &self,
+        label: Option<&str>,
+        forked_from: Option<ContextId>,
+        last_activity_at: u64,
+        concluded: bool,
+        promoted: bool,
+    ) -> bool {
+        if self.is_empty() || (promoted && !self.include_promoted) {
+            return false;
+        }
+        if let Some(prefix) = &self.label_prefix
+            && !label.is_some_and(|l| l.starts_with(prefix.as_str()))
+        {
+            return false;
+        }
+        if let Some(parent) = self.forked_from
+            && forked_from != Some(parent)
+        {
+            return false;
+        }
+        if let Some(before) = self.idle_before
+            && last_activity_at >= before
+        {
+            return false;
+        }
+        !self.concluded_only || concluded
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -133,6 +514,15 @@ mod tests {
         assert_eq!(info.display_name().len(), 8); // short hex
     }
 
+    #[test]
+    fn label_key_folds_case_and_separators() {
+        assert_eq!(context_label_key("Bug Hunt"), "bug-hunt");
+        assert_eq!(context_label_key("bug_hunt."), "bug-hunt");
+        assert_eq!(context_label_key(" --bug  hunt"), "bug-hunt");
+        assert_eq!(context_label_key("lost+found"), "lost+found");
+        assert_ne!(context_label_key("bughunt"), context_label_key("bug-hunt"));
+    }
+
     #[test]
     fn test_fork_lineage() {
         let creator = PrincipalId::new();
@@ -205,4 +595,110 @@ mod tests {
         assert_eq!(chain[0].id, a.id);
         assert_eq!(chain[1].id, b.id);
     }
+
+    #[test]
+    fn test_context_stats_tally() {
+        use crate::{BlockId, BlockSnapshot, Role};
+
+        let ctx = ContextId::new();
+        let amy = PrincipalId::new();
+        let model = PrincipalId::new();
+
+        let mut first = BlockSnapshot::text(BlockId::new(ctx, amy, 1), None, Role::User, "hello");
+        first.created_at = 100;
+        let mut reply =
+            BlockSnapshot::text(BlockId::new(ctx, model, 1), None, Role::Model, "hi there");
+        reply.created_at = 200;
+        let mut again = BlockSnapshot::text(BlockId::new(ctx, amy, 2), None, Role::User, "ok");
+        again.created_at = 300;
+
+        let stats = ContextStats::tally(&[first, reply, again]);
+        assert_eq!(stats.block_count, 3);
+        assert_eq!(stats.total_bytes, 5 + 8 + 2);
+        assert_eq!(stats.by_kind.get("text"), Some(&3));
+        assert_eq!(stats.by_role.get("user"), Some(&2));
+        assert_eq!(stats.by_role.get("model"), Some(&1));
+        assert_eq!(stats.last_activity_at, 300);
+
+        // Newest-first: amy's last block (300) outranks the model's (200).
+        assert_eq!(stats.principals.len(), 2);
+        assert_eq!(stats.principals[0].principal_id, amy);
+        assert_eq!(stats.principals[0].block_count, 2);
+        assert_eq!(stats.principals[0].last_activity_at, 300);
+        assert_eq!(stats.principals[1].principal_id, model);
+    }
+
+    #[test]
+    fn test_context_stats_tally_empty() {
+        let stats = ContextStats::tally(std::iter::empty());
+        assert_eq!(stats, ContextStats::default());
+    }
+
+    #[test]
+    fn test_context_list_query_default_matches_everything() {
+        let query = ContextListQuery::default();
+        assert!(query.matches(None, None, 0));
+        assert_eq!(query.paginate(vec![1, 2, 3]), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_context_list_query_filters_and_together() {
+        let owner = PrincipalId::new();
+        let query = ContextListQuery::default()
+            .with_label_prefix("debug")
+            .with_active_since(1_000)
+            .with_owner(owner);
+
+        assert!(query.matches(Some("debug-auth"), Some(owner), 1_000));
+        assert!(!query.matches(None, Some(owner), 1_000), "unlabeled never matches a prefix");
+        assert!(!query.matches(Some("main"), Some(owner), 1_000));
+        assert!(!query.matches(Some("debug-auth"), Some(owner), 999));
+        assert!(!query.matches(Some("debug-auth"), Some(PrincipalId::new()), 1_000));
+        assert!(!query.matches(Some("debug-auth"), None, 1_000), "no DB row, no owner match");
+    }
+
+    #[test]
+    fn test_context_list_query_paginates() {
+        let query = ContextListQuery::default().with_page(1, 2);
+        assert_eq!(query.paginate(vec![1, 2, 3, 4]), vec![2, 3]);
+        assert_eq!(query.paginate(vec![1]), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_context_close_filter_empty_matches_nothing() {
+        let filter = ContextCloseFilter::default();
+        assert!(filter.is_empty());
+        assert!(!filter.matches(Some("x"), None, 0, true, false));
+
+        let promoted_only = ContextCloseFilter {
+            include_promoted: true,
+            ..Default::default()
+        };
+        assert!(promoted_only.is_empty(), "include_promoted alone selects nothing");
+    }
+
+    #[test]
+    fn test_context_close_filter_filters_and_together() {
+        let parent = ContextId::new();
+        let filter = ContextCloseFilter::default()
+            .with_label_prefix("exp-")
+            .with_forked_from(parent)
+            .with_idle_before(1_000)
+            .concluded_only();
+
+        assert!(filter.matches(Some("exp-1"), Some(parent), 999, true, false));
+        assert!(!filter.matches(Some("exp-1"), Some(parent), 1_000, true, false));
+        assert!(!filter.matches(Some("main"), Some(parent), 999, true, false));
+        assert!(!filter.matches(Some("exp-1"), None, 999, true, false));
+        assert!(!filter.matches(Some("exp-1"), Some(parent), 999, false, false));
+        assert!(
+            !filter.matches(Some("exp-1"), Some(parent), 999, true, true),
+            "ring-0 seats are skipped by default"
+        );
+        let seats_too = ContextCloseFilter {
+            include_promoted: true,
+            ..filter
+        };
+        assert!(seats_too.matches(Some("exp-1"), Some(parent), 999, true, true));
+    }
 }
@@ -0,0 +1,229 @@
+//! Context shares — read-only snapshots for people without a seat.
+//!
+//! A [`ContextShare`] freezes a context's blocks as they stood when the share
+//! was made and names the copy by an unguessable token. Whoever holds the
+//! token can read the copy — over an observer connection (SSH user
+//! [`SSH_OBSERVER_USER`], any key, no kernel binding) or as the standalone
+//! page [`render_html`] writes — but nothing they do reaches the live
+//! context, and later turns don't reach them. A share ends when it expires
+//! or is revoked; the snapshot stays on the server for the record either way.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::{BlockKind, BlockSnapshot};
+use crate::ids::{ContextId, PrincipalId};
+
+/// Lifetime of a share made without one: a week.
+pub const DEFAULT_SHARE_TTL_SECS: u64 = 7 * 24 * 3600;
+
+/// Longest share accepted: thirty days. Share again to extend.
+pub const MAX_SHARE_TTL_SECS: u64 = 30 * 24 * 3600;
+
+/// SSH username that opens an observer connection, when the server was
+/// started with `--allow-observers`. Any key is accepted for it; the
+/// connection may open shares by token and nothing else. Reserved: no
+/// registered principal may take the name.
+pub const SSH_OBSERVER_USER: &str = "observer";
+
+/// A fresh share token: 128 random bits as 32 hex digits.
+pub fn new_share_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// One frozen, token-addressed copy of a context.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextShare {
+    pub token: String,
+    pub context_id: ContextId,
+    /// The context's label when shared, else empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+    /// Document version the snapshot was taken at.
+    pub version: u64,
+    pub block_count: u32,
+    pub created_by: PrincipalId,
+    /// Unix millis.
+    pub created_at: u64,
+    /// Unix millis; the share is closed from this instant.
+    pub expires_at: u64,
+    /// Unix millis, once revoked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<u64>,
+}
+
+impl ContextShare {
+    pub fn status(&self, now_millis: u64) -> ShareStatus {
+        if self.revoked_at.is_some() {
+            ShareStatus::Revoked
+        } else if now_millis >= self.expires_at {
+            ShareStatus::Expired
+        } else {
+            ShareStatus::Live
+        }
+    }
+
+    pub fn is_live(&self, now_millis: u64) -> bool {
+        self.status(now_millis) == ShareStatus::Live
+    }
+
+    /// The shared context as people read it: the label, else the id's short
+    /// form.
+    pub fn title(&self) -> String {
+        if self.label.is_empty() {
+            self.context_id.short()
+        } else {
+            self.label.clone()
+        }
+    }
+}
+
+/// Whether a share can still be opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareStatus {
+    Live,
+    Expired,
+    Revoked,
+}
+
+impl ShareStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Live => "live",
+            Self::Expired => "expired",
+            Self::Revoked => "revoked",
+        }
+    }
+}
+
+impl fmt::Display for ShareStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A share's blocks as one self-contained HTML page: no scripts, no external
+/// assets, every block's text escaped. Thinking and notification blocks are
+/// left out, as in the Markdown transcript.
+pub fn render_html(share: &ContextShare, blocks: &[BlockSnapshot]) -> String {
+    let title = escape_html(&share.title());
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title} — kaijutsu</title>\n<style>{SHARE_CSS}</style>\n</head>\n<body>\n\
+         <header><h1>{title}</h1><p>read-only snapshot of context <code>{}</code> at \
+         version {}, {} blocks</p></header>\n<main>\n",
+        share.context_id.to_hex(),
+        share.version,
+        share.block_count,
+    );
+    for block in blocks {
+        if matches!(block.kind, BlockKind::Thinking | BlockKind::Notification) {
+            continue;
+        }
+        let head = match block.kind {
+            BlockKind::ToolCall => format!(
+                "{} → {}",
+                block.role.as_str(),
+                block.tool_name.as_deref().unwrap_or("tool")
+            ),
+            BlockKind::ToolResult => match block.exit_code {
+                Some(code) if code != 0 => format!("result (exit {code})"),
+                _ if block.is_error => "result (error)".to_string(),
+                _ => "result".to_string(),
+            },
+            BlockKind::Drift => match block.source_context {
+                Some(ctx) => format!("drift from {}", ctx.short()),
+                None => "drift".to_string(),
+            },
+            _ => block.role.as_str().to_string(),
+        };
+        let body = if block.kind == BlockKind::ToolCall && block.content.is_empty() {
+            block.tool_input.as_deref().unwrap_or_default()
+        } else {
+            block.content.as_str()
+        };
+        out.push_str(&format!(
+            "<section class=\"block {} {}\"><h2>{}</h2><pre>{}</pre></section>\n",
+            block.role.as_str(),
+            block.kind.as_str(),
+            escape_html(&head),
+            escape_html(body.trim_end()),
+        ));
+    }
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+const SHARE_CSS: &str = "body{font-family:system-ui,sans-serif;max-width:60rem;margin:2rem auto;\
+padding:0 1rem;color:#222}header p{color:#666}section{border-left:3px solid #ccc;\
+margin:1rem 0;padding:0 1rem}section.user{border-color:#4a7}section.model{border-color:#47a}\
+section.tool{border-color:#a74}h2{font-size:.8rem;text-transform:uppercase;color:#888;\
+margin:.5rem 0}pre{white-space:pre-wrap;word-wrap:break-word;margin:0 0 .5rem}";
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{BlockId, BlockSnapshotBuilder, Role};
+
+    fn share(expires_at: u64) -> ContextShare {
+        ContextShare {
+            token: new_share_token(),
+            context_id: ContextId::new(),
+            label: "bug <hunt>".into(),
+            version: 7,
+            block_count: 2,
+            created_by: PrincipalId::new(),
+            created_at: 0,
+            expires_at,
+            revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn status_follows_expiry_and_revocation() {
+        let mut s = share(1_000);
+        assert_eq!(s.token.len(), 32);
+        assert_eq!(s.status(999), ShareStatus::Live);
+        assert_eq!(s.status(1_000), ShareStatus::Expired);
+        s.revoked_at = Some(500);
+        assert_eq!(s.status(0), ShareStatus::Revoked);
+    }
+
+    #[test]
+    fn html_escapes_content_and_skips_thinking() {
+        let s = share(1_000);
+        let author = PrincipalId::new();
+        let said =
+            BlockSnapshotBuilder::new(BlockId::new(s.context_id, author, 1), BlockKind::Text)
+                .role(Role::User)
+                .content("<script>alert('x')</script> & more")
+                .build();
+        let thought =
+            BlockSnapshotBuilder::new(BlockId::new(s.context_id, author, 2), BlockKind::Thinking)
+                .role(Role::Model)
+                .content("private musing")
+                .build();
+        let html = render_html(&s, &[said, thought]);
+        assert!(html.contains("<title>bug &lt;hunt&gt; — kaijutsu</title>"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; more"));
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("private musing"));
+    }
+}
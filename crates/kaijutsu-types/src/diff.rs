@@ -0,0 +1,296 @@
+//! Text diffing, shared by every surface that shows one.
+//!
+//! Lines are matched with a real diff algorithm (Myers by default, via
+//! `similar`), so an inserted line reads as one `+` rather than shifting every
+//! later line into a `-`/`+` pair. Lines changed in place are refined to word
+//! level: [`DiffLine::emphasis`] marks the words that actually differ, and
+//! [`edits`] reports the same granularity as byte ranges for highlighting.
+//!
+//! Used by `kj block diff`, the MCP PR-summary prompt, and the app's inline
+//! edit highlights.
+
+use std::ops::Range;
+
+pub use similar::Algorithm;
+use similar::{DiffTag, TextDiff};
+
+/// Which side(s) of the diff a line belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineTag {
+    Equal,
+    Delete,
+    Insert,
+}
+
+impl LineTag {
+    /// The conventional one-char marker: `' '`, `'-'` or `'+'`.
+    pub fn marker(&self) -> char {
+        match self {
+            Self::Equal => ' ',
+            Self::Delete => '-',
+            Self::Insert => '+',
+        }
+    }
+}
+
+/// One line of a [`LineDiff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine<'a> {
+    pub tag: LineTag,
+    /// The line, without its terminator.
+    pub text: &'a str,
+    /// Byte ranges of `text` that differ from the line it is paired with.
+    /// Empty unless the line is one half of an in-place change.
+    pub emphasis: Vec<Range<usize>>,
+}
+
+/// Line counts for a diff. A deleted line paired with an inserted one in the
+/// same hunk counts once, as `changed`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffStats {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+}
+
+impl DiffStats {
+    pub fn is_empty(&self) -> bool {
+        self.added + self.removed + self.changed == 0
+    }
+}
+
+/// A line-by-line diff: every line of both texts in display order, with the
+/// deleted half of a hunk before its inserted half.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDiff<'a> {
+    pub lines: Vec<DiffLine<'a>>,
+    pub stats: DiffStats,
+}
+
+impl<'a> LineDiff<'a> {
+    pub fn new(old: &'a str, new: &'a str) -> Self {
+        Self::with_algorithm(old, new, Algorithm::Myers)
+    }
+
+    pub fn with_algorithm(old: &'a str, new: &'a str, algorithm: Algorithm) -> Self {
+        let diff = TextDiff::configure().algorithm(algorithm).diff_lines(old, new);
+        let (olds, news) = (diff.old_slices(), diff.new_slices());
+        let side = |tag, slices: &[&'a str]| -> Vec<DiffLine<'a>> {
+            slices
+                .iter()
+                .map(|line| DiffLine {
+                    tag,
+                    text: strip_eol(line),
+                    emphasis: Vec::new(),
+                })
+                .collect()
+        };
+
+        let mut lines = Vec::new();
+        let mut stats = DiffStats::default();
+        for op in diff.ops() {
+            let (tag, o, n) = op.as_tag_tuple();
+            match tag {
+                DiffTag::Equal => lines.extend(side(LineTag::Equal, &olds[o])),
+                DiffTag::Delete => {
+                    stats.removed += o.len();
+                    lines.extend(side(LineTag::Delete, &olds[o]));
+                }
+                DiffTag::Insert => {
+                    stats.added += n.len();
+                    lines.extend(side(LineTag::Insert, &news[n]));
+                }
+                DiffTag::Replace => {
+                    let paired = o.len().min(n.len());
+                    stats.changed += paired;
+                    stats.removed += o.len() - paired;
+                    stats.added += n.len() - paired;
+                    let mut deleted = side(LineTag::Delete, &olds[o]);
+                    let mut inserted = side(LineTag::Insert, &news[n]);
+                    for (d, i) in deleted.iter_mut().zip(inserted.iter_mut()) {
+                        let mut words = Vec::new();
+                        word_edits(d.text, i.text, algorithm, 0, 0, &mut words);
+                        d.emphasis = words
+                            .iter()
+                            .map(|e| e.old.clone())
+                            .filter(|r| !r.is_empty())
+                            .collect();
+                        i.emphasis = words
+                            .into_iter()
+                            .map(|e| e.new)
+                            .filter(|r| !r.is_empty())
+                            .collect();
+                    }
+                    lines.extend(deleted);
+                    lines.extend(inserted);
+                }
+            }
+        }
+        Self { lines, stats }
+    }
+}
+
+/// Byte range `old` of the old text became byte range `new` of the new text.
+/// Either side may be empty (pure insertion or deletion).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+/// The changed regions turning `old` into `new`, in order and at word
+/// granularity inside changed lines. Empty when the texts are equal.
+pub fn edits(old: &str, new: &str) -> Vec<Edit> {
+    let algorithm = Algorithm::Myers;
+    let diff = TextDiff::configure().algorithm(algorithm).diff_lines(old, new);
+    let old_at = offsets(diff.old_slices());
+    let new_at = offsets(diff.new_slices());
+
+    let mut out = Vec::new();
+    for op in diff.ops() {
+        let (tag, o, n) = op.as_tag_tuple();
+        let old_bytes = old_at[o.start]..old_at[o.end];
+        let new_bytes = new_at[n.start]..new_at[n.end];
+        match tag {
+            DiffTag::Equal => {}
+            DiffTag::Replace => word_edits(
+                &old[old_bytes.clone()],
+                &new[new_bytes.clone()],
+                algorithm,
+                old_bytes.start,
+                new_bytes.start,
+                &mut out,
+            ),
+            DiffTag::Delete | DiffTag::Insert => push_edit(
+                &mut out,
+                Edit {
+                    old: old_bytes,
+                    new: new_bytes,
+                },
+            ),
+        }
+    }
+    out
+}
+
+/// Word-level edits between two slices, shifted by the slices' offsets in
+/// their full texts.
+fn word_edits(
+    old: &str,
+    new: &str,
+    algorithm: Algorithm,
+    old_base: usize,
+    new_base: usize,
+    out: &mut Vec<Edit>,
+) {
+    let diff = TextDiff::configure().algorithm(algorithm).diff_words(old, new);
+    let old_at = offsets(diff.old_slices());
+    let new_at = offsets(diff.new_slices());
+    for op in diff.ops() {
+        let (tag, o, n) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        push_edit(
+            out,
+            Edit {
+                old: old_base + old_at[o.start]..old_base + old_at[o.end],
+                new: new_base + new_at[n.start]..new_base + new_at[n.end],
+            },
+        );
+    }
+}
+
+/// Append, merging with the previous edit when the two touch on both sides.
+fn push_edit(out: &mut Vec<Edit>, edit: Edit) {
+    if let Some(last) = out.last_mut()
+        && last.old.end == edit.old.start
+        && last.new.end == edit.new.start
+    {
+        last.old.end = edit.old.end;
+        last.new.end = edit.new.end;
+    } else {
+        out.push(edit);
+    }
+}
+
+/// Start offset of each slice, plus the total length at the end.
+fn offsets(slices: &[&str]) -> Vec<usize> {
+    let mut at = Vec::with_capacity(slices.len() + 1);
+    let mut total = 0;
+    at.push(0);
+    for s in slices {
+        total += s.len();
+        at.push(total);
+    }
+    at
+}
+
+fn strip_eol(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(diff: &LineDiff) -> String {
+        diff.lines
+            .iter()
+            .map(|l| format!("{}{}\n", l.tag.marker(), l.text))
+            .collect()
+    }
+
+    #[test]
+    fn inserted_line_does_not_shift_the_rest() {
+        let diff = LineDiff::new("a\nb\nc\n", "a\nNEW\nb\nc\n");
+        assert_eq!(render(&diff), " a\n+NEW\n b\n c\n");
+        assert_eq!(
+            diff.stats,
+            DiffStats {
+                added: 1,
+                removed: 0,
+                changed: 0
+            }
+        );
+    }
+
+    #[test]
+    fn replaced_line_is_changed_with_word_emphasis() {
+        let diff = LineDiff::new("let x = 1;\nkeep\n", "let y = 1;\nkeep\n");
+        assert_eq!(render(&diff), "-let x = 1;\n+let y = 1;\n keep\n");
+        assert_eq!(diff.stats.changed, 1);
+        assert_eq!(diff.lines[0].emphasis, vec![4..5]);
+        assert_eq!(diff.lines[1].emphasis, vec![4..5]);
+    }
+
+    #[test]
+    fn identical_texts_have_no_changes() {
+        let diff = LineDiff::new("same\nsame", "same\nsame");
+        assert!(diff.stats.is_empty());
+        assert!(edits("same", "same").is_empty());
+    }
+
+    #[test]
+    fn edits_are_word_granular_byte_ranges() {
+        assert_eq!(
+            edits("one three four", "one two three four"),
+            vec![Edit { old: 4..4, new: 4..8 }]
+        );
+        assert_eq!(
+            edits("keep drop keep", "keep keep"),
+            vec![Edit { old: 5..10, new: 5..5 }]
+        );
+        // Two separate lines changed → two separate edits.
+        let e = edits("a x\nsame\nb y\n", "a X\nsame\nb Y\n");
+        assert_eq!(e.len(), 2);
+        assert_eq!(e[1].new, 11..12);
+    }
+
+    #[test]
+    fn edits_stay_on_char_boundaries() {
+        let e = edits("café au lait", "cafè au lait");
+        assert_eq!(e, vec![Edit { old: 0..5, new: 0..5 }]);
+    }
+}
@@ -0,0 +1,148 @@
+//! Digest subscriptions: one context following another through periodic
+//! LLM summaries.
+//!
+//! A [`DigestSubscription`] has the kernel distill its `source` context and
+//! deliver the result as a `Drift` block into its `subscriber` — every
+//! `every_secs`, when a turn in the source finishes (`on_milestone`), or
+//! both. A digest only goes out when the source has new blocks since the
+//! last one, so a quiet source costs nothing.
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::BlockId;
+use crate::ids::{ContextId, PrincipalId};
+
+/// Shortest allowed `every_secs`, and the least time between two digests of
+/// one subscription whatever triggers them.
+pub const MIN_DIGEST_INTERVAL_SECS: u32 = 60;
+
+/// Most digest subscriptions one context may hold.
+pub const MAX_DIGEST_SUBSCRIPTIONS: usize = 16;
+
+/// One context's standing request for digests of another.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestSubscription {
+    /// The context the digests are delivered into.
+    pub subscriber: ContextId,
+    /// The context being summarized.
+    pub source: ContextId,
+    /// Deliver at most this often while the source has new blocks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_secs: Option<u32>,
+    /// Deliver when a turn in the source finishes with new blocks.
+    #[serde(default)]
+    pub on_milestone: bool,
+    /// What the digest should focus on; `None` takes the default brief.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<String>,
+    pub created_by: PrincipalId,
+    /// Unix millis.
+    pub created_at: u64,
+    /// Unix millis of the last delivered digest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_digest_at: Option<u64>,
+    /// The source's last block when the last digest was taken (or when the
+    /// subscription started) — "new blocks" means the source moved past it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_block: Option<BlockId>,
+}
+
+impl DigestSubscription {
+    /// Check a subscription before storing it.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.subscriber == self.source {
+            return Err("a context cannot subscribe to its own digest".to_string());
+        }
+        if self.every_secs.is_none() && !self.on_milestone {
+            return Err("set an interval, milestone delivery, or both".to_string());
+        }
+        if let Some(secs) = self.every_secs
+            && secs < MIN_DIGEST_INTERVAL_SECS
+        {
+            return Err(format!(
+                "interval must be at least {MIN_DIGEST_INTERVAL_SECS}s"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether a digest should go out at `now` (Unix millis), given the
+    /// source's current last block and whether a turn is running in it.
+    pub fn is_due(&self, now: u64, source_last: Option<BlockId>, source_busy: bool) -> bool {
+        if source_last.is_none() || source_last == self.last_block {
+            return false;
+        }
+        let since = now.saturating_sub(self.last_digest_at.unwrap_or(self.created_at));
+        if since < u64::from(MIN_DIGEST_INTERVAL_SECS) * 1000 {
+            return false;
+        }
+        let interval_due = self
+            .every_secs
+            .is_some_and(|secs| since >= u64::from(secs) * 1000);
+        let milestone_due = self.on_milestone && !source_busy;
+        interval_due || milestone_due
+    }
+
+    /// Human-readable trigger: `every 300s`, `on milestone`, or both.
+    pub fn trigger_label(&self) -> String {
+        match (self.every_secs, self.on_milestone) {
+            (Some(secs), true) => format!("every {secs}s + on milestone"),
+            (Some(secs), false) => format!("every {secs}s"),
+            (None, _) => "on milestone".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub(every_secs: Option<u32>, on_milestone: bool) -> DigestSubscription {
+        DigestSubscription {
+            subscriber: ContextId::new(),
+            source: ContextId::new(),
+            every_secs,
+            on_milestone,
+            focus: None,
+            created_by: PrincipalId::nil(),
+            created_at: 0,
+            last_digest_at: None,
+            last_block: None,
+        }
+    }
+
+    #[test]
+    fn validate_needs_a_trigger_and_a_sane_interval() {
+        assert!(sub(None, false).validate().is_err());
+        assert!(sub(Some(10), false).validate().is_err());
+        assert!(sub(Some(300), false).validate().is_ok());
+        assert!(sub(None, true).validate().is_ok());
+        let mut own = sub(None, true);
+        own.source = own.subscriber;
+        assert!(own.validate().is_err());
+    }
+
+    #[test]
+    fn due_only_with_new_blocks_and_after_the_gap() {
+        let mut s = sub(Some(300), false);
+        let block = BlockId::new(s.source, PrincipalId::nil(), 4);
+        assert!(!s.is_due(400_000, None, false), "empty source");
+        assert!(s.is_due(400_000, Some(block), false));
+        assert!(!s.is_due(200_000, Some(block), false), "interval not up");
+
+        s.last_block = Some(block);
+        assert!(!s.is_due(900_000, Some(block), false), "nothing new");
+
+        let milestone = sub(None, true);
+        let block = BlockId::new(milestone.source, PrincipalId::nil(), 1);
+        assert!(
+            !milestone.is_due(90_000, Some(block), true),
+            "turn still running"
+        );
+        assert!(milestone.is_due(90_000, Some(block), false));
+        assert!(
+            !milestone.is_due(30_000, Some(block), false),
+            "inside the gap"
+        );
+    }
+}
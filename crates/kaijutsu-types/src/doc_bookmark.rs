@@ -0,0 +1,67 @@
+//! Document bookmarks — a named point in a document's history.
+//!
+//! A [`DocBookmark`] (`before-refactor`) pins a document as it stood when it
+//! was made. Frontiers are local version numbers and mean nothing on another
+//! replica or after a compaction, so the server keeps a frozen copy of the
+//! document's blocks beside the name: a bookmark can be read back, diffed
+//! against the live document, or reverted to for as long as it exists.
+//! Names are unique per document.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ids::{ContextId, PrincipalId};
+
+/// Longest accepted bookmark name; see [`validate_bookmark_name`].
+pub const MAX_BOOKMARK_NAME_LEN: usize = 64;
+
+/// One named, frozen state of a document.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocBookmark {
+    pub context_id: ContextId,
+    pub name: String,
+    /// Document version the bookmark was taken at.
+    pub version: u64,
+    pub block_count: u32,
+    pub created_by: PrincipalId,
+    /// Unix millis.
+    pub created_at: u64,
+}
+
+/// Check a bookmark name (`before-refactor`): 1–64 ASCII letters, digits,
+/// `-`, `_` or `.`, starting with a letter — the block-label alphabet.
+pub fn validate_bookmark_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_BOOKMARK_NAME_LEN {
+        return Err(format!(
+            "bookmark name must be 1-{MAX_BOOKMARK_NAME_LEN} characters, got {}",
+            name.len()
+        ));
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(format!("bookmark name '{name}' must start with a letter"));
+    }
+    if let Some(bad) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        return Err(format!(
+            "bookmark name '{name}' contains '{bad}'; use letters, digits, '-', '_' or '.'"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_use_the_label_alphabet() {
+        for ok in ["before-refactor", "v1.2", "a", "take_2"] {
+            assert!(validate_bookmark_name(ok).is_ok(), "{ok}");
+        }
+        for bad in ["", "2nd", "-x", "has space", "@b", "x/y"] {
+            assert!(validate_bookmark_name(bad).is_err(), "{bad:?}");
+        }
+        assert!(validate_bookmark_name(&"x".repeat(MAX_BOOKMARK_NAME_LEN + 1)).is_err());
+    }
+}
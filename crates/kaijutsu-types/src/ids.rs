@@ -44,6 +44,11 @@ pub struct WorkspaceId(uuid::Uuid);
 #[serde(transparent)]
 pub struct PresetId(uuid::Uuid);
 
+/// A context-name reservation token (UUIDv7).
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReservationId(uuid::Uuid);
+
 // ── Shared behavior ─────────────────────────────────────────────────────────
 
 macro_rules! impl_typed_id {
@@ -186,6 +191,7 @@ impl_typed_id!(ContextId, "ContextId");
 impl_typed_id!(SessionId, "SessionId");
 impl_typed_id!(WorkspaceId, "WorkspaceId");
 impl_typed_id!(PresetId, "PresetId");
+impl_typed_id!(ReservationId, "ReservationId");
 
 // ── PrefixResolvable ────────────────────────────────────────────────────────
 
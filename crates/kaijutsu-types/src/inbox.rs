@@ -0,0 +1,102 @@
+//! Per-principal notification inbox.
+//!
+//! An inbox item is a durable "someone wants your attention" pointer addressed
+//! to one principal (a seat): a mention, a consent prompt, a drift landing in
+//! a context they own, a task handed to them. Items are rows in `KernelDb`,
+//! pushed live over the block subscription, and stay listed until acked —
+//! so a principal that was offline when it was pinged still sees it.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumString;
+
+use crate::ids::{ContextId, PrincipalId};
+
+/// Why an inbox item was posted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum InboxKind {
+    /// Named with `@username` in a block.
+    Mention,
+    /// A tool call or action is waiting on this principal's approval.
+    ConsentRequest,
+    /// Drift content was delivered into a context this principal created.
+    DriftArrival,
+    /// Work was handed to this principal.
+    TaskAssignment,
+    /// An edit was suggested on a block this principal owns, or their
+    /// suggestion was accepted or rejected.
+    Suggestion,
+}
+
+impl InboxKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mention => "mention",
+            Self::ConsentRequest => "consent_request",
+            Self::DriftArrival => "drift_arrival",
+            Self::TaskAssignment => "task_assignment",
+            Self::Suggestion => "suggestion",
+        }
+    }
+}
+
+impl fmt::Display for InboxKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One notification in a principal's inbox.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InboxItem {
+    /// Kernel-assigned, monotonically increasing per kernel.
+    pub id: u64,
+    /// The seat this item is addressed to.
+    pub recipient: PrincipalId,
+    pub kind: InboxKind,
+    /// The context the item points at, if any.
+    pub context_id: Option<ContextId>,
+    /// Who caused it (`None` for kernel-originated items).
+    pub sender: Option<PrincipalId>,
+    /// One-line human-readable summary.
+    pub summary: String,
+    /// Unix millis.
+    pub created_at: u64,
+    /// Unix millis of the ack, or `None` while unread.
+    pub acked_at: Option<u64>,
+}
+
+impl InboxItem {
+    pub fn is_acked(&self) -> bool {
+        self.acked_at.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn inbox_kind_as_str_roundtrip() {
+        for kind in [
+            InboxKind::Mention,
+            InboxKind::ConsentRequest,
+            InboxKind::DriftArrival,
+            InboxKind::TaskAssignment,
+            InboxKind::Suggestion,
+        ] {
+            assert_eq!(InboxKind::from_str(kind.as_str()).unwrap(), kind);
+            let json = serde_json::to_string(&kind).unwrap();
+            assert_eq!(json, format!("\"{}\"", kind.as_str()));
+        }
+    }
+
+    #[test]
+    fn inbox_kind_rejects_unknown() {
+        assert!(InboxKind::from_str("reminder").is_err());
+    }
+}
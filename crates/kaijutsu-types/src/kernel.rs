@@ -43,6 +43,42 @@ impl Kernel {
     }
 }
 
+/// Server-side filter + page for `listKernels`. The default matches everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KernelListQuery {
+    /// Keep kernels whose name starts with this.
+    pub name_prefix: Option<String>,
+    /// Entries to skip after filtering.
+    pub offset: u32,
+    /// Page size. `None` = no limit.
+    pub limit: Option<u32>,
+}
+
+impl KernelListQuery {
+    pub fn with_name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.name_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn with_page(mut self, offset: u32, limit: u32) -> Self {
+        self.offset = offset;
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether a kernel with this name passes the filter.
+    pub fn matches(&self, name: &str) -> bool {
+        self.name_prefix
+            .as_deref()
+            .is_none_or(|prefix| name.starts_with(prefix))
+    }
+
+    /// Cut a filtered listing down to this query's page.
+    pub fn paginate<T>(&self, items: Vec<T>) -> Vec<T> {
+        crate::context::paginate(items, self.offset, self.limit)
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -109,4 +145,13 @@ mod tests {
         let b = Kernel::new(founder, None);
         assert_ne!(a.id, b.id);
     }
+
+    #[test]
+    fn test_kernel_list_query() {
+        let query = KernelListQuery::default().with_name_prefix("kaijutsu");
+        assert!(query.matches("kaijutsu-dev"));
+        assert!(!query.matches("scratch"));
+        assert!(KernelListQuery::default().matches("anything"));
+        assert_eq!(query.with_page(0, 1).paginate(vec!["a", "b"]), vec!["a"]);
+    }
 }
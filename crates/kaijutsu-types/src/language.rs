@@ -0,0 +1,250 @@
+//! Source-language tags for Text/Code blocks.
+//!
+//! A block's `language` (see [`crate::BlockSnapshot::language`]) names the
+//! syntax its text is written in — `rust`, `python`, `json`, `diff`. The app
+//! uses it to pick a highlighter; Markdown export uses it as the fence info
+//! string. Tags are lowercase names in the style of Markdown fences and
+//! editors' language ids, never file extensions: [`normalize_language`] folds
+//! the common aliases (`rs`, `py`, `sh`, `yml`) onto one name.
+//!
+//! The kernel sets the tag on tool output it can classify: from a path in the
+//! tool's input, from the file a shell `cat` printed, or — failing those — by
+//! sniffing the text itself ([`sniff_language`]).
+
+/// Longest accepted language tag.
+pub const LANGUAGE_MAX_LEN: usize = 32;
+
+/// Language for a file path, from its extension or well-known file name.
+pub fn language_for_path(path: &str) -> Option<&'static str> {
+    let name = path.rsplit('/').next()?;
+    match name {
+        "Makefile" | "makefile" | "GNUmakefile" => return Some("make"),
+        "Dockerfile" => return Some("dockerfile"),
+        "Cargo.lock" => return Some("toml"),
+        _ => {}
+    }
+    let (stem, ext) = name.rsplit_once('.')?;
+    if stem.is_empty() {
+        // Dotfiles (`.bashrc`) have no extension to go on.
+        return None;
+    }
+    let lang = match ext {
+        "rs" => "rust",
+        "py" => "python",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" => "typescript",
+        "tsx" => "typescriptreact",
+        "jsx" => "javascriptreact",
+        "go" => "go",
+        "rb" => "ruby",
+        "lua" => "lua",
+        "sh" | "bash" => "bash",
+        "zsh" => "zsh",
+        "c" => "c",
+        "cpp" | "cc" | "cxx" => "cpp",
+        "h" => "c",
+        "hpp" => "cpp",
+        "java" => "java",
+        "kt" => "kotlin",
+        "toml" => "toml",
+        "yaml" | "yml" => "yaml",
+        "json" => "json",
+        "md" => "markdown",
+        "html" => "html",
+        "css" => "css",
+        "sql" => "sql",
+        "wgsl" => "wgsl",
+        "capnp" => "capnp",
+        "diff" | "patch" => "diff",
+        _ => return None,
+    };
+    Some(lang)
+}
+
+/// Canonical form of a user- or model-supplied language tag: trimmed,
+/// lowercased, with common aliases folded. `None` for an empty tag or one
+/// that couldn't be a fence info string (whitespace, backticks, over
+/// [`LANGUAGE_MAX_LEN`]).
+pub fn normalize_language(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_ascii_lowercase();
+    if tag.is_empty() || tag.len() > LANGUAGE_MAX_LEN {
+        return None;
+    }
+    if !tag
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '#' | '-' | '_' | '.'))
+    {
+        return None;
+    }
+    let canonical = match tag.as_str() {
+        "rs" => "rust",
+        "py" | "python3" => "python",
+        "js" | "node" => "javascript",
+        "ts" => "typescript",
+        "sh" | "shell" => "bash",
+        "yml" => "yaml",
+        "md" => "markdown",
+        "c++" => "cpp",
+        "patch" => "diff",
+        other => return Some(other.to_string()),
+    };
+    Some(canonical.to_string())
+}
+
+/// Guess the language of `text` from its content: a shebang line, a unified
+/// diff header, or a JSON document. Deliberately conservative — a wrong tag
+/// highlights worse than none.
+pub fn sniff_language(text: &str) -> Option<&'static str> {
+    if let Some(first) = text.lines().next()
+        && let Some(interp) = first.strip_prefix("#!")
+    {
+        return shebang_language(interp);
+    }
+    if text.starts_with("diff --git ")
+        || (text.starts_with("--- ") && text.lines().nth(1).is_some_and(|l| l.starts_with("+++ ")))
+    {
+        return Some("diff");
+    }
+    let trimmed = text.trim();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::de::IgnoredAny>(trimmed).is_ok()
+    {
+        return Some("json");
+    }
+    None
+}
+
+/// Language for a shebang interpreter line (without the `#!`).
+fn shebang_language(interp: &str) -> Option<&'static str> {
+    let mut words = interp.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-'))?;
+    }
+    let lang = match program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+        "sh" | "bash" | "dash" => "bash",
+        "zsh" => "zsh",
+        "python" => "python",
+        "node" => "javascript",
+        "ruby" => "ruby",
+        "lua" => "lua",
+        "perl" => "perl",
+        "kaish" => "kaish",
+        _ => return None,
+    };
+    Some(lang)
+}
+
+/// Commands whose output is a file's text verbatim.
+const FILE_PRINTERS: &[&str] = &["cat", "head", "tail", "bat"];
+
+/// Language of a tool's output: from a `path`/`file_path` argument in its
+/// JSON input, else sniffed from the output.
+pub fn language_for_tool_output(input: &serde_json::Value, output: &str) -> Option<&'static str> {
+    ["path", "file_path"]
+        .iter()
+        .find_map(|key| input.get(key).and_then(serde_json::Value::as_str))
+        .and_then(language_for_path)
+        .or_else(|| sniff_language(output))
+}
+
+/// Language of a shell command's output. A simple `cat FILE` (or `head`,
+/// `tail`, `bat`) of one file takes the file's language; anything else is
+/// sniffed from the output.
+pub fn language_for_command_output(command: &str, output: &str) -> Option<&'static str> {
+    let mut words = command.split_whitespace();
+    let printed = match words.next() {
+        Some(program) if FILE_PRINTERS.contains(&program) => {
+            let mut files = words.filter(|w| !w.starts_with('-'));
+            match (files.next(), files.next()) {
+                (Some(file), None) if !file.contains(['|', ';', '>', '<', '&', '$']) => {
+                    language_for_path(file)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    printed.or_else(|| sniff_language(output))
+}
+
+/// Wrap `text` in a Markdown code fence tagged with `language`. The fence is
+/// one backtick longer than the longest backtick run in `text`, so content
+/// holding its own fences can't close it early.
+pub fn markdown_fence(text: &str, language: Option<&str>) -> String {
+    let longest_run = text
+        .split(|c: char| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let newline = if text.is_empty() || text.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    format!(
+        "{fence}{}\n{text}{newline}{fence}\n",
+        language.unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn paths_and_aliases_map_to_one_name() {
+        assert_eq!(language_for_path("src/main.rs"), Some("rust"));
+        assert_eq!(language_for_path("/mnt/proj/Makefile"), Some("make"));
+        assert_eq!(language_for_path("notes.d/README"), None);
+        assert_eq!(language_for_path(".bashrc"), None);
+        assert_eq!(normalize_language(" RS "), Some("rust".to_string()));
+        assert_eq!(normalize_language("yml"), Some("yaml".to_string()));
+        assert_eq!(normalize_language("haskell"), Some("haskell".to_string()));
+        assert_eq!(normalize_language("rust ```"), None);
+        assert_eq!(normalize_language(""), None);
+    }
+
+    #[test]
+    fn tool_and_command_output_detection() {
+        assert_eq!(
+            language_for_tool_output(&json!({"path": "lib.py"}), "x = 1"),
+            Some("python")
+        );
+        assert_eq!(
+            language_for_tool_output(&json!({}), "{\"a\": [1, 2]}"),
+            Some("json")
+        );
+        assert_eq!(language_for_tool_output(&json!({}), "{ not json"), None);
+        assert_eq!(
+            language_for_command_output("cat -n Cargo.toml", "[package]"),
+            Some("toml")
+        );
+        assert_eq!(
+            language_for_command_output("cat a.rs b.rs", "fn a() {}"),
+            None
+        );
+        assert_eq!(
+            language_for_command_output("git diff", "diff --git a/x b/x\n"),
+            Some("diff")
+        );
+        assert_eq!(
+            sniff_language("#!/usr/bin/env python3\nprint(1)\n"),
+            Some("python")
+        );
+    }
+
+    #[test]
+    fn fences_outgrow_backticks_in_the_text() {
+        assert_eq!(
+            markdown_fence("fn main() {}", Some("rust")),
+            "```rust\nfn main() {}\n```\n"
+        );
+        assert_eq!(
+            markdown_fence("```sh\nls\n```\n", None),
+            "````\n```sh\nls\n```\n````\n"
+        );
+    }
+}
@@ -43,20 +43,48 @@
 //! | [`BlockSnapshot`] | Serializable block state                     |
 //! |-------------------|----------------------------------------------|
 
+pub mod assembly;
 pub mod block;
+pub mod block_lock;
+pub mod broadcast;
+pub mod budget;
 pub mod codec;
 pub mod compaction;
+pub mod completion;
+pub mod config_apply;
+pub mod consent;
 pub mod context;
+pub mod context_share;
+pub mod diff;
+pub mod digest;
+pub mod doc_bookmark;
 pub mod enums;
 pub mod error_block;
 pub mod ids;
+pub mod inbox;
 pub mod kernel;
+pub mod language;
+pub mod llm_params;
+pub mod mention;
 pub mod paths;
+pub mod permalink;
+pub mod prefs;
 pub mod principal;
+pub mod provenance;
+pub mod push_violation;
+pub mod reflow;
+pub mod repl;
+#[cfg(feature = "rpc-compress")]
+pub mod rpc_compress;
+pub mod sandbox;
 pub mod session;
+pub mod shell_command;
 pub mod share;
+pub mod suggestion;
+pub mod test_report;
 pub mod theme;
 pub mod tick;
+pub mod timeline;
 pub mod timeout;
 pub mod track;
 
@@ -95,18 +123,55 @@ pub use block::{
     ResourcePayload, Role, Status, ToolKind, ERROR_DETAIL_HYDRATION_BUDGET,
     NOTIFICATION_DETAIL_HYDRATION_BUDGET, RESOURCE_CONTENT_HYDRATION_BUDGET,
     TOOL_CONTENT_HYDRATION_BUDGET, format_error_for_llm, format_notification_for_llm,
-    format_resource_for_llm, format_tool_content_for_llm,
+    format_resource_for_llm, format_tool_content_for_llm, resolve_block_prefix, resolve_block_ref,
+    validate_block_label, MAX_BLOCK_LABEL_LEN,
 };
+pub use block_lock::{BlockLock, DEFAULT_LOCK_TTL_SECS, LockPolicy, MAX_LOCK_TTL_SECS};
+pub use broadcast::{
+    BroadcastState, BroadcastStatus, BroadcastTarget, BroadcastTargetStatus, MAX_BROADCAST_TARGETS,
+};
+pub use assembly::{Assembly, AssemblyStage, MAX_ASSEMBLY_WINDOW};
+pub use budget::{BUDGET_WINDOW_SECS, BudgetStatus, ExecutionBudget, ToolHalt};
+pub use llm_params::{LlmParams, MAX_TEMPERATURE, ToolChoice};
 pub use error_block::IntoErrorPayload;
 pub use compaction::CompactionBoundary;
-pub use context::{Context, RING_SLOTS, fork_lineage};
+pub use config_apply::{ConfigApplyReport, ConfigChange};
+pub use consent::{
+    ConsentDecision, ConsentEntry, ConsentPrompt, ConsentVerdict, ConsentVerification,
+};
+pub use completion::{CompletionToken, token_at};
+pub use context::{
+    ACTIVITY_WINDOW_SECS, Context, ContextActivity, ContextCloseFilter, ContextHealth,
+    ContextListQuery, ContextNameReservation, ContextRecovery, ContextStats, HealthIssue,
+    PrincipalActivity, RING_SLOTS, context_label_key, fork_lineage,
+};
+pub use context_share::{
+    ContextShare, DEFAULT_SHARE_TTL_SECS, MAX_SHARE_TTL_SECS, SSH_OBSERVER_USER, ShareStatus,
+};
+pub use digest::{DigestSubscription, MAX_DIGEST_SUBSCRIPTIONS, MIN_DIGEST_INTERVAL_SECS};
+pub use doc_bookmark::{DocBookmark, MAX_BOOKMARK_NAME_LEN, validate_bookmark_name};
 pub use enums::{ConsentMode, ContextState, DocKind, EdgeKind, ForkKind};
-pub use ids::{ContextId, KernelId, PresetId, PrincipalId, SessionId, WorkspaceId};
+pub use ids::{ContextId, KernelId, PresetId, PrincipalId, ReservationId, SessionId, WorkspaceId};
 pub use ids::{PrefixError, PrefixResolvable, resolve_context_prefix, resolve_prefix};
-pub use kernel::Kernel;
+pub use inbox::{InboxItem, InboxKind};
+pub use kernel::{Kernel, KernelListQuery};
+pub use mention::{BlockMention, MentionTarget, mention_names};
+pub use permalink::{PERMALINK_SCHEME, Permalink, PermalinkError};
+pub use prefs::{Preferences, validate_pref};
 pub use principal::{Credential, CredentialKind, Principal};
+pub use provenance::AgentProvenance;
+pub use push_violation::{PushRule, PushViolation, describe_violations};
+pub use reflow::Reflow;
+pub use repl::{ReplLanguage, repl_echo};
+pub use sandbox::{SandboxLimits, SandboxProfile};
 pub use session::Session;
+pub use shell_command::{ShellCommand, parse_argv};
+pub use suggestion::{Suggestion, SuggestionState, TextEdit};
+pub use test_report::{TestCase, TestOutcome, TestReport, TestRunner};
 pub use tick::{Span, Tick, TickDelta};
+pub use timeline::{
+    ContextTimeline, DEFAULT_TIMELINE_BUCKETS, MAX_TIMELINE_BUCKETS, TimelineBucket,
+};
 pub use timeout::TimeoutPolicy;
 pub use track::{TrackId, TrackIdError};
 
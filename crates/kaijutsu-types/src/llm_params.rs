@@ -0,0 +1,205 @@
+//! Per-context LLM generation parameters.
+//!
+//! A context may pin `temperature`, `max_tokens`, `top_p` and `tool_choice`
+//! for its turns instead of taking the provider defaults. They are set over
+//! RPC (`setLlmParams`), inherited by forks, and a single prompt can override
+//! any of them for that one turn (`LlmRequest.params`) — the override wins
+//! field by field, see [`LlmParams::overlay`]. `None` everywhere means
+//! "provider default".
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Highest accepted `temperature`. Anthropic caps at 1.0, OpenAI-style
+/// providers at 2.0; the provider rejects what it doesn't support.
+pub const MAX_TEMPERATURE: f64 = 2.0;
+
+/// How the model may use tools on a turn.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum ToolChoice {
+    /// The model decides (provider default).
+    Auto,
+    /// The model must call some tool.
+    Any,
+    /// The model must not call tools.
+    None,
+    /// The model must call this tool.
+    Tool(String),
+}
+
+impl fmt::Display for ToolChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => f.write_str("auto"),
+            Self::Any => f.write_str("any"),
+            Self::None => f.write_str("none"),
+            Self::Tool(name) => write!(f, "tool:{name}"),
+        }
+    }
+}
+
+impl FromStr for ToolChoice {
+    type Err = String;
+
+    /// `auto`, `any` (alias `required`), `none`, or `tool:<name>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "auto" => Ok(Self::Auto),
+            "any" | "required" => Ok(Self::Any),
+            "none" => Ok(Self::None),
+            other => match other.strip_prefix("tool:").map(str::trim) {
+                Some(name) if !name.is_empty() => Ok(Self::Tool(name.to_string())),
+                _ => Err(format!(
+                    "invalid tool_choice '{other}' (auto, any, none, or tool:<name>)"
+                )),
+            },
+        }
+    }
+}
+
+impl From<ToolChoice> for String {
+    fn from(choice: ToolChoice) -> Self {
+        choice.to_string()
+    }
+}
+
+impl TryFrom<String> for ToolChoice {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Generation parameters for a context's turns. `None` = provider default.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LlmParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Output token cap per model call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+}
+
+impl LlmParams {
+    /// Whether nothing is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the values before storing or sending them.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(t) = self.temperature
+            && !(0.0..=MAX_TEMPERATURE).contains(&t)
+        {
+            return Err(format!(
+                "temperature must be between 0 and {MAX_TEMPERATURE}"
+            ));
+        }
+        if let Some(p) = self.top_p
+            && !(p > 0.0 && p <= 1.0)
+        {
+            return Err("top_p must be greater than 0 and at most 1".to_string());
+        }
+        if self.max_tokens == Some(0) {
+            return Err("max_tokens must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// `self` with every field `over` sets replaced by `over`'s value.
+    pub fn overlay(&self, over: &LlmParams) -> LlmParams {
+        LlmParams {
+            temperature: over.temperature.or(self.temperature),
+            max_tokens: over.max_tokens.or(self.max_tokens),
+            top_p: over.top_p.or(self.top_p),
+            tool_choice: over
+                .tool_choice
+                .clone()
+                .or_else(|| self.tool_choice.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_choice_round_trips_as_a_string() {
+        for s in ["auto", "any", "none", "tool:shell"] {
+            let choice: ToolChoice = s.parse().unwrap();
+            assert_eq!(choice.to_string(), s);
+            let json = serde_json::to_string(&choice).unwrap();
+            assert_eq!(json, format!("\"{s}\""));
+            assert_eq!(serde_json::from_str::<ToolChoice>(&json).unwrap(), choice);
+        }
+        assert_eq!("required".parse(), Ok(ToolChoice::Any));
+        assert!("tool:".parse::<ToolChoice>().is_err());
+        assert!("sometimes".parse::<ToolChoice>().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_values() {
+        assert!(LlmParams::default().validate().is_ok());
+        let ok = LlmParams {
+            temperature: Some(0.0),
+            max_tokens: Some(1),
+            top_p: Some(1.0),
+            tool_choice: Some(ToolChoice::None),
+        };
+        assert!(ok.validate().is_ok());
+        for bad in [
+            LlmParams {
+                temperature: Some(2.5),
+                ..Default::default()
+            },
+            LlmParams {
+                temperature: Some(-0.1),
+                ..Default::default()
+            },
+            LlmParams {
+                top_p: Some(0.0),
+                ..Default::default()
+            },
+            LlmParams {
+                top_p: Some(f64::NAN),
+                ..Default::default()
+            },
+            LlmParams {
+                max_tokens: Some(0),
+                ..Default::default()
+            },
+        ] {
+            assert!(bad.validate().is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn overlay_wins_field_by_field() {
+        let context = LlmParams {
+            temperature: Some(0.2),
+            max_tokens: Some(4096),
+            top_p: None,
+            tool_choice: Some(ToolChoice::Auto),
+        };
+        let request = LlmParams {
+            temperature: Some(0.9),
+            tool_choice: Some(ToolChoice::Tool("shell".into())),
+            ..Default::default()
+        };
+        let merged = context.overlay(&request);
+        assert_eq!(merged.temperature, Some(0.9));
+        assert_eq!(merged.max_tokens, Some(4096));
+        assert_eq!(merged.top_p, None);
+        assert_eq!(merged.tool_choice, Some(ToolChoice::Tool("shell".into())));
+        assert_eq!(context.overlay(&LlmParams::default()), context);
+    }
+}
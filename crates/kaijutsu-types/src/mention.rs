@@ -0,0 +1,98 @@
+//! `@`-mentions in user blocks.
+//!
+//! When a user block is created the kernel scans its text for `@name` tokens
+//! and resolves each against known seats (principals, by username) and then
+//! context labels. Resolved mentions are stored on the block itself
+//! ([`BlockSnapshot::mentions`](crate::BlockSnapshot::mentions)) so every
+//! replica sees who and what was addressed, and drive routing: a principal
+//! gets an inbox item, a context gets the block drifted to it.
+//!
+//! Unresolved tokens are not recorded — `@` is also ordinary punctuation.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ids::{ContextId, PrincipalId};
+
+/// What an `@name` token resolved to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MentionTarget {
+    /// A seat, matched by username.
+    Principal(PrincipalId),
+    /// A context, matched by label.
+    Context(ContextId),
+}
+
+/// One resolved mention on a block.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlockMention {
+    /// The name as written, without the `@`.
+    pub name: String,
+    pub target: MentionTarget,
+}
+
+impl BlockMention {
+    pub fn principal(name: impl Into<String>, id: PrincipalId) -> Self {
+        Self {
+            name: name.into(),
+            target: MentionTarget::Principal(id),
+        }
+    }
+
+    pub fn context(name: impl Into<String>, id: ContextId) -> Self {
+        Self {
+            name: name.into(),
+            target: MentionTarget::Context(id),
+        }
+    }
+}
+
+/// Distinct `@name` tokens in `text`, in order of first appearance.
+///
+/// An `@` only starts a mention at a word boundary, so `amy@example.com` is
+/// not one; names are ASCII alphanumerics plus `_`, `-`, and `.`, and trailing
+/// sentence punctuation is not part of the name.
+pub fn mention_names(text: &str) -> Vec<&str> {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.');
+    let mut found: Vec<&str> = Vec::new();
+    let mut prev: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        if c == '@' && !prev.is_some_and(|p| p.is_alphanumeric() || p == '_') {
+            let rest = &text[i + 1..];
+            let end = rest.find(|c: char| !is_name(c)).unwrap_or(rest.len());
+            let name = rest[..end].trim_end_matches(['.', '-']);
+            if !name.is_empty() && !found.contains(&name) {
+                found.push(name);
+            }
+        }
+        prev = Some(c);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_parse_at_word_boundaries() {
+        assert_eq!(
+            mention_names("hey @amy, can @bob.k look? cc @amy."),
+            vec!["amy", "bob.k"]
+        );
+        assert!(mention_names("mail amy@example.com").is_empty());
+        assert!(mention_names("just an @ sign").is_empty());
+        assert_eq!(mention_names("(@review-queue) ok"), vec!["review-queue"]);
+    }
+
+    #[test]
+    fn mention_cbor_round_trip() {
+        let mentions = vec![
+            BlockMention::principal("amy", PrincipalId::new()),
+            BlockMention::context("review", ContextId::new()),
+        ];
+        let bytes = crate::codec::encode(&mentions).unwrap();
+        let back: Vec<BlockMention> = crate::codec::decode(&bytes).unwrap();
+        assert_eq!(back, mentions);
+    }
+}
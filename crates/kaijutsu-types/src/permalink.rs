@@ -0,0 +1,150 @@
+//! [`Permalink`] — a stable, shareable address for one block.
+//!
+//! `kaijutsu://{kernel}/{context}/{block-key}`: the kernel id as the host, then
+//! the context id, then the full block key (`BlockId::to_key`). The context
+//! segment repeats what the block key already carries so a link reads as a
+//! path, and parsing rejects a link whose two disagree. The app yanks these
+//! for the focused block and opens them (connect, join, scroll); the MCP
+//! `block_permalink` tool mints them for issues and chats.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::block::BlockId;
+use crate::ids::{ContextId, KernelId};
+
+/// The URL scheme of a permalink.
+pub const PERMALINK_SCHEME: &str = "kaijutsu";
+
+/// Address of one block in one kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Permalink {
+    pub kernel_id: KernelId,
+    pub block_id: BlockId,
+}
+
+/// Why a string is not a [`Permalink`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PermalinkError {
+    #[error("not a {PERMALINK_SCHEME}:// link")]
+    Scheme,
+    #[error("expected {PERMALINK_SCHEME}://kernel/context/block-key")]
+    Shape,
+    #[error("invalid kernel id '{0}'")]
+    Kernel(String),
+    #[error("invalid context id '{0}'")]
+    Context(String),
+    #[error("invalid block key '{0}'")]
+    Block(String),
+    #[error("block {block} is not in context {context}")]
+    ContextMismatch { context: String, block: String },
+}
+
+impl Permalink {
+    pub fn new(kernel_id: KernelId, block_id: BlockId) -> Self {
+        Self {
+            kernel_id,
+            block_id,
+        }
+    }
+
+    /// The context the block lives in.
+    pub fn context_id(&self) -> ContextId {
+        self.block_id.context_id
+    }
+
+    /// Parse `kaijutsu://kernel/context/block-key`. A trailing `/` is allowed.
+    pub fn parse(s: &str) -> Result<Self, PermalinkError> {
+        let rest = s
+            .trim()
+            .strip_prefix(PERMALINK_SCHEME)
+            .and_then(|r| r.strip_prefix("://"))
+            .ok_or(PermalinkError::Scheme)?;
+        let mut parts = rest.trim_end_matches('/').split('/');
+        let (Some(kernel), Some(context), Some(block), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(PermalinkError::Shape);
+        };
+        let kernel_id =
+            KernelId::parse(kernel).map_err(|_| PermalinkError::Kernel(kernel.to_string()))?;
+        let context_id =
+            ContextId::parse(context).map_err(|_| PermalinkError::Context(context.to_string()))?;
+        let block_id =
+            BlockId::from_key(block).ok_or_else(|| PermalinkError::Block(block.to_string()))?;
+        if block_id.context_id != context_id {
+            return Err(PermalinkError::ContextMismatch {
+                context: context_id.short(),
+                block: block.to_string(),
+            });
+        }
+        Ok(Self::new(kernel_id, block_id))
+    }
+}
+
+impl fmt::Display for Permalink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{PERMALINK_SCHEME}://{}/{}/{}",
+            self.kernel_id.to_hex(),
+            self.block_id.context_id.to_hex(),
+            self.block_id.to_key()
+        )
+    }
+}
+
+impl FromStr for Permalink {
+    type Err = PermalinkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::PrincipalId;
+
+    fn sample() -> Permalink {
+        Permalink::new(
+            KernelId::new(),
+            BlockId::new(ContextId::new(), PrincipalId::new(), 7),
+        )
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        let link = sample();
+        let s = link.to_string();
+        assert!(s.starts_with("kaijutsu://"));
+        assert_eq!(Permalink::parse(&s).unwrap(), link);
+        assert_eq!(format!("{s}/").parse::<Permalink>().unwrap(), link);
+    }
+
+    #[test]
+    fn rejects_malformed_links() {
+        let link = sample();
+        let kernel = link.kernel_id.to_hex();
+        let context = link.context_id().to_hex();
+        let key = link.block_id.to_key();
+        assert_eq!(
+            Permalink::parse(&format!("https://{kernel}/{context}/{key}")),
+            Err(PermalinkError::Scheme)
+        );
+        assert_eq!(
+            Permalink::parse(&format!("kaijutsu://{kernel}/{context}")),
+            Err(PermalinkError::Shape)
+        );
+        assert!(matches!(
+            Permalink::parse(&format!("kaijutsu://nope/{context}/{key}")),
+            Err(PermalinkError::Kernel(_))
+        ));
+        let other = ContextId::new().to_hex();
+        assert!(matches!(
+            Permalink::parse(&format!("kaijutsu://{kernel}/{other}/{key}")),
+            Err(PermalinkError::ContextMismatch { .. })
+        ));
+    }
+}
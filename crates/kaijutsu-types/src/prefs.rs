@@ -0,0 +1,110 @@
+//! Per-principal preferences.
+//!
+//! A small key/value store of a user's defaults, kept by the kernel (one row
+//! per principal and key in `KernelDb`) so they follow the user to every
+//! device that connects as them. Two keys are understood by the kernel itself;
+//! anything under `ui.` is opaque client state (the app's settings) that the
+//! kernel only stores.
+//!
+//! | Key               | Value                         | Used by                    |
+//! |-------------------|-------------------------------|----------------------------|
+//! | `default_model`   | `provider/model`              | new contexts you create    |
+//! | `default_consent` | `collaborative`/`autonomous`  | new contexts you create    |
+//! | `ui.*`            | anything                      | clients                    |
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::enums::ConsentMode;
+
+/// A principal's preferences, sorted by key.
+pub type Preferences = BTreeMap<String, String>;
+
+/// Model for contexts this principal creates, as `provider/model`.
+pub const PREF_DEFAULT_MODEL: &str = "default_model";
+
+/// Consent mode for contexts this principal creates.
+pub const PREF_DEFAULT_CONSENT: &str = "default_consent";
+
+/// Namespace for client-owned settings.
+pub const PREF_UI_PREFIX: &str = "ui.";
+
+/// Longest key accepted.
+pub const PREF_MAX_KEY_LEN: usize = 64;
+
+/// Longest value accepted, in bytes.
+pub const PREF_MAX_VALUE_LEN: usize = 4096;
+
+/// Check that `key` may be set to `value`. Unknown keys outside `ui.` are
+/// rejected so typos fail at the prompt rather than being silently ignored.
+pub fn validate_pref(key: &str, value: &str) -> Result<(), String> {
+    if key.is_empty() || key.len() > PREF_MAX_KEY_LEN {
+        return Err(format!("preference key must be 1-{PREF_MAX_KEY_LEN} bytes"));
+    }
+    if !key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err(format!(
+            "preference key '{key}' may only contain ASCII letters, digits, '_', '-' and '.'"
+        ));
+    }
+    if value.len() > PREF_MAX_VALUE_LEN {
+        return Err(format!("preference value exceeds {PREF_MAX_VALUE_LEN} bytes"));
+    }
+    match key {
+        PREF_DEFAULT_MODEL => match value.split_once('/') {
+            Some((provider, model)) if !provider.is_empty() && !model.is_empty() => Ok(()),
+            _ => Err(format!("{PREF_DEFAULT_MODEL} must be 'provider/model', got '{value}'")),
+        },
+        PREF_DEFAULT_CONSENT => ConsentMode::from_str(value).map(|_| ()).map_err(|_| {
+            format!("{PREF_DEFAULT_CONSENT} must be 'collaborative' or 'autonomous', got '{value}'")
+        }),
+        _ if key.starts_with(PREF_UI_PREFIX) && key.len() > PREF_UI_PREFIX.len() => Ok(()),
+        _ => Err(format!(
+            "unknown preference '{key}' (known: {PREF_DEFAULT_MODEL}, {PREF_DEFAULT_CONSENT}, {PREF_UI_PREFIX}*)"
+        )),
+    }
+}
+
+/// `default_model` split into `(provider, model)`, if set.
+pub fn default_model(prefs: &Preferences) -> Option<(&str, &str)> {
+    prefs.get(PREF_DEFAULT_MODEL)?.split_once('/')
+}
+
+/// `default_consent` parsed, if set and valid.
+pub fn default_consent(prefs: &Preferences) -> Option<ConsentMode> {
+    ConsentMode::from_str(prefs.get(PREF_DEFAULT_CONSENT)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_keys_are_validated() {
+        assert!(validate_pref(PREF_DEFAULT_MODEL, "anthropic/claude-sonnet").is_ok());
+        assert!(validate_pref(PREF_DEFAULT_MODEL, "claude-sonnet").is_err());
+        assert!(validate_pref(PREF_DEFAULT_CONSENT, "Autonomous").is_ok());
+        assert!(validate_pref(PREF_DEFAULT_CONSENT, "yolo").is_err());
+    }
+
+    #[test]
+    fn ui_namespace_is_open_and_everything_else_is_closed() {
+        assert!(validate_pref("ui.theme", "dark").is_ok());
+        assert!(validate_pref("ui.", "x").is_err());
+        assert!(validate_pref("default_modle", "a/b").is_err());
+        assert!(validate_pref("ui.bad key", "x").is_err());
+        assert!(validate_pref("ui.big", &"x".repeat(PREF_MAX_VALUE_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let mut prefs = Preferences::new();
+        assert_eq!(default_model(&prefs), None);
+        prefs.insert(PREF_DEFAULT_MODEL.into(), "deepseek/deepseek-chat".into());
+        prefs.insert(PREF_DEFAULT_CONSENT.into(), "autonomous".into());
+        assert_eq!(default_model(&prefs), Some(("deepseek", "deepseek-chat")));
+        assert_eq!(default_consent(&prefs), Some(ConsentMode::Autonomous));
+    }
+}
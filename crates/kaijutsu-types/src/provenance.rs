@@ -0,0 +1,86 @@
+//! [`AgentProvenance`] — the hosting agent session behind a block.
+//!
+//! `kaijutsu-mcp` runs inside a coding agent (Claude Code, …) and mirrors
+//! that agent's session into a context. `id.principal_id` says which
+//! principal wrote a block, but every mirrored block shares the MCP's
+//! principal; this records *which agent session* it came from, so blocks can
+//! be traced back across several agents working one kernel. Write-once
+//! metadata on the block (`BlockSnapshot::provenance`), stamped by the MCP
+//! from `kaijutsu-agent-tools` detection.
+
+use serde::{Deserialize, Serialize};
+
+/// The agent session that authored a block.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentProvenance {
+    /// Agent identifier (`claude-code`, `gemini-cli`).
+    pub agent: String,
+    /// Agent version string, when the agent reports one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The agent's own session id (a UUID for Claude Code).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Project directory the agent was working in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+}
+
+impl AgentProvenance {
+    /// A compact label: the agent, its version, and a short session id
+    /// (`claude-code 2.1.3 4f1c2a9b`).
+    pub fn chip(&self) -> String {
+        let mut out = self.agent.clone();
+        if let Some(version) = &self.version {
+            out.push(' ');
+            out.push_str(version);
+        }
+        if let Some(session) = &self.session_id {
+            out.push(' ');
+            out.extend(session.chars().take(8));
+        }
+        out
+    }
+
+    /// Whether this is session `session`, given in full or as a prefix.
+    pub fn is_session(&self, session: &str) -> bool {
+        !session.is_empty()
+            && self
+                .session_id
+                .as_deref()
+                .is_some_and(|id| id.starts_with(session))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claude() -> AgentProvenance {
+        AgentProvenance {
+            agent: "claude-code".into(),
+            version: Some("2.1.3".into()),
+            session_id: Some("4f1c2a9b-7e0d-4c55-9a61-0b8e2f3d1c77".into()),
+            project_dir: Some("/home/me/src/kaijutsu".into()),
+        }
+    }
+
+    #[test]
+    fn chip_shortens_the_session() {
+        assert_eq!(claude().chip(), "claude-code 2.1.3 4f1c2a9b");
+        let bare = AgentProvenance {
+            agent: "gemini-cli".into(),
+            ..Default::default()
+        };
+        assert_eq!(bare.chip(), "gemini-cli");
+    }
+
+    #[test]
+    fn session_matches_by_prefix() {
+        let p = claude();
+        assert!(p.is_session("4f1c2a9b"));
+        assert!(p.is_session("4f1c2a9b-7e0d-4c55-9a61-0b8e2f3d1c77"));
+        assert!(!p.is_session("5f1c"));
+        assert!(!p.is_session(""));
+    }
+}
@@ -0,0 +1,101 @@
+//! Why the kernel refused a client's pushed ops.
+//!
+//! `pushOps` merges a client's CRDT changes into a shared document. The CRDT
+//! accepts anything that decodes, so before the merge the kernel checks the
+//! blocks a push creates against the document's shape — a tool result needs
+//! its tool call, a parent has to exist, a config document holds one text
+//! block. A push with any [`PushViolation`] is refused whole; nothing from it
+//! is merged.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::BlockId;
+
+/// The constraint a pushed block broke.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushRule {
+    /// The block, its parent or its tool call belongs to another context.
+    ForeignBlock,
+    /// The parent is neither in the document nor created by the push.
+    UnknownParent,
+    /// The parent chain is a cycle or deeper than `MAX_DAG_DEPTH`.
+    TooDeep,
+    /// A tool result with no tool call, or one naming a block that isn't a
+    /// tool call.
+    OrphanToolResult,
+    /// A block kind the document kind doesn't hold.
+    KindNotAllowed,
+    /// A role the document kind doesn't hold.
+    RoleNotAllowed,
+    /// A second block in a single-block document (config, symlink).
+    SingleBlock,
+}
+
+impl PushRule {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ForeignBlock => "foreign_block",
+            Self::UnknownParent => "unknown_parent",
+            Self::TooDeep => "too_deep",
+            Self::OrphanToolResult => "orphan_tool_result",
+            Self::KindNotAllowed => "kind_not_allowed",
+            Self::RoleNotAllowed => "role_not_allowed",
+            Self::SingleBlock => "single_block",
+        }
+    }
+}
+
+impl fmt::Display for PushRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PushRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "foreign_block" => Self::ForeignBlock,
+            "unknown_parent" => Self::UnknownParent,
+            "too_deep" => Self::TooDeep,
+            "orphan_tool_result" => Self::OrphanToolResult,
+            "kind_not_allowed" => Self::KindNotAllowed,
+            "role_not_allowed" => Self::RoleNotAllowed,
+            "single_block" => Self::SingleBlock,
+            other => return Err(format!("unknown push rule '{other}'")),
+        })
+    }
+}
+
+/// One pushed block that broke one rule.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushViolation {
+    pub block_id: BlockId,
+    pub rule: PushRule,
+    /// What exactly was wrong, for the log and the client's error.
+    pub detail: String,
+}
+
+impl fmt::Display for PushViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "block {} [{}]: {}",
+            self.block_id, self.rule, self.detail
+        )
+    }
+}
+
+/// `violations` as one line: each [`PushViolation`], `; `-separated.
+pub fn describe_violations(violations: &[PushViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,250 @@
+//! Word-wrap metadata for long-line blocks.
+//!
+//! Agents often emit a whole paragraph — or a whole answer — as one line. That
+//! renders as a horizontal scroll and diffs as a single changed line. A
+//! block's [`Reflow`] (see [`crate::BlockSnapshot::reflow`]) records how its
+//! prose should wrap: a preferred width and whether existing newlines are hard
+//! breaks.
+//!
+//! Readers (`block_read`, `kj block read`) wrap a view with [`reflow_text`]
+//! and leave the CRDT text alone; `block_reflow` rewrites the text itself in
+//! one edit. Either way code survives untouched: a block whose
+//! [`language`](crate::BlockSnapshot::language) names a programming language
+//! is never rewrapped ([`reflows_language`]), and inside prose, fenced and
+//! indented code, tables, and headings pass through verbatim.
+
+use serde::{Deserialize, Serialize};
+
+/// Narrowest accepted wrap width.
+pub const MIN_REFLOW_WIDTH: u16 = 20;
+/// Widest accepted wrap width.
+pub const MAX_REFLOW_WIDTH: u16 = 1000;
+/// Width `block_reflow` uses when neither the caller nor the block names one.
+pub const DEFAULT_REFLOW_WIDTH: u16 = 80;
+
+/// How a block's prose wraps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Reflow {
+    /// Preferred line width in characters.
+    pub width: u16,
+    /// Treat every newline as a hard break: only overlong lines are split.
+    /// When false, single newlines inside a paragraph are soft breaks and the
+    /// paragraph is refilled; blank lines still separate paragraphs.
+    #[serde(default)]
+    pub preserve_newlines: bool,
+}
+
+impl Reflow {
+    /// A validated reflow setting.
+    pub fn new(width: u16, preserve_newlines: bool) -> Result<Self, String> {
+        if !(MIN_REFLOW_WIDTH..=MAX_REFLOW_WIDTH).contains(&width) {
+            return Err(format!(
+                "wrap width must be {MIN_REFLOW_WIDTH}-{MAX_REFLOW_WIDTH}, got {width}"
+            ));
+        }
+        Ok(Self {
+            width,
+            preserve_newlines,
+        })
+    }
+}
+
+/// Whether text tagged `language` is prose that may be rewrapped. Untagged,
+/// Markdown, and plain-text blocks are; anything naming a programming or data
+/// language is code and keeps its line structure.
+pub fn reflows_language(language: Option<&str>) -> bool {
+    matches!(language, None | Some("markdown" | "text"))
+}
+
+/// Rewrap `text` to `reflow.width`. Idempotent: reflowing the output again
+/// with the same setting returns it unchanged.
+pub fn reflow_text(text: &str, reflow: Reflow) -> String {
+    let width = usize::from(reflow.width);
+    let mut out: Vec<String> = Vec::new();
+    // Pending soft-wrapped paragraph: (first-line prefix, continuation
+    // prefix, words).
+    let mut para: Option<(String, String, Vec<&str>)> = None;
+    let mut fence: Option<&str> = None;
+
+    let flush = |para: &mut Option<(String, String, Vec<&str>)>, out: &mut Vec<String>| {
+        if let Some((first, rest, words)) = para.take() {
+            wrap_words(&words, width, &first, &rest, out);
+        }
+    };
+
+    for line in text.split('\n') {
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = fence {
+            out.push(line.to_string());
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            flush(&mut para, &mut out);
+            fence = Some(marker);
+            out.push(line.to_string());
+            continue;
+        }
+        if is_verbatim(line) {
+            flush(&mut para, &mut out);
+            out.push(line.to_string());
+            continue;
+        }
+
+        let (first, rest, body) = split_prefix(line);
+        let starts_item = first != rest;
+        if reflow.preserve_newlines {
+            let words: Vec<&str> = body.split_whitespace().collect();
+            wrap_words(&words, width, &first, &rest, &mut out);
+            continue;
+        }
+        match &mut para {
+            Some((_, cont, words)) if !starts_item && *cont == rest => {
+                words.extend(body.split_whitespace());
+            }
+            _ => {
+                flush(&mut para, &mut out);
+                para = Some((first, rest, body.split_whitespace().collect()));
+            }
+        }
+    }
+    flush(&mut para, &mut out);
+    out.join("\n")
+}
+
+/// Lines that never wrap or join: blank lines, indented code, tables,
+/// headings, and thematic breaks.
+fn is_verbatim(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty()
+        || line.starts_with('\t')
+        || line.starts_with("    ")
+        || trimmed.starts_with('|')
+        || trimmed.starts_with('#')
+        || (trimmed.len() >= 3 && trimmed.chars().all(|c| matches!(c, '-' | '*' | '_')))
+}
+
+/// Split a prose line into (first-line prefix, continuation prefix, body).
+/// The prefix carries leading indent, `>` quote markers, and a list marker;
+/// continuation lines hang under the item's text.
+fn split_prefix(line: &str) -> (String, String, &str) {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let mut first = line[..indent].to_string();
+    let mut rest = first.clone();
+    let mut body = &line[indent..];
+
+    while let Some(after) = body.strip_prefix('>') {
+        let after = after.strip_prefix(' ').unwrap_or(after);
+        first.push_str("> ");
+        rest.push_str("> ");
+        body = after;
+    }
+
+    let bullet = ["- ", "* ", "+ "].into_iter().find(|m| body.starts_with(m));
+    let marker_len = bullet.map(str::len).or_else(|| {
+        let digits = body.chars().take_while(char::is_ascii_digit).count();
+        (digits > 0 && digits < 10 && body[digits..].starts_with(". ")).then_some(digits + 2)
+    });
+    if let Some(len) = marker_len {
+        first.push_str(&body[..len]);
+        rest.push_str(&" ".repeat(len));
+        body = &body[len..];
+    }
+    (first, rest, body)
+}
+
+/// Greedy fill. A word longer than the width (a URL, a path) gets a line to
+/// itself rather than being split.
+fn wrap_words(words: &[&str], width: usize, first: &str, rest: &str, out: &mut Vec<String>) {
+    if words.is_empty() {
+        out.push(first.trim_end().to_string());
+        return;
+    }
+    let mut line = first.to_string();
+    let mut len = first.chars().count();
+    let mut empty = true;
+    for word in words {
+        let word_len = word.chars().count();
+        if !empty && len + 1 + word_len > width {
+            out.push(std::mem::replace(&mut line, rest.to_string()));
+            len = rest.chars().count();
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+            len += 1;
+        }
+        line.push_str(word);
+        len += word_len;
+        empty = false;
+    }
+    out.push(line);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrap(width: u16) -> Reflow {
+        Reflow::new(width, false).unwrap()
+    }
+
+    #[test]
+    fn soft_breaks_refill_and_blank_lines_separate_paragraphs() {
+        let text = "one two three\nfour five six seven\n\neight nine";
+        assert_eq!(
+            reflow_text(text, wrap(20)),
+            "one two three four\nfive six seven\n\neight nine"
+        );
+    }
+
+    #[test]
+    fn preserve_newlines_only_splits_long_lines() {
+        let text = "short\nthis line is a good deal longer than twenty";
+        let reflow = Reflow::new(20, true).unwrap();
+        assert_eq!(
+            reflow_text(text, reflow),
+            "short\nthis line is a good\ndeal longer than\ntwenty"
+        );
+    }
+
+    #[test]
+    fn code_and_structure_pass_through() {
+        let text = "# A heading that is far too long to fit\n\
+                    ```rust\nfn main() { println!(\"a long line of code that stays\"); }\n```\n\
+                    \x20   indented code that is also long and stays\n\
+                    | a | table | row | that | is | long | and | stays |";
+        assert_eq!(reflow_text(text, wrap(20)), text);
+    }
+
+    #[test]
+    fn list_items_hang_and_stay_separate() {
+        let text = "- first item runs long enough to wrap\n- second\n  continued here";
+        assert_eq!(
+            reflow_text(text, wrap(20)),
+            "- first item runs\n  long enough to\n  wrap\n- second continued\n  here"
+        );
+    }
+
+    #[test]
+    fn reflow_is_idempotent_and_keeps_trailing_newline() {
+        let text = "> quoted text that goes on and on past the width\n\nsee https://example.com/a/very/long/url/that/cannot/break\n";
+        let once = reflow_text(text, wrap(24));
+        assert_eq!(reflow_text(&once, wrap(24)), once);
+        assert!(once.ends_with('\n'));
+        assert!(once.starts_with("> quoted text that goes\n> on"));
+        assert!(once.contains("\nhttps://example.com/a/very/long/url/that/cannot/break\n"));
+    }
+
+    #[test]
+    fn width_and_language_gates() {
+        assert!(Reflow::new(MIN_REFLOW_WIDTH - 1, false).is_err());
+        assert!(Reflow::new(MAX_REFLOW_WIDTH + 1, false).is_err());
+        assert!(reflows_language(None));
+        assert!(reflows_language(Some("markdown")));
+        assert!(!reflows_language(Some("rust")));
+    }
+}
@@ -0,0 +1,109 @@
+//! [`ReplLanguage`] — the interpreters a `Repl` block can be bound to.
+//!
+//! A REPL session is a [`BlockKind::Repl`](crate::BlockKind::Repl) block with
+//! no parent: its content is the input log and its `language` names the
+//! interpreter. Each submitted line batch gets a child `Repl` block holding
+//! the echoed input and the output that followed. The kernel owns the
+//! processes (`kaijutsu-kernel/src/repl.rs`); this module is the part the app
+//! and hydration share — names, prompts, and the echo format.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Interpreter behind a REPL session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplLanguage {
+    Python,
+    Node,
+    Kaish,
+}
+
+impl ReplLanguage {
+    pub const ALL: [ReplLanguage; 3] = [Self::Python, Self::Node, Self::Kaish];
+
+    /// The block `language` tag of sessions in this language.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Python => "python",
+            Self::Node => "javascript",
+            Self::Kaish => "kaish",
+        }
+    }
+
+    /// Parse a language name or common alias (`py`, `js`, `sh`).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "python" | "python3" | "py" => Some(Self::Python),
+            "node" | "nodejs" | "javascript" | "js" => Some(Self::Node),
+            "kaish" | "kai" | "sh" | "shell" => Some(Self::Kaish),
+            _ => None,
+        }
+    }
+
+    /// Prompt shown before each echoed input line.
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            Self::Python => ">>> ",
+            Self::Node => "> ",
+            Self::Kaish => "$ ",
+        }
+    }
+
+    /// Whether the interpreter is a host process (needs the `exec`
+    /// authority) rather than the kernel's embedded shell.
+    pub fn is_host_process(&self) -> bool {
+        !matches!(self, Self::Kaish)
+    }
+}
+
+impl fmt::Display for ReplLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ReplLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+            .ok_or_else(|| format!("unknown REPL language '{s}' (expected python, node, or kaish)"))
+    }
+}
+
+/// The echo that opens an output block: each input line behind the prompt.
+pub fn repl_echo(language: ReplLanguage, input: &str) -> String {
+    let mut out = String::with_capacity(input.len() + 8);
+    for line in input.lines() {
+        out.push_str(language.prompt());
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_round_trips_through_its_tag() {
+        for lang in ReplLanguage::ALL {
+            assert_eq!(ReplLanguage::parse(lang.as_str()), Some(lang));
+        }
+        assert_eq!("py".parse::<ReplLanguage>(), Ok(ReplLanguage::Python));
+        assert!("ruby".parse::<ReplLanguage>().is_err());
+    }
+
+    #[test]
+    fn echo_prompts_every_line() {
+        assert_eq!(
+            repl_echo(ReplLanguage::Python, "x = 1\nprint(x)\n"),
+            ">>> x = 1\n>>> print(x)\n"
+        );
+        assert_eq!(repl_echo(ReplLanguage::Kaish, ""), "");
+    }
+}
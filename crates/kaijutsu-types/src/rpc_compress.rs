@@ -0,0 +1,390 @@
+//! zstd framing for the Cap'n Proto RPC byte stream.
+//!
+//! Negotiated per channel by subsystem name: a client that wants compression
+//! requests [`SSH_RPC_ZSTD_SUBSYSTEM`]; a server that knows the name acks and
+//! both ends wrap the channel stream in [`CompressedStream`]. A server that
+//! doesn't refuses the request and the client falls back to plain
+//! [`SSH_RPC_SUBSYSTEM`] on a fresh channel, so either side can be upgraded
+//! first.
+//!
+//! The wrapper sits below `twoparty::VatNetwork` and knows nothing about
+//! Cap'n Proto: writes are buffered until a flush (the VatNetwork flushes
+//! after each outgoing message) or [`MAX_FRAME`] bytes, then sent as one
+//! frame — zstd-compressed when the frame is at least
+//! [`RPC_COMPRESS_THRESHOLD`] bytes and compression actually shrinks it,
+//! stored otherwise. Small request/response traffic pays five bytes of
+//! header; document states, oplogs and attachments shrink.
+//!
+//! Frame: `tag: u8` (0 stored, 1 zstd), `raw_len: u32 LE`, then for zstd
+//! frames `wire_len: u32 LE`, then the payload.
+//!
+//! [`SSH_RPC_SUBSYSTEM`]: crate::SSH_RPC_SUBSYSTEM
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use futures_io::{AsyncRead, AsyncWrite};
+
+/// SSH subsystem name for the zstd-framed RPC channel — [`crate::SSH_RPC_SUBSYSTEM`]
+/// with [`CompressedStream`] on both ends.
+pub const SSH_RPC_ZSTD_SUBSYSTEM: &str = "kaijutsu-rpc+zstd";
+
+/// Frames smaller than this are sent stored: compressing a 200-byte RPC
+/// call costs more than it saves.
+pub const RPC_COMPRESS_THRESHOLD: usize = 4 * 1024;
+
+/// Largest plaintext frame either side writes, and the cap on what a reader
+/// will decode — a corrupt or hostile header can't make it allocate more.
+pub const MAX_FRAME: usize = 8 * 1024 * 1024;
+
+/// zstd level: fast enough to stay off the profile, most of the ratio.
+const ZSTD_LEVEL: i32 = 3;
+
+const TAG_STORED: u8 = 0;
+const TAG_ZSTD: u8 = 1;
+
+/// Which framing a negotiated RPC channel uses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RpcCompression {
+    None,
+    Zstd,
+}
+
+impl RpcCompression {
+    /// The subsystem name that selects this framing.
+    pub fn subsystem(self) -> &'static str {
+        match self {
+            Self::None => crate::SSH_RPC_SUBSYSTEM,
+            Self::Zstd => SSH_RPC_ZSTD_SUBSYSTEM,
+        }
+    }
+
+    /// The framing a subsystem name selects, if it is an RPC subsystem.
+    pub fn from_subsystem(name: &str) -> Option<Self> {
+        match name {
+            crate::SSH_RPC_SUBSYSTEM => Some(Self::None),
+            SSH_RPC_ZSTD_SUBSYSTEM => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Encode `raw` as one frame, appending to `out`.
+fn encode_frame(raw: &[u8], out: &mut Vec<u8>) {
+    if raw.len() >= RPC_COMPRESS_THRESHOLD
+        && let Ok(packed) = zstd::bulk::compress(raw, ZSTD_LEVEL)
+        && packed.len() < raw.len()
+    {
+        out.push(TAG_ZSTD);
+        out.extend_from_slice(&(raw.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(packed.len() as u32).to_le_bytes());
+        out.extend_from_slice(&packed);
+    } else {
+        out.push(TAG_STORED);
+        out.extend_from_slice(&(raw.len() as u32).to_le_bytes());
+        out.extend_from_slice(raw);
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Where the reader is within the current frame.
+enum ReadState {
+    /// Filling the 5-byte tag + raw_len header.
+    Header { buf: [u8; 5], filled: usize },
+    /// Filling a zstd frame's `wire_len`.
+    WireLen {
+        raw_len: usize,
+        buf: [u8; 4],
+        filled: usize,
+    },
+    /// Filling the payload.
+    Payload {
+        tag: u8,
+        raw_len: usize,
+        buf: Vec<u8>,
+        filled: usize,
+    },
+}
+
+/// An `AsyncRead + AsyncWrite` stream carrying [module](self) frames over
+/// `inner`.
+pub struct CompressedStream<S> {
+    inner: S,
+    read_state: ReadState,
+    /// Decoded bytes not yet handed to the caller.
+    plain: Vec<u8>,
+    plain_pos: usize,
+    /// Written bytes not yet framed.
+    pending: Vec<u8>,
+    /// Encoded frames not yet written to `inner`.
+    out: Vec<u8>,
+    out_pos: usize,
+}
+
+impl<S> CompressedStream<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            read_state: ReadState::Header {
+                buf: [0; 5],
+                filled: 0,
+            },
+            plain: Vec::new(),
+            plain_pos: 0,
+            pending: Vec::new(),
+            out: Vec::new(),
+            out_pos: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: AsyncWrite + Unpin> CompressedStream<S> {
+    /// Frame everything written so far.
+    fn seal(&mut self) {
+        if !self.pending.is_empty() {
+            encode_frame(&self.pending, &mut self.out);
+            self.pending.clear();
+        }
+    }
+
+    /// Write out encoded frames until none are left.
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.out_pos < self.out.len() {
+            let n = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.out[self.out_pos..]))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.out_pos += n;
+        }
+        self.out.clear();
+        self.out_pos = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + Unpin> CompressedStream<S> {
+    /// Read into `buf[*filled..]`; `Ok(false)` at a clean EOF (nothing of
+    /// the current frame read yet).
+    fn poll_fill(
+        inner: &mut S,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        filled: &mut usize,
+        at_frame_start: bool,
+    ) -> Poll<io::Result<bool>> {
+        while *filled < buf.len() {
+            let n = ready!(Pin::new(&mut *inner).poll_read(cx, &mut buf[*filled..]))?;
+            if n == 0 {
+                if at_frame_start && *filled == 0 {
+                    return Poll::Ready(Ok(false));
+                }
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+            *filled += n;
+        }
+        Poll::Ready(Ok(true))
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for CompressedStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        out: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            if this.plain_pos < this.plain.len() {
+                let n = out.len().min(this.plain.len() - this.plain_pos);
+                out[..n].copy_from_slice(&this.plain[this.plain_pos..this.plain_pos + n]);
+                this.plain_pos += n;
+                return Poll::Ready(Ok(n));
+            }
+            match &mut this.read_state {
+                ReadState::Header { buf, filled } => {
+                    if !ready!(Self::poll_fill(&mut this.inner, cx, buf, filled, true))? {
+                        return Poll::Ready(Ok(0));
+                    }
+                    let tag = buf[0];
+                    let raw_len = u32::from_le_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize;
+                    if raw_len > MAX_FRAME {
+                        return Poll::Ready(Err(invalid(format!(
+                            "rpc frame of {raw_len} bytes exceeds {MAX_FRAME}"
+                        ))));
+                    }
+                    this.read_state = match tag {
+                        TAG_STORED => ReadState::Payload {
+                            tag,
+                            raw_len,
+                            buf: vec![0; raw_len],
+                            filled: 0,
+                        },
+                        TAG_ZSTD => ReadState::WireLen {
+                            raw_len,
+                            buf: [0; 4],
+                            filled: 0,
+                        },
+                        other => {
+                            return Poll::Ready(Err(invalid(format!(
+                                "unknown rpc frame tag {other}"
+                            ))));
+                        }
+                    };
+                }
+                ReadState::WireLen {
+                    raw_len,
+                    buf,
+                    filled,
+                } => {
+                    ready!(Self::poll_fill(&mut this.inner, cx, buf, filled, false))?;
+                    let wire_len = u32::from_le_bytes(*buf) as usize;
+                    if wire_len > MAX_FRAME {
+                        return Poll::Ready(Err(invalid(format!(
+                            "compressed rpc frame of {wire_len} bytes exceeds {MAX_FRAME}"
+                        ))));
+                    }
+                    this.read_state = ReadState::Payload {
+                        tag: TAG_ZSTD,
+                        raw_len: *raw_len,
+                        buf: vec![0; wire_len],
+                        filled: 0,
+                    };
+                }
+                ReadState::Payload {
+                    tag,
+                    raw_len,
+                    buf,
+                    filled,
+                } => {
+                    ready!(Self::poll_fill(&mut this.inner, cx, buf, filled, false))?;
+                    let payload = std::mem::take(buf);
+                    this.plain = if *tag == TAG_ZSTD {
+                        let decoded = zstd::bulk::decompress(&payload, *raw_len)?;
+                        if decoded.len() != *raw_len {
+                            return Poll::Ready(Err(invalid(format!(
+                                "rpc frame decoded to {} bytes, header said {raw_len}",
+                                decoded.len()
+                            ))));
+                        }
+                        decoded
+                    } else {
+                        payload
+                    };
+                    this.plain_pos = 0;
+                    this.read_state = ReadState::Header {
+                        buf: [0; 5],
+                        filled: 0,
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for CompressedStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        // Backpressure: accept nothing new while a frame is still in flight.
+        ready!(this.poll_drain(cx))?;
+        let n = buf.len().min(MAX_FRAME - this.pending.len());
+        this.pending.extend_from_slice(&buf[..n]);
+        if this.pending.len() == MAX_FRAME {
+            this.seal();
+            // Start sending; the bytes are accepted either way.
+            if let Poll::Ready(Err(e)) = this.poll_drain(cx) {
+                return Poll::Ready(Err(e));
+            }
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        this.seal();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        this.seal();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::io::Cursor;
+    use futures::{AsyncReadExt, AsyncWriteExt};
+
+    /// Write `chunks` (flushing after each) and read everything back.
+    fn roundtrip(chunks: &[Vec<u8>]) -> (Vec<u8>, usize) {
+        futures::executor::block_on(async {
+            let mut writer = CompressedStream::new(Cursor::new(Vec::new()));
+            for chunk in chunks {
+                writer.write_all(chunk).await.unwrap();
+                writer.flush().await.unwrap();
+            }
+            let wire = writer.into_inner().into_inner();
+            let wire_len = wire.len();
+            let mut reader = CompressedStream::new(Cursor::new(wire));
+            let mut plain = Vec::new();
+            reader.read_to_end(&mut plain).await.unwrap();
+            (plain, wire_len)
+        })
+    }
+
+    #[test]
+    fn small_frames_are_stored_and_large_ones_shrink() {
+        let small = b"bootstrap".to_vec();
+        let (plain, wire) = roundtrip(std::slice::from_ref(&small));
+        assert_eq!(plain, small);
+        assert_eq!(wire, small.len() + 5);
+
+        let oplog: Vec<u8> = b"insert block text ".repeat(4096);
+        let (plain, wire) = roundtrip(&[small.clone(), oplog.clone()]);
+        assert_eq!(plain, [small, oplog.clone()].concat());
+        assert!(wire < oplog.len() / 4, "{wire} bytes on the wire");
+    }
+
+    #[test]
+    fn writes_past_max_frame_split_into_frames() {
+        let big: Vec<u8> = (0..MAX_FRAME + 1000).map(|i| (i % 251) as u8).collect();
+        let (plain, _) = roundtrip(std::slice::from_ref(&big));
+        assert_eq!(plain, big);
+    }
+
+    #[test]
+    fn oversized_header_is_rejected() {
+        let mut wire = vec![TAG_STORED];
+        wire.extend_from_slice(&((MAX_FRAME + 1) as u32).to_le_bytes());
+        let err = futures::executor::block_on(async {
+            let mut reader = CompressedStream::new(Cursor::new(wire));
+            let mut buf = [0u8; 16];
+            reader.read(&mut buf).await.unwrap_err()
+        });
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn subsystem_names_roundtrip() {
+        for c in [RpcCompression::None, RpcCompression::Zstd] {
+            assert_eq!(RpcCompression::from_subsystem(c.subsystem()), Some(c));
+        }
+        assert_eq!(RpcCompression::from_subsystem("sftp"), None);
+    }
+}
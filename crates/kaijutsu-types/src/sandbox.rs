@@ -0,0 +1,197 @@
+//! Per-context sandbox profiles for host command execution.
+//!
+//! A context with a profile runs every host binary its shell launches — from
+//! the model's `shell` tool, rc scripts, hooks and the interactive shell alike
+//! — through the kernel's sandboxed launcher:
+//!
+//! - `binaries` lists the only host binaries it may run, by name (looked up on
+//!   the kernel's `$PATH`) or by absolute path;
+//! - `fs_from_mounts` confines host filesystem access to the real directories
+//!   behind the kernel's VFS mounts (read-only mounts stay read-only), plus
+//!   read access to [`SANDBOX_SYSTEM_PATHS`] so binaries can load;
+//! - `network: false` gives the process an empty network namespace;
+//! - `limits` become rlimits on the process.
+//!
+//! Profiles are set over RPC (`setSandboxProfile`) — never by the context
+//! itself — and apply whatever the context's consent mode. They follow forks.
+//! A context without a profile runs host commands under its loadout's `exec`
+//! authority alone.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Host directories a sandboxed process may always read, when
+/// `fs_from_mounts` is set: what dynamically linked binaries need to load and
+/// start. Reading, not executing — only the allowlisted binary and its
+/// loader may be exec'd.
+pub const SANDBOX_SYSTEM_PATHS: &[&str] = &["/usr", "/bin", "/sbin", "/lib", "/lib64", "/etc"];
+
+/// Longest binary list accepted.
+pub const SANDBOX_MAX_BINARIES: usize = 256;
+
+/// How a context may run host binaries.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxProfile {
+    /// Binaries the context may run: bare names match commands invoked by
+    /// name, absolute paths match the resolved binary. Empty allows none.
+    #[serde(default)]
+    pub binaries: Vec<String>,
+    /// Confine host filesystem access to the VFS mounts' real directories.
+    #[serde(default)]
+    pub fs_from_mounts: bool,
+    /// Allow network access.
+    #[serde(default)]
+    pub network: bool,
+    #[serde(default)]
+    pub limits: SandboxLimits,
+}
+
+/// Resource limits for a sandboxed process. `None` leaves the kernel's own
+/// limit in place.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxLimits {
+    /// CPU time in seconds (`RLIMIT_CPU`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_secs: Option<u64>,
+    /// Address space in bytes (`RLIMIT_AS`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+    /// Largest file the process may write, in bytes (`RLIMIT_FSIZE`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_size_bytes: Option<u64>,
+    /// Open file descriptors (`RLIMIT_NOFILE`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u64>,
+    /// Processes owned by the kernel's user, counted across the host
+    /// (`RLIMIT_NPROC`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processes: Option<u64>,
+}
+
+impl SandboxLimits {
+    /// Whether any limit is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl SandboxProfile {
+    /// Check the profile before storing it.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.binaries.len() > SANDBOX_MAX_BINARIES {
+            return Err(format!(
+                "sandbox allows at most {SANDBOX_MAX_BINARIES} binaries, got {}",
+                self.binaries.len()
+            ));
+        }
+        for binary in &self.binaries {
+            if binary.is_empty() || binary.contains('\0') {
+                return Err("sandbox binary names must be non-empty".to_string());
+            }
+            if binary.contains('/') && !binary.starts_with('/') {
+                return Err(format!(
+                    "sandbox binary '{binary}' must be a bare name or an absolute path"
+                ));
+            }
+        }
+        let limits = [
+            ("cpu_secs", self.limits.cpu_secs),
+            ("memory_bytes", self.limits.memory_bytes),
+            ("file_size_bytes", self.limits.file_size_bytes),
+            ("open_files", self.limits.open_files),
+            ("processes", self.limits.processes),
+        ];
+        if let Some((name, _)) = limits.iter().find(|(_, v)| *v == Some(0)) {
+            return Err(format!("sandbox limit {name} must be greater than 0"));
+        }
+        Ok(())
+    }
+
+    /// Whether `invoked` (the command as typed), resolved to `resolved`, may
+    /// run. A bare-name entry only matches a command invoked by that bare
+    /// name, so allowing `ls` doesn't allow `./ls`; an absolute entry matches
+    /// the resolved binary however it was invoked.
+    pub fn allows_binary(&self, invoked: &str, resolved: &str) -> bool {
+        self.binaries.iter().any(|entry| {
+            if entry.starts_with('/') {
+                entry == resolved
+            } else {
+                !invoked.contains('/') && entry == invoked
+            }
+        })
+    }
+}
+
+impl fmt::Display for SandboxProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.binaries.is_empty() {
+            write!(f, "binaries: none")?;
+        } else {
+            write!(f, "binaries: {}", self.binaries.join(", "))?;
+        }
+        write!(
+            f,
+            "; filesystem: {}; network: {}",
+            if self.fs_from_mounts { "mounts" } else { "host" },
+            if self.network { "on" } else { "off" },
+        )?;
+        if !self.limits.is_empty() {
+            write!(f, "; limits: {}", self.limits)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SandboxLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            self.cpu_secs.map(|v| format!("cpu {v}s")),
+            self.memory_bytes.map(|v| format!("memory {v}B")),
+            self.file_size_bytes.map(|v| format!("file size {v}B")),
+            self.open_files.map(|v| format!("open files {v}")),
+            self.processes.map(|v| format!("processes {v}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_names_only_match_bare_invocations() {
+        let profile = SandboxProfile {
+            binaries: vec!["git".into(), "/usr/bin/rg".into()],
+            ..Default::default()
+        };
+        assert!(profile.allows_binary("git", "/usr/bin/git"));
+        assert!(!profile.allows_binary("./git", "/home/me/git"));
+        assert!(!profile.allows_binary("/usr/bin/git", "/usr/bin/git"));
+        assert!(profile.allows_binary("rg", "/usr/bin/rg"));
+        assert!(profile.allows_binary("/usr/bin/rg", "/usr/bin/rg"));
+        assert!(!profile.allows_binary("curl", "/usr/bin/curl"));
+    }
+
+    #[test]
+    fn validate_rejects_relative_paths_and_zero_limits() {
+        let mut profile = SandboxProfile {
+            binaries: vec!["bin/tool".into()],
+            ..Default::default()
+        };
+        assert!(profile.validate().is_err());
+        profile.binaries = vec!["tool".into()];
+        profile.limits.open_files = Some(0);
+        assert!(profile.validate().unwrap_err().contains("open_files"));
+        profile.limits.open_files = Some(64);
+        assert!(profile.validate().is_ok());
+        assert_eq!(
+            profile.to_string(),
+            "binaries: tool; filesystem: host; network: off; limits: open files 64"
+        );
+    }
+}
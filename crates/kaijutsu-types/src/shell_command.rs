@@ -0,0 +1,150 @@
+//! Structured metadata for shell command blocks.
+//!
+//! A shell `ToolCall` block keeps the raw command line in `tool_input`
+//! (`{"code": ...}`); [`ShellCommand`] records how it ran — the word-split
+//! argv, the working directory, the context's env overrides, and who asked —
+//! as write-once metadata on the block (`BlockSnapshot::shell_command`). That
+//! is enough to audit a command after the fact and to re-run it as it ran
+//! (`shellRerun`), whatever the context's shell state is by then.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::BlockId;
+use crate::ids::PrincipalId;
+
+/// How a shell command block ran.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShellCommand {
+    /// The command line split into words as the shell would split it: quotes
+    /// removed, escapes applied, no expansion. Control operators (`|`, `;`,
+    /// `&&`, `>` …) are words of their own.
+    pub argv: Vec<String>,
+    /// Working directory the command ran in.
+    pub cwd: String,
+    /// The context's exported env overrides in effect (`kj context set
+    /// --env`) — the delta from the shell's default environment.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// The principal that asked for the command.
+    pub requested_by: PrincipalId,
+    /// The command block this one re-ran, when it is a re-run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerun_of: Option<BlockId>,
+}
+
+impl ShellCommand {
+    /// A compact label for the command's surroundings: the cwd, plus how many
+    /// env overrides it ran with (`/mnt/project +2 env`).
+    pub fn chip(&self) -> String {
+        match self.env.len() {
+            0 => self.cwd.clone(),
+            n => format!("{} +{n} env", self.cwd),
+        }
+    }
+}
+
+/// Split a command line into words (see [`ShellCommand::argv`]). An
+/// unterminated quote runs to the end of the line.
+pub fn parse_argv(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // A word may be empty but still present (`''`), so track it separately.
+    let mut in_word = false;
+    let mut chars = line.chars().peekable();
+
+    fn finish(words: &mut Vec<String>, word: &mut String, in_word: &mut bool) {
+        if *in_word {
+            words.push(std::mem::take(word));
+            *in_word = false;
+        }
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => finish(&mut words, &mut word, &mut in_word),
+            '\'' => {
+                in_word = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    word.push(c);
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' if matches!(chars.peek(), Some('"' | '\\' | '$' | '`')) => {
+                            word.extend(chars.next());
+                        }
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                word.extend(chars.next());
+            }
+            '|' | '&' | ';' | '<' | '>' => {
+                finish(&mut words, &mut word, &mut in_word);
+                let mut op = c.to_string();
+                if let Some(&next) = chars.peek()
+                    && (next == c && c != ';' || c == '>' && next == '&')
+                {
+                    op.push(next);
+                    chars.next();
+                }
+                words.push(op);
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    finish(&mut words, &mut word, &mut in_word);
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_argv_splits_like_a_shell() {
+        assert_eq!(
+            parse_argv("cargo test -p kaijutsu-types"),
+            ["cargo", "test", "-p", "kaijutsu-types"]
+        );
+        assert_eq!(
+            parse_argv(r#"git commit -m "fix: the \"bug\"" ''"#),
+            ["git", "commit", "-m", r#"fix: the "bug""#, ""]
+        );
+        assert_eq!(parse_argv("echo it\\'s 'a b'c"), ["echo", "it's", "a bc"]);
+        assert_eq!(
+            parse_argv("ls|wc -l && echo ok>out 2>&1"),
+            [
+                "ls", "|", "wc", "-l", "&&", "echo", "ok", ">", "out", "2", ">&", "1"
+            ]
+        );
+        assert!(parse_argv("   ").is_empty());
+    }
+
+    #[test]
+    fn chip_counts_env_overrides() {
+        let mut cmd = ShellCommand {
+            argv: vec!["ls".into()],
+            cwd: "/mnt/project".into(),
+            env: BTreeMap::new(),
+            requested_by: PrincipalId::nil(),
+            rerun_of: None,
+        };
+        assert_eq!(cmd.chip(), "/mnt/project");
+        cmd.env.insert("RUST_LOG".into(), "debug".into());
+        assert_eq!(cmd.chip(), "/mnt/project +1 env");
+    }
+}
@@ -0,0 +1,183 @@
+//! Block edit suggestions — proposed changes that wait for approval.
+//!
+//! A suggestion is a patch against a block's text as it stood when the
+//! suggestion was made (`base`), stored instead of applied. The block's owner
+//! accepts it, which applies the patch as ordinary CRDT text ops authored by
+//! the proposer, or rejects it. Positions are in chars, like the CRDT text
+//! layer. A patch only applies to the text it was made against: if the block
+//! changed in the meantime the suggestion is stale and must be re-proposed.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumString;
+
+use crate::block::BlockId;
+use crate::ids::PrincipalId;
+
+/// One splice of a suggestion's patch: at char `pos` of the base text,
+/// remove `delete` chars and put `insert` in their place.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub pos: usize,
+    pub delete: usize,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub insert: String,
+}
+
+/// Where a suggestion stands.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum SuggestionState {
+    /// Waiting for a decision.
+    #[default]
+    Open,
+    /// Applied to the block.
+    Accepted,
+    /// Turned down, or withdrawn by its proposer.
+    Rejected,
+}
+
+impl SuggestionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Accepted => "accepted",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+impl fmt::Display for SuggestionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A proposed edit to one block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suggestion {
+    /// Kernel-assigned, monotonically increasing per kernel.
+    pub id: u64,
+    pub block_id: BlockId,
+    pub proposer: PrincipalId,
+    /// The block's text the patch was made against.
+    pub base: String,
+    /// Non-overlapping, in ascending `pos` order.
+    pub edits: Vec<TextEdit>,
+    /// Why the change is proposed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub state: SuggestionState,
+    /// Unix millis.
+    pub created_at: u64,
+    /// Who accepted or rejected it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<PrincipalId>,
+    /// Unix millis of the decision.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<u64>,
+    /// Why it was rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Suggestion {
+    /// The block's text as it would read once accepted.
+    pub fn proposed(&self) -> Result<String, String> {
+        apply_edits(&self.base, &self.edits)
+    }
+}
+
+/// The patch turning `base` into `proposed`, at word granularity inside
+/// changed lines (see [`crate::diff::edits`]). Empty when they are equal.
+pub fn edits_between(base: &str, proposed: &str) -> Vec<TextEdit> {
+    crate::diff::edits(base, proposed)
+        .into_iter()
+        .map(|edit| TextEdit {
+            pos: base[..edit.old.start].chars().count(),
+            delete: base[edit.old.clone()].chars().count(),
+            insert: proposed[edit.new].to_string(),
+        })
+        .collect()
+}
+
+/// Apply a patch to the text it was made against. Fails when the edits
+/// overlap, are out of order, or run past the end of `base`.
+pub fn apply_edits(base: &str, edits: &[TextEdit]) -> Result<String, String> {
+    let chars: Vec<char> = base.chars().collect();
+    let mut out = String::with_capacity(base.len());
+    let mut at = 0;
+    for edit in edits {
+        if edit.pos < at {
+            return Err(format!("edit at {} overlaps the one before it", edit.pos));
+        }
+        let end = edit.pos + edit.delete;
+        if end > chars.len() {
+            return Err(format!(
+                "edit {}..{end} runs past the end of the text ({} chars)",
+                edit.pos,
+                chars.len()
+            ));
+        }
+        out.extend(&chars[at..edit.pos]);
+        out.push_str(&edit.insert);
+        at = end;
+    }
+    out.extend(&chars[at..]);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_between_round_trips_through_apply() {
+        let cases = [
+            ("fn main() {}\n", "fn main() {\n    run();\n}\n"),
+            ("naïve café\nsecond line\n", "naïve bistro\nsecond line\n"),
+            ("drop me\nkeep me\n", "keep me\n"),
+            ("", "from nothing"),
+            ("same", "same"),
+        ];
+        for (base, proposed) in cases {
+            let edits = edits_between(base, proposed);
+            assert_eq!(apply_edits(base, &edits).as_deref(), Ok(proposed));
+        }
+        assert!(edits_between("same", "same").is_empty());
+    }
+
+    #[test]
+    fn edit_positions_are_chars_not_bytes() {
+        let edits = edits_between("café au lait", "café noir");
+        assert_eq!(edits[0].pos, 5);
+    }
+
+    #[test]
+    fn apply_edits_rejects_overlap_and_overrun() {
+        let edit = |pos, delete| TextEdit {
+            pos,
+            delete,
+            insert: "x".into(),
+        };
+        assert!(apply_edits("abcdef", &[edit(2, 2), edit(3, 1)]).is_err());
+        assert!(apply_edits("abc", &[edit(2, 2)]).is_err());
+        assert_eq!(
+            apply_edits("abcdef", &[edit(0, 1), edit(4, 2)]).as_deref(),
+            Ok("xbcdx")
+        );
+    }
+
+    #[test]
+    fn state_round_trips() {
+        for state in [
+            SuggestionState::Open,
+            SuggestionState::Accepted,
+            SuggestionState::Rejected,
+        ] {
+            assert_eq!(state.as_str().parse::<SuggestionState>(), Ok(state));
+        }
+    }
+}
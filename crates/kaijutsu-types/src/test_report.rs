@@ -0,0 +1,471 @@
+//! Structured test-run results parsed from a test runner's output.
+//!
+//! The kaish `testreport` builtin feeds a runner's combined output through
+//! [`TestReport::parse`] and stores the result as the `ToolResult` block's
+//! structured output, so agents read per-test outcomes, durations, and
+//! failure excerpts instead of scraping the raw text. [`TestReport::from_json`]
+//! recognises those payloads again (by their [`TEST_REPORT_KIND`] marker) for
+//! the `test_results` MCP tool.
+//!
+//! Supported runners: `cargo test` (libtest's default format, including the
+//! `<1.234s>` suffix of `--report-time`) and `pytest` (per-test lines need
+//! `-v` or `-rA`; per-test durations come from `--durations=0`). Counts come
+//! from the runners' own summary lines, so they stay right even when the
+//! per-test lines are missing.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumString;
+
+/// `kind` marker on every serialized [`TestReport`].
+pub const TEST_REPORT_KIND: &str = "test_report";
+
+/// Longest failure excerpt kept per test, in lines.
+pub const MAX_EXCERPT_LINES: usize = 40;
+
+/// Which runner produced the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum TestRunner {
+    Cargo,
+    Pytest,
+}
+
+impl TestRunner {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo",
+            Self::Pytest => "pytest",
+        }
+    }
+
+    /// Guess the runner from its output.
+    pub fn detect(output: &str) -> Option<Self> {
+        if output.lines().any(|l| l.starts_with("test result: ")) {
+            Some(Self::Cargo)
+        } else if output.contains("test session starts")
+            || output.lines().any(|l| pytest_summary(l).is_some())
+        {
+            Some(Self::Pytest)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for TestRunner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// One test's result.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    pub outcome: TestOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// The failure's output (panic message, traceback), trimmed to
+    /// [`MAX_EXCERPT_LINES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+/// A whole run: totals plus whatever per-test detail the output carried.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestReport {
+    /// Always [`TEST_REPORT_KIND`].
+    pub kind: String,
+    pub runner: TestRunner,
+    pub passed: u32,
+    pub failed: u32,
+    pub ignored: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    pub tests: Vec<TestCase>,
+}
+
+impl TestReport {
+    fn new(runner: TestRunner) -> Self {
+        Self {
+            kind: TEST_REPORT_KIND.to_string(),
+            runner,
+            passed: 0,
+            failed: 0,
+            ignored: 0,
+            duration_ms: None,
+            tests: Vec::new(),
+        }
+    }
+
+    /// Parse a runner's output; `runner: None` detects it. `None` if the
+    /// output holds no recognisable results (e.g. the build failed first).
+    pub fn parse(runner: Option<TestRunner>, output: &str) -> Option<Self> {
+        match runner.or_else(|| TestRunner::detect(output))? {
+            TestRunner::Cargo => parse_cargo(output),
+            TestRunner::Pytest => parse_pytest(output),
+        }
+    }
+
+    /// Read a report back from a block's structured output.
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        if value.get("kind")?.as_str()? != TEST_REPORT_KIND {
+            return None;
+        }
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &TestCase> {
+        self.tests
+            .iter()
+            .filter(|t| t.outcome == TestOutcome::Failed)
+    }
+
+    /// One line: `cargo: 41 passed, 1 failed, 2 ignored in 3.20s`.
+    pub fn summary(&self) -> String {
+        let mut line = format!(
+            "{}: {} passed, {} failed, {} ignored",
+            self.runner, self.passed, self.failed, self.ignored
+        );
+        if let Some(ms) = self.duration_ms {
+            line.push_str(&format!(" in {:.2}s", ms as f64 / 1000.0));
+        }
+        line
+    }
+
+    fn case(&mut self, name: &str) -> &mut TestCase {
+        let idx = match self.tests.iter().position(|t| t.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.tests.push(TestCase {
+                    name: name.to_string(),
+                    outcome: TestOutcome::Failed,
+                    duration_ms: None,
+                    failure: None,
+                });
+                self.tests.len() - 1
+            }
+        };
+        &mut self.tests[idx]
+    }
+
+    fn add_duration(&mut self, ms: u64) {
+        *self.duration_ms.get_or_insert(0) += ms;
+    }
+}
+
+fn secs_to_ms(secs: &str) -> Option<u64> {
+    let secs: f64 = secs.trim().trim_end_matches('s').parse().ok()?;
+    Some((secs * 1000.0).round() as u64)
+}
+
+fn excerpt(lines: &[&str]) -> Option<String> {
+    let start = lines.iter().position(|l| !l.trim().is_empty())?;
+    let end = lines.iter().rposition(|l| !l.trim().is_empty())? + 1;
+    let mut kept: Vec<&str> = lines[start..end].to_vec();
+    if kept.len() > MAX_EXCERPT_LINES {
+        let dropped = kept.len() - MAX_EXCERPT_LINES;
+        kept.truncate(MAX_EXCERPT_LINES);
+        return Some(format!("{}\n… {dropped} more lines", kept.join("\n")));
+    }
+    Some(kept.join("\n"))
+}
+
+/// libtest output; one `test result:` line per test binary, summed.
+fn parse_cargo(output: &str) -> Option<TestReport> {
+    let mut report = TestReport::new(TestRunner::Cargo);
+    let mut saw_result = false;
+    let lines: Vec<&str> = output.lines().collect();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some(rest) = line.strip_prefix("test result: ") {
+            saw_result = true;
+            for part in rest.split(';') {
+                let part = part
+                    .trim()
+                    .trim_start_matches("ok. ")
+                    .trim_start_matches("FAILED. ");
+                let mut words = part.split_whitespace();
+                match (words.next(), words.next()) {
+                    (Some(n), Some("passed")) => report.passed += n.parse().unwrap_or(0),
+                    (Some(n), Some("failed")) => report.failed += n.parse().unwrap_or(0),
+                    (Some(n), Some("ignored")) => report.ignored += n.parse().unwrap_or(0),
+                    (Some("finished"), Some("in")) => {
+                        if let Some(ms) = words.next().and_then(secs_to_ms) {
+                            report.add_duration(ms);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        } else if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|l| l.strip_suffix(" stdout ----"))
+        {
+            let body_start = i + 1;
+            let mut end = body_start;
+            while end < lines.len() && !lines[end].starts_with("---- ") && lines[end] != "failures:"
+            {
+                end += 1;
+            }
+            report.case(name).failure = excerpt(&lines[body_start..end]);
+            i = end;
+            continue;
+        } else if let Some((name, status)) = line
+            .strip_prefix("test ")
+            .and_then(|l| l.rsplit_once(" ... "))
+        {
+            let (status, time) = match status.split_once(" <") {
+                Some((status, time)) => (status, time.strip_suffix('>').and_then(secs_to_ms)),
+                None => (status, None),
+            };
+            let outcome = match status.split(',').next().unwrap_or("") {
+                "ok" => Some(TestOutcome::Passed),
+                "FAILED" => Some(TestOutcome::Failed),
+                "ignored" => Some(TestOutcome::Ignored),
+                _ => None,
+            };
+            if let Some(outcome) = outcome {
+                let case = report.case(name);
+                case.outcome = outcome;
+                case.duration_ms = time;
+            }
+        }
+        i += 1;
+    }
+    saw_result.then_some(report)
+}
+
+/// The counts on pytest's closing line, e.g.
+/// `==== 1 failed, 2 passed, 1 skipped in 0.12s ====`:
+/// `(passed, failed, ignored, duration_ms)`.
+fn pytest_summary(line: &str) -> Option<(u32, u32, u32, Option<u64>)> {
+    let inner = line.trim().trim_matches('=').trim();
+    let (counts, time) = inner.rsplit_once(" in ")?;
+    let duration = time.split_whitespace().next().and_then(secs_to_ms)?;
+    let (mut passed, mut failed, mut ignored) = (0, 0, 0);
+    let mut any = false;
+    for part in counts.split(',') {
+        let mut words = part.split_whitespace();
+        let n: u32 = words.next()?.parse().ok()?;
+        match words.next()? {
+            "passed" | "xpassed" => passed += n,
+            "failed" | "error" | "errors" => failed += n,
+            "skipped" | "xfailed" => ignored += n,
+            "deselected" | "warning" | "warnings" | "rerun" => {}
+            _ => return None,
+        }
+        any = true;
+    }
+    any.then_some((passed, failed, ignored, Some(duration)))
+}
+
+fn parse_pytest(output: &str) -> Option<TestReport> {
+    let mut report = TestReport::new(TestRunner::Pytest);
+    let mut saw_summary = false;
+    let lines: Vec<&str> = output.lines().collect();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some((passed, failed, ignored, duration)) = pytest_summary(line) {
+            saw_summary = true;
+            report.passed = passed;
+            report.failed = failed;
+            report.ignored = ignored;
+            report.duration_ms = duration;
+        } else if let Some(title) = line
+            .strip_prefix('_')
+            .filter(|_| line.ends_with('_'))
+            .map(|l| l.trim_matches('_').trim())
+            .filter(|t| !t.is_empty())
+        {
+            // A failure section: `____ test_name ____` up to the next header.
+            let body_start = i + 1;
+            let mut end = body_start;
+            while end < lines.len() && !lines[end].starts_with('_') && !lines[end].starts_with('=')
+            {
+                end += 1;
+            }
+            let body = excerpt(&lines[body_start..end]);
+            // The header names the function; verbose lines carry the node id.
+            let name = report
+                .tests
+                .iter()
+                .find(|t| t.name.ends_with(&format!("::{title}")))
+                .map(|t| t.name.clone())
+                .unwrap_or_else(|| title.to_string());
+            let case = report.case(&name);
+            case.outcome = TestOutcome::Failed;
+            case.failure = body;
+            i = end;
+            continue;
+        } else if let Some((status, rest)) = line.split_once(' ')
+            && let Some(outcome) = pytest_outcome(status)
+            && rest.contains("::")
+        {
+            // `-rA` short summary: `FAILED tests/test_x.py::test_b - AssertionError`.
+            let (name, reason) = match rest.split_once(" - ") {
+                Some((name, reason)) => (name.trim(), Some(reason.trim())),
+                None => (rest.trim(), None),
+            };
+            let case = report.case(name);
+            case.outcome = outcome;
+            if outcome == TestOutcome::Failed && case.failure.is_none() {
+                case.failure = reason.map(str::to_string);
+            }
+        } else if let Some((name, rest)) = line.split_once(' ')
+            && name.contains("::")
+            && let Some(outcome) = rest.split_whitespace().next().and_then(pytest_outcome)
+        {
+            // `-v`: `tests/test_x.py::test_a PASSED   [ 50%]`.
+            report.case(name).outcome = outcome;
+        } else if let Some((time, rest)) = line.trim().split_once("s call")
+            && let Some(ms) = secs_to_ms(time)
+        {
+            // `--durations`: `0.50s call     tests/test_x.py::test_a`.
+            let name = rest.trim();
+            if let Some(case) = report.tests.iter_mut().find(|t| t.name == name) {
+                case.duration_ms = Some(ms);
+            }
+        }
+        i += 1;
+    }
+    saw_summary.then_some(report)
+}
+
+fn pytest_outcome(status: &str) -> Option<TestOutcome> {
+    match status {
+        "PASSED" | "XPASS" => Some(TestOutcome::Passed),
+        "FAILED" | "ERROR" => Some(TestOutcome::Failed),
+        "SKIPPED" | "XFAIL" => Some(TestOutcome::Ignored),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARGO: &str = "\
+running 3 tests
+test store::tests::insert ... ok
+test store::tests::delete ... FAILED
+test store::tests::slow ... ignored, needs network
+
+failures:
+
+---- store::tests::delete stdout ----
+thread 'store::tests::delete' panicked at src/store.rs:88:9:
+assertion `left == right` failed
+  left: 1
+ right: 0
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    store::tests::delete
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.25s
+
+running 1 test
+test src/lib.rs - doc (line 3) ... ok <0.120s>
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.50s
+";
+
+    const PYTEST: &str = "\
+============================= test session starts ==============================
+collected 3 items
+
+tests/test_api.py::test_get PASSED                                       [ 33%]
+tests/test_api.py::test_post FAILED                                      [ 66%]
+tests/test_api.py::test_put SKIPPED (no server)                          [100%]
+
+=================================== FAILURES ===================================
+__________________________________ test_post ___________________________________
+
+    def test_post():
+>       assert post() == 201
+E       assert 500 == 201
+
+tests/test_api.py:9: AssertionError
+============================= slowest durations ==============================
+1.50s call     tests/test_api.py::test_post
+0.01s call     tests/test_api.py::test_get
+=========================== short test summary info ============================
+FAILED tests/test_api.py::test_post - assert 500 == 201
+============== 1 failed, 1 passed, 1 skipped in 1.62s ==============
+";
+
+    #[test]
+    fn cargo_output_sums_binaries_and_keeps_failure_excerpts() {
+        let report = TestReport::parse(None, CARGO).unwrap();
+        assert_eq!(report.runner, TestRunner::Cargo);
+        assert_eq!((report.passed, report.failed, report.ignored), (2, 1, 1));
+        assert_eq!(report.duration_ms, Some(750));
+        assert_eq!(report.tests.len(), 4);
+
+        let failed: Vec<_> = report.failures().collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].name, "store::tests::delete");
+        let excerpt = failed[0].failure.as_deref().unwrap();
+        assert!(excerpt.starts_with("thread 'store::tests::delete' panicked"));
+        assert!(excerpt.ends_with("display a backtrace"), "{excerpt}");
+
+        let doc = &report.tests[3];
+        assert_eq!(doc.name, "src/lib.rs - doc (line 3)");
+        assert_eq!(doc.duration_ms, Some(120));
+        assert_eq!(report.tests[2].outcome, TestOutcome::Ignored);
+    }
+
+    #[test]
+    fn pytest_output_matches_failures_to_node_ids() {
+        let report = TestReport::parse(None, PYTEST).unwrap();
+        assert_eq!(report.runner, TestRunner::Pytest);
+        assert_eq!((report.passed, report.failed, report.ignored), (1, 1, 1));
+        assert_eq!(report.duration_ms, Some(1620));
+        assert_eq!(report.tests.len(), 3, "{:?}", report.tests);
+
+        let post = report.failures().next().unwrap();
+        assert_eq!(post.name, "tests/test_api.py::test_post");
+        assert_eq!(post.duration_ms, Some(1500));
+        assert!(
+            post.failure
+                .as_deref()
+                .unwrap()
+                .contains("E       assert 500 == 201")
+        );
+        assert_eq!(report.tests[2].outcome, TestOutcome::Ignored);
+    }
+
+    #[test]
+    fn unrecognised_output_is_not_a_report() {
+        assert!(TestReport::parse(None, "error[E0425]: cannot find value `x`").is_none());
+        assert!(TestReport::parse(Some(TestRunner::Pytest), CARGO).is_none());
+    }
+
+    #[test]
+    fn reports_round_trip_through_json_by_kind() {
+        let report = TestReport::parse(Some(TestRunner::Cargo), CARGO).unwrap();
+        let json = report.to_json();
+        assert_eq!(json["kind"], TEST_REPORT_KIND);
+        assert_eq!(TestReport::from_json(&json), Some(report));
+        assert!(TestReport::from_json(&serde_json::json!({"kind": "other"})).is_none());
+    }
+}
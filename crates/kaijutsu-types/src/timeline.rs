@@ -0,0 +1,119 @@
+//! Context timelines — what happened in a context, bucketed over time.
+//!
+//! A [`ContextTimeline`] splits a window of a context's life into equal
+//! buckets and counts, per bucket, the blocks created, the edits journaled,
+//! the tool runs, the drifts that arrived and the errors. It answers "when
+//! did things go wrong" in a long session at a glance: the app draws it as
+//! the strip above the conversation, MCP clients read it as
+//! `context_timeline`. The kernel builds it (`getContextTimeline`);
+//! `kaijutsu_kernel::context_timeline` says where each count comes from.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ids::ContextId;
+
+/// Buckets in a timeline when the caller doesn't say.
+pub const DEFAULT_TIMELINE_BUCKETS: u32 = 60;
+
+/// Most buckets a timeline may have.
+pub const MAX_TIMELINE_BUCKETS: u32 = 500;
+
+/// One slice of a timeline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineBucket {
+    /// Blocks created, of any kind.
+    pub blocks: u32,
+    /// Journaled document edits (every mutation, streamed text included).
+    pub edits: u32,
+    /// Tool calls made.
+    pub tool_runs: u32,
+    /// Drift blocks that arrived from other contexts.
+    pub drifts: u32,
+    /// Error blocks and failed tool results.
+    pub errors: u32,
+}
+
+impl TimelineBucket {
+    /// Events of every kind but edits, which would drown the rest.
+    pub fn events(&self) -> u32 {
+        self.blocks + self.tool_runs + self.drifts + self.errors
+    }
+}
+
+/// A context's events in equal buckets from `start`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextTimeline {
+    pub context_id: ContextId,
+    /// Start of the first bucket (Unix millis).
+    pub start: u64,
+    /// Width of each bucket, in millis.
+    pub bucket_ms: u64,
+    /// Oldest first.
+    pub buckets: Vec<TimelineBucket>,
+}
+
+impl ContextTimeline {
+    /// Empty buckets covering `start..end`. `buckets` is clamped to
+    /// `1..=MAX_TIMELINE_BUCKETS`; the last bucket may run past `end`.
+    pub fn new(context_id: ContextId, start: u64, end: u64, buckets: u32) -> Self {
+        let count = buckets.clamp(1, MAX_TIMELINE_BUCKETS) as u64;
+        let bucket_ms = end.saturating_sub(start).div_ceil(count).max(1);
+        Self {
+            context_id,
+            start,
+            bucket_ms,
+            buckets: vec![TimelineBucket::default(); count as usize],
+        }
+    }
+
+    /// End of the last bucket (Unix millis, exclusive).
+    pub fn end(&self) -> u64 {
+        self.start + self.bucket_ms * self.buckets.len() as u64
+    }
+
+    /// Start of bucket `index` (Unix millis).
+    pub fn bucket_start(&self, index: usize) -> u64 {
+        self.start + self.bucket_ms * index as u64
+    }
+
+    /// The bucket holding `at` (Unix millis), if the timeline covers it.
+    pub fn bucket_mut(&mut self, at: u64) -> Option<&mut TimelineBucket> {
+        let index = at.checked_sub(self.start)? / self.bucket_ms;
+        self.buckets.get_mut(index as usize)
+    }
+
+    /// The busiest bucket's [`TimelineBucket::events`], for scaling a chart.
+    pub fn peak(&self) -> u32 {
+        self.buckets
+            .iter()
+            .map(TimelineBucket::events)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_split_the_window_evenly() {
+        let ctx = ContextId::new();
+        let mut timeline = ContextTimeline::new(ctx, 1_000, 7_000, 3);
+        assert_eq!(timeline.bucket_ms, 2_000);
+        assert_eq!(timeline.end(), 7_000);
+        assert_eq!(timeline.bucket_start(2), 5_000);
+
+        timeline.bucket_mut(2_999).unwrap().blocks += 1;
+        timeline.bucket_mut(3_000).unwrap().errors += 2;
+        assert!(timeline.bucket_mut(999).is_none());
+        assert!(timeline.bucket_mut(7_000).is_none());
+        assert_eq!(timeline.buckets[0].blocks, 1);
+        assert_eq!(timeline.buckets[1].errors, 2);
+        assert_eq!(timeline.peak(), 2);
+
+        let clamped = ContextTimeline::new(ctx, 0, 10, 10_000);
+        assert_eq!(clamped.buckets.len(), MAX_TIMELINE_BUCKETS as usize);
+        assert_eq!(clamped.bucket_ms, 1, "never a zero-width bucket");
+    }
+}